@@ -0,0 +1,370 @@
+//! Generated data for load and UI testing
+//!
+//! A [`DataSpec`] asks for a number of DynamoDB items, S3 objects and SQS messages, each
+//! rendered from a JSON template. Template strings hold `{{function}}` placeholders that
+//! [`Faker`] fills in: a string that is only a placeholder takes the function's type
+//! (`"{{int:1:10}}"` becomes a number), otherwise the values are written into the text.
+//!
+//! | Function | Value |
+//! | :--- | :--- |
+//! | `index` | Position of the item, from 0 |
+//! | `uuid` | Random UUID |
+//! | `int:MIN:MAX`, `float:MIN:MAX` | Number in the range, inclusive; floats have 2 decimals |
+//! | `bool` | `true` or `false` |
+//! | `pick:A\|B\|C` | One of the options |
+//! | `first_name`, `last_name`, `name`, `email`, `phone` | Person details |
+//! | `company`, `city`, `country` | Organisation and place names |
+//! | `word`, `sentence` | Lorem-style text |
+//! | `date`, `timestamp` | Day or RFC 3339 time within the last year |
+//! | `now` | Current RFC 3339 time |
+//!
+//! Specs come from the seed file named by `CLOUDEMU_SEED_FILE`, loaded at startup, or
+//! from `POST /_aws/data/generate`. Tables, buckets and queues that do not exist yet are
+//! created. With a `seed`, the same spec generates the same data every time.
+
+use crate::Emulator;
+use crate::error::{ApiError, EmulatorError};
+use axum::{extract::State, response::{IntoResponse, Response}, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Most items, objects or messages one target may ask for
+pub const MAX_COUNT: usize = 100_000;
+
+const FIRST_NAMES: &[&str] = &["Ada", "Alan", "Grace", "Linus", "Margaret", "Ken", "Barbara", "Dennis", "Frances", "Tim", "Radia", "Guido"];
+const LAST_NAMES: &[&str] = &["Lovelace", "Turing", "Hopper", "Torvalds", "Hamilton", "Thompson", "Liskov", "Ritchie", "Allen", "Berners-Lee", "Perlman", "van Rossum"];
+const COMPANY_WORDS: &[&str] = &["Acme", "Globex", "Initech", "Umbrella", "Stark", "Wayne", "Hooli", "Vandelay", "Cyberdyne", "Soylent"];
+const COMPANY_SUFFIXES: &[&str] = &["Inc", "LLC", "Ltd", "Group", "Labs", "Systems"];
+const CITIES: &[&str] = &["Amsterdam", "Berlin", "Cape Town", "Dublin", "Lisbon", "Nairobi", "Osaka", "Seattle", "Sydney", "Toronto"];
+const COUNTRIES: &[&str] = &["Australia", "Canada", "Germany", "Ireland", "Japan", "Kenya", "Netherlands", "Portugal", "South Africa", "United States"];
+const DOMAINS: &[&str] = &["example.com", "example.org", "example.net"];
+const WORDS: &[&str] = &[
+    "lorem", "ipsum", "dolor", "sit", "amet", "consectetur", "adipiscing", "elit", "sed", "do",
+    "eiusmod", "tempor", "incididunt", "ut", "labore", "et", "dolore", "magna", "aliqua", "enim",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataSpec {
+    /// Seed of the random values; without one every run differs
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub tables: Vec<TableData>,
+    #[serde(default)]
+    pub buckets: Vec<BucketData>,
+    #[serde(default)]
+    pub queues: Vec<QueueData>,
+}
+
+/// Items for a DynamoDB table; the template is plain JSON, stored as attribute values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableData {
+    pub name: String,
+    pub count: usize,
+    pub template: Value,
+    /// String partition key of the table when it has to be created
+    #[serde(default = "default_partition_key")]
+    pub partition_key: String,
+    /// String sort key of the table when it has to be created
+    #[serde(default)]
+    pub sort_key: Option<String>,
+}
+
+/// Objects for an S3 bucket; string templates are stored as text, anything else as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BucketData {
+    pub name: String,
+    pub count: usize,
+    pub template: Value,
+    /// Template of the object keys
+    #[serde(default = "default_object_key")]
+    pub key: String,
+}
+
+/// Messages for an SQS queue; string templates are sent as they are, anything else as JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueData {
+    pub name: String,
+    pub count: usize,
+    pub template: Value,
+}
+
+fn default_partition_key() -> String {
+    "id".to_string()
+}
+
+fn default_object_key() -> String {
+    "{{uuid}}.json".to_string()
+}
+
+/// How much a spec generated
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GeneratedData {
+    pub items: usize,
+    pub objects: usize,
+    pub messages: usize,
+}
+
+impl DataSpec {
+    /// Parse a spec from YAML, or JSON, which is also YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self, EmulatorError> {
+        serde_yaml::from_str(yaml).map_err(|e| EmulatorError::InvalidArgument(format!("Invalid data spec: {}", e)))
+    }
+
+    /// Create the targets that are missing and write the generated data to them
+    pub fn generate(&self, emulator: &Emulator) -> Result<GeneratedData, EmulatorError> {
+        self.validate()?;
+        let mut faker = match self.seed {
+            Some(seed) => Faker::new(seed),
+            None => Faker::new(chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64),
+        };
+        let storage = &emulator.storage;
+        let (account, region) = (&emulator.config.account_id, &emulator.config.region);
+        let mut generated = GeneratedData::default();
+
+        for table in &self.tables {
+            if !table.template.is_object() {
+                return Err(EmulatorError::InvalidArgument(format!("Template of table {} must be an object", table.name)));
+            }
+            let metadata = match storage.get_table(&table.name) {
+                Ok(metadata) => metadata,
+                Err(EmulatorError::NotFound(..)) => {
+                    let keys: Vec<(&str, &str)> = std::iter::once((table.partition_key.as_str(), "HASH"))
+                        .chain(table.sort_key.as_deref().map(|sk| (sk, "RANGE")))
+                        .collect();
+                    let attribute_definitions: Vec<Value> = keys.iter()
+                        .map(|(name, _)| json!({ "AttributeName": name, "AttributeType": "S" })).collect();
+                    let key_schema: Vec<Value> = keys.iter()
+                        .map(|(name, key_type)| json!({ "AttributeName": name, "KeyType": key_type })).collect();
+                    storage.create_table(&table.name, &json!(attribute_definitions).to_string(), &json!(key_schema).to_string(), account, region)?
+                }
+                Err(e) => return Err(e),
+            };
+            let key_schema: Vec<Value> = serde_json::from_str(&metadata.key_schema).unwrap_or_default();
+            let key_name = |key_type: &str| key_schema.iter()
+                .find(|k| k["KeyType"] == key_type)
+                .and_then(|k| k["AttributeName"].as_str())
+                .map(str::to_string);
+            let pk_name = key_name("HASH").ok_or_else(|| EmulatorError::Internal("Table has no HASH key".into()))?;
+            let sk_name = key_name("RANGE");
+
+            for index in 0..table.count {
+                let item = faker.render(&table.template, index)?;
+                let pk = key_text(&item, &pk_name)
+                    .ok_or_else(|| EmulatorError::InvalidArgument(format!("Template of table {} has no partition key {}", table.name, pk_name)))?;
+                let sk = match &sk_name {
+                    Some(sk_name) => Some(key_text(&item, sk_name)
+                        .ok_or_else(|| EmulatorError::InvalidArgument(format!("Template of table {} has no sort key {}", table.name, sk_name)))?),
+                    None => None,
+                };
+                storage.put_item(&table.name, &pk, sk.as_deref(), &to_attribute_value(&item)["M"].to_string())?;
+                generated.items += 1;
+            }
+        }
+
+        for bucket in &self.buckets {
+            if !storage.bucket_exists(&bucket.name)? {
+                storage.create_bucket(&bucket.name, region)?;
+            }
+            for index in 0..bucket.count {
+                let key = faker.render_text(&bucket.key, index)?;
+                let (body, content_type) = match faker.render(&bucket.template, index)? {
+                    Value::String(text) => (text, "text/plain"),
+                    json => (json.to_string(), "application/json"),
+                };
+                storage.put_object(&bucket.name, &key, body.as_bytes(), Some(content_type), None)?;
+                generated.objects += 1;
+            }
+        }
+
+        for queue in &self.queues {
+            match storage.create_queue(&queue.name, account, region) {
+                Ok(_) | Err(EmulatorError::AlreadyExists(_)) => {}
+                Err(e) => return Err(e),
+            }
+            for index in 0..queue.count {
+                let body = match faker.render(&queue.template, index)? {
+                    Value::String(text) => text,
+                    json => json.to_string(),
+                };
+                storage.send_message(&queue.name, &body)?;
+                generated.messages += 1;
+            }
+        }
+
+        Ok(generated)
+    }
+
+    fn validate(&self) -> Result<(), EmulatorError> {
+        let targets = self.tables.iter().map(|t| (&t.name, t.count))
+            .chain(self.buckets.iter().map(|b| (&b.name, b.count)))
+            .chain(self.queues.iter().map(|q| (&q.name, q.count)));
+        for (name, count) in targets {
+            if count > MAX_COUNT {
+                return Err(EmulatorError::InvalidArgument(format!("{} asks for {} entries, more than the {} allowed", name, count, MAX_COUNT)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Text of a key attribute of a generated item
+fn key_text(item: &Value, name: &str) -> Option<String> {
+    match item.get(name)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// DynamoDB attribute value of a plain JSON value
+fn to_attribute_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "NULL": true }),
+        Value::Bool(b) => json!({ "BOOL": b }),
+        Value::Number(n) => json!({ "N": n.to_string() }),
+        Value::String(s) => json!({ "S": s }),
+        Value::Array(items) => json!({ "L": items.iter().map(to_attribute_value).collect::<Vec<_>>() }),
+        Value::Object(map) => json!({ "M": map.iter().map(|(k, v)| (k.clone(), to_attribute_value(v))).collect::<serde_json::Map<_, _>>() }),
+    }
+}
+
+/// Deterministic source of fake values (SplitMix64)
+pub struct Faker {
+    state: u64,
+}
+
+impl Faker {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n`; `n` must not be 0
+    fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    fn pick<'a>(&mut self, options: &[&'a str]) -> &'a str {
+        options[self.below(options.len() as u64) as usize]
+    }
+
+    /// Fill every placeholder of a template for the item at `index`
+    pub fn render(&mut self, template: &Value, index: usize) -> Result<Value, EmulatorError> {
+        Ok(match template {
+            Value::String(text) => match text.strip_prefix("{{").and_then(|t| t.strip_suffix("}}")) {
+                Some(function) if !function.contains("{{") => self.value(function.trim(), index)?,
+                _ => Value::String(self.render_text(text, index)?),
+            },
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.render(v, index)).collect::<Result<_, _>>()?),
+            Value::Object(map) => Value::Object(map.iter()
+                .map(|(k, v)| Ok((k.clone(), self.render(v, index)?)))
+                .collect::<Result<_, EmulatorError>>()?),
+            other => other.clone(),
+        })
+    }
+
+    /// Fill the placeholders of a string, writing every value as text
+    pub fn render_text(&mut self, text: &str, index: usize) -> Result<String, EmulatorError> {
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            let end = rest[start..].find("}}")
+                .ok_or_else(|| EmulatorError::InvalidArgument(format!("Unclosed placeholder in {}", text)))?;
+            out.push_str(&rest[..start]);
+            match self.value(rest[start + 2..start + end].trim(), index)? {
+                Value::String(s) => out.push_str(&s),
+                other => out.push_str(&other.to_string()),
+            }
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Value of one faker function
+    pub fn value(&mut self, function: &str, index: usize) -> Result<Value, EmulatorError> {
+        let (name, args) = function.split_once(':').unwrap_or((function, ""));
+        Ok(match name {
+            "index" => json!(index),
+            "uuid" => {
+                let mut bytes = [0u8; 16];
+                bytes[..8].copy_from_slice(&self.next_u64().to_le_bytes());
+                bytes[8..].copy_from_slice(&self.next_u64().to_le_bytes());
+                json!(uuid::Builder::from_random_bytes(bytes).into_uuid().to_string())
+            }
+            "int" => {
+                let (min, max) = range::<i64>(function, args)?;
+                // The span of the full i64 range does not fit in a u64 count
+                let span = max.abs_diff(min);
+                let offset = if span == u64::MAX { self.next_u64() } else { self.below(span + 1) };
+                json!(min.wrapping_add(offset as i64))
+            }
+            "float" => {
+                let (min, max) = range::<f64>(function, args)?;
+                let fraction = self.next_u64() as f64 / u64::MAX as f64;
+                json!(((min + fraction * (max - min)) * 100.0).round() / 100.0)
+            }
+            "bool" => json!(self.below(2) == 1),
+            "pick" => {
+                let options: Vec<&str> = args.split('|').collect();
+                json!(self.pick(&options))
+            }
+            "first_name" => json!(self.pick(FIRST_NAMES)),
+            "last_name" => json!(self.pick(LAST_NAMES)),
+            "name" => json!(format!("{} {}", self.pick(FIRST_NAMES), self.pick(LAST_NAMES))),
+            "email" => {
+                let (first, last) = (self.pick(FIRST_NAMES), self.pick(LAST_NAMES).replace([' ', '-'], ""));
+                json!(format!("{}.{}{}@{}", first, last, self.below(100), self.pick(DOMAINS)).to_lowercase())
+            }
+            "phone" => json!(format!("+1-555-{:03}-{:04}", self.below(1000), self.below(10_000))),
+            "company" => json!(format!("{} {}", self.pick(COMPANY_WORDS), self.pick(COMPANY_SUFFIXES))),
+            "city" => json!(self.pick(CITIES)),
+            "country" => json!(self.pick(COUNTRIES)),
+            "word" => json!(self.pick(WORDS)),
+            "sentence" => {
+                let len = 4 + self.below(8) as usize;
+                let words: Vec<&str> = (0..len).map(|_| self.pick(WORDS)).collect();
+                let sentence = words.join(" ");
+                json!(format!("{}{}.", sentence[..1].to_uppercase(), &sentence[1..]))
+            }
+            "date" => json!(self.past_time().format("%Y-%m-%d").to_string()),
+            "timestamp" => json!(self.past_time().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            "now" => json!(chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+            _ => return Err(EmulatorError::InvalidArgument(format!("Unknown faker function: {}", function))),
+        })
+    }
+
+    /// A time within the last year
+    fn past_time(&mut self) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now() - chrono::Duration::seconds(self.below(365 * 24 * 3600) as i64)
+    }
+}
+
+/// `MIN:MAX` arguments of `int` and `float`
+fn range<T: std::str::FromStr + PartialOrd>(function: &str, args: &str) -> Result<(T, T), EmulatorError> {
+    let invalid = || EmulatorError::InvalidArgument(format!("{} needs MIN:MAX arguments with MIN <= MAX", function));
+    let (min, max) = args.split_once(':').ok_or_else(invalid)?;
+    let (min, max) = (min.parse().map_err(|_| invalid())?, max.parse().map_err(|_| invalid())?);
+    if min > max {
+        return Err(invalid());
+    }
+    Ok((min, max))
+}
+
+/// Generate data from a spec in the request body (POST /_aws/data/generate)
+pub async fn generate_data(State(emulator): State<Arc<Emulator>>, Json(spec): Json<DataSpec>) -> Response {
+    match spec.generate(&emulator) {
+        Ok(generated) => Json(generated).into_response(),
+        Err(e) => ApiError(e).into_response(),
+    }
+}
@@ -48,18 +48,21 @@ impl IntoResponse for ApiError {
         let err = self.0;
         
         let status = match &err {
-            EmulatorError::NoSuchBucket(_) | EmulatorError::NoSuchKey(_) | EmulatorError::NoSuchBucketPolicy(_) | EmulatorError::NotFound(..) => {
+            EmulatorError::NoSuchBucket(_) | EmulatorError::NoSuchKey(_) | EmulatorError::NoSuchUpload(_) | EmulatorError::NoSuchBucketPolicy(_) |
+            EmulatorError::NoSuchLifecycleConfiguration(_) | EmulatorError::NotFound(..) => {
                 StatusCode::NOT_FOUND
             }
             EmulatorError::BucketAlreadyExists(_) | EmulatorError::AlreadyExists(_) => StatusCode::CONFLICT,
             EmulatorError::BucketNotEmpty(_) | EmulatorError::InvalidRequest(_) | EmulatorError::InvalidArgument(_) | 
-            EmulatorError::MalformedXml(_) | EmulatorError::MalformedPolicy(_) | EmulatorError::InvalidObjectState(_) => {
+            EmulatorError::MalformedXml(_) | EmulatorError::MalformedPolicy(_) | EmulatorError::InvalidObjectState(_) |
+            EmulatorError::NotAuthorized(_) | EmulatorError::InvalidParameter { .. } => {
                 StatusCode::BAD_REQUEST
             }
             EmulatorError::Internal(_) | EmulatorError::Database(_) | EmulatorError::Io(_) | EmulatorError::Json(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             EmulatorError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            EmulatorError::LimitExceeded { .. } | EmulatorError::AccessDenied { .. } => err.status_code(),
         };
 
         let code = err.code();
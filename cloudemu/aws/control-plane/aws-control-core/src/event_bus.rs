@@ -0,0 +1,243 @@
+//! Internal resource event bus
+//!
+//! Services publish a [`ResourceEvent`] whenever an API call creates, changes or
+//! deletes a resource. Consumers (S3 bucket notifications, EventBridge, CloudTrail,
+//! the dashboard inspector) subscribe to the bus instead of being called directly
+//! by the services that produce the events.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::debug;
+
+/// Number of recent events kept for the inspector
+pub const RECENT_EVENT_CAPACITY: usize = 200;
+
+/// Stands in for sensitive request parameters, as in CloudTrail
+pub const REDACTED: &str = "HIDDEN_DUE_TO_SECURITY_REASONS";
+
+/// Parameter names holding secrets are matched by these fragments, case-insensitively,
+/// unless they only name or identify one, like `SecretId`
+const SENSITIVE_FRAGMENTS: &[&str] = &[
+    "secret", "password", "plaintext", "keymaterial", "privatekey", "passphrase",
+    "authtoken", "sessiontoken", "accesstoken", "refreshtoken", "idtoken",
+];
+
+/// Replace the values of secret-bearing parameters (`SecretString`, `MasterUserPassword`,
+/// `Plaintext`, ...) at any depth, so events never carry them to subscribers
+pub fn redact_sensitive(params: &mut Value) {
+    match params {
+        Value::Object(fields) => {
+            for (name, value) in fields.iter_mut() {
+                let name = name.to_ascii_lowercase();
+                let identifies = ["id", "ids", "arn", "arns", "name", "names"].iter().any(|suffix| name.ends_with(suffix));
+                if !identifies && SENSITIVE_FRAGMENTS.iter().any(|fragment| name.contains(fragment)) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_sensitive(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_sensitive),
+        _ => {}
+    }
+}
+
+/// Kind of change an API call made to a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LifecycleAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Write operations that move data rather than change a resource
+const DATA_OPERATIONS: &[&str] = &[
+    "PutItem", "UpdateItem", "DeleteItem", "BatchWriteItem", "TransactWriteItems",
+    "PutEvents", "PutMetricData", "PutLogEvents", "PutRecord", "PutRecords",
+    "SendMessage", "SendMessageBatch", "DeleteMessage", "DeleteMessageBatch",
+    "StartExecution", "StopExecution",
+];
+
+impl LifecycleAction {
+    /// Classify an API operation by its verb, e.g. `CreateQueue` -> `Created`.
+    /// Reads and data-plane writes return `None`.
+    pub fn from_operation(operation: &str) -> Option<Self> {
+        if DATA_OPERATIONS.contains(&operation) {
+            return None;
+        }
+
+        const CREATED: &[&str] = &["Create", "Register", "Run", "Allocate", "Subscribe", "Import"];
+        const UPDATED: &[&str] = &[
+            "Put", "Update", "Modify", "Set", "Change", "Tag", "Untag", "Attach", "Detach",
+            "Associate", "Disassociate", "Add", "Enable", "Disable", "Start", "Stop", "Reboot",
+        ];
+        const DELETED: &[&str] = &["Delete", "Deregister", "Terminate", "Release", "Unsubscribe", "Remove", "Purge"];
+
+        let has_verb = |verbs: &[&str]| verbs.iter().any(|v| operation.starts_with(v));
+        if has_verb(DELETED) {
+            Some(LifecycleAction::Deleted)
+        } else if has_verb(CREATED) {
+            Some(LifecycleAction::Created)
+        } else if has_verb(UPDATED) {
+            Some(LifecycleAction::Updated)
+        } else {
+            None
+        }
+    }
+}
+
+/// A resource lifecycle event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceEvent {
+    pub id: String,
+    /// RFC 3339 timestamp with millisecond precision
+    pub time: String,
+    /// Short service name, e.g. `s3`, `sqs`, `ec2`
+    pub service: String,
+    /// API operation that caused the change, e.g. `PutObject`
+    pub operation: String,
+    pub action: LifecycleAction,
+    /// ARN or name of the affected resource when known
+    pub resource: Option<String>,
+    /// Service specific detail (request parameters for generic events)
+    pub detail: Value,
+}
+
+impl ResourceEvent {
+    pub fn new(
+        service: &str,
+        operation: &str,
+        action: LifecycleAction,
+        resource: Option<String>,
+        detail: Value,
+    ) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            service: service.to_string(),
+            operation: operation.to_string(),
+            action,
+            resource,
+            detail,
+        }
+    }
+}
+
+/// Consumer of resource events.
+/// Subscribers run synchronously on the publishing request, in subscription order.
+pub trait EventSubscriber: Send + Sync {
+    fn on_event(&self, event: &ResourceEvent);
+}
+
+/// In-process publish/subscribe bus for resource events
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: RwLock<Vec<Arc<dyn EventSubscriber>>>,
+    recent: Mutex<VecDeque<ResourceEvent>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a consumer for every subsequently published event
+    pub fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) {
+        self.subscribers.write().unwrap().push(subscriber);
+    }
+
+    /// Deliver an event to all subscribers and keep it in the recent history
+    pub fn publish(&self, event: ResourceEvent) {
+        debug!("EventBus: {} {} {:?} {:?}", event.service, event.operation, event.action, event.resource);
+
+        // Clone the list so a subscriber may publish follow-up events
+        let subscribers = self.subscribers.read().unwrap().clone();
+        for subscriber in subscribers {
+            subscriber.on_event(&event);
+        }
+
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == RECENT_EVENT_CAPACITY {
+            recent.pop_front();
+        }
+        recent.push_back(event);
+    }
+
+    /// Most recent events, newest first
+    pub fn recent(&self, limit: usize) -> Vec<ResourceEvent> {
+        self.recent.lock().unwrap().iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl EventSubscriber for Recorder {
+        fn on_event(&self, event: &ResourceEvent) {
+            self.0.lock().unwrap().push(event.operation.clone());
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_action_from_operation() {
+        assert_eq!(LifecycleAction::from_operation("CreateQueue"), Some(LifecycleAction::Created));
+        assert_eq!(LifecycleAction::from_operation("RunInstances"), Some(LifecycleAction::Created));
+        assert_eq!(LifecycleAction::from_operation("PutRule"), Some(LifecycleAction::Updated));
+        assert_eq!(LifecycleAction::from_operation("TerminateInstances"), Some(LifecycleAction::Deleted));
+        assert_eq!(LifecycleAction::from_operation("DescribeInstances"), None);
+        assert_eq!(LifecycleAction::from_operation("PutItem"), None);
+        assert_eq!(LifecycleAction::from_operation("SendMessage"), None);
+    }
+
+    #[test]
+    fn test_redact_sensitive() {
+        let mut params = json!({
+            "Name": "db-creds",
+            "SecretId": "db-creds",
+            "SecretString": "hunter2",
+            "MasterUserPassword": "hunter2",
+            "Tags": [{ "Key": "team", "Value": "data" }],
+            "Credentials": { "ClientSecret": "s3cr3t", "ClientId": "app" }
+        });
+        redact_sensitive(&mut params);
+        assert_eq!(params["Name"], "db-creds");
+        assert_eq!(params["SecretId"], "db-creds");
+        assert_eq!(params["SecretString"], REDACTED);
+        assert_eq!(params["MasterUserPassword"], REDACTED);
+        assert_eq!(params["Tags"][0]["Value"], "data");
+        assert_eq!(params["Credentials"]["ClientSecret"], REDACTED);
+        assert_eq!(params["Credentials"]["ClientId"], "app");
+    }
+
+    #[test]
+    fn test_publish_delivers_and_records() {
+        let bus = EventBus::new();
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        bus.subscribe(recorder.clone());
+
+        for operation in ["CreateQueue", "DeleteQueue"] {
+            bus.publish(ResourceEvent::new("sqs", operation, LifecycleAction::Created, None, json!({})));
+        }
+
+        assert_eq!(*recorder.0.lock().unwrap(), vec!["CreateQueue", "DeleteQueue"]);
+        let recent = bus.recent(1);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].operation, "DeleteQueue");
+    }
+
+    #[test]
+    fn test_recent_history_is_bounded() {
+        let bus = EventBus::new();
+        for _ in 0..RECENT_EVENT_CAPACITY + 10 {
+            bus.publish(ResourceEvent::new("s3", "CreateBucket", LifecycleAction::Created, None, json!({})));
+        }
+        assert_eq!(bus.recent(usize::MAX).len(), RECENT_EVENT_CAPACITY);
+    }
+}
@@ -1,7 +1,28 @@
-use axum::{extract::State, response::Html};
+use axum::{
+    extract::{Query, State},
+    response::Html,
+    Json,
+};
 use crate::Emulator;
+use crate::event_bus::ResourceEvent;
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Recent events shown on the dashboard
+const DASHBOARD_EVENT_LIMIT: usize = 20;
+
+/// SNS sandbox messages shown on the dashboard
+const DASHBOARD_SANDBOX_LIMIT: usize = 20;
+
+/// Event inspector: recent resource events, newest first (GET /_aws/events?limit=N)
+pub async fn recent_events(
+    State(emulator): State<Arc<Emulator>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<Vec<ResourceEvent>> {
+    let limit = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(50);
+    Json(emulator.bus.recent(limit))
+}
+
 pub async fn render_dashboard(State(emulator): State<Arc<Emulator>>) -> Html<String> {
     let mut html = String::from(r#"
         <!DOCTYPE html>
@@ -117,6 +138,26 @@ pub async fn render_dashboard(State(emulator): State<Arc<Emulator>>) -> Html<Str
     }
     html.push_str("</ul></div>");
 
+    html.push_str(r#"<div class="card">
+                    <h2><span class="icon">⚡</span> Recent Events</h2>
+                    <ul>"#);
+    let events = emulator.bus.recent(DASHBOARD_EVENT_LIMIT);
+    if events.is_empty() { html.push_str("<li class='empty'>No resource events yet</li>"); }
+    for e in events {
+        html.push_str(&format!("<li><span class='res-name'>{} {}</span> <span class='res-meta'>{:?} | {} | {}</span></li>", e.service, e.operation, e.action, e.resource.unwrap_or_default(), e.time));
+    }
+    html.push_str("</ul></div>");
+
+    html.push_str(r#"<div class="card">
+                    <h2><span class="icon">📱</span> SNS Sandbox</h2>
+                    <ul>"#);
+    let messages = emulator.storage.list_sandbox_messages(None, None).unwrap_or_default();
+    if messages.is_empty() { html.push_str("<li class='empty'>No SMS or push messages sent</li>"); }
+    for m in messages.iter().rev().take(DASHBOARD_SANDBOX_LIMIT) {
+        html.push_str(&format!("<li><span class='res-name'>{} to {}</span> <span class='res-meta'>{} | {}</span> <span class='res-meta'>{}</span></li>", m.kind.to_uppercase(), m.destination, m.platform.as_deref().unwrap_or("SMS"), m.sent_at, m.message));
+    }
+    html.push_str("</ul></div>");
+
     html.push_str("</div></body></html>");
     Html(html)
 }
@@ -1,4 +1,5 @@
 use crate::Emulator;
+use crate::event_bus::{redact_sensitive, LifecycleAction, ResourceEvent};
 use axum::{
     extract::State,
     http::{HeaderMap, StatusCode},
@@ -29,7 +30,18 @@ pub async fn dispatch(
 
     let service = target.split('.').next().unwrap_or("");
 
-    match service {
+    // Successful mutating calls are published on the resource event bus
+    let lifecycle = service_name(target).and_then(|name| {
+        let params = request_parameters(&body, &body_bytes);
+        let operation = params["Action"].as_str()
+            .or_else(|| target.rsplit_once('.').map(|(_, op)| op))
+            .unwrap_or("")
+            .to_string();
+        LifecycleAction::from_operation(&operation).map(|action| (name, operation, action, params))
+    });
+
+    let publisher = emulator.clone();
+    let response = match service {
         #[cfg(feature = "dynamodb")]
         "DynamoDB_20120810" => {
             crate::services::dynamodb::handlers::handle_request(
@@ -87,6 +99,14 @@ pub async fn dispatch(
                 Json(body),
             ).await
         }
+        #[cfg(feature = "cognito")]
+        "AWSCognitoIdentityService" => {
+             crate::services::identity::federation::handle_request(
+                State(emulator),
+                headers,
+                Json(body),
+            ).await
+        }
         #[cfg(feature = "stepfunctions")]
         "AWSStepFunctions" => {
              crate::services::workflows::handlers::handle_request(
@@ -202,9 +222,82 @@ pub async fn dispatch(
                 Json(body),
             ).await
         }
+        #[cfg(feature = "kinesis")]
+        "Kinesis_20131202" => {
+             crate::services::kinesis::handlers::handle_request(
+                State(emulator),
+                headers,
+                Json(body),
+            ).await
+        }
+        #[cfg(feature = "cloudtrail")]
+        "com" if target.starts_with("com.amazonaws.cloudtrail.") => {
+             crate::services::cloudtrail::handlers::handle_request(
+                State(emulator),
+                headers,
+                Json(body),
+            ).await
+        }
         _ => {
             warn!("Unknown service target: {}", target);
             (StatusCode::NOT_FOUND, format!("Unknown service target: {}", target)).into_response()
         }
+    };
+
+    if let Some((service, operation, action, params)) = lifecycle {
+        if response.status().is_success() {
+            let resource = resource_identifier(&params);
+            let mut params = params;
+            redact_sensitive(&mut params);
+            publisher.bus.publish(ResourceEvent::new(service, &operation, action, resource, params));
+        }
     }
+
+    response
+}
+
+/// Short service name (as in `<name>.amazonaws.com`) for targets whose calls are published
+pub(crate) fn service_name(target: &str) -> Option<&'static str> {
+    let name = match target.split('.').next().unwrap_or("") {
+        "DynamoDB_20120810" => "dynamodb",
+        "AmazonSQS" | "AWSSQS" => "sqs",
+        "secretsmanager" => "secretsmanager",
+        "AWSEvents" => "events",
+        "TrentService" => "kms",
+        "Monitoring" => "monitoring",
+        "Logs_20140530" => "logs",
+        "AWSCognitoIdentityProviderService" => "cognito-idp",
+        "AWSCognitoIdentityService" => "cognito-identity",
+        "AWSStepFunctions" => "states",
+        "AmazonEC2" => "ec2",
+        "AmazonSNS" | "" => "sns",
+        "AmazonEC2ContainerServiceV20141113" => "ecs",
+        "AmazonRDSv18" | "AmazonRDS" => "rds",
+        "AWSIdentityManagementV20100508" => "iam",
+        "ElasticLoadBalancing_v20151201" | "ElasticLoadBalancing_20120601" => "elasticloadbalancing",
+        "ElastiCache" | "AmazonElastiCache_20150202" => "elasticache",
+        "AmazonEC2ContainerRegistry_V20150921" => "ecr",
+        "Kinesis_20131202" => "kinesis",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Request parameters as JSON: the JSON body, or the form fields of Query protocol calls
+fn request_parameters(body: &Value, body_bytes: &[u8]) -> Value {
+    if body.as_object().is_some_and(|o| !o.is_empty()) {
+        return body.clone();
+    }
+    let form = crate::adapters::aws_query::parse_query_string(&String::from_utf8_lossy(body_bytes));
+    serde_json::to_value(form).unwrap_or_default()
+}
+
+/// Best-effort identifier of the affected resource: the first ARN, name, URL or id parameter
+fn resource_identifier(params: &Value) -> Option<String> {
+    let fields = params.as_object()?;
+    ["Arn", "Name", "Url", "Id"].iter().find_map(|suffix| {
+        fields.iter()
+            .filter(|(key, _)| key.ends_with(suffix) || key.ends_with(&suffix.to_lowercase()))
+            .find_map(|(_, value)| value.as_str().filter(|v| !v.is_empty()).map(str::to_string))
+    })
 }
@@ -17,6 +17,12 @@ pub fn create_router(emulator: Arc<Emulator>) -> Router {
         .route("/health", get(health_check))
         .route("/_localstack/health", get(health_check)) // LocalStack compat
         .route("/dashboard", get(super::dashboard::render_dashboard))
+        .route("/_aws/events", get(super::dashboard::recent_events))
+        .route("/_aws/terraform/fixtures", axum::routing::post(super::terraform::create_fixtures))
+        .route("/_aws/terraform/provider.tf", get(super::terraform::provider_file))
+        .route("/_aws/data/generate", axum::routing::post(crate::datagen::generate_data))
+        .route("/_aws/latency", get(crate::latency::get_profile).put(crate::latency::set_profile))
+        .route("/_aws/limits", get(crate::limits::get_limits).put(crate::limits::update_limits))
         .route("/", axum::routing::post(super::dispatcher::dispatch));
 
     // S3 routes
@@ -28,7 +34,11 @@ pub fn create_router(emulator: Arc<Emulator>) -> Router {
             // Bucket operations
             .route("/:bucket", any(crate::services::s3::handlers::bucket_handler))
             // Object operations  
-            .route("/:bucket/*key", any(crate::services::s3::handlers::object_handler));
+            .route("/:bucket/*key", any(crate::services::s3::handlers::object_handler))
+            // Static website endpoint (underscore keeps it clear of valid bucket names)
+            .route("/_website/:bucket", any(crate::services::s3::handlers::website_index_handler))
+            .route("/_website/:bucket/", any(crate::services::s3::handlers::website_index_handler))
+            .route("/_website/:bucket/*key", any(crate::services::s3::handlers::website_handler));
     }
 
     // Lambda routes
@@ -50,6 +60,51 @@ pub fn create_router(emulator: Arc<Emulator>) -> Router {
             .route("/restapis/:api_id/resources/:resource_id", any(crate::services::apigateway::handlers::handle_request));
     }
 
+    // Workload credential endpoints (instance metadata and ECS container credentials)
+    #[cfg(feature = "iam")]
+    {
+        use crate::services::iam::credentials;
+        router = router
+            .route("/_aws/ec2/:instance_id/latest/api/token", axum::routing::put(credentials::metadata_token))
+            .route("/_aws/ec2/:instance_id/latest/meta-data/", get(credentials::metadata_index))
+            .route("/_aws/ec2/:instance_id/latest/meta-data/*path", get(credentials::metadata))
+            .route("/_aws/ecs/credentials/:cluster/:task_id", get(credentials::container_credentials));
+    }
+
+    // DAX cluster endpoint and its cache settings
+    #[cfg(feature = "dynamodb")]
+    {
+        use crate::services::dynamodb::{cache, handlers};
+        router = router
+            .route("/_aws/dax", axum::routing::post(handlers::handle_dax_request))
+            .route("/_aws/dax/cache", get(cache::get_cache).put(cache::update_cache).delete(cache::flush_cache));
+    }
+
+    // SNS SMS and mobile push sandbox
+    #[cfg(feature = "sns")]
+    {
+        use crate::services::sns::sandbox;
+        router = router
+            .route("/_aws/sns/sandbox", get(sandbox::list_messages).delete(sandbox::clear_messages));
+    }
+
+    // EventBridge Pipes routes
+    #[cfg(feature = "pipes")]
+    {
+        router = router
+            .route("/v1/pipes", any(crate::services::pipes::handlers::handle_request))
+            .route("/v1/pipes/:name", any(crate::services::pipes::handlers::handle_request))
+            .route("/v1/pipes/:name/start", any(crate::services::pipes::handlers::handle_request))
+            .route("/v1/pipes/:name/stop", any(crate::services::pipes::handlers::handle_request));
+    }
+
+    router = router.layer(axum::middleware::from_fn_with_state(emulator.clone(), crate::latency::inject));
+    if emulator.config.strict_auth {
+        // Unsigned and badly signed requests are rejected before any latency is injected
+        router = router.layer(axum::middleware::from_fn_with_state(emulator.clone(), super::ingress::strict_auth));
+    } else {
+        router = router.layer(axum::middleware::from_fn(super::ingress::presigned_expiry));
+    }
     router
         .with_state(emulator)
         .layer(TraceLayer::new_for_http())
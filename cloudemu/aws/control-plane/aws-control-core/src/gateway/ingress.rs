@@ -1,24 +1,58 @@
+use super::sigv4::{self, Authorization, Presigned};
 use crate::Emulator;
-use crate::error::Result;
+use crate::error::{ApiError, EmulatorError, Result};
 use aws_data_core::Config;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tracing::info;
 
+/// Largest difference between a request's `X-Amz-Date` and the clock, as in AWS
+const MAX_CLOCK_SKEW_SECS: i64 = 15 * 60;
+
 /// Ingress Controller: Starts the server and binds the Gateway
 pub async fn start(host: &str, port: u16, data_dir: PathBuf) -> Result<()> {
-    let config = Config {
+    start_with_config(Config {
         host: host.to_string(),
         port,
         data_dir,
         ..Default::default()
-    };
+    }).await
+}
+
+/// Start the server with a full configuration, e.g. with Terraform fixture mode enabled
+pub async fn start_with_config(config: Config) -> Result<()> {
     let addr = format!("{}:{}", config.host, config.port);
     let emulator = Arc::new(Emulator::with_config(config)?);
     
     // Gateway creation (Router)
     let app = super::gateway::create_router(emulator.clone());
+
+    // Pipes persisted as RUNNING resume polling after a restart
+    #[cfg(feature = "pipes")]
+    emulator.pipes.resume_pollers(&emulator)?;
+
+    // Lifecycle rules are applied in the background
+    #[cfg(feature = "s3")]
+    tokio::spawn(crate::services::s3::lifecycle::run(emulator.clone()));
+
+    if emulator.config.terraform_mode {
+        let fixtures = super::terraform::seed_fixtures(&emulator)?;
+        info!("Terraform fixture mode: {}", fixtures);
+        info!("Provider override: http://{}/_aws/terraform/provider.tf", addr);
+    }
+
+    if let Some(path) = &emulator.config.seed_file {
+        let spec = crate::datagen::DataSpec::from_yaml(&std::fs::read_to_string(path)?)?;
+        let generated = spec.generate(&emulator)?;
+        info!("Seeded from {}: {} items, {} objects, {} messages", path.display(), generated.items, generated.objects, generated.messages);
+    }
     
     info!("CloudEmu starting on http://{}", addr);
     
@@ -46,8 +80,15 @@ pub async fn start(host: &str, port: u16, data_dir: PathBuf) -> Result<()> {
     info!("  ✓ SNS");
     #[cfg(feature = "lambda")]
     info!("  ✓ Lambda");
+    #[cfg(feature = "pipes")]
+    info!("  ✓ EventBridge Pipes");
+    #[cfg(feature = "kinesis")]
+    info!("  ✓ Kinesis");
     info!("─────────────────────────────────────────");
     
+    if emulator.config.strict_auth {
+        info!("Strict auth: requests must be signed with Signature V4");
+    }
     info!("Data directory: {}", emulator.config.data_dir.display());
     info!("Region: {}", emulator.config.region);
     info!("─────────────────────────────────────────");
@@ -61,3 +102,133 @@ pub async fn start(host: &str, port: u16, data_dir: PathBuf) -> Result<()> {
     
     Ok(())
 }
+
+/// Strict auth mode: API requests without a valid Signature V4 are rejected with the error
+/// AWS would return (`AccessDenied`, `InvalidAccessKeyId`, `SignatureDoesNotMatch`,
+/// `RequestTimeTooSkewed`), so signing bugs surface locally. Admin, health and dashboard
+/// endpoints stay open.
+///
+/// Keys are looked up in the configured credentials and then in the IAM access keys and
+/// session credentials of the emulated account. The body is hashed unless the request
+/// declares its hash in `X-Amz-Content-SHA256`, as S3 clients do; the declared hash is signed
+/// but not checked against the body, and chunk signatures of streamed uploads are not verified.
+/// Presigned URLs are verified from their query string, with an unsigned payload, and
+/// refused once expired.
+pub async fn strict_auth(State(emulator): State<Arc<Emulator>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let internal = path.starts_with("/_aws/") || path.starts_with("/_localstack/") || path == "/health" || path == "/dashboard";
+    if internal {
+        return next.run(request).await;
+    }
+    match verify_signature(&emulator, request).await {
+        Ok(request) => next.run(request).await,
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+/// Outside strict auth, presigned URLs are still refused once expired, so that expiry can be
+/// tested with any credentials; their signature is not checked
+pub async fn presigned_expiry(request: Request, next: Next) -> Response {
+    let checked = Presigned::parse(request.uri().query())
+        .and_then(|presigned| presigned.as_ref().map_or(Ok(()), check_validity));
+    match checked {
+        Ok(()) => next.run(request).await,
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+/// Refuse a presigned URL used before it was signed or after it expired
+fn check_validity(presigned: &Presigned) -> std::result::Result<(), EmulatorError> {
+    let now = chrono::Utc::now();
+    if (presigned.signed_at - now).num_seconds() > MAX_CLOCK_SKEW_SECS {
+        return Err(EmulatorError::AccessDenied { code: "AccessDenied", message: "Request is not valid yet".to_string() });
+    }
+    if now > presigned.expires_at() {
+        return Err(EmulatorError::AccessDenied { code: "AccessDenied", message: "Request has expired".to_string() });
+    }
+    Ok(())
+}
+
+/// Check the signature of a request, handing it back with its body when it is valid
+async fn verify_signature(emulator: &Emulator, request: Request) -> std::result::Result<Request, EmulatorError> {
+    let (auth, amz_date, content_hash) = match Presigned::parse(request.uri().query())? {
+        Some(presigned) => {
+            check_validity(&presigned)?;
+            (presigned.auth, presigned.amz_date, Some(sigv4::UNSIGNED_PAYLOAD.to_string()))
+        }
+        None => signed_headers(&request)?,
+    };
+    if !amz_date.starts_with(&auth.date) {
+        return Err(EmulatorError::InvalidParameter {
+            code: "AuthorizationHeaderMalformed",
+            message: format!("Invalid credential date '{}'; it is not the same as X-Amz-Date", auth.date),
+        });
+    }
+
+    let secret = match emulator.config.credentials.iter().find(|(key, _)| *key == auth.access_key_id) {
+        Some((_, secret)) => Some(secret.clone()),
+        None => emulator.storage.find_secret_access_key(&auth.access_key_id)?,
+    };
+    let Some(secret) = secret else {
+        return Err(EmulatorError::AccessDenied {
+            code: "InvalidAccessKeyId",
+            message: format!("The AWS Access Key Id '{}' you provided does not exist in our records", auth.access_key_id),
+        });
+    };
+
+    let (request, payload_hash) = match content_hash {
+        Some(hash) => (request, hash),
+        None => {
+            let (parts, body) = request.into_parts();
+            let bytes = axum::body::to_bytes(body, usize::MAX).await.map_err(|e| EmulatorError::InvalidRequest(e.to_string()))?;
+            let hash = sigv4::sha256_hex(&bytes);
+            (Request::from_parts(parts, Body::from(bytes)), hash)
+        }
+    };
+
+    let uri = request.uri();
+    let canonical = sigv4::canonical_request(request.method().as_str(), uri.path(), uri.query(), request.headers(), &auth, &payload_hash);
+    let string_to_sign = sigv4::string_to_sign(&amz_date, &auth, &canonical);
+    if !sigv4::signatures_match(&sigv4::signature(&secret, &auth, &string_to_sign), &auth.signature) {
+        tracing::warn!(access_key_id = %auth.access_key_id, "Signature mismatch; canonical request:\n{}\nstring to sign:\n{}", canonical, string_to_sign);
+        return Err(EmulatorError::AccessDenied {
+            code: "SignatureDoesNotMatch",
+            message: "The request signature we calculated does not match the signature you provided. Check your key and signing method.".to_string(),
+        });
+    }
+    Ok(request)
+}
+
+/// Authorization, `X-Amz-Date` and declared payload hash of a request signed in its headers.
+///
+/// Owned, as the request's body is not Sync and a borrow of it cannot be held across the body read.
+fn signed_headers(request: &Request) -> std::result::Result<(Authorization, String, Option<String>), EmulatorError> {
+    let header = |name: &str| request.headers().get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+
+    let Some(authorization) = header("authorization") else {
+        return Err(EmulatorError::AccessDenied {
+            code: "AccessDenied",
+            message: "Strict auth is enabled and the request is not signed".to_string(),
+        });
+    };
+    let auth = Authorization::parse(&authorization)?;
+
+    let Some(amz_date) = header("x-amz-date") else {
+        return Err(EmulatorError::AccessDenied {
+            code: "AccessDenied",
+            message: "AWS authentication requires a valid X-Amz-Date header".to_string(),
+        });
+    };
+    let signed_at = sigv4::parse_amz_date(&amz_date).ok_or_else(|| EmulatorError::AccessDenied {
+        code: "AccessDenied",
+        message: format!("X-Amz-Date '{}' is not of the form YYYYMMDDTHHMMSSZ", amz_date),
+    })?;
+    let skew = (chrono::Utc::now() - signed_at).num_seconds().abs();
+    if skew > MAX_CLOCK_SKEW_SECS {
+        return Err(EmulatorError::AccessDenied {
+            code: "RequestTimeTooSkewed",
+            message: format!("The difference between the request time {} and the current time is too large", signed_at.to_rfc3339()),
+        });
+    }
+    Ok((auth, amz_date, header("x-amz-content-sha256")))
+}
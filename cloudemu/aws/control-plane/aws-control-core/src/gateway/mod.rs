@@ -4,6 +4,8 @@ pub mod dispatcher;
 #[allow(clippy::module_inception)]
 pub mod gateway;
 pub mod ingress;
+pub mod sigv4;
 pub mod dashboard;
+pub mod terraform;
 
 pub use gateway::create_router;
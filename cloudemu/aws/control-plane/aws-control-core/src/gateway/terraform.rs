@@ -0,0 +1,120 @@
+//! Terraform acceptance-test fixture mode: the resources a fresh account starts with
+//! and a provider override file pointing the AWS provider at the emulator
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use crate::Emulator;
+use crate::error::{ApiError, EmulatorError};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Account alias seeded when the account has none
+const DEFAULT_ACCOUNT_ALIAS: &str = "cloudemu";
+
+/// Create the default VPC and account alias if they are missing; safe to call repeatedly
+pub fn seed_fixtures(emulator: &Emulator) -> Result<Value, EmulatorError> {
+    let mut fixtures = json!({});
+    #[cfg(feature = "ec2")]
+    {
+        let vpc = emulator.ec2.ensure_default_vpc(&emulator.config.region)?;
+        fixtures["DefaultVpcId"] = json!(vpc.id);
+    }
+    #[cfg(feature = "iam")]
+    {
+        let aliases = emulator.storage.list_account_aliases()?;
+        let alias = match aliases.into_iter().next() {
+            Some(alias) => alias,
+            None => {
+                emulator.iam.create_account_alias(DEFAULT_ACCOUNT_ALIAS)?;
+                DEFAULT_ACCOUNT_ALIAS.to_string()
+            }
+        };
+        fixtures["AccountAlias"] = json!(alias);
+    }
+    Ok(fixtures)
+}
+
+/// `endpoints` block keys of the Terraform AWS provider, per service feature
+const PROVIDER_ENDPOINT_KEYS: [(bool, &[&str]); 24] = [
+    (cfg!(feature = "s3"), &["s3"]),
+    (cfg!(feature = "dynamodb"), &["dynamodb"]),
+    (cfg!(feature = "sqs"), &["sqs"]),
+    (cfg!(feature = "sns"), &["sns"]),
+    (cfg!(feature = "lambda"), &["lambda"]),
+    (cfg!(feature = "secretsmanager"), &["secretsmanager"]),
+    (cfg!(feature = "eventbridge"), &["events"]),
+    (cfg!(feature = "kms"), &["kms"]),
+    (cfg!(feature = "cloudwatch"), &["cloudwatch", "logs"]),
+    (cfg!(feature = "cognito"), &["cognitoidp"]),
+    (cfg!(feature = "stepfunctions"), &["sfn"]),
+    (cfg!(feature = "ec2"), &["ec2"]),
+    (cfg!(feature = "ecs"), &["ecs"]),
+    (cfg!(feature = "rds"), &["rds"]),
+    (cfg!(feature = "iam"), &["iam"]),
+    (cfg!(feature = "route53"), &["route53"]),
+    (cfg!(feature = "pricing"), &["pricing"]),
+    (cfg!(feature = "apigateway"), &["apigateway"]),
+    (cfg!(feature = "elb"), &["elb", "elbv2"]),
+    (cfg!(feature = "elasticache"), &["elasticache"]),
+    (cfg!(feature = "ecr"), &["ecr"]),
+    (cfg!(feature = "pipes"), &["pipes"]),
+    (cfg!(feature = "cloudtrail"), &["cloudtrail"]),
+    (cfg!(feature = "kinesis"), &["kinesis"]),
+];
+
+/// Provider endpoint keys of the services compiled in
+pub fn provider_endpoint_keys() -> Vec<&'static str> {
+    PROVIDER_ENDPOINT_KEYS.iter()
+        .filter(|(enabled, _)| *enabled)
+        .flat_map(|(_, keys)| keys.iter().copied())
+        .collect()
+}
+
+/// Render a `provider "aws"` override for `endpoint`. Every read is consistent with the
+/// preceding write here, so the provider's validation calls and retry backoff are turned off.
+pub fn provider_override(endpoint: &str, region: &str) -> String {
+    let keys = provider_endpoint_keys();
+    let width = keys.iter().map(|k| k.len()).max().unwrap_or(0);
+    let mut tf = String::from("# Generated by CloudEmu - save as provider_override.tf\n");
+    tf.push_str("provider \"aws\" {\n");
+    tf.push_str(&format!("  region                      = \"{}\"\n", region));
+    tf.push_str("  access_key                  = \"test\"\n");
+    tf.push_str("  secret_key                  = \"test\"\n");
+    tf.push_str("  s3_use_path_style           = true\n");
+    tf.push_str("  skip_credentials_validation = true\n");
+    tf.push_str("  skip_metadata_api_check     = true\n");
+    tf.push_str("  skip_region_validation      = true\n");
+    tf.push_str("  skip_requesting_account_id  = true\n");
+    tf.push_str("  max_retries                 = 1\n");
+    tf.push_str("\n  endpoints {\n");
+    for key in keys {
+        tf.push_str(&format!("    {:width$} = \"{}\"\n", key, endpoint, width = width));
+    }
+    tf.push_str("  }\n}\n");
+    tf
+}
+
+/// Seed the fixtures on demand (POST /_aws/terraform/fixtures)
+pub async fn create_fixtures(State(emulator): State<Arc<Emulator>>) -> Response {
+    match seed_fixtures(&emulator) {
+        Ok(fixtures) => Json(fixtures).into_response(),
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+/// Provider override file for the services compiled in (GET /_aws/terraform/provider.tf).
+/// The endpoint is the address the caller reached, since the bind address may be 0.0.0.0.
+pub async fn provider_file(State(emulator): State<Arc<Emulator>>, headers: HeaderMap) -> Response {
+    let endpoint = headers.get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .map(|host| format!("http://{}", host))
+        .unwrap_or_else(|| emulator.endpoint());
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        provider_override(&endpoint, &emulator.config.region),
+    ).into_response()
+}
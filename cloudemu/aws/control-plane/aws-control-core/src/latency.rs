@@ -0,0 +1,403 @@
+//! Per-operation latency injection, so performance tests see cloud-like timing
+//!
+//! A [`LatencyProfile`] gives a p50 and p99 in milliseconds per service and operation. Every
+//! API request is delayed by a sample from a log-normal distribution fitted to those two
+//! percentiles, capped at twice the p99. Lookups fall back from the operation to the
+//! service default and then to the profile default; calls with no match are not delayed.
+//!
+//! ```yaml
+//! default: { p50_ms: 10, p99_ms: 50 }
+//! services:
+//!   s3:
+//!     default: { p50_ms: 20, p99_ms: 80 }
+//!     operations:
+//!       GetObject: { p50_ms: 15, p99_ms: 60 }
+//! ```
+//!
+//! Services use their endpoint names (`s3`, `dynamodb`, `sqs`, `lambda`, ...) and operations
+//! their API names. The profile comes from `CLOUDEMU_LATENCY_PROFILE`, a preset name (`off`
+//! or `realistic`) or the path to a YAML or JSON file, and can be replaced at runtime with
+//! `PUT /_aws/latency`. Admin (`/_aws/...`), health and dashboard endpoints are never delayed.
+
+use crate::Emulator;
+use crate::datagen::Faker;
+use crate::error::{ApiError, EmulatorError};
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+/// z-score of the 99th percentile of the standard normal distribution
+const Z_P99: f64 = 2.326_347_874;
+
+/// Latency percentiles of one operation, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Distribution {
+    pub p50_ms: f64,
+    pub p99_ms: f64,
+}
+
+impl Distribution {
+    pub fn new(p50_ms: f64, p99_ms: f64) -> Self {
+        Self { p50_ms, p99_ms }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), EmulatorError> {
+        if !(self.p50_ms >= 0.0 && self.p99_ms >= self.p50_ms && self.p99_ms.is_finite()) {
+            return Err(EmulatorError::InvalidArgument(format!(
+                "Latency of {} needs 0 <= p50_ms <= p99_ms, got {} and {}", name, self.p50_ms, self.p99_ms
+            )));
+        }
+        Ok(())
+    }
+
+    /// Draw a delay; `unit` yields uniform samples in `(0, 1]`
+    fn sample(&self, mut unit: impl FnMut() -> f64) -> Duration {
+        if self.p50_ms <= 0.0 {
+            return Duration::ZERO;
+        }
+        let sigma = (self.p99_ms / self.p50_ms).ln() / Z_P99;
+        // Box-Muller transform
+        let z = (-2.0 * unit().ln()).sqrt() * (2.0 * std::f64::consts::PI * unit()).cos();
+        let ms = (self.p50_ms * (sigma * z).exp()).min(self.p99_ms * 2.0);
+        Duration::from_secs_f64(ms / 1000.0)
+    }
+}
+
+/// Latencies of one service
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceLatency {
+    /// Latency of operations not listed below
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Distribution>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub operations: HashMap<String, Distribution>,
+}
+
+/// Latencies applied to API calls
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LatencyProfile {
+    /// Latency of services not listed below
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Distribution>,
+    #[serde(default)]
+    pub services: HashMap<String, ServiceLatency>,
+}
+
+/// `(service, operation, p50, p99)` of the `realistic` preset; `*` is the service default.
+/// Figures are typical same-region client timings, excluding the work a call triggers.
+const REALISTIC: &[(&str, &str, f64, f64)] = &[
+    ("s3", "*", 20.0, 90.0),
+    ("s3", "GetObject", 15.0, 70.0),
+    ("s3", "HeadObject", 10.0, 45.0),
+    ("s3", "PutObject", 25.0, 110.0),
+    ("s3", "ListObjectsV2", 30.0, 140.0),
+    ("s3", "CreateBucket", 120.0, 500.0),
+    ("dynamodb", "*", 6.0, 25.0),
+    ("dynamodb", "GetItem", 4.0, 15.0),
+    ("dynamodb", "PutItem", 6.0, 25.0),
+    ("dynamodb", "Query", 8.0, 35.0),
+    ("dynamodb", "Scan", 30.0, 150.0),
+    ("dynamodb", "CreateTable", 200.0, 800.0),
+    ("sqs", "*", 10.0, 40.0),
+    ("sqs", "SendMessage", 8.0, 30.0),
+    ("sqs", "ReceiveMessage", 10.0, 50.0),
+    ("sns", "*", 15.0, 60.0),
+    ("lambda", "*", 25.0, 120.0),
+    ("lambda", "Invoke", 20.0, 150.0),
+    ("kms", "*", 8.0, 30.0),
+    ("secretsmanager", "*", 15.0, 60.0),
+    ("events", "*", 15.0, 60.0),
+    ("states", "*", 25.0, 100.0),
+    ("kinesis", "*", 12.0, 50.0),
+    ("logs", "*", 15.0, 60.0),
+    ("monitoring", "*", 15.0, 60.0),
+    ("iam", "*", 40.0, 160.0),
+    ("cognito-idp", "*", 40.0, 160.0),
+];
+
+impl LatencyProfile {
+    /// Built-in profile by name
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::default()),
+            "realistic" => {
+                let mut profile = Self { default: Some(Distribution::new(20.0, 80.0)), services: HashMap::new() };
+                for (service, operation, p50, p99) in REALISTIC {
+                    let latency = profile.services.entry(service.to_string()).or_default();
+                    let distribution = Distribution::new(*p50, *p99);
+                    match *operation {
+                        "*" => latency.default = Some(distribution),
+                        operation => {
+                            latency.operations.insert(operation.to_string(), distribution);
+                        }
+                    }
+                }
+                Some(profile)
+            }
+            _ => None,
+        }
+    }
+
+    /// A preset name, or the path to a YAML or JSON profile
+    pub fn resolve(spec: &str) -> Result<Self, EmulatorError> {
+        if let Some(profile) = Self::preset(spec) {
+            return Ok(profile);
+        }
+        let text = std::fs::read_to_string(spec).map_err(|e| {
+            EmulatorError::InvalidArgument(format!("Latency profile {} is neither off, realistic nor a readable file: {}", spec, e))
+        })?;
+        let profile: Self = serde_yaml::from_str(&text)
+            .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid latency profile: {}", e)))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    pub fn validate(&self) -> Result<(), EmulatorError> {
+        if let Some(default) = &self.default {
+            default.validate("default")?;
+        }
+        for (service, latency) in &self.services {
+            if let Some(default) = &latency.default {
+                default.validate(service)?;
+            }
+            for (operation, distribution) in &latency.operations {
+                distribution.validate(&format!("{}.{}", service, operation))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.services.values().all(|s| s.default.is_none() && s.operations.is_empty())
+    }
+
+    /// Distribution applied to an operation
+    pub fn lookup(&self, service: &str, operation: &str) -> Option<&Distribution> {
+        let latency = self.services.get(service);
+        latency.and_then(|s| s.operations.get(operation))
+            .or_else(|| latency.and_then(|s| s.default.as_ref()))
+            .or(self.default.as_ref())
+    }
+}
+
+/// Active latency profile of an emulator
+pub struct LatencyModel {
+    profile: RwLock<LatencyProfile>,
+    rng: Mutex<Faker>,
+}
+
+impl LatencyModel {
+    pub fn new(profile: LatencyProfile) -> Self {
+        let seed = uuid::Uuid::new_v4().as_u64_pair().0;
+        Self { profile: RwLock::new(profile), rng: Mutex::new(Faker::new(seed)) }
+    }
+
+    /// Model for `CLOUDEMU_LATENCY_PROFILE`, off when unset
+    pub fn from_config(config: &crate::Config) -> Result<Self, EmulatorError> {
+        let profile = match &config.latency_profile {
+            Some(spec) => LatencyProfile::resolve(spec)?,
+            None => LatencyProfile::default(),
+        };
+        Ok(Self::new(profile))
+    }
+
+    pub fn profile(&self) -> LatencyProfile {
+        self.profile.read().unwrap().clone()
+    }
+
+    pub fn set_profile(&self, profile: LatencyProfile) -> Result<(), EmulatorError> {
+        profile.validate()?;
+        *self.profile.write().unwrap() = profile;
+        Ok(())
+    }
+
+    /// Delay for one call, or `None` when the profile does not cover it
+    pub fn sample(&self, service: &str, operation: &str) -> Option<Duration> {
+        let distribution = *self.profile.read().unwrap().lookup(service, operation)?;
+        let mut rng = self.rng.lock().unwrap();
+        // Top 53 bits as a float in (0, 1], so ln() stays finite
+        Some(distribution.sample(|| ((rng.next_u64() >> 11) as f64 + 1.0) / (1u64 << 53) as f64))
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.profile.read().unwrap().is_empty()
+    }
+}
+
+/// Middleware delaying API calls by the active profile
+pub async fn inject(State(emulator): State<Arc<Emulator>>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    let internal = path.starts_with("/_aws/") || path.starts_with("/_localstack/") || path == "/health" || path == "/dashboard";
+    if internal || !emulator.latency.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let (request, call) = classify(request).await;
+    if let Some(delay) = call.and_then(|(service, operation)| emulator.latency.sample(&service, &operation)) {
+        tokio::time::sleep(delay).await;
+    }
+    next.run(request).await
+}
+
+/// Service and operation of a request. Query protocol bodies are buffered to read the
+/// `Action` and handed back in a rebuilt request.
+async fn classify(request: Request) -> (Request, Option<(String, String)>) {
+    let target = header_str(request.headers(), "x-amz-target").to_string();
+    let mut action = request.uri().query()
+        .and_then(|query| crate::adapters::aws_query::parse_query_string(query).remove("Action"));
+
+    let is_form = header_str(request.headers(), header::CONTENT_TYPE.as_str()).starts_with("application/x-www-form-urlencoded");
+    let request = if action.is_none() && target.is_empty() && is_form {
+        let (parts, body) = request.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap_or_default();
+        action = crate::adapters::aws_query::parse_query_string(&String::from_utf8_lossy(&bytes)).remove("Action");
+        Request::from_parts(parts, Body::from(bytes))
+    } else {
+        request
+    };
+
+    let call = classify_parts(request.method(), request.uri().path(), request.headers(), &target, action);
+    (request, call)
+}
+
+fn classify_parts(method: &Method, path: &str, headers: &HeaderMap, target: &str, action: Option<String>) -> Option<(String, String)> {
+    if !target.is_empty() {
+        let service = crate::gateway::dispatcher::service_name(target)?;
+        let operation = target.rsplit_once('.').map(|(_, op)| op).unwrap_or(target);
+        return Some((service.to_string(), operation.to_string()));
+    }
+    if let Some(action) = action {
+        // Query protocol calls are signed for their service; unsigned ones go to SNS like the dispatcher
+        let service = signing_service(headers).unwrap_or_else(|| "sns".to_string());
+        return Some((service, action));
+    }
+    if let Some(rest) = path.strip_prefix("/2015-03-31/functions") {
+        let operation = if rest.ends_with("/invocations") { "Invoke" } else { "*" };
+        return Some(("lambda".to_string(), operation.to_string()));
+    }
+    if path.starts_with("/restapis") {
+        return Some(("apigateway".to_string(), "*".to_string()));
+    }
+    if path.starts_with("/v1/pipes") {
+        return Some(("pipes".to_string(), "*".to_string()));
+    }
+    if path.starts_with("/_website/") {
+        return Some(("s3".to_string(), "GetObject".to_string()));
+    }
+    Some(("s3".to_string(), s3_operation(method, path).to_string()))
+}
+
+/// S3 operation of a REST request
+fn s3_operation(method: &Method, path: &str) -> &'static str {
+    let segments = path.trim_matches('/').splitn(2, '/').filter(|s| !s.is_empty()).count();
+    match (segments, method.as_str()) {
+        (0, _) => "ListBuckets",
+        (1, "GET") => "ListObjectsV2",
+        (1, "PUT") => "CreateBucket",
+        (1, "DELETE") => "DeleteBucket",
+        (1, "HEAD") => "HeadBucket",
+        (_, "GET") => "GetObject",
+        (_, "PUT") => "PutObject",
+        (_, "DELETE") => "DeleteObject",
+        (_, "HEAD") => "HeadObject",
+        (_, "POST") => "PostObject",
+        _ => "*",
+    }
+}
+
+/// Service of the SigV4 credential scope (`Credential=AKID/date/region/service/aws4_request`)
+fn signing_service(headers: &HeaderMap) -> Option<String> {
+    let authorization = header_str(headers, header::AUTHORIZATION.as_str());
+    let credential = authorization.split("Credential=").nth(1)?.split(',').next()?;
+    credential.split('/').nth(3).map(str::to_string)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> &'a str {
+    headers.get(name).and_then(|h| h.to_str().ok()).unwrap_or("")
+}
+
+/// Body of `PUT /_aws/latency`: a preset name or a full profile
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum LatencyRequest {
+    Preset { preset: String },
+    Profile(LatencyProfile),
+}
+
+/// `GET /_aws/latency`
+pub async fn get_profile(State(emulator): State<Arc<Emulator>>) -> Response {
+    Json(emulator.latency.profile()).into_response()
+}
+
+/// `PUT /_aws/latency`
+pub async fn set_profile(State(emulator): State<Arc<Emulator>>, Json(request): Json<LatencyRequest>) -> Response {
+    let profile = match request {
+        LatencyRequest::Preset { preset } => match LatencyProfile::preset(&preset) {
+            Some(profile) => profile,
+            None => return ApiError(EmulatorError::InvalidArgument(format!("Unknown latency preset {}, expected off or realistic", preset))).into_response(),
+        },
+        LatencyRequest::Profile(profile) => profile,
+    };
+    match emulator.latency.set_profile(profile) {
+        Ok(()) => Json(emulator.latency.profile()).into_response(),
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_matches_percentiles() {
+        let model = LatencyModel::new(LatencyProfile {
+            default: Some(Distribution::new(20.0, 100.0)),
+            services: HashMap::new(),
+        });
+        let mut samples: Vec<f64> = (0..20_000)
+            .map(|_| model.sample("s3", "GetObject").unwrap().as_secs_f64() * 1000.0)
+            .collect();
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let p50 = samples[samples.len() / 2];
+        let p99 = samples[samples.len() * 99 / 100];
+        assert!((17.0..23.0).contains(&p50), "p50 {}", p50);
+        assert!((80.0..125.0).contains(&p99), "p99 {}", p99);
+        assert!(samples.last().copied().unwrap() <= 200.0);
+    }
+
+    #[test]
+    fn test_classify_requests() {
+        let headers = HeaderMap::new();
+        let call = |method: Method, path: &str, target: &str| classify_parts(&method, path, &headers, target, None);
+        assert_eq!(call(Method::POST, "/", "DynamoDB_20120810.GetItem"), Some(("dynamodb".into(), "GetItem".into())));
+        assert_eq!(call(Method::GET, "/bucket/a/b.txt", ""), Some(("s3".into(), "GetObject".into())));
+        assert_eq!(call(Method::PUT, "/bucket", ""), Some(("s3".into(), "CreateBucket".into())));
+        assert_eq!(call(Method::POST, "/2015-03-31/functions/f/invocations", ""), Some(("lambda".into(), "Invoke".into())));
+
+        let mut signed = HeaderMap::new();
+        signed.insert(header::AUTHORIZATION, "AWS4-HMAC-SHA256 Credential=AKID/20240101/us-east-1/iam/aws4_request, SignedHeaders=host, Signature=0".parse().unwrap());
+        assert_eq!(classify_parts(&Method::POST, "/", &signed, "", Some("CreateRole".into())), Some(("iam".into(), "CreateRole".into())));
+    }
+
+    #[test]
+    fn test_realistic_preset_lookup() {
+        let profile = LatencyProfile::preset("realistic").unwrap();
+        assert_eq!(profile.lookup("s3", "GetObject"), Some(&Distribution::new(15.0, 70.0)));
+        assert_eq!(profile.lookup("s3", "DeleteObject"), Some(&Distribution::new(20.0, 90.0)));
+        assert_eq!(profile.lookup("route53", "ListHostedZones"), Some(&Distribution::new(20.0, 80.0)));
+        assert!(LatencyProfile::preset("off").unwrap().is_empty());
+        assert!(LatencyProfile::resolve("fast").is_err());
+    }
+}
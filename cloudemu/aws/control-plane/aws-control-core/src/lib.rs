@@ -1,6 +1,11 @@
 pub mod adapters;
+pub mod datagen;
 pub mod error;
+pub mod event_bus;
 pub mod gateway;
+pub mod latency;
+pub mod limits;
+pub mod scenario;
 pub mod services;
 
 pub use error::{ApiError, Result};
@@ -8,12 +13,19 @@ pub use error::{ApiError, Result};
 // use tokio::net::TcpListener;
 // use tracing::info;
 
-use aws_data_core::{Config, StorageEngine};
+pub use aws_data_core::Config;
+use aws_data_core::StorageEngine;
 
 /// Emulator state containing all services and storage
 pub struct Emulator {
     pub config: Config,
     pub storage: StorageEngine,
+    /// Resource lifecycle events published by all services
+    pub bus: event_bus::EventBus,
+    /// Latency injected into API calls
+    pub latency: latency::LatencyModel,
+    /// Service quotas of the emulated account
+    pub limits: limits::Limits,
     #[cfg(feature = "s3")]
     pub s3: services::s3::S3Service,
     #[cfg(feature = "dynamodb")]
@@ -56,6 +68,12 @@ pub struct Emulator {
     pub elasticache: services::elasticache::ElastiCacheService,
     #[cfg(feature = "ecr")]
     pub ecr: services::ecr::EcrService,
+    #[cfg(feature = "pipes")]
+    pub pipes: services::pipes::PipesService,
+    #[cfg(feature = "cloudtrail")]
+    pub cloudtrail: services::cloudtrail::CloudTrailService,
+    #[cfg(feature = "kinesis")]
+    pub kinesis: services::kinesis::KinesisService,
 }
 
 impl Emulator {
@@ -73,13 +91,13 @@ impl Emulator {
             #[cfg(feature = "s3")]
             s3: services::s3::S3Service::new(storage.clone()),
             #[cfg(feature = "dynamodb")]
-            dynamodb: services::dynamodb::DynamoDbService::new(storage.clone()),
+            dynamodb: services::dynamodb::DynamoDbService::new(storage.clone(), &config),
             #[cfg(feature = "sqs")]
             sqs: services::sqs::SqsService::new(storage.clone()),
             #[cfg(feature = "secretsmanager")]
             secrets: services::secrets::SecretsService::new(storage.clone()),
             #[cfg(feature = "eventbridge")]
-            events: services::events::EventsService::new(storage.clone()),
+            events: services::events::EventsService::new(storage.clone(), config.clone()),
             #[cfg(feature = "kms")]
             kms: services::kms::KmsService::new(storage.clone()),
             #[cfg(feature = "cloudwatch")]
@@ -87,7 +105,7 @@ impl Emulator {
             #[cfg(feature = "cognito")]
             identity: services::identity::IdentityService::new(storage.clone()),
             #[cfg(feature = "stepfunctions")]
-            workflows: services::workflows::WorkflowsService::new(storage.clone()),
+            workflows: services::workflows::WorkflowsService::new(storage.clone(), &config)?,
             #[cfg(feature = "sns")]
             sns: services::sns::SnsService::new(storage.clone()),
             #[cfg(feature = "lambda")]
@@ -95,11 +113,11 @@ impl Emulator {
             #[cfg(feature = "ec2")]
             ec2: services::ec2::Ec2Service::new(storage.clone()),
             #[cfg(feature = "ecs")]
-            ecs: services::ecs::EcsService::new(),
+            ecs: services::ecs::EcsService::new(storage.clone()),
             #[cfg(feature = "rds")]
             rds: services::rds::RdsService::new(),
             #[cfg(feature = "iam")]
-            iam: services::iam::IamService::new(),
+            iam: services::iam::IamService::new(storage.clone()),
             #[cfg(feature = "route53")]
             route53: services::route53::Route53Service::new(),
             #[cfg(feature = "pricing")]
@@ -112,9 +130,19 @@ impl Emulator {
             elasticache: services::elasticache::ElastiCacheService::new(storage.clone()),
             #[cfg(feature = "ecr")]
             ecr: services::ecr::EcrService::new(storage.clone()),
+            #[cfg(feature = "pipes")]
+            pipes: services::pipes::PipesService::new(storage.clone()),
+            #[cfg(feature = "cloudtrail")]
+            cloudtrail: services::cloudtrail::CloudTrailService::new(storage.clone(), config.clone()),
+            #[cfg(feature = "kinesis")]
+            kinesis: services::kinesis::KinesisService::new(storage.clone()),
+            bus: event_bus::EventBus::new(),
+            latency: latency::LatencyModel::from_config(&config)?,
+            limits: limits::Limits::default(),
             storage,
             config,
-        })
+        }
+        .with_event_subscribers())
     }
 
     /// Create a new emulator with custom configuration
@@ -126,13 +154,13 @@ impl Emulator {
             #[cfg(feature = "s3")]
             s3: services::s3::S3Service::new(storage.clone()),
             #[cfg(feature = "dynamodb")]
-            dynamodb: services::dynamodb::DynamoDbService::new(storage.clone()),
+            dynamodb: services::dynamodb::DynamoDbService::new(storage.clone(), &config),
             #[cfg(feature = "sqs")]
             sqs: services::sqs::SqsService::new(storage.clone()),
             #[cfg(feature = "secretsmanager")]
             secrets: services::secrets::SecretsService::new(storage.clone()),
             #[cfg(feature = "eventbridge")]
-            events: services::events::EventsService::new(storage.clone()),
+            events: services::events::EventsService::new(storage.clone(), config.clone()),
             #[cfg(feature = "kms")]
             kms: services::kms::KmsService::new(storage.clone()),
             #[cfg(feature = "cloudwatch")]
@@ -140,7 +168,7 @@ impl Emulator {
             #[cfg(feature = "cognito")]
             identity: services::identity::IdentityService::new(storage.clone()),
             #[cfg(feature = "stepfunctions")]
-            workflows: services::workflows::WorkflowsService::new(storage.clone()),
+            workflows: services::workflows::WorkflowsService::new(storage.clone(), &config)?,
             #[cfg(feature = "sns")]
             sns: services::sns::SnsService::new(storage.clone()),
             #[cfg(feature = "lambda")]
@@ -148,11 +176,11 @@ impl Emulator {
             #[cfg(feature = "ec2")]
             ec2: services::ec2::Ec2Service::new(storage.clone()),
             #[cfg(feature = "ecs")]
-            ecs: services::ecs::EcsService::new(),
+            ecs: services::ecs::EcsService::new(storage.clone()),
             #[cfg(feature = "rds")]
             rds: services::rds::RdsService::new(),
             #[cfg(feature = "iam")]
-            iam: services::iam::IamService::new(),
+            iam: services::iam::IamService::new(storage.clone()),
             #[cfg(feature = "route53")]
             route53: services::route53::Route53Service::new(),
             #[cfg(feature = "pricing")]
@@ -165,9 +193,32 @@ impl Emulator {
             elasticache: services::elasticache::ElastiCacheService::new(storage.clone()),
             #[cfg(feature = "ecr")]
             ecr: services::ecr::EcrService::new(storage.clone()),
+            #[cfg(feature = "pipes")]
+            pipes: services::pipes::PipesService::new(storage.clone()),
+            #[cfg(feature = "cloudtrail")]
+            cloudtrail: services::cloudtrail::CloudTrailService::new(storage.clone(), config.clone()),
+            #[cfg(feature = "kinesis")]
+            kinesis: services::kinesis::KinesisService::new(storage.clone()),
+            bus: event_bus::EventBus::new(),
+            latency: latency::LatencyModel::from_config(&config)?,
+            limits: limits::Limits::default(),
             storage,
             config,
-        })
+        }
+        .with_event_subscribers())
+    }
+    
+    /// Subscribe the services that consume resource events to the bus
+    fn with_event_subscribers(self) -> Self {
+        #[cfg(feature = "s3")]
+        self.bus.subscribe(std::sync::Arc::new(
+            services::s3::BucketNotifications::new(self.storage.clone(), self.config.clone()),
+        ));
+        #[cfg(feature = "eventbridge")]
+        self.bus.subscribe(std::sync::Arc::new(self.events.clone()));
+        #[cfg(feature = "cloudtrail")]
+        self.bus.subscribe(std::sync::Arc::new(self.cloudtrail.clone()));
+        self
     }
     
     /// Get the endpoint URL
@@ -0,0 +1,207 @@
+//! Service quotas of the emulated account, so limit-handling code paths run locally
+//!
+//! Requests over a quota fail with the error code and HTTP status the real service uses:
+//!
+//! | Quota | Default | Error |
+//! | :--- | :--- | :--- |
+//! | `s3_max_buckets` | 10,000 buckets | `TooManyBuckets` (400) |
+//! | `lambda_max_code_zip_bytes` | 50 MB zipped code | `RequestEntityTooLargeException` (413) |
+//! | `sqs_max_message_bytes` | 1 MiB body and attributes | `InvalidParameterValue` (400) |
+//! | `dynamodb_max_item_bytes` | 400 KB item | `ValidationException` (400) |
+//!
+//! Defaults match AWS. `GET /_aws/limits` returns the quotas; `PUT /_aws/limits` changes the
+//! fields it is given, so a test can lower a quota to reach it with little data.
+
+use crate::Emulator;
+use crate::error::{ApiError, EmulatorError};
+use axum::{extract::State, response::{IntoResponse, Response}, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::{Arc, RwLock};
+
+/// Quotas of the emulated account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceLimits {
+    pub s3_max_buckets: usize,
+    pub lambda_max_code_zip_bytes: usize,
+    pub sqs_max_message_bytes: usize,
+    pub dynamodb_max_item_bytes: usize,
+}
+
+impl Default for ServiceLimits {
+    fn default() -> Self {
+        Self {
+            s3_max_buckets: 10_000,
+            lambda_max_code_zip_bytes: 50 * 1024 * 1024,
+            sqs_max_message_bytes: 1024 * 1024,
+            dynamodb_max_item_bytes: 400 * 1024,
+        }
+    }
+}
+
+/// Active quotas of an emulator
+#[derive(Default)]
+pub struct Limits {
+    limits: RwLock<ServiceLimits>,
+}
+
+impl Limits {
+    pub fn get(&self) -> ServiceLimits {
+        self.limits.read().unwrap().clone()
+    }
+
+    pub fn set(&self, limits: ServiceLimits) {
+        *self.limits.write().unwrap() = limits;
+    }
+
+    /// Change the quotas named in `changes`, a JSON object of [`ServiceLimits`] fields
+    pub fn update(&self, changes: &Value) -> Result<ServiceLimits, EmulatorError> {
+        let changes = changes.as_object()
+            .ok_or_else(|| EmulatorError::InvalidArgument("Limits must be a JSON object".into()))?;
+        let mut limits = self.limits.write().unwrap();
+        let mut merged = serde_json::to_value(&*limits)?;
+        for (name, value) in changes {
+            merged[name] = value.clone();
+        }
+        *limits = serde_json::from_value(merged)
+            .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid limits: {}", e)))?;
+        Ok(limits.clone())
+    }
+
+    /// Before creating a bucket when `existing` buckets are owned
+    pub fn check_bucket_count(&self, existing: usize) -> Result<(), EmulatorError> {
+        if existing >= self.get().s3_max_buckets {
+            return Err(EmulatorError::LimitExceeded {
+                code: "TooManyBuckets",
+                status: 400,
+                message: "You have attempted to create more buckets than allowed".into(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Zipped function code uploaded with CreateFunction
+    pub fn check_code_size(&self, zipped_bytes: usize) -> Result<(), EmulatorError> {
+        let max = self.get().lambda_max_code_zip_bytes;
+        if zipped_bytes > max {
+            return Err(EmulatorError::LimitExceeded {
+                code: "RequestEntityTooLargeException",
+                status: 413,
+                message: format!("Zipped code size {} exceeds the maximum of {} bytes for the CreateFunction operation", zipped_bytes, max),
+            });
+        }
+        Ok(())
+    }
+
+    /// Body and message attributes of an SQS message, for a queue whose
+    /// `MaximumMessageSize` is `queue_max`
+    pub fn check_message_size(&self, body: &str, attributes: &Value, queue_max: usize) -> Result<(), EmulatorError> {
+        let max = self.get().sqs_max_message_bytes.min(queue_max);
+        if message_size(body, attributes) > max {
+            return Err(EmulatorError::LimitExceeded {
+                code: "InvalidParameterValue",
+                status: 400,
+                message: format!("One or more parameters are invalid. Reason: Message must be shorter than {} bytes.", max),
+            });
+        }
+        Ok(())
+    }
+
+    /// A DynamoDB item in attribute-value form
+    pub fn check_item_size(&self, item: &Value) -> Result<(), EmulatorError> {
+        if item_size(item) > self.get().dynamodb_max_item_bytes {
+            return Err(EmulatorError::LimitExceeded {
+                code: "ValidationException",
+                status: 400,
+                message: "Item size has exceeded the maximum allowed size".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Size SQS counts against the message limit: the body plus each attribute's name, data
+/// type and value
+pub fn message_size(body: &str, attributes: &Value) -> usize {
+    let attributes = attributes.as_object().map(|map| {
+        map.iter().map(|(name, attr)| {
+            let value = attr["StringValue"].as_str().or(attr["BinaryValue"].as_str()).unwrap_or_default();
+            name.len() + attr["DataType"].as_str().unwrap_or_default().len() + value.len()
+        }).sum()
+    }).unwrap_or(0);
+    body.len() + attributes
+}
+
+/// Size DynamoDB counts against the item limit: attribute names plus their values
+pub fn item_size(item: &Value) -> usize {
+    item.as_object()
+        .map(|attributes| attributes.iter().map(|(name, value)| name.len() + value_size(value)).sum())
+        .unwrap_or(0)
+}
+
+fn value_size(value: &Value) -> usize {
+    let Some((data_type, inner)) = value.as_object().and_then(|o| o.iter().next()) else {
+        return 0;
+    };
+    match (data_type.as_str(), inner) {
+        ("S", Value::String(s)) => s.len(),
+        // Numbers take about one byte per two significant digits, plus one
+        ("N", Value::String(n)) => n.trim_start_matches(['-', '0']).replace('.', "").len().div_ceil(2) + 1,
+        ("B", Value::String(b)) => base64_len(b),
+        ("BOOL", _) | ("NULL", _) => 1,
+        ("SS", Value::Array(items)) => items.iter().filter_map(Value::as_str).map(str::len).sum(),
+        ("NS", Value::Array(items)) => items.iter().filter_map(Value::as_str).map(|n| n.len().div_ceil(2) + 1).sum(),
+        ("BS", Value::Array(items)) => items.iter().filter_map(Value::as_str).map(base64_len).sum(),
+        // Lists and maps take 3 bytes, plus 1 per element
+        ("L", Value::Array(items)) => 3 + items.iter().map(|item| 1 + value_size(item)).sum::<usize>(),
+        ("M", Value::Object(_)) => 3 + inner.as_object().map(|map| map.len()).unwrap_or(0) + item_size(inner),
+        _ => 0,
+    }
+}
+
+/// Decoded size of base64 text
+fn base64_len(encoded: &str) -> usize {
+    let padding = encoded.bytes().rev().take_while(|b| *b == b'=').count();
+    (encoded.len() / 4 * 3).saturating_sub(padding)
+}
+
+/// `GET /_aws/limits`
+pub async fn get_limits(State(emulator): State<Arc<Emulator>>) -> Response {
+    Json(emulator.limits.get()).into_response()
+}
+
+/// `PUT /_aws/limits`
+pub async fn update_limits(State(emulator): State<Arc<Emulator>>, Json(changes): Json<Value>) -> Response {
+    match emulator.limits.update(&changes) {
+        Ok(limits) => Json(limits).into_response(),
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_item_size() {
+        // Names plus 5 bytes for the string, 3 for a 3-digit number and 1 for the boolean
+        let item = json!({ "id": { "S": "abcde" }, "count": { "N": "123" }, "admin": { "BOOL": true } });
+        assert_eq!(item_size(&item), (2 + 5) + (5 + 3) + (5 + 1));
+        let nested = json!({ "tags": { "L": [{ "S": "a" }, { "S": "bc" }] } });
+        assert_eq!(item_size(&nested), 4 + 3 + 2 + 3);
+        assert_eq!(item_size(&json!({ "data": { "B": "aGVsbG8=" } })), 4 + 5);
+    }
+
+    #[test]
+    fn test_update_merges_fields() {
+        let limits = Limits::default();
+        let updated = limits.update(&json!({ "s3_max_buckets": 2 })).unwrap();
+        assert_eq!(updated.s3_max_buckets, 2);
+        assert_eq!(updated.dynamodb_max_item_bytes, 400 * 1024);
+        assert!(limits.update(&json!({ "s3_buckets": 2 })).is_err());
+        assert!(limits.update(&json!({ "s3_max_buckets": -1 })).is_err());
+        assert_eq!(limits.get().s3_max_buckets, 2);
+    }
+}
@@ -0,0 +1,267 @@
+//! Declarative multi-service test flows
+//!
+//! A [`Scenario`] is a list of requests against the emulator's router, each with the
+//! status and response content it expects. Values captured from one response (a queue
+//! URL, a message body) are substituted as `${name}` into later steps, and a step with a
+//! timeout is repeated until its expectations hold, for effects that arrive
+//! asynchronously such as a bucket notification landing in a queue.
+//!
+//! Scenarios are written in YAML and loaded with [`Scenario::from_yaml`], or built in
+//! Rust with [`Scenario::new`] and the [`Step`] builders.
+
+use axum::body::Body;
+use axum::http::Request;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tower::ServiceExt;
+
+/// Wait between attempts of a step that has a timeout
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Content type of `target` steps unless the step sets one
+const JSON_PROTOCOL_CONTENT_TYPE: &str = "application/x-amz-json-1.0";
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScenarioError {
+    #[error("invalid scenario: {0}")]
+    Parse(String),
+    #[error("step '{step}' failed: {reason}")]
+    Step { step: String, reason: String },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    /// Variables available to every step, alongside the captured ones
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    pub steps: Vec<Step>,
+}
+
+/// One request and what its response must look like
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Step {
+    pub name: String,
+    /// Method of a REST-style request (S3, Lambda, API Gateway)
+    #[serde(default)]
+    pub method: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+    /// JSON-protocol operation such as `AmazonSQS.SendMessage`, sent as `POST /`
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Strings are sent as they are (S3 XML, object data); anything else as JSON
+    #[serde(default)]
+    pub body: Option<Value>,
+    #[serde(default)]
+    pub expect: Expect,
+    /// Variables to set from the response, by JSON pointer, e.g. `queue: /QueueUrl`
+    #[serde(default)]
+    pub capture: BTreeMap<String, String>,
+    /// Repeat the request until the expectations hold or this many milliseconds pass
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Expect {
+    #[serde(default)]
+    pub status: Option<u16>,
+    /// Values at JSON pointers of the response body
+    #[serde(default)]
+    pub json: BTreeMap<String, Value>,
+    /// Text the response body must contain
+    #[serde(default)]
+    pub contains: Vec<String>,
+}
+
+impl Scenario {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), ..Self::default() }
+    }
+
+    pub fn from_yaml(yaml: &str) -> Result<Self, ScenarioError> {
+        serde_yaml::from_str(yaml).map_err(|e| ScenarioError::Parse(e.to_string()))
+    }
+
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Run the steps in order, stopping at the first that fails; returns the variables
+    /// as they are after the last step
+    pub async fn run(&self, router: &axum::Router) -> Result<BTreeMap<String, String>, ScenarioError> {
+        let mut vars = self.vars.clone();
+        for step in &self.steps {
+            step.run(router, &mut vars).await?;
+        }
+        Ok(vars)
+    }
+}
+
+impl Step {
+    /// A REST-style request
+    pub fn request(name: impl Into<String>, method: &str, path: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            method: Some(method.to_string()),
+            path: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    /// A JSON-protocol operation
+    pub fn call(name: impl Into<String>, target: impl Into<String>, body: Value) -> Self {
+        Self {
+            name: name.into(),
+            target: Some(target.into()),
+            body: Some(body),
+            ..Self::default()
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Value>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn expect_status(mut self, status: u16) -> Self {
+        self.expect.status = Some(status);
+        self
+    }
+
+    pub fn expect_json(mut self, pointer: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.expect.json.insert(pointer.into(), value.into());
+        self
+    }
+
+    pub fn expect_contains(mut self, text: impl Into<String>) -> Self {
+        self.expect.contains.push(text.into());
+        self
+    }
+
+    pub fn capture(mut self, var: impl Into<String>, pointer: impl Into<String>) -> Self {
+        self.capture.insert(var.into(), pointer.into());
+        self
+    }
+
+    pub fn within(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    async fn run(&self, router: &axum::Router, vars: &mut BTreeMap<String, String>) -> Result<(), ScenarioError> {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(self.timeout_ms.unwrap_or(0));
+        loop {
+            let (status, body) = self.send(router, vars).await?;
+            let outcome = self.check(status, &body);
+            match outcome {
+                Ok(json) => {
+                    for (var, pointer) in &self.capture {
+                        let value = json.as_ref().and_then(|json| json.pointer(pointer))
+                            .ok_or_else(|| self.fail(format!("nothing at {} to capture as {}", pointer, var)))?;
+                        vars.insert(var.clone(), as_text(value));
+                    }
+                    return Ok(());
+                }
+                Err(_) if tokio::time::Instant::now() < deadline => tokio::time::sleep(RETRY_INTERVAL).await,
+                Err(reason) => return Err(self.fail(reason)),
+            }
+        }
+    }
+
+    async fn send(&self, router: &axum::Router, vars: &BTreeMap<String, String>) -> Result<(u16, String), ScenarioError> {
+        let (method, path) = match &self.target {
+            Some(_) => ("POST".to_string(), "/".to_string()),
+            None => (
+                self.method.clone().unwrap_or_else(|| "GET".into()),
+                substitute(self.path.as_deref().unwrap_or("/"), vars),
+            ),
+        };
+        let mut request = Request::builder().method(method.as_str()).uri(path);
+        if let Some(target) = &self.target {
+            request = request.header("x-amz-target", target.as_str());
+            if !self.headers.keys().any(|name| name.eq_ignore_ascii_case("content-type")) {
+                request = request.header("content-type", JSON_PROTOCOL_CONTENT_TYPE);
+            }
+        }
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), substitute(value, vars));
+        }
+        let body = match &self.body {
+            None => Body::empty(),
+            Some(Value::String(text)) => Body::from(substitute(text, vars)),
+            Some(json) => Body::from(substitute_json(json, vars).to_string()),
+        };
+        let request = request.body(body).map_err(|e| self.fail(e.to_string()))?;
+
+        let response = router.clone().oneshot(request).await.map_err(|e| self.fail(e.to_string()))?;
+        let status = response.status().as_u16();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await
+            .map_err(|e| self.fail(e.to_string()))?;
+        Ok((status, String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// The response parsed as JSON when it is JSON, or why it does not meet the expectations
+    fn check(&self, status: u16, body: &str) -> Result<Option<Value>, String> {
+        if let Some(expected) = self.expect.status {
+            if status != expected {
+                return Err(format!("expected status {}, got {}: {}", expected, status, body));
+            }
+        }
+        for text in &self.expect.contains {
+            if !body.contains(text.as_str()) {
+                return Err(format!("response does not contain '{}': {}", text, body));
+            }
+        }
+        let json: Option<Value> = serde_json::from_str(body).ok();
+        for (pointer, expected) in &self.expect.json {
+            let actual = json.as_ref().and_then(|json| json.pointer(pointer));
+            if actual != Some(expected) {
+                return Err(format!("expected {} at {}, got {}", expected, pointer, actual.unwrap_or(&Value::Null)));
+            }
+        }
+        Ok(json)
+    }
+
+    fn fail(&self, reason: String) -> ScenarioError {
+        ScenarioError::Step { step: self.name.clone(), reason }
+    }
+}
+
+/// Captured strings are stored without their JSON quotes
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace every `${name}` with its variable; unknown names are left as they are
+fn substitute(text: &str, vars: &BTreeMap<String, String>) -> String {
+    vars.iter().fold(text.to_string(), |text, (name, value)| text.replace(&format!("${{{}}}", name), value))
+}
+
+fn substitute_json(value: &Value, vars: &BTreeMap<String, String>) -> Value {
+    match value {
+        Value::String(s) => Value::String(substitute(s, vars)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| substitute_json(v, vars)).collect()),
+        Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (k.clone(), substitute_json(v, vars))).collect()),
+        other => other.clone(),
+    }
+}
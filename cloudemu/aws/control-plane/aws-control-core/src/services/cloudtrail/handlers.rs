@@ -0,0 +1,104 @@
+use crate::Emulator;
+use crate::error::EmulatorError;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::info;
+
+/// LookupEvents returns at most this many events per call
+const MAX_LOOKUP_RESULTS: u64 = 50;
+
+pub async fn handle_request(
+    State(emulator): State<Arc<Emulator>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let target = headers
+        .get("x-amz-target")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    info!("CloudTrail: {}", target);
+    let action = target.split('.').next_back().unwrap_or(target);
+
+    let result = match action {
+        "LookupEvents" => lookup_events(&emulator, body),
+        _ => Err(EmulatorError::InvalidRequest(format!("Unknown or unsupported target: {}", target))),
+    };
+
+    match result {
+        Ok(json_val) => Json::<Value>(json_val).into_response(),
+        Err(e) => {
+            let json_err = json!({
+                "__type": e.code(),
+                "message": e.message()
+            });
+
+            (e.status_code(), Json::<Value>(json_err)).into_response()
+        }
+    }
+}
+
+/// Convert an epoch-seconds timestamp to the RFC 3339 form events are stored with
+fn epoch_to_rfc3339(value: &Value) -> Result<Option<String>, EmulatorError> {
+    let Some(secs) = value.as_f64() else {
+        return Ok(None);
+    };
+    let time = DateTime::from_timestamp_millis((secs * 1000.0) as i64)
+        .ok_or_else(|| EmulatorError::InvalidArgument(format!("Invalid timestamp: {}", secs)))?;
+    Ok(Some(time.to_rfc3339_opts(SecondsFormat::Millis, true)))
+}
+
+fn lookup_events(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let attributes = body["LookupAttributes"].as_array().cloned().unwrap_or_default();
+    if attributes.len() > 1 {
+        return Err(EmulatorError::InvalidArgument("Only one lookup attribute is supported".into()));
+    }
+    let attribute = match attributes.first() {
+        Some(attr) => Some((
+            attr["AttributeKey"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing AttributeKey".into()))?,
+            attr["AttributeValue"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing AttributeValue".into()))?,
+        )),
+        None => None,
+    };
+    let start_time = epoch_to_rfc3339(&body["StartTime"])?;
+    let end_time = epoch_to_rfc3339(&body["EndTime"])?;
+    let max_results = body["MaxResults"].as_u64().unwrap_or(MAX_LOOKUP_RESULTS).clamp(1, MAX_LOOKUP_RESULTS);
+
+    let events = emulator.storage.lookup_cloudtrail_events(
+        attribute,
+        start_time.as_deref(),
+        end_time.as_deref(),
+        max_results as usize,
+    )?;
+
+    let events: Vec<Value> = events.into_iter().map(|e| {
+        let event_time = DateTime::parse_from_rfc3339(&e.event_time)
+            .map(|t| t.timestamp_millis() as f64 / 1000.0)
+            .unwrap_or_default();
+        let resources: Vec<Value> = e.resource_name.iter().map(|name| json!({
+            "ResourceType": e.resource_type,
+            "ResourceName": name
+        })).collect();
+        json!({
+            "EventId": e.event_id,
+            "EventName": e.event_name,
+            "ReadOnly": "false",
+            "EventTime": event_time,
+            "EventSource": e.event_source,
+            "Username": e.username,
+            "Resources": resources,
+            "CloudTrailEvent": e.cloud_trail_event
+        })
+    }).collect();
+
+    Ok(json!({
+        "Events": events
+    }))
+}
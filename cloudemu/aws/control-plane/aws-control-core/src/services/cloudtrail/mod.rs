@@ -0,0 +1,7 @@
+pub mod service;
+pub mod handlers;
+
+pub use service::CloudTrailService;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,60 @@
+use crate::event_bus::{EventSubscriber, ResourceEvent};
+use aws_data_core::storage::{CloudTrailEvent, StorageEngine};
+use aws_data_core::Config;
+use serde_json::json;
+use tracing::warn;
+
+/// CloudTrail records every resource lifecycle event published on the internal bus
+/// as a management event, queryable through LookupEvents.
+#[derive(Clone)]
+pub struct CloudTrailService {
+    storage: StorageEngine,
+    config: Config,
+}
+
+impl CloudTrailService {
+    pub fn new(storage: StorageEngine, config: Config) -> Self {
+        Self { storage, config }
+    }
+}
+
+impl EventSubscriber for CloudTrailService {
+    fn on_event(&self, event: &ResourceEvent) {
+        let account_id = &self.config.account_id;
+        let event_source = format!("{}.amazonaws.com", event.service);
+        let record = json!({
+            "eventVersion": "1.08",
+            "userIdentity": {
+                "type": "Root",
+                "principalId": account_id,
+                "arn": format!("arn:aws:iam::{}:root", account_id),
+                "accountId": account_id,
+            },
+            "eventTime": event.time,
+            "eventSource": event_source,
+            "eventName": event.operation,
+            "awsRegion": self.config.region,
+            "sourceIPAddress": "127.0.0.1",
+            "requestParameters": event.detail,
+            "eventID": event.id,
+            "readOnly": false,
+            "eventType": "AwsApiCall",
+            "managementEvent": true,
+            "recipientAccountId": account_id,
+        });
+
+        let recorded = self.storage.record_cloudtrail_event(&CloudTrailEvent {
+            event_id: event.id.clone(),
+            event_name: event.operation.clone(),
+            event_source,
+            event_time: event.time.clone(),
+            username: "root".to_string(),
+            resource_type: None,
+            resource_name: event.resource.clone(),
+            cloud_trail_event: record.to_string(),
+        });
+        if let Err(e) = recorded {
+            warn!("CloudTrail: Failed to record {} event: {}", event.operation, e);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn call(target: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", target)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+async fn read_json(response: axum::response::Response) -> Value {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn test_cloudtrail_records_lifecycle_events() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let response = app.clone().oneshot(call("AmazonSQS.CreateQueue", json!({ "QueueName": "orders" }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // Reads are not recorded
+    app.clone().oneshot(call("AmazonSQS.ListQueues", json!({}))).await.unwrap();
+
+    let lookup = json!({
+        "LookupAttributes": [{ "AttributeKey": "EventSource", "AttributeValue": "sqs.amazonaws.com" }]
+    });
+    let response = app.clone()
+        .oneshot(call("com.amazonaws.cloudtrail.v20131101.CloudTrail_20131101.LookupEvents", lookup))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = read_json(response).await;
+    let events = body["Events"].as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["EventName"], "CreateQueue");
+    assert_eq!(events[0]["Resources"][0]["ResourceName"], "orders");
+    let record: Value = serde_json::from_str(events[0]["CloudTrailEvent"].as_str().unwrap()).unwrap();
+    assert_eq!(record["requestParameters"]["QueueName"], "orders");
+
+    // The inspector sees the same events
+    let request = Request::builder().uri("/_aws/events?limit=5").body(Body::empty()).unwrap();
+    let recent = read_json(app.clone().oneshot(request).await.unwrap()).await;
+    assert_eq!(recent[0]["service"], "sqs");
+    assert_eq!(recent[0]["operation"], "CreateQueue");
+    assert_eq!(recent[0]["action"], "Created");
+}
+
+#[tokio::test]
+async fn test_lifecycle_events_reach_eventbridge_rules() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    emulator.storage.create_queue("audit", "000000000000", "us-east-1").unwrap();
+    emulator.storage.create_event_bus("default", "000000000000", "us-east-1").unwrap();
+
+    let rule = json!({
+        "Name": "queue-changes",
+        "EventPattern": json!({ "source": ["aws.sqs"], "detail-type": ["AWS API Call via CloudTrail"] }).to_string()
+    });
+    app.clone().oneshot(call("AWSEvents.PutRule", rule)).await.unwrap();
+    let targets = json!({
+        "Rule": "queue-changes",
+        "Targets": [{ "Id": "audit", "Arn": "arn:aws:sqs:us-east-1:000000000000:audit" }]
+    });
+    app.clone().oneshot(call("AWSEvents.PutTargets", targets)).await.unwrap();
+
+    app.clone().oneshot(call("AmazonSQS.CreateQueue", json!({ "QueueName": "orders" }))).await.unwrap();
+
+    let messages = emulator.storage.receive_message("audit", 10).unwrap();
+    assert_eq!(messages.len(), 1);
+    let event: Value = serde_json::from_str(&messages[0].body).unwrap();
+    assert_eq!(event["source"], "aws.sqs");
+    assert_eq!(event["detail"]["eventName"], "CreateQueue");
+    assert_eq!(event["resources"][0], "orders");
+}
@@ -0,0 +1,205 @@
+//! DAX-style read-through cache in front of DynamoDB
+//!
+//! `POST /_aws/dax` is the cluster endpoint: it serves the DynamoDB API with reads answered
+//! from two caches, as DAX does. The item cache holds `GetItem` results, misses included, by
+//! key; the query cache holds `Query` and `Scan` results by request. `PutItem` through the
+//! endpoint writes through to the item cache but leaves the query cache alone, and writes to
+//! the regular endpoint bypass both, so reads see stale data until their entries expire.
+//! Strongly consistent reads go straight to the table.
+//!
+//! `GET /_aws/dax/cache` returns the settings and hit counts, `PUT` changes the settings it is
+//! given and `DELETE` flushes both caches. With caching disabled the endpoint passes every
+//! request through, so the cache can be toggled without changing clients.
+
+use crate::Emulator;
+use crate::error::{ApiError, EmulatorError};
+use axum::{extract::State, response::{IntoResponse, Response}, Json};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Cache behaviour of the DAX endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaxSettings {
+    pub enabled: bool,
+    pub item_ttl_ms: u64,
+    pub query_ttl_ms: u64,
+}
+
+impl Default for DaxSettings {
+    /// Disabled, with the 5 minute TTLs of a new DAX cluster
+    fn default() -> Self {
+        Self { enabled: false, item_ttl_ms: 300_000, query_ttl_ms: 300_000 }
+    }
+}
+
+/// Reads answered from each cache and reads that went to the table
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub item_hits: u64,
+    pub item_misses: u64,
+    pub query_hits: u64,
+    pub query_misses: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheKind {
+    /// `GetItem` by key
+    Item,
+    /// `Query` and `Scan` by request
+    Query,
+}
+
+struct Entry {
+    response: Value,
+    expires_at: Instant,
+}
+
+/// Item and query caches of the DAX endpoint, keyed by table and item key or request
+#[derive(Default)]
+pub struct DaxCache {
+    settings: RwLock<DaxSettings>,
+    items: Mutex<HashMap<(String, String), Entry>>,
+    queries: Mutex<HashMap<(String, String), Entry>>,
+    stats: Mutex<CacheStats>,
+}
+
+impl DaxCache {
+    /// A cache enabled with both TTLs set to `ttl_ms`, or disabled without one
+    pub fn new(ttl_ms: Option<u64>) -> Self {
+        let settings = match ttl_ms {
+            Some(ttl_ms) => DaxSettings { enabled: true, item_ttl_ms: ttl_ms, query_ttl_ms: ttl_ms },
+            None => DaxSettings::default(),
+        };
+        Self { settings: RwLock::new(settings), ..Self::default() }
+    }
+
+    pub fn settings(&self) -> DaxSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.settings.read().unwrap().enabled
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    /// Change the settings named in `changes`, a JSON object of [`DaxSettings`] fields. Cached
+    /// entries are flushed so the new settings apply to every read.
+    pub fn update(&self, changes: &Value) -> Result<DaxSettings, EmulatorError> {
+        let changes = changes.as_object()
+            .ok_or_else(|| EmulatorError::InvalidArgument("DAX settings must be a JSON object".into()))?;
+        let mut settings = self.settings.write().unwrap();
+        let mut merged = serde_json::to_value(&*settings)?;
+        for (name, value) in changes {
+            merged[name] = value.clone();
+        }
+        *settings = serde_json::from_value(merged)
+            .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid DAX settings: {}", e)))?;
+        self.flush();
+        Ok(settings.clone())
+    }
+
+    /// Drop every cached entry and reset the counts
+    pub fn flush(&self) {
+        self.items.lock().unwrap().clear();
+        self.queries.lock().unwrap().clear();
+        *self.stats.lock().unwrap() = CacheStats::default();
+    }
+
+    /// The cached response, counting the read as a hit or a miss
+    pub fn lookup(&self, kind: CacheKind, table: &str, key: &str) -> Option<Value> {
+        let mut cache = self.cache(kind).lock().unwrap();
+        let id = (table.to_string(), key.to_string());
+        let response = match cache.get(&id) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.response.clone()),
+            Some(_) => {
+                cache.remove(&id);
+                None
+            }
+            None => None,
+        };
+        let mut stats = self.stats.lock().unwrap();
+        match (kind, response.is_some()) {
+            (CacheKind::Item, true) => stats.item_hits += 1,
+            (CacheKind::Item, false) => stats.item_misses += 1,
+            (CacheKind::Query, true) => stats.query_hits += 1,
+            (CacheKind::Query, false) => stats.query_misses += 1,
+        }
+        response
+    }
+
+    /// Cache a response for the TTL of its cache
+    pub fn store(&self, kind: CacheKind, table: &str, key: String, response: Value) {
+        let settings = self.settings();
+        let ttl = match kind {
+            CacheKind::Item => settings.item_ttl_ms,
+            CacheKind::Query => settings.query_ttl_ms,
+        };
+        let entry = Entry { response, expires_at: Instant::now() + Duration::from_millis(ttl) };
+        self.cache(kind).lock().unwrap().insert((table.to_string(), key), entry);
+    }
+
+    fn cache(&self, kind: CacheKind) -> &Mutex<HashMap<(String, String), Entry>> {
+        match kind {
+            CacheKind::Item => &self.items,
+            CacheKind::Query => &self.queries,
+        }
+    }
+}
+
+/// `GET /_aws/dax/cache`
+pub async fn get_cache(State(emulator): State<Arc<Emulator>>) -> Response {
+    let cache = &emulator.dynamodb.cache;
+    Json(json!({ "settings": cache.settings(), "stats": cache.stats() })).into_response()
+}
+
+/// `PUT /_aws/dax/cache`
+pub async fn update_cache(State(emulator): State<Arc<Emulator>>, Json(changes): Json<Value>) -> Response {
+    match emulator.dynamodb.cache.update(&changes) {
+        Ok(settings) => Json(settings).into_response(),
+        Err(e) => ApiError(e).into_response(),
+    }
+}
+
+/// `DELETE /_aws/dax/cache`
+pub async fn flush_cache(State(emulator): State<Arc<Emulator>>) -> Response {
+    emulator.dynamodb.cache.flush();
+    Json(json!({})).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_expire_and_count() {
+        let cache = DaxCache::new(Some(30));
+        assert!(cache.lookup(CacheKind::Item, "users", "u1").is_none());
+        cache.store(CacheKind::Item, "users", "u1".into(), json!({ "Item": { "id": { "S": "u1" } } }));
+        assert!(cache.lookup(CacheKind::Item, "users", "u1").is_some());
+        assert!(cache.lookup(CacheKind::Query, "users", "u1").is_none());
+        assert!(cache.lookup(CacheKind::Item, "orders", "u1").is_none());
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(cache.lookup(CacheKind::Item, "users", "u1").is_none());
+        assert_eq!(cache.stats(), CacheStats { item_hits: 1, item_misses: 3, query_hits: 0, query_misses: 1 });
+    }
+
+    #[test]
+    fn test_update_merges_and_flushes() {
+        let cache = DaxCache::new(None);
+        assert!(!cache.enabled());
+        cache.store(CacheKind::Query, "users", "scan".into(), json!({ "Items": [] }));
+
+        let settings = cache.update(&json!({ "enabled": true, "query_ttl_ms": 1000 })).unwrap();
+        assert_eq!(settings, DaxSettings { enabled: true, item_ttl_ms: 300_000, query_ttl_ms: 1000 });
+        assert!(cache.lookup(CacheKind::Query, "users", "scan").is_none());
+        assert!(cache.update(&json!({ "ttl": 5 })).is_err());
+    }
+}
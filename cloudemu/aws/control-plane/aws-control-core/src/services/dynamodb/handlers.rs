@@ -1,5 +1,7 @@
 use crate::Emulator;
 use crate::error::EmulatorError;
+use super::cache::CacheKind;
+use aws_data_core::storage::TableStream;
 use axum::{
     extract::State,
     http::HeaderMap,
@@ -21,17 +23,90 @@ pub async fn handle_request(
     
     let action = target.split('.').next_back().unwrap_or("");
 
-    let result = match action {
-        "CreateTable" => create_table(&emulator, body).await,
-        "PutItem" => put_item(&emulator, body).await,
-        "GetItem" => get_item(&emulator, body).await,
-        "Query" => query(&emulator, body).await,
-        "Scan" => scan(&emulator, body).await,
-        "DescribeTable" => describe_table(&emulator, body).await,
-        "ListTables" => list_tables(&emulator, body).await,
+    respond(dispatch(&emulator, action, body).await)
+}
+
+/// DAX cluster endpoint (`POST /_aws/dax`): the DynamoDB API with reads served from
+/// [`super::cache::DaxCache`]
+pub async fn handle_dax_request(
+    State(emulator): State<Arc<Emulator>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let target = headers
+        .get("x-amz-target")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    let action = target.split('.').next_back().unwrap_or("");
+
+    respond(dax(&emulator, action, body).await)
+}
+
+async fn dispatch(emulator: &Emulator, action: &str, body: Value) -> Result<Value, EmulatorError> {
+    match action {
+        "CreateTable" => create_table(emulator, body).await,
+        "PutItem" => put_item(emulator, body).await,
+        "GetItem" => get_item(emulator, body).await,
+        "Query" => query(emulator, body).await,
+        "Scan" => scan(emulator, body).await,
+        "DescribeTable" => describe_table(emulator, body).await,
+        "ListTables" => list_tables(emulator, body).await,
+        "UpdateContinuousBackups" => update_continuous_backups(emulator, body).await,
+        "DescribeContinuousBackups" => describe_continuous_backups(emulator, body).await,
+        "RestoreTableToPointInTime" => restore_table_to_point_in_time(emulator, body).await,
         _ => Err(EmulatorError::InvalidRequest(format!("Unsupported DynamoDB action: {}", action))),
+    }
+}
+
+/// Eventually consistent reads are answered from the cache when it is enabled; `PutItem`
+/// writes the new item through to the item cache
+async fn dax(emulator: &Emulator, action: &str, body: Value) -> Result<Value, EmulatorError> {
+    let cache = &emulator.dynamodb.cache;
+    let kind = match action {
+        "GetItem" => CacheKind::Item,
+        "Query" | "Scan" => CacheKind::Query,
+        "PutItem" if cache.enabled() => {
+            let response = put_item(emulator, body.clone()).await?;
+            let table_name = body["TableName"].as_str().unwrap_or_default();
+            let key = item_key(emulator, table_name, &body["Item"])?;
+            cache.store(CacheKind::Item, table_name, key, json!({ "Item": body["Item"] }));
+            return Ok(response);
+        }
+        _ => return dispatch(emulator, action, body).await,
     };
+    if !cache.enabled() || body["ConsistentRead"].as_bool() == Some(true) {
+        return dispatch(emulator, action, body).await;
+    }
 
+    let table_name = body["TableName"].as_str()
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing TableName".into()))?
+        .to_string();
+    // Maps serialize with sorted keys, so equal keys and requests give equal cache keys
+    let key = match kind {
+        CacheKind::Item => body["Key"].to_string(),
+        CacheKind::Query => format!("{}:{}", action, body),
+    };
+    if let Some(response) = cache.lookup(kind, &table_name, &key) {
+        return Ok(response);
+    }
+    let response = dispatch(emulator, action, body).await?;
+    cache.store(kind, &table_name, key, response.clone());
+    Ok(response)
+}
+
+/// Key attributes of an item, in the form `GetItem` takes them
+fn item_key(emulator: &Emulator, table_name: &str, item: &Value) -> Result<String, EmulatorError> {
+    let table = emulator.storage.get_table(table_name)?;
+    let key_schema: Vec<Value> = serde_json::from_str(&table.key_schema).unwrap_or_default();
+    let key: serde_json::Map<String, Value> = key_schema.iter()
+        .filter_map(|k| k["AttributeName"].as_str())
+        .filter_map(|name| Some((name.to_string(), item.get(name)?.clone())))
+        .collect();
+    Ok(Value::Object(key).to_string())
+}
+
+fn respond(result: Result<Value, EmulatorError>) -> Response {
     match result {
         Ok(json_val) => Json::<Value>(json_val).into_response(),
         Err(e) => {
@@ -50,6 +125,17 @@ async fn create_table(emulator: &Emulator, body: Value) -> Result<Value, Emulato
     let attr_defs = serde_json::to_string(&body["AttributeDefinitions"]).unwrap_or_default();
     let key_schema = serde_json::to_string(&body["KeySchema"]).unwrap_or_default();
     
+    // Validated before the table is created so a bad specification creates nothing
+    let stream_view_type = match &body["StreamSpecification"] {
+        spec if spec["StreamEnabled"].as_bool() == Some(true) => match spec["StreamViewType"].as_str() {
+            Some(view_type @ ("KEYS_ONLY" | "NEW_IMAGE" | "OLD_IMAGE" | "NEW_AND_OLD_IMAGES")) => Some(view_type),
+            other => return Err(EmulatorError::InvalidArgument(
+                format!("Invalid StreamSpecification.StreamViewType: {}", other.unwrap_or(""))
+            )),
+        },
+        _ => None,
+    };
+    
     let table = emulator.storage.create_table(
         name,
         &attr_defs,
@@ -57,22 +143,38 @@ async fn create_table(emulator: &Emulator, body: Value) -> Result<Value, Emulato
         &emulator.config.account_id,
         &emulator.config.region
     )?;
+    let stream = match stream_view_type {
+        Some(view_type) => Some(emulator.storage.enable_table_stream(name, view_type)?),
+        None => None,
+    };
 
-    Ok(json!({
-        "TableDescription": {
-            "TableName": table.name,
-            "TableArn": table.arn,
-            "TableStatus": table.status,
-            "CreationDateTime": 1234567890.0,
-            "ItemCount": 0,
-            "TableSizeBytes": 0
-        }
-    }))
+    let mut description = json!({
+        "TableName": table.name,
+        "TableArn": table.arn,
+        "TableStatus": table.status,
+        "CreationDateTime": 1234567890.0,
+        "ItemCount": 0,
+        "TableSizeBytes": 0
+    });
+    add_stream_description(&mut description, stream.as_ref());
+    Ok(json!({ "TableDescription": description }))
+}
+
+fn add_stream_description(description: &mut Value, stream: Option<&TableStream>) {
+    if let Some(stream) = stream {
+        description["StreamSpecification"] = json!({
+            "StreamEnabled": true,
+            "StreamViewType": stream.view_type
+        });
+        description["LatestStreamArn"] = json!(stream.stream_arn);
+        description["LatestStreamLabel"] = json!(stream.stream_arn.rsplit('/').next());
+    }
 }
 
 async fn put_item(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
     let table_name = body["TableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TableName".into()))?;
     let item = &body["Item"];
+    emulator.limits.check_item_size(item)?;
     
     // Retrieve table metadata to find PK name
     let table = emulator.storage.get_table(table_name)?;
@@ -150,15 +252,15 @@ async fn get_item(emulator: &Emulator, body: Value) -> Result<Value, EmulatorErr
     }
 }
 
-async fn describe_table(_emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+async fn describe_table(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
      let name = body["TableName"].as_str().unwrap_or("unknown");
-     Ok(json!({
-        "Table": {
-            "TableName": name,
-            "TableStatus": "ACTIVE",
-            "CreationDateTime": 1234567890.0
-        }
-     }))
+     let mut table = json!({
+        "TableName": name,
+        "TableStatus": "ACTIVE",
+        "CreationDateTime": 1234567890.0
+     });
+     add_stream_description(&mut table, emulator.storage.get_table_stream(name)?.as_ref());
+     Ok(json!({ "Table": table }))
 }
 
 async fn list_tables(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
@@ -169,6 +271,74 @@ async fn list_tables(emulator: &Emulator, _body: Value) -> Result<Value, Emulato
     }))
 }
 
+async fn update_continuous_backups(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let table_name = body["TableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TableName".into()))?;
+    let enabled = body["PointInTimeRecoverySpecification"]["PointInTimeRecoveryEnabled"]
+        .as_bool()
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing PointInTimeRecoverySpecification.PointInTimeRecoveryEnabled".into()))?;
+
+    let enabled_at = emulator.storage.update_point_in_time_recovery(table_name, enabled)?;
+    Ok(json!({ "ContinuousBackupsDescription": continuous_backups_description(enabled_at) }))
+}
+
+async fn describe_continuous_backups(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let table_name = body["TableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TableName".into()))?;
+    let enabled_at = emulator.storage.get_point_in_time_recovery(table_name)?;
+    Ok(json!({ "ContinuousBackupsDescription": continuous_backups_description(enabled_at) }))
+}
+
+async fn restore_table_to_point_in_time(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let source = body["SourceTableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing SourceTableName".into()))?;
+    let target = body["TargetTableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TargetTableName".into()))?;
+
+    // RestoreDateTime is epoch seconds; UseLatestRestorableTime restores to now
+    let restore_at = if body["UseLatestRestorableTime"].as_bool().unwrap_or(false) {
+        chrono::Utc::now().timestamp_millis()
+    } else {
+        let seconds = body["RestoreDateTime"].as_f64()
+            .ok_or_else(|| EmulatorError::InvalidArgument("Either RestoreDateTime or UseLatestRestorableTime must be specified".into()))?;
+        (seconds * 1000.0) as i64
+    };
+
+    let table = emulator.storage.restore_table_to_point_in_time(
+        source,
+        target,
+        restore_at,
+        &emulator.config.account_id,
+        &emulator.config.region
+    )?;
+    let item_count = emulator.storage.scan_items(target)?.len();
+
+    Ok(json!({
+        "TableDescription": {
+            "TableName": table.name,
+            "TableArn": table.arn,
+            "TableStatus": table.status,
+            "ItemCount": item_count,
+            "RestoreSummary": {
+                "SourceTableArn": emulator.storage.get_table(source)?.arn,
+                "RestoreDateTime": restore_at as f64 / 1000.0,
+                "RestoreInProgress": false
+            }
+        }
+    }))
+}
+
+fn continuous_backups_description(enabled_at: Option<i64>) -> Value {
+    let pitr = match enabled_at {
+        Some(enabled_at) => json!({
+            "PointInTimeRecoveryStatus": "ENABLED",
+            "EarliestRestorableDateTime": enabled_at as f64 / 1000.0,
+            "LatestRestorableDateTime": chrono::Utc::now().timestamp_millis() as f64 / 1000.0
+        }),
+        None => json!({ "PointInTimeRecoveryStatus": "DISABLED" }),
+    };
+    json!({
+        "ContinuousBackupsStatus": "ENABLED",
+        "PointInTimeRecoveryDescription": pitr
+    })
+}
+
 async fn query(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
     let table_name = body["TableName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TableName".into()))?;
     
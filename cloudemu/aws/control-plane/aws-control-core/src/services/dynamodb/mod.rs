@@ -1,5 +1,6 @@
 pub mod service;
 pub mod handlers;
+pub mod cache;
 
 pub use service::DynamoDbService;
 
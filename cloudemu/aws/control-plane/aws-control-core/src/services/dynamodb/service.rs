@@ -1,11 +1,15 @@
+use super::cache::DaxCache;
 use aws_data_core::storage::StorageEngine;
+use aws_data_core::Config;
 
 pub struct DynamoDbService {
     _storage: StorageEngine,
+    /// Read-through cache of the DAX endpoint
+    pub cache: DaxCache,
 }
 
 impl DynamoDbService {
-    pub fn new(storage: StorageEngine) -> Self {
-        Self { _storage: storage }
+    pub fn new(storage: StorageEngine, config: &Config) -> Self {
+        Self { _storage: storage, cache: DaxCache::new(config.dax_ttl_ms) }
     }
 }
@@ -76,3 +76,146 @@ async fn test_dynamodb_json_api() {
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
     assert_eq!(json["Item"]["Name"]["S"], "Alice");
 }
+
+#[tokio::test]
+async fn test_dynamodb_point_in_time_recovery() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let call = |action: &str, body: serde_json::Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("DynamoDB_20120810.{}", action))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+    };
+
+    app.clone().oneshot(call("CreateTable", json!({
+        "TableName": "Orders",
+        "KeySchema": [{"AttributeName": "OrderId", "KeyType": "HASH"}],
+        "AttributeDefinitions": [{"AttributeName": "OrderId", "AttributeType": "S"}]
+    }))).await.unwrap();
+
+    let response = app.clone().oneshot(call("DescribeContinuousBackups", json!({"TableName": "Orders"}))).await.unwrap();
+    assert_eq!(read_json(response).await["ContinuousBackupsDescription"]["PointInTimeRecoveryDescription"]["PointInTimeRecoveryStatus"], "DISABLED");
+
+    let response = app.clone().oneshot(call("UpdateContinuousBackups", json!({
+        "TableName": "Orders",
+        "PointInTimeRecoverySpecification": {"PointInTimeRecoveryEnabled": true}
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(read_json(response).await["ContinuousBackupsDescription"]["PointInTimeRecoveryDescription"]["PointInTimeRecoveryStatus"], "ENABLED");
+
+    app.clone().oneshot(call("PutItem", json!({
+        "TableName": "Orders",
+        "Item": {"OrderId": {"S": "o1"}, "Status": {"S": "PENDING"}}
+    }))).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let checkpoint = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    app.clone().oneshot(call("PutItem", json!({
+        "TableName": "Orders",
+        "Item": {"OrderId": {"S": "o1"}, "Status": {"S": "SHIPPED"}}
+    }))).await.unwrap();
+
+    let response = app.clone().oneshot(call("RestoreTableToPointInTime", json!({
+        "SourceTableName": "Orders",
+        "TargetTableName": "OrdersRestored",
+        "RestoreDateTime": checkpoint
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let restored = read_json(response).await;
+    assert_eq!(restored["TableDescription"]["TableName"], "OrdersRestored");
+    assert_eq!(restored["TableDescription"]["ItemCount"], 1);
+
+    let response = app.clone().oneshot(call("GetItem", json!({
+        "TableName": "OrdersRestored",
+        "Key": {"OrderId": {"S": "o1"}}
+    }))).await.unwrap();
+    assert_eq!(read_json(response).await["Item"]["Status"]["S"], "PENDING");
+
+    // Timestamps before PITR was enabled are outside the restore window
+    let response = app.clone().oneshot(call("RestoreTableToPointInTime", json!({
+        "SourceTableName": "Orders",
+        "TargetTableName": "TooEarly",
+        "RestoreDateTime": 1.0
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_dax_endpoint_caches_reads() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+
+    let call = |uri: &str, action: &str, body: serde_json::Value| {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("x-amz-target", format!("DynamoDB_20120810.{}", action))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+    };
+    let put = |uri: &str, status: &str| call(uri, "PutItem", json!({
+        "TableName": "Orders",
+        "Item": {"OrderId": {"S": "o1"}, "Status": {"S": status}}
+    }));
+    let get = |consistent: bool| call("/_aws/dax", "GetItem", json!({
+        "TableName": "Orders",
+        "Key": {"OrderId": {"S": "o1"}},
+        "ConsistentRead": consistent
+    }));
+    let scan = || call("/_aws/dax", "Scan", json!({"TableName": "Orders"}));
+
+    app.clone().oneshot(call("/", "CreateTable", json!({
+        "TableName": "Orders",
+        "KeySchema": [{"AttributeName": "OrderId", "KeyType": "HASH"}],
+        "AttributeDefinitions": [{"AttributeName": "OrderId", "AttributeType": "S"}]
+    }))).await.unwrap();
+    app.clone().oneshot(put("/", "PENDING")).await.unwrap();
+
+    // Disabled, the endpoint reads the table every time
+    app.clone().oneshot(put("/", "PACKED")).await.unwrap();
+    assert_eq!(read_json(app.clone().oneshot(get(false)).await.unwrap()).await["Item"]["Status"]["S"], "PACKED");
+
+    let settings = Request::builder()
+        .method("PUT")
+        .uri("/_aws/dax/cache")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({"enabled": true, "item_ttl_ms": 60_000}).to_string()))
+        .unwrap();
+    assert_eq!(app.clone().oneshot(settings).await.unwrap().status(), StatusCode::OK);
+
+    // A write to the table behind the cache's back leaves the cached item stale
+    assert_eq!(read_json(app.clone().oneshot(get(false)).await.unwrap()).await["Item"]["Status"]["S"], "PACKED");
+    assert_eq!(read_json(app.clone().oneshot(scan()).await.unwrap()).await["Items"][0]["Status"]["S"], "PACKED");
+    app.clone().oneshot(put("/", "SHIPPED")).await.unwrap();
+    assert_eq!(read_json(app.clone().oneshot(get(false)).await.unwrap()).await["Item"]["Status"]["S"], "PACKED");
+    assert_eq!(read_json(app.clone().oneshot(get(true)).await.unwrap()).await["Item"]["Status"]["S"], "SHIPPED");
+
+    // A write through the endpoint updates the item cache but not the query cache
+    app.clone().oneshot(put("/_aws/dax", "DELIVERED")).await.unwrap();
+    assert_eq!(read_json(app.clone().oneshot(get(false)).await.unwrap()).await["Item"]["Status"]["S"], "DELIVERED");
+    assert_eq!(read_json(app.clone().oneshot(scan()).await.unwrap()).await["Items"][0]["Status"]["S"], "PACKED");
+
+    let response = app.clone().oneshot(Request::builder().uri("/_aws/dax/cache").body(Body::empty()).unwrap()).await.unwrap();
+    let cache = read_json(response).await;
+    assert_eq!(cache["settings"]["item_ttl_ms"], 60_000);
+    assert_eq!(cache["stats"], json!({"item_hits": 2, "item_misses": 1, "query_hits": 1, "query_misses": 1}));
+
+    let flush = Request::builder().method("DELETE").uri("/_aws/dax/cache").body(Body::empty()).unwrap();
+    app.clone().oneshot(flush).await.unwrap();
+    assert_eq!(read_json(app.clone().oneshot(scan()).await.unwrap()).await["Items"][0]["Status"]["S"], "DELIVERED");
+    assert_eq!(emulator.dynamodb.cache.stats().query_misses, 1);
+}
@@ -1,5 +1,6 @@
 use crate::Emulator;
 use crate::error::EmulatorError;
+use super::service::{profile_association_id, RunInstancesRequest};
 use axum::{
     extract::State,
     http::HeaderMap,
@@ -23,10 +24,31 @@ pub async fn handle_request(
         "DescribeInstances" => describe_instances(&emulator, body).await,
         "CreateVpc" => create_vpc(&emulator, body).await,
         "DescribeVpcs" => describe_vpcs(&emulator, body).await,
+        "DeleteVpc" => delete_vpc(&emulator, body).await,
+        "DescribeVpcAttribute" => describe_vpc_attribute(&emulator, body).await,
+        "ModifyVpcAttribute" => modify_vpc_attribute(&emulator, body).await,
         "CreateSubnet" => create_subnet(&emulator, body).await,
         "DescribeSubnets" => describe_subnets(&emulator, body).await,
+        "DeleteSubnet" => delete_subnet(&emulator, body).await,
         "CreateSecurityGroup" => create_security_group(&emulator, body).await,
         "DescribeSecurityGroups" => describe_security_groups(&emulator, body).await,
+        "CreateInternetGateway" => create_internet_gateway(&emulator, body).await,
+        "AttachInternetGateway" => attach_internet_gateway(&emulator, body).await,
+        "DetachInternetGateway" => detach_internet_gateway(&emulator, body).await,
+        "DeleteInternetGateway" => delete_internet_gateway(&emulator, body).await,
+        "DescribeInternetGateways" => describe_internet_gateways(&emulator, body).await,
+        "CreateRouteTable" => create_route_table(&emulator, body).await,
+        "DeleteRouteTable" => delete_route_table(&emulator, body).await,
+        "DescribeRouteTables" => describe_route_tables(&emulator, body).await,
+        "CreateRoute" => create_route(&emulator, body).await,
+        "DeleteRoute" => delete_route(&emulator, body).await,
+        "AssociateRouteTable" => associate_route_table(&emulator, body).await,
+        "DisassociateRouteTable" => disassociate_route_table(&emulator, body).await,
+        "CreateNatGateway" => create_nat_gateway(&emulator, body).await,
+        "DeleteNatGateway" => delete_nat_gateway(&emulator, body).await,
+        "DescribeNatGateways" => describe_nat_gateways(&emulator, body).await,
+        "AssociateIamInstanceProfile" => associate_iam_instance_profile(&emulator, body).await,
+        "DisassociateIamInstanceProfile" => disassociate_iam_instance_profile(&emulator, body).await,
         "CreateKeyPair" => create_key_pair(&emulator, body).await,
         "DescribeKeyPairs" => describe_key_pairs(&emulator, body).await,
         _ => Err(EmulatorError::NotImplemented(format!("EC2 action: {}", action))),
@@ -38,26 +60,106 @@ pub async fn handle_request(
     }
 }
 
+fn required<'a>(body: &'a Value, field: &str) -> Result<&'a str, EmulatorError> {
+    body[field].as_str().ok_or_else(|| EmulatorError::InvalidArgument(format!("Missing {}", field)))
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    value.as_array()
+        .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+/// Values a filter name refers to in a serialized resource
+fn filter_values(item: &Value, name: &str) -> Vec<String> {
+    let as_string = |v: &Value| match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    };
+    let associations = || item["associations"].as_array().cloned().unwrap_or_default();
+    match name {
+        "attachment.vpc-id" => as_string(&item["vpc_id"]).into_iter().collect(),
+        "association.subnet-id" => associations().iter().filter_map(|a| as_string(&a["subnet_id"])).collect(),
+        "association.route-table-association-id" => associations().iter().filter_map(|a| as_string(&a["id"])).collect(),
+        "association.main" => associations().iter().filter_map(|a| as_string(&a["main"])).collect(),
+        "group-name" => as_string(&item["name"]).into_iter().collect(),
+        "isDefault" | "is-default" => as_string(&item["is_default"]).into_iter().collect(),
+        name => as_string(&item[name.replace(['-', '.'], "_")]).into_iter().collect(),
+    }
+}
+
+/// Apply the request's ID list (`ids_field`) and `Filters` to serialized resources.
+/// Requesting an ID that does not exist is an error, as in EC2.
+fn describe(items: Vec<Value>, body: &Value, ids_field: &str, kind: &str) -> Result<Vec<Value>, EmulatorError> {
+    let ids = string_list(&body[ids_field]);
+    if let Some(missing) = ids.iter().find(|id| !items.iter().any(|item| item["id"] == id.as_str())) {
+        return Err(EmulatorError::NotFound(kind.into(), missing.clone()));
+    }
+    let filters: Vec<(String, Vec<String>)> = body["Filters"].as_array()
+        .map(|filters| filters.iter()
+            .filter_map(|f| Some((f["Name"].as_str()?.to_string(), string_list(&f["Values"]))))
+            .collect())
+        .unwrap_or_default();
+
+    Ok(items.into_iter()
+        .filter(|item| ids.is_empty() || ids.iter().any(|id| item["id"] == id.as_str()))
+        .filter(|item| filters.iter().all(|(name, values)| {
+            filter_values(item, name).iter().any(|v| values.contains(v))
+        }))
+        .collect())
+}
+
+fn to_values<T: serde::Serialize>(items: Vec<T>) -> Result<Vec<Value>, EmulatorError> {
+    items.into_iter().map(|item| serde_json::to_value(item).map_err(EmulatorError::from)).collect()
+}
+
 async fn run_instances(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let image_id = body["ImageId"].as_str().unwrap_or("ami-mock");
-    let instance_type = body["InstanceType"].as_str().unwrap_or("t3.micro");
-    let subnet_id = body["SubnetId"].as_str();
-    
-    let instance = emulator.storage.run_instances(
-        image_id,
-        instance_type,
-        None, // vpc_id
-        subnet_id,
-        body["KeyName"].as_str()
-    )?;
-    
+    let instances = emulator.ec2.run_instances(RunInstancesRequest {
+        image_id: body["ImageId"].as_str().unwrap_or("ami-mock").to_string(),
+        instance_type: body["InstanceType"].as_str().unwrap_or("t3.micro").to_string(),
+        subnet_id: body["SubnetId"].as_str().map(str::to_string),
+        security_group_ids: string_list(&body["SecurityGroupIds"]),
+        key_name: body["KeyName"].as_str().map(str::to_string),
+        iam_instance_profile: instance_profile_param(&body["IamInstanceProfile"]),
+        count: body["MinCount"].as_u64().unwrap_or(1) as usize,
+    })?;
+
     Ok(json!({
-        "Instances": [instance]
+        "Instances": instances
     }))
 }
 
-async fn describe_instances(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
-    let instances = emulator.storage.list_instances()?;
+/// `IamInstanceProfileSpecification` names a profile by `Arn` or `Name`
+fn instance_profile_param(spec: &Value) -> Option<String> {
+    spec["Arn"].as_str().or_else(|| spec["Name"].as_str()).map(str::to_string)
+}
+
+fn profile_association_json(instance_id: &str, profile_arn: &str, state: &str) -> Value {
+    json!({
+        "AssociationId": profile_association_id(instance_id),
+        "InstanceId": instance_id,
+        "IamInstanceProfile": { "Arn": profile_arn },
+        "State": state
+    })
+}
+
+async fn associate_iam_instance_profile(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let instance_id = required(&body, "InstanceId")?;
+    let profile = instance_profile_param(&body["IamInstanceProfile"])
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing IamInstanceProfile".into()))?;
+    let profile = emulator.ec2.associate_iam_instance_profile(instance_id, &profile)?;
+    Ok(json!({ "IamInstanceProfileAssociation": profile_association_json(instance_id, &profile.arn, "associated") }))
+}
+
+async fn disassociate_iam_instance_profile(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let (instance, profile) = emulator.ec2.disassociate_iam_instance_profile(required(&body, "AssociationId")?)?;
+    Ok(json!({ "IamInstanceProfileAssociation": profile_association_json(&instance.id, &profile.arn, "disassociating") }))
+}
+
+async fn describe_instances(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let instances = describe(to_values(emulator.storage.list_instances()?)?, &body, "InstanceIds", "Instance")?;
     Ok(json!({
         "Reservations": [
             {
@@ -68,46 +170,157 @@ async fn describe_instances(emulator: &Emulator, _body: Value) -> Result<Value,
 }
 
 async fn create_vpc(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let cidr = body["CidrBlock"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing CidrBlock".into()))?;
-    let vpc = emulator.storage.create_vpc(cidr)?;
+    let cidr = required(&body, "CidrBlock")?;
+    let vpc = emulator.ec2.create_vpc(cidr)?;
     Ok(json!({ "Vpc": vpc }))
 }
 
-async fn describe_vpcs(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
-    let vpcs = emulator.storage.list_vpcs()?;
+async fn describe_vpcs(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let vpcs = describe(to_values(emulator.storage.list_vpcs()?)?, &body, "VpcIds", "Vpc")?;
     Ok(json!({ "Vpcs": vpcs }))
 }
 
+async fn delete_vpc(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.delete_vpc(required(&body, "VpcId")?)?;
+    Ok(json!({}))
+}
+
+async fn describe_vpc_attribute(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let vpc_id = required(&body, "VpcId")?;
+    let vpc = emulator.storage.get_vpc(vpc_id)?;
+    match required(&body, "Attribute")? {
+        "enableDnsSupport" => Ok(json!({ "VpcId": vpc_id, "EnableDnsSupport": { "Value": vpc.enable_dns_support } })),
+        "enableDnsHostnames" => Ok(json!({ "VpcId": vpc_id, "EnableDnsHostnames": { "Value": vpc.enable_dns_hostnames } })),
+        other => Err(EmulatorError::InvalidArgument(format!("Unsupported VPC attribute {}", other))),
+    }
+}
+
+async fn modify_vpc_attribute(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let vpc_id = required(&body, "VpcId")?;
+    let dns_support = body["EnableDnsSupport"]["Value"].as_bool();
+    let dns_hostnames = body["EnableDnsHostnames"]["Value"].as_bool();
+    emulator.storage.set_vpc_dns_attributes(vpc_id, dns_support, dns_hostnames)?;
+    Ok(json!({}))
+}
+
 async fn create_subnet(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let vpc_id = body["VpcId"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing VpcId".into()))?;
-    let cidr = body["CidrBlock"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing CidrBlock".into()))?;
+    let vpc_id = required(&body, "VpcId")?;
+    let cidr = required(&body, "CidrBlock")?;
     let az = body["AvailabilityZone"].as_str().unwrap_or("us-east-1a");
-    
-    let subnet = emulator.storage.create_subnet(vpc_id, cidr, az)?;
+
+    let subnet = emulator.ec2.create_subnet(vpc_id, cidr, az)?;
     Ok(json!({ "Subnet": subnet }))
 }
 
-async fn describe_subnets(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
-    let subnets = emulator.storage.list_subnets()?;
+async fn describe_subnets(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let subnets = describe(to_values(emulator.storage.list_subnets()?)?, &body, "SubnetIds", "Subnet")?;
     Ok(json!({ "Subnets": subnets }))
 }
 
+async fn delete_subnet(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.delete_subnet(required(&body, "SubnetId")?)?;
+    Ok(json!({}))
+}
+
 async fn create_security_group(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let vpc_id = body["VpcId"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing VpcId".into()))?;
-    let name = body["GroupName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing GroupName".into()))?;
+    let vpc_id = required(&body, "VpcId")?;
+    let name = required(&body, "GroupName")?;
     let desc = body["Description"].as_str().unwrap_or("");
-    
-    let sg = emulator.storage.create_security_group(vpc_id, name, desc)?;
+
+    let sg = emulator.ec2.create_security_group(vpc_id, name, desc)?;
     Ok(json!({ "GroupId": sg.id }))
 }
 
-async fn describe_security_groups(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
-    let sgs = emulator.storage.list_security_groups()?;
+async fn describe_security_groups(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let sgs = describe(to_values(emulator.storage.list_security_groups()?)?, &body, "GroupIds", "SecurityGroup")?;
     Ok(json!({ "SecurityGroups": sgs }))
 }
 
+async fn create_internet_gateway(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
+    let igw = emulator.ec2.create_internet_gateway()?;
+    Ok(json!({ "InternetGateway": igw }))
+}
+
+async fn attach_internet_gateway(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.attach_internet_gateway(required(&body, "InternetGatewayId")?, required(&body, "VpcId")?)?;
+    Ok(json!({}))
+}
+
+async fn detach_internet_gateway(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.detach_internet_gateway(required(&body, "InternetGatewayId")?, required(&body, "VpcId")?)?;
+    Ok(json!({}))
+}
+
+async fn delete_internet_gateway(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.delete_internet_gateway(required(&body, "InternetGatewayId")?)?;
+    Ok(json!({}))
+}
+
+async fn describe_internet_gateways(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let igws = describe(to_values(emulator.storage.list_internet_gateways()?)?, &body, "InternetGatewayIds", "InternetGateway")?;
+    Ok(json!({ "InternetGateways": igws }))
+}
+
+async fn create_route_table(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let table = emulator.ec2.create_route_table(required(&body, "VpcId")?)?;
+    Ok(json!({ "RouteTable": table }))
+}
+
+async fn delete_route_table(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.delete_route_table(required(&body, "RouteTableId")?)?;
+    Ok(json!({}))
+}
+
+async fn describe_route_tables(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let tables = describe(to_values(emulator.storage.list_route_tables()?)?, &body, "RouteTableIds", "RouteTable")?;
+    Ok(json!({ "RouteTables": tables }))
+}
+
+async fn create_route(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.create_route(
+        required(&body, "RouteTableId")?,
+        required(&body, "DestinationCidrBlock")?,
+        body["GatewayId"].as_str(),
+        body["NatGatewayId"].as_str(),
+    )?;
+    Ok(json!({ "Return": true }))
+}
+
+async fn delete_route(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.delete_route(required(&body, "RouteTableId")?, required(&body, "DestinationCidrBlock")?)?;
+    Ok(json!({}))
+}
+
+async fn associate_route_table(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let association_id = emulator.ec2.associate_route_table(required(&body, "RouteTableId")?, required(&body, "SubnetId")?)?;
+    Ok(json!({ "AssociationId": association_id }))
+}
+
+async fn disassociate_route_table(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.ec2.disassociate_route_table(required(&body, "AssociationId")?)?;
+    Ok(json!({}))
+}
+
+async fn create_nat_gateway(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let subnet_id = required(&body, "SubnetId")?;
+    let connectivity_type = body["ConnectivityType"].as_str().unwrap_or("public");
+    let nat = emulator.ec2.create_nat_gateway(subnet_id, connectivity_type)?;
+    Ok(json!({ "NatGateway": nat }))
+}
+
+async fn delete_nat_gateway(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let nat_gateway_id = required(&body, "NatGatewayId")?;
+    emulator.ec2.delete_nat_gateway(nat_gateway_id)?;
+    Ok(json!({ "NatGatewayId": nat_gateway_id }))
+}
+
+async fn describe_nat_gateways(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let nats = describe(to_values(emulator.storage.list_nat_gateways()?)?, &body, "NatGatewayIds", "NatGateway")?;
+    Ok(json!({ "NatGateways": nats }))
+}
+
 async fn create_key_pair(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let name = body["KeyName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing KeyName".into()))?;
+    let name = required(&body, "KeyName")?;
     let key = emulator.storage.create_key_pair(name)?;
     Ok(json!(key))
 }
@@ -1,4 +1,7 @@
 mod service;
 pub mod handlers;
 
-pub use service::Ec2Service;
+pub use service::{Ec2Service, RunInstancesRequest};
+
+#[cfg(test)]
+mod tests;
@@ -1,4 +1,92 @@
 use aws_data_core::StorageEngine;
+use aws_data_core::storage::{
+    IamInstanceProfile, InstanceMetadata, InternetGatewayMetadata, NatGatewayMetadata, RouteMetadata,
+    RouteTableMetadata, SecurityGroupMetadata, SubnetMetadata, VpcMetadata,
+};
+use crate::error::EmulatorError;
+use std::net::Ipv4Addr;
+
+/// Smallest and largest VPC and subnet blocks accepted by EC2
+const MIN_PREFIX: u8 = 16;
+const MAX_PREFIX: u8 = 28;
+
+/// Address range of the default VPC and its per-zone default subnets, as in a new account
+const DEFAULT_VPC_CIDR: &str = "172.31.0.0/16";
+const DEFAULT_SUBNETS: [(&str, &str); 3] = [("a", "172.31.0.0/20"), ("b", "172.31.16.0/20"), ("c", "172.31.32.0/20")];
+
+/// Addresses EC2 reserves at the start of every subnet (network, router, DNS, future use)
+const RESERVED_LEADING: u32 = 4;
+
+/// IPv4 CIDR block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: u32,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Parse `a.b.c.d/n`; the address must be the network address of the block
+    pub fn parse(s: &str) -> Result<Self, EmulatorError> {
+        let invalid = || EmulatorError::InvalidArgument(format!("Value ({}) for parameter cidrBlock is invalid. This is not a valid CIDR block.", s));
+        let (addr, prefix) = s.split_once('/').ok_or_else(invalid)?;
+        let addr: Ipv4Addr = addr.parse().map_err(|_| invalid())?;
+        let prefix: u8 = prefix.parse().map_err(|_| invalid())?;
+        if prefix > 32 {
+            return Err(invalid());
+        }
+        let cidr = Self { network: u32::from(addr), prefix };
+        if cidr.network & cidr.mask() != cidr.network {
+            return Err(invalid());
+        }
+        Ok(cidr)
+    }
+
+    fn mask(&self) -> u32 {
+        u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0)
+    }
+
+    fn last(&self) -> u32 {
+        self.network | !self.mask()
+    }
+
+    pub fn contains(&self, other: &Cidr) -> bool {
+        other.prefix >= self.prefix && other.network & self.mask() == self.network
+    }
+
+    pub fn overlaps(&self, other: &Cidr) -> bool {
+        self.contains(other) || other.contains(self)
+    }
+
+    /// Check the block size against the limits for VPCs and subnets
+    fn validate_size(&self, original: &str) -> Result<(), EmulatorError> {
+        if !(MIN_PREFIX..=MAX_PREFIX).contains(&self.prefix) {
+            return Err(EmulatorError::InvalidArgument(format!(
+                "The CIDR '{}' is invalid. The block size must be between /{} and /{}.", original, MIN_PREFIX, MAX_PREFIX
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Instances have at most one profile association, so its ID is derived from the instance ID
+const PROFILE_ASSOCIATION_PREFIX: &str = "iip-assoc-";
+
+pub fn profile_association_id(instance_id: &str) -> String {
+    format!("{}{}", PROFILE_ASSOCIATION_PREFIX, instance_id.trim_start_matches("i-"))
+}
+
+/// Settings for RunInstances
+#[derive(Debug, Clone, Default)]
+pub struct RunInstancesRequest {
+    pub image_id: String,
+    pub instance_type: String,
+    pub subnet_id: Option<String>,
+    pub security_group_ids: Vec<String>,
+    pub key_name: Option<String>,
+    /// Instance profile name or ARN
+    pub iam_instance_profile: Option<String>,
+    pub count: usize,
+}
 
 #[derive(Clone)]
 pub struct Ec2Service {
@@ -9,4 +97,325 @@ impl Ec2Service {
     pub fn new(storage: StorageEngine) -> Self {
         Self { storage }
     }
+
+    // ==================== VPCs ====================
+
+    /// Create a VPC with its main route table
+    pub fn create_vpc(&self, cidr_block: &str) -> Result<VpcMetadata, EmulatorError> {
+        Cidr::parse(cidr_block)?.validate_size(cidr_block)?;
+        let vpc = self.storage.create_vpc(cidr_block)?;
+        self.storage.create_route_table(&vpc.id, &vpc.cidr_block, true)?;
+        Ok(vpc)
+    }
+
+    /// Return the default VPC, creating it with default subnets, security group and an
+    /// internet route when the account has none
+    pub fn ensure_default_vpc(&self, region: &str) -> Result<VpcMetadata, EmulatorError> {
+        if let Some(vpc) = self.storage.list_vpcs()?.into_iter().find(|vpc| vpc.is_default) {
+            return Ok(vpc);
+        }
+        let vpc = self.storage.create_default_vpc(DEFAULT_VPC_CIDR)?;
+        let main = self.storage.create_route_table(&vpc.id, &vpc.cidr_block, true)?;
+        for (zone, cidr) in DEFAULT_SUBNETS {
+            self.create_subnet(&vpc.id, cidr, &format!("{}{}", region, zone))?;
+        }
+        self.create_security_group(&vpc.id, "default", "default VPC security group")?;
+        let igw = self.create_internet_gateway()?;
+        self.attach_internet_gateway(&igw.id, &vpc.id)?;
+        self.create_route(&main.id, "0.0.0.0/0", Some(&igw.id), None)?;
+        Ok(vpc)
+    }
+
+    pub fn delete_vpc(&self, vpc_id: &str) -> Result<(), EmulatorError> {
+        self.storage.get_vpc(vpc_id)?;
+        let in_use = self.storage.list_subnets()?.iter().any(|s| s.vpc_id == vpc_id)
+            || self.storage.list_internet_gateways()?.iter().any(|igw| igw.vpc_id.as_deref() == Some(vpc_id))
+            || self.storage.list_route_tables()?.iter().any(|rt| rt.vpc_id == vpc_id && !rt.is_main);
+        if in_use {
+            return Err(dependency_violation(vpc_id));
+        }
+        self.storage.delete_vpc(vpc_id)
+    }
+
+    pub fn create_subnet(&self, vpc_id: &str, cidr_block: &str, az: &str) -> Result<SubnetMetadata, EmulatorError> {
+        let vpc = self.storage.get_vpc(vpc_id)?;
+        let cidr = Cidr::parse(cidr_block)?;
+        cidr.validate_size(cidr_block)?;
+        if !Cidr::parse(&vpc.cidr_block)?.contains(&cidr) {
+            return Err(EmulatorError::InvalidArgument(format!("The CIDR '{}' is invalid for VPC {} ({}).", cidr_block, vpc_id, vpc.cidr_block)));
+        }
+        for subnet in self.storage.list_subnets()?.iter().filter(|s| s.vpc_id == vpc_id) {
+            if Cidr::parse(&subnet.cidr_block)?.overlaps(&cidr) {
+                return Err(EmulatorError::InvalidArgument(format!("The CIDR '{}' conflicts with another subnet ({}).", cidr_block, subnet.id)));
+            }
+        }
+        self.storage.create_subnet(vpc_id, cidr_block, az)
+    }
+
+    pub fn delete_subnet(&self, subnet_id: &str) -> Result<(), EmulatorError> {
+        self.storage.get_subnet(subnet_id)?;
+        let in_use = self.storage.list_instances()?.iter().any(|i| i.subnet_id.as_deref() == Some(subnet_id) && i.state != "terminated")
+            || self.active_nat_gateways()?.iter().any(|nat| nat.subnet_id == subnet_id);
+        if in_use {
+            return Err(dependency_violation(subnet_id));
+        }
+        self.storage.delete_subnet(subnet_id)
+    }
+
+    pub fn create_security_group(&self, vpc_id: &str, name: &str, description: &str) -> Result<SecurityGroupMetadata, EmulatorError> {
+        self.storage.get_vpc(vpc_id)?;
+        if self.storage.list_security_groups()?.iter().any(|sg| sg.vpc_id == vpc_id && sg.name == name) {
+            return Err(EmulatorError::AlreadyExists(format!("The security group '{}' already exists for VPC '{}'", name, vpc_id)));
+        }
+        self.storage.create_security_group(vpc_id, name, description)
+    }
+
+    // ==================== Internet gateways ====================
+
+    pub fn create_internet_gateway(&self) -> Result<InternetGatewayMetadata, EmulatorError> {
+        self.storage.create_internet_gateway()
+    }
+
+    pub fn attach_internet_gateway(&self, igw_id: &str, vpc_id: &str) -> Result<(), EmulatorError> {
+        let igw = self.storage.get_internet_gateway(igw_id)?;
+        self.storage.get_vpc(vpc_id)?;
+        if let Some(attached) = igw.vpc_id {
+            return Err(EmulatorError::InvalidRequest(format!("Resource.AlreadyAssociated: {} is already attached to {}", igw_id, attached)));
+        }
+        if self.storage.list_internet_gateways()?.iter().any(|other| other.vpc_id.as_deref() == Some(vpc_id)) {
+            return Err(EmulatorError::InvalidRequest(format!("Resource.AlreadyAssociated: {} already has an internet gateway attached", vpc_id)));
+        }
+        self.storage.set_internet_gateway_attachment(igw_id, Some(vpc_id))
+    }
+
+    pub fn detach_internet_gateway(&self, igw_id: &str, vpc_id: &str) -> Result<(), EmulatorError> {
+        let igw = self.storage.get_internet_gateway(igw_id)?;
+        if igw.vpc_id.as_deref() != Some(vpc_id) {
+            return Err(EmulatorError::InvalidRequest(format!("Gateway.NotAttached: {} is not attached to {}", igw_id, vpc_id)));
+        }
+        let routed = self.storage.list_route_tables()?.iter()
+            .any(|rt| rt.routes.iter().any(|r| r.gateway_id.as_deref() == Some(igw_id)));
+        if routed || self.active_nat_gateways()?.iter().any(|nat| nat.vpc_id == vpc_id && nat.connectivity_type == "public") {
+            return Err(dependency_violation(igw_id));
+        }
+        self.storage.set_internet_gateway_attachment(igw_id, None)
+    }
+
+    pub fn delete_internet_gateway(&self, igw_id: &str) -> Result<(), EmulatorError> {
+        if self.storage.get_internet_gateway(igw_id)?.vpc_id.is_some() {
+            return Err(dependency_violation(igw_id));
+        }
+        self.storage.delete_internet_gateway(igw_id)
+    }
+
+    // ==================== Route tables ====================
+
+    pub fn create_route_table(&self, vpc_id: &str) -> Result<RouteTableMetadata, EmulatorError> {
+        let vpc = self.storage.get_vpc(vpc_id)?;
+        self.storage.create_route_table(vpc_id, &vpc.cidr_block, false)
+    }
+
+    pub fn delete_route_table(&self, route_table_id: &str) -> Result<(), EmulatorError> {
+        let table = self.storage.get_route_table(route_table_id)?;
+        if !table.associations.is_empty() {
+            return Err(dependency_violation(route_table_id));
+        }
+        self.storage.delete_route_table(route_table_id)
+    }
+
+    /// Add a route to an internet gateway attached to the VPC or a NAT gateway in it
+    pub fn create_route(
+        &self,
+        route_table_id: &str,
+        destination_cidr_block: &str,
+        gateway_id: Option<&str>,
+        nat_gateway_id: Option<&str>,
+    ) -> Result<(), EmulatorError> {
+        let table = self.storage.get_route_table(route_table_id)?;
+        Cidr::parse(destination_cidr_block)?;
+        match (gateway_id, nat_gateway_id) {
+            (Some(igw_id), None) => {
+                let igw = self.storage.get_internet_gateway(igw_id)?;
+                if igw.vpc_id.as_deref() != Some(table.vpc_id.as_str()) {
+                    return Err(EmulatorError::InvalidArgument(format!("route table {} and network gateway {} belong to different networks", route_table_id, igw_id)));
+                }
+            }
+            (None, Some(nat_id)) => {
+                let nat = self.storage.get_nat_gateway(nat_id)?;
+                if nat.state != "available" || nat.vpc_id != table.vpc_id {
+                    return Err(EmulatorError::InvalidArgument(format!("route table {} and NAT gateway {} belong to different networks", route_table_id, nat_id)));
+                }
+            }
+            _ => return Err(EmulatorError::InvalidArgument("Exactly one of GatewayId or NatGatewayId is required".into())),
+        }
+
+        self.storage.create_route(route_table_id, &RouteMetadata {
+            destination_cidr_block: destination_cidr_block.to_string(),
+            gateway_id: gateway_id.map(|s| s.to_string()),
+            nat_gateway_id: nat_gateway_id.map(|s| s.to_string()),
+            origin: "CreateRoute".into(),
+        })
+    }
+
+    pub fn delete_route(&self, route_table_id: &str, destination_cidr_block: &str) -> Result<(), EmulatorError> {
+        let table = self.storage.get_route_table(route_table_id)?;
+        let local = table.routes.iter()
+            .any(|r| r.destination_cidr_block == destination_cidr_block && r.gateway_id.as_deref() == Some("local"));
+        if local {
+            return Err(EmulatorError::InvalidArgument(format!("cannot remove local route {} in route table {}", destination_cidr_block, route_table_id)));
+        }
+        self.storage.delete_route(route_table_id, destination_cidr_block)
+    }
+
+    /// Associate a subnet with a route table in the same VPC; returns the association ID
+    pub fn associate_route_table(&self, route_table_id: &str, subnet_id: &str) -> Result<String, EmulatorError> {
+        let table = self.storage.get_route_table(route_table_id)?;
+        let subnet = self.storage.get_subnet(subnet_id)?;
+        if subnet.vpc_id != table.vpc_id {
+            return Err(EmulatorError::InvalidArgument(format!("Route table {} and subnet {} belong to different networks", route_table_id, subnet_id)));
+        }
+        self.storage.associate_route_table(route_table_id, subnet_id)
+    }
+
+    pub fn disassociate_route_table(&self, association_id: &str) -> Result<(), EmulatorError> {
+        self.storage.disassociate_route_table(association_id)
+    }
+
+    // ==================== NAT gateways ====================
+
+    /// Create a NAT gateway in a subnet; public gateways need an internet gateway on the VPC
+    pub fn create_nat_gateway(&self, subnet_id: &str, connectivity_type: &str) -> Result<NatGatewayMetadata, EmulatorError> {
+        let subnet = self.storage.get_subnet(subnet_id)?;
+        let public_ip = match connectivity_type {
+            "public" => {
+                let attached = self.storage.list_internet_gateways()?.iter().any(|igw| igw.vpc_id.as_deref() == Some(subnet.vpc_id.as_str()));
+                if !attached {
+                    return Err(EmulatorError::InvalidRequest(format!("Gateway.NotAttached: Network {} has no Internet gateway attached", subnet.vpc_id)));
+                }
+                Some(format!("54.12.35.{}", uuid::Uuid::new_v4().as_bytes()[0]))
+            }
+            "private" => None,
+            other => return Err(EmulatorError::InvalidArgument(format!("Invalid ConnectivityType {}; expected public or private", other))),
+        };
+        let private_ip = self.allocate_private_ip(&subnet)?;
+        self.storage.create_nat_gateway(&subnet.vpc_id, subnet_id, connectivity_type, &private_ip.to_string(), public_ip.as_deref())
+    }
+
+    pub fn delete_nat_gateway(&self, nat_gateway_id: &str) -> Result<(), EmulatorError> {
+        self.storage.get_nat_gateway(nat_gateway_id)?;
+        self.storage.set_nat_gateway_state(nat_gateway_id, "deleted")
+    }
+
+    fn active_nat_gateways(&self) -> Result<Vec<NatGatewayMetadata>, EmulatorError> {
+        Ok(self.storage.list_nat_gateways()?.into_iter().filter(|nat| nat.state != "deleted").collect())
+    }
+
+    // ==================== Instances ====================
+
+    /// Launch instances; in a subnet they get an address from its block and may use the VPC's security groups
+    pub fn run_instances(&self, request: RunInstancesRequest) -> Result<Vec<InstanceMetadata>, EmulatorError> {
+        let subnet = request.subnet_id.as_deref().map(|id| self.storage.get_subnet(id)).transpose()?;
+        if !request.security_group_ids.is_empty() {
+            let groups = self.storage.list_security_groups()?;
+            for id in &request.security_group_ids {
+                let group = groups.iter().find(|g| &g.id == id)
+                    .ok_or_else(|| EmulatorError::NotFound("SecurityGroup".into(), id.clone()))?;
+                match &subnet {
+                    Some(subnet) if subnet.vpc_id != group.vpc_id => {
+                        return Err(EmulatorError::InvalidArgument(format!("Security group {} and subnet {} belong to different networks", id, subnet.id)));
+                    }
+                    None => return Err(EmulatorError::InvalidArgument("Security group IDs require a SubnetId".into())),
+                    _ => {}
+                }
+            }
+        }
+
+        let profile = request.iam_instance_profile.as_deref().map(|p| self.storage.get_instance_profile(p)).transpose()?;
+
+        let mut instances = Vec::with_capacity(request.count);
+        for _ in 0..request.count.max(1) {
+            let private_ip = subnet.as_ref().map(|s| self.allocate_private_ip(s)).transpose()?;
+            let mut instance = self.storage.run_instances(
+                &request.image_id,
+                &request.instance_type,
+                subnet.as_ref().map(|s| s.vpc_id.as_str()),
+                subnet.as_ref().map(|s| s.id.as_str()),
+                request.key_name.as_deref(),
+                &request.security_group_ids,
+                private_ip.map(|ip| ip.to_string()).as_deref(),
+            )?;
+            if let Some(profile) = &profile {
+                self.storage.set_instance_profile(&instance.id, Some(&profile.arn))?;
+                instance.iam_instance_profile = Some(profile.arn.clone());
+            }
+            instances.push(instance);
+        }
+        Ok(instances)
+    }
+
+    /// Attach an instance profile (name or ARN) to an instance without one
+    pub fn associate_iam_instance_profile(&self, instance_id: &str, profile: &str) -> Result<IamInstanceProfile, EmulatorError> {
+        let instance = self.storage.get_instance(instance_id)?;
+        if instance.iam_instance_profile.is_some() {
+            return Err(EmulatorError::InvalidRequest(format!("There is an existing association for instance {}", instance_id)));
+        }
+        let profile = self.storage.get_instance_profile(profile)?;
+        self.storage.set_instance_profile(instance_id, Some(&profile.arn))?;
+        Ok(profile)
+    }
+
+    /// Detach the instance profile identified by an association ID
+    pub fn disassociate_iam_instance_profile(&self, association_id: &str) -> Result<(InstanceMetadata, IamInstanceProfile), EmulatorError> {
+        let not_found = || EmulatorError::NotFound("IamInstanceProfileAssociation".into(), association_id.into());
+        let instance_id = association_id.strip_prefix(PROFILE_ASSOCIATION_PREFIX)
+            .map(|suffix| format!("i-{}", suffix))
+            .ok_or_else(not_found)?;
+        let instance = self.storage.get_instance(&instance_id).map_err(|_| not_found())?;
+        let profile_arn = instance.iam_instance_profile.as_deref().ok_or_else(not_found)?;
+        let profile = self.storage.get_instance_profile(profile_arn)?;
+        self.storage.set_instance_profile(&instance.id, None)?;
+        Ok((instance, profile))
+    }
+
+    /// Lowest free address in the subnet, skipping the addresses EC2 reserves
+    fn allocate_private_ip(&self, subnet: &SubnetMetadata) -> Result<Ipv4Addr, EmulatorError> {
+        let cidr = Cidr::parse(&subnet.cidr_block)?;
+        let mut used: Vec<String> = self.storage.list_instances()?.into_iter()
+            .filter(|i| i.subnet_id.as_deref() == Some(subnet.id.as_str()) && i.state != "terminated")
+            .filter_map(|i| i.private_ip)
+            .collect();
+        used.extend(self.active_nat_gateways()?.into_iter()
+            .filter(|nat| nat.subnet_id == subnet.id)
+            .map(|nat| nat.private_ip));
+
+        // The broadcast address is reserved too
+        (cidr.network + RESERVED_LEADING..cidr.last())
+            .map(Ipv4Addr::from)
+            .find(|ip| !used.contains(&ip.to_string()))
+            .ok_or_else(|| EmulatorError::InvalidRequest(format!("InsufficientFreeAddressesInSubnet: {} has no free addresses", subnet.id)))
+    }
+}
+
+fn dependency_violation(id: &str) -> EmulatorError {
+    EmulatorError::InvalidRequest(format!("DependencyViolation: The resource '{}' has dependencies and cannot be deleted.", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr() {
+        let vpc = Cidr::parse("10.0.0.0/16").unwrap();
+        assert!(vpc.contains(&Cidr::parse("10.0.1.0/24").unwrap()));
+        assert!(!vpc.contains(&Cidr::parse("10.1.0.0/24").unwrap()));
+        assert!(Cidr::parse("10.0.1.0/24").unwrap().overlaps(&Cidr::parse("10.0.0.0/23").unwrap()));
+        assert!(!Cidr::parse("10.0.1.0/24").unwrap().overlaps(&Cidr::parse("10.0.2.0/24").unwrap()));
+        assert!(Cidr::parse("10.0.0.1/16").is_err());
+        assert!(Cidr::parse("10.0.0.0/33").is_err());
+        assert!(Cidr::parse("10.0.0.0").is_err());
+        assert!(Cidr::parse("0.0.0.0/0").unwrap().contains(&vpc));
+        assert!(vpc.validate_size("10.0.0.0/16").is_ok());
+        assert!(Cidr::parse("10.0.0.0/8").unwrap().validate_size("10.0.0.0/8").is_err());
+    }
 }
@@ -0,0 +1,119 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use std::sync::Arc;
+use serde_json::{json, Value};
+
+async fn call(app: &axum::Router, action: &str, mut body: Value) -> (StatusCode, Value) {
+    body["Action"] = json!(action);
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", format!("AmazonEC2.{}", action))
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn create(app: &axum::Router, action: &str, body: Value, field: &str) -> Value {
+    let (status, resp) = call(app, action, body).await;
+    assert_eq!(status, StatusCode::OK, "{} failed: {}", action, resp);
+    resp[field].clone()
+}
+
+#[tokio::test]
+async fn test_ec2_vpc_network_model() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+
+    // CIDR blocks are validated
+    for cidr in ["10.0.0.0/8", "10.0.0.1/16", "10.0.0.0"] {
+        let (status, _) = call(&app, "CreateVpc", json!({ "CidrBlock": cidr })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", cidr);
+    }
+    let vpc = create(&app, "CreateVpc", json!({ "CidrBlock": "10.0.0.0/16" }), "Vpc").await;
+    let vpc_id = vpc["id"].as_str().unwrap();
+
+    let (_, resp) = call(&app, "DescribeVpcs", json!({ "VpcIds": [vpc_id] })).await;
+    assert_eq!(resp["Vpcs"].as_array().unwrap().len(), 1);
+    let (status, _) = call(&app, "DescribeVpcs", json!({ "VpcIds": ["vpc-missing"] })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    call(&app, "ModifyVpcAttribute", json!({ "VpcId": vpc_id, "EnableDnsHostnames": { "Value": true } })).await;
+    let (_, resp) = call(&app, "DescribeVpcAttribute", json!({ "VpcId": vpc_id, "Attribute": "enableDnsHostnames" })).await;
+    assert_eq!(resp["EnableDnsHostnames"]["Value"], true);
+
+    // Subnets must lie inside the VPC and not overlap each other
+    let subnet = create(&app, "CreateSubnet", json!({ "VpcId": vpc_id, "CidrBlock": "10.0.1.0/24" }), "Subnet").await;
+    let subnet_id = subnet["id"].as_str().unwrap();
+    for cidr in ["10.1.0.0/24", "10.0.1.128/25", "10.0.0.0/30"] {
+        let (status, _) = call(&app, "CreateSubnet", json!({ "VpcId": vpc_id, "CidrBlock": cidr })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", cidr);
+    }
+    let (_, resp) = call(&app, "DescribeSubnets", json!({ "Filters": [{ "Name": "vpc-id", "Values": [vpc_id] }] })).await;
+    assert_eq!(resp["Subnets"].as_array().unwrap().len(), 1);
+
+    // The VPC's main route table routes its own range locally
+    let (_, resp) = call(&app, "DescribeRouteTables", json!({
+        "Filters": [{ "Name": "vpc-id", "Values": [vpc_id] }, { "Name": "association.main", "Values": ["true"] }]
+    })).await;
+    let main = &resp["RouteTables"][0];
+    assert_eq!(main["routes"][0]["destination_cidr_block"], "10.0.0.0/16");
+    assert_eq!(main["routes"][0]["gateway_id"], "local");
+
+    // Public routing through an internet gateway
+    let igw = create(&app, "CreateInternetGateway", json!({}), "InternetGateway").await;
+    let igw_id = igw["id"].as_str().unwrap();
+    let rtb = create(&app, "CreateRouteTable", json!({ "VpcId": vpc_id }), "RouteTable").await;
+    let rtb_id = rtb["id"].as_str().unwrap();
+    let (status, _) = call(&app, "CreateRoute", json!({ "RouteTableId": rtb_id, "DestinationCidrBlock": "0.0.0.0/0", "GatewayId": igw_id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "the gateway is not attached yet");
+    let (status, _) = call(&app, "CreateNatGateway", json!({ "SubnetId": subnet_id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "public NAT needs an internet gateway");
+
+    call(&app, "AttachInternetGateway", json!({ "InternetGatewayId": igw_id, "VpcId": vpc_id })).await;
+    let (status, _) = call(&app, "CreateRoute", json!({ "RouteTableId": rtb_id, "DestinationCidrBlock": "0.0.0.0/0", "GatewayId": igw_id })).await;
+    assert_eq!(status, StatusCode::OK);
+    let association_id = create(&app, "AssociateRouteTable", json!({ "RouteTableId": rtb_id, "SubnetId": subnet_id }), "AssociationId").await;
+    let (status, _) = call(&app, "AssociateRouteTable", json!({ "RouteTableId": rtb_id, "SubnetId": subnet_id })).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (_, resp) = call(&app, "DescribeRouteTables", json!({ "Filters": [{ "Name": "association.subnet-id", "Values": [subnet_id] }] })).await;
+    assert_eq!(resp["RouteTables"][0]["id"], rtb_id);
+    assert_eq!(resp["RouteTables"][0]["routes"][1]["gateway_id"], igw_id);
+
+    // NAT gateways and instances get addresses from the subnet, after the reserved ones
+    let nat = create(&app, "CreateNatGateway", json!({ "SubnetId": subnet_id }), "NatGateway").await;
+    assert_eq!(nat["private_ip"], "10.0.1.4");
+    let (_, sg) = call(&app, "CreateSecurityGroup", json!({ "VpcId": vpc_id, "GroupName": "web" })).await;
+    let sg_id = sg["GroupId"].as_str().unwrap();
+    let instances = create(&app, "RunInstances", json!({ "SubnetId": subnet_id, "SecurityGroupIds": [sg_id], "MinCount": 2 }), "Instances").await;
+    assert_eq!(instances[0]["private_ip"], "10.0.1.5");
+    assert_eq!(instances[1]["private_ip"], "10.0.1.6");
+    assert_eq!(instances[0]["vpc_id"], vpc_id);
+    assert_eq!(instances[0]["security_groups"], json!([sg_id]));
+
+    // Security groups must belong to the subnet's VPC
+    let other = create(&app, "CreateVpc", json!({ "CidrBlock": "172.16.0.0/16" }), "Vpc").await;
+    let (_, other_sg) = call(&app, "CreateSecurityGroup", json!({ "VpcId": other["id"], "GroupName": "web" })).await;
+    let (status, _) = call(&app, "RunInstances", json!({ "SubnetId": subnet_id, "SecurityGroupIds": [other_sg["GroupId"]] })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Resources in use cannot be deleted
+    let (status, _) = call(&app, "DeleteVpc", json!({ "VpcId": vpc_id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(&app, "DetachInternetGateway", json!({ "InternetGatewayId": igw_id, "VpcId": vpc_id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(&app, "DeleteRouteTable", json!({ "RouteTableId": rtb_id })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    call(&app, "DisassociateRouteTable", json!({ "AssociationId": association_id })).await;
+    let (status, _) = call(&app, "DeleteRouteTable", json!({ "RouteTableId": rtb_id })).await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -1,5 +1,7 @@
+use super::{DeploymentConfiguration, ServiceUpdate};
 use crate::Emulator;
 use crate::error::EmulatorError;
+use aws_data_core::storage::{EcsServiceMetadata, EcsTask};
 use axum::{
     extract::State,
     http::HeaderMap,
@@ -23,6 +25,14 @@ pub async fn handle_request(
         "CreateCluster" => create_cluster(&emulator, body).await,
         "ListClusters" => list_clusters(&emulator, body).await,
         "RegisterTaskDefinition" => register_task_definition(&emulator, body).await,
+        "CreateService" => create_service(&emulator, body).await,
+        "UpdateService" => update_service(&emulator, body).await,
+        "DeleteService" => delete_service(&emulator, body).await,
+        "DescribeServices" => describe_services(&emulator, body).await,
+        "ListServices" => list_services(&emulator, body).await,
+        "ListTasks" => list_tasks(&emulator, body).await,
+        "DescribeTasks" => describe_tasks(&emulator, body).await,
+        "StopTask" => stop_task(&emulator, body).await,
         _ => Err(EmulatorError::NotImplemented(format!("ECS action: {}", action))),
     };
 
@@ -88,7 +98,7 @@ async fn register_task_definition(emulator: &Emulator, body: Value) -> Result<Va
         });
     }
 
-    let task_def = emulator.storage.register_task_definition(family, containers)?;
+    let task_def = emulator.storage.register_task_definition(family, containers, body["taskRoleArn"].as_str())?;
     
     // Convert back to JSON for response (simplified)
     Ok(json!({
@@ -96,7 +106,183 @@ async fn register_task_definition(emulator: &Emulator, body: Value) -> Result<Va
             "family": task_def.family,
             "taskDefinitionArn": task_def.arn,
             "revision": task_def.revision,
+            "taskRoleArn": task_def.task_role_arn,
             "status": "ACTIVE"
         }
     }))
 }
+
+/// Service events returned by DescribeServices, matching the AWS limit
+const MAX_SERVICE_EVENTS: i64 = 100;
+
+fn cluster_param(body: &Value) -> &str {
+    body["cluster"].as_str().unwrap_or("default")
+}
+
+/// `deploymentConfiguration` of CreateService/UpdateService, on top of `base`
+fn deployment_configuration(body: &Value, base: DeploymentConfiguration) -> DeploymentConfiguration {
+    let config = &body["deploymentConfiguration"];
+    let breaker = &config["deploymentCircuitBreaker"];
+    DeploymentConfiguration {
+        minimum_healthy_percent: config["minimumHealthyPercent"].as_i64().map_or(base.minimum_healthy_percent, |v| v as i32),
+        maximum_percent: config["maximumPercent"].as_i64().map_or(base.maximum_percent, |v| v as i32),
+        circuit_breaker_enable: breaker["enable"].as_bool().unwrap_or(base.circuit_breaker_enable),
+        circuit_breaker_rollback: breaker["rollback"].as_bool().unwrap_or(base.circuit_breaker_rollback),
+    }
+}
+
+async fn create_service(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = body["serviceName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing serviceName".into()))?;
+    let task_definition = body["taskDefinition"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing taskDefinition".into()))?;
+    let desired_count = body["desiredCount"].as_i64().unwrap_or(0) as i32;
+    let config = deployment_configuration(&body, DeploymentConfiguration::default());
+
+    let service = emulator.ecs.create_service(cluster_param(&body), name, task_definition, desired_count, config)?;
+    Ok(json!({ "service": service_json(emulator, &service)? }))
+}
+
+async fn update_service(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = body["service"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing service".into()))?;
+    let cluster = cluster_param(&body);
+    let current = emulator.storage.get_ecs_service(&emulator.storage.get_cluster_arn(cluster)?, name)?;
+
+    let update = ServiceUpdate {
+        task_definition: body["taskDefinition"].as_str().map(str::to_string),
+        desired_count: body["desiredCount"].as_i64().map(|v| v as i32),
+        deployment_configuration: body.get("deploymentConfiguration")
+            .map(|_| deployment_configuration(&body, DeploymentConfiguration::of(&current))),
+        force_new_deployment: body["forceNewDeployment"].as_bool().unwrap_or(false),
+    };
+    let service = emulator.ecs.update_service(cluster, name, update)?;
+    Ok(json!({ "service": service_json(emulator, &service)? }))
+}
+
+async fn delete_service(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = body["service"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing service".into()))?;
+    let force = body["force"].as_bool().unwrap_or(false);
+
+    let service = emulator.ecs.delete_service(cluster_param(&body), name, force)?;
+    Ok(json!({ "service": service_json(emulator, &service)? }))
+}
+
+async fn describe_services(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let cluster_arn = emulator.storage.get_cluster_arn(cluster_param(&body))?;
+    let names = body["services"].as_array().ok_or_else(|| EmulatorError::InvalidArgument("Missing services".into()))?;
+
+    let mut services = Vec::new();
+    let mut failures = Vec::new();
+    for name in names.iter().filter_map(Value::as_str) {
+        match emulator.storage.get_ecs_service(&cluster_arn, name) {
+            Ok(service) => services.push(service_json(emulator, &service)?),
+            Err(EmulatorError::NotFound(..)) => failures.push(json!({ "arn": name, "reason": "MISSING" })),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(json!({ "services": services, "failures": failures }))
+}
+
+async fn list_services(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let cluster_arn = emulator.storage.get_cluster_arn(cluster_param(&body))?;
+    let arns: Vec<String> = emulator.storage.list_ecs_services(&cluster_arn)?.into_iter().map(|s| s.arn).collect();
+    Ok(json!({ "serviceArns": arns }))
+}
+
+async fn list_tasks(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let cluster_arn = emulator.storage.get_cluster_arn(cluster_param(&body))?;
+    let service_arn = match body["serviceName"].as_str() {
+        Some(name) => Some(emulator.storage.get_ecs_service(&cluster_arn, name)?.arn),
+        None => None,
+    };
+    let status = body["desiredStatus"].as_str().unwrap_or("RUNNING");
+
+    let arns: Vec<String> = emulator.storage.list_ecs_tasks(&cluster_arn, service_arn.as_deref(), Some(status))?
+        .into_iter()
+        .map(|t| t.arn)
+        .collect();
+    Ok(json!({ "taskArns": arns }))
+}
+
+async fn describe_tasks(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let cluster_arn = emulator.storage.get_cluster_arn(cluster_param(&body))?;
+    let ids = body["tasks"].as_array().ok_or_else(|| EmulatorError::InvalidArgument("Missing tasks".into()))?;
+
+    let mut tasks = Vec::new();
+    let mut failures = Vec::new();
+    for id in ids.iter().filter_map(Value::as_str) {
+        match emulator.storage.get_ecs_task(&cluster_arn, id) {
+            Ok(task) => tasks.push(task_json(&task)),
+            Err(EmulatorError::NotFound(..)) => failures.push(json!({ "arn": id, "reason": "MISSING" })),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(json!({ "tasks": tasks, "failures": failures }))
+}
+
+async fn stop_task(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let task = body["task"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing task".into()))?;
+    let task = emulator.ecs.stop_task(cluster_param(&body), task, body["reason"].as_str())?;
+    Ok(json!({ "task": task_json(&task) }))
+}
+
+fn service_json(emulator: &Emulator, service: &EcsServiceMetadata) -> Result<Value, EmulatorError> {
+    let running = emulator.storage.list_ecs_tasks(&service.cluster_arn, Some(&service.arn), Some("RUNNING"))?;
+    let deployments: Vec<Value> = emulator.storage.list_ecs_deployments(&service.arn)?.into_iter()
+        .filter(|d| d.status != "INACTIVE")
+        .map(|d| {
+            let running_count = running.iter().filter(|t| t.deployment_id.as_deref() == Some(d.id.as_str())).count();
+            json!({
+                "id": d.id,
+                "status": d.status,
+                "taskDefinition": d.task_definition,
+                "desiredCount": d.desired_count,
+                "pendingCount": 0,
+                "runningCount": running_count,
+                "failedTasks": d.failed_tasks,
+                "rolloutState": d.rollout_state,
+                "rolloutStateReason": d.rollout_state_reason,
+                "createdAt": d.created_at,
+                "updatedAt": d.updated_at
+            })
+        })
+        .collect();
+    let events: Vec<Value> = emulator.storage.list_ecs_service_events(&service.arn, MAX_SERVICE_EVENTS)?.into_iter()
+        .map(|e| json!({ "id": e.id, "createdAt": e.created_at, "message": e.message }))
+        .collect();
+
+    Ok(json!({
+        "serviceArn": service.arn,
+        "serviceName": service.name,
+        "clusterArn": service.cluster_arn,
+        "taskDefinition": service.task_definition,
+        "status": service.status,
+        "desiredCount": service.desired_count,
+        "runningCount": running.len(),
+        "pendingCount": 0,
+        "launchType": "FARGATE",
+        "deploymentConfiguration": {
+            "minimumHealthyPercent": service.minimum_healthy_percent,
+            "maximumPercent": service.maximum_percent,
+            "deploymentCircuitBreaker": {
+                "enable": service.circuit_breaker_enable,
+                "rollback": service.circuit_breaker_rollback
+            }
+        },
+        "deployments": deployments,
+        "events": events,
+        "createdAt": service.created_at
+    }))
+}
+
+fn task_json(task: &EcsTask) -> Value {
+    json!({
+        "taskArn": task.arn,
+        "clusterArn": task.cluster_arn,
+        "taskDefinitionArn": task.task_definition,
+        "group": task.service_arn.as_deref().map(|arn| format!("service:{}", arn.rsplit('/').next().unwrap_or(arn))),
+        "startedBy": task.deployment_id,
+        "lastStatus": task.last_status,
+        "desiredStatus": task.last_status,
+        "stoppedReason": task.stopped_reason,
+        "createdAt": task.created_at
+    })
+}
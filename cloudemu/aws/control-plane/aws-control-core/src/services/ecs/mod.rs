@@ -1,4 +1,7 @@
 mod service;
 pub mod handlers;
 
-pub use service::EcsService;
+#[cfg(test)]
+mod tests;
+
+pub use service::{DeploymentConfiguration, EcsService, ServiceUpdate};
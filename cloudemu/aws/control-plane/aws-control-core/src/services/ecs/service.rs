@@ -1,7 +1,450 @@
-pub struct EcsService {}
+//! ECS Service - service scheduler and rolling deployments
+//!
+//! Tasks are not backed by containers: a launched task is RUNNING unless its image
+//! cannot be pulled, which the emulator can only tell for images of its own ECR registry.
+//! Reconciliation runs synchronously after every change, so DescribeServices always
+//! shows the settled state and the events that led to it.
+
+use aws_data_core::error::{EmulatorError, Result};
+use aws_data_core::storage::{EcsDeployment, EcsServiceMetadata, EcsTask, StorageEngine};
+use std::sync::Mutex;
+use tracing::info;
+
+/// Upper bound on reconciliation rounds for a single change
+const MAX_ROUNDS: usize = 1000;
+
+/// Rolling update bounds and circuit breaker of a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeploymentConfiguration {
+    pub minimum_healthy_percent: i32,
+    pub maximum_percent: i32,
+    pub circuit_breaker_enable: bool,
+    pub circuit_breaker_rollback: bool,
+}
+
+impl Default for DeploymentConfiguration {
+    fn default() -> Self {
+        Self {
+            minimum_healthy_percent: 100,
+            maximum_percent: 200,
+            circuit_breaker_enable: false,
+            circuit_breaker_rollback: false,
+        }
+    }
+}
+
+impl DeploymentConfiguration {
+    fn validate(&self) -> Result<()> {
+        if !(0..=100).contains(&self.minimum_healthy_percent) {
+            return Err(EmulatorError::InvalidArgument("minimumHealthyPercent must be between 0 and 100".into()));
+        }
+        if !(100..=200).contains(&self.maximum_percent) {
+            return Err(EmulatorError::InvalidArgument("maximumPercent must be between 100 and 200".into()));
+        }
+        if self.maximum_percent <= self.minimum_healthy_percent {
+            return Err(EmulatorError::InvalidArgument("maximumPercent must be greater than minimumHealthyPercent".into()));
+        }
+        Ok(())
+    }
+
+    /// Configuration currently applied to a service
+    pub fn of(service: &EcsServiceMetadata) -> Self {
+        Self {
+            minimum_healthy_percent: service.minimum_healthy_percent,
+            maximum_percent: service.maximum_percent,
+            circuit_breaker_enable: service.circuit_breaker_enable,
+            circuit_breaker_rollback: service.circuit_breaker_rollback,
+        }
+    }
+}
+
+/// Changes requested by UpdateService
+#[derive(Debug, Clone, Default)]
+pub struct ServiceUpdate {
+    pub task_definition: Option<String>,
+    pub desired_count: Option<i32>,
+    pub deployment_configuration: Option<DeploymentConfiguration>,
+    pub force_new_deployment: bool,
+}
+
+/// ECS Service
+pub struct EcsService {
+    storage: StorageEngine,
+    /// Serialises scheduler runs so concurrent updates cannot interleave their rounds
+    scheduler: Mutex<()>,
+}
 
 impl EcsService {
-    pub fn new() -> Self {
-        Self {}
+    /// Create a new ECS service
+    pub fn new(storage: StorageEngine) -> Self {
+        Self {
+            storage,
+            scheduler: Mutex::new(()),
+        }
+    }
+
+    pub fn create_service(
+        &self,
+        cluster: &str,
+        name: &str,
+        task_definition: &str,
+        desired_count: i32,
+        config: DeploymentConfiguration,
+    ) -> Result<EcsServiceMetadata> {
+        let _guard = self.scheduler.lock().unwrap();
+        let cluster_arn = self.storage.get_cluster_arn(cluster)?;
+        let task_definition = self.storage.get_task_definition(task_definition)?;
+        validate_desired_count(desired_count)?;
+        config.validate()?;
+
+        let cluster_name = cluster_arn.rsplit('/').next().unwrap_or(cluster);
+        let service = EcsServiceMetadata {
+            arn: format!("arn:aws:ecs:us-east-1:000000000000:service/{}/{}", cluster_name, name),
+            name: name.to_string(),
+            cluster_arn,
+            task_definition: task_definition.arn,
+            desired_count,
+            minimum_healthy_percent: config.minimum_healthy_percent,
+            maximum_percent: config.maximum_percent,
+            circuit_breaker_enable: config.circuit_breaker_enable,
+            circuit_breaker_rollback: config.circuit_breaker_rollback,
+            status: "ACTIVE".to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        self.storage.create_ecs_service(&service)?;
+        self.start_deployment(&service)?;
+        self.reconcile(&service)?;
+
+        // A circuit breaker rollback may have changed the task definition
+        self.storage.get_ecs_service(&service.cluster_arn, &service.arn)
+    }
+
+    pub fn update_service(&self, cluster: &str, name: &str, update: ServiceUpdate) -> Result<EcsServiceMetadata> {
+        let _guard = self.scheduler.lock().unwrap();
+        let cluster_arn = self.storage.get_cluster_arn(cluster)?;
+        let mut service = self.active_service(&cluster_arn, name)?;
+
+        let mut redeploy = update.force_new_deployment;
+        if let Some(reference) = update.task_definition.as_deref() {
+            let task_definition = self.storage.get_task_definition(reference)?;
+            redeploy |= task_definition.arn != service.task_definition;
+            service.task_definition = task_definition.arn;
+        }
+        if let Some(desired_count) = update.desired_count {
+            validate_desired_count(desired_count)?;
+            service.desired_count = desired_count;
+        }
+        if let Some(config) = update.deployment_configuration {
+            config.validate()?;
+            service.minimum_healthy_percent = config.minimum_healthy_percent;
+            service.maximum_percent = config.maximum_percent;
+            service.circuit_breaker_enable = config.circuit_breaker_enable;
+            service.circuit_breaker_rollback = config.circuit_breaker_rollback;
+        }
+        self.storage.update_ecs_service(&service)?;
+
+        if redeploy {
+            self.start_deployment(&service)?;
+        } else if let Some(mut primary) = self.primary_deployment(&service)? {
+            // Scaling changes the target of the current rollout
+            if primary.desired_count != service.desired_count {
+                primary.desired_count = service.desired_count;
+                if primary.rollout_state == "COMPLETED" {
+                    primary.rollout_state = "IN_PROGRESS".to_string();
+                }
+                primary.updated_at = chrono::Utc::now().timestamp();
+                self.storage.put_ecs_deployment(&primary)?;
+            }
+        }
+        self.reconcile(&service)?;
+
+        self.storage.get_ecs_service(&cluster_arn, &service.arn)
+    }
+
+    /// Delete a service; without `force` it must be scaled to zero first
+    pub fn delete_service(&self, cluster: &str, name: &str, force: bool) -> Result<EcsServiceMetadata> {
+        let _guard = self.scheduler.lock().unwrap();
+        let cluster_arn = self.storage.get_cluster_arn(cluster)?;
+        let mut service = self.active_service(&cluster_arn, name)?;
+        if service.desired_count > 0 && !force {
+            return Err(EmulatorError::InvalidArgument("The service cannot be stopped while it is scaled above 0.".into()));
+        }
+
+        let running = self.storage.list_ecs_tasks(&service.cluster_arn, Some(&service.arn), Some("RUNNING"))?;
+        self.stop_tasks(&service, &running, "Service was deleted")?;
+        for mut deployment in self.storage.list_ecs_deployments(&service.arn)? {
+            deployment.status = "INACTIVE".to_string();
+            self.storage.put_ecs_deployment(&deployment)?;
+        }
+        service.desired_count = 0;
+        service.status = "INACTIVE".to_string();
+        self.storage.update_ecs_service(&service)?;
+        info!("ECS: Deleted service {}", service.name);
+
+        Ok(service)
+    }
+
+    /// Stop a task; the scheduler replaces it if it belongs to a service
+    pub fn stop_task(&self, cluster: &str, task: &str, reason: Option<&str>) -> Result<EcsTask> {
+        let _guard = self.scheduler.lock().unwrap();
+        let cluster_arn = self.storage.get_cluster_arn(cluster)?;
+        let mut task = self.storage.get_ecs_task(&cluster_arn, task)?;
+        if task.last_status == "STOPPED" {
+            return Ok(task);
+        }
+
+        task.last_status = "STOPPED".to_string();
+        task.stopped_reason = Some(reason.unwrap_or("Task stopped by user").to_string());
+        self.storage.put_ecs_task(&task)?;
+
+        if let Some(service_arn) = task.service_arn.as_deref() {
+            let service = self.storage.get_ecs_service(&cluster_arn, service_arn)?;
+            if service.status == "ACTIVE" {
+                self.reconcile(&service)?;
+            }
+        }
+        Ok(task)
+    }
+
+    fn active_service(&self, cluster_arn: &str, name: &str) -> Result<EcsServiceMetadata> {
+        let service = self.storage.get_ecs_service(cluster_arn, name)?;
+        if service.status != "ACTIVE" {
+            return Err(EmulatorError::InvalidArgument(format!("Service {} is not ACTIVE", name)));
+        }
+        Ok(service)
+    }
+
+    fn primary_deployment(&self, service: &EcsServiceMetadata) -> Result<Option<EcsDeployment>> {
+        Ok(self.storage.list_ecs_deployments(&service.arn)?.into_iter().find(|d| d.status == "PRIMARY"))
+    }
+
+    /// Make the service's task definition the PRIMARY deployment; earlier ones become ACTIVE and drain
+    fn start_deployment(&self, service: &EcsServiceMetadata) -> Result<EcsDeployment> {
+        let now = chrono::Utc::now().timestamp();
+        for mut previous in self.storage.list_ecs_deployments(&service.arn)? {
+            if previous.status == "PRIMARY" {
+                previous.status = "ACTIVE".to_string();
+                previous.updated_at = now;
+                self.storage.put_ecs_deployment(&previous)?;
+            }
+        }
+
+        let deployment = EcsDeployment {
+            id: format!("ecs-svc/{}", uuid::Uuid::new_v4().as_u128() % 10u128.pow(19)),
+            service_arn: service.arn.clone(),
+            task_definition: service.task_definition.clone(),
+            status: "PRIMARY".to_string(),
+            desired_count: service.desired_count,
+            failed_tasks: 0,
+            rollout_state: "IN_PROGRESS".to_string(),
+            rollout_state_reason: Some("ECS deployment in progress.".to_string()),
+            created_at: now,
+            updated_at: now,
+        };
+        self.storage.put_ecs_deployment(&deployment)?;
+        info!("ECS: Service {} started deployment {} of {}", service.name, deployment.id, deployment.task_definition);
+        Ok(deployment)
+    }
+
+    /// Drive the service towards its desired state: launch tasks of the PRIMARY deployment
+    /// within `maximumPercent`, stop tasks of older deployments while keeping
+    /// `minimumHealthyPercent` running, and trip the circuit breaker on repeated launch failures.
+    fn reconcile(&self, service: &EcsServiceMetadata) -> Result<()> {
+        let mut service = service.clone();
+
+        for _ in 0..MAX_ROUNDS {
+            let Some(mut primary) = self.primary_deployment(&service)? else {
+                return Ok(());
+            };
+            let config = DeploymentConfiguration::of(&service);
+            let desired = service.desired_count;
+            let running = self.storage.list_ecs_tasks(&service.cluster_arn, Some(&service.arn), Some("RUNNING"))?;
+            let (current, old): (Vec<EcsTask>, Vec<EcsTask>) = running.into_iter()
+                .partition(|t| t.deployment_id.as_deref() == Some(primary.id.as_str()));
+            let mut progressed = false;
+
+            // Scale in
+            if current.len() as i32 > desired {
+                let excess = current.len() - desired as usize;
+                self.stop_tasks(&service, &current[..excess], "Scaling activity initiated by deployment")?;
+                continue;
+            }
+
+            // Launch new tasks, bounded by maximumPercent across all deployments
+            let max_tasks = desired * config.maximum_percent / 100;
+            let total = (current.len() + old.len()) as i32;
+            let to_launch = (desired - current.len() as i32).min(max_tasks - total);
+            if to_launch > 0 && primary.rollout_state != "FAILED" {
+                let failed = self.launch_tasks(&service, &primary, to_launch)?;
+                let launched = to_launch - failed;
+                progressed |= launched > 0;
+
+                if failed > 0 {
+                    primary.failed_tasks += failed;
+                    primary.updated_at = chrono::Utc::now().timestamp();
+                    self.storage.put_ecs_deployment(&primary)?;
+
+                    if config.circuit_breaker_enable {
+                        // Keep retrying until the breaker trips
+                        progressed = true;
+                        if primary.failed_tasks >= failure_threshold(desired) {
+                            self.trip_circuit_breaker(&mut service, &mut primary)?;
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            // Drain older deployments, keeping minimumHealthyPercent of the desired count running
+            let current_running = self.storage.list_ecs_tasks(&service.cluster_arn, Some(&service.arn), Some("RUNNING"))?
+                .into_iter()
+                .filter(|t| t.deployment_id.as_deref() == Some(primary.id.as_str()))
+                .count() as i32;
+            let min_healthy = (desired * config.minimum_healthy_percent + 99) / 100;
+            let can_stop = (current_running + old.len() as i32 - min_healthy).max(0) as usize;
+            let stoppable = if primary.rollout_state == "FAILED" { 0 } else { can_stop.min(old.len()) };
+            if stoppable > 0 {
+                self.stop_tasks(&service, &old[..stoppable], "Scaling activity initiated by deployment")?;
+                progressed = true;
+            }
+            self.retire_drained_deployments(&service, &primary)?;
+
+            // Steady state
+            if current_running == desired && old.len() == stoppable {
+                if primary.rollout_state == "IN_PROGRESS" {
+                    primary.rollout_state = "COMPLETED".to_string();
+                    primary.rollout_state_reason = Some("ECS deployment completed.".to_string());
+                    primary.updated_at = chrono::Utc::now().timestamp();
+                    self.storage.put_ecs_deployment(&primary)?;
+                    self.event(&service, &format!("(deployment {}) deployment completed.", primary.id))?;
+                    self.event(&service, "has reached a steady state.")?;
+                }
+                return Ok(());
+            }
+            if !progressed {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Launch `count` tasks for a deployment, returning how many failed to start
+    fn launch_tasks(&self, service: &EcsServiceMetadata, deployment: &EcsDeployment, count: i32) -> Result<i32> {
+        let task_definition = self.storage.get_task_definition(&deployment.task_definition)?;
+        let pull_error = task_definition.container_definitions.iter()
+            .find_map(|c| self.image_pull_error(&c.image));
+        let cluster_name = service.cluster_arn.rsplit('/').next().unwrap_or_default();
+
+        let mut started = Vec::new();
+        for _ in 0..count {
+            let task = EcsTask {
+                arn: format!("arn:aws:ecs:us-east-1:000000000000:task/{}/{}", cluster_name, uuid::Uuid::new_v4().simple()),
+                cluster_arn: service.cluster_arn.clone(),
+                service_arn: Some(service.arn.clone()),
+                deployment_id: Some(deployment.id.clone()),
+                task_definition: deployment.task_definition.clone(),
+                last_status: if pull_error.is_some() { "STOPPED" } else { "RUNNING" }.to_string(),
+                stopped_reason: pull_error.clone(),
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            self.storage.put_ecs_task(&task)?;
+            started.push(task);
+        }
+
+        match pull_error {
+            Some(reason) => {
+                self.event(service, &format!("failed to launch {} tasks: ({}).", count, reason))?;
+                Ok(count)
+            }
+            None => {
+                self.event(service, &format!("has started {} tasks: {}.", count, task_list(&started)))?;
+                Ok(0)
+            }
+        }
+    }
+
+    fn stop_tasks(&self, service: &EcsServiceMetadata, tasks: &[EcsTask], reason: &str) -> Result<()> {
+        if tasks.is_empty() {
+            return Ok(());
+        }
+        for task in tasks {
+            let mut task = task.clone();
+            task.last_status = "STOPPED".to_string();
+            task.stopped_reason = Some(reason.to_string());
+            self.storage.put_ecs_task(&task)?;
+        }
+        self.event(service, &format!("has stopped {} running tasks: {}.", tasks.len(), task_list(tasks)))
+    }
+
+    /// Older deployments without running tasks are done
+    fn retire_drained_deployments(&self, service: &EcsServiceMetadata, primary: &EcsDeployment) -> Result<()> {
+        let running = self.storage.list_ecs_tasks(&service.cluster_arn, Some(&service.arn), Some("RUNNING"))?;
+        for mut deployment in self.storage.list_ecs_deployments(&service.arn)? {
+            let drained = !running.iter().any(|t| t.deployment_id.as_deref() == Some(deployment.id.as_str()));
+            if deployment.status == "ACTIVE" && deployment.id != primary.id && drained && primary.rollout_state != "FAILED" {
+                deployment.status = "INACTIVE".to_string();
+                deployment.updated_at = chrono::Utc::now().timestamp();
+                self.storage.put_ecs_deployment(&deployment)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail the PRIMARY deployment and, if enabled, roll back to the previous task definition
+    fn trip_circuit_breaker(&self, service: &mut EcsServiceMetadata, primary: &mut EcsDeployment) -> Result<()> {
+        primary.rollout_state = "FAILED".to_string();
+        primary.rollout_state_reason = Some(format!("ECS deployment circuit breaker: task failed to start. ({} failed tasks)", primary.failed_tasks));
+        primary.updated_at = chrono::Utc::now().timestamp();
+        self.storage.put_ecs_deployment(primary)?;
+        self.event(service, &format!("(deployment {}) deployment failed: tasks failed to start.", primary.id))?;
+
+        if !service.circuit_breaker_rollback {
+            return Ok(());
+        }
+        let previous = self.storage.list_ecs_deployments(&service.arn)?.into_iter()
+            .find(|d| d.id != primary.id && d.task_definition != primary.task_definition);
+        let Some(previous) = previous else {
+            return Ok(());
+        };
+
+        service.task_definition = previous.task_definition.clone();
+        self.storage.update_ecs_service(service)?;
+        let rollback = self.start_deployment(service)?;
+        self.event(service, &format!("rolling back to deployment {}.", rollback.id))?;
+        Ok(())
+    }
+
+    /// Images of the emulator's own ECR registry can only be pulled from existing repositories
+    fn image_pull_error(&self, image: &str) -> Option<String> {
+        let (registry, path) = image.split_once('/')?;
+        if !registry.contains(".dkr.ecr.") {
+            return None;
+        }
+        let repository = path.split(['@', ':']).next().unwrap_or(path);
+        let exists = self.storage.list_repositories().ok()?.iter().any(|r| r.repository_name == repository);
+        (!exists).then(|| format!("CannotPullContainerError: pull image manifest has been retried 5 time(s): {} not found", image))
     }
+
+    fn event(&self, service: &EcsServiceMetadata, message: &str) -> Result<()> {
+        self.storage.add_ecs_service_event(&service.arn, &format!("(service {}) {}", service.name, message))
+    }
+}
+
+fn validate_desired_count(desired_count: i32) -> Result<()> {
+    if desired_count < 0 {
+        return Err(EmulatorError::InvalidArgument("desiredCount must be at least 0".into()));
+    }
+    Ok(())
+}
+
+/// Failed task launches that trip the circuit breaker: half the desired count, between 3 and 200
+fn failure_threshold(desired_count: i32) -> i32 {
+    ((desired_count + 1) / 2).clamp(3, 200)
+}
+
+fn task_list(tasks: &[EcsTask]) -> String {
+    tasks.iter()
+        .map(|t| format!("(task {})", t.arn.rsplit('/').next().unwrap_or(&t.arn)))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
@@ -0,0 +1,169 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use std::sync::Arc;
+use serde_json::{json, Value};
+
+async fn call(app: &axum::Router, action: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", format!("AmazonEC2ContainerServiceV20141113.{}", action))
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn register(app: &axum::Router, image: &str) -> String {
+    let (status, body) = call(app, "RegisterTaskDefinition", json!({
+        "family": "web",
+        "containerDefinitions": [{ "name": "web", "image": image }]
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    body["taskDefinition"]["taskDefinitionArn"].as_str().unwrap().to_string()
+}
+
+async fn describe(app: &axum::Router) -> Value {
+    let (_, body) = call(app, "DescribeServices", json!({ "cluster": "prod", "services": ["web"] })).await;
+    body["services"][0].clone()
+}
+
+fn messages(service: &Value) -> Vec<String> {
+    // Events are returned newest first
+    service["events"].as_array().unwrap().iter().rev()
+        .map(|e| e["message"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_ecs_service_rolling_deployment() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    call(&app, "CreateCluster", json!({ "clusterName": "prod" })).await;
+    let v1 = register(&app, "nginx:1.25").await;
+    let v2 = register(&app, "nginx:1.27").await;
+
+    let (status, _) = call(&app, "CreateService", json!({
+        "cluster": "missing", "serviceName": "web", "taskDefinition": "web", "desiredCount": 1
+    })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = call(&app, "CreateService", json!({
+        "cluster": "prod",
+        "serviceName": "web",
+        "taskDefinition": "web:1",
+        "desiredCount": 4,
+        "deploymentConfiguration": { "minimumHealthyPercent": 100, "maximumPercent": 150 }
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["service"]["runningCount"], 4);
+    assert_eq!(body["service"]["deployments"][0]["rolloutState"], "COMPLETED");
+    assert_eq!(body["service"]["taskDefinition"], v1);
+
+    // Rolling update: at most 6 tasks running and never fewer than 4
+    let (status, body) = call(&app, "UpdateService", json!({
+        "cluster": "prod", "service": "web", "taskDefinition": "web:2"
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    let service = &body["service"];
+    assert_eq!(service["taskDefinition"], v2);
+    assert_eq!(service["runningCount"], 4);
+    let deployments = service["deployments"].as_array().unwrap();
+    assert_eq!(deployments.len(), 1, "old deployment should be drained");
+    assert_eq!(deployments[0]["status"], "PRIMARY");
+    assert_eq!(deployments[0]["rolloutState"], "COMPLETED");
+    assert_eq!(deployments[0]["runningCount"], 4);
+
+    let events = messages(service);
+    let rollout: Vec<&String> = events.iter().skip_while(|m| !m.contains("has reached a steady state")).skip(1).collect();
+    let started = rollout.iter().filter(|m| m.contains("has started 2 tasks")).count();
+    let stopped = rollout.iter().filter(|m| m.contains("has stopped 2 running tasks")).count();
+    assert_eq!((started, stopped), (2, 2), "expected two batches of two: {:?}", rollout);
+    assert!(rollout.last().unwrap().ends_with("has reached a steady state."));
+
+    // Desired count reconciliation
+    let (_, body) = call(&app, "UpdateService", json!({ "cluster": "prod", "service": "web", "desiredCount": 2 })).await;
+    assert_eq!(body["service"]["runningCount"], 2);
+    let (_, body) = call(&app, "ListTasks", json!({ "cluster": "prod", "serviceName": "web" })).await;
+    let tasks = body["taskArns"].as_array().unwrap().clone();
+    assert_eq!(tasks.len(), 2);
+
+    // A stopped task is replaced
+    let (_, body) = call(&app, "StopTask", json!({ "cluster": "prod", "task": tasks[0], "reason": "test" })).await;
+    assert_eq!(body["task"]["lastStatus"], "STOPPED");
+    let service = describe(&app).await;
+    assert_eq!(service["runningCount"], 2);
+    assert!(messages(&service).last().unwrap().contains("has started 1 tasks"));
+    let (_, body) = call(&app, "DescribeTasks", json!({ "cluster": "prod", "tasks": [tasks[0]] })).await;
+    assert_eq!(body["tasks"][0]["stoppedReason"], "test");
+    assert_eq!(body["tasks"][0]["group"], "service:web");
+
+    // Deleting requires scaling to zero unless forced
+    let (status, _) = call(&app, "DeleteService", json!({ "cluster": "prod", "service": "web" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = call(&app, "DeleteService", json!({ "cluster": "prod", "service": "web", "force": true })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["service"]["status"], "INACTIVE");
+    let (_, body) = call(&app, "ListServices", json!({ "cluster": "prod" })).await;
+    assert!(body["serviceArns"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ecs_circuit_breaker_rollback() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    call(&app, "CreateCluster", json!({ "clusterName": "prod" })).await;
+    let repository = emulator.storage.create_repository("web").unwrap();
+    let good = register(&app, &format!("{}:1.0", repository.repository_uri)).await;
+    register(&app, "000000000000.dkr.ecr.us-east-1.amazonaws.com/missing:1.1").await;
+
+    let (status, _) = call(&app, "CreateService", json!({
+        "cluster": "prod",
+        "serviceName": "web",
+        "taskDefinition": "web:1",
+        "desiredCount": 2,
+        "deploymentConfiguration": { "maximumPercent": 100, "minimumHealthyPercent": 100 }
+    })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = call(&app, "CreateService", json!({
+        "cluster": "prod",
+        "serviceName": "web",
+        "taskDefinition": "web:1",
+        "desiredCount": 2,
+        "deploymentConfiguration": {
+            "minimumHealthyPercent": 50,
+            "maximumPercent": 200,
+            "deploymentCircuitBreaker": { "enable": true, "rollback": true }
+        }
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The new image cannot be pulled; the breaker trips and the service rolls back
+    let (status, body) = call(&app, "UpdateService", json!({
+        "cluster": "prod", "service": "web", "taskDefinition": "web:2"
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    let service = &body["service"];
+    assert_eq!(service["taskDefinition"], good);
+    assert_eq!(service["runningCount"], 2);
+
+    let deployments = service["deployments"].as_array().unwrap();
+    assert_eq!(deployments.len(), 1);
+    assert_eq!(deployments[0]["taskDefinition"], good);
+    assert_eq!(deployments[0]["rolloutState"], "COMPLETED");
+
+    let events = messages(service);
+    assert!(events.iter().any(|m| m.contains("CannotPullContainerError")));
+    assert!(events.iter().any(|m| m.contains("deployment failed: tasks failed to start.")));
+    assert!(events.iter().any(|m| m.contains("rolling back to deployment")));
+    assert!(events.last().unwrap().ends_with("has reached a steady state."));
+}
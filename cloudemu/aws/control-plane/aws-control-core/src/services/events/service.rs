@@ -1,11 +1,148 @@
-use aws_data_core::storage::StorageEngine;
+use crate::event_bus::{EventSubscriber, ResourceEvent};
+use aws_data_core::error::Result;
+use aws_data_core::storage::{EventTargetMetadata, StorageEngine};
+use aws_data_core::Config;
+use serde_json::{json, Value};
+use tracing::{info, warn};
 
+/// Detail type of events forwarded from the internal resource event bus
+pub const API_CALL_DETAIL_TYPE: &str = "AWS API Call via CloudTrail";
+
+#[derive(Clone)]
 pub struct EventsService {
-    _storage: StorageEngine,
+    storage: StorageEngine,
+    config: Config,
 }
 
 impl EventsService {
-    pub fn new(storage: StorageEngine) -> Self {
-        Self { _storage: storage }
+    pub fn new(storage: StorageEngine, config: Config) -> Self {
+        Self { storage, config }
+    }
+
+    /// Record a PutEvents entry on its bus and deliver it to the targets of every matching rule.
+    /// Returns the event id.
+    pub fn put_event(&self, entry: &Value) -> Result<String> {
+        let bus_name = entry["EventBusName"].as_str().unwrap_or("default");
+        let source = entry["Source"].as_str().unwrap_or("");
+        let detail_type = entry["DetailType"].as_str().unwrap_or("");
+        let detail = entry["Detail"].as_str().unwrap_or("{}");
+        let resources = entry["Resources"].to_string();
+
+        let event_id = self.storage.record_event(
+            bus_name,
+            source,
+            detail_type,
+            detail,
+            Some(&resources)
+        )?;
+
+        // Match event against rules and trigger targets
+        for rule in self.storage.list_rules(bus_name)? {
+            if rule.state != "ENABLED" {
+                continue;
+            }
+
+            if let Some(pattern_str) = &rule.event_pattern {
+                if matches_pattern(entry, pattern_str) {
+                    info!("EventBridge: Event {} matched rule {}", event_id, rule.name);
+
+                    for target in self.storage.list_targets(bus_name, &rule.name)? {
+                        self.trigger_target(&target, entry, &event_id);
+                    }
+                }
+            }
+        }
+
+        Ok(event_id)
+    }
+
+    /// Trigger a target with an event
+    fn trigger_target(&self, target: &EventTargetMetadata, event: &Value, event_id: &str) {
+        let arn = &target.arn;
+
+        // Parse ARN to determine target type
+        if arn.contains(":sqs:") {
+            // Send to SQS queue
+            if let Some(queue_name) = arn.split(':').next_back() {
+                let message = json!({
+                    "version": "0",
+                    "id": event_id,
+                    "detail-type": event["DetailType"],
+                    "source": event["Source"],
+                    "time": chrono::Utc::now().to_rfc3339(),
+                    "region": self.config.region,
+                    "resources": event.get("Resources").unwrap_or(&json!([])),
+                    "detail": serde_json::from_str::<Value>(event["Detail"].as_str().unwrap_or("{}")).unwrap_or(json!({}))
+                });
+
+                if let Err(e) = self.storage.send_message(queue_name, &message.to_string()) {
+                    warn!("EventBridge: Failed to send to SQS {}: {}", queue_name, e);
+                } else {
+                    info!("EventBridge: Sent event to SQS queue {}", queue_name);
+                }
+            }
+        } else if arn.contains(":sns:") {
+            // Publish to SNS topic
+            info!("EventBridge: Would publish to SNS topic {} (SNS publish via EventBridge integration)", arn);
+        } else if arn.contains(":lambda:") {
+            // Invoke Lambda
+            info!("EventBridge: Would invoke Lambda {} (Lambda execution not yet implemented)", arn);
+        } else {
+            warn!("EventBridge: Unknown target type: {}", arn);
+        }
     }
 }
+
+/// Resource lifecycle events reach the default bus as `aws.<service>` API call events
+impl EventSubscriber for EventsService {
+    fn on_event(&self, event: &ResourceEvent) {
+        let detail = json!({
+            "eventID": event.id,
+            "eventTime": event.time,
+            "eventSource": format!("{}.amazonaws.com", event.service),
+            "eventName": event.operation,
+            "awsRegion": self.config.region,
+            "requestParameters": event.detail,
+        });
+        let entry = json!({
+            "EventBusName": "default",
+            "Source": format!("aws.{}", event.service),
+            "DetailType": API_CALL_DETAIL_TYPE,
+            "Detail": detail.to_string(),
+            "Resources": event.resource.iter().collect::<Vec<_>>(),
+        });
+
+        if let Err(e) = self.put_event(&entry) {
+            warn!("EventBridge: Failed to forward {} event: {}", event.operation, e);
+        }
+    }
+}
+
+/// Check if an event matches an EventBridge pattern
+fn matches_pattern(event: &Value, pattern_str: &str) -> bool {
+    let pattern: Value = match serde_json::from_str(pattern_str) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    // Simple pattern matching - check source and detail-type
+    if let Some(sources) = pattern["source"].as_array() {
+        let event_source = event["Source"].as_str().unwrap_or("");
+        let source_match = sources.iter().any(|s| s.as_str() == Some(event_source));
+        if !source_match {
+            return false;
+        }
+    }
+
+    if let Some(detail_types) = pattern["detail-type"].as_array() {
+        let event_detail_type = event["DetailType"].as_str().unwrap_or("");
+        let type_match = detail_types.iter().any(|t| t.as_str() == Some(event_detail_type));
+        if !type_match {
+            return false;
+        }
+    }
+
+    // For full implementation, we'd need deeper pattern matching on detail object
+    // For now, this covers the most common use cases
+    true
+}
@@ -0,0 +1,112 @@
+//! Credential endpoints for emulated compute
+//!
+//! EC2 instances reach an IMDS-compatible service by pointing
+//! `AWS_EC2_METADATA_SERVICE_ENDPOINT` at `/_aws/ec2/{instance_id}/`, and ECS
+//! tasks set `AWS_CONTAINER_CREDENTIALS_FULL_URI` to
+//! `/_aws/ecs/credentials/{cluster}/{task_id}`, so SDK default credential
+//! chains resolve the role of the instance profile or task definition.
+
+use crate::Emulator;
+use crate::error::{ApiError, EmulatorError};
+use aws_data_core::storage::IamSessionCredentials;
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+fn timestamp(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+fn credentials_json(credentials: &IamSessionCredentials) -> Value {
+    json!({
+        "AccessKeyId": credentials.access_key_id,
+        "SecretAccessKey": credentials.secret_access_key,
+        "Token": credentials.session_token,
+        "Expiration": timestamp(credentials.expiration),
+    })
+}
+
+fn into_response(result: Result<Response, EmulatorError>) -> Response {
+    result.unwrap_or_else(|e| ApiError(e).into_response())
+}
+
+/// IMDSv2 session token; tokens are accepted but not required on metadata requests
+pub async fn metadata_token(Path(_instance_id): Path<String>) -> Response {
+    uuid::Uuid::new_v4().simple().to_string().into_response()
+}
+
+pub async fn metadata_index(
+    State(emulator): State<Arc<Emulator>>,
+    Path(instance_id): Path<String>,
+) -> Response {
+    into_response(instance_metadata(&emulator, &instance_id, ""))
+}
+
+pub async fn metadata(
+    State(emulator): State<Arc<Emulator>>,
+    Path((instance_id, path)): Path<(String, String)>,
+) -> Response {
+    into_response(instance_metadata(&emulator, &instance_id, &path))
+}
+
+fn instance_metadata(emulator: &Emulator, instance_id: &str, path: &str) -> Result<Response, EmulatorError> {
+    let instance = emulator.storage.get_instance(instance_id)?;
+    let text = |s: String| Ok(s.into_response());
+
+    match path.trim_end_matches('/') {
+        "" => text(["ami-id", "iam/", "instance-id", "instance-type", "local-ipv4", "placement/", "public-ipv4"].join("\n")),
+        "ami-id" => text(instance.image_id),
+        "instance-id" => text(instance.id),
+        "instance-type" => text(instance.instance_type),
+        "local-ipv4" => text(instance.private_ip.unwrap_or_default()),
+        "public-ipv4" => text(instance.public_ip.unwrap_or_default()),
+        "placement" => text("availability-zone\nregion".into()),
+        "placement/region" => text(emulator.config.region.clone()),
+        "placement/availability-zone" => text(format!("{}a", emulator.config.region)),
+        "iam" => text("info\nsecurity-credentials/".into()),
+        "iam/info" => {
+            let profile = emulator.iam.instance_profile_of(instance_id)?;
+            Ok(Json(json!({
+                "Code": "Success",
+                "LastUpdated": timestamp(chrono::Utc::now().timestamp()),
+                "InstanceProfileArn": profile.arn,
+                "InstanceProfileId": "AIPA...",
+            })).into_response())
+        }
+        "iam/security-credentials" => {
+            let (role, _) = emulator.iam.instance_credentials(instance_id)?;
+            text(role.name)
+        }
+        other => match other.strip_prefix("iam/security-credentials/") {
+            Some(role_name) => {
+                let (role, credentials) = emulator.iam.instance_credentials(instance_id)?;
+                if role.name != role_name {
+                    return Err(EmulatorError::NotFound("Role".into(), role_name.into()));
+                }
+                let mut body = credentials_json(&credentials);
+                body["Code"] = json!("Success");
+                body["LastUpdated"] = json!(timestamp(chrono::Utc::now().timestamp()));
+                body["Type"] = json!("AWS-HMAC");
+                Ok(Json(body).into_response())
+            }
+            None => Err(EmulatorError::NotFound("MetadataPath".into(), other.into())),
+        },
+    }
+}
+
+pub async fn container_credentials(
+    State(emulator): State<Arc<Emulator>>,
+    Path((cluster, task_id)): Path<(String, String)>,
+) -> Response {
+    into_response(emulator.iam.task_credentials(&cluster, &task_id).map(|credentials| {
+        let mut body = credentials_json(&credentials);
+        body["RoleArn"] = json!(credentials.role_arn);
+        Json(body).into_response()
+    }))
+}
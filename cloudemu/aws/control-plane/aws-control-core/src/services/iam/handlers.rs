@@ -11,6 +11,7 @@ use std::collections::HashMap;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use crate::adapters::aws_query::parse_query_string;
+use aws_data_core::storage::IamInstanceProfile;
 
 pub async fn handle_request(
     State(emulator): State<Arc<Emulator>>,
@@ -31,6 +32,15 @@ pub async fn handle_request(
         "CreateUser" => create_user(&emulator, &params).await,
         "ListUsers" => list_users(&emulator, &params).await,
         "CreateAccessKey" => create_access_key(&emulator, &params).await,
+        "CreateInstanceProfile" => create_instance_profile(&emulator, &params).await,
+        "GetInstanceProfile" => get_instance_profile(&emulator, &params).await,
+        "ListInstanceProfiles" => list_instance_profiles(&emulator, &params).await,
+        "DeleteInstanceProfile" => delete_instance_profile(&emulator, &params).await,
+        "AddRoleToInstanceProfile" => add_role_to_instance_profile(&emulator, &params).await,
+        "RemoveRoleFromInstanceProfile" => remove_role_from_instance_profile(&emulator, &params).await,
+        "CreateAccountAlias" => create_account_alias(&emulator, &params).await,
+        "ListAccountAliases" => list_account_aliases(&emulator, &params).await,
+        "DeleteAccountAlias" => delete_account_alias(&emulator, &params).await,
         _ => Err(EmulatorError::NotImplemented(format!("IAM action: {}", action))),
     };
 
@@ -261,3 +271,171 @@ async fn create_access_key(emulator: &Emulator, params: &HashMap<String, String>
         }
     }))
 }
+
+fn instance_profile_json(emulator: &Emulator, profile: &IamInstanceProfile) -> Result<Value, EmulatorError> {
+    let roles = match &profile.role_name {
+        Some(name) => {
+            let role = emulator.storage.get_role(name)?;
+            vec![json!({
+                "RoleName": role.name,
+                "RoleId": "AROA...",
+                "Arn": role.arn,
+                "CreateDate": "2023-01-01T00:00:00Z",
+                "Path": role.path,
+                "AssumeRolePolicyDocument": role.assume_role_policy_document
+            })]
+        }
+        None => vec![],
+    };
+    let created = chrono::DateTime::from_timestamp(profile.created_at, 0).unwrap_or_default();
+
+    Ok(json!({
+        "InstanceProfileName": profile.name,
+        "InstanceProfileId": "AIPA...",
+        "Arn": profile.arn,
+        "Path": profile.path,
+        "CreateDate": created.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        "Roles": roles
+    }))
+}
+
+async fn create_instance_profile(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let name = params.get("InstanceProfileName").ok_or_else(|| EmulatorError::InvalidArgument("Missing InstanceProfileName".into()))?;
+    let path = params.get("Path").map(|s| s.as_str()).unwrap_or("/");
+
+    let profile = emulator.storage.create_instance_profile(name, path)?;
+
+    Ok(json!({
+        "CreateInstanceProfileResponse": {
+            "CreateInstanceProfileResult": {
+                "InstanceProfile": instance_profile_json(emulator, &profile)?
+            },
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn get_instance_profile(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let name = params.get("InstanceProfileName").ok_or_else(|| EmulatorError::InvalidArgument("Missing InstanceProfileName".into()))?;
+    let profile = emulator.storage.get_instance_profile(name)?;
+
+    Ok(json!({
+        "GetInstanceProfileResponse": {
+            "GetInstanceProfileResult": {
+                "InstanceProfile": instance_profile_json(emulator, &profile)?
+            },
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn list_instance_profiles(emulator: &Emulator, _params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let profiles = emulator.storage.list_instance_profiles()?
+        .iter()
+        .map(|p| instance_profile_json(emulator, p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(json!({
+        "ListInstanceProfilesResponse": {
+            "ListInstanceProfilesResult": {
+                "InstanceProfiles": profiles,
+                "IsTruncated": false
+            },
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn delete_instance_profile(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let name = params.get("InstanceProfileName").ok_or_else(|| EmulatorError::InvalidArgument("Missing InstanceProfileName".into()))?;
+
+    emulator.iam.delete_instance_profile(name)?;
+
+    Ok(json!({
+        "DeleteInstanceProfileResponse": {
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn add_role_to_instance_profile(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let name = params.get("InstanceProfileName").ok_or_else(|| EmulatorError::InvalidArgument("Missing InstanceProfileName".into()))?;
+    let role_name = params.get("RoleName").ok_or_else(|| EmulatorError::InvalidArgument("Missing RoleName".into()))?;
+
+    emulator.iam.add_role_to_instance_profile(name, role_name)?;
+
+    Ok(json!({
+        "AddRoleToInstanceProfileResponse": {
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn remove_role_from_instance_profile(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let name = params.get("InstanceProfileName").ok_or_else(|| EmulatorError::InvalidArgument("Missing InstanceProfileName".into()))?;
+    let role_name = params.get("RoleName").ok_or_else(|| EmulatorError::InvalidArgument("Missing RoleName".into()))?;
+
+    emulator.iam.remove_role_from_instance_profile(name, role_name)?;
+
+    Ok(json!({
+        "RemoveRoleFromInstanceProfileResponse": {
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn create_account_alias(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let alias = params.get("AccountAlias").ok_or_else(|| EmulatorError::InvalidArgument("Missing AccountAlias".into()))?;
+
+    emulator.iam.create_account_alias(alias)?;
+
+    Ok(json!({
+        "CreateAccountAliasResponse": {
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn list_account_aliases(emulator: &Emulator, _params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let aliases = emulator.storage.list_account_aliases()?;
+
+    Ok(json!({
+        "ListAccountAliasesResponse": {
+            "ListAccountAliasesResult": {
+                "AccountAliases": aliases,
+                "IsTruncated": false
+            },
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
+
+async fn delete_account_alias(emulator: &Emulator, params: &HashMap<String, String>) -> Result<Value, EmulatorError> {
+    let alias = params.get("AccountAlias").ok_or_else(|| EmulatorError::InvalidArgument("Missing AccountAlias".into()))?;
+
+    emulator.storage.delete_account_alias(alias)?;
+
+    Ok(json!({
+        "DeleteAccountAliasResponse": {
+            "ResponseMetadata": {
+                "RequestId": "req-123"
+            }
+        }
+    }))
+}
@@ -1,4 +1,8 @@
 mod service;
+pub mod credentials;
 pub mod handlers;
 
-pub use service::IamService;
+pub use service::{IamService, DEFAULT_SESSION_DURATION_SECS};
+
+#[cfg(test)]
+mod tests;
@@ -1,7 +1,119 @@
-pub struct IamService {}
+use aws_data_core::StorageEngine;
+use aws_data_core::storage::{IamInstanceProfile, IamRole, IamSessionCredentials};
+use crate::error::EmulatorError;
+
+/// Lifetime of credentials vended to workloads and role sessions
+pub const DEFAULT_SESSION_DURATION_SECS: i64 = 3600;
+
+/// Cached session credentials are replaced once they get this close to expiring
+const CREDENTIAL_REFRESH_WINDOW_SECS: i64 = 900;
+
+#[derive(Clone)]
+pub struct IamService {
+    pub storage: StorageEngine,
+}
 
 impl IamService {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(storage: StorageEngine) -> Self {
+        Self { storage }
+    }
+
+    /// Look up a role by ARN
+    pub fn get_role_by_arn(&self, role_arn: &str) -> Result<IamRole, EmulatorError> {
+        let name = role_arn.rsplit('/').next().unwrap_or(role_arn);
+        let role = self.storage.get_role(name)?;
+        if role.arn != role_arn {
+            return Err(EmulatorError::NotFound("Role".into(), role_arn.into()));
+        }
+        Ok(role)
+    }
+
+    pub fn add_role_to_instance_profile(&self, profile_name: &str, role_name: &str) -> Result<(), EmulatorError> {
+        let profile = self.storage.get_instance_profile(profile_name)?;
+        self.storage.get_role(role_name)?;
+        if profile.role_name.is_some() {
+            return Err(EmulatorError::InvalidRequest(format!(
+                "Cannot exceed quota for InstanceSessionsPerInstanceProfile: 1 (instance profile {} already has a role)", profile_name
+            )));
+        }
+        self.storage.set_instance_profile_role(&profile.name, Some(role_name))?;
+        Ok(())
+    }
+
+    pub fn remove_role_from_instance_profile(&self, profile_name: &str, role_name: &str) -> Result<(), EmulatorError> {
+        let profile = self.storage.get_instance_profile(profile_name)?;
+        if profile.role_name.as_deref() != Some(role_name) {
+            return Err(EmulatorError::NotFound("Role".into(), format!("{} in instance profile {}", role_name, profile_name)));
+        }
+        self.storage.set_instance_profile_role(&profile.name, None)?;
+        Ok(())
+    }
+
+    /// Account aliases are 3-63 lowercase letters, digits and hyphens, without a leading or trailing hyphen
+    pub fn create_account_alias(&self, alias: &str) -> Result<(), EmulatorError> {
+        let valid = (3..=63).contains(&alias.len())
+            && alias.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            && !alias.starts_with('-')
+            && !alias.ends_with('-');
+        if !valid {
+            return Err(EmulatorError::InvalidArgument(format!("Invalid account alias: {}", alias)));
+        }
+        self.storage.create_account_alias(alias)?;
+        Ok(())
+    }
+
+    /// Instance profiles can only be deleted once their role is removed
+    pub fn delete_instance_profile(&self, profile_name: &str) -> Result<(), EmulatorError> {
+        let profile = self.storage.get_instance_profile(profile_name)?;
+        if profile.role_name.is_some() {
+            return Err(EmulatorError::InvalidRequest(format!(
+                "Cannot delete entity, must remove roles from instance profile first: {}", profile_name
+            )));
+        }
+        self.storage.delete_instance_profile(&profile.name)?;
+        Ok(())
+    }
+
+    /// Temporary credentials for a role session, reusing the current ones until they near expiry
+    pub fn session_credentials(&self, role_arn: &str, session_name: &str) -> Result<IamSessionCredentials, EmulatorError> {
+        self.get_role_by_arn(role_arn)?;
+        if let Some(credentials) = self.storage.find_session_credentials(role_arn, session_name, CREDENTIAL_REFRESH_WINDOW_SECS)? {
+            return Ok(credentials);
+        }
+        self.storage.create_session_credentials(role_arn, session_name, DEFAULT_SESSION_DURATION_SECS)
+    }
+
+    /// Instance profile attached to a running EC2 instance
+    pub fn instance_profile_of(&self, instance_id: &str) -> Result<IamInstanceProfile, EmulatorError> {
+        let instance = self.storage.get_instance(instance_id)?;
+        if instance.state == "terminated" {
+            return Err(EmulatorError::InvalidRequest(format!("Instance {} is terminated", instance_id)));
+        }
+        let profile_arn = instance.iam_instance_profile
+            .ok_or_else(|| EmulatorError::NotFound("InstanceProfile".into(), format!("for instance {}", instance_id)))?;
+        self.storage.get_instance_profile(&profile_arn)
+    }
+
+    /// Credentials an EC2 instance obtains from its metadata service
+    pub fn instance_credentials(&self, instance_id: &str) -> Result<(IamRole, IamSessionCredentials), EmulatorError> {
+        let profile = self.instance_profile_of(instance_id)?;
+        let role_name = profile.role_name
+            .ok_or_else(|| EmulatorError::NotFound("Role".into(), format!("in instance profile {}", profile.name)))?;
+        let role = self.storage.get_role(&role_name)?;
+        let credentials = self.session_credentials(&role.arn, instance_id)?;
+        Ok((role, credentials))
+    }
+
+    /// Credentials an ECS task obtains from the container credentials endpoint
+    pub fn task_credentials(&self, cluster: &str, task_id: &str) -> Result<IamSessionCredentials, EmulatorError> {
+        let cluster_arn = self.storage.get_cluster_arn(cluster)?;
+        let task = self.storage.get_ecs_task(&cluster_arn, task_id)?;
+        if task.last_status == "STOPPED" {
+            return Err(EmulatorError::InvalidRequest(format!("Task {} is stopped", task_id)));
+        }
+        let role_arn = self.storage.get_task_definition(&task.task_definition)?.task_role_arn
+            .ok_or_else(|| EmulatorError::NotFound("TaskRole".into(), format!("for task {}", task_id)))?;
+        let session_name = task.arn.rsplit('/').next().unwrap_or(task_id).to_string();
+        self.session_credentials(&role_arn, &session_name)
     }
 }
@@ -0,0 +1,238 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use std::sync::Arc;
+use serde_json::{json, Value};
+
+const TRUST_POLICY: &str = r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Principal":{"Service":"ec2.amazonaws.com"},"Action":"sts:AssumeRole"}]}"#;
+
+async fn send(app: &axum::Router, request: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, bytes.to_vec())
+}
+
+async fn iam(app: &axum::Router, action: &str, params: &[(&str, &str)]) -> (StatusCode, Value) {
+    let mut form = format!("Action={}", action);
+    for (key, value) in params {
+        form.push_str(&format!("&{}={}", key, percent_encoding::utf8_percent_encode(value, percent_encoding::NON_ALPHANUMERIC)));
+    }
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", format!("AWSIdentityManagementV20100508.{}", action))
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body(Body::from(form))
+        .unwrap();
+    let (status, bytes) = send(app, request).await;
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn json_call(app: &axum::Router, target: &str, body: Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", target)
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let (status, bytes) = send(app, request).await;
+    (status, serde_json::from_slice(&bytes).unwrap_or(Value::Null))
+}
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().method("GET").uri(uri).body(Body::empty()).unwrap();
+    let (status, bytes) = send(app, request).await;
+    (status, String::from_utf8(bytes).unwrap())
+}
+
+async fn create_role(app: &axum::Router, name: &str) -> String {
+    let (status, body) = iam(app, "CreateRole", &[("RoleName", name), ("AssumeRolePolicyDocument", TRUST_POLICY)]).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    body["CreateRoleResponse"]["CreateRoleResult"]["Role"]["Arn"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_iam_instance_profile_lifecycle() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    create_role(&app, "web-role").await;
+    create_role(&app, "batch-role").await;
+
+    let (status, body) = iam(&app, "CreateInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let profile = &body["CreateInstanceProfileResponse"]["CreateInstanceProfileResult"]["InstanceProfile"];
+    assert_eq!(profile["Arn"], "arn:aws:iam::000000000000:instance-profile/web");
+    assert_eq!(profile["Roles"], json!([]));
+    let (status, _) = iam(&app, "CreateInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Missing roles and a second role are rejected
+    let (status, _) = iam(&app, "AddRoleToInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "missing")]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = iam(&app, "AddRoleToInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "web-role")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = iam(&app, "AddRoleToInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "batch-role")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, body) = iam(&app, "GetInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    let roles = &body["GetInstanceProfileResponse"]["GetInstanceProfileResult"]["InstanceProfile"]["Roles"];
+    assert_eq!(roles[0]["RoleName"], "web-role");
+    let (_, body) = iam(&app, "ListInstanceProfiles", &[]).await;
+    assert_eq!(body["ListInstanceProfilesResponse"]["ListInstanceProfilesResult"]["InstanceProfiles"].as_array().unwrap().len(), 1);
+
+    // The role must be removed before the profile can be deleted
+    let (status, _) = iam(&app, "DeleteInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = iam(&app, "RemoveRoleFromInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "batch-role")]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = iam(&app, "RemoveRoleFromInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "web-role")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = iam(&app, "DeleteInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = iam(&app, "GetInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_iam_account_alias() {
+    let app = gateway::create_router(Arc::new(Emulator::in_memory().unwrap()));
+
+    let (status, _) = iam(&app, "CreateAccountAlias", &[("AccountAlias", "-bad")]).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = iam(&app, "CreateAccountAlias", &[("AccountAlias", "first-alias")]).await;
+    assert_eq!(status, StatusCode::OK);
+    // An account has one alias; creating another replaces it
+    let (status, _) = iam(&app, "CreateAccountAlias", &[("AccountAlias", "second-alias")]).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = iam(&app, "ListAccountAliases", &[]).await;
+    assert_eq!(body["ListAccountAliasesResponse"]["ListAccountAliasesResult"]["AccountAliases"], json!(["second-alias"]));
+
+    let (status, _) = iam(&app, "DeleteAccountAlias", &[("AccountAlias", "first-alias")]).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = iam(&app, "DeleteAccountAlias", &[("AccountAlias", "second-alias")]).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[cfg(feature = "ec2")]
+#[tokio::test]
+async fn test_instance_metadata_credentials() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    let role_arn = create_role(&app, "web-role").await;
+    iam(&app, "CreateInstanceProfile", &[("InstanceProfileName", "web")]).await;
+    iam(&app, "AddRoleToInstanceProfile", &[("InstanceProfileName", "web"), ("RoleName", "web-role")]).await;
+
+    let (status, _) = json_call(&app, "AmazonEC2.RunInstances", json!({
+        "Action": "RunInstances", "ImageId": "ami-1", "IamInstanceProfile": { "Name": "missing" }
+    })).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = json_call(&app, "AmazonEC2.RunInstances", json!({
+        "Action": "RunInstances", "ImageId": "ami-1", "IamInstanceProfile": { "Name": "web" }
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    let instance_id = body["Instances"][0]["id"].as_str().unwrap().to_string();
+    assert_eq!(body["Instances"][0]["iam_instance_profile"], "arn:aws:iam::000000000000:instance-profile/web");
+
+    // IMDSv2 token, then the credential discovery the SDKs perform
+    let base = format!("/_aws/ec2/{}/latest", instance_id);
+    let request = Request::builder().method("PUT").uri(format!("{}/api/token", base))
+        .header("x-aws-ec2-metadata-token-ttl-seconds", "21600").body(Body::empty()).unwrap();
+    let (status, token) = send(&app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!token.is_empty());
+
+    let (status, body) = get(&app, &format!("{}/meta-data/instance-id", base)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, instance_id);
+    let (_, body) = get(&app, &format!("{}/meta-data/iam/security-credentials/", base)).await;
+    assert_eq!(body, "web-role");
+    let (status, body) = get(&app, &format!("{}/meta-data/iam/security-credentials/web-role", base)).await;
+    assert_eq!(status, StatusCode::OK);
+    let credentials: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(credentials["Code"], "Success");
+    assert!(credentials["AccessKeyId"].as_str().unwrap().starts_with("ASIA"));
+    assert!(credentials["Expiration"].as_str().unwrap().ends_with('Z'));
+
+    // Credentials are reused until they near expiry
+    let (_, body) = get(&app, &format!("{}/meta-data/iam/security-credentials/web-role", base)).await;
+    let again: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(again["AccessKeyId"], credentials["AccessKeyId"]);
+    let stored = emulator.storage.find_session_credentials(&role_arn, &instance_id, 0).unwrap().unwrap();
+    assert_eq!(stored.session_token, credentials["Token"].as_str().unwrap());
+
+    let (status, _) = get(&app, &format!("{}/meta-data/iam/security-credentials/other-role", base)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Instances launched without a profile get no credentials until one is associated
+    let (_, body) = json_call(&app, "AmazonEC2.RunInstances", json!({ "Action": "RunInstances", "ImageId": "ami-1" })).await;
+    let bare_id = body["Instances"][0]["id"].as_str().unwrap().to_string();
+    let (status, _) = get(&app, &format!("/_aws/ec2/{}/latest/meta-data/iam/security-credentials/", bare_id)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = json_call(&app, "AmazonEC2.AssociateIamInstanceProfile", json!({
+        "Action": "AssociateIamInstanceProfile",
+        "InstanceId": bare_id,
+        "IamInstanceProfile": { "Arn": "arn:aws:iam::000000000000:instance-profile/web" }
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    let association_id = body["IamInstanceProfileAssociation"]["AssociationId"].as_str().unwrap().to_string();
+    let (status, _) = json_call(&app, "AmazonEC2.AssociateIamInstanceProfile", json!({
+        "Action": "AssociateIamInstanceProfile", "InstanceId": bare_id, "IamInstanceProfile": { "Name": "web" }
+    })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (_, body) = get(&app, &format!("/_aws/ec2/{}/latest/meta-data/iam/security-credentials/", bare_id)).await;
+    assert_eq!(body, "web-role");
+
+    let (status, _) = json_call(&app, "AmazonEC2.DisassociateIamInstanceProfile", json!({
+        "Action": "DisassociateIamInstanceProfile", "AssociationId": association_id
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get(&app, &format!("/_aws/ec2/{}/latest/meta-data/iam/security-credentials/", bare_id)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[cfg(feature = "ecs")]
+#[tokio::test]
+async fn test_container_credentials() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    let role_arn = create_role(&app, "task-role").await;
+    let ecs = |action: &str| format!("AmazonEC2ContainerServiceV20141113.{}", action);
+
+    json_call(&app, &ecs("CreateCluster"), json!({ "clusterName": "prod" })).await;
+    let (status, body) = json_call(&app, &ecs("RegisterTaskDefinition"), json!({
+        "family": "web",
+        "taskRoleArn": role_arn,
+        "containerDefinitions": [{ "name": "web", "image": "nginx:1.27" }]
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["taskDefinition"]["taskRoleArn"], role_arn);
+    json_call(&app, &ecs("CreateService"), json!({
+        "cluster": "prod", "serviceName": "web", "taskDefinition": "web", "desiredCount": 1
+    })).await;
+    let (_, body) = json_call(&app, &ecs("ListTasks"), json!({ "cluster": "prod" })).await;
+    let task_arn = body["taskArns"][0].as_str().unwrap().to_string();
+    let task_id = task_arn.rsplit('/').next().unwrap();
+
+    let (status, body) = get(&app, &format!("/_aws/ecs/credentials/prod/{}", task_id)).await;
+    assert_eq!(status, StatusCode::OK);
+    let credentials: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(credentials["RoleArn"], role_arn);
+    assert!(credentials["AccessKeyId"].as_str().unwrap().starts_with("ASIA"));
+    assert!(credentials["Token"].as_str().is_some());
+
+    let (status, _) = get(&app, "/_aws/ecs/credentials/prod/unknown").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Stopped tasks no longer receive credentials
+    json_call(&app, &ecs("StopTask"), json!({ "cluster": "prod", "task": task_arn })).await;
+    let (status, _) = get(&app, &format!("/_aws/ecs/credentials/prod/{}", task_id)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,214 @@
+//! Cognito Identity: identity pools exchanging user-pool ID tokens for temporary credentials
+//!
+//! A pool names the user pools (`cognito-idp.<region>.amazonaws.com/<pool id>`) and app
+//! clients it trusts, and the IAM roles its identities assume. `GetId` maps a login to a
+//! stable identity ID; `GetCredentialsForIdentity` checks the login's ID token (issuer,
+//! audience, expiry, user) and vends session credentials of the authenticated role through
+//! the IAM engine. Identities without a login get the unauthenticated role when the pool
+//! allows guests.
+
+use crate::Emulator;
+use crate::error::EmulatorError;
+use aws_data_core::storage::IdentityPoolMetadata;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+pub async fn handle_request(
+    State(emulator): State<Arc<Emulator>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let target = headers
+        .get("x-amz-target")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    info!("Cognito Identity: {}", target);
+    let action = target.split('.').next_back().unwrap_or(target);
+
+    let result = match action {
+        "CreateIdentityPool" => create_identity_pool(&emulator, body),
+        "DescribeIdentityPool" => describe_identity_pool(&emulator, body),
+        "ListIdentityPools" => list_identity_pools(&emulator),
+        "DeleteIdentityPool" => delete_identity_pool(&emulator, body),
+        "SetIdentityPoolRoles" => set_identity_pool_roles(&emulator, body),
+        "GetIdentityPoolRoles" => get_identity_pool_roles(&emulator, body),
+        "GetId" => get_id(&emulator, body),
+        "GetCredentialsForIdentity" => get_credentials_for_identity(&emulator, body),
+        _ => Err(EmulatorError::InvalidRequest(format!("Unknown or unsupported target: {}", target))),
+    };
+
+    match result {
+        Ok(json_val) => Json::<Value>(json_val).into_response(),
+        Err(e) => {
+            let json_err = json!({
+                "__type": e.code(),
+                "message": e.message()
+            });
+            (e.status_code(), Json::<Value>(json_err)).into_response()
+        }
+    }
+}
+
+fn pool_id(body: &Value) -> Result<&str, EmulatorError> {
+    body["IdentityPoolId"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing IdentityPoolId".into()))
+}
+
+fn pool_json(pool: &IdentityPoolMetadata) -> Value {
+    json!({
+        "IdentityPoolId": pool.id,
+        "IdentityPoolName": pool.name,
+        "AllowUnauthenticatedIdentities": pool.allow_unauthenticated,
+        "CognitoIdentityProviders": pool.providers
+    })
+}
+
+fn create_identity_pool(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = body["IdentityPoolName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing IdentityPoolName".into()))?;
+    let allow_unauthenticated = body["AllowUnauthenticatedIdentities"].as_bool()
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing AllowUnauthenticatedIdentities".into()))?;
+    let providers = match &body["CognitoIdentityProviders"] {
+        Value::Null => json!([]),
+        providers @ Value::Array(list) if list.iter().all(|p| p["ProviderName"].is_string()) => providers.clone(),
+        _ => return Err(EmulatorError::InvalidArgument("Each of CognitoIdentityProviders needs a ProviderName".into())),
+    };
+    let pool = emulator.storage.create_identity_pool(name, allow_unauthenticated, &providers, &emulator.config.region)?;
+    Ok(pool_json(&pool))
+}
+
+fn describe_identity_pool(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    Ok(pool_json(&emulator.storage.get_identity_pool(pool_id(&body)?)?))
+}
+
+fn list_identity_pools(emulator: &Emulator) -> Result<Value, EmulatorError> {
+    let pools: Vec<Value> = emulator.storage.list_identity_pools()?.into_iter()
+        .map(|p| json!({ "IdentityPoolId": p.id, "IdentityPoolName": p.name }))
+        .collect();
+    Ok(json!({ "IdentityPools": pools }))
+}
+
+fn delete_identity_pool(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.storage.delete_identity_pool(pool_id(&body)?)?;
+    Ok(json!({}))
+}
+
+fn set_identity_pool_roles(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let pool_id = pool_id(&body)?;
+    let roles: HashMap<String, String> = serde_json::from_value(body["Roles"].clone())
+        .map_err(|_| EmulatorError::InvalidArgument("Roles must map authenticated and unauthenticated to role ARNs".into()))?;
+    if let Some(kind) = roles.keys().find(|kind| !matches!(kind.as_str(), "authenticated" | "unauthenticated")) {
+        return Err(EmulatorError::InvalidArgument(format!("Unknown role type: {}", kind)));
+    }
+    for role_arn in roles.values() {
+        emulator.iam.get_role_by_arn(role_arn)?;
+    }
+    emulator.storage.set_identity_pool_roles(pool_id, &roles)?;
+    Ok(json!({}))
+}
+
+fn get_identity_pool_roles(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let pool = emulator.storage.get_identity_pool(pool_id(&body)?)?;
+    Ok(json!({ "IdentityPoolId": pool.id, "Roles": pool.roles }))
+}
+
+fn get_id(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let pool = emulator.storage.get_identity_pool(pool_id(&body)?)?;
+    let login = verify_logins(emulator, &pool, &body["Logins"])?;
+    if login.is_none() && !pool.allow_unauthenticated {
+        return Err(EmulatorError::NotAuthorized("Unauthenticated access is not supported for this identity pool.".into()));
+    }
+    let identity_id = emulator.storage.get_or_create_identity(&pool.id, login.as_deref())?;
+    Ok(json!({ "IdentityId": identity_id }))
+}
+
+fn get_credentials_for_identity(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let identity_id = body["IdentityId"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing IdentityId".into()))?;
+    let (pool_id, identity_login) = emulator.storage.get_identity(identity_id)?;
+    let pool = emulator.storage.get_identity_pool(&pool_id)?;
+
+    let role = match identity_login {
+        Some(identity_login) => {
+            // Credentials of an authenticated identity need a current token of the same login
+            if verify_logins(emulator, &pool, &body["Logins"])?.as_deref() != Some(identity_login.as_str()) {
+                return Err(EmulatorError::NotAuthorized("Invalid login token. Logins don't match the identity.".into()));
+            }
+            "authenticated"
+        }
+        None if pool.allow_unauthenticated => "unauthenticated",
+        None => return Err(EmulatorError::NotAuthorized("Unauthenticated access is not supported for this identity pool.".into())),
+    };
+    let role_arn = pool.roles.get(role)
+        .ok_or_else(|| EmulatorError::InvalidRequest(format!("Identity pool {} has no {} role", pool.id, role)))?;
+
+    let credentials = emulator.iam.session_credentials(role_arn, identity_id)?;
+    Ok(json!({
+        "IdentityId": identity_id,
+        "Credentials": {
+            "AccessKeyId": credentials.access_key_id,
+            "SecretKey": credentials.secret_access_key,
+            "SessionToken": credentials.session_token,
+            "Expiration": credentials.expiration
+        }
+    }))
+}
+
+/// Login (`<provider>:<subject>`) of the first of `logins` after checking every token, or
+/// `None` without logins
+fn verify_logins(emulator: &Emulator, pool: &IdentityPoolMetadata, logins: &Value) -> Result<Option<String>, EmulatorError> {
+    let Some(logins) = logins.as_object().filter(|logins| !logins.is_empty()) else {
+        return Ok(None);
+    };
+    let mut first = None;
+    for (provider, token) in logins {
+        let token = token.as_str().ok_or_else(|| EmulatorError::InvalidArgument(format!("Login token for {} must be a string", provider)))?;
+        let subject = verify_token(emulator, pool, provider, token)?;
+        first.get_or_insert_with(|| format!("{}:{}", provider, subject));
+    }
+    Ok(first)
+}
+
+/// Subject of a user-pool ID token issued by `provider`
+fn verify_token(emulator: &Emulator, pool: &IdentityPoolMetadata, provider: &str, token: &str) -> Result<String, EmulatorError> {
+    let invalid = |reason: &str| EmulatorError::NotAuthorized(format!("Invalid login token. {}", reason));
+    let configured = pool.providers.as_array().and_then(|providers| providers.iter().find(|p| p["ProviderName"] == provider))
+        .ok_or_else(|| invalid("Token is not from a supported provider of this identity pool."))?;
+    let claims = decode_claims(token).ok_or_else(|| invalid("Not a valid OpenId Connect identity token."))?;
+
+    if claims["iss"].as_str() != Some(format!("https://{}", provider).as_str()) {
+        return Err(invalid("Issuer doesn't match providerName"));
+    }
+    if claims["token_use"] != "id" {
+        return Err(invalid("Not an ID token."));
+    }
+    if let Some(client_id) = configured["ClientId"].as_str() {
+        if claims["aud"] != client_id {
+            return Err(invalid("Incorrect token audience."));
+        }
+    }
+    if claims["exp"].as_i64().is_none_or(|exp| exp <= chrono::Utc::now().timestamp()) {
+        return Err(invalid("Token expired."));
+    }
+
+    // The user must still exist in the user pool the token names
+    let user_pool_id = provider.rsplit('/').next().unwrap_or(provider);
+    let username = claims["cognito:username"].as_str().or(claims["sub"].as_str())
+        .ok_or_else(|| invalid("Token has no subject."))?;
+    emulator.storage.admin_get_user(user_pool_id, username)
+        .map_err(|_| invalid("User does not exist."))?;
+    Ok(claims["sub"].as_str().unwrap_or(username).to_string())
+}
+
+/// Payload of a JWT; the signature is not checked, as the emulator's tokens are unsigned
+fn decode_claims(token: &str) -> Option<Value> {
+    let payload = token.split('.').nth(1)?;
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+}
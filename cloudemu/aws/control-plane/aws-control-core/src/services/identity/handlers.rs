@@ -180,8 +180,9 @@ async fn initiate_auth(emulator: &Emulator, body: Value) -> Result<Value, Emulat
     let _user_data = emulator.storage.admin_get_user(client_id, username)?;
     
     // Generate Tokens
-    let access_token = generate_mock_jwt(username, "access");
-    let id_token = generate_mock_jwt(username, "id");
+    let issuer = issuer(&emulator.config.region, client_id);
+    let access_token = generate_mock_jwt(&issuer, client_id, username, "access");
+    let id_token = generate_mock_jwt(&issuer, client_id, username, "id");
     let refresh_token = "mock-refresh-token";
     
     Ok(json!({
@@ -196,13 +197,20 @@ async fn initiate_auth(emulator: &Emulator, body: Value) -> Result<Value, Emulat
     }))
 }
 
-fn generate_mock_jwt(username: &str, token_type: &str) -> String {
+/// Token issuer of a user pool, which identity pools name it by without the scheme
+pub(crate) fn issuer(region: &str, pool_id: &str) -> String {
+    format!("https://cognito-idp.{}.amazonaws.com/{}", region, pool_id)
+}
+
+fn generate_mock_jwt(issuer: &str, client_id: &str, username: &str, token_type: &str) -> String {
     let header = URL_SAFE_NO_PAD.encode(json!({"alg":"HS256","typ":"JWT"}).to_string());
     let payload = URL_SAFE_NO_PAD.encode(json!({
         "sub": username,
+        "cognito:username": username,
         "token_use": token_type,
+        "aud": client_id,
         "exp": chrono::Utc::now().timestamp() + 3600,
-        "iss": "cloudemu"
+        "iss": issuer
     }).to_string());
     format!("{}.{}.mock-signature", header, payload)
 }
@@ -0,0 +1,233 @@
+use crate::Emulator;
+use crate::error::EmulatorError;
+use aws_data_core::storage::KinesisStream;
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::info;
+
+/// Every stream has this single shard
+const SHARD_ID: &str = "shardId-000000000000";
+
+pub async fn handle_request(
+    State(emulator): State<Arc<Emulator>>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    let target = headers
+        .get("x-amz-target")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    info!("Kinesis: {}", target);
+    let action = target.split('.').next_back().unwrap_or(target);
+
+    let result = match action {
+        "CreateStream" => create_stream(&emulator, body),
+        "DeleteStream" => delete_stream(&emulator, body),
+        "DescribeStream" => describe_stream(&emulator, body),
+        "DescribeStreamSummary" => describe_stream_summary(&emulator, body),
+        "ListStreams" => list_streams(&emulator),
+        "ListShards" => list_shards(&emulator, body),
+        "PutRecord" => put_record(&emulator, body),
+        "PutRecords" => put_records(&emulator, body),
+        "GetShardIterator" => get_shard_iterator(&emulator, body),
+        "GetRecords" => get_records(&emulator, body),
+
+        _ => Err(EmulatorError::InvalidRequest(format!("Unknown or unsupported target: {}", target))),
+    };
+
+    match result {
+        Ok(json_val) => Json::<Value>(json_val).into_response(),
+        Err(e) => {
+            let json_err = json!({
+                "__type": e.code(),
+                "message": e.message()
+            });
+
+            (e.status_code(), Json::<Value>(json_err)).into_response()
+        }
+    }
+}
+
+/// Stream addressed by `StreamName` or `StreamARN`
+fn stream_name(body: &Value) -> Result<String, EmulatorError> {
+    body["StreamName"].as_str()
+        .or_else(|| body["StreamARN"].as_str().and_then(|arn| arn.rsplit_once("stream/").map(|(_, name)| name)))
+        .map(|name| name.to_string())
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing StreamName".into()))
+}
+
+fn shard_json(emulator: &Emulator, stream: &KinesisStream) -> Result<Value, EmulatorError> {
+    Ok(json!({
+        "ShardId": SHARD_ID,
+        "HashKeyRange": {
+            "StartingHashKey": "0",
+            "EndingHashKey": "340282366920938463463374607431768211455"
+        },
+        "SequenceNumberRange": {
+            "StartingSequenceNumber": "1",
+            "EndingSequenceNumber": null
+        },
+        "LatestSequenceNumber": emulator.storage.latest_kinesis_sequence(&stream.name)?.to_string()
+    }))
+}
+
+fn create_stream(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = body["StreamName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing StreamName".into()))?;
+    emulator.storage.create_kinesis_stream(name, &emulator.config.account_id, &emulator.config.region)?;
+    Ok(json!({}))
+}
+
+fn delete_stream(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.storage.delete_kinesis_stream(&stream_name(&body)?)?;
+    Ok(json!({}))
+}
+
+fn describe_stream(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let stream = emulator.storage.get_kinesis_stream(&stream_name(&body)?)?;
+    Ok(json!({
+        "StreamDescription": {
+            "StreamName": stream.name,
+            "StreamARN": stream.arn,
+            "StreamStatus": stream.status,
+            "Shards": [shard_json(emulator, &stream)?],
+            "HasMoreShards": false,
+            "RetentionPeriodHours": stream.retention_period_hours,
+            "StreamCreationTimestamp": chrono::DateTime::parse_from_rfc3339(&stream.created_at).map(|t| t.timestamp()).unwrap_or(0)
+        }
+    }))
+}
+
+fn describe_stream_summary(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let stream = emulator.storage.get_kinesis_stream(&stream_name(&body)?)?;
+    Ok(json!({
+        "StreamDescriptionSummary": {
+            "StreamName": stream.name,
+            "StreamARN": stream.arn,
+            "StreamStatus": stream.status,
+            "RetentionPeriodHours": stream.retention_period_hours,
+            "StreamCreationTimestamp": chrono::DateTime::parse_from_rfc3339(&stream.created_at).map(|t| t.timestamp()).unwrap_or(0),
+            "OpenShardCount": 1
+        }
+    }))
+}
+
+fn list_streams(emulator: &Emulator) -> Result<Value, EmulatorError> {
+    let streams = emulator.storage.list_kinesis_streams()?;
+    let summaries: Vec<Value> = streams.iter().map(|s| json!({
+        "StreamName": s.name,
+        "StreamARN": s.arn,
+        "StreamStatus": s.status
+    })).collect();
+
+    Ok(json!({
+        "StreamNames": streams.iter().map(|s| s.name.clone()).collect::<Vec<_>>(),
+        "StreamSummaries": summaries,
+        "HasMoreStreams": false
+    }))
+}
+
+fn list_shards(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let stream = emulator.storage.get_kinesis_stream(&stream_name(&body)?)?;
+    Ok(json!({ "Shards": [shard_json(emulator, &stream)?] }))
+}
+
+fn put_record(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = stream_name(&body)?;
+    let sequence = put(emulator, &name, &body)?;
+    Ok(json!({
+        "ShardId": SHARD_ID,
+        "SequenceNumber": sequence.to_string()
+    }))
+}
+
+fn put_records(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = stream_name(&body)?;
+    let entries = body["Records"].as_array().ok_or_else(|| EmulatorError::InvalidArgument("Missing Records".into()))?;
+    emulator.storage.get_kinesis_stream(&name)?;
+
+    // Entries fail independently, like the other batch APIs
+    let mut failed = 0;
+    let results: Vec<Value> = entries.iter().map(|entry| match put(emulator, &name, entry) {
+        Ok(sequence) => json!({ "ShardId": SHARD_ID, "SequenceNumber": sequence.to_string() }),
+        Err(e) => {
+            failed += 1;
+            json!({ "ErrorCode": e.code(), "ErrorMessage": e.message() })
+        }
+    }).collect();
+
+    Ok(json!({
+        "FailedRecordCount": failed,
+        "Records": results
+    }))
+}
+
+/// Append the base64 `Data` of a record entry under its `PartitionKey`
+fn put(emulator: &Emulator, stream_name: &str, entry: &Value) -> Result<u64, EmulatorError> {
+    let partition_key = entry["PartitionKey"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing PartitionKey".into()))?;
+    let data = entry["Data"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing Data".into()))?;
+    let data = general_purpose::STANDARD.decode(data)
+        .map_err(|e| EmulatorError::InvalidArgument(format!("Data is not valid base64: {}", e)))?;
+
+    emulator.storage.put_kinesis_record(stream_name, partition_key, &data)
+}
+
+/// Shard iterators encode the stream and the sequence number reading resumes after
+fn encode_iterator(stream_name: &str, after: u64) -> String {
+    general_purpose::STANDARD.encode(format!("{}:{}", stream_name, after))
+}
+
+fn decode_iterator(iterator: &str) -> Result<(String, u64), EmulatorError> {
+    let invalid = || EmulatorError::InvalidArgument(format!("Invalid ShardIterator: {}", iterator));
+    let decoded = general_purpose::STANDARD.decode(iterator).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (name, after) = decoded.rsplit_once(':').ok_or_else(invalid)?;
+    Ok((name.to_string(), after.parse().map_err(|_| invalid())?))
+}
+
+fn get_shard_iterator(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = stream_name(&body)?;
+    let iterator_type = body["ShardIteratorType"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing ShardIteratorType".into()))?;
+    let sequence = || body["StartingSequenceNumber"].as_str()
+        .and_then(|s| s.parse::<u64>().ok())
+        .ok_or_else(|| EmulatorError::InvalidArgument("Missing StartingSequenceNumber".into()));
+
+    let after = match iterator_type {
+        "TRIM_HORIZON" => 0,
+        "LATEST" => emulator.storage.latest_kinesis_sequence(&name)?,
+        "AT_SEQUENCE_NUMBER" => sequence()?.saturating_sub(1),
+        "AFTER_SEQUENCE_NUMBER" => sequence()?,
+        other => return Err(EmulatorError::InvalidArgument(format!("Unsupported ShardIteratorType: {}", other))),
+    };
+    emulator.storage.get_kinesis_stream(&name)?;
+
+    Ok(json!({ "ShardIterator": encode_iterator(&name, after) }))
+}
+
+fn get_records(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let iterator = body["ShardIterator"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing ShardIterator".into()))?;
+    let limit = body["Limit"].as_u64().unwrap_or(10000) as usize;
+    let (name, after) = decode_iterator(iterator)?;
+
+    let records = emulator.storage.get_kinesis_records(&name, after, limit)?;
+    let next = records.last().map(|r| r.sequence_number).unwrap_or(after);
+    let records: Vec<Value> = records.into_iter().map(|r| json!({
+        "SequenceNumber": r.sequence_number.to_string(),
+        "ApproximateArrivalTimestamp": r.arrival_timestamp as f64 / 1000.0,
+        "Data": general_purpose::STANDARD.encode(&r.data),
+        "PartitionKey": r.partition_key
+    })).collect();
+
+    Ok(json!({
+        "Records": records,
+        "NextShardIterator": encode_iterator(&name, next),
+        "MillisBehindLatest": 0
+    }))
+}
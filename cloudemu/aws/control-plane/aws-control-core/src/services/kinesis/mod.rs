@@ -0,0 +1,17 @@
+use aws_data_core::StorageEngine;
+
+pub mod handlers;
+
+#[derive(Clone)]
+pub struct KinesisService {
+    pub storage: StorageEngine,
+}
+
+impl KinesisService {
+    pub fn new(storage: StorageEngine) -> Self {
+        Self { storage }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,76 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use std::sync::Arc;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn test_kinesis_put_and_get_records() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let call = |action: &str, body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("Kinesis_20131202.{}", action))
+            .header("content-type", "application/x-amz-json-1.1")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<Value>(&bytes).unwrap()
+    };
+
+    let response = app.clone().oneshot(call("CreateStream", json!({ "StreamName": "clicks", "ShardCount": 1 }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // "first" and "second", base64-encoded
+    let response = app.clone().oneshot(call("PutRecord", json!({
+        "StreamName": "clicks", "PartitionKey": "user-1", "Data": "Zmlyc3Q="
+    }))).await.unwrap();
+    assert_eq!(read_json(response).await["SequenceNumber"], "1");
+
+    let response = app.clone().oneshot(call("GetShardIterator", json!({
+        "StreamName": "clicks", "ShardId": "shardId-000000000000", "ShardIteratorType": "LATEST"
+    }))).await.unwrap();
+    let latest = read_json(response).await["ShardIterator"].as_str().unwrap().to_string();
+
+    let response = app.clone().oneshot(call("PutRecords", json!({
+        "StreamARN": "arn:aws:kinesis:us-east-1:000000000000:stream/clicks",
+        "Records": [
+            { "PartitionKey": "user-2", "Data": "c2Vjb25k" },
+            { "PartitionKey": "user-3", "Data": "not base64!" }
+        ]
+    }))).await.unwrap();
+    let json = read_json(response).await;
+    assert_eq!(json["FailedRecordCount"], 1);
+    assert_eq!(json["Records"][0]["SequenceNumber"], "2");
+
+    // LATEST only sees records put after the iterator was taken
+    let response = app.clone().oneshot(call("GetRecords", json!({ "ShardIterator": latest }))).await.unwrap();
+    let json = read_json(response).await;
+    assert_eq!(json["Records"].as_array().unwrap().len(), 1);
+    assert_eq!(json["Records"][0]["Data"], "c2Vjb25k");
+    assert_eq!(json["Records"][0]["PartitionKey"], "user-2");
+
+    let response = app.clone().oneshot(call("GetRecords", json!({ "ShardIterator": json["NextShardIterator"] }))).await.unwrap();
+    assert!(read_json(response).await["Records"].as_array().unwrap().is_empty());
+
+    let response = app.clone().oneshot(call("GetShardIterator", json!({
+        "StreamName": "clicks", "ShardId": "shardId-000000000000", "ShardIteratorType": "TRIM_HORIZON"
+    }))).await.unwrap();
+    let horizon = read_json(response).await["ShardIterator"].clone();
+    let response = app.clone().oneshot(call("GetRecords", json!({ "ShardIterator": horizon, "Limit": 10 }))).await.unwrap();
+    assert_eq!(read_json(response).await["Records"].as_array().unwrap().len(), 2);
+
+    let response = app.clone().oneshot(call("DeleteStream", json!({ "StreamName": "clicks" }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app.clone().oneshot(call("DescribeStream", json!({ "StreamName": "clicks" }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
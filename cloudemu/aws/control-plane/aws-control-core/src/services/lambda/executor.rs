@@ -5,11 +5,15 @@ use tempfile::tempdir;
 use zip::ZipArchive;
 use serde_json::{Value, json};
 
+/// Environment variable through which Lambda exposes the invocation's trace context
+const TRACE_ENV: &str = "_X_AMZN_TRACE_ID";
+
 pub fn execute_lambda(
     runtime: &str,
     handler: &str,
     zip_bytes: &[u8],
     payload: &Value,
+    trace_header: Option<&str>,
 ) -> Result<Value, EmulatorError> {
     if zip_bytes.is_empty() {
         return Err(EmulatorError::InvalidArgument("Function code is missing".into()));
@@ -30,9 +34,9 @@ pub fn execute_lambda(
     let method_name = handler_parts[1];
     
     if runtime.contains("python") {
-        execute_python(tmp_dir.path(), file_name, method_name, payload)
+        execute_python(tmp_dir.path(), file_name, method_name, payload, trace_header)
     } else if runtime.contains("node") {
-        execute_nodejs(tmp_dir.path(), file_name, method_name, payload)
+        execute_nodejs(tmp_dir.path(), file_name, method_name, payload, trace_header)
     } else {
         Err(EmulatorError::InvalidArgument(format!("Unsupported runtime: {}", runtime)))
     }
@@ -43,6 +47,7 @@ fn execute_python(
     file_name: &str,
     method_name: &str,
     payload: &Value,
+    trace_header: Option<&str>,
 ) -> Result<Value, EmulatorError> {
     let payload_str = serde_json::to_string(payload).unwrap_or_default();
     
@@ -65,7 +70,11 @@ except Exception as e:
 "#
     );
     
-    let mut child = Command::new("python")
+    let mut command = Command::new("python");
+    if let Some(trace) = trace_header {
+        command.env(TRACE_ENV, trace);
+    }
+    let mut child = command
         .current_dir(path)
         .arg("-c")
         .arg(wrapper)
@@ -102,6 +111,7 @@ fn execute_nodejs(
     file_name: &str,
     method_name: &str,
     payload: &Value,
+    trace_header: Option<&str>,
 ) -> Result<Value, EmulatorError> {
     let payload_str = serde_json::to_string(payload).unwrap_or_default();
     
@@ -125,7 +135,11 @@ process.stdin.on('end', async () => {{
 "#
     );
     
-    let mut child = Command::new("node")
+    let mut command = Command::new("node");
+    if let Some(trace) = trace_header {
+        command.env(TRACE_ENV, trace);
+    }
+    let mut child = command
         .current_dir(path)
         .arg("-e")
         .arg(wrapper)
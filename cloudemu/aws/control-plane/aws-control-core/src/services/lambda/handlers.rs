@@ -21,6 +21,7 @@ pub async fn handle_request(
         let function_name = path_val.split('/')
             .find(|s| !s.is_empty() && *s != "2015-03-31" && *s != "functions")
             .unwrap_or("");
+        let trace_header = crate::services::message_attributes::trace_header(req.headers());
             
         // For invocation, we need the body
         let body_bytes = match axum::body::to_bytes(req.into_body(), 10 * 1024 * 1024).await {
@@ -30,15 +31,16 @@ pub async fn handle_request(
         
         let body_val: Value = serde_json::from_slice(&body_bytes).unwrap_or(json!({}));
 
-        return match invoke(&emulator, function_name, body_val).await {
+        return match invoke_traced(&emulator, function_name, body_val, trace_header.as_deref()).await {
             Ok(val) => Json::<Value>(val).into_response(),
             Err(e) => (e.status_code(), Json(json!({"message": e.message()}))).into_response(),
         };
     }
 
     if method == axum::http::Method::POST && path_val.ends_with("/functions") {
-         // Create function
-         let body_bytes = match axum::body::to_bytes(req.into_body(), 10 * 1024 * 1024).await {
+         // Create function; read enough for the base64 code of the largest upload the quota allows
+         let limit = emulator.limits.get().lambda_max_code_zip_bytes.div_ceil(3) * 4 + 1024 * 1024;
+         let body_bytes = match axum::body::to_bytes(req.into_body(), limit).await {
             Ok(b) => b,
             Err(_) => return (axum::http::StatusCode::BAD_REQUEST, "Invalid body").into_response(),
         };
@@ -46,7 +48,8 @@ pub async fn handle_request(
         
         return match create_function(&emulator, body_val).await {
             Ok(val) => Json::<Value>(val).into_response(),
-            Err(e) => (e.status_code(), Json(json!({"message": e.message()}))).into_response(),
+            // SDKs read the error code from x-amzn-errortype
+            Err(e) => (e.status_code(), [("x-amzn-errortype", e.code())], Json(json!({"Type": "User", "message": e.message()}))).into_response(),
         };
     }
 
@@ -56,10 +59,15 @@ pub async fn handle_request(
 
 // Special handler for POST /2015-03-31/functions/{FunctionName}/invocations
 pub async fn invoke(emulator: &Emulator, name: &str, payload: Value) -> Result<Value, EmulatorError> {
+    invoke_traced(emulator, name, payload, None).await
+}
+
+/// Invoke with the caller's trace context, exposed to the handler as `_X_AMZN_TRACE_ID`
+pub async fn invoke_traced(emulator: &Emulator, name: &str, payload: Value, trace_header: Option<&str>) -> Result<Value, EmulatorError> {
     let function = emulator.storage.get_function(name)?;
     let code_bytes = emulator.storage.get_function_code(name)?;
     
-    execute_lambda(&function.runtime, &function.handler, &code_bytes, &payload)
+    execute_lambda(&function.runtime, &function.handler, &code_bytes, &payload, trace_header)
 }
 
 pub async fn create_function(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
@@ -76,6 +84,7 @@ pub async fn create_function(emulator: &Emulator, body: Value) -> Result<Value,
     } else {
         return Err(EmulatorError::InvalidArgument("Missing Code.ZipFile (Base64 encoded zip)".into()));
     };
+    emulator.limits.check_code_size(code_bytes.len())?;
     
     let func = emulator.storage.create_function(aws_data_core::storage::CreateFunctionParams {
         name,
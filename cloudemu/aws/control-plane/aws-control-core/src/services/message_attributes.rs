@@ -0,0 +1,271 @@
+//! Message attribute and trace header handling shared by SNS, SQS and pipes.
+//!
+//! Attributes are stored in the SQS API shape (`{"name": {"DataType": "String", "StringValue": "v"}}`)
+//! and converted to the SNS notification shape or the Lambda event shape on delivery.
+
+use crate::error::EmulatorError;
+use axum::http::HeaderMap;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use md5::{Digest, Md5};
+use serde_json::{json, Map, Value};
+
+/// HTTP header carrying the X-Ray trace context of a request
+pub const TRACE_HEADER: &str = "x-amzn-trace-id";
+
+/// SQS system attribute holding the propagated trace context
+pub const AWS_TRACE_HEADER: &str = "AWSTraceHeader";
+
+/// Maximum number of message attributes per message (matches SQS and SNS)
+const MAX_ATTRIBUTES: usize = 10;
+
+/// Longest attribute name and data type
+const MAX_NAME_LENGTH: usize = 256;
+
+/// Most significant digits of a `Number` attribute
+const MAX_NUMBER_DIGITS: usize = 38;
+
+/// Trace context of the incoming request, if the caller sent one
+pub fn trace_header(headers: &HeaderMap) -> Option<String> {
+    headers.get(TRACE_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Validate `MessageAttributes` in the SQS API shape, returning the stored JSON (None when empty)
+pub fn validate(attributes: &Value) -> Result<Option<String>, EmulatorError> {
+    let map = match attributes {
+        Value::Null => return Ok(None),
+        Value::Object(map) if map.is_empty() => return Ok(None),
+        Value::Object(map) => map,
+        _ => return Err(EmulatorError::InvalidArgument("MessageAttributes must be a map".into())),
+    };
+    if map.len() > MAX_ATTRIBUTES {
+        return Err(invalid(format!("Number of message attributes [{}] exceeds the allowed maximum [{}].", map.len(), MAX_ATTRIBUTES)));
+    }
+
+    for (name, attr) in map {
+        check_name(name)?;
+        let data_type = attr["DataType"].as_str().filter(|t| !t.is_empty())
+            .ok_or_else(|| invalid(format!("The message attribute '{}' must contain a non-empty message attribute type.", name)))?;
+        if data_type.len() > MAX_NAME_LENGTH {
+            return Err(invalid(format!("The message attribute '{}' has a type longer than {} characters.", name, MAX_NAME_LENGTH)));
+        }
+        let value_key = match data_type.split('.').next() {
+            Some("String") | Some("Number") => "StringValue",
+            Some("Binary") => "BinaryValue",
+            _ => return Err(invalid(format!("The type of message (user) attribute '{}' is invalid. You must use only the following supported type prefixes: Binary, Number, String.", name))),
+        };
+        let value = attr[value_key].as_str().filter(|v| !v.is_empty())
+            .ok_or_else(|| invalid(format!("The message attribute '{}' must contain non-empty message attribute value for message attribute type '{}'.", name, data_type)))?;
+        let valid = match value_key {
+            "BinaryValue" => STANDARD.decode(value).is_ok(),
+            _ if data_type.starts_with("Number") => is_number(value),
+            _ => value.chars().all(is_valid_char),
+        };
+        if !valid {
+            return Err(invalid(format!("The message attribute '{}' with type '{}' has an invalid value.", name, data_type)));
+        }
+    }
+    Ok(Some(attributes.to_string()))
+}
+
+fn invalid(message: String) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "InvalidParameterValue", message }
+}
+
+/// Names use letters, digits, `_`, `-` and `.`, without leading, trailing or repeated
+/// periods, and may not claim the reserved `AWS.` and `Amazon.` prefixes
+fn check_name(name: &str) -> Result<(), EmulatorError> {
+    let lower = name.to_ascii_lowercase();
+    let reason = if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        format!("must be 1 to {} characters long", MAX_NAME_LENGTH)
+    } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        "can only contain alphanumeric characters, hyphens, underscores and periods".to_string()
+    } else if name.starts_with('.') || name.ends_with('.') || name.contains("..") {
+        "can't start or end with a period or contain successive periods".to_string()
+    } else if lower.starts_with("aws.") || lower.starts_with("amazon.") {
+        "can't start with the reserved prefixes AWS. or Amazon.".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(invalid(format!("Message (user) attribute name '{}' is invalid: it {}.", name, reason)))
+}
+
+/// Decimal numbers of up to 38 significant digits, between -10^126 and 10^126
+fn is_number(value: &str) -> bool {
+    let (mantissa, exponent) = match value.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => match exponent.parse::<i32>() {
+            Ok(exponent) => (mantissa, exponent),
+            Err(_) => return false,
+        },
+        None => (value, 0),
+    };
+    let unsigned = mantissa.strip_prefix(['-', '+']).unwrap_or(mantissa);
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if whole.is_empty() && fraction.is_empty() || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let digits = format!("{}{}", whole, fraction);
+    let significant = digits.trim_start_matches('0');
+    if significant.is_empty() {
+        return true;
+    }
+    if significant.trim_end_matches('0').len() > MAX_NUMBER_DIGITS {
+        return false;
+    }
+    // Power of ten of the first significant digit
+    let magnitude = whole.len() as i32 - (digits.len() - significant.len()) as i32 - 1 + exponent;
+    (-128..126).contains(&magnitude)
+}
+
+/// Characters SQS accepts in message bodies and string attributes: tab, line feed, carriage
+/// return and Unicode from U+0020 apart from the surrogates, U+FFFE and U+FFFF
+pub fn is_valid_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Hex MD5 of a message body, as SQS returns in `MD5OfMessageBody`
+pub fn md5_of_body(body: &str) -> String {
+    hex(Md5::digest(body.as_bytes()).as_slice())
+}
+
+/// Hex MD5 of message attributes in the SQS API shape, as SQS returns in
+/// `MD5OfMessageAttributes`: each attribute, by name, as its length-prefixed name and data
+/// type, a transport type byte and its length-prefixed value
+pub fn md5_of_attributes(attributes: &Value) -> Option<String> {
+    let map = attributes.as_object().filter(|map| !map.is_empty())?;
+    let mut names: Vec<&String> = map.keys().collect();
+    names.sort();
+    let mut hasher = Md5::new();
+    let field = |hasher: &mut Md5, bytes: &[u8]| {
+        hasher.update((bytes.len() as u32).to_be_bytes());
+        hasher.update(bytes);
+    };
+    for name in names {
+        let attr = &map[name];
+        let data_type = attr["DataType"].as_str().unwrap_or_default();
+        field(&mut hasher, name.as_bytes());
+        field(&mut hasher, data_type.as_bytes());
+        if data_type.starts_with("Binary") {
+            hasher.update([2]);
+            field(&mut hasher, &STANDARD.decode(attr["BinaryValue"].as_str().unwrap_or_default()).unwrap_or_default());
+        } else {
+            hasher.update([1]);
+            field(&mut hasher, attr["StringValue"].as_str().unwrap_or_default().as_bytes());
+        }
+    }
+    Some(hex(hasher.finalize().as_slice()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Keep the attributes selected by `MessageAttributeNames` (`All`, `.*`, `prefix.*` or exact names)
+pub fn select(stored: Option<&str>, names: &Value) -> Option<Value> {
+    let attributes: Map<String, Value> = serde_json::from_str(stored?).ok()?;
+    let names: Vec<&str> = names.as_array()?.iter().filter_map(Value::as_str).collect();
+
+    let selected: Map<String, Value> = attributes.into_iter()
+        .filter(|(name, _)| names.iter().any(|n| match *n {
+            "All" | ".*" => true,
+            n if n.ends_with(".*") => name.starts_with(&n[..n.len() - 1]),
+            n => n == name,
+        }))
+        .collect();
+    (!selected.is_empty()).then_some(Value::Object(selected))
+}
+
+/// SNS notification shape: `{"name": {"Type": "String", "Value": "v"}}`
+pub fn to_sns(stored: Option<&str>) -> Value {
+    let attributes: Map<String, Value> = stored.and_then(|s| serde_json::from_str(s).ok()).unwrap_or_default();
+    attributes.into_iter()
+        .map(|(name, attr)| {
+            let value = attr.get("StringValue").or_else(|| attr.get("BinaryValue")).cloned().unwrap_or(Value::Null);
+            (name, json!({ "Type": attr["DataType"], "Value": value }))
+        })
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// Lambda SQS event shape: `{"name": {"stringValue": "v", "dataType": "String", ...}}`
+pub fn to_lambda(stored: Option<&str>) -> Value {
+    let attributes: Map<String, Value> = stored.and_then(|s| serde_json::from_str(s).ok()).unwrap_or_default();
+    attributes.into_iter()
+        .map(|(name, attr)| (name, json!({
+            "stringValue": attr["StringValue"],
+            "binaryValue": attr["BinaryValue"],
+            "stringListValues": [],
+            "binaryListValues": [],
+            "dataType": attr["DataType"]
+        })))
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// System attributes JSON carrying the trace header, if any
+pub fn system_attributes(trace_header: Option<&str>) -> Option<String> {
+    trace_header.map(|trace| json!({ AWS_TRACE_HEADER: trace }).to_string())
+}
+
+/// Trace header stored in a message's system attributes
+pub fn stored_trace_header(system_attributes: Option<&str>) -> Option<String> {
+    let attributes: Value = serde_json::from_str(system_attributes?).ok()?;
+    attributes[AWS_TRACE_HEADER].as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_convert() {
+        let attributes = json!({
+            "tenant": { "DataType": "String", "StringValue": "acme" },
+            "retry.count": { "DataType": "Number", "StringValue": "3" }
+        });
+        let stored = validate(&attributes).unwrap();
+        assert!(validate(&json!({ "bad": { "DataType": "String" } })).is_err());
+        assert!(validate(&json!({ "bad": { "DataType": "Date", "StringValue": "x" } })).is_err());
+        assert_eq!(validate(&json!({})).unwrap(), None);
+
+        let selected = select(stored.as_deref(), &json!(["retry.*"])).unwrap();
+        assert_eq!(selected.as_object().unwrap().len(), 1);
+        assert_eq!(select(stored.as_deref(), &json!(["All"])).unwrap(), attributes);
+        assert!(select(stored.as_deref(), &json!(["missing"])).is_none());
+
+        assert_eq!(to_sns(stored.as_deref())["tenant"], json!({ "Type": "String", "Value": "acme" }));
+        assert_eq!(to_lambda(stored.as_deref())["retry.count"]["stringValue"], "3");
+
+        let system = system_attributes(Some("Root=1-abc")).unwrap();
+        assert_eq!(stored_trace_header(Some(&system)).as_deref(), Some("Root=1-abc"));
+    }
+
+    #[test]
+    fn test_names_and_values() {
+        for name in ["AWS.tenant", "amazon.x", ".tenant", "a..b", "tenant.", "white space"] {
+            assert!(validate(&json!({ name: { "DataType": "String", "StringValue": "v" } })).is_err(), "{}", name);
+        }
+        assert!(validate(&json!({ "a-b_c.d": { "DataType": "String.custom", "StringValue": "v" } })).is_ok());
+
+        for number in ["0", "-1.5", "+3", "1e10", "0.000", "12345678901234567890123456789012345678", "1e125"] {
+            assert!(is_number(number), "{}", number);
+        }
+        for number in ["", "-", "1.2.3", "0x10", "1e", "123456789012345678901234567890123456789", "1e126", "1e-129"] {
+            assert!(!is_number(number), "{}", number);
+        }
+        assert!(validate(&json!({ "b": { "DataType": "Binary", "BinaryValue": "AAEC" } })).is_ok());
+        assert!(validate(&json!({ "s": { "DataType": "String", "StringValue": "bell \u{7}" } })).is_err());
+    }
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(md5_of_body("Hello Queue"), "248425fd3a51f61916c101ce303575e5");
+        let attributes = json!({
+            "tenant": { "DataType": "String", "StringValue": "acme" },
+            "priority": { "DataType": "Number", "StringValue": "1" }
+        });
+        assert_eq!(md5_of_attributes(&attributes).as_deref(), Some("0402840f12f8bbfdf019024494b42baf"));
+        assert_eq!(md5_of_attributes(&json!({})), None);
+    }
+}
@@ -1,5 +1,7 @@
 //! Cloud service implementations
 
+pub mod message_attributes;
+
 #[cfg(feature = "s3")]
 pub mod s3;
 
@@ -62,3 +64,12 @@ pub mod elasticache;
 
 #[cfg(feature = "ecr")]
 pub mod ecr;
+
+#[cfg(feature = "pipes")]
+pub mod pipes;
+
+#[cfg(feature = "cloudtrail")]
+pub mod cloudtrail;
+
+#[cfg(feature = "kinesis")]
+pub mod kinesis;
@@ -0,0 +1,378 @@
+//! CloudWatch Logs filter pattern matching
+//!
+//! Supports the term syntax (`ERROR`, `"exact phrase"`, `?optional`, `-excluded`)
+//! and JSON selectors (`{ $.level = "error" && $.latency > 100 }`).
+
+use crate::error::EmulatorError;
+use serde_json::Value;
+
+/// A parsed filter pattern
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterPattern {
+    /// Empty pattern: matches every event
+    All,
+    /// Term pattern: all required terms present, at least one optional term (if any), no excluded term
+    Terms {
+        required: Vec<String>,
+        optional: Vec<String>,
+        excluded: Vec<String>,
+    },
+    /// JSON selector: any group matches when all of its conditions match
+    Json(Vec<Vec<JsonCondition>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    IsNull,
+    NotExists,
+    IsTrue,
+    IsFalse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    None,
+    Number(f64),
+    Text(String),
+}
+
+/// Single `$.path <op> value` comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonCondition {
+    path: Vec<PathSegment>,
+    op: Operator,
+    operand: Operand,
+}
+
+impl FilterPattern {
+    /// Parse a filter pattern
+    pub fn parse(pattern: &str) -> Result<Self, EmulatorError> {
+        let trimmed = pattern.trim();
+        if trimmed.is_empty() || trimmed == "\"\"" {
+            return Ok(FilterPattern::All);
+        }
+
+        if trimmed.starts_with('[') {
+            return Err(EmulatorError::InvalidArgument(
+                "Space-delimited filter patterns are not supported".into()
+            ));
+        }
+
+        if let Some(inner) = trimmed.strip_prefix('{') {
+            let inner = inner.strip_suffix('}')
+                .ok_or_else(|| EmulatorError::InvalidArgument(format!("Invalid filter pattern: {}", pattern)))?;
+
+            let groups = split_outside_quotes(inner, "||")
+                .into_iter()
+                .map(|group| {
+                    split_outside_quotes(&group, "&&")
+                        .into_iter()
+                        .map(|cond| parse_condition(cond.trim()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(FilterPattern::Json(groups));
+        }
+
+        let mut required = Vec::new();
+        let mut optional = Vec::new();
+        let mut excluded = Vec::new();
+        for token in tokenize_terms(trimmed) {
+            if let Some(term) = token.strip_prefix('?') {
+                optional.push(unquote(term));
+            } else if let Some(term) = token.strip_prefix('-') {
+                excluded.push(unquote(term));
+            } else {
+                required.push(unquote(&token));
+            }
+        }
+
+        Ok(FilterPattern::Terms { required, optional, excluded })
+    }
+
+    /// Check whether a log message matches the pattern
+    pub fn matches(&self, message: &str) -> bool {
+        match self {
+            FilterPattern::All => true,
+            FilterPattern::Terms { required, optional, excluded } => {
+                required.iter().all(|t| message.contains(t.as_str()))
+                    && (optional.is_empty() || optional.iter().any(|t| message.contains(t.as_str())))
+                    && !excluded.iter().any(|t| message.contains(t.as_str()))
+            }
+            FilterPattern::Json(groups) => {
+                let doc: Value = match serde_json::from_str(message) {
+                    Ok(v) => v,
+                    Err(_) => return false,
+                };
+                groups.iter().any(|group| group.iter().all(|cond| cond.evaluate(&doc)))
+            }
+        }
+    }
+}
+
+impl JsonCondition {
+    fn evaluate(&self, doc: &Value) -> bool {
+        let mut current = Some(doc);
+        for segment in &self.path {
+            current = match (current, segment) {
+                (Some(v), PathSegment::Key(k)) => v.get(k),
+                (Some(v), PathSegment::Index(i)) => v.get(*i),
+                (None, _) => None,
+            };
+        }
+
+        match (&self.op, current) {
+            (Operator::NotExists, value) => value.is_none(),
+            (_, None) => false,
+            (Operator::IsNull, Some(v)) => v.is_null(),
+            (Operator::IsTrue, Some(v)) => v.as_bool() == Some(true),
+            (Operator::IsFalse, Some(v)) => v.as_bool() == Some(false),
+            (op, Some(v)) => match &self.operand {
+                Operand::Number(n) => match value_as_f64(v) {
+                    Some(actual) => match op {
+                        Operator::Eq => actual == *n,
+                        Operator::Ne => actual != *n,
+                        Operator::Lt => actual < *n,
+                        Operator::Le => actual <= *n,
+                        Operator::Gt => actual > *n,
+                        Operator::Ge => actual >= *n,
+                        _ => false,
+                    },
+                    None => false,
+                },
+                Operand::Text(expected) => match v.as_str() {
+                    Some(actual) => match op {
+                        Operator::Eq => wildcard_match(expected, actual),
+                        Operator::Ne => !wildcard_match(expected, actual),
+                        _ => false,
+                    },
+                    None => false,
+                },
+                Operand::None => false,
+            },
+        }
+    }
+}
+
+fn value_as_f64(v: &Value) -> Option<f64> {
+    v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn parse_condition(cond: &str) -> Result<JsonCondition, EmulatorError> {
+    let invalid = || EmulatorError::InvalidArgument(format!("Invalid filter condition: {}", cond));
+
+    if !cond.starts_with("$.") {
+        return Err(invalid());
+    }
+
+    let upper = cond.to_ascii_uppercase();
+    for (suffix, op) in [
+        (" IS NULL", Operator::IsNull),
+        (" NOT EXISTS", Operator::NotExists),
+        (" IS TRUE", Operator::IsTrue),
+        (" IS FALSE", Operator::IsFalse),
+    ] {
+        if upper.ends_with(suffix) {
+            let path = parse_path(cond[..cond.len() - suffix.len()].trim()).ok_or_else(invalid)?;
+            return Ok(JsonCondition { path, op, operand: Operand::None });
+        }
+    }
+
+    let op_start = cond.find(['=', '!', '<', '>']).ok_or_else(invalid)?;
+    let rest = &cond[op_start..];
+    let (op, op_len) = if rest.starts_with("!=") {
+        (Operator::Ne, 2)
+    } else if rest.starts_with("<=") {
+        (Operator::Le, 2)
+    } else if rest.starts_with(">=") {
+        (Operator::Ge, 2)
+    } else if rest.starts_with('=') {
+        (Operator::Eq, 1)
+    } else if rest.starts_with('<') {
+        (Operator::Lt, 1)
+    } else if rest.starts_with('>') {
+        (Operator::Gt, 1)
+    } else {
+        return Err(invalid());
+    };
+
+    let path = parse_path(cond[..op_start].trim()).ok_or_else(invalid)?;
+    let raw = rest[op_len..].trim();
+    if raw.is_empty() {
+        return Err(invalid());
+    }
+
+    let operand = if raw.starts_with('"') {
+        Operand::Text(unquote(raw))
+    } else if let Ok(n) = raw.parse::<f64>() {
+        Operand::Number(n)
+    } else {
+        Operand::Text(raw.to_string())
+    };
+
+    if matches!(operand, Operand::Text(_)) && !matches!(op, Operator::Eq | Operator::Ne) {
+        return Err(invalid());
+    }
+
+    Ok(JsonCondition { path, op, operand })
+}
+
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let body = path.strip_prefix("$.")?;
+    let mut segments = Vec::new();
+    for part in body.split('.') {
+        let (key, mut indexes) = match part.find('[') {
+            Some(i) => (&part[..i], &part[i..]),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key.to_string()));
+        }
+        while let Some(stripped) = indexes.strip_prefix('[') {
+            let end = stripped.find(']')?;
+            segments.push(PathSegment::Index(stripped[..end].parse().ok()?));
+            indexes = &stripped[end + 1..];
+        }
+        if !indexes.is_empty() {
+            return None;
+        }
+    }
+    if segments.is_empty() { None } else { Some(segments) }
+}
+
+/// Split on a separator, ignoring separators inside double quotes
+fn split_outside_quotes(s: &str, sep: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut rest = s;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        if !in_quotes && rest.starts_with(sep) {
+            parts.push(std::mem::take(&mut current));
+            rest = &rest[sep.len()..];
+            continue;
+        }
+        current.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    parts.push(current);
+    parts
+}
+
+/// Split a term pattern on whitespace, keeping quoted phrases together
+fn tokenize_terms(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn unquote(s: &str) -> String {
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+        .to_string()
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == text;
+    }
+
+    let parts: Vec<&str> = pattern.split('*').collect();
+    let mut remaining = text;
+
+    if let Some(first) = parts.first() {
+        match remaining.strip_prefix(first) {
+            Some(r) => remaining = r,
+            None => return false,
+        }
+    }
+    for part in &parts[1..parts.len() - 1] {
+        match remaining.find(part) {
+            Some(i) => remaining = &remaining[i + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.ends_with(parts[parts.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_term_patterns() {
+        assert!(FilterPattern::parse("").unwrap().matches("anything"));
+
+        let p = FilterPattern::parse("ERROR").unwrap();
+        assert!(p.matches("[ERROR] disk full"));
+        assert!(!p.matches("[INFO] all good"));
+
+        let p = FilterPattern::parse("ERROR -timeout").unwrap();
+        assert!(p.matches("ERROR disk full"));
+        assert!(!p.matches("ERROR timeout contacting db"));
+
+        let p = FilterPattern::parse("?ERROR ?WARN").unwrap();
+        assert!(p.matches("WARN low memory"));
+        assert!(!p.matches("INFO started"));
+
+        let p = FilterPattern::parse("\"disk full\"").unwrap();
+        assert!(p.matches("ERROR disk full"));
+        assert!(!p.matches("disk is full"));
+    }
+
+    #[test]
+    fn test_json_patterns() {
+        let p = FilterPattern::parse(r#"{ $.level = "error" && $.latency > 100 }"#).unwrap();
+        assert!(p.matches(r#"{"level": "error", "latency": 250}"#));
+        assert!(!p.matches(r#"{"level": "error", "latency": 50}"#));
+        assert!(!p.matches("not json"));
+
+        let p = FilterPattern::parse(r#"{ $.user.id = "adm*" || $.tags[0] = "audit" }"#).unwrap();
+        assert!(p.matches(r#"{"user": {"id": "admin-1"}}"#));
+        assert!(p.matches(r#"{"tags": ["audit"]}"#));
+        assert!(!p.matches(r#"{"user": {"id": "guest"}}"#));
+
+        let p = FilterPattern::parse("{ $.error NOT EXISTS }").unwrap();
+        assert!(p.matches(r#"{"ok": true}"#));
+
+        assert!(FilterPattern::parse("{ $.level > \"x\" }").is_err());
+        assert!(FilterPattern::parse("[ip, user]").is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::Emulator;
 use crate::error::EmulatorError;
-use aws_data_core::storage::{MetricMetadata, LogEventMetadata};
+use super::filter::FilterPattern;
+use aws_data_core::storage::{MetricMetadata, LogEventMetadata, SubscriptionFilterMetadata};
 use axum::{
     extract::State,
     http::HeaderMap,
@@ -9,7 +10,7 @@ use axum::{
 };
 use serde_json::{json, Value};
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 pub async fn handle_request(
     State(emulator): State<Arc<Emulator>>,
@@ -35,6 +36,9 @@ pub async fn handle_request(
         "CreateLogStream" => create_log_stream(&emulator, body).await,
         "PutLogEvents" => put_log_events(&emulator, body).await,
         "GetLogEvents" => get_log_events(&emulator, body).await,
+        "PutSubscriptionFilter" => put_subscription_filter(&emulator, body).await,
+        "DescribeSubscriptionFilters" => describe_subscription_filters(&emulator, body).await,
+        "DeleteSubscriptionFilter" => delete_subscription_filter(&emulator, body).await,
         
         _ => Err(EmulatorError::InvalidRequest(format!("Unknown or unsupported target: {}", target))),
     };
@@ -135,7 +139,8 @@ async fn put_log_events(emulator: &Emulator, body: Value) -> Result<Value, Emula
         });
     }
 
-    emulator.storage.put_log_events(group_name, stream_name, events)?;
+    emulator.storage.put_log_events(group_name, stream_name, events.clone())?;
+    forward_to_subscriptions(emulator, group_name, stream_name, &events).await;
     
     Ok(json!({
         "nextSequenceToken": "stub-token"
@@ -160,3 +165,177 @@ async fn get_log_events(emulator: &Emulator, body: Value) -> Result<Value, Emula
         "nextBackwardToken": "stub-token"
     }))
 }
+
+// --- Subscription Filters ---
+
+async fn put_subscription_filter(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let group_name = body["logGroupName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing logGroupName".into()))?;
+    let filter_name = body["filterName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing filterName".into()))?;
+    let filter_pattern = body["filterPattern"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing filterPattern".into()))?;
+    let destination_arn = body["destinationArn"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing destinationArn".into()))?;
+    
+    // Validate both pattern and destination up front so delivery never has to
+    FilterPattern::parse(filter_pattern)?;
+    if let SubscriptionDestination::Kinesis(stream) = SubscriptionDestination::from_arn(destination_arn)? {
+        emulator.storage.get_kinesis_stream(&stream)?;
+    }
+    
+    emulator.storage.put_subscription_filter(&SubscriptionFilterMetadata {
+        log_group_name: group_name.to_string(),
+        filter_name: filter_name.to_string(),
+        filter_pattern: filter_pattern.to_string(),
+        destination_arn: destination_arn.to_string(),
+        role_arn: body["roleArn"].as_str().map(|s| s.to_string()),
+        distribution: body["distribution"].as_str().unwrap_or("ByLogStream").to_string(),
+        created_at: chrono::Utc::now().timestamp_millis().to_string(),
+    })?;
+    
+    Ok(json!({}))
+}
+
+async fn describe_subscription_filters(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let group_name = body["logGroupName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing logGroupName".into()))?;
+    let prefix = body["filterNamePrefix"].as_str();
+    
+    let filters = emulator.storage.describe_subscription_filters(group_name, prefix)?;
+    let filter_list: Vec<Value> = filters.into_iter().map(|f| {
+        json!({
+            "filterName": f.filter_name,
+            "logGroupName": f.log_group_name,
+            "filterPattern": f.filter_pattern,
+            "destinationArn": f.destination_arn,
+            "roleArn": f.role_arn,
+            "distribution": f.distribution,
+            "creationTime": f.created_at.parse::<i64>().unwrap_or(0)
+        })
+    }).collect();
+    
+    Ok(json!({
+        "subscriptionFilters": filter_list
+    }))
+}
+
+async fn delete_subscription_filter(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let group_name = body["logGroupName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing logGroupName".into()))?;
+    let filter_name = body["filterName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing filterName".into()))?;
+    
+    emulator.storage.delete_subscription_filter(group_name, filter_name)?;
+    Ok(json!({}))
+}
+
+/// Where a subscription filter delivers matching events
+enum SubscriptionDestination {
+    Lambda(String),
+    Kinesis(String),
+}
+
+impl SubscriptionDestination {
+    fn from_arn(arn: &str) -> Result<Self, EmulatorError> {
+        let parts: Vec<&str> = arn.split(':').collect();
+        match parts.get(2) {
+            Some(&"lambda") => parts.get(6)
+                .map(|name| SubscriptionDestination::Lambda(name.to_string()))
+                .ok_or_else(|| EmulatorError::InvalidArgument(format!("Invalid Lambda ARN: {}", arn))),
+            Some(&"kinesis") => parts.get(5)
+                .and_then(|resource| resource.strip_prefix("stream/"))
+                .map(|name| SubscriptionDestination::Kinesis(name.to_string()))
+                .ok_or_else(|| EmulatorError::InvalidArgument(format!("Invalid Kinesis stream ARN: {}", arn))),
+            _ => Err(EmulatorError::InvalidArgument(
+                format!("Unsupported subscription destination: {}", arn)
+            )),
+        }
+    }
+}
+
+/// Forward events matching each subscription filter of the log group to its destination.
+/// Payloads use the CloudWatch Logs format: gzipped JSON, base64-encoded for Lambda.
+/// The events are already stored, so delivery failures are logged rather than returned.
+async fn forward_to_subscriptions(
+    emulator: &Emulator,
+    group_name: &str,
+    stream_name: &str,
+    events: &[LogEventMetadata],
+) {
+    let filters = match emulator.storage.describe_subscription_filters(group_name, None) {
+        Ok(filters) => filters,
+        Err(e) => {
+            warn!("Logs: reading subscription filters of {} failed: {}", group_name, e);
+            return;
+        }
+    };
+    
+    for filter in filters {
+        if let Err(e) = forward_to_subscription(emulator, group_name, stream_name, &filter, events).await {
+            warn!("Logs: subscription delivery to {} failed: {}", filter.destination_arn, e);
+        }
+    }
+}
+
+/// Deliver the events matching one subscription filter
+async fn forward_to_subscription(
+    emulator: &Emulator,
+    group_name: &str,
+    stream_name: &str,
+    filter: &SubscriptionFilterMetadata,
+    events: &[LogEventMetadata],
+) -> Result<(), EmulatorError> {
+    let pattern = FilterPattern::parse(&filter.filter_pattern)?;
+    let matched: Vec<Value> = events.iter()
+        .filter(|e| pattern.matches(&e.message))
+        .map(|e| json!({
+            "id": uuid::Uuid::new_v4().simple().to_string(),
+            "timestamp": e.timestamp.parse::<i64>().unwrap_or(0),
+            "message": e.message
+        }))
+        .collect();
+    if matched.is_empty() {
+        return Ok(());
+    }
+    
+    let message = json!({
+        "messageType": "DATA_MESSAGE",
+        "owner": emulator.config.account_id,
+        "logGroup": group_name,
+        "logStream": stream_name,
+        "subscriptionFilters": [filter.filter_name],
+        "logEvents": matched
+    });
+    let data = gzip(message.to_string().as_bytes())?;
+    
+    match SubscriptionDestination::from_arn(&filter.destination_arn)? {
+        SubscriptionDestination::Lambda(function_name) => {
+            invoke_subscriber(emulator, &function_name, &data).await;
+        }
+        SubscriptionDestination::Kinesis(stream) => {
+            let partition_key = format!("{}/{}", group_name, stream_name);
+            emulator.storage.put_kinesis_record(&stream, &partition_key, &data)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "lambda")]
+async fn invoke_subscriber(emulator: &Emulator, function_name: &str, data: &[u8]) {
+    use base64::{Engine as _, engine::general_purpose};
+    
+    let payload = json!({ "awslogs": { "data": general_purpose::STANDARD.encode(data) } });
+    // Delivery failures do not fail PutLogEvents, matching the asynchronous AWS behaviour
+    if let Err(e) = crate::services::lambda::handlers::invoke(emulator, function_name, payload).await {
+        warn!("Logs: subscription delivery to {} failed: {}", function_name, e);
+    }
+}
+
+#[cfg(not(feature = "lambda"))]
+async fn invoke_subscriber(_emulator: &Emulator, function_name: &str, _data: &[u8]) {
+    warn!("Logs: Lambda support is disabled, dropping subscription delivery to {}", function_name);
+}
+
+fn gzip(data: &[u8]) -> Result<Vec<u8>, EmulatorError> {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+    
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
@@ -1,4 +1,5 @@
 pub mod service;
 pub mod handlers;
+pub mod filter;
 
 pub use service::MonitoringService;
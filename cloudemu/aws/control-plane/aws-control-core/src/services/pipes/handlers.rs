@@ -0,0 +1,232 @@
+use crate::Emulator;
+use crate::error::EmulatorError;
+use aws_data_core::storage::Pipe;
+use axum::{
+    extract::State,
+    http::{Method, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::info;
+
+/// REST entry point for `/v1/pipes[/{Name}[/start|/stop]]`
+pub async fn handle_request(
+    State(emulator): State<Arc<Emulator>>,
+    method: Method,
+    uri: Uri,
+    body: String,
+) -> Response {
+    let segments: Vec<&str> = uri.path()
+        .trim_start_matches("/v1/pipes")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+    info!("Pipes: {} {}", method, uri.path());
+
+    let result = match (method, segments.as_slice()) {
+        (Method::GET, []) => list_pipes(&emulator, uri.query().unwrap_or("")),
+        (Method::POST, [name]) => parse_body(&body).and_then(|b| create_pipe(&emulator, name, b)),
+        (Method::GET, [name]) => describe_pipe(&emulator, name),
+        (Method::PUT, [name]) => parse_body(&body).and_then(|b| update_pipe(&emulator, name, b)),
+        (Method::DELETE, [name]) => delete_pipe(&emulator, name),
+        (Method::POST, [name, "start"]) => set_state(&emulator, name, "RUNNING"),
+        (Method::POST, [name, "stop"]) => set_state(&emulator, name, "STOPPED"),
+        _ => Err(EmulatorError::InvalidRequest(format!("Unsupported Pipes operation: {}", uri.path()))),
+    };
+
+    match result {
+        Ok(json_val) => Json::<Value>(json_val).into_response(),
+        Err(e) => {
+            let json_err = json!({
+                "__type": e.code(),
+                "message": e.message()
+            });
+            (e.status_code(), Json::<Value>(json_err)).into_response()
+        }
+    }
+}
+
+fn parse_body(body: &str) -> Result<Value, EmulatorError> {
+    if body.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
+/// Serialize an optional JSON object field back to its string form for storage
+fn json_field(body: &Value, key: &str) -> Option<String> {
+    body.get(key).filter(|v| !v.is_null()).map(|v| v.to_string())
+}
+
+fn validate(pipe: &Pipe) -> Result<(), EmulatorError> {
+    super::poller::Source::parse(&pipe.source)?;
+    super::poller::validate_target(&pipe.target)?;
+    if let Some(enrichment) = &pipe.enrichment {
+        if enrichment.split(':').nth(2) != Some("lambda") {
+            return Err(EmulatorError::InvalidArgument(
+                format!("Unsupported enrichment {}; expected a Lambda function", enrichment)
+            ));
+        }
+    }
+    super::poller::filter_patterns(pipe.filter_criteria.as_deref())?;
+    Ok(())
+}
+
+fn desired_state(body: &Value, default: &str) -> Result<String, EmulatorError> {
+    match body["DesiredState"].as_str().unwrap_or(default) {
+        state @ ("RUNNING" | "STOPPED") => Ok(state.to_string()),
+        other => Err(EmulatorError::InvalidArgument(format!("Invalid DesiredState: {}", other))),
+    }
+}
+
+/// Start or stop the poller so it follows the pipe's desired state
+fn apply_state(emulator: &Arc<Emulator>, pipe: &Pipe) {
+    if pipe.desired_state == "RUNNING" {
+        emulator.pipes.start_poller(emulator.clone(), &pipe.name);
+    } else {
+        emulator.pipes.stop_poller(&pipe.name);
+    }
+}
+
+fn pipe_summary(pipe: &Pipe) -> Value {
+    json!({
+        "Arn": pipe.arn,
+        "Name": pipe.name,
+        "CurrentState": pipe.current_state,
+        "DesiredState": pipe.desired_state,
+        "CreationTime": pipe.created_at,
+        "LastModifiedTime": pipe.updated_at
+    })
+}
+
+fn create_pipe(emulator: &Arc<Emulator>, name: &str, body: Value) -> Result<Value, EmulatorError> {
+    let source = body["Source"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing Source".into()))?;
+    let target = body["Target"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing Target".into()))?;
+    let role_arn = body["RoleArn"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing RoleArn".into()))?;
+    let desired_state = desired_state(&body, "RUNNING")?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let pipe = Pipe {
+        name: name.to_string(),
+        arn: format!("arn:aws:pipes:{}:{}:pipe/{}", emulator.config.region, emulator.config.account_id, name),
+        source: source.to_string(),
+        target: target.to_string(),
+        enrichment: body["Enrichment"].as_str().map(|s| s.to_string()),
+        role_arn: role_arn.to_string(),
+        description: body["Description"].as_str().map(|s| s.to_string()),
+        filter_criteria: json_field(&body, "FilterCriteria"),
+        source_parameters: json_field(&body, "SourceParameters"),
+        target_parameters: json_field(&body, "TargetParameters"),
+        current_state: desired_state.clone(),
+        desired_state,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    validate(&pipe)?;
+    let checkpoint = super::poller::starting_checkpoint(emulator, &pipe)?;
+
+    emulator.storage.create_pipe(&pipe)?;
+    if let Some(checkpoint) = checkpoint {
+        emulator.storage.set_pipe_checkpoint(name, checkpoint)?;
+    }
+    apply_state(emulator, &pipe);
+    Ok(pipe_summary(&pipe))
+}
+
+fn describe_pipe(emulator: &Emulator, name: &str) -> Result<Value, EmulatorError> {
+    let pipe = emulator.storage.get_pipe(name)?;
+    let parsed = |raw: &Option<String>| raw.as_deref()
+        .and_then(|s| serde_json::from_str::<Value>(s).ok())
+        .unwrap_or(Value::Null);
+
+    let mut response = pipe_summary(&pipe);
+    response["Source"] = json!(pipe.source);
+    response["Target"] = json!(pipe.target);
+    response["Enrichment"] = json!(pipe.enrichment);
+    response["RoleArn"] = json!(pipe.role_arn);
+    response["Description"] = json!(pipe.description);
+    response["FilterCriteria"] = parsed(&pipe.filter_criteria);
+    response["SourceParameters"] = parsed(&pipe.source_parameters);
+    response["TargetParameters"] = parsed(&pipe.target_parameters);
+    Ok(response)
+}
+
+fn list_pipes(emulator: &Emulator, query: &str) -> Result<Value, EmulatorError> {
+    let mut name_prefix = None;
+    let mut current_state = None;
+    for (key, value) in query.split('&').filter_map(|kv| kv.split_once('=')) {
+        match key {
+            "NamePrefix" => name_prefix = Some(value),
+            "CurrentState" => current_state = Some(value),
+            _ => {}
+        }
+    }
+
+    let pipes: Vec<Value> = emulator.storage.list_pipes()?
+        .iter()
+        .filter(|p| name_prefix.is_none_or(|prefix| p.name.starts_with(prefix)))
+        .filter(|p| current_state.is_none_or(|state| p.current_state == state))
+        .map(|p| {
+            let mut summary = pipe_summary(p);
+            summary["Source"] = json!(p.source);
+            summary["Target"] = json!(p.target);
+            summary["Enrichment"] = json!(p.enrichment);
+            summary
+        })
+        .collect();
+
+    Ok(json!({ "Pipes": pipes }))
+}
+
+fn update_pipe(emulator: &Arc<Emulator>, name: &str, body: Value) -> Result<Value, EmulatorError> {
+    let mut pipe = emulator.storage.get_pipe(name)?;
+
+    if let Some(target) = body["Target"].as_str() {
+        pipe.target = target.to_string();
+    }
+    if let Some(role_arn) = body["RoleArn"].as_str() {
+        pipe.role_arn = role_arn.to_string();
+    }
+    if body.get("Enrichment").is_some() {
+        pipe.enrichment = body["Enrichment"].as_str().filter(|s| !s.is_empty()).map(|s| s.to_string());
+    }
+    if let Some(description) = body["Description"].as_str() {
+        pipe.description = Some(description.to_string());
+    }
+    if body.get("FilterCriteria").is_some() {
+        pipe.filter_criteria = json_field(&body, "FilterCriteria");
+    }
+    if body.get("SourceParameters").is_some() {
+        pipe.source_parameters = json_field(&body, "SourceParameters");
+    }
+    if body.get("TargetParameters").is_some() {
+        pipe.target_parameters = json_field(&body, "TargetParameters");
+    }
+    pipe.desired_state = desired_state(&body, &pipe.desired_state)?;
+    pipe.current_state = pipe.desired_state.clone();
+    validate(&pipe)?;
+
+    emulator.storage.update_pipe(&pipe)?;
+    let pipe = emulator.storage.get_pipe(name)?;
+    apply_state(emulator, &pipe);
+    Ok(pipe_summary(&pipe))
+}
+
+fn delete_pipe(emulator: &Emulator, name: &str) -> Result<Value, EmulatorError> {
+    let pipe = emulator.storage.get_pipe(name)?;
+    emulator.pipes.stop_poller(name);
+    emulator.storage.delete_pipe(name)?;
+
+    let mut response = pipe_summary(&pipe);
+    response["CurrentState"] = json!("DELETING");
+    Ok(response)
+}
+
+fn set_state(emulator: &Arc<Emulator>, name: &str, state: &str) -> Result<Value, EmulatorError> {
+    emulator.storage.set_pipe_state(name, state, state)?;
+    let pipe = emulator.storage.get_pipe(name)?;
+    apply_state(emulator, &pipe);
+    Ok(pipe_summary(&pipe))
+}
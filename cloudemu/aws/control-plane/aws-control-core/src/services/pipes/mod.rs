@@ -0,0 +1,11 @@
+//! EventBridge Pipes: point-to-point integrations from a polled source to a target
+
+pub mod service;
+pub mod handlers;
+pub mod pattern;
+pub mod poller;
+
+#[cfg(test)]
+mod tests;
+
+pub use service::PipesService;
@@ -0,0 +1,145 @@
+//! EventBridge event pattern matching
+//!
+//! Supports exact values, nested fields and the content filters
+//! `prefix`, `suffix`, `equals-ignore-case`, `anything-but`, `exists` and `numeric`.
+
+use crate::error::EmulatorError;
+use serde_json::Value;
+
+/// Parse a pattern from its JSON string form, rejecting anything that is not an object
+pub fn parse(pattern: &str) -> Result<Value, EmulatorError> {
+    let value: Value = serde_json::from_str(pattern)
+        .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid event pattern: {}", e)))?;
+    if !value.is_object() {
+        return Err(EmulatorError::InvalidArgument("Event pattern must be a JSON object".into()));
+    }
+    Ok(value)
+}
+
+/// Check whether an event matches a pattern
+pub fn matches(pattern: &Value, event: &Value) -> bool {
+    let fields = match pattern.as_object() {
+        Some(fields) => fields,
+        None => return false,
+    };
+
+    fields.iter().all(|(key, expected)| {
+        let actual = event.get(key);
+        match expected {
+            Value::Object(_) => actual.is_some_and(|a| matches(expected, a)),
+            Value::Array(matchers) => matchers.iter().any(|m| match_value(m, actual)),
+            _ => false,
+        }
+    })
+}
+
+/// Match a single array entry of a pattern against a (possibly missing) event field
+fn match_value(matcher: &Value, actual: Option<&Value>) -> bool {
+    if let Some(exists) = matcher.get("exists").and_then(|v| v.as_bool()) {
+        return exists == actual.is_some();
+    }
+
+    let actual = match actual {
+        Some(v) => v,
+        None => return false,
+    };
+
+    // Array fields match when any element matches
+    if let Value::Array(items) = actual {
+        return items.iter().any(|item| match_scalar(matcher, item));
+    }
+    match_scalar(matcher, actual)
+}
+
+fn match_scalar(matcher: &Value, actual: &Value) -> bool {
+    let filter = match matcher.as_object() {
+        Some(filter) => filter,
+        None => return literal_eq(matcher, actual),
+    };
+
+    filter.iter().all(|(op, arg)| match op.as_str() {
+        "prefix" => string_test(arg, actual, |p, a| a.starts_with(p)),
+        "suffix" => string_test(arg, actual, |s, a| a.ends_with(s)),
+        "equals-ignore-case" => string_test(arg, actual, |e, a| a.eq_ignore_ascii_case(e)),
+        "anything-but" => match arg {
+            Value::Array(values) => !values.iter().any(|v| literal_eq(v, actual)),
+            Value::Object(_) => !match_scalar(arg, actual),
+            _ => !literal_eq(arg, actual),
+        },
+        "numeric" => numeric_test(arg, actual),
+        _ => false,
+    })
+}
+
+fn literal_eq(expected: &Value, actual: &Value) -> bool {
+    match (expected.as_f64(), actual.as_f64()) {
+        (Some(e), Some(a)) => e == a,
+        _ => expected == actual,
+    }
+}
+
+fn string_test(arg: &Value, actual: &Value, test: impl Fn(&str, &str) -> bool) -> bool {
+    match (arg.as_str(), actual.as_str()) {
+        (Some(arg), Some(actual)) => test(arg, actual),
+        _ => false,
+    }
+}
+
+/// `{"numeric": [">", 0, "<=", 5]}`: every operator/operand pair must hold
+fn numeric_test(arg: &Value, actual: &Value) -> bool {
+    let (conditions, actual) = match (arg.as_array(), actual.as_f64()) {
+        (Some(c), Some(a)) if c.len() % 2 == 0 && !c.is_empty() => (c, a),
+        _ => return false,
+    };
+
+    conditions.chunks(2).all(|pair| {
+        let bound = match pair[1].as_f64() {
+            Some(b) => b,
+            None => return false,
+        };
+        match pair[0].as_str() {
+            Some("=") => actual == bound,
+            Some("<") => actual < bound,
+            Some("<=") => actual <= bound,
+            Some(">") => actual > bound,
+            Some(">=") => actual >= bound,
+            _ => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_patterns() {
+        let event = json!({
+            "source": "orders",
+            "body": { "type": "order", "total": 42, "tags": ["priority", "eu"], "customer": "ACME-1" }
+        });
+
+        let p = parse(r#"{"body": {"type": ["order"]}}"#).unwrap();
+        assert!(matches(&p, &event));
+        let p = parse(r#"{"body": {"type": ["refund"]}}"#).unwrap();
+        assert!(!matches(&p, &event));
+
+        let p = parse(r#"{"body": {"total": [{"numeric": [">", 10, "<=", 42]}], "tags": ["eu"]}}"#).unwrap();
+        assert!(matches(&p, &event));
+        let p = parse(r#"{"body": {"total": [{"numeric": ["<", 10]}]}}"#).unwrap();
+        assert!(!matches(&p, &event));
+
+        let p = parse(r#"{"body": {"customer": [{"prefix": "ACME"}], "coupon": [{"exists": false}]}}"#).unwrap();
+        assert!(matches(&p, &event));
+        let p = parse(r#"{"body": {"customer": [{"equals-ignore-case": "acme-1"}]}}"#).unwrap();
+        assert!(matches(&p, &event));
+
+        let p = parse(r#"{"source": [{"anything-but": ["orders", "refunds"]}]}"#).unwrap();
+        assert!(!matches(&p, &event));
+        let p = parse(r#"{"source": [{"anything-but": {"prefix": "inv"}}]}"#).unwrap();
+        assert!(matches(&p, &event));
+
+        assert!(parse("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Source poller: receive a batch, filter, enrich and deliver to the target.
+//! Queue messages are deleted once delivered; stream sources keep a checkpoint of the
+//! last record delivered instead, so a failed batch is retried from the same position.
+
+use super::pattern;
+use crate::Emulator;
+use crate::error::EmulatorError;
+use crate::services::message_attributes;
+use aws_data_core::storage::{KinesisRecord, MessageMetadata, Pipe, TableStreamRecord};
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const DEFAULT_BATCH_SIZE: i32 = 10;
+const DEFAULT_STREAM_BATCH_SIZE: usize = 100;
+
+/// Source a pipe polls, parsed from its ARN
+pub enum Source<'a> {
+    Sqs(&'a str),
+    Kinesis(&'a str),
+    /// DynamoDB stream, by table name
+    DynamoDb(&'a str),
+}
+
+impl<'a> Source<'a> {
+    /// Validate that a source ARN is supported
+    pub fn parse(arn: &'a str) -> Result<Self, EmulatorError> {
+        let parts: Vec<&str> = arn.splitn(6, ':').collect();
+        let resource = parts.get(5).copied().unwrap_or("");
+        let source = match parts.get(2).copied() {
+            Some("sqs") if !resource.is_empty() => Some(Source::Sqs(resource)),
+            Some("kinesis") => resource.strip_prefix("stream/").map(Source::Kinesis),
+            Some("dynamodb") => resource.strip_prefix("table/")
+                .and_then(|r| r.split_once("/stream/"))
+                .map(|(table, _)| Source::DynamoDb(table)),
+            _ => None,
+        };
+        source.ok_or_else(|| EmulatorError::InvalidArgument(format!("Invalid pipe source: {}", arn)))
+    }
+
+    /// `SourceParameters` member holding this source's settings
+    fn parameters_key(&self) -> &'static str {
+        match self {
+            Source::Sqs(_) => "SqsQueueParameters",
+            Source::Kinesis(_) => "KinesisStreamParameters",
+            Source::DynamoDb(_) => "DynamoDBStreamParameters",
+        }
+    }
+}
+
+/// Poll the pipe's source until the pipe is stopped or deleted
+pub async fn run(emulator: Arc<Emulator>, name: String) {
+    loop {
+        let pipe = match emulator.storage.get_pipe(&name) {
+            Ok(pipe) => pipe,
+            Err(_) => return,
+        };
+        if pipe.desired_state != "RUNNING" {
+            return;
+        }
+
+        match poll_once(&emulator, &pipe).await {
+            Ok(0) => tokio::time::sleep(POLL_INTERVAL).await,
+            Ok(n) => debug!("Pipes: {} delivered {} record(s)", name, n),
+            Err(e) => {
+                warn!("Pipes: {} delivery failed: {}", name, e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+/// Run a single poll cycle, returning the number of records delivered to the target
+pub async fn poll_once(emulator: &Emulator, pipe: &Pipe) -> Result<usize, EmulatorError> {
+    let source = Source::parse(&pipe.source)?;
+    let source_parameters = parse_json(pipe.source_parameters.as_deref())?;
+    let batch_size = source_parameters[source.parameters_key()]["BatchSize"].as_i64();
+
+    match source {
+        Source::Sqs(queue_name) => {
+            let batch_size = batch_size.map(|n| n as i32).unwrap_or(DEFAULT_BATCH_SIZE);
+            poll_queue(emulator, pipe, queue_name, batch_size).await
+        }
+        Source::Kinesis(stream_name) => {
+            let after = emulator.storage.get_pipe_checkpoint(&pipe.name)?;
+            let batch_size = batch_size.map(|n| n as usize).unwrap_or(DEFAULT_STREAM_BATCH_SIZE);
+            let records = emulator.storage.get_kinesis_records(stream_name, after, batch_size)?
+                .iter()
+                .map(|record| (record.sequence_number, kinesis_record(emulator, pipe, record)))
+                .collect();
+            poll_stream(emulator, pipe, records).await
+        }
+        Source::DynamoDb(table_name) => {
+            let after = emulator.storage.get_pipe_checkpoint(&pipe.name)?;
+            let batch_size = batch_size.map(|n| n as usize).unwrap_or(DEFAULT_STREAM_BATCH_SIZE);
+            let records = emulator.storage.get_table_stream_records(table_name, after, batch_size)?
+                .iter()
+                .map(|record| (record.sequence_number, dynamodb_record(emulator, pipe, record)))
+                .collect();
+            poll_stream(emulator, pipe, records).await
+        }
+    }
+}
+
+/// Checkpoint a new pipe starts from on its stream source: `StartingPosition` TRIM_HORIZON
+/// reads the records already in the stream, LATEST only those added from now on.
+/// Queue sources have no checkpoint.
+pub fn starting_checkpoint(emulator: &Emulator, pipe: &Pipe) -> Result<Option<u64>, EmulatorError> {
+    let source = Source::parse(&pipe.source)?;
+    let latest = match source {
+        Source::Sqs(_) => return Ok(None),
+        Source::Kinesis(stream_name) => emulator.storage.latest_kinesis_sequence(stream_name)?,
+        Source::DynamoDb(table_name) => {
+            emulator.storage.get_table_stream_by_arn(&pipe.source)?;
+            emulator.storage.latest_table_stream_sequence(table_name)?
+        }
+    };
+
+    let source_parameters = parse_json(pipe.source_parameters.as_deref())?;
+    match source_parameters[source.parameters_key()]["StartingPosition"].as_str() {
+        Some("TRIM_HORIZON") => Ok(Some(0)),
+        Some("LATEST") => Ok(Some(latest)),
+        other => Err(EmulatorError::InvalidArgument(format!(
+            "Invalid {}.StartingPosition: {}; expected TRIM_HORIZON or LATEST",
+            source.parameters_key(), other.unwrap_or("")
+        ))),
+    }
+}
+
+async fn poll_queue(emulator: &Emulator, pipe: &Pipe, queue_name: &str, batch_size: i32) -> Result<usize, EmulatorError> {
+    let messages = emulator.storage.receive_message(queue_name, batch_size)?;
+    if messages.is_empty() {
+        return Ok(0);
+    }
+
+    let filters = filter_patterns(pipe.filter_criteria.as_deref())?;
+    let mut records = Vec::new();
+    let mut delivered = Vec::new();
+    for message in &messages {
+        let record = sqs_record(emulator, pipe, message);
+        if filters.is_empty() || filters.iter().any(|f| pattern::matches(f, &filter_view(&record))) {
+            records.push(record);
+            delivered.push(message);
+        } else {
+            // Filtered-out records are dropped from the source, as on AWS
+            delete(emulator, queue_name, message);
+        }
+    }
+    if records.is_empty() {
+        return Ok(0);
+    }
+
+    let mut payload = Value::Array(records);
+    if let Some(enrichment) = pipe.enrichment.as_deref() {
+        payload = invoke_lambda(emulator, enrichment, payload).await?;
+    }
+    deliver(emulator, pipe, payload).await?;
+
+    for message in &delivered {
+        delete(emulator, queue_name, message);
+    }
+    Ok(delivered.len())
+}
+
+/// Deliver a batch of stream records and advance the pipe's checkpoint past it.
+/// Filtered-out records are skipped but still move the checkpoint.
+async fn poll_stream(emulator: &Emulator, pipe: &Pipe, records: Vec<(u64, Value)>) -> Result<usize, EmulatorError> {
+    let Some(last) = records.last().map(|(sequence, _)| *sequence) else {
+        return Ok(0);
+    };
+
+    let filters = filter_patterns(pipe.filter_criteria.as_deref())?;
+    let records: Vec<Value> = records.into_iter()
+        .map(|(_, record)| record)
+        .filter(|record| filters.is_empty() || filters.iter().any(|f| pattern::matches(f, &filter_view(record))))
+        .collect();
+    let delivered = records.len();
+
+    if !records.is_empty() {
+        let mut payload = Value::Array(records);
+        if let Some(enrichment) = pipe.enrichment.as_deref() {
+            payload = invoke_lambda(emulator, enrichment, payload).await?;
+        }
+        deliver(emulator, pipe, payload).await?;
+    }
+
+    emulator.storage.set_pipe_checkpoint(&pipe.name, last)?;
+    Ok(delivered)
+}
+
+/// Validate that a target or enrichment ARN names a supported service
+pub fn validate_target(target: &str) -> Result<(), EmulatorError> {
+    match target.split(':').nth(2) {
+        Some("lambda") | Some("states") => Ok(()),
+        _ => Err(EmulatorError::InvalidArgument(
+            format!("Unsupported pipe target {}; expected a Lambda function or Step Functions state machine", target)
+        )),
+    }
+}
+
+/// Parse the `FilterCriteria.Filters[].Pattern` strings of a pipe
+pub fn filter_patterns(filter_criteria: Option<&str>) -> Result<Vec<Value>, EmulatorError> {
+    let criteria = parse_json(filter_criteria)?;
+    criteria["Filters"]
+        .as_array()
+        .map(|filters| filters.iter()
+            .filter_map(|f| f["Pattern"].as_str())
+            .map(pattern::parse)
+            .collect())
+        .unwrap_or_else(|| Ok(Vec::new()))
+}
+
+fn parse_json(raw: Option<&str>) -> Result<Value, EmulatorError> {
+    match raw {
+        Some(raw) => Ok(serde_json::from_str(raw)?),
+        None => Ok(Value::Null),
+    }
+}
+
+fn sqs_record(emulator: &Emulator, pipe: &Pipe, message: &MessageMetadata) -> Value {
+    let mut record = json!({
+        "messageId": message.id,
+        "receiptHandle": message.receipt_handle,
+        "body": message.body,
+        "attributes": {
+            "ApproximateReceiveCount": message.receive_count.to_string(),
+            "SentTimestamp": message.sent_at
+        },
+        "messageAttributes": message_attributes::to_lambda(message.message_attributes.as_deref()),
+        "md5OfBody": message.md5_body,
+        "eventSource": "aws:sqs",
+        "eventSourceARN": pipe.source,
+        "awsRegion": emulator.config.region
+    });
+    if let Some(trace) = message_attributes::stored_trace_header(message.system_attributes.as_deref()) {
+        record["attributes"][message_attributes::AWS_TRACE_HEADER] = Value::String(trace);
+    }
+    record
+}
+
+fn kinesis_record(emulator: &Emulator, pipe: &Pipe, record: &KinesisRecord) -> Value {
+    json!({
+        "eventSource": "aws:kinesis",
+        "eventVersion": "1.0",
+        "eventID": format!("shardId-000000000000:{}", record.sequence_number),
+        "eventName": "aws:kinesis:record",
+        "invokeIdentityArn": pipe.role_arn,
+        "awsRegion": emulator.config.region,
+        "eventSourceARN": pipe.source,
+        "kinesisSchemaVersion": "1.0",
+        "partitionKey": record.partition_key,
+        "sequenceNumber": record.sequence_number.to_string(),
+        "data": general_purpose::STANDARD.encode(&record.data),
+        "approximateArrivalTimestamp": record.arrival_timestamp as f64 / 1000.0
+    })
+}
+
+fn dynamodb_record(emulator: &Emulator, pipe: &Pipe, record: &TableStreamRecord) -> Value {
+    let mut change = json!({
+        "ApproximateCreationDateTime": record.created_at,
+        "Keys": record.keys,
+        "SequenceNumber": record.sequence_number.to_string(),
+        "StreamViewType": record.view_type
+    });
+    if let Some(image) = &record.new_image {
+        change["NewImage"] = image.clone();
+    }
+    if let Some(image) = &record.old_image {
+        change["OldImage"] = image.clone();
+    }
+    json!({
+        "eventID": uuid::Uuid::new_v4().simple().to_string(),
+        "eventName": record.event_name,
+        "eventVersion": "1.1",
+        "eventSource": "aws:dynamodb",
+        "awsRegion": emulator.config.region,
+        "dynamodb": change,
+        "eventSourceARN": pipe.source
+    })
+}
+
+/// Filters see JSON message bodies and Kinesis data as objects so patterns can match on
+/// their fields
+fn filter_view(record: &Value) -> Value {
+    let mut view = record.clone();
+    if let Some(parsed) = record["body"].as_str().and_then(|b| serde_json::from_str::<Value>(b).ok()) {
+        view["body"] = parsed;
+    }
+    let data = record["data"].as_str().and_then(|d| general_purpose::STANDARD.decode(d).ok());
+    if let Some(parsed) = data.and_then(|d| serde_json::from_slice::<Value>(&d).ok()) {
+        view["data"] = parsed;
+    }
+    view
+}
+
+fn delete(emulator: &Emulator, queue_name: &str, message: &MessageMetadata) {
+    if let Some(handle) = message.receipt_handle.as_deref() {
+        if let Err(e) = emulator.storage.delete_message(queue_name, handle) {
+            warn!("Pipes: failed to delete message {}: {}", message.id, e);
+        }
+    }
+}
+
+async fn deliver(emulator: &Emulator, pipe: &Pipe, payload: Value) -> Result<(), EmulatorError> {
+    match pipe.target.split(':').nth(2) {
+        Some("states") => start_execution(emulator, &pipe.target, payload).await,
+        _ => invoke_lambda(emulator, &pipe.target, payload).await.map(|_| ()),
+    }
+}
+
+#[cfg(feature = "lambda")]
+async fn invoke_lambda(emulator: &Emulator, arn: &str, payload: Value) -> Result<Value, EmulatorError> {
+    // arn:aws:lambda:<region>:<account>:function:<name>[:<qualifier>]
+    let function_name = arn.split(':').nth(6).unwrap_or(arn);
+    crate::services::lambda::handlers::invoke(emulator, function_name, payload).await
+}
+
+#[cfg(not(feature = "lambda"))]
+async fn invoke_lambda(_emulator: &Emulator, arn: &str, _payload: Value) -> Result<Value, EmulatorError> {
+    Err(EmulatorError::NotImplemented(format!("Lambda support is disabled, cannot invoke {}", arn)))
+}
+
+#[cfg(feature = "stepfunctions")]
+async fn start_execution(emulator: &Emulator, arn: &str, payload: Value) -> Result<(), EmulatorError> {
+    let body = json!({ "stateMachineArn": arn, "input": payload.to_string() });
+    crate::services::workflows::handlers::start_execution(emulator, body).await.map(|_| ())
+}
+
+#[cfg(not(feature = "stepfunctions"))]
+async fn start_execution(_emulator: &Emulator, arn: &str, _payload: Value) -> Result<(), EmulatorError> {
+    Err(EmulatorError::NotImplemented(format!("Step Functions support is disabled, cannot start {}", arn)))
+}
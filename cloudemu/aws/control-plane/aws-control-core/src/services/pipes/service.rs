@@ -0,0 +1,51 @@
+//! Pipes Service - owns the background pollers of running pipes
+
+use crate::Emulator;
+use aws_data_core::storage::StorageEngine;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+use tracing::info;
+
+/// Pipes Service
+pub struct PipesService {
+    storage: StorageEngine,
+    pollers: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl PipesService {
+    /// Create a new Pipes service
+    pub fn new(storage: StorageEngine) -> Self {
+        Self {
+            storage,
+            pollers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start (or restart) the source poller for a pipe
+    pub fn start_poller(&self, emulator: Arc<Emulator>, name: &str) {
+        let handle = tokio::spawn(super::poller::run(emulator, name.to_string())).abort_handle();
+        if let Some(previous) = self.pollers.lock().unwrap().insert(name.to_string(), handle) {
+            previous.abort();
+        }
+        info!("Pipes: Started poller for {}", name);
+    }
+
+    /// Stop the source poller for a pipe, if one is running
+    pub fn stop_poller(&self, name: &str) {
+        if let Some(handle) = self.pollers.lock().unwrap().remove(name) {
+            handle.abort();
+            info!("Pipes: Stopped poller for {}", name);
+        }
+    }
+
+    /// Restart pollers for every persisted pipe whose desired state is RUNNING
+    pub fn resume_pollers(&self, emulator: &Arc<Emulator>) -> aws_data_core::error::Result<()> {
+        for pipe in self.storage.list_pipes()? {
+            if pipe.desired_state == "RUNNING" {
+                self.start_poller(emulator.clone(), &pipe.name);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+};
+use tower::ServiceExt;
+use crate::gateway;
+use crate::Emulator;
+use std::sync::Arc;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn test_pipe_sqs_to_step_functions() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+
+    let send = |method: &str, uri: &str, body: Value| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let read_json = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice::<Value>(&bytes).unwrap()
+    };
+
+    let queue = emulator.storage.create_queue("orders", "000000000000", "us-east-1").unwrap();
+    let definition = r#"{"StartAt": "Done", "States": {"Done": {"Type": "Pass", "End": true}}}"#;
+    let machine = emulator.storage.create_state_machine("process", definition, "role", "STANDARD", "000000000000", "us-east-1").unwrap();
+
+    // Sources are checked up front
+    let response = app.clone().oneshot(send("POST", "/v1/pipes/stream", json!({
+        "Source": "arn:aws:kinesis:us-east-1:000000000000:stream/clicks",
+        "Target": machine.arn,
+        "RoleArn": "arn:aws:iam::000000000000:role/pipes"
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app.clone().oneshot(send("POST", "/v1/pipes/orders-pipe", json!({
+        "Source": queue.arn,
+        "Target": machine.arn,
+        "RoleArn": "arn:aws:iam::000000000000:role/pipes",
+        "SourceParameters": { "SqsQueueParameters": { "BatchSize": 5 } },
+        "FilterCriteria": { "Filters": [{ "Pattern": r#"{"body": {"type": ["order"]}}"# }] }
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(read_json(response).await["CurrentState"], "RUNNING");
+
+    emulator.storage.send_message("orders", r#"{"type": "refund", "id": 1}"#).unwrap();
+    emulator.storage.send_message("orders", r#"{"type": "order", "id": 2}"#).unwrap();
+
+    // The poller runs in the background; wait for the execution to appear
+    let mut executions = Vec::new();
+    for _ in 0..50 {
+        executions = emulator.storage.list_executions(&machine.arn).unwrap();
+        if !executions.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(executions.len(), 1, "pipe did not start an execution");
+
+    let input: Value = serde_json::from_str(executions[0].input.as_deref().unwrap()).unwrap();
+    let records = input.as_array().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["eventSource"], "aws:sqs");
+    assert!(records[0]["body"].as_str().unwrap().contains("\"order\""));
+
+    // Both the delivered and the filtered-out message are removed from the queue
+    emulator.storage.get_connection(aws_data_core::storage::Namespace::Sqs).unwrap().execute("UPDATE sqs_messages SET visible_at = ''", []).unwrap();
+    assert!(emulator.storage.receive_message("orders", 10).unwrap().is_empty());
+
+    // Stopped pipes leave messages on the queue
+    let response = app.clone().oneshot(send("POST", "/v1/pipes/orders-pipe/stop", json!({}))).await.unwrap();
+    assert_eq!(read_json(response).await["CurrentState"], "STOPPED");
+    emulator.storage.send_message("orders", r#"{"type": "order", "id": 3}"#).unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(emulator.storage.list_executions(&machine.arn).unwrap().len(), 1);
+
+    let response = app.clone().oneshot(send("GET", "/v1/pipes?NamePrefix=orders", json!({}))).await.unwrap();
+    assert_eq!(read_json(response).await["Pipes"].as_array().unwrap().len(), 1);
+
+    let response = app.clone().oneshot(send("GET", "/v1/pipes/orders-pipe", json!({}))).await.unwrap();
+    let described = read_json(response).await;
+    assert_eq!(described["SourceParameters"]["SqsQueueParameters"]["BatchSize"], 5);
+
+    let response = app.clone().oneshot(send("DELETE", "/v1/pipes/orders-pipe", json!({}))).await.unwrap();
+    assert_eq!(read_json(response).await["CurrentState"], "DELETING");
+    let response = app.clone().oneshot(send("GET", "/v1/pipes/orders-pipe", json!({}))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_pipe_propagates_sns_attributes_and_trace() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+
+    let sns = |action: &str, body: Value| {
+        let mut body = body;
+        body["Action"] = json!(action);
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("AmazonSNS.{}", action))
+            .header("content-type", "application/json")
+            .header("X-Amzn-Trace-Id", "Root=1-67891233-abcdef012345678912345678;Sampled=1")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let topic = emulator.storage.create_topic("orders", "000000000000", "us-east-1").unwrap();
+    let wrapped = emulator.storage.create_queue("orders-wrapped", "000000000000", "us-east-1").unwrap();
+    let raw = emulator.storage.create_queue("orders-raw", "000000000000", "us-east-1").unwrap();
+    let definition = r#"{"StartAt": "Done", "States": {"Done": {"Type": "Pass", "End": true}}}"#;
+    let machine = emulator.storage.create_state_machine("audit", definition, "role", "STANDARD", "000000000000", "us-east-1").unwrap();
+
+    app.clone().oneshot(sns("Subscribe", json!({
+        "TopicArn": topic.arn, "Protocol": "sqs", "Endpoint": wrapped.arn
+    }))).await.unwrap();
+    app.clone().oneshot(sns("Subscribe", json!({
+        "TopicArn": topic.arn, "Protocol": "sqs", "Endpoint": raw.arn,
+        "Attributes": { "RawMessageDelivery": "true" }
+    }))).await.unwrap();
+
+    let response = app.clone().oneshot(sns("Publish", json!({
+        "TopicArn": topic.arn,
+        "Message": r#"{"id": 7}"#,
+        "MessageAttributes": { "tenant": { "DataType": "String", "StringValue": "acme" } }
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The envelope carries the attributes in SNS format; the trace rides on the SQS message
+    let wrapped_messages = emulator.storage.receive_message("orders-wrapped", 10).unwrap();
+    let envelope: Value = serde_json::from_str(&wrapped_messages[0].body).unwrap();
+    assert_eq!(envelope["MessageAttributes"]["tenant"], json!({ "Type": "String", "Value": "acme" }));
+    assert!(wrapped_messages[0].system_attributes.as_deref().unwrap().contains("Root=1-67891233"));
+
+    // Raw delivery: pipe the queue into Step Functions and check the Lambda-format record
+    let response = app.clone().oneshot(Request::builder()
+        .method("POST")
+        .uri("/v1/pipes/raw-pipe")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({
+            "Source": raw.arn,
+            "Target": machine.arn,
+            "RoleArn": "arn:aws:iam::000000000000:role/pipes"
+        }).to_string()))
+        .unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let mut executions = Vec::new();
+    for _ in 0..50 {
+        executions = emulator.storage.list_executions(&machine.arn).unwrap();
+        if !executions.is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert_eq!(executions.len(), 1, "pipe did not start an execution");
+
+    let input: Value = serde_json::from_str(executions[0].input.as_deref().unwrap()).unwrap();
+    let record = &input[0];
+    assert_eq!(record["body"], r#"{"id": 7}"#);
+    assert_eq!(record["messageAttributes"]["tenant"]["stringValue"], "acme");
+    assert_eq!(record["messageAttributes"]["tenant"]["dataType"], "String");
+    assert_eq!(record["attributes"]["AWSTraceHeader"], "Root=1-67891233-abcdef012345678912345678;Sampled=1");
+}
+
+#[tokio::test]
+async fn test_pipe_stream_sources() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+
+    let send = |uri: &str, body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let wait_for_executions = |arn: String, count: usize| {
+        let emulator = emulator.clone();
+        async move {
+            for _ in 0..50 {
+                let executions = emulator.storage.list_executions(&arn).unwrap();
+                if executions.len() >= count {
+                    return executions;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            }
+            panic!("pipe did not start {} execution(s)", count);
+        }
+    };
+
+    let definition = r#"{"StartAt": "Done", "States": {"Done": {"Type": "Pass", "End": true}}}"#;
+    let clicks_machine = emulator.storage.create_state_machine("clicks", definition, "role", "STANDARD", "000000000000", "us-east-1").unwrap();
+    let orders_machine = emulator.storage.create_state_machine("orders", definition, "role", "STANDARD", "000000000000", "us-east-1").unwrap();
+
+    // Kinesis: TRIM_HORIZON picks up records put before the pipe existed
+    let stream = emulator.storage.create_kinesis_stream("clicks", "000000000000", "us-east-1").unwrap();
+    emulator.storage.put_kinesis_record("clicks", "user-1", br#"{"page": "home"}"#).unwrap();
+    emulator.storage.put_kinesis_record("clicks", "user-2", br#"{"page": "checkout"}"#).unwrap();
+
+    let pipe = |source: &str, target: &str, parameters: Value| json!({
+        "Source": source,
+        "Target": target,
+        "RoleArn": "arn:aws:iam::000000000000:role/pipes",
+        "SourceParameters": parameters
+    });
+    let response = app.clone().oneshot(send("/v1/pipes/no-position", pipe(&stream.arn, &clicks_machine.arn, json!({})))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let mut clicks_pipe = pipe(&stream.arn, &clicks_machine.arn, json!({ "KinesisStreamParameters": { "StartingPosition": "TRIM_HORIZON" } }));
+    clicks_pipe["FilterCriteria"] = json!({ "Filters": [{ "Pattern": r#"{"data": {"page": ["checkout"]}}"# }] });
+    let response = app.clone().oneshot(send("/v1/pipes/clicks-pipe", clicks_pipe)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let executions = wait_for_executions(clicks_machine.arn.clone(), 1).await;
+    let input: Value = serde_json::from_str(executions[0].input.as_deref().unwrap()).unwrap();
+    assert_eq!(input.as_array().unwrap().len(), 1);
+    assert_eq!(input[0]["eventSource"], "aws:kinesis");
+    assert_eq!(input[0]["partitionKey"], "user-2");
+    assert_eq!(input[0]["sequenceNumber"], "2");
+
+    // The checkpoint moves on, so later records are delivered once
+    emulator.storage.put_kinesis_record("clicks", "user-3", br#"{"page": "checkout"}"#).unwrap();
+    let executions = wait_for_executions(clicks_machine.arn.clone(), 2).await;
+    assert_eq!(executions.len(), 2);
+    assert_eq!(emulator.storage.get_pipe_checkpoint("clicks-pipe").unwrap(), 3);
+
+    // DynamoDB: LATEST only sees changes made after the pipe was created
+    let tables = |action: &str, body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("DynamoDB_20120810.{}", action))
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let response = app.clone().oneshot(tables("CreateTable", json!({
+        "TableName": "orders",
+        "KeySchema": [{ "AttributeName": "id", "KeyType": "HASH" }],
+        "AttributeDefinitions": [{ "AttributeName": "id", "AttributeType": "S" }],
+        "StreamSpecification": { "StreamEnabled": true, "StreamViewType": "NEW_AND_OLD_IMAGES" }
+    }))).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let table: Value = serde_json::from_slice(&bytes).unwrap();
+    let stream_arn = table["TableDescription"]["LatestStreamArn"].as_str().unwrap().to_string();
+
+    app.clone().oneshot(tables("PutItem", json!({ "TableName": "orders", "Item": { "id": { "S": "1" }, "total": { "N": "5" } } }))).await.unwrap();
+    let response = app.clone().oneshot(send("/v1/pipes/orders-pipe", pipe(&stream_arn, &orders_machine.arn, json!({
+        "DynamoDBStreamParameters": { "StartingPosition": "LATEST", "BatchSize": 10 }
+    })))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    app.clone().oneshot(tables("PutItem", json!({ "TableName": "orders", "Item": { "id": { "S": "1" }, "total": { "N": "7" } } }))).await.unwrap();
+
+    let executions = wait_for_executions(orders_machine.arn.clone(), 1).await;
+    let input: Value = serde_json::from_str(executions[0].input.as_deref().unwrap()).unwrap();
+    assert_eq!(input.as_array().unwrap().len(), 1);
+    assert_eq!(input[0]["eventSource"], "aws:dynamodb");
+    assert_eq!(input[0]["eventName"], "MODIFY");
+    assert_eq!(input[0]["dynamodb"]["Keys"], json!({ "id": { "S": "1" } }));
+    assert_eq!(input[0]["dynamodb"]["OldImage"]["total"], json!({ "N": "5" }));
+    assert_eq!(input[0]["dynamodb"]["NewImage"]["total"], json!({ "N": "7" }));
+}
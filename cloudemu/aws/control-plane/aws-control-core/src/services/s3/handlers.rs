@@ -1,17 +1,62 @@
 //! S3 HTTP Handlers
 
-use super::xml;
+use super::{select, xml};
 use crate::Emulator;
 use crate::error::{ApiError, EmulatorError};
+use crate::event_bus::{LifecycleAction, ResourceEvent};
+use aws_data_core::storage::{ObjectMetadata, StoredData};
 use axum::{
     body::Body,
     extract::{Path, Query, State},
     http::{HeaderMap, Method, StatusCode, header},
     response::Response,
 };
-use std::{collections::HashMap, sync::Arc};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use std::{collections::HashMap, io::Write, sync::Arc};
+use tokio_util::io::ReaderStream;
 use tracing::{info, debug};
 
+/// Read buffer size for streamed object downloads
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stream a request body into the object store, chunk by chunk
+async fn store_body(emulator: &Emulator, mut body: Body) -> Result<StoredData, ApiError> {
+    let mut writer = emulator.storage.object_writer()?;
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| EmulatorError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+        if let Some(chunk) = frame.data_ref() {
+            writer.write_all(chunk)?;
+        }
+    }
+    Ok(writer.finish().map_err(EmulatorError::from)?)
+}
+
+/// Response body streaming an object's data from the object store
+fn object_body(file: std::fs::File) -> Body {
+    Body::from_stream(ReaderStream::with_capacity(tokio::fs::File::from_std(file), STREAM_CHUNK_SIZE))
+}
+
+/// Publish an S3 resource event; `key` is set for object events
+fn publish_event(emulator: &Emulator, operation: &str, action: LifecycleAction, bucket: &str, key: Option<&str>, detail: Value) {
+    let resource = match key {
+        Some(key) => format!("arn:aws:s3:::{}/{}", bucket, key),
+        None => format!("arn:aws:s3:::{}", bucket),
+    };
+    emulator.bus.publish(ResourceEvent::new("s3", operation, action, Some(resource), detail));
+}
+
+/// Event detail for a newly written object
+fn object_detail(bucket: &str, key: &str, meta: &ObjectMetadata) -> Value {
+    json!({
+        "bucket": bucket,
+        "key": key,
+        "size": meta.size,
+        "eTag": meta.etag.trim_matches('"'),
+        "versionId": meta.version_id
+    })
+}
+
 /// List all buckets (GET /)
 pub async fn list_buckets(
     State(emulator): State<Arc<Emulator>>,
@@ -47,6 +92,18 @@ pub async fn bucket_handler(
     if params.contains_key("policy") {
         return handle_bucket_policy(&emulator, &method, &bucket, &body, &request_id).await;
     }
+    if params.contains_key("replication") {
+        return handle_bucket_replication(&emulator, &method, &bucket, &body, &request_id).await;
+    }
+    if params.contains_key("lifecycle") {
+        return handle_bucket_lifecycle(&emulator, &method, &bucket, &body, &request_id).await;
+    }
+    if params.contains_key("website") {
+        return handle_bucket_website(&emulator, &method, &bucket, &body, &request_id).await;
+    }
+    if params.contains_key("notification") {
+        return handle_bucket_notification(&emulator, &method, &bucket, &body, &request_id).await;
+    }
     if params.contains_key("location") {
         return handle_bucket_location(&emulator, &bucket, &request_id).await;
     }
@@ -60,8 +117,16 @@ pub async fn bucket_handler(
     
     match method {
         Method::PUT => {
-            // CreateBucket
-            emulator.storage.create_bucket(&bucket, &emulator.config.region)?;
+            // CreateBucket (honours CreateBucketConfiguration/LocationConstraint)
+            let body_str = String::from_utf8_lossy(&body);
+            let region = xml::extract_location_constraint(&body_str)
+                .unwrap_or_else(|| emulator.config.region.clone());
+            let buckets = emulator.storage.list_buckets()?;
+            if !buckets.iter().any(|b| b.name == bucket) {
+                emulator.limits.check_bucket_count(buckets.len())?;
+            }
+            emulator.storage.create_bucket(&bucket, &region)?;
+            publish_event(&emulator, "CreateBucket", LifecycleAction::Created, &bucket, None, json!({ "bucket": bucket, "region": region }));
             
             Ok(Response::builder()
                 .status(StatusCode::OK)
@@ -74,6 +139,7 @@ pub async fn bucket_handler(
         Method::DELETE => {
             // DeleteBucket
             emulator.storage.delete_bucket(&bucket)?;
+            publish_event(&emulator, "DeleteBucket", LifecycleAction::Deleted, &bucket, None, json!({ "bucket": bucket }));
             
             Ok(Response::builder()
                 .status(StatusCode::NO_CONTENT)
@@ -230,6 +296,192 @@ async fn handle_bucket_policy(
     }
 }
 
+/// Handle bucket replication operations
+async fn handle_bucket_replication(
+    emulator: &Emulator,
+    method: &Method,
+    bucket: &str,
+    body: &[u8],
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: {} /{}?replication", method, bucket);
+    
+    match *method {
+        Method::GET => {
+            let config = emulator.storage.get_bucket_replication(bucket)?
+                .ok_or_else(|| EmulatorError::NotFound("ReplicationConfiguration".into(), bucket.to_string()))?;
+            let xml_body = xml::get_bucket_replication_xml(&config);
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/xml")
+                .header("x-amz-request-id", request_id)
+                .body(Body::from(xml_body))
+                .unwrap())
+        }
+        Method::PUT => {
+            let config = xml::parse_replication_configuration(&String::from_utf8_lossy(body))?;
+            emulator.storage.set_bucket_replication(bucket, &config)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        Method::DELETE => {
+            emulator.storage.delete_bucket_replication(bucket)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+/// Handle bucket lifecycle operations
+async fn handle_bucket_lifecycle(
+    emulator: &Emulator,
+    method: &Method,
+    bucket: &str,
+    body: &[u8],
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: {} /{}?lifecycle", method, bucket);
+    
+    match *method {
+        Method::GET => {
+            let config = emulator.storage.get_bucket_lifecycle(bucket)?
+                .ok_or_else(|| EmulatorError::NoSuchLifecycleConfiguration(bucket.to_string()))?;
+            let xml_body = xml::get_bucket_lifecycle_xml(&config);
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/xml")
+                .header("x-amz-request-id", request_id)
+                .body(Body::from(xml_body))
+                .unwrap())
+        }
+        Method::PUT => {
+            let config = xml::parse_lifecycle_configuration(&String::from_utf8_lossy(body))?;
+            emulator.storage.set_bucket_lifecycle(bucket, &config)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        Method::DELETE => {
+            emulator.storage.delete_bucket_lifecycle(bucket)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+/// Handle bucket website operations
+async fn handle_bucket_website(
+    emulator: &Emulator,
+    method: &Method,
+    bucket: &str,
+    body: &[u8],
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: {} /{}?website", method, bucket);
+    
+    match *method {
+        Method::GET => {
+            let config = emulator.storage.get_bucket_website(bucket)?
+                .ok_or_else(|| EmulatorError::NotFound("WebsiteConfiguration".into(), bucket.to_string()))?;
+            let xml_body = xml::get_bucket_website_xml(&config);
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/xml")
+                .header("x-amz-request-id", request_id)
+                .body(Body::from(xml_body))
+                .unwrap())
+        }
+        Method::PUT => {
+            let config = xml::parse_website_configuration(&String::from_utf8_lossy(body))?;
+            emulator.storage.set_bucket_website(bucket, &config)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        Method::DELETE => {
+            emulator.storage.delete_bucket_website(bucket)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::NO_CONTENT)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
+/// Handle bucket notification configuration (PUT replaces it; an empty configuration disables it)
+async fn handle_bucket_notification(
+    emulator: &Emulator,
+    method: &Method,
+    bucket: &str,
+    body: &[u8],
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: {} /{}?notification", method, bucket);
+    
+    match *method {
+        Method::GET => {
+            let config = emulator.storage.get_bucket_notification(bucket)?;
+            let xml_body = xml::get_bucket_notification_xml(&config);
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(header::CONTENT_TYPE, "application/xml")
+                .header("x-amz-request-id", request_id)
+                .body(Body::from(xml_body))
+                .unwrap())
+        }
+        Method::PUT => {
+            let config = xml::parse_notification_configuration(&String::from_utf8_lossy(body))?;
+            emulator.storage.set_bucket_notification(bucket, &config)?;
+            
+            Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("x-amz-request-id", request_id)
+                .body(Body::empty())
+                .unwrap())
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap())
+    }
+}
+
 /// Handle GetBucketLocation
 async fn handle_bucket_location(
     emulator: &Emulator,
@@ -290,25 +542,40 @@ pub async fn object_handler(
     Path((bucket, key)): Path<(String, String)>,
     Query(params): Query<HashMap<String, String>>,
     headers: HeaderMap,
-    body: axum::body::Bytes,
+    body: Body,
 ) -> Result<Response<Body>, ApiError> {
     let request_id = uuid::Uuid::new_v4().to_string();
     info!("S3: {} /{}/{}", method, bucket, key);
     
-    // Check for multipart upload operations
-    if params.contains_key("uploads") {
-        return handle_create_multipart_upload(&emulator, &bucket, &key, &request_id).await;
+    // Multipart upload operations
+    if method == Method::POST && params.contains_key("uploads") {
+        return handle_create_multipart_upload(&emulator, &bucket, &key, &headers, &request_id).await;
+    }
+    
+    if method == Method::POST && params.contains_key("select") {
+        let version_id = params.get("versionId").map(|s| s.as_str());
+        return handle_select_object_content(&emulator, &bucket, &key, version_id, body, &request_id).await;
     }
     
     if let Some(upload_id) = params.get("uploadId") {
-        if let Some(part_number_str) = params.get("partNumber") {
-            let part_number: i32 = part_number_str.parse()
-                .map_err(|_| EmulatorError::InvalidArgument("Invalid partNumber".into()))?;
-            return handle_upload_part(&emulator, &bucket, &key, upload_id, part_number, &body, &request_id).await;
-        } else {
-            // Complete or Abort multipart upload
-            return handle_complete_multipart_upload(&emulator, &bucket, &key, upload_id, &body, &request_id).await;
-        }
+        return match method {
+            Method::PUT => {
+                let part_number: i32 = params.get("partNumber")
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| EmulatorError::InvalidArgument("Invalid partNumber".into()))?;
+                match headers.get("x-amz-copy-source") {
+                    Some(copy_source) => handle_upload_part_copy(&emulator, &bucket, &key, upload_id, part_number, copy_source, &headers, &request_id).await,
+                    None => handle_upload_part(&emulator, &bucket, &key, upload_id, part_number, body, &request_id).await,
+                }
+            }
+            Method::POST => handle_complete_multipart_upload(&emulator, &bucket, &key, upload_id, body, &request_id).await,
+            Method::DELETE => handle_abort_multipart_upload(&emulator, &bucket, &key, upload_id, &request_id).await,
+            Method::GET => handle_list_parts(&emulator, &bucket, &key, upload_id, &params, &request_id).await,
+            _ => Ok(Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .unwrap()),
+        };
     }
     
     let version_id = params.get("versionId").map(|s| s.as_str());
@@ -324,28 +591,22 @@ pub async fn object_handler(
             let content_type = headers.get(header::CONTENT_TYPE)
                 .and_then(|h| h.to_str().ok());
             
-            // Extract user metadata
-            let mut metadata = HashMap::new();
-            for (name, value) in headers.iter() {
-                if let Some(meta_key) = name.as_str().strip_prefix("x-amz-meta-") {
-                    if let Ok(v) = value.to_str() {
-                        metadata.insert(meta_key.to_string(), v.to_string());
-                    }
-                }
-            }
-            let metadata_json = if metadata.is_empty() { 
-                None 
-            } else { 
-                Some(serde_json::to_string(&metadata)?)
-            };
+            let metadata_json = user_metadata(&headers)?;
             
-            let obj_meta = emulator.storage.put_object(
+            // Fail before streaming the body into the store
+            if !emulator.storage.bucket_exists(&bucket)? {
+                return Err(EmulatorError::NoSuchBucket(bucket).into());
+            }
+            let data = store_body(&emulator, body).await?;
+            let obj_meta = emulator.storage.put_object_data(
                 &bucket, 
                 &key, 
-                &body, 
+                data, 
                 content_type,
                 metadata_json.as_deref(),
             )?;
+            emulator.s3.schedule_replication(&bucket, &obj_meta);
+            publish_event(&emulator, "PutObject", LifecycleAction::Created, &bucket, Some(&key), object_detail(&bucket, &key, &obj_meta));
             
             let mut response = Response::builder()
                 .status(StatusCode::OK)
@@ -355,11 +616,14 @@ pub async fn object_handler(
             if let Some(vid) = obj_meta.version_id {
                 response = response.header("x-amz-version-id", vid);
             }
+            if let Some(status) = obj_meta.replication_status {
+                response = response.header("x-amz-replication-status", status);
+            }
             
             Ok(response.body(Body::empty()).unwrap())
         }
         Method::GET => {
-            let (obj_meta, data) = emulator.storage.get_object(&bucket, &key, version_id)?;
+            let (obj_meta, file) = emulator.storage.open_object(&bucket, &key, version_id)?;
             
             let mut response = Response::builder()
                 .status(StatusCode::OK)
@@ -372,11 +636,14 @@ pub async fn object_handler(
             if let Some(vid) = obj_meta.version_id {
                 response = response.header("x-amz-version-id", vid);
             }
+            if let Some(status) = obj_meta.replication_status {
+                response = response.header("x-amz-replication-status", status);
+            }
             
-            Ok(response.body(Body::from(data)).unwrap())
+            Ok(response.body(object_body(file)).unwrap())
         }
         Method::HEAD => {
-            let (obj_meta, _) = emulator.storage.get_object(&bucket, &key, version_id)?;
+            let obj_meta = emulator.storage.head_object(&bucket, &key, version_id)?;
             
             let mut response = Response::builder()
                 .status(StatusCode::OK)
@@ -389,11 +656,20 @@ pub async fn object_handler(
             if let Some(vid) = obj_meta.version_id {
                 response = response.header("x-amz-version-id", vid);
             }
+            if let Some(status) = obj_meta.replication_status {
+                response = response.header("x-amz-replication-status", status);
+            }
             
             Ok(response.body(Body::empty()).unwrap())
         }
         Method::DELETE => {
             let delete_marker_version = emulator.storage.delete_object(&bucket, &key, version_id)?;
+            publish_event(&emulator, "DeleteObject", LifecycleAction::Deleted, &bucket, Some(&key), json!({
+                "bucket": bucket,
+                "key": key,
+                "versionId": delete_marker_version.as_deref().or(version_id),
+                "deleteMarker": delete_marker_version.is_some()
+            }));
             
             let mut response = Response::builder()
                 .status(StatusCode::NO_CONTENT)
@@ -416,6 +692,71 @@ pub async fn object_handler(
     }
 }
 
+/// SelectObjectContent: run an S3 Select query over an object
+async fn handle_select_object_content(
+    emulator: &Emulator,
+    bucket: &str,
+    key: &str,
+    version_id: Option<&str>,
+    body: Body,
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    let body = body.collect().await
+        .map_err(|e| EmulatorError::InvalidRequest(format!("Failed to read request body: {}", e)))?
+        .to_bytes();
+    let request = select::parse_request(&String::from_utf8_lossy(&body))?;
+    let (_, data) = emulator.storage.get_object(bucket, key, version_id)?;
+    let stream = select::select(&request, &data)?;
+    
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/vnd.amazon.eventstream")
+        .header("x-amz-request-id", request_id)
+        .body(Body::from(stream))
+        .unwrap())
+}
+
+/// User metadata of the `x-amz-meta-*` headers, as JSON; `None` without any
+fn user_metadata(headers: &HeaderMap) -> Result<Option<String>, ApiError> {
+    let mut metadata = HashMap::new();
+    for (name, value) in headers.iter() {
+        if let Some(meta_key) = name.as_str().strip_prefix("x-amz-meta-") {
+            if let Ok(v) = value.to_str() {
+                metadata.insert(meta_key.to_string(), v.to_string());
+            }
+        }
+    }
+    if metadata.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(serde_json::to_string(&metadata)?))
+    }
+}
+
+/// Bucket, key and version of an `x-amz-copy-source` header, `[/]bucket/key[?versionId=id]`
+fn parse_copy_source(copy_source: &axum::http::HeaderValue) -> Result<(String, String, Option<String>), ApiError> {
+    let source = copy_source.to_str().unwrap_or("");
+    let (path, version_id) = match source.split_once("?versionId=") {
+        Some((path, version_id)) => (path, Some(version_id.to_string())),
+        None => (source, None),
+    };
+    let path = percent_encoding::percent_decode_str(path).decode_utf8_lossy();
+    let (bucket, key) = path.trim_start_matches('/').split_once('/')
+        .filter(|(bucket, key)| !bucket.is_empty() && !key.is_empty())
+        .ok_or_else(|| EmulatorError::InvalidArgument("Invalid x-amz-copy-source".to_string()))?;
+    Ok((bucket.to_string(), key.to_string(), version_id))
+}
+
+/// Inclusive byte range of an `x-amz-copy-source-range` header, `bytes=first-last`
+fn parse_copy_source_range(range: &str) -> Result<(u64, u64), ApiError> {
+    range.strip_prefix("bytes=")
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(first, last)| Some((first.parse().ok()?, last.parse().ok()?)))
+        .ok_or_else(|| EmulatorError::InvalidArgument(format!(
+            "The x-amz-copy-source-range value must be of the form bytes=first-last, got {}", range
+        )).into())
+}
+
 /// Handle CopyObject
 async fn handle_copy_object(
     emulator: &Emulator,
@@ -424,21 +765,14 @@ async fn handle_copy_object(
     copy_source: &axum::http::HeaderValue,
     request_id: &str,
 ) -> Result<Response<Body>, ApiError> {
-    let source = copy_source.to_str().unwrap_or("");
-    let source = percent_encoding::percent_decode_str(source)
-        .decode_utf8_lossy();
-    let source = source.trim_start_matches('/');
-    
-    let (src_bucket, src_key) = source.split_once('/')
-        .ok_or_else(|| EmulatorError::InvalidArgument("Invalid x-amz-copy-source".to_string()))?;
+    let (src_bucket, src_key, _) = parse_copy_source(copy_source)?;
     
     info!("S3: CopyObject {}/{} -> {}/{}", src_bucket, src_key, dest_bucket, dest_key);
     
-    // Get source object
-    let (_, data) = emulator.storage.get_object(src_bucket, src_key, None)?;
-    
-    // Put to destination
-    let obj_meta = emulator.storage.put_object(dest_bucket, dest_key, &data, None, None)?;
+    // The destination shares the source's stored data
+    let obj_meta = emulator.storage.copy_object(&src_bucket, &src_key, dest_bucket, dest_key)?;
+    emulator.s3.schedule_replication(dest_bucket, &obj_meta);
+    publish_event(emulator, "CopyObject", LifecycleAction::Created, dest_bucket, Some(dest_key), object_detail(dest_bucket, dest_key, &obj_meta));
     
     let xml_body = xml::copy_object_xml(&obj_meta.etag, &obj_meta.last_modified);
     
@@ -459,11 +793,14 @@ async fn handle_create_multipart_upload(
     emulator: &Emulator,
     bucket: &str,
     key: &str,
+    headers: &HeaderMap,
     request_id: &str,
 ) -> Result<Response<Body>, ApiError> {
     info!("S3: CreateMultipartUpload {}/{}", bucket, key);
     
-    let upload_id = emulator.storage.create_multipart_upload(bucket, key)?;
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|h| h.to_str().ok());
+    let metadata = user_metadata(headers)?;
+    let upload_id = emulator.storage.create_multipart_upload(bucket, key, content_type, metadata.as_deref())?;
     let xml_body = xml::create_multipart_upload_xml(bucket, key, &upload_id);
     
     Ok(Response::builder()
@@ -481,34 +818,80 @@ async fn handle_upload_part(
     key: &str,
     upload_id: &str,
     part_number: i32,
-    body: &[u8],
+    body: Body,
     request_id: &str,
 ) -> Result<Response<Body>, ApiError> {
     info!("S3: UploadPart {}/{} uploadId={} partNumber={}", bucket, key, upload_id, part_number);
     
-    let etag = emulator.storage.upload_part(upload_id, part_number, body)?;
+    // Fail before streaming the body into the store
+    emulator.storage.get_multipart_upload(bucket, key, upload_id)?;
+    let data = store_body(emulator, body).await?;
+    let part = emulator.storage.upload_part_data(bucket, key, upload_id, part_number, data)?;
     
     Ok(Response::builder()
         .status(StatusCode::OK)
-        .header("ETag", &etag)
+        .header("ETag", &part.etag)
         .header("x-amz-request-id", request_id)
         .body(Body::empty())
         .unwrap())
 }
 
-/// Handle CompleteMultipartUpload
-async fn handle_complete_multipart_upload(
+/// Handle UploadPartCopy
+#[allow(clippy::too_many_arguments)]
+async fn handle_upload_part_copy(
     emulator: &Emulator,
     bucket: &str,
     key: &str,
     upload_id: &str,
-    _body: &[u8],
+    part_number: i32,
+    copy_source: &axum::http::HeaderValue,
+    headers: &HeaderMap,
     request_id: &str,
 ) -> Result<Response<Body>, ApiError> {
-    info!("S3: CompleteMultipartUpload {}/{} uploadId={}", bucket, key, upload_id);
+    let (src_bucket, src_key, src_version_id) = parse_copy_source(copy_source)?;
+    let range = headers.get("x-amz-copy-source-range")
+        .map(|range| parse_copy_source_range(range.to_str().unwrap_or("")))
+        .transpose()?;
+    info!("S3: UploadPartCopy {}/{} -> {}/{} uploadId={} partNumber={}", src_bucket, src_key, bucket, key, upload_id, part_number);
     
-    let etag = emulator.storage.complete_multipart_upload(bucket, key, upload_id)?;
-    let xml_body = xml::complete_multipart_upload_xml(bucket, key, &etag);
+    let part = emulator.storage.upload_part_copy(
+        bucket, key, upload_id, part_number, &src_bucket, &src_key, src_version_id.as_deref(), range,
+    )?;
+    
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/xml")
+        .header("x-amz-request-id", request_id);
+    if let Some(vid) = src_version_id {
+        response = response.header("x-amz-copy-source-version-id", vid);
+    }
+    Ok(response.body(Body::from(xml::copy_part_xml(&part))).unwrap())
+}
+
+/// Handle ListParts
+async fn handle_list_parts(
+    emulator: &Emulator,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    params: &HashMap<String, String>,
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    debug!("S3: ListParts {}/{} uploadId={}", bucket, key, upload_id);
+    
+    let upload = emulator.storage.get_multipart_upload(bucket, key, upload_id)?;
+    let max_parts: usize = params.get("max-parts")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(1000)
+        .min(1000);
+    let part_number_marker: i32 = params.get("part-number-marker")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    
+    let mut parts = emulator.storage.list_parts(bucket, key, upload_id, part_number_marker)?;
+    let is_truncated = parts.len() > max_parts;
+    parts.truncate(max_parts);
+    let xml_body = xml::list_parts_xml(&upload, &parts, part_number_marker, max_parts, is_truncated);
     
     Ok(Response::builder()
         .status(StatusCode::OK)
@@ -517,3 +900,204 @@ async fn handle_complete_multipart_upload(
         .body(Body::from(xml_body))
         .unwrap())
 }
+
+/// Handle AbortMultipartUpload
+async fn handle_abort_multipart_upload(
+    emulator: &Emulator,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: AbortMultipartUpload {}/{} uploadId={}", bucket, key, upload_id);
+    
+    emulator.storage.abort_multipart_upload(bucket, key, upload_id)?;
+    
+    Ok(Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .header("x-amz-request-id", request_id)
+        .body(Body::empty())
+        .unwrap())
+}
+
+/// Handle CompleteMultipartUpload
+async fn handle_complete_multipart_upload(
+    emulator: &Emulator,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    body: Body,
+    request_id: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3: CompleteMultipartUpload {}/{} uploadId={}", bucket, key, upload_id);
+    
+    let body = axum::body::to_bytes(body, usize::MAX).await
+        .map_err(|e| EmulatorError::InvalidRequest(format!("Failed to read request body: {}", e)))?;
+    let parts = xml::parse_complete_multipart_upload(&String::from_utf8_lossy(&body))?;
+    let object = emulator.storage.complete_multipart_upload(bucket, key, upload_id, &parts)?;
+    emulator.s3.schedule_replication(bucket, &object);
+    publish_event(emulator, "CompleteMultipartUpload", LifecycleAction::Created, bucket, Some(key), json!({
+        "bucket": bucket,
+        "key": key,
+        "eTag": object.etag.trim_matches('"')
+    }));
+    let xml_body = xml::complete_multipart_upload_xml(bucket, key, &object.etag);
+    
+    let mut response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/xml")
+        .header("x-amz-request-id", request_id);
+    if let Some(vid) = object.version_id {
+        response = response.header("x-amz-version-id", vid);
+    }
+    Ok(response.body(Body::from(xml_body)).unwrap())
+}
+
+// ==================== Website Endpoint ====================
+
+/// Serve the website index of a bucket (GET/HEAD /_website/:bucket)
+pub async fn website_index_handler(
+    State(emulator): State<Arc<Emulator>>,
+    method: Method,
+    Path(bucket): Path<String>,
+) -> Result<Response<Body>, ApiError> {
+    serve_website(&emulator, &method, &bucket, "").await
+}
+
+/// Serve a bucket as a static website (GET/HEAD /_website/:bucket/*key)
+pub async fn website_handler(
+    State(emulator): State<Arc<Emulator>>,
+    method: Method,
+    Path((bucket, key)): Path<(String, String)>,
+) -> Result<Response<Body>, ApiError> {
+    serve_website(&emulator, &method, &bucket, &key).await
+}
+
+async fn serve_website(
+    emulator: &Emulator,
+    method: &Method,
+    bucket: &str,
+    key: &str,
+) -> Result<Response<Body>, ApiError> {
+    info!("S3 Website: {} /{}/{}", method, bucket, key);
+    
+    if *method != Method::GET && *method != Method::HEAD {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::empty())
+            .unwrap());
+    }
+    
+    let config = emulator.storage.get_bucket_website(bucket)?
+        .ok_or_else(|| EmulatorError::NotFound("WebsiteConfiguration".into(), bucket.to_string()))?;
+    
+    if let Some(ref target) = config.redirect_all_requests_to {
+        return Ok(website_redirect(bucket, target, key, None));
+    }
+    
+    // Rules without an error-code condition apply before the object lookup
+    if let Some(rule) = config.routing_rules.iter().find(|r| routing_rule_matches(r, key, None)) {
+        return Ok(website_redirect(bucket, &rule.redirect, key, rule.key_prefix_equals.as_deref()));
+    }
+    
+    let index = config.index_document.as_deref().unwrap_or("index.html");
+    let object_key = if key.is_empty() || key.ends_with('/') {
+        format!("{}{}", key, index)
+    } else {
+        key.to_string()
+    };
+    
+    match emulator.storage.open_object(bucket, &object_key, None) {
+        Ok((meta, file)) => Ok(website_object_response(method, StatusCode::OK, &meta, file)),
+        Err(EmulatorError::NoSuchKey(_)) => {
+            // A "directory" requested without its trailing slash redirects to the slashed form
+            if !object_key.ends_with(index) && emulator.storage.head_object(bucket, &format!("{}/{}", key, index), None).is_ok() {
+                return Ok(Response::builder()
+                    .status(StatusCode::FOUND)
+                    .header(header::LOCATION, format!("/_website/{}/{}/", bucket, key))
+                    .body(Body::empty())
+                    .unwrap());
+            }
+            
+            if let Some(rule) = config.routing_rules.iter().find(|r| routing_rule_matches(r, key, Some(404))) {
+                return Ok(website_redirect(bucket, &rule.redirect, key, rule.key_prefix_equals.as_deref()));
+            }
+            
+            if let Some(ref error_key) = config.error_document {
+                if let Ok((meta, file)) = emulator.storage.open_object(bucket, error_key, None) {
+                    return Ok(website_object_response(method, StatusCode::NOT_FOUND, &meta, file));
+                }
+            }
+            
+            Ok(Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                .body(Body::from(format!(
+                    "<html><head><title>404 Not Found</title></head><body><h1>404 Not Found</h1><ul><li>Code: NoSuchKey</li><li>Key: {}</li></ul></body></html>",
+                    object_key
+                )))
+                .unwrap())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn routing_rule_matches(rule: &aws_data_core::storage::RoutingRule, key: &str, status: Option<u16>) -> bool {
+    let prefix_matches = rule.key_prefix_equals.as_deref().is_none_or(|p| key.starts_with(p));
+    let code_matches = match (&rule.http_error_code_returned_equals, status) {
+        (None, None) => true,
+        (Some(code), Some(status)) => code.parse::<u16>().ok() == Some(status),
+        _ => false,
+    };
+    prefix_matches && code_matches
+}
+
+fn website_redirect(
+    bucket: &str,
+    redirect: &aws_data_core::storage::WebsiteRedirect,
+    key: &str,
+    matched_prefix: Option<&str>,
+) -> Response<Body> {
+    let new_key = if let Some(ref replacement) = redirect.replace_key_with {
+        replacement.clone()
+    } else if let Some(ref new_prefix) = redirect.replace_key_prefix_with {
+        format!("{}{}", new_prefix, &key[matched_prefix.map_or(0, |p| p.len())..])
+    } else {
+        key.to_string()
+    };
+    
+    let location = match redirect.host_name {
+        Some(ref host) => format!("{}://{}/{}", redirect.protocol.as_deref().unwrap_or("http"), host, new_key),
+        None => format!("/_website/{}/{}", bucket, new_key),
+    };
+    let status = redirect.http_redirect_code.as_deref()
+        .and_then(|c| c.parse::<u16>().ok())
+        .and_then(|c| StatusCode::from_u16(c).ok())
+        .unwrap_or(StatusCode::MOVED_PERMANENTLY);
+    
+    Response::builder()
+        .status(status)
+        .header(header::LOCATION, location)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn website_object_response(
+    method: &Method,
+    status: StatusCode,
+    meta: &aws_data_core::storage::ObjectMetadata,
+    file: std::fs::File,
+) -> Response<Body> {
+    let builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, &meta.content_type)
+        .header(header::CONTENT_LENGTH, meta.size)
+        .header("ETag", &meta.etag)
+        .header("Last-Modified", &meta.last_modified);
+    
+    if *method == Method::HEAD {
+        builder.body(Body::empty()).unwrap()
+    } else {
+        builder.body(object_body(file)).unwrap()
+    }
+}
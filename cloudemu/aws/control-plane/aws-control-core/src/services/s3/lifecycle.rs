@@ -0,0 +1,52 @@
+//! Lifecycle sweeper: applies the buckets' lifecycle rules in the background.
+//! With `s3_lifecycle_day_secs` set, a lifecycle day passes in that many seconds and the
+//! sweeper runs often enough for tests to observe expiry.
+
+use crate::Emulator;
+use aws_data_core::error::Result;
+use aws_data_core::storage::LifecycleActions;
+use aws_data_core::Config;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Length of a lifecycle day
+pub fn day_length(config: &Config) -> Duration {
+    config.s3_lifecycle_day_secs.map_or(DAY, Duration::from_secs)
+}
+
+/// Time between sweeps: a tenth of a day, within bounds
+fn sweep_interval(day: Duration) -> Duration {
+    (day / 10).clamp(MIN_SWEEP_INTERVAL, MAX_SWEEP_INTERVAL)
+}
+
+/// Apply every bucket's lifecycle rules once
+pub fn sweep(emulator: &Emulator) -> Result<LifecycleActions> {
+    let day = chrono::Duration::from_std(day_length(&emulator.config)).unwrap_or(chrono::Duration::days(1));
+    let actions = emulator.storage.apply_lifecycle(chrono::Utc::now(), day)?;
+    if actions != LifecycleActions::default() {
+        info!(
+            "S3: Lifecycle expired {} object(s) and {} noncurrent version(s), transitioned {}, removed {} delete marker(s), aborted {} upload(s)",
+            actions.expired, actions.noncurrent_expired, actions.transitioned, actions.delete_markers_removed, actions.uploads_aborted
+        );
+    }
+    Ok(actions)
+}
+
+/// Sweep for as long as the emulator runs
+pub async fn run(emulator: Arc<Emulator>) {
+    let mut interval = tokio::time::interval(sweep_interval(day_length(&emulator.config)));
+    loop {
+        interval.tick().await;
+        let emulator = emulator.clone();
+        match tokio::task::spawn_blocking(move || sweep(&emulator)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("S3: Lifecycle sweep failed: {}", e),
+            Err(e) => warn!("S3: Lifecycle sweep panicked: {}", e),
+        }
+    }
+}
@@ -1,9 +1,15 @@
 //! S3 Service Implementation
 
 pub mod handlers;
+pub mod lifecycle;
+mod notifications;
+mod parquet;
+mod select;
 mod service;
+mod sql;
 mod xml;
 
+pub use notifications::BucketNotifications;
 pub use service::S3Service;
 
 #[cfg(test)]
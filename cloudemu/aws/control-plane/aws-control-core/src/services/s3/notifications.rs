@@ -0,0 +1,117 @@
+//! S3 event notifications, fed by object events on the resource event bus
+
+use crate::event_bus::{EventSubscriber, ResourceEvent};
+use aws_data_core::error::Result;
+use aws_data_core::storage::{NotificationRule, StorageEngine};
+use aws_data_core::Config;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+/// Delivers bucket notifications for object events published by the S3 handlers
+pub struct BucketNotifications {
+    storage: StorageEngine,
+    config: Config,
+}
+
+/// S3 event name for an object operation, e.g. `PutObject` -> `ObjectCreated:Put`
+fn event_name(event: &ResourceEvent) -> Option<&'static str> {
+    let name = match event.operation.as_str() {
+        "PutObject" => "ObjectCreated:Put",
+        "CopyObject" => "ObjectCreated:Copy",
+        "CompleteMultipartUpload" => "ObjectCreated:CompleteMultipartUpload",
+        "DeleteObject" if event.detail["deleteMarker"].as_bool() == Some(true) => "ObjectRemoved:DeleteMarkerCreated",
+        "DeleteObject" => "ObjectRemoved:Delete",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Whether a rule subscribes to the event (`s3:ObjectCreated:*` style wildcards) and key
+pub fn rule_matches(rule: &NotificationRule, event_name: &str, key: &str) -> bool {
+    let subscribed = rule.events.iter().any(|pattern| {
+        let pattern = pattern.strip_prefix("s3:").unwrap_or(pattern);
+        match pattern.strip_suffix('*') {
+            Some(prefix) => event_name.starts_with(prefix),
+            None => pattern == event_name,
+        }
+    });
+
+    subscribed
+        && rule.prefix.as_deref().is_none_or(|p| key.starts_with(p))
+        && rule.suffix.as_deref().is_none_or(|s| key.ends_with(s))
+}
+
+impl BucketNotifications {
+    pub fn new(storage: StorageEngine, config: Config) -> Self {
+        Self { storage, config }
+    }
+
+    fn notify(&self, event: &ResourceEvent, event_name: &str) -> Result<()> {
+        let bucket = event.detail["bucket"].as_str().unwrap_or("");
+        let key = event.detail["key"].as_str().unwrap_or("");
+        let config = self.storage.get_bucket_notification(bucket)?;
+
+        for rule in config.queue_configurations.iter().filter(|r| rule_matches(r, event_name, key)) {
+            let queue_name = rule.destination_arn.split(':').next_back().unwrap_or("");
+            let message = self.record(event, event_name, rule);
+            match self.storage.send_message(queue_name, &message.to_string()) {
+                Ok(_) => info!("S3: Sent {} notification for {}/{} to SQS queue {}", event_name, bucket, key, queue_name),
+                Err(e) => warn!("S3: Failed to send notification to SQS {}: {}", queue_name, e),
+            }
+        }
+        for rule in config.topic_configurations.iter().filter(|r| rule_matches(r, event_name, key)) {
+            info!("S3: Would publish {} notification to SNS topic {}", event_name, rule.destination_arn);
+        }
+        for rule in config.lambda_function_configurations.iter().filter(|r| rule_matches(r, event_name, key)) {
+            info!("S3: Would invoke Lambda {} for {} notification", rule.destination_arn, event_name);
+        }
+
+        Ok(())
+    }
+
+    /// S3 event message in the format delivered by AWS
+    fn record(&self, event: &ResourceEvent, event_name: &str, rule: &NotificationRule) -> Value {
+        let bucket = event.detail["bucket"].as_str().unwrap_or("");
+        json!({
+            "Records": [{
+                "eventVersion": "2.1",
+                "eventSource": "aws:s3",
+                "awsRegion": self.config.region,
+                "eventTime": event.time,
+                "eventName": event_name,
+                "userIdentity": { "principalId": format!("AWS:{}", self.config.account_id) },
+                "requestParameters": { "sourceIPAddress": "127.0.0.1" },
+                "responseElements": { "x-amz-request-id": event.id },
+                "s3": {
+                    "s3SchemaVersion": "1.0",
+                    "configurationId": rule.id,
+                    "bucket": {
+                        "name": bucket,
+                        "ownerIdentity": { "principalId": self.config.account_id },
+                        "arn": format!("arn:aws:s3:::{}", bucket)
+                    },
+                    "object": {
+                        "key": event.detail["key"],
+                        "size": event.detail["size"],
+                        "eTag": event.detail["eTag"],
+                        "versionId": event.detail["versionId"],
+                        "sequencer": format!("{:016X}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default())
+                    }
+                }
+            }]
+        })
+    }
+}
+
+impl EventSubscriber for BucketNotifications {
+    fn on_event(&self, event: &ResourceEvent) {
+        if event.service != "s3" {
+            return;
+        }
+        if let Some(name) = event_name(event) {
+            if let Err(e) = self.notify(event, name) {
+                warn!("S3: Failed to deliver {} notifications: {}", name, e);
+            }
+        }
+    }
+}
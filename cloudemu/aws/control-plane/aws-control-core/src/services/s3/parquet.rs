@@ -0,0 +1,653 @@
+//! Parquet-lite reader for S3 Select
+//!
+//! Reads flat schemas of `BOOLEAN`, `INT32`, `INT64`, `FLOAT`, `DOUBLE` and `BYTE_ARRAY`
+//! columns from data pages v1 and v2, PLAIN or dictionary encoded, uncompressed or compressed
+//! with Snappy or gzip. Nested and repeated columns, `INT96` and fixed-length byte arrays are
+//! rejected. Each row becomes a JSON object keyed by column name.
+
+use crate::error::EmulatorError;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::io::Read;
+
+const MAGIC: &[u8] = b"PAR1";
+
+// Physical types
+const BOOLEAN: i64 = 0;
+const INT32: i64 = 1;
+const INT64: i64 = 2;
+const FLOAT: i64 = 4;
+const DOUBLE: i64 = 5;
+const BYTE_ARRAY: i64 = 6;
+
+// Compression codecs
+const UNCOMPRESSED: i64 = 0;
+const SNAPPY: i64 = 1;
+const GZIP: i64 = 2;
+
+// Encodings
+const PLAIN: i64 = 0;
+const PLAIN_DICTIONARY: i64 = 2;
+const RLE_DICTIONARY: i64 = 8;
+
+// Page types
+const DATA_PAGE: i64 = 0;
+const DICTIONARY_PAGE: i64 = 2;
+const DATA_PAGE_V2: i64 = 3;
+
+fn invalid(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "ParquetParsingError", message: message.into() }
+}
+
+fn unsupported(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "UnsupportedParquetType", message: message.into() }
+}
+
+/// A value of the Thrift compact protocol
+#[derive(Debug, Clone)]
+enum Thrift {
+    Int(i64),
+    Double,
+    Binary(Vec<u8>),
+    Bool(bool),
+    List(Vec<Thrift>),
+    Struct(HashMap<i16, Thrift>),
+    Map,
+}
+
+type Fields = HashMap<i16, Thrift>;
+
+fn int(fields: &Fields, id: i16) -> Option<i64> {
+    match fields.get(&id) {
+        Some(Thrift::Int(i)) => Some(*i),
+        _ => None,
+    }
+}
+
+fn bool_field(fields: &Fields, id: i16) -> Option<bool> {
+    match fields.get(&id) {
+        Some(Thrift::Bool(b)) => Some(*b),
+        _ => None,
+    }
+}
+
+fn structure(fields: &Fields, id: i16) -> Option<&Fields> {
+    match fields.get(&id) {
+        Some(Thrift::Struct(s)) => Some(s),
+        _ => None,
+    }
+}
+
+fn list(fields: &Fields, id: i16) -> &[Thrift] {
+    match fields.get(&id) {
+        Some(Thrift::List(items)) => items,
+        _ => &[],
+    }
+}
+
+fn string(fields: &Fields, id: i16) -> Option<String> {
+    match fields.get(&id) {
+        Some(Thrift::Binary(b)) => Some(String::from_utf8_lossy(b).into_owned()),
+        _ => None,
+    }
+}
+
+/// Cursor over a byte slice, failing on truncated input
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], EmulatorError> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len())
+            .ok_or_else(|| invalid("Unexpected end of data"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, EmulatorError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, EmulatorError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            value |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("Varint too long"))
+    }
+
+    fn zigzag(&mut self) -> Result<i64, EmulatorError> {
+        let v = self.varint()?;
+        Ok((v >> 1) as i64 ^ -((v & 1) as i64))
+    }
+
+    fn u32_le(&mut self) -> Result<u32, EmulatorError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Thrift compact struct
+    fn thrift_struct(&mut self, depth: usize) -> Result<Fields, EmulatorError> {
+        if depth > 32 {
+            return Err(invalid("Metadata nested too deeply"));
+        }
+        let mut fields = HashMap::new();
+        let mut last_id = 0i16;
+        loop {
+            let header = self.byte()?;
+            if header == 0 {
+                return Ok(fields);
+            }
+            let delta = header >> 4;
+            let id = if delta == 0 { self.zigzag()? as i16 } else { last_id.wrapping_add(delta as i16) };
+            last_id = id;
+            let value = match header & 0x0f {
+                1 => Thrift::Bool(true),
+                2 => Thrift::Bool(false),
+                ty => self.thrift_value(ty, depth)?,
+            };
+            fields.insert(id, value);
+        }
+    }
+
+    fn thrift_value(&mut self, ty: u8, depth: usize) -> Result<Thrift, EmulatorError> {
+        Ok(match ty {
+            1 | 2 => Thrift::Bool(self.byte()? == 1),
+            3 => Thrift::Int(self.byte()? as i8 as i64),
+            4..=6 => Thrift::Int(self.zigzag()?),
+            7 => {
+                self.take(8)?;
+                Thrift::Double
+            }
+            8 => {
+                let len = self.varint()? as usize;
+                Thrift::Binary(self.take(len)?.to_vec())
+            }
+            9 | 10 => {
+                let header = self.byte()?;
+                let size = match header >> 4 {
+                    15 => self.varint()? as usize,
+                    n => n as usize,
+                };
+                let elem = header & 0x0f;
+                let mut items = Vec::with_capacity(size.min(1024));
+                for _ in 0..size {
+                    items.push(self.thrift_value(elem, depth + 1)?);
+                }
+                Thrift::List(items)
+            }
+            11 => {
+                let size = self.varint()? as usize;
+                if size > 0 {
+                    let types = self.byte()?;
+                    for _ in 0..size {
+                        self.thrift_value(types >> 4, depth + 1)?;
+                        self.thrift_value(types & 0x0f, depth + 1)?;
+                    }
+                }
+                Thrift::Map
+            }
+            12 => Thrift::Struct(self.thrift_struct(depth + 1)?),
+            other => return Err(invalid(format!("Unknown Thrift type {}", other))),
+        })
+    }
+}
+
+/// A leaf column of a flat schema
+struct Column {
+    name: String,
+    physical: i64,
+    optional: bool,
+}
+
+/// Read the rows of a Parquet file as JSON objects
+pub fn read_rows(data: &[u8]) -> Result<Vec<Value>, EmulatorError> {
+    if data.len() < 12 || !data.starts_with(MAGIC) || !data.ends_with(MAGIC) {
+        return Err(invalid("Not a Parquet file"));
+    }
+    let footer_len = u32::from_le_bytes(data[data.len() - 8..data.len() - 4].try_into().unwrap()) as usize;
+    let footer_start = (data.len() - 8).checked_sub(footer_len).filter(|s| *s >= MAGIC.len())
+        .ok_or_else(|| invalid("Invalid footer length"))?;
+    let metadata = Cursor::new(&data[footer_start..data.len() - 8]).thrift_struct(0)?;
+
+    let columns = schema_columns(&metadata)?;
+    let mut rows = Vec::new();
+    for row_group in list(&metadata, 4) {
+        let Thrift::Struct(row_group) = row_group else { return Err(invalid("Invalid row group")) };
+        let num_rows = int(row_group, 3).unwrap_or(0).max(0) as usize;
+        let chunks = list(row_group, 1);
+        if chunks.len() != columns.len() {
+            return Err(invalid("Row group does not match the schema"));
+        }
+        let values = chunks.iter().zip(&columns)
+            .map(|(chunk, column)| match chunk {
+                Thrift::Struct(chunk) => read_column_chunk(data, chunk, column, num_rows),
+                _ => Err(invalid("Invalid column chunk")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for i in 0..num_rows {
+            let mut row = Map::new();
+            for (column, values) in columns.iter().zip(&values) {
+                row.insert(column.name.clone(), values.get(i).cloned().unwrap_or(Value::Null));
+            }
+            rows.push(Value::Object(row));
+        }
+    }
+    Ok(rows)
+}
+
+fn schema_columns(metadata: &Fields) -> Result<Vec<Column>, EmulatorError> {
+    let schema = list(metadata, 2);
+    let mut columns = Vec::new();
+    for element in schema.iter().skip(1) {
+        let Thrift::Struct(element) = element else { return Err(invalid("Invalid schema element")) };
+        let name = string(element, 4).unwrap_or_default();
+        if int(element, 5).unwrap_or(0) > 0 {
+            return Err(unsupported(format!("Nested column {} is not supported", name)));
+        }
+        let optional = match int(element, 3).unwrap_or(0) {
+            0 => false,
+            1 => true,
+            _ => return Err(unsupported(format!("Repeated column {} is not supported", name))),
+        };
+        let physical = int(element, 1).ok_or_else(|| invalid(format!("Column {} has no type", name)))?;
+        if ![BOOLEAN, INT32, INT64, FLOAT, DOUBLE, BYTE_ARRAY].contains(&physical) {
+            return Err(unsupported(format!("Column {} has an unsupported type", name)));
+        }
+        columns.push(Column { name, physical, optional });
+    }
+    Ok(columns)
+}
+
+fn read_column_chunk(data: &[u8], chunk: &Fields, column: &Column, num_rows: usize) -> Result<Vec<Value>, EmulatorError> {
+    let meta = structure(chunk, 3).ok_or_else(|| invalid("Column chunk has no metadata"))?;
+    let codec = int(meta, 4).unwrap_or(UNCOMPRESSED);
+    let start = int(meta, 11).or_else(|| int(meta, 9)).ok_or_else(|| invalid("Column chunk has no pages"))?;
+    let size = int(meta, 7).unwrap_or(0);
+    let end = start.checked_add(size).filter(|end| start >= 0 && *end as usize <= data.len())
+        .ok_or_else(|| invalid("Column chunk is out of bounds"))? as usize;
+
+    let mut cursor = Cursor::new(&data[..end]);
+    cursor.pos = start as usize;
+    let mut dictionary = Vec::new();
+    let mut values = Vec::with_capacity(num_rows);
+    while cursor.pos < end && values.len() < num_rows {
+        let header = cursor.thrift_struct(0)?;
+        let page_type = int(&header, 1).unwrap_or(-1);
+        let uncompressed_size = int(&header, 2).unwrap_or(0).max(0) as usize;
+        let page = cursor.take(int(&header, 3).unwrap_or(0).max(0) as usize)?;
+        match page_type {
+            DICTIONARY_PAGE => {
+                let dict = structure(&header, 7).ok_or_else(|| invalid("Dictionary page has no header"))?;
+                let raw = decompress(codec, page, uncompressed_size)?;
+                dictionary = decode_plain(&raw, int(dict, 1).unwrap_or(0).max(0) as usize, column.physical)?;
+            }
+            DATA_PAGE => {
+                let page_header = structure(&header, 5).ok_or_else(|| invalid("Data page has no header"))?;
+                let count = int(page_header, 1).unwrap_or(0).max(0) as usize;
+                let raw = decompress(codec, page, uncompressed_size)?;
+                let mut cursor = Cursor::new(&raw);
+                let defined = if column.optional {
+                    let len = cursor.u32_le()? as usize;
+                    Some(decode_hybrid(cursor.take(len)?, 1, count)?)
+                } else {
+                    None
+                };
+                let encoding = int(page_header, 2).unwrap_or(PLAIN);
+                append_page(&mut values, &raw[cursor.pos..], encoding, count, defined, column, &dictionary)?;
+            }
+            DATA_PAGE_V2 => {
+                let page_header = structure(&header, 8).ok_or_else(|| invalid("Data page has no header"))?;
+                let count = int(page_header, 1).unwrap_or(0).max(0) as usize;
+                let def_len = int(page_header, 5).unwrap_or(0).max(0) as usize;
+                let rep_len = int(page_header, 6).unwrap_or(0).max(0) as usize;
+                let mut cursor = Cursor::new(page);
+                let defined = cursor.take(def_len)?;
+                cursor.take(rep_len)?;
+                let defined = if column.optional { Some(decode_hybrid(defined, 1, count)?) } else { None };
+                let raw = match bool_field(page_header, 7).unwrap_or(true) {
+                    true => decompress(codec, &page[cursor.pos..], uncompressed_size.saturating_sub(def_len + rep_len))?,
+                    false => page[cursor.pos..].to_vec(),
+                };
+                let encoding = int(page_header, 4).unwrap_or(PLAIN);
+                append_page(&mut values, &raw, encoding, count, defined, column, &dictionary)?;
+            }
+            // Index pages carry no values
+            _ => {}
+        }
+    }
+    Ok(values)
+}
+
+/// Decode a data page's values, with nulls where the definition levels are 0
+fn append_page(
+    values: &mut Vec<Value>,
+    data: &[u8],
+    encoding: i64,
+    count: usize,
+    defined: Option<Vec<u32>>,
+    column: &Column,
+    dictionary: &[Value],
+) -> Result<(), EmulatorError> {
+    let present = defined.as_ref().map_or(count, |levels| levels.iter().filter(|l| **l == 1).count());
+    let decoded = match encoding {
+        PLAIN => decode_plain(data, present, column.physical)?,
+        PLAIN_DICTIONARY | RLE_DICTIONARY => {
+            let (width, indices) = data.split_first().ok_or_else(|| invalid("Missing dictionary index width"))?;
+            decode_hybrid(indices, *width, present)?
+                .into_iter()
+                .map(|i| dictionary.get(i as usize).cloned().ok_or_else(|| invalid("Dictionary index out of range")))
+                .collect::<Result<Vec<_>, _>>()?
+        }
+        other => return Err(unsupported(format!("Encoding {} of column {} is not supported", other, column.name))),
+    };
+    match defined {
+        None => values.extend(decoded),
+        Some(levels) => {
+            let mut decoded = decoded.into_iter();
+            values.extend(levels.into_iter().map(|l| if l == 1 { decoded.next().unwrap_or(Value::Null) } else { Value::Null }));
+        }
+    }
+    Ok(())
+}
+
+fn decode_plain(data: &[u8], count: usize, physical: i64) -> Result<Vec<Value>, EmulatorError> {
+    let mut cursor = Cursor::new(data);
+    let mut values = Vec::with_capacity(count.min(data.len() * 8));
+    for i in 0..count {
+        values.push(match physical {
+            BOOLEAN => {
+                let byte = *data.get(i / 8).ok_or_else(|| invalid("Unexpected end of data"))?;
+                Value::Bool((byte >> (i % 8)) & 1 == 1)
+            }
+            INT32 => Value::from(i32::from_le_bytes(cursor.take(4)?.try_into().unwrap())),
+            INT64 => Value::from(i64::from_le_bytes(cursor.take(8)?.try_into().unwrap())),
+            FLOAT => float(f32::from_le_bytes(cursor.take(4)?.try_into().unwrap()) as f64),
+            DOUBLE => float(f64::from_le_bytes(cursor.take(8)?.try_into().unwrap())),
+            _ => {
+                let len = cursor.u32_le()? as usize;
+                Value::String(String::from_utf8_lossy(cursor.take(len)?).into_owned())
+            }
+        });
+    }
+    Ok(values)
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+/// Decode `count` values of the RLE / bit-packing hybrid encoding
+fn decode_hybrid(data: &[u8], width: u8, count: usize) -> Result<Vec<u32>, EmulatorError> {
+    if width > 32 {
+        return Err(invalid("Invalid bit width"));
+    }
+    let mut cursor = Cursor::new(data);
+    let mut values = Vec::with_capacity(count);
+    while values.len() < count {
+        let header = cursor.varint()?;
+        if header & 1 == 0 {
+            // RLE run: one value, repeated
+            let run = (header >> 1) as usize;
+            let mut value = 0u32;
+            for (i, b) in cursor.take(width.div_ceil(8) as usize)?.iter().enumerate() {
+                value |= u32::from(*b) << (8 * i);
+            }
+            values.extend(std::iter::repeat_n(value, run.min(count - values.len())));
+        } else {
+            // Bit-packed groups of 8 values, least significant bit first
+            let total = (header >> 1) as usize * 8;
+            let bytes = cursor.take((total * width as usize).div_ceil(8))?;
+            for i in 0..total.min(count - values.len()) {
+                let mut value = 0u32;
+                for bit in 0..width as usize {
+                    let at = i * width as usize + bit;
+                    value |= u32::from((bytes[at / 8] >> (at % 8)) & 1) << bit;
+                }
+                values.push(value);
+            }
+        }
+    }
+    Ok(values)
+}
+
+fn decompress(codec: i64, data: &[u8], size: usize) -> Result<Vec<u8>, EmulatorError> {
+    match codec {
+        UNCOMPRESSED => Ok(data.to_vec()),
+        SNAPPY => snappy(data),
+        GZIP => {
+            let mut raw = Vec::with_capacity(size);
+            flate2::read::MultiGzDecoder::new(data).read_to_end(&mut raw)
+                .map_err(|e| invalid(format!("Invalid gzip page: {}", e)))?;
+            Ok(raw)
+        }
+        other => Err(unsupported(format!("Compression codec {} is not supported", other))),
+    }
+}
+
+/// Decompress a raw Snappy block
+fn snappy(data: &[u8]) -> Result<Vec<u8>, EmulatorError> {
+    let mut cursor = Cursor::new(data);
+    let len = cursor.varint()? as usize;
+    let mut out = Vec::with_capacity(len.min(64 * 1024 * 1024));
+    while cursor.pos < data.len() {
+        let tag = cursor.byte()?;
+        let (length, offset) = match tag & 3 {
+            0 => {
+                let mut length = (tag >> 2) as usize;
+                if length >= 60 {
+                    let bytes = cursor.take(length - 59)?;
+                    length = bytes.iter().rev().fold(0, |acc, b| (acc << 8) | *b as usize);
+                }
+                out.extend_from_slice(cursor.take(length + 1)?);
+                continue;
+            }
+            1 => (((tag >> 2) & 7) as usize + 4, (((tag >> 5) as usize) << 8) | cursor.byte()? as usize),
+            2 => ((tag >> 2) as usize + 1, u16::from_le_bytes(cursor.take(2)?.try_into().unwrap()) as usize),
+            _ => ((tag >> 2) as usize + 1, cursor.u32_le()? as usize),
+        };
+        if offset == 0 || offset > out.len() {
+            return Err(invalid("Invalid Snappy copy offset"));
+        }
+        // Copies may overlap their own output
+        let start = out.len() - offset;
+        for i in 0..length {
+            out.push(out[start + i]);
+        }
+    }
+    if out.len() != len {
+        return Err(invalid("Snappy block has the wrong length"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    /// Thrift compact writer, enough for Parquet metadata
+    #[derive(Default)]
+    struct Writer {
+        out: Vec<u8>,
+        last: Vec<i16>,
+    }
+
+    impl Writer {
+        fn varint(&mut self, mut v: u64) {
+            while v >= 0x80 {
+                self.out.push(v as u8 | 0x80);
+                v >>= 7;
+            }
+            self.out.push(v as u8);
+        }
+
+        fn field(&mut self, id: i16, ty: u8) {
+            let last = self.last.last_mut().unwrap();
+            self.out.push((((id - *last) as u8) << 4) | ty);
+            *last = id;
+        }
+
+        fn int(&mut self, id: i16, v: i64) -> &mut Self {
+            self.field(id, 6);
+            self.varint(((v << 1) ^ (v >> 63)) as u64);
+            self
+        }
+
+        fn binary(&mut self, id: i16, v: &[u8]) -> &mut Self {
+            self.field(id, 8);
+            self.varint(v.len() as u64);
+            self.out.extend_from_slice(v);
+            self
+        }
+
+        fn begin(&mut self, id: i16) -> &mut Self {
+            self.field(id, 12);
+            self.last.push(0);
+            self
+        }
+
+        fn list(&mut self, id: i16, size: usize) -> &mut Self {
+            self.field(id, 9);
+            self.out.push(((size as u8) << 4) | 12);
+            self
+        }
+
+        /// Start a struct that is a list element
+        fn element(&mut self) -> &mut Self {
+            self.last.push(0);
+            self
+        }
+
+        fn end(&mut self) -> &mut Self {
+            self.out.push(0);
+            self.last.pop();
+            self
+        }
+    }
+
+    fn page(header: impl FnOnce(&mut Writer), data: &[u8]) -> Vec<u8> {
+        let mut writer = Writer { last: vec![0], ..Default::default() };
+        header(&mut writer);
+        writer.end();
+        writer.out.extend_from_slice(data);
+        writer.out
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    /// A Parquet file of three rows: required INT64 `id`, optional dictionary-encoded,
+    /// gzip-compressed `name` and required DOUBLE `score` in a v2 data page
+    pub(crate) fn sample_file() -> Vec<u8> {
+        let mut file = MAGIC.to_vec();
+
+        let ids: Vec<u8> = [1i64, 2, 3].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let id_offset = file.len();
+        file.extend(page(|w| {
+            w.int(1, DATA_PAGE).int(2, ids.len() as i64).int(3, ids.len() as i64);
+            w.begin(5).int(1, 3).int(2, PLAIN).int(3, 3).int(4, 3).end();
+        }, &ids));
+        let id_size = file.len() - id_offset;
+
+        // Dictionary ["alice", "bob"]; rows "bob", null, "alice"
+        let dictionary: Vec<u8> = ["alice", "bob"].iter()
+            .flat_map(|s| (s.len() as u32).to_le_bytes().into_iter().chain(s.bytes()))
+            .collect();
+        let dictionary = gzip(&dictionary);
+        // Definition levels 1, 0, 1 bit-packed, then indices 1, 0 bit-packed at width 1
+        let data = [2, 0, 0, 0, 0x03, 0x05, 1, 0x03, 0x01];
+        let data_gz = gzip(&data);
+        let name_offset = file.len();
+        file.extend(page(|w| {
+            w.int(1, DICTIONARY_PAGE).int(2, 16).int(3, dictionary.len() as i64);
+            w.begin(7).int(1, 2).int(2, PLAIN).end();
+        }, &dictionary));
+        file.extend(page(|w| {
+            w.int(1, DATA_PAGE).int(2, data.len() as i64).int(3, data_gz.len() as i64);
+            w.begin(5).int(1, 3).int(2, RLE_DICTIONARY).int(3, 3).int(4, 3).end();
+        }, &data_gz));
+        let name_size = file.len() - name_offset;
+
+        let scores: Vec<u8> = [9.5f64, 7.25, 3.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let score_offset = file.len();
+        file.extend(page(|w| {
+            w.int(1, DATA_PAGE_V2).int(2, scores.len() as i64).int(3, scores.len() as i64);
+            w.begin(8).int(1, 3).int(2, 0).int(3, 3).int(4, PLAIN).int(5, 0).int(6, 0).end();
+        }, &scores));
+        let score_size = file.len() - score_offset;
+
+        let mut meta = Writer { last: vec![0], ..Default::default() };
+        meta.int(1, 1);
+        meta.list(2, 4);
+        meta.element().binary(4, b"schema").int(5, 3).end();
+        meta.element().int(1, INT64).int(3, 0).binary(4, b"id").end();
+        meta.element().int(1, BYTE_ARRAY).int(3, 1).binary(4, b"name").int(6, 0).end();
+        meta.element().int(1, DOUBLE).int(3, 0).binary(4, b"score").end();
+        meta.int(3, 3);
+        meta.list(4, 1);
+        meta.element().list(1, 3);
+        for (name, ty, codec, offset, size, dict) in [
+            ("id", INT64, UNCOMPRESSED, id_offset, id_size, false),
+            ("name", BYTE_ARRAY, GZIP, name_offset, name_size, true),
+            ("score", DOUBLE, UNCOMPRESSED, score_offset, score_size, false),
+        ] {
+            meta.element().int(2, 0).begin(3).int(1, ty).binary(3, name.as_bytes()).int(4, codec).int(5, 3)
+                .int(6, size as i64).int(7, size as i64);
+            if dict {
+                meta.int(9, 0).int(11, offset as i64);
+            } else {
+                meta.int(9, offset as i64);
+            }
+            meta.end().end();
+        }
+        meta.int(2, 0).int(3, 3).end();
+        meta.end();
+
+        file.extend_from_slice(&meta.out);
+        file.extend_from_slice(&(meta.out.len() as u32).to_le_bytes());
+        file.extend_from_slice(MAGIC);
+        file
+    }
+
+    #[test]
+    fn test_read_rows() {
+        let rows = read_rows(&sample_file()).unwrap();
+        assert_eq!(rows, [
+            json!({ "id": 1, "name": "bob", "score": 9.5 }),
+            json!({ "id": 2, "name": null, "score": 7.25 }),
+            json!({ "id": 3, "name": "alice", "score": 3.0 }),
+        ]);
+
+        assert!(matches!(read_rows(b"id,name\n1,a\n"), Err(EmulatorError::InvalidParameter { code: "ParquetParsingError", .. })));
+    }
+
+    #[test]
+    fn test_snappy() {
+        // Literal "abcd", then a copy of 8 bytes (tag 0x11) at offset 4 that overlaps its output
+        let block = [12, 3 << 2, b'a', b'b', b'c', b'd', 0x11, 4];
+        assert_eq!(snappy(&block).unwrap(), b"abcdabcdabcd");
+        assert!(snappy(&[12, 0x11, 4]).is_err());
+    }
+
+    #[test]
+    fn test_decode_hybrid() {
+        // A run of five 7s, then 0 to 7 bit-packed at width 3
+        let data = [10, 7, 3, 0x88, 0xc6, 0xfa];
+        assert_eq!(decode_hybrid(&data, 3, 13).unwrap(), [7, 7, 7, 7, 7, 0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+}
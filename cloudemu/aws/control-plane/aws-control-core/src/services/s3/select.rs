@@ -0,0 +1,468 @@
+//! S3 Select: SelectObjectContent over CSV, JSON and Parquet objects.
+//! The query runs over the whole object and the result is framed as an AWS event stream of
+//! `Records` messages, then `Stats` and `End`.
+
+use super::parquet;
+use super::sql::{self, Query, Record, Row};
+use crate::error::EmulatorError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Read;
+use std::sync::Arc;
+
+/// Largest payload of a `Records` message
+const RECORDS_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SelectRequestXml {
+    expression: String,
+    expression_type: String,
+    #[serde(default)]
+    request_progress: Option<RequestProgressXml>,
+    input_serialization: InputSerializationXml,
+    output_serialization: OutputSerializationXml,
+    #[serde(default)]
+    scan_range: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RequestProgressXml {
+    #[serde(default)]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InputSerializationXml {
+    #[serde(default)]
+    compression_type: Option<String>,
+    #[serde(rename = "CSV", default)]
+    csv: Option<CsvXml>,
+    #[serde(rename = "JSON", default)]
+    json: Option<JsonXml>,
+    #[serde(default)]
+    parquet: Option<serde::de::IgnoredAny>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CsvXml {
+    file_header_info: Option<String>,
+    comments: Option<String>,
+    quote_escape_character: Option<String>,
+    record_delimiter: Option<String>,
+    field_delimiter: Option<String>,
+    quote_character: Option<String>,
+    quote_fields: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JsonXml {
+    #[serde(rename = "Type")]
+    kind: Option<String>,
+    record_delimiter: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OutputSerializationXml {
+    #[serde(rename = "CSV", default)]
+    csv: Option<CsvXml>,
+    #[serde(rename = "JSON", default)]
+    json: Option<JsonXml>,
+}
+
+/// CSV dialect of the input or the output
+#[derive(Debug, Clone, PartialEq)]
+struct CsvFormat {
+    field_delimiter: String,
+    record_delimiter: String,
+    quote: String,
+    quote_escape: String,
+    /// Input: prefix of comment lines
+    comments: Option<String>,
+    /// Input: `USE`, `IGNORE` or `NONE`
+    header: String,
+    /// Output: quote every field rather than only those that need it
+    quote_always: bool,
+}
+
+impl CsvFormat {
+    fn from_xml(xml: CsvXml) -> Result<Self, EmulatorError> {
+        // Blank values fall back to the defaults; the XML reader trims whitespace
+        let or_default = |value: Option<String>, default: &str| value.filter(|v| !v.is_empty()).unwrap_or_else(|| default.to_string());
+        let quote = or_default(xml.quote_character, "\"");
+        let header = or_default(xml.file_header_info, "NONE").to_ascii_uppercase();
+        if !["USE", "IGNORE", "NONE"].contains(&header.as_str()) {
+            return Err(EmulatorError::InvalidArgument(format!("Invalid FileHeaderInfo: {}", header)));
+        }
+        let quote_fields = or_default(xml.quote_fields, "ASNEEDED").to_ascii_uppercase();
+        if !["ASNEEDED", "ALWAYS"].contains(&quote_fields.as_str()) {
+            return Err(EmulatorError::InvalidArgument(format!("Invalid QuoteFields: {}", quote_fields)));
+        }
+        Ok(Self {
+            field_delimiter: or_default(xml.field_delimiter, ","),
+            record_delimiter: or_default(xml.record_delimiter, "\n"),
+            quote_escape: or_default(xml.quote_escape_character, &quote),
+            quote,
+            comments: xml.comments.filter(|c| !c.is_empty()),
+            header,
+            quote_always: quote_fields == "ALWAYS",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum InputFormat {
+    Csv(CsvFormat),
+    Json,
+    Parquet,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum OutputFormat {
+    Csv(CsvFormat),
+    Json { record_delimiter: String },
+}
+
+/// A parsed SelectObjectContent request
+#[derive(Debug)]
+pub struct SelectRequest {
+    query: Query,
+    gzip: bool,
+    input: InputFormat,
+    output: OutputFormat,
+    progress: bool,
+}
+
+/// Parse a SelectObjectContentRequest document
+pub fn parse_request(body: &str) -> Result<SelectRequest, EmulatorError> {
+    let xml: SelectRequestXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    if !xml.expression_type.eq_ignore_ascii_case("SQL") {
+        return Err(EmulatorError::InvalidParameter {
+            code: "InvalidExpressionType",
+            message: format!("ExpressionType {} is not supported", xml.expression_type),
+        });
+    }
+    if xml.scan_range.is_some() {
+        return Err(EmulatorError::NotImplemented("S3 Select ScanRange".into()));
+    }
+
+    let input_xml = xml.input_serialization;
+    let gzip = match input_xml.compression_type.as_deref().map(str::to_ascii_uppercase).as_deref() {
+        None | Some("") | Some("NONE") => false,
+        Some("GZIP") => true,
+        Some("BZIP2") => return Err(EmulatorError::NotImplemented("S3 Select over BZIP2 objects".into())),
+        Some(other) => return Err(EmulatorError::InvalidParameter {
+            code: "InvalidCompressionFormat",
+            message: format!("CompressionType {} is not supported", other),
+        }),
+    };
+    let input = match (input_xml.csv, input_xml.json, input_xml.parquet) {
+        (Some(csv), None, None) => InputFormat::Csv(CsvFormat::from_xml(csv)?),
+        (None, Some(json), None) => {
+            let kind = json.kind.unwrap_or_else(|| "DOCUMENT".into()).to_ascii_uppercase();
+            if kind != "DOCUMENT" && kind != "LINES" {
+                return Err(EmulatorError::InvalidArgument(format!("Invalid JSON Type: {}", kind)));
+            }
+            InputFormat::Json
+        }
+        (None, None, Some(_)) if gzip => return Err(EmulatorError::InvalidParameter {
+            code: "InvalidCompressionFormat",
+            message: "Parquet objects cannot have a CompressionType".into(),
+        }),
+        (None, None, Some(_)) => InputFormat::Parquet,
+        _ => return Err(EmulatorError::InvalidArgument("InputSerialization needs exactly one of CSV, JSON or Parquet".into())),
+    };
+    let output = match (xml.output_serialization.csv, xml.output_serialization.json) {
+        (Some(csv), None) => OutputFormat::Csv(CsvFormat::from_xml(csv)?),
+        (None, Some(json)) => OutputFormat::Json {
+            record_delimiter: json.record_delimiter.filter(|d| !d.is_empty()).unwrap_or_else(|| "\n".into()),
+        },
+        _ => return Err(EmulatorError::InvalidArgument("OutputSerialization needs exactly one of CSV or JSON".into())),
+    };
+
+    let query = Query::parse(&xml.expression)?;
+    if query.has_from_path() && !matches!(input, InputFormat::Json) {
+        return Err(EmulatorError::InvalidParameter {
+            code: "UnsupportedSyntax",
+            message: "A path after S3Object needs JSON input".into(),
+        });
+    }
+    Ok(SelectRequest {
+        query,
+        gzip,
+        input,
+        output,
+        progress: xml.request_progress.is_some_and(|p| p.enabled),
+    })
+}
+
+/// Run a request over an object's data, returning the event stream
+pub fn select(request: &SelectRequest, data: &[u8]) -> Result<Vec<u8>, EmulatorError> {
+    let raw = if request.gzip {
+        let mut raw = Vec::new();
+        flate2::read::MultiGzDecoder::new(data).read_to_end(&mut raw).map_err(|e| EmulatorError::InvalidParameter {
+            code: "InvalidCompressionFormat",
+            message: format!("Object is not valid gzip: {}", e),
+        })?;
+        raw
+    } else {
+        data.to_vec()
+    };
+
+    let records = read_records(&request.input, &raw)?;
+    let rows = request.query.run(records.into_iter().map(Ok))?;
+
+    let mut stream = Vec::new();
+    let mut returned = 0;
+    let mut chunk = Vec::new();
+    for row in rows {
+        write_row(&mut chunk, &row, &request.output);
+        if chunk.len() >= RECORDS_CHUNK_SIZE {
+            returned += chunk.len();
+            stream.extend(records_message(&chunk));
+            chunk.clear();
+        }
+    }
+    if !chunk.is_empty() {
+        returned += chunk.len();
+        stream.extend(records_message(&chunk));
+    }
+
+    let details = format!(
+        "<BytesScanned>{}</BytesScanned><BytesProcessed>{}</BytesProcessed><BytesReturned>{}</BytesReturned>",
+        data.len(), raw.len(), returned
+    );
+    if request.progress {
+        let payload = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Progress>{}</Progress>", details);
+        stream.extend(message(&[(":message-type", "event"), (":event-type", "Progress"), (":content-type", "text/xml")], payload.as_bytes()));
+    }
+    let payload = format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?><Stats>{}</Stats>", details);
+    stream.extend(message(&[(":message-type", "event"), (":event-type", "Stats"), (":content-type", "text/xml")], payload.as_bytes()));
+    stream.extend(message(&[(":message-type", "event"), (":event-type", "End")], &[]));
+    Ok(stream)
+}
+
+fn read_records(input: &InputFormat, raw: &[u8]) -> Result<Vec<Record>, EmulatorError> {
+    match input {
+        InputFormat::Csv(format) => {
+            let text = std::str::from_utf8(raw).map_err(|_| EmulatorError::InvalidParameter {
+                code: "InvalidTextEncoding",
+                message: "CSV objects must be UTF-8".into(),
+            })?;
+            let mut rows = parse_csv(text, format)?.into_iter();
+            let header = match format.header.as_str() {
+                "USE" => rows.next().map(Arc::new),
+                "IGNORE" => {
+                    rows.next();
+                    None
+                }
+                _ => None,
+            };
+            Ok(rows.map(|fields| Record::Csv { fields, header: header.clone() }).collect())
+        }
+        InputFormat::Json => serde_json::Deserializer::from_slice(raw)
+            .into_iter::<Value>()
+            .map(|value| value.map(Record::Json).map_err(|e| EmulatorError::InvalidParameter {
+                code: "JSONParsingError",
+                message: format!("Invalid JSON: {}", e),
+            }))
+            .collect(),
+        InputFormat::Parquet => Ok(parquet::read_rows(raw)?.into_iter().map(Record::Json).collect()),
+    }
+}
+
+/// Split CSV text into records of fields; blank lines and comment lines are skipped
+fn parse_csv(text: &str, format: &CsvFormat) -> Result<Vec<Vec<String>>, EmulatorError> {
+    let (quote, escape) = (format.quote.as_str(), format.quote_escape.as_str());
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut was_quoted = false;
+    let mut record_start = true;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if quoted {
+            if escape != quote && rest.starts_with(escape) && rest[escape.len()..].starts_with(quote) {
+                field.push_str(quote);
+                rest = &rest[escape.len() + quote.len()..];
+            } else if rest.starts_with(quote) && escape == quote && rest[quote.len()..].starts_with(quote) {
+                field.push_str(quote);
+                rest = &rest[2 * quote.len()..];
+            } else if rest.starts_with(quote) {
+                quoted = false;
+                rest = &rest[quote.len()..];
+            } else {
+                field.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+            continue;
+        }
+
+        if record_start && format.comments.as_deref().is_some_and(|prefix| rest.starts_with(prefix)) {
+            rest = rest.find(format.record_delimiter.as_str()).map_or("", |end| &rest[end + format.record_delimiter.len()..]);
+            continue;
+        }
+        record_start = false;
+        // With the default delimiter, CRLF line endings end records too
+        let delimiter = if format.record_delimiter == "\n" && rest.starts_with("\r\n") { "\r\n" } else { format.record_delimiter.as_str() };
+        if rest.starts_with(format.field_delimiter.as_str()) {
+            fields.push(std::mem::take(&mut field));
+            was_quoted = false;
+            rest = &rest[format.field_delimiter.len()..];
+        } else if rest.starts_with(delimiter) {
+            fields.push(std::mem::take(&mut field));
+            if fields.len() > 1 || !fields[0].is_empty() || was_quoted {
+                records.push(std::mem::take(&mut fields));
+            }
+            fields.clear();
+            was_quoted = false;
+            record_start = true;
+            rest = &rest[delimiter.len()..];
+        } else if rest.starts_with(quote) && field.is_empty() && !was_quoted {
+            quoted = true;
+            was_quoted = true;
+            rest = &rest[quote.len()..];
+        } else {
+            field.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    if quoted {
+        return Err(EmulatorError::InvalidParameter { code: "CSVParsingError", message: "Unterminated quoted field".into() });
+    }
+    if !record_start {
+        fields.push(field);
+        records.push(fields);
+    }
+    Ok(records)
+}
+
+fn write_row(out: &mut Vec<u8>, row: &Row, format: &OutputFormat) {
+    match format {
+        OutputFormat::Csv(csv) => {
+            let fields: Vec<String> = match row {
+                Row::Record(Record::Csv { fields, .. }) => fields.clone(),
+                Row::Record(Record::Json(Value::Object(map))) => map.values().map(sql::text).collect(),
+                Row::Record(Record::Json(value)) => vec![sql::text(value)],
+                Row::Fields(fields) => fields.iter().map(|(_, value)| sql::text(value)).collect(),
+            };
+            let fields: Vec<String> = fields.iter().map(|field| quote_csv(field, csv)).collect();
+            out.extend_from_slice(fields.join(&csv.field_delimiter).as_bytes());
+            out.extend_from_slice(csv.record_delimiter.as_bytes());
+        }
+        OutputFormat::Json { record_delimiter } => {
+            let json = match row {
+                Row::Record(Record::Csv { fields, header }) => json_object(fields.iter().enumerate().map(|(i, field)| {
+                    let name = header.as_ref().and_then(|h| h.get(i)).cloned().unwrap_or_else(|| format!("_{}", i + 1));
+                    (name, Value::String(field.clone()))
+                })),
+                Row::Record(Record::Json(value @ Value::Object(_))) => value.to_string(),
+                Row::Record(Record::Json(value)) => json_object(std::iter::once(("_1".to_string(), value.clone()))),
+                Row::Fields(fields) => json_object(fields.iter().cloned()),
+            };
+            out.extend_from_slice(json.as_bytes());
+            out.extend_from_slice(record_delimiter.as_bytes());
+        }
+    }
+}
+
+/// JSON object with its keys in the given order
+fn json_object(fields: impl Iterator<Item = (String, Value)>) -> String {
+    let members: Vec<String> = fields.map(|(name, value)| format!("{}:{}", Value::String(name), value)).collect();
+    format!("{{{}}}", members.join(","))
+}
+
+fn quote_csv(field: &str, format: &CsvFormat) -> String {
+    let needs_quotes = format.quote_always
+        || field.contains(format.field_delimiter.as_str())
+        || field.contains(format.record_delimiter.as_str())
+        || field.contains(format.quote.as_str())
+        || field.contains(['\r', '\n']);
+    if !needs_quotes {
+        return field.to_string();
+    }
+    let escaped = field.replace(format.quote.as_str(), &format!("{}{}", format.quote_escape, format.quote));
+    format!("{}{}{}", format.quote, escaped, format.quote)
+}
+
+fn records_message(payload: &[u8]) -> Vec<u8> {
+    message(&[(":message-type", "event"), (":event-type", "Records"), (":content-type", "application/octet-stream")], payload)
+}
+
+/// Frame an event stream message: prelude of total and header lengths with its CRC,
+/// string-valued headers, the payload and a CRC of the whole message
+fn message(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for (name, value) in headers {
+        encoded.push(name.len() as u8);
+        encoded.extend_from_slice(name.as_bytes());
+        encoded.push(7);
+        encoded.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        encoded.extend_from_slice(value.as_bytes());
+    }
+    let total = 12 + encoded.len() + payload.len() + 4;
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(&(total as u32).to_be_bytes());
+    out.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+    out.extend_from_slice(&crc32fast::hash(&out).to_be_bytes());
+    out.extend_from_slice(&encoded);
+    out.extend_from_slice(payload);
+    out.extend_from_slice(&crc32fast::hash(&out).to_be_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_format() -> CsvFormat {
+        CsvFormat::from_xml(CsvXml::default()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let mut format = csv_format();
+        format.comments = Some("#".into());
+        let text = "# comment\r\nid,name\r\n1,\"Smith, \"\"Jo\"\"\"\n\n2,\"multi\nline\"\n3,";
+        assert_eq!(parse_csv(text, &format).unwrap(), [
+            vec!["id", "name"],
+            vec!["1", "Smith, \"Jo\""],
+            vec!["2", "multi\nline"],
+            vec!["3", ""],
+        ]);
+        assert!(parse_csv("1,\"open\n", &format).is_err());
+
+        let format = CsvFormat { field_delimiter: "|".into(), record_delimiter: ";".into(), ..csv_format() };
+        assert_eq!(parse_csv("a|b;c|d;", &format).unwrap(), [vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn test_csv_output_quoting() {
+        let format = csv_format();
+        assert_eq!(quote_csv("plain", &format), "plain");
+        assert_eq!(quote_csv("a,b", &format), "\"a,b\"");
+        assert_eq!(quote_csv("say \"hi\"", &format), "\"say \"\"hi\"\"\"");
+        let always = CsvFormat { quote_always: true, ..format };
+        assert_eq!(quote_csv("plain", &always), "\"plain\"");
+    }
+
+    #[test]
+    fn test_message_framing() {
+        let frame = message(&[(":event-type", "End")], b"");
+        let total = u32::from_be_bytes(frame[0..4].try_into().unwrap()) as usize;
+        let headers = u32::from_be_bytes(frame[4..8].try_into().unwrap()) as usize;
+        assert_eq!(total, frame.len());
+        assert_eq!(headers, 1 + 11 + 1 + 2 + 3);
+        assert_eq!(u32::from_be_bytes(frame[8..12].try_into().unwrap()), crc32fast::hash(&frame[0..8]));
+        assert_eq!(&frame[13..24], b":event-type");
+        assert_eq!(u32::from_be_bytes(frame[total - 4..].try_into().unwrap()), crc32fast::hash(&frame[..total - 4]));
+    }
+}
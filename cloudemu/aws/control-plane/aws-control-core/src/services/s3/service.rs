@@ -1,6 +1,7 @@
 //! S3 Service - High-level operations
 
-use aws_data_core::storage::StorageEngine;
+use aws_data_core::storage::{ObjectMetadata, StorageEngine};
+use tracing::{info, warn};
 
 /// S3 Service
 pub struct S3Service {
@@ -17,4 +18,31 @@ impl S3Service {
     pub fn storage(&self) -> &StorageEngine {
         &self.storage
     }
+    
+    /// Queue asynchronous replication of a newly written object that was stored PENDING.
+    /// The object is already stored, so a failure to queue it is logged rather than returned.
+    pub fn schedule_replication(&self, bucket: &str, object: &ObjectMetadata) {
+        if object.replication_status.as_deref() != Some("PENDING") {
+            return;
+        }
+        let rule = match self.storage.match_replication_rule(bucket, &object.key) {
+            Ok(Some(rule)) => rule,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("S3: Replication of {}/{} could not be queued: {}", bucket, object.key, e);
+                return;
+            }
+        };
+        
+        let storage = self.storage.clone();
+        let bucket = bucket.to_string();
+        let key = object.key.clone();
+        let version_id = object.version_id.clone();
+        tokio::task::spawn_blocking(move || {
+            match storage.replicate_object(&bucket, &key, version_id.as_deref(), &rule) {
+                Ok(_) => info!("S3: Replicated {}/{} -> {} (rule {})", bucket, key, rule.destination_bucket, rule.id),
+                Err(e) => warn!("S3: Replication of {}/{} failed: {}", bucket, key, e),
+            }
+        });
+    }
 }
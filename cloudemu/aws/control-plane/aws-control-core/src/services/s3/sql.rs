@@ -0,0 +1,1152 @@
+//! SQL evaluator for S3 Select
+//!
+//! Supports `SELECT <projection> FROM S3Object[<path>] [[AS] alias] [WHERE <condition>] [LIMIT <n>]`.
+//! Projections are `*`, expressions with optional `AS` names, or the aggregates `COUNT`, `SUM`,
+//! `AVG`, `MIN` and `MAX`. Expressions have comparisons, `AND`/`OR`/`NOT`, arithmetic, `||`,
+//! `LIKE`, `BETWEEN`, `IN`, `IS [NOT] NULL`, `CAST` and the functions `LOWER`, `UPPER`, `TRIM`,
+//! `CHAR_LENGTH`, `SUBSTRING`, `COALESCE` and `NULLIF`.
+//!
+//! Columns are `_1`, `_2`, ... or header names for CSV, and paths such as `s.address.city` or
+//! `s.tags[0]` for JSON. Unquoted names match case-insensitively. CSV fields are strings; when
+//! one is compared with or added to a number it is read as a number, so `WHERE s._2 > 10` works
+//! without a `CAST`.
+
+use crate::error::EmulatorError;
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+/// A record of the object being queried
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    /// CSV fields, with the header names when the file has a header
+    Csv { fields: Vec<String>, header: Option<Arc<Vec<String>>> },
+    /// A JSON value, or a Parquet row
+    Json(Value),
+}
+
+/// A row of the query result
+#[derive(Debug, Clone, PartialEq)]
+pub enum Row {
+    /// A whole record, for `SELECT *`
+    Record(Record),
+    /// Named values of the projection, in order
+    Fields(Vec<(String, Value)>),
+}
+
+/// Step of a path into a JSON record
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Attribute; quoted names match exactly
+    Key { name: String, quoted: bool },
+    Index(usize),
+    /// `[*]`: every element of an array
+    Wildcard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Concat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Int,
+    Float,
+    String,
+    Bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Column(Vec<Step>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    IsNull { expr: Box<Expr>, negated: bool },
+    Like { expr: Box<Expr>, pattern: Box<Expr>, escape: Option<char>, negated: bool },
+    Between { expr: Box<Expr>, low: Box<Expr>, high: Box<Expr>, negated: bool },
+    In { expr: Box<Expr>, list: Vec<Expr>, negated: bool },
+    Cast(Box<Expr>, Type),
+    Function(String, Vec<Expr>),
+    /// An aggregate; no argument for `COUNT(*)`
+    Aggregate(Aggregate, Option<Box<Expr>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Projection {
+    All,
+    Items(Vec<(Expr, Option<String>)>),
+}
+
+/// A parsed S3 Select query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    projection: Projection,
+    /// Path after `S3Object`, e.g. `[*].records[*]`
+    from: Vec<Step>,
+    alias: Option<String>,
+    condition: Option<Expr>,
+    limit: Option<usize>,
+}
+
+fn parse_error(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "ParseUnexpectedToken", message: message.into() }
+}
+
+fn unsupported(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "UnsupportedSyntax", message: message.into() }
+}
+
+fn invalid_arguments(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "EvaluatorInvalidArguments", message: message.into() }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Identifier or keyword
+    Word(String),
+    QuotedIdent(String),
+    Str(String),
+    Number(Number),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 17] = ["<>", "!=", "<=", ">=", "||", "=", "<", ">", "+", "-", "*", "/", "%", ",", "(", ")", "."];
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, EmulatorError> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '\'' || c == '"' {
+            // Strings and quoted identifiers; a doubled quote stands for itself
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(parse_error("Unterminated quoted text")),
+                    Some(&q) if q == c && chars.get(i + 1) == Some(&c) => {
+                        text.push(c);
+                        i += 2;
+                    }
+                    Some(&q) if q == c => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&other) => {
+                        text.push(other);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(if c == '\'' { Token::Str(text) } else { Token::QuotedIdent(text) });
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i].eq_ignore_ascii_case(&'e')
+                || ((chars[i] == '+' || chars[i] == '-') && chars[i - 1].eq_ignore_ascii_case(&'e'))) {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = match text.parse::<i64>() {
+                Ok(n) => Number::from(n),
+                Err(_) => text.parse::<f64>().ok().and_then(Number::from_f64)
+                    .ok_or_else(|| parse_error(format!("Invalid number: {}", text)))?,
+            };
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else if c == '[' || c == ']' {
+            tokens.push(Token::Symbol(if c == '[' { "[" } else { "]" }));
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let symbol = SYMBOLS.iter().find(|s| rest.starts_with(**s))
+                .ok_or_else(|| parse_error(format!("Unexpected character: {}", c)))?;
+            tokens.push(Token::Symbol(symbol));
+            i += symbol.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), EmulatorError> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(parse_error(format!("Expected {} but found {}", keyword, self.describe_next())))
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), EmulatorError> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            Err(parse_error(format!("Expected {} but found {}", symbol, self.describe_next())))
+        }
+    }
+
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            None => "the end of the expression".into(),
+            Some(Token::Word(w)) | Some(Token::QuotedIdent(w)) => w.clone(),
+            Some(Token::Str(s)) => format!("'{}'", s),
+            Some(Token::Number(n)) => n.to_string(),
+            Some(Token::Symbol(s)) => s.to_string(),
+        }
+    }
+
+    /// Identifier, quoted or not; keywords that end a clause are not identifiers
+    fn identifier(&mut self) -> Option<(String, bool)> {
+        match self.peek() {
+            Some(Token::QuotedIdent(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Some((name, true))
+            }
+            Some(Token::Word(word)) if !is_reserved(word) => {
+                let word = word.clone();
+                self.pos += 1;
+                Some((word, false))
+            }
+            _ => None,
+        }
+    }
+
+    fn query(&mut self) -> Result<Query, EmulatorError> {
+        self.expect_keyword("SELECT")?;
+        let projection = if self.symbol("*") {
+            Projection::All
+        } else {
+            let mut items = Vec::new();
+            loop {
+                let expr = self.expr()?;
+                let name = if self.keyword("AS") {
+                    Some(self.identifier().ok_or_else(|| parse_error("Expected a name after AS"))?.0)
+                } else {
+                    None
+                };
+                items.push((expr, name));
+                if !self.symbol(",") {
+                    break;
+                }
+            }
+            Projection::Items(items)
+        };
+
+        self.expect_keyword("FROM")?;
+        match self.identifier() {
+            Some((table, _)) if table.eq_ignore_ascii_case("S3Object") => {}
+            _ => return Err(parse_error(format!("Expected S3Object but found {}", self.describe_next()))),
+        }
+        let from = self.path_steps()?;
+        self.keyword("AS");
+        let alias = self.identifier().map(|(alias, _)| alias);
+
+        let condition = if self.keyword("WHERE") { Some(self.expr()?) } else { None };
+        let limit = if self.keyword("LIMIT") {
+            match self.next() {
+                Some(Token::Number(n)) if n.as_u64().is_some() => Some(n.as_u64().unwrap() as usize),
+                _ => return Err(parse_error("LIMIT takes a non-negative integer")),
+            }
+        } else {
+            None
+        };
+        if self.peek().is_some() {
+            return Err(parse_error(format!("Unexpected {}", self.describe_next())));
+        }
+
+        let query = Query { projection, from, alias, condition, limit };
+        if let Some(condition) = &query.condition {
+            if condition.has_aggregate() {
+                return Err(unsupported("Aggregates are not allowed in WHERE"));
+            }
+        }
+        if let Projection::Items(items) = &query.projection {
+            let aggregates = items.iter().filter(|(expr, _)| expr.has_aggregate()).count();
+            if aggregates > 0 && aggregates < items.len() {
+                return Err(unsupported("A projection cannot mix aggregates and other expressions"));
+            }
+        }
+        Ok(query)
+    }
+
+    /// `.name`, `[n]` and `[*]` steps following a column or `S3Object`
+    fn path_steps(&mut self) -> Result<Vec<Step>, EmulatorError> {
+        let mut steps = Vec::new();
+        loop {
+            if self.symbol(".") {
+                if self.symbol("*") {
+                    steps.push(Step::Wildcard);
+                    continue;
+                }
+                let (name, quoted) = match self.next() {
+                    Some(Token::Word(w)) => (w, false),
+                    Some(Token::QuotedIdent(w)) => (w, true),
+                    _ => return Err(parse_error("Expected an attribute name after '.'")),
+                };
+                steps.push(Step::Key { name, quoted });
+            } else if self.symbol("[") {
+                if self.symbol("*") {
+                    steps.push(Step::Wildcard);
+                } else {
+                    match self.next() {
+                        Some(Token::Number(n)) if n.as_u64().is_some() => steps.push(Step::Index(n.as_u64().unwrap() as usize)),
+                        Some(Token::Str(name)) => steps.push(Step::Key { name, quoted: true }),
+                        _ => return Err(parse_error("Expected an index, a quoted name or * in brackets")),
+                    }
+                }
+                self.expect_symbol("]")?;
+            } else {
+                return Ok(steps);
+            }
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, EmulatorError> {
+        let mut left = self.and()?;
+        while self.keyword("OR") {
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, EmulatorError> {
+        let mut left = self.not()?;
+        while self.keyword("AND") {
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(self.not()?));
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<Expr, EmulatorError> {
+        if self.keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, EmulatorError> {
+        let left = self.additive()?;
+        let ops = [("=", BinaryOp::Eq), ("!=", BinaryOp::Ne), ("<>", BinaryOp::Ne), ("<=", BinaryOp::Le), (">=", BinaryOp::Ge), ("<", BinaryOp::Lt), (">", BinaryOp::Gt)];
+        for (symbol, op) in ops {
+            if self.symbol(symbol) {
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(self.additive()?)));
+            }
+        }
+        if self.keyword("IS") {
+            let negated = self.keyword("NOT");
+            if !self.keyword("NULL") {
+                self.expect_keyword("MISSING")?;
+            }
+            return Ok(Expr::IsNull { expr: Box::new(left), negated });
+        }
+        let negated = self.keyword("NOT");
+        if self.keyword("LIKE") {
+            let pattern = self.additive()?;
+            let escape = if self.keyword("ESCAPE") {
+                match self.next() {
+                    Some(Token::Str(s)) if s.chars().count() == 1 => s.chars().next(),
+                    _ => return Err(parse_error("ESCAPE takes a single character")),
+                }
+            } else {
+                None
+            };
+            return Ok(Expr::Like { expr: Box::new(left), pattern: Box::new(pattern), escape, negated });
+        }
+        if self.keyword("BETWEEN") {
+            let low = self.additive()?;
+            self.expect_keyword("AND")?;
+            let high = self.additive()?;
+            return Ok(Expr::Between { expr: Box::new(left), low: Box::new(low), high: Box::new(high), negated });
+        }
+        if self.keyword("IN") {
+            self.expect_symbol("(")?;
+            let mut list = vec![self.expr()?];
+            while self.symbol(",") {
+                list.push(self.expr()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expr::In { expr: Box::new(left), list, negated });
+        }
+        if negated {
+            return Err(parse_error(format!("Expected LIKE, BETWEEN or IN after NOT but found {}", self.describe_next())));
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Expr, EmulatorError> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.symbol("+") {
+                BinaryOp::Add
+            } else if self.symbol("-") {
+                BinaryOp::Sub
+            } else if self.symbol("||") {
+                BinaryOp::Concat
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.multiplicative()?));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, EmulatorError> {
+        let mut left = self.unary()?;
+        loop {
+            let op = if self.symbol("*") {
+                BinaryOp::Mul
+            } else if self.symbol("/") {
+                BinaryOp::Div
+            } else if self.symbol("%") {
+                BinaryOp::Mod
+            } else {
+                return Ok(left);
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, EmulatorError> {
+        if self.symbol("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, EmulatorError> {
+        if self.symbol("(") {
+            let expr = self.expr()?;
+            self.expect_symbol(")")?;
+            return Ok(expr);
+        }
+        match self.peek().cloned() {
+            Some(Token::Str(s)) => {
+                self.pos += 1;
+                Ok(Expr::Literal(Value::String(s)))
+            }
+            Some(Token::Number(n)) => {
+                self.pos += 1;
+                Ok(Expr::Literal(Value::Number(n)))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("NULL") || word.eq_ignore_ascii_case("MISSING") => {
+                self.pos += 1;
+                Ok(Expr::Literal(Value::Null))
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("TRUE") || word.eq_ignore_ascii_case("FALSE") => {
+                self.pos += 1;
+                Ok(Expr::Literal(Value::Bool(word.eq_ignore_ascii_case("TRUE"))))
+            }
+            Some(Token::Word(word)) if matches!(self.tokens.get(self.pos + 1), Some(Token::Symbol("("))) => {
+                self.pos += 2;
+                self.call(&word.to_ascii_uppercase())
+            }
+            Some(Token::Word(_)) | Some(Token::QuotedIdent(_)) => {
+                let (name, quoted) = self.identifier()
+                    .ok_or_else(|| parse_error(format!("Expected an expression but found {}", self.describe_next())))?;
+                let mut steps = vec![Step::Key { name, quoted }];
+                steps.extend(self.path_steps()?);
+                Ok(Expr::Column(steps))
+            }
+            _ => Err(parse_error(format!("Expected an expression but found {}", self.describe_next()))),
+        }
+    }
+
+    /// Function call, after its opening parenthesis
+    fn call(&mut self, name: &str) -> Result<Expr, EmulatorError> {
+        let aggregate = match name {
+            "COUNT" => Some(Aggregate::Count),
+            "SUM" => Some(Aggregate::Sum),
+            "AVG" => Some(Aggregate::Avg),
+            "MIN" => Some(Aggregate::Min),
+            "MAX" => Some(Aggregate::Max),
+            _ => None,
+        };
+        if let Some(aggregate) = aggregate {
+            if aggregate == Aggregate::Count && self.symbol("*") {
+                self.expect_symbol(")")?;
+                return Ok(Expr::Aggregate(aggregate, None));
+            }
+            let arg = self.expr()?;
+            self.expect_symbol(")")?;
+            return Ok(Expr::Aggregate(aggregate, Some(Box::new(arg))));
+        }
+
+        if name == "CAST" {
+            let expr = self.expr()?;
+            self.expect_keyword("AS")?;
+            let ty = match self.next() {
+                Some(Token::Word(w)) => match w.to_ascii_uppercase().as_str() {
+                    "INT" | "INTEGER" | "BIGINT" => Type::Int,
+                    "FLOAT" | "REAL" | "DOUBLE" | "DECIMAL" | "NUMERIC" => Type::Float,
+                    "STRING" | "VARCHAR" | "CHAR" => Type::String,
+                    "BOOL" | "BOOLEAN" => Type::Bool,
+                    other => return Err(unsupported(format!("CAST to {} is not supported", other))),
+                },
+                _ => return Err(parse_error("Expected a type after AS")),
+            };
+            self.expect_symbol(")")?;
+            return Ok(Expr::Cast(Box::new(expr), ty));
+        }
+
+        let mut args = Vec::new();
+        if !self.symbol(")") {
+            args.push(self.expr()?);
+            if name == "SUBSTRING" && self.keyword("FROM") {
+                args.push(self.expr()?);
+                if self.keyword("FOR") {
+                    args.push(self.expr()?);
+                }
+            } else {
+                while self.symbol(",") {
+                    args.push(self.expr()?);
+                }
+            }
+            self.expect_symbol(")")?;
+        }
+        let arity = match name {
+            "LOWER" | "UPPER" | "TRIM" | "CHAR_LENGTH" | "CHARACTER_LENGTH" => 1..=1,
+            "SUBSTRING" => 2..=3,
+            "NULLIF" => 2..=2,
+            "COALESCE" => 1..=usize::MAX,
+            _ => return Err(unsupported(format!("Function {} is not supported", name))),
+        };
+        if !arity.contains(&args.len()) {
+            return Err(invalid_arguments(format!("Wrong number of arguments to {}", name)));
+        }
+        Ok(Expr::Function(name.to_string(), args))
+    }
+}
+
+/// Keywords that cannot start an alias or a column
+fn is_reserved(word: &str) -> bool {
+    ["SELECT", "FROM", "WHERE", "LIMIT", "AS", "AND", "OR", "NOT", "IS", "LIKE", "BETWEEN", "IN", "ESCAPE"]
+        .iter()
+        .any(|k| word.eq_ignore_ascii_case(k))
+}
+
+impl Expr {
+    fn has_aggregate(&self) -> bool {
+        match self {
+            Expr::Aggregate(..) => true,
+            Expr::Literal(_) | Expr::Column(_) => false,
+            Expr::Not(e) | Expr::Neg(e) | Expr::Cast(e, _) | Expr::IsNull { expr: e, .. } => e.has_aggregate(),
+            Expr::Binary(_, l, r) => l.has_aggregate() || r.has_aggregate(),
+            Expr::Like { expr, pattern, .. } => expr.has_aggregate() || pattern.has_aggregate(),
+            Expr::Between { expr, low, high, .. } => expr.has_aggregate() || low.has_aggregate() || high.has_aggregate(),
+            Expr::In { expr, list, .. } => expr.has_aggregate() || list.iter().any(Expr::has_aggregate),
+            Expr::Function(_, args) => args.iter().any(Expr::has_aggregate),
+        }
+    }
+
+    /// Name of a projected column: its last attribute, as S3 names it
+    fn default_name(&self) -> Option<String> {
+        match self {
+            Expr::Column(steps) => steps.iter().rev().find_map(|step| match step {
+                Step::Key { name, .. } => Some(name.clone()),
+                _ => None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Query {
+    /// Parse a query
+    pub fn parse(sql: &str) -> Result<Self, EmulatorError> {
+        let mut parser = Parser { tokens: tokenize(sql)?, pos: 0 };
+        parser.query()
+    }
+
+    /// The path after `S3Object` selects records inside JSON values
+    pub fn has_from_path(&self) -> bool {
+        !self.from.iter().all(|step| *step == Step::Wildcard)
+    }
+
+    /// Run the query over the records of an object
+    pub fn run(&self, records: impl Iterator<Item = Result<Record, EmulatorError>>) -> Result<Vec<Row>, EmulatorError> {
+        let mut matched = Vec::new();
+        for record in records {
+            for record in self.unnest(record?) {
+                let keep = match &self.condition {
+                    Some(condition) => truthy(&self.eval(condition, &record)?),
+                    None => true,
+                };
+                if keep {
+                    matched.push(record);
+                }
+            }
+        }
+
+        let limit = self.limit.unwrap_or(usize::MAX);
+        match &self.projection {
+            Projection::All => Ok(matched.into_iter().take(limit).map(Row::Record).collect()),
+            Projection::Items(items) if items.iter().any(|(expr, _)| expr.has_aggregate()) => {
+                let fields = items.iter().enumerate()
+                    .map(|(i, (expr, name))| Ok((column_name(name, expr, i), self.aggregate(expr, &matched)?)))
+                    .collect::<Result<Vec<_>, EmulatorError>>()?;
+                Ok(vec![Row::Fields(fields)].into_iter().take(limit).collect())
+            }
+            Projection::Items(items) => matched.iter().take(limit)
+                .map(|record| {
+                    let fields = items.iter().enumerate()
+                        .map(|(i, (expr, name))| Ok((column_name(name, expr, i), self.eval(expr, record)?)))
+                        .collect::<Result<Vec<_>, EmulatorError>>()?;
+                    Ok(Row::Fields(fields))
+                })
+                .collect(),
+        }
+    }
+
+    /// Records the `FROM` path selects inside a record
+    fn unnest(&self, record: Record) -> Vec<Record> {
+        if !self.has_from_path() {
+            return match record {
+                // `S3Object[*]` over a document that is an array: its elements
+                Record::Json(Value::Array(items)) if !self.from.is_empty() => items.into_iter().map(Record::Json).collect(),
+                record => vec![record],
+            };
+        }
+        let Record::Json(value) = record else { return Vec::new() };
+        let mut values = vec![value];
+        for step in &self.from {
+            values = values.into_iter().flat_map(|value| match (step, value) {
+                (Step::Wildcard, Value::Array(items)) => items,
+                (Step::Wildcard, value) => vec![value],
+                (step, value) => lookup(&value, step).into_iter().collect(),
+            }).collect();
+        }
+        values.into_iter().map(Record::Json).collect()
+    }
+
+    fn resolve(&self, steps: &[Step], record: &Record) -> Value {
+        let steps = match (steps.first(), &self.alias) {
+            (Some(Step::Key { name, .. }), Some(alias)) if name.eq_ignore_ascii_case(alias) => &steps[1..],
+            _ => steps,
+        };
+        match record {
+            Record::Csv { fields, header } => {
+                let (Some(Step::Key { name, quoted }), []) = (steps.first(), steps.get(1..).unwrap_or_default()) else {
+                    return Value::Null;
+                };
+                let position = name.strip_prefix('_')
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|n| *n > 0)
+                    .map(|n| n - 1)
+                    .or_else(|| header.as_ref()?.iter().position(|h| if *quoted { h == name } else { h.eq_ignore_ascii_case(name) }));
+                position.and_then(|i| fields.get(i)).map_or(Value::Null, |field| Value::String(field.clone()))
+            }
+            Record::Json(value) => {
+                let mut current = value.clone();
+                for step in steps {
+                    match lookup(&current, step) {
+                        Some(next) => current = next,
+                        None => return Value::Null,
+                    }
+                }
+                current
+            }
+        }
+    }
+
+    fn eval(&self, expr: &Expr, record: &Record) -> Result<Value, EmulatorError> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Column(steps) => self.resolve(steps, record),
+            Expr::Not(e) => match self.eval(e, record)? {
+                Value::Null => Value::Null,
+                v => Value::Bool(!truthy(&v)),
+            },
+            Expr::Neg(e) => arithmetic(BinaryOp::Sub, &Value::from(0), &self.eval(e, record)?)?,
+            Expr::Binary(BinaryOp::And, l, r) => {
+                let left = self.eval(l, record)?;
+                if left != Value::Null && !truthy(&left) {
+                    return Ok(Value::Bool(false));
+                }
+                let right = self.eval(r, record)?;
+                match (left, right) {
+                    (_, r) if r != Value::Null && !truthy(&r) => Value::Bool(false),
+                    (Value::Null, _) | (_, Value::Null) => Value::Null,
+                    _ => Value::Bool(true),
+                }
+            }
+            Expr::Binary(BinaryOp::Or, l, r) => {
+                let left = self.eval(l, record)?;
+                if truthy(&left) {
+                    return Ok(Value::Bool(true));
+                }
+                let right = self.eval(r, record)?;
+                match (left, right) {
+                    (_, r) if truthy(&r) => Value::Bool(true),
+                    (Value::Null, _) | (_, Value::Null) => Value::Null,
+                    _ => Value::Bool(false),
+                }
+            }
+            Expr::Binary(BinaryOp::Concat, l, r) => match (self.eval(l, record)?, self.eval(r, record)?) {
+                (Value::Null, _) | (_, Value::Null) => Value::Null,
+                (l, r) => Value::String(text(&l) + &text(&r)),
+            },
+            Expr::Binary(op @ (BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod), l, r) => {
+                arithmetic(*op, &self.eval(l, record)?, &self.eval(r, record)?)?
+            }
+            Expr::Binary(op, l, r) => {
+                let ordering = compare(&self.eval(l, record)?, &self.eval(r, record)?);
+                match ordering {
+                    None => Value::Null,
+                    Some(ordering) => Value::Bool(match op {
+                        BinaryOp::Eq => ordering == Ordering::Equal,
+                        BinaryOp::Ne => ordering != Ordering::Equal,
+                        BinaryOp::Lt => ordering == Ordering::Less,
+                        BinaryOp::Le => ordering != Ordering::Greater,
+                        BinaryOp::Gt => ordering == Ordering::Greater,
+                        _ => ordering != Ordering::Less,
+                    }),
+                }
+            }
+            Expr::IsNull { expr, negated } => Value::Bool((self.eval(expr, record)? == Value::Null) != *negated),
+            Expr::Like { expr, pattern, escape, negated } => match (self.eval(expr, record)?, self.eval(pattern, record)?) {
+                (Value::Null, _) | (_, Value::Null) => Value::Null,
+                (value, pattern) => Value::Bool(like(&text(&value), &text(&pattern), *escape) != *negated),
+            },
+            Expr::Between { expr, low, high, negated } => {
+                let value = self.eval(expr, record)?;
+                let above = compare(&value, &self.eval(low, record)?).map(|o| o != Ordering::Less);
+                let below = compare(&value, &self.eval(high, record)?).map(|o| o != Ordering::Greater);
+                match (above, below) {
+                    (Some(a), Some(b)) => Value::Bool((a && b) != *negated),
+                    _ => Value::Null,
+                }
+            }
+            Expr::In { expr, list, negated } => {
+                let value = self.eval(expr, record)?;
+                if value == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let mut found = false;
+                for item in list {
+                    if compare(&value, &self.eval(item, record)?) == Some(Ordering::Equal) {
+                        found = true;
+                        break;
+                    }
+                }
+                Value::Bool(found != *negated)
+            }
+            Expr::Cast(expr, ty) => cast(self.eval(expr, record)?, *ty)?,
+            Expr::Function(name, args) => {
+                let args = args.iter().map(|arg| self.eval(arg, record)).collect::<Result<Vec<_>, _>>()?;
+                function(name, args)?
+            }
+            Expr::Aggregate(..) => return Err(unsupported("Aggregates are only allowed in the projection")),
+        })
+    }
+
+    /// Evaluate an aggregate projection over the matching records
+    fn aggregate(&self, expr: &Expr, records: &[Record]) -> Result<Value, EmulatorError> {
+        match expr {
+            Expr::Aggregate(Aggregate::Count, None) => Ok(Value::from(records.len())),
+            Expr::Aggregate(aggregate, Some(arg)) => {
+                let mut values = Vec::new();
+                for record in records {
+                    let value = self.eval(arg, record)?;
+                    if value != Value::Null {
+                        values.push(value);
+                    }
+                }
+                match aggregate {
+                    Aggregate::Count => Ok(Value::from(values.len())),
+                    Aggregate::Min | Aggregate::Max => {
+                        let wanted = if *aggregate == Aggregate::Min { Ordering::Less } else { Ordering::Greater };
+                        Ok(values.into_iter().reduce(|best, v| if compare(&v, &best) == Some(wanted) { v } else { best }).unwrap_or(Value::Null))
+                    }
+                    Aggregate::Sum | Aggregate::Avg => {
+                        if values.is_empty() {
+                            return Ok(Value::Null);
+                        }
+                        let count = values.len();
+                        let mut sum = Value::from(0);
+                        for value in values {
+                            sum = arithmetic(BinaryOp::Add, &sum, &value)?;
+                        }
+                        if *aggregate == Aggregate::Sum {
+                            Ok(sum)
+                        } else {
+                            Ok(float(number(&sum).unwrap_or_default() / count as f64))
+                        }
+                    }
+                }
+            }
+            // An expression over aggregates, e.g. `SUM(s.a) / COUNT(*)`
+            Expr::Binary(op, l, r) => {
+                let (l, r) = (self.aggregate(l, records)?, self.aggregate(r, records)?);
+                self.eval(&Expr::Binary(*op, Box::new(Expr::Literal(l)), Box::new(Expr::Literal(r))), &Record::Json(Value::Null))
+            }
+            Expr::Cast(e, ty) => cast(self.aggregate(e, records)?, *ty),
+            Expr::Neg(e) => arithmetic(BinaryOp::Sub, &Value::from(0), &self.aggregate(e, records)?),
+            Expr::Literal(value) => Ok(value.clone()),
+            _ => Err(unsupported("Only arithmetic and CAST may combine aggregates")),
+        }
+    }
+}
+
+fn column_name(name: &Option<String>, expr: &Expr, index: usize) -> String {
+    name.clone()
+        .or_else(|| expr.default_name())
+        .unwrap_or_else(|| format!("_{}", index + 1))
+}
+
+/// Step into a JSON value; unquoted keys fall back to a case-insensitive match
+fn lookup(value: &Value, step: &Step) -> Option<Value> {
+    match (step, value) {
+        (Step::Key { name, quoted }, Value::Object(map)) => map.get(name)
+            .or_else(|| if *quoted { None } else { map.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v) })
+            .cloned(),
+        (Step::Index(i), Value::Array(items)) => items.get(*i).cloned(),
+        (Step::Wildcard, value) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+fn truthy(value: &Value) -> bool {
+    matches!(value, Value::Bool(true))
+}
+
+/// Text of a value as it is output and concatenated
+pub fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Numeric value of a number, or of a string holding one
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn integer(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+        (Value::Bool(b), Value::String(s)) => s.parse::<bool>().ok().map(|s| b.cmp(&s)),
+        (Value::String(s), Value::Bool(b)) => s.parse::<bool>().ok().map(|s| s.cmp(b)),
+        (l, r) => match (number(l), number(r)) {
+            (Some(l), Some(r)) => l.partial_cmp(&r),
+            _ => (l == r).then_some(Ordering::Equal),
+        },
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, EmulatorError> {
+    if *left == Value::Null || *right == Value::Null {
+        return Ok(Value::Null);
+    }
+    let invalid = || EmulatorError::InvalidParameter {
+        code: "InvalidDataType",
+        message: format!("Arithmetic needs numbers, not {} and {}", left, right),
+    };
+    let is_integer = |v: &Value| match v {
+        Value::Number(n) => n.is_i64(),
+        Value::String(s) => s.trim().parse::<i64>().is_ok(),
+        _ => false,
+    };
+    if is_integer(left) && is_integer(right) {
+        let (l, r) = (integer(left).ok_or_else(invalid)?, integer(right).ok_or_else(invalid)?);
+        if matches!(op, BinaryOp::Div | BinaryOp::Mod) && r == 0 {
+            return Err(EmulatorError::InvalidParameter { code: "EvaluatorDivisionByZero", message: "Division by zero".into() });
+        }
+        let result = match op {
+            BinaryOp::Add => l.checked_add(r),
+            BinaryOp::Sub => l.checked_sub(r),
+            BinaryOp::Mul => l.checked_mul(r),
+            BinaryOp::Div => l.checked_div(r),
+            _ => l.checked_rem(r),
+        };
+        return result.map(Value::from).ok_or_else(|| EmulatorError::InvalidParameter {
+            code: "IntegerOverflow",
+            message: "Integer overflow".into(),
+        });
+    }
+    let (l, r) = (number(left).ok_or_else(invalid)?, number(right).ok_or_else(invalid)?);
+    Ok(float(match op {
+        BinaryOp::Add => l + r,
+        BinaryOp::Sub => l - r,
+        BinaryOp::Mul => l * r,
+        BinaryOp::Div => l / r,
+        _ => l % r,
+    }))
+}
+
+fn cast(value: Value, ty: Type) -> Result<Value, EmulatorError> {
+    if value == Value::Null {
+        return Ok(Value::Null);
+    }
+    let failed = || EmulatorError::InvalidParameter { code: "CastFailed", message: format!("Cannot cast {} to {:?}", value, ty) };
+    Ok(match ty {
+        Type::Int => match integer(&value) {
+            Some(i) => Value::from(i),
+            None => Value::from(number(&value).ok_or_else(failed)?.trunc() as i64),
+        },
+        Type::Float => float(number(&value).ok_or_else(failed)?),
+        Type::String => Value::String(text(&value)),
+        Type::Bool => match &value {
+            Value::Bool(b) => Value::Bool(*b),
+            Value::String(s) => Value::Bool(s.trim().to_ascii_lowercase().parse().map_err(|_| failed())?),
+            Value::Number(n) => Value::Bool(n.as_f64() != Some(0.0)),
+            _ => return Err(failed()),
+        },
+    })
+}
+
+fn function(name: &str, args: Vec<Value>) -> Result<Value, EmulatorError> {
+    if name == "COALESCE" {
+        return Ok(args.into_iter().find(|v| *v != Value::Null).unwrap_or(Value::Null));
+    }
+    if name == "NULLIF" {
+        return Ok(if compare(&args[0], &args[1]) == Some(Ordering::Equal) { Value::Null } else { args[0].clone() });
+    }
+    if args[0] == Value::Null {
+        return Ok(Value::Null);
+    }
+    let s = text(&args[0]);
+    Ok(match name {
+        "LOWER" => Value::String(s.to_lowercase()),
+        "UPPER" => Value::String(s.to_uppercase()),
+        "TRIM" => Value::String(s.trim().to_string()),
+        "CHAR_LENGTH" | "CHARACTER_LENGTH" => Value::from(s.chars().count()),
+        _ => {
+            // SUBSTRING: 1-based start, which may be before the first character
+            let start = integer(&args[1]).ok_or_else(|| invalid_arguments("SUBSTRING takes an integer start"))?;
+            let end = match args.get(2) {
+                Some(length) => start.saturating_add(integer(length).filter(|l| *l >= 0)
+                    .ok_or_else(|| invalid_arguments("SUBSTRING takes a non-negative length"))?),
+                None => i64::MAX,
+            };
+            let (from, to) = (start.max(1) - 1, end.max(1) - 1);
+            Value::String(s.chars().skip(from as usize).take(to.saturating_sub(from) as usize).collect())
+        }
+    })
+}
+
+/// SQL `LIKE`: `%` matches any run of characters, `_` any one character
+fn like(value: &str, pattern: &str, escape: Option<char>) -> bool {
+    enum Token {
+        Any,
+        One,
+        Char(char),
+    }
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            c if Some(c) == escape => Token::Char(chars.next().unwrap_or(c)),
+            '%' => Token::Any,
+            '_' => Token::One,
+            c => Token::Char(c),
+        });
+    }
+    let value: Vec<char> = value.chars().collect();
+    // matches[j]: the first i characters of the value match the first j tokens
+    let mut matches = vec![false; tokens.len() + 1];
+    matches[0] = true;
+    for (j, token) in tokens.iter().enumerate() {
+        if matches!(token, Token::Any) {
+            matches[j + 1] = matches[j];
+        }
+    }
+    for c in &value {
+        let mut next = vec![false; tokens.len() + 1];
+        for (j, token) in tokens.iter().enumerate() {
+            next[j + 1] = match token {
+                Token::Any => next[j] || matches[j + 1],
+                Token::One => matches[j],
+                Token::Char(t) => matches[j] && t == c,
+            };
+        }
+        matches = next;
+    }
+    matches[tokens.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn csv(rows: &[&[&str]], header: Option<&[&str]>) -> Vec<Result<Record, EmulatorError>> {
+        let header = header.map(|h| Arc::new(h.iter().map(|s| s.to_string()).collect::<Vec<_>>()));
+        rows.iter().map(|row| Ok(Record::Csv {
+            fields: row.iter().map(|s| s.to_string()).collect(),
+            header: header.clone(),
+        })).collect()
+    }
+
+    fn fields(rows: Vec<Row>) -> Vec<Vec<(String, Value)>> {
+        rows.into_iter().map(|row| match row {
+            Row::Fields(fields) => fields,
+            Row::Record(record) => panic!("expected fields, got {:?}", record),
+        }).collect()
+    }
+
+    #[test]
+    fn test_select_csv() {
+        let header: &[&str] = &["name", "city", "age"];
+        let rows: &[&[&str]] = &[&["alice", "Paris", "31"], &["bob", "Berlin", "25"], &["carol", "Paris", "42"]];
+
+        let query = Query::parse("SELECT s.name, s._3 AS years FROM S3Object s WHERE s.city = 'Paris' AND s.age > 35").unwrap();
+        let result = fields(query.run(csv(rows, Some(header)).into_iter()).unwrap());
+        assert_eq!(result, [vec![("name".to_string(), json!("carol")), ("years".to_string(), json!("42"))]]);
+
+        let query = Query::parse("select count(*), sum(cast(_3 as int)), avg(_3), max(_1) from s3object where _2 like 'P%'").unwrap();
+        let result = fields(query.run(csv(rows, None).into_iter()).unwrap());
+        assert_eq!(result[0], [
+            ("_1".to_string(), json!(2)),
+            ("_2".to_string(), json!(73)),
+            ("_3".to_string(), json!(36.5)),
+            ("_4".to_string(), json!("carol")),
+        ]);
+
+        let query = Query::parse("SELECT * FROM S3Object LIMIT 2").unwrap();
+        assert_eq!(query.run(csv(rows, None).into_iter()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_select_json() {
+        let records = [
+            Record::Json(json!({ "id": 1, "user": { "Name": "alice", "tags": ["admin", "dev"] }, "score": 9.5 })),
+            Record::Json(json!({ "id": 2, "user": { "Name": "bob", "tags": [] }, "score": null })),
+            Record::Json(json!({ "id": 3, "user": { "Name": "Carol", "tags": ["dev"] }, "score": 7 })),
+        ];
+
+        let query = Query::parse(r#"SELECT s.id, UPPER(s.user.name) AS name, s.user.tags[0] FROM S3Object[*] s WHERE s.score IS NOT NULL AND s.id IN (1, 3) AND NOT s.id BETWEEN 2 AND 2"#).unwrap();
+        let result = fields(query.run(records.iter().cloned().map(Ok)).unwrap());
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], [
+            ("id".to_string(), json!(1)),
+            ("name".to_string(), json!("ALICE")),
+            ("tags".to_string(), json!("admin")),
+        ]);
+
+        // Quoted names match exactly
+        let query = Query::parse(r#"SELECT s.user."name" FROM S3Object s"#).unwrap();
+        assert_eq!(fields(query.run(records.iter().cloned().map(Ok)).unwrap())[0][0].1, Value::Null);
+
+        // A FROM path selects records nested in documents
+        let document = vec![Ok(Record::Json(json!({ "items": [{ "sku": "a", "qty": 2 }, { "sku": "b", "qty": 5 }] })))];
+        let query = Query::parse("SELECT i.sku, i.qty * 2 + 1 FROM S3Object[*].items[*] i WHERE i.qty >= 3").unwrap();
+        assert_eq!(fields(query.run(document.into_iter()).unwrap()), [vec![("sku".to_string(), json!("b")), ("_2".to_string(), json!(11))]]);
+    }
+
+    #[test]
+    fn test_functions_and_like() {
+        assert!(like("report-2024.csv", "report-%.csv", None));
+        assert!(like("a_b", "a!_b", Some('!')));
+        assert!(!like("axb", "a!_b", Some('!')));
+        assert!(like("abc", "_b_", None));
+        assert!(!like("abcd", "_b_", None));
+
+        let query = Query::parse("SELECT SUBSTRING(_1, 2, 3), SUBSTRING(_1 FROM 4), CHAR_LENGTH(_1), COALESCE(_9, 'none'), NULLIF(_1, 'x'), TRIM('  y ') || '!' FROM S3Object").unwrap();
+        let result = fields(query.run(csv(&[&["abcdef"]], None).into_iter()).unwrap());
+        let values: Vec<_> = result[0].iter().map(|(_, v)| v.clone()).collect();
+        assert_eq!(values, [json!("bcd"), json!("def"), json!(6), json!("none"), json!("abcdef"), json!("y!")]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let code = |sql: &str| match Query::parse(sql) {
+            Err(EmulatorError::InvalidParameter { code, .. }) => code,
+            other => panic!("expected an error for {}, got {:?}", sql, other),
+        };
+        assert_eq!(code("SELECT FROM S3Object"), "ParseUnexpectedToken");
+        assert_eq!(code("SELECT * FROM table"), "ParseUnexpectedToken");
+        assert_eq!(code("SELECT * FROM S3Object WHERE"), "ParseUnexpectedToken");
+        assert_eq!(code("SELECT * FROM S3Object WHERE _1 = 'open"), "ParseUnexpectedToken");
+        assert_eq!(code("SELECT _1, COUNT(*) FROM S3Object"), "UnsupportedSyntax");
+        assert_eq!(code("SELECT * FROM S3Object WHERE COUNT(*) > 1"), "UnsupportedSyntax");
+        assert_eq!(code("SELECT MD5(_1) FROM S3Object"), "UnsupportedSyntax");
+        assert_eq!(code("SELECT LOWER(_1, _2) FROM S3Object"), "EvaluatorInvalidArguments");
+    }
+}
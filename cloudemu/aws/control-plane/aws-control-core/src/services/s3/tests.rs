@@ -56,3 +56,518 @@ async fn test_s3_bucket_lifecycle() {
     let response = app.clone().oneshot(req).await.unwrap();
     assert_eq!(response.status(), StatusCode::NO_CONTENT);
 }
+
+#[tokio::test]
+async fn test_s3_bucket_replication() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let send = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    // Source bucket in the default region, destination in another region
+    let response = app.clone().oneshot(send("PUT", "/source", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let dest_config = r#"<CreateBucketConfiguration><LocationConstraint>eu-west-1</LocationConstraint></CreateBucketConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/replica", dest_config)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("GET", "/replica?location", "")).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("eu-west-1"));
+
+    let versioning = r#"<VersioningConfiguration><Status>Enabled</Status></VersioningConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/source?versioning", versioning)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let replication = r#"<ReplicationConfiguration>
+  <Role>arn:aws:iam::000000000000:role/replication</Role>
+  <Rule>
+    <ID>all</ID>
+    <Status>Enabled</Status>
+    <Filter><Prefix></Prefix></Filter>
+    <Destination><Bucket>arn:aws:s3:::replica</Bucket></Destination>
+  </Rule>
+</ReplicationConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/source?replication", replication)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("GET", "/source?replication", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("PUT", "/source/data.txt", "replicate me")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-amz-replication-status"], "PENDING");
+
+    // Replication is asynchronous; wait for the replica to appear
+    let mut replicated = false;
+    for _ in 0..50 {
+        let response = app.clone().oneshot(send("HEAD", "/replica/data.txt", "")).await.unwrap();
+        if response.status() == StatusCode::OK {
+            assert_eq!(response.headers()["x-amz-replication-status"], "REPLICA");
+            replicated = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(replicated, "object was not replicated");
+
+    let response = app.clone().oneshot(send("HEAD", "/source/data.txt", "")).await.unwrap();
+    assert_eq!(response.headers()["x-amz-replication-status"], "COMPLETED");
+
+    let response = app.clone().oneshot(send("GET", "/replica/data.txt", "")).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&body[..], b"replicate me");
+}
+
+#[tokio::test]
+async fn test_s3_static_website() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let send = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let read_body = |response: axum::response::Response| async move {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8_lossy(&bytes).to_string()
+    };
+
+    app.clone().oneshot(send("PUT", "/site", "")).await.unwrap();
+    app.clone().oneshot(send("PUT", "/site/index.html", "<h1>home</h1>")).await.unwrap();
+    app.clone().oneshot(send("PUT", "/site/404.html", "<h1>missing</h1>")).await.unwrap();
+    app.clone().oneshot(send("PUT", "/site/app/index.html", "<h1>app</h1>")).await.unwrap();
+
+    // No website configuration yet
+    let response = app.clone().oneshot(send("GET", "/_website/site/", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let website = r#"<WebsiteConfiguration>
+  <IndexDocument><Suffix>index.html</Suffix></IndexDocument>
+  <ErrorDocument><Key>404.html</Key></ErrorDocument>
+  <RoutingRules>
+    <RoutingRule>
+      <Condition><KeyPrefixEquals>old/</KeyPrefixEquals></Condition>
+      <Redirect><ReplaceKeyPrefixWith>app/</ReplaceKeyPrefixWith></Redirect>
+    </RoutingRule>
+  </RoutingRules>
+</WebsiteConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/site?website", website)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("GET", "/site?website", "")).await.unwrap();
+    assert!(read_body(response).await.contains("<Suffix>index.html</Suffix>"));
+
+    // Index documents are served for the root and for "directories"
+    let response = app.clone().oneshot(send("GET", "/_website/site", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(read_body(response).await, "<h1>home</h1>");
+    let response = app.clone().oneshot(send("GET", "/_website/site/app/", "")).await.unwrap();
+    assert_eq!(read_body(response).await, "<h1>app</h1>");
+
+    // Directory without trailing slash redirects
+    let response = app.clone().oneshot(send("GET", "/_website/site/app", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FOUND);
+    assert_eq!(response.headers()["location"], "/_website/site/app/");
+
+    // Routing rules rewrite the key prefix
+    let response = app.clone().oneshot(send("GET", "/_website/site/old/index.html", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(response.headers()["location"], "/_website/site/app/index.html");
+
+    // Missing keys serve the error document with a 404
+    let response = app.clone().oneshot(send("GET", "/_website/site/nope.html", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(read_body(response).await, "<h1>missing</h1>");
+
+    // Redirect-all sends every request to another host
+    let redirect_all = r#"<WebsiteConfiguration>
+  <RedirectAllRequestsTo><HostName>example.com</HostName><Protocol>https</Protocol></RedirectAllRequestsTo>
+</WebsiteConfiguration>"#;
+    app.clone().oneshot(send("PUT", "/site?website", redirect_all)).await.unwrap();
+    let response = app.clone().oneshot(send("GET", "/_website/site/about", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(response.headers()["location"], "https://example.com/about");
+
+    let response = app.clone().oneshot(send("DELETE", "/site?website", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_s3_event_notifications() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    emulator.storage.create_queue("images", "000000000000", "us-east-1").unwrap();
+
+    let send = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    app.clone().oneshot(send("PUT", "/uploads", "")).await.unwrap();
+
+    let notification = r#"<NotificationConfiguration>
+  <QueueConfiguration>
+    <Id>new-images</Id>
+    <Queue>arn:aws:sqs:us-east-1:000000000000:images</Queue>
+    <Event>s3:ObjectCreated:*</Event>
+    <Filter><S3Key><FilterRule><Name>prefix</Name><Value>images/</Value></FilterRule></S3Key></Filter>
+  </QueueConfiguration>
+</NotificationConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/uploads?notification", notification)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("GET", "/uploads?notification", "")).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&bytes).contains("<Id>new-images</Id>"));
+
+    // Only created objects under the prefix are delivered
+    app.clone().oneshot(send("PUT", "/uploads/images/cat.png", "meow")).await.unwrap();
+    app.clone().oneshot(send("PUT", "/uploads/docs/readme.txt", "hello")).await.unwrap();
+    app.clone().oneshot(send("DELETE", "/uploads/images/cat.png", "")).await.unwrap();
+
+    let messages = emulator.storage.receive_message("images", 10).unwrap();
+    assert_eq!(messages.len(), 1);
+    let event: serde_json::Value = serde_json::from_str(&messages[0].body).unwrap();
+    let record = &event["Records"][0];
+    assert_eq!(record["eventSource"], "aws:s3");
+    assert_eq!(record["eventName"], "ObjectCreated:Put");
+    assert_eq!(record["s3"]["configurationId"], "new-images");
+    assert_eq!(record["s3"]["bucket"]["name"], "uploads");
+    assert_eq!(record["s3"]["object"]["key"], "images/cat.png");
+    assert_eq!(record["s3"]["object"]["size"], 4);
+}
+
+#[tokio::test]
+async fn test_s3_large_object_streaming() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let req = Request::builder().method("PUT").uri("/large").body(Body::empty()).unwrap();
+    assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
+
+    // Larger than axum's default limit for buffered bodies
+    let data: Vec<u8> = (0..5 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let req = Request::builder()
+        .method("PUT")
+        .uri("/large/blob.bin")
+        .body(Body::from(data.clone()))
+        .unwrap();
+    assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
+
+    let req = Request::builder().method("GET").uri("/large/blob.bin").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-length"], data.len().to_string().as_str());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(body.len(), data.len());
+    assert!(body[..] == data[..]);
+
+    // Multipart parts are streamed too
+    let req = Request::builder().method("POST").uri("/large/multi.bin?uploads").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let xml = String::from_utf8(body.to_vec()).unwrap();
+    let upload_id = xml.split("<UploadId>").nth(1).unwrap().split("</UploadId>").next().unwrap();
+
+    // Every part but the last is at least 5 MiB
+    let chunks = [&data[..], &data[..1024]];
+    let mut complete = String::from("<CompleteMultipartUpload>");
+    for (part, chunk) in chunks.iter().enumerate() {
+        let req = Request::builder()
+            .method("PUT")
+            .uri(format!("/large/multi.bin?partNumber={}&uploadId={}", part + 1, upload_id))
+            .body(Body::from(chunk.to_vec()))
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()["etag"].to_str().unwrap();
+        complete.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", part + 1, etag));
+    }
+    complete.push_str("</CompleteMultipartUpload>");
+    let req = Request::builder()
+        .method("POST")
+        .uri(format!("/large/multi.bin?uploadId={}", upload_id))
+        .body(Body::from(complete))
+        .unwrap();
+    assert_eq!(app.clone().oneshot(req).await.unwrap().status(), StatusCode::OK);
+
+    let req = Request::builder().method("GET").uri("/large/multi.bin").body(Body::empty()).unwrap();
+    let response = app.oneshot(req).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(body[..] == chunks.concat()[..]);
+}
+
+#[tokio::test]
+async fn test_s3_multipart_upload_api() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+    let send = |method: &str, uri: String, body: Body| {
+        let req = Request::builder().method(method).uri(uri).header("content-type", "text/plain").body(body).unwrap();
+        app.clone().oneshot(req)
+    };
+    let text = |response: axum::response::Response| async move {
+        String::from_utf8(axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+    };
+    let field = |xml: &str, name: &str| xml.split(&format!("<{}>", name)).nth(1).unwrap().split(&format!("</{}>", name)).next().unwrap().to_string();
+
+    send("PUT", "/mp".into(), Body::empty()).await.unwrap();
+    send("PUT", "/mp/source.txt".into(), Body::from("0123456789")).await.unwrap();
+
+    let response = send("POST", "/mp/target.txt?uploads".into(), Body::empty()).await.unwrap();
+    let upload_id = field(&text(response).await, "UploadId");
+
+    // Part 1 is uploaded, part 2 copied from a range of another object
+    let response = send("PUT", format!("/mp/target.txt?partNumber=1&uploadId={}", upload_id), Body::from(vec![b'a'; 5 * 1024 * 1024])).await.unwrap();
+    let etag1 = response.headers()["etag"].to_str().unwrap().to_string();
+    let req = Request::builder()
+        .method("PUT")
+        .uri(format!("/mp/target.txt?partNumber=2&uploadId={}", upload_id))
+        .header("x-amz-copy-source", "/mp/source.txt")
+        .header("x-amz-copy-source-range", "bytes=2-5")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag2 = field(&text(response).await, "ETag");
+
+    let response = send("GET", format!("/mp/target.txt?uploadId={}&max-parts=1", upload_id), Body::empty()).await.unwrap();
+    let listed = text(response).await;
+    assert_eq!(field(&listed, "IsTruncated"), "true");
+    assert_eq!(field(&listed, "ETag"), etag1);
+    let response = send("GET", format!("/mp/target.txt?uploadId={}&part-number-marker=1", upload_id), Body::empty()).await.unwrap();
+    assert_eq!(field(&text(response).await, "Size"), "4");
+
+    // Parts out of order are rejected
+    let parts = |order: [(u32, &str); 2]| format!(
+        "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+        order.iter().map(|(n, etag)| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", n, etag)).collect::<String>()
+    );
+    let response = send("POST", format!("/mp/target.txt?uploadId={}", upload_id), Body::from(parts([(2, &etag2), (1, &etag1)]))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(field(&text(response).await, "Code"), "InvalidPartOrder");
+
+    let response = send("POST", format!("/mp/target.txt?uploadId={}", upload_id), Body::from(parts([(1, &etag1), (2, &etag2)]))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = field(&text(response).await, "ETag");
+    assert!(etag.ends_with("-2\""));
+
+    let response = send("HEAD", "/mp/target.txt".into(), Body::empty()).await.unwrap();
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    assert_eq!(response.headers()["content-length"], (5 * 1024 * 1024 + 4).to_string().as_str());
+
+    // Aborted uploads are gone
+    let response = send("POST", "/mp/aborted.txt?uploads".into(), Body::empty()).await.unwrap();
+    let upload_id = field(&text(response).await, "UploadId");
+    let response = send("DELETE", format!("/mp/aborted.txt?uploadId={}", upload_id), Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = send("GET", format!("/mp/aborted.txt?uploadId={}", upload_id), Body::empty()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(field(&text(response).await, "Code"), "NoSuchUpload");
+}
+
+#[tokio::test]
+async fn test_s3_lifecycle_configuration() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let send = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    app.clone().oneshot(send("PUT", "/aging", "")).await.unwrap();
+    let response = app.clone().oneshot(send("GET", "/aging?lifecycle", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("<Code>NoSuchLifecycleConfiguration</Code>"));
+
+    let lifecycle = r#"<LifecycleConfiguration>
+  <Rule>
+    <ID>logs</ID>
+    <Filter><Prefix>logs/</Prefix></Filter>
+    <Status>Enabled</Status>
+    <Expiration><Days>30</Days></Expiration>
+  </Rule>
+</LifecycleConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/aging?lifecycle", lifecycle)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(send("GET", "/aging?lifecycle", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let xml = String::from_utf8_lossy(&body);
+    assert!(xml.contains("<ID>logs</ID>"));
+    assert!(xml.contains("<Days>30</Days>"));
+
+    let response = app.clone().oneshot(send("PUT", "/aging?lifecycle", "<LifecycleConfiguration/>")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app.clone().oneshot(send("DELETE", "/aging?lifecycle", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let response = app.clone().oneshot(send("GET", "/aging?lifecycle", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_s3_lifecycle_sweeper_expires_objects() {
+    // One lifecycle day per second
+    let mut emulator = Emulator::in_memory().unwrap();
+    emulator.config.s3_lifecycle_day_secs = Some(1);
+    let emulator = Arc::new(emulator);
+    let app = gateway::create_router(emulator.clone());
+
+    let send = |method: &str, uri: &str, body: &str| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    app.clone().oneshot(send("PUT", "/aging", "")).await.unwrap();
+    let lifecycle = r#"<LifecycleConfiguration>
+  <Rule>
+    <ID>tmp</ID>
+    <Filter><Prefix>tmp/</Prefix></Filter>
+    <Status>Enabled</Status>
+    <Expiration><Days>1</Days></Expiration>
+  </Rule>
+</LifecycleConfiguration>"#;
+    let response = app.clone().oneshot(send("PUT", "/aging?lifecycle", lifecycle)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    app.clone().oneshot(send("PUT", "/aging/tmp/scratch.txt", "short-lived")).await.unwrap();
+    app.clone().oneshot(send("PUT", "/aging/keep.txt", "kept")).await.unwrap();
+
+    let sweeper = tokio::spawn(super::lifecycle::run(emulator));
+
+    let mut expired = false;
+    for _ in 0..50 {
+        let response = app.clone().oneshot(send("HEAD", "/aging/tmp/scratch.txt", "")).await.unwrap();
+        if response.status() == StatusCode::NOT_FOUND {
+            expired = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    sweeper.abort();
+    assert!(expired, "object did not expire");
+
+    let response = app.clone().oneshot(send("HEAD", "/aging/keep.txt", "")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Split an event stream into (event type, payload) pairs, checking both CRCs
+fn decode_event_stream(mut stream: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let mut events = Vec::new();
+    while !stream.is_empty() {
+        let total = u32::from_be_bytes(stream[0..4].try_into().unwrap()) as usize;
+        let headers_len = u32::from_be_bytes(stream[4..8].try_into().unwrap()) as usize;
+        assert_eq!(u32::from_be_bytes(stream[8..12].try_into().unwrap()), crc32fast::hash(&stream[0..8]));
+        assert_eq!(u32::from_be_bytes(stream[total - 4..total].try_into().unwrap()), crc32fast::hash(&stream[..total - 4]));
+
+        let mut headers = &stream[12..12 + headers_len];
+        let mut event_type = String::new();
+        while !headers.is_empty() {
+            let name_len = headers[0] as usize;
+            let name = String::from_utf8_lossy(&headers[1..1 + name_len]).to_string();
+            assert_eq!(headers[1 + name_len], 7);
+            let value_len = u16::from_be_bytes(headers[2 + name_len..4 + name_len].try_into().unwrap()) as usize;
+            let value = String::from_utf8_lossy(&headers[4 + name_len..4 + name_len + value_len]).to_string();
+            if name == ":event-type" {
+                event_type = value;
+            }
+            headers = &headers[4 + name_len + value_len..];
+        }
+        events.push((event_type, stream[12 + headers_len..total - 4].to_vec()));
+        stream = &stream[total..];
+    }
+    events
+}
+
+#[tokio::test]
+async fn test_s3_select_object_content() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator);
+
+    let send = |method: &str, uri: &str, body: Vec<u8>| {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::from(body))
+            .unwrap()
+    };
+    let select = |expression: &str, input: &str, output: &str| {
+        format!(
+            "<SelectObjectContentRequest><Expression>{}</Expression><ExpressionType>SQL</ExpressionType>\
+             <InputSerialization>{}</InputSerialization><OutputSerialization>{}</OutputSerialization></SelectObjectContentRequest>",
+            expression, input, output
+        ).into_bytes()
+    };
+
+    app.clone().oneshot(send("PUT", "/analytics", vec![])).await.unwrap();
+    let csv = "region,product,units\neu,widget,12\nus,gadget,3\neu,\"gizmo, large\",7\n";
+    app.clone().oneshot(send("PUT", "/analytics/sales.csv", csv.into())).await.unwrap();
+
+    // CSV in, CSV out, filtered by header names
+    let request = select(
+        "SELECT s.product, s.units FROM S3Object s WHERE s.region = 'eu' AND s.units &gt; 5",
+        "<CSV><FileHeaderInfo>USE</FileHeaderInfo></CSV>",
+        "<CSV/>",
+    );
+    let response = app.clone().oneshot(send("POST", "/analytics/sales.csv?select&select-type=2", request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let events = decode_event_stream(&body);
+    let types: Vec<&str> = events.iter().map(|(t, _)| t.as_str()).collect();
+    assert_eq!(types, ["Records", "Stats", "End"]);
+    assert_eq!(String::from_utf8_lossy(&events[0].1), "widget,12\n\"gizmo, large\",7\n");
+    let stats = String::from_utf8_lossy(&events[1].1);
+    assert!(stats.contains(&format!("<BytesScanned>{}</BytesScanned>", csv.len())));
+    assert!(stats.contains("<BytesReturned>27</BytesReturned>"));
+
+    // JSON lines in, JSON out, with an aggregate
+    let lines = "{\"user\":\"a\",\"ms\":120}\n{\"user\":\"b\",\"ms\":80}\n{\"user\":\"a\",\"ms\":40}\n";
+    app.clone().oneshot(send("PUT", "/analytics/latency.json", lines.into())).await.unwrap();
+    let request = select(
+        "SELECT COUNT(*) AS calls, AVG(s.ms) AS mean FROM S3Object s WHERE s.user = 'a'",
+        "<JSON><Type>LINES</Type></JSON>",
+        "<JSON/>",
+    );
+    let response = app.clone().oneshot(send("POST", "/analytics/latency.json?select&select-type=2", request)).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(String::from_utf8_lossy(&decode_event_stream(&body)[0].1), "{\"calls\":2,\"mean\":80.0}\n");
+
+    // Parquet in, CSV out
+    app.clone().oneshot(send("PUT", "/analytics/scores.parquet", super::parquet::tests::sample_file())).await.unwrap();
+    let request = select("SELECT id, name FROM S3Object WHERE score &gt;= 7", "<Parquet/>", "<CSV/>");
+    let response = app.clone().oneshot(send("POST", "/analytics/scores.parquet?select&select-type=2", request)).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(String::from_utf8_lossy(&decode_event_stream(&body)[0].1), "1,bob\n2,\n");
+
+    // A query that does not parse
+    let request = select("SELECT FROM S3Object", "<CSV/>", "<CSV/>");
+    let response = app.clone().oneshot(send("POST", "/analytics/sales.csv?select&select-type=2", request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("<Code>ParseUnexpectedToken</Code>"));
+
+    let request = select("SELECT * FROM S3Object", "<CSV/>", "<CSV/>");
+    let response = app.clone().oneshot(send("POST", "/analytics/missing.csv?select&select-type=2", request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
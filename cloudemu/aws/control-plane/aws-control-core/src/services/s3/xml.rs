@@ -1,6 +1,13 @@
 //! XML generation for S3 responses
 
-use aws_data_core::storage::{BucketMetadata, ListObjectsResult};
+use aws_data_core::storage::{
+    BucketMetadata, ListObjectsResult, ReplicationConfiguration, ReplicationRule,
+    WebsiteConfiguration, WebsiteRedirect, RoutingRule,
+    NotificationConfiguration, NotificationRule, MultipartUpload, UploadedPart,
+    LifecycleConfiguration, LifecycleRule, LifecycleTransition,
+};
+use aws_data_core::error::EmulatorError;
+use serde::Deserialize;
 
 /// Generate ListAllMyBucketsResult XML
 pub fn list_buckets_xml(buckets: &[BucketMetadata], owner_id: &str) -> String {
@@ -140,6 +147,18 @@ pub fn get_bucket_location_xml(region: &str) -> String {
     )
 }
 
+/// Extract the LocationConstraint from a CreateBucketConfiguration body
+pub fn extract_location_constraint(body: &str) -> Option<String> {
+    let start = body.find("<LocationConstraint>")? + "<LocationConstraint>".len();
+    let end = body[start..].find("</LocationConstraint>")? + start;
+    let region = body[start..end].trim();
+    if region.is_empty() {
+        None
+    } else {
+        Some(region.to_string())
+    }
+}
+
 /// Generate CopyObjectResult XML
 pub fn copy_object_xml(etag: &str, last_modified: &str) -> String {
     format!(
@@ -152,6 +171,600 @@ pub fn copy_object_xml(etag: &str, last_modified: &str) -> String {
     )
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReplicationConfigurationXml {
+    #[serde(default)]
+    role: String,
+    #[serde(rename = "Rule", default)]
+    rules: Vec<ReplicationRuleXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReplicationRuleXml {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    priority: Option<i32>,
+    status: String,
+    prefix: Option<String>,
+    filter: Option<ReplicationFilterXml>,
+    destination: ReplicationDestinationXml,
+    delete_marker_replication: Option<StatusXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReplicationFilterXml {
+    prefix: Option<String>,
+    and: Option<ReplicationFilterAndXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReplicationFilterAndXml {
+    prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ReplicationDestinationXml {
+    bucket: String,
+    account: Option<String>,
+    storage_class: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct StatusXml {
+    status: String,
+}
+
+/// Parse a PutBucketReplication request body
+pub fn parse_replication_configuration(body: &str) -> Result<ReplicationConfiguration, EmulatorError> {
+    let parsed: ReplicationConfigurationXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    
+    if parsed.rules.is_empty() {
+        return Err(EmulatorError::MalformedXml("ReplicationConfiguration requires at least one Rule".into()));
+    }
+    
+    let rules = parsed.rules.into_iter().enumerate().map(|(i, rule)| {
+        // Filter takes precedence over the legacy rule-level Prefix
+        let prefix = rule.filter
+            .and_then(|f| f.prefix.or_else(|| f.and.and_then(|a| a.prefix)))
+            .or(rule.prefix)
+            .unwrap_or_default();
+        let destination_bucket = rule.destination.bucket
+            .strip_prefix("arn:aws:s3:::")
+            .unwrap_or(&rule.destination.bucket)
+            .to_string();
+        
+        ReplicationRule {
+            id: rule.id.unwrap_or_else(|| format!("rule-{}", i + 1)),
+            priority: rule.priority.unwrap_or(0),
+            status: rule.status,
+            prefix,
+            destination_bucket,
+            destination_account: rule.destination.account,
+            storage_class: rule.destination.storage_class,
+            delete_marker_replication: rule.delete_marker_replication
+                .map(|d| d.status == "Enabled")
+                .unwrap_or(false),
+        }
+    }).collect();
+    
+    Ok(ReplicationConfiguration { role: parsed.role, rules })
+}
+
+/// Generate GetBucketReplication response
+pub fn get_bucket_replication_xml(config: &ReplicationConfiguration) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+<ReplicationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#);
+    
+    xml.push_str("\n  <Role>");
+    xml.push_str(&escape_xml(&config.role));
+    xml.push_str("</Role>");
+    
+    for rule in &config.rules {
+        xml.push_str("\n  <Rule>");
+        xml.push_str(&format!("\n    <ID>{}</ID>", escape_xml(&rule.id)));
+        xml.push_str(&format!("\n    <Priority>{}</Priority>", rule.priority));
+        xml.push_str(&format!("\n    <Status>{}</Status>", escape_xml(&rule.status)));
+        xml.push_str(&format!("\n    <Filter>\n      <Prefix>{}</Prefix>\n    </Filter>", escape_xml(&rule.prefix)));
+        xml.push_str(&format!(
+            "\n    <DeleteMarkerReplication>\n      <Status>{}</Status>\n    </DeleteMarkerReplication>",
+            if rule.delete_marker_replication { "Enabled" } else { "Disabled" }
+        ));
+        xml.push_str("\n    <Destination>");
+        xml.push_str(&format!("\n      <Bucket>arn:aws:s3:::{}</Bucket>", escape_xml(&rule.destination_bucket)));
+        if let Some(ref account) = rule.destination_account {
+            xml.push_str(&format!("\n      <Account>{}</Account>", escape_xml(account)));
+        }
+        if let Some(ref class) = rule.storage_class {
+            xml.push_str(&format!("\n      <StorageClass>{}</StorageClass>", escape_xml(class)));
+        }
+        xml.push_str("\n    </Destination>");
+        xml.push_str("\n  </Rule>");
+    }
+    
+    xml.push_str("\n</ReplicationConfiguration>");
+    xml
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WebsiteConfigurationXml {
+    index_document: Option<IndexDocumentXml>,
+    error_document: Option<ErrorDocumentXml>,
+    redirect_all_requests_to: Option<RedirectXml>,
+    routing_rules: Option<RoutingRulesXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct IndexDocumentXml {
+    suffix: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorDocumentXml {
+    key: String,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+struct RedirectXml {
+    host_name: Option<String>,
+    protocol: Option<String>,
+    replace_key_prefix_with: Option<String>,
+    replace_key_with: Option<String>,
+    http_redirect_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RoutingRulesXml {
+    #[serde(rename = "RoutingRule", default)]
+    rules: Vec<RoutingRuleXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RoutingRuleXml {
+    condition: Option<RoutingConditionXml>,
+    redirect: RedirectXml,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RoutingConditionXml {
+    key_prefix_equals: Option<String>,
+    http_error_code_returned_equals: Option<String>,
+}
+
+impl From<RedirectXml> for WebsiteRedirect {
+    fn from(r: RedirectXml) -> Self {
+        WebsiteRedirect {
+            host_name: r.host_name,
+            protocol: r.protocol,
+            replace_key_prefix_with: r.replace_key_prefix_with,
+            replace_key_with: r.replace_key_with,
+            http_redirect_code: r.http_redirect_code,
+        }
+    }
+}
+
+/// Parse a PutBucketWebsite request body
+pub fn parse_website_configuration(body: &str) -> Result<WebsiteConfiguration, EmulatorError> {
+    let parsed: WebsiteConfigurationXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    
+    let routing_rules = parsed.routing_rules
+        .map(|r| r.rules)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|rule| {
+            let (key_prefix_equals, http_error_code_returned_equals) = rule.condition
+                .map(|c| (c.key_prefix_equals, c.http_error_code_returned_equals))
+                .unwrap_or((None, None));
+            RoutingRule {
+                key_prefix_equals,
+                http_error_code_returned_equals,
+                redirect: rule.redirect.into(),
+            }
+        })
+        .collect();
+    
+    Ok(WebsiteConfiguration {
+        index_document: parsed.index_document.map(|i| i.suffix),
+        error_document: parsed.error_document.map(|e| e.key),
+        redirect_all_requests_to: parsed.redirect_all_requests_to.map(Into::into),
+        routing_rules,
+    })
+}
+
+/// Generate GetBucketWebsite response
+pub fn get_bucket_website_xml(config: &WebsiteConfiguration) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+<WebsiteConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#);
+    
+    if let Some(ref redirect) = config.redirect_all_requests_to {
+        xml.push_str("
+  <RedirectAllRequestsTo>");
+        if let Some(ref host) = redirect.host_name {
+            xml.push_str(&format!("
+    <HostName>{}</HostName>", escape_xml(host)));
+        }
+        if let Some(ref protocol) = redirect.protocol {
+            xml.push_str(&format!("
+    <Protocol>{}</Protocol>", escape_xml(protocol)));
+        }
+        xml.push_str("
+  </RedirectAllRequestsTo>");
+    }
+    if let Some(ref suffix) = config.index_document {
+        xml.push_str(&format!("
+  <IndexDocument>
+    <Suffix>{}</Suffix>
+  </IndexDocument>", escape_xml(suffix)));
+    }
+    if let Some(ref key) = config.error_document {
+        xml.push_str(&format!("
+  <ErrorDocument>
+    <Key>{}</Key>
+  </ErrorDocument>", escape_xml(key)));
+    }
+    
+    if !config.routing_rules.is_empty() {
+        xml.push_str("
+  <RoutingRules>");
+        for rule in &config.routing_rules {
+            xml.push_str("
+    <RoutingRule>");
+            if rule.key_prefix_equals.is_some() || rule.http_error_code_returned_equals.is_some() {
+                xml.push_str("
+      <Condition>");
+                if let Some(ref prefix) = rule.key_prefix_equals {
+                    xml.push_str(&format!("
+        <KeyPrefixEquals>{}</KeyPrefixEquals>", escape_xml(prefix)));
+                }
+                if let Some(ref code) = rule.http_error_code_returned_equals {
+                    xml.push_str(&format!("
+        <HttpErrorCodeReturnedEquals>{}</HttpErrorCodeReturnedEquals>", escape_xml(code)));
+                }
+                xml.push_str("
+      </Condition>");
+            }
+            
+            let r = &rule.redirect;
+            xml.push_str("
+      <Redirect>");
+            let fields = [
+                ("HostName", &r.host_name),
+                ("HttpRedirectCode", &r.http_redirect_code),
+                ("Protocol", &r.protocol),
+                ("ReplaceKeyPrefixWith", &r.replace_key_prefix_with),
+                ("ReplaceKeyWith", &r.replace_key_with),
+            ];
+            for (name, value) in fields {
+                if let Some(v) = value {
+                    xml.push_str(&format!("
+        <{0}>{1}</{0}>", name, escape_xml(v)));
+                }
+            }
+            xml.push_str("
+      </Redirect>");
+            xml.push_str("
+    </RoutingRule>");
+        }
+        xml.push_str("
+  </RoutingRules>");
+    }
+    
+    xml.push_str("
+</WebsiteConfiguration>");
+    xml
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NotificationConfigurationXml {
+    #[serde(rename = "QueueConfiguration", default)]
+    queues: Vec<NotificationRuleXml>,
+    #[serde(rename = "TopicConfiguration", default)]
+    topics: Vec<NotificationRuleXml>,
+    #[serde(rename = "CloudFunctionConfiguration", default)]
+    functions: Vec<NotificationRuleXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NotificationRuleXml {
+    id: Option<String>,
+    #[serde(alias = "Topic", alias = "CloudFunction")]
+    queue: String,
+    #[serde(rename = "Event", default)]
+    events: Vec<String>,
+    filter: Option<NotificationFilterXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NotificationFilterXml {
+    #[serde(rename = "S3Key")]
+    s3_key: S3KeyFilterXml,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct S3KeyFilterXml {
+    #[serde(rename = "FilterRule", default)]
+    rules: Vec<FilterRuleXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct FilterRuleXml {
+    name: String,
+    value: String,
+}
+
+impl TryFrom<NotificationRuleXml> for NotificationRule {
+    type Error = EmulatorError;
+    
+    fn try_from(r: NotificationRuleXml) -> Result<Self, EmulatorError> {
+        if r.events.is_empty() || r.events.iter().any(|e| !e.starts_with("s3:")) {
+            return Err(EmulatorError::InvalidArgument(
+                "Each notification configuration needs at least one s3: event".into()
+            ));
+        }
+        
+        let mut rule = NotificationRule {
+            id: r.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            destination_arn: r.queue,
+            events: r.events,
+            prefix: None,
+            suffix: None,
+        };
+        for filter in r.filter.map(|f| f.s3_key.rules).unwrap_or_default() {
+            match filter.name.to_ascii_lowercase().as_str() {
+                "prefix" => rule.prefix = Some(filter.value),
+                "suffix" => rule.suffix = Some(filter.value),
+                _ => return Err(EmulatorError::InvalidArgument(
+                    format!("Invalid filter rule name: {}", filter.name)
+                )),
+            }
+        }
+        Ok(rule)
+    }
+}
+
+/// Parse a PutBucketNotificationConfiguration request body
+pub fn parse_notification_configuration(body: &str) -> Result<NotificationConfiguration, EmulatorError> {
+    let parsed: NotificationConfigurationXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    
+    let convert = |rules: Vec<NotificationRuleXml>| {
+        rules.into_iter().map(NotificationRule::try_from).collect::<Result<Vec<_>, _>>()
+    };
+    Ok(NotificationConfiguration {
+        queue_configurations: convert(parsed.queues)?,
+        topic_configurations: convert(parsed.topics)?,
+        lambda_function_configurations: convert(parsed.functions)?,
+    })
+}
+
+/// Generate GetBucketNotificationConfiguration response
+pub fn get_bucket_notification_xml(config: &NotificationConfiguration) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+<NotificationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#);
+    
+    let groups = [
+        ("QueueConfiguration", "Queue", &config.queue_configurations),
+        ("TopicConfiguration", "Topic", &config.topic_configurations),
+        ("CloudFunctionConfiguration", "CloudFunction", &config.lambda_function_configurations),
+    ];
+    for (element, destination, rules) in groups {
+        for rule in rules {
+            xml.push_str(&format!("
+  <{}>
+    <Id>{}</Id>
+    <{2}>{3}</{2}>", element, escape_xml(&rule.id), destination, escape_xml(&rule.destination_arn)));
+            for event in &rule.events {
+                xml.push_str(&format!("
+    <Event>{}</Event>", escape_xml(event)));
+            }
+            if rule.prefix.is_some() || rule.suffix.is_some() {
+                xml.push_str("
+    <Filter>
+      <S3Key>");
+                for (name, value) in [("prefix", &rule.prefix), ("suffix", &rule.suffix)] {
+                    if let Some(v) = value {
+                        xml.push_str(&format!("
+        <FilterRule><Name>{}</Name><Value>{}</Value></FilterRule>", name, escape_xml(v)));
+                    }
+                }
+                xml.push_str("
+      </S3Key>
+    </Filter>");
+            }
+            xml.push_str(&format!("
+  </{}>", element));
+        }
+    }
+    
+    xml.push_str("
+</NotificationConfiguration>");
+    xml
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LifecycleConfigurationXml {
+    #[serde(rename = "Rule", default)]
+    rules: Vec<LifecycleRuleXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LifecycleRuleXml {
+    #[serde(rename = "ID")]
+    id: Option<String>,
+    status: String,
+    prefix: Option<String>,
+    filter: Option<ReplicationFilterXml>,
+    expiration: Option<ExpirationXml>,
+    #[serde(rename = "Transition", default)]
+    transitions: Vec<TransitionXml>,
+    noncurrent_version_expiration: Option<NoncurrentExpirationXml>,
+    #[serde(rename = "NoncurrentVersionTransition", default)]
+    noncurrent_version_transitions: Vec<NoncurrentTransitionXml>,
+    abort_incomplete_multipart_upload: Option<AbortIncompleteMultipartUploadXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExpirationXml {
+    days: Option<u32>,
+    date: Option<String>,
+    expired_object_delete_marker: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TransitionXml {
+    days: Option<u32>,
+    storage_class: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NoncurrentExpirationXml {
+    noncurrent_days: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct NoncurrentTransitionXml {
+    noncurrent_days: u32,
+    storage_class: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AbortIncompleteMultipartUploadXml {
+    days_after_initiation: u32,
+}
+
+/// Parse a PutBucketLifecycleConfiguration request body
+pub fn parse_lifecycle_configuration(body: &str) -> Result<LifecycleConfiguration, EmulatorError> {
+    let parsed: LifecycleConfigurationXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    
+    if parsed.rules.is_empty() {
+        return Err(EmulatorError::MalformedXml("LifecycleConfiguration requires at least one Rule".into()));
+    }
+    
+    let rules = parsed.rules.into_iter().enumerate().map(|(i, rule)| {
+        let prefix = rule.filter
+            .and_then(|f| f.prefix.or_else(|| f.and.and_then(|a| a.prefix)))
+            .or(rule.prefix)
+            .unwrap_or_default();
+        let (expiration_days, expiration_date, expired_object_delete_marker) = rule.expiration
+            .map(|e| (e.days, e.date, e.expired_object_delete_marker.unwrap_or(false)))
+            .unwrap_or((None, None, false));
+        if let Some(ref date) = expiration_date {
+            if chrono::DateTime::parse_from_rfc3339(date).is_err() {
+                return Err(EmulatorError::InvalidArgument(format!("Invalid expiration date: {}", date)));
+            }
+        }
+        let transitions = rule.transitions.into_iter().map(|t| match t.days {
+            Some(days) => Ok(LifecycleTransition { days, storage_class: t.storage_class }),
+            None => Err(EmulatorError::InvalidArgument("Transitions must specify Days; dates are not supported".into())),
+        }).collect::<Result<Vec<_>, _>>()?;
+        
+        let rule = LifecycleRule {
+            id: rule.id.unwrap_or_else(|| format!("rule-{}", i + 1)),
+            status: rule.status,
+            prefix,
+            expiration_days,
+            expiration_date,
+            expired_object_delete_marker,
+            transitions,
+            noncurrent_version_expiration_days: rule.noncurrent_version_expiration.map(|e| e.noncurrent_days),
+            noncurrent_version_transitions: rule.noncurrent_version_transitions.into_iter()
+                .map(|t| LifecycleTransition { days: t.noncurrent_days, storage_class: t.storage_class })
+                .collect(),
+            abort_incomplete_multipart_upload_days: rule.abort_incomplete_multipart_upload.map(|a| a.days_after_initiation),
+        };
+        let has_action = rule.expiration_days.is_some() || rule.expiration_date.is_some() || rule.expired_object_delete_marker
+            || !rule.transitions.is_empty() || rule.noncurrent_version_expiration_days.is_some()
+            || !rule.noncurrent_version_transitions.is_empty() || rule.abort_incomplete_multipart_upload_days.is_some();
+        if !has_action {
+            return Err(EmulatorError::MalformedXml(format!("Lifecycle rule {} has no action", rule.id)));
+        }
+        Ok(rule)
+    }).collect::<Result<Vec<_>, _>>()?;
+    
+    Ok(LifecycleConfiguration { rules })
+}
+
+/// Generate GetBucketLifecycleConfiguration response
+pub fn get_bucket_lifecycle_xml(config: &LifecycleConfiguration) -> String {
+    let mut xml = String::from(r#"<?xml version="1.0" encoding="UTF-8"?>
+<LifecycleConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#);
+    
+    for rule in &config.rules {
+        xml.push_str("\n  <Rule>");
+        xml.push_str(&format!("\n    <ID>{}</ID>", escape_xml(&rule.id)));
+        xml.push_str(&format!("\n    <Filter>\n      <Prefix>{}</Prefix>\n    </Filter>", escape_xml(&rule.prefix)));
+        xml.push_str(&format!("\n    <Status>{}</Status>", escape_xml(&rule.status)));
+        if rule.expiration_days.is_some() || rule.expiration_date.is_some() || rule.expired_object_delete_marker {
+            xml.push_str("\n    <Expiration>");
+            if let Some(days) = rule.expiration_days {
+                xml.push_str(&format!("\n      <Days>{}</Days>", days));
+            }
+            if let Some(ref date) = rule.expiration_date {
+                xml.push_str(&format!("\n      <Date>{}</Date>", escape_xml(date)));
+            }
+            if rule.expired_object_delete_marker {
+                xml.push_str("\n      <ExpiredObjectDeleteMarker>true</ExpiredObjectDeleteMarker>");
+            }
+            xml.push_str("\n    </Expiration>");
+        }
+        for transition in &rule.transitions {
+            xml.push_str(&format!(
+                "\n    <Transition>\n      <Days>{}</Days>\n      <StorageClass>{}</StorageClass>\n    </Transition>",
+                transition.days, escape_xml(&transition.storage_class)
+            ));
+        }
+        if let Some(days) = rule.noncurrent_version_expiration_days {
+            xml.push_str(&format!(
+                "\n    <NoncurrentVersionExpiration>\n      <NoncurrentDays>{}</NoncurrentDays>\n    </NoncurrentVersionExpiration>",
+                days
+            ));
+        }
+        for transition in &rule.noncurrent_version_transitions {
+            xml.push_str(&format!(
+                "\n    <NoncurrentVersionTransition>\n      <NoncurrentDays>{}</NoncurrentDays>\n      <StorageClass>{}</StorageClass>\n    </NoncurrentVersionTransition>",
+                transition.days, escape_xml(&transition.storage_class)
+            ));
+        }
+        if let Some(days) = rule.abort_incomplete_multipart_upload_days {
+            xml.push_str(&format!(
+                "\n    <AbortIncompleteMultipartUpload>\n      <DaysAfterInitiation>{}</DaysAfterInitiation>\n    </AbortIncompleteMultipartUpload>",
+                days
+            ));
+        }
+        xml.push_str("\n  </Rule>");
+    }
+    
+    xml.push_str("\n</LifecycleConfiguration>");
+    xml
+}
+
 // TODO: Implement batch delete operations (DeleteObjects)
 // pub fn delete_objects_xml(deleted: &[String], errors: &[(String, String, String)]) -> String
 
@@ -191,6 +804,81 @@ pub fn complete_multipart_upload_xml(bucket: &str, key: &str, etag: &str) -> Str
     )
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompleteMultipartUploadXml {
+    #[serde(rename = "Part", default)]
+    parts: Vec<CompletedPartXml>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CompletedPartXml {
+    part_number: i32,
+    #[serde(rename = "ETag")]
+    etag: String,
+}
+
+/// Parse a CompleteMultipartUpload request body into (part number, ETag) pairs, in request order
+pub fn parse_complete_multipart_upload(body: &str) -> Result<Vec<(i32, String)>, EmulatorError> {
+    let parsed: CompleteMultipartUploadXml = quick_xml::de::from_str(body)
+        .map_err(|e| EmulatorError::MalformedXml(e.to_string()))?;
+    Ok(parsed.parts.into_iter().map(|part| (part.part_number, part.etag.trim().to_string())).collect())
+}
+
+/// Generate UploadPartCopy response XML
+pub fn copy_part_xml(part: &UploadedPart) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<CopyPartResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <ETag>{}</ETag>
+    <LastModified>{}</LastModified>
+</CopyPartResult>"#,
+        part.etag, part.last_modified
+    )
+}
+
+/// Generate ListParts response XML
+pub fn list_parts_xml(
+    upload: &MultipartUpload,
+    parts: &[UploadedPart],
+    part_number_marker: i32,
+    max_parts: usize,
+    is_truncated: bool,
+) -> String {
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListPartsResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+    <Bucket>{}</Bucket>
+    <Key>{}</Key>
+    <UploadId>{}</UploadId>
+    <StorageClass>STANDARD</StorageClass>
+    <PartNumberMarker>{}</PartNumberMarker>
+    <MaxParts>{}</MaxParts>
+    <IsTruncated>{}</IsTruncated>"#,
+        escape_xml(&upload.bucket), escape_xml(&upload.key), upload.upload_id, part_number_marker, max_parts, is_truncated
+    );
+    if let Some(last) = parts.last() {
+        xml.push_str(&format!("
+    <NextPartNumberMarker>{}</NextPartNumberMarker>", last.part_number));
+    }
+    for part in parts {
+        xml.push_str(&format!(
+            r#"
+    <Part>
+        <PartNumber>{}</PartNumber>
+        <LastModified>{}</LastModified>
+        <ETag>{}</ETag>
+        <Size>{}</Size>
+    </Part>"#,
+            part.part_number, part.last_modified, part.etag, part.size
+        ));
+    }
+    xml.push_str("
+</ListPartsResult>");
+    xml
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +910,140 @@ mod tests {
         let enabled = get_bucket_versioning_xml("Enabled");
         assert!(enabled.contains("<Status>Enabled</Status>"));
     }
+    
+    #[test]
+    fn test_parse_replication_configuration() {
+        let body = r#"<ReplicationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Role>arn:aws:iam::000000000000:role/replication</Role>
+  <Rule>
+    <ID>logs</ID>
+    <Priority>2</Priority>
+    <Status>Enabled</Status>
+    <Filter><Prefix>logs/</Prefix></Filter>
+    <DeleteMarkerReplication><Status>Disabled</Status></DeleteMarkerReplication>
+    <Destination>
+      <Bucket>arn:aws:s3:::replica</Bucket>
+      <Account>111111111111</Account>
+    </Destination>
+  </Rule>
+</ReplicationConfiguration>"#;
+        
+        let config = parse_replication_configuration(body).unwrap();
+        assert_eq!(config.rules.len(), 1);
+        let rule = &config.rules[0];
+        assert_eq!(rule.prefix, "logs/");
+        assert_eq!(rule.priority, 2);
+        assert_eq!(rule.destination_bucket, "replica");
+        assert_eq!(rule.destination_account.as_deref(), Some("111111111111"));
+        
+        let xml = get_bucket_replication_xml(&config);
+        assert!(xml.contains("<Bucket>arn:aws:s3:::replica</Bucket>"));
+        assert!(parse_replication_configuration("<ReplicationConfiguration/>").is_err());
+    }
+    
+    #[test]
+    fn test_parse_website_configuration() {
+        let body = r#"<WebsiteConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <IndexDocument><Suffix>index.html</Suffix></IndexDocument>
+  <ErrorDocument><Key>error.html</Key></ErrorDocument>
+  <RoutingRules>
+    <RoutingRule>
+      <Condition><KeyPrefixEquals>docs/</KeyPrefixEquals></Condition>
+      <Redirect><ReplaceKeyPrefixWith>documents/</ReplaceKeyPrefixWith></Redirect>
+    </RoutingRule>
+  </RoutingRules>
+</WebsiteConfiguration>"#;
+        
+        let config = parse_website_configuration(body).unwrap();
+        assert_eq!(config.index_document.as_deref(), Some("index.html"));
+        assert_eq!(config.error_document.as_deref(), Some("error.html"));
+        assert_eq!(config.routing_rules.len(), 1);
+        assert_eq!(config.routing_rules[0].key_prefix_equals.as_deref(), Some("docs/"));
+        assert_eq!(config.routing_rules[0].redirect.replace_key_prefix_with.as_deref(), Some("documents/"));
+        
+        let xml = get_bucket_website_xml(&config);
+        assert!(xml.contains("<Suffix>index.html</Suffix>"));
+        assert!(xml.contains("<KeyPrefixEquals>docs/</KeyPrefixEquals>"));
+    }
+    
+    #[test]
+    fn test_parse_notification_configuration() {
+        let body = r#"<NotificationConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <QueueConfiguration>
+    <Id>images</Id>
+    <Queue>arn:aws:sqs:us-east-1:000000000000:images</Queue>
+    <Event>s3:ObjectCreated:*</Event>
+    <Event>s3:ObjectRemoved:Delete</Event>
+    <Filter><S3Key>
+      <FilterRule><Name>prefix</Name><Value>images/</Value></FilterRule>
+      <FilterRule><Name>suffix</Name><Value>.png</Value></FilterRule>
+    </S3Key></Filter>
+  </QueueConfiguration>
+  <TopicConfiguration>
+    <Topic>arn:aws:sns:us-east-1:000000000000:uploads</Topic>
+    <Event>s3:ObjectCreated:Put</Event>
+  </TopicConfiguration>
+</NotificationConfiguration>"#;
+        
+        let config = parse_notification_configuration(body).unwrap();
+        assert_eq!(config.queue_configurations.len(), 1);
+        let queue = &config.queue_configurations[0];
+        assert_eq!(queue.id, "images");
+        assert_eq!(queue.events.len(), 2);
+        assert_eq!(queue.prefix.as_deref(), Some("images/"));
+        assert_eq!(queue.suffix.as_deref(), Some(".png"));
+        assert_eq!(config.topic_configurations[0].destination_arn, "arn:aws:sns:us-east-1:000000000000:uploads");
+        
+        let xml = get_bucket_notification_xml(&config);
+        assert!(xml.contains("<Queue>arn:aws:sqs:us-east-1:000000000000:images</Queue>"));
+        assert!(xml.contains("<FilterRule><Name>suffix</Name><Value>.png</Value></FilterRule>"));
+        assert!(xml.contains("<Topic>arn:aws:sns:us-east-1:000000000000:uploads</Topic>"));
+        
+        let invalid = r#"<NotificationConfiguration><QueueConfiguration>
+  <Queue>arn:aws:sqs:us-east-1:000000000000:images</Queue><Event>ObjectCreated</Event>
+</QueueConfiguration></NotificationConfiguration>"#;
+        assert!(parse_notification_configuration(invalid).is_err());
+    }
+    
+    #[test]
+    fn test_parse_lifecycle_configuration() {
+        let body = r#"<LifecycleConfiguration xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+  <Rule>
+    <ID>logs</ID>
+    <Filter><Prefix>logs/</Prefix></Filter>
+    <Status>Enabled</Status>
+    <Transition><Days>30</Days><StorageClass>STANDARD_IA</StorageClass></Transition>
+    <Transition><Days>90</Days><StorageClass>GLACIER</StorageClass></Transition>
+    <Expiration><Days>365</Days></Expiration>
+    <NoncurrentVersionExpiration><NoncurrentDays>7</NoncurrentDays></NoncurrentVersionExpiration>
+  </Rule>
+  <Rule>
+    <Prefix>tmp/</Prefix>
+    <Status>Disabled</Status>
+    <AbortIncompleteMultipartUpload><DaysAfterInitiation>1</DaysAfterInitiation></AbortIncompleteMultipartUpload>
+  </Rule>
+</LifecycleConfiguration>"#;
+        
+        let config = parse_lifecycle_configuration(body).unwrap();
+        assert_eq!(config.rules.len(), 2);
+        let logs = &config.rules[0];
+        assert_eq!(logs.prefix, "logs/");
+        assert_eq!(logs.expiration_days, Some(365));
+        assert_eq!(logs.transitions.len(), 2);
+        assert_eq!(logs.transitions[1].storage_class, "GLACIER");
+        assert_eq!(logs.noncurrent_version_expiration_days, Some(7));
+        let tmp = &config.rules[1];
+        assert_eq!(tmp.id, "rule-2");
+        assert_eq!(tmp.prefix, "tmp/");
+        assert_eq!(tmp.abort_incomplete_multipart_upload_days, Some(1));
+        
+        let xml = get_bucket_lifecycle_xml(&config);
+        assert!(xml.contains("<StorageClass>GLACIER</StorageClass>"));
+        assert!(xml.contains("<DaysAfterInitiation>1</DaysAfterInitiation>"));
+        assert_eq!(parse_lifecycle_configuration(&xml).unwrap().rules.len(), 2);
+        
+        assert!(parse_lifecycle_configuration("<LifecycleConfiguration/>").is_err());
+        let no_action = "<LifecycleConfiguration><Rule><Status>Enabled</Status></Rule></LifecycleConfiguration>";
+        assert!(parse_lifecycle_configuration(no_action).is_err());
+    }
 }
@@ -1,18 +1,21 @@
 use crate::Emulator;
 use crate::error::EmulatorError;
+use crate::services::message_attributes;
+use super::sandbox::{self, Published};
+use aws_data_core::storage::{PlatformEndpointMetadata, SubscriptionMetadata};
 use axum::{
     extract::State,
     http::HeaderMap,
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 use std::sync::Arc;
 use tracing::info;
 
 pub async fn handle_request(
     State(emulator): State<Arc<Emulator>>,
-    _headers: HeaderMap,
+    headers: HeaderMap,
     Json(body): Json<Value>,
 ) -> Response {
     let action = body["Action"].as_str().unwrap_or("");
@@ -20,8 +23,16 @@ pub async fn handle_request(
     let result = match action {
         "CreateTopic" => create_topic(&emulator, body).await,
         "Subscribe" => subscribe(&emulator, body).await,
-        "Publish" => publish(&emulator, body).await,
+        "Publish" => publish(&emulator, &headers, body).await,
         "ListTopics" => list_topics(&emulator, body).await,
+        "CreatePlatformApplication" => create_platform_application(&emulator, body),
+        "ListPlatformApplications" => list_platform_applications(&emulator),
+        "DeletePlatformApplication" => delete_platform_application(&emulator, body),
+        "CreatePlatformEndpoint" => create_platform_endpoint(&emulator, body),
+        "ListEndpointsByPlatformApplication" => list_endpoints_by_platform_application(&emulator, body),
+        "GetEndpointAttributes" => get_endpoint_attributes(&emulator, body),
+        "SetEndpointAttributes" => set_endpoint_attributes(&emulator, body),
+        "DeleteEndpoint" => delete_endpoint(&emulator, body),
         _ => Err(EmulatorError::InvalidRequest(format!("Unsupported SNS action: {}", action))),
     };
 
@@ -57,8 +68,19 @@ async fn subscribe(emulator: &Emulator, body: Value) -> Result<Value, EmulatorEr
     let topic_arn = body["TopicArn"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing TopicArn".into()))?;
     let protocol = body["Protocol"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing Protocol".into()))?;
     let endpoint = body["Endpoint"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing Endpoint".into()))?;
+    match protocol {
+        "sms" => sandbox::check_phone_number(endpoint)?,
+        "application" => { emulator.storage.get_platform_endpoint(endpoint)?; }
+        _ => {}
+    }
+    // Subscription attributes such as RawMessageDelivery, as a map of strings
+    let attributes = match &body["Attributes"] {
+        Value::Object(map) if !map.is_empty() => Some(body["Attributes"].to_string()),
+        Value::Object(_) | Value::Null => None,
+        _ => return Err(EmulatorError::InvalidArgument("Attributes must be a map".into())),
+    };
     
-    let sub_arn = emulator.storage.subscribe(topic_arn, protocol, endpoint)?;
+    let sub_arn = emulator.storage.subscribe_with_attributes(topic_arn, protocol, endpoint, attributes.as_deref())?;
     
     Ok(json!({
         "SubscribeResponse": {
@@ -69,14 +91,72 @@ async fn subscribe(emulator: &Emulator, body: Value) -> Result<Value, EmulatorEr
     }))
 }
 
-async fn publish(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
-    let topic_arn = body["TopicArn"].as_str()
-        .ok_or_else(|| EmulatorError::InvalidArgument("Missing TopicArn".into()))?;
+fn invalid_parameter(message: impl Into<String>) -> EmulatorError {
+    EmulatorError::InvalidParameter { code: "InvalidParameter", message: message.into() }
+}
+
+/// Texts of a `MessageStructure=json` message by protocol or platform; `default` is required
+fn message_structure(message: &str) -> Result<Map<String, Value>, EmulatorError> {
+    let texts = serde_json::from_str::<Value>(message).ok()
+        .and_then(|value| value.as_object().cloned())
+        .filter(|texts| texts.values().all(Value::is_string))
+        .ok_or_else(|| invalid_parameter("Invalid parameter: Message Structure - JSON message body failed to parse"))?;
+    if !texts.contains_key("default") {
+        return Err(invalid_parameter("Invalid parameter: Message Structure - No default entry in JSON message body"));
+    }
+    Ok(texts)
+}
+
+/// Text of a message for a protocol (`sqs`, `sms`, ...) or push platform (`GCM`, ...)
+fn message_for<'a>(message: &'a str, structure: Option<&'a Map<String, Value>>, key: &str) -> &'a str {
+    match structure {
+        Some(texts) => texts.get(key).or_else(|| texts.get("default")).and_then(Value::as_str).unwrap_or_default(),
+        None => message,
+    }
+}
+
+async fn publish(emulator: &Emulator, headers: &HeaderMap, body: Value) -> Result<Value, EmulatorError> {
     let message = body["Message"].as_str()
         .ok_or_else(|| EmulatorError::InvalidArgument("Missing Message".into()))?;
     let subject = body["Subject"].as_str();
-    
+    let attributes = message_attributes::validate(&body["MessageAttributes"])?;
+    let structure = match body["MessageStructure"].as_str() {
+        Some("json") => Some(message_structure(message)?),
+        _ => None,
+    };
     let message_id = uuid::Uuid::new_v4().to_string();
+    let published = |topic_arn| Published {
+        message_id: &message_id,
+        subject,
+        attributes: attributes.as_deref(),
+        topic_arn,
+    };
+
+    // Push texts are picked by the platform of each endpoint rather than the protocol
+    let push_message = |platform: &str| Ok(message_for(message, structure.as_ref(), platform).to_string());
+
+    if ["TopicArn", "TargetArn", "PhoneNumber"].iter().filter(|field| body[**field].is_string()).count() != 1 {
+        return Err(invalid_parameter("Invalid parameter: Exactly one of TopicArn, TargetArn or PhoneNumber must be specified"));
+    }
+    if let Some(phone_number) = body["PhoneNumber"].as_str() {
+        sandbox::check_phone_number(phone_number)?;
+        sandbox::send_sms(emulator, &published(None), phone_number, message_for(message, structure.as_ref(), "sms"))?;
+        return Ok(publish_response(&message_id));
+    }
+    // A TargetArn names a platform endpoint or, like TopicArn, a topic
+    let topic_arn = body["TopicArn"].as_str().or(body["TargetArn"].as_str()).unwrap_or_default();
+    if topic_arn.contains(":endpoint/") {
+        sandbox::send_push(emulator, &published(None), topic_arn, push_message)?;
+        return Ok(publish_response(&message_id));
+    }
+
+    // The trace context follows the message to every subscriber
+    let trace_header = body["MessageSystemAttributes"][message_attributes::AWS_TRACE_HEADER]["StringValue"].as_str()
+        .map(str::to_string)
+        .or_else(|| message_attributes::trace_header(headers));
+    let system_attributes = message_attributes::system_attributes(trace_header.as_deref());
+    
+    let timestamp = chrono::Utc::now().to_rfc3339();
     
     // Get all subscriptions for this topic
     let subscriptions = emulator.storage.list_subscriptions_by_topic(topic_arn)?;
@@ -85,23 +165,40 @@ async fn publish(emulator: &Emulator, body: Value) -> Result<Value, EmulatorErro
     
     // Deliver to each subscriber
     for sub in subscriptions {
+        let message = message_for(message, structure.as_ref(), &sub.protocol);
         match sub.protocol.as_str() {
             "sqs" => {
                 // Deliver to SQS queue
-                if let Some(queue_name) = sub.endpoint.split('/').last() {
-                    let sqs_message = json!({
-                        "Type": "Notification",
-                        "MessageId": message_id,
-                        "TopicArn": topic_arn,
-                        "Subject": subject.unwrap_or(""),
-                        "Message": message,
-                        "Timestamp": chrono::Utc::now().to_rfc3339()
-                    });
-                    
-                    if let Err(e) = emulator.storage.send_message(
-                        queue_name,
-                        &sqs_message.to_string()
-                    ) {
+                if let Some(queue_name) = sub.endpoint.split([':', '/']).next_back() {
+                    // Raw delivery hands the message and its attributes to SQS unwrapped
+                    let delivered = if raw_message_delivery(&sub) {
+                        emulator.storage.send_message_with_attributes(
+                            queue_name,
+                            message,
+                            attributes.as_deref(),
+                            system_attributes.as_deref(),
+                        )
+                    } else {
+                        let mut sqs_message = json!({
+                            "Type": "Notification",
+                            "MessageId": message_id,
+                            "TopicArn": topic_arn,
+                            "Subject": subject.unwrap_or(""),
+                            "Message": message,
+                            "Timestamp": timestamp
+                        });
+                        if attributes.is_some() {
+                            sqs_message["MessageAttributes"] = message_attributes::to_sns(attributes.as_deref());
+                        }
+                        emulator.storage.send_message_with_attributes(
+                            queue_name,
+                            &sqs_message.to_string(),
+                            None,
+                            system_attributes.as_deref(),
+                        )
+                    };
+
+                    if let Err(e) = delivered {
                         tracing::warn!("Failed to deliver SNS message to SQS {}: {}", queue_name, e);
                     } else {
                         info!("SNS: Delivered to SQS queue {}", queue_name);
@@ -117,11 +214,33 @@ async fn publish(emulator: &Emulator, body: Value) -> Result<Value, EmulatorErro
                 info!("SNS: Would send email to {} (not implemented in emulator)", sub.endpoint);
             },
             "sms" => {
-                info!("SNS: Would send SMS to {} (not implemented in emulator)", sub.endpoint);
+                if let Err(e) = sandbox::send_sms(emulator, &published(Some(topic_arn)), &sub.endpoint, message) {
+                    tracing::warn!("Failed to deliver SNS message to SMS {}: {}", sub.endpoint, e);
+                }
+            },
+            "application" => {
+                if let Err(e) = sandbox::send_push(emulator, &published(Some(topic_arn)), &sub.endpoint, push_message) {
+                    tracing::warn!("Failed to deliver SNS message to endpoint {}: {}", sub.endpoint, e);
+                }
             },
             "lambda" => {
-                // Invoke Lambda function
-                info!("SNS: Would invoke Lambda {} (Lambda execution not yet implemented)", sub.endpoint);
+                let event = json!({
+                    "Records": [{
+                        "EventSource": "aws:sns",
+                        "EventVersion": "1.0",
+                        "EventSubscriptionArn": sub.arn,
+                        "Sns": {
+                            "Type": "Notification",
+                            "MessageId": message_id,
+                            "TopicArn": topic_arn,
+                            "Subject": subject,
+                            "Message": message,
+                            "Timestamp": timestamp,
+                            "MessageAttributes": message_attributes::to_sns(attributes.as_deref())
+                        }
+                    }]
+                });
+                invoke_subscriber(emulator, &sub.endpoint, event, trace_header.as_deref()).await;
             },
             _ => {
                 tracing::warn!("SNS: Unknown protocol {}", sub.protocol);
@@ -129,13 +248,38 @@ async fn publish(emulator: &Emulator, body: Value) -> Result<Value, EmulatorErro
         }
     }
     
-    Ok(json!({
+    Ok(publish_response(&message_id))
+}
+
+fn publish_response(message_id: &str) -> Value {
+    json!({
         "PublishResponse": {
             "PublishResult": {
                 "MessageId": message_id
             }
         }
-    }))
+    })
+}
+
+fn raw_message_delivery(sub: &SubscriptionMetadata) -> bool {
+    sub.attributes.as_deref()
+        .and_then(|a| serde_json::from_str::<Value>(a).ok())
+        .is_some_and(|a| a["RawMessageDelivery"].as_str().is_some_and(|v| v.eq_ignore_ascii_case("true")))
+}
+
+#[cfg(feature = "lambda")]
+async fn invoke_subscriber(emulator: &Emulator, endpoint: &str, event: Value, trace_header: Option<&str>) {
+    let function_name = endpoint.rsplit(':').next().unwrap_or(endpoint);
+    // Delivery failures do not fail Publish; SNS invokes Lambda asynchronously
+    match crate::services::lambda::handlers::invoke_traced(emulator, function_name, event, trace_header).await {
+        Ok(_) => info!("SNS: Delivered to Lambda {}", function_name),
+        Err(e) => tracing::warn!("Failed to deliver SNS message to Lambda {}: {}", function_name, e),
+    }
+}
+
+#[cfg(not(feature = "lambda"))]
+async fn invoke_subscriber(_emulator: &Emulator, endpoint: &str, _event: Value, _trace_header: Option<&str>) {
+    tracing::warn!("SNS: Lambda support is disabled, cannot deliver to {}", endpoint);
 }
 
 async fn list_topics(emulator: &Emulator, _body: Value) -> Result<Value, EmulatorError> {
@@ -153,3 +297,104 @@ async fn list_topics(emulator: &Emulator, _body: Value) -> Result<Value, Emulato
         }
     }))
 }
+
+// ==================== Mobile Push ====================
+
+fn required<'a>(body: &'a Value, field: &str) -> Result<&'a str, EmulatorError> {
+    body[field].as_str().ok_or_else(|| EmulatorError::InvalidArgument(format!("Missing {}", field)))
+}
+
+/// Push services SNS delivers to
+const PLATFORMS: &[&str] = &["ADM", "APNS", "APNS_SANDBOX", "BAIDU", "GCM", "MPNS", "WNS"];
+
+fn create_platform_application(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let name = required(&body, "Name")?;
+    let platform = required(&body, "Platform")?;
+    if !PLATFORMS.contains(&platform) {
+        return Err(invalid_parameter(format!("Invalid parameter: Platform Reason: {} is not supported", platform)));
+    }
+    let attributes = body["Attributes"].as_object().filter(|a| !a.is_empty()).map(|a| Value::Object(a.clone()).to_string());
+    let application = emulator.storage.create_platform_application(name, platform, attributes.as_deref(), &emulator.config.account_id, &emulator.config.region)?;
+    Ok(json!({
+        "CreatePlatformApplicationResponse": {
+            "CreatePlatformApplicationResult": { "PlatformApplicationArn": application.arn }
+        }
+    }))
+}
+
+fn list_platform_applications(emulator: &Emulator) -> Result<Value, EmulatorError> {
+    let applications: Vec<Value> = emulator.storage.list_platform_applications()?.into_iter()
+        .map(|app| json!({
+            "PlatformApplicationArn": app.arn,
+            "Attributes": app.attributes.and_then(|a| serde_json::from_str::<Value>(&a).ok()).unwrap_or_else(|| json!({}))
+        }))
+        .collect();
+    Ok(json!({
+        "ListPlatformApplicationsResponse": {
+            "ListPlatformApplicationsResult": { "PlatformApplications": applications }
+        }
+    }))
+}
+
+fn delete_platform_application(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.storage.delete_platform_application(required(&body, "PlatformApplicationArn")?)?;
+    Ok(json!({ "DeletePlatformApplicationResponse": {} }))
+}
+
+fn create_platform_endpoint(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let endpoint = emulator.storage.create_platform_endpoint(
+        required(&body, "PlatformApplicationArn")?,
+        required(&body, "Token")?,
+        body["CustomUserData"].as_str(),
+    )?;
+    Ok(json!({
+        "CreatePlatformEndpointResponse": {
+            "CreatePlatformEndpointResult": { "EndpointArn": endpoint.arn }
+        }
+    }))
+}
+
+fn endpoint_attributes(endpoint: &PlatformEndpointMetadata) -> Value {
+    let mut attributes = json!({ "Enabled": endpoint.enabled.to_string(), "Token": endpoint.token });
+    if let Some(data) = &endpoint.custom_user_data {
+        attributes["CustomUserData"] = json!(data);
+    }
+    attributes
+}
+
+fn list_endpoints_by_platform_application(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let endpoints: Vec<Value> = emulator.storage.list_endpoints_by_platform_application(required(&body, "PlatformApplicationArn")?)?
+        .iter()
+        .map(|endpoint| json!({ "EndpointArn": endpoint.arn, "Attributes": endpoint_attributes(endpoint) }))
+        .collect();
+    Ok(json!({
+        "ListEndpointsByPlatformApplicationResponse": {
+            "ListEndpointsByPlatformApplicationResult": { "Endpoints": endpoints }
+        }
+    }))
+}
+
+fn get_endpoint_attributes(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let endpoint = emulator.storage.get_platform_endpoint(required(&body, "EndpointArn")?)?;
+    Ok(json!({
+        "GetEndpointAttributesResponse": {
+            "GetEndpointAttributesResult": { "Attributes": endpoint_attributes(&endpoint) }
+        }
+    }))
+}
+
+/// Only `Enabled` can be changed; SNS disables endpoints the push service rejects, and tests
+/// do the same to exercise that path
+fn set_endpoint_attributes(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let arn = required(&body, "EndpointArn")?;
+    match body["Attributes"]["Enabled"].as_str() {
+        Some(enabled) => emulator.storage.set_endpoint_enabled(arn, enabled.eq_ignore_ascii_case("true"))?,
+        None => { emulator.storage.get_platform_endpoint(arn)?; }
+    }
+    Ok(json!({ "SetEndpointAttributesResponse": {} }))
+}
+
+fn delete_endpoint(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    emulator.storage.delete_endpoint(required(&body, "EndpointArn")?)?;
+    Ok(json!({ "DeleteEndpointResponse": {} }))
+}
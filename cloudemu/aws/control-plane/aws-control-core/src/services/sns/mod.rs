@@ -1,4 +1,5 @@
 mod service;
 pub mod handlers;
+pub mod sandbox;
 
 pub use service::SnsService;
@@ -0,0 +1,114 @@
+//! SMS and mobile push sandbox
+//!
+//! The emulator has no carrier or push service to hand messages to, so SMS to phone numbers
+//! and notifications to platform endpoints are recorded instead of dropped. Tests read them
+//! back from `GET /_aws/sns/sandbox` (filtered by `kind=sms|push` and `destination`) and clear
+//! them with `DELETE /_aws/sns/sandbox`; the dashboard shows the latest ones.
+
+use crate::Emulator;
+use crate::error::EmulatorError;
+use aws_data_core::storage::SandboxMessage;
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+/// What was published and how, shared by every delivery of one message
+pub struct Published<'a> {
+    pub message_id: &'a str,
+    pub subject: Option<&'a str>,
+    pub attributes: Option<&'a str>,
+    pub topic_arn: Option<&'a str>,
+}
+
+/// E.164: `+`, a country code and at most 15 digits in all
+pub fn check_phone_number(number: &str) -> Result<(), EmulatorError> {
+    let digits = number.strip_prefix('+').unwrap_or_default();
+    if digits.is_empty() || digits.len() > 15 || digits.starts_with('0') || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(EmulatorError::InvalidParameter {
+            code: "InvalidParameter",
+            message: format!("Invalid parameter: PhoneNumber Reason: {} is not valid to publish to", number),
+        });
+    }
+    Ok(())
+}
+
+/// Record an SMS to `phone_number`
+pub fn send_sms(emulator: &Emulator, published: &Published, phone_number: &str, message: &str) -> Result<(), EmulatorError> {
+    record(emulator, published, "sms", phone_number, None, message)?;
+    info!("SNS: Recorded SMS to {} in the sandbox", phone_number);
+    Ok(())
+}
+
+/// Record a push notification to a platform endpoint. `message` picks the text for the
+/// endpoint's platform; it fails for disabled endpoints, as SNS does.
+pub fn send_push(emulator: &Emulator, published: &Published, endpoint_arn: &str, message: impl Fn(&str) -> Result<String, EmulatorError>) -> Result<(), EmulatorError> {
+    let endpoint = emulator.storage.get_platform_endpoint(endpoint_arn)?;
+    if !endpoint.enabled {
+        return Err(EmulatorError::InvalidParameter {
+            code: "EndpointDisabled",
+            message: "Endpoint is disabled".into(),
+        });
+    }
+    let platform = emulator.storage.get_platform_application(&endpoint.application_arn)?.platform;
+    record(emulator, published, "push", endpoint_arn, Some(&platform), &message(&platform)?)?;
+    info!("SNS: Recorded {} push notification to {} in the sandbox", platform, endpoint_arn);
+    Ok(())
+}
+
+fn record(emulator: &Emulator, published: &Published, kind: &str, destination: &str, platform: Option<&str>, message: &str) -> Result<(), EmulatorError> {
+    emulator.storage.record_sandbox_message(&SandboxMessage {
+        message_id: published.message_id.to_string(),
+        kind: kind.to_string(),
+        destination: destination.to_string(),
+        platform: platform.map(str::to_string),
+        message: message.to_string(),
+        subject: published.subject.map(str::to_string),
+        attributes: published.attributes.map(str::to_string),
+        topic_arn: published.topic_arn.map(str::to_string),
+        sent_at: chrono::Utc::now().to_rfc3339(),
+    })?;
+    Ok(())
+}
+
+/// `GET /_aws/sns/sandbox`
+pub async fn list_messages(
+    State(emulator): State<Arc<Emulator>>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    match emulator.storage.list_sandbox_messages(params.get("kind").map(String::as_str), params.get("destination").map(String::as_str)) {
+        Ok(messages) => Json(json!({ "Messages": messages })).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+/// `DELETE /_aws/sns/sandbox`
+pub async fn clear_messages(State(emulator): State<Arc<Emulator>>) -> Response {
+    match emulator.storage.clear_sandbox_messages() {
+        Ok(cleared) => Json(json!({ "Cleared": cleared })).into_response(),
+        Err(e) => error_response(e),
+    }
+}
+
+fn error_response(e: EmulatorError) -> Response {
+    (e.status_code(), Json::<Value>(json!({ "Error": { "Code": e.code(), "Message": e.message() } }))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phone_numbers() {
+        assert!(check_phone_number("+15555550100").is_ok());
+        assert!(check_phone_number("+447700900123").is_ok());
+        for number in ["15555550100", "+", "+0123", "+1 555 555 0100", "+1234567890123456"] {
+            assert!(check_phone_number(number).is_err(), "{}", number);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use crate::Emulator;
 use crate::error::EmulatorError;
+use crate::services::message_attributes;
+use super::payload;
 use axum::{
     extract::State,
     http::HeaderMap,
@@ -30,7 +32,7 @@ pub async fn handle_request(
 
     let result = match action {
         "CreateQueue" => create_queue(&emulator, body).await,
-        "SendMessage" => send_message(&emulator, body).await,
+        "SendMessage" => send_message(&emulator, &headers, body).await,
         "ReceiveMessage" => receive_message(&emulator, body).await,
         "DeleteMessage" => delete_message(&emulator, body).await,
         "ListQueues" => list_queues(&emulator, body).await,
@@ -50,26 +52,82 @@ pub async fn handle_request(
     }
 }
 
+/// Smallest and largest `MaximumMessageSize` of a queue
+const MESSAGE_SIZE_RANGE: std::ops::RangeInclusive<i32> = 1024..=1_048_576;
+
 async fn create_queue(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
     let name = body["QueueName"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing QueueName".into()))?;
+    // Attribute values are strings in the API
+    let maximum_message_size = match &body["Attributes"]["MaximumMessageSize"] {
+        Value::Null => None,
+        value => match value.as_str().and_then(|v| v.parse::<i32>().ok()) {
+            Some(size) if MESSAGE_SIZE_RANGE.contains(&size) => Some(size),
+            _ => return Err(EmulatorError::InvalidParameter {
+                code: "InvalidAttributeValue",
+                message: format!("Invalid value for the parameter MaximumMessageSize. Reason: Must be between {} and {}.", MESSAGE_SIZE_RANGE.start(), MESSAGE_SIZE_RANGE.end()),
+            }),
+        },
+    };
     let queue = emulator.storage.create_queue(name, &emulator.config.account_id, &emulator.config.region)?;
+    if let Some(size) = maximum_message_size {
+        emulator.storage.set_queue_maximum_message_size(name, size)?;
+    }
     
     Ok(json!({
         "QueueUrl": queue.url
     }))
 }
 
-async fn send_message(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+async fn send_message(emulator: &Emulator, headers: &HeaderMap, body: Value) -> Result<Value, EmulatorError> {
     let queue_url = body["QueueUrl"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing QueueUrl".into()))?;
     let queue_name = queue_url.split('/').next_back().unwrap_or("");
-    let message_body = body["MessageBody"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing MessageBody".into()))?;
-    
-    let message_id = emulator.storage.send_message(queue_name, message_body)?;
+    let message_body = body["MessageBody"].as_str().filter(|b| !b.is_empty()).ok_or_else(|| EmulatorError::InvalidParameter {
+        code: "MissingParameter",
+        message: "The request must contain the parameter MessageBody.".into(),
+    })?;
+    if !message_body.chars().all(message_attributes::is_valid_char) {
+        return Err(EmulatorError::InvalidParameter {
+            code: "InvalidMessageContents",
+            message: "Invalid binary character in the message body. Only #x9 | #xA | #xD | #x20 to #xD7FF | #xE000 to #xFFFD | #x10000 to #x10FFFF are allowed.".into(),
+        });
+    }
+    message_attributes::validate(&body["MessageAttributes"])?;
+    let queue = emulator.storage.get_queue(queue_name)?;
+
+    // SDKs check the digests against what they sent, not against an offloaded pointer
+    let md5_of_body = message_attributes::md5_of_body(message_body);
+    let md5_of_attributes = message_attributes::md5_of_attributes(&body["MessageAttributes"]);
+    let max = queue.maximum_message_size as usize;
+    let (message_body, message_attributes) = match &emulator.config.sqs_payload_bucket {
+        Some(bucket) if crate::limits::message_size(message_body, &body["MessageAttributes"]) > max.min(emulator.limits.get().sqs_max_message_bytes) => {
+            payload::offload(emulator, bucket, message_body, &body["MessageAttributes"])?
+        }
+        _ => (message_body.to_string(), body["MessageAttributes"].clone()),
+    };
+    let attributes = message_attributes::validate(&message_attributes)?;
+    emulator.limits.check_message_size(&message_body, &message_attributes, max)?;
+
+    // An explicit AWSTraceHeader wins over the trace context of the request itself
+    let trace_header = body["MessageSystemAttributes"][message_attributes::AWS_TRACE_HEADER]["StringValue"].as_str()
+        .map(str::to_string)
+        .or_else(|| message_attributes::trace_header(headers));
+    let system_attributes = message_attributes::system_attributes(trace_header.as_deref());
+
+    let message_id = emulator.storage.send_message_with_attributes(
+        queue_name,
+        &message_body,
+        attributes.as_deref(),
+        system_attributes.as_deref(),
+    )?;
     
-    Ok(json!({
-        "MD5OfMessageBody": "todo",
+    let mut response = json!({
+        "MD5OfMessageBody": md5_of_body,
         "MessageId": message_id
-    }))
+    });
+    if let Some(md5) = md5_of_attributes {
+        response["MD5OfMessageAttributes"] = json!(md5);
+    }
+    Ok(response)
 }
 
 async fn receive_message(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
@@ -80,12 +138,20 @@ async fn receive_message(emulator: &Emulator, body: Value) -> Result<Value, Emul
     let messages = emulator.storage.receive_message(queue_name, max_messages)?;
     
     let msg_list: Vec<Value> = messages.into_iter().map(|m| {
-        json!({
+        let mut msg = json!({
             "MessageId": m.id,
             "ReceiptHandle": m.receipt_handle,
             "Body": m.body,
-            "MD5OfBody": m.md5_body.unwrap_or_else(|| "todo".to_string()),
-        })
+            "MD5OfBody": m.md5_body.clone().unwrap_or_else(|| message_attributes::md5_of_body(&m.body)),
+        });
+        if let Some(attributes) = message_attributes::select(m.message_attributes.as_deref(), &body["MessageAttributeNames"]) {
+            msg["MD5OfMessageAttributes"] = json!(message_attributes::md5_of_attributes(&attributes));
+            msg["MessageAttributes"] = attributes;
+        }
+        if let Some(attributes) = system_attributes(&m, &body) {
+            msg["Attributes"] = attributes;
+        }
+        msg
     }).collect();
     
     Ok(json!({
@@ -93,6 +159,35 @@ async fn receive_message(emulator: &Emulator, body: Value) -> Result<Value, Emul
     }))
 }
 
+/// System attributes requested through `AttributeNames` or `MessageSystemAttributeNames`
+fn system_attributes(message: &aws_data_core::storage::MessageMetadata, body: &Value) -> Option<Value> {
+    let requested: Vec<&str> = ["AttributeNames", "MessageSystemAttributeNames"].iter()
+        .filter_map(|key| body[*key].as_array())
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    if requested.is_empty() {
+        return None;
+    }
+
+    let sent_timestamp = chrono::DateTime::parse_from_rfc3339(&message.sent_at)
+        .map(|t| t.timestamp_millis().to_string())
+        .unwrap_or_default();
+    let mut available = vec![
+        ("SentTimestamp", sent_timestamp),
+        ("ApproximateReceiveCount", (message.receive_count + 1).to_string()),
+    ];
+    if let Some(trace) = message_attributes::stored_trace_header(message.system_attributes.as_deref()) {
+        available.push((message_attributes::AWS_TRACE_HEADER, trace));
+    }
+
+    let selected: serde_json::Map<String, Value> = available.into_iter()
+        .filter(|(name, _)| requested.iter().any(|r| *r == "All" || r == name))
+        .map(|(name, value)| (name.to_string(), Value::String(value)))
+        .collect();
+    (!selected.is_empty()).then_some(Value::Object(selected))
+}
+
 async fn delete_message(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
     let queue_url = body["QueueUrl"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing QueueUrl".into()))?;
     let queue_name = queue_url.split('/').next_back().unwrap_or("");
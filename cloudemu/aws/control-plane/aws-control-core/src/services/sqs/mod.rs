@@ -1,5 +1,6 @@
 pub mod service;
 pub mod handlers;
+pub mod payload;
 
 pub use service::SqsService;
 
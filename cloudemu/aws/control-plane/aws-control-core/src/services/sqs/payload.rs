@@ -0,0 +1,41 @@
+//! S3-backed payloads in the format of the SQS Extended Client
+//!
+//! With `CLOUDEMU_SQS_PAYLOAD_BUCKET` set, a message over its queue's size limit is stored in
+//! that bucket and sent as the pointer the Extended Client libraries (Java, Python, .NET)
+//! write, with the original size in the `ExtendedPayloadSize` attribute. Consumers using
+//! those libraries fetch the payload as they would from AWS; producers get the behavior
+//! without the library.
+
+use crate::Emulator;
+use crate::error::EmulatorError;
+use serde_json::{json, Value};
+
+/// Class name the Extended Client libraries tag their pointers with
+pub const POINTER_CLASS: &str = "software.amazon.payloadoffloading.PayloadS3Pointer";
+
+/// Attribute holding the size of the offloaded body
+pub const SIZE_ATTRIBUTE: &str = "ExtendedPayloadSize";
+
+/// Store `body` in the payload bucket, returning the pointer to send instead and the
+/// attributes to send it with
+pub fn offload(emulator: &Emulator, bucket: &str, body: &str, attributes: &Value) -> Result<(String, Value), EmulatorError> {
+    if attributes.get(SIZE_ATTRIBUTE).is_some() {
+        return Err(EmulatorError::InvalidParameter {
+            code: "InvalidParameterValue",
+            message: format!("Message attribute name {} is reserved for use by the SQS Extended Client.", SIZE_ATTRIBUTE),
+        });
+    }
+    if !emulator.storage.bucket_exists(bucket)? {
+        emulator.storage.create_bucket(bucket, &emulator.config.region)?;
+    }
+    let key = uuid::Uuid::new_v4().to_string();
+    emulator.storage.put_object(bucket, &key, body.as_bytes(), Some("text/plain"), None)?;
+
+    let mut attributes = match attributes {
+        Value::Object(_) => attributes.clone(),
+        _ => json!({}),
+    };
+    attributes[SIZE_ATTRIBUTE] = json!({ "DataType": "Number", "StringValue": body.len().to_string() });
+    let pointer = json!([POINTER_CLASS, { "s3BucketName": bucket, "s3Key": key }]);
+    Ok((pointer.to_string(), attributes))
+}
@@ -74,3 +74,164 @@ async fn test_sqs_api() {
     assert!(!messages.is_empty());
     assert_eq!(messages[0]["Body"], "Hello Queue");
 }
+
+#[tokio::test]
+async fn test_sqs_message_attributes_and_trace_header() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    emulator.storage.create_queue("traced", "000000000000", "us-east-1").unwrap();
+    let queue_url = "http://localhost:4566/000000000000/traced";
+
+    let request = |action: &str, trace: Option<&str>, body: serde_json::Value| {
+        let mut builder = Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("AmazonSQS.{}", action))
+            .header("content-type", "application/json");
+        if let Some(trace) = trace {
+            builder = builder.header("X-Amzn-Trace-Id", trace);
+        }
+        builder.body(Body::from(body.to_string())).unwrap()
+    };
+
+    // Invalid attributes are rejected
+    let response = app.clone().oneshot(request("SendMessage", None, json!({
+        "QueueUrl": queue_url,
+        "MessageBody": "bad",
+        "MessageAttributes": { "tenant": { "DataType": "String" } }
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // The request's trace context becomes the message's AWSTraceHeader
+    let trace = "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1";
+    let response = app.clone().oneshot(request("SendMessage", Some(trace), json!({
+        "QueueUrl": queue_url,
+        "MessageBody": "order placed",
+        "MessageAttributes": {
+            "tenant": { "DataType": "String", "StringValue": "acme" },
+            "priority": { "DataType": "Number", "StringValue": "1" }
+        }
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(request("ReceiveMessage", None, json!({
+        "QueueUrl": queue_url,
+        "MessageAttributeNames": ["tenant"],
+        "AttributeNames": ["AWSTraceHeader", "ApproximateReceiveCount"]
+    }))).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let message = &json["Messages"][0];
+    assert_eq!(message["MessageAttributes"], json!({ "tenant": { "DataType": "String", "StringValue": "acme" } }));
+    assert_eq!(message["Attributes"]["AWSTraceHeader"], trace);
+    assert_eq!(message["Attributes"]["ApproximateReceiveCount"], "1");
+    assert!(message["Attributes"].get("SentTimestamp").is_none());
+
+    // An explicit AWSTraceHeader system attribute takes precedence over the request header
+    app.clone().oneshot(request("SendMessage", Some(trace), json!({
+        "QueueUrl": queue_url,
+        "MessageBody": "explicit",
+        "MessageSystemAttributes": { "AWSTraceHeader": { "DataType": "String", "StringValue": "Root=1-explicit" } }
+    }))).await.unwrap();
+    let response = app.clone().oneshot(request("ReceiveMessage", None, json!({
+        "QueueUrl": queue_url,
+        "MessageSystemAttributeNames": ["All"]
+    }))).await.unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let message = &json["Messages"][0];
+    assert_eq!(message["Body"], "explicit");
+    assert_eq!(message["Attributes"]["AWSTraceHeader"], "Root=1-explicit");
+    assert!(message["Attributes"]["SentTimestamp"].is_string());
+    assert!(message.get("MessageAttributes").is_none());
+}
+
+#[tokio::test]
+async fn test_sqs_message_validation_and_digests() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let app = gateway::create_router(emulator.clone());
+    let call = |action: &str, body: serde_json::Value| {
+        let app = app.clone();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("x-amz-target", format!("AmazonSQS.{}", action))
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default())
+        }
+    };
+
+    let (status, json) = call("CreateQueue", json!({ "QueueName": "small", "Attributes": { "MaximumMessageSize": "512" } })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(json["__type"], "InvalidAttributeValue");
+    call("CreateQueue", json!({ "QueueName": "small", "Attributes": { "MaximumMessageSize": "1024" } })).await;
+    let queue_url = "http://localhost:4566/000000000000/small";
+
+    let rejected = [
+        (json!({ "MessageBody": "" }), "MissingParameter"),
+        (json!({ "MessageBody": "bell \u{7}" }), "InvalidMessageContents"),
+        (json!({ "MessageBody": "x".repeat(1025) }), "InvalidParameterValue"),
+        (json!({ "MessageBody": "m", "MessageAttributes": { "AWS.tenant": { "DataType": "String", "StringValue": "a" } } }), "InvalidParameterValue"),
+        (json!({ "MessageBody": "m", "MessageAttributes": { "n": { "DataType": "Number", "StringValue": "12a" } } }), "InvalidParameterValue"),
+        (json!({ "MessageBody": "m", "MessageAttributes": { "b": { "DataType": "Binary", "BinaryValue": "not base64!" } } }), "InvalidParameterValue"),
+    ];
+    for (mut body, code) in rejected {
+        body["QueueUrl"] = json!(queue_url);
+        let (status, json) = call("SendMessage", body.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+        assert_eq!(json["__type"], code, "{}", body);
+    }
+
+    // Digests match what the SDKs compute
+    let (status, json) = call("SendMessage", json!({
+        "QueueUrl": queue_url,
+        "MessageBody": "Hello Queue",
+        "MessageAttributes": { "tenant": { "DataType": "String", "StringValue": "acme" } }
+    })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(json["MD5OfMessageBody"], "248425fd3a51f61916c101ce303575e5");
+    let md5_of_attributes = json["MD5OfMessageAttributes"].clone();
+    assert!(md5_of_attributes.is_string());
+
+    let (_, json) = call("ReceiveMessage", json!({ "QueueUrl": queue_url, "MessageAttributeNames": ["All"] })).await;
+    let message = &json["Messages"][0];
+    assert_eq!(message["MD5OfBody"], "248425fd3a51f61916c101ce303575e5");
+    assert_eq!(message["MD5OfMessageAttributes"], md5_of_attributes);
+}
+
+#[tokio::test]
+async fn test_sqs_extended_payload() {
+    let mut emulator = Emulator::in_memory().unwrap();
+    emulator.config.sqs_payload_bucket = Some("sqs-payloads".into());
+    let emulator = Arc::new(emulator);
+    let app = gateway::create_router(emulator.clone());
+    emulator.storage.create_queue("large", "000000000000", "us-east-1").unwrap();
+    emulator.storage.set_queue_maximum_message_size("large", 1024).unwrap();
+
+    let body = "x".repeat(4096);
+    let request = Request::builder()
+        .method("POST")
+        .uri("/")
+        .header("x-amz-target", "AmazonSQS.SendMessage")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "QueueUrl": "http://localhost:4566/000000000000/large", "MessageBody": body }).to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The queue holds a pointer to the body in the payload bucket
+    let message = emulator.storage.receive_message("large", 1).unwrap().remove(0);
+    let pointer: serde_json::Value = serde_json::from_str(&message.body).unwrap();
+    assert_eq!(pointer[0], super::payload::POINTER_CLASS);
+    assert_eq!(pointer[1]["s3BucketName"], "sqs-payloads");
+    let (_, stored) = emulator.storage.get_object("sqs-payloads", pointer[1]["s3Key"].as_str().unwrap(), None).unwrap();
+    assert_eq!(stored, body.as_bytes());
+    let attributes: serde_json::Value = serde_json::from_str(message.message_attributes.as_deref().unwrap()).unwrap();
+    assert_eq!(attributes[super::payload::SIZE_ATTRIBUTE]["StringValue"], "4096");
+}
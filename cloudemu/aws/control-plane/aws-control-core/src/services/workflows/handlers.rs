@@ -6,6 +6,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use super::interpreter::{StateError, StateMachineExecutor};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use tracing::info;
@@ -29,6 +30,8 @@ pub async fn handle_request(
         "DeleteStateMachine" => delete_state_machine(&emulator, body).await,
         "StartExecution" => start_execution(&emulator, body).await,
         "DescribeExecution" => describe_execution(&emulator, body).await,
+        "ListExecutions" => list_executions(&emulator, body).await,
+        "TestState" => test_state(body),
         _ => Err(EmulatorError::InvalidRequest(format!("Unknown or unsupported target: {}", target))),
     };
 
@@ -92,16 +95,26 @@ async fn delete_state_machine(emulator: &Emulator, body: Value) -> Result<Value,
     Ok(json!({}))
 }
 
-async fn start_execution(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+pub async fn start_execution(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
     let machine_arn = body["stateMachineArn"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing stateMachineArn".into()))?;
     let name = body["name"].as_str();
     let input = body["input"].as_str().unwrap_or("{}");
+    // As in Step Functions Local, `<stateMachineArn>#<TestCase>` runs a test case of the mock config
+    let (machine_arn, test_case) = match machine_arn.split_once('#') {
+        Some((arn, test_case)) => (arn, Some(test_case)),
+        None => (machine_arn, None),
+    };
 
     // Get state machine definition
     let machines = emulator.storage.list_state_machines()?;
     let machine = machines.iter().find(|m| m.arn == machine_arn)
         .ok_or_else(|| EmulatorError::NotFound("StateMachine".into(), machine_arn.into()))?;
 
+    let mut mocks = match test_case {
+        Some(test_case) => emulator.workflows.mocks.test_case(&machine.name, test_case)?,
+        None => Default::default(),
+    };
+
     // Create execution record
     let mut exec = emulator.storage.start_execution(
         machine_arn,
@@ -113,7 +126,7 @@ async fn start_execution(emulator: &Emulator, body: Value) -> Result<Value, Emul
 
     // Execute the state machine
     info!("StepFunctions: Executing state machine: {}", machine.name);
-    match super::interpreter::StateMachineExecutor::execute(&machine.definition, input) {
+    match StateMachineExecutor::execute_mocked(&machine.definition, input, &mut mocks) {
         Ok(output) => {
             // Update execution with success
             emulator.storage.update_execution_status(&exec.arn, "SUCCEEDED", Some(&output))?;
@@ -151,3 +164,68 @@ async fn describe_execution(emulator: &Emulator, body: Value) -> Result<Value, E
         "output": exec.output
     }))
 }
+
+async fn list_executions(emulator: &Emulator, body: Value) -> Result<Value, EmulatorError> {
+    let machine_arn = body["stateMachineArn"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing stateMachineArn".into()))?;
+    let status_filter = body["statusFilter"].as_str();
+
+    let executions: Vec<Value> = emulator.storage.list_executions(machine_arn)?
+        .into_iter()
+        .filter(|e| status_filter.is_none_or(|s| s == e.status))
+        .map(|e| json!({
+            "executionArn": e.arn,
+            "stateMachineArn": e.state_machine_arn,
+            "name": e.name,
+            "status": e.status,
+            "startDate": 1234567890.0
+        }))
+        .collect();
+
+    Ok(json!({
+        "executions": executions
+    }))
+}
+
+/// Run one state with the given input, and a mocked integration result for Task states,
+/// without creating a state machine
+fn test_state(body: Value) -> Result<Value, EmulatorError> {
+    let definition = body["definition"].as_str().ok_or_else(|| EmulatorError::InvalidArgument("Missing definition".into()))?;
+    let definition: Value = serde_json::from_str(definition)
+        .map_err(|e| EmulatorError::InvalidParameter { code: "InvalidDefinition", message: format!("Invalid state definition: {}", e) })?;
+    // A whole state machine can be given along with the name of the state to test
+    let state = match body["stateName"].as_str() {
+        Some(name) => definition["States"].get(name).cloned()
+            .ok_or_else(|| EmulatorError::InvalidParameter { code: "InvalidDefinition", message: format!("State not found: {}", name) })?,
+        None => definition,
+    };
+    let input: Value = serde_json::from_str(body["input"].as_str().unwrap_or("{}"))
+        .map_err(|e| EmulatorError::InvalidParameter { code: "InvalidExecutionInput", message: format!("Invalid input JSON: {}", e) })?;
+
+    let mock = &body["mock"];
+    let mock = match mock["result"].as_str() {
+        Some(result) => Some(Ok(serde_json::from_str(result)
+            .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid mock result JSON: {}", e)))?)),
+        None => mock["errorOutput"].as_object().map(|error| Err(StateError {
+            error: error.get("error").and_then(Value::as_str).unwrap_or("States.TaskFailed").to_string(),
+            cause: error.get("cause").and_then(Value::as_str).unwrap_or_default().to_string(),
+        })),
+    };
+
+    let test = StateMachineExecutor::test_state(&state, &input, mock)?;
+    let mut response = json!({ "status": test.status.as_str() });
+    if let Some(output) = test.output {
+        response["output"] = json!(output.to_string());
+    }
+    if let Some(error) = test.error {
+        response["error"] = json!(error.error);
+        response["cause"] = json!(error.cause);
+    }
+    if let Some(next_state) = test.next_state {
+        response["nextState"] = json!(next_state);
+    }
+    // INFO, the default, leaves out how the state processed its data
+    if matches!(body["inspectionLevel"].as_str(), Some("DEBUG" | "TRACE")) {
+        response["inspectionData"] = test.inspection.to_json();
+    }
+    Ok(response)
+}
@@ -1,86 +1,258 @@
 /// Step Functions State Machine Interpreter
 /// Implements Amazon States Language (ASL) execution
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use super::mocks::MockedTasks;
 use crate::error::EmulatorError;
 type Result<T> = std::result::Result<T, EmulatorError>;
 
+/// Error raised by a state, which the `ErrorEquals` of retriers and catchers match
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateError {
+    pub error: String,
+    pub cause: String,
+}
+
+impl StateError {
+    fn matches(&self, rule: &Value) -> bool {
+        rule["ErrorEquals"].as_array()
+            .is_some_and(|names| names.iter().any(|name| name == "States.ALL" || name.as_str() == Some(self.error.as_str())))
+    }
+
+    /// Error output a catcher passes to its next state
+    fn output(&self) -> Value {
+        json!({ "Error": self.error, "Cause": self.cause })
+    }
+}
+
+/// A state's data at each step of its input and output processing
+#[derive(Debug, Clone, Default)]
+pub struct Inspection {
+    pub input: Value,
+    pub after_input_path: Option<Value>,
+    pub after_parameters: Option<Value>,
+    pub result: Option<Value>,
+    pub after_result_selector: Option<Value>,
+    pub after_result_path: Option<Value>,
+}
+
+impl Inspection {
+    /// `inspectionData` of a TestState response, whose values are JSON text
+    pub fn to_json(&self) -> Value {
+        let mut data = json!({ "input": self.input.to_string() });
+        for (key, value) in [
+            ("afterInputPath", &self.after_input_path),
+            ("afterParameters", &self.after_parameters),
+            ("result", &self.result),
+            ("afterResultSelector", &self.after_result_selector),
+            ("afterResultPath", &self.after_result_path),
+        ] {
+            if let Some(value) = value {
+                data[key] = json!(value.to_string());
+            }
+        }
+        data
+    }
+}
+
+/// Outcome of a state tested on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    Succeeded,
+    Failed,
+    /// The state failed with an error one of its retriers would retry
+    Retriable,
+    /// The state failed with an error one of its catchers handles
+    CaughtError,
+}
+
+impl TestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Succeeded => "SUCCEEDED",
+            Self::Failed => "FAILED",
+            Self::Retriable => "RETRIABLE",
+            Self::CaughtError => "CAUGHT_ERROR",
+        }
+    }
+}
+
+/// Result of the TestState API
+#[derive(Debug, Clone)]
+pub struct StateTest {
+    pub status: TestStatus,
+    pub output: Option<Value>,
+    pub error: Option<StateError>,
+    pub next_state: Option<String>,
+    pub inspection: Inspection,
+}
+
 pub struct StateMachineExecutor;
 
 impl StateMachineExecutor {
     /// Execute a state machine with the given input
     pub fn execute(definition: &str, input: &str) -> Result<String> {
+        Self::execute_mocked(definition, input, &mut MockedTasks::default())
+    }
+
+    /// Execute a state machine whose mocked Task states return stubbed responses
+    pub fn execute_mocked(definition: &str, input: &str, mocks: &mut MockedTasks) -> Result<String> {
         let def: Value = serde_json::from_str(definition)
             .map_err(|e| EmulatorError::InvalidRequest(format!("Invalid state machine definition: {}", e)))?;
         
-        let mut current_input: Value = serde_json::from_str(input)
+        let input: Value = serde_json::from_str(input)
             .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid input JSON: {}", e)))?;
         
-        let start_at = def["StartAt"].as_str()
-            .ok_or_else(|| EmulatorError::InvalidRequest("Missing StartAt in definition".into()))?;
+        run(&def, input, mocks).map(|output| output.to_string())
+    }
+
+    /// Run one state on its own, as the TestState API does. A Task state takes `mock` as the
+    /// result of its integration, or passes its input through; the retrier or catcher its
+    /// error matches is reported, not followed.
+    pub fn test_state(state_def: &Value, input: &Value, mock: Option<std::result::Result<Value, StateError>>) -> Result<StateTest> {
+        let state_type = state_def["Type"].as_str()
+            .ok_or_else(|| EmulatorError::InvalidRequest("Missing Type in state definition".into()))?;
+        let next = state_def["Next"].as_str().map(String::from);
+        let mut test = StateTest {
+            status: TestStatus::Succeeded,
+            output: None,
+            error: None,
+            next_state: None,
+            inspection: Inspection { input: input.clone(), ..Default::default() },
+        };
+
+        match state_type {
+            "Task" => {
+                let mut inspection = task_input(state_def, input)?;
+                let result = mock.unwrap_or_else(|| Ok(inspection.after_parameters.clone().unwrap_or(Value::Null)));
+                match result {
+                    Ok(result) => {
+                        test.output = Some(task_output(state_def, &mut inspection, result)?);
+                        test.next_state = next;
+                    }
+                    Err(error) => {
+                        if state_def["Retry"].as_array().and_then(|retriers| retriers.iter().find(|r| error.matches(r)))
+                            .is_some_and(|retrier| retrier["MaxAttempts"].as_u64().unwrap_or(3) > 0) {
+                            test.status = TestStatus::Retriable;
+                        } else if let Some(catcher) = catcher(state_def, &error) {
+                            test.status = TestStatus::CaughtError;
+                            test.output = Some(result_path(catcher, &error.output(), input));
+                            test.next_state = catcher["Next"].as_str().map(String::from);
+                        } else {
+                            test.status = TestStatus::Failed;
+                        }
+                        test.error = Some(error);
+                    }
+                }
+                test.inspection = inspection;
+            }
+            "Choice" => {
+                test.output = Some(input.clone());
+                test.next_state = Some(execute_choice_state(state_def, input)?);
+            }
+            "Succeed" => test.output = Some(input.clone()),
+            "Fail" => {
+                test.status = TestStatus::Failed;
+                test.error = Some(StateError {
+                    error: state_def["Error"].as_str().unwrap_or("States.TaskFailed").to_string(),
+                    cause: state_def["Cause"].as_str().unwrap_or("State machine failed").to_string(),
+                });
+            }
+            "Pass" | "Wait" | "Parallel" | "Map" => {
+                let output = match state_type {
+                    "Pass" => execute_pass_state(state_def, input)?,
+                    "Wait" => execute_wait_state(state_def, input)?,
+                    "Parallel" => execute_parallel_state(state_def, input, &mut MockedTasks::default())?,
+                    _ => execute_map_state(state_def, input, &mut MockedTasks::default())?,
+                };
+                test.output = Some(output);
+                test.next_state = next;
+            }
+            _ => return Err(EmulatorError::InvalidRequest(format!("Unknown state type: {}", state_type))),
+        }
+        Ok(test)
+    }
+}
+
+/// Run a state machine definition, or a Parallel branch or Map iterator, to its end
+fn run(def: &Value, mut current_input: Value, mocks: &mut MockedTasks) -> Result<Value> {
+    let start_at = def["StartAt"].as_str()
+        .ok_or_else(|| EmulatorError::InvalidRequest("Missing StartAt in definition".into()))?;
+    
+    let states = def["States"].as_object()
+        .ok_or_else(|| EmulatorError::InvalidRequest("Missing States in definition".into()))?;
+    
+    let mut current_state = start_at.to_string();
+    let mut iteration_count = 0;
+    const MAX_ITERATIONS: usize = 1000; // Prevent infinite loops
+    
+    loop {
+        if iteration_count >= MAX_ITERATIONS {
+            return Err(EmulatorError::Internal("State machine exceeded maximum iterations".into()));
+        }
+        iteration_count += 1;
         
-        let states = def["States"].as_object()
-            .ok_or_else(|| EmulatorError::InvalidRequest("Missing States in definition".into()))?;
+        let state_def = states.get(&current_state)
+            .ok_or_else(|| EmulatorError::InvalidRequest(format!("State not found: {}", current_state)))?;
         
-        let mut current_state = start_at.to_string();
-        let mut iteration_count = 0;
-        const MAX_ITERATIONS: usize = 1000; // Prevent infinite loops
+        let state_type = state_def["Type"].as_str()
+            .ok_or_else(|| EmulatorError::InvalidRequest(format!("Missing Type for state: {}", current_state)))?;
         
-        loop {
-            if iteration_count >= MAX_ITERATIONS {
-                return Err(EmulatorError::Internal("State machine exceeded maximum iterations".into()));
-            }
-            iteration_count += 1;
-            
-            let state_def = states.get(&current_state)
-                .ok_or_else(|| EmulatorError::InvalidRequest(format!("State not found: {}", current_state)))?;
-            
-            let state_type = state_def["Type"].as_str()
-                .ok_or_else(|| EmulatorError::InvalidRequest(format!("Missing Type for state: {}", current_state)))?;
-            
-            tracing::info!("StepFunctions: Executing state '{}' (Type: {})", current_state, state_type);
-            
-            match state_type {
-                "Pass" => {
-                    current_input = execute_pass_state(state_def, &current_input)?;
-                },
-                "Task" => {
-                    current_input = execute_task_state(state_def, &current_input)?;
-                },
-                "Choice" => {
-                    current_state = execute_choice_state(state_def, &current_input)?;
-                    continue; // Don't check End, Choice handles its own transitions
-                }
-                "Wait" => {
-                    current_input = execute_wait_state(state_def, &current_input)?;
-                },
-                "Succeed" => {
-                    return Ok(current_input.to_string());
-                },
-                "Fail" => {
-                    let error = state_def["Error"].as_str().unwrap_or("States.TaskFailed");
-                    let cause = state_def["Cause"].as_str().unwrap_or("State machine failed");
-                    return Err(EmulatorError::Internal(format!("{}: {}", error, cause)));
-                },
-                "Parallel" => {
-                    current_input = execute_parallel_state(state_def, &current_input)?;
-                },
-                "Map" => {
-                    current_input = execute_map_state(state_def, &current_input)?;
-                },
-                _ => {
-                    return Err(EmulatorError::InvalidRequest(format!("Unknown state type: {}", state_type)));
+        tracing::info!("StepFunctions: Executing state '{}' (Type: {})", current_state, state_type);
+        
+        match state_type {
+            "Pass" => {
+                current_input = execute_pass_state(state_def, &current_input)?;
+            },
+            "Task" => {
+                match execute_task_state(&current_state, state_def, &current_input, mocks)? {
+                    Ok(output) => current_input = output,
+                    Err(error) => match catcher(state_def, &error) {
+                        Some(catcher) => {
+                            tracing::info!("StepFunctions: State '{}' caught {}", current_state, error.error);
+                            current_input = result_path(catcher, &error.output(), &current_input);
+                            current_state = catcher["Next"].as_str()
+                                .ok_or_else(|| EmulatorError::InvalidRequest("Catcher missing Next".into()))?.to_string();
+                            continue;
+                        }
+                        None => return Err(EmulatorError::Internal(format!("{}: {}", error.error, error.cause))),
+                    },
                 }
+            },
+            "Choice" => {
+                current_state = execute_choice_state(state_def, &current_input)?;
+                continue; // Don't check End, Choice handles its own transitions
             }
-            
-            // Check if this is an end state
-            if state_def["End"].as_bool().unwrap_or(false) {
-                return Ok(current_input.to_string());
+            "Wait" => {
+                current_input = execute_wait_state(state_def, &current_input)?;
+            },
+            "Succeed" => {
+                return Ok(current_input);
+            },
+            "Fail" => {
+                let error = state_def["Error"].as_str().unwrap_or("States.TaskFailed");
+                let cause = state_def["Cause"].as_str().unwrap_or("State machine failed");
+                return Err(EmulatorError::Internal(format!("{}: {}", error, cause)));
+            },
+            "Parallel" => {
+                current_input = execute_parallel_state(state_def, &current_input, mocks)?;
+            },
+            "Map" => {
+                current_input = execute_map_state(state_def, &current_input, mocks)?;
+            },
+            _ => {
+                return Err(EmulatorError::InvalidRequest(format!("Unknown state type: {}", state_type)));
             }
-            
-            // Get next state
-            current_state = state_def["Next"].as_str()
-                .ok_or_else(|| EmulatorError::InvalidRequest(format!("State '{}' has no Next and is not an End state", current_state)))?.to_string();
         }
+        
+        // Check if this is an end state
+        if state_def["End"].as_bool().unwrap_or(false) {
+            return Ok(current_input);
+        }
+        
+        // Get next state
+        current_state = state_def["Next"].as_str()
+            .ok_or_else(|| EmulatorError::InvalidRequest(format!("State '{}' has no Next and is not an End state", current_state)))?.to_string();
     }
 }
 
@@ -110,11 +282,88 @@ fn execute_pass_state(state_def: &Value, input: &Value) -> Result<Value> {
     Ok(output)
 }
 
-fn execute_task_state(_state_def: &Value, input: &Value) -> Result<Value> {
-    // For emulator, Task states pass through their input as output
-    // In a real implementation, this would invoke Lambda, run activities, etc.
-    tracing::info!("StepFunctions: Task state executing (emulated - passing through)");
-    Ok(input.clone())
+/// Run a Task state, retrying its integration as its retriers allow. Integrations are not
+/// invoked: mocked states return their stubbed response and others pass their effective input
+/// through. A final error is returned for the caller to catch.
+fn execute_task_state(name: &str, state_def: &Value, input: &Value, mocks: &mut MockedTasks) -> Result<std::result::Result<Value, StateError>> {
+    let mut inspection = task_input(state_def, input)?;
+    let effective_input = inspection.after_parameters.clone().unwrap_or(Value::Null);
+    let retriers = state_def["Retry"].as_array().cloned().unwrap_or_default();
+    let mut retries: HashMap<usize, u64> = HashMap::new();
+
+    loop {
+        let result = match mocks.invoke(name)? {
+            Some(mocked) => mocked.result(),
+            None => {
+                tracing::info!("StepFunctions: Task state executing (emulated - passing through)");
+                Ok(effective_input.clone())
+            }
+        };
+        let error = match result {
+            Ok(result) => return task_output(state_def, &mut inspection, result).map(Ok),
+            Err(error) => error,
+        };
+
+        // Only the first retrier matching an error applies; backoff is skipped like Wait states
+        let Some(index) = retriers.iter().position(|retrier| error.matches(retrier)) else {
+            return Ok(Err(error));
+        };
+        let attempts = retries.entry(index).or_default();
+        if *attempts >= retriers[index]["MaxAttempts"].as_u64().unwrap_or(3) {
+            return Ok(Err(error));
+        }
+        *attempts += 1;
+        tracing::info!("StepFunctions: Retrying state '{}' after {} (attempt {})", name, error.error, attempts);
+    }
+}
+
+/// Effective input of a Task state, after its InputPath and Parameters
+fn task_input(state_def: &Value, input: &Value) -> Result<Inspection> {
+    let after_input_path = match state_def["InputPath"].as_str() {
+        Some(path) => apply_output_path(input, path)?,
+        None => input.clone(),
+    };
+    let after_parameters = match state_def.get("Parameters") {
+        Some(parameters) => apply_parameters(parameters, &after_input_path)?,
+        None => after_input_path.clone(),
+    };
+    Ok(Inspection {
+        input: input.clone(),
+        after_input_path: Some(after_input_path),
+        after_parameters: Some(after_parameters),
+        ..Default::default()
+    })
+}
+
+/// Output of a Task state from its result, after its ResultSelector, ResultPath and OutputPath
+fn task_output(state_def: &Value, inspection: &mut Inspection, result: Value) -> Result<Value> {
+    let selected = match state_def.get("ResultSelector") {
+        Some(selector) => apply_parameters(selector, &result)?,
+        None => result.clone(),
+    };
+    let after_result_path = result_path(state_def, &selected, &inspection.input);
+    let output = match state_def["OutputPath"].as_str() {
+        Some(path) => apply_output_path(&after_result_path, path)?,
+        None => after_result_path.clone(),
+    };
+    inspection.result = Some(result);
+    inspection.after_result_selector = Some(selected);
+    inspection.after_result_path = Some(after_result_path);
+    Ok(output)
+}
+
+/// Place a result in the input by the ResultPath of a state or catcher; `null` discards it
+fn result_path(def: &Value, result: &Value, input: &Value) -> Value {
+    match def.get("ResultPath") {
+        None => result.clone(),
+        Some(Value::Null) => input.clone(),
+        Some(path) => apply_result_path(result, input, path.as_str().unwrap_or("$")),
+    }
+}
+
+/// First catcher of a state matching an error
+fn catcher<'a>(state_def: &'a Value, error: &StateError) -> Option<&'a Value> {
+    state_def["Catch"].as_array()?.iter().find(|catcher| error.matches(catcher))
 }
 
 fn execute_choice_state(state_def: &Value, input: &Value) -> Result<String> {
@@ -179,7 +428,7 @@ fn execute_wait_state(_state_def: &Value, input: &Value) -> Result<Value> {
     Ok(input.clone())
 }
 
-fn execute_parallel_state(state_def: &Value, input: &Value) -> Result<Value> {
+fn execute_parallel_state(state_def: &Value, input: &Value, mocks: &mut MockedTasks) -> Result<Value> {
     let branches = state_def["Branches"].as_array()
         .ok_or_else(|| EmulatorError::InvalidRequest("Parallel state missing Branches".into()))?;
     
@@ -187,18 +436,13 @@ fn execute_parallel_state(state_def: &Value, input: &Value) -> Result<Value> {
     
     for branch in branches {
         // Execute each branch
-        let branch_def = serde_json::to_string(branch)
-            .map_err(|e| EmulatorError::Internal(e.to_string()))?;
-        let branch_output = StateMachineExecutor::execute(&branch_def, &input.to_string())?;
-        let output_val: Value = serde_json::from_str(&branch_output)
-            .map_err(|e| EmulatorError::Internal(e.to_string()))?;
-        results.push(output_val);
+        results.push(run(branch, input.clone(), mocks)?);
     }
     
     Ok(json!(results))
 }
 
-fn execute_map_state(state_def: &Value, input: &Value) -> Result<Value> {
+fn execute_map_state(state_def: &Value, input: &Value, mocks: &mut MockedTasks) -> Result<Value> {
     let items = input.as_array()
         .ok_or_else(|| EmulatorError::InvalidArgument("Map state input must be an array".into()))?;
     
@@ -208,12 +452,7 @@ fn execute_map_state(state_def: &Value, input: &Value) -> Result<Value> {
     let mut results = Vec::new();
     
     for item in items {
-        let iterator_def = serde_json::to_string(iterator)
-            .map_err(|e| EmulatorError::Internal(e.to_string()))?;
-        let item_output = StateMachineExecutor::execute(&iterator_def, &item.to_string())?;
-        let output_val: Value = serde_json::from_str(&item_output)
-            .map_err(|e| EmulatorError::Internal(e.to_string()))?;
-        results.push(output_val);
+        results.push(run(iterator, item.clone(), mocks)?);
     }
     
     Ok(json!(results))
@@ -352,5 +591,80 @@ mod tests {
         let output = StateMachineExecutor::execute(&def, "[1, 2, 3]").unwrap();
         assert_eq!(output, "[1,2,3]");
     }
-}
 
+    #[test]
+    fn test_mocked_task_retry_and_catch() {
+        let mocks = super::super::mocks::MockConfig::parse(&json!({
+            "StateMachines": { "Order": { "TestCases": {
+                "Flaky": { "Charge": "FailsOnce" },
+                "Declined": { "Charge": "Declined" }
+            } } },
+            "MockedResponses": {
+                "FailsOnce": {
+                    "0": { "Throw": { "Error": "Lambda.ServiceException", "Cause": "throttled" } },
+                    "1": { "Return": { "charged": 10 } }
+                },
+                "Declined": { "0-9": { "Throw": { "Error": "Payment.Declined", "Cause": "Card expired" } } }
+            }
+        }).to_string()).unwrap();
+        let def = json!({
+            "StartAt": "Charge",
+            "States": {
+                "Charge": {
+                    "Type": "Task",
+                    "Resource": "arn:aws:lambda:us-east-1:000000000000:function:charge",
+                    "ResultPath": "$.payment",
+                    "Retry": [{ "ErrorEquals": ["Lambda.ServiceException"], "MaxAttempts": 2 }],
+                    "Catch": [{ "ErrorEquals": ["States.ALL"], "ResultPath": "$.error", "Next": "Refund" }],
+                    "End": true
+                },
+                "Refund": { "Type": "Pass", "End": true }
+            }
+        }).to_string();
+
+        let mut flaky = mocks.test_case("Order", "Flaky").unwrap();
+        let output = StateMachineExecutor::execute_mocked(&def, r#"{"id": 1}"#, &mut flaky).unwrap();
+        assert_eq!(output, r#"{"id":1,"payment":{"charged":10}}"#);
+
+        // Not retried, caught, and the Pass state after the catcher keeps the error output
+        let mut declined = mocks.test_case("Order", "Declined").unwrap();
+        let output: Value = serde_json::from_str(&StateMachineExecutor::execute_mocked(&def, r#"{"id": 1}"#, &mut declined).unwrap()).unwrap();
+        assert_eq!(output["error"], json!({ "Error": "Payment.Declined", "Cause": "Card expired" }));
+
+        // Unmocked, the task passes its input through
+        assert_eq!(StateMachineExecutor::execute(&def, r#"{"id": 1}"#).unwrap(), r#"{"id":1,"payment":{"id":1}}"#);
+    }
+
+    #[test]
+    fn test_state_in_isolation() {
+        let task = json!({
+            "Type": "Task",
+            "Resource": "arn:aws:states:::dynamodb:getItem",
+            "InputPath": "$.order",
+            "ResultSelector": { "found": true },
+            "ResultPath": "$.lookup",
+            "Retry": [{ "ErrorEquals": ["States.Timeout"] }],
+            "Catch": [{ "ErrorEquals": ["Order.Missing"], "Next": "NotFound" }],
+            "Next": "Ship"
+        });
+        let input = json!({ "order": { "id": 7 } });
+
+        let test = StateMachineExecutor::test_state(&task, &input, Some(Ok(json!({ "Item": {} })))).unwrap();
+        assert_eq!(test.status, TestStatus::Succeeded);
+        assert_eq!(test.output, Some(json!({ "order": { "id": 7 }, "lookup": { "found": true } })));
+        assert_eq!(test.next_state.as_deref(), Some("Ship"));
+        assert_eq!(test.inspection.after_input_path, Some(json!({ "id": 7 })));
+        assert_eq!(test.inspection.result, Some(json!({ "Item": {} })));
+
+        let error = |error: &str| Some(Err(StateError { error: error.into(), cause: "".into() }));
+        assert_eq!(StateMachineExecutor::test_state(&task, &input, error("States.Timeout")).unwrap().status, TestStatus::Retriable);
+        let caught = StateMachineExecutor::test_state(&task, &input, error("Order.Missing")).unwrap();
+        assert_eq!(caught.status, TestStatus::CaughtError);
+        assert_eq!(caught.next_state.as_deref(), Some("NotFound"));
+        assert_eq!(caught.output, Some(json!({ "Error": "Order.Missing", "Cause": "" })));
+        assert_eq!(StateMachineExecutor::test_state(&task, &input, error("Other")).unwrap().status, TestStatus::Failed);
+
+        let choice = json!({ "Type": "Choice", "Choices": [{ "Variable": "$.order.id", "NumericGreaterThan": 5, "Next": "Big" }], "Default": "Small" });
+        assert_eq!(StateMachineExecutor::test_state(&choice, &input, None).unwrap().next_state.as_deref(), Some("Big"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Mocked service integrations
+//!
+//! A mock configuration file, in the format of Step Functions Local, stubs the responses of
+//! Task states so state machines can be tested without the services they call. Test cases map
+//! state names to mocked responses; executions started with `<stateMachineArn>#<TestCase>`
+//! use them, and Task states left out of the test case pass their input through as usual.
+//!
+//! ```json
+//! {
+//!   "StateMachines": {
+//!     "OrderFlow": { "TestCases": { "PaymentDeclined": { "Charge": "DeclinedThenPaid" } } }
+//!   },
+//!   "MockedResponses": {
+//!     "DeclinedThenPaid": {
+//!       "0": { "Throw": { "Error": "Payment.Declined", "Cause": "Card expired" } },
+//!       "1-2": { "Return": { "status": "paid" } }
+//!     }
+//!   }
+//! }
+//! ```
+
+use super::interpreter::StateError;
+use crate::error::EmulatorError;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, EmulatorError>;
+
+/// Response of one invocation of a mocked Task state
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum MockedInvocation {
+    /// The integration returns this result
+    Return(Value),
+    /// The integration fails with this error
+    Throw(MockedError),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MockedError {
+    #[serde(rename = "Error")]
+    pub error: String,
+    #[serde(rename = "Cause", default)]
+    pub cause: String,
+}
+
+impl MockedInvocation {
+    pub fn result(&self) -> std::result::Result<Value, StateError> {
+        match self {
+            Self::Return(result) => Ok(result.clone()),
+            Self::Throw(e) => Err(StateError { error: e.error.clone(), cause: e.cause.clone() }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct MockedTestCases {
+    #[serde(rename = "TestCases", default)]
+    test_cases: HashMap<String, HashMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+struct MockConfigFile {
+    #[serde(rename = "StateMachines", default)]
+    state_machines: HashMap<String, MockedTestCases>,
+    #[serde(rename = "MockedResponses", default)]
+    mocked_responses: HashMap<String, HashMap<String, MockedInvocation>>,
+}
+
+/// Responses of a mocked response, by the invocations (`"0"`, `"1-2"`) they answer
+type MockedResponse = Vec<(RangeInclusive<usize>, MockedInvocation)>;
+
+/// Mocked responses and the test cases of each state machine using them
+#[derive(Debug, Default)]
+pub struct MockConfig {
+    /// State machine name -> test case -> state name -> mocked response name
+    test_cases: HashMap<String, HashMap<String, HashMap<String, String>>>,
+    responses: HashMap<String, MockedResponse>,
+}
+
+fn invocations(key: &str) -> Option<RangeInclusive<usize>> {
+    match key.split_once('-') {
+        Some((first, last)) => Some(first.trim().parse().ok()?..=last.trim().parse().ok()?),
+        None => key.trim().parse().ok().map(|n| n..=n),
+    }
+}
+
+impl MockConfig {
+    /// Configuration for `CLOUDEMU_SFN_MOCK_CONFIG`, empty when unset
+    pub fn from_config(config: &crate::Config) -> Result<Self> {
+        match &config.sfn_mock_config {
+            Some(path) => Self::load(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            EmulatorError::InvalidArgument(format!("Cannot read Step Functions mock config {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: MockConfigFile = serde_json::from_str(text)
+            .map_err(|e| EmulatorError::InvalidArgument(format!("Invalid Step Functions mock config: {}", e)))?;
+
+        let mut responses = HashMap::new();
+        for (name, by_key) in file.mocked_responses {
+            let mut response = Vec::with_capacity(by_key.len());
+            for (key, invocation) in by_key {
+                let range = invocations(&key).filter(|range| !range.is_empty()).ok_or_else(|| {
+                    EmulatorError::InvalidArgument(format!("Mocked response {} has invalid invocation key {}, expected like 0 or 1-2", name, key))
+                })?;
+                response.push((range, invocation));
+            }
+            response.sort_by_key(|(range, _)| *range.start());
+            responses.insert(name, response);
+        }
+
+        let mut test_cases = HashMap::new();
+        for (machine, cases) in file.state_machines {
+            for (case, states) in &cases.test_cases {
+                if let Some(missing) = states.values().find(|name| !responses.contains_key(*name)) {
+                    return Err(EmulatorError::InvalidArgument(format!(
+                        "Test case {} of state machine {} uses undefined mocked response {}", case, machine, missing
+                    )));
+                }
+            }
+            test_cases.insert(machine, cases.test_cases);
+        }
+
+        Ok(Self { test_cases, responses })
+    }
+
+    /// Mocked Task states of a state machine's test case
+    pub fn test_case(&self, state_machine: &str, test_case: &str) -> Result<MockedTasks<'_>> {
+        let states = self.test_cases.get(state_machine).and_then(|cases| cases.get(test_case)).ok_or_else(|| {
+            EmulatorError::InvalidArgument(format!("Test case {} of state machine {} is not in the mock config", test_case, state_machine))
+        })?;
+        Ok(MockedTasks {
+            responses: states.iter().map(|(state, response)| (state.as_str(), &self.responses[response])).collect(),
+            invocations: HashMap::new(),
+        })
+    }
+}
+
+/// Mocked Task states of one execution, answering each state's invocations in order; retries
+/// are invocations too
+#[derive(Debug, Default)]
+pub struct MockedTasks<'a> {
+    responses: HashMap<&'a str, &'a MockedResponse>,
+    invocations: HashMap<String, usize>,
+}
+
+impl MockedTasks<'_> {
+    /// Response to the next invocation of a state, or `None` when the state is not mocked
+    pub fn invoke(&mut self, state: &str) -> Result<Option<MockedInvocation>> {
+        let Some(response) = self.responses.get(state) else {
+            return Ok(None);
+        };
+        let invocation = self.invocations.entry(state.to_string()).or_default();
+        let n = *invocation;
+        *invocation += 1;
+        response.iter().find(|(range, _)| range.contains(&n)).map(|(_, invocation)| Some(invocation.clone())).ok_or_else(|| {
+            EmulatorError::InvalidArgument(format!("Mocked state {} has no response for invocation {}", state, n))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_invocations_are_answered_in_order() {
+        let config = MockConfig::parse(&json!({
+            "StateMachines": { "OrderFlow": { "TestCases": { "Declined": { "Charge": "DeclinedThenPaid" } } } },
+            "MockedResponses": {
+                "DeclinedThenPaid": {
+                    "0": { "Throw": { "Error": "Payment.Declined", "Cause": "Card expired" } },
+                    "1-2": { "Return": { "status": "paid" } }
+                }
+            }
+        }).to_string()).unwrap();
+
+        let mut tasks = config.test_case("OrderFlow", "Declined").unwrap();
+        assert_eq!(tasks.invoke("Ship").unwrap(), None);
+        assert_eq!(tasks.invoke("Charge").unwrap().unwrap().result().unwrap_err().error, "Payment.Declined");
+        assert_eq!(tasks.invoke("Charge").unwrap().unwrap().result().unwrap(), json!({ "status": "paid" }));
+        assert!(tasks.invoke("Charge").unwrap().is_some());
+        assert!(tasks.invoke("Charge").is_err());
+
+        assert!(config.test_case("OrderFlow", "Missing").is_err());
+        assert!(MockConfig::parse(r#"{"StateMachines": {"M": {"TestCases": {"T": {"S": "Undefined"}}}}}"#).is_err());
+        assert!(MockConfig::parse(r#"{"MockedResponses": {"R": {"first": {"Return": {}}}}}"#).is_err());
+    }
+}
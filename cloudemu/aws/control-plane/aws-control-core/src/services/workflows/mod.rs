@@ -1,5 +1,6 @@
 pub mod service;
 pub mod handlers;
 pub mod interpreter;
+pub mod mocks;
 
 pub use service::WorkflowsService;
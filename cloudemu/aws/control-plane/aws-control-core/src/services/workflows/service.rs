@@ -1,11 +1,15 @@
+use super::mocks::MockConfig;
 use aws_data_core::storage::StorageEngine;
+use aws_data_core::{Config, EmulatorError};
 
 pub struct WorkflowsService {
     _storage: StorageEngine,
+    /// Mocked Task responses of the test cases executions can run
+    pub mocks: MockConfig,
 }
 
 impl WorkflowsService {
-    pub fn new(storage: StorageEngine) -> Self {
-        Self { _storage: storage }
+    pub fn new(storage: StorageEngine, config: &Config) -> Result<Self, EmulatorError> {
+        Ok(Self { _storage: storage, mocks: MockConfig::from_config(config)? })
     }
 }
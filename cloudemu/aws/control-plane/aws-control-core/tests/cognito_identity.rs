@@ -0,0 +1,63 @@
+use aws_control_core::scenario::{Scenario, Step};
+use aws_control_core::{Emulator, gateway};
+use serde_json::json;
+use std::sync::Arc;
+
+const TRUST_POLICY: &str = r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Principal":{"Federated":"cognito-identity.amazonaws.com"},"Action":"sts:AssumeRoleWithWebIdentity"}]}"#;
+
+#[tokio::test]
+async fn test_identity_pool_exchanges_id_token_for_role_credentials() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let router = gateway::create_router(emulator.clone());
+    let signed_in = emulator.storage.create_role("app-signed-in", TRUST_POLICY).unwrap().arn;
+    let guest = emulator.storage.create_role("app-guest", TRUST_POLICY).unwrap().arn;
+    let user_pool = emulator.storage.create_user_pool("app", "000000000000", "us-east-1").unwrap().id;
+    emulator.storage.admin_create_user(&user_pool, "alice", vec![]).unwrap();
+    // Logins are keyed by provider name
+    let provider = format!("cognito-idp.us-east-1.amazonaws.com/{}", user_pool);
+    let login = |token: &str| json!({ provider.clone(): token });
+
+    Scenario::new("federation")
+        .var("signed_in", signed_in)
+        .var("guest", guest)
+        .var("user_pool", user_pool.clone())
+        // The emulator accepts the user pool ID as app client ID
+        .step(Step::call("sign in", "AWSCognitoIdentityProviderService.InitiateAuth", json!({
+            "ClientId": "${user_pool}", "AuthFlow": "USER_PASSWORD_AUTH", "AuthParameters": { "USERNAME": "alice", "PASSWORD": "secret" }
+        })).capture("id_token", "/AuthenticationResult/IdToken").capture("access_token", "/AuthenticationResult/AccessToken"))
+        .step(Step::call("identity pool", "AWSCognitoIdentityService.CreateIdentityPool", json!({
+            "IdentityPoolName": "app",
+            "AllowUnauthenticatedIdentities": true,
+            "CognitoIdentityProviders": [{ "ProviderName": "cognito-idp.us-east-1.amazonaws.com/${user_pool}", "ClientId": "${user_pool}" }]
+        })).capture("pool", "/IdentityPoolId"))
+        .step(Step::call("roles", "AWSCognitoIdentityService.SetIdentityPoolRoles", json!({
+            "IdentityPoolId": "${pool}", "Roles": { "authenticated": "${signed_in}", "unauthenticated": "${guest}" }
+        })).expect_status(200))
+        .step(Step::call("identity", "AWSCognitoIdentityService.GetId", json!({ "IdentityPoolId": "${pool}", "Logins": login("${id_token}") }))
+            .capture("identity", "/IdentityId"))
+        .step(Step::call("credentials", "AWSCognitoIdentityService.GetCredentialsForIdentity", json!({ "IdentityId": "${identity}", "Logins": login("${id_token}") }))
+            .expect_status(200)
+            .expect_contains("\"AccessKeyId\":\"ASIA"))
+        .step(Step::call("no token", "AWSCognitoIdentityService.GetCredentialsForIdentity", json!({ "IdentityId": "${identity}" }))
+            .expect_status(400)
+            .expect_json("/__type", "NotAuthorizedException"))
+        .step(Step::call("access token", "AWSCognitoIdentityService.GetId", json!({ "IdentityPoolId": "${pool}", "Logins": login("${access_token}") }))
+            .expect_status(400)
+            .expect_json("/__type", "NotAuthorizedException"))
+        .step(Step::call("other provider", "AWSCognitoIdentityService.GetId", json!({
+            "IdentityPoolId": "${pool}", "Logins": { "cognito-idp.us-east-1.amazonaws.com/us-east-1_other": "${id_token}" }
+        })).expect_status(400).expect_contains("not from a supported provider"))
+        .step(Step::call("guest", "AWSCognitoIdentityService.GetId", json!({ "IdentityPoolId": "${pool}" }))
+            .capture("guest_identity", "/IdentityId"))
+        .step(Step::call("guest credentials", "AWSCognitoIdentityService.GetCredentialsForIdentity", json!({ "IdentityId": "${guest_identity}" }))
+            .expect_status(200)
+            .expect_contains("\"AccessKeyId\":\"ASIA"))
+        .run(&router).await.unwrap();
+
+    // A login keeps its identity
+    let pool = &emulator.storage.list_identity_pools().unwrap()[0];
+    let identity = emulator.storage.get_or_create_identity(&pool.id, Some(&format!("{}:alice", provider))).unwrap();
+    let (pool_id, login) = emulator.storage.get_identity(&identity).unwrap();
+    assert_eq!((pool_id, login), (pool.id.clone(), Some(format!("{}:alice", provider))));
+    assert_eq!(emulator.storage.get_or_create_identity(&pool.id, Some(&format!("{}:alice", provider))).unwrap(), identity);
+}
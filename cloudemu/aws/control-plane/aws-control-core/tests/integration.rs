@@ -89,3 +89,52 @@ async fn test_lambda_invocation() {
     // Should be Not Found or specific Lambda error
     assert!(response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::INTERNAL_SERVER_ERROR);
 }
+
+#[tokio::test]
+async fn test_terraform_fixtures_and_provider_override() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let router = gateway::create_router(emulator);
+
+    let seed = || Request::builder()
+        .uri("/_aws/terraform/fixtures")
+        .method("POST")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(seed()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let fixtures: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(fixtures["AccountAlias"], "cloudemu");
+
+    // Seeding again keeps the same default VPC
+    let response = router.clone().oneshot(seed()).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let again: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(again["DefaultVpcId"], fixtures["DefaultVpcId"]);
+
+    // aws_default_vpc looks the VPC up by the isDefault filter
+    let req = Request::builder()
+        .uri("/")
+        .method("POST")
+        .header("x-amz-target", "AmazonEC2.DescribeVpcs")
+        .header("content-type", "application/x-amz-json-1.1")
+        .body(Body::from(json!({"Action": "DescribeVpcs", "Filters": [{"Name": "isDefault", "Values": ["true"]}]}).to_string()))
+        .unwrap();
+    let response = router.clone().oneshot(req).await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let vpcs: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(vpcs["Vpcs"].as_array().unwrap().len(), 1);
+    assert_eq!(vpcs["Vpcs"][0]["id"], fixtures["DefaultVpcId"]);
+
+    let req = Request::builder()
+        .uri("/_aws/terraform/provider.tf")
+        .header("host", "localhost:4566")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(req).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let tf = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(tf.contains("skip_requesting_account_id  = true"));
+    assert!(tf.contains("s3 ") && tf.contains("= \"http://localhost:4566\""));
+}
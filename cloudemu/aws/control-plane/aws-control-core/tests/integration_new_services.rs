@@ -149,3 +149,85 @@ async fn test_ecr_workflow() {
     assert_eq!(repos.len(), 1);
     assert_eq!(repos[0]["repositoryName"], "my-repo");
 }
+
+#[tokio::test]
+async fn test_logs_subscription_filter_workflow() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let router = gateway::create_router(emulator.clone());
+
+    let logs = |action: &str, body: Value| {
+        Request::builder()
+            .uri("/")
+            .method("POST")
+            .header("x-amz-target", format!("Logs_20140530.{}", action))
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    };
+
+    router.clone().oneshot(logs("CreateLogGroup", json!({ "logGroupName": "app" }))).await.unwrap();
+    router.clone().oneshot(logs("CreateLogStream", json!({ "logGroupName": "app", "logStreamName": "web-1" }))).await.unwrap();
+
+    let stream_arn = "arn:aws:kinesis:us-east-1:000000000000:stream/log-sink";
+    let subscribe = || logs("PutSubscriptionFilter", json!({
+        "logGroupName": "app",
+        "filterName": "errors",
+        "filterPattern": "{ $.level = \"error\" }",
+        "destinationArn": stream_arn
+    }));
+
+    // The destination stream must exist
+    let response = router.clone().oneshot(subscribe()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    emulator.storage.create_kinesis_stream("log-sink", "000000000000", "us-east-1").unwrap();
+    let response = router.clone().oneshot(subscribe()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Invalid patterns are rejected
+    let response = router.clone().oneshot(logs("PutSubscriptionFilter", json!({
+        "logGroupName": "app",
+        "filterName": "broken",
+        "filterPattern": "{ $.level = }",
+        "destinationArn": stream_arn
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = router.clone().oneshot(logs("DescribeSubscriptionFilters", json!({ "logGroupName": "app" }))).await.unwrap();
+    let json = get_body_as_json(response).await;
+    assert_eq!(json["subscriptionFilters"].as_array().unwrap().len(), 1);
+
+    let response = router.clone().oneshot(logs("PutLogEvents", json!({
+        "logGroupName": "app",
+        "logStreamName": "web-1",
+        "logEvents": [
+            { "timestamp": 1000, "message": "{\"level\": \"info\", \"msg\": \"ok\"}" },
+            { "timestamp": 2000, "message": "{\"level\": \"error\", \"msg\": \"boom\"}" }
+        ]
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Only the matching event reaches the stream, as gzipped CloudWatch Logs JSON
+    let records = emulator.storage.get_kinesis_records("log-sink", 0, 10).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].partition_key, "app/web-1");
+    let mut decoded = String::new();
+    std::io::Read::read_to_string(&mut flate2::read::GzDecoder::new(&records[0].data[..]), &mut decoded).unwrap();
+    let payload: Value = serde_json::from_str(&decoded).unwrap();
+    assert_eq!(payload["logGroup"], "app");
+    assert_eq!(payload["subscriptionFilters"][0], "errors");
+    assert_eq!(payload["logEvents"].as_array().unwrap().len(), 1);
+    assert_eq!(payload["logEvents"][0]["timestamp"], 2000);
+
+    // A failed delivery does not fail the call whose events were already stored
+    emulator.storage.delete_kinesis_stream("log-sink").unwrap();
+    let response = router.clone().oneshot(logs("PutLogEvents", json!({
+        "logGroupName": "app",
+        "logStreamName": "web-1",
+        "logEvents": [{ "timestamp": 3000, "message": "{\"level\": \"error\", \"msg\": \"again\"}" }]
+    }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = router.clone().oneshot(logs("GetLogEvents", json!({ "logGroupName": "app", "logStreamName": "web-1" }))).await.unwrap();
+    assert_eq!(get_body_as_json(response).await["events"].as_array().unwrap().len(), 3);
+
+    let response = router.clone().oneshot(logs("DeleteSubscriptionFilter", json!({ "logGroupName": "app", "filterName": "errors" }))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
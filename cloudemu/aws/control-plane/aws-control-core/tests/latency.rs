@@ -0,0 +1,48 @@
+use aws_control_core::scenario::{Scenario, Step};
+use aws_control_core::{Emulator, gateway};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_latency_profile_delays_matching_calls() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let router = gateway::create_router(emulator.clone());
+
+    Scenario::new("configure")
+        .step(Step::request("set profile", "PUT", "/_aws/latency")
+            .header("content-type", "application/json")
+            .body(json!({ "services": { "dynamodb": { "operations": { "ListTables": { "p50_ms": 150, "p99_ms": 150 } } } } }))
+            .expect_status(200)
+            .expect_json("/services/dynamodb/operations/ListTables/p50_ms", 150.0))
+        .step(Step::request("unknown preset", "PUT", "/_aws/latency")
+            .header("content-type", "application/json")
+            .body(json!({ "preset": "instant" }))
+            .expect_status(400))
+        .step(Step::request("inverted percentiles", "PUT", "/_aws/latency")
+            .header("content-type", "application/json")
+            .body(json!({ "default": { "p50_ms": 50, "p99_ms": 10 } }))
+            .expect_status(400))
+        .run(&router).await.unwrap();
+
+    let started = Instant::now();
+    Scenario::new("slow")
+        .step(Step::call("list", "DynamoDB_20120810.ListTables", json!({})).expect_status(200))
+        .run(&router).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(150));
+
+    let started = Instant::now();
+    Scenario::new("fast")
+        .step(Step::call("describe", "DynamoDB_20120810.DescribeLimits", json!({})))
+        .step(Step::request("list buckets", "GET", "/").expect_status(200))
+        .run(&router).await.unwrap();
+    assert!(started.elapsed() < Duration::from_millis(150));
+
+    Scenario::new("off")
+        .step(Step::request("preset", "PUT", "/_aws/latency")
+            .header("content-type", "application/json")
+            .body(json!({ "preset": "off" }))
+            .expect_status(200))
+        .run(&router).await.unwrap();
+    assert!(emulator.latency.sample("dynamodb", "ListTables").is_none());
+}
@@ -0,0 +1,50 @@
+use aws_control_core::scenario::{Scenario, Step};
+use aws_control_core::{Emulator, gateway};
+use base64::Engine as _;
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_lowered_limits_return_service_errors() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    let router = gateway::create_router(emulator.clone());
+
+    let code = base64::engine::general_purpose::STANDARD.encode(vec![0u8; 2048]);
+    Scenario::new("limits")
+        .step(Step::request("lower limits", "PUT", "/_aws/limits")
+            .header("content-type", "application/json")
+            .body(json!({ "s3_max_buckets": 1, "sqs_max_message_bytes": 16, "dynamodb_max_item_bytes": 32, "lambda_max_code_zip_bytes": 1024 }))
+            .expect_status(200)
+            .expect_json("/s3_max_buckets", 1))
+        .step(Step::request("unknown limit", "PUT", "/_aws/limits")
+            .header("content-type", "application/json")
+            .body(json!({ "s3_bucket_count": 5 }))
+            .expect_status(400))
+        .step(Step::request("first bucket", "PUT", "/first").expect_status(200))
+        .step(Step::request("second bucket", "PUT", "/second").expect_status(400).expect_contains("<Code>TooManyBuckets</Code>"))
+        .step(Step::call("queue", "AmazonSQS.CreateQueue", json!({ "QueueName": "q" })))
+        .step(Step::call("small message", "AmazonSQS.SendMessage", json!({ "QueueUrl": "q", "MessageBody": "hi" })).expect_status(200))
+        .step(Step::call("large message", "AmazonSQS.SendMessage", json!({ "QueueUrl": "q", "MessageBody": "x".repeat(17) }))
+            .expect_status(400)
+            .expect_json("/__type", "InvalidParameterValue"))
+        .step(Step::call("table", "DynamoDB_20120810.CreateTable", json!({
+            "TableName": "t",
+            "AttributeDefinitions": [{ "AttributeName": "id", "AttributeType": "S" }],
+            "KeySchema": [{ "AttributeName": "id", "KeyType": "HASH" }]
+        })))
+        .step(Step::call("large item", "DynamoDB_20120810.PutItem", json!({ "TableName": "t", "Item": { "id": { "S": "1" }, "bio": { "S": "y".repeat(40) } } }))
+            .expect_status(400)
+            .expect_json("/__type", "ValidationException"))
+        .step(Step::request("large function", "POST", "/2015-03-31/functions")
+            .header("content-type", "application/json")
+            .body(json!({ "FunctionName": "f", "Runtime": "python3.12", "Role": "arn:aws:iam::000000000000:role/r", "Handler": "h", "Code": { "ZipFile": code } }))
+            .expect_status(413)
+            .expect_contains("maximum of 1024 bytes"))
+        .run(&router).await.unwrap();
+
+    // Restoring the default lets the bucket through
+    emulator.limits.set(Default::default());
+    Scenario::new("defaults")
+        .step(Step::request("second bucket", "PUT", "/second").expect_status(200))
+        .run(&router).await.unwrap();
+}
@@ -0,0 +1,43 @@
+use aws_control_core::scenario::{Scenario, ScenarioError, Step};
+use aws_control_core::{Emulator, gateway};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn test_yaml_scenario_across_services() {
+    let router = gateway::create_router(Arc::new(Emulator::in_memory().unwrap()));
+
+    let scenario = Scenario::from_yaml(include_str!("scenarios/upload_pipeline.yaml")).unwrap();
+    let vars = scenario.run(&router).await.unwrap();
+    assert!(vars["queue_url"].ends_with("/images"));
+    assert!(!vars["receipt"].is_empty());
+}
+
+#[tokio::test]
+async fn test_builder_scenario_reports_failing_step() {
+    let router = gateway::create_router(Arc::new(Emulator::in_memory().unwrap()));
+
+    let scenario = Scenario::new("queue round trip")
+        .step(Step::call("create queue", "AmazonSQS.CreateQueue", json!({ "QueueName": "jobs" }))
+            .expect_status(200)
+            .capture("queue", "/QueueUrl"))
+        .step(Step::call("send", "AmazonSQS.SendMessage", json!({ "QueueUrl": "${queue}", "MessageBody": "hello" }))
+            .expect_status(200))
+        .step(Step::call("receive", "AmazonSQS.ReceiveMessage", json!({ "QueueUrl": "${queue}" }))
+            .expect_json("/Messages/0/Body", "hello"));
+    scenario.run(&router).await.unwrap();
+
+    // A step that never holds fails with its name once the timeout passes
+    let scenario = Scenario::new("missing bucket")
+        .step(Step::request("get object", "GET", "/nowhere/key.txt").expect_status(200).within(Duration::from_millis(200)));
+    match scenario.run(&router).await {
+        Err(ScenarioError::Step { step, reason }) => {
+            assert_eq!(step, "get object");
+            assert!(reason.contains("expected status 200, got 404"), "{}", reason);
+        }
+        other => panic!("expected a failed step, got {:?}", other),
+    }
+
+    assert!(matches!(Scenario::from_yaml("steps: 3"), Err(ScenarioError::Parse(_))));
+}
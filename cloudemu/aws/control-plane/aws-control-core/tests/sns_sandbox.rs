@@ -0,0 +1,61 @@
+use aws_control_core::scenario::{Scenario, Step};
+use aws_control_core::{Emulator, gateway};
+use serde_json::json;
+use std::sync::Arc;
+
+fn sns(name: &str, action: &str, mut body: serde_json::Value) -> Step {
+    body["Action"] = json!(action);
+    Step::call(name, format!("AmazonSNS.{}", action), body)
+}
+
+#[tokio::test]
+async fn test_sms_and_push_are_recorded_in_the_sandbox() {
+    let router = gateway::create_router(Arc::new(Emulator::in_memory().unwrap()));
+
+    Scenario::new("sns sandbox")
+        .step(sns("direct sms", "Publish", json!({ "PhoneNumber": "+15555550100", "Message": "Your code is 1234" }))
+            .expect_status(200))
+        .step(sns("invalid number", "Publish", json!({ "PhoneNumber": "555-0100", "Message": "hi" }))
+            .expect_status(400)
+            .expect_json("/Error/Code", "InvalidParameter"))
+        .step(sns("two targets", "Publish", json!({ "PhoneNumber": "+15555550100", "TopicArn": "arn:aws:sns:us-east-1:000000000000:alerts", "Message": "hi" }))
+            .expect_status(400))
+        .step(sns("topic", "CreateTopic", json!({ "Name": "alerts" }))
+            .capture("topic", "/CreateTopicResponse/CreateTopicResult/TopicArn"))
+        .step(sns("sms subscription", "Subscribe", json!({ "TopicArn": "${topic}", "Protocol": "sms", "Endpoint": "+447700900123" }))
+            .expect_status(200))
+        .step(sns("app", "CreatePlatformApplication", json!({ "Name": "mobile", "Platform": "GCM", "Attributes": { "PlatformCredential": "key" } }))
+            .capture("app", "/CreatePlatformApplicationResponse/CreatePlatformApplicationResult/PlatformApplicationArn"))
+        .step(sns("endpoint", "CreatePlatformEndpoint", json!({ "PlatformApplicationArn": "${app}", "Token": "device-1", "CustomUserData": "alice" }))
+            .capture("endpoint", "/CreatePlatformEndpointResponse/CreatePlatformEndpointResult/EndpointArn"))
+        .step(sns("push subscription", "Subscribe", json!({ "TopicArn": "${topic}", "Protocol": "application", "Endpoint": "${endpoint}" }))
+            .expect_status(200))
+        .step(sns("topic publish", "Publish", json!({
+            "TopicArn": "${topic}",
+            "MessageStructure": "json",
+            "Message": json!({ "default": "Disk full", "GCM": "{\"notification\":{\"title\":\"Disk full\"}}" }).to_string()
+        })).expect_status(200))
+        .step(Step::request("sms to the phone", "GET", "/_aws/sns/sandbox?kind=sms&destination=%2B447700900123")
+            .expect_json("/Messages/0/message", "Disk full"))
+        .step(Step::request("push to the device", "GET", "/_aws/sns/sandbox?kind=push")
+            .expect_json("/Messages/0/platform", "GCM")
+            .expect_json("/Messages/0/message", "{\"notification\":{\"title\":\"Disk full\"}}"))
+        .step(sns("no default", "Publish", json!({ "TargetArn": "${endpoint}", "MessageStructure": "json", "Message": "{\"GCM\":\"x\"}" }))
+            .expect_status(400))
+        .step(sns("disable", "SetEndpointAttributes", json!({ "EndpointArn": "${endpoint}", "Attributes": { "Enabled": "false" } }))
+            .expect_status(200))
+        .step(sns("endpoint attributes", "GetEndpointAttributes", json!({ "EndpointArn": "${endpoint}" }))
+            .expect_json("/GetEndpointAttributesResponse/GetEndpointAttributesResult/Attributes/Enabled", "false")
+            .expect_json("/GetEndpointAttributesResponse/GetEndpointAttributesResult/Attributes/CustomUserData", "alice"))
+        .step(sns("disabled endpoint", "Publish", json!({ "TargetArn": "${endpoint}", "Message": "ping" }))
+            .expect_status(400)
+            .expect_json("/Error/Code", "EndpointDisabled"))
+        .step(Step::request("everything sent", "GET", "/_aws/sns/sandbox")
+            .expect_json("/Messages/0/destination", "+15555550100")
+            .expect_json("/Messages/0/message", "Your code is 1234"))
+        .step(Step::request("clear", "DELETE", "/_aws/sns/sandbox")
+            .expect_json("/Cleared", 3))
+        .step(Step::request("cleared", "GET", "/_aws/sns/sandbox")
+            .expect_json("/Messages", json!([])))
+        .run(&router).await.unwrap();
+}
@@ -0,0 +1,86 @@
+use aws_control_core::scenario::{Scenario, Step};
+use aws_control_core::{Config, Emulator, gateway};
+use serde_json::json;
+use std::sync::Arc;
+
+fn sfn(name: &str, action: &str, body: serde_json::Value) -> Step {
+    Step::call(name, format!("AWSStepFunctions.{}", action), body)
+}
+
+#[tokio::test]
+async fn test_state_runs_one_state_with_a_mocked_result() {
+    let router = gateway::create_router(Arc::new(Emulator::in_memory().unwrap()));
+    let task = json!({
+        "Type": "Task",
+        "Resource": "arn:aws:states:::lambda:invoke",
+        "ResultSelector": { "total": 42 },
+        "ResultPath": "$.invoice",
+        "Catch": [{ "ErrorEquals": ["Invoice.Missing"], "Next": "Notify" }],
+        "Next": "Send"
+    }).to_string();
+
+    Scenario::new("test state")
+        .step(sfn("mocked result", "TestState", json!({
+            "definition": task,
+            "input": "{\"order\": 7}",
+            "inspectionLevel": "DEBUG",
+            "mock": { "result": "{\"Payload\": {}}" }
+        }))
+            .expect_status(200)
+            .expect_json("/status", "SUCCEEDED")
+            .expect_json("/nextState", "Send")
+            .expect_json("/output", "{\"invoice\":{\"total\":42},\"order\":7}")
+            .expect_json("/inspectionData/result", "{\"Payload\":{}}"))
+        .step(sfn("mocked error", "TestState", json!({
+            "definition": task,
+            "mock": { "errorOutput": { "error": "Invoice.Missing", "cause": "no invoice" } }
+        }))
+            .expect_json("/status", "CAUGHT_ERROR")
+            .expect_json("/nextState", "Notify")
+            .expect_json("/error", "Invoice.Missing"))
+        .step(sfn("state of a machine", "TestState", json!({
+            "definition": json!({ "StartAt": "Fail", "States": { "Fail": { "Type": "Fail", "Error": "Bad", "Cause": "input" } } }).to_string(),
+            "stateName": "Fail"
+        }))
+            .expect_json("/status", "FAILED")
+            .expect_json("/cause", "input"))
+        .step(sfn("invalid definition", "TestState", json!({ "definition": "{" }))
+            .expect_status(400)
+            .expect_json("/__type", "InvalidDefinition"))
+        .run(&router).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_executions_run_test_cases_of_the_mock_config() {
+    let dir = std::env::temp_dir().join(format!("cloudemu-sfn-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mocks = dir.join("MockConfigFile.json");
+    std::fs::write(&mocks, json!({
+        "StateMachines": { "orders": { "TestCases": { "Declined": { "Charge": "Declined" } } } },
+        "MockedResponses": { "Declined": { "0": { "Throw": { "Error": "Payment.Declined", "Cause": "Card expired" } } } }
+    }).to_string()).unwrap();
+    let config = Config::default().data_dir(dir.join("data")).sfn_mock_config(Some(mocks));
+    let router = gateway::create_router(Arc::new(Emulator::with_config(config).unwrap()));
+    let definition = json!({
+        "StartAt": "Charge",
+        "States": { "Charge": { "Type": "Task", "Resource": "arn:aws:lambda:us-east-1:000000000000:function:charge", "End": true } }
+    }).to_string();
+
+    Scenario::new("mocked execution")
+        .step(sfn("create", "CreateStateMachine", json!({ "name": "orders", "definition": definition, "roleArn": "arn:aws:iam::000000000000:role/sfn" }))
+            .capture("machine", "/stateMachineArn"))
+        .step(sfn("real", "StartExecution", json!({ "stateMachineArn": "${machine}", "input": "{\"id\": 1}" }))
+            .capture("real", "/executionArn"))
+        .step(sfn("describe real", "DescribeExecution", json!({ "executionArn": "${real}" }))
+            .expect_json("/status", "SUCCEEDED"))
+        .step(sfn("declined", "StartExecution", json!({ "stateMachineArn": "${machine}#Declined", "input": "{\"id\": 1}" }))
+            .capture("declined", "/executionArn"))
+        .step(sfn("describe declined", "DescribeExecution", json!({ "executionArn": "${declined}" }))
+            .expect_json("/status", "FAILED")
+            .expect_contains("Payment.Declined"))
+        .step(sfn("unknown test case", "StartExecution", json!({ "stateMachineArn": "${machine}#Approved" }))
+            .expect_status(400))
+        .run(&router).await.unwrap();
+
+    std::fs::remove_dir_all(&dir).ok();
+}
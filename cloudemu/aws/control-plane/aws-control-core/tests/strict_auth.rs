@@ -0,0 +1,220 @@
+use aws_control_core::gateway::{self, sigv4};
+use aws_control_core::Emulator;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    Router,
+};
+use tower::ServiceExt; // for `oneshot`
+use std::sync::Arc;
+
+fn strict_router() -> (Arc<Emulator>, Router) {
+    let mut emulator = Emulator::in_memory().unwrap();
+    emulator.config.strict_auth = true;
+    let emulator = Arc::new(emulator);
+    (emulator.clone(), gateway::create_router(emulator))
+}
+
+/// Sign a request the way the AWS SDKs do
+fn signed(
+    method: &str,
+    uri: &str,
+    service: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+    (access_key_id, secret): (&str, &str),
+    signed_at: chrono::DateTime<chrono::Utc>,
+) -> Request<Body> {
+    let amz_date = signed_at.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("host", "localhost:4566")
+        .header("x-amz-date", &amz_date);
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let mut request = builder.body(Body::from(body.to_vec())).unwrap();
+
+    let mut signed_headers: Vec<String> = request.headers().keys().map(|name| name.as_str().to_string()).collect();
+    signed_headers.sort();
+    let mut auth = sigv4::Authorization {
+        access_key_id: access_key_id.to_string(),
+        date: amz_date[..8].to_string(),
+        region: "us-east-1".to_string(),
+        service: service.to_string(),
+        signed_headers,
+        signature: String::new(),
+    };
+    let payload_hash = request.headers().get("x-amz-content-sha256")
+        .map(|hash| hash.to_str().unwrap().to_string())
+        .unwrap_or_else(|| sigv4::sha256_hex(body));
+    let canonical = sigv4::canonical_request(method, request.uri().path(), request.uri().query(), request.headers(), &auth, &payload_hash);
+    auth.signature = sigv4::signature(secret, &auth, &sigv4::string_to_sign(&amz_date, &auth, &canonical));
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        sigv4::ALGORITHM, auth.access_key_id, auth.scope(), auth.signed_headers.join(";"), auth.signature
+    );
+    request.headers_mut().insert("authorization", authorization.parse().unwrap());
+    request
+}
+
+/// Presign a URL the way the AWS SDKs do, signing only the host
+fn presigned(
+    method: &str,
+    path: &str,
+    (access_key_id, secret): (&str, &str),
+    signed_at: chrono::DateTime<chrono::Utc>,
+    expires_secs: i64,
+) -> Request<Body> {
+    let amz_date = signed_at.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut auth = sigv4::Authorization {
+        access_key_id: access_key_id.to_string(),
+        date: amz_date[..8].to_string(),
+        region: "us-east-1".to_string(),
+        service: "s3".to_string(),
+        signed_headers: vec!["host".to_string()],
+        signature: String::new(),
+    };
+    let query = format!(
+        "X-Amz-Algorithm={}&X-Amz-Credential={}&X-Amz-Date={}&X-Amz-Expires={}&X-Amz-SignedHeaders=host",
+        sigv4::ALGORITHM, sigv4::uri_encode(&format!("{}/{}", access_key_id, auth.scope()), true), amz_date, expires_secs
+    );
+    let mut headers = axum::http::HeaderMap::new();
+    headers.insert("host", "localhost:4566".parse().unwrap());
+    let canonical = sigv4::canonical_request(method, path, Some(&query), &headers, &auth, sigv4::UNSIGNED_PAYLOAD);
+    auth.signature = sigv4::signature(secret, &auth, &sigv4::string_to_sign(&amz_date, &auth, &canonical));
+
+    Request::builder()
+        .method(method)
+        .uri(format!("{}?{}&X-Amz-Signature={}", path, query, auth.signature))
+        .header("host", "localhost:4566")
+        .body(Body::empty())
+        .unwrap()
+}
+
+async fn error_code(router: &Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8_lossy(&body);
+    let code = body.split("<Code>").nth(1).and_then(|rest| rest.split("</Code>").next()).unwrap_or_default();
+    (status, code.to_string())
+}
+
+#[tokio::test]
+async fn test_strict_auth_accepts_valid_signatures() {
+    let (emulator, router) = strict_router();
+    let now = chrono::Utc::now();
+    let empty_hash = sigv4::sha256_hex(b"");
+
+    // S3 declares its payload hash
+    let list = signed("GET", "/?max-buckets=10", "s3", &[("x-amz-content-sha256", &empty_hash)], b"", ("test", "test"), now);
+    assert_eq!(router.clone().oneshot(list).await.unwrap().status(), StatusCode::OK);
+
+    let put = signed("PUT", "/photos", "s3", &[("x-amz-content-sha256", "UNSIGNED-PAYLOAD")], b"", ("test", "test"), now);
+    assert_eq!(router.clone().oneshot(put).await.unwrap().status(), StatusCode::OK);
+
+    // JSON protocol bodies are hashed by the gateway
+    let body = br#"{"TableName": "orders", "KeySchema": [{"AttributeName": "pk", "KeyType": "HASH"}], "AttributeDefinitions": [{"AttributeName": "pk", "AttributeType": "S"}], "BillingMode": "PAY_PER_REQUEST"}"#;
+    let create = signed("POST", "/", "dynamodb", &[
+        ("x-amz-target", "DynamoDB_20120810.CreateTable"),
+        ("content-type", "application/x-amz-json-1.0"),
+    ], body, ("test", "test"), now);
+    assert_eq!(router.clone().oneshot(create).await.unwrap().status(), StatusCode::OK);
+
+    // Access keys of IAM users are accepted too
+    let key = emulator.storage.create_access_key("ci").unwrap();
+    let list = signed("GET", "/", "s3", &[("x-amz-content-sha256", &empty_hash)], b"", (&key.access_key_id, &key.secret_access_key), now);
+    assert_eq!(router.clone().oneshot(list).await.unwrap().status(), StatusCode::OK);
+
+    let health = Request::builder().uri("/health").body(Body::empty()).unwrap();
+    assert_eq!(router.clone().oneshot(health).await.unwrap().status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_strict_auth_rejects_bad_requests() {
+    let (_, router) = strict_router();
+    let now = chrono::Utc::now();
+    let empty_hash = sigv4::sha256_hex(b"");
+    let s3_headers = [("x-amz-content-sha256", empty_hash.as_str())];
+
+    let unsigned = Request::builder().uri("/").body(Body::empty()).unwrap();
+    assert_eq!(error_code(&router, unsigned).await, (StatusCode::FORBIDDEN, "AccessDenied".to_string()));
+
+    let wrong_secret = signed("GET", "/", "s3", &s3_headers, b"", ("test", "wrong"), now);
+    assert_eq!(error_code(&router, wrong_secret).await, (StatusCode::FORBIDDEN, "SignatureDoesNotMatch".to_string()));
+
+    let unknown_key = signed("GET", "/", "s3", &s3_headers, b"", ("AKIAUNKNOWN", "test"), now);
+    assert_eq!(error_code(&router, unknown_key).await, (StatusCode::FORBIDDEN, "InvalidAccessKeyId".to_string()));
+
+    let stale = signed("GET", "/", "s3", &s3_headers, b"", ("test", "test"), now - chrono::Duration::hours(1));
+    assert_eq!(error_code(&router, stale).await, (StatusCode::FORBIDDEN, "RequestTimeTooSkewed".to_string()));
+
+    // A body changed after signing no longer matches its hash
+    let mut tampered = signed("POST", "/", "dynamodb", &[("x-amz-target", "DynamoDB_20120810.ListTables")], b"{}", ("test", "test"), now);
+    *tampered.body_mut() = Body::from(r#"{"Limit": 1}"#);
+    assert_eq!(error_code(&router, tampered).await, (StatusCode::FORBIDDEN, "SignatureDoesNotMatch".to_string()));
+
+    let mut malformed = signed("GET", "/", "s3", &s3_headers, b"", ("test", "test"), now);
+    malformed.headers_mut().insert("authorization", "AWS test:signature".parse().unwrap());
+    assert_eq!(error_code(&router, malformed).await, (StatusCode::BAD_REQUEST, "AuthorizationHeaderMalformed".to_string()));
+}
+
+#[tokio::test]
+async fn test_strict_auth_presigned_urls() {
+    let (_, router) = strict_router();
+    let now = chrono::Utc::now();
+    let empty_hash = sigv4::sha256_hex(b"");
+    let create = signed("PUT", "/photos", "s3", &[("x-amz-content-sha256", &empty_hash)], b"", ("test", "test"), now);
+    assert_eq!(router.clone().oneshot(create).await.unwrap().status(), StatusCode::OK);
+
+    // Uploads through a presigned PUT leave the body unsigned
+    let mut upload = presigned("PUT", "/photos/cat.jpg", ("test", "test"), now, 300);
+    *upload.body_mut() = Body::from("meow");
+    assert_eq!(router.clone().oneshot(upload).await.unwrap().status(), StatusCode::OK);
+
+    let download = presigned("GET", "/photos/cat.jpg", ("test", "test"), now, 300);
+    let response = router.clone().oneshot(download).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(&axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap()[..], b"meow");
+
+    // URLs signed long ago stay valid until they expire
+    let week_old = presigned("GET", "/photos/cat.jpg", ("test", "test"), now - chrono::Duration::days(6), 7 * 24 * 60 * 60);
+    assert_eq!(router.clone().oneshot(week_old).await.unwrap().status(), StatusCode::OK);
+
+    let expired = presigned("GET", "/photos/cat.jpg", ("test", "test"), now - chrono::Duration::minutes(10), 300);
+    assert_eq!(error_code(&router, expired).await, (StatusCode::FORBIDDEN, "AccessDenied".to_string()));
+
+    let wrong_secret = presigned("GET", "/photos/cat.jpg", ("test", "wrong"), now, 300);
+    assert_eq!(error_code(&router, wrong_secret).await, (StatusCode::FORBIDDEN, "SignatureDoesNotMatch".to_string()));
+
+    // The signature covers the path and query
+    let mut other_key = presigned("GET", "/photos/cat.jpg", ("test", "test"), now, 300);
+    *other_key.uri_mut() = other_key.uri().to_string().replace("cat.jpg", "dog.jpg").parse().unwrap();
+    assert_eq!(error_code(&router, other_key).await, (StatusCode::FORBIDDEN, "SignatureDoesNotMatch".to_string()));
+
+    let mut extended = presigned("GET", "/photos/cat.jpg", ("test", "test"), now, 300);
+    *extended.uri_mut() = extended.uri().to_string().replace("X-Amz-Expires=300", "X-Amz-Expires=3000").parse().unwrap();
+    assert_eq!(error_code(&router, extended).await, (StatusCode::FORBIDDEN, "SignatureDoesNotMatch".to_string()));
+
+    let too_long = presigned("GET", "/photos/cat.jpg", ("test", "test"), now, 8 * 24 * 60 * 60);
+    assert_eq!(error_code(&router, too_long).await, (StatusCode::BAD_REQUEST, "AuthorizationQueryParametersError".to_string()));
+}
+
+#[tokio::test]
+async fn test_presigned_urls_expire_without_strict_auth() {
+    let emulator = Arc::new(Emulator::in_memory().unwrap());
+    emulator.storage.create_bucket("photos", "us-east-1").unwrap();
+    emulator.storage.put_object("photos", "cat.jpg", b"meow", None, None).unwrap();
+    let router = gateway::create_router(emulator);
+    let now = chrono::Utc::now();
+
+    // Any credentials are accepted, but not an expired URL
+    let download = presigned("GET", "/photos/cat.jpg", ("anyone", "anything"), now, 60);
+    assert_eq!(router.clone().oneshot(download).await.unwrap().status(), StatusCode::OK);
+
+    let expired = presigned("GET", "/photos/cat.jpg", ("anyone", "anything"), now - chrono::Duration::minutes(2), 60);
+    assert_eq!(error_code(&router, expired).await, (StatusCode::FORBIDDEN, "AccessDenied".to_string()));
+}
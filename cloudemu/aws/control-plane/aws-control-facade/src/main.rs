@@ -1,7 +1,7 @@
 use clap::Parser;
 use std::path::PathBuf;
 use tracing::info;
-use aws_control_facade::gateway;
+use aws_control_facade::{aws_control_core::Config as EmulatorConfig, gateway};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -17,6 +17,42 @@ struct Config {
     /// Host
     #[arg(long, default_value = "0.0.0.0", env = "CLOUDEMU_HOST")]
     host: String,
+
+    /// Terraform acceptance-test fixture mode (default VPC, account alias, provider override file)
+    #[arg(long, env = "CLOUDEMU_TERRAFORM_MODE")]
+    terraform: bool,
+
+    /// YAML or JSON spec of generated items, objects and messages to load at startup
+    #[arg(long, env = "CLOUDEMU_SEED_FILE")]
+    seed_file: Option<PathBuf>,
+
+    /// Latency injected into API calls: off, realistic or the path to a YAML profile
+    #[arg(long, env = "CLOUDEMU_LATENCY_PROFILE")]
+    latency_profile: Option<String>,
+
+    /// Bucket SQS offloads oversized messages to, as the SQS Extended Client does
+    #[arg(long, env = "CLOUDEMU_SQS_PAYLOAD_BUCKET")]
+    sqs_payload_bucket: Option<String>,
+
+    /// Enable the DAX endpoint's cache with this item and query TTL in milliseconds
+    #[arg(long, env = "CLOUDEMU_DAX_TTL_MS")]
+    dax_ttl_ms: Option<u64>,
+
+    /// Step Functions Local mock config stubbing Task responses per test case
+    #[arg(long, env = "CLOUDEMU_SFN_MOCK_CONFIG")]
+    sfn_mock_config: Option<PathBuf>,
+
+    /// Seconds that count as one day for S3 lifecycle rules
+    #[arg(long, env = "CLOUDEMU_S3_LIFECYCLE_DAY_SECS")]
+    s3_lifecycle_day_secs: Option<u64>,
+
+    /// Reject requests without a valid Signature V4
+    #[arg(long, env = "CLOUDEMU_STRICT_AUTH")]
+    strict_auth: bool,
+
+    /// Comma-separated ACCESS_KEY_ID:SECRET pairs accepted in strict auth mode (default test:test)
+    #[arg(long, env = "CLOUDEMU_CREDENTIALS")]
+    credentials: Option<String>,
 }
 
 #[tokio::main]
@@ -27,7 +63,26 @@ async fn main() -> anyhow::Result<()> {
     info!("Starting CloudEmu AWS Server on {}:{}", config.host, config.port);
     info!("Data Directory: {:?}", config.data_dir);
 
-    gateway::ingress::start(&config.host, config.port, config.data_dir).await?;
+    let emulator_config = EmulatorConfig::default()
+        .host(config.host)
+        .port(config.port)
+        .data_dir(config.data_dir)
+        .terraform_mode(config.terraform)
+        .seed_file(config.seed_file)
+        .latency_profile(config.latency_profile)
+        .sqs_payload_bucket(config.sqs_payload_bucket)
+        .dax_ttl_ms(config.dax_ttl_ms)
+        .sfn_mock_config(config.sfn_mock_config)
+        .s3_lifecycle_day_secs(config.s3_lifecycle_day_secs)
+        .strict_auth(config.strict_auth);
+    let emulator_config = match config.credentials {
+        Some(list) => emulator_config.credentials(
+            EmulatorConfig::parse_credentials(&list)
+                .ok_or_else(|| anyhow::anyhow!("--credentials takes ACCESS_KEY_ID:SECRET pairs"))?,
+        ),
+        None => emulator_config,
+    };
+    gateway::ingress::start_with_config(emulator_config).await?;
     
     Ok(())
 }
@@ -17,8 +17,29 @@ pub struct Config {
     pub account_id: String,
     /// Enable request logging
     pub enable_logging: bool,
-    /// Enable AWS Signature V4 validation
-    pub validate_signatures: bool,
+    /// Strict auth mode: API requests must carry a valid AWS Signature V4 made with one of
+    /// `credentials` or an IAM access key of the emulated account
+    pub strict_auth: bool,
+    /// Access key IDs and secrets accepted in strict auth mode
+    pub credentials: Vec<(String, String)>,
+    /// Terraform acceptance-test fixture mode: seed the default VPC and account alias at startup
+    pub terraform_mode: bool,
+    /// YAML or JSON data spec generated into tables, buckets and queues at startup
+    pub seed_file: Option<PathBuf>,
+    /// Latency injected into API calls: `off`, `realistic` or the path to a profile file
+    pub latency_profile: Option<String>,
+    /// Bucket SQS offloads messages over the queue's size limit to, in the format of the
+    /// SQS Extended Client; oversized messages are rejected when unset
+    pub sqs_payload_bucket: Option<String>,
+    /// TTL in milliseconds of the DAX endpoint's item and query caches; caching starts
+    /// disabled when unset
+    pub dax_ttl_ms: Option<u64>,
+    /// Step Functions Local mock config file, whose test cases stub the responses of Task
+    /// states for executions started with `<stateMachineArn>#<TestCase>`
+    pub sfn_mock_config: Option<PathBuf>,
+    /// Seconds that count as one day for S3 lifecycle rules, so that expiry and transitions
+    /// can be observed quickly; a real day when unset
+    pub s3_lifecycle_day_secs: Option<u64>,
 }
 
 impl Default for Config {
@@ -30,7 +51,15 @@ impl Default for Config {
             region: "us-east-1".to_string(),
             account_id: "000000000000".to_string(),
             enable_logging: true,
-            validate_signatures: false, // Disabled by default for ease of use
+            strict_auth: false, // Disabled by default for ease of use
+            credentials: vec![("test".to_string(), "test".to_string())],
+            terraform_mode: false,
+            seed_file: None,
+            latency_profile: None,
+            sqs_payload_bucket: None,
+            dax_ttl_ms: None,
+            sfn_mock_config: None,
+            s3_lifecycle_day_secs: None,
         }
     }
 }
@@ -60,8 +89,38 @@ impl Config {
         if let Ok(logging) = std::env::var("CLOUDEMU_LOGGING") {
             config.enable_logging = logging == "true" || logging == "1";
         }
-        if let Ok(validate) = std::env::var("CLOUDEMU_VALIDATE_SIGNATURES") {
-            config.validate_signatures = validate == "true" || validate == "1";
+        if let Ok(strict) = std::env::var("CLOUDEMU_STRICT_AUTH") {
+            config.strict_auth = strict == "true" || strict == "1";
+        }
+        if let Ok(credentials) = std::env::var("CLOUDEMU_CREDENTIALS") {
+            if let Some(credentials) = Self::parse_credentials(&credentials) {
+                config.credentials = credentials;
+            }
+        }
+        if let Ok(terraform) = std::env::var("CLOUDEMU_TERRAFORM_MODE") {
+            config.terraform_mode = terraform == "true" || terraform == "1";
+        }
+        if let Ok(seed_file) = std::env::var("CLOUDEMU_SEED_FILE") {
+            config.seed_file = Some(PathBuf::from(seed_file));
+        }
+        if let Ok(profile) = std::env::var("CLOUDEMU_LATENCY_PROFILE") {
+            config.latency_profile = Some(profile);
+        }
+        if let Ok(bucket) = std::env::var("CLOUDEMU_SQS_PAYLOAD_BUCKET") {
+            config.sqs_payload_bucket = Some(bucket);
+        }
+        if let Ok(ttl) = std::env::var("CLOUDEMU_DAX_TTL_MS") {
+            if let Ok(ttl) = ttl.parse() {
+                config.dax_ttl_ms = Some(ttl);
+            }
+        }
+        if let Ok(path) = std::env::var("CLOUDEMU_SFN_MOCK_CONFIG") {
+            config.sfn_mock_config = Some(PathBuf::from(path));
+        }
+        if let Ok(secs) = std::env::var("CLOUDEMU_S3_LIFECYCLE_DAY_SECS") {
+            if let Ok(secs) = secs.parse() {
+                config.s3_lifecycle_day_secs = Some(secs);
+            }
         }
         
         config
@@ -90,4 +149,70 @@ impl Config {
         self.region = region.into();
         self
     }
+
+    /// Builder-style terraform_mode setter
+    pub fn terraform_mode(mut self, enabled: bool) -> Self {
+        self.terraform_mode = enabled;
+        self
+    }
+
+    /// Builder-style seed_file setter
+    pub fn seed_file(mut self, path: Option<PathBuf>) -> Self {
+        self.seed_file = path;
+        self
+    }
+
+    /// Builder-style latency_profile setter
+    pub fn latency_profile(mut self, profile: Option<String>) -> Self {
+        self.latency_profile = profile;
+        self
+    }
+
+    /// Builder-style sqs_payload_bucket setter
+    pub fn sqs_payload_bucket(mut self, bucket: Option<String>) -> Self {
+        self.sqs_payload_bucket = bucket;
+        self
+    }
+
+    /// Builder-style dax_ttl_ms setter
+    pub fn dax_ttl_ms(mut self, ttl_ms: Option<u64>) -> Self {
+        self.dax_ttl_ms = ttl_ms;
+        self
+    }
+
+    /// Builder-style sfn_mock_config setter
+    pub fn sfn_mock_config(mut self, path: Option<PathBuf>) -> Self {
+        self.sfn_mock_config = path;
+        self
+    }
+
+    /// Builder-style s3_lifecycle_day_secs setter
+    pub fn s3_lifecycle_day_secs(mut self, secs: Option<u64>) -> Self {
+        self.s3_lifecycle_day_secs = secs;
+        self
+    }
+
+    /// Builder-style strict_auth setter
+    pub fn strict_auth(mut self, enabled: bool) -> Self {
+        self.strict_auth = enabled;
+        self
+    }
+
+    /// Builder-style credentials setter
+    pub fn credentials(mut self, credentials: Vec<(String, String)>) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    /// Parse comma-separated `ACCESS_KEY_ID:SECRET` pairs; `None` if any pair lacks its secret
+    pub fn parse_credentials(list: &str) -> Option<Vec<(String, String)>> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, secret) = pair.split_once(':')?;
+                Some((key.to_string(), secret.to_string()))
+            })
+            .collect()
+    }
 }
@@ -23,6 +23,9 @@ pub enum EmulatorError {
     #[error("InvalidObjectState")]
     InvalidObjectState(String),
     
+    #[error("NoSuchUpload")]
+    NoSuchUpload(String),
+    
     // Policy Errors
     #[error("NoSuchBucketPolicy")]
     NoSuchBucketPolicy(String),
@@ -30,6 +33,10 @@ pub enum EmulatorError {
     #[error("MalformedPolicy")]
     MalformedPolicy(String),
     
+    // Lifecycle Errors
+    #[error("NoSuchLifecycleConfiguration")]
+    NoSuchLifecycleConfiguration(String),
+    
     // General Errors
     #[error("InvalidRequest")]
     InvalidRequest(String),
@@ -60,6 +67,22 @@ pub enum EmulatorError {
 
     #[error("{0} already exists")]
     AlreadyExists(String),
+
+    /// Credentials or tokens were rejected
+    #[error("NotAuthorizedException")]
+    NotAuthorized(String),
+
+    /// A service quota was exceeded; carries the service's own error code and HTTP status
+    #[error("{code}")]
+    LimitExceeded { code: &'static str, status: u16, message: String },
+
+    /// A parameter broke a rule of the service; carries the service's own error code
+    #[error("{code}")]
+    InvalidParameter { code: &'static str, message: String },
+
+    /// The request's signature or credentials were rejected; carries the service's own error code
+    #[error("{code}")]
+    AccessDenied { code: &'static str, message: String },
 }
 
 use http::StatusCode;
@@ -68,18 +91,22 @@ impl EmulatorError {
     /// Get HTTP status code
     pub fn status_code(&self) -> StatusCode {
         match self {
-            Self::NoSuchBucket(_) | Self::NoSuchKey(_) | Self::NoSuchBucketPolicy(_) | Self::NotFound(..) => {
+            Self::NoSuchBucket(_) | Self::NoSuchKey(_) | Self::NoSuchUpload(_) | Self::NoSuchBucketPolicy(_) |
+            Self::NoSuchLifecycleConfiguration(_) | Self::NotFound(..) => {
                 StatusCode::NOT_FOUND
             }
             Self::BucketAlreadyExists(_) | Self::AlreadyExists(_) => StatusCode::CONFLICT,
             Self::BucketNotEmpty(_) | Self::InvalidRequest(_) | Self::InvalidArgument(_) | 
-            Self::MalformedXml(_) | Self::MalformedPolicy(_) | Self::InvalidObjectState(_) => {
+            Self::MalformedXml(_) | Self::MalformedPolicy(_) | Self::InvalidObjectState(_) |
+            Self::NotAuthorized(_) | Self::InvalidParameter { .. } => {
                 StatusCode::BAD_REQUEST
             }
             Self::Internal(_) | Self::Database(_) | Self::Io(_) | Self::Json(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             Self::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+            Self::LimitExceeded { status, .. } => StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_REQUEST),
+            Self::AccessDenied { .. } => StatusCode::FORBIDDEN,
         }
     }
 
@@ -91,7 +118,9 @@ impl EmulatorError {
             Self::BucketNotEmpty(_) => "BucketNotEmpty",
             Self::NoSuchKey(_) => "NoSuchKey",
             Self::InvalidObjectState(_) => "InvalidObjectState",
+            Self::NoSuchUpload(_) => "NoSuchUpload",
             Self::NoSuchBucketPolicy(_) => "NoSuchBucketPolicy",
+            Self::NoSuchLifecycleConfiguration(_) => "NoSuchLifecycleConfiguration",
             Self::MalformedPolicy(_) => "MalformedPolicy", 
             Self::InvalidRequest(_) => "InvalidRequest",
             Self::InvalidArgument(_) => "InvalidArgument",
@@ -102,7 +131,11 @@ impl EmulatorError {
             Self::Json(_) => "InternalError",
             Self::NotFound(..) => "ResourceNotFound",
             Self::AlreadyExists(_) => "ResourceAlreadyExists",
+            Self::NotAuthorized(_) => "NotAuthorizedException",
             Self::NotImplemented(_) => "NotImplemented",
+            Self::LimitExceeded { code, .. } => code,
+            Self::InvalidParameter { code, .. } => code,
+            Self::AccessDenied { code, .. } => code,
         }
     }
     
@@ -114,7 +147,9 @@ impl EmulatorError {
             Self::BucketNotEmpty(name) => format!("The bucket you tried to delete is not empty: {}", name),
             Self::NoSuchKey(key) => format!("The specified key does not exist: {}", key),
             Self::InvalidObjectState(msg) => msg.clone(),
+            Self::NoSuchUpload(id) => format!("The specified multipart upload does not exist: {}", id),
             Self::NoSuchBucketPolicy(name) => format!("The bucket policy does not exist: {}", name),
+            Self::NoSuchLifecycleConfiguration(name) => format!("The lifecycle configuration does not exist: {}", name),
             Self::MalformedPolicy(msg) => format!("Malformed policy: {}", msg),
             Self::InvalidRequest(msg) => msg.clone(),
             Self::InvalidArgument(msg) => msg.clone(),
@@ -125,11 +160,17 @@ impl EmulatorError {
             Self::Json(e) => e.to_string(),
             Self::NotFound(type_, id) => format!("{} not found: {}", type_, id),
             Self::AlreadyExists(msg) => msg.clone(),
+            Self::NotAuthorized(msg) => msg.clone(),
             Self::NotImplemented(msg) => format!("Not implemented: {}", msg),
+            Self::LimitExceeded { message, .. } => message.clone(),
+            Self::InvalidParameter { message, .. } => message.clone(),
+            Self::AccessDenied { message, .. } => message.clone(),
         }
     }
 }
 
+emu_storage::impl_from_storage_error!(EmulatorError);
+
 impl From<rusqlite::Error> for EmulatorError {
     fn from(e: rusqlite::Error) -> Self {
         Self::Database(e.to_string())
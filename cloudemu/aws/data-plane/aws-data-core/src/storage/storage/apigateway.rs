@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -32,7 +32,7 @@ pub struct ApiMethod {
 
 impl StorageEngine {
     pub fn init_apigateway_tables(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         
         conn.execute(
             "CREATE TABLE IF NOT EXISTS aws_api_gateways (
@@ -72,7 +72,7 @@ impl StorageEngine {
     }
 
     pub fn create_rest_api(&self, name: &str, description: Option<&str>) -> Result<ApiGateway> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         let id = uuid::Uuid::new_v4().to_string().replace("-", "").to_lowercase()[..10].to_string();
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -100,7 +100,7 @@ impl StorageEngine {
     }
     
     pub fn list_rest_apis(&self) -> Result<Vec<ApiGateway>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         let mut stmt = conn.prepare("SELECT id, name, description, endpoint_type, created_at FROM aws_api_gateways")?;
         
         let rows = stmt.query_map([], |row| {
@@ -122,7 +122,7 @@ impl StorageEngine {
     }
 
     pub fn get_rest_api(&self, api_id: &str) -> Result<ApiGateway> {
-         let conn = self.get_connection()?;
+         let conn = self.shard(Namespace::ApiGateway);
          let mut stmt = conn.prepare("SELECT id, name, description, endpoint_type, created_at FROM aws_api_gateways WHERE id = ?1")?;
          
          let api = stmt.query_row(params![api_id], |row| {
@@ -139,7 +139,7 @@ impl StorageEngine {
     }
 
     pub fn create_resource(&self, api_id: &str, parent_id: &str, path_part: &str) -> Result<ApiResource> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         let id = uuid::Uuid::new_v4().to_string().replace("-", "").to_lowercase()[..10].to_string();
         
         // Simple path calculation (mock)
@@ -161,7 +161,7 @@ impl StorageEngine {
     }
     
     pub fn list_resources(&self, api_id: &str) -> Result<Vec<ApiResource>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         let mut stmt = conn.prepare("SELECT id, api_id, parent_id, path_part, path FROM aws_api_resources WHERE api_id = ?1")?;
         
         let rows = stmt.query_map(params![api_id], |row| {
@@ -183,7 +183,7 @@ impl StorageEngine {
     }
 
     pub fn put_method(&self, api_id: &str, resource_id: &str, http_method: &str, auth_type: &str) -> Result<ApiMethod> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ApiGateway);
         
         conn.execute(
             "INSERT OR REPLACE INTO aws_api_methods (api_id, resource_id, http_method, authorization_type, api_key_required)
@@ -0,0 +1,150 @@
+use super::{StorageEngine, Namespace};
+use crate::error::{EmulatorError, Result};
+use serde::{Deserialize, Serialize};
+use rusqlite::params;
+
+/// Management event recorded by the emulated CloudTrail.
+/// `event_time` is RFC 3339 with millisecond precision so it orders as text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudTrailEvent {
+    pub event_id: String,
+    pub event_name: String,
+    pub event_source: String,
+    pub event_time: String,
+    pub username: String,
+    pub resource_type: Option<String>,
+    pub resource_name: Option<String>,
+    /// Full CloudTrail record as JSON
+    pub cloud_trail_event: String,
+}
+
+impl StorageEngine {
+    pub fn init_cloudtrail_tables(&self) -> Result<()> {
+        let conn = self.shard(Namespace::CloudTrail);
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS aws_cloudtrail_events (
+                event_id TEXT PRIMARY KEY,
+                event_name TEXT NOT NULL,
+                event_source TEXT NOT NULL,
+                event_time TEXT NOT NULL,
+                username TEXT NOT NULL,
+                resource_type TEXT,
+                resource_name TEXT,
+                cloud_trail_event TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_cloudtrail_events_time ON aws_cloudtrail_events(event_time);",
+        )?;
+
+        Ok(())
+    }
+
+    pub fn record_cloudtrail_event(&self, event: &CloudTrailEvent) -> Result<()> {
+        let conn = self.shard(Namespace::CloudTrail);
+
+        conn.execute(
+            "INSERT INTO aws_cloudtrail_events (event_id, event_name, event_source, event_time, username,
+                resource_type, resource_name, cloud_trail_event)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                event.event_id, event.event_name, event.event_source, event.event_time, event.username,
+                event.resource_type, event.resource_name, event.cloud_trail_event
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Look up events newest first, optionally filtered by one lookup attribute
+    /// (`EventId`, `EventName`, `EventSource`, `ResourceType`, `ResourceName` or `Username`)
+    /// and an inclusive time range.
+    pub fn lookup_cloudtrail_events(
+        &self,
+        attribute: Option<(&str, &str)>,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CloudTrailEvent>> {
+        let (column, value) = match attribute {
+            Some((key, value)) => {
+                let column = match key {
+                    "EventId" => "event_id",
+                    "EventName" => "event_name",
+                    "EventSource" => "event_source",
+                    "ResourceType" => "resource_type",
+                    "ResourceName" => "resource_name",
+                    "Username" => "username",
+                    other => return Err(EmulatorError::InvalidArgument(format!("Unsupported lookup attribute: {}", other))),
+                };
+                (column, Some(value))
+            }
+            None => ("event_id", None),
+        };
+
+        let conn = self.shard(Namespace::CloudTrail);
+        let sql = format!(
+            "SELECT event_id, event_name, event_source, event_time, username, resource_type, resource_name, cloud_trail_event
+             FROM aws_cloudtrail_events
+             WHERE (?1 IS NULL OR {} = ?1) AND (?2 IS NULL OR event_time >= ?2) AND (?3 IS NULL OR event_time <= ?3)
+             ORDER BY event_time DESC, rowid DESC LIMIT ?4",
+            column
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![value, start_time, end_time, limit as i64], |row| {
+            Ok(CloudTrailEvent {
+                event_id: row.get(0)?,
+                event_name: row.get(1)?,
+                event_source: row.get(2)?,
+                event_time: row.get(3)?,
+                username: row.get(4)?,
+                resource_type: row.get(5)?,
+                resource_name: row.get(6)?,
+                cloud_trail_event: row.get(7)?,
+            })
+        })?;
+
+        let mut events = Vec::new();
+        for r in rows {
+            events.push(r?);
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, name: &str, time: &str) -> CloudTrailEvent {
+        CloudTrailEvent {
+            event_id: id.to_string(),
+            event_name: name.to_string(),
+            event_source: "sqs.amazonaws.com".to_string(),
+            event_time: time.to_string(),
+            username: "root".to_string(),
+            resource_type: None,
+            resource_name: Some("orders".to_string()),
+            cloud_trail_event: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_cloudtrail_lookup() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.record_cloudtrail_event(&event("1", "CreateQueue", "2026-01-01T00:00:00.000Z")).unwrap();
+        engine.record_cloudtrail_event(&event("2", "DeleteQueue", "2026-01-02T00:00:00.000Z")).unwrap();
+
+        let all = engine.lookup_cloudtrail_events(None, None, None, 50).unwrap();
+        assert_eq!(all.iter().map(|e| e.event_id.as_str()).collect::<Vec<_>>(), vec!["2", "1"]);
+
+        let created = engine.lookup_cloudtrail_events(Some(("EventName", "CreateQueue")), None, None, 50).unwrap();
+        assert_eq!(created.len(), 1);
+
+        let later = engine.lookup_cloudtrail_events(None, Some("2026-01-01T12:00:00.000Z"), None, 50).unwrap();
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].event_name, "DeleteQueue");
+
+        assert!(engine.lookup_cloudtrail_events(Some(("AccessKeyId", "x")), None, None, 50).is_err());
+    }
+}
@@ -1,6 +1,49 @@
-use super::engine::{StorageEngine, TableMetadata};
+use super::engine::{StorageEngine, Namespace, TableMetadata};
 use crate::error::{EmulatorError, Result};
-use rusqlite::params;
+use emu_storage::StreamStore;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// DynamoDB stream of a table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStream {
+    pub table_name: String,
+    pub stream_arn: String,
+    /// KEYS_ONLY | NEW_IMAGE | OLD_IMAGE | NEW_AND_OLD_IMAGES
+    pub view_type: String,
+}
+
+/// Item change read from a table stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStreamRecord {
+    pub sequence_number: u64,
+    /// INSERT | MODIFY | REMOVE
+    pub event_name: String,
+    pub keys: Value,
+    pub new_image: Option<Value>,
+    pub old_image: Option<Value>,
+    pub view_type: String,
+    /// Epoch seconds
+    pub created_at: i64,
+}
+
+/// Change as appended to the stream store
+#[derive(Serialize, Deserialize)]
+struct StoredChange {
+    event_name: String,
+    keys: Value,
+    new_image: Option<Value>,
+    old_image: Option<Value>,
+    view_type: String,
+    created_at: i64,
+}
+
+const STREAM_VIEW_TYPES: &[&str] = &["KEYS_ONLY", "NEW_IMAGE", "OLD_IMAGE", "NEW_AND_OLD_IMAGES"];
+
+fn stream_key(table_name: &str) -> String {
+    format!("dynamodb/{}", table_name)
+}
 
 impl StorageEngine {
     // ==================== DynamoDB Operations ====================
@@ -8,7 +51,7 @@ impl StorageEngine {
     // NOTE: This function will be refactored with a builder pattern in upcoming storage refactor
     #[allow(clippy::too_many_arguments)]
     pub fn create_table(&self, name: &str, attr_defs: &str, key_schema: &str, account_id: &str, region: &str) -> Result<TableMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         let arn = format!("arn:aws:dynamodb:{}:{}:table/{}", region, account_id, name);
         let now = chrono::Utc::now().to_rfc3339();
 
@@ -34,7 +77,7 @@ impl StorageEngine {
     }
 
     pub fn get_table(&self, name: &str) -> Result<TableMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         db.query_row(
             "SELECT name, arn, status, attribute_definitions, key_schema, created_at FROM ddb_tables WHERE name = ?1",
             params![name],
@@ -50,7 +93,22 @@ impl StorageEngine {
     }
 
     pub fn put_item(&self, table_name: &str, pk: &str, sk: Option<&str>, item_json: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
+
+        // Tables with a stream record the item's previous image along with the new one
+        let stream: Option<(String, String)> = db.query_row(
+            "SELECT s.view_type, t.key_schema FROM ddb_streams s JOIN ddb_tables t ON t.name = s.table_name WHERE s.table_name = ?1",
+            params![table_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+        let old_image: Option<String> = match stream {
+            Some(_) => db.query_row(
+                "SELECT item_json FROM ddb_items WHERE table_name = ?1 AND partition_key = ?2 AND sort_key IS ?3",
+                params![table_name, pk, sk],
+                |row| row.get(0),
+            ).optional()?,
+            None => None,
+        };
         
         // SQLite treats NULLs as distinct in UNIQUE/PK constraints, so INSERT OR REPLACE doesn't work for NULL sort_keys.
         // We manually delete conflict if it exists.
@@ -65,11 +123,122 @@ impl StorageEngine {
             "INSERT OR REPLACE INTO ddb_items (table_name, partition_key, sort_key, item_json) VALUES (?1, ?2, ?3, ?4)",
             params![table_name, pk, sk, item_json],
         )?;
+
+        // Journal the new image when point-in-time recovery is enabled
+        db.execute(
+            "INSERT INTO ddb_journal (table_name, partition_key, sort_key, item_json, changed_at)
+             SELECT ?1, ?2, ?3, ?4, ?5 WHERE EXISTS (SELECT 1 FROM ddb_pitr WHERE table_name = ?1)",
+            params![table_name, pk, sk, item_json, chrono::Utc::now().timestamp_millis()],
+        )?;
+        drop(db);
+
+        if let Some((view_type, key_schema)) = stream {
+            self.record_stream_change(table_name, &view_type, &key_schema, old_image.as_deref(), Some(item_json))?;
+        }
+        Ok(())
+    }
+
+    /// Append an item change to the table's stream, keeping the images its view type asks for
+    fn record_stream_change(&self, table_name: &str, view_type: &str, key_schema: &str, old: Option<&str>, new: Option<&str>) -> Result<()> {
+        let old: Option<Value> = old.map(serde_json::from_str).transpose()?;
+        let new: Option<Value> = new.map(serde_json::from_str).transpose()?;
+        let event_name = match (&old, &new) {
+            (None, Some(_)) => "INSERT",
+            (Some(_), Some(_)) => "MODIFY",
+            _ => "REMOVE",
+        };
+
+        let key_schema: Vec<Value> = serde_json::from_str(key_schema).unwrap_or_default();
+        let image = new.as_ref().or(old.as_ref());
+        let keys: serde_json::Map<String, Value> = key_schema.iter()
+            .filter_map(|k| k["AttributeName"].as_str())
+            .filter_map(|name| image.and_then(|i| i.get(name)).map(|v| (name.to_string(), v.clone())))
+            .collect();
+
+        let change = StoredChange {
+            event_name: event_name.to_string(),
+            keys: Value::Object(keys),
+            new_image: new.filter(|_| matches!(view_type, "NEW_IMAGE" | "NEW_AND_OLD_IMAGES")),
+            old_image: old.filter(|_| matches!(view_type, "OLD_IMAGE" | "NEW_AND_OLD_IMAGES")),
+            view_type: view_type.to_string(),
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        self.streams.append(&stream_key(table_name), &serde_json::to_vec(&change)?)?;
         Ok(())
     }
 
+    /// Record the item changes of a table in a stream, returning the stream
+    pub fn enable_table_stream(&self, table_name: &str, view_type: &str) -> Result<TableStream> {
+        if !STREAM_VIEW_TYPES.contains(&view_type) {
+            return Err(EmulatorError::InvalidArgument(format!("Invalid StreamViewType: {}", view_type)));
+        }
+        let table = self.get_table(table_name)?;
+        let stream = TableStream {
+            table_name: table_name.to_string(),
+            stream_arn: format!("{}/stream/{}", table.arn, chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3f")),
+            view_type: view_type.to_string(),
+        };
+
+        let db = self.shard(Namespace::DynamoDb);
+        db.execute(
+            "INSERT INTO ddb_streams (table_name, stream_arn, view_type) VALUES (?1, ?2, ?3)",
+            params![stream.table_name, stream.stream_arn, stream.view_type],
+        ).map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint") {
+                EmulatorError::AlreadyExists(format!("Table {} already has a stream", table_name))
+            } else {
+                EmulatorError::Database(e.to_string())
+            }
+        })?;
+        Ok(stream)
+    }
+
+    /// Stream of a table, if it has one
+    pub fn get_table_stream(&self, table_name: &str) -> Result<Option<TableStream>> {
+        let db = self.shard(Namespace::DynamoDb);
+        Ok(db.query_row(
+            "SELECT table_name, stream_arn, view_type FROM ddb_streams WHERE table_name = ?1",
+            params![table_name],
+            row_to_table_stream,
+        ).optional()?)
+    }
+
+    pub fn get_table_stream_by_arn(&self, stream_arn: &str) -> Result<TableStream> {
+        let db = self.shard(Namespace::DynamoDb);
+        db.query_row(
+            "SELECT table_name, stream_arn, view_type FROM ddb_streams WHERE stream_arn = ?1",
+            params![stream_arn],
+            row_to_table_stream,
+        ).optional()?
+            .ok_or_else(|| EmulatorError::NotFound("Stream".into(), stream_arn.into()))
+    }
+
+    /// Up to `limit` changes of a table after the sequence number `after`, oldest first
+    pub fn get_table_stream_records(&self, table_name: &str, after: u64, limit: usize) -> Result<Vec<TableStreamRecord>> {
+        self.streams.read(&stream_key(table_name), after, limit)?
+            .into_iter()
+            .map(|record| {
+                let change: StoredChange = serde_json::from_slice(&record.data)?;
+                Ok(TableStreamRecord {
+                    sequence_number: record.seq,
+                    event_name: change.event_name,
+                    keys: change.keys,
+                    new_image: change.new_image,
+                    old_image: change.old_image,
+                    view_type: change.view_type,
+                    created_at: change.created_at,
+                })
+            })
+            .collect()
+    }
+
+    /// Sequence number of the last change recorded for a table, 0 if there is none
+    pub fn latest_table_stream_sequence(&self, table_name: &str) -> Result<u64> {
+        Ok(self.streams.last_seq(&stream_key(table_name))?)
+    }
+
     pub fn get_item(&self, table_name: &str, pk: &str, sk: Option<&str>) -> Result<Option<String>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         
         if let Some(s) = sk {
             let mut stmt = db.prepare(
@@ -87,7 +256,7 @@ impl StorageEngine {
     }
 
     pub fn query_items(&self, table_name: &str, pk: &str) -> Result<Vec<String>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         let mut stmt = db.prepare(
             "SELECT item_json FROM ddb_items WHERE table_name = ?1 AND partition_key = ?2"
         )?;
@@ -101,7 +270,7 @@ impl StorageEngine {
         Ok(items)
     }
     pub fn scan_items(&self, table_name: &str) -> Result<Vec<String>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         let mut stmt = db.prepare(
             "SELECT item_json FROM ddb_items WHERE table_name = ?1"
         )?;
@@ -115,8 +284,85 @@ impl StorageEngine {
         Ok(items)
     }
 
+    /// Enable or disable point-in-time recovery. Enabling snapshots the current items into the
+    /// change journal; disabling discards the recovery history, as on AWS.
+    /// Returns the earliest restorable time (epoch millis) while PITR is enabled.
+    pub fn update_point_in_time_recovery(&self, table_name: &str, enabled: bool) -> Result<Option<i64>> {
+        self.get_table(table_name)?;
+        let mut db = self.shard(Namespace::DynamoDb);
+        let tx = db.transaction()?;
+
+        if !enabled {
+            tx.execute("DELETE FROM ddb_pitr WHERE table_name = ?1", params![table_name])?;
+            tx.execute("DELETE FROM ddb_journal WHERE table_name = ?1", params![table_name])?;
+            tx.commit()?;
+            return Ok(None);
+        }
+
+        let existing: Option<i64> = tx.query_row(
+            "SELECT enabled_at FROM ddb_pitr WHERE table_name = ?1",
+            params![table_name],
+            |row| row.get(0),
+        ).ok();
+        if existing.is_some() {
+            return Ok(existing);
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        tx.execute("INSERT INTO ddb_pitr (table_name, enabled_at) VALUES (?1, ?2)", params![table_name, now])?;
+        tx.execute(
+            "INSERT INTO ddb_journal (table_name, partition_key, sort_key, item_json, changed_at)
+             SELECT table_name, partition_key, sort_key, item_json, ?2 FROM ddb_items WHERE table_name = ?1",
+            params![table_name, now],
+        )?;
+        tx.commit()?;
+        Ok(Some(now))
+    }
+
+    /// Earliest restorable time (epoch millis) if point-in-time recovery is enabled
+    pub fn get_point_in_time_recovery(&self, table_name: &str) -> Result<Option<i64>> {
+        self.get_table(table_name)?;
+        let db = self.shard(Namespace::DynamoDb);
+        Ok(db.query_row(
+            "SELECT enabled_at FROM ddb_pitr WHERE table_name = ?1",
+            params![table_name],
+            |row| row.get(0),
+        ).ok())
+    }
+
+    /// Create `target` with the source table's key schema and the items as they were at
+    /// `restore_at` (epoch millis), replaying the change journal
+    pub fn restore_table_to_point_in_time(&self, source: &str, target: &str, restore_at: i64, account_id: &str, region: &str) -> Result<TableMetadata> {
+        let source_table = self.get_table(source)?;
+        let enabled_at = self.get_point_in_time_recovery(source)?
+            .ok_or_else(|| EmulatorError::InvalidRequest(format!("Point in time recovery is not enabled for table {}", source)))?;
+
+        let now = chrono::Utc::now().timestamp_millis();
+        if restore_at < enabled_at || restore_at > now {
+            return Err(EmulatorError::InvalidRequest(
+                "RestoreDateTime is outside the restorable window of the source table".into()
+            ));
+        }
+
+        let table = self.create_table(target, &source_table.attribute_definitions, &source_table.key_schema, account_id, region)?;
+
+        let db = self.shard(Namespace::DynamoDb);
+        db.execute(
+            "INSERT INTO ddb_items (table_name, partition_key, sort_key, item_json)
+             SELECT ?2, j.partition_key, j.sort_key, j.item_json FROM ddb_journal j
+             WHERE j.table_name = ?1 AND j.item_json IS NOT NULL AND j.seq = (
+                 SELECT MAX(k.seq) FROM ddb_journal k
+                 WHERE k.table_name = j.table_name AND k.partition_key = j.partition_key
+                 AND k.sort_key IS j.sort_key AND k.changed_at <= ?3
+             )",
+            params![source, target, restore_at],
+        )?;
+
+        Ok(table)
+    }
+
     pub fn list_tables(&self) -> Result<Vec<TableMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::DynamoDb);
         let mut stmt = db.prepare(
             "SELECT name, arn, status, attribute_definitions, key_schema, created_at FROM ddb_tables ORDER BY name"
         )?;
@@ -134,6 +380,14 @@ impl StorageEngine {
     }
 }
 
+fn row_to_table_stream(row: &rusqlite::Row) -> rusqlite::Result<TableStream> {
+    Ok(TableStream {
+        table_name: row.get(0)?,
+        stream_arn: row.get(1)?,
+        view_type: row.get(2)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,4 +432,66 @@ mod tests {
         let retrieved = engine.get_item("test", "1", None).unwrap();
         assert_eq!(retrieved, Some(item));
     }
+
+    #[test]
+    fn test_dynamodb_point_in_time_restore() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_table("orders", "[]", "[]", "000000000000", "us-east-1").unwrap();
+        engine.put_item("orders", "1", None, r#"{"v": 1}"#).unwrap();
+
+        // Restoring requires PITR
+        let now = chrono::Utc::now().timestamp_millis();
+        assert!(engine.restore_table_to_point_in_time("orders", "copy", now, "000000000000", "us-east-1").is_err());
+
+        let enabled_at = engine.update_point_in_time_recovery("orders", true).unwrap().unwrap();
+        assert_eq!(engine.get_point_in_time_recovery("orders").unwrap(), Some(enabled_at));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        engine.put_item("orders", "2", None, r#"{"v": 2}"#).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let checkpoint = chrono::Utc::now().timestamp_millis();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        engine.put_item("orders", "1", None, r#"{"v": 10}"#).unwrap();
+        engine.put_item("orders", "3", None, r#"{"v": 3}"#).unwrap();
+
+        // Items existing before PITR was enabled are part of the baseline
+        engine.restore_table_to_point_in_time("orders", "at-enable", enabled_at, "000000000000", "us-east-1").unwrap();
+        assert_eq!(engine.scan_items("at-enable").unwrap(), vec![r#"{"v": 1}"#.to_string()]);
+
+        engine.restore_table_to_point_in_time("orders", "at-checkpoint", checkpoint, "000000000000", "us-east-1").unwrap();
+        assert_eq!(engine.get_item("at-checkpoint", "1", None).unwrap().as_deref(), Some(r#"{"v": 1}"#));
+        assert!(engine.get_item("at-checkpoint", "2", None).unwrap().is_some());
+        assert!(engine.get_item("at-checkpoint", "3", None).unwrap().is_none());
+
+        assert!(engine.restore_table_to_point_in_time("orders", "too-early", enabled_at - 1, "000000000000", "us-east-1").is_err());
+
+        // Disabling PITR drops the recovery history
+        assert_eq!(engine.update_point_in_time_recovery("orders", false).unwrap(), None);
+        assert_eq!(engine.get_point_in_time_recovery("orders").unwrap(), None);
+    }
+
+    #[test]
+    fn test_dynamodb_stream_records_item_changes() {
+        let engine = StorageEngine::in_memory().unwrap();
+        let key_schema = r#"[{"AttributeName": "id", "KeyType": "HASH"}]"#;
+        engine.create_table("orders", "[]", key_schema, "000000000000", "us-east-1").unwrap();
+        engine.put_item("orders", "1", None, r#"{"id": {"S": "1"}, "v": {"N": "1"}}"#).unwrap();
+
+        assert!(engine.enable_table_stream("orders", "EVERYTHING").is_err());
+        let stream = engine.enable_table_stream("orders", "NEW_AND_OLD_IMAGES").unwrap();
+        assert!(stream.stream_arn.starts_with("arn:aws:dynamodb:us-east-1:000000000000:table/orders/stream/"));
+        assert_eq!(engine.get_table_stream_by_arn(&stream.stream_arn).unwrap().table_name, "orders");
+
+        // Changes made before the stream existed are not recorded
+        engine.put_item("orders", "1", None, r#"{"id": {"S": "1"}, "v": {"N": "2"}}"#).unwrap();
+        engine.put_item("orders", "2", None, r#"{"id": {"S": "2"}, "v": {"N": "1"}}"#).unwrap();
+
+        let records = engine.get_table_stream_records("orders", 0, 10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].event_name, "MODIFY");
+        assert_eq!(records[0].keys, json!({"id": {"S": "1"}}));
+        assert_eq!(records[0].old_image.as_ref().unwrap()["v"], json!({"N": "1"}));
+        assert_eq!(records[0].new_image.as_ref().unwrap()["v"], json!({"N": "2"}));
+        assert_eq!(records[1].event_name, "INSERT");
+        assert_eq!(engine.latest_table_stream_sequence("orders").unwrap(), 2);
+    }
 }
@@ -1,30 +1,35 @@
-use super::engine::{StorageEngine, InstanceMetadata, KeyPairMetadata};
-use crate::error::Result;
-use rusqlite::params;
+use super::engine::{StorageEngine, Namespace, InstanceMetadata, KeyPairMetadata};
+use crate::error::{EmulatorError, Result};
+use rusqlite::{params, OptionalExtension};
 use uuid::Uuid;
 
 impl StorageEngine {
     // ==================== EC2 Operations ====================
 
+    #[allow(clippy::too_many_arguments)]
     pub fn run_instances(
         &self, 
         image_id: &str, 
         instance_type: &str, 
         vpc_id: Option<&str>, 
         subnet_id: Option<&str>,
-        key_name: Option<&str>
+        key_name: Option<&str>,
+        security_groups: &[String],
+        private_ip: Option<&str>,
     ) -> Result<InstanceMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let id = format!("i-{}", &Uuid::new_v4().to_string()[..8]);
         let launch_time = chrono::Utc::now().to_rfc3339();
         
-        // Mock IP assignment
-        let private_ip = format!("10.0.0.{}", rand::random::<u8>());
+        // Mock IP assignment outside a subnet
+        let private_ip = private_ip.map(|ip| ip.to_string())
+            .unwrap_or_else(|| format!("10.0.0.{}", rand::random::<u8>()));
         let public_ip = format!("54.12.34.{}", rand::random::<u8>());
+        let sg_json = serde_json::to_string(security_groups)?;
         
         db.execute(
-            "INSERT INTO ec2_instances (id, image_id, instance_type, key_name, private_ip, public_ip, vpc_id, subnet_id, launch_time) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            params![id, image_id, instance_type, key_name, private_ip, public_ip, vpc_id, subnet_id, launch_time],
+            "INSERT INTO ec2_instances (id, image_id, instance_type, key_name, private_ip, public_ip, vpc_id, subnet_id, security_groups, launch_time) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![id, image_id, instance_type, key_name, private_ip, public_ip, vpc_id, subnet_id, sg_json, launch_time],
         )?;
         
         Ok(InstanceMetadata {
@@ -37,43 +42,69 @@ impl StorageEngine {
             public_ip: Some(public_ip),
             vpc_id: vpc_id.map(|s| s.to_string()),
             subnet_id: subnet_id.map(|s| s.to_string()),
-            security_groups: vec![],
+            security_groups: security_groups.to_vec(),
+            iam_instance_profile: None,
             launch_time,
             tags: None,
         })
     }
 
     pub fn list_instances(&self) -> Result<Vec<InstanceMetadata>> {
-        let db = self.db.lock();
-        let mut stmt = db.prepare("SELECT id, image_id, instance_type, key_name, state, private_ip, public_ip, vpc_id, subnet_id, security_groups, launch_time, tags FROM ec2_instances")?;
-        let instances = stmt.query_map([], |row| {
-            let sg_json: Option<String> = row.get(9)?;
-            let security_groups = if let Some(j) = sg_json {
-                serde_json::from_str(&j).unwrap_or_default()
-            } else {
-                vec![]
-            };
-
-            Ok(InstanceMetadata {
-                id: row.get(0)?,
-                image_id: row.get(1)?,
-                instance_type: row.get(2)?,
-                key_name: row.get(3)?,
-                state: row.get(4)?,
-                private_ip: row.get(5)?,
-                public_ip: row.get(6)?,
-                vpc_id: row.get(7)?,
-                subnet_id: row.get(8)?,
-                security_groups,
-                launch_time: row.get(10)?,
-                tags: row.get(11)?,
-            })
-        })?.filter_map(|r| r.ok()).collect();
+        let db = self.shard(Namespace::Ec2);
+        let mut stmt = db.prepare("SELECT id, image_id, instance_type, key_name, state, private_ip, public_ip, vpc_id, subnet_id, security_groups, launch_time, tags, iam_instance_profile FROM ec2_instances")?;
+        let instances = stmt.query_map([], Self::row_to_instance)?.filter_map(|r| r.ok()).collect();
         Ok(instances)
     }
 
+    pub fn get_instance(&self, id: &str) -> Result<InstanceMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        db.query_row(
+            "SELECT id, image_id, instance_type, key_name, state, private_ip, public_ip, vpc_id, subnet_id, security_groups, launch_time, tags, iam_instance_profile FROM ec2_instances WHERE id = ?",
+            params![id],
+            Self::row_to_instance,
+        ).optional()?.ok_or_else(|| EmulatorError::NotFound("Instance".into(), id.into()))
+    }
+
+    fn row_to_instance(row: &rusqlite::Row) -> rusqlite::Result<InstanceMetadata> {
+        let sg_json: Option<String> = row.get(9)?;
+        let security_groups = if let Some(j) = sg_json {
+            serde_json::from_str(&j).unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        Ok(InstanceMetadata {
+            id: row.get(0)?,
+            image_id: row.get(1)?,
+            instance_type: row.get(2)?,
+            key_name: row.get(3)?,
+            state: row.get(4)?,
+            private_ip: row.get(5)?,
+            public_ip: row.get(6)?,
+            vpc_id: row.get(7)?,
+            subnet_id: row.get(8)?,
+            security_groups,
+            iam_instance_profile: row.get(12)?,
+            launch_time: row.get(10)?,
+            tags: row.get(11)?,
+        })
+    }
+
+    /// Attach or detach the instance profile of an instance
+    pub fn set_instance_profile(&self, id: &str, profile_arn: Option<&str>) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute(
+            "UPDATE ec2_instances SET iam_instance_profile = ? WHERE id = ?",
+            params![profile_arn, id],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Instance".into(), id.into()));
+        }
+        Ok(())
+    }
+
     pub fn create_key_pair(&self, name: &str) -> Result<KeyPairMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let fingerprint = format!("ae:{:02x}:{:02x}:{:02x}", rand::random::<u8>(), rand::random::<u8>(), rand::random::<u8>());
         
         db.execute(
@@ -90,7 +121,7 @@ impl StorageEngine {
     }
 
     pub fn list_key_pairs(&self) -> Result<Vec<KeyPairMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let mut stmt = db.prepare("SELECT key_name, key_fingerprint, tags FROM ec2_key_pairs")?;
         let keys = stmt.query_map([], |row| {
             Ok(KeyPairMetadata {
@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -14,7 +14,7 @@ pub struct EcrRepository {
 
 impl StorageEngine {
     pub fn init_ecr_tables(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Ecr);
         
         conn.execute(
             "CREATE TABLE IF NOT EXISTS aws_ecr_repositories (
@@ -31,7 +31,7 @@ impl StorageEngine {
     }
 
     pub fn create_repository(&self, name: &str) -> Result<EcrRepository> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Ecr);
         let account_id = "000000000000";
         let region = "us-east-1";
         let arn = format!("arn:aws:ecr:{}:{}:repository/{}", region, account_id, name);
@@ -54,7 +54,7 @@ impl StorageEngine {
     }
     
     pub fn list_repositories(&self) -> Result<Vec<EcrRepository>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Ecr);
         let mut stmt = conn.prepare("SELECT repository_name, repository_arn, registry_id, repository_uri, created_at FROM aws_ecr_repositories")?;
         
         let rows = stmt.query_map([], |row| {
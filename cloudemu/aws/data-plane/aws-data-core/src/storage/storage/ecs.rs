@@ -1,5 +1,5 @@
-use super::StorageEngine;
-use crate::error::Result;
+use super::{StorageEngine, Namespace};
+use crate::error::{EmulatorError, Result};
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
 
@@ -16,6 +16,9 @@ pub struct EcsTaskDefinition {
     pub family: String,
     pub revision: i32,
     pub container_definitions: Vec<ContainerDefinition>,
+    /// Role whose credentials are vended to the task's containers
+    #[serde(default)]
+    pub task_role_arn: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,13 +37,67 @@ pub struct PortMapping {
     pub protocol: String,
 }
 
+/// Long-running ECS service keeping `desired_count` tasks of its task definition running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcsServiceMetadata {
+    pub arn: String,
+    pub name: String,
+    pub cluster_arn: String,
+    pub task_definition: String,
+    pub desired_count: i32,
+    pub minimum_healthy_percent: i32,
+    pub maximum_percent: i32,
+    pub circuit_breaker_enable: bool,
+    pub circuit_breaker_rollback: bool,
+    pub status: String, // ACTIVE | INACTIVE
+    pub created_at: i64,
+}
+
+/// One task definition rollout of a service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcsDeployment {
+    pub id: String,
+    pub service_arn: String,
+    pub task_definition: String,
+    pub status: String, // PRIMARY | ACTIVE | INACTIVE
+    pub desired_count: i32,
+    pub failed_tasks: i32,
+    pub rollout_state: String, // IN_PROGRESS | COMPLETED | FAILED
+    pub rollout_state_reason: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcsTask {
+    pub arn: String,
+    pub cluster_arn: String,
+    pub service_arn: Option<String>,
+    pub deployment_id: Option<String>,
+    pub task_definition: String,
+    pub last_status: String, // RUNNING | STOPPED
+    pub stopped_reason: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcsServiceEvent {
+    pub id: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
 impl StorageEngine {
     // ECS Table constants
     const TABLE_ECS_CLUSTERS: &'static str = "aws_ecs_clusters";
     const TABLE_ECS_TASK_DEFS: &'static str = "aws_ecs_task_definitions";
+    const TABLE_ECS_SERVICES: &'static str = "aws_ecs_services";
+    const TABLE_ECS_DEPLOYMENTS: &'static str = "aws_ecs_deployments";
+    const TABLE_ECS_TASKS: &'static str = "aws_ecs_tasks";
+    const TABLE_ECS_SERVICE_EVENTS: &'static str = "aws_ecs_service_events";
 
     pub fn init_ecs_tables(&self) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Ecs);
         
         conn.execute(&format!(
             "CREATE TABLE IF NOT EXISTS {} (
@@ -64,11 +121,79 @@ impl StorageEngine {
             Self::TABLE_ECS_TASK_DEFS
         ), [])?;
 
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                arn TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                cluster_arn TEXT NOT NULL,
+                task_definition TEXT NOT NULL,
+                desired_count INTEGER NOT NULL,
+                minimum_healthy_percent INTEGER NOT NULL,
+                maximum_percent INTEGER NOT NULL,
+                circuit_breaker_enable INTEGER NOT NULL,
+                circuit_breaker_rollback INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            Self::TABLE_ECS_SERVICES
+        ), [])?;
+
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                id TEXT PRIMARY KEY,
+                service_arn TEXT NOT NULL,
+                task_definition TEXT NOT NULL,
+                status TEXT NOT NULL,
+                desired_count INTEGER NOT NULL,
+                failed_tasks INTEGER NOT NULL,
+                rollout_state TEXT NOT NULL,
+                rollout_state_reason TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            Self::TABLE_ECS_DEPLOYMENTS
+        ), [])?;
+
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                arn TEXT PRIMARY KEY,
+                cluster_arn TEXT NOT NULL,
+                service_arn TEXT,
+                deployment_id TEXT,
+                task_definition TEXT NOT NULL,
+                last_status TEXT NOT NULL,
+                stopped_reason TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            Self::TABLE_ECS_TASKS
+        ), [])?;
+
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                id TEXT NOT NULL,
+                service_arn TEXT NOT NULL,
+                message TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            Self::TABLE_ECS_SERVICE_EVENTS
+        ), [])?;
+
         Ok(())
     }
 
+    /// Resolve a cluster name or ARN to the ARN of an existing cluster
+    pub fn get_cluster_arn(&self, cluster: &str) -> Result<String> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.query_row(
+            &format!("SELECT arn FROM {} WHERE name = ?1 OR arn = ?1", Self::TABLE_ECS_CLUSTERS),
+            params![cluster],
+            |row| row.get(0),
+        ).map_err(|_| EmulatorError::NotFound("Cluster".into(), cluster.into()))
+    }
+
     pub fn create_cluster(&self, name: &str) -> Result<EcsCluster> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Ecs);
         let arn = format!("arn:aws:ecs:us-east-1:000000000000:cluster/{}", name);
         let status = "ACTIVE";
         
@@ -81,7 +206,7 @@ impl StorageEngine {
     }
 
     pub fn list_clusters(&self) -> Result<Vec<String>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Ecs);
         let mut stmt = conn.prepare(&format!("SELECT arn FROM {}", Self::TABLE_ECS_CLUSTERS))?;
         
         let arns = stmt.query_map([], |row| row.get(0))?
@@ -93,9 +218,10 @@ impl StorageEngine {
     pub fn register_task_definition(
         &self, 
         family: &str, 
-        containers: Vec<ContainerDefinition>
+        containers: Vec<ContainerDefinition>,
+        task_role_arn: Option<&str>,
     ) -> Result<EcsTaskDefinition> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Ecs);
 
         // Get next revision
         let last_rev: Option<i32> = conn.query_row(
@@ -112,6 +238,7 @@ impl StorageEngine {
             family: family.to_string(),
             revision,
             container_definitions: containers,
+            task_role_arn: task_role_arn.map(|s| s.to_string()),
         };
 
         let json = serde_json::to_string(&def).unwrap();
@@ -123,4 +250,226 @@ impl StorageEngine {
 
         Ok(def)
     }
+
+    /// Look up a task definition by ARN, `family:revision` or `family` (latest revision)
+    pub fn get_task_definition(&self, reference: &str) -> Result<EcsTaskDefinition> {
+        let conn = self.shard(Namespace::Ecs);
+        let family_revision = reference.rsplit('/').next().unwrap_or(reference);
+        let json: String = match family_revision.split_once(':') {
+            Some((family, revision)) => conn.query_row(
+                &format!("SELECT definition_json FROM {} WHERE family = ?1 AND revision = ?2", Self::TABLE_ECS_TASK_DEFS),
+                params![family, revision],
+                |row| row.get(0),
+            ),
+            None => conn.query_row(
+                &format!("SELECT definition_json FROM {} WHERE family = ?1 ORDER BY revision DESC LIMIT 1", Self::TABLE_ECS_TASK_DEFS),
+                params![family_revision],
+                |row| row.get(0),
+            ),
+        }.map_err(|_| EmulatorError::NotFound("TaskDefinition".into(), reference.into()))?;
+
+        serde_json::from_str(&json).map_err(|e| EmulatorError::Internal(e.to_string()))
+    }
+
+    // ==================== Services ====================
+
+    pub fn create_ecs_service(&self, service: &EcsServiceMetadata) -> Result<()> {
+        let conn = self.shard(Namespace::Ecs);
+        let exists: bool = conn.query_row(
+            &format!("SELECT 1 FROM {} WHERE cluster_arn = ?1 AND name = ?2 AND status = 'ACTIVE'", Self::TABLE_ECS_SERVICES),
+            params![service.cluster_arn, service.name],
+            |_| Ok(true),
+        ).unwrap_or(false);
+        if exists {
+            return Err(EmulatorError::InvalidArgument(format!("Creation of service was not idempotent: {} already exists", service.name)));
+        }
+
+        // A deleted service with the same name is replaced along with its history
+        for table in [Self::TABLE_ECS_DEPLOYMENTS, Self::TABLE_ECS_SERVICE_EVENTS] {
+            conn.execute(&format!("DELETE FROM {} WHERE service_arn = ?1", table), params![service.arn])?;
+        }
+
+        conn.execute(
+            &format!("INSERT OR REPLACE INTO {} (arn, name, cluster_arn, task_definition, desired_count, minimum_healthy_percent,
+                maximum_percent, circuit_breaker_enable, circuit_breaker_rollback, status, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)", Self::TABLE_ECS_SERVICES),
+            params![
+                service.arn, service.name, service.cluster_arn, service.task_definition, service.desired_count,
+                service.minimum_healthy_percent, service.maximum_percent, service.circuit_breaker_enable,
+                service.circuit_breaker_rollback, service.status, service.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_ecs_service(&self, service: &EcsServiceMetadata) -> Result<()> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.execute(
+            &format!("UPDATE {} SET task_definition = ?2, desired_count = ?3, minimum_healthy_percent = ?4, maximum_percent = ?5,
+                circuit_breaker_enable = ?6, circuit_breaker_rollback = ?7, status = ?8 WHERE arn = ?1", Self::TABLE_ECS_SERVICES),
+            params![
+                service.arn, service.task_definition, service.desired_count, service.minimum_healthy_percent,
+                service.maximum_percent, service.circuit_breaker_enable, service.circuit_breaker_rollback, service.status
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a service of a cluster by name or ARN
+    pub fn get_ecs_service(&self, cluster_arn: &str, service: &str) -> Result<EcsServiceMetadata> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.query_row(
+            &format!("SELECT arn, name, cluster_arn, task_definition, desired_count, minimum_healthy_percent, maximum_percent,
+                circuit_breaker_enable, circuit_breaker_rollback, status, created_at
+                FROM {} WHERE cluster_arn = ?1 AND (name = ?2 OR arn = ?2)", Self::TABLE_ECS_SERVICES),
+            params![cluster_arn, service],
+            Self::row_to_ecs_service,
+        ).map_err(|_| EmulatorError::NotFound("Service".into(), service.into()))
+    }
+
+    pub fn list_ecs_services(&self, cluster_arn: &str) -> Result<Vec<EcsServiceMetadata>> {
+        let conn = self.shard(Namespace::Ecs);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT arn, name, cluster_arn, task_definition, desired_count, minimum_healthy_percent, maximum_percent,
+                circuit_breaker_enable, circuit_breaker_rollback, status, created_at
+                FROM {} WHERE cluster_arn = ?1 AND status = 'ACTIVE' ORDER BY created_at", Self::TABLE_ECS_SERVICES
+        ))?;
+        let services = stmt.query_map(params![cluster_arn], Self::row_to_ecs_service)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(services)
+    }
+
+    fn row_to_ecs_service(row: &rusqlite::Row) -> rusqlite::Result<EcsServiceMetadata> {
+        Ok(EcsServiceMetadata {
+            arn: row.get(0)?,
+            name: row.get(1)?,
+            cluster_arn: row.get(2)?,
+            task_definition: row.get(3)?,
+            desired_count: row.get(4)?,
+            minimum_healthy_percent: row.get(5)?,
+            maximum_percent: row.get(6)?,
+            circuit_breaker_enable: row.get(7)?,
+            circuit_breaker_rollback: row.get(8)?,
+            status: row.get(9)?,
+            created_at: row.get(10)?,
+        })
+    }
+
+    // ==================== Deployments ====================
+
+    pub fn put_ecs_deployment(&self, deployment: &EcsDeployment) -> Result<()> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.execute(
+            &format!("INSERT INTO {} (id, service_arn, task_definition, status, desired_count, failed_tasks, rollout_state,
+                rollout_state_reason, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                ON CONFLICT(id) DO UPDATE SET status = ?4, desired_count = ?5, failed_tasks = ?6, rollout_state = ?7,
+                rollout_state_reason = ?8, updated_at = ?10", Self::TABLE_ECS_DEPLOYMENTS),
+            params![
+                deployment.id, deployment.service_arn, deployment.task_definition, deployment.status,
+                deployment.desired_count, deployment.failed_tasks, deployment.rollout_state,
+                deployment.rollout_state_reason, deployment.created_at, deployment.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Deployments of a service, newest first
+    pub fn list_ecs_deployments(&self, service_arn: &str) -> Result<Vec<EcsDeployment>> {
+        let conn = self.shard(Namespace::Ecs);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, service_arn, task_definition, status, desired_count, failed_tasks, rollout_state,
+                rollout_state_reason, created_at, updated_at
+                FROM {} WHERE service_arn = ?1 ORDER BY rowid DESC", Self::TABLE_ECS_DEPLOYMENTS
+        ))?;
+        let deployments = stmt.query_map(params![service_arn], |row| {
+            Ok(EcsDeployment {
+                id: row.get(0)?,
+                service_arn: row.get(1)?,
+                task_definition: row.get(2)?,
+                status: row.get(3)?,
+                desired_count: row.get(4)?,
+                failed_tasks: row.get(5)?,
+                rollout_state: row.get(6)?,
+                rollout_state_reason: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(deployments)
+    }
+
+    // ==================== Tasks ====================
+
+    pub fn put_ecs_task(&self, task: &EcsTask) -> Result<()> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.execute(
+            &format!("INSERT OR REPLACE INTO {} (arn, cluster_arn, service_arn, deployment_id, task_definition, last_status,
+                stopped_reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)", Self::TABLE_ECS_TASKS),
+            params![
+                task.arn, task.cluster_arn, task.service_arn, task.deployment_id, task.task_definition,
+                task.last_status, task.stopped_reason, task.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Tasks of a cluster, optionally narrowed to a service and a status, oldest first
+    pub fn list_ecs_tasks(&self, cluster_arn: &str, service_arn: Option<&str>, last_status: Option<&str>) -> Result<Vec<EcsTask>> {
+        let conn = self.shard(Namespace::Ecs);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT arn, cluster_arn, service_arn, deployment_id, task_definition, last_status, stopped_reason, created_at
+                FROM {} WHERE cluster_arn = ?1 AND (?2 IS NULL OR service_arn = ?2) AND (?3 IS NULL OR last_status = ?3)
+                ORDER BY rowid", Self::TABLE_ECS_TASKS
+        ))?;
+        let tasks = stmt.query_map(params![cluster_arn, service_arn, last_status], Self::row_to_ecs_task)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(tasks)
+    }
+
+    /// Look up a task of a cluster by ARN or task ID
+    pub fn get_ecs_task(&self, cluster_arn: &str, task: &str) -> Result<EcsTask> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.query_row(
+            &format!("SELECT arn, cluster_arn, service_arn, deployment_id, task_definition, last_status, stopped_reason, created_at
+                FROM {} WHERE cluster_arn = ?1 AND (arn = ?2 OR arn LIKE '%/' || ?2)", Self::TABLE_ECS_TASKS),
+            params![cluster_arn, task],
+            Self::row_to_ecs_task,
+        ).map_err(|_| EmulatorError::NotFound("Task".into(), task.into()))
+    }
+
+    fn row_to_ecs_task(row: &rusqlite::Row) -> rusqlite::Result<EcsTask> {
+        Ok(EcsTask {
+            arn: row.get(0)?,
+            cluster_arn: row.get(1)?,
+            service_arn: row.get(2)?,
+            deployment_id: row.get(3)?,
+            task_definition: row.get(4)?,
+            last_status: row.get(5)?,
+            stopped_reason: row.get(6)?,
+            created_at: row.get(7)?,
+        })
+    }
+
+    // ==================== Service events ====================
+
+    pub fn add_ecs_service_event(&self, service_arn: &str, message: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Ecs);
+        conn.execute(
+            &format!("INSERT INTO {} (id, service_arn, message, created_at) VALUES (?1, ?2, ?3, ?4)", Self::TABLE_ECS_SERVICE_EVENTS),
+            params![uuid::Uuid::new_v4().to_string(), service_arn, message, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent events of a service, newest first
+    pub fn list_ecs_service_events(&self, service_arn: &str, limit: i64) -> Result<Vec<EcsServiceEvent>> {
+        let conn = self.shard(Namespace::Ecs);
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, message, created_at FROM {} WHERE service_arn = ?1 ORDER BY seq DESC LIMIT ?2", Self::TABLE_ECS_SERVICE_EVENTS
+        ))?;
+        let events = stmt.query_map(params![service_arn, limit], |row| {
+            Ok(EcsServiceEvent { id: row.get(0)?, message: row.get(1)?, created_at: row.get(2)? })
+        })?.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(events)
+    }
 }
@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -16,7 +16,7 @@ pub struct CacheCluster {
 
 impl StorageEngine {
     pub fn init_elasticache_tables(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ElastiCache);
         
         conn.execute(
             "CREATE TABLE IF NOT EXISTS aws_cache_clusters (
@@ -35,7 +35,7 @@ impl StorageEngine {
     }
 
     pub fn create_cache_cluster(&self, id: &str, node_type: &str, engine: &str, num_nodes: i32) -> Result<CacheCluster> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ElastiCache);
         let now = chrono::Utc::now().to_rfc3339();
         let status = "available";
         let version = "6.x"; // Mock version
@@ -58,7 +58,7 @@ impl StorageEngine {
     }
     
     pub fn list_cache_clusters(&self) -> Result<Vec<CacheCluster>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::ElastiCache);
         let mut stmt = conn.prepare("SELECT cache_cluster_id, cache_node_type, engine, engine_version, cache_cluster_status, num_cache_nodes, created_at FROM aws_cache_clusters")?;
         
         let rows = stmt.query_map([], |row| {
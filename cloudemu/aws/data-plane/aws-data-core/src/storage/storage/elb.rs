@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -26,7 +26,7 @@ pub struct TargetGroup {
 
 impl StorageEngine {
     pub fn init_elb_tables(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Elb);
         
         conn.execute(
             "CREATE TABLE IF NOT EXISTS aws_load_balancers (
@@ -57,7 +57,7 @@ impl StorageEngine {
     }
 
     pub fn create_load_balancer(&self, name: &str, subnets: Vec<String>, scheme: &str) -> Result<LoadBalancer> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Elb);
         let arn = format!("arn:aws:elasticloadbalancing:us-east-1:000000000000:loadbalancer/app/{}/{}", name, uuid::Uuid::new_v4().to_string().replace("-", "")[..16].to_string());
         let dns_name = format!("{}.elb.localhost.localstack.cloud", name);
         let now = chrono::Utc::now().to_rfc3339();
@@ -85,7 +85,7 @@ impl StorageEngine {
     }
     
     pub fn list_load_balancers(&self) -> Result<Vec<LoadBalancer>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Elb);
         let mut stmt = conn.prepare("SELECT arn, name, dns_name, scheme, vpc_id, state, created_at FROM aws_load_balancers")?;
         
         let rows = stmt.query_map([], |row| {
@@ -109,7 +109,7 @@ impl StorageEngine {
     }
 
     pub fn create_target_group(&self, name: &str, protocol: &str, port: i32, vpc_id: &str) -> Result<TargetGroup> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Elb);
         let arn = format!("arn:aws:elasticloadbalancing:us-east-1:000000000000:targetgroup/{}/{}", name, uuid::Uuid::new_v4().to_string().replace("-", "")[..16].to_string());
         
         conn.execute(
@@ -129,7 +129,7 @@ impl StorageEngine {
     }
     
     pub fn list_target_groups(&self) -> Result<Vec<TargetGroup>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Elb);
         let mut stmt = conn.prepare("SELECT arn, name, protocol, port, vpc_id, target_type FROM aws_target_groups")?;
         
         let rows = stmt.query_map([], |row| {
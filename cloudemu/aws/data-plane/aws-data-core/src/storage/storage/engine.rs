@@ -2,48 +2,104 @@
 
 use crate::config::Config;
 use crate::error::Result;
+use emu_storage::{database, FsBlobStore, SqliteStreams, Storage, StorageBackend};
 use rusqlite::Connection;
-use std::fs;
-use std::path::PathBuf;
 use std::sync::Arc;
-use parking_lot::Mutex;
-use super::schema::SCHEMA;
+use parking_lot::{Mutex, MutexGuard};
+use super::schema::{self, SCHEMA};
 use serde::{Serialize, Deserialize};
 
+/// Service namespace of the metadata tables.
+/// Each namespace has its own connection and lock, so requests for different services
+/// never wait on each other; a method must not hold one shard while acquiring another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Namespace {
+    S3,
+    DynamoDb,
+    Sqs,
+    Sns,
+    Lambda,
+    Secrets,
+    Events,
+    Kms,
+    Monitoring,
+    Identity,
+    Workflows,
+    /// EC2 instances and the VPC resources they reference
+    Ec2,
+    Ecs,
+    Rds,
+    Iam,
+    Route53,
+    ApiGateway,
+    Elb,
+    ElastiCache,
+    Ecr,
+    Pipes,
+    Pricing,
+    CloudTrail,
+    Kinesis,
+}
+
+impl Namespace {
+    pub const ALL: [Namespace; 24] = [
+        Namespace::S3, Namespace::DynamoDb, Namespace::Sqs, Namespace::Sns, Namespace::Lambda,
+        Namespace::Secrets, Namespace::Events, Namespace::Kms, Namespace::Monitoring,
+        Namespace::Identity, Namespace::Workflows, Namespace::Ec2, Namespace::Ecs, Namespace::Rds,
+        Namespace::Iam, Namespace::Route53, Namespace::ApiGateway, Namespace::Elb,
+        Namespace::ElastiCache, Namespace::Ecr, Namespace::Pipes, Namespace::Pricing,
+        Namespace::CloudTrail, Namespace::Kinesis,
+    ];
+}
+
 /// Storage engine with SQLite for metadata and filesystem for objects
 #[derive(Clone)]
 pub struct StorageEngine {
-    /// SQLite connection (wrapped for thread safety)
-    pub(crate) db: Arc<Mutex<Connection>>,
-    /// Directory for object data
-    pub(crate) objects_dir: PathBuf,
+    /// SQLite connection of each namespace (indexed by `Namespace as usize`), each to the
+    /// same database: the WAL database on disk, or one shared in-memory database
+    pub(crate) shards: Arc<[Arc<Mutex<Connection>>]>,
+    /// Content-addressed object data
+    pub(crate) blobs: FsBlobStore,
+    /// Records of Kinesis streams
+    pub(crate) streams: SqliteStreams,
 }
 
 impl StorageEngine {
     /// Create a new storage engine
     pub fn new(config: &Config) -> Result<Self> {
-        // Create data directory
-        fs::create_dir_all(&config.data_dir)?;
-        
-        // Create objects directory
-        let objects_dir = config.data_dir.join("objects");
-        fs::create_dir_all(&objects_dir)?;
-        
-        // Open SQLite database
-        let db_path = config.data_dir.join("metadata.db");
-        let conn = Connection::open(&db_path)?;
-        
-        // Enable WAL mode for better concurrency
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
-        
-        // Create schema
-        conn.execute_batch(SCHEMA)?;
+        // The schema is created through the first shard; the others are further
+        // connections to the same WAL database, so shards can read while another one writes
+        let storage = Storage::open(&config.data_dir, SCHEMA)?;
+        let db_path = config.data_dir.join(emu_storage::DATABASE_FILE);
+        let mut shards = vec![storage.db];
+        for _ in 1..Namespace::ALL.len() {
+            shards.push(Arc::new(Mutex::new(database::connect(&db_path)?)));
+        }
         
+        Self::init(shards, storage.blobs)
+    }
+    
+    /// Create a new in-memory storage engine (for testing)
+    pub fn in_memory() -> Result<Self> {
+        let name = format!("cloudemu-{}", uuid::Uuid::new_v4());
+        let mut shards = vec![Arc::new(Mutex::new(database::open_shared_in_memory(&name, SCHEMA)?))];
+        for _ in 1..Namespace::ALL.len() {
+            shards.push(Arc::new(Mutex::new(database::connect_in_memory(&name)?)));
+        }
+
+        Self::init(shards, FsBlobStore::temp()?)
+    }
+    
+    fn init(shards: Vec<Arc<Mutex<Connection>>>, blobs: FsBlobStore) -> Result<Self> {
+        let streams = SqliteStreams::new(shards[Namespace::Kinesis as usize].clone())?;
         let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            objects_dir,
+            shards: shards.into(),
+            blobs,
+            streams,
         };
 
+        schema::migrate(&engine.shard(Namespace::S3))?;
+
         engine.init_ecs_tables()?;
         engine.init_rds_tables()?;
         engine.init_iam_tables()?;
@@ -52,37 +108,25 @@ impl StorageEngine {
         engine.init_elb_tables()?;
         engine.init_elasticache_tables()?;
         engine.init_ecr_tables()?;
+        engine.init_pipes_tables()?;
+        engine.init_cloudtrail_tables()?;
+        engine.init_kinesis_tables()?;
 
         Ok(engine)
     }
 
-    pub fn get_connection(&self) -> Result<parking_lot::MutexGuard<'_, Connection>> {
-        Ok(self.db.lock())
+    /// Connection of a namespace, locked for the lifetime of the guard
+    pub fn get_connection(&self, namespace: Namespace) -> Result<MutexGuard<'_, Connection>> {
+        Ok(self.shard(namespace))
     }
     
-    /// Create a new in-memory storage engine (for testing)
-    pub fn in_memory() -> Result<Self> {
-        let conn = Connection::open_in_memory()?;
-        conn.execute_batch(SCHEMA)?;
-        
-        let temp_dir = std::env::temp_dir().join(format!("cloudemu-{}", uuid::Uuid::new_v4()));
-        fs::create_dir_all(&temp_dir)?;
-        
-        let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            objects_dir: temp_dir,
-        };
-
-        engine.init_ecs_tables()?;
-        engine.init_rds_tables()?;
-        engine.init_iam_tables()?;
-        engine.init_route53_tables()?;
-        engine.init_apigateway_tables()?;
-        engine.init_elb_tables()?;
-        engine.init_elasticache_tables()?;
-        engine.init_ecr_tables()?;
+    /// Connection of a namespace, unless another caller holds it
+    pub fn try_get_connection(&self, namespace: Namespace) -> Option<MutexGuard<'_, Connection>> {
+        self.shards[namespace as usize].try_lock()
+    }
 
-        Ok(engine)
+    pub(crate) fn shard(&self, namespace: Namespace) -> MutexGuard<'_, Connection> {
+        self.shards[namespace as usize].lock()
     }
     
     // Bucket Operations moved to s3.rs
@@ -116,6 +160,17 @@ impl StorageEngine {
     // EC2 Operations moved to ec2.rs
 }
 
+/// Every shard reaches the same database, so a snapshot through one of them holds them all
+impl StorageBackend for StorageEngine {
+    fn database(&self) -> &Arc<Mutex<Connection>> {
+        &self.shards[0]
+    }
+
+    fn blobs(&self) -> &FsBlobStore {
+        &self.blobs
+    }
+}
+
 /// Bucket metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BucketMetadata {
@@ -138,6 +193,139 @@ pub struct ObjectMetadata {
     pub content_type: String,
     pub storage_class: String,
     pub is_delete_marker: bool,
+    /// Replication status: PENDING, COMPLETED, FAILED (source) or REPLICA (destination)
+    pub replication_status: Option<String>,
+}
+
+/// A multipart upload in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultipartUpload {
+    pub upload_id: String,
+    pub bucket: String,
+    pub key: String,
+    pub initiated: String,
+    /// Content type and user metadata (JSON) the completed object gets
+    pub content_type: Option<String>,
+    pub metadata: Option<String>,
+}
+
+/// A part uploaded to a multipart upload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedPart {
+    pub part_number: i32,
+    /// Quoted hex MD5 of the part's data
+    pub etag: String,
+    pub size: u64,
+    pub last_modified: String,
+}
+
+/// Bucket replication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfiguration {
+    pub role: String,
+    pub rules: Vec<ReplicationRule>,
+}
+
+/// Bucket replication rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationRule {
+    pub id: String,
+    pub priority: i32,
+    pub status: String,
+    pub prefix: String,
+    pub destination_bucket: String,
+    pub destination_account: Option<String>,
+    pub storage_class: Option<String>,
+    pub delete_marker_replication: bool,
+}
+
+/// Bucket static website configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebsiteConfiguration {
+    pub index_document: Option<String>,
+    pub error_document: Option<String>,
+    pub redirect_all_requests_to: Option<WebsiteRedirect>,
+    pub routing_rules: Vec<RoutingRule>,
+}
+
+/// Redirect target used by website configurations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebsiteRedirect {
+    pub host_name: Option<String>,
+    pub protocol: Option<String>,
+    pub replace_key_prefix_with: Option<String>,
+    pub replace_key_with: Option<String>,
+    pub http_redirect_code: Option<String>,
+}
+
+/// Website routing rule: redirect when the condition matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub key_prefix_equals: Option<String>,
+    pub http_error_code_returned_equals: Option<String>,
+    pub redirect: WebsiteRedirect,
+}
+
+/// Bucket event notification configuration, one list per destination type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationConfiguration {
+    pub queue_configurations: Vec<NotificationRule>,
+    pub topic_configurations: Vec<NotificationRule>,
+    pub lambda_function_configurations: Vec<NotificationRule>,
+}
+
+/// Notification rule: deliver matching events for matching keys to a destination ARN
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationRule {
+    pub id: String,
+    pub destination_arn: String,
+    pub events: Vec<String>,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+}
+
+/// Bucket lifecycle configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifecycleConfiguration {
+    pub rules: Vec<LifecycleRule>,
+}
+
+/// Lifecycle rule: expire or transition the objects under a prefix once they are old enough.
+/// Ages are in days.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifecycleRule {
+    pub id: String,
+    pub status: String,
+    pub prefix: String,
+    /// Age at which current versions expire
+    pub expiration_days: Option<u32>,
+    /// Date (RFC 3339) from which current versions expire
+    pub expiration_date: Option<String>,
+    /// Remove delete markers that no longer have noncurrent versions behind them
+    pub expired_object_delete_marker: bool,
+    pub transitions: Vec<LifecycleTransition>,
+    /// Time after becoming noncurrent at which versions are deleted
+    pub noncurrent_version_expiration_days: Option<u32>,
+    pub noncurrent_version_transitions: Vec<LifecycleTransition>,
+    /// Age at which incomplete multipart uploads are aborted
+    pub abort_incomplete_multipart_upload_days: Option<u32>,
+}
+
+/// Move versions to a storage class after a number of days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleTransition {
+    pub days: u32,
+    pub storage_class: String,
+}
+
+/// What one pass over the lifecycle rules did
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LifecycleActions {
+    pub expired: usize,
+    pub transitioned: usize,
+    pub noncurrent_expired: usize,
+    pub delete_markers_removed: usize,
+    pub uploads_aborted: usize,
 }
 
 /// List objects result
@@ -257,6 +445,18 @@ pub struct LogEventMetadata {
     pub message: String,
 }
 
+/// CloudWatch Logs subscription filter metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionFilterMetadata {
+    pub log_group_name: String,
+    pub filter_name: String,
+    pub filter_pattern: String,
+    pub destination_arn: String,
+    pub role_arn: Option<String>,
+    pub distribution: String,
+    pub created_at: String,
+}
+
 /// Cognito User Pool metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPoolMetadata {
@@ -287,6 +487,19 @@ pub struct UserMetadata {
     pub created_at: String,
 }
 
+/// Cognito Identity Pool metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityPoolMetadata {
+    pub id: String,
+    pub name: String,
+    pub allow_unauthenticated: bool,
+    /// `CognitoIdentityProviders` entries: `ProviderName`, `ClientId`, `ServerSideTokenCheck`
+    pub providers: serde_json::Value,
+    /// Role ARNs by `authenticated` / `unauthenticated`
+    pub roles: std::collections::HashMap<String, String>,
+    pub created_at: String,
+}
+
 /// Step Functions State Machine metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateMachineMetadata {
@@ -322,6 +535,8 @@ pub struct QueueMetadata {
     pub message_retention_period: i32,
     pub delay_seconds: i32,
     pub receive_message_wait_time_seconds: i32,
+    /// Largest message body plus attributes the queue accepts, in bytes
+    pub maximum_message_size: i32,
 }
 
 /// SQS Message metadata
@@ -335,6 +550,10 @@ pub struct MessageMetadata {
     pub visible_at: String,
     pub receipt_handle: Option<String>,
     pub receive_count: i32,
+    /// SQS message attributes as JSON (`{"name": {"DataType": ..., "StringValue": ...}}`)
+    pub message_attributes: Option<String>,
+    /// System attributes set by the sender as JSON, e.g. `{"AWSTraceHeader": "Root=..."}`
+    pub system_attributes: Option<String>,
 }
 
 /// DynamoDB Table metadata
@@ -374,6 +593,50 @@ pub struct SubscriptionMetadata {
     pub protocol: String,
     pub endpoint: String,
     pub created_at: String,
+    /// Subscription attributes as JSON, e.g. `{"RawMessageDelivery": "true"}`
+    pub attributes: Option<String>,
+}
+
+/// SNS platform application (mobile push) metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformApplicationMetadata {
+    pub arn: String,
+    pub name: String,
+    /// `APNS`, `APNS_SANDBOX`, `GCM`, `ADM`, `BAIDU`, `MPNS` or `WNS`
+    pub platform: String,
+    /// Application attributes as JSON, e.g. `{"PlatformCredential": "..."}`
+    pub attributes: Option<String>,
+    pub created_at: String,
+}
+
+/// SNS platform endpoint: one device of a platform application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformEndpointMetadata {
+    pub arn: String,
+    pub application_arn: String,
+    pub token: String,
+    pub custom_user_data: Option<String>,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+/// An SMS or push notification SNS recorded in its sandbox instead of sending
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxMessage {
+    pub message_id: String,
+    /// `sms` or `push`
+    pub kind: String,
+    /// Phone number of an SMS, endpoint ARN of a push notification
+    pub destination: String,
+    /// Platform of a push notification's endpoint
+    pub platform: Option<String>,
+    pub message: String,
+    pub subject: Option<String>,
+    /// Message attributes as JSON, in the SQS API shape
+    pub attributes: Option<String>,
+    /// Topic the message was published to, unless it was sent directly
+    pub topic_arn: Option<String>,
+    pub sent_at: String,
 }
 
 /// Lambda metadata
@@ -393,6 +656,8 @@ pub struct VpcMetadata {
     pub cidr_block: String,
     pub state: String,
     pub is_default: bool,
+    pub enable_dns_support: bool,
+    pub enable_dns_hostnames: bool,
     pub tags: Option<String>,
 }
 
@@ -431,6 +696,7 @@ pub struct InstanceMetadata {
     pub vpc_id: Option<String>,
     pub subnet_id: Option<String>,
     pub security_groups: Vec<String>,
+    pub iam_instance_profile: Option<String>,
     pub launch_time: String,
     pub tags: Option<String>,
 }
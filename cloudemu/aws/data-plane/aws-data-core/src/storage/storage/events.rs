@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, EventBusMetadata, EventRuleMetadata, EventTargetMetadata};
+use super::engine::{StorageEngine, Namespace, EventBusMetadata, EventRuleMetadata, EventTargetMetadata};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -6,7 +6,7 @@ impl StorageEngine {
     // ==================== EventBridge Operations ====================
     
     pub fn create_event_bus(&self, name: &str, account_id: &str, region: &str) -> Result<EventBusMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let arn = format!("arn:aws:events:{}:{}:event-bus/{}", region, account_id, name);
         
         db.execute(
@@ -28,7 +28,7 @@ impl StorageEngine {
     }
     
     pub fn get_event_bus(&self, name: &str) -> Result<EventBusMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         db.query_row(
             "SELECT name, arn, policy FROM event_buses WHERE name = ?1",
             params![name],
@@ -41,7 +41,7 @@ impl StorageEngine {
     }
     
     pub fn list_event_buses(&self) -> Result<Vec<EventBusMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let mut stmt = db.prepare("SELECT name, arn, policy FROM event_buses")?;
         let buses = stmt.query_map([], |row| Ok(EventBusMetadata {
             name: row.get(0)?,
@@ -54,7 +54,7 @@ impl StorageEngine {
     }
     
     pub fn delete_event_bus(&self, name: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let rows = db.execute("DELETE FROM event_buses WHERE name = ?1", params![name])?;
         if rows == 0 {
             return Err(EmulatorError::NotFound("EventBus".into(), name.into()));
@@ -65,7 +65,7 @@ impl StorageEngine {
     // NOTE: This function will be refactored with a builder pattern in upcoming storage refactor
     #[allow(clippy::too_many_arguments)]
     pub fn put_rule(&self, name: &str, bus_name: &str, pattern: Option<&str>, state: &str, description: Option<&str>, schedule: Option<&str>, account_id: &str, region: &str) -> Result<String> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let now = chrono::Utc::now().to_rfc3339();
         let arn = format!("arn:aws:events:{}:{}:rule/{}/{}", region, account_id, bus_name, name);
         
@@ -85,7 +85,7 @@ impl StorageEngine {
     }
     
     pub fn list_rules(&self, bus_name: &str) -> Result<Vec<EventRuleMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let mut stmt = db.prepare(
             "SELECT name, event_bus_name, arn, event_pattern, state, description, schedule_expression, created_at 
              FROM event_rules WHERE event_bus_name = ?1"
@@ -106,7 +106,7 @@ impl StorageEngine {
     }
     
     pub fn put_targets(&self, bus_name: &str, rule_name: &str, targets: Vec<EventTargetMetadata>) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         for target in targets {
             db.execute(
                 r#"INSERT INTO event_targets (id, rule_name, event_bus_name, arn, input, input_path)
@@ -122,7 +122,7 @@ impl StorageEngine {
     }
     
     pub fn list_targets(&self, bus_name: &str, rule_name: &str) -> Result<Vec<EventTargetMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let mut stmt = db.prepare(
             "SELECT id, rule_name, event_bus_name, arn, input, input_path 
              FROM event_targets WHERE event_bus_name = ?1 AND rule_name = ?2"
@@ -141,7 +141,7 @@ impl StorageEngine {
     }
 
     pub fn record_event(&self, bus_name: &str, source: &str, detail_type: &str, detail: &str, resources: Option<&str>) -> Result<String> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Events);
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
         
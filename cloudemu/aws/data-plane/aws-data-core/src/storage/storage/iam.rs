@@ -1,7 +1,7 @@
-use super::StorageEngine;
-use crate::error::Result;
+use super::{StorageEngine, Namespace};
+use crate::error::{EmulatorError, Result};
 use serde::{Deserialize, Serialize};
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IamRole {
@@ -37,6 +37,27 @@ pub struct IamAccessKey {
     pub status: String,
 }
 
+/// Container for a role that EC2 instances launch with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IamInstanceProfile {
+    pub name: String,
+    pub arn: String,
+    pub path: String,
+    pub role_name: Option<String>,
+    pub created_at: i64,
+}
+
+/// Temporary credentials issued for a role session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IamSessionCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: String,
+    pub role_arn: String,
+    pub session_name: String,
+    pub expiration: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RolePolicyAttachment {
     pub role_name: String,
@@ -49,9 +70,12 @@ impl StorageEngine {
     const TABLE_IAM_USERS: &'static str = "aws_iam_users";
     const TABLE_IAM_ACCESS_KEYS: &'static str = "aws_iam_access_keys";
     const TABLE_IAM_ROLE_ATTACHMENTS: &'static str = "aws_iam_role_policy_attachments";
+    const TABLE_IAM_INSTANCE_PROFILES: &'static str = "aws_iam_instance_profiles";
+    const TABLE_IAM_SESSION_CREDENTIALS: &'static str = "aws_iam_session_credentials";
+    const TABLE_IAM_ACCOUNT_ALIASES: &'static str = "aws_iam_account_aliases";
 
     pub fn init_iam_tables(&self) -> Result<()> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Iam);
         
         conn.execute(&format!(
             "CREATE TABLE IF NOT EXISTS {} (
@@ -112,12 +136,44 @@ impl StorageEngine {
             Self::TABLE_IAM_ROLE_ATTACHMENTS
         ), [])?;
 
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                arn TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                path TEXT NOT NULL,
+                role_name TEXT,
+                created_at INTEGER,
+                UNIQUE(name)
+            )", 
+            Self::TABLE_IAM_INSTANCE_PROFILES
+        ), [])?;
+
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                access_key_id TEXT PRIMARY KEY,
+                secret_access_key TEXT NOT NULL,
+                session_token TEXT NOT NULL,
+                role_arn TEXT NOT NULL,
+                session_name TEXT NOT NULL,
+                expiration INTEGER NOT NULL
+            )", 
+            Self::TABLE_IAM_SESSION_CREDENTIALS
+        ), [])?;
+
+        conn.execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                alias TEXT PRIMARY KEY,
+                created_at INTEGER
+            )", 
+            Self::TABLE_IAM_ACCOUNT_ALIASES
+        ), [])?;
+
         Ok(())
     }
 
     // Role methods
     pub fn create_role(&self, name: &str, document: &str) -> Result<IamRole> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Iam);
         let arn = format!("arn:aws:iam::000000000000:role/{}", name);
         let path = "/";
 
@@ -138,7 +194,7 @@ impl StorageEngine {
     }
 
     pub fn get_role(&self, name: &str) -> Result<IamRole> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Iam);
         let mut stmt = conn.prepare(&format!("SELECT arn, path, assume_role_policy_document, description FROM {} WHERE name = ?1", Self::TABLE_IAM_ROLES))?;
         
         let role = stmt.query_row(params![name], |row| {
@@ -149,13 +205,13 @@ impl StorageEngine {
                 assume_role_policy_document: row.get(2)?,
                 description: row.get(3)?,
             })
-        })?;
+        }).optional()?.ok_or_else(|| EmulatorError::NotFound("Role".into(), name.into()))?;
 
         Ok(role)
     }
 
     pub fn list_roles(&self) -> Result<Vec<IamRole>> {
-        let conn = self.get_connection()?;
+        let conn = self.shard(Namespace::Iam);
         let mut stmt = conn.prepare(&format!("SELECT name, arn, path, assume_role_policy_document, description FROM {}", Self::TABLE_IAM_ROLES))?;
         
         let roles = stmt.query_map([], |row| {
@@ -174,7 +230,7 @@ impl StorageEngine {
 
     // Policy methods
     pub fn create_policy(&self, name: &str, document: &str) -> Result<IamPolicy> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Iam);
         let arn = format!("arn:aws:iam::000000000000:policy/{}", name);
         let path = "/";
         let version = "v1";
@@ -196,7 +252,7 @@ impl StorageEngine {
     }
     
     pub fn list_policies(&self) -> Result<Vec<IamPolicy>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Iam);
         let mut stmt = conn.prepare(&format!("SELECT name, arn, path, default_version_id, document FROM {}", Self::TABLE_IAM_POLICIES))?;
         
         let policies = stmt.query_map([], |row| {
@@ -214,7 +270,7 @@ impl StorageEngine {
     }
 
     pub fn attach_role_policy(&self, role_name: &str, policy_arn: &str) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Iam);
         conn.execute(
             &format!("INSERT OR IGNORE INTO {} (role_name, policy_arn, created_at) VALUES (?1, ?2, ?3)", Self::TABLE_IAM_ROLE_ATTACHMENTS),
             params![role_name, policy_arn, chrono::Utc::now().timestamp()],
@@ -224,7 +280,7 @@ impl StorageEngine {
 
     // User methods
     pub fn create_user(&self, name: &str) -> Result<IamUser> {
-         let conn = self.db.lock();
+         let conn = self.shard(Namespace::Iam);
          let id = format!("AIDA{}", uuid::Uuid::new_v4().to_string().replace("-","").to_uppercase()[..16].to_string());
          let arn = format!("arn:aws:iam::000000000000:user/{}", name);
          let path = "/";
@@ -246,7 +302,7 @@ impl StorageEngine {
 
 
     pub fn list_users(&self) -> Result<Vec<IamUser>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Iam);
         let mut stmt = conn.prepare(&format!("SELECT id, name, arn, path FROM {}", Self::TABLE_IAM_USERS))?;
         
         let users = stmt.query_map([], |row| {
@@ -264,7 +320,7 @@ impl StorageEngine {
 
     // Access Key methods
     pub fn create_access_key(&self, user_name: &str) -> Result<IamAccessKey> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Iam);
         let access_key = format!("AKIA{}", uuid::Uuid::new_v4().to_string().replace("-","").to_uppercase()[..16].to_string());
         let secret = uuid::Uuid::new_v4().to_string().replace("-",""); // simple secret
         let status = "Active";
@@ -283,4 +339,167 @@ impl StorageEngine {
             status: status.to_string(),
         })
     }
+
+    /// Secret of an active access key or of unexpired session credentials
+    pub fn find_secret_access_key(&self, access_key_id: &str) -> Result<Option<String>> {
+        let conn = self.shard(Namespace::Iam);
+        let secret = conn.query_row(
+            &format!("SELECT secret_access_key FROM {} WHERE access_key_id = ?1 AND status = 'Active'", Self::TABLE_IAM_ACCESS_KEYS),
+            params![access_key_id],
+            |row| row.get(0),
+        ).optional()?;
+        if secret.is_some() {
+            return Ok(secret);
+        }
+        let secret = conn.query_row(
+            &format!("SELECT secret_access_key FROM {} WHERE access_key_id = ?1 AND expiration > ?2", Self::TABLE_IAM_SESSION_CREDENTIALS),
+            params![access_key_id, chrono::Utc::now().timestamp()],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(secret)
+    }
+
+    // Instance profile methods
+    pub fn create_instance_profile(&self, name: &str, path: &str) -> Result<IamInstanceProfile> {
+        let conn = self.shard(Namespace::Iam);
+        let profile = IamInstanceProfile {
+            name: name.to_string(),
+            arn: format!("arn:aws:iam::000000000000:instance-profile{}{}", path, name),
+            path: path.to_string(),
+            role_name: None,
+            created_at: chrono::Utc::now().timestamp(),
+        };
+        let inserted = conn.execute(
+            &format!("INSERT OR IGNORE INTO {} (arn, name, path, created_at) VALUES (?1, ?2, ?3, ?4)", Self::TABLE_IAM_INSTANCE_PROFILES),
+            params![profile.arn, profile.name, profile.path, profile.created_at],
+        )?;
+        if inserted == 0 {
+            return Err(EmulatorError::AlreadyExists(format!("Instance Profile {} already exists.", name)));
+        }
+        Ok(profile)
+    }
+
+    /// Look up an instance profile by name or ARN
+    pub fn get_instance_profile(&self, name_or_arn: &str) -> Result<IamInstanceProfile> {
+        let conn = self.shard(Namespace::Iam);
+        conn.query_row(
+            &format!("SELECT name, arn, path, role_name, created_at FROM {} WHERE name = ?1 OR arn = ?1", Self::TABLE_IAM_INSTANCE_PROFILES),
+            params![name_or_arn],
+            Self::row_to_instance_profile,
+        ).optional()?.ok_or_else(|| EmulatorError::NotFound("InstanceProfile".into(), name_or_arn.into()))
+    }
+
+    pub fn list_instance_profiles(&self) -> Result<Vec<IamInstanceProfile>> {
+        let conn = self.shard(Namespace::Iam);
+        let mut stmt = conn.prepare(&format!("SELECT name, arn, path, role_name, created_at FROM {} ORDER BY name", Self::TABLE_IAM_INSTANCE_PROFILES))?;
+        let profiles = stmt.query_map([], Self::row_to_instance_profile)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(profiles)
+    }
+
+    fn row_to_instance_profile(row: &rusqlite::Row) -> rusqlite::Result<IamInstanceProfile> {
+        Ok(IamInstanceProfile {
+            name: row.get(0)?,
+            arn: row.get(1)?,
+            path: row.get(2)?,
+            role_name: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    /// Set or clear the role of an instance profile
+    pub fn set_instance_profile_role(&self, name: &str, role_name: Option<&str>) -> Result<()> {
+        let conn = self.shard(Namespace::Iam);
+        let rows = conn.execute(
+            &format!("UPDATE {} SET role_name = ?1 WHERE name = ?2", Self::TABLE_IAM_INSTANCE_PROFILES),
+            params![role_name, name],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("InstanceProfile".into(), name.into()));
+        }
+        Ok(())
+    }
+
+    pub fn delete_instance_profile(&self, name: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Iam);
+        let rows = conn.execute(&format!("DELETE FROM {} WHERE name = ?1", Self::TABLE_IAM_INSTANCE_PROFILES), params![name])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("InstanceProfile".into(), name.into()));
+        }
+        Ok(())
+    }
+
+    // Account alias methods
+    /// Set the account alias; an account has at most one, so any previous alias is replaced
+    pub fn create_account_alias(&self, alias: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Iam);
+        conn.execute(&format!("DELETE FROM {}", Self::TABLE_IAM_ACCOUNT_ALIASES), [])?;
+        conn.execute(
+            &format!("INSERT INTO {} (alias, created_at) VALUES (?1, ?2)", Self::TABLE_IAM_ACCOUNT_ALIASES),
+            params![alias, chrono::Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_account_aliases(&self) -> Result<Vec<String>> {
+        let conn = self.shard(Namespace::Iam);
+        let mut stmt = conn.prepare(&format!("SELECT alias FROM {}", Self::TABLE_IAM_ACCOUNT_ALIASES))?;
+        let aliases = stmt.query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(aliases)
+    }
+
+    pub fn delete_account_alias(&self, alias: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Iam);
+        let rows = conn.execute(&format!("DELETE FROM {} WHERE alias = ?1", Self::TABLE_IAM_ACCOUNT_ALIASES), params![alias])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("AccountAlias".into(), alias.into()));
+        }
+        Ok(())
+    }
+
+    // Session credential methods
+    /// Issue temporary credentials for a role session valid for `duration_secs`
+    pub fn create_session_credentials(&self, role_arn: &str, session_name: &str, duration_secs: i64) -> Result<IamSessionCredentials> {
+        let conn = self.shard(Namespace::Iam);
+        let token = || uuid::Uuid::new_v4().simple().to_string();
+        let credentials = IamSessionCredentials {
+            access_key_id: format!("ASIA{}", &token().to_uppercase()[..16]),
+            secret_access_key: format!("{}{}", token(), &token()[..8]),
+            session_token: format!("FwoGZXIvYXdzE{}{}{}", token(), token(), token()),
+            role_arn: role_arn.to_string(),
+            session_name: session_name.to_string(),
+            expiration: chrono::Utc::now().timestamp() + duration_secs,
+        };
+        conn.execute(
+            &format!("INSERT INTO {} (
+                access_key_id, secret_access_key, session_token, role_arn, session_name, expiration
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)", Self::TABLE_IAM_SESSION_CREDENTIALS),
+            params![
+                credentials.access_key_id, credentials.secret_access_key, credentials.session_token,
+                credentials.role_arn, credentials.session_name, credentials.expiration
+            ],
+        )?;
+        Ok(credentials)
+    }
+
+    /// Latest unexpired credentials of a role session that stay valid for at least `min_remaining_secs`
+    pub fn find_session_credentials(&self, role_arn: &str, session_name: &str, min_remaining_secs: i64) -> Result<Option<IamSessionCredentials>> {
+        let conn = self.shard(Namespace::Iam);
+        let credentials = conn.query_row(
+            &format!("SELECT access_key_id, secret_access_key, session_token, role_arn, session_name, expiration
+                FROM {} WHERE role_arn = ?1 AND session_name = ?2 AND expiration > ?3
+                ORDER BY expiration DESC LIMIT 1", Self::TABLE_IAM_SESSION_CREDENTIALS),
+            params![role_arn, session_name, chrono::Utc::now().timestamp() + min_remaining_secs],
+            |row| Ok(IamSessionCredentials {
+                access_key_id: row.get(0)?,
+                secret_access_key: row.get(1)?,
+                session_token: row.get(2)?,
+                role_arn: row.get(3)?,
+                session_name: row.get(4)?,
+                expiration: row.get(5)?,
+            }),
+        ).optional()?;
+        Ok(credentials)
+    }
 }
@@ -1,12 +1,12 @@
-use super::engine::{StorageEngine, UserPoolMetadata, UserMetadata, UserGroupMetadata};
+use super::engine::{StorageEngine, Namespace, UserPoolMetadata, UserMetadata, UserGroupMetadata, IdentityPoolMetadata};
 use crate::error::{EmulatorError, Result};
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension, Row};
 
 impl StorageEngine {
     // ==================== Cognito Operations ====================
     
     pub fn create_user_pool(&self, name: &str, account_id: &str, region: &str) -> Result<UserPoolMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let pool_id = format!("{}_{}", region, uuid::Uuid::new_v4().to_string().replace("-", ""));
         let arn = format!("arn:aws:cognito-idp:{}:{}:userpool/{}", region, account_id, pool_id);
         let now = chrono::Utc::now().to_rfc3339();
@@ -25,7 +25,7 @@ impl StorageEngine {
     }
 
     pub fn list_user_pools(&self) -> Result<Vec<UserPoolMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let mut stmt = db.prepare("SELECT id, name, arn, created_at FROM cognito_user_pools")?;
         let pools = stmt.query_map([], |row| Ok(UserPoolMetadata {
             id: row.get(0)?,
@@ -39,7 +39,7 @@ impl StorageEngine {
     }
 
     pub fn admin_create_user(&self, pool_id: &str, username: &str, attributes: Vec<(String, String)>) -> Result<UserMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let now = chrono::Utc::now().to_rfc3339();
         
         let email = attributes.iter().find(|(n, _)| n == "email").map(|(_, v)| v.to_string());
@@ -73,7 +73,7 @@ impl StorageEngine {
     }
 
     pub fn admin_get_user(&self, pool_id: &str, username: &str) -> Result<(UserMetadata, Vec<(String, String)>)> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let user = db.query_row(
             "SELECT user_pool_id, username, email, status, enabled, created_at FROM cognito_users WHERE user_pool_id = ?1 AND username = ?2",
             params![pool_id, username],
@@ -96,7 +96,7 @@ impl StorageEngine {
     }
 
     pub fn create_group(&self, pool_id: &str, group_name: &str, description: Option<&str>, precedence: Option<i32>) -> Result<UserGroupMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let now = chrono::Utc::now().to_rfc3339();
         
         db.execute(
@@ -120,7 +120,7 @@ impl StorageEngine {
     }
 
     pub fn list_groups(&self, pool_id: &str) -> Result<Vec<UserGroupMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Identity);
         let mut stmt = db.prepare("SELECT user_pool_id, group_name, description, precedence, created_at FROM cognito_groups WHERE user_pool_id = ?1")?;
         let groups = stmt.query_map(params![pool_id], |row| Ok(UserGroupMetadata {
             user_pool_id: row.get(0)?,
@@ -133,4 +133,116 @@ impl StorageEngine {
         .collect();
         Ok(groups)
     }
+
+    // ==================== Cognito Identity Operations ====================
+
+    pub fn create_identity_pool(&self, name: &str, allow_unauthenticated: bool, providers: &serde_json::Value, region: &str) -> Result<IdentityPoolMetadata> {
+        let db = self.shard(Namespace::Identity);
+        let pool_id = format!("{}:{}", region, uuid::Uuid::new_v4());
+        let now = chrono::Utc::now().to_rfc3339();
+
+        db.execute(
+            "INSERT INTO cognito_identity_pools (id, name, allow_unauthenticated, providers, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![pool_id, name, allow_unauthenticated, providers.to_string(), now],
+        )?;
+
+        Ok(IdentityPoolMetadata {
+            id: pool_id,
+            name: name.to_string(),
+            allow_unauthenticated,
+            providers: providers.clone(),
+            roles: Default::default(),
+            created_at: now,
+        })
+    }
+
+    fn identity_pool_from_row(row: &Row) -> rusqlite::Result<IdentityPoolMetadata> {
+        let providers: String = row.get(3)?;
+        let roles: String = row.get(4)?;
+        Ok(IdentityPoolMetadata {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            allow_unauthenticated: row.get(2)?,
+            providers: serde_json::from_str(&providers).unwrap_or_default(),
+            roles: serde_json::from_str(&roles).unwrap_or_default(),
+            created_at: row.get(5)?,
+        })
+    }
+
+    pub fn get_identity_pool(&self, pool_id: &str) -> Result<IdentityPoolMetadata> {
+        let db = self.shard(Namespace::Identity);
+        db.query_row(
+            "SELECT id, name, allow_unauthenticated, providers, roles, created_at FROM cognito_identity_pools WHERE id = ?1",
+            params![pool_id],
+            Self::identity_pool_from_row,
+        ).optional()?
+        .ok_or_else(|| EmulatorError::NotFound("IdentityPool".into(), pool_id.into()))
+    }
+
+    pub fn list_identity_pools(&self) -> Result<Vec<IdentityPoolMetadata>> {
+        let db = self.shard(Namespace::Identity);
+        let mut stmt = db.prepare("SELECT id, name, allow_unauthenticated, providers, roles, created_at FROM cognito_identity_pools ORDER BY created_at")?;
+        let pools = stmt.query_map([], Self::identity_pool_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(pools)
+    }
+
+    pub fn delete_identity_pool(&self, pool_id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Identity);
+        db.execute("DELETE FROM cognito_identities WHERE identity_pool_id = ?1", params![pool_id])?;
+        let rows = db.execute("DELETE FROM cognito_identity_pools WHERE id = ?1", params![pool_id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("IdentityPool".into(), pool_id.into()));
+        }
+        Ok(())
+    }
+
+    /// Replace the roles identities of a pool assume
+    pub fn set_identity_pool_roles(&self, pool_id: &str, roles: &std::collections::HashMap<String, String>) -> Result<()> {
+        let db = self.shard(Namespace::Identity);
+        let rows = db.execute(
+            "UPDATE cognito_identity_pools SET roles = ?2 WHERE id = ?1",
+            params![pool_id, serde_json::to_string(roles)?],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("IdentityPool".into(), pool_id.into()));
+        }
+        Ok(())
+    }
+
+    /// Identity ID of a login (`provider/subject`) in a pool, created on first use; every
+    /// call without a login creates a new unauthenticated identity
+    pub fn get_or_create_identity(&self, pool_id: &str, login: Option<&str>) -> Result<String> {
+        let db = self.shard(Namespace::Identity);
+        if let Some(login) = login {
+            let existing = db.query_row(
+                "SELECT identity_id FROM cognito_identities WHERE identity_pool_id = ?1 AND login = ?2",
+                params![pool_id, login],
+                |row| row.get(0),
+            ).optional()?;
+            if let Some(identity_id) = existing {
+                return Ok(identity_id);
+            }
+        }
+        // Identity IDs carry the pool's region, as in us-east-1:<uuid>
+        let region = pool_id.split(':').next().unwrap_or_default();
+        let identity_id = format!("{}:{}", region, uuid::Uuid::new_v4());
+        db.execute(
+            "INSERT INTO cognito_identities (identity_id, identity_pool_id, login, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![identity_id, pool_id, login, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(identity_id)
+    }
+
+    /// Pool and login of an identity
+    pub fn get_identity(&self, identity_id: &str) -> Result<(String, Option<String>)> {
+        let db = self.shard(Namespace::Identity);
+        db.query_row(
+            "SELECT identity_pool_id, login FROM cognito_identities WHERE identity_id = ?1",
+            params![identity_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?
+        .ok_or_else(|| EmulatorError::NotFound("Identity".into(), identity_id.into()))
+    }
 }
@@ -0,0 +1,196 @@
+use super::{StorageEngine, Namespace};
+use crate::error::{EmulatorError, Result};
+use base64::{Engine as _, engine::general_purpose};
+use emu_storage::StreamStore;
+use serde::{Deserialize, Serialize};
+use rusqlite::params;
+
+/// Kinesis data stream. Streams have a single shard whose records are kept in the
+/// shared stream store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KinesisStream {
+    pub name: String,
+    pub arn: String,
+    pub status: String,
+    pub retention_period_hours: i32,
+    pub created_at: String,
+}
+
+/// Record of a Kinesis stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KinesisRecord {
+    pub sequence_number: u64,
+    pub partition_key: String,
+    pub data: Vec<u8>,
+    /// Epoch milliseconds
+    pub arrival_timestamp: i64,
+}
+
+/// Record as appended to the stream store
+#[derive(Serialize, Deserialize)]
+struct StoredRecord {
+    partition_key: String,
+    data: String,
+    arrival_timestamp: i64,
+}
+
+fn stream_key(name: &str) -> String {
+    format!("kinesis/{}", name)
+}
+
+impl StorageEngine {
+    pub fn init_kinesis_tables(&self) -> Result<()> {
+        let conn = self.shard(Namespace::Kinesis);
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS kinesis_streams (
+                name TEXT PRIMARY KEY,
+                arn TEXT NOT NULL,
+                status TEXT NOT NULL,
+                retention_period_hours INTEGER NOT NULL DEFAULT 24,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn create_kinesis_stream(&self, name: &str, account_id: &str, region: &str) -> Result<KinesisStream> {
+        let conn = self.shard(Namespace::Kinesis);
+        let stream = KinesisStream {
+            name: name.to_string(),
+            arn: format!("arn:aws:kinesis:{}:{}:stream/{}", region, account_id, name),
+            status: "ACTIVE".to_string(),
+            retention_period_hours: 24,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        conn.execute(
+            "INSERT INTO kinesis_streams (name, arn, status, retention_period_hours, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![stream.name, stream.arn, stream.status, stream.retention_period_hours, stream.created_at],
+        ).map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint") {
+                EmulatorError::AlreadyExists(format!("Stream {} already exists", name))
+            } else {
+                EmulatorError::Database(e.to_string())
+            }
+        })?;
+
+        Ok(stream)
+    }
+
+    pub fn get_kinesis_stream(&self, name: &str) -> Result<KinesisStream> {
+        let conn = self.shard(Namespace::Kinesis);
+
+        conn.query_row(
+            "SELECT name, arn, status, retention_period_hours, created_at FROM kinesis_streams WHERE name = ?1",
+            params![name],
+            row_to_stream,
+        ).map_err(|_| EmulatorError::NotFound("Stream".into(), name.into()))
+    }
+
+    pub fn list_kinesis_streams(&self) -> Result<Vec<KinesisStream>> {
+        let conn = self.shard(Namespace::Kinesis);
+        let mut stmt = conn.prepare(
+            "SELECT name, arn, status, retention_period_hours, created_at FROM kinesis_streams ORDER BY name"
+        )?;
+
+        let streams = stmt.query_map([], row_to_stream)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(streams)
+    }
+
+    /// Delete a stream and its records
+    pub fn delete_kinesis_stream(&self, name: &str) -> Result<()> {
+        let rows = self.shard(Namespace::Kinesis)
+            .execute("DELETE FROM kinesis_streams WHERE name = ?1", params![name])?;
+
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Stream".into(), name.into()));
+        }
+        let key = stream_key(name);
+        self.streams.trim(&key, self.streams.last_seq(&key)?)?;
+        Ok(())
+    }
+
+    /// Append a record to a stream, returning its sequence number
+    pub fn put_kinesis_record(&self, stream_name: &str, partition_key: &str, data: &[u8]) -> Result<u64> {
+        self.get_kinesis_stream(stream_name)?;
+
+        let record = StoredRecord {
+            partition_key: partition_key.to_string(),
+            data: general_purpose::STANDARD.encode(data),
+            arrival_timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+        let bytes = serde_json::to_vec(&record)?;
+        Ok(self.streams.append(&stream_key(stream_name), &bytes)?)
+    }
+
+    /// Up to `limit` records of a stream after the sequence number `after`, oldest first
+    pub fn get_kinesis_records(&self, stream_name: &str, after: u64, limit: usize) -> Result<Vec<KinesisRecord>> {
+        self.get_kinesis_stream(stream_name)?;
+
+        self.streams.read(&stream_key(stream_name), after, limit)?
+            .into_iter()
+            .map(|record| {
+                let stored: StoredRecord = serde_json::from_slice(&record.data)?;
+                Ok(KinesisRecord {
+                    sequence_number: record.seq,
+                    partition_key: stored.partition_key,
+                    data: general_purpose::STANDARD.decode(stored.data)
+                        .map_err(|e| EmulatorError::Internal(e.to_string()))?,
+                    arrival_timestamp: stored.arrival_timestamp,
+                })
+            })
+            .collect()
+    }
+
+    /// Sequence number of the last record put to a stream, 0 if there is none
+    pub fn latest_kinesis_sequence(&self, stream_name: &str) -> Result<u64> {
+        self.get_kinesis_stream(stream_name)?;
+        Ok(self.streams.last_seq(&stream_key(stream_name))?)
+    }
+}
+
+fn row_to_stream(row: &rusqlite::Row) -> rusqlite::Result<KinesisStream> {
+    Ok(KinesisStream {
+        name: row.get(0)?,
+        arn: row.get(1)?,
+        status: row.get(2)?,
+        retention_period_hours: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinesis_records() {
+        let engine = StorageEngine::in_memory().unwrap();
+        let stream = engine.create_kinesis_stream("clicks", "000000000000", "us-east-1").unwrap();
+        assert_eq!(stream.arn, "arn:aws:kinesis:us-east-1:000000000000:stream/clicks");
+        assert!(matches!(
+            engine.create_kinesis_stream("clicks", "000000000000", "us-east-1"),
+            Err(EmulatorError::AlreadyExists(_))
+        ));
+        assert!(engine.put_kinesis_record("views", "user-1", b"{}").is_err());
+
+        assert_eq!(engine.put_kinesis_record("clicks", "user-1", b"first").unwrap(), 1);
+        assert_eq!(engine.put_kinesis_record("clicks", "user-2", b"second").unwrap(), 2);
+        assert_eq!(engine.latest_kinesis_sequence("clicks").unwrap(), 2);
+
+        let records = engine.get_kinesis_records("clicks", 1, 10).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].partition_key, "user-2");
+        assert_eq!(records[0].data, b"second");
+
+        engine.delete_kinesis_stream("clicks").unwrap();
+        assert!(engine.list_kinesis_streams().unwrap().is_empty());
+        engine.create_kinesis_stream("clicks", "000000000000", "us-east-1").unwrap();
+        assert!(engine.get_kinesis_records("clicks", 0, 10).unwrap().is_empty());
+    }
+}
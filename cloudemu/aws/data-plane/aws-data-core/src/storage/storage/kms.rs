@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, KmsKeyMetadata};
+use super::engine::{StorageEngine, Namespace, KmsKeyMetadata};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -6,7 +6,7 @@ impl StorageEngine {
     // ==================== KMS Operations ====================
 
     pub fn create_key(&self, description: Option<&str>, key_usage: &str, tags: Option<&str>, account_id: &str, region: &str) -> Result<KmsKeyMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Kms);
         let key_id = uuid::Uuid::new_v4().to_string();
         let arn = format!("arn:aws:kms:{}:{}:key/{}", region, account_id, key_id);
         let now = chrono::Utc::now().to_rfc3339();
@@ -28,7 +28,7 @@ impl StorageEngine {
     }
     
     pub fn get_key(&self, key_id: &str) -> Result<KmsKeyMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Kms);
         db.query_row(
             "SELECT id, arn, description, key_usage, key_state, created_at, tags FROM kms_keys WHERE id = ?1 OR arn = ?1",
             params![key_id],
@@ -53,7 +53,7 @@ impl StorageEngine {
     }
     
     fn set_key_state(&self, key_id: &str, state: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Kms);
         let rows = db.execute(
              "UPDATE kms_keys SET key_state = ?1 WHERE id = ?2 OR arn = ?2",
              params![state, key_id]
@@ -65,7 +65,7 @@ impl StorageEngine {
     }
 
     pub fn list_keys(&self) -> Result<Vec<KmsKeyMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Kms);
         let mut stmt = db.prepare("SELECT id, arn, description, key_usage, key_state, created_at, tags FROM kms_keys")
             .map_err(|e| EmulatorError::Database(e.to_string()))?;
             
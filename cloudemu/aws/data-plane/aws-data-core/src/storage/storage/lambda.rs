@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, LambdaMetadata};
+use super::engine::{StorageEngine, Namespace, LambdaMetadata};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -21,7 +21,7 @@ impl StorageEngine {
         
         let code_hash = self.store_object_data(params.code_bytes)?;
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Lambda);
         db.execute(
             "INSERT INTO lambda_functions (name, arn, runtime, role, handler, code_hash, last_modified) VALUES (?, ?, ?, ?, ?, ?, ?)",
             params![params.name, arn, params.runtime, params.role, params.handler, code_hash, last_modified],
@@ -43,7 +43,7 @@ impl StorageEngine {
     }
 
     pub fn get_function(&self, name: &str) -> Result<LambdaMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Lambda);
         let mut stmt = db.prepare("SELECT name, arn, runtime, handler, last_modified FROM lambda_functions WHERE name = ? OR arn = ?")?;
         let row = stmt.query_row(params![name, name], |row| {
             Ok(LambdaMetadata {
@@ -59,7 +59,7 @@ impl StorageEngine {
     }
 
     pub fn get_function_code(&self, name: &str) -> Result<Vec<u8>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Lambda);
         let code_hash: String = db.query_row(
             "SELECT code_hash FROM lambda_functions WHERE name = ? OR arn = ?",
             params![name, name],
@@ -71,7 +71,7 @@ impl StorageEngine {
     }
 
     pub fn list_functions(&self) -> Result<Vec<LambdaMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Lambda);
         let mut stmt = db.prepare(
             "SELECT name, arn, runtime, handler, last_modified FROM lambda_functions ORDER BY name"
         )?;
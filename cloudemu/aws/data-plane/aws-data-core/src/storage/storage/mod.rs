@@ -24,29 +24,51 @@ mod apigateway;
 mod elb;
 mod elasticache;
 mod ecr;
+mod pipes;
+mod cloudtrail;
+mod kinesis;
 
 pub use engine::{
-    StorageEngine, BucketMetadata, ObjectMetadata, ListObjectsResult,
+    StorageEngine, Namespace, BucketMetadata, ObjectMetadata, ListObjectsResult,
+    MultipartUpload, UploadedPart,
+    ReplicationConfiguration, ReplicationRule,
+    WebsiteConfiguration, WebsiteRedirect, RoutingRule,
+    NotificationConfiguration, NotificationRule,
+    LifecycleConfiguration, LifecycleRule, LifecycleTransition, LifecycleActions,
     SecretMetadata, SecretValue, KmsKeyMetadata,
     EventBusMetadata, EventRuleMetadata, EventTargetMetadata,
     MetricMetadata, LogGroupMetadata, LogStreamMetadata, LogEventMetadata,
-    UserPoolMetadata, UserGroupMetadata, UserMetadata,
+    SubscriptionFilterMetadata,
+    UserPoolMetadata, UserGroupMetadata, UserMetadata, IdentityPoolMetadata,
     StateMachineMetadata, ExecutionMetadata,
     QueueMetadata, MessageMetadata,
     TableMetadata, ItemMetadata,
     TopicMetadata, SubscriptionMetadata, LambdaMetadata,
+    PlatformApplicationMetadata, PlatformEndpointMetadata, SandboxMessage,
     VpcMetadata, SubnetMetadata, SecurityGroupMetadata,
     InstanceMetadata, KeyPairMetadata,
 };
 
-pub use ecs::{EcsCluster, EcsTaskDefinition, ContainerDefinition, PortMapping};
+pub use ecs::{
+    EcsCluster, EcsTaskDefinition, ContainerDefinition, PortMapping,
+    EcsServiceMetadata, EcsDeployment, EcsTask, EcsServiceEvent,
+};
 pub use rds::{RdsInstance};
-pub use iam::{IamRole, IamPolicy, IamUser, IamAccessKey};
+pub use iam::{IamRole, IamPolicy, IamUser, IamAccessKey, IamInstanceProfile, IamSessionCredentials};
 pub use route53::{HostedZone, ResourceRecordSet, ResourceRecord};
 pub use apigateway::{ApiGateway, ApiResource, ApiMethod};
 pub use elb::{LoadBalancer, TargetGroup};
 pub use elasticache::{CacheCluster};
 pub use ecr::{EcrRepository};
+pub use pipes::Pipe;
+pub use cloudtrail::CloudTrailEvent;
+pub use dynamodb::{TableStream, TableStreamRecord};
+pub use kinesis::{KinesisStream, KinesisRecord};
+pub use s3::{ObjectWriter, StoredData};
+pub use vpc::{
+    InternetGatewayMetadata, RouteMetadata, RouteTableAssociationMetadata,
+    RouteTableMetadata, NatGatewayMetadata,
+};
 
 pub use pricing::{Product, OfferTerm};
 
@@ -1,4 +1,7 @@
-use super::engine::{StorageEngine, MetricMetadata, LogGroupMetadata, LogStreamMetadata, LogEventMetadata};
+use super::engine::{
+    StorageEngine, Namespace, MetricMetadata, LogGroupMetadata, LogStreamMetadata, LogEventMetadata,
+    SubscriptionFilterMetadata,
+};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -6,7 +9,7 @@ impl StorageEngine {
     // ==================== CloudWatch Operations ====================
     
     pub fn put_metric_data(&self, namespace: &str, metrics: Vec<MetricMetadata>) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         for m in metrics {
             db.execute(
                 "INSERT INTO cw_metrics (namespace, metric_name, dimensions, value, unit, timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
@@ -17,7 +20,7 @@ impl StorageEngine {
     }
 
     pub fn list_metrics(&self, namespace: Option<&str>, metric_name: Option<&str>) -> Result<Vec<MetricMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         let mut query = "SELECT namespace, metric_name, dimensions, value, unit, timestamp FROM cw_metrics WHERE 1=1".to_string();
         let mut args: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
 
@@ -49,7 +52,7 @@ impl StorageEngine {
     }
 
     pub fn create_log_group(&self, name: &str, account_id: &str, region: &str) -> Result<LogGroupMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         let arn = format!("arn:aws:logs:{}:{}:log-group:{}", region, account_id, name);
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -73,7 +76,7 @@ impl StorageEngine {
     }
 
     pub fn delete_log_group(&self, name: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         let rows = db.execute("DELETE FROM cw_log_groups WHERE name = ?1", params![name])?;
         if rows == 0 {
             return Err(EmulatorError::NotFound("LogGroup".into(), name.into()));
@@ -82,7 +85,7 @@ impl StorageEngine {
     }
 
     pub fn create_log_stream(&self, group_name: &str, stream_name: &str, account_id: &str, region: &str) -> Result<LogStreamMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         let arn = format!("arn:aws:logs:{}:{}:log-group:{}:log-stream:{}", region, account_id, group_name, stream_name);
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -106,7 +109,7 @@ impl StorageEngine {
     }
 
     pub fn put_log_events(&self, group_name: &str, stream_name: &str, events: Vec<LogEventMetadata>) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         for e in events {
             db.execute(
                 "INSERT INTO cw_log_events (log_group_name, log_stream_name, timestamp, message) VALUES (?1, ?2, ?3, ?4)",
@@ -117,7 +120,7 @@ impl StorageEngine {
     }
 
     pub fn get_log_events(&self, group_name: &str, stream_name: &str) -> Result<Vec<LogEventMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Monitoring);
         let mut stmt = db.prepare(
             "SELECT timestamp, message FROM cw_log_events WHERE log_group_name = ?1 AND log_stream_name = ?2 ORDER BY timestamp"
         )?;
@@ -129,6 +132,75 @@ impl StorageEngine {
         .collect();
         Ok(events)
     }
+
+    // ==================== Subscription Filters ====================
+
+    /// Create or replace a subscription filter. A log group holds at most two filters.
+    pub fn put_subscription_filter(&self, filter: &SubscriptionFilterMetadata) -> Result<()> {
+        let db = self.shard(Namespace::Monitoring);
+        
+        let group_exists: bool = db.query_row(
+            "SELECT COUNT(*) > 0 FROM cw_log_groups WHERE name = ?1",
+            params![filter.log_group_name],
+            |row| row.get(0),
+        )?;
+        if !group_exists {
+            return Err(EmulatorError::NotFound("LogGroup".into(), filter.log_group_name.clone()));
+        }
+        
+        let others: i64 = db.query_row(
+            "SELECT COUNT(*) FROM cw_subscription_filters WHERE log_group_name = ?1 AND filter_name != ?2",
+            params![filter.log_group_name, filter.filter_name],
+            |row| row.get(0),
+        )?;
+        if others >= 2 {
+            return Err(EmulatorError::InvalidRequest(
+                format!("Log group {} already has the maximum number of subscription filters", filter.log_group_name)
+            ));
+        }
+        
+        db.execute(
+            "INSERT OR REPLACE INTO cw_subscription_filters (log_group_name, filter_name, filter_pattern, destination_arn, role_arn, distribution, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                filter.log_group_name, filter.filter_name, filter.filter_pattern, filter.destination_arn,
+                filter.role_arn, filter.distribution, filter.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn describe_subscription_filters(&self, group_name: &str, name_prefix: Option<&str>) -> Result<Vec<SubscriptionFilterMetadata>> {
+        let db = self.shard(Namespace::Monitoring);
+        let mut stmt = db.prepare(
+            "SELECT log_group_name, filter_name, filter_pattern, destination_arn, role_arn, distribution, created_at
+             FROM cw_subscription_filters WHERE log_group_name = ?1 AND filter_name LIKE ?2 || '%' ORDER BY filter_name"
+        )?;
+        let filters = stmt.query_map(params![group_name, name_prefix.unwrap_or("")], |row| Ok(SubscriptionFilterMetadata {
+            log_group_name: row.get(0)?,
+            filter_name: row.get(1)?,
+            filter_pattern: row.get(2)?,
+            destination_arn: row.get(3)?,
+            role_arn: row.get(4)?,
+            distribution: row.get(5)?,
+            created_at: row.get(6)?,
+        }))?
+        .filter_map(|r| r.ok())
+        .collect();
+        Ok(filters)
+    }
+
+    pub fn delete_subscription_filter(&self, group_name: &str, filter_name: &str) -> Result<()> {
+        let db = self.shard(Namespace::Monitoring);
+        let rows = db.execute(
+            "DELETE FROM cw_subscription_filters WHERE log_group_name = ?1 AND filter_name = ?2",
+            params![group_name, filter_name],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("SubscriptionFilter".into(), filter_name.into()));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -178,5 +250,35 @@ mod tests {
         assert_eq!(fetched.len(), 2);
         assert_eq!(fetched[0].message, "msg1");
     }
+    
+    #[test]
+    fn test_subscription_filters() {
+        let engine = StorageEngine::in_memory().unwrap();
+        let filter = |name: &str| SubscriptionFilterMetadata {
+            log_group_name: "app".to_string(),
+            filter_name: name.to_string(),
+            filter_pattern: "ERROR".to_string(),
+            destination_arn: "arn:aws:kinesis:us-east-1:123:stream/logs".to_string(),
+            role_arn: None,
+            distribution: "ByLogStream".to_string(),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+        };
+        
+        // Log group must exist
+        assert!(engine.put_subscription_filter(&filter("errors")).is_err());
+        engine.create_log_group("app", "123", "us-east-1").unwrap();
+        
+        engine.put_subscription_filter(&filter("errors")).unwrap();
+        engine.put_subscription_filter(&filter("warnings")).unwrap();
+        // Replacing an existing filter is allowed, a third one is not
+        engine.put_subscription_filter(&filter("errors")).unwrap();
+        assert!(engine.put_subscription_filter(&filter("third")).is_err());
+        
+        assert_eq!(engine.describe_subscription_filters("app", None).unwrap().len(), 2);
+        assert_eq!(engine.describe_subscription_filters("app", Some("warn")).unwrap().len(), 1);
+        
+        engine.delete_subscription_filter("app", "errors").unwrap();
+        assert!(engine.delete_subscription_filter("app", "errors").is_err());
+    }
 }
 
@@ -0,0 +1,245 @@
+use super::{StorageEngine, Namespace};
+use crate::error::{EmulatorError, Result};
+use serde::{Deserialize, Serialize};
+use rusqlite::{params, OptionalExtension};
+
+/// EventBridge Pipe connecting a source to a target.
+/// Filter criteria and parameters are stored as the raw JSON from the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipe {
+    pub name: String,
+    pub arn: String,
+    pub source: String,
+    pub target: String,
+    pub enrichment: Option<String>,
+    pub role_arn: String,
+    pub description: Option<String>,
+    pub filter_criteria: Option<String>,
+    pub source_parameters: Option<String>,
+    pub target_parameters: Option<String>,
+    pub desired_state: String, // RUNNING | STOPPED
+    pub current_state: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl StorageEngine {
+    pub fn init_pipes_tables(&self) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS aws_pipes (
+                name TEXT PRIMARY KEY,
+                arn TEXT NOT NULL,
+                source TEXT NOT NULL,
+                target TEXT NOT NULL,
+                enrichment TEXT,
+                role_arn TEXT NOT NULL,
+                description TEXT,
+                filter_criteria TEXT,
+                source_parameters TEXT,
+                target_parameters TEXT,
+                desired_state TEXT NOT NULL,
+                current_state TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Last sequence number delivered from a stream source
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS aws_pipe_checkpoints (
+                pipe_name TEXT PRIMARY KEY,
+                sequence_number INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn create_pipe(&self, pipe: &Pipe) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+
+        conn.execute(
+            "INSERT INTO aws_pipes (name, arn, source, target, enrichment, role_arn, description, filter_criteria,
+                source_parameters, target_parameters, desired_state, current_state, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                pipe.name, pipe.arn, pipe.source, pipe.target, pipe.enrichment, pipe.role_arn, pipe.description,
+                pipe.filter_criteria, pipe.source_parameters, pipe.target_parameters,
+                pipe.desired_state, pipe.current_state, pipe.created_at, pipe.updated_at
+            ],
+        ).map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint") {
+                EmulatorError::AlreadyExists(format!("Pipe {} already exists", pipe.name))
+            } else {
+                EmulatorError::Database(e.to_string())
+            }
+        })?;
+
+        Ok(())
+    }
+
+    pub fn get_pipe(&self, name: &str) -> Result<Pipe> {
+        let conn = self.shard(Namespace::Pipes);
+
+        conn.query_row(
+            "SELECT name, arn, source, target, enrichment, role_arn, description, filter_criteria,
+                source_parameters, target_parameters, desired_state, current_state, created_at, updated_at
+             FROM aws_pipes WHERE name = ?1",
+            params![name],
+            row_to_pipe,
+        ).map_err(|_| EmulatorError::NotFound("Pipe".into(), name.into()))
+    }
+
+    pub fn list_pipes(&self) -> Result<Vec<Pipe>> {
+        let conn = self.shard(Namespace::Pipes);
+        let mut stmt = conn.prepare(
+            "SELECT name, arn, source, target, enrichment, role_arn, description, filter_criteria,
+                source_parameters, target_parameters, desired_state, current_state, created_at, updated_at
+             FROM aws_pipes ORDER BY name"
+        )?;
+
+        let pipes = stmt.query_map([], row_to_pipe)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(pipes)
+    }
+
+    /// Replace the mutable fields of a pipe (source and name are immutable)
+    pub fn update_pipe(&self, pipe: &Pipe) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let rows = conn.execute(
+            "UPDATE aws_pipes SET target = ?1, enrichment = ?2, role_arn = ?3, description = ?4, filter_criteria = ?5,
+                source_parameters = ?6, target_parameters = ?7, desired_state = ?8, current_state = ?9, updated_at = ?10
+             WHERE name = ?11",
+            params![
+                pipe.target, pipe.enrichment, pipe.role_arn, pipe.description, pipe.filter_criteria,
+                pipe.source_parameters, pipe.target_parameters, pipe.desired_state, pipe.current_state, now, pipe.name
+            ],
+        )?;
+
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Pipe".into(), pipe.name.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn set_pipe_state(&self, name: &str, desired_state: &str, current_state: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let rows = conn.execute(
+            "UPDATE aws_pipes SET desired_state = ?1, current_state = ?2, updated_at = ?3 WHERE name = ?4",
+            params![desired_state, current_state, now, name],
+        )?;
+
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Pipe".into(), name.into()));
+        }
+        Ok(())
+    }
+
+    pub fn delete_pipe(&self, name: &str) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+        let rows = conn.execute("DELETE FROM aws_pipes WHERE name = ?1", params![name])?;
+
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Pipe".into(), name.into()));
+        }
+        conn.execute("DELETE FROM aws_pipe_checkpoints WHERE pipe_name = ?1", params![name])?;
+        Ok(())
+    }
+
+    /// Last sequence number the pipe delivered from its stream source, 0 before the first
+    pub fn get_pipe_checkpoint(&self, name: &str) -> Result<u64> {
+        let conn = self.shard(Namespace::Pipes);
+        let sequence: Option<i64> = conn.query_row(
+            "SELECT sequence_number FROM aws_pipe_checkpoints WHERE pipe_name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(sequence.unwrap_or(0) as u64)
+    }
+
+    pub fn set_pipe_checkpoint(&self, name: &str, sequence_number: u64) -> Result<()> {
+        let conn = self.shard(Namespace::Pipes);
+        conn.execute(
+            "INSERT OR REPLACE INTO aws_pipe_checkpoints (pipe_name, sequence_number) VALUES (?1, ?2)",
+            params![name, sequence_number as i64],
+        )?;
+        Ok(())
+    }
+}
+
+fn row_to_pipe(row: &rusqlite::Row) -> rusqlite::Result<Pipe> {
+    Ok(Pipe {
+        name: row.get(0)?,
+        arn: row.get(1)?,
+        source: row.get(2)?,
+        target: row.get(3)?,
+        enrichment: row.get(4)?,
+        role_arn: row.get(5)?,
+        description: row.get(6)?,
+        filter_criteria: row.get(7)?,
+        source_parameters: row.get(8)?,
+        target_parameters: row.get(9)?,
+        desired_state: row.get(10)?,
+        current_state: row.get(11)?,
+        created_at: row.get(12)?,
+        updated_at: row.get(13)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipe_crud() {
+        let engine = StorageEngine::in_memory().unwrap();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let mut pipe = Pipe {
+            name: "orders".into(),
+            arn: "arn:aws:pipes:us-east-1:000000000000:pipe/orders".into(),
+            source: "arn:aws:sqs:us-east-1:000000000000:orders".into(),
+            target: "arn:aws:lambda:us-east-1:000000000000:function:process".into(),
+            enrichment: None,
+            role_arn: "arn:aws:iam::000000000000:role/pipes".into(),
+            description: None,
+            filter_criteria: Some(r#"{"Filters":[{"Pattern":"{\"body\":{\"type\":[\"order\"]}}"}]}"#.into()),
+            source_parameters: None,
+            target_parameters: None,
+            desired_state: "RUNNING".into(),
+            current_state: "RUNNING".into(),
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        engine.create_pipe(&pipe).unwrap();
+        assert!(matches!(engine.create_pipe(&pipe), Err(EmulatorError::AlreadyExists(_))));
+
+        pipe.description = Some("order processing".into());
+        engine.update_pipe(&pipe).unwrap();
+        engine.set_pipe_state("orders", "STOPPED", "STOPPED").unwrap();
+
+        let fetched = engine.get_pipe("orders").unwrap();
+        assert_eq!(fetched.description.as_deref(), Some("order processing"));
+        assert_eq!(fetched.current_state, "STOPPED");
+        assert!(fetched.filter_criteria.is_some());
+        assert_eq!(engine.list_pipes().unwrap().len(), 1);
+
+        assert_eq!(engine.get_pipe_checkpoint("orders").unwrap(), 0);
+        engine.set_pipe_checkpoint("orders", 7).unwrap();
+        assert_eq!(engine.get_pipe_checkpoint("orders").unwrap(), 7);
+
+        // A pipe recreated under the same name starts over
+        engine.delete_pipe("orders").unwrap();
+        assert!(engine.get_pipe("orders").is_err());
+        assert_eq!(engine.get_pipe_checkpoint("orders").unwrap(), 0);
+    }
+}
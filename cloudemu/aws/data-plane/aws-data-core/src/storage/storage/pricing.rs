@@ -1,3 +1,4 @@
+use super::Namespace;
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::{params, OptionalExtension};
@@ -28,7 +29,7 @@ impl super::StorageEngine {
         service_code: &str,
         filter_fn: impl Fn(&Product) -> bool,
     ) -> Result<Vec<(Product, Vec<OfferTerm>)>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Pricing);
         let mut stmt = conn.prepare(
             r#"
             SELECT 
@@ -92,7 +93,7 @@ impl super::StorageEngine {
 
     /// Implement a seeding function for mock data
     pub async fn seed_pricing_data(&self) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Pricing);
         let sku = "ABC-123";
         // Check if exists
         let exists: Option<String> = conn.query_row(
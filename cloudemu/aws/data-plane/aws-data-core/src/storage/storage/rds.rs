@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -19,7 +19,7 @@ impl StorageEngine {
     const TABLE_RDS_INSTANCES: &'static str = "aws_rds_instances";
 
     pub fn init_rds_tables(&self) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Rds);
         
         conn.execute(&format!(
             "CREATE TABLE IF NOT EXISTS {} (
@@ -47,7 +47,7 @@ impl StorageEngine {
         username: &str,
         allocated_storage: i32
     ) -> Result<RdsInstance> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Rds);
         
         let status = "available";
         let address = format!("{}.cluster-mock.us-east-1.rds.amazonaws.com", identifier);
@@ -77,7 +77,7 @@ impl StorageEngine {
     }
 
     pub fn list_db_instances(&self) -> Result<Vec<RdsInstance>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Rds);
         let mut stmt = conn.prepare(&format!("SELECT Identifier, Engine, Class, Status, Username, Allocated_Storage, Endpoint_Address, Endpoint_Port FROM {}", Self::TABLE_RDS_INSTANCES))?;
         
         let instances = stmt.query_map([], |row| {
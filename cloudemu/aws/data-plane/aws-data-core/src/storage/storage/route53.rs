@@ -1,4 +1,4 @@
-use super::StorageEngine;
+use super::{StorageEngine, Namespace};
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
 use rusqlite::params;
@@ -30,7 +30,7 @@ impl StorageEngine {
     const TABLE_ROUTE53_RECORDS: &'static str = "aws_route53_records";
 
     pub fn init_route53_tables(&self) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Route53);
         
         conn.execute(&format!(
             "CREATE TABLE IF NOT EXISTS {} (
@@ -61,7 +61,7 @@ impl StorageEngine {
     }
 
     pub fn create_hosted_zone(&self, name: &str, caller_ref: &str) -> Result<HostedZone> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Route53);
         let id = format!("/hostedzone/Z{}", uuid::Uuid::new_v4().to_string().replace("-", "").to_uppercase()[..14].to_string());
         
         conn.execute(
@@ -83,7 +83,7 @@ impl StorageEngine {
     }
 
     pub fn list_hosted_zones(&self) -> Result<Vec<HostedZone>> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Route53);
         let mut stmt = conn.prepare(&format!("SELECT id, name, caller_reference, config_comment, private_zone FROM {}", Self::TABLE_ROUTE53_ZONES))?;
         
         let zones = stmt.query_map([], |row| {
@@ -101,7 +101,7 @@ impl StorageEngine {
     }
 
     pub fn change_resource_record_sets(&self, zone_id: &str, change_batch: Vec<ResourceRecordSet>) -> Result<()> {
-        let conn = self.db.lock();
+        let conn = self.shard(Namespace::Route53);
         
         for record in change_batch {
             let records_json = serde_json::to_string(&record.resource_records).unwrap();
@@ -117,7 +117,7 @@ impl StorageEngine {
     }
     
     pub fn list_resource_record_sets(&self, zone_id: &str) -> Result<Vec<ResourceRecordSet>> {
-         let conn = self.db.lock();
+         let conn = self.shard(Namespace::Route53);
          let mut stmt = conn.prepare(&format!("SELECT name, type, ttl, records_json FROM {} WHERE zone_id = ?1", Self::TABLE_ROUTE53_RECORDS))?;
          
          let records = stmt.query_map(params![zone_id], |row| {
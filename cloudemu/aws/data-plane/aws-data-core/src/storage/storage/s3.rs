@@ -1,14 +1,28 @@
-use super::engine::{StorageEngine, BucketMetadata, ObjectMetadata, ListObjectsResult};
+use super::engine::{StorageEngine, Namespace, BucketMetadata, ObjectMetadata, ListObjectsResult, ReplicationConfiguration, ReplicationRule, WebsiteConfiguration, NotificationConfiguration, MultipartUpload, UploadedPart, LifecycleConfiguration, LifecycleRule, LifecycleTransition, LifecycleActions};
 use crate::error::{EmulatorError, Result};
+use emu_storage::BlobStore;
+use chrono::{DateTime, Utc};
+use md5::{Digest, Md5};
 use rusqlite::params;
+use std::collections::HashMap;
 use std::fs;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Object data is kept in the shared content-addressed blob store
+pub use emu_storage::{BlobWriter as ObjectWriter, StoredBlob as StoredData};
+
+/// Highest part number of a multipart upload
+const MAX_PART_NUMBER: i32 = 10_000;
+
+/// Smallest size of every part of a multipart upload but the last
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
 
 impl StorageEngine {
     // ==================== Bucket Operations ====================
     
     /// Create a bucket
     pub fn create_bucket(&self, name: &str, region: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let now = chrono::Utc::now().to_rfc3339();
         
         db.execute(
@@ -27,7 +41,7 @@ impl StorageEngine {
     
     /// Delete a bucket
     pub fn delete_bucket(&self, name: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         
         // Check if bucket exists
         let exists: bool = db.query_row(
@@ -58,7 +72,7 @@ impl StorageEngine {
     
     /// Check if bucket exists
     pub fn bucket_exists(&self, name: &str) -> Result<bool> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let exists: bool = db.query_row(
             "SELECT 1 FROM buckets WHERE name = ?1",
             params![name],
@@ -69,7 +83,7 @@ impl StorageEngine {
     
     /// Get bucket metadata
     pub fn get_bucket(&self, name: &str) -> Result<BucketMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         db.query_row(
             "SELECT name, region, created_at, versioning, policy, acl FROM buckets WHERE name = ?1",
             params![name],
@@ -88,7 +102,7 @@ impl StorageEngine {
     
     /// List all buckets
     pub fn list_buckets(&self) -> Result<Vec<BucketMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let mut stmt = db.prepare(
             "SELECT name, region, created_at, versioning, policy, acl FROM buckets ORDER BY name"
         )?;
@@ -111,7 +125,7 @@ impl StorageEngine {
     
     /// Set bucket versioning
     pub fn set_bucket_versioning(&self, name: &str, status: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let rows = db.execute(
             "UPDATE buckets SET versioning = ?1 WHERE name = ?2",
             params![status, name],
@@ -126,7 +140,7 @@ impl StorageEngine {
     
     /// Get bucket versioning status
     pub fn get_bucket_versioning(&self, name: &str) -> Result<String> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         db.query_row(
             "SELECT versioning FROM buckets WHERE name = ?1",
             params![name],
@@ -136,7 +150,7 @@ impl StorageEngine {
     
     /// Set bucket policy
     pub fn set_bucket_policy(&self, name: &str, policy: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let rows = db.execute(
             "UPDATE buckets SET policy = ?1 WHERE name = ?2",
             params![policy, name],
@@ -151,7 +165,7 @@ impl StorageEngine {
     
     /// Get bucket policy
     pub fn get_bucket_policy(&self, name: &str) -> Result<Option<String>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         db.query_row(
             "SELECT policy FROM buckets WHERE name = ?1",
             params![name],
@@ -161,7 +175,7 @@ impl StorageEngine {
     
     /// Delete bucket policy
     pub fn delete_bucket_policy(&self, name: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let rows = db.execute(
             "UPDATE buckets SET policy = NULL WHERE name = ?1",
             params![name],
@@ -174,6 +188,275 @@ impl StorageEngine {
         Ok(())
     }
     
+    /// Set bucket replication configuration
+    pub fn set_bucket_replication(&self, name: &str, config: &ReplicationConfiguration) -> Result<()> {
+        let versioning = self.get_bucket_versioning(name)?;
+        if versioning != "Enabled" {
+            return Err(EmulatorError::InvalidRequest(
+                "Versioning must be 'Enabled' on the bucket to apply a replication configuration".into()
+            ));
+        }
+        for rule in &config.rules {
+            if !self.bucket_exists(&rule.destination_bucket)? {
+                return Err(EmulatorError::InvalidRequest(
+                    format!("Destination bucket must exist: {}", rule.destination_bucket)
+                ));
+            }
+        }
+        
+        let json = serde_json::to_string(config)?;
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET replication_config = ?1 WHERE name = ?2",
+            params![json, name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get bucket replication configuration
+    pub fn get_bucket_replication(&self, name: &str) -> Result<Option<ReplicationConfiguration>> {
+        let db = self.shard(Namespace::S3);
+        let json: Option<String> = db.query_row(
+            "SELECT replication_config FROM buckets WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|_| EmulatorError::NoSuchBucket(name.to_string()))?;
+        
+        match json {
+            Some(j) => Ok(Some(serde_json::from_str(&j)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Delete bucket replication configuration
+    pub fn delete_bucket_replication(&self, name: &str) -> Result<()> {
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET replication_config = NULL WHERE name = ?1",
+            params![name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Set bucket lifecycle configuration
+    pub fn set_bucket_lifecycle(&self, name: &str, config: &LifecycleConfiguration) -> Result<()> {
+        let json = serde_json::to_string(config)?;
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET lifecycle_rules = ?1 WHERE name = ?2",
+            params![json, name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get bucket lifecycle configuration
+    pub fn get_bucket_lifecycle(&self, name: &str) -> Result<Option<LifecycleConfiguration>> {
+        let db = self.shard(Namespace::S3);
+        let json: Option<String> = db.query_row(
+            "SELECT lifecycle_rules FROM buckets WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|_| EmulatorError::NoSuchBucket(name.to_string()))?;
+        
+        match json {
+            Some(j) => Ok(Some(serde_json::from_str(&j)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Delete bucket lifecycle configuration
+    pub fn delete_bucket_lifecycle(&self, name: &str) -> Result<()> {
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET lifecycle_rules = NULL WHERE name = ?1",
+            params![name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Find the highest-priority enabled replication rule matching a key
+    pub fn match_replication_rule(&self, bucket: &str, key: &str) -> Result<Option<ReplicationRule>> {
+        let config = match self.get_bucket_replication(bucket)? {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        
+        Ok(config.rules.into_iter()
+            .filter(|r| r.status == "Enabled" && key.starts_with(&r.prefix))
+            .max_by_key(|r| r.priority))
+    }
+    
+    /// Set the replication status of an object version (latest version when `version_id` is None)
+    pub fn set_object_replication_status(&self, bucket: &str, key: &str, version_id: Option<&str>, status: &str) -> Result<()> {
+        let db = self.shard(Namespace::S3);
+        let rows = match version_id {
+            Some(vid) => db.execute(
+                "UPDATE objects SET replication_status = ?1 WHERE bucket = ?2 AND key = ?3 AND version_id = ?4",
+                params![status, bucket, key, vid],
+            )?,
+            None => db.execute(
+                "UPDATE objects SET replication_status = ?1 WHERE bucket = ?2 AND key = ?3 AND is_latest = 1",
+                params![status, bucket, key],
+            )?,
+        };
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchKey(key.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Copy an object version to the rule's destination bucket.
+    /// The source is marked COMPLETED (or FAILED) and the copy is marked REPLICA.
+    pub fn replicate_object(&self, bucket: &str, key: &str, version_id: Option<&str>, rule: &ReplicationRule) -> Result<ObjectMetadata> {
+        let (meta, content_hash) = self.object_record(bucket, key, version_id)?;
+        let result = self.copy_to_replica(bucket, &meta, content_hash, rule);
+        let status = if result.is_ok() { "COMPLETED" } else { "FAILED" };
+        
+        // Mark the version that was copied, even if a newer one has been written since
+        let db = self.shard(Namespace::S3);
+        db.execute(
+            "UPDATE objects SET replication_status = ?1 WHERE bucket = ?2 AND key = ?3 AND version_id IS ?4 AND etag = ?5",
+            params![status, bucket, key, meta.version_id, meta.etag],
+        )?;
+        result
+    }
+    
+    fn copy_to_replica(&self, bucket: &str, meta: &ObjectMetadata, content_hash: String, rule: &ReplicationRule) -> Result<ObjectMetadata> {
+        let user_metadata: Option<String> = {
+            let db = self.shard(Namespace::S3);
+            db.query_row(
+                "SELECT metadata FROM objects WHERE bucket = ?1 AND key = ?2 AND version_id IS ?3",
+                params![bucket, meta.key, meta.version_id],
+                |row| row.get(0),
+            ).unwrap_or(None)
+        };
+        
+        // The replica shares the source's stored data and is written with its final status,
+        // so it is never seen as an ordinary object that could itself be replicated
+        let data = StoredData { content_hash, size: meta.size };
+        self.insert_object(
+            &rule.destination_bucket,
+            &meta.key,
+            data,
+            Some(&meta.content_type),
+            user_metadata.as_deref(),
+            rule.storage_class.as_deref().unwrap_or(&meta.storage_class),
+            Some("REPLICA"),
+        )
+    }
+    
+    /// Set bucket website configuration
+    pub fn set_bucket_website(&self, name: &str, config: &WebsiteConfiguration) -> Result<()> {
+        if config.index_document.is_none() && config.redirect_all_requests_to.is_none() {
+            return Err(EmulatorError::InvalidArgument(
+                "A value for IndexDocument Suffix must be provided if RedirectAllRequestsTo is empty".into()
+            ));
+        }
+        if let Some(ref suffix) = config.index_document {
+            if suffix.is_empty() || suffix.contains('/') {
+                return Err(EmulatorError::InvalidArgument(
+                    "The IndexDocument Suffix is not well formed".into()
+                ));
+            }
+        }
+        
+        let json = serde_json::to_string(config)?;
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET website_config = ?1 WHERE name = ?2",
+            params![json, name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get bucket website configuration
+    pub fn get_bucket_website(&self, name: &str) -> Result<Option<WebsiteConfiguration>> {
+        let db = self.shard(Namespace::S3);
+        let json: Option<String> = db.query_row(
+            "SELECT website_config FROM buckets WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|_| EmulatorError::NoSuchBucket(name.to_string()))?;
+        
+        match json {
+            Some(j) => Ok(Some(serde_json::from_str(&j)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Delete bucket website configuration
+    pub fn delete_bucket_website(&self, name: &str) -> Result<()> {
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET website_config = NULL WHERE name = ?1",
+            params![name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Set bucket notification configuration (an empty configuration disables notifications)
+    pub fn set_bucket_notification(&self, name: &str, config: &NotificationConfiguration) -> Result<()> {
+        let json = serde_json::to_string(config)?;
+        let db = self.shard(Namespace::S3);
+        let rows = db.execute(
+            "UPDATE buckets SET notification_config = ?1 WHERE name = ?2",
+            params![json, name],
+        )?;
+        
+        if rows == 0 {
+            return Err(EmulatorError::NoSuchBucket(name.to_string()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get bucket notification configuration (empty when none has been set)
+    pub fn get_bucket_notification(&self, name: &str) -> Result<NotificationConfiguration> {
+        let db = self.shard(Namespace::S3);
+        let json: Option<String> = db.query_row(
+            "SELECT notification_config FROM buckets WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|_| EmulatorError::NoSuchBucket(name.to_string()))?;
+        
+        match json {
+            Some(j) => Ok(serde_json::from_str(&j)?),
+            None => Ok(NotificationConfiguration::default()),
+        }
+    }
+    
     // ==================== Object Operations ====================
     
     /// Put an object
@@ -183,8 +466,35 @@ impl StorageEngine {
             return Err(EmulatorError::NoSuchBucket(bucket.to_string()));
         }
         
-        // Calculate hash and ETag
-        let content_hash = self.store_object_data(data)?;
+        let mut writer = self.object_writer()?;
+        writer.write_all(data)?;
+        self.put_object_data(bucket, key, writer.finish()?, content_type, metadata)
+    }
+    
+    /// Put an object whose data is already in the object store (streamed uploads, copies).
+    /// Objects matching a replication rule are stored with the PENDING replication status.
+    pub fn put_object_data(&self, bucket: &str, key: &str, data: StoredData, content_type: Option<&str>, metadata: Option<&str>) -> Result<ObjectMetadata> {
+        let replication_status = self.match_replication_rule(bucket, key)?.map(|_| "PENDING");
+        self.insert_object(bucket, key, data, content_type, metadata, "STANDARD", replication_status)
+    }
+    
+    /// Make `data` the latest version of an object
+    #[allow(clippy::too_many_arguments)]
+    fn insert_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        data: StoredData,
+        content_type: Option<&str>,
+        metadata: Option<&str>,
+        storage_class: &str,
+        replication_status: Option<&str>,
+    ) -> Result<ObjectMetadata> {
+        if !self.bucket_exists(bucket)? {
+            return Err(EmulatorError::NoSuchBucket(bucket.to_string()));
+        }
+        
+        let StoredData { content_hash, size } = data;
         let etag = format!("\"{}\"", &content_hash[..32]);
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -196,94 +506,105 @@ impl StorageEngine {
             None
         };
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
+        let tx = db.unchecked_transaction()?;
         
         // If versioning is not enabled, delete existing object
         if versioning != "Enabled" {
-            db.execute(
+            tx.execute(
                 "DELETE FROM objects WHERE bucket = ?1 AND key = ?2",
                 params![bucket, key],
             )?;
         } else {
             // Mark previous version as not latest
-            db.execute(
+            tx.execute(
                 "UPDATE objects SET is_latest = 0 WHERE bucket = ?1 AND key = ?2 AND is_latest = 1",
                 params![bucket, key],
             )?;
         }
         
         // Insert new object
-        db.execute(
+        tx.execute(
             r#"INSERT INTO objects 
-               (bucket, key, version_id, is_latest, content_hash, content_length, content_type, etag, last_modified, metadata)
-               VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+               (bucket, key, version_id, is_latest, content_hash, content_length, content_type, etag, last_modified, metadata, storage_class, replication_status)
+               VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"#,
             params![
                 bucket,
                 key,
                 version_id,
                 content_hash,
-                data.len() as i64,
+                size as i64,
                 content_type.unwrap_or("application/octet-stream"),
                 etag,
                 now,
                 metadata,
+                storage_class,
+                replication_status,
             ],
         )?;
+        tx.commit()?;
         
         Ok(ObjectMetadata {
             key: key.to_string(),
             version_id,
             etag,
-            size: data.len() as u64,
+            size,
             last_modified: now,
             content_type: content_type.unwrap_or("application/octet-stream").to_string(),
-            storage_class: "STANDARD".to_string(),
+            storage_class: storage_class.to_string(),
             is_delete_marker: false,
+            replication_status: replication_status.map(str::to_string),
         })
     }
     
     /// Get an object
     pub fn get_object(&self, bucket: &str, key: &str, version_id: Option<&str>) -> Result<(ObjectMetadata, Vec<u8>)> {
-        let db = self.db.lock();
+        let (metadata, mut file) = self.open_object(bucket, key, version_id)?;
         
-        let query = if let Some(vid) = version_id {
-            db.query_row(
-                r#"SELECT key, version_id, etag, content_length, last_modified, content_type, storage_class, is_delete_marker, content_hash
-                   FROM objects WHERE bucket = ?1 AND key = ?2 AND version_id = ?3"#,
-                params![bucket, key, vid],
-                |row| Ok((
-                    ObjectMetadata {
-                        key: row.get(0)?,
-                        version_id: row.get(1)?,
-                        etag: row.get(2)?,
-                        size: row.get::<_, i64>(3)? as u64,
-                        last_modified: row.get(4)?,
-                        content_type: row.get(5)?,
-                        storage_class: row.get(6)?,
-                        is_delete_marker: row.get::<_, i64>(7)? != 0,
-                    },
-                    row.get::<_, String>(8)?,
-                ))
-            )
-        } else {
-            db.query_row(
-                r#"SELECT key, version_id, etag, content_length, last_modified, content_type, storage_class, is_delete_marker, content_hash
-                   FROM objects WHERE bucket = ?1 AND key = ?2 AND is_latest = 1"#,
-                params![bucket, key],
-                |row| Ok((
-                    ObjectMetadata {
-                        key: row.get(0)?,
-                        version_id: row.get(1)?,
-                        etag: row.get(2)?,
-                        size: row.get::<_, i64>(3)? as u64,
-                        last_modified: row.get(4)?,
-                        content_type: row.get(5)?,
-                        storage_class: row.get(6)?,
-                        is_delete_marker: row.get::<_, i64>(7)? != 0,
-                    },
-                    row.get::<_, String>(8)?,
-                ))
-            )
+        let mut data = Vec::with_capacity(metadata.size as usize);
+        file.read_to_end(&mut data)?;
+        
+        Ok((metadata, data))
+    }
+    
+    /// Get an object's metadata without touching its data
+    pub fn head_object(&self, bucket: &str, key: &str, version_id: Option<&str>) -> Result<ObjectMetadata> {
+        self.object_record(bucket, key, version_id).map(|(metadata, _)| metadata)
+    }
+    
+    /// Open an object for streaming reads
+    pub fn open_object(&self, bucket: &str, key: &str, version_id: Option<&str>) -> Result<(ObjectMetadata, fs::File)> {
+        let (metadata, content_hash) = self.object_record(bucket, key, version_id)?;
+        let file = self.open_object_data(&content_hash)?;
+        Ok((metadata, file))
+    }
+    
+    /// Metadata and content hash of an object version (latest when `version_id` is None)
+    fn object_record(&self, bucket: &str, key: &str, version_id: Option<&str>) -> Result<(ObjectMetadata, String)> {
+        let db = self.shard(Namespace::S3);
+        
+        let sql = format!(
+            r#"SELECT key, version_id, etag, content_length, last_modified, content_type, storage_class, is_delete_marker, content_hash, replication_status
+               FROM objects WHERE bucket = ?1 AND key = ?2 AND {}"#,
+            if version_id.is_some() { "version_id = ?3" } else { "is_latest = 1" }
+        );
+        let map_row = |row: &rusqlite::Row| Ok((
+            ObjectMetadata {
+                key: row.get(0)?,
+                version_id: row.get(1)?,
+                etag: row.get(2)?,
+                size: row.get::<_, i64>(3)? as u64,
+                last_modified: row.get(4)?,
+                content_type: row.get(5)?,
+                storage_class: row.get(6)?,
+                is_delete_marker: row.get::<_, i64>(7)? != 0,
+                replication_status: row.get(9)?,
+            },
+            row.get::<_, String>(8)?,
+        ));
+        let query = match version_id {
+            Some(vid) => db.query_row(&sql, params![bucket, key, vid], map_row),
+            None => db.query_row(&sql, params![bucket, key], map_row),
         };
         
         let (metadata, content_hash) = query.map_err(|_| EmulatorError::NoSuchKey(key.to_string()))?;
@@ -292,11 +613,14 @@ impl StorageEngine {
             return Err(EmulatorError::NoSuchKey(key.to_string()));
         }
         
-        drop(db); // Release lock before reading file
-        
-        let data = self.read_object_data(&content_hash)?;
-        
-        Ok((metadata, data))
+        Ok((metadata, content_hash))
+    }
+    
+    /// Copy an object by reference to its stored data
+    pub fn copy_object(&self, src_bucket: &str, src_key: &str, dest_bucket: &str, dest_key: &str) -> Result<ObjectMetadata> {
+        let (source, content_hash) = self.object_record(src_bucket, src_key, None)?;
+        let data = StoredData { content_hash, size: source.size };
+        self.put_object_data(dest_bucket, dest_key, data, Some(&source.content_type), None)
     }
     
     /// Delete an object
@@ -306,7 +630,7 @@ impl StorageEngine {
         }
         
         let versioning = self.get_bucket_versioning(bucket)?;
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         
         if versioning == "Enabled" && version_id.is_none() {
             // Insert delete marker
@@ -351,13 +675,13 @@ impl StorageEngine {
             return Err(EmulatorError::NoSuchBucket(bucket.to_string()));
         }
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         let prefix_str = prefix.unwrap_or("");
         let start_after = continuation_token.unwrap_or("");
         
         // Query objects
         let mut stmt = db.prepare(
-            r#"SELECT key, version_id, etag, content_length, last_modified, content_type, storage_class
+            r#"SELECT key, version_id, etag, content_length, last_modified, content_type, storage_class, replication_status
                FROM objects 
                WHERE bucket = ?1 AND is_latest = 1 AND is_delete_marker = 0 AND key LIKE ?2 AND key > ?3
                ORDER BY key
@@ -376,6 +700,7 @@ impl StorageEngine {
                 content_type: row.get(5)?,
                 storage_class: row.get(6)?,
                 is_delete_marker: false,
+                replication_status: row.get(7)?,
             })
         )?
         .filter_map(|r| r.ok())
@@ -426,113 +751,376 @@ impl StorageEngine {
     
     // ==================== Object Data Storage ====================
     
+    /// Start writing object data to the filesystem
+    pub fn object_writer(&self) -> Result<ObjectWriter> {
+        Ok(self.blobs.writer()?)
+    }
+    
     /// Store object data to filesystem, returns content hash
     pub fn store_object_data(&self, data: &[u8]) -> Result<String> {
-        use sha2::{Sha256, Digest};
-        
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        let hash = hex::encode(hasher.finalize());
-        
-        // Content-addressed storage: first 2 chars as directory
-        let dir = self.objects_dir.join(&hash[..2]);
-        fs::create_dir_all(&dir)?;
-        
-        let file_path = dir.join(&hash);
-        if !file_path.exists() {
-            fs::write(&file_path, data)?;
-        }
-        
-        Ok(hash)
+        Ok(self.blobs.put(data)?)
     }
     
     /// Read object data from filesystem
     pub fn read_object_data(&self, content_hash: &str) -> Result<Vec<u8>> {
-        if content_hash.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let file_path = self.objects_dir.join(&content_hash[..2]).join(content_hash);
-        fs::read(&file_path).map_err(|e| EmulatorError::Internal(e.to_string()))
+        Ok(self.blobs.get(content_hash)?)
+    }
+    
+    /// Open object data on the filesystem for streaming reads
+    pub fn open_object_data(&self, content_hash: &str) -> Result<fs::File> {
+        Ok(self.blobs.open_blob(content_hash)?)
     }
 
     // ==================== Multipart Upload Operations ====================
 
-    pub fn create_multipart_upload(&self, bucket: &str, key: &str) -> Result<String> {
+    /// Start a multipart upload; the completed object gets `content_type` and `metadata`
+    pub fn create_multipart_upload(&self, bucket: &str, key: &str, content_type: Option<&str>, metadata: Option<&str>) -> Result<String> {
+        if !self.bucket_exists(bucket)? {
+            return Err(EmulatorError::NoSuchBucket(bucket.to_string()));
+        }
+        
         let upload_id = uuid::Uuid::new_v4().to_string();
         let initiated = chrono::Utc::now().to_rfc3339();
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         db.execute(
-            "INSERT INTO multipart_uploads (upload_id, bucket, key, initiated) VALUES (?, ?, ?, ?)",
-            params![upload_id, bucket, key, initiated],
+            "INSERT INTO multipart_uploads (upload_id, bucket, key, initiated, content_type, metadata) VALUES (?, ?, ?, ?, ?, ?)",
+            params![upload_id, bucket, key, initiated, content_type, metadata],
         )?;
         
         Ok(upload_id)
     }
 
-    pub fn upload_part(&self, upload_id: &str, part_number: i32, data: &[u8]) -> Result<String> {
-        let content_hash = self.store_object_data(data)?;
-        let etag = format!("\"{}\"", &content_hash[..32]);
+    /// A multipart upload in progress for `bucket`/`key`
+    pub fn get_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str) -> Result<MultipartUpload> {
+        let db = self.shard(Namespace::S3);
+        db.query_row(
+            "SELECT upload_id, bucket, key, initiated, content_type, metadata FROM multipart_uploads WHERE upload_id = ?1 AND bucket = ?2 AND key = ?3",
+            params![upload_id, bucket, key],
+            |row| Ok(MultipartUpload {
+                upload_id: row.get(0)?,
+                bucket: row.get(1)?,
+                key: row.get(2)?,
+                initiated: row.get(3)?,
+                content_type: row.get(4)?,
+                metadata: row.get(5)?,
+            }),
+        ).map_err(|_| EmulatorError::NoSuchUpload(upload_id.to_string()))
+    }
+
+    pub fn upload_part(&self, bucket: &str, key: &str, upload_id: &str, part_number: i32, data: &[u8]) -> Result<UploadedPart> {
+        let mut writer = self.object_writer()?;
+        writer.write_all(data)?;
+        self.upload_part_data(bucket, key, upload_id, part_number, writer.finish()?)
+    }
+
+    /// Record a part whose data is already in the object store, replacing any earlier
+    /// upload of the same part number
+    pub fn upload_part_data(&self, bucket: &str, key: &str, upload_id: &str, part_number: i32, data: StoredData) -> Result<UploadedPart> {
+        self.get_multipart_upload(bucket, key, upload_id)?;
+        if !(1..=MAX_PART_NUMBER).contains(&part_number) {
+            return Err(EmulatorError::InvalidArgument(format!(
+                "Part number must be an integer between 1 and {}, inclusive", MAX_PART_NUMBER
+            )));
+        }
+        
+        // Parts carry S3's ETag, the MD5 of their data, which clients check and the
+        // ETag of the completed object is made of
+        let StoredData { content_hash, size } = data;
+        let mut md5 = Md5::new();
+        io::copy(&mut self.open_object_data(&content_hash)?, &mut md5)?;
+        let etag = format!("\"{}\"", hex::encode(md5.finalize()));
         let last_modified = chrono::Utc::now().to_rfc3339();
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::S3);
         db.execute(
             "INSERT OR REPLACE INTO multipart_parts (upload_id, part_number, content_hash, size, etag, last_modified) VALUES (?, ?, ?, ?, ?, ?)",
-            params![upload_id, part_number, content_hash, data.len() as i64, etag, last_modified],
+            params![upload_id, part_number, content_hash, size as i64, etag, last_modified],
         )?;
         
-        Ok(etag)
+        Ok(UploadedPart { part_number, etag, size, last_modified })
     }
 
-    pub fn complete_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str) -> Result<String> {
-        let db = self.db.lock();
+    /// Upload a part copied from an object, whole or its inclusive byte `range`
+    #[allow(clippy::too_many_arguments)]
+    pub fn upload_part_copy(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        part_number: i32,
+        src_bucket: &str,
+        src_key: &str,
+        src_version_id: Option<&str>,
+        range: Option<(u64, u64)>,
+    ) -> Result<UploadedPart> {
+        self.get_multipart_upload(bucket, key, upload_id)?;
+        if !self.bucket_exists(src_bucket)? {
+            return Err(EmulatorError::NoSuchBucket(src_bucket.to_string()));
+        }
+        let (source, content_hash) = self.object_record(src_bucket, src_key, src_version_id)?;
+        
+        let data = match range {
+            // The part shares the source's stored data
+            None => StoredData { content_hash, size: source.size },
+            Some((first, last)) => {
+                if first > last || last >= source.size {
+                    return Err(EmulatorError::InvalidArgument(format!(
+                        "The x-amz-copy-source-range bytes={}-{} is not satisfiable for an object of {} bytes",
+                        first, last, source.size
+                    )));
+                }
+                let mut file = self.open_object_data(&content_hash)?;
+                file.seek(SeekFrom::Start(first))?;
+                let mut writer = self.object_writer()?;
+                io::copy(&mut file.take(last - first + 1), &mut writer)?;
+                writer.finish()?
+            }
+        };
+        self.upload_part_data(bucket, key, upload_id, part_number, data)
+    }
+
+    /// Parts of a multipart upload numbered above `part_number_marker`, in order
+    pub fn list_parts(&self, bucket: &str, key: &str, upload_id: &str, part_number_marker: i32) -> Result<Vec<UploadedPart>> {
+        self.get_multipart_upload(bucket, key, upload_id)?;
         
-        // Get all parts in order
+        let db = self.shard(Namespace::S3);
         let mut stmt = db.prepare(
-            "SELECT content_hash FROM multipart_parts WHERE upload_id = ? ORDER BY part_number"
+            "SELECT part_number, etag, size, last_modified FROM multipart_parts WHERE upload_id = ?1 AND part_number > ?2 ORDER BY part_number"
         )?;
-        let parts: Vec<String> = stmt.query_map(params![upload_id], |row| row.get(0))?
-            .collect::<std::result::Result<Vec<_>, _>>()?;
-        
+        let parts = stmt.query_map(params![upload_id, part_number_marker], |row| Ok(UploadedPart {
+            part_number: row.get(0)?,
+            etag: row.get(1)?,
+            size: row.get::<_, i64>(2)? as u64,
+            last_modified: row.get(3)?,
+        }))?.collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(parts)
+    }
+
+    /// Assemble the listed parts, as (part number, ETag) in ascending part number order,
+    /// into the object.
+    ///
+    /// Each part must have been uploaded with that ETag and all but the last must be at least
+    /// 5 MiB. The object's ETag is S3's: the MD5 of the parts' binary MD5s, followed by
+    /// `-<part count>`. Parts left out of the list are discarded.
+    pub fn complete_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str, parts: &[(i32, String)]) -> Result<ObjectMetadata> {
+        let upload = self.get_multipart_upload(bucket, key, upload_id)?;
         if parts.is_empty() {
-            return Err(EmulatorError::InvalidRequest("No parts uploaded".into()));
+            return Err(EmulatorError::MalformedXml("You must specify at least one part".into()));
         }
-        
-        // Combine all parts
-        let mut combined_data = Vec::new();
-        for part_hash in &parts {
-            let part_data = self.read_object_data(part_hash)?;
-            combined_data.extend_from_slice(&part_data);
+        if parts.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(EmulatorError::InvalidParameter {
+                code: "InvalidPartOrder",
+                message: "The list of parts was not in ascending order. Parts must be ordered by part number.".into(),
+            });
         }
         
-        // Store the combined object
-        let final_hash = self.store_object_data(&combined_data)?;
-        let etag = format!("\"{}\"", &final_hash[..32]);
+        // Content hash, size and ETag of every uploaded part
+        let uploaded: HashMap<i32, (String, u64, String)> = {
+            let db = self.shard(Namespace::S3);
+            let mut stmt = db.prepare(
+                "SELECT part_number, content_hash, size, etag FROM multipart_parts WHERE upload_id = ?1"
+            )?;
+            let parts = stmt.query_map(params![upload_id], |row| Ok((
+                row.get(0)?,
+                (row.get(1)?, row.get::<_, i64>(2)? as u64, row.get(3)?),
+            )))?.collect::<std::result::Result<_, _>>()?;
+            parts
+        };
         
-        // Create object metadata
-        let last_modified = chrono::Utc::now().to_rfc3339();
-        db.execute(
-            "INSERT INTO objects (bucket, key, version_id, is_latest, content_hash, content_length, content_type, etag, last_modified, metadata) VALUES (?, ?, NULL, 1, ?, ?, 'application/octet-stream', ?, ?, NULL)",
-            params![bucket, key, final_hash, combined_data.len() as i64, etag, last_modified],
-        )?;
+        let mut content_hashes = Vec::with_capacity(parts.len());
+        let mut digests = Md5::new();
+        for (i, (part_number, etag)) in parts.iter().enumerate() {
+            let Some((content_hash, size, part_etag)) = uploaded.get(part_number)
+                .filter(|(_, _, part_etag)| part_etag.trim_matches('"') == etag.trim_matches('"')) else {
+                return Err(EmulatorError::InvalidParameter {
+                    code: "InvalidPart",
+                    message: format!(
+                        "One or more of the specified parts could not be found or its entity tag did not match: part {} with ETag {}",
+                        part_number, etag
+                    ),
+                });
+            };
+            if i + 1 < parts.len() && *size < MIN_PART_SIZE {
+                return Err(EmulatorError::InvalidParameter {
+                    code: "EntityTooSmall",
+                    message: format!(
+                        "Part {} is {} bytes; every part but the last must be at least {} bytes",
+                        part_number, size, MIN_PART_SIZE
+                    ),
+                });
+            }
+            digests.update(hex::decode(part_etag.trim_matches('"')).map_err(|e| EmulatorError::Internal(e.to_string()))?);
+            content_hashes.push(content_hash);
+        }
+        let etag = format!("\"{}-{}\"", hex::encode(digests.finalize()), parts.len());
         
-        // Clean up multipart data
-        db.execute("DELETE FROM multipart_uploads WHERE upload_id = ?", params![upload_id])?;
+        // Combine the parts, streaming them into the final object
+        let mut writer = self.object_writer()?;
+        for content_hash in content_hashes {
+            io::copy(&mut self.open_object_data(content_hash)?, &mut writer)?;
+        }
+        let mut object = self.put_object_data(bucket, key, writer.finish()?, upload.content_type.as_deref(), upload.metadata.as_deref())?;
+        
+        {
+            let db = self.shard(Namespace::S3);
+            db.execute(
+                "UPDATE objects SET etag = ?1 WHERE bucket = ?2 AND key = ?3 AND version_id IS ?4 AND is_latest = 1",
+                params![etag, bucket, key, object.version_id],
+            )?;
+        }
+        object.etag = etag;
         
-        Ok(etag)
+        self.delete_multipart_upload(upload_id)?;
+        Ok(object)
+    }
+
+    /// Abort a multipart upload, discarding its parts
+    pub fn abort_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str) -> Result<()> {
+        self.get_multipart_upload(bucket, key, upload_id)?;
+        self.delete_multipart_upload(upload_id)
     }
 
-    pub fn abort_multipart_upload(&self, upload_id: &str) -> Result<()> {
-        let db = self.db.lock();
+    fn delete_multipart_upload(&self, upload_id: &str) -> Result<()> {
+        let db = self.shard(Namespace::S3);
+        db.execute("DELETE FROM multipart_parts WHERE upload_id = ?", params![upload_id])?;
         db.execute("DELETE FROM multipart_uploads WHERE upload_id = ?", params![upload_id])?;
         Ok(())
     }
+    
+    // ==================== Lifecycle ====================
+    
+    /// Apply the enabled lifecycle rules of every bucket as of `now`, counting `day` as one day.
+    ///
+    /// Expired current versions are deleted as a DELETE without a version ID would, leaving a
+    /// delete marker in versioned buckets. Noncurrent versions age from when their successor
+    /// was written.
+    pub fn apply_lifecycle(&self, now: DateTime<Utc>, day: chrono::Duration) -> Result<LifecycleActions> {
+        let configs: Vec<(String, String)> = {
+            let db = self.shard(Namespace::S3);
+            let mut stmt = db.prepare("SELECT name, lifecycle_rules FROM buckets WHERE lifecycle_rules IS NOT NULL")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<std::result::Result<_, _>>()?
+        };
+        
+        let mut actions = LifecycleActions::default();
+        for (bucket, json) in configs {
+            let config: LifecycleConfiguration = serde_json::from_str(&json)?;
+            for rule in config.rules.iter().filter(|rule| rule.status == "Enabled") {
+                self.apply_lifecycle_rule(&bucket, rule, now, day, &mut actions)?;
+            }
+        }
+        Ok(actions)
+    }
+    
+    fn apply_lifecycle_rule(&self, bucket: &str, rule: &LifecycleRule, now: DateTime<Utc>, day: chrono::Duration, actions: &mut LifecycleActions) -> Result<()> {
+        let older_than = |since: &str, days: u32| {
+            DateTime::parse_from_rfc3339(since).is_ok_and(|since| now - since.with_timezone(&Utc) >= day * days as i32)
+        };
+        let expiration_date = rule.expiration_date.as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+        
+        // Noncurrent versions, as (key, version ID, storage class, when the next version was written).
+        // Handled first, so that a version expired in this pass ages as noncurrent from the next one.
+        let noncurrent: Vec<(String, String, String, Option<String>)> = {
+            let db = self.shard(Namespace::S3);
+            let mut stmt = db.prepare(
+                r#"SELECT o.key, o.version_id, COALESCE(o.storage_class, 'STANDARD'),
+                          (SELECT MIN(n.last_modified) FROM objects n WHERE n.bucket = o.bucket AND n.key = o.key AND n.id > o.id)
+                   FROM objects o
+                   WHERE o.bucket = ?1 AND o.is_latest = 0 AND o.is_delete_marker = 0 AND o.version_id IS NOT NULL"#,
+            )?;
+            let rows = stmt.query_map(params![bucket], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+            rows.collect::<std::result::Result<_, _>>()?
+        };
+        for (key, version_id, storage_class, noncurrent_since) in noncurrent.into_iter().filter(|(key, ..)| key.starts_with(&rule.prefix)) {
+            let Some(since) = noncurrent_since else { continue };
+            if rule.noncurrent_version_expiration_days.is_some_and(|days| older_than(&since, days)) {
+                self.delete_object(bucket, &key, Some(&version_id))?;
+                actions.noncurrent_expired += 1;
+            } else if let Some(class) = transition_target(&rule.noncurrent_version_transitions, |days| older_than(&since, days)) {
+                if class != storage_class {
+                    let db = self.shard(Namespace::S3);
+                    db.execute(
+                        "UPDATE objects SET storage_class = ?1 WHERE bucket = ?2 AND key = ?3 AND version_id = ?4",
+                        params![class, bucket, key, version_id],
+                    )?;
+                    actions.transitioned += 1;
+                }
+            }
+        }
+        
+        // Current versions, as (key, last modified, storage class)
+        let current: Vec<(String, String, String)> = {
+            let db = self.shard(Namespace::S3);
+            let mut stmt = db.prepare(
+                "SELECT key, last_modified, COALESCE(storage_class, 'STANDARD') FROM objects WHERE bucket = ?1 AND is_latest = 1 AND is_delete_marker = 0",
+            )?;
+            let rows = stmt.query_map(params![bucket], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+            rows.collect::<std::result::Result<_, _>>()?
+        };
+        for (key, last_modified, storage_class) in current.into_iter().filter(|(key, ..)| key.starts_with(&rule.prefix)) {
+            let expired = rule.expiration_days.is_some_and(|days| older_than(&last_modified, days))
+                || expiration_date.is_some_and(|date| now >= date);
+            if expired {
+                self.delete_object(bucket, &key, None)?;
+                actions.expired += 1;
+            } else if let Some(class) = transition_target(&rule.transitions, |days| older_than(&last_modified, days)) {
+                if class != storage_class {
+                    let db = self.shard(Namespace::S3);
+                    db.execute(
+                        "UPDATE objects SET storage_class = ?1 WHERE bucket = ?2 AND key = ?3 AND is_latest = 1",
+                        params![class, bucket, key],
+                    )?;
+                    actions.transitioned += 1;
+                }
+            }
+        }
+        
+        if rule.expired_object_delete_marker {
+            let db = self.shard(Namespace::S3);
+            let mut stmt = db.prepare(
+                r#"SELECT o.id, o.key FROM objects o
+                   WHERE o.bucket = ?1 AND o.is_latest = 1 AND o.is_delete_marker = 1
+                     AND NOT EXISTS (SELECT 1 FROM objects n WHERE n.bucket = o.bucket AND n.key = o.key AND n.id != o.id)"#,
+            )?;
+            let markers: Vec<(i64, String)> = stmt.query_map(params![bucket], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<std::result::Result<_, _>>()?;
+            for (id, _) in markers.iter().filter(|(_, key)| key.starts_with(&rule.prefix)) {
+                db.execute("DELETE FROM objects WHERE id = ?1", params![id])?;
+                actions.delete_markers_removed += 1;
+            }
+        }
+        
+        if let Some(days) = rule.abort_incomplete_multipart_upload_days {
+            let uploads: Vec<(String, String, String)> = {
+                let db = self.shard(Namespace::S3);
+                let mut stmt = db.prepare("SELECT upload_id, key, initiated FROM multipart_uploads WHERE bucket = ?1")?;
+                let rows = stmt.query_map(params![bucket], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+                rows.collect::<std::result::Result<_, _>>()?
+            };
+            for (upload_id, _, _) in uploads.iter().filter(|(_, key, initiated)| key.starts_with(&rule.prefix) && older_than(initiated, days)) {
+                self.delete_multipart_upload(upload_id)?;
+                actions.uploads_aborted += 1;
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Storage class of the latest transition that is due
+fn transition_target(transitions: &[LifecycleTransition], due: impl Fn(u32) -> bool) -> Option<&str> {
+    transitions.iter()
+        .filter(|transition| due(transition.days))
+        .max_by_key(|transition| transition.days)
+        .map(|transition| transition.storage_class.as_str())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::engine::NotificationRule;
     
     #[test]
     fn test_s3_basic_operations() {
@@ -557,34 +1145,115 @@ mod tests {
         assert!(engine.get_object("test-bucket", "test.txt", None).is_err());
     }
     
+    #[test]
+    fn test_s3_snapshot_opens_as_data_directory() {
+        use crate::storage::Config;
+        use emu_storage::StorageBackend;
+
+        let dir = std::env::temp_dir().join(format!("cloudemu-snapshot-{}", uuid::Uuid::new_v4()));
+        let engine = StorageEngine::new(&Config::default().data_dir(dir.join("live"))).unwrap();
+        engine.create_bucket("test-bucket", "us-east-1").unwrap();
+        engine.put_object("test-bucket", "test.txt", b"Hello, S3!", Some("text/plain"), None).unwrap();
+
+        engine.snapshot(&dir.join("copy")).unwrap();
+        let restored = StorageEngine::new(&Config::default().data_dir(dir.join("copy"))).unwrap();
+        assert_eq!(restored.get_object("test-bucket", "test.txt", None).unwrap().1, b"Hello, S3!");
+        drop((engine, restored));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_s3_multipart_upload() {
         let engine = StorageEngine::in_memory().unwrap();
         engine.create_bucket("multipart-bucket", "us-east-1").unwrap();
         
         // Initiate multipart upload
-        let upload_id = engine.create_multipart_upload("multipart-bucket", "large.bin").unwrap();
+        let upload_id = engine.create_multipart_upload("multipart-bucket", "large.bin", Some("application/zip"), None).unwrap();
         assert!(!upload_id.is_empty());
         
-        // Upload parts
-        let part1 = b"Part 1 data";
+        // Upload parts; all but the last must be at least 5 MiB
+        let part1 = vec![b'a'; MIN_PART_SIZE as usize];
         let part2 = b"Part 2 data";
         
-        let etag1 = engine.upload_part(&upload_id, 1, part1).unwrap();
-        let etag2 = engine.upload_part(&upload_id, 2, part2).unwrap();
-        assert!(etag1.starts_with('"'));
-        assert!(etag2.starts_with('"'));
+        let etag1 = engine.upload_part("multipart-bucket", "large.bin", &upload_id, 1, &part1).unwrap().etag;
+        let etag2 = engine.upload_part("multipart-bucket", "large.bin", &upload_id, 2, part2).unwrap().etag;
+        assert_eq!(etag2, format!("\"{}\"", hex::encode(Md5::digest(part2))));
+        assert!(engine.upload_part("multipart-bucket", "large.bin", &upload_id, 0, part2).is_err());
+        assert!(engine.upload_part("multipart-bucket", "other.bin", &upload_id, 1, part2).is_err());
+        
+        let listed = engine.list_parts("multipart-bucket", "large.bin", &upload_id, 0).unwrap();
+        assert_eq!(listed.iter().map(|p| (p.part_number, p.size)).collect::<Vec<_>>(), vec![(1, MIN_PART_SIZE), (2, 11)]);
+        assert_eq!(engine.list_parts("multipart-bucket", "large.bin", &upload_id, 1).unwrap().len(), 1);
         
         // Complete upload
-        let final_etag = engine.complete_multipart_upload("multipart-bucket", "large.bin", &upload_id).unwrap();
-        assert!(final_etag.starts_with('"'));
+        let parts = vec![(1, etag1.clone()), (2, etag2.clone())];
+        let object = engine.complete_multipart_upload("multipart-bucket", "large.bin", &upload_id, &parts).unwrap();
+        let mut digests = Md5::digest(&part1).to_vec();
+        digests.extend(Md5::digest(part2));
+        let expected_etag = format!("\"{}-2\"", hex::encode(Md5::digest(&digests)));
+        assert_eq!(object.etag, expected_etag);
+        assert_eq!(object.content_type, "application/zip");
         
         // Verify combined file
-        let (_, data) = engine.get_object("multipart-bucket", "large.bin", None).unwrap();
-        let mut expected = Vec::new();
-        expected.extend_from_slice(part1);
+        let (meta, data) = engine.get_object("multipart-bucket", "large.bin", None).unwrap();
+        assert_eq!(meta.etag, expected_etag);
+        let mut expected = part1.clone();
         expected.extend_from_slice(part2);
         assert_eq!(data, expected);
+        
+        // The upload is gone once completed
+        assert!(matches!(
+            engine.complete_multipart_upload("multipart-bucket", "large.bin", &upload_id, &parts),
+            Err(EmulatorError::NoSuchUpload(_))
+        ));
+    }
+    
+    #[test]
+    fn test_s3_multipart_upload_validation() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("multipart-bucket", "us-east-1").unwrap();
+        assert!(engine.create_multipart_upload("missing", "key", None, None).is_err());
+        
+        let upload_id = engine.create_multipart_upload("multipart-bucket", "key", None, None).unwrap();
+        let small = engine.upload_part("multipart-bucket", "key", &upload_id, 1, b"small").unwrap().etag;
+        let last = engine.upload_part("multipart-bucket", "key", &upload_id, 2, b"last").unwrap().etag;
+        let code = |parts: &[(i32, String)]| match engine.complete_multipart_upload("multipart-bucket", "key", &upload_id, parts) {
+            Err(e) => e.code(),
+            Ok(_) => "",
+        };
+        
+        assert_eq!(code(&[(2, last.clone()), (1, small.clone())]), "InvalidPartOrder");
+        assert_eq!(code(&[(1, last.clone())]), "InvalidPart");
+        assert_eq!(code(&[(3, last.clone())]), "InvalidPart");
+        assert_eq!(code(&[(1, small.clone()), (2, last.clone())]), "EntityTooSmall");
+        assert_eq!(code(&[]), "MalformedXML");
+        
+        // A single part of any size is fine, and left-out parts are dropped
+        let object = engine.complete_multipart_upload("multipart-bucket", "key", &upload_id, &[(2, last.clone())]).unwrap();
+        assert!(object.etag.ends_with("-1\""));
+        assert_eq!(engine.get_object("multipart-bucket", "key", None).unwrap().1, b"last");
+        
+        let upload_id = engine.create_multipart_upload("multipart-bucket", "key", None, None).unwrap();
+        engine.abort_multipart_upload("multipart-bucket", "key", &upload_id).unwrap();
+        assert!(engine.list_parts("multipart-bucket", "key", &upload_id, 0).is_err());
+        assert!(engine.abort_multipart_upload("multipart-bucket", "key", &upload_id).is_err());
+    }
+    
+    #[test]
+    fn test_s3_upload_part_copy() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("multipart-bucket", "us-east-1").unwrap();
+        engine.put_object("multipart-bucket", "source", b"0123456789", None, None).unwrap();
+        
+        let upload_id = engine.create_multipart_upload("multipart-bucket", "copy", None, None).unwrap();
+        let part = engine.upload_part_copy("multipart-bucket", "copy", &upload_id, 1, "multipart-bucket", "source", None, Some((2, 5))).unwrap();
+        assert_eq!(part.size, 4);
+        assert_eq!(part.etag, format!("\"{}\"", hex::encode(Md5::digest(b"2345"))));
+        assert!(engine.upload_part_copy("multipart-bucket", "copy", &upload_id, 2, "multipart-bucket", "source", None, Some((5, 10))).is_err());
+        assert!(engine.upload_part_copy("multipart-bucket", "copy", &upload_id, 2, "multipart-bucket", "missing", None, None).is_err());
+        
+        engine.complete_multipart_upload("multipart-bucket", "copy", &upload_id, &[(1, part.etag)]).unwrap();
+        assert_eq!(engine.get_object("multipart-bucket", "copy", None).unwrap().1, b"2345");
     }
     
     #[test]
@@ -609,4 +1278,192 @@ mod tests {
         let (_, v2_data) = engine.get_object("versioned", "file.txt", obj2.version_id.as_deref()).unwrap();
         assert_eq!(v2_data, b"v2");
     }
+    
+    #[test]
+    fn test_s3_replication() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("source", "us-east-1").unwrap();
+        engine.create_bucket("replica", "eu-west-1").unwrap();
+        
+        let config = ReplicationConfiguration {
+            role: "arn:aws:iam::000000000000:role/replication".to_string(),
+            rules: vec![ReplicationRule {
+                id: "logs".to_string(),
+                priority: 1,
+                status: "Enabled".to_string(),
+                prefix: "logs/".to_string(),
+                destination_bucket: "replica".to_string(),
+                destination_account: Some("111111111111".to_string()),
+                storage_class: Some("STANDARD_IA".to_string()),
+                delete_marker_replication: false,
+            }],
+        };
+        
+        // Versioning is required on the source bucket
+        assert!(engine.set_bucket_replication("source", &config).is_err());
+        engine.set_bucket_versioning("source", "Enabled").unwrap();
+        engine.set_bucket_replication("source", &config).unwrap();
+        assert_eq!(engine.get_bucket_replication("source").unwrap().unwrap().rules.len(), 1);
+        
+        // Only keys under the rule prefix match
+        assert!(engine.match_replication_rule("source", "other/a.txt").unwrap().is_none());
+        let rule = engine.match_replication_rule("source", "logs/a.txt").unwrap().unwrap();
+        
+        let obj = engine.put_object("source", "logs/a.txt", b"entry", Some("text/plain"), None).unwrap();
+        assert_eq!(obj.replication_status.as_deref(), Some("PENDING"));
+        let replica = engine.replicate_object("source", "logs/a.txt", obj.version_id.as_deref(), &rule).unwrap();
+        assert_eq!(replica.replication_status.as_deref(), Some("REPLICA"));
+        
+        let (src_meta, _) = engine.get_object("source", "logs/a.txt", None).unwrap();
+        assert_eq!(src_meta.replication_status.as_deref(), Some("COMPLETED"));
+        
+        let (dst_meta, dst_data) = engine.get_object("replica", "logs/a.txt", None).unwrap();
+        assert_eq!(dst_data, b"entry");
+        assert_eq!(dst_meta.content_type, "text/plain");
+        assert_eq!(dst_meta.storage_class, "STANDARD_IA");
+        assert_eq!(dst_meta.replication_status.as_deref(), Some("REPLICA"));
+        
+        engine.delete_bucket_replication("source").unwrap();
+        assert!(engine.get_bucket_replication("source").unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_s3_website_config() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("site", "us-east-1").unwrap();
+        assert!(engine.get_bucket_website("site").unwrap().is_none());
+        
+        // Either an index document or a redirect-all target is required
+        assert!(engine.set_bucket_website("site", &WebsiteConfiguration::default()).is_err());
+        
+        let config = WebsiteConfiguration {
+            index_document: Some("index.html".to_string()),
+            error_document: Some("404.html".to_string()),
+            ..Default::default()
+        };
+        engine.set_bucket_website("site", &config).unwrap();
+        let stored = engine.get_bucket_website("site").unwrap().unwrap();
+        assert_eq!(stored.index_document.as_deref(), Some("index.html"));
+        assert_eq!(stored.error_document.as_deref(), Some("404.html"));
+        
+        engine.delete_bucket_website("site").unwrap();
+        assert!(engine.get_bucket_website("site").unwrap().is_none());
+        assert!(engine.set_bucket_website("missing", &config).is_err());
+    }
+    
+    #[test]
+    fn test_s3_notification_config() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("uploads", "us-east-1").unwrap();
+        assert!(engine.get_bucket_notification("uploads").unwrap().queue_configurations.is_empty());
+        
+        let config = NotificationConfiguration {
+            queue_configurations: vec![NotificationRule {
+                id: "images".to_string(),
+                destination_arn: "arn:aws:sqs:us-east-1:000000000000:images".to_string(),
+                events: vec!["s3:ObjectCreated:*".to_string()],
+                prefix: Some("images/".to_string()),
+                suffix: None,
+            }],
+            ..Default::default()
+        };
+        engine.set_bucket_notification("uploads", &config).unwrap();
+        let stored = engine.get_bucket_notification("uploads").unwrap();
+        assert_eq!(stored.queue_configurations.len(), 1);
+        assert_eq!(stored.queue_configurations[0].prefix.as_deref(), Some("images/"));
+        
+        assert!(engine.get_bucket_notification("missing").is_err());
+        assert!(engine.set_bucket_notification("missing", &config).is_err());
+    }
+    
+    #[test]
+    fn test_s3_streamed_object_data() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("stream", "us-east-1").unwrap();
+        
+        // Chunked writes address the same content as a single write
+        let mut writer = engine.object_writer().unwrap();
+        for chunk in [&b"Hello, "[..], b"streamed ", b"S3!"] {
+            writer.write_all(chunk).unwrap();
+        }
+        let stored = writer.finish().unwrap();
+        assert_eq!(stored.size, 19);
+        assert_eq!(stored.content_hash, engine.store_object_data(b"Hello, streamed S3!").unwrap());
+        
+        let meta = engine.put_object_data("stream", "a.txt", stored, Some("text/plain"), None).unwrap();
+        let (_, mut file) = engine.open_object("stream", "a.txt", None).unwrap();
+        let mut data = String::new();
+        file.read_to_string(&mut data).unwrap();
+        assert_eq!(data, "Hello, streamed S3!");
+        assert_eq!(engine.head_object("stream", "a.txt", None).unwrap().etag, meta.etag);
+        
+        // Copies reference the source data
+        let copy = engine.copy_object("stream", "a.txt", "stream", "b.txt").unwrap();
+        assert_eq!(copy.etag, meta.etag);
+        assert_eq!(copy.content_type, "text/plain");
+        assert_eq!(engine.get_object("stream", "b.txt", None).unwrap().1, b"Hello, streamed S3!");
+        
+        // An abandoned writer leaves nothing behind
+        let mut writer = engine.object_writer().unwrap();
+        writer.write_all(b"partial").unwrap();
+        drop(writer);
+        assert_eq!(fs::read_dir(engine.blobs.dir().join("tmp")).unwrap().count(), 0);
+    }
+    
+    #[test]
+    fn test_s3_lifecycle_rules() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_bucket("aging", "us-east-1").unwrap();
+        engine.set_bucket_versioning("aging", "Enabled").unwrap();
+        assert!(engine.get_bucket_lifecycle("aging").unwrap().is_none());
+        
+        let config = LifecycleConfiguration {
+            rules: vec![
+                LifecycleRule {
+                    id: "logs".into(),
+                    status: "Enabled".into(),
+                    prefix: "logs/".into(),
+                    expiration_days: Some(30),
+                    expired_object_delete_marker: false,
+                    transitions: vec![LifecycleTransition { days: 10, storage_class: "GLACIER".into() }],
+                    noncurrent_version_expiration_days: Some(5),
+                    abort_incomplete_multipart_upload_days: Some(1),
+                    ..Default::default()
+                },
+                LifecycleRule {
+                    id: "disabled".into(),
+                    status: "Disabled".into(),
+                    expiration_days: Some(1),
+                    ..Default::default()
+                },
+            ],
+        };
+        engine.set_bucket_lifecycle("aging", &config).unwrap();
+        assert_eq!(engine.get_bucket_lifecycle("aging").unwrap().unwrap().rules.len(), 2);
+        
+        let v1 = engine.put_object("aging", "logs/a.log", b"v1", None, None).unwrap();
+        engine.put_object("aging", "logs/a.log", b"v2", None, None).unwrap();
+        engine.put_object("aging", "data/b.bin", b"kept", None, None).unwrap();
+        engine.create_multipart_upload("aging", "logs/big.log", None, None).unwrap();
+        
+        let day = chrono::Duration::days(1);
+        let now = Utc::now();
+        assert_eq!(engine.apply_lifecycle(now, day).unwrap(), LifecycleActions::default());
+        
+        // Transitioned, the noncurrent version expired and the upload aborted
+        let actions = engine.apply_lifecycle(now + chrono::Duration::days(12), day).unwrap();
+        assert_eq!(actions, LifecycleActions { transitioned: 1, noncurrent_expired: 1, uploads_aborted: 1, ..Default::default() });
+        assert_eq!(engine.head_object("aging", "logs/a.log", None).unwrap().storage_class, "GLACIER");
+        assert!(engine.head_object("aging", "logs/a.log", v1.version_id.as_deref()).is_err());
+        
+        // Expired behind a delete marker; objects outside the prefix are kept
+        let actions = engine.apply_lifecycle(now + chrono::Duration::days(31), day).unwrap();
+        assert_eq!(actions, LifecycleActions { expired: 1, ..Default::default() });
+        assert!(engine.head_object("aging", "logs/a.log", None).is_err());
+        assert_eq!(engine.get_object("aging", "data/b.bin", None).unwrap().1, b"kept");
+        
+        engine.delete_bucket_lifecycle("aging").unwrap();
+        assert!(engine.get_bucket_lifecycle("aging").unwrap().is_none());
+        assert!(engine.set_bucket_lifecycle("missing", &config).is_err());
+    }
 }
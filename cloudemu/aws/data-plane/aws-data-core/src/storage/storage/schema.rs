@@ -1,5 +1,7 @@
 //! SQLite schema for the emulator
 
+use rusqlite::Connection;
+
 /// SQL to create all tables
 pub const SCHEMA: &str = r#"
 -- Buckets table
@@ -19,6 +21,8 @@ CREATE TABLE IF NOT EXISTS buckets (
     cors_rules TEXT,
     notification_config TEXT,
     public_access_block TEXT,
+    replication_config TEXT,
+    website_config TEXT,
     tags TEXT,
     
     -- Flags
@@ -49,6 +53,7 @@ CREATE TABLE IF NOT EXISTS objects (
     last_modified TEXT NOT NULL,
     metadata TEXT,
     storage_class TEXT DEFAULT 'STANDARD',
+    replication_status TEXT,
     
     -- Constraints
     FOREIGN KEY (bucket) REFERENCES buckets(name) ON DELETE CASCADE
@@ -66,6 +71,7 @@ CREATE TABLE IF NOT EXISTS multipart_uploads (
     bucket TEXT NOT NULL,
     key TEXT NOT NULL,
     initiated TEXT NOT NULL,
+    content_type TEXT,
     metadata TEXT,
     
     FOREIGN KEY (bucket) REFERENCES buckets(name) ON DELETE CASCADE
@@ -236,6 +242,20 @@ CREATE TABLE IF NOT EXISTS cw_log_events (
     FOREIGN KEY (log_group_name, log_stream_name) REFERENCES cw_log_streams(log_group_name, name) ON DELETE CASCADE
 );
 
+-- CloudWatch Logs Subscription Filters
+CREATE TABLE IF NOT EXISTS cw_subscription_filters (
+    log_group_name TEXT NOT NULL,
+    filter_name TEXT NOT NULL,
+    filter_pattern TEXT NOT NULL,
+    destination_arn TEXT NOT NULL,
+    role_arn TEXT,
+    distribution TEXT NOT NULL DEFAULT 'ByLogStream',
+    created_at TEXT NOT NULL,
+    
+    PRIMARY KEY (log_group_name, filter_name),
+    FOREIGN KEY (log_group_name) REFERENCES cw_log_groups(name) ON DELETE CASCADE
+);
+
 -- Cognito User Pools
 CREATE TABLE IF NOT EXISTS cognito_user_pools (
     id TEXT PRIMARY KEY,
@@ -280,6 +300,28 @@ CREATE TABLE IF NOT EXISTS cognito_user_attributes (
     FOREIGN KEY (user_pool_id, username) REFERENCES cognito_users(user_pool_id, username) ON DELETE CASCADE
 );
 
+-- Cognito Identity Pools
+CREATE TABLE IF NOT EXISTS cognito_identity_pools (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    allow_unauthenticated BOOLEAN DEFAULT 0,
+    providers TEXT NOT NULL DEFAULT '[]',
+    roles TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL
+);
+
+-- Cognito Identities; an authenticated identity's login is its provider and subject,
+-- unauthenticated identities have none
+CREATE TABLE IF NOT EXISTS cognito_identities (
+    identity_id TEXT PRIMARY KEY,
+    identity_pool_id TEXT NOT NULL,
+    login TEXT,
+    created_at TEXT NOT NULL,
+
+    UNIQUE (identity_pool_id, login),
+    FOREIGN KEY (identity_pool_id) REFERENCES cognito_identity_pools(id) ON DELETE CASCADE
+);
+
 -- Step Functions State Machines
 CREATE TABLE IF NOT EXISTS sf_state_machines (
     arn TEXT PRIMARY KEY,
@@ -314,6 +356,7 @@ CREATE TABLE IF NOT EXISTS sqs_queues (
     message_retention_period INTEGER DEFAULT 345600,
     delay_seconds INTEGER DEFAULT 0,
     receive_message_wait_time_seconds INTEGER DEFAULT 0,
+    maximum_message_size INTEGER DEFAULT 1048576,
     policy TEXT,
     tags TEXT
 );
@@ -359,6 +402,33 @@ CREATE TABLE IF NOT EXISTS ddb_items (
     FOREIGN KEY (table_name) REFERENCES ddb_tables(name) ON DELETE CASCADE
 );
 
+-- DynamoDB point-in-time recovery: tables with PITR enabled
+CREATE TABLE IF NOT EXISTS ddb_pitr (
+    table_name TEXT PRIMARY KEY,
+    enabled_at INTEGER NOT NULL,
+    FOREIGN KEY (table_name) REFERENCES ddb_tables(name) ON DELETE CASCADE
+);
+
+-- DynamoDB change journal for PITR tables (item_json NULL = item deleted)
+CREATE TABLE IF NOT EXISTS ddb_journal (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    table_name TEXT NOT NULL,
+    partition_key TEXT NOT NULL,
+    sort_key TEXT,
+    item_json TEXT,
+    changed_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ddb_journal_key ON ddb_journal(table_name, partition_key, sort_key, changed_at);
+
+-- DynamoDB Streams: tables whose item changes are recorded in the shared stream store
+CREATE TABLE IF NOT EXISTS ddb_streams (
+    table_name TEXT PRIMARY KEY,
+    stream_arn TEXT NOT NULL UNIQUE,
+    view_type TEXT NOT NULL,
+    FOREIGN KEY (table_name) REFERENCES ddb_tables(name) ON DELETE CASCADE
+);
+
 -- SNS Topics
 CREATE TABLE IF NOT EXISTS sns_topics (
     name TEXT PRIMARY KEY,
@@ -381,6 +451,41 @@ CREATE TABLE IF NOT EXISTS sns_subscriptions (
     FOREIGN KEY (topic_arn) REFERENCES sns_topics(arn) ON DELETE CASCADE
 );
 
+-- SNS Platform Applications (mobile push)
+CREATE TABLE IF NOT EXISTS sns_platform_applications (
+    arn TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    platform TEXT NOT NULL,
+    attributes TEXT,
+    created_at TEXT NOT NULL
+);
+
+-- SNS Platform Endpoints (devices of a platform application)
+CREATE TABLE IF NOT EXISTS sns_platform_endpoints (
+    arn TEXT PRIMARY KEY,
+    application_arn TEXT NOT NULL,
+    token TEXT NOT NULL,
+    custom_user_data TEXT,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL,
+    UNIQUE (application_arn, token),
+    FOREIGN KEY (application_arn) REFERENCES sns_platform_applications(arn) ON DELETE CASCADE
+);
+
+-- SNS SMS and push messages, recorded instead of sent
+CREATE TABLE IF NOT EXISTS sns_sandbox_messages (
+    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+    message_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    platform TEXT,
+    message TEXT NOT NULL,
+    subject TEXT,
+    attributes TEXT,
+    topic_arn TEXT,
+    sent_at TEXT NOT NULL
+);
+
 -- Lambda Functions
 CREATE TABLE IF NOT EXISTS lambda_functions (
     name TEXT PRIMARY KEY,
@@ -402,6 +507,8 @@ CREATE TABLE IF NOT EXISTS vpc_vpcs (
     cidr_block TEXT NOT NULL,
     state TEXT DEFAULT 'available',
     is_default INTEGER DEFAULT 0,
+    enable_dns_support INTEGER DEFAULT 1,
+    enable_dns_hostnames INTEGER DEFAULT 0,
     tags TEXT
 );
 
@@ -427,6 +534,54 @@ CREATE TABLE IF NOT EXISTS vpc_security_groups (
     FOREIGN KEY (vpc_id) REFERENCES vpc_vpcs(id) ON DELETE CASCADE
 );
 
+-- Internet Gateways table
+CREATE TABLE IF NOT EXISTS vpc_internet_gateways (
+    id TEXT PRIMARY KEY,
+    vpc_id TEXT, -- attached VPC
+    tags TEXT
+);
+
+-- Route Tables table
+CREATE TABLE IF NOT EXISTS vpc_route_tables (
+    id TEXT PRIMARY KEY,
+    vpc_id TEXT NOT NULL,
+    is_main INTEGER DEFAULT 0,
+    tags TEXT,
+    FOREIGN KEY (vpc_id) REFERENCES vpc_vpcs(id) ON DELETE CASCADE
+);
+
+-- Routes table
+CREATE TABLE IF NOT EXISTS vpc_routes (
+    route_table_id TEXT NOT NULL,
+    destination_cidr_block TEXT NOT NULL,
+    gateway_id TEXT, -- 'local' or an internet gateway
+    nat_gateway_id TEXT,
+    origin TEXT NOT NULL DEFAULT 'CreateRoute', -- CreateRouteTable | CreateRoute
+    PRIMARY KEY (route_table_id, destination_cidr_block),
+    FOREIGN KEY (route_table_id) REFERENCES vpc_route_tables(id) ON DELETE CASCADE
+);
+
+-- Route Table subnet associations table
+CREATE TABLE IF NOT EXISTS vpc_route_table_associations (
+    id TEXT PRIMARY KEY,
+    route_table_id TEXT NOT NULL,
+    subnet_id TEXT NOT NULL UNIQUE,
+    FOREIGN KEY (route_table_id) REFERENCES vpc_route_tables(id) ON DELETE CASCADE
+);
+
+-- NAT Gateways table
+CREATE TABLE IF NOT EXISTS vpc_nat_gateways (
+    id TEXT PRIMARY KEY,
+    vpc_id TEXT NOT NULL,
+    subnet_id TEXT NOT NULL,
+    state TEXT DEFAULT 'available', -- available | deleted
+    connectivity_type TEXT NOT NULL DEFAULT 'public',
+    private_ip TEXT NOT NULL,
+    public_ip TEXT,
+    created_at TEXT NOT NULL,
+    tags TEXT
+);
+
 -- EC2 Instances table
 CREATE TABLE IF NOT EXISTS ec2_instances (
     id TEXT PRIMARY KEY,
@@ -439,6 +594,7 @@ CREATE TABLE IF NOT EXISTS ec2_instances (
     vpc_id TEXT,
     subnet_id TEXT,
     security_groups TEXT, -- JSON array of IDs
+    iam_instance_profile TEXT, -- instance profile ARN
     launch_time TEXT NOT NULL,
     tags TEXT,
     FOREIGN KEY (vpc_id) REFERENCES vpc_vpcs(id) ON DELETE SET NULL,
@@ -476,3 +632,62 @@ CREATE TABLE IF NOT EXISTS pricing_terms (
 );
 
 "#;
+
+/// Columns added to tables after they were first released, as (table, column, declaration).
+/// `CREATE TABLE IF NOT EXISTS` leaves the tables of an existing database as they were, so
+/// [`migrate`] adds these to them.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("buckets", "replication_config", "TEXT"),
+    ("objects", "replication_status", "TEXT"),
+    ("buckets", "website_config", "TEXT"),
+    ("vpc_vpcs", "enable_dns_support", "INTEGER DEFAULT 1"),
+    ("vpc_vpcs", "enable_dns_hostnames", "INTEGER DEFAULT 0"),
+    ("ec2_instances", "iam_instance_profile", "TEXT"),
+    ("sqs_queues", "maximum_message_size", "INTEGER DEFAULT 1048576"),
+    ("multipart_uploads", "content_type", "TEXT"),
+];
+
+/// Bring the tables of a database created by an earlier version up to [`SCHEMA`]
+pub fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    for (table, column, declaration) in ADDED_COLUMNS {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+            [table, column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, declaration), [])?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_adds_columns_to_existing_tables() {
+        // The schema as released before the added columns
+        let released: String = SCHEMA.lines()
+            .filter(|line| !ADDED_COLUMNS.iter().any(|(_, column, _)| line.trim_start().starts_with(&format!("{} ", column))))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&released).unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+
+        let missing = |conn: &Connection| -> Vec<&str> {
+            ADDED_COLUMNS.iter().filter(|(table, column, _)| !conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+                [table, column],
+                |row| row.get::<_, bool>(0),
+            ).unwrap()).map(|(_, column, _)| *column).collect()
+        };
+        assert_eq!(missing(&conn).len(), ADDED_COLUMNS.len());
+
+        migrate(&conn).unwrap();
+        migrate(&conn).unwrap();
+        assert!(missing(&conn).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, SecretMetadata, SecretValue};
+use super::engine::{StorageEngine, Namespace, SecretMetadata, SecretValue};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -7,7 +7,7 @@ impl StorageEngine {
 
     /// Create a secret
     pub fn create_secret(&self, name: &str, description: Option<&str>, tags: Option<&str>, account_id: &str, region: &str) -> Result<SecretMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Secrets);
         let now = chrono::Utc::now().to_rfc3339();
         let arn = format!("arn:aws:secretsmanager:{}:{}:secret:{}", region, account_id, name);
 
@@ -34,7 +34,7 @@ impl StorageEngine {
     
     /// Put secret value
     pub fn put_secret_value(&self, secret_id: &str, secret_string: Option<&str>, secret_binary: Option<&[u8]>) -> Result<(String, String)> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Secrets);
         
         let arn: String = db.query_row(
             "SELECT arn FROM secrets WHERE name = ?1 OR arn = ?1",
@@ -61,7 +61,7 @@ impl StorageEngine {
 
     /// Get secret value
     pub fn get_secret_value(&self, secret_id: &str, version_id: Option<&str>, _version_stage: Option<&str>) -> Result<SecretValue> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Secrets);
         let (arn, name): (String, String) = db.query_row(
             "SELECT arn, name FROM secrets WHERE name = ?1 OR arn = ?1",
             params![secret_id],
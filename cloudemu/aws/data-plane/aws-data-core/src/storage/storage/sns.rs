@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, TopicMetadata};
+use super::engine::{StorageEngine, Namespace, TopicMetadata, PlatformApplicationMetadata, PlatformEndpointMetadata, SandboxMessage};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -9,7 +9,7 @@ impl StorageEngine {
         let arn = format!("arn:aws:sns:{}:{}:{}", region, account_id, name);
         let created_at = chrono::Utc::now().to_rfc3339();
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sns);
         db.execute(
             "INSERT INTO sns_topics (name, arn, created_at) VALUES (?, ?, ?)",
             params![name, arn, created_at],
@@ -30,21 +30,26 @@ impl StorageEngine {
     }
 
     pub fn subscribe(&self, topic_arn: &str, protocol: &str, endpoint: &str) -> Result<String> {
+        self.subscribe_with_attributes(topic_arn, protocol, endpoint, None)
+    }
+
+    /// Subscribe with subscription attributes (JSON object), e.g. `RawMessageDelivery`
+    pub fn subscribe_with_attributes(&self, topic_arn: &str, protocol: &str, endpoint: &str, attributes: Option<&str>) -> Result<String> {
         let sub_id = uuid::Uuid::new_v4().to_string();
         let sub_arn = format!("{}:{}", topic_arn, sub_id);
         let created_at = chrono::Utc::now().to_rfc3339();
         
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sns);
         db.execute(
-            "INSERT INTO sns_subscriptions (arn, topic_arn, protocol, endpoint, created_at) VALUES (?, ?, ?, ?, ?)",
-            params![sub_arn, topic_arn, protocol, endpoint, created_at],
+            "INSERT INTO sns_subscriptions (arn, topic_arn, protocol, endpoint, created_at, subscription_attributes) VALUES (?, ?, ?, ?, ?, ?)",
+            params![sub_arn, topic_arn, protocol, endpoint, created_at, attributes],
         )?;
         
         Ok(sub_arn)
     }
 
     pub fn list_topics(&self) -> Result<Vec<TopicMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sns);
         let mut stmt = db.prepare("SELECT name, arn, display_name, created_at FROM sns_topics")?;
         let rows = stmt.query_map([], |row| {
             Ok(TopicMetadata {
@@ -63,9 +68,9 @@ impl StorageEngine {
     }
 
     pub fn list_subscriptions_by_topic(&self, topic_arn: &str) -> Result<Vec<super::engine::SubscriptionMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sns);
         let mut stmt = db.prepare(
-            "SELECT arn, topic_arn, protocol, endpoint, created_at FROM sns_subscriptions WHERE topic_arn = ?"
+            "SELECT arn, topic_arn, protocol, endpoint, created_at, subscription_attributes FROM sns_subscriptions WHERE topic_arn = ?"
         )?;
         let rows = stmt.query_map(params![topic_arn], |row| {
             Ok(super::engine::SubscriptionMetadata {
@@ -74,6 +79,7 @@ impl StorageEngine {
                 protocol: row.get(2)?,
                 endpoint: row.get(3)?,
                 created_at: row.get(4)?,
+                attributes: row.get(5)?,
             })
         })?;
         
@@ -83,6 +89,173 @@ impl StorageEngine {
         }
         Ok(result)
     }
+
+    // ==================== SNS Mobile Push ====================
+
+    pub fn create_platform_application(&self, name: &str, platform: &str, attributes: Option<&str>, account_id: &str, region: &str) -> Result<PlatformApplicationMetadata> {
+        let application = PlatformApplicationMetadata {
+            arn: format!("arn:aws:sns:{}:{}:app/{}/{}", region, account_id, platform, name),
+            name: name.to_string(),
+            platform: platform.to_string(),
+            attributes: attributes.map(str::to_string),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let db = self.shard(Namespace::Sns);
+        db.execute(
+            "INSERT INTO sns_platform_applications (arn, name, platform, attributes, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![application.arn, application.name, application.platform, application.attributes, application.created_at],
+        ).map_err(|e| {
+            if e.to_string().contains("UNIQUE constraint") {
+                EmulatorError::AlreadyExists(format!("Platform application {} already exists", name))
+            } else {
+                EmulatorError::Database(e.to_string())
+            }
+        })?;
+        Ok(application)
+    }
+
+    fn platform_application_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlatformApplicationMetadata> {
+        Ok(PlatformApplicationMetadata {
+            arn: row.get(0)?,
+            name: row.get(1)?,
+            platform: row.get(2)?,
+            attributes: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    pub fn get_platform_application(&self, arn: &str) -> Result<PlatformApplicationMetadata> {
+        let db = self.shard(Namespace::Sns);
+        db.query_row(
+            "SELECT arn, name, platform, attributes, created_at FROM sns_platform_applications WHERE arn = ?1",
+            params![arn],
+            Self::platform_application_from_row,
+        ).map_err(|_| EmulatorError::NotFound("PlatformApplication".into(), arn.into()))
+    }
+
+    pub fn list_platform_applications(&self) -> Result<Vec<PlatformApplicationMetadata>> {
+        let db = self.shard(Namespace::Sns);
+        let mut stmt = db.prepare("SELECT arn, name, platform, attributes, created_at FROM sns_platform_applications ORDER BY arn")?;
+        let applications = stmt.query_map([], Self::platform_application_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(applications)
+    }
+
+    /// Delete a platform application with its endpoints
+    pub fn delete_platform_application(&self, arn: &str) -> Result<()> {
+        let db = self.shard(Namespace::Sns);
+        db.execute("DELETE FROM sns_platform_endpoints WHERE application_arn = ?1", params![arn])?;
+        db.execute("DELETE FROM sns_platform_applications WHERE arn = ?1", params![arn])?;
+        Ok(())
+    }
+
+    /// Endpoint of a device token, created on first use; the same token always gets the
+    /// same endpoint, as in SNS
+    pub fn create_platform_endpoint(&self, application_arn: &str, token: &str, custom_user_data: Option<&str>) -> Result<PlatformEndpointMetadata> {
+        self.get_platform_application(application_arn)?;
+        let db = self.shard(Namespace::Sns);
+        let arn = format!("{}/{}", application_arn.replacen(":app/", ":endpoint/", 1), uuid::Uuid::new_v4());
+        db.execute(
+            "INSERT OR IGNORE INTO sns_platform_endpoints (arn, application_arn, token, custom_user_data, enabled, created_at) VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+            params![arn, application_arn, token, custom_user_data, chrono::Utc::now().to_rfc3339()],
+        )?;
+        let arn: String = db.query_row(
+            "SELECT arn FROM sns_platform_endpoints WHERE application_arn = ?1 AND token = ?2",
+            params![application_arn, token],
+            |row| row.get(0),
+        )?;
+        drop(db);
+        self.get_platform_endpoint(&arn)
+    }
+
+    fn platform_endpoint_from_row(row: &rusqlite::Row) -> rusqlite::Result<PlatformEndpointMetadata> {
+        Ok(PlatformEndpointMetadata {
+            arn: row.get(0)?,
+            application_arn: row.get(1)?,
+            token: row.get(2)?,
+            custom_user_data: row.get(3)?,
+            enabled: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }
+
+    pub fn get_platform_endpoint(&self, arn: &str) -> Result<PlatformEndpointMetadata> {
+        let db = self.shard(Namespace::Sns);
+        db.query_row(
+            "SELECT arn, application_arn, token, custom_user_data, enabled, created_at FROM sns_platform_endpoints WHERE arn = ?1",
+            params![arn],
+            Self::platform_endpoint_from_row,
+        ).map_err(|_| EmulatorError::NotFound("Endpoint".into(), arn.into()))
+    }
+
+    pub fn list_endpoints_by_platform_application(&self, application_arn: &str) -> Result<Vec<PlatformEndpointMetadata>> {
+        self.get_platform_application(application_arn)?;
+        let db = self.shard(Namespace::Sns);
+        let mut stmt = db.prepare(
+            "SELECT arn, application_arn, token, custom_user_data, enabled, created_at FROM sns_platform_endpoints WHERE application_arn = ?1 ORDER BY created_at"
+        )?;
+        let endpoints = stmt.query_map(params![application_arn], Self::platform_endpoint_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(endpoints)
+    }
+
+    /// Enable or disable an endpoint; publishing to a disabled endpoint fails
+    pub fn set_endpoint_enabled(&self, arn: &str, enabled: bool) -> Result<()> {
+        let db = self.shard(Namespace::Sns);
+        let rows = db.execute("UPDATE sns_platform_endpoints SET enabled = ?2 WHERE arn = ?1", params![arn, enabled])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Endpoint".into(), arn.into()));
+        }
+        Ok(())
+    }
+
+    pub fn delete_endpoint(&self, arn: &str) -> Result<()> {
+        let db = self.shard(Namespace::Sns);
+        db.execute("DELETE FROM sns_platform_endpoints WHERE arn = ?1", params![arn])?;
+        Ok(())
+    }
+
+    // ==================== SNS Sandbox ====================
+
+    /// Record an SMS or push notification SNS would have sent
+    pub fn record_sandbox_message(&self, message: &SandboxMessage) -> Result<()> {
+        let db = self.shard(Namespace::Sns);
+        db.execute(
+            "INSERT INTO sns_sandbox_messages (message_id, kind, destination, platform, message, subject, attributes, topic_arn, sent_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![message.message_id, message.kind, message.destination, message.platform, message.message,
+                message.subject, message.attributes, message.topic_arn, message.sent_at],
+        )?;
+        Ok(())
+    }
+
+    /// Recorded messages in the order they were sent, optionally of one kind or destination
+    pub fn list_sandbox_messages(&self, kind: Option<&str>, destination: Option<&str>) -> Result<Vec<SandboxMessage>> {
+        let db = self.shard(Namespace::Sns);
+        let mut stmt = db.prepare(
+            "SELECT message_id, kind, destination, platform, message, subject, attributes, topic_arn, sent_at FROM sns_sandbox_messages
+             WHERE (?1 IS NULL OR kind = ?1) AND (?2 IS NULL OR destination = ?2) ORDER BY seq"
+        )?;
+        let messages = stmt.query_map(params![kind, destination], |row| Ok(SandboxMessage {
+                message_id: row.get(0)?,
+                kind: row.get(1)?,
+                destination: row.get(2)?,
+                platform: row.get(3)?,
+                message: row.get(4)?,
+                subject: row.get(5)?,
+                attributes: row.get(6)?,
+                topic_arn: row.get(7)?,
+                sent_at: row.get(8)?,
+            }))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(messages)
+    }
+
+    /// Forget every recorded message, returning how many there were
+    pub fn clear_sandbox_messages(&self) -> Result<usize> {
+        let db = self.shard(Namespace::Sns);
+        Ok(db.execute("DELETE FROM sns_sandbox_messages", [])?)
+    }
 }
 
 #[cfg(test)]
@@ -113,5 +286,42 @@ mod tests {
         assert_eq!(subs[0].protocol, "sqs");
         assert_eq!(subs[0].endpoint, "arn:aws:sqs:us-east-1:123:queue");
     }
-}
 
+    #[test]
+    fn test_platform_endpoints_and_sandbox() {
+        let engine = StorageEngine::in_memory().unwrap();
+        let app = engine.create_platform_application("mobile", "GCM", None, "123456789012", "us-east-1").unwrap();
+        assert_eq!(app.arn, "arn:aws:sns:us-east-1:123456789012:app/GCM/mobile");
+        assert!(engine.create_platform_application("mobile", "GCM", None, "123456789012", "us-east-1").is_err());
+
+        // A token keeps its endpoint
+        let endpoint = engine.create_platform_endpoint(&app.arn, "device-token", Some("alice")).unwrap();
+        assert!(endpoint.arn.starts_with("arn:aws:sns:us-east-1:123456789012:endpoint/GCM/mobile/"));
+        assert_eq!(engine.create_platform_endpoint(&app.arn, "device-token", None).unwrap().arn, endpoint.arn);
+        engine.set_endpoint_enabled(&endpoint.arn, false).unwrap();
+        assert!(!engine.get_platform_endpoint(&endpoint.arn).unwrap().enabled);
+        assert_eq!(engine.list_endpoints_by_platform_application(&app.arn).unwrap().len(), 1);
+
+        let message = |kind: &str, destination: &str| SandboxMessage {
+            message_id: uuid::Uuid::new_v4().to_string(),
+            kind: kind.into(),
+            destination: destination.into(),
+            platform: None,
+            message: "hello".into(),
+            subject: None,
+            attributes: None,
+            topic_arn: None,
+            sent_at: chrono::Utc::now().to_rfc3339(),
+        };
+        engine.record_sandbox_message(&message("sms", "+15555550100")).unwrap();
+        engine.record_sandbox_message(&message("push", &endpoint.arn)).unwrap();
+        engine.record_sandbox_message(&message("sms", "+15555550101")).unwrap();
+        assert_eq!(engine.list_sandbox_messages(None, None).unwrap().len(), 3);
+        assert_eq!(engine.list_sandbox_messages(Some("sms"), None).unwrap()[1].destination, "+15555550101");
+        assert_eq!(engine.list_sandbox_messages(None, Some(&endpoint.arn)).unwrap().len(), 1);
+        assert_eq!(engine.clear_sandbox_messages().unwrap(), 3);
+
+        engine.delete_platform_application(&app.arn).unwrap();
+        assert!(engine.get_platform_endpoint(&endpoint.arn).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, QueueMetadata, MessageMetadata};
+use super::engine::{StorageEngine, Namespace, QueueMetadata, MessageMetadata};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -6,7 +6,7 @@ impl StorageEngine {
     // ==================== SQS Operations ====================
 
     pub fn create_queue(&self, name: &str, account_id: &str, region: &str) -> Result<QueueMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sqs);
         let arn = format!("arn:aws:sqs:{}:{}:{}", region, account_id, name);
         let url = format!("http://localhost:4566/{}/{}", account_id, name);
         let now = chrono::Utc::now().to_rfc3339();
@@ -31,11 +31,38 @@ impl StorageEngine {
             message_retention_period: 345600,
             delay_seconds: 0,
             receive_message_wait_time_seconds: 0,
+            maximum_message_size: 1_048_576,
         })
     }
 
+    pub fn get_queue(&self, name: &str) -> Result<QueueMetadata> {
+        self.list_queues()?.into_iter().find(|q| q.name == name)
+            .ok_or_else(|| EmulatorError::NotFound("Queue".into(), name.into()))
+    }
+
+    /// Change the largest message, in bytes, the queue accepts
+    pub fn set_queue_maximum_message_size(&self, name: &str, bytes: i32) -> Result<()> {
+        let db = self.shard(Namespace::Sqs);
+        let rows = db.execute("UPDATE sqs_queues SET maximum_message_size = ?1 WHERE name = ?2", params![bytes, name])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Queue".into(), name.into()));
+        }
+        Ok(())
+    }
+
     pub fn send_message(&self, queue_name: &str, body: &str) -> Result<String> {
-        let db = self.db.lock();
+        self.send_message_with_attributes(queue_name, body, None, None)
+    }
+
+    /// Send a message carrying message attributes and system attributes (both JSON objects)
+    pub fn send_message_with_attributes(
+        &self,
+        queue_name: &str,
+        body: &str,
+        message_attributes: Option<&str>,
+        system_attributes: Option<&str>,
+    ) -> Result<String> {
+        let db = self.shard(Namespace::Sqs);
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
 
@@ -51,19 +78,20 @@ impl StorageEngine {
         }
 
         db.execute(
-            "INSERT INTO sqs_messages (id, queue_name, body, sent_at, visible_at) VALUES (?1, ?2, ?3, ?4, ?4)",
-            params![id, queue_name, body, now],
+            "INSERT INTO sqs_messages (id, queue_name, body, sent_at, visible_at, message_attributes, attributes)
+             VALUES (?1, ?2, ?3, ?4, ?4, ?5, ?6)",
+            params![id, queue_name, body, now, message_attributes, system_attributes],
         )?;
 
         Ok(id)
     }
 
     pub fn receive_message(&self, queue_name: &str, max_count: i32) -> Result<Vec<MessageMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sqs);
         let now = chrono::Utc::now().to_rfc3339();
 
         let mut stmt = db.prepare(
-            "SELECT id, body, md5_body, sent_at, visible_at, receive_count FROM sqs_messages 
+            "SELECT id, body, md5_body, sent_at, visible_at, receive_count, message_attributes, attributes FROM sqs_messages 
              WHERE queue_name = ?1 AND visible_at <= ?2 
              LIMIT ?3"
         )?;
@@ -78,6 +106,8 @@ impl StorageEngine {
                 visible_at: row.get(4)?,
                 receipt_handle: None,
                 receive_count: row.get(5)?,
+                message_attributes: row.get(6)?,
+                system_attributes: row.get(7)?,
             })
         })?.filter_map(|r| r.ok()).collect();
 
@@ -99,7 +129,7 @@ impl StorageEngine {
     }
 
     pub fn delete_message(&self, queue_name: &str, receipt_handle: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sqs);
         let rows = db.execute(
             "DELETE FROM sqs_messages WHERE queue_name = ?1 AND receipt_handle = ?2",
             params![queue_name, receipt_handle],
@@ -113,9 +143,9 @@ impl StorageEngine {
     }
 
     pub fn list_queues(&self) -> Result<Vec<QueueMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Sqs);
         let mut stmt = db.prepare(
-            "SELECT name, url, arn, created_at, visibility_timeout, message_retention_period, delay_seconds, receive_message_wait_time_seconds FROM sqs_queues ORDER BY name"
+            "SELECT name, url, arn, created_at, visibility_timeout, message_retention_period, delay_seconds, receive_message_wait_time_seconds, maximum_message_size FROM sqs_queues ORDER BY name"
         )?;
         let queues = stmt.query_map([], |row| {
             Ok(QueueMetadata {
@@ -127,6 +157,7 @@ impl StorageEngine {
                 message_retention_period: row.get(5)?,
                 delay_seconds: row.get(6)?,
                 receive_message_wait_time_seconds: row.get(7)?,
+                maximum_message_size: row.get(8)?,
             })
         })?.filter_map(|r| r.ok()).collect();
         Ok(queues)
@@ -163,5 +194,24 @@ mod tests {
         let messages_after = engine.receive_message("my-queue", 10).unwrap();
         assert_eq!(messages_after.len(), 0);
     }
+
+    #[test]
+    fn test_sqs_message_attributes_round_trip() {
+        let engine = StorageEngine::in_memory().unwrap();
+        engine.create_queue("attr-queue", "123", "us-east-1").unwrap();
+
+        let attributes = r#"{"tenant":{"DataType":"String","StringValue":"acme"}}"#;
+        let system = r#"{"AWSTraceHeader":"Root=1-5759e988-bd862e3fe1be46a994272793"}"#;
+        engine.send_message_with_attributes("attr-queue", "traced", Some(attributes), Some(system)).unwrap();
+        engine.send_message("attr-queue", "plain").unwrap();
+
+        let messages = engine.receive_message("attr-queue", 10).unwrap();
+        let traced = messages.iter().find(|m| m.body == "traced").unwrap();
+        assert_eq!(traced.message_attributes.as_deref(), Some(attributes));
+        assert_eq!(traced.system_attributes.as_deref(), Some(system));
+        let plain = messages.iter().find(|m| m.body == "plain").unwrap();
+        assert!(plain.message_attributes.is_none());
+        assert!(plain.system_attributes.is_none());
+    }
 }
 
@@ -1,53 +1,172 @@
-use super::engine::{StorageEngine, VpcMetadata, SubnetMetadata, SecurityGroupMetadata};
-use crate::error::Result;
-use rusqlite::params;
+use super::engine::{StorageEngine, Namespace, VpcMetadata, SubnetMetadata, SecurityGroupMetadata};
+use crate::error::{EmulatorError, Result};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Internet gateway, attached to at most one VPC
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InternetGatewayMetadata {
+    pub id: String,
+    pub vpc_id: Option<String>,
+    pub tags: Option<String>,
+}
+
+/// Route of a route table; `gateway_id` is `local` for the VPC's own range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteMetadata {
+    pub destination_cidr_block: String,
+    pub gateway_id: Option<String>,
+    pub nat_gateway_id: Option<String>,
+    pub origin: String, // CreateRouteTable | CreateRoute
+}
+
+/// Association of a route table with a subnet, or the implicit main association
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteTableAssociationMetadata {
+    pub id: String,
+    pub route_table_id: String,
+    pub subnet_id: Option<String>,
+    pub main: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteTableMetadata {
+    pub id: String,
+    pub vpc_id: String,
+    pub is_main: bool,
+    pub routes: Vec<RouteMetadata>,
+    pub associations: Vec<RouteTableAssociationMetadata>,
+    pub tags: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NatGatewayMetadata {
+    pub id: String,
+    pub vpc_id: String,
+    pub subnet_id: String,
+    pub state: String, // available | deleted
+    pub connectivity_type: String, // public | private
+    pub private_ip: String,
+    pub public_ip: Option<String>,
+    pub created_at: String,
+    pub tags: Option<String>,
+}
+
 impl StorageEngine {
     // ==================== VPC Operations ====================
 
     pub fn create_vpc(&self, cidr_block: &str) -> Result<VpcMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let id = format!("vpc-{}", &Uuid::new_v4().to_string()[..8]);
-        
+
         db.execute(
             "INSERT INTO vpc_vpcs (id, cidr_block) VALUES (?, ?)",
             params![id, cidr_block],
         )?;
-        
+
         Ok(VpcMetadata {
             id,
             cidr_block: cidr_block.to_string(),
             state: "available".into(),
             is_default: false,
+            enable_dns_support: true,
+            enable_dns_hostnames: false,
+            tags: None,
+        })
+    }
+
+    /// Create the account's default VPC, which has DNS hostnames enabled
+    pub fn create_default_vpc(&self, cidr_block: &str) -> Result<VpcMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        let id = format!("vpc-{}", &Uuid::new_v4().to_string()[..8]);
+
+        db.execute(
+            "INSERT INTO vpc_vpcs (id, cidr_block, is_default, enable_dns_hostnames) VALUES (?, ?, 1, 1)",
+            params![id, cidr_block],
+        )?;
+
+        Ok(VpcMetadata {
+            id,
+            cidr_block: cidr_block.to_string(),
+            state: "available".into(),
+            is_default: true,
+            enable_dns_support: true,
+            enable_dns_hostnames: true,
             tags: None,
         })
     }
 
     pub fn list_vpcs(&self) -> Result<Vec<VpcMetadata>> {
-        let db = self.db.lock();
-        let mut stmt = db.prepare("SELECT id, cidr_block, state, is_default, tags FROM vpc_vpcs")?;
-        let vpcs = stmt.query_map([], |row| {
-            Ok(VpcMetadata {
-                id: row.get(0)?,
-                cidr_block: row.get(1)?,
-                state: row.get(2)?,
-                is_default: row.get::<_, i32>(3)? != 0,
-                tags: row.get(4)?,
-            })
-        })?.filter_map(|r| r.ok()).collect();
+        let db = self.shard(Namespace::Ec2);
+        let mut stmt = db.prepare("SELECT id, cidr_block, state, is_default, enable_dns_support, enable_dns_hostnames, tags FROM vpc_vpcs")?;
+        let vpcs = stmt.query_map([], Self::vpc_from_row)?.filter_map(|r| r.ok()).collect();
         Ok(vpcs)
     }
 
+    pub fn get_vpc(&self, id: &str) -> Result<VpcMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        db.query_row(
+            "SELECT id, cidr_block, state, is_default, enable_dns_support, enable_dns_hostnames, tags FROM vpc_vpcs WHERE id = ?",
+            params![id],
+            Self::vpc_from_row,
+        ).optional()?.ok_or_else(|| EmulatorError::NotFound("Vpc".into(), id.into()))
+    }
+
+    fn vpc_from_row(row: &rusqlite::Row) -> rusqlite::Result<VpcMetadata> {
+        Ok(VpcMetadata {
+            id: row.get(0)?,
+            cidr_block: row.get(1)?,
+            state: row.get(2)?,
+            is_default: row.get::<_, i32>(3)? != 0,
+            enable_dns_support: row.get::<_, i32>(4)? != 0,
+            enable_dns_hostnames: row.get::<_, i32>(5)? != 0,
+            tags: row.get(6)?,
+        })
+    }
+
+    /// Set `enableDnsSupport` / `enableDnsHostnames`
+    pub fn set_vpc_dns_attributes(&self, id: &str, dns_support: Option<bool>, dns_hostnames: Option<bool>) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute(
+            "UPDATE vpc_vpcs SET enable_dns_support = COALESCE(?, enable_dns_support), enable_dns_hostnames = COALESCE(?, enable_dns_hostnames) WHERE id = ?",
+            params![dns_support, dns_hostnames, id],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Vpc".into(), id.into()));
+        }
+        Ok(())
+    }
+
+    /// Delete a VPC with its security groups and route tables
+    pub fn delete_vpc(&self, id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        db.execute(
+            "DELETE FROM vpc_routes WHERE route_table_id IN (SELECT id FROM vpc_route_tables WHERE vpc_id = ?)",
+            params![id],
+        )?;
+        db.execute(
+            "DELETE FROM vpc_route_table_associations WHERE route_table_id IN (SELECT id FROM vpc_route_tables WHERE vpc_id = ?)",
+            params![id],
+        )?;
+        db.execute("DELETE FROM vpc_route_tables WHERE vpc_id = ?", params![id])?;
+        db.execute("DELETE FROM vpc_security_groups WHERE vpc_id = ?", params![id])?;
+        let rows = db.execute("DELETE FROM vpc_vpcs WHERE id = ?", params![id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Vpc".into(), id.into()));
+        }
+        Ok(())
+    }
+
     pub fn create_subnet(&self, vpc_id: &str, cidr_block: &str, az: &str) -> Result<SubnetMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let id = format!("subnet-{}", &Uuid::new_v4().to_string()[..8]);
-        
+
         db.execute(
             "INSERT INTO vpc_subnets (id, vpc_id, cidr_block, availability_zone) VALUES (?, ?, ?, ?)",
             params![id, vpc_id, cidr_block, az],
         )?;
-        
+
         Ok(SubnetMetadata {
             id,
             vpc_id: vpc_id.to_string(),
@@ -60,31 +179,53 @@ impl StorageEngine {
     }
 
     pub fn list_subnets(&self) -> Result<Vec<SubnetMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let mut stmt = db.prepare("SELECT id, vpc_id, cidr_block, availability_zone, state, map_public_ip_on_launch, tags FROM vpc_subnets")?;
-        let subnets = stmt.query_map([], |row| {
-            Ok(SubnetMetadata {
-                id: row.get(0)?,
-                vpc_id: row.get(1)?,
-                cidr_block: row.get(2)?,
-                availability_zone: row.get(3)?,
-                state: row.get(4)?,
-                map_public_ip_on_launch: row.get::<_, i32>(5)? != 0,
-                tags: row.get(6)?,
-            })
-        })?.filter_map(|r| r.ok()).collect();
+        let subnets = stmt.query_map([], Self::subnet_from_row)?.filter_map(|r| r.ok()).collect();
         Ok(subnets)
     }
 
+    pub fn get_subnet(&self, id: &str) -> Result<SubnetMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        db.query_row(
+            "SELECT id, vpc_id, cidr_block, availability_zone, state, map_public_ip_on_launch, tags FROM vpc_subnets WHERE id = ?",
+            params![id],
+            Self::subnet_from_row,
+        ).optional()?.ok_or_else(|| EmulatorError::NotFound("Subnet".into(), id.into()))
+    }
+
+    fn subnet_from_row(row: &rusqlite::Row) -> rusqlite::Result<SubnetMetadata> {
+        Ok(SubnetMetadata {
+            id: row.get(0)?,
+            vpc_id: row.get(1)?,
+            cidr_block: row.get(2)?,
+            availability_zone: row.get(3)?,
+            state: row.get(4)?,
+            map_public_ip_on_launch: row.get::<_, i32>(5)? != 0,
+            tags: row.get(6)?,
+        })
+    }
+
+    /// Delete a subnet and its route table association
+    pub fn delete_subnet(&self, id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        db.execute("DELETE FROM vpc_route_table_associations WHERE subnet_id = ?", params![id])?;
+        let rows = db.execute("DELETE FROM vpc_subnets WHERE id = ?", params![id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Subnet".into(), id.into()));
+        }
+        Ok(())
+    }
+
     pub fn create_security_group(&self, vpc_id: &str, name: &str, desc: &str) -> Result<SecurityGroupMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let id = format!("sg-{}", &Uuid::new_v4().to_string()[..8]);
-        
+
         db.execute(
             "INSERT INTO vpc_security_groups (id, group_name, description, vpc_id) VALUES (?, ?, ?, ?)",
             params![id, name, desc, vpc_id],
         )?;
-        
+
         Ok(SecurityGroupMetadata {
             id,
             name: name.to_string(),
@@ -95,7 +236,7 @@ impl StorageEngine {
     }
 
     pub fn list_security_groups(&self) -> Result<Vec<SecurityGroupMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Ec2);
         let mut stmt = db.prepare("SELECT id, group_name, description, vpc_id, tags FROM vpc_security_groups")?;
         let sgs = stmt.query_map([], |row| {
             Ok(SecurityGroupMetadata {
@@ -108,4 +249,242 @@ impl StorageEngine {
         })?.filter_map(|r| r.ok()).collect();
         Ok(sgs)
     }
+
+    // ==================== Internet Gateway Operations ====================
+
+    pub fn create_internet_gateway(&self) -> Result<InternetGatewayMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        let id = format!("igw-{}", &Uuid::new_v4().to_string()[..8]);
+        db.execute("INSERT INTO vpc_internet_gateways (id) VALUES (?)", params![id])?;
+        Ok(InternetGatewayMetadata { id, vpc_id: None, tags: None })
+    }
+
+    pub fn list_internet_gateways(&self) -> Result<Vec<InternetGatewayMetadata>> {
+        let db = self.shard(Namespace::Ec2);
+        let mut stmt = db.prepare("SELECT id, vpc_id, tags FROM vpc_internet_gateways")?;
+        let igws = stmt.query_map([], |row| {
+            Ok(InternetGatewayMetadata { id: row.get(0)?, vpc_id: row.get(1)?, tags: row.get(2)? })
+        })?.filter_map(|r| r.ok()).collect();
+        Ok(igws)
+    }
+
+    pub fn get_internet_gateway(&self, id: &str) -> Result<InternetGatewayMetadata> {
+        self.list_internet_gateways()?
+            .into_iter()
+            .find(|igw| igw.id == id)
+            .ok_or_else(|| EmulatorError::NotFound("InternetGateway".into(), id.into()))
+    }
+
+    /// Attach the gateway to a VPC, or detach it with `None`
+    pub fn set_internet_gateway_attachment(&self, id: &str, vpc_id: Option<&str>) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute("UPDATE vpc_internet_gateways SET vpc_id = ? WHERE id = ?", params![vpc_id, id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("InternetGateway".into(), id.into()));
+        }
+        Ok(())
+    }
+
+    pub fn delete_internet_gateway(&self, id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute("DELETE FROM vpc_internet_gateways WHERE id = ?", params![id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("InternetGateway".into(), id.into()));
+        }
+        Ok(())
+    }
+
+    // ==================== Route Table Operations ====================
+
+    /// Create a route table with the VPC's `local` route
+    pub fn create_route_table(&self, vpc_id: &str, vpc_cidr: &str, is_main: bool) -> Result<RouteTableMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        let id = format!("rtb-{}", &Uuid::new_v4().to_string()[..8]);
+        db.execute(
+            "INSERT INTO vpc_route_tables (id, vpc_id, is_main) VALUES (?, ?, ?)",
+            params![id, vpc_id, is_main as i32],
+        )?;
+        db.execute(
+            "INSERT INTO vpc_routes (route_table_id, destination_cidr_block, gateway_id, origin) VALUES (?, ?, 'local', 'CreateRouteTable')",
+            params![id, vpc_cidr],
+        )?;
+        drop(db);
+        self.get_route_table(&id)
+    }
+
+    pub fn list_route_tables(&self) -> Result<Vec<RouteTableMetadata>> {
+        let ids: Vec<String> = {
+            let db = self.shard(Namespace::Ec2);
+            let mut stmt = db.prepare("SELECT id FROM vpc_route_tables")?;
+            let ids = stmt.query_map([], |row| row.get(0))?.filter_map(|r| r.ok()).collect();
+            ids
+        };
+        ids.iter().map(|id| self.get_route_table(id)).collect()
+    }
+
+    pub fn get_route_table(&self, id: &str) -> Result<RouteTableMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        let (vpc_id, is_main, tags): (String, bool, Option<String>) = db.query_row(
+            "SELECT vpc_id, is_main, tags FROM vpc_route_tables WHERE id = ?",
+            params![id],
+            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0, row.get(2)?)),
+        ).optional()?.ok_or_else(|| EmulatorError::NotFound("RouteTable".into(), id.into()))?;
+
+        let mut stmt = db.prepare(
+            "SELECT destination_cidr_block, gateway_id, nat_gateway_id, origin FROM vpc_routes WHERE route_table_id = ? ORDER BY origin DESC, destination_cidr_block"
+        )?;
+        let routes = stmt.query_map(params![id], |row| {
+            Ok(RouteMetadata {
+                destination_cidr_block: row.get(0)?,
+                gateway_id: row.get(1)?,
+                nat_gateway_id: row.get(2)?,
+                origin: row.get(3)?,
+            })
+        })?.filter_map(|r| r.ok()).collect();
+
+        let mut stmt = db.prepare("SELECT id, subnet_id FROM vpc_route_table_associations WHERE route_table_id = ?")?;
+        let mut associations: Vec<RouteTableAssociationMetadata> = stmt.query_map(params![id], |row| {
+            Ok(RouteTableAssociationMetadata {
+                id: row.get(0)?,
+                route_table_id: id.to_string(),
+                subnet_id: row.get(1)?,
+                main: false,
+            })
+        })?.filter_map(|r| r.ok()).collect();
+        if is_main {
+            associations.insert(0, RouteTableAssociationMetadata {
+                id: format!("rtbassoc-{}", &id[4..]),
+                route_table_id: id.to_string(),
+                subnet_id: None,
+                main: true,
+            });
+        }
+
+        Ok(RouteTableMetadata { id: id.to_string(), vpc_id, is_main, routes, associations, tags })
+    }
+
+    pub fn delete_route_table(&self, id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        db.execute("DELETE FROM vpc_routes WHERE route_table_id = ?", params![id])?;
+        let rows = db.execute("DELETE FROM vpc_route_tables WHERE id = ?", params![id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("RouteTable".into(), id.into()));
+        }
+        Ok(())
+    }
+
+    pub fn create_route(&self, route_table_id: &str, route: &RouteMetadata) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO vpc_routes (route_table_id, destination_cidr_block, gateway_id, nat_gateway_id, origin) VALUES (?, ?, ?, ?, ?)",
+            params![route_table_id, route.destination_cidr_block, route.gateway_id, route.nat_gateway_id, route.origin],
+        )?;
+        if inserted == 0 {
+            return Err(EmulatorError::AlreadyExists(format!(
+                "The route identified by {} already exists in {}", route.destination_cidr_block, route_table_id
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn delete_route(&self, route_table_id: &str, destination_cidr_block: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute(
+            "DELETE FROM vpc_routes WHERE route_table_id = ? AND destination_cidr_block = ?",
+            params![route_table_id, destination_cidr_block],
+        )?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("Route".into(), format!("{} in {}", destination_cidr_block, route_table_id)));
+        }
+        Ok(())
+    }
+
+    /// Associate a subnet with a route table; returns the association ID
+    pub fn associate_route_table(&self, route_table_id: &str, subnet_id: &str) -> Result<String> {
+        let db = self.shard(Namespace::Ec2);
+        let id = format!("rtbassoc-{}", &Uuid::new_v4().to_string()[..8]);
+        let inserted = db.execute(
+            "INSERT OR IGNORE INTO vpc_route_table_associations (id, route_table_id, subnet_id) VALUES (?, ?, ?)",
+            params![id, route_table_id, subnet_id],
+        )?;
+        if inserted == 0 {
+            return Err(EmulatorError::AlreadyExists(format!("The subnet {} is already associated with a route table", subnet_id)));
+        }
+        Ok(id)
+    }
+
+    pub fn disassociate_route_table(&self, association_id: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute("DELETE FROM vpc_route_table_associations WHERE id = ?", params![association_id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("RouteTableAssociation".into(), association_id.into()));
+        }
+        Ok(())
+    }
+
+    // ==================== NAT Gateway Operations ====================
+
+    pub fn create_nat_gateway(
+        &self,
+        vpc_id: &str,
+        subnet_id: &str,
+        connectivity_type: &str,
+        private_ip: &str,
+        public_ip: Option<&str>,
+    ) -> Result<NatGatewayMetadata> {
+        let db = self.shard(Namespace::Ec2);
+        let nat = NatGatewayMetadata {
+            id: format!("nat-{}", &Uuid::new_v4().simple().to_string()[..17]),
+            vpc_id: vpc_id.to_string(),
+            subnet_id: subnet_id.to_string(),
+            state: "available".into(),
+            connectivity_type: connectivity_type.to_string(),
+            private_ip: private_ip.to_string(),
+            public_ip: public_ip.map(|s| s.to_string()),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            tags: None,
+        };
+        db.execute(
+            "INSERT INTO vpc_nat_gateways (id, vpc_id, subnet_id, state, connectivity_type, private_ip, public_ip, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+            params![nat.id, nat.vpc_id, nat.subnet_id, nat.state, nat.connectivity_type, nat.private_ip, nat.public_ip, nat.created_at],
+        )?;
+        Ok(nat)
+    }
+
+    pub fn list_nat_gateways(&self) -> Result<Vec<NatGatewayMetadata>> {
+        let db = self.shard(Namespace::Ec2);
+        let mut stmt = db.prepare(
+            "SELECT id, vpc_id, subnet_id, state, connectivity_type, private_ip, public_ip, created_at, tags FROM vpc_nat_gateways ORDER BY created_at"
+        )?;
+        let nats = stmt.query_map([], |row| {
+            Ok(NatGatewayMetadata {
+                id: row.get(0)?,
+                vpc_id: row.get(1)?,
+                subnet_id: row.get(2)?,
+                state: row.get(3)?,
+                connectivity_type: row.get(4)?,
+                private_ip: row.get(5)?,
+                public_ip: row.get(6)?,
+                created_at: row.get(7)?,
+                tags: row.get(8)?,
+            })
+        })?.filter_map(|r| r.ok()).collect();
+        Ok(nats)
+    }
+
+    pub fn get_nat_gateway(&self, id: &str) -> Result<NatGatewayMetadata> {
+        self.list_nat_gateways()?
+            .into_iter()
+            .find(|nat| nat.id == id)
+            .ok_or_else(|| EmulatorError::NotFound("NatGateway".into(), id.into()))
+    }
+
+    pub fn set_nat_gateway_state(&self, id: &str, state: &str) -> Result<()> {
+        let db = self.shard(Namespace::Ec2);
+        let rows = db.execute("UPDATE vpc_nat_gateways SET state = ? WHERE id = ?", params![state, id])?;
+        if rows == 0 {
+            return Err(EmulatorError::NotFound("NatGateway".into(), id.into()));
+        }
+        Ok(())
+    }
 }
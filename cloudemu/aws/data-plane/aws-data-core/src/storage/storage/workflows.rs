@@ -1,4 +1,4 @@
-use super::engine::{StorageEngine, StateMachineMetadata, ExecutionMetadata};
+use super::engine::{StorageEngine, Namespace, StateMachineMetadata, ExecutionMetadata};
 use crate::error::{EmulatorError, Result};
 use rusqlite::params;
 
@@ -6,7 +6,7 @@ impl StorageEngine {
     // ==================== Step Functions Operations ====================
     
     pub fn create_state_machine(&self, name: &str, definition: &str, role_arn: &str, machine_type: &str, account_id: &str, region: &str) -> Result<StateMachineMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         let arn = format!("arn:aws:states:{}:{}:stateMachine:{}", region, account_id, name);
         let now = chrono::Utc::now().to_rfc3339();
         
@@ -32,7 +32,7 @@ impl StorageEngine {
     }
 
     pub fn list_state_machines(&self) -> Result<Vec<StateMachineMetadata>> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         let mut stmt = db.prepare("SELECT arn, name, definition, role_arn, type, created_at FROM sf_state_machines")?;
         let machines = stmt.query_map([], |row| Ok(StateMachineMetadata {
             arn: row.get(0)?,
@@ -48,7 +48,7 @@ impl StorageEngine {
     }
 
     pub fn delete_state_machine(&self, arn: &str) -> Result<()> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         let rows = db.execute("DELETE FROM sf_state_machines WHERE arn = ?1", params![arn])?;
         if rows == 0 {
             return Err(EmulatorError::NotFound("StateMachine".into(), arn.into()));
@@ -57,7 +57,7 @@ impl StorageEngine {
     }
 
     pub fn start_execution(&self, state_machine_arn: &str, name: Option<&str>, input: Option<&str>, account_id: &str, region: &str) -> Result<ExecutionMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         let exec_name = name.map(|s| s.to_string()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
         let arn = format!("arn:aws:states:{}:{}:execution:{}:{}", region, account_id, state_machine_arn.split(':').next_back().unwrap_or("unknown"), exec_name);
         let now = chrono::Utc::now().to_rfc3339();
@@ -80,7 +80,7 @@ impl StorageEngine {
     }
 
     pub fn describe_execution(&self, arn: &str) -> Result<ExecutionMetadata> {
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         db.query_row(
             "SELECT arn, state_machine_arn, name, status, input, output, start_date, stop_date FROM sf_executions WHERE arn = ?1",
             params![arn],
@@ -97,6 +97,27 @@ impl StorageEngine {
         ).map_err(|_| EmulatorError::NotFound("Execution".into(), arn.into()))
     }
 
+    pub fn list_executions(&self, state_machine_arn: &str) -> Result<Vec<ExecutionMetadata>> {
+        let db = self.shard(Namespace::Workflows);
+        let mut stmt = db.prepare(
+            "SELECT arn, state_machine_arn, name, status, input, output, start_date, stop_date FROM sf_executions
+             WHERE state_machine_arn = ?1 ORDER BY start_date"
+        )?;
+        let executions = stmt.query_map(params![state_machine_arn], |row| Ok(ExecutionMetadata {
+            arn: row.get(0)?,
+            state_machine_arn: row.get(1)?,
+            name: row.get(2)?,
+            status: row.get(3)?,
+            input: row.get(4)?,
+            output: row.get(5)?,
+            start_date: row.get(6)?,
+            stop_date: row.get(7)?,
+        }))?
+        .filter_map(|r| r.ok())
+        .collect();
+        Ok(executions)
+    }
+
     pub fn update_execution_status(&self, arn: &str, status: &str, output: Option<&str>) -> Result<()> {
         let stop_date = if status == "SUCCEEDED" || status == "FAILED" || status == "ABORTED" {
             Some(chrono::Utc::now().to_rfc3339())
@@ -104,7 +125,7 @@ impl StorageEngine {
             None
         };
 
-        let db = self.db.lock();
+        let db = self.shard(Namespace::Workflows);
         db.execute(
             "UPDATE sf_executions SET status = ?1, output = ?2, stop_date = ?3 WHERE arn = ?4",
             params![status, output, stop_date, arn],
@@ -143,6 +164,7 @@ mod tests {
         let fetched_exec = engine.describe_execution(&exec.arn).unwrap();
         assert_eq!(fetched_exec.status, "SUCCEEDED");
         assert_eq!(fetched_exec.output, Some("{\"done\": true}".to_string()));
+        assert_eq!(engine.list_executions(&machine.arn).unwrap().len(), 1);
     }
 }
 
@@ -0,0 +1,132 @@
+//! Concurrency tests for the sharded StorageEngine
+//!
+//! Whether a shard is held is checked with `try_get_connection`, so no test depends on how
+//! long a thread takes.
+
+use aws_data_core::{Config, Namespace, StorageEngine};
+use std::thread;
+
+const ACCOUNT: &str = "000000000000";
+const REGION: &str = "us-east-1";
+/// Writes per thread in `write_across_namespaces`
+const OPS: usize = 50;
+
+/// One writer thread's operation, called with the operation index
+type Writer = Box<dyn Fn(&StorageEngine, usize) + Send>;
+
+fn on_disk_engine() -> (StorageEngine, std::path::PathBuf) {
+    let data_dir = std::env::temp_dir().join(format!("cloudemu-concurrency-{}", uuid::Uuid::new_v4()));
+    let config = Config { data_dir: data_dir.clone(), ..Default::default() };
+    (StorageEngine::new(&config).unwrap(), data_dir)
+}
+
+#[test]
+fn test_held_shard_does_not_block_other_namespaces() {
+    let (on_disk, data_dir) = on_disk_engine();
+    for engine in [on_disk, StorageEngine::in_memory().unwrap()] {
+        let _s3 = engine.get_connection(Namespace::S3).unwrap();
+        assert!(engine.try_get_connection(Namespace::Sqs).is_some());
+
+        let worker = engine.clone();
+        thread::spawn(move || {
+            worker.create_queue("orders", ACCOUNT, REGION).unwrap();
+            worker.send_message("orders", "hello").unwrap();
+        }).join().unwrap();
+    }
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_held_shard_blocks_its_own_namespace() {
+    let (on_disk, data_dir) = on_disk_engine();
+    for engine in [on_disk, StorageEngine::in_memory().unwrap()] {
+        let s3 = engine.get_connection(Namespace::S3).unwrap();
+        assert!(engine.try_get_connection(Namespace::S3).is_none());
+
+        let worker = engine.clone();
+        let handle = thread::spawn(move || worker.create_bucket("photos", REGION).unwrap());
+        drop(s3);
+        handle.join().unwrap();
+        assert!(engine.bucket_exists("photos").unwrap());
+    }
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_parallel_writers_across_namespaces() {
+    let (engine, data_dir) = on_disk_engine();
+    write_across_namespaces(&engine);
+
+    // Every shard sees data written through the others
+    let reopened = StorageEngine::new(&Config { data_dir: data_dir.clone(), ..Default::default() }).unwrap();
+    assert_eq!(reopened.list_topics().unwrap().len(), OPS);
+    let _ = std::fs::remove_dir_all(data_dir);
+
+    write_across_namespaces(&StorageEngine::in_memory().unwrap());
+}
+
+/// Write to four namespaces from a thread each, then check every write landed
+fn write_across_namespaces(engine: &StorageEngine) {
+    engine.create_bucket("parallel", REGION).unwrap();
+    engine.create_queue("parallel", ACCOUNT, REGION).unwrap();
+
+    let writers: Vec<Writer> = vec![
+        Box::new(|e, i| { e.put_object("parallel", &format!("key-{}", i), b"data", None, None).unwrap(); }),
+        Box::new(|e, i| { e.send_message("parallel", &format!("message-{}", i)).unwrap(); }),
+        Box::new(|e, i| { e.create_topic(&format!("topic-{}", i), ACCOUNT, REGION).unwrap(); }),
+        Box::new(|e, i| { e.create_secret(&format!("secret-{}", i), None, None, ACCOUNT, REGION).unwrap(); }),
+    ];
+
+    let handles: Vec<_> = writers.into_iter().map(|write| {
+        let engine = engine.clone();
+        thread::spawn(move || (0..OPS).for_each(|i| write(&engine, i)))
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(engine.list_objects("parallel", None, None, 1000, None).unwrap().contents.len(), OPS);
+    assert_eq!(engine.receive_message("parallel", 10).unwrap().len(), 10);
+    assert_eq!(engine.list_topics().unwrap().len(), OPS);
+}
+
+#[test]
+fn test_parallel_writers_in_one_namespace() {
+    const THREADS: usize = 8;
+    const OPS: usize = 25;
+    let (engine, data_dir) = on_disk_engine();
+    engine.create_bucket("shared", REGION).unwrap();
+
+    let handles: Vec<_> = (0..THREADS).map(|t| {
+        let engine = engine.clone();
+        thread::spawn(move || {
+            for i in 0..OPS {
+                engine.put_object("shared", &format!("{}/{}", t, i), format!("{}-{}", t, i).as_bytes(), None, None).unwrap();
+            }
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let listed = engine.list_objects("shared", None, None, 1000, None).unwrap();
+    assert_eq!(listed.contents.len(), THREADS * OPS);
+    let (_, data) = engine.get_object("shared", "3/7", None).unwrap();
+    assert_eq!(data, b"3-7");
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+#[test]
+fn test_in_memory_shards_share_one_database() {
+    let engine = StorageEngine::in_memory().unwrap();
+    engine.create_queue("orders", ACCOUNT, REGION).unwrap();
+
+    // Each namespace has its own lock, so holding one never blocks taking another
+    let _sqs = engine.get_connection(Namespace::Sqs).unwrap();
+    let s3 = engine.try_get_connection(Namespace::S3).expect("in-memory shards share a lock");
+    let count: i64 = s3.query_row("SELECT COUNT(*) FROM sqs_queues", [], |row| row.get(0)).unwrap();
+    assert_eq!(count, 1);
+
+    // Engines do not see each other's data
+    assert!(StorageEngine::in_memory().unwrap().list_topics().unwrap().is_empty());
+}
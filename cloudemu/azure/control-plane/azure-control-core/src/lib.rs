@@ -18,3 +18,4 @@ pub mod services;
 
 pub use provider::AzureProvider;
 pub use azure_data_core::storage::StorageEngine as AzureStorageEngine;
+pub use azure_data_core::storage::Config as AzureConfig;
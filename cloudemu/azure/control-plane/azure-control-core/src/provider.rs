@@ -280,4 +280,28 @@ mod tests {
         let body = String::from_utf8(response.body).unwrap();
         assert!(body.contains("_rid"));
     }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let (provider, temp_dir) = create_test_provider();
+        let req = Request {
+            method: "PUT".to_string(),
+            path: "/secrets/db-password".to_string(),
+            headers: std::collections::HashMap::new(),
+            body: b"hunter2".to_vec(),
+        };
+        provider.handle_request(req).await.unwrap();
+        drop(provider);
+
+        let provider = AzureProvider::with_config(Config::default().data_dir(temp_dir.path()));
+        let req = Request {
+            method: "GET".to_string(),
+            path: "/secrets/db-password".to_string(),
+            headers: std::collections::HashMap::new(),
+            body: vec![],
+        };
+        let response = provider.handle_request(req).await.unwrap();
+        let body = String::from_utf8(response.body).unwrap();
+        assert!(body.contains("hunter2"));
+    }
 }
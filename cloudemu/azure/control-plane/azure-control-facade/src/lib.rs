@@ -16,7 +16,7 @@ use azure_control_spi::{CloudProviderTrait, Request as AzureRequest};
 pub use azure_control_core;
 pub use azure_control_spi;
 pub use azure_control_api;
-pub use azure_control_core::AzureProvider;
+pub use azure_control_core::{AzureConfig, AzureProvider};
 
 /// Create an Axum router for the Azure provider.
 pub fn router(provider: Arc<AzureProvider>) -> Router {
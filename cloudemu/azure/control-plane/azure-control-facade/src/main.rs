@@ -1,9 +1,12 @@
 use clap::Parser;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use std::net::SocketAddr;
 use tracing::info;
-use azure_control_facade::{router, AzureProvider};
+use azure_control_facade::{router, AzureConfig, AzureProvider};
+
+const DEFAULT_DATA_DIR: &str = ".cloudemu/azure";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -12,6 +15,10 @@ struct Config {
     #[arg(long, default_value_t = 4567, env = "CLOUDEMU_AZURE_PORT")]
     port: u16,
 
+    /// Data directory [default: .cloudemu/azure, or .cloudemu if only that holds data]
+    #[arg(long, env = "CLOUDEMU_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
     /// Host
     #[arg(long, default_value = "0.0.0.0", env = "CLOUDEMU_HOST")]
     host: String,
@@ -21,10 +28,15 @@ struct Config {
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     let config = Config::parse();
+    // Releases before per-provider directories kept Azure data in `.cloudemu` itself
+    let data_dir = config.data_dir.unwrap_or_else(|| {
+        emu_storage::data_dir_or_legacy(Path::new(DEFAULT_DATA_DIR), Path::new(emu_storage::LEGACY_DATA_DIR))
+    });
 
     info!("Starting CloudEmu Azure Server on {}:{}", config.host, config.port);
+    info!("Data Directory: {:?}", data_dir);
 
-    let provider = Arc::new(AzureProvider::new());
+    let provider = Arc::new(AzureProvider::with_config(AzureConfig::from_env().data_dir(data_dir)));
     let app = router(provider);
 
     let host_ip: std::net::IpAddr = config.host.parse()?;
@@ -0,0 +1,215 @@
+//! Content-addressed blob storage
+//!
+//! A blob is stored once under the SHA-256 of its data, so writing the same data twice
+//! costs nothing and a blob never changes once written. Metadata tables refer to blobs by
+//! that hash.
+
+use crate::error::{Result, StorageError};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Blobs addressed by the hex SHA-256 of their data
+pub trait BlobStore: Send + Sync {
+    /// Store `data`, returning its content hash
+    fn put(&self, data: &[u8]) -> Result<String>;
+
+    /// Data of the blob `content_hash`; the empty hash reads as an empty blob
+    fn get(&self, content_hash: &str) -> Result<Vec<u8>>;
+
+    /// Whether the blob `content_hash` is stored
+    fn contains(&self, content_hash: &str) -> bool;
+}
+
+/// Blob store on the filesystem, sharded into directories by the first two characters of
+/// each hash
+#[derive(Debug, Clone)]
+pub struct FsBlobStore {
+    dir: PathBuf,
+}
+
+impl FsBlobStore {
+    /// Store blobs under `dir`, creating it if needed
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Store blobs in a fresh temporary directory (for testing)
+    pub fn temp() -> Result<Self> {
+        Self::open(std::env::temp_dir().join(format!("cloudemu-{}", uuid::Uuid::new_v4())))
+    }
+
+    /// Directory holding the blobs
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Start writing a blob whose hash is computed as it is written
+    pub fn writer(&self) -> Result<BlobWriter> {
+        let temp_dir = self.dir.join("tmp");
+        fs::create_dir_all(&temp_dir)?;
+
+        let temp_path = temp_dir.join(uuid::Uuid::new_v4().to_string());
+        let file = fs::File::create(&temp_path)?;
+
+        Ok(BlobWriter {
+            file: Some(file),
+            temp_path,
+            dir: self.dir.clone(),
+            hasher: Sha256::new(),
+            size: 0,
+        })
+    }
+
+    /// Open a blob for streaming reads
+    pub fn open_blob(&self, content_hash: &str) -> Result<fs::File> {
+        Ok(fs::File::open(self.path(content_hash)?)?)
+    }
+
+    /// Copy every blob into the store at `dest`. Blobs never change, so ones already
+    /// there are left as they are.
+    pub fn copy_to(&self, dest: &Path) -> Result<()> {
+        fs::create_dir_all(dest)?;
+        for prefix in fs::read_dir(&self.dir)? {
+            let prefix = prefix?;
+            // Unfinished writes live in `tmp`, which is not a shard
+            if !prefix.file_type()?.is_dir() || prefix.file_name() == "tmp" {
+                continue;
+            }
+            let target_dir = dest.join(prefix.file_name());
+            fs::create_dir_all(&target_dir)?;
+            for blob in fs::read_dir(prefix.path())? {
+                let blob = blob?;
+                let target = target_dir.join(blob.file_name());
+                if !target.exists() {
+                    fs::copy(blob.path(), target)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Path of a blob, refusing keys that are not hex hashes so none escapes the store
+    fn path(&self, content_hash: &str) -> Result<PathBuf> {
+        if content_hash.len() < 2 || !content_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(StorageError::InvalidKey(content_hash.to_string()));
+        }
+        Ok(self.dir.join(&content_hash[..2]).join(content_hash))
+    }
+}
+
+impl BlobStore for FsBlobStore {
+    fn put(&self, data: &[u8]) -> Result<String> {
+        let mut writer = self.writer()?;
+        writer.write_all(data)?;
+        Ok(writer.finish()?.content_hash)
+    }
+
+    fn get(&self, content_hash: &str) -> Result<Vec<u8>> {
+        if content_hash.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read(self.path(content_hash)?)?)
+    }
+
+    fn contains(&self, content_hash: &str) -> bool {
+        self.path(content_hash).is_ok_and(|path| path.exists())
+    }
+}
+
+/// A blob written to the store
+#[derive(Debug, Clone)]
+pub struct StoredBlob {
+    /// Hex SHA-256 of the data
+    pub content_hash: String,
+    /// Size of the data in bytes
+    pub size: u64,
+}
+
+/// Incremental writer into a [`FsBlobStore`].
+/// Data goes to a temporary file while it is hashed and is moved to its content
+/// address by [`BlobWriter::finish`]; an unfinished writer removes its file on drop.
+pub struct BlobWriter {
+    file: Option<fs::File>,
+    temp_path: PathBuf,
+    dir: PathBuf,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl BlobWriter {
+    /// Flush the data and move it to its content address
+    pub fn finish(mut self) -> Result<StoredBlob> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        let hash = hex::encode(std::mem::take(&mut self.hasher).finalize());
+
+        let dir = self.dir.join(&hash[..2]);
+        fs::create_dir_all(&dir)?;
+
+        let file_path = dir.join(&hash);
+        if file_path.exists() {
+            fs::remove_file(&self.temp_path)?;
+        } else {
+            fs::rename(&self.temp_path, &file_path)?;
+        }
+
+        Ok(StoredBlob { content_hash: hash, size: self.size })
+    }
+}
+
+impl Write for BlobWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let file = self.file.as_mut().ok_or_else(|| io::Error::other("blob writer is finished"))?;
+        let written = file.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for BlobWriter {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blobs_are_content_addressed() {
+        let store = FsBlobStore::temp().unwrap();
+        let hash = store.put(b"hello world").unwrap();
+        assert_eq!(hash, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        assert_eq!(store.put(b"hello world").unwrap(), hash);
+        assert_eq!(store.get(&hash).unwrap(), b"hello world");
+        assert!(store.contains(&hash));
+        assert!(store.get("").unwrap().is_empty());
+
+        // Keys are hashes, never paths
+        assert!(matches!(store.get("../../etc/passwd"), Err(StorageError::InvalidKey(_))));
+        assert!(!store.contains("../x"));
+
+        // An abandoned writer leaves nothing behind
+        let mut writer = store.writer().unwrap();
+        writer.write_all(b"partial").unwrap();
+        drop(writer);
+        assert_eq!(fs::read_dir(store.dir().join("tmp")).unwrap().count(), 0);
+        fs::remove_dir_all(store.dir()).unwrap();
+    }
+}
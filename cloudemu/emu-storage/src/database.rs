@@ -0,0 +1,60 @@
+//! SQLite metadata database
+
+use crate::error::Result;
+use rusqlite::Connection;
+use std::path::Path;
+use std::time::Duration;
+
+/// How long a connection waits for another connection's SQLite write lock
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Open the database at `path` and create `schema` in it.
+///
+/// The journal is switched to WAL (persistent) so readers never wait on a writer, and the
+/// schema is created in one transaction rather than one commit per table.
+pub fn open(path: &Path, schema: &str) -> Result<Connection> {
+    let conn = connect(path)?;
+    conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+    conn.execute_batch(&format!("BEGIN; {} COMMIT;", schema))?;
+    Ok(conn)
+}
+
+/// Open another connection to a database already set up by [`open`]
+pub fn connect(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch("PRAGMA synchronous=NORMAL;")?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Open a private in-memory database holding `schema` (for testing)
+pub fn open_in_memory(schema: &str) -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    conn.execute_batch(&format!("BEGIN; {} COMMIT;", schema))?;
+    Ok(conn)
+}
+
+/// Open the in-memory database `name` and create `schema` in it. Unlike [`open_in_memory`],
+/// further connections reach the same database through [`connect_in_memory`]; it lives until
+/// the last of them closes (for testing)
+pub fn open_shared_in_memory(name: &str, schema: &str) -> Result<Connection> {
+    let conn = connect_in_memory(name)?;
+    conn.execute_batch(&format!("BEGIN; {} COMMIT;", schema))?;
+    Ok(conn)
+}
+
+/// Open another connection to an in-memory database set up by [`open_shared_in_memory`]
+pub fn connect_in_memory(name: &str) -> Result<Connection> {
+    let conn = Connection::open(format!("file:/{}?vfs=memdb", name))?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+/// Write a consistent copy of the database to `dest`, replacing any file there
+pub fn snapshot(conn: &Connection, dest: &Path) -> Result<()> {
+    if dest.exists() {
+        std::fs::remove_file(dest)?;
+    }
+    conn.execute("VACUUM INTO ?1", [dest.to_string_lossy()])?;
+    Ok(())
+}
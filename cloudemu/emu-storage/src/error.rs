@@ -0,0 +1,37 @@
+//! Error types for the shared storage
+
+/// Result type alias
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// Storage error type
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    /// The metadata database failed
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// A blob or the data directory could not be read or written
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A blob key is not a content hash
+    #[error("Invalid content hash: {0}")]
+    InvalidKey(String),
+}
+
+/// Implement `From<StorageError>` for a provider's error type. The type must convert from
+/// `rusqlite::Error` and `std::io::Error` and have an `Internal(String)` variant.
+#[macro_export]
+macro_rules! impl_from_storage_error {
+    ($error:ty) => {
+        impl From<$crate::StorageError> for $error {
+            fn from(e: $crate::StorageError) -> Self {
+                match e {
+                    $crate::StorageError::Database(e) => e.into(),
+                    $crate::StorageError::Io(e) => e.into(),
+                    e @ $crate::StorageError::InvalidKey(_) => Self::Internal(e.to_string()),
+                }
+            }
+        }
+    };
+}
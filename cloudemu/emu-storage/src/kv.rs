@@ -0,0 +1,108 @@
+//! Key-value storage
+//!
+//! Keys live in namespaces, typically one per service, and are kept sorted so a namespace can
+//! be scanned by prefix.
+
+use crate::error::Result;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::Arc;
+
+/// Values addressed by namespace and key
+pub trait KvStore: Send + Sync {
+    /// Value of `key`, if set
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Set `key` to `value`, replacing any previous value
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()>;
+
+    /// Remove `key`, returning whether it was set
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool>;
+
+    /// Entries whose key starts with `prefix`, sorted by key
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>>;
+}
+
+/// Key-value store in a table of the metadata database
+#[derive(Clone)]
+pub struct SqliteKv {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteKv {
+    /// Keep entries in `conn`'s database, creating their table if needed
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Result<Self> {
+        conn.lock().execute_batch(
+            "CREATE TABLE IF NOT EXISTS kv_entries (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                value BLOB NOT NULL,
+                PRIMARY KEY (namespace, key)
+            );"
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl KvStore for SqliteKv {
+    fn get(&self, namespace: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let value = self.conn.lock().query_row(
+            "SELECT value FROM kv_entries WHERE namespace = ?1 AND key = ?2",
+            params![namespace, key],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(value)
+    }
+
+    fn put(&self, namespace: &str, key: &str, value: &[u8]) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT OR REPLACE INTO kv_entries (namespace, key, value) VALUES (?1, ?2, ?3)",
+            params![namespace, key, value],
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, namespace: &str, key: &str) -> Result<bool> {
+        let deleted = self.conn.lock().execute(
+            "DELETE FROM kv_entries WHERE namespace = ?1 AND key = ?2",
+            params![namespace, key],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    fn scan(&self, namespace: &str, prefix: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT key, value FROM kv_entries
+             WHERE namespace = ?1 AND substr(key, 1, length(?2)) = ?2
+             ORDER BY key"
+        )?;
+        let entries = stmt.query_map(params![namespace, prefix], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaces_and_prefix_scans() {
+        let kv = SqliteKv::new(Arc::new(Mutex::new(Connection::open_in_memory().unwrap()))).unwrap();
+        kv.put("tags", "bucket/logs/env", b"prod").unwrap();
+        kv.put("tags", "bucket/logs/team", b"ops").unwrap();
+        kv.put("tags", "bucket/assets/env", b"dev").unwrap();
+        kv.put("other", "bucket/logs/env", b"test").unwrap();
+        kv.put("tags", "bucket/logs/env", b"staging").unwrap();
+
+        assert_eq!(kv.get("tags", "bucket/logs/env").unwrap().unwrap(), b"staging");
+        let keys: Vec<_> = kv.scan("tags", "bucket/logs/").unwrap().into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["bucket/logs/env", "bucket/logs/team"]);
+
+        assert!(kv.delete("tags", "bucket/logs/env").unwrap());
+        assert!(!kv.delete("tags", "bucket/logs/env").unwrap());
+        assert!(kv.get("tags", "bucket/logs/env").unwrap().is_none());
+        assert!(kv.get("other", "bucket/logs/env").unwrap().is_some());
+    }
+}
@@ -0,0 +1,151 @@
+//! CloudEmu Shared Storage
+//!
+//! Storage primitives every provider's data plane is built on:
+//!
+//! - [`database`]: the SQLite metadata database, opened with the same journal, locking and
+//!   schema-creation rules for every provider. Each provider brings its own schema.
+//! - [`blob`]: content-addressed blob storage for object data and function code.
+//! - [`kv`] and [`stream`]: key-value and append-only stream stores for services that do not
+//!   need tables of their own.
+//!
+//! Blobs, key-value entries and streams are reached through the [`BlobStore`], [`KvStore`]
+//! and [`StreamStore`] traits, so a new backend implements them once and is available to
+//! every provider. Each provider's storage engine implements [`StorageBackend`], which gives
+//! it snapshots.
+
+#![warn(missing_docs)]
+
+pub mod blob;
+pub mod database;
+pub mod error;
+pub mod kv;
+pub mod stream;
+
+pub use blob::{BlobStore, BlobWriter, FsBlobStore, StoredBlob};
+pub use error::{Result, StorageError};
+pub use kv::{KvStore, SqliteKv};
+pub use stream::{Record, SqliteStreams, StreamStore};
+
+use parking_lot::Mutex;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Name of the metadata database inside a data directory
+pub const DATABASE_FILE: &str = "metadata.db";
+
+/// Name of the blob directory inside a data directory
+pub const OBJECTS_DIR: &str = "objects";
+
+/// Data directory the Azure and GCP emulators shared before each got one of its own
+pub const LEGACY_DATA_DIR: &str = ".cloudemu";
+
+/// `data_dir`, unless it holds no database yet and `legacy` does. An emulator upgraded from a
+/// release that kept its data in `legacy` goes on using that data instead of starting empty.
+pub fn data_dir_or_legacy(data_dir: &Path, legacy: &Path) -> PathBuf {
+    if !data_dir.join(DATABASE_FILE).exists() && legacy.join(DATABASE_FILE).exists() {
+        legacy.to_path_buf()
+    } else {
+        data_dir.to_path_buf()
+    }
+}
+
+/// The metadata database and blobs behind a provider's storage engine
+pub trait StorageBackend {
+    /// Connection to the metadata database
+    fn database(&self) -> &Arc<Mutex<Connection>>;
+
+    /// Object data referenced from the database by content hash
+    fn blobs(&self) -> &FsBlobStore;
+
+    /// Write a consistent copy of the metadata and blobs to `dest`, which can then be
+    /// opened as a data directory of its own
+    fn snapshot(&self, dest: &Path) -> Result<()> {
+        std::fs::create_dir_all(dest)?;
+        database::snapshot(&self.database().lock(), &dest.join(DATABASE_FILE))?;
+        self.blobs().copy_to(&dest.join(OBJECTS_DIR))
+    }
+}
+
+/// A data directory: one metadata database and the blobs it refers to
+#[derive(Clone)]
+pub struct Storage {
+    /// Connection to the metadata database
+    pub db: Arc<Mutex<Connection>>,
+    /// Object data referenced from the database by content hash
+    pub blobs: FsBlobStore,
+}
+
+impl Storage {
+    /// Open the data directory `data_dir`, creating it and `schema` if needed
+    pub fn open(data_dir: &Path, schema: &str) -> Result<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        let blobs = FsBlobStore::open(data_dir.join(OBJECTS_DIR))?;
+        let conn = database::open(&data_dir.join(DATABASE_FILE), schema)?;
+        Ok(Self { db: Arc::new(Mutex::new(conn)), blobs })
+    }
+
+    /// Keep the metadata in memory and the blobs in a fresh temporary directory (for testing)
+    pub fn in_memory(schema: &str) -> Result<Self> {
+        let conn = database::open_in_memory(schema)?;
+        Ok(Self { db: Arc::new(Mutex::new(conn)), blobs: FsBlobStore::temp()? })
+    }
+
+    /// Key-value store in the metadata database
+    pub fn kv(&self) -> Result<SqliteKv> {
+        SqliteKv::new(self.db.clone())
+    }
+
+    /// Stream store in the metadata database
+    pub fn streams(&self) -> Result<SqliteStreams> {
+        SqliteStreams::new(self.db.clone())
+    }
+}
+
+impl StorageBackend for Storage {
+    fn database(&self) -> &Arc<Mutex<Connection>> {
+        &self.db
+    }
+
+    fn blobs(&self) -> &FsBlobStore {
+        &self.blobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS buckets (name TEXT PRIMARY KEY, content_hash TEXT);";
+
+    #[test]
+    fn test_snapshot_opens_as_data_directory() {
+        let dir = std::env::temp_dir().join(format!("emu-storage-{}", uuid::Uuid::new_v4()));
+        let storage = Storage::open(&dir.join("live"), SCHEMA).unwrap();
+        let hash = storage.blobs.put(b"hello").unwrap();
+        storage.db.lock().execute("INSERT INTO buckets VALUES ('assets', ?1)", [&hash]).unwrap();
+        storage.kv().unwrap().put("config", "region", b"eu-west-1").unwrap();
+
+        storage.snapshot(&dir.join("copy")).unwrap();
+        let copy = Storage::open(&dir.join("copy"), SCHEMA).unwrap();
+        let copied: String = copy.db.lock()
+            .query_row("SELECT content_hash FROM buckets WHERE name = 'assets'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(copy.blobs.get(&copied).unwrap(), b"hello");
+        assert_eq!(copy.kv().unwrap().get("config", "region").unwrap().unwrap(), b"eu-west-1");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_legacy_data_dir_is_used_until_replaced() {
+        let dir = std::env::temp_dir().join(format!("emu-storage-{}", uuid::Uuid::new_v4()));
+        let (legacy, current) = (dir.clone(), dir.join("gcp"));
+        assert_eq!(data_dir_or_legacy(&current, &legacy), current);
+
+        Storage::open(&legacy, SCHEMA).unwrap();
+        assert_eq!(data_dir_or_legacy(&current, &legacy), legacy);
+        Storage::open(&current, SCHEMA).unwrap();
+        assert_eq!(data_dir_or_legacy(&current, &legacy), current);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
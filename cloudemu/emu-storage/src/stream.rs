@@ -0,0 +1,134 @@
+//! Append-only record streams
+//!
+//! Each stream numbers its records from 1 in the order they were appended. Readers keep
+//! the last sequence number they saw and ask for what follows, and old records are trimmed
+//! from the front. Numbers are never reused, even once a stream has been trimmed empty.
+
+use crate::error::Result;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::Arc;
+
+/// A record read from a stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// Position in the stream, starting at 1
+    pub seq: u64,
+    /// Data appended
+    pub data: Vec<u8>,
+}
+
+/// Named streams of records
+pub trait StreamStore: Send + Sync {
+    /// Append `data` to `stream`, returning its sequence number
+    fn append(&self, stream: &str, data: &[u8]) -> Result<u64>;
+
+    /// Up to `limit` records of `stream` after the sequence number `after`, oldest first
+    fn read(&self, stream: &str, after: u64, limit: usize) -> Result<Vec<Record>>;
+
+    /// Remove the records of `stream` up to and including `through`, returning how many
+    fn trim(&self, stream: &str, through: u64) -> Result<usize>;
+
+    /// Sequence number of the last record appended to `stream`, 0 if there is none
+    fn last_seq(&self, stream: &str) -> Result<u64>;
+}
+
+/// Streams kept in tables of the metadata database
+#[derive(Clone)]
+pub struct SqliteStreams {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStreams {
+    /// Keep streams in `conn`'s database, creating their tables if needed
+    pub fn new(conn: Arc<Mutex<Connection>>) -> Result<Self> {
+        conn.lock().execute_batch(
+            "CREATE TABLE IF NOT EXISTS stream_heads (
+                stream TEXT PRIMARY KEY,
+                last_seq INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS stream_records (
+                stream TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                data BLOB NOT NULL,
+                PRIMARY KEY (stream, seq)
+            );"
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl StreamStore for SqliteStreams {
+    fn append(&self, stream: &str, data: &[u8]) -> Result<u64> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let seq: i64 = tx.query_row(
+            "INSERT INTO stream_heads (stream, last_seq) VALUES (?1, 1)
+             ON CONFLICT(stream) DO UPDATE SET last_seq = last_seq + 1
+             RETURNING last_seq",
+            params![stream],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "INSERT INTO stream_records (stream, seq, data) VALUES (?1, ?2, ?3)",
+            params![stream, seq, data],
+        )?;
+        tx.commit()?;
+        Ok(seq as u64)
+    }
+
+    fn read(&self, stream: &str, after: u64, limit: usize) -> Result<Vec<Record>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT seq, data FROM stream_records WHERE stream = ?1 AND seq > ?2 ORDER BY seq LIMIT ?3"
+        )?;
+        let records = stmt.query_map(
+            params![stream, after as i64, limit as i64],
+            |row| Ok(Record { seq: row.get::<_, i64>(0)? as u64, data: row.get(1)? }),
+        )?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(records)
+    }
+
+    fn trim(&self, stream: &str, through: u64) -> Result<usize> {
+        let trimmed = self.conn.lock().execute(
+            "DELETE FROM stream_records WHERE stream = ?1 AND seq <= ?2",
+            params![stream, through as i64],
+        )?;
+        Ok(trimmed)
+    }
+
+    fn last_seq(&self, stream: &str) -> Result<u64> {
+        let seq: Option<i64> = self.conn.lock().query_row(
+            "SELECT last_seq FROM stream_heads WHERE stream = ?1",
+            params![stream],
+            |row| row.get(0),
+        ).optional()?;
+        Ok(seq.unwrap_or(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streams_are_numbered_and_trimmed() {
+        let streams = SqliteStreams::new(Arc::new(Mutex::new(Connection::open_in_memory().unwrap()))).unwrap();
+        for event in ["created", "updated", "deleted"] {
+            streams.append("orders", event.as_bytes()).unwrap();
+        }
+        assert_eq!(streams.append("invoices", b"created").unwrap(), 1);
+
+        let records = streams.read("orders", 1, 10).unwrap();
+        assert_eq!(records.iter().map(|r| r.seq).collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(records[0].data, b"updated");
+        assert_eq!(streams.read("orders", 0, 1).unwrap().len(), 1);
+        assert_eq!(streams.last_seq("orders").unwrap(), 3);
+        assert_eq!(streams.last_seq("refunds").unwrap(), 0);
+
+        // Trimming frees the records but not their numbers
+        assert_eq!(streams.trim("orders", 3).unwrap(), 3);
+        assert!(streams.read("orders", 0, 10).unwrap().is_empty());
+        assert_eq!(streams.append("orders", b"created").unwrap(), 4);
+    }
+}
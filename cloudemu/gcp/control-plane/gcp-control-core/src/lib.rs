@@ -18,3 +18,4 @@ pub mod services;
 
 pub use provider::GcpProvider;
 pub use gcp_data_core::storage::StorageEngine as GcpStorageEngine;
+pub use gcp_data_core::storage::Config as GcpConfig;
@@ -9,16 +9,22 @@ use crate::services::{
     billing::CloudBillingService,
 };
 use gcp_control_spi::{
-    CloudProvider, CloudProviderTrait, CloudResult, Request, Response, ServiceType,
+    CloudError, CloudProvider, CloudProviderTrait, CloudResult, Request, Response, ServiceType,
 };
-use gcp_data_core::storage::{StorageEngine, Config};
+use gcp_data_core::storage::{Config, ProjectEngines, StorageEngine};
 use std::sync::Arc;
 
 /// Google Cloud Platform provider.
+///
+/// Every project named in a request path gets its own storage engine, so emulated
+/// projects can reuse resource names without seeing each other's resources. Cloud Storage
+/// bucket names are global, as in GCP, so buckets and objects live in the default project.
 pub struct GcpProvider {
-    #[allow(dead_code)]
-    engine: Arc<StorageEngine>,
-    storage: CloudStorageService,
+    projects: ProjectEngines,
+}
+
+/// The services of one project, all backed by that project's engine
+struct ProjectServices {
     firestore: FirestoreService,
     pubsub: PubSubService,
     functions: CloudFunctionsService,
@@ -35,15 +41,9 @@ pub struct GcpProvider {
     kms: crate::services::kms::KmsService,
 }
 
-impl GcpProvider {
-    /// Create a new GCP provider.
-    pub fn new() -> Self {
-        let config = Config::from_env();
-        let engine = Arc::new(StorageEngine::new(&config).expect("Failed to initialize storage engine"));
-        
+impl ProjectServices {
+    fn new(engine: Arc<StorageEngine>) -> Self {
         Self {
-            engine: engine.clone(),
-            storage: CloudStorageService::new(engine.clone()),
             firestore: FirestoreService::new(engine.clone()),
             pubsub: PubSubService::new(engine.clone()),
             functions: CloudFunctionsService::new(engine.clone()),
@@ -57,32 +57,58 @@ impl GcpProvider {
             workflows: crate::services::workflows::WorkflowsService::new(engine.clone()),
             networking: crate::services::networking::NetworkingService::new(engine.clone()),
             run: crate::services::run::CloudRunService::new(engine.clone()),
-            kms: crate::services::kms::KmsService::new(engine.clone()),
+            kms: crate::services::kms::KmsService::new(engine),
         }
     }
+}
+
+impl GcpProvider {
+    /// Create a new GCP provider.
+    pub fn new() -> Self {
+        Self::with_config(Config::from_env())
+    }
+
+    /// Create a GCP provider storing its projects under `config.data_dir`
+    pub fn with_config(config: Config) -> Self {
+        let projects = ProjectEngines::new(&config).expect("Failed to initialize storage engine");
+        Self { projects }
+    }
 
     /// Create a new in-memory GCP provider for testing
     pub fn in_memory() -> Self {
-         let engine = Arc::new(StorageEngine::in_memory().expect("Failed to create in-memory engine"));
+        let projects = ProjectEngines::in_memory().expect("Failed to create in-memory engine");
+        Self { projects }
+    }
 
-         Self {
-            engine: engine.clone(),
-            storage: CloudStorageService::new(engine.clone()),
-            firestore: FirestoreService::new(engine.clone()),
-            pubsub: PubSubService::new(engine.clone()),
-            functions: CloudFunctionsService::new(engine.clone()),
-            secret_manager: SecretManagerService::new(engine.clone()),
-            billing: CloudBillingService::new(engine.clone()),
-            compute: crate::services::compute::ComputeService::new(engine.clone()),
-            sql: crate::services::sql::SqlService::new(engine.clone()),
-            iam: crate::services::iam::IamService::new(engine.clone()),
-            dns: crate::services::dns::DnsService::new(engine.clone()),
-            monitoring: crate::services::monitoring::MonitoringService::new(engine.clone()),
-            workflows: crate::services::workflows::WorkflowsService::new(engine.clone()),
-            networking: crate::services::networking::NetworkingService::new(engine.clone()),
-            run: crate::services::run::CloudRunService::new(engine.clone()),
-            kms: crate::services::kms::KmsService::new(engine.clone()),
+    /// Projects opened since startup or found in the data directory
+    pub fn projects(&self) -> CloudResult<Vec<String>> {
+        self.projects.projects().map_err(|e| CloudError::Internal(e.to_string()))
+    }
+
+    /// Project a request belongs to: the `{id}` of an API path starting
+    /// `[/{api}]/{version}/projects/{id}` or, for Cloud Run, `/apis/{group}/{version}/namespaces/{id}`;
+    /// otherwise the default project. Only that prefix counts, so a bucket or object named `projects` is
+    /// never taken for a project.
+    fn project_of<'a>(&'a self, path: &'a str) -> &'a str {
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let mut segments = path.trim_start_matches('/').split('/');
+        let is_version = |segment: &str| {
+            segment.strip_prefix('v').and_then(|rest| rest.chars().next()).is_some_and(|c| c.is_ascii_digit())
+        };
+        let mut segment = segments.next();
+        // `/compute/v1/...` and `/sql/v1beta4/...` name the API before the version, and
+        // `/apis/serving.knative.dev/v1/...` the API group
+        if segment == Some("apis") {
+            segment = segments.nth(1);
+        } else if segment.is_some_and(|s| !is_version(s)) {
+            segment = segments.next();
+        }
+        if segment.is_some_and(is_version) && matches!(segments.next(), Some("projects" | "namespaces")) {
+            if let Some(project) = segments.next().filter(|p| !p.is_empty()) {
+                return project;
+            }
         }
+        self.projects.default_project()
     }
 }
 
@@ -95,75 +121,81 @@ impl Default for GcpProvider {
 #[async_trait::async_trait]
 impl CloudProviderTrait for GcpProvider {
     async fn handle_request(&self, req: Request) -> CloudResult<Response> {
+        let engine = self.projects.engine(self.project_of(&req.path))
+            .map_err(|e| CloudError::Validation(e.to_string()))?;
+        let services = ProjectServices::new(engine);
+
         // Route based on path patterns
         let path = &req.path;
         
         // Firestore: /projects/.../databases/.../documents/...
         if path.contains("/databases/") && path.contains("/documents") {
-            return self.firestore.handle_request(req).await;
+            return services.firestore.handle_request(req).await;
         }
         
         // Pub/Sub: /v1/projects/.../topics/... or .../subscriptions/...
         if path.contains("/topics/") || path.contains("/subscriptions/") {
-            return self.pubsub.handle_request(req).await;
+            return services.pubsub.handle_request(req).await;
         }
         
         // Cloud Functions: /v1/projects/.../locations/.../functions/...
         if path.contains("/functions/") {
-            return self.functions.handle_request(req).await;
+            return services.functions.handle_request(req).await;
         }
         
         // Secret Manager: /v1/projects/.../secrets/...
         // Secret Manager: /v1/projects/.../secrets/...
         if path.contains("/secrets/") {
-            return self.secret_manager.handle_request(req).await;
+            return services.secret_manager.handle_request(req).await;
         }
 
         // Cloud Billing: /v1/services
         if path.contains("/services") && !path.contains("/locations/") {
-             return self.billing.handle_request(req).await;
+             return services.billing.handle_request(req).await;
         }
         
         // Compute Engine: /compute/v1/projects/...
         if path.contains("/compute/v1/") {
-            return self.compute.handle_request(req).await;
+            return services.compute.handle_request(req).await;
         }
 
         // Cloud SQL: /sql/v1beta4/projects/...
         if path.contains("/sql/") {
-            return self.sql.handle_request(req).await;
+            return services.sql.handle_request(req).await;
         }
 
         if path.contains("/serviceAccounts") {
-             return self.iam.handle_request(req).await;
+             return services.iam.handle_request(req).await;
         }
 
         if path.contains("/dns/v1") || path.contains("/managedZones") {
-             return self.dns.handle_request(req).await;
+             return services.dns.handle_request(req).await;
         }
 
         if path.contains("/timeSeries") {
-             return self.monitoring.handle_request(req).await;
+             return services.monitoring.handle_request(req).await;
         }
 
         if path.contains("/workflows") {
-             return self.workflows.handle_request(req).await;
+             return services.workflows.handle_request(req).await;
         }
 
         if path.contains("/global/networks") || path.contains("/subnetworks") {
-             return self.networking.handle_request(req).await;
+             return services.networking.handle_request(req).await;
         }
 
         if path.contains("/locations/") && path.contains("/services") {
-             return self.run.handle_request(req).await;
+             return services.run.handle_request(req).await;
         }
 
         if path.contains("/keyRings") {
-             return self.kms.handle_request(req).await;
+             return services.kms.handle_request(req).await;
         }
         
-        // Default: Cloud Storage (bucket/object operations)
-        self.storage.handle_request(req).await
+        // Default: Cloud Storage (bucket/object operations), whose buckets are global
+        let engine = self.projects.engine(self.projects.default_project())
+            .map_err(|e| CloudError::Internal(e.to_string()))?;
+        CloudStorageService::new(engine).handle_request(req).await
     }
 
     fn supported_services(&self) -> Vec<ServiceType> {
@@ -212,4 +244,76 @@ mod tests {
         let response = provider.handle_request(req).await.unwrap();
         assert_eq!(response.status, 201);
     }
+
+    fn request(method: &str, path: &str, body: &[u8]) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: std::collections::HashMap::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_projects_are_isolated() {
+        let provider = GcpProvider::in_memory();
+
+        let created = provider.handle_request(request("PUT", "/v1/projects/alpha/topics/orders", b"")).await.unwrap();
+        assert_eq!(created.status, 201);
+        // The same name is free in another project
+        let created = provider.handle_request(request("PUT", "/v1/projects/beta/topics/orders", b"")).await.unwrap();
+        assert_eq!(created.status, 201);
+        provider.handle_request(request("PUT", "/v1/projects/gamma/topics/invoices", b"")).await.unwrap();
+
+        let fetched = provider.handle_request(request("GET", "/v1/projects/gamma/topics/invoices", b"")).await.unwrap();
+        assert_eq!(fetched.status, 200);
+        assert!(provider.handle_request(request("GET", "/v1/projects/alpha/topics/invoices", b"")).await.is_err());
+
+        assert!(provider.handle_request(request("PUT", "/v1/projects/../topics/orders", b"")).await.is_err());
+        assert_eq!(provider.projects().unwrap(), vec!["alpha", "beta", "cloudemu", "gamma"]);
+    }
+
+    #[tokio::test]
+    async fn test_buckets_are_global() {
+        let provider = GcpProvider::in_memory();
+
+        // A bucket created for a project is reachable without naming it again
+        provider.handle_request(request("PUT", "/reports?project=alpha", b"")).await.unwrap();
+        provider.handle_request(request("PUT", "/reports/2026/q1.csv", b"csv")).await.unwrap();
+        let object = provider.handle_request(request("GET", "/reports/2026/q1.csv?project=beta", b"")).await.unwrap();
+        assert_eq!(object.body, b"csv");
+
+        // Path segments past the API prefix are object keys, not projects
+        provider.handle_request(request("PUT", "/assets", b"")).await.unwrap();
+        provider.handle_request(request("PUT", "/assets/projects/logo.png", b"png")).await.unwrap();
+        let object = provider.handle_request(request("GET", "/assets/projects/logo.png", b"")).await.unwrap();
+        assert_eq!(object.body, b"png");
+        assert_eq!(provider.project_of("/assets/projects/logo.png"), "cloudemu");
+        assert_eq!(provider.project_of("/compute/v1/projects/alpha/zones/us-east1-b/instances"), "alpha");
+        assert_eq!(provider.project_of("/v1/projects/beta/secrets/token"), "beta");
+        assert_eq!(provider.project_of("/apis/serving.knative.dev/v1/namespaces/gamma/services"), "gamma");
+        assert_eq!(provider.project_of("/v1/buckets/projects/alpha"), "cloudemu");
+        assert_eq!(provider.projects().unwrap(), vec!["cloudemu"]);
+    }
+
+    #[tokio::test]
+    async fn test_state_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("cloudemu-gcp-{}", uuid::Uuid::new_v4()));
+        let config = Config::default().data_dir(&dir);
+
+        let provider = GcpProvider::with_config(config.clone());
+        provider.handle_request(request("PUT", "/assets", b"")).await.unwrap();
+        provider.handle_request(request("PUT", "/assets/logo.png", b"png")).await.unwrap();
+        provider.handle_request(request("PUT", "/v1/projects/staging/topics/orders", b"")).await.unwrap();
+        drop(provider);
+
+        let provider = GcpProvider::with_config(config);
+        let object = provider.handle_request(request("GET", "/assets/logo.png", b"")).await.unwrap();
+        assert_eq!(object.body, b"png");
+        let topic = provider.handle_request(request("GET", "/v1/projects/staging/topics/orders", b"")).await.unwrap();
+        assert_eq!(topic.status, 200);
+        assert_eq!(provider.projects().unwrap(), vec!["cloudemu", "staging"]);
+        drop(provider);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -13,7 +13,8 @@ impl CloudStorageService {
     }
 
     pub async fn handle_request(&self, req: Request) -> CloudResult<Response> {
-        let path = req.path.trim_start_matches('/');
+        // Query parameters such as `project` do not name the bucket or object
+        let path = req.path.split_once('?').map_or(req.path.as_str(), |(path, _)| path).trim_start_matches('/');
         let parts: Vec<&str> = path.split('/').collect();
 
         if parts.is_empty() {
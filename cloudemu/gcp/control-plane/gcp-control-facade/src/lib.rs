@@ -16,7 +16,7 @@ use gcp_control_spi::{CloudProviderTrait, Request as GcpRequest};
 pub use gcp_control_core;
 pub use gcp_control_spi;
 pub use gcp_control_api;
-pub use gcp_control_core::GcpProvider;
+pub use gcp_control_core::{GcpConfig, GcpProvider};
 
 /// Create an Axum router for the GCP provider.
 pub fn router(provider: Arc<GcpProvider>) -> Router {
@@ -1,9 +1,12 @@
 use clap::Parser;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use std::net::SocketAddr;
 use tracing::info;
-use gcp_control_facade::{router, GcpProvider};
+use gcp_control_facade::{router, GcpConfig, GcpProvider};
+
+const DEFAULT_DATA_DIR: &str = ".cloudemu/gcp";
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -12,6 +15,14 @@ struct Config {
     #[arg(long, default_value_t = 4568, env = "CLOUDEMU_GCP_PORT")]
     port: u16,
 
+    /// Data directory [default: .cloudemu/gcp, or .cloudemu if only that holds data]
+    #[arg(long, env = "CLOUDEMU_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Project for requests that name none
+    #[arg(long, default_value = "cloudemu", env = "CLOUDEMU_PROJECT_ID")]
+    project: String,
+
     /// Host
     #[arg(long, default_value = "0.0.0.0", env = "CLOUDEMU_HOST")]
     host: String,
@@ -21,10 +32,18 @@ struct Config {
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     let config = Config::parse();
+    // Releases before per-provider directories kept GCP data in `.cloudemu` itself
+    let data_dir = config.data_dir.unwrap_or_else(|| {
+        emu_storage::data_dir_or_legacy(Path::new(DEFAULT_DATA_DIR), Path::new(emu_storage::LEGACY_DATA_DIR))
+    });
 
     info!("Starting CloudEmu GCP Server on {}:{}", config.host, config.port);
+    info!("Data Directory: {:?}", data_dir);
+    info!("Default Project: {}", config.project);
 
-    let provider = Arc::new(GcpProvider::new());
+    let provider = Arc::new(GcpProvider::with_config(
+        GcpConfig::from_env().data_dir(data_dir).project_id(config.project),
+    ));
     let app = router(provider);
 
     let host_ip: std::net::IpAddr = config.host.parse()?;
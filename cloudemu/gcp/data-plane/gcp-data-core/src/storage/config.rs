@@ -15,6 +15,8 @@ pub struct Config {
     pub region: String,
     /// AWS account ID to use
     pub account_id: String,
+    /// GCP project for requests whose path names none
+    pub project_id: String,
     /// Enable request logging
     pub enable_logging: bool,
     /// Enable AWS Signature V4 validation
@@ -29,6 +31,7 @@ impl Default for Config {
             data_dir: PathBuf::from(".cloudemu"),
             region: "us-east-1".to_string(),
             account_id: "000000000000".to_string(),
+            project_id: "cloudemu".to_string(),
             enable_logging: true,
             validate_signatures: false, // Disabled by default for ease of use
         }
@@ -43,10 +46,10 @@ impl Config {
         if let Ok(host) = std::env::var("CLOUDEMU_HOST") {
             config.host = host;
         }
-        if let Ok(port) = std::env::var("CLOUDEMU_PORT") {
+        if let Ok(port) = std::env::var("CLOUDEMU_PORT") {
             if let Ok(p) = port.parse() {
-            config.port = p;
-            }
+            config.port = p;
+            }
         }
         if let Ok(dir) = std::env::var("CLOUDEMU_DATA_DIR") {
             config.data_dir = PathBuf::from(dir);
@@ -57,6 +60,9 @@ impl Config {
         if let Ok(account) = std::env::var("CLOUDEMU_ACCOUNT_ID") {
             config.account_id = account;
         }
+        if let Ok(project) = std::env::var("CLOUDEMU_PROJECT_ID") {
+            config.project_id = project;
+        }
         if let Ok(logging) = std::env::var("CLOUDEMU_LOGGING") {
             config.enable_logging = logging == "true" || logging == "1";
         }
@@ -90,5 +96,11 @@ impl Config {
         self.region = region.into();
         self
     }
+
+    /// Builder-style project_id setter
+    pub fn project_id(mut self, project: impl Into<String>) -> Self {
+        self.project_id = project.into();
+        self
+    }
 }
 
@@ -130,6 +130,8 @@ impl EmulatorError {
     }
 }
 
+emu_storage::impl_from_storage_error!(EmulatorError);
+
 impl From<rusqlite::Error> for EmulatorError {
     fn from(e: rusqlite::Error) -> Self {
         Self::Database(e.to_string())
@@ -2,9 +2,8 @@
 
 use crate::config::Config;
 use crate::error::Result;
+use emu_storage::{BlobStore, FsBlobStore, Storage, StorageBackend};
 use rusqlite::Connection;
-use std::fs;
-use std::path::PathBuf;
 use std::sync::Arc;
 use parking_lot::Mutex;
 use super::schema::SCHEMA;
@@ -15,46 +14,14 @@ use serde::{Serialize, Deserialize};
 pub struct StorageEngine {
     /// SQLite connection (wrapped for thread safety)
     pub(crate) db: Arc<Mutex<Connection>>,
-    /// Directory for object data
-    pub(crate) objects_dir: PathBuf,
+    /// Content-addressed object data
+    pub(crate) blobs: FsBlobStore,
 }
 
 impl StorageEngine {
     /// Create a new storage engine
     pub fn new(config: &Config) -> Result<Self> {
-        // Create data directory
-        fs::create_dir_all(&config.data_dir)?;
-        
-        // Create objects directory
-        let objects_dir = config.data_dir.join("objects");
-        fs::create_dir_all(&objects_dir)?;
-        
-        // Open SQLite database
-        let db_path = config.data_dir.join("metadata.db");
-        let conn = Connection::open(&db_path)?;
-        
-        // Enable WAL mode for better concurrency
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
-        
-        // Create schema
-        conn.execute_batch(SCHEMA)?;
-        
-        let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            objects_dir,
-        };
-
-        engine.init_iam_tables()?;
-        engine.init_dns_tables()?;
-        engine.init_monitoring_tables()?;
-        engine.init_workflows_tables()?;
-        engine.init_networking_tables()?;
-        engine.init_run_tables()?;
-        engine.init_kms_tables()?;
-        engine.init_compute_tables()?;
-        engine.init_sql_tables()?;
-
-        Ok(engine)
+        Self::init(Storage::open(&config.data_dir, SCHEMA)?)
     }
 
     pub fn get_connection(&self) -> Result<parking_lot::MutexGuard<Connection>> {
@@ -63,19 +30,25 @@ impl StorageEngine {
     
     /// Create a new in-memory storage engine (for testing)
     pub fn in_memory() -> Result<Self> {
-        let conn = Connection::open_in_memory()?;
-        conn.execute_batch(SCHEMA)?;
-        
-        let temp_dir = std::env::temp_dir().join(format!("cloudemu-{}", uuid::Uuid::new_v4()));
-        fs::create_dir_all(&temp_dir)?;
-        
+        Self::init(Storage::in_memory(SCHEMA)?)
+    }
+    
+    /// Create the service tables, so on-disk and in-memory engines hold the same schema
+    fn init(storage: Storage) -> Result<Self> {
         let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            objects_dir: temp_dir,
+            db: storage.db,
+            blobs: storage.blobs,
         };
 
         engine.init_iam_tables()?;
         engine.init_dns_tables()?;
+        engine.init_monitoring_tables()?;
+        engine.init_workflows_tables()?;
+        engine.init_networking_tables()?;
+        engine.init_run_tables()?;
+        engine.init_kms_tables()?;
+        engine.init_compute_tables()?;
+        engine.init_sql_tables()?;
 
         Ok(engine)
     }
@@ -84,32 +57,12 @@ impl StorageEngine {
     
     /// Store object data to filesystem, returns content hash
     pub(crate) fn store_object_data(&self, data: &[u8]) -> Result<String> {
-        use sha2::{Sha256, Digest};
-        
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        let hash = hex::encode(hasher.finalize());
-        
-        // Content-addressed storage: first 2 chars as directory
-        let dir = self.objects_dir.join(&hash[..2]);
-        fs::create_dir_all(&dir)?;
-        
-        let file_path = dir.join(&hash);
-        if !file_path.exists() {
-            fs::write(&file_path, data)?;
-        }
-        
-        Ok(hash)
+        Ok(self.blobs.put(data)?)
     }
     
     /// Read object data from filesystem
     pub(crate) fn read_object_data(&self, content_hash: &str) -> Result<Vec<u8>> {
-        if content_hash.is_empty() {
-            return Ok(Vec::new());
-        }
-        
-        let file_path = self.objects_dir.join(&content_hash[..2]).join(content_hash);
-        fs::read(&file_path).map_err(|e| crate::error::EmulatorError::Internal(e.to_string()))
+        Ok(self.blobs.get(content_hash)?)
     }
     
     // Bucket Operations moved to s3.rs
@@ -139,6 +92,16 @@ impl StorageEngine {
     // Lambda Operations moved to lambda.rs
 }
 
+impl StorageBackend for StorageEngine {
+    fn database(&self) -> &Arc<Mutex<Connection>> {
+        &self.db
+    }
+
+    fn blobs(&self) -> &FsBlobStore {
+        &self.blobs
+    }
+}
+
 /// Bucket metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BucketMetadata {
@@ -1,6 +1,7 @@
 //! Storage engine - SQLite metadata + filesystem objects
 
 mod engine;
+mod projects;
 mod schema;
 // AWS modules removed (s3, dynamodb, etc)
 mod gcs;
@@ -36,6 +37,7 @@ pub use engine::{
     GcpInstanceMetadata,
 };
 
+pub use projects::ProjectEngines;
 pub use iam::ServiceAccount;
 pub use dns::ManagedZone;
 pub use workflows::Workflow;
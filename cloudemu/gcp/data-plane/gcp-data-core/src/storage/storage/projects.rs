@@ -0,0 +1,145 @@
+//! One storage engine per emulated GCP project
+//!
+//! Resources are keyed by name inside an engine, so projects get separate engines rather
+//! than a project column on every table. The default project lives at the root of the data
+//! directory, where a single-project emulator kept its data; every other project lives
+//! under `projects/<project-id>`.
+
+use super::engine::StorageEngine;
+use crate::config::Config;
+use crate::error::{EmulatorError, Result};
+use parking_lot::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Storage engines of all projects, opened the first time a project is used
+pub struct ProjectEngines {
+    /// Data directory of the default project; `None` keeps every project in memory
+    data_dir: Option<PathBuf>,
+    default_project: String,
+    engines: Mutex<HashMap<String, Arc<StorageEngine>>>,
+}
+
+impl ProjectEngines {
+    /// Open the projects under `config.data_dir`, starting with the default project
+    pub fn new(config: &Config) -> Result<Self> {
+        let projects = Self {
+            data_dir: Some(config.data_dir.clone()),
+            default_project: config.project_id.clone(),
+            engines: Mutex::new(HashMap::new()),
+        };
+        projects.engine(&config.project_id)?;
+        Ok(projects)
+    }
+
+    /// Keep every project in memory (for testing)
+    pub fn in_memory() -> Result<Self> {
+        let config = Config::default();
+        let projects = Self {
+            data_dir: None,
+            default_project: config.project_id.clone(),
+            engines: Mutex::new(HashMap::new()),
+        };
+        projects.engine(&config.project_id)?;
+        Ok(projects)
+    }
+
+    /// Project used by requests whose path names none
+    pub fn default_project(&self) -> &str {
+        &self.default_project
+    }
+
+    /// Engine of `project`, created on first use
+    pub fn engine(&self, project: &str) -> Result<Arc<StorageEngine>> {
+        validate_project_id(project)?;
+
+        let mut engines = self.engines.lock();
+        if let Some(engine) = engines.get(project) {
+            return Ok(engine.clone());
+        }
+
+        let engine = match &self.data_dir {
+            Some(data_dir) => {
+                let dir = if project == self.default_project {
+                    data_dir.clone()
+                } else {
+                    data_dir.join("projects").join(project)
+                };
+                StorageEngine::new(&Config::default().data_dir(dir))?
+            }
+            None => StorageEngine::in_memory()?,
+        };
+        let engine = Arc::new(engine);
+        engines.insert(project.to_string(), engine.clone());
+        Ok(engine)
+    }
+
+    /// Projects opened since startup or found on disk, sorted
+    pub fn projects(&self) -> Result<Vec<String>> {
+        let mut projects: BTreeSet<String> = self.engines.lock().keys().cloned().collect();
+        if let Some(data_dir) = &self.data_dir {
+            let dir = data_dir.join("projects");
+            if dir.is_dir() {
+                for entry in fs::read_dir(dir)? {
+                    let entry = entry?;
+                    if entry.file_type()?.is_dir() {
+                        projects.insert(entry.file_name().to_string_lossy().into_owned());
+                    }
+                }
+            }
+        }
+        Ok(projects.into_iter().collect())
+    }
+}
+
+/// Project IDs become directory names, so only letters, digits, `-`, `_` and `.` are allowed
+fn validate_project_id(project: &str) -> Result<()> {
+    let valid = !project.is_empty()
+        && !project.starts_with('.')
+        && project.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(EmulatorError::InvalidArgument(format!("Invalid project ID: {}", project)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projects_are_isolated() {
+        let projects = ProjectEngines::in_memory().unwrap();
+        projects.engine("alpha").unwrap().create_pubsub_topic("orders", "alpha").unwrap();
+
+        assert!(projects.engine("alpha").unwrap().get_pubsub_topic("orders").is_ok());
+        assert!(projects.engine("beta").unwrap().get_pubsub_topic("orders").is_err());
+        assert!(projects.engine("../escape").is_err());
+        assert_eq!(projects.projects().unwrap(), vec!["alpha", "beta", "cloudemu"]);
+    }
+
+    #[test]
+    fn test_projects_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("cloudemu-projects-{}", uuid::Uuid::new_v4()));
+        let config = Config::default().data_dir(&dir);
+
+        let projects = ProjectEngines::new(&config).unwrap();
+        projects.engine("cloudemu").unwrap().create_gcs_bucket("assets", "cloudemu", "US").unwrap();
+        projects.engine("staging").unwrap().create_gcs_bucket("assets", "staging", "EU").unwrap();
+        drop(projects);
+
+        // The default project keeps the root of the data directory
+        assert!(dir.join("metadata.db").exists());
+        assert!(dir.join("projects/staging/metadata.db").exists());
+
+        let projects = ProjectEngines::new(&config).unwrap();
+        assert_eq!(projects.projects().unwrap(), vec!["cloudemu", "staging"]);
+        assert_eq!(projects.engine("cloudemu").unwrap().get_gcs_bucket("assets").unwrap().location, "US");
+        assert_eq!(projects.engine("staging").unwrap().get_gcs_bucket("assets").unwrap().location, "EU");
+        drop(projects);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -8,6 +8,8 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] emu_storage::StorageError),
     #[error("Not Found: {0}")]
     NotFound(String),
 }
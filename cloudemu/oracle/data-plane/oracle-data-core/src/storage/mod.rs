@@ -1,11 +1,40 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
+use emu_storage::{FsBlobStore, SqliteKv, Storage, StorageBackend};
+use parking_lot::{Mutex, MutexGuard};
 use rusqlite::Connection;
 use crate::error::Result;
 
+/// Tables shared by every OCI service
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS oci_resources (
+        id TEXT PRIMARY KEY,
+        ocid TEXT NOT NULL,
+        resource_type TEXT NOT NULL,
+        data JSON NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS pricing_products (
+        sku TEXT PRIMARY KEY,
+        service_code TEXT,
+        product_family TEXT,
+        attributes JSON
+    );
+    CREATE TABLE IF NOT EXISTS pricing_terms (
+        id TEXT PRIMARY KEY,
+        sku TEXT,
+        offer_term_code TEXT,
+        description TEXT,
+        effective_date TEXT,
+        price_dimensions JSON
+    );
+";
+
 pub struct StorageEngine {
     pub db: Arc<Mutex<Connection>>,
-    pub data_dir: PathBuf,
+    /// Content-addressed object data
+    pub blobs: FsBlobStore,
+    /// Key-value entries, such as NoSQL rows
+    pub kv: SqliteKv,
 }
 
 pub mod pricing;
@@ -27,47 +56,18 @@ pub mod nosql;
 
 impl StorageEngine {
     pub fn new(data_dir: PathBuf) -> Result<Self> {
-        std::fs::create_dir_all(&data_dir)?;
-        let db_path = data_dir.join("metadata.db");
-        let conn = Connection::open(&db_path)?;
-        
-        // Init tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS oci_resources (
-                id TEXT PRIMARY KEY,
-                ocid TEXT NOT NULL,
-                resource_type TEXT NOT NULL,
-                data JSON NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Pricing Tables
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS pricing_products (
-                sku TEXT PRIMARY KEY,
-                service_code TEXT,
-                product_family TEXT,
-                attributes JSON
-            )",
-            [],
-        )?;
-        
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS pricing_terms (
-                id TEXT PRIMARY KEY,
-                sku TEXT,
-                offer_term_code TEXT,
-                description TEXT,
-                effective_date TEXT,
-                price_dimensions JSON
-            )",
-            [],
-        )?;
+        Self::init(Storage::open(&data_dir, SCHEMA)?)
+    }
+
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Storage::in_memory(SCHEMA)?)
+    }
 
+    fn init(storage: Storage) -> Result<Self> {
         let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            data_dir,
+            kv: storage.kv()?,
+            db: storage.db,
+            blobs: storage.blobs,
         };
 
         engine.init_compute_tables()?;
@@ -86,28 +86,17 @@ impl StorageEngine {
         Ok(engine)
     }
 
-    pub fn in_memory() -> Result<Self> {
-        let conn = Connection::open_in_memory()?;
-        let engine = Self {
-            db: Arc::new(Mutex::new(conn)),
-            data_dir: PathBuf::from(""), // In-memory
-        };
-        engine.init_compute_tables()?;
-        engine.init_db_tables()?;
-        engine.init_identity_tables()?;
-        engine.init_dns_tables()?;
-        engine.init_object_storage_tables()?;
-        engine.init_monitoring_tables()?;
-        engine.init_monitoring_tables()?;
-        engine.init_functions_tables()?;
-        engine.init_queue_tables()?;
-        engine.init_networking_tables()?;
-        engine.init_containers_tables()?;
-        engine.init_vault_tables()?;
-        engine.init_nosql_tables()?;
-        Ok(engine)
+    pub fn get_connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        Ok(self.db.lock())
     }
-    pub fn get_connection(&self) -> Result<std::sync::MutexGuard<rusqlite::Connection>> {
-        self.db.lock().map_err(|_| crate::error::Error::NotFound("Lock poisoned".into()))
+}
+
+impl StorageBackend for StorageEngine {
+    fn database(&self) -> &Arc<Mutex<Connection>> {
+        &self.db
+    }
+
+    fn blobs(&self) -> &FsBlobStore {
+        &self.blobs
     }
 }
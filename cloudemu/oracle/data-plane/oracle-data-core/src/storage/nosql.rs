@@ -1,7 +1,13 @@
 use super::StorageEngine;
 use crate::error::Result;
 use serde::{Deserialize, Serialize};
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
+use emu_storage::KvStore;
+
+/// Key-value namespace holding the rows of a NoSQL table
+fn rows_namespace(table: &str) -> String {
+    format!("nosql/{}", table)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoSqlTable {
@@ -33,15 +39,22 @@ impl StorageEngine {
             [],
         )?;
 
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS oci_nosql_rows (
-                table_name TEXT NOT NULL,
-                key TEXT NOT NULL,
-                value TEXT NOT NULL,
-                PRIMARY KEY(table_name, key)
-            )",
+        // Rows used to have a table of their own; move them to the key-value store
+        let legacy_rows: Option<String> = conn.query_row(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'oci_nosql_rows'",
             [],
-        )?;
+            |row| row.get(0),
+        ).optional()?;
+        if legacy_rows.is_some() {
+            let rows = conn.prepare("SELECT table_name, key, value FROM oci_nosql_rows")?
+                .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            drop(conn);
+            for (table, key, value) in rows {
+                self.kv.put(&rows_namespace(&table), &key, value.as_bytes())?;
+            }
+            self.get_connection()?.execute("DROP TABLE oci_nosql_rows", [])?;
+        }
         Ok(())
     }
 
@@ -63,14 +76,7 @@ impl StorageEngine {
     }
 
     pub fn put_nosql_row(&self, table: &str, key: &str, value: &str) -> Result<()> {
-        let conn = self.get_connection()?;
-        
-        conn.execute(
-            "INSERT OR REPLACE INTO oci_nosql_rows (table_name, key, value)
-             VALUES (?1, ?2, ?3)",
-            params![table, key, value],
-        )?;
-
+        self.kv.put(&rows_namespace(table), key, value.as_bytes())?;
         Ok(())
     }
 }
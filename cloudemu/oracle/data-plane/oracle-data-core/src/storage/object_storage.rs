@@ -1,5 +1,6 @@
 use super::StorageEngine;
 use crate::error::Result;
+use emu_storage::BlobStore;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 
@@ -55,11 +56,22 @@ impl StorageEngine {
                 time_created INTEGER,
                 etag TEXT NOT NULL,
                 content_type TEXT,
+                content_hash TEXT,
                 PRIMARY KEY(namespace, bucket_name, name)
             )", 
             Self::TABLE_OCI_OBJECTS
         ), [])?;
 
+        // Objects used to be files named after them; data written since is kept in the blob store
+        let has_content_hash: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = 'content_hash'",
+            [Self::TABLE_OCI_OBJECTS],
+            |row| row.get(0),
+        )?;
+        if !has_content_hash {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN content_hash TEXT", Self::TABLE_OCI_OBJECTS), [])?;
+        }
+
         Ok(())
     }
 
@@ -75,10 +87,6 @@ impl StorageEngine {
             params![name, namespace, compartment_id, user, now, etag],
         )?;
 
-        // Create directory for bucket objects
-        let bucket_path = self.data_dir.join("objects").join(namespace).join(name);
-        std::fs::create_dir_all(&bucket_path)?;
-
         Ok(Bucket {
             name: name.to_string(),
             namespace: namespace.to_string(),
@@ -113,24 +121,19 @@ impl StorageEngine {
         let now = chrono::Utc::now().timestamp();
         let size = data.len() as u64;
         let md5 = format!("{:x}", md5::compute(data)); // simple md5
+        let content_hash = self.blobs.put(data)?;
 
         conn.execute(
             &format!("INSERT INTO {} (
-                name, bucket_name, namespace, size, md5, time_created, etag, content_type
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                name, bucket_name, namespace, size, md5, time_created, etag, content_type, content_hash
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
               ON CONFLICT(namespace, bucket_name, name) DO UPDATE SET
-                size=excluded.size, md5=excluded.md5, time_created=excluded.time_created, etag=excluded.etag, content_type=excluded.content_type
+                size=excluded.size, md5=excluded.md5, time_created=excluded.time_created, etag=excluded.etag,
+                content_type=excluded.content_type, content_hash=excluded.content_hash
             ", Self::TABLE_OCI_OBJECTS),
-            params![name, bucket, namespace, size, md5, now, etag, content_type],
+            params![name, bucket, namespace, size, md5, now, etag, content_type, content_hash],
         )?;
 
-        // Write content to file
-        let file_path = self.data_dir.join("objects").join(namespace).join(bucket).join(name);
-        if let Some(parent) = file_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(file_path, data)?;
-
         Ok(Object {
             name: name.to_string(),
             bucket_name: bucket.to_string(),
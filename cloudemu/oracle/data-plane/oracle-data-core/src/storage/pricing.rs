@@ -25,7 +25,7 @@ impl super::StorageEngine {
         &self,
         service_code: &str,
     ) -> Result<Vec<(Product, Vec<OfferTerm>)>> {
-        let conn = self.db.lock().map_err(|_| crate::error::Error::NotFound("Lock poisoned".into()))?;
+        let conn = self.get_connection()?;
         let mut stmt = conn.prepare(
             r#"
             SELECT 
@@ -84,7 +84,7 @@ impl super::StorageEngine {
 
     /// Implement a seeding function for mock Oracle OCI pricing data
     pub async fn seed_pricing_data(&self) -> Result<()> {
-        let conn = self.db.lock().map_err(|_| crate::error::Error::NotFound("Lock poisoned".into()))?;
+        let conn = self.get_connection()?;
         
         // Compute (VM.Standard2.1)
         let sku = "B9F0-5A32-9D1C"; 
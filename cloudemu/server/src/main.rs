@@ -48,6 +48,42 @@ struct Config {
     /// Base Data Directory
     #[arg(long, default_value = ".cloudemu", env = "CLOUDEMU_DATA_DIR")]
     data_dir: PathBuf,
+
+    /// Terraform acceptance-test fixture mode for the AWS service
+    #[arg(long, env = "CLOUDEMU_TERRAFORM_MODE")]
+    terraform: bool,
+
+    /// YAML or JSON spec of generated data to load into the AWS service at startup
+    #[arg(long, env = "CLOUDEMU_SEED_FILE")]
+    seed_file: Option<PathBuf>,
+
+    /// Latency injected into AWS API calls: off, realistic or the path to a YAML profile
+    #[arg(long, env = "CLOUDEMU_LATENCY_PROFILE")]
+    latency_profile: Option<String>,
+
+    /// Bucket AWS SQS offloads oversized messages to, as the SQS Extended Client does
+    #[arg(long, env = "CLOUDEMU_SQS_PAYLOAD_BUCKET")]
+    sqs_payload_bucket: Option<String>,
+
+    /// Enable the AWS DAX endpoint's cache with this item and query TTL in milliseconds
+    #[arg(long, env = "CLOUDEMU_DAX_TTL_MS")]
+    dax_ttl_ms: Option<u64>,
+
+    /// Step Functions Local mock config stubbing AWS Task responses per test case
+    #[arg(long, env = "CLOUDEMU_SFN_MOCK_CONFIG")]
+    sfn_mock_config: Option<PathBuf>,
+
+    /// Seconds that count as one day for AWS S3 lifecycle rules
+    #[arg(long, env = "CLOUDEMU_S3_LIFECYCLE_DAY_SECS")]
+    s3_lifecycle_day_secs: Option<u64>,
+
+    /// Reject AWS requests without a valid Signature V4
+    #[arg(long, env = "CLOUDEMU_STRICT_AUTH")]
+    strict_auth: bool,
+
+    /// Comma-separated ACCESS_KEY_ID:SECRET pairs the AWS service accepts in strict auth mode (default test:test)
+    #[arg(long, env = "CLOUDEMU_CREDENTIALS")]
+    credentials: Option<String>,
 }
 
 // Simple handler for Oracle axum adapter
@@ -106,12 +142,28 @@ async fn main() -> anyhow::Result<()> {
     info!("----------------------------------------");
 
     // Start AWS
-    let aws_host = config.host.clone();
-    let aws_port = config.aws_port;
-    let aws_dir = config.data_dir.join("aws");
+    let aws_config = aws_control_facade::aws_control_core::Config::default()
+        .host(config.host.clone())
+        .port(config.aws_port)
+        .data_dir(config.data_dir.join("aws"))
+        .terraform_mode(config.terraform)
+        .seed_file(config.seed_file.clone())
+        .latency_profile(config.latency_profile.clone())
+        .sqs_payload_bucket(config.sqs_payload_bucket.clone())
+        .dax_ttl_ms(config.dax_ttl_ms)
+        .sfn_mock_config(config.sfn_mock_config.clone())
+        .s3_lifecycle_day_secs(config.s3_lifecycle_day_secs)
+        .strict_auth(config.strict_auth);
+    let aws_config = match &config.credentials {
+        Some(list) => aws_config.credentials(
+            aws_control_facade::aws_control_core::Config::parse_credentials(list)
+                .ok_or_else(|| anyhow::anyhow!("--credentials takes ACCESS_KEY_ID:SECRET pairs"))?,
+        ),
+        None => aws_config,
+    };
     
     let aws_handle = task::spawn(async move {
-        if let Err(e) = aws_control_facade::gateway::ingress::start(&aws_host, aws_port, aws_dir).await {
+        if let Err(e) = aws_control_facade::gateway::ingress::start_with_config(aws_config).await {
             error!("AWS Server failed: {:?}", e);
         }
     });
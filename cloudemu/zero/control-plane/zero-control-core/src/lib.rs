@@ -1,13 +1,20 @@
 //! ZeroCloud Control Plane Orchestrator
 
-use zero_control_spi::{ZeroRequest, ZeroResponse, ZeroResult, ZeroService, ZeroError};
+use zero_control_spi::{LivenessProbe, PortMapping, RestartPolicy, SecurityRule, VolumeMount, ZeroBody, ZeroRequest, ZeroResponse, ZeroResult, ZeroService, ZeroError};
 use zero_data_core::ZeroEngine;
+use zero_data_core::events::{VOLUME_CREATED, WORKLOAD_STARTED, WORKLOAD_STOPPED};
 use async_trait::async_trait;
 use std::sync::Arc;
 use serde_json::json;
 
+pub mod openapi;
+pub mod schema;
 pub mod services;
 
+/// Largest body read into memory for routes that take a JSON document;
+/// store object data is streamed instead
+pub const MAX_REQUEST_BODY_BYTES: usize = 64 * 1024 * 1024;
+
 pub struct ZeroProvider {
     engine: Arc<ZeroEngine>,
     pub store: services::store::StoreService,
@@ -17,6 +24,20 @@ pub struct ZeroProvider {
     pub iam: services::iam::IamService,
     pub lb: services::lb::LbService,
     pub eks: services::eks::EksService,
+    pub dns: services::dns::DnsService,
+    pub topic: services::topic::TopicService,
+    pub scheduler: services::scheduler::SchedulerService,
+    pub autoscaling: services::autoscaling::AutoscalingService,
+    pub event_source: services::event_source::EventSourceService,
+    pub backup: services::backup::BackupService,
+    pub placement: services::placement::PlacementService,
+    pub namespace: services::namespace::NamespaceService,
+    pub audit: services::audit::AuditService,
+    pub system: services::system::SystemService,
+    pub workload_metrics: services::workload_metrics::WorkloadMetricsService,
+    pub supervisor: services::supervisor::SupervisorService,
+    pub images: services::image::ImageService,
+    pub rollout: services::rollout::RolloutService,
 }
 
 impl ZeroProvider {
@@ -28,99 +49,798 @@ impl ZeroProvider {
         let iam = services::iam::IamService::new(engine.clone());
         let lb = services::lb::LbService::new(engine.clone());
         let eks = services::eks::EksService::new(engine.clone());
-        Self { engine, store, db, func, queue, iam, lb, eks }
+        let dns = services::dns::DnsService::new(engine.clone());
+        let topic = services::topic::TopicService::new(engine.clone());
+        let scheduler = services::scheduler::SchedulerService::new(engine.clone());
+        let autoscaling = services::autoscaling::AutoscalingService::new(engine.clone());
+        let event_source = services::event_source::EventSourceService::new(engine.clone());
+        let backup = services::backup::BackupService::new(engine.clone());
+        let placement = services::placement::PlacementService::new(engine.clone());
+        let namespace = services::namespace::NamespaceService::new(engine.clone());
+        let audit = services::audit::AuditService::new(engine.clone());
+        let system = services::system::SystemService::new(engine.clone(), lb.clone(), event_source.clone());
+        let workload_metrics = services::workload_metrics::WorkloadMetricsService::new(engine.clone());
+        let supervisor = services::supervisor::SupervisorService::new(engine.clone());
+        let images = services::image::ImageService::new(engine.clone());
+        let rollout = services::rollout::RolloutService::new(engine.clone());
+        Self { engine, store, db, func, queue, iam, lb, eks, dns, topic, scheduler, autoscaling, event_source, backup, placement, namespace, audit, system, workload_metrics, supervisor, images, rollout }
+    }
+
+    /// Receive resource lifecycle events from now on
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<zero_data_core::events::ResourceEvent> {
+        self.engine.events.subscribe()
+    }
+
+    /// Spawn a background task that deletes expired DB items every `interval`.
+    pub fn spawn_ttl_sweeper(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match db.sweep_expired().await {
+                    Ok(0) => {},
+                    Ok(n) => tracing::debug!("TTL sweeper removed {} expired items", n),
+                    Err(e) => tracing::error!("TTL sweep failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that looks for load balancer targets due for a health check every `interval`.
+    pub fn spawn_lb_health_checker(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let lb = self.lb.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match lb.check_due_target_health().await {
+                    Ok(0) => {},
+                    Ok(n) => tracing::debug!("LB health checker updated {} targets", n),
+                    Err(e) => tracing::error!("LB health check failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that fires due scheduler rules every `interval`.
+    pub fn spawn_scheduler(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let scheduler = self.scheduler.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                // Due rules are claimed before they run, so a slow webhook never delays the next tick
+                let scheduler = scheduler.clone();
+                tokio::spawn(async move {
+                    match scheduler.run_due_rules(chrono::Utc::now()).await {
+                        Ok(0) => {},
+                        Ok(n) => tracing::debug!("Scheduler fired {} rules", n),
+                        Err(e) => tracing::error!("Scheduler run failed: {}", e),
+                    }
+                });
+            }
+        })
+    }
+
+    /// Spawn a background task that scales autoscaling groups to their metrics every `interval`.
+    pub fn spawn_autoscaler(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let autoscaling = self.autoscaling.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match autoscaling.reconcile(chrono::Utc::now()).await {
+                    Ok(0) => {},
+                    Ok(n) => tracing::debug!("Autoscaler launched or removed {} instances", n),
+                    Err(e) => tracing::error!("Autoscaling failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that records the resource usage of every workload every `interval`.
+    pub fn spawn_metrics_sampler(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let workload_metrics = self.workload_metrics.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match workload_metrics.sample(chrono::Utc::now()).await {
+                    Ok(0) => {},
+                    Ok(n) => tracing::debug!("Metrics sampler measured {} workloads", n),
+                    Err(e) => tracing::error!("Sampling workload metrics failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that probes supervised workloads and restarts those that
+    /// stopped or failed under their restart policy every `interval`.
+    pub fn spawn_supervisor(&self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let supervisor = self.supervisor.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match supervisor.check(chrono::Utc::now()).await {
+                    Ok(0) => {},
+                    Ok(n) => tracing::info!("Supervisor restarted {} workloads", n),
+                    Err(e) => tracing::error!("Supervising workloads failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Spawn a background task that removes the images no workload has used for `retention`
+    /// every `interval`.
+    pub fn spawn_image_gc(&self, interval: std::time::Duration, retention: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let images = self.images.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match images.prune(retention, chrono::Utc::now()).await {
+                    Ok(report) if report.removed.is_empty() => {},
+                    Ok(report) => tracing::info!("Image GC removed {} images, reclaiming {} bytes", report.removed.len(), report.reclaimed_bytes),
+                    Err(e) => tracing::error!("Image GC failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Start the embedded DNS resolver on a UDP `port` so workloads can resolve hosted
+    /// zones and each other by name.
+    pub async fn start_dns_resolver(&self, port: u16) -> ZeroResult<tokio::task::JoinHandle<()>> {
+        services::dns_server::start_resolver(self.dns.clone(), port).await
+    }
+
+    /// Start the S3-compatible gateway over the store service on a TCP `port`; with
+    /// `require_auth` only requests signed with an access key are served.
+    pub async fn start_s3_gateway(&self, port: u16, require_auth: bool) -> ZeroResult<tokio::task::JoinHandle<()>> {
+        services::s3_gateway::S3Gateway::new(self.engine.clone(), require_auth).start(port).await
     }
 }
 
 #[async_trait]
 impl ZeroService for ZeroProvider {
-    async fn handle_request(&self, req: ZeroRequest) -> ZeroResult<ZeroResponse> {
-        let parts: Vec<&str> = req.path.split('/').filter(|s| !s.is_empty()).collect();
+    /// Serve a request, appending state-changing ones to the audit trail
+    async fn handle_request(&self, mut req: ZeroRequest) -> ZeroResult<ZeroResponse> {
+        if !services::audit::is_mutation(&req.method) {
+            return self.dispatch(req).await;
+        }
+        // Nothing changes, so there is nothing to audit
+        if services::dry_run::requested(&req) {
+            return self.dry_run(req).await;
+        }
+        let principal = services::audit::principal(&req).to_string();
+        let (method, path) = (req.method.clone(), req.path.clone());
+        let (body, digest) = services::audit::digest(std::mem::take(&mut req.body));
+        req.body = body;
+
+        let outcome = self.dispatch(req).await;
+        // The change has already happened, so a failure to record it is logged rather than returned
+        if let Err(e) = self.audit.record(&principal, &method, &path, digest.finish(), &outcome).await {
+            tracing::error!("Failed to record audit event for {} {}: {}", method, path, e);
+        }
+        outcome
+    }
+}
+
+impl ZeroProvider {
+    /// Validate a mutating request and report what it would change, without changing it
+    async fn dry_run(&self, mut req: ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::dry_run::{self, NAMESPACE};
+
+        let path = req.route_path().to_string();
+        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let body = std::mem::take(&mut req.body);
+        if !dry_run::streams_body(&parts) {
+            req.body = body.collect(MAX_REQUEST_BODY_BYTES).await?.into();
+        }
+        let body = match dry_run::route_schema(&req.method, &parts) {
+            Some(schema) => Some(schema::parse_body(&req, schema)?),
+            None => None,
+        };
+
+        let namespace = services::namespace::requested(&req);
+        let change = dry_run::change(&req.method, &parts, body.as_ref());
+        match (change.kind, change.id.as_deref()) {
+            (Some(NAMESPACE), Some(name)) => match (change.operation, self.namespace.get(name).await) {
+                ("create", Ok(_)) => return Err(ZeroError::AlreadyExists(format!("Namespace {} already exists", name))),
+                ("create", Err(_)) => {},
+                (_, found) => {
+                    found?;
+                },
+            },
+            (Some(kind), Some(id)) if change.operation == "create" => {
+                self.namespace.check_reserve(namespace, kind, id, change.usage.unwrap_or_default()).await?;
+            },
+            (Some(kind), Some(id)) => {
+                self.require_in_namespace(namespace, kind, id, kind).await?;
+                // Workloads that predate namespaces are in `default` without a record, so ask their driver
+                if kind == services::namespace::WORKLOAD {
+                    self.placement.compute_for(id).await?.get_workload_status(id).await?;
+                }
+                if let Some(usage) = change.usage {
+                    self.namespace.check_resize(kind, id, usage).await?;
+                }
+            },
+            _ => {},
+        }
+
+        let (action, resource) = services::iam::request_action(&req.method, &path);
+        let mut report = json!({
+            "dry_run": true,
+            "method": req.method,
+            "path": req.path,
+            "principal": services::audit::principal(&req),
+            "action": action,
+            "resource": resource,
+            "namespace": namespace,
+            "change": change,
+        });
+        if change.usage.is_some() {
+            let current = self.namespace.get(namespace).await?;
+            report["quota"] = json!({ "limit": current.quota, "used": current.used });
+        }
+        Ok(ZeroResponse::json(report))
+    }
+
+    async fn dispatch(&self, mut req: ZeroRequest) -> ZeroResult<ZeroResponse> {
+        let body = std::mem::take(&mut req.body);
+        // Object data streams between the client and the volume. Object keys may contain `?`,
+        // so these routes take the whole path and no query string.
+        let full: Vec<&str> = req.path.split('/').filter(|s| !s.is_empty()).collect();
+        if let (Some(&"v1"), Some(&"store"), Some(["buckets", bucket, "objects", key @ ..])) = (full.first(), full.get(1), full.get(2..)) {
+            if !key.is_empty() {
+                let namespace = services::namespace::requested(&req);
+                return self.route_object(namespace, &req.method, bucket, &key.join("/"), body).await;
+            }
+        }
+
+        let path = req.route_path().to_string();
+        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
         // Path format: ["v1", "service", ...]
         // Example: /v1/store/buckets -> ["v1", "store", "buckets"]
 
         if parts.is_empty() || parts[0] != "v1" {
              return Ok(ZeroResponse::json(json!({ "message": "ZeroCloud API v1" })));
         }
+        // Backups stream in both directions too
+        if parts.get(1) == Some(&"backup") {
+            return self.route_backup(&parts[2..], &req.method, body).await;
+        }
+        req.body = body.collect(MAX_REQUEST_BODY_BYTES).await?.into();
 
         match parts.get(1) {
             Some(&"nodes") | Some(&"stats") | Some(&"workloads") | Some(&"volumes") => {
                 self.route_core(&parts[1..], &req).await
             },
+            Some(&"namespaces") => self.route_namespace(&parts[1..], &req).await,
+            Some(&"images") => self.route_images(&parts[2..], &req).await,
             Some(&"networks") | Some(&"loadbalancers") => {
                 self.route_net(&parts[1..], &req).await
             },
+            Some(&"network") => self.route_net(&parts[2..], &req).await,
             Some(&"store") => self.route_store(&parts[2..], &req).await,
             Some(&"db") => self.route_db(&parts[2..], &req).await,
             Some(&"func") => self.route_func(&parts[2..], &req).await,
             Some(&"queue") => self.route_queue(&parts[2..], &req).await,
             Some(&"iam") => self.route_iam(&parts[2..], &req).await,
             Some(&"eks") => self.route_eks(&parts[2..], &req).await,
+            Some(&"dns") => self.route_dns(&parts[2..], &req).await,
+            Some(&"topics") => self.route_topic(&parts[1..], &req).await,
+            Some(&"scheduler") => self.route_scheduler(&parts[2..], &req).await,
+            Some(&"autoscaling") => self.route_autoscaling(&parts[2..], &req).await,
+            Some(&"audit") => self.route_audit(&parts[2..], &req).await,
+            Some(&"system") => self.route_system(&parts[2..], &req).await,
+            Some(&"schemas") if req.method == "GET" && parts.len() == 2 => {
+                let routes: Vec<_> = schema::ROUTES.iter().map(|route| route.describe()).collect();
+                Ok(ZeroResponse::json(json!({ "routes": routes })))
+            },
+            Some(&"openapi.json") if req.method == "GET" && parts.len() == 2 => {
+                Ok(ZeroResponse::json(openapi::document()))
+            },
             _ => Err(ZeroError::NotFound(format!("Service not found: {:?}", parts.get(1)))),
         }
     }
-}
 
-impl ZeroProvider {
     async fn route_eks(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        let (action, cluster, nodegroup) = match (req.method.as_str(), parts) {
+            ("POST", ["clusters"]) => ("CreateCluster", None, None),
+            ("GET", ["clusters"]) => ("ListClusters", None, None),
+            ("GET", ["clusters", name]) => ("DescribeCluster", Some(*name), None),
+            ("DELETE", ["clusters", name]) => ("DeleteCluster", Some(*name), None),
+            ("POST", ["clusters", name, "node-groups"]) => ("CreateNodegroup", Some(*name), None),
+            ("GET", ["clusters", name, "node-groups"]) => ("ListNodegroups", Some(*name), None),
+            ("GET", ["clusters", name, "node-groups", nodegroup]) => ("DescribeNodegroup", Some(*name), Some(*nodegroup)),
+            ("DELETE", ["clusters", name, "node-groups", nodegroup]) => ("DeleteNodegroup", Some(*name), Some(*nodegroup)),
+            _ => return Err(ZeroError::NotFound(format!("EKS route not found: {:?}", parts))),
+        };
+
+        let mut params = match action {
+            "CreateCluster" => schema::parse_body(req, &schema::CREATE_CLUSTER)?.into_value(),
+            "CreateNodegroup" => schema::parse_body(req, &schema::CREATE_NODEGROUP)?.into_value(),
+            _ => json!({}),
+        };
+        // Path parameters take precedence over the body
+        if let Some(name) = cluster {
+            params["name"] = json!(name);
+        }
+        if let Some(nodegroup) = nodegroup {
+            params["nodegroupName"] = json!(nodegroup);
+        }
+        self.eks.handle_in(services::namespace::requested(req), action, params).await.map(ZeroResponse::json_bytes)
+    }
+
+    async fn route_dns(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("GET", ["zones"]) => {
+                let zones = self.dns.list_zones().await?;
+                Ok(ZeroResponse::json(json!({ "zones": zones })))
+            },
+            ("POST", ["zones"]) => {
+                let body = schema::parse_body(req, &schema::CREATE_ZONE)?;
+                let name = body.str("name");
+                let zone = self.dns.create_zone(name).await?;
+                Ok(ZeroResponse::json(zone))
+            },
+            ("DELETE", ["zones", zone]) => {
+                self.dns.delete_zone(zone).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted" })))
+            },
+            ("GET", ["zones", zone, "records"]) => {
+                let records = self.dns.list_record_sets(zone).await?;
+                Ok(ZeroResponse::json(json!({ "records": records })))
+            },
+            ("PUT", ["zones", zone, "records"]) => {
+                let record_set: services::dns::RecordSet = schema::parse_body(req, &schema::PUT_RECORD_SET)?.into_typed()?;
+                let record_set = self.dns.put_record_set(zone, record_set).await?;
+                Ok(ZeroResponse::json(json!(record_set)))
+            },
+            ("DELETE", ["zones", zone, "records", name, record_type]) => {
+                self.dns.delete_record_set(zone, name, record_type.parse()?).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted" })))
+            },
+            ("GET", ["resolve", name, record_type]) => {
+                let answers = match self.dns.resolve(name, Some(record_type.parse()?)).await? {
+                    services::dns::Resolution::Answers(answers) => answers,
+                    services::dns::Resolution::NoData => Vec::new(),
+                    _ => return Err(ZeroError::NotFound(format!("Domain not found: {}", name))),
+                };
+                let answers: Vec<_> = answers.into_iter().map(|a| json!({
+                    "name": a.name, "type": a.record_type, "ttl": a.ttl, "value": a.value
+                })).collect();
+                Ok(ZeroResponse::json(json!({ "answers": answers })))
+            },
+            _ => Err(ZeroError::NotFound(format!("DNS route not found: {:?}", parts))),
+        }
+    }
+
+    async fn route_topic(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("GET", ["topics"]) => {
+                let topics = self.topic.list_topics().await?;
+                Ok(ZeroResponse::json(json!({ "Topics": topics })))
+            },
+            ("POST", ["topics"]) => {
+                let body = schema::parse_body(req, &schema::CREATE_TOPIC)?;
+                let topic = self.topic.create_topic(body.str("name")).await?;
+                Ok(ZeroResponse::json(json!(topic)))
+            },
+            ("DELETE", ["topics", name]) => {
+                self.topic.delete_topic(name).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "name": name })))
+            },
+            ("GET", ["topics", name, "subscriptions"]) => {
+                let subscriptions = self.topic.list_subscriptions(name).await?;
+                Ok(ZeroResponse::json(json!({ "Subscriptions": subscriptions })))
+            },
+            ("POST", ["topics", name, "subscriptions"]) => {
+                let request = schema::parse_body(req, &schema::SUBSCRIBE)?.into_typed()?;
+                let subscription = self.topic.subscribe(name, request).await?;
+                Ok(ZeroResponse::json(json!(subscription)))
+            },
+            ("DELETE", ["topics", name, "subscriptions", id]) => {
+                self.topic.unsubscribe(name, id).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "id": id })))
+            },
+            ("POST", ["topics", name, "messages"]) => {
+                let request = schema::parse_body(req, &schema::PUBLISH)?.into_typed()?;
+                let result = self.topic.publish(name, request).await?;
+                Ok(ZeroResponse::json(json!(result)))
+            },
+            _ => Err(ZeroError::NotFound("Topic route not found".into()))
+        }
+    }
+
+    async fn route_scheduler(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("GET", ["rules"]) => {
+                let rules = self.scheduler.list_rules().await?;
+                Ok(ZeroResponse::json(json!({ "Rules": rules })))
+            },
+            ("POST", ["rules"]) => {
+                let request = schema::parse_body(req, &schema::CREATE_RULE)?.into_typed()?;
+                let rule = self.scheduler.create_rule(request).await?;
+                Ok(ZeroResponse::json(json!(rule)))
+            },
+            ("GET", ["rules", name]) => {
+                let rule = self.scheduler.get_rule(name).await?;
+                Ok(ZeroResponse::json(json!(rule)))
+            },
+            ("DELETE", ["rules", name]) => {
+                self.scheduler.delete_rule(name).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "name": name })))
+            },
+            _ => Err(ZeroError::NotFound("Scheduler route not found".into()))
+        }
+    }
+
+    async fn route_autoscaling(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, SCALING_GROUP};
+        let namespace = services::namespace::requested(req);
+        match (req.method.as_str(), parts) {
+            ("GET", ["groups"]) => {
+                let assignments = self.namespace.assignments(SCALING_GROUP).await?;
+                let groups: Vec<_> = self.autoscaling.list_groups().await?.into_iter()
+                    .filter(|group| in_namespace(&assignments, &group.name, namespace))
+                    .collect();
+                Ok(ZeroResponse::json(json!({ "Groups": groups })))
+            },
+            ("POST", ["groups"]) => {
+                let request = schema::parse_body(req, &schema::CREATE_SCALING_GROUP)?.into_typed()?;
+                let group = self.autoscaling.create_group_in(namespace, request).await?;
+                Ok(ZeroResponse::json(json!(group)))
+            },
+            ("GET", ["groups", name]) => {
+                self.require_in_namespace(namespace, SCALING_GROUP, name, "Scaling group").await?;
+                let group = self.autoscaling.get_group(name).await?;
+                Ok(ZeroResponse::json(json!(group)))
+            },
+            ("DELETE", ["groups", name]) => {
+                self.require_in_namespace(namespace, SCALING_GROUP, name, "Scaling group").await?;
+                self.autoscaling.delete_group(name).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "name": name })))
+            },
+            _ => Err(ZeroError::NotFound("Autoscaling route not found".into()))
+        }
+    }
+
+    async fn route_audit(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("GET", ["events"]) => {
+                let filter = services::audit::AuditFilter::from_request(req)?;
+                let events = self.audit.events(&filter).await?;
+                Ok(ZeroResponse::json(json!({ "events": events })))
+            },
+            _ => Err(ZeroError::NotFound("Audit route not found".into()))
+        }
+    }
+
+    async fn route_system(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("DELETE", ["state"]) => {
+                let scope = req.query("service").as_deref().unwrap_or("all").parse()?;
+                let summary = self.system.reset(scope).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Reset", "service": scope, "removed": summary })))
+            },
+            _ => Err(ZeroError::NotFound("System route not found".into()))
+        }
+    }
+
+    async fn route_images(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        match (req.method.as_str(), parts) {
+            ("GET", []) => {
+                let images = self.images.list().await?;
+                Ok(ZeroResponse::json(json!({ "images": images })))
+            },
+            ("POST", ["pull"]) => {
+                let body = schema::parse_body(req, &schema::PULL_IMAGE)?;
+                let image = self.images.pull(body.str("reference"), chrono::Utc::now()).await?;
+                Ok(ZeroResponse::json(json!(image)))
+            },
+            ("POST", ["prune"]) => {
+                let body = schema::parse_body(req, &schema::PRUNE_IMAGES)?;
+                let unused_for = std::time::Duration::from_secs(body.int("unused_for_secs") as u64);
+                let report = self.images.prune(unused_for, chrono::Utc::now()).await?;
+                Ok(ZeroResponse::json(json!(report)))
+            },
+            _ => Err(ZeroError::NotFound("Image route not found".into()))
+        }
+    }
+
+    async fn route_namespace(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
         match (req.method.as_str(), parts) {
-            ("POST", ["clusters"]) => self.eks.handle("CreateCluster", &req.body).await.map(ZeroResponse::json_bytes),
-            ("GET", ["clusters", _name]) => self.eks.handle("DescribeCluster", &req.body).await.map(ZeroResponse::json_bytes),
-            ("DELETE", ["clusters", _name]) => self.eks.handle("DeleteCluster", &req.body).await.map(ZeroResponse::json_bytes),
-             // Simple matching for now - AWS URLs are more complex (e.g. /clusters/{name}/nodegroups/{name})
-             // I will add a catch-all or basic pattern for now to satisfy simple calls
-             _ => {
-                 // Try to guess action based on URL structure or just handle generically
-                 // For now, let's just log and fail if not matched
-                 Err(ZeroError::NotFound(format!("EKS route not found: {:?}", parts)))
-             }
+            ("GET", ["namespaces"]) => {
+                let namespaces = self.namespace.list().await?;
+                Ok(ZeroResponse::json(json!({ "namespaces": namespaces })))
+            },
+            ("POST", ["namespaces"]) => {
+                let body = schema::parse_body(req, &schema::CREATE_NAMESPACE)?.into_typed()?;
+                let namespace = self.namespace.create(body).await?;
+                Ok(ZeroResponse::json(json!(namespace)))
+            },
+            ("GET", ["namespaces", name]) => {
+                let namespace = self.namespace.get(name).await?;
+                Ok(ZeroResponse::json(json!(namespace)))
+            },
+            ("PUT", ["namespaces", name, "quota"]) => {
+                let quota = schema::parse_body(req, &schema::UPDATE_QUOTA)?.into_typed()?;
+                let namespace = self.namespace.set_quota(name, quota).await?;
+                Ok(ZeroResponse::json(json!(namespace)))
+            },
+            ("DELETE", ["namespaces", name]) => {
+                self.namespace.delete(name).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "name": name })))
+            },
+            _ => Err(ZeroError::NotFound("Namespace route not found".into()))
         }
     }
 
     async fn route_core(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, Usage, VOLUME, WORKLOAD};
+        let namespace = services::namespace::requested(req);
         match (req.method.as_str(), parts) {
             ("GET", ["nodes"]) => {
                  let nodes = self.engine.list_nodes().map_err(|e| ZeroError::Internal(e.to_string()))?;
                  Ok(ZeroResponse::json(json!({ "nodes": nodes })))
             },
+            ("POST", ["nodes"]) => {
+                let body = schema::parse_body(req, &schema::REGISTER_NODE)?.into_typed::<services::placement::RegisterNodeRequest>()?;
+                let node = self.engine.register_labeled_node(&body.hostname, &body.ip_address, body.labels, body.taints, body.endpoint.as_deref())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                Ok(ZeroResponse::json(json!(node)))
+            },
+            ("PUT", ["nodes", node]) => {
+                let body = schema::parse_body(req, &schema::UPDATE_NODE)?.into_typed::<services::placement::NodeLabels>()?;
+                let node = self.engine.set_node_labels(node, body.labels, body.taints)
+                    .map_err(|e| ZeroError::Internal(e.to_string()))?
+                    .ok_or_else(|| ZeroError::NotFound(format!("Node not found: {}", node)))?;
+                Ok(ZeroResponse::json(json!(node)))
+            },
             ("GET", ["stats"]) => {
                 let stats = self.engine.compute.get_stats().await?;
                 Ok(ZeroResponse::json(json!(stats)))
             },
             ("GET", ["workloads"]) => {
-                let workloads = self.engine.compute.list_workloads().await?;
-                Ok(ZeroResponse::json(json!({ "workloads": workloads })))
+                let mut workloads = self.engine.compute.list_workloads().await?;
+                let placements = self.placement.placements().await?;
+                // A node's driver may also run containers that were not placed there
+                for (node, on_node) in self.placement.node_workloads().await? {
+                    workloads.extend(on_node.into_iter().filter(|workload| placements.get(&workload.id) == Some(&node.hostname)));
+                }
+                let assignments = self.namespace.assignments(WORKLOAD).await?;
+                let restart_counts = self.supervisor.restart_counts()?;
+                let mut listed = Vec::new();
+                for mut workload in workloads.into_iter().filter(|workload| in_namespace(&assignments, &workload.id, namespace)) {
+                    // Ports the network driver forwards are only known to the engine
+                    if workload.ports.is_empty() {
+                        workload.ports = self.engine.published_ports.list(&workload.id)?;
+                    }
+                    workload.restart_count = restart_counts.get(&workload.id).copied().unwrap_or_default();
+                    let node = placements.get(&workload.id).cloned();
+                    let mut workload = json!(workload);
+                    workload["node"] = json!(node);
+                    listed.push(workload);
+                }
+                Ok(ZeroResponse::json(json!({ "workloads": listed })))
             },
             ("POST", ["workloads"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let id = body["id"].as_str().ok_or_else(|| ZeroError::Validation("Missing id".into()))?;
-                let image = body["image"].as_str().ok_or_else(|| ZeroError::Validation("Missing image".into()))?;
-                let cpu = body["cpu"].as_f64().unwrap_or(1.0) as f32;
-                let memory = body["memory_mb"].as_i64().unwrap_or(512) as i32;
-                let status = self.engine.compute.create_workload(id, image, cpu, memory).await?;
-                Ok(ZeroResponse::json(json!(status)))
+                let body = schema::parse_body(req, &schema::CREATE_WORKLOAD)?;
+                let id = body.str("id");
+                let cpu = body.get("cpu").as_f64().unwrap_or_default() as f32;
+                let memory = body.int("memory_mb") as i32;
+                let constraints: services::placement::PlacementConstraints = serde_json::from_value(json!({
+                    "node_selector": body.get("node_selector"),
+                    "affinity": body.get("affinity"),
+                    "tolerations": body.get("tolerations"),
+                })).map_err(|e| ZeroError::Validation(e.to_string()))?;
+                let mounts = self.volume_mounts(namespace, body.get("volumes")).await?;
+                let ports: Vec<PortMapping> = serde_json::from_value(body.get("ports").clone())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                let restart_policy: RestartPolicy = serde_json::from_value(body.get("restart_policy").clone())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                let liveness_probe: Option<LivenessProbe> = serde_json::from_value(body.get("liveness_probe").clone())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                if let Some(probe) = &liveness_probe {
+                    services::supervisor::validate_probe(probe)?;
+                }
+                let usage = Usage { cpu: cpu as f64, memory_mb: memory as i64, volume_gb: 0 };
+                self.namespace.reserve(namespace, WORKLOAD, id, usage).await?;
+                let placed = match self.placement.place(id, &constraints).await {
+                    Ok(Some(_)) if !mounts.is_empty() => {
+                        self.placement.release(id).await?;
+                        self.namespace.release(WORKLOAD, id).await?;
+                        return Err(ZeroError::Validation(format!("Volumes live on this server, so {} cannot mount them on another node", id)));
+                    }
+                    Ok(placed) => placed,
+                    Err(e) => {
+                        self.namespace.release(WORKLOAD, id).await?;
+                        return Err(e);
+                    }
+                };
+                let (node, compute) = match placed {
+                    Some((node, driver)) => (Some(node), driver),
+                    None => (None, self.engine.compute.clone()),
+                };
+                let mut status = match compute.create_workload_with_ports(id, body.str("image"), cpu, memory, &mounts, &ports).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        self.placement.release(id).await?;
+                        self.namespace.release(WORKLOAD, id).await?;
+                        return Err(e);
+                    }
+                };
+                // Drivers that cannot publish ports leave them to this server's network driver
+                if !ports.is_empty() && status.ports.is_empty() {
+                    let published = match node {
+                        Some(_) => Err(ZeroError::Validation(format!("The driver of the node running {} cannot publish ports", id))),
+                        None => self.engine.publish_ports(id, &ports).await,
+                    };
+                    match published {
+                        Ok(published) => status.ports = published,
+                        Err(e) => {
+                            let _ = compute.delete_workload(id).await;
+                            self.placement.release(id).await?;
+                            self.namespace.release(WORKLOAD, id).await?;
+                            return Err(e);
+                        }
+                    }
+                }
+                let spec = services::supervisor::WorkloadSpec { image: body.str("image").to_string(), cpu, memory_mb: memory, mounts, ports };
+                self.supervisor.supervise(id, &spec, restart_policy, liveness_probe.as_ref(), chrono::Utc::now())?;
+                self.rollout.record(id, &spec, chrono::Utc::now())?;
+                // Images on nodes are cached by their own drivers
+                if node.is_none() {
+                    self.images.use_image(id, &spec.image, chrono::Utc::now())?;
+                }
+                let node = node.map(|node| node.hostname);
+                self.engine.events.publish(WORKLOAD_STARTED, id, json!({ "image": body.str("image"), "node": node }));
+                let mut status = json!(status);
+                status["node"] = json!(node);
+                Ok(ZeroResponse::json(status))
+            },
+            ("PUT", ["workloads", id]) => {
+                let body = schema::parse_body(req, &schema::UPDATE_WORKLOAD)?;
+                self.require_in_namespace(namespace, WORKLOAD, id, "Workload").await?;
+                self.placement.compute_for(id).await?.get_workload_status(id).await?;
+                let health_timeout = std::time::Duration::from_secs(body.int("health_timeout_secs") as u64);
+                let rollout = self.rollout.update(id, body.str("image"), health_timeout).await?;
+                Ok(ZeroResponse::json(json!(rollout)))
+            },
+            ("GET", ["workloads", id, "revisions"]) => {
+                self.require_in_namespace(namespace, WORKLOAD, id, "Workload").await?;
+                self.placement.compute_for(id).await?.get_workload_status(id).await?;
+                let revisions = self.rollout.list(id)?;
+                Ok(ZeroResponse::json(json!({ "id": id, "revisions": revisions })))
+            },
+            ("GET", ["workloads", id, "metrics"]) => {
+                self.require_in_namespace(namespace, WORKLOAD, id, "Workload").await?;
+                self.placement.compute_for(id).await?.get_workload_status(id).await?;
+                let period = match req.query("period") {
+                    Some(period) => period.parse().map_err(|_| ZeroError::Validation(format!("Invalid metrics period: {}", period)))?,
+                    None => services::workload_metrics::DEFAULT_PERIOD_SECS,
+                };
+                let datapoints = self.workload_metrics.datapoints(id, period)?;
+                Ok(ZeroResponse::json(json!({ "id": id, "period": period, "datapoints": datapoints })))
             },
             ("DELETE", ["workloads"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let id = body["id"].as_str().ok_or_else(|| ZeroError::Validation("Missing id".into()))?;
-                self.engine.compute.delete_workload(id).await?;
+                let body = schema::parse_body(req, &schema::DELETE_WORKLOAD)?;
+                let id = body.str("id");
+                if !self.namespace.contains(namespace, WORKLOAD, id).await? {
+                    return Err(ZeroError::NotFound(format!("Workload {} not found in namespace {}", id, namespace)));
+                }
+                // Or the supervisor could bring it back before it is released
+                self.supervisor.forget(id)?;
+                self.placement.compute_for(id).await?.delete_workload(id).await?;
+                self.rollout.forget(id)?;
+                self.placement.release(id).await?;
+                self.namespace.release(WORKLOAD, id).await?;
+                self.engine.unpublish_ports(id).await?;
+                self.engine.ipam.release_all(id)?;
+                self.engine.metrics.forget(id);
+                self.images.release(id, chrono::Utc::now())?;
+                self.engine.events.publish(WORKLOAD_STOPPED, id, json!({}));
                 Ok(ZeroResponse::json(json!({ "status": "Deleted", "id": id })))
             },
             ("GET", ["volumes"]) => {
                 let volumes = self.engine.storage.list_volumes().await?;
+                let assignments = self.namespace.assignments(VOLUME).await?;
+                let volumes: Vec<_> = volumes.into_iter()
+                    .filter(|volume| in_namespace(&assignments, &volume.id, namespace))
+                    .collect();
                 Ok(ZeroResponse::json(json!({ "volumes": volumes })))
             },
             ("POST", ["volumes"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let id = body["id"].as_str().ok_or_else(|| ZeroError::Validation("Missing id".into()))?;
-                let size = body["size_gb"].as_i64().unwrap_or(10) as i32;
-                let status = self.engine.storage.create_volume(id, size).await?;
+                let body = schema::parse_body(req, &schema::CREATE_VOLUME)?;
+                let id = body.str("id");
+                let size_gb = body.int("size_gb");
+                self.namespace.reserve(namespace, VOLUME, id, Usage { volume_gb: size_gb, ..Usage::default() }).await?;
+                let status = match self.engine.storage.create_volume(id, size_gb as i32).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        self.namespace.release(VOLUME, id).await?;
+                        return Err(e);
+                    }
+                };
+                self.engine.events.publish(VOLUME_CREATED, id, json!({ "size_gb": size_gb }));
+                Ok(ZeroResponse::json(json!(status)))
+            },
+            ("PUT", ["volumes", id]) => {
+                let body = schema::parse_body(req, &schema::RESIZE_VOLUME)?;
+                self.require_in_namespace(namespace, VOLUME, id, "Volume").await?;
+                let size_gb = body.int("size_gb");
+                let volume = self.engine.storage.list_volumes().await?.into_iter().find(|volume| volume.id == *id)
+                    .ok_or_else(|| ZeroError::NotFound(format!("Volume not found: {}", id)))?;
+                let held = Usage { volume_gb: volume.size_gb.unwrap_or_default() as i64, ..Usage::default() };
+                self.namespace.resize(VOLUME, id, Usage { volume_gb: size_gb, ..Usage::default() }).await?;
+                let status = match self.engine.storage.resize_volume(id, size_gb as i32).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        self.namespace.resize(VOLUME, id, held).await?;
+                        return Err(e);
+                    }
+                };
                 Ok(ZeroResponse::json(json!(status)))
             },
             _ => Err(ZeroError::NotFound("Core route not found".into()))
         }
     }
 
+    /// Record a new resource in a namespace while `create` makes it, forgetting it again
+    /// when creation fails
+    async fn create_in_namespace<T>(&self, namespace: &str, kind: &str, id: &str, create: impl std::future::Future<Output = ZeroResult<T>>) -> ZeroResult<T> {
+        self.namespace.reserve(namespace, kind, id, services::namespace::Usage::default()).await?;
+        match create.await {
+            Ok(created) => Ok(created),
+            Err(e) => {
+                self.namespace.release(kind, id).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Resources of other namespaces are reported as missing
+    async fn require_in_namespace(&self, namespace: &str, kind: &str, id: &str, label: &str) -> ZeroResult<()> {
+        if self.namespace.contains(namespace, kind, id).await? {
+            Ok(())
+        } else {
+            Err(ZeroError::NotFound(format!("{} {} not found in namespace {}", label, id, namespace)))
+        }
+    }
+
+    /// Resolve the volumes requested for a workload to their host paths; workloads only
+    /// mount volumes of their own namespace
+    async fn volume_mounts(&self, namespace: &str, volumes: &serde_json::Value) -> ZeroResult<Vec<VolumeMount>> {
+        let Some(volumes) = volumes.as_array() else {
+            return Ok(Vec::new());
+        };
+        let existing = self.engine.storage.list_volumes().await?;
+        let mut mounts: Vec<VolumeMount> = Vec::new();
+        for volume in volumes {
+            let volume_id = volume["volume_id"].as_str().unwrap_or_default();
+            let target = volume["target"].as_str().unwrap_or_default();
+            let status = existing.iter().find(|v| v.id == volume_id)
+                .ok_or_else(|| ZeroError::NotFound(format!("Volume not found: {}", volume_id)))?;
+            if !self.namespace.contains(namespace, services::namespace::VOLUME, volume_id).await? {
+                return Err(ZeroError::NotFound(format!("Volume {} not found in namespace {}", volume_id, namespace)));
+            }
+            if mounts.iter().any(|m| m.target == target) {
+                return Err(ZeroError::Validation(format!("More than one volume is mounted at {}", target)));
+            }
+            mounts.push(VolumeMount {
+                volume_id: volume_id.to_string(),
+                source: status.path.clone(),
+                backing_file: self.engine.storage.backing_file(volume_id).await?,
+                target: target.to_string(),
+                read_only: volume["read_only"].as_bool().unwrap_or_default(),
+            });
+        }
+        Ok(mounts)
+    }
+
     async fn route_net(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
         match (req.method.as_str(), parts) {
             ("GET", ["networks"]) => {
@@ -128,45 +848,79 @@ impl ZeroProvider {
                 Ok(ZeroResponse::json(json!({ "networks": networks })))
             },
             ("POST", ["networks"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let id = body["id"].as_str().ok_or_else(|| ZeroError::Validation("Missing id".into()))?;
-                let cidr = body["cidr"].as_str().unwrap_or("10.0.0.0/24");
-                let status = self.engine.network.create_network(id, cidr).await?;
+                let body = schema::parse_body(req, &schema::CREATE_NETWORK)?;
+                let status = self.engine.create_network(body.str("id"), body.str("cidr")).await?;
                 Ok(ZeroResponse::json(json!(status)))
             },
+            ("GET", ["networks", id]) => {
+                Ok(ZeroResponse::json(json!(self.engine.ipam.addresses(id)?)))
+            },
+            ("DELETE", ["networks", id]) => {
+                self.engine.delete_network(id).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "id": id })))
+            },
+            ("POST", ["networks", id, "workloads"]) => {
+                let body = schema::parse_body(req, &schema::CONNECT_WORKLOAD)?;
+                let ip = self.engine.connect_workload(body.str("workload_id"), id, body.opt_str("ip")).await?;
+                Ok(ZeroResponse::json(json!({ "network_id": id, "workload_id": body.str("workload_id"), "ip": ip })))
+            },
+            ("GET", ["networks", id, "security-groups"]) => {
+                self.engine.ipam.subnet(id)?;
+                Ok(ZeroResponse::json(json!({ "security_groups": self.engine.security_groups.list(id)? })))
+            },
+            ("POST", ["networks", id, "security-groups"]) => {
+                let body = schema::parse_body(req, &schema::CREATE_SECURITY_GROUP)?;
+                let rules = security_rules(&body)?;
+                Ok(ZeroResponse::json(json!(self.engine.create_security_group(id, body.str("name"), &rules).await?)))
+            },
+            ("PUT", ["networks", id, "security-groups", group_id]) => {
+                let body = schema::parse_body(req, &schema::UPDATE_SECURITY_GROUP)?;
+                let rules = security_rules(&body)?;
+                Ok(ZeroResponse::json(json!(self.engine.update_security_group(id, group_id, &rules).await?)))
+            },
+            ("DELETE", ["networks", id, "security-groups", group_id]) => {
+                self.engine.delete_security_group(id, group_id).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "id": group_id })))
+            },
             ("GET", ["loadbalancers"]) => {
                 let lbs = self.lb.list_load_balancers().await?;
                 Ok(ZeroResponse::json(json!({ "LoadBalancers": lbs })))
             },
             ("POST", ["loadbalancers"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                let lb_type = body["type"].as_str().unwrap_or("application");
-                let status = self.lb.create_load_balancer(name, lb_type).await?;
+                let body = schema::parse_body(req, &schema::CREATE_LOAD_BALANCER)?;
+                let status = self.lb.create_load_balancer(body.str("name"), body.str("type")).await?;
                 Ok(ZeroResponse::json(status))
             },
             ("POST", ["targetgroups"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                let port = body["port"].as_i64().unwrap_or(80) as i32;
-                let protocol = body["protocol"].as_str().unwrap_or("HTTP");
-                let arn = self.lb.create_target_group(name, port, protocol).await?;
+                let body = schema::parse_body(req, &schema::CREATE_TARGET_GROUP)?;
+                let health_check = match body.get("health_check") {
+                    serde_json::Value::Null => services::lb::HealthCheckOptions::default(),
+                    options => serde_json::from_value(options.clone()).map_err(|e| ZeroError::Validation(format!("Invalid health check: {}", e)))?,
+                };
+                let arn = self.lb.create_target_group_with_options(body.str("name"), body.int("port") as i32, body.str("protocol"), health_check).await?;
                 Ok(ZeroResponse::json(json!({ "TargetGroupArn": arn })))
             },
-            ("POST", ["targetgroups", arn, "targets"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let target_id = body["id"].as_str().ok_or_else(|| ZeroError::Validation("Missing target id".into()))?;
-                let port = body["port"].as_i64().unwrap_or(80) as i32;
-                self.lb.register_targets(arn, target_id, port).await?;
+            // Target group ARNs contain '/', so they span several path segments
+            ("POST", ["targetgroups", arn @ .., "targets"]) if !arn.is_empty() => {
+                let body = schema::parse_body(req, &schema::REGISTER_TARGET)?;
+                self.lb.register_targets(&arn.join("/"), body.str("id"), body.int("port") as i32).await?;
                 Ok(ZeroResponse::json(json!({ "status": "Registered" })))
             },
+            ("GET", ["targetgroups", arn @ .., "health"]) if !arn.is_empty() => {
+                let arn = arn.join("/");
+                let health_check = self.lb.get_health_check(&arn).await?;
+                let targets = self.lb.describe_target_health(&arn).await?;
+                Ok(ZeroResponse::json(json!({
+                    "TargetGroupArn": arn,
+                    "HealthCheck": health_check,
+                    "TargetHealthDescriptions": targets
+                })))
+            },
             ("POST", ["listeners"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let lb_name = body["load_balancer_name"].as_str().ok_or_else(|| ZeroError::Validation("Missing lb name".into()))?;
-                let port = body["port"].as_i64().ok_or_else(|| ZeroError::Validation("Missing port".into()))? as i32;
-                let protocol = body["protocol"].as_str().unwrap_or("HTTP");
-                let tg_arn = body["target_group_arn"].as_str().ok_or_else(|| ZeroError::Validation("Missing target group arn".into()))?;
-                let arn = self.lb.create_listener(lb_name, port, protocol, tg_arn).await?;
+                let body = schema::parse_body(req, &schema::CREATE_LISTENER)?;
+                let arn = self.lb.create_listener(
+                    body.str("load_balancer_name"), body.int("port") as i32, body.str("protocol"), body.str("target_group_arn"),
+                ).await?;
                 Ok(ZeroResponse::json(json!({ "ListenerArn": arn })))
             },
             _ => Err(ZeroError::NotFound("Network route not found".into()))
@@ -174,93 +928,308 @@ impl ZeroProvider {
     }
 
     async fn route_store(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, BUCKET};
+        let namespace = services::namespace::requested(req);
         match (req.method.as_str(), parts) {
             ("GET", ["buckets"]) => {
-                let buckets = self.store.list_buckets().await?;
+                let assignments = self.namespace.assignments(BUCKET).await?;
+                let buckets: Vec<_> = self.store.list_buckets().await?.into_iter()
+                    .filter(|bucket| in_namespace(&assignments, bucket, namespace))
+                    .collect();
                 Ok(ZeroResponse::json(json!({ "buckets": buckets })))
             },
             ("POST", ["buckets"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                self.store.create_bucket(name).await?;
+                let body = schema::parse_body(req, &schema::CREATE_BUCKET)?;
+                let name = body.str("name");
+                self.create_in_namespace(namespace, BUCKET, name, self.store.create_bucket(name)).await?;
                 Ok(ZeroResponse::json(json!({ "status": "Created", "name": name })))
             },
+            ("GET", ["buckets", bucket, "objects"]) => {
+                self.require_in_namespace(namespace, BUCKET, bucket, "Bucket").await?;
+                let objects = self.store.list_objects(bucket).await?;
+                Ok(ZeroResponse::json(json!({ "objects": objects })))
+            },
             _ => Err(ZeroError::NotFound("Store route not found".into()))
         }
     }
 
+    async fn route_object(&self, namespace: &str, method: &str, bucket: &str, key: &str, body: ZeroBody) -> ZeroResult<ZeroResponse> {
+        self.require_in_namespace(namespace, services::namespace::BUCKET, bucket, "Bucket").await?;
+        match method {
+            "PUT" => {
+                let object = self.store.put_object(bucket, key, body.into_stream()).await?;
+                Ok(ZeroResponse::json(json!(object)))
+            },
+            "GET" => {
+                let (object, data) = self.store.get_object(bucket, key).await?;
+                let mut resp = ZeroResponse::stream("application/octet-stream", data);
+                resp.headers.insert("Content-Length".to_string(), object.size.to_string());
+                Ok(resp)
+            },
+            "DELETE" => {
+                self.store.delete_object(bucket, key).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "key": key })))
+            },
+            _ => Err(ZeroError::NotFound("Store route not found".into()))
+        }
+    }
+
+    async fn route_backup(&self, parts: &[&str], method: &str, body: ZeroBody) -> ZeroResult<ZeroResponse> {
+        match (method, parts) {
+            ("GET", []) => {
+                let (reader, writer) = tokio::io::duplex(64 * 1024);
+                let backup = self.backup.clone();
+                tokio::spawn(async move {
+                    // The response has already started, so a failure can only cut the archive short
+                    if let Err(e) = backup.create_backup(tokio_util::io::SyncIoBridge::new(writer)).await {
+                        tracing::error!("Backup failed: {}", e);
+                    }
+                });
+                let mut resp = ZeroResponse::stream("application/gzip", tokio_util::io::ReaderStream::new(reader));
+                resp.headers.insert("Content-Disposition".to_string(), "attachment; filename=\"zerocloud-backup.tar.gz\"".to_string());
+                Ok(resp)
+            },
+            ("POST", ["restore"]) => {
+                let reader = tokio_util::io::SyncIoBridge::new(tokio_util::io::StreamReader::new(body.into_stream()));
+                let summary = self.backup.restore_backup(reader).await?;
+                // Load balancer listeners live in the data plane, so bring them in line with the restored database
+                if let Err(e) = self.lb.sync_data_plane().await {
+                    tracing::warn!("Load balancer sync after restore failed: {}", e);
+                }
+                // Background work recorded in the restored database picks up where the backup left it
+                if let Err(e) = self.event_source.resume_pollers().await {
+                    tracing::warn!("Resuming event source mappings after restore failed: {}", e);
+                }
+                if let Err(e) = self.func.resume_invocations().await {
+                    tracing::warn!("Resuming asynchronous invocations after restore failed: {}", e);
+                }
+                Ok(ZeroResponse::json(json!(summary)))
+            },
+            _ => Err(ZeroError::NotFound("Backup route not found".into()))
+        }
+    }
+
     async fn route_db(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, TABLE};
+        let namespace = services::namespace::requested(req);
+        if let ["tables", table_name, ..] = parts {
+            self.require_in_namespace(namespace, TABLE, table_name, "Table").await?;
+        }
         match (req.method.as_str(), parts) {
             ("GET", ["tables"]) => {
-                let tables = self.db.list_tables().await?;
+                let assignments = self.namespace.assignments(TABLE).await?;
+                let tables: Vec<_> = self.db.list_tables().await?.into_iter()
+                    .filter(|table| in_namespace(&assignments, table, namespace))
+                    .collect();
                 Ok(ZeroResponse::json(json!({ "tables": tables })))
             },
             ("POST", ["tables"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                let pk = body["pk"].as_str().unwrap_or("id");
-                self.db.create_table(name, pk).await?;
+                let body = schema::parse_body(req, &schema::CREATE_TABLE)?;
+                let name = body.str("name");
+                self.create_in_namespace(namespace, TABLE, name, self.db.create_table(name, body.str("pk"))).await?;
                 Ok(ZeroResponse::json(json!({ "status": "Created", "name": name })))
             },
             ("POST", ["tables", table_name, "items"]) => {
-                 let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                 let pk_value = body["pk"].as_str().ok_or_else(|| ZeroError::Validation("Missing pk value".into()))?.to_string();
-                 self.db.put_item(table_name, &pk_value, body).await?;
+                 let body = schema::parse_body(req, &schema::PUT_ITEM)?;
+                 let pk_value = body.str("pk").to_string();
+                 self.db.put_item(table_name, &pk_value, body.into_value()).await?;
                  Ok(ZeroResponse::json(json!({ "status": "ItemPut", "table": table_name })))
             },
+            ("GET", ["tables", table_name, "ttl"]) => {
+                let spec = self.db.describe_ttl(table_name).await?;
+                Ok(ZeroResponse::json(json!({ "table": table_name, "ttl": spec })))
+            },
+            ("PUT", ["tables", table_name, "ttl"]) => {
+                let body = schema::parse_body(req, &schema::UPDATE_TTL)?;
+                let enabled = body.get("enabled").as_bool().unwrap_or(true);
+                // Disabling without an attribute keeps the previously configured one
+                let attribute = match body.opt_str("attribute") {
+                    Some(a) => a.to_string(),
+                    None => self.db.describe_ttl(table_name).await?
+                        .map(|s| s.attribute)
+                        .unwrap_or_else(|| "ttl".to_string()),
+                };
+                let spec = self.db.update_ttl(table_name, &attribute, enabled).await?;
+                Ok(ZeroResponse::json(json!({ "status": "TtlUpdated", "ttl": spec })))
+            },
             _ => Err(ZeroError::NotFound("DB route not found".into()))
         }
     }
 
     async fn route_func(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, FUNCTION};
+        let namespace = services::namespace::requested(req);
+        if let ["functions", name, ..] = parts {
+            self.require_in_namespace(namespace, FUNCTION, name, "Function").await?;
+        }
         match (req.method.as_str(), parts) {
             ("GET", ["functions"]) => {
-                let funcs = self.func.list_functions().await?;
-                Ok(ZeroResponse::json(json!({ "functions": funcs })))
+                let assignments = self.namespace.assignments(FUNCTION).await?;
+                let funcs: Vec<_> = self.func.list_functions().await?.into_iter()
+                    .filter(|function| in_namespace(&assignments, function, namespace))
+                    .collect();
+                let configurations: Vec<_> = self.func.list_function_configurations().await?.into_iter()
+                    .filter(|config| in_namespace(&assignments, &config.name, namespace))
+                    .collect();
+                Ok(ZeroResponse::json(json!({ "functions": funcs, "configurations": configurations })))
+            },
+            ("GET", ["functions", name]) => {
+                let config = self.func.get_function(name).await?;
+                Ok(ZeroResponse::json(json!(config)))
             },
             ("POST", ["functions"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                let handler = body["handler"].as_str().unwrap_or("index.handler");
-                let code = body["code"].as_str().unwrap_or(""); 
-                self.func.create_function(name, handler, code).await?;
-                Ok(ZeroResponse::json(json!({ "status": "Created", "name": name })))
+                let body = schema::parse_body(req, &schema::CREATE_FUNCTION)?;
+                let name = body.str("name");
+                let runtime: services::func_runtime::Runtime = body.str("runtime").parse()?;
+                let environment = match body.get("environment") {
+                    serde_json::Value::Null => Default::default(),
+                    env => serde_json::from_value(env.clone()).map_err(|e| ZeroError::Validation(format!("Invalid environment: {}", e)))?,
+                };
+                let options = services::func::FunctionOptions {
+                    runtime,
+                    environment,
+                    timeout_secs: body.opt_int("timeout_secs").map(|t| t as u32),
+                    memory_mb: body.opt_int("memory_mb").map(|m| m as u32),
+                };
+                self.create_in_namespace(namespace, FUNCTION, name, self.func.create_function_with_options(name, body.str("handler"), body.str("code"), options)).await?;
+                let config = self.func.get_function(name).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Created", "name": name, "runtime": runtime.as_str(), "configuration": config })))
             },
             ("POST", ["functions", name, "invocations"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).unwrap_or(json!({}));
-                let result = self.func.invoke_function(name, body).await?;
-                Ok(ZeroResponse::json(result))
+                let body: serde_json::Value = serde_json::from_slice(req.body.as_bytes()).unwrap_or(json!({}));
+                let invocation_type: services::func::InvocationType = req.headers.iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(services::func::INVOCATION_TYPE_HEADER))
+                    .map(|(_, v)| v.parse())
+                    .transpose()?
+                    .unwrap_or_default();
+                let result = self.func.invoke_function_with_type(name, body, invocation_type).await?;
+                let mut resp = ZeroResponse::json(result);
+                if invocation_type == services::func::InvocationType::Event {
+                    resp.status = 202;
+                }
+                Ok(resp)
+            },
+            ("GET", ["functions", name, "invocations"]) => {
+                let invocations = self.func.list_invocations(name).await?;
+                Ok(ZeroResponse::json(json!({ "invocations": invocations })))
+            },
+            ("GET", ["functions", name, "invocations", id]) => {
+                let invocation = self.func.get_invocation(name, id).await?;
+                Ok(ZeroResponse::json(json!(invocation)))
+            },
+            ("GET", ["event-source-mappings"]) => {
+                let mappings = self.event_source.list_mappings().await?;
+                Ok(ZeroResponse::json(json!({ "EventSourceMappings": mappings })))
+            },
+            ("POST", ["event-source-mappings"]) => {
+                let request: services::event_source::CreateMappingRequest =
+                    schema::parse_body(req, &schema::CREATE_EVENT_SOURCE_MAPPING)?.into_typed()?;
+                self.require_in_namespace(namespace, FUNCTION, &request.function_name, "Function").await?;
+                self.require_in_namespace(namespace, services::namespace::QUEUE, &request.queue_name, "Queue").await?;
+                let mapping = self.event_source.create_mapping(request).await?;
+                Ok(ZeroResponse::json(json!(mapping)))
+            },
+            ("GET", ["event-source-mappings", id]) => {
+                let mapping = self.event_source.get_mapping(id).await?;
+                Ok(ZeroResponse::json(json!(mapping)))
+            },
+            ("PATCH", ["event-source-mappings", id]) => {
+                let body = schema::parse_body(req, &schema::UPDATE_EVENT_SOURCE_MAPPING)?;
+                let enabled = body.get("enabled").as_bool().unwrap_or_default();
+                let mapping = self.event_source.set_enabled(id, enabled).await?;
+                Ok(ZeroResponse::json(json!(mapping)))
+            },
+            ("DELETE", ["event-source-mappings", id]) => {
+                self.event_source.delete_mapping(id).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted", "id": id })))
             },
             _ => Err(ZeroError::NotFound("Func route not found".into()))
         }
     }
 
     async fn route_queue(&self, parts: &[&str], req: &ZeroRequest) -> ZeroResult<ZeroResponse> {
+        use services::namespace::{in_namespace, QUEUE};
+        let namespace = services::namespace::requested(req);
+        if let ["queues", name, ..] = parts {
+            self.require_in_namespace(namespace, QUEUE, name, "Queue").await?;
+        }
         match (req.method.as_str(), parts) {
             ("GET", ["queues"]) => {
-                let urls = self.queue.list_queues().await?;
+                let assignments = self.namespace.assignments(QUEUE).await?;
+                // Queue URLs end in `/{name}/messages`
+                let urls: Vec<_> = self.queue.list_queues().await?.into_iter()
+                    .filter(|url| {
+                        let name = url.trim_end_matches("/messages").rsplit('/').next().unwrap_or_default();
+                        in_namespace(&assignments, name, namespace)
+                    })
+                    .collect();
                 Ok(ZeroResponse::json(json!({ "QueueUrls": urls })))
             },
             ("POST", ["queues"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let name = body["name"].as_str().ok_or_else(|| ZeroError::Validation("Missing name".into()))?;
-                let url = self.queue.create_queue(name).await?;
+                let body = schema::parse_body(req, &schema::CREATE_QUEUE)?;
+                let name = body.str("name").to_string();
+                let options: services::queue::QueueOptions = body.into_typed()?;
+                let url = self.create_in_namespace(namespace, QUEUE, &name, self.queue.create_queue_with_options(&name, options)).await?;
                 Ok(ZeroResponse::json(json!({ "QueueUrl": url })))
             },
             ("POST", ["queues", name, "messages"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let msg_body = body["body"].as_str().ok_or_else(|| ZeroError::Validation("Missing body".into()))?;
-                let id = self.queue.send_message(name, msg_body).await?;
+                let body = schema::parse_body(req, &schema::SEND_MESSAGE)?;
+                let msg_body = body.str("body").to_string();
+                let options: services::queue::SendOptions = body.into_typed()?;
+                let id = self.queue.send_message_with_options(name, &msg_body, options).await?;
                 Ok(ZeroResponse::json(json!({ "MessageId": id })))
             },
             ("GET", ["queues", name, "messages"]) => {
                 let msg = self.queue.receive_message(name).await?;
                 Ok(ZeroResponse::json(json!({ "Messages": msg })))
             },
+            ("POST", ["queues", name, "receive"]) => {
+                let options: services::queue::ReceiveOptions = schema::parse_body(req, &schema::RECEIVE_MESSAGES)?.into_typed()?;
+                let messages = self.queue.receive_messages(name, options).await?;
+                Ok(ZeroResponse::json(json!({ "Messages": messages })))
+            },
+            ("POST", ["queues", name, "batch", "send"]) => {
+                let body = schema::parse_body(req, &schema::SEND_MESSAGE_BATCH)?;
+                let entries: Vec<services::queue::SendBatchEntry> = serde_json::from_value(body.get("entries").clone())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                let result = self.queue.send_message_batch(name, entries).await?;
+                Ok(ZeroResponse::json(json!(result)))
+            },
+            ("POST", ["queues", name, "batch", "delete"]) => {
+                let body = schema::parse_body(req, &schema::DELETE_MESSAGE_BATCH)?;
+                let entries: Vec<services::queue::DeleteBatchEntry> = serde_json::from_value(body.get("entries").clone())
+                    .map_err(|e| ZeroError::Validation(e.to_string()))?;
+                let result = self.queue.delete_message_batch(name, entries).await?;
+                Ok(ZeroResponse::json(json!(result)))
+            },
             ("DELETE", ["queues", name, "messages", receipt_handle]) => {
                 self.queue.delete_message(name, receipt_handle).await?;
                 Ok(ZeroResponse::json(json!({ "status": "Deleted" })))
             },
+            ("PATCH", ["queues", name, "messages", receipt_handle, "visibility"]) => {
+                let body = schema::parse_body(req, &schema::CHANGE_MESSAGE_VISIBILITY)?;
+                let timeout = body.int("visibility_timeout") as u32;
+                self.queue.change_message_visibility(name, receipt_handle, timeout).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Updated", "VisibilityTimeout": timeout })))
+            },
+            ("GET", ["queues", name, "redrive-policy"]) => {
+                let policy = self.queue.redrive_policy(name).await?;
+                Ok(ZeroResponse::json(json!({ "RedrivePolicy": policy })))
+            },
+            ("PUT", ["queues", name, "redrive-policy"]) => {
+                let policy: services::queue::RedrivePolicy = schema::parse_body(req, &schema::SET_REDRIVE_POLICY)?.into_typed()?;
+                self.queue.set_redrive_policy(name, Some(policy.clone())).await?;
+                Ok(ZeroResponse::json(json!({ "RedrivePolicy": policy })))
+            },
+            ("DELETE", ["queues", name, "redrive-policy"]) => {
+                self.queue.set_redrive_policy(name, None).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Deleted" })))
+            },
+            ("POST", ["queues", name, "redrive"]) => {
+                let body = schema::parse_body(req, &schema::REDRIVE)?;
+                let moved = self.queue.redrive(name, body.opt_str("destination")).await?;
+                Ok(ZeroResponse::json(json!({ "status": "Redriven", "MovedCount": moved })))
+            },
             _ => Err(ZeroError::NotFound("Queue route not found".into()))
         }
     }
@@ -272,14 +1241,14 @@ impl ZeroProvider {
                 Ok(ZeroResponse::json(json!({ "Users": users })))
             },
             ("POST", ["users"]) => {
-                let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-                let username = body["username"].as_str().ok_or_else(|| ZeroError::Validation("Missing username".into()))?;
+                let body = schema::parse_body(req, &schema::CREATE_USER)?;
+                let username = body.str("username");
                 self.iam.create_user(username).await?;
             Ok(ZeroResponse::json(json!({ "User": { "UserName": username } })))
         },
         ("POST", ["users", username, "policy"]) => {
-             let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-             let policy_doc = body["PolicyDocument"].to_string(); 
+             let body = schema::parse_body(req, &schema::ATTACH_USER_POLICY)?;
+             let policy_doc = policy_document(&body);
              self.iam.attach_user_policy(username, &policy_doc).await?;
              Ok(ZeroResponse::json(json!({ "status": "Attached" })))
         },
@@ -288,22 +1257,58 @@ impl ZeroProvider {
              Ok(ZeroResponse::json(json!({ "Roles": roles })))
         },
         ("POST", ["roles"]) => {
-             let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-             let rolename = body["Rolename"].as_str().ok_or_else(|| ZeroError::Validation("Missing Rolename".into()))?;
+             let body = schema::parse_body(req, &schema::CREATE_ROLE)?;
+             let rolename = body.str("Rolename");
              self.iam.create_role(rolename).await?;
              Ok(ZeroResponse::json(json!({ "Role": { "RoleName": rolename } })))
         },
+        ("POST", ["roles", rolename, "policy"]) => {
+             let body = schema::parse_body(req, &schema::ATTACH_ROLE_POLICY)?;
+             let policy_doc = policy_document(&body);
+             self.iam.attach_role_policy(rolename, &policy_doc).await?;
+             Ok(ZeroResponse::json(json!({ "status": "Attached" })))
+        },
+        ("POST", ["roles", rolename, "assume"]) => {
+             let body = schema::parse_body(req, &schema::ASSUME_ROLE)?;
+             let assumed = self.iam.assume_role(rolename, body.str("RoleSessionName"), body.opt_int("DurationSeconds")).await?;
+             Ok(ZeroResponse::json(json!(assumed)))
+        },
         ("GET", ["groups"]) => {
              let groups = self.iam.list_groups().await?;
              Ok(ZeroResponse::json(json!({ "Groups": groups })))
         },
         ("POST", ["groups"]) => {
-             let body: serde_json::Value = serde_json::from_slice(&req.body).map_err(|e| ZeroError::Validation(e.to_string()))?;
-             let groupname = body["Groupname"].as_str().ok_or_else(|| ZeroError::Validation("Missing Groupname".into()))?;
+             let body = schema::parse_body(req, &schema::CREATE_GROUP)?;
+             let groupname = body.str("Groupname");
              self.iam.create_group(groupname).await?;
              Ok(ZeroResponse::json(json!({ "Group": { "GroupName": groupname } })))
+        },
+        ("POST", ["users", username, "access-keys"]) => {
+             let key = self.iam.create_access_key(username).await?;
+             Ok(ZeroResponse::json(json!({ "AccessKey": key })))
+        },
+        ("GET", ["users", username, "access-keys"]) => {
+             let keys = self.iam.list_access_keys(username).await?;
+             Ok(ZeroResponse::json(json!({ "AccessKeys": keys })))
+        },
+        ("DELETE", ["users", username, "access-keys", access_key_id]) => {
+             self.iam.delete_access_key(username, access_key_id).await?;
+             Ok(ZeroResponse::json(json!({ "status": "Deleted" })))
         },
          _ => Err(ZeroError::NotFound("IAM route not found".into()))
         }
     }
 }
+
+/// Policy document of an attach-policy body, sent either as a JSON object or as its serialized string
+fn policy_document(body: &schema::ValidBody) -> String {
+    match body.get("PolicyDocument") {
+        serde_json::Value::String(doc) => doc.clone(),
+        doc => doc.to_string(),
+    }
+}
+
+/// Rules of a security group body
+fn security_rules(body: &schema::ValidBody) -> ZeroResult<Vec<SecurityRule>> {
+    serde_json::from_value(body.get("rules").clone()).map_err(|e| ZeroError::Validation(e.to_string()))
+}
@@ -0,0 +1,289 @@
+//! OpenAPI 3 description of the ZeroCloud API.
+//!
+//! [`OPERATIONS`] lists every route the provider serves. Routes that take a JSON body
+//! reuse their [`BodySchema`], so the document cannot drift from what the routes accept.
+//! The document is served at `GET /v1/openapi.json`.
+
+use crate::schema::{self, BodySchema};
+use serde_json::{json, Map, Value};
+
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+/// Request body of an operation
+pub enum RequestBody {
+    None,
+    /// A JSON body checked against a schema
+    Schema(&'static BodySchema),
+    /// Any JSON document, passed through as is
+    AnyJson,
+    /// Raw bytes, streamed
+    Binary,
+}
+
+/// One method on one route
+pub struct Operation {
+    pub id: &'static str,
+    pub method: &'static str,
+    /// Route path; `{name}` segments are path parameters
+    pub path: &'static str,
+    pub tag: &'static str,
+    pub summary: &'static str,
+    pub body: RequestBody,
+    /// The response is raw bytes rather than JSON
+    pub binary_response: bool,
+}
+
+const fn op(id: &'static str, method: &'static str, path: &'static str, tag: &'static str, summary: &'static str) -> Operation {
+    Operation { id, method, path, tag, summary, body: RequestBody::None, binary_response: false }
+}
+
+/// An operation whose method, path and summary come from its body schema
+const fn validated(id: &'static str, tag: &'static str, schema: &'static BodySchema) -> Operation {
+    Operation {
+        id,
+        method: schema.method,
+        path: schema.path,
+        tag,
+        summary: schema.description,
+        body: RequestBody::Schema(schema),
+        binary_response: false,
+    }
+}
+
+/// Every route of the API
+pub const OPERATIONS: &[Operation] = &[
+    op("ListNodes", "GET", "/v1/nodes", "Compute", "List the nodes of the cluster"),
+    validated("RegisterNode", "Compute", &schema::REGISTER_NODE),
+    validated("UpdateNode", "Compute", &schema::UPDATE_NODE),
+    op("GetStats", "GET", "/v1/stats", "Compute", "Resource usage of the compute driver"),
+    op("ListWorkloads", "GET", "/v1/workloads", "Compute", "List the workloads of the namespace named by X-Zero-Namespace, or default"),
+    validated("CreateWorkload", "Compute", &schema::CREATE_WORKLOAD),
+    op("GetWorkloadMetrics", "GET", "/v1/workloads/{id}/metrics", "Compute", "CPU, memory and network usage of a workload over the last hour, in datapoints of period seconds (default 60)"),
+    validated("UpdateWorkload", "Compute", &schema::UPDATE_WORKLOAD),
+    op("ListWorkloadRevisions", "GET", "/v1/workloads/{id}/revisions", "Compute", "Revisions of a workload, oldest first, with the one it runs marked active"),
+    validated("DeleteWorkload", "Compute", &schema::DELETE_WORKLOAD),
+    op("ListVolumes", "GET", "/v1/volumes", "Compute", "List the block volumes of the namespace named by X-Zero-Namespace, or default"),
+    validated("CreateVolume", "Compute", &schema::CREATE_VOLUME),
+    validated("ResizeVolume", "Compute", &schema::RESIZE_VOLUME),
+    op("ListImages", "GET", "/v1/images", "Compute", "Images in the compute driver's cache, with whether a workload uses them and when they were last used"),
+    validated("PullImage", "Compute", &schema::PULL_IMAGE),
+    validated("PruneImages", "Compute", &schema::PRUNE_IMAGES),
+    op("ListNamespaces", "GET", "/v1/namespaces", "Compute", "List namespaces with their quotas and usage"),
+    validated("CreateNamespace", "Compute", &schema::CREATE_NAMESPACE),
+    op("GetNamespace", "GET", "/v1/namespaces/{name}", "Compute", "Describe a namespace with its quota and usage"),
+    validated("UpdateQuota", "Compute", &schema::UPDATE_QUOTA),
+    op("DeleteNamespace", "DELETE", "/v1/namespaces/{name}", "Compute", "Delete an empty namespace"),
+
+    op("ListNetworks", "GET", "/v1/networks", "Network", "List networks"),
+    validated("CreateNetwork", "Network", &schema::CREATE_NETWORK),
+    op("GetNetwork", "GET", "/v1/networks/{id}", "Network", "CIDR block of a network and the addresses assigned in it"),
+    op("DeleteNetwork", "DELETE", "/v1/networks/{id}", "Network", "Delete a network, freeing its block and addresses"),
+    validated("ConnectWorkload", "Network", &schema::CONNECT_WORKLOAD),
+    op("ListSecurityGroups", "GET", "/v1/networks/{id}/security-groups", "Network", "List the security groups of a network"),
+    validated("CreateSecurityGroup", "Network", &schema::CREATE_SECURITY_GROUP),
+    validated("UpdateSecurityGroup", "Network", &schema::UPDATE_SECURITY_GROUP),
+    op("DeleteSecurityGroup", "DELETE", "/v1/networks/{id}/security-groups/{group_id}", "Network", "Remove a security group from a network"),
+    op("ListLoadBalancers", "GET", "/v1/network/loadbalancers", "Network", "List load balancers"),
+    validated("CreateLoadBalancer", "Network", &schema::CREATE_LOAD_BALANCER),
+    validated("CreateTargetGroup", "Network", &schema::CREATE_TARGET_GROUP),
+    validated("RegisterTarget", "Network", &schema::REGISTER_TARGET),
+    // Target group ARNs contain '/', which is sent unescaped
+    op("DescribeTargetHealth", "GET", "/v1/network/targetgroups/{arn}/health", "Network", "Health check settings and the health of each target"),
+    validated("CreateListener", "Network", &schema::CREATE_LISTENER),
+
+    op("ListBuckets", "GET", "/v1/store/buckets", "Store", "List buckets"),
+    validated("CreateBucket", "Store", &schema::CREATE_BUCKET),
+    op("ListObjects", "GET", "/v1/store/buckets/{bucket}/objects", "Store", "List the objects of a bucket"),
+    Operation { body: RequestBody::Binary, ..op("PutObject", "PUT", "/v1/store/buckets/{bucket}/objects/{key}", "Store", "Upload an object; the body is streamed to disk") },
+    Operation { binary_response: true, ..op("GetObject", "GET", "/v1/store/buckets/{bucket}/objects/{key}", "Store", "Download an object") },
+    op("DeleteObject", "DELETE", "/v1/store/buckets/{bucket}/objects/{key}", "Store", "Delete an object"),
+
+    op("ListTables", "GET", "/v1/db/tables", "DB", "List tables"),
+    validated("CreateTable", "DB", &schema::CREATE_TABLE),
+    validated("PutItem", "DB", &schema::PUT_ITEM),
+    op("DescribeTimeToLive", "GET", "/v1/db/tables/{table}/ttl", "DB", "Item expiry settings of a table"),
+    validated("UpdateTimeToLive", "DB", &schema::UPDATE_TTL),
+
+    op("ListFunctions", "GET", "/v1/func/functions", "Func", "List functions and their configurations"),
+    validated("CreateFunction", "Func", &schema::CREATE_FUNCTION),
+    op("GetFunction", "GET", "/v1/func/functions/{name}", "Func", "Configuration of a function"),
+    Operation { body: RequestBody::AnyJson, ..op("Invoke", "POST", "/v1/func/functions/{name}/invocations", "Func", "Invoke a function with a JSON event; set X-Zero-Invocation-Type: Event to invoke asynchronously") },
+    op("ListInvocations", "GET", "/v1/func/functions/{name}/invocations", "Func", "List the invocations of a function"),
+    op("GetInvocation", "GET", "/v1/func/functions/{name}/invocations/{id}", "Func", "Status and result of an invocation"),
+    op("ListEventSourceMappings", "GET", "/v1/func/event-source-mappings", "Func", "List event source mappings"),
+    validated("CreateEventSourceMapping", "Func", &schema::CREATE_EVENT_SOURCE_MAPPING),
+    op("GetEventSourceMapping", "GET", "/v1/func/event-source-mappings/{id}", "Func", "Describe an event source mapping"),
+    validated("UpdateEventSourceMapping", "Func", &schema::UPDATE_EVENT_SOURCE_MAPPING),
+    op("DeleteEventSourceMapping", "DELETE", "/v1/func/event-source-mappings/{id}", "Func", "Delete an event source mapping"),
+
+    op("ListQueues", "GET", "/v1/queue/queues", "Queue", "List queue URLs"),
+    validated("CreateQueue", "Queue", &schema::CREATE_QUEUE),
+    validated("SendMessage", "Queue", &schema::SEND_MESSAGE),
+    op("ReceiveMessage", "GET", "/v1/queue/queues/{queue}/messages", "Queue", "Receive a single message"),
+    validated("ReceiveMessages", "Queue", &schema::RECEIVE_MESSAGES),
+    validated("SendMessageBatch", "Queue", &schema::SEND_MESSAGE_BATCH),
+    validated("DeleteMessageBatch", "Queue", &schema::DELETE_MESSAGE_BATCH),
+    op("DeleteMessage", "DELETE", "/v1/queue/queues/{queue}/messages/{receipt_handle}", "Queue", "Delete a received message"),
+    validated("ChangeMessageVisibility", "Queue", &schema::CHANGE_MESSAGE_VISIBILITY),
+    op("GetRedrivePolicy", "GET", "/v1/queue/queues/{queue}/redrive-policy", "Queue", "Get the dead-letter queue of a queue"),
+    validated("SetRedrivePolicy", "Queue", &schema::SET_REDRIVE_POLICY),
+    op("DeleteRedrivePolicy", "DELETE", "/v1/queue/queues/{queue}/redrive-policy", "Queue", "Stop dead-lettering the messages of a queue"),
+    validated("Redrive", "Queue", &schema::REDRIVE),
+
+    op("ListTopics", "GET", "/v1/topics", "Topic", "List topics"),
+    validated("CreateTopic", "Topic", &schema::CREATE_TOPIC),
+    op("DeleteTopic", "DELETE", "/v1/topics/{topic}", "Topic", "Delete a topic and its subscriptions"),
+    op("ListSubscriptions", "GET", "/v1/topics/{topic}/subscriptions", "Topic", "List the subscriptions of a topic"),
+    validated("Subscribe", "Topic", &schema::SUBSCRIBE),
+    op("Unsubscribe", "DELETE", "/v1/topics/{topic}/subscriptions/{id}", "Topic", "Delete a subscription"),
+    validated("Publish", "Topic", &schema::PUBLISH),
+
+    op("ListRules", "GET", "/v1/scheduler/rules", "Scheduler", "List scheduled rules"),
+    validated("CreateRule", "Scheduler", &schema::CREATE_RULE),
+    op("DescribeRule", "GET", "/v1/scheduler/rules/{rule}", "Scheduler", "Describe a rule with its next and last runs"),
+    op("DeleteRule", "DELETE", "/v1/scheduler/rules/{rule}", "Scheduler", "Delete a rule"),
+
+    op("ListScalingGroups", "GET", "/v1/autoscaling/groups", "Autoscaling", "List autoscaling groups"),
+    validated("CreateScalingGroup", "Autoscaling", &schema::CREATE_SCALING_GROUP),
+    op("DescribeScalingGroup", "GET", "/v1/autoscaling/groups/{group}", "Autoscaling", "Describe a group with its instances and last metric"),
+    op("DeleteScalingGroup", "DELETE", "/v1/autoscaling/groups/{group}", "Autoscaling", "Delete a group and its instances"),
+
+    Operation { binary_response: true, ..op("CreateBackup", "GET", "/v1/backup", "Backup", "Download a gzipped tar archive of the database and every volume") },
+    Operation { body: RequestBody::Binary, ..op("RestoreBackup", "POST", "/v1/backup/restore", "Backup", "Restore an archive from CreateBackup; the database is replaced") },
+
+    op("ResetSystemState", "DELETE", "/v1/system/state", "System", "Delete the resources of every namespace; the service query parameter is store, db, queue or all, the default, which also stops workloads and removes scaling groups and volumes"),
+
+    op("ListAuditEvents", "GET", "/v1/audit/events", "Audit", "List state-changing requests, oldest first, filtered by the since, until, after and limit query parameters"),
+
+    op("ListUsers", "GET", "/v1/iam/users", "IAM", "List users"),
+    validated("CreateUser", "IAM", &schema::CREATE_USER),
+    validated("AttachUserPolicy", "IAM", &schema::ATTACH_USER_POLICY),
+    op("CreateAccessKey", "POST", "/v1/iam/users/{user}/access-keys", "IAM", "Create an access key; the secret is only returned here"),
+    op("ListAccessKeys", "GET", "/v1/iam/users/{user}/access-keys", "IAM", "List the access keys of a user"),
+    op("DeleteAccessKey", "DELETE", "/v1/iam/users/{user}/access-keys/{access_key_id}", "IAM", "Delete an access key"),
+    op("ListRoles", "GET", "/v1/iam/roles", "IAM", "List roles"),
+    validated("CreateRole", "IAM", &schema::CREATE_ROLE),
+    validated("AttachRolePolicy", "IAM", &schema::ATTACH_ROLE_POLICY),
+    validated("AssumeRole", "IAM", &schema::ASSUME_ROLE),
+    op("ListGroups", "GET", "/v1/iam/groups", "IAM", "List groups"),
+    validated("CreateGroup", "IAM", &schema::CREATE_GROUP),
+
+    op("ListClusters", "GET", "/v1/eks/clusters", "EKS", "List clusters"),
+    validated("CreateCluster", "EKS", &schema::CREATE_CLUSTER),
+    op("DescribeCluster", "GET", "/v1/eks/clusters/{cluster}", "EKS", "Describe a cluster, with a kubeconfig for it"),
+    op("DeleteCluster", "DELETE", "/v1/eks/clusters/{cluster}", "EKS", "Delete a cluster without node groups"),
+    op("ListNodegroups", "GET", "/v1/eks/clusters/{cluster}/node-groups", "EKS", "List the node groups of a cluster"),
+    validated("CreateNodegroup", "EKS", &schema::CREATE_NODEGROUP),
+    op("DescribeNodegroup", "GET", "/v1/eks/clusters/{cluster}/node-groups/{nodegroup}", "EKS", "Describe a node group"),
+    op("DeleteNodegroup", "DELETE", "/v1/eks/clusters/{cluster}/node-groups/{nodegroup}", "EKS", "Delete a node group and its nodes"),
+
+    op("ListZones", "GET", "/v1/dns/zones", "DNS", "List hosted zones"),
+    validated("CreateZone", "DNS", &schema::CREATE_ZONE),
+    op("DeleteZone", "DELETE", "/v1/dns/zones/{zone}", "DNS", "Delete a hosted zone and its records"),
+    op("ListRecordSets", "GET", "/v1/dns/zones/{zone}/records", "DNS", "List the record sets of a zone"),
+    validated("PutRecordSet", "DNS", &schema::PUT_RECORD_SET),
+    op("DeleteRecordSet", "DELETE", "/v1/dns/zones/{zone}/records/{name}/{type}", "DNS", "Delete the record set of a name and type"),
+    op("Resolve", "GET", "/v1/dns/resolve/{name}/{type}", "DNS", "Resolve a name as the embedded resolver would"),
+
+    op("ListSchemas", "GET", "/v1/schemas", "Meta", "JSON Schemas of every request body"),
+    op("GetOpenApi", "GET", "/v1/openapi.json", "Meta", "This document"),
+];
+
+/// The OpenAPI document of the API
+pub fn document() -> Value {
+    let mut paths = Map::new();
+    for operation in OPERATIONS {
+        let item = paths.entry(operation.path).or_insert_with(|| json!({}));
+        item[operation.method.to_ascii_lowercase()] = operation_object(operation);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "ZeroCloud API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Control plane of ZeroCloud. Requests may be signed with AWS Signature Version 4 using an access key from `POST /v1/iam/users/{user}/access-keys`."
+        },
+        "servers": [{ "url": "/" }],
+        "paths": paths,
+        "components": {
+            "schemas": {
+                "FieldError": {
+                    "type": "object",
+                    "required": ["field", "message"],
+                    "properties": {
+                        "field": { "type": "string", "description": "Path of the field in the body; empty for the body itself" },
+                        "message": { "type": "string" }
+                    }
+                },
+                "Error": {
+                    "type": "object",
+                    "required": ["code", "message", "request_id"],
+                    "properties": {
+                        "code": { "type": "string", "description": "Stable, machine-readable error code, e.g. `NotFound`" },
+                        "message": { "type": "string" },
+                        "request_id": { "type": "string" },
+                        "fields": { "type": "array", "items": { "$ref": "#/components/schemas/FieldError" } }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn operation_object(operation: &Operation) -> Value {
+    let parameters: Vec<Value> = operation.path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+        .collect();
+
+    let success = if operation.binary_response {
+        json!({ "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } })
+    } else {
+        json!({ "application/json": { "schema": { "type": "object" } } })
+    };
+    let mut object = json!({
+        "operationId": operation.id,
+        "tags": [operation.tag],
+        "summary": operation.summary,
+        "responses": {
+            "200": { "description": "Success", "content": success },
+            "default": {
+                "description": "Error",
+                "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+            }
+        }
+    });
+    if !parameters.is_empty() {
+        object["parameters"] = Value::Array(parameters);
+    }
+
+    let body = match &operation.body {
+        RequestBody::None => None,
+        RequestBody::Schema(schema) => Some(("application/json", to_openapi_schema(schema.schema()))),
+        RequestBody::AnyJson => Some(("application/json", json!({}))),
+        RequestBody::Binary => Some(("application/octet-stream", json!({ "type": "string", "format": "binary" }))),
+    };
+    if let Some((content_type, schema)) = body {
+        object["requestBody"] = json!({ "required": true, "content": { content_type: { "schema": schema } } });
+    }
+    object
+}
+
+/// OpenAPI 3.0 schemas allow a single `type`; lists of types become `oneOf`
+fn to_openapi_schema(mut schema: Value) -> Value {
+    if let Some(Value::Array(types)) = schema.as_object_mut().and_then(|s| s.remove("type")) {
+        schema["oneOf"] = types.into_iter().map(|t| json!({ "type": t })).collect();
+    }
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        for property in properties.values_mut() {
+            *property = to_openapi_schema(property.take());
+        }
+    }
+    for key in ["items", "additionalProperties"] {
+        if let Some(nested) = schema.get_mut(key).filter(|n| n.is_object()) {
+            *nested = to_openapi_schema(nested.take());
+        }
+    }
+    schema
+}
@@ -0,0 +1,898 @@
+//! JSON Schemas of ZeroCloud request bodies.
+//!
+//! Every route that takes a JSON body declares its schema here. Routes read their body
+//! through [`parse_body`], which checks it against the schema, reports every invalid field
+//! at once and fills in the schema's `default`s, so handlers can read fields without
+//! re-checking them. `GET /v1/schemas` serves [`ROUTES`] for client and API doc generation.
+//!
+//! The validator implements the subset of JSON Schema the schemas use: `type`, `enum`,
+//! `required`, `properties`, `additionalProperties`, `items`, `minimum`, `maximum`,
+//! `minLength`, `maxLength`, `minItems`, `maxItems` and `default`.
+
+use crate::services::{autoscaling, dns, eks, func, lb, queue, rollout, supervisor};
+use zero_control_spi::{FieldError, ZeroError, ZeroRequest, ZeroResult};
+use serde_json::{json, Value};
+
+/// Schema of the JSON body a route takes
+pub struct BodySchema {
+    pub method: &'static str,
+    /// Route path; `{name}` segments are path parameters
+    pub path: &'static str,
+    pub description: &'static str,
+    schema: fn() -> Value,
+}
+
+impl BodySchema {
+    pub fn schema(&self) -> Value {
+        (self.schema)()
+    }
+
+    /// The schema with its route, as served by `GET /v1/schemas`
+    pub fn describe(&self) -> Value {
+        json!({
+            "method": self.method,
+            "path": self.path,
+            "description": self.description,
+            "schema": self.schema()
+        })
+    }
+}
+
+/// A request body that passed validation, with defaults applied
+#[derive(Debug)]
+pub struct ValidBody(Value);
+
+impl ValidBody {
+    /// A string field the schema requires or defaults
+    pub fn str(&self, field: &str) -> &str {
+        self.0[field].as_str().unwrap_or_default()
+    }
+
+    pub fn opt_str(&self, field: &str) -> Option<&str> {
+        self.0[field].as_str()
+    }
+
+    /// An integer field the schema requires or defaults
+    pub fn int(&self, field: &str) -> i64 {
+        self.0[field].as_i64().unwrap_or_default()
+    }
+
+    pub fn opt_int(&self, field: &str) -> Option<i64> {
+        self.0[field].as_i64()
+    }
+
+    pub fn get(&self, field: &str) -> &Value {
+        &self.0[field]
+    }
+
+    /// Deserialize the body into a typed request
+    pub fn into_typed<T: serde::de::DeserializeOwned>(self) -> ZeroResult<T> {
+        serde_json::from_value(self.0).map_err(|e| ZeroError::Validation(e.to_string()))
+    }
+
+    pub fn into_value(self) -> Value {
+        self.0
+    }
+}
+
+/// Parse and validate a request body; an empty or `null` body is validated as `{}`
+pub fn parse_body(req: &ZeroRequest, schema: &BodySchema) -> ZeroResult<ValidBody> {
+    let bytes = req.body.as_bytes();
+    let mut body: Value = if bytes.iter().all(u8::is_ascii_whitespace) {
+        json!({})
+    } else {
+        serde_json::from_slice(bytes).map_err(|e| ZeroError::invalid_fields(vec![FieldError {
+            field: String::new(),
+            message: format!("Body is not valid JSON: {}", e),
+        }]))?
+    };
+    if body.is_null() {
+        body = json!({});
+    }
+
+    let schema = schema.schema();
+    let errors = validate(&schema, &body);
+    if !errors.is_empty() {
+        return Err(ZeroError::invalid_fields(errors));
+    }
+    apply_defaults(&schema, &mut body);
+    Ok(ValidBody(body))
+}
+
+/// Check `value` against `schema`; returns every invalid field
+pub fn validate(schema: &Value, value: &Value) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<FieldError>) {
+    let mut fail = |message: String| errors.push(FieldError { field: path.to_string(), message });
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !types.iter().any(|t| has_type(value, t)) {
+            fail(format!("must be {}", types.iter().map(|t| article(t)).collect::<Vec<_>>().join(" or ")));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            let names = allowed.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+            fail(format!("must be one of {}", names));
+        }
+    }
+
+    match value {
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    fail(format!("must be at least {}", minimum));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    fail(format!("must be at most {}", maximum));
+                }
+            }
+        }
+        Value::String(string) => {
+            let len = string.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    fail(if min == 1 { "must not be empty".to_string() } else { format!("must be at least {} characters", min) });
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    fail(format!("must be at most {} characters", max));
+                }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if len < min {
+                    fail(if min == 1 { "must not be empty".to_string() } else { format!("must have at least {} items", min) });
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if len > max {
+                    fail(format!("must have at most {} items", max));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::Object(fields) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                if fields.get(required).is_none_or(Value::is_null) {
+                    errors.push(FieldError { field: join(path, required), message: "is required".into() });
+                }
+            }
+            for (name, field) in fields {
+                match properties.and_then(|p| p.get(name)) {
+                    // An explicit null is the same as leaving an optional field out
+                    Some(_) if field.is_null() => {}
+                    Some(field_schema) => validate_at(field_schema, field, &join(path, name), errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(FieldError { field: join(path, name), message: "is not a known field".into() });
+                        }
+                        Some(extra) if extra.is_object() => validate_at(extra, field, &join(path, name), errors),
+                        _ => {}
+                    },
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Fill in the `default` of every absent property, recursing into objects
+fn apply_defaults(schema: &Value, value: &mut Value) {
+    let (Some(properties), Some(fields)) = (schema.get("properties").and_then(Value::as_object), value.as_object_mut()) else {
+        return;
+    };
+    for (name, field_schema) in properties {
+        match fields.get_mut(name) {
+            Some(field) if !field.is_null() => apply_defaults(field_schema, field),
+            _ => {
+                if let Some(default) = field_schema.get("default") {
+                    fields.insert(name.clone(), default.clone());
+                }
+            }
+        }
+    }
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn article(json_type: &str) -> String {
+    match json_type {
+        "integer" | "array" | "object" => format!("an {}", json_type),
+        "null" => "null".to_string(),
+        other => format!("a {}", other),
+    }
+}
+
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() { field.to_string() } else { format!("{}.{}", path, field) }
+}
+
+/// A non-empty string
+fn name() -> Value {
+    json!({ "type": "string", "minLength": 1 })
+}
+
+fn port() -> Value {
+    json!({ "type": "integer", "minimum": 1, "maximum": 65535 })
+}
+
+/// Node labels or a node selector
+fn labels() -> Value {
+    json!({ "type": "object", "additionalProperties": name(), "default": {} })
+}
+
+/// Taints or tolerations, as `key=value` or `key`
+fn taints() -> Value {
+    json!({ "type": "array", "items": name(), "default": [] })
+}
+
+fn object(required: &[&str], properties: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+        "additionalProperties": false
+    })
+}
+
+// --- Compute, storage and networking ---
+
+pub const CREATE_WORKLOAD: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/workloads",
+    description: "Create a workload (VM or container); volumes mount at a path in containers and as a disk such as vdb in VMs, ports are published on the host, on a free one for host_port 0, and the restart policy and liveness probe decide when the workload is restarted",
+    schema: || object(&["id", "image"], json!({
+        "id": name(),
+        "image": name(),
+        "cpu": { "type": "number", "minimum": 0.1, "default": 1.0 },
+        "memory_mb": { "type": "integer", "minimum": 1, "default": 512 },
+        "volumes": {
+            "type": "array",
+            "items": object(&["volume_id", "target"], json!({
+                "volume_id": name(),
+                "target": name(),
+                "read_only": { "type": "boolean", "default": false }
+            }))
+        },
+        "ports": {
+            "type": "array",
+            "items": object(&["container_port"], json!({
+                "host_port": { "type": "integer", "minimum": 0, "maximum": 65535, "default": 0 },
+                "container_port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+                "protocol": { "type": "string", "enum": ["tcp", "udp"], "default": "tcp" }
+            })),
+            "default": []
+        },
+        "node_selector": labels(),
+        "affinity": {
+            "type": "array",
+            "items": object(&["key", "operator"], json!({
+                "key": name(),
+                "operator": { "type": "string", "enum": ["In", "NotIn", "Exists", "DoesNotExist"] },
+                "values": { "type": "array", "items": name() }
+            })),
+            "default": []
+        },
+        "tolerations": taints(),
+        "restart_policy": { "type": "string", "enum": ["never", "on-failure", "always"], "default": "never" },
+        "liveness_probe": object(&["port"], json!({
+            "http_path": { "type": "string", "minLength": 1 },
+            "port": port(),
+            "interval_secs": { "type": "integer", "minimum": 1, "maximum": supervisor::MAX_PROBE_INTERVAL_SECS, "default": 10 },
+            "failure_threshold": { "type": "integer", "minimum": 1, "maximum": supervisor::MAX_PROBE_FAILURE_THRESHOLD, "default": 3 }
+        }))
+    })),
+};
+
+pub const DELETE_WORKLOAD: BodySchema = BodySchema {
+    method: "DELETE",
+    path: "/v1/workloads",
+    description: "Delete a workload",
+    schema: || object(&["id"], json!({ "id": name() })),
+};
+
+pub const UPDATE_WORKLOAD: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/workloads/{id}",
+    description: "Roll a workload over to a new image: the new revision must run and pass the liveness probe before it replaces the old one, or the workload stays on its old revision",
+    schema: || object(&["image"], json!({
+        "image": name(),
+        "health_timeout_secs": {
+            "type": "integer",
+            "minimum": 1,
+            "maximum": rollout::MAX_HEALTH_TIMEOUT_SECS,
+            "default": rollout::DEFAULT_HEALTH_TIMEOUT_SECS
+        }
+    })),
+};
+
+pub const REGISTER_NODE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/nodes",
+    description: "Register a node with labels and taints for workload placement",
+    schema: || object(&["hostname", "ip_address"], json!({
+        "hostname": name(),
+        "ip_address": name(),
+        "labels": labels(),
+        "taints": taints(),
+        "endpoint": name()
+    })),
+};
+
+pub const UPDATE_NODE: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/nodes/{node}",
+    description: "Replace the labels and taints of a node, found by ID or hostname",
+    schema: || object(&[], json!({
+        "labels": labels(),
+        "taints": taints()
+    })),
+};
+
+pub const CREATE_VOLUME: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/volumes",
+    description: "Create a block volume",
+    schema: || object(&["id"], json!({
+        "id": name(),
+        "size_gb": { "type": "integer", "minimum": 1, "default": 10 }
+    })),
+};
+
+pub const RESIZE_VOLUME: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/volumes/{id}",
+    description: "Grow a block volume, keeping its data",
+    schema: || object(&["size_gb"], json!({
+        "size_gb": { "type": "integer", "minimum": 1 }
+    })),
+};
+
+pub const PULL_IMAGE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/images/pull",
+    description: "Pull an image into the compute driver's cache; references without a tag get :latest",
+    schema: || object(&["reference"], json!({ "reference": name() })),
+};
+
+pub const PRUNE_IMAGES: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/images/prune",
+    description: "Remove the pulled or used images that no workload uses, keeping those used in the last unused_for_secs",
+    schema: || object(&[], json!({
+        "unused_for_secs": { "type": "integer", "minimum": 0, "default": 0 }
+    })),
+};
+
+fn quota() -> Value {
+    json!({
+        "cpu": { "type": "number", "minimum": 0 },
+        "memory_mb": { "type": "integer", "minimum": 0 },
+        "volume_gb": { "type": "integer", "minimum": 0 },
+        "workloads": { "type": "integer", "minimum": 0 },
+        "queues": { "type": "integer", "minimum": 0 }
+    })
+}
+
+pub const CREATE_NAMESPACE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/namespaces",
+    description: "Create a namespace; cpu, memory_mb and volume_gb cap what its workloads and volumes may hold, workloads and queues how many it may have, and a missing one is unlimited",
+    schema: || {
+        let mut properties = quota();
+        properties["name"] = json!({ "type": "string", "minLength": 1, "maxLength": 63 });
+        object(&["name"], properties)
+    },
+};
+
+pub const UPDATE_QUOTA: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/namespaces/{name}/quota",
+    description: "Replace the quota of a namespace; resources already past it stay, new ones are refused",
+    schema: || object(&[], quota()),
+};
+
+pub const CREATE_NETWORK: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/networks",
+    description: "Create a network; its CIDR block must not overlap the block of another network",
+    schema: || object(&["id"], json!({
+        "id": name(),
+        "cidr": { "type": "string", "default": "10.0.0.0/24" }
+    })),
+};
+
+pub const CONNECT_WORKLOAD: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/networks/{id}/workloads",
+    description: "Connect a workload to a network with a static address, or the lowest free one",
+    schema: || object(&["workload_id"], json!({
+        "workload_id": name(),
+        "ip": name()
+    })),
+};
+
+/// Ingress and egress rules of a security group
+fn security_rules() -> Value {
+    json!({
+        "type": "array",
+        "items": object(&["direction", "protocol", "cidr"], json!({
+            "direction": { "type": "string", "enum": ["ingress", "egress"] },
+            "protocol": { "type": "string", "enum": ["tcp", "udp", "icmp", "all"] },
+            "from_port": port(),
+            "to_port": port(),
+            "cidr": name()
+        })),
+        "default": []
+    })
+}
+
+pub const CREATE_SECURITY_GROUP: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/networks/{id}/security-groups",
+    description: "Add a security group to a network; once a network has groups, only traffic their rules allow reaches or leaves its workloads, and a group without egress rules allows all egress",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "rules": security_rules()
+    })),
+};
+
+pub const UPDATE_SECURITY_GROUP: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/networks/{id}/security-groups/{group_id}",
+    description: "Replace the rules of a security group",
+    schema: || object(&["rules"], json!({ "rules": security_rules() })),
+};
+
+pub const CREATE_LOAD_BALANCER: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/network/loadbalancers",
+    description: "Create a load balancer",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "type": { "type": "string", "default": "application" }
+    })),
+};
+
+pub const CREATE_TARGET_GROUP: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/network/targetgroups",
+    description: "Create a target group with its health check",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "port": { "type": "integer", "minimum": 1, "maximum": 65535, "default": 80 },
+        "protocol": { "type": "string", "default": "HTTP" },
+        "health_check": object(&[], json!({
+            "path": { "type": "string" },
+            "interval_secs": { "type": "integer", "minimum": 1, "maximum": lb::MAX_HEALTH_CHECK_INTERVAL_SECS },
+            "healthy_threshold": { "type": "integer", "minimum": 1, "maximum": lb::MAX_HEALTH_CHECK_THRESHOLD },
+            "unhealthy_threshold": { "type": "integer", "minimum": 1, "maximum": lb::MAX_HEALTH_CHECK_THRESHOLD }
+        }))
+    })),
+};
+
+pub const REGISTER_TARGET: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/network/targetgroups/{arn}/targets",
+    description: "Register a workload or host with a target group",
+    schema: || object(&["id"], json!({
+        "id": name(),
+        "port": { "type": "integer", "minimum": 1, "maximum": 65535, "default": 80 }
+    })),
+};
+
+pub const CREATE_LISTENER: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/network/listeners",
+    description: "Create a listener forwarding a port to a target group",
+    schema: || object(&["load_balancer_name", "port", "target_group_arn"], json!({
+        "load_balancer_name": name(),
+        "port": port(),
+        "protocol": { "type": "string", "default": "HTTP" },
+        "target_group_arn": name()
+    })),
+};
+
+// --- Store and DB ---
+
+pub const CREATE_BUCKET: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/store/buckets",
+    description: "Create a bucket",
+    schema: || object(&["name"], json!({ "name": name() })),
+};
+
+pub const CREATE_TABLE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/db/tables",
+    description: "Create a table",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "pk": { "type": "string", "minLength": 1, "default": "id" }
+    })),
+};
+
+pub const PUT_ITEM: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/db/tables/{table}/items",
+    description: "Put an item; any attributes besides `pk` are stored as given",
+    schema: || json!({
+        "type": "object",
+        "required": ["pk"],
+        "properties": { "pk": name() }
+    }),
+};
+
+pub const UPDATE_TTL: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/db/tables/{table}/ttl",
+    description: "Enable or disable item expiry on an attribute",
+    schema: || object(&[], json!({
+        "enabled": { "type": "boolean", "default": true },
+        "attribute": name()
+    })),
+};
+
+// --- Functions ---
+
+pub const CREATE_FUNCTION: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/func/functions",
+    description: "Create a function",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "handler": { "type": "string", "default": "index.handler" },
+        "code": { "type": "string", "default": "" },
+        "runtime": { "type": "string", "default": "inline" },
+        "environment": { "type": "object", "additionalProperties": { "type": "string" } },
+        "timeout_secs": { "type": "integer", "minimum": 1, "maximum": func::MAX_TIMEOUT_SECS },
+        "memory_mb": { "type": "integer", "minimum": func::MIN_MEMORY_MB, "maximum": func::MAX_MEMORY_MB }
+    })),
+};
+
+pub const CREATE_EVENT_SOURCE_MAPPING: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/func/event-source-mappings",
+    description: "Invoke a function with batches of queue messages",
+    schema: || object(&["function_name", "queue_name"], json!({
+        "function_name": name(),
+        "queue_name": name(),
+        "batch_size": { "type": "integer", "minimum": 1, "maximum": queue::MAX_BATCH_SIZE },
+        "max_retries": { "type": "integer", "minimum": 0 },
+        "dead_letter_queue": name(),
+        "enabled": { "type": "boolean", "default": true }
+    })),
+};
+
+pub const UPDATE_EVENT_SOURCE_MAPPING: BodySchema = BodySchema {
+    method: "PATCH",
+    path: "/v1/func/event-source-mappings/{id}",
+    description: "Enable or disable an event source mapping",
+    schema: || object(&["enabled"], json!({ "enabled": { "type": "boolean" } })),
+};
+
+// --- Queues ---
+
+fn send_options() -> Value {
+    json!({
+        "group_id": name(),
+        "deduplication_id": name(),
+        "delay_seconds": { "type": "integer", "minimum": 0, "maximum": queue::MAX_DELAY_SECS }
+    })
+}
+
+pub const CREATE_QUEUE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues",
+    description: "Create a standard or FIFO queue",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "fifo": { "type": "boolean", "default": false },
+        "content_based_deduplication": { "type": "boolean", "default": false },
+        "visibility_timeout": { "type": "integer", "minimum": 0, "maximum": queue::MAX_VISIBILITY_TIMEOUT_SECS },
+        "delay_seconds": { "type": "integer", "minimum": 0, "maximum": queue::MAX_DELAY_SECS },
+        "redrive_policy": object(&["dead_letter_queue", "max_receive_count"], json!({
+            "dead_letter_queue": name(),
+            "max_receive_count": { "type": "integer", "minimum": 1 }
+        }))
+    })),
+};
+
+pub const SEND_MESSAGE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues/{queue}/messages",
+    description: "Send a message",
+    schema: || {
+        let mut properties = send_options();
+        properties["body"] = json!({ "type": "string" });
+        object(&["body"], properties)
+    },
+};
+
+pub const RECEIVE_MESSAGES: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues/{queue}/receive",
+    description: "Receive up to `max_messages` messages, waiting up to `wait_time_seconds`",
+    schema: || object(&[], json!({
+        "max_messages": { "type": "integer", "minimum": 1, "maximum": queue::MAX_BATCH_SIZE },
+        "wait_time_seconds": { "type": "integer", "minimum": 0, "maximum": queue::MAX_WAIT_TIME_SECS },
+        "visibility_timeout": { "type": "integer", "minimum": 0, "maximum": queue::MAX_VISIBILITY_TIMEOUT_SECS }
+    })),
+};
+
+pub const SEND_MESSAGE_BATCH: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues/{queue}/batch/send",
+    description: "Send up to 10 messages",
+    schema: || {
+        let mut entry = send_options();
+        entry["id"] = name();
+        entry["body"] = json!({ "type": "string" });
+        object(&["entries"], json!({
+            "entries": {
+                "type": "array",
+                "minItems": 1,
+                "maxItems": queue::MAX_BATCH_SIZE,
+                "items": object(&["id", "body"], entry)
+            }
+        }))
+    },
+};
+
+pub const DELETE_MESSAGE_BATCH: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues/{queue}/batch/delete",
+    description: "Delete up to 10 messages by receipt handle",
+    schema: || object(&["entries"], json!({
+        "entries": {
+            "type": "array",
+            "minItems": 1,
+            "maxItems": queue::MAX_BATCH_SIZE,
+            "items": object(&["id", "receipt_handle"], json!({ "id": name(), "receipt_handle": name() }))
+        }
+    })),
+};
+
+pub const CHANGE_MESSAGE_VISIBILITY: BodySchema = BodySchema {
+    method: "PATCH",
+    path: "/v1/queue/queues/{queue}/messages/{receipt_handle}/visibility",
+    description: "Change how long a received message stays hidden",
+    schema: || object(&["visibility_timeout"], json!({
+        "visibility_timeout": { "type": "integer", "minimum": 0, "maximum": queue::MAX_VISIBILITY_TIMEOUT_SECS }
+    })),
+};
+
+pub const SET_REDRIVE_POLICY: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/queue/queues/{queue}/redrive-policy",
+    description: "Set the dead-letter queue of an existing queue",
+    schema: || object(&["dead_letter_queue", "max_receive_count"], json!({
+        "dead_letter_queue": name(),
+        "max_receive_count": { "type": "integer", "minimum": 1 }
+    })),
+};
+
+pub const REDRIVE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/queue/queues/{queue}/redrive",
+    description: "Move a dead-letter queue's messages back to their source queue, or to `destination`",
+    schema: || object(&[], json!({ "destination": name() })),
+};
+
+// --- Topics ---
+
+pub const CREATE_TOPIC: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/topics",
+    description: "Create a topic, or return the existing topic of that name",
+    schema: || object(&["name"], json!({ "name": { "type": "string", "minLength": 1, "maxLength": 256 } })),
+};
+
+pub const SUBSCRIBE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/topics/{topic}/subscriptions",
+    description: "Subscribe a queue or webhook URL, optionally filtering messages by their attributes",
+    schema: || object(&["protocol", "endpoint"], json!({
+        "protocol": { "type": "string", "enum": ["queue", "webhook"] },
+        "endpoint": name(),
+        "filter_policy": {
+            "type": "object",
+            "additionalProperties": { "type": "array", "minItems": 1 }
+        },
+        "raw_message_delivery": { "type": "boolean", "default": false }
+    })),
+};
+
+pub const PUBLISH: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/topics/{topic}/messages",
+    description: "Publish a message to every subscription whose filter policy matches its attributes",
+    schema: || object(&["message"], json!({
+        "message": { "type": "string" },
+        "subject": { "type": "string" },
+        "attributes": { "type": "object", "additionalProperties": { "type": ["string", "number"] } }
+    })),
+};
+
+// --- Scheduler ---
+
+pub const CREATE_RULE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/scheduler/rules",
+    description: "Create a rule that invokes a function, sends a queue message or calls a webhook on a cron or rate schedule",
+    schema: || object(&["name", "schedule", "target_type", "target"], json!({
+        "name": { "type": "string", "minLength": 1, "maxLength": 64 },
+        "schedule": name(),
+        "target_type": { "type": "string", "enum": ["function", "queue", "webhook"] },
+        "target": name(),
+        "input": {}
+    })),
+};
+
+// --- Autoscaling ---
+
+pub const CREATE_SCALING_GROUP: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/autoscaling/groups",
+    description: "Create a group of workloads scaled between min_size and max_size to hold a CPU or queue depth target",
+    schema: || object(&["name", "image", "min_size", "max_size", "metric", "target_value"], json!({
+        "name": { "type": "string", "minLength": 1, "maxLength": 48 },
+        "image": name(),
+        "cpu": { "type": "number", "minimum": 0.1, "default": 1.0 },
+        "memory_mb": { "type": "integer", "minimum": 1, "default": 512 },
+        "min_size": { "type": "integer", "minimum": 0, "maximum": autoscaling::MAX_GROUP_SIZE },
+        "max_size": { "type": "integer", "minimum": 1, "maximum": autoscaling::MAX_GROUP_SIZE },
+        "desired_capacity": { "type": "integer", "minimum": 0, "maximum": autoscaling::MAX_GROUP_SIZE },
+        "metric": { "type": "string", "enum": ["cpu", "queue_depth"] },
+        "target_value": { "type": "number" },
+        "queue": name(),
+        "cooldown_seconds": { "type": "integer", "minimum": 0 }
+    })),
+};
+
+// --- IAM ---
+
+fn policy() -> Value {
+    object(&["PolicyDocument"], json!({
+        "PolicyDocument": { "type": ["object", "string"] }
+    }))
+}
+
+pub const CREATE_USER: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/users",
+    description: "Create a user",
+    schema: || object(&["username"], json!({ "username": name() })),
+};
+
+pub const ATTACH_USER_POLICY: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/users/{user}/policy",
+    description: "Attach a policy document, as an object or its serialized string",
+    schema: policy,
+};
+
+pub const CREATE_ROLE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/roles",
+    description: "Create a role",
+    schema: || object(&["Rolename"], json!({ "Rolename": name() })),
+};
+
+pub const ATTACH_ROLE_POLICY: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/roles/{role}/policy",
+    description: "Attach a policy document, as an object or its serialized string",
+    schema: policy,
+};
+
+pub const ASSUME_ROLE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/roles/{role}/assume",
+    description: "Issue temporary credentials for a role",
+    schema: || object(&["RoleSessionName"], json!({
+        "RoleSessionName": name(),
+        "DurationSeconds": { "type": "integer", "minimum": 1 }
+    })),
+};
+
+pub const CREATE_GROUP: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/iam/groups",
+    description: "Create a group",
+    schema: || object(&["Groupname"], json!({ "Groupname": name() })),
+};
+
+// --- EKS and DNS ---
+
+pub const CREATE_CLUSTER: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/eks/clusters",
+    description: "Create a Kubernetes cluster",
+    schema: || object(&["name"], json!({
+        "name": name(),
+        "version": { "type": "string", "default": eks::DEFAULT_KUBERNETES_VERSION }
+    })),
+};
+
+pub const CREATE_NODEGROUP: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/eks/clusters/{cluster}/node-groups",
+    description: "Create a node group and register its nodes",
+    schema: || {
+        let size = json!({ "type": "integer", "minimum": 0, "maximum": eks::MAX_NODEGROUP_SIZE });
+        object(&["nodegroupName"], json!({
+            "nodegroupName": name(),
+            "scalingConfig": object(&[], json!({ "minSize": size, "maxSize": size, "desiredSize": size })),
+            "instanceTypes": { "type": "array", "minItems": 1, "items": name() }
+        }))
+    },
+};
+
+pub const CREATE_ZONE: BodySchema = BodySchema {
+    method: "POST",
+    path: "/v1/dns/zones",
+    description: "Create a hosted zone",
+    schema: || object(&["name"], json!({ "name": name() })),
+};
+
+pub const PUT_RECORD_SET: BodySchema = BodySchema {
+    method: "PUT",
+    path: "/v1/dns/zones/{zone}/records",
+    description: "Create or replace the record set of a name and type",
+    schema: || object(&["name", "type", "values"], json!({
+        "name": name(),
+        "type": { "type": "string", "enum": ["A", "CNAME", "TXT"] },
+        "ttl": { "type": "integer", "minimum": 0, "maximum": dns::MAX_RECORD_TTL, "default": dns::DEFAULT_RECORD_TTL },
+        "values": { "type": "array", "minItems": 1, "items": { "type": "string" } }
+    })),
+};
+
+/// Every route that takes a JSON body
+pub const ROUTES: &[&BodySchema] = &[
+    &CREATE_WORKLOAD, &UPDATE_WORKLOAD, &DELETE_WORKLOAD, &REGISTER_NODE, &UPDATE_NODE, &CREATE_VOLUME, &RESIZE_VOLUME,
+    &PULL_IMAGE, &PRUNE_IMAGES,
+    &CREATE_NAMESPACE, &UPDATE_QUOTA, &CREATE_NETWORK, &CONNECT_WORKLOAD, &CREATE_SECURITY_GROUP, &UPDATE_SECURITY_GROUP,
+    &CREATE_LOAD_BALANCER, &CREATE_TARGET_GROUP, &REGISTER_TARGET, &CREATE_LISTENER,
+    &CREATE_BUCKET, &CREATE_TABLE, &PUT_ITEM, &UPDATE_TTL,
+    &CREATE_FUNCTION, &CREATE_EVENT_SOURCE_MAPPING, &UPDATE_EVENT_SOURCE_MAPPING,
+    &CREATE_QUEUE, &SEND_MESSAGE, &RECEIVE_MESSAGES, &SEND_MESSAGE_BATCH, &DELETE_MESSAGE_BATCH,
+    &CHANGE_MESSAGE_VISIBILITY, &SET_REDRIVE_POLICY, &REDRIVE,
+    &CREATE_TOPIC, &SUBSCRIBE, &PUBLISH,
+    &CREATE_RULE,
+    &CREATE_SCALING_GROUP,
+    &CREATE_USER, &ATTACH_USER_POLICY, &CREATE_ROLE, &ATTACH_ROLE_POLICY, &ASSUME_ROLE, &CREATE_GROUP,
+    &CREATE_CLUSTER, &CREATE_NODEGROUP, &CREATE_ZONE, &PUT_RECORD_SET,
+];
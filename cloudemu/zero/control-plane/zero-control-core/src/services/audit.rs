@@ -0,0 +1,254 @@
+//! Audit trail of every state-changing API request
+//!
+//! Each request other than `GET`, `HEAD` or `OPTIONS` appends one event to `audit_events`:
+//! who sent it, what it asked for, when, the SHA-256 of its body and how it ended. Triggers
+//! refuse updates and deletes, so the table only ever grows, and a backup restore keeps it.
+
+use zero_control_spi::{ZeroBody, ZeroError, ZeroRequest, ZeroResponse, ZeroResult};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection};
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex, PoisonError};
+use serde::{Serialize, Deserialize};
+
+/// Header carrying the authenticated principal, set by the API server. Requests made
+/// in-process, such as from the CLI, have none and are recorded as [`LOCAL_PRINCIPAL`].
+pub const PRINCIPAL_HEADER: &str = "x-zero-principal";
+
+pub const LOCAL_PRINCIPAL: &str = "local";
+
+/// Query string parameters filtering `GET /v1/audit/events`
+pub const SINCE_PARAM: &str = "since";
+pub const UNTIL_PARAM: &str = "until";
+pub const AFTER_PARAM: &str = "after";
+pub const LIMIT_PARAM: &str = "limit";
+
+pub const DEFAULT_LIMIT: usize = 100;
+pub const MAX_LIMIT: usize = 1000;
+
+/// Whether a request can change state and so is audited
+pub fn is_mutation(method: &str) -> bool {
+    !matches!(method, "GET" | "HEAD" | "OPTIONS")
+}
+
+/// Principal a request was made by
+pub fn principal(req: &ZeroRequest) -> &str {
+    req.header(PRINCIPAL_HEADER).filter(|p| !p.is_empty()).unwrap_or(LOCAL_PRINCIPAL)
+}
+
+/// Wrap a body so its hash is computed as the route reads it; streamed object data is
+/// hashed on its way to the volume rather than buffered
+pub fn digest(body: ZeroBody) -> (ZeroBody, BodyDigest) {
+    match body {
+        ZeroBody::Full(bytes) => {
+            let hash = hex::encode(Sha256::digest(&bytes));
+            (ZeroBody::Full(bytes), BodyDigest(Arc::new(Mutex::new(DigestState::Done(hash)))))
+        }
+        stream => {
+            let state = Arc::new(Mutex::new(DigestState::Reading(Sha256::new())));
+            let reading = state.clone();
+            let finished = state.clone();
+            let stream = stream.into_stream()
+                .map(move |chunk| {
+                    if let (Ok(bytes), DigestState::Reading(hasher)) =
+                        (&chunk, &mut *reading.lock().unwrap_or_else(PoisonError::into_inner))
+                    {
+                        hasher.update(bytes);
+                    }
+                    chunk
+                })
+                .chain(futures::stream::poll_fn(move |_| {
+                    let mut state = finished.lock().unwrap_or_else(PoisonError::into_inner);
+                    if let DigestState::Reading(hasher) = std::mem::replace(&mut *state, DigestState::Unread) {
+                        *state = DigestState::Done(hex::encode(hasher.finalize()));
+                    }
+                    std::task::Poll::Ready(None)
+                }));
+            (ZeroBody::from_stream(stream), BodyDigest(state))
+        }
+    }
+}
+
+enum DigestState {
+    Reading(Sha256),
+    Done(String),
+    Unread,
+}
+
+/// Hash of a request body, known once the body has been read to the end
+pub struct BodyDigest(Arc<Mutex<DigestState>>);
+
+impl BodyDigest {
+    /// Hex SHA-256 of the body, or `None` when the route stopped before reading all of it
+    pub fn finish(&self) -> Option<String> {
+        match &*self.0.lock().unwrap_or_else(PoisonError::into_inner) {
+            DigestState::Done(hash) => Some(hash.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// Position in the log; later events have higher numbers
+    pub seq: i64,
+    pub time: String,
+    pub principal: String,
+    pub method: String,
+    pub path: String,
+    pub body_sha256: Option<String>,
+    /// HTTP status the request ended with
+    pub status: u16,
+    /// Error code of a failed request
+    pub error: Option<String>,
+}
+
+/// Events to return; times are RFC 3339 and both ends are inclusive
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Only events after this sequence number, for following the log
+    pub after: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl AuditFilter {
+    pub fn from_request(req: &ZeroRequest) -> ZeroResult<Self> {
+        Ok(Self {
+            since: req.query(SINCE_PARAM),
+            until: req.query(UNTIL_PARAM),
+            after: number(req, AFTER_PARAM)?,
+            limit: number(req, LIMIT_PARAM)?,
+        })
+    }
+
+    /// The filter as a query string, for requesting events through the API
+    pub fn to_query(&self) -> String {
+        let mut params = Vec::new();
+        params.extend(self.since.as_ref().map(|since| format!("{}={}", SINCE_PARAM, query_escape(since))));
+        params.extend(self.until.as_ref().map(|until| format!("{}={}", UNTIL_PARAM, query_escape(until))));
+        params.extend(self.after.map(|after| format!("{}={}", AFTER_PARAM, after)));
+        params.extend(self.limit.map(|limit| format!("{}={}", LIMIT_PARAM, limit)));
+        params.join("&")
+    }
+}
+
+fn number<T: std::str::FromStr>(req: &ZeroRequest, param: &str) -> ZeroResult<Option<T>> {
+    req.query(param)
+        .map(|value| value.parse().map_err(|_| ZeroError::Validation(format!("{} must be a number: {}", param, value))))
+        .transpose()
+}
+
+/// RFC 3339 times hold `+`, which would otherwise read back as a space
+fn query_escape(value: &str) -> String {
+    value.replace('%', "%25").replace('+', "%2B").replace('&', "%26")
+}
+
+#[derive(Clone)]
+pub struct AuditService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl AuditService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    pub(crate) fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS audit_events (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                time TEXT NOT NULL,
+                principal TEXT NOT NULL,
+                method TEXT NOT NULL,
+                path TEXT NOT NULL,
+                body_sha256 TEXT,
+                status INTEGER NOT NULL,
+                error TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_audit_events_time ON audit_events(time);
+            CREATE TRIGGER IF NOT EXISTS audit_events_no_update BEFORE UPDATE ON audit_events
+            BEGIN SELECT RAISE(ABORT, 'audit events are append-only'); END;
+            CREATE TRIGGER IF NOT EXISTS audit_events_no_delete BEFORE DELETE ON audit_events
+            BEGIN SELECT RAISE(ABORT, 'audit events are append-only'); END;"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Append the outcome of a request
+    pub async fn record(
+        &self,
+        principal: &str,
+        method: &str,
+        path: &str,
+        body_sha256: Option<String>,
+        outcome: &ZeroResult<ZeroResponse>,
+    ) -> ZeroResult<AuditEvent> {
+        let (status, error) = match outcome {
+            Ok(resp) => (resp.status, None),
+            Err(e) => (e.status(), Some(e.code().to_string())),
+        };
+        let time = timestamp(chrono::Utc::now());
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        conn.execute(
+            "INSERT INTO audit_events (time, principal, method, path, body_sha256, status, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![time, principal, method, path, body_sha256, status, error],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(AuditEvent {
+            seq: conn.last_insert_rowid(),
+            time,
+            principal: principal.to_string(),
+            method: method.to_string(),
+            path: path.to_string(),
+            body_sha256,
+            status,
+            error,
+        })
+    }
+
+    /// Events matching `filter`, oldest first
+    pub async fn events(&self, filter: &AuditFilter) -> ZeroResult<Vec<AuditEvent>> {
+        let since = filter.since.as_deref().map(parse_time).transpose()?;
+        let until = filter.until.as_deref().map(parse_time).transpose()?;
+        let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT seq, time, principal, method, path, body_sha256, status, error FROM audit_events
+             WHERE (?1 IS NULL OR time >= ?1) AND (?2 IS NULL OR time <= ?2) AND seq > ?3
+             ORDER BY seq LIMIT ?4"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let events = stmt.query_map(
+            params![since, until, filter.after.unwrap_or(0), limit as i64],
+            |row| Ok(AuditEvent {
+                seq: row.get(0)?,
+                time: row.get(1)?,
+                principal: row.get(2)?,
+                method: row.get(3)?,
+                path: row.get(4)?,
+                body_sha256: row.get(5)?,
+                status: row.get(6)?,
+                error: row.get(7)?,
+            }),
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(events)
+    }
+}
+
+/// Times are stored in one fixed-width UTC form so they compare as text
+fn timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+fn parse_time(value: &str) -> ZeroResult<String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|time| timestamp(time.with_timezone(&chrono::Utc)))
+        .map_err(|_| ZeroError::Validation(format!("Not an RFC 3339 time: {}", value)))
+}
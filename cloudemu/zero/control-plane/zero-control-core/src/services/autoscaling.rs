@@ -0,0 +1,488 @@
+//! Autoscaling groups: identical workloads kept between a minimum and a maximum size by
+//! target tracking
+//!
+//! Every reconcile compares a group's metric with its target and sets the desired capacity to
+//! - `ceil(capacity * cpu / target)` for `cpu` groups, where `cpu` is the average usage of the
+//!   group's instances in percent of the CPUs each was given, as measured by
+//!   `ComputeDriver::workload_cpu_percent`; other workloads on the node do not count
+//! - `ceil(visible messages / target)` for `queue_depth` groups, where the target is the
+//!   backlog one instance should handle
+//!
+//! clamped to the group's bounds. Capacity changes wait out the group's cooldown. Instances
+//! are `<group>-<suffix>` workloads; instances that disappear from the compute driver are
+//! replaced, and scaling in removes the newest first. Each instance reserves the group's CPU
+//! and memory in the namespace the group was created in, so a namespace quota caps how far
+//! its groups scale out.
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::events::{WORKLOAD_STARTED, WORKLOAD_STOPPED};
+use zero_data_core::rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use super::queue::QueueService;
+use super::namespace::{NamespaceService, Usage, DEFAULT_NAMESPACE, SCALING_GROUP, WORKLOAD};
+
+/// How often `ZeroProvider::spawn_autoscaler` reconciles the groups
+pub const AUTOSCALING_TICK: std::time::Duration = std::time::Duration::from_secs(15);
+
+pub const MAX_GROUP_SIZE: u32 = 20;
+
+const DEFAULT_CPU: f32 = 1.0;
+
+const DEFAULT_MEMORY_MB: i32 = 512;
+
+const DEFAULT_COOLDOWN_SECS: u32 = 60;
+
+/// What a group scales on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricType {
+    /// CPU usage of the node in percent
+    Cpu,
+    /// Messages waiting in a queue
+    QueueDepth,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Cpu => "cpu",
+            MetricType::QueueDepth => "queue_depth",
+        }
+    }
+}
+
+impl std::str::FromStr for MetricType {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "cpu" => Ok(MetricType::Cpu),
+            "queue_depth" => Ok(MetricType::QueueDepth),
+            other => Err(ZeroError::Validation(format!("Unknown scaling metric: {}", other))),
+        }
+    }
+}
+
+/// Settings for a new group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateGroupRequest {
+    pub name: String,
+    /// Image every instance runs
+    pub image: String,
+    #[serde(default)]
+    pub cpu: Option<f32>,
+    #[serde(default)]
+    pub memory_mb: Option<i32>,
+    pub min_size: u32,
+    pub max_size: u32,
+    /// Size to start at; defaults to `min_size`
+    #[serde(default)]
+    pub desired_capacity: Option<u32>,
+    pub metric: MetricType,
+    /// CPU percent to hold, or messages per instance for `queue_depth`
+    pub target_value: f64,
+    /// Queue whose depth drives a `queue_depth` group
+    #[serde(default)]
+    pub queue: Option<String>,
+    /// Seconds to wait after a capacity change before the next one (default 60)
+    #[serde(default)]
+    pub cooldown_seconds: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScalingGroup {
+    pub name: String,
+    pub arn: String,
+    pub image: String,
+    pub cpu: f32,
+    pub memory_mb: i32,
+    pub min_size: u32,
+    pub max_size: u32,
+    pub desired_capacity: u32,
+    pub metric: MetricType,
+    pub target_value: f64,
+    pub queue: Option<String>,
+    pub cooldown_seconds: u32,
+    /// Metric value seen by the last reconcile
+    pub last_metric: Option<f64>,
+    pub last_scaling_at: Option<String>,
+    /// Workload IDs of the instances, oldest first
+    pub instances: Vec<String>,
+    pub created_at: String,
+}
+
+#[derive(Clone)]
+pub struct AutoscalingService {
+    engine: Arc<ZeroEngine>,
+    queue: QueueService,
+    namespace: NamespaceService,
+    /// Groups are changed by one caller at a time, so the same demand never launches instances twice
+    scaling: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl AutoscalingService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self {
+            queue: QueueService::new(engine.clone()),
+            namespace: NamespaceService::new(engine.clone()),
+            engine,
+            scaling: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS autoscaling_groups (
+                name TEXT PRIMARY KEY,
+                image TEXT NOT NULL,
+                cpu REAL NOT NULL,
+                memory_mb INTEGER NOT NULL,
+                min_size INTEGER NOT NULL,
+                max_size INTEGER NOT NULL,
+                desired_capacity INTEGER NOT NULL,
+                metric TEXT NOT NULL,
+                target_value REAL NOT NULL,
+                queue TEXT,
+                cooldown_seconds INTEGER NOT NULL,
+                last_metric REAL,
+                last_scaling_at TEXT,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS autoscaling_instances (
+                workload_id TEXT PRIMARY KEY,
+                group_name TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    /// Create a group in the default namespace and launch its starting instances
+    pub async fn create_group(&self, request: CreateGroupRequest) -> ZeroResult<ScalingGroup> {
+        self.create_group_in(DEFAULT_NAMESPACE, request).await
+    }
+
+    /// Create a group whose instances count against `namespace`, and launch its starting
+    /// instances; nothing is kept when they cannot all be launched
+    pub async fn create_group_in(&self, namespace: &str, request: CreateGroupRequest) -> ZeroResult<ScalingGroup> {
+        let name = &request.name;
+        // Names prefix workload IDs, so they keep to what every compute driver accepts
+        if name.is_empty() || name.len() > 48 || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+            return Err(ZeroError::Validation(format!(
+                "Invalid group name {:?}: use 1-48 letters, digits, dots, hyphens and underscores", name
+            )));
+        }
+        if request.image.is_empty() {
+            return Err(ZeroError::Validation("A group needs an image".into()));
+        }
+        let desired_capacity = request.desired_capacity.unwrap_or(request.min_size);
+        if request.min_size > desired_capacity || desired_capacity > request.max_size || request.max_size == 0 || request.max_size > MAX_GROUP_SIZE {
+            return Err(ZeroError::Validation(format!(
+                "Group sizes must satisfy min_size <= desired_capacity <= max_size <= {}", MAX_GROUP_SIZE
+            )));
+        }
+        if !(request.target_value.is_finite() && request.target_value > 0.0) {
+            return Err(ZeroError::Validation("target_value must be greater than 0".into()));
+        }
+        let cpu = request.cpu.unwrap_or(DEFAULT_CPU);
+        let memory_mb = request.memory_mb.unwrap_or(DEFAULT_MEMORY_MB);
+        if cpu <= 0.0 || memory_mb <= 0 {
+            return Err(ZeroError::Validation("cpu and memory_mb must be greater than 0".into()));
+        }
+        match (request.metric, &request.queue) {
+            (MetricType::QueueDepth, Some(queue)) => {
+                if !self.queue.has_queue(queue) {
+                    return Err(ZeroError::NotFound(format!("Queue not found: {}", queue)));
+                }
+            }
+            (MetricType::QueueDepth, None) => {
+                return Err(ZeroError::Validation("queue_depth groups need a queue".into()));
+            }
+            (MetricType::Cpu, Some(_)) => {
+                return Err(ZeroError::Validation("Only queue_depth groups take a queue".into()));
+            }
+            (MetricType::Cpu, None) => {}
+        }
+
+        let _scaling = self.scaling.lock().await;
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            if Self::find_group(&conn, name)?.is_some() {
+                return Err(ZeroError::AlreadyExists(format!("Autoscaling group already exists: {}", name)));
+            }
+        }
+        self.namespace.reserve(namespace, SCALING_GROUP, name, Usage::default()).await?;
+        let (group, inserted) = {
+            let group = ScalingGroup {
+                arn: group_arn(name),
+                name: request.name.clone(),
+                image: request.image,
+                cpu,
+                memory_mb,
+                min_size: request.min_size,
+                max_size: request.max_size,
+                desired_capacity,
+                metric: request.metric,
+                target_value: request.target_value,
+                queue: request.queue,
+                cooldown_seconds: request.cooldown_seconds.unwrap_or(DEFAULT_COOLDOWN_SECS),
+                last_metric: None,
+                last_scaling_at: None,
+                instances: Vec::new(),
+                created_at: Utc::now().to_rfc3339(),
+            };
+            let inserted = self.engine.db.lock().execute(
+                "INSERT INTO autoscaling_groups (name, image, cpu, memory_mb, min_size, max_size, desired_capacity, metric, target_value, queue, cooldown_seconds, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    group.name, group.image, group.cpu, group.memory_mb, group.min_size, group.max_size,
+                    group.desired_capacity, group.metric.as_str(), group.target_value, group.queue,
+                    group.cooldown_seconds, group.created_at,
+                ],
+            );
+            (group, inserted)
+        };
+        if let Err(e) = inserted {
+            self.namespace.release(SCALING_GROUP, name).await?;
+            return Err(ZeroError::Internal(e.to_string()));
+        }
+        if let Err(e) = self.converge(&group).await {
+            self.remove_group(name).await?;
+            return Err(e);
+        }
+        self.get_group(name).await
+    }
+
+    pub async fn list_groups(&self) -> ZeroResult<Vec<ScalingGroup>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::query_groups(&conn, "ORDER BY name", params![])
+    }
+
+    pub async fn get_group(&self, name: &str) -> ZeroResult<ScalingGroup> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::find_group(&conn, name)?.ok_or_else(|| ZeroError::NotFound(format!("Autoscaling group not found: {}", name)))
+    }
+
+    /// Delete a group and its instances
+    pub async fn delete_group(&self, name: &str) -> ZeroResult<()> {
+        let _scaling = self.scaling.lock().await;
+        self.remove_group(name).await
+    }
+
+    /// Delete a group, its instances and their reservations; the caller holds `scaling`
+    async fn remove_group(&self, name: &str) -> ZeroResult<()> {
+        let group = self.get_group(name).await?;
+        for id in &group.instances {
+            if let Err(e) = self.engine.compute.delete_workload(id).await {
+                tracing::warn!("Autoscaling: failed to remove instance {} of {}: {}", id, name, e);
+            } else {
+                self.engine.events.publish(WORKLOAD_STOPPED, id, json!({ "scaling_group": name }));
+            }
+            self.namespace.release(WORKLOAD, id).await?;
+        }
+        {
+            let conn = self.engine.db.lock();
+            conn.execute("DELETE FROM autoscaling_instances WHERE group_name = ?1", params![name])
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            conn.execute("DELETE FROM autoscaling_groups WHERE name = ?1", params![name])
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+        self.namespace.release(SCALING_GROUP, name).await
+    }
+
+    /// Measure every group, adjust its desired capacity and launch or remove instances to
+    /// match; returns how many instances were launched or removed
+    pub async fn reconcile(&self, now: DateTime<Utc>) -> ZeroResult<usize> {
+        let _scaling = self.scaling.lock().await;
+        let groups = self.list_groups().await?;
+        if groups.is_empty() {
+            return Ok(0);
+        }
+        let running: HashSet<String> = self.engine.compute.list_workloads().await?
+            .into_iter()
+            .map(|workload| workload.id)
+            .collect();
+
+        let mut changes = 0;
+        for mut group in groups {
+            // Instances removed behind the group's back are replaced
+            let (alive, gone): (Vec<_>, Vec<_>) = group.instances.drain(..).partition(|id| running.contains(id));
+            group.instances = alive;
+            if !gone.is_empty() {
+                tracing::info!("Autoscaling: replacing {} lost instances of {}", gone.len(), group.name);
+                for id in &gone {
+                    self.engine.db.lock().execute("DELETE FROM autoscaling_instances WHERE workload_id = ?1", params![id])
+                        .map_err(|e| ZeroError::Internal(e.to_string()))?;
+                    self.namespace.release(WORKLOAD, id).await?;
+                }
+            }
+
+            match self.metric(&group).await {
+                Ok(metric) => {
+                    let desired = desired_capacity(&group, metric);
+                    let scale = desired != group.desired_capacity && cooled_down(&group, now);
+                    if scale {
+                        tracing::info!(
+                            "Autoscaling: {} {} at {} against a target of {}, {} -> {} instances",
+                            group.name, group.metric.as_str(), metric, group.target_value, group.desired_capacity, desired
+                        );
+                        group.desired_capacity = desired;
+                    }
+                    self.engine.db.lock().execute(
+                        "UPDATE autoscaling_groups SET last_metric = ?2, desired_capacity = ?3,
+                         last_scaling_at = CASE WHEN ?4 THEN ?5 ELSE last_scaling_at END WHERE name = ?1",
+                        params![group.name, metric, group.desired_capacity, scale, now.to_rfc3339()],
+                    ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+                }
+                Err(e) => tracing::warn!("Autoscaling: cannot measure {} of {}: {}", group.metric.as_str(), group.name, e),
+            }
+
+            match self.converge(&group).await {
+                Ok(n) => changes += n,
+                Err(e) => tracing::error!("Autoscaling: failed to scale {}: {}", group.name, e),
+            }
+        }
+        Ok(changes)
+    }
+
+    async fn metric(&self, group: &ScalingGroup) -> ZeroResult<f64> {
+        match group.metric {
+            MetricType::Cpu => {
+                if group.instances.is_empty() {
+                    return Ok(0.0);
+                }
+                let mut utilization = 0.0;
+                for id in &group.instances {
+                    utilization += self.engine.compute.workload_cpu_percent(id).await? as f64 / group.cpu as f64;
+                }
+                Ok(utilization / group.instances.len() as f64)
+            }
+            MetricType::QueueDepth => {
+                let queue = group.queue.as_deref().unwrap_or_default();
+                Ok(self.queue.visible_messages(queue)? as f64)
+            }
+        }
+    }
+
+    /// Launch or remove instances until the group has its desired capacity
+    async fn converge(&self, group: &ScalingGroup) -> ZeroResult<usize> {
+        let desired = group.desired_capacity as usize;
+        let mut changes = 0;
+        if group.instances.len() < desired {
+            let namespace = self.namespace.namespace_of(SCALING_GROUP, &group.name).await?;
+            let usage = Usage { cpu: group.cpu as f64, memory_mb: group.memory_mb as i64, ..Usage::default() };
+            for _ in group.instances.len()..desired {
+                let id = format!("{}-{}", group.name, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+                self.launch(&namespace, usage, group, &id).await?;
+                changes += 1;
+            }
+        }
+        for id in group.instances.iter().skip(desired).rev() {
+            if let Err(e) = self.engine.compute.delete_workload(id).await {
+                // Kept, so the next reconcile tries again
+                tracing::warn!("Autoscaling: failed to remove instance {} of {}: {}", id, group.name, e);
+                continue;
+            }
+            self.engine.events.publish(WORKLOAD_STOPPED, id, json!({ "scaling_group": group.name }));
+            self.engine.db.lock().execute("DELETE FROM autoscaling_instances WHERE workload_id = ?1", params![id])
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            self.namespace.release(WORKLOAD, id).await?;
+            changes += 1;
+        }
+        Ok(changes)
+    }
+
+    /// Start one instance, with its CPU and memory reserved in the group's namespace
+    async fn launch(&self, namespace: &str, usage: Usage, group: &ScalingGroup, id: &str) -> ZeroResult<()> {
+        self.namespace.reserve(namespace, WORKLOAD, id, usage).await?;
+        if let Err(e) = self.engine.compute.create_workload(id, &group.image, group.cpu, group.memory_mb).await {
+            self.namespace.release(WORKLOAD, id).await?;
+            return Err(e);
+        }
+        self.engine.db.lock().execute(
+            "INSERT INTO autoscaling_instances (workload_id, group_name, created_at) VALUES (?1, ?2, ?3)",
+            params![id, group.name, Utc::now().to_rfc3339()],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        self.engine.events.publish(WORKLOAD_STARTED, id, json!({ "image": group.image, "scaling_group": group.name }));
+        Ok(())
+    }
+
+    fn find_group(conn: &Connection, name: &str) -> ZeroResult<Option<ScalingGroup>> {
+        Ok(Self::query_groups(conn, "WHERE name = ?1", params![name])?.pop())
+    }
+
+    fn query_groups(
+        conn: &Connection,
+        clause: &str,
+        params: &[&dyn zero_data_core::rusqlite::ToSql],
+    ) -> ZeroResult<Vec<ScalingGroup>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT name, image, cpu, memory_mb, min_size, max_size, desired_capacity, metric, target_value, queue,
+                    cooldown_seconds, last_metric, last_scaling_at, created_at
+             FROM autoscaling_groups {}", clause
+        )).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                ScalingGroup {
+                    arn: group_arn(&row.get::<_, String>(0)?),
+                    name: row.get(0)?,
+                    image: row.get(1)?,
+                    cpu: row.get(2)?,
+                    memory_mb: row.get(3)?,
+                    min_size: row.get(4)?,
+                    max_size: row.get(5)?,
+                    desired_capacity: row.get(6)?,
+                    metric: MetricType::Cpu,
+                    target_value: row.get(8)?,
+                    queue: row.get(9)?,
+                    cooldown_seconds: row.get(10)?,
+                    last_metric: row.get(11)?,
+                    last_scaling_at: row.get(12)?,
+                    instances: Vec::new(),
+                    created_at: row.get(13)?,
+                },
+                row.get::<_, String>(7)?,
+            ))
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        let mut instances = conn.prepare("SELECT workload_id FROM autoscaling_instances WHERE group_name = ?1 ORDER BY rowid")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        rows.into_iter().map(|(mut group, metric)| {
+            group.metric = metric.parse()?;
+            group.instances = instances.query_map([&group.name], |row| row.get(0))
+                .map_err(|e| ZeroError::Internal(e.to_string()))?
+                .collect::<Result<Vec<String>, _>>()
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            Ok(group)
+        }).collect()
+    }
+}
+
+fn group_arn(name: &str) -> String {
+    format!("arn:aws:autoscaling:us-east-1:000000000000:autoScalingGroup:{}", name)
+}
+
+/// Capacity that brings the metric to the group's target
+fn desired_capacity(group: &ScalingGroup, metric: f64) -> u32 {
+    let wanted = match group.metric {
+        MetricType::Cpu => group.desired_capacity.max(1) as f64 * metric / group.target_value,
+        MetricType::QueueDepth => metric / group.target_value,
+    };
+    (wanted.ceil() as u32).clamp(group.min_size, group.max_size)
+}
+
+fn cooled_down(group: &ScalingGroup, now: DateTime<Utc>) -> bool {
+    match group.last_scaling_at.as_deref().and_then(|t| DateTime::parse_from_rfc3339(t).ok()) {
+        Some(last) => now.signed_duration_since(last) >= chrono::Duration::seconds(group.cooldown_seconds as i64),
+        None => true,
+    }
+}
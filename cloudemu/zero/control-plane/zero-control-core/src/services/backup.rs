@@ -0,0 +1,301 @@
+//! Backups of a whole control plane: the engine database and the data of every volume and
+//! bucket in one gzipped tar archive, for moving a home lab to another machine
+//!
+//! Archive layout:
+//! - `zerocloud.db`: snapshot of the engine database
+//! - `volumes/<volume>/...`: the files in each volume directory, such as bucket objects
+//! - `blocks/<volume>/size` and `blocks/<volume>/<offset>`: a volume's block file, as its length
+//!   and the chunks that hold data, so restored block files stay sparse
+
+use super::audit::AuditService;
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+
+const DB_ENTRY: &str = "zerocloud.db";
+
+const VOLUMES_DIR: &str = "volumes";
+
+const BLOCKS_DIR: &str = "blocks";
+
+/// Block files are archived in chunks of this size, leaving out chunks of zeros
+const BLOCK_CHUNK: u64 = 4 * 1024 * 1024;
+
+/// What a restore brought back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RestoreSummary {
+    /// Whether the archive held a database snapshot, which replaced the current database
+    pub database: bool,
+    /// Volumes and buckets written to, in archive order
+    pub volumes: Vec<String>,
+    pub files: usize,
+}
+
+#[derive(Clone)]
+pub struct BackupService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl BackupService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Write an archive of the database and every volume to `writer`
+    pub async fn create_backup(&self, writer: impl Write + Send + 'static) -> ZeroResult<()> {
+        let mut volumes = Vec::new();
+        for volume in self.engine.storage.list_volumes().await? {
+            let backing_file = self.engine.storage.backing_file(&volume.id).await?;
+            volumes.push((volume.id, PathBuf::from(volume.path), backing_file.map(PathBuf::from)));
+        }
+        let engine = self.engine.clone();
+        tokio::task::spawn_blocking(move || write_archive(&engine, &volumes, writer))
+            .await
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+    }
+
+    /// Restore an archive written by `create_backup`. The database is replaced, apart from the
+    /// audit trail, and the archived files are written over those of the same name; other
+    /// volumes are left alone.
+    pub async fn restore_backup(&self, reader: impl Read + Send + 'static) -> ZeroResult<RestoreSummary> {
+        let engine = self.engine.clone();
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || read_archive(&engine, &runtime, reader))
+            .await
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+    }
+}
+
+fn archive_error(e: std::io::Error) -> ZeroError {
+    ZeroError::Internal(format!("Backup archive error: {}", e))
+}
+
+fn temp_db_path() -> PathBuf {
+    std::env::temp_dir().join(format!("zerocloud-{}.db", uuid::Uuid::new_v4().simple()))
+}
+
+fn write_archive(engine: &ZeroEngine, volumes: &[(String, PathBuf, Option<PathBuf>)], writer: impl Write) -> ZeroResult<()> {
+    let mut archive = tar::Builder::new(flate2::write::GzEncoder::new(writer, flate2::Compression::fast()));
+
+    let db_file = temp_db_path();
+    let appended = engine.backup_db(&db_file)
+        .map_err(|e| ZeroError::Internal(format!("Database snapshot failed: {}", e)))
+        .and_then(|_| archive.append_path_with_name(&db_file, DB_ENTRY).map_err(archive_error));
+    let _ = std::fs::remove_file(&db_file);
+    appended?;
+
+    for (id, dir, backing_file) in volumes {
+        let name = Path::new(VOLUMES_DIR).join(id);
+        archive.append_dir(&name, dir).map_err(archive_error)?;
+        append_dir_contents(&mut archive, dir, &name, backing_file.as_deref())?;
+        if let Some(backing_file) = backing_file {
+            append_blocks(&mut archive, id, backing_file)?;
+        }
+    }
+
+    let mut writer = archive.into_inner().and_then(|gz| gz.finish()).map_err(archive_error)?;
+    writer.flush().map_err(archive_error)
+}
+
+/// Add the files under `dir` as `name/...`, apart from the volume's block file.
+/// Only directories and regular files are archived; links are not followed.
+fn append_dir_contents<W: Write>(archive: &mut tar::Builder<W>, dir: &Path, name: &Path, block_file: Option<&Path>) -> ZeroResult<()> {
+    let mut entries = std::fs::read_dir(dir).map_err(archive_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(archive_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if Some(path.as_path()) == block_file {
+            continue;
+        }
+        let entry_name = name.join(entry.file_name());
+        let file_type = entry.file_type().map_err(archive_error)?;
+        if file_type.is_dir() {
+            archive.append_dir(&entry_name, &path).map_err(archive_error)?;
+            append_dir_contents(archive, &path, &entry_name, block_file)?;
+        } else if file_type.is_file() {
+            let mut file = std::fs::File::open(&path).map_err(archive_error)?;
+            archive.append_file(&entry_name, &mut file).map_err(archive_error)?;
+        } else {
+            tracing::warn!("Not backing up {}, which is not a regular file or directory", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn append_blocks<W: Write>(archive: &mut tar::Builder<W>, volume: &str, block_file: &Path) -> ZeroResult<()> {
+    let mut file = std::fs::File::open(block_file).map_err(archive_error)?;
+    let size = file.metadata().map_err(archive_error)?.len();
+    let name = Path::new(BLOCKS_DIR).join(volume);
+    append_bytes(archive, &name.join("size"), size.to_string().as_bytes())?;
+
+    let mut offset = 0u64;
+    let mut chunk = Vec::with_capacity(BLOCK_CHUNK as usize);
+    loop {
+        chunk.clear();
+        (&mut file).take(BLOCK_CHUNK).read_to_end(&mut chunk).map_err(archive_error)?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        if chunk.iter().any(|byte| *byte != 0) {
+            append_bytes(archive, &name.join(offset.to_string()), &chunk)?;
+        }
+        offset += chunk.len() as u64;
+    }
+}
+
+fn append_bytes<W: Write>(archive: &mut tar::Builder<W>, name: &Path, data: &[u8]) -> ZeroResult<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    archive.append_data(&mut header, name, data).map_err(archive_error)
+}
+
+fn read_archive(engine: &ZeroEngine, runtime: &tokio::runtime::Handle, reader: impl Read) -> ZeroResult<RestoreSummary> {
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    let mut summary = RestoreSummary { database: false, volumes: Vec::new(), files: 0 };
+    let mut volume_dirs = HashMap::new();
+
+    for entry in archive.entries().map_err(archive_error)? {
+        let mut entry = entry.map_err(archive_error)?;
+        let path = entry.path().map_err(archive_error)?.into_owned();
+        // Links and device files could point restored data outside the volumes
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_dir() {
+            return Err(ZeroError::Validation(format!(
+                "Unsupported entry type {:?} in backup archive: {}", entry_type, path.display()
+            )));
+        }
+        // Entries must stay inside the volume they name
+        let parts = path.components()
+            .map(|component| match component {
+                Component::Normal(part) => part.to_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| ZeroError::Validation(format!("Backup archive path is not UTF-8: {}", path.display()))),
+                _ => Err(ZeroError::Validation(format!("Unsafe path in backup archive: {}", path.display()))),
+            })
+            .collect::<ZeroResult<Vec<_>>>()?;
+
+        match parts.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [DB_ENTRY] => {
+                let db_file = temp_db_path();
+                let restored = entry.unpack(&db_file).map_err(archive_error)
+                    .and_then(|_| restore_db(engine, &db_file));
+                let _ = std::fs::remove_file(&db_file);
+                restored?;
+                summary.database = true;
+            }
+            [VOLUMES_DIR, volume, rest @ ..] => {
+                let dir = volume_dir(engine, runtime, &mut volume_dirs, &mut summary, volume)?;
+                if entry_type.is_dir() {
+                    create_dirs(&dir, rest)?;
+                } else if let [parents @ .., file_name] = rest {
+                    let target = create_dirs(&dir, parents)?.join(file_name);
+                    // Replace whatever is there rather than writing through a link
+                    match std::fs::symlink_metadata(&target) {
+                        Ok(metadata) if metadata.is_dir() => return Err(ZeroError::Validation(format!(
+                            "Backup archive file {} would replace a directory", path.display()
+                        ))),
+                        Ok(_) => std::fs::remove_file(&target).map_err(archive_error)?,
+                        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                        Err(e) => return Err(archive_error(e)),
+                    }
+                    entry.unpack(&target).map_err(archive_error)?;
+                    summary.files += 1;
+                }
+            }
+            [BLOCKS_DIR, volume, "size"] => {
+                volume_dir(engine, runtime, &mut volume_dirs, &mut summary, volume)?;
+                let mut size = String::new();
+                entry.read_to_string(&mut size).map_err(archive_error)?;
+                let size = size.trim().parse::<u64>()
+                    .map_err(|_| ZeroError::Validation(format!("Invalid block file size for volume {}", volume)))?;
+                // Start from an empty block file, so blocks left out as zeros are zeros again
+                if let Some(old) = runtime.block_on(engine.storage.backing_file(volume))? {
+                    std::fs::remove_file(old).map_err(archive_error)?;
+                }
+                if size > 0 {
+                    runtime.block_on(engine.storage.write_block(volume, size - 1, vec![0]))?;
+                }
+            }
+            [BLOCKS_DIR, volume, offset] => {
+                let offset = offset.parse::<u64>()
+                    .map_err(|_| ZeroError::Validation(format!("Invalid block offset {} for volume {}", offset, volume)))?;
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).map_err(archive_error)?;
+                runtime.block_on(engine.storage.write_block(volume, offset, data))?;
+            }
+            _ => tracing::warn!("Skipping unknown backup archive entry {}", path.display()),
+        }
+    }
+    Ok(summary)
+}
+
+/// Replace the database with a snapshot but keep the live audit trail, so a restore cannot
+/// rewrite the record of what was done before it
+fn restore_db(engine: &ZeroEngine, db_file: &Path) -> ZeroResult<()> {
+    let failed = |e: &dyn std::fmt::Display| ZeroError::Internal(format!("Database restore failed: {}", e));
+    {
+        let conn = engine.db.lock();
+        AuditService::ensure_tables(&conn)?;
+        conn.execute_batch(
+            "ATTACH DATABASE ':memory:' AS live;
+             CREATE TABLE live.audit_events AS SELECT * FROM main.audit_events;"
+        ).map_err(|e| failed(&e))?;
+    }
+    let restored = engine.restore_db(db_file).map_err(|e| failed(&e));
+
+    let conn = engine.db.lock();
+    // Dropping the table also drops its triggers, which refuse deletes
+    let kept = restored
+        .and_then(|_| conn.execute_batch("DROP TABLE IF EXISTS main.audit_events").map_err(|e| failed(&e)))
+        .and_then(|_| AuditService::ensure_tables(&conn))
+        .and_then(|_| conn.execute_batch(
+            "INSERT INTO main.audit_events (seq, time, principal, method, path, body_sha256, status, error)
+             SELECT seq, time, principal, method, path, body_sha256, status, error FROM live.audit_events ORDER BY seq;"
+        ).map_err(|e| failed(&e)));
+    let detached = conn.execute_batch("DETACH DATABASE live").map_err(|e| failed(&e));
+    kept.and(detached)
+}
+
+/// Create the directories `parts` under `base`, refusing to go through anything that is not
+/// a real directory, such as a link into another part of the file system
+fn create_dirs(base: &Path, parts: &[&str]) -> ZeroResult<PathBuf> {
+    let mut dir = base.to_path_buf();
+    for part in parts {
+        dir.push(part);
+        match std::fs::symlink_metadata(&dir) {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => return Err(ZeroError::Validation(format!(
+                "Backup archive directory {} is not a directory on disk", dir.display()
+            ))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => std::fs::create_dir(&dir).map_err(archive_error)?,
+            Err(e) => return Err(archive_error(e)),
+        }
+    }
+    Ok(dir)
+}
+
+/// Directory of a volume being restored, creating the volume the first time it is seen
+fn volume_dir(
+    engine: &ZeroEngine,
+    runtime: &tokio::runtime::Handle,
+    volume_dirs: &mut HashMap<String, PathBuf>,
+    summary: &mut RestoreSummary,
+    volume: &str,
+) -> ZeroResult<PathBuf> {
+    if let Some(dir) = volume_dirs.get(volume) {
+        return Ok(dir.clone());
+    }
+    let status = runtime.block_on(engine.storage.create_volume(volume, 0))?;
+    summary.volumes.push(volume.to_string());
+    volume_dirs.insert(volume.to_string(), PathBuf::from(&status.path));
+    Ok(PathBuf::from(status.path))
+}
@@ -1,42 +1,172 @@
 use zero_control_spi::{ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection};
 use std::sync::Arc;
+use serde::{Serialize, Deserialize};
 
+#[derive(Clone)]
 pub struct DbService {
     engine: Arc<ZeroEngine>,
 }
 
+/// Per-table TTL configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtlSpec {
+    pub table: String,
+    pub attribute: String,
+    pub enabled: bool,
+}
+
 impl DbService {
     pub fn new(engine: Arc<ZeroEngine>) -> Self {
         Self { engine }
     }
 
-    pub async fn create_table(&self, name: &str, _pk: &str) -> ZeroResult<()> {
+    /// Item tables share the engine database with every other service, so `db_tables`
+    /// registers which of its tables hold items.
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS db_tables (
+                name TEXT PRIMARY KEY,
+                pk TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS db_ttl (
+                table_name TEXT PRIMARY KEY,
+                attribute TEXT NOT NULL,
+                enabled INTEGER NOT NULL DEFAULT 1
+            );
+            -- Item tables restored from backups taken before the registry existed
+            INSERT OR IGNORE INTO db_tables (name, pk)
+                SELECT m.name, 'pk' FROM sqlite_master m WHERE m.type = 'table'
+                AND (SELECT group_concat(name) FROM pragma_table_info(m.name)) = 'pk,item_json';"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    fn is_item_table(conn: &Connection, name: &str) -> ZeroResult<bool> {
+        conn.query_row("SELECT count(*) > 0 FROM db_tables WHERE name = ?1", params![name], |row| row.get(0))
+            .map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    pub async fn create_table(&self, name: &str, pk: &str) -> ZeroResult<()> {
         let conn = self.engine.db.lock();
-        // Create a table with dynamic columns? No, simplified: PK + JSON Body
-        let sql = format!("CREATE TABLE IF NOT EXISTS {} (
+        Self::ensure_tables(&conn)?;
+        if Self::is_item_table(&conn, name)? {
+            return Ok(());
+        }
+        let taken: bool = conn.query_row(
+            "SELECT count(*) > 0 FROM sqlite_master WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if taken {
+            return Err(ZeroError::AlreadyExists(format!("Table name {} is reserved", name)));
+        }
+
+        // Items are stored as JSON under their partition key
+        let sql = format!("CREATE TABLE {} (
             pk TEXT PRIMARY KEY,
             item_json TEXT NOT NULL
         )", name);
-        
         conn.execute(&sql, [])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("INSERT INTO db_tables (name, pk) VALUES (?1, ?2)", params![name, pk])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
         Ok(())
     }
 
     pub async fn list_tables(&self) -> ZeroResult<Vec<String>> {
         let conn = self.engine.db.lock();
-        let mut stmt = conn.prepare("SELECT name FROM sqlite_master WHERE type='table' AND name != 'nodes'").map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Self::ensure_tables(&conn)?;
+        let mut stmt = conn.prepare("SELECT name FROM db_tables ORDER BY name").map_err(|e| ZeroError::Internal(e.to_string()))?;
         let tables = stmt.query_map([], |row| row.get(0)).map_err(|e| ZeroError::Internal(e.to_string()))?
             .collect::<Result<Vec<String>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
         Ok(tables)
     }
 
+    /// Drop a table with its items and TTL configuration
+    pub async fn delete_table(&self, name: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        if !Self::is_item_table(&conn, name)? {
+            return Err(ZeroError::NotFound(format!("Table {} not found", name)));
+        }
+        conn.execute(&format!("DROP TABLE IF EXISTS {}", name), [])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM db_ttl WHERE table_name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM db_tables WHERE name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
     pub async fn put_item(&self, table: &str, pk_value: &str, item: serde_json::Value) -> ZeroResult<()> {
         let conn = self.engine.db.lock();
         let query = format!("INSERT OR REPLACE INTO {} (pk, item_json) VALUES (?1, ?2)", table);
-        conn.execute(&query, zero_data_core::rusqlite::params![pk_value, item.to_string()])
+        conn.execute(&query, params![pk_value, item.to_string()])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
         Ok(())
     }
+
+    /// Enable or disable TTL on a table. `attribute` names the item field holding
+    /// the expiry time in epoch seconds.
+    pub async fn update_ttl(&self, table: &str, attribute: &str, enabled: bool) -> ZeroResult<TtlSpec> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        if !Self::is_item_table(&conn, table)? {
+            return Err(ZeroError::NotFound(format!("Table {} not found", table)));
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO db_ttl (table_name, attribute, enabled) VALUES (?1, ?2, ?3)",
+            params![table, attribute, enabled],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        Ok(TtlSpec { table: table.to_string(), attribute: attribute.to_string(), enabled })
+    }
+
+    /// Describe the TTL configuration of a table
+    pub async fn describe_ttl(&self, table: &str) -> ZeroResult<Option<TtlSpec>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let spec = conn.query_row(
+            "SELECT table_name, attribute, enabled FROM db_ttl WHERE table_name = ?1",
+            params![table],
+            |row| Ok(TtlSpec {
+                table: row.get(0)?,
+                attribute: row.get(1)?,
+                enabled: row.get(2)?,
+            }),
+        ).ok();
+        Ok(spec)
+    }
+
+    /// Delete every item whose TTL timestamp has passed. Returns the number of items removed.
+    /// A table that cannot be swept is logged and skipped.
+    pub async fn sweep_expired(&self) -> ZeroResult<usize> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+
+        let mut stmt = conn.prepare("SELECT t.table_name, t.attribute FROM db_ttl t JOIN db_tables d ON d.name = t.table_name WHERE t.enabled = 1")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let specs = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        drop(stmt);
+
+        let now = chrono::Utc::now().timestamp();
+        let mut deleted = 0;
+        for (table, attribute) in specs {
+            // Items without a numeric TTL attribute never expire
+            let sql = format!(
+                "DELETE FROM {} WHERE CAST(json_extract(item_json, '$.' || ?1) AS INTEGER) <= ?2",
+                table
+            );
+            match conn.execute(&sql, params![attribute, now]) {
+                Ok(n) => deleted += n,
+                Err(e) => tracing::warn!("TTL sweep of table {} failed: {}", table, e),
+            }
+        }
+        Ok(deleted)
+    }
 }
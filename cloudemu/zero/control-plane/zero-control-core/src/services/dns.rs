@@ -0,0 +1,385 @@
+//! ZeroDNS: hosted zones and their record sets.
+//!
+//! Names are stored lower-case and fully qualified without the trailing dot. Besides the
+//! hosted zones, `<workload-id>.zero.internal` resolves to the workload's IP address so
+//! workloads can reach each other by name through the embedded resolver (`dns_server`).
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection, OptionalExtension, Params};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+
+/// Zone answering for workloads by id
+pub const WORKLOAD_ZONE: &str = "zero.internal";
+pub const DEFAULT_RECORD_TTL: u32 = 300;
+pub const MAX_RECORD_TTL: u32 = 604_800;
+/// CNAME chains are followed at most this many hops
+const MAX_CNAME_HOPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RecordType {
+    A,
+    Cname,
+    Txt,
+}
+
+impl RecordType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordType::A => "A",
+            RecordType::Cname => "CNAME",
+            RecordType::Txt => "TXT",
+        }
+    }
+}
+
+impl std::str::FromStr for RecordType {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "A" => Ok(RecordType::A),
+            "CNAME" => Ok(RecordType::Cname),
+            "TXT" => Ok(RecordType::Txt),
+            other => Err(ZeroError::Validation(format!("Unsupported record type {}; expected A, CNAME or TXT", other))),
+        }
+    }
+}
+
+/// Values of one name and type in a zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordSet {
+    /// Fully qualified, or relative to the zone; `@` is the zone apex
+    pub name: String,
+    #[serde(rename = "type")]
+    pub record_type: RecordType,
+    #[serde(default = "default_ttl")]
+    pub ttl: u32,
+    pub values: Vec<String>,
+}
+
+fn default_ttl() -> u32 {
+    DEFAULT_RECORD_TTL
+}
+
+/// A single resource record in an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub name: String,
+    pub record_type: RecordType,
+    pub ttl: u32,
+    pub value: String,
+}
+
+/// Outcome of resolving a name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// The name has records of the requested type (possibly behind CNAMEs)
+    Answers(Vec<Answer>),
+    /// The name exists but has no records of the requested type
+    NoData,
+    /// The name is in a hosted zone but does not exist
+    NxDomain,
+    /// No hosted zone contains the name
+    NotAuthoritative,
+}
+
+#[derive(Clone)]
+pub struct DnsService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl DnsService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    pub async fn create_zone(&self, name: &str) -> ZeroResult<serde_json::Value> {
+        let name = normalize_name(name)?;
+        if name == WORKLOAD_ZONE {
+            return Err(ZeroError::Validation(format!("{} is reserved for workload names", WORKLOAD_ZONE)));
+        }
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        if zone_exists(&conn, &name)? {
+            return Err(ZeroError::AlreadyExists(format!("Zone already exists: {}", name)));
+        }
+        let created_at = chrono::Utc::now().to_rfc3339();
+        conn.execute("INSERT INTO dns_zones (name, created_at) VALUES (?1, ?2)", params![name, created_at])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(json!({ "name": name, "created_at": created_at }))
+    }
+
+    pub async fn list_zones(&self) -> ZeroResult<Vec<serde_json::Value>> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT z.name, z.created_at, COUNT(r.name) FROM dns_zones z
+             LEFT JOIN dns_records r ON r.zone = z.name GROUP BY z.name ORDER BY z.name"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let zones = stmt.query_map([], |row| {
+            Ok(json!({
+                "name": row.get::<_, String>(0)?,
+                "created_at": row.get::<_, String>(1)?,
+                "record_sets": row.get::<_, i64>(2)?
+            }))
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(zones)
+    }
+
+    /// Delete a zone and all of its record sets
+    pub async fn delete_zone(&self, zone: &str) -> ZeroResult<()> {
+        let zone = normalize_name(zone)?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        if !zone_exists(&conn, &zone)? {
+            return Err(ZeroError::NotFound(format!("Zone not found: {}", zone)));
+        }
+        conn.execute("DELETE FROM dns_records WHERE zone = ?1", [&zone])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM dns_zones WHERE name = ?1", [&zone])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Create or replace the record set of a name and type
+    pub async fn put_record_set(&self, zone: &str, record_set: RecordSet) -> ZeroResult<RecordSet> {
+        let zone = normalize_name(zone)?;
+        let name = qualify(&record_set.name, &zone)?;
+        validate_values(record_set.record_type, &record_set.values)?;
+        if record_set.ttl > MAX_RECORD_TTL {
+            return Err(ZeroError::Validation(format!("TTL must be at most {} seconds", MAX_RECORD_TTL)));
+        }
+        let values: Vec<String> = match record_set.record_type {
+            RecordType::Cname => record_set.values.iter().map(|v| normalize_name(v)).collect::<ZeroResult<_>>()?,
+            _ => record_set.values.clone(),
+        };
+
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        if !zone_exists(&conn, &zone)? {
+            return Err(ZeroError::NotFound(format!("Zone not found: {}", zone)));
+        }
+        // A CNAME cannot share its name with other records
+        let conflict: Option<String> = conn.query_row(
+            "SELECT type FROM dns_records WHERE zone = ?1 AND name = ?2 AND type != ?3 AND (type = 'CNAME' OR ?3 = 'CNAME') LIMIT 1",
+            params![zone, name, record_set.record_type.as_str()],
+            |row| row.get(0),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if let Some(existing) = conflict {
+            return Err(ZeroError::Validation(format!(
+                "{} already has a {} record; a CNAME cannot coexist with other records", name, existing
+            )));
+        }
+
+        let encoded = serde_json::to_string(&values).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO dns_records (zone, name, type, ttl, record_values) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![zone, name, record_set.record_type.as_str(), record_set.ttl, encoded],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        Ok(RecordSet { name, record_type: record_set.record_type, ttl: record_set.ttl, values })
+    }
+
+    pub async fn list_record_sets(&self, zone: &str) -> ZeroResult<Vec<RecordSet>> {
+        let zone = normalize_name(zone)?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        if !zone_exists(&conn, &zone)? {
+            return Err(ZeroError::NotFound(format!("Zone not found: {}", zone)));
+        }
+        query_record_sets(&conn, "SELECT name, type, ttl, record_values FROM dns_records WHERE zone = ?1 ORDER BY name, type", [&zone])
+    }
+
+    pub async fn delete_record_set(&self, zone: &str, name: &str, record_type: RecordType) -> ZeroResult<()> {
+        let zone = normalize_name(zone)?;
+        let name = qualify(name, &zone)?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let deleted = conn.execute(
+            "DELETE FROM dns_records WHERE zone = ?1 AND name = ?2 AND type = ?3",
+            params![zone, name, record_type.as_str()],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if deleted == 0 {
+            return Err(ZeroError::NotFound(format!("No {} record for {} in {}", record_type.as_str(), name, zone)));
+        }
+        Ok(())
+    }
+
+    /// Resolve a name to records of `record_type` (all types when `None`), following
+    /// CNAMEs within the hosted zones
+    pub async fn resolve(&self, name: &str, record_type: Option<RecordType>) -> ZeroResult<Resolution> {
+        let mut name = normalize_name(name)?;
+        let mut answers = Vec::new();
+
+        for _ in 0..MAX_CNAME_HOPS {
+            if let Some(workload_id) = name.strip_suffix(WORKLOAD_ZONE).and_then(|n| n.strip_suffix('.')) {
+                return Ok(self.resolve_workload(workload_id, &name, record_type, answers).await);
+            }
+
+            let record_sets = {
+                let conn = self.engine.db.lock();
+                ensure_tables(&conn)?;
+                let Some(zone) = find_zone(&conn, &name)? else {
+                    return Ok(if answers.is_empty() { Resolution::NotAuthoritative } else { Resolution::Answers(answers) });
+                };
+                query_record_sets(
+                    &conn,
+                    "SELECT name, type, ttl, record_values FROM dns_records WHERE zone = ?1 AND name = ?2 ORDER BY type",
+                    params![zone, name],
+                )?
+            };
+            if record_sets.is_empty() {
+                // A dangling CNAME still answers with the CNAME itself
+                return Ok(if answers.is_empty() { Resolution::NxDomain } else { Resolution::Answers(answers) });
+            }
+
+            // A CNAME is the only record set at its name and answers for every type
+            if let Some(cname) = record_sets.iter().find(|r| r.record_type == RecordType::Cname) {
+                let target = cname.values[0].clone();
+                answers.push(Answer { name: name.clone(), record_type: RecordType::Cname, ttl: cname.ttl, value: target.clone() });
+                if matches!(record_type, None | Some(RecordType::Cname)) {
+                    return Ok(Resolution::Answers(answers));
+                }
+                name = target;
+                continue;
+            }
+
+            let matching = record_sets.into_iter()
+                .filter(|r| record_type.is_none_or(|t| t == r.record_type))
+                .flat_map(|r| r.values.into_iter().map(move |value| Answer { name: r.name.clone(), record_type: r.record_type, ttl: r.ttl, value }))
+                .collect::<Vec<_>>();
+            if matching.is_empty() && answers.is_empty() {
+                return Ok(Resolution::NoData);
+            }
+            answers.extend(matching);
+            return Ok(Resolution::Answers(answers));
+        }
+        Ok(Resolution::Answers(answers))
+    }
+
+    async fn resolve_workload(&self, workload_id: &str, name: &str, record_type: Option<RecordType>, mut answers: Vec<Answer>) -> Resolution {
+        let ip = match self.engine.compute.get_workload_status(workload_id).await {
+            Ok(status) => status.ip_address,
+            Err(_) => return if answers.is_empty() { Resolution::NxDomain } else { Resolution::Answers(answers) },
+        };
+        match ip {
+            Some(ip) if record_type.is_none_or(|t| t == RecordType::A) => {
+                answers.push(Answer { name: name.to_string(), record_type: RecordType::A, ttl: 0, value: ip });
+                Resolution::Answers(answers)
+            }
+            _ if !answers.is_empty() => Resolution::Answers(answers),
+            _ => Resolution::NoData,
+        }
+    }
+}
+
+/// Lower-case a domain name, drop its trailing dot and check its labels
+pub fn normalize_name(name: &str) -> ZeroResult<String> {
+    let name = name.trim_end_matches('.').to_ascii_lowercase();
+    let valid = !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    if valid {
+        Ok(name)
+    } else {
+        Err(ZeroError::Validation(format!("Invalid domain name: {}", name)))
+    }
+}
+
+/// Fully qualified record name: `@` is the apex, names outside the zone are relative to it
+fn qualify(name: &str, zone: &str) -> ZeroResult<String> {
+    if name == "@" {
+        return Ok(zone.to_string());
+    }
+    let name = normalize_name(name)?;
+    if name == zone || name.ends_with(&format!(".{}", zone)) {
+        Ok(name)
+    } else {
+        normalize_name(&format!("{}.{}", name, zone))
+    }
+}
+
+fn validate_values(record_type: RecordType, values: &[String]) -> ZeroResult<()> {
+    if values.is_empty() {
+        return Err(ZeroError::Validation("A record set needs at least one value".into()));
+    }
+    match record_type {
+        RecordType::A => {
+            for value in values {
+                value.parse::<Ipv4Addr>()
+                    .map_err(|_| ZeroError::Validation(format!("Invalid IPv4 address: {}", value)))?;
+            }
+        }
+        RecordType::Cname if values.len() > 1 => {
+            return Err(ZeroError::Validation("A CNAME record has exactly one value".into()));
+        }
+        RecordType::Cname => {}
+        RecordType::Txt => {
+            if values.iter().any(|v| v.len() > 255) {
+                return Err(ZeroError::Validation("TXT values are limited to 255 bytes".into()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn zone_exists(conn: &Connection, zone: &str) -> ZeroResult<bool> {
+    conn.query_row("SELECT 1 FROM dns_zones WHERE name = ?1", [zone], |_| Ok(()))
+        .optional()
+        .map(|row| row.is_some())
+        .map_err(|e| ZeroError::Internal(e.to_string()))
+}
+
+/// The most specific hosted zone containing `name`
+fn find_zone(conn: &Connection, name: &str) -> ZeroResult<Option<String>> {
+    conn.query_row(
+        "SELECT name FROM dns_zones WHERE ?1 = name OR substr(?1, -length(name) - 1) = '.' || name ORDER BY length(name) DESC LIMIT 1",
+        [name],
+        |row| row.get(0),
+    ).optional().map_err(|e| ZeroError::Internal(e.to_string()))
+}
+
+fn query_record_sets(conn: &Connection, sql: &str, params: impl Params) -> ZeroResult<Vec<RecordSet>> {
+    let mut stmt = conn.prepare(sql).map_err(|e| ZeroError::Internal(e.to_string()))?;
+    let rows = stmt.query_map(params, |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, u32>(2)?, row.get::<_, String>(3)?))
+    }).map_err(|e| ZeroError::Internal(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+    rows.into_iter().map(|(name, record_type, ttl, values)| {
+        Ok(RecordSet {
+            name,
+            record_type: record_type.parse()?,
+            ttl,
+            values: serde_json::from_str(&values).map_err(|e| ZeroError::Internal(e.to_string()))?,
+        })
+    }).collect()
+}
+
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS dns_zones (
+            name TEXT PRIMARY KEY,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS dns_records (
+            zone TEXT NOT NULL,
+            name TEXT NOT NULL,
+            type TEXT NOT NULL,
+            ttl INTEGER NOT NULL,
+            record_values TEXT NOT NULL,
+            PRIMARY KEY (zone, name, type)
+        );
+    ").map_err(|e| ZeroError::Internal(e.to_string()))
+}
@@ -0,0 +1,197 @@
+//! Embedded DNS resolver answering UDP queries from the hosted zones.
+//!
+//! Supports standard queries (one question, class IN) for A, CNAME, TXT and ANY records.
+//! The resolver is authoritative only: names outside the hosted zones and `zero.internal`
+//! are refused rather than recursed.
+
+use super::dns::{Answer, DnsService, RecordType, Resolution};
+use zero_control_spi::{ZeroResult, ZeroError};
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+
+/// Largest response sent without truncation (RFC 1035 UDP limit)
+const MAX_UDP_RESPONSE: usize = 512;
+
+const TYPE_A: u16 = 1;
+const TYPE_CNAME: u16 = 5;
+const TYPE_TXT: u16 = 16;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+
+const RCODE_FORMAT_ERROR: u8 = 1;
+const RCODE_NAME_ERROR: u8 = 3;
+const RCODE_NOT_IMPLEMENTED: u8 = 4;
+const RCODE_REFUSED: u8 = 5;
+
+/// Bind `port` on all interfaces and answer queries until the returned task is aborted
+pub async fn start_resolver(dns: DnsService, port: u16) -> ZeroResult<tokio::task::JoinHandle<()>> {
+    let socket = UdpSocket::bind(("0.0.0.0", port)).await
+        .map_err(|e| ZeroError::Internal(format!("Failed to bind DNS port {}: {}", port, e)))?;
+    let socket = Arc::new(socket);
+    tracing::info!("ZeroDNS resolver on udp/{}", port);
+
+    Ok(tokio::spawn(async move {
+        let mut buf = [0u8; MAX_UDP_RESPONSE];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    tracing::warn!("ZeroDNS failed to receive: {}", e);
+                    continue;
+                }
+            };
+            let query = buf[..len].to_vec();
+            let dns = dns.clone();
+            let socket = socket.clone();
+            tokio::spawn(async move {
+                if let Some(response) = answer_query(&dns, &query).await {
+                    if let Err(e) = socket.send_to(&response, peer).await {
+                        tracing::debug!("ZeroDNS failed to reply to {}: {}", peer, e);
+                    }
+                }
+            });
+        }
+    }))
+}
+
+/// Question of a query: its name, type and the offset where the question section ends
+struct Question {
+    name: String,
+    qtype: u16,
+    qclass: u16,
+    end: usize,
+}
+
+/// Build the response to a DNS message; `None` for messages that are not queries
+pub async fn answer_query(dns: &DnsService, query: &[u8]) -> Option<Vec<u8>> {
+    if query.len() < 12 || query[2] & 0x80 != 0 {
+        return None;
+    }
+    let opcode = (query[2] >> 3) & 0x0f;
+    let question_count = u16::from_be_bytes([query[4], query[5]]);
+    if opcode != 0 {
+        return Some(header_only(query, RCODE_NOT_IMPLEMENTED));
+    }
+    let question = match parse_question(query) {
+        Some(question) if question_count == 1 => question,
+        _ => return Some(header_only(query, RCODE_FORMAT_ERROR)),
+    };
+
+    let record_type = match question.qtype {
+        TYPE_A => Some(RecordType::A),
+        TYPE_CNAME => Some(RecordType::Cname),
+        TYPE_TXT => Some(RecordType::Txt),
+        _ => None,
+    };
+    let resolution = if question.qclass != CLASS_IN {
+        Resolution::NotAuthoritative
+    } else if record_type.is_none() && question.qtype != TYPE_ANY {
+        // A type we do not host: the name may exist, but never with this type
+        match dns.resolve(&question.name, None).await {
+            Ok(Resolution::Answers(_)) => Resolution::NoData,
+            Ok(other) => other,
+            Err(_) => Resolution::NxDomain,
+        }
+    } else {
+        match dns.resolve(&question.name, record_type).await {
+            Ok(resolution) => resolution,
+            // Invalid names cannot exist
+            Err(_) => Resolution::NxDomain,
+        }
+    };
+
+    let (rcode, answers) = match resolution {
+        Resolution::Answers(answers) => (0, answers),
+        Resolution::NoData => (0, Vec::new()),
+        Resolution::NxDomain => (RCODE_NAME_ERROR, Vec::new()),
+        Resolution::NotAuthoritative => (RCODE_REFUSED, Vec::new()),
+    };
+    Some(build_response(query, &question, rcode, &answers))
+}
+
+fn parse_question(query: &[u8]) -> Option<Question> {
+    let mut labels = Vec::new();
+    let mut pos = 12;
+    loop {
+        let len = *query.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        // Compression pointers never appear in the question of a query
+        if len > 63 {
+            return None;
+        }
+        let label = query.get(pos..pos + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += len;
+    }
+    let fields = query.get(pos..pos + 4)?;
+    Some(Question {
+        name: labels.join("."),
+        qtype: u16::from_be_bytes([fields[0], fields[1]]),
+        qclass: u16::from_be_bytes([fields[2], fields[3]]),
+        end: pos + 4,
+    })
+}
+
+/// Response flags: QR and AA set, opcode and RD copied from the query
+fn response_flags(query: &[u8], rcode: u8) -> [u8; 2] {
+    [0x80 | 0x04 | (query[2] & 0x79), rcode & 0x0f]
+}
+
+fn header_only(query: &[u8], rcode: u8) -> Vec<u8> {
+    let mut response = Vec::with_capacity(12);
+    response.extend_from_slice(&query[..2]);
+    response.extend_from_slice(&response_flags(query, rcode));
+    response.extend_from_slice(&[0; 8]);
+    response
+}
+
+fn build_response(query: &[u8], question: &Question, rcode: u8, answers: &[Answer]) -> Vec<u8> {
+    let mut response = Vec::with_capacity(MAX_UDP_RESPONSE);
+    response.extend_from_slice(&query[..2]);
+    response.extend_from_slice(&response_flags(query, rcode));
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&(answers.len() as u16).to_be_bytes());
+    response.extend_from_slice(&[0; 4]);
+    response.extend_from_slice(&query[12..question.end]);
+
+    for answer in answers {
+        encode_name(&mut response, &answer.name);
+        let (record_type, rdata) = match answer.record_type {
+            RecordType::A => (TYPE_A, answer.value.parse::<std::net::Ipv4Addr>().map(|ip| ip.octets().to_vec()).unwrap_or_default()),
+            RecordType::Cname => {
+                let mut rdata = Vec::new();
+                encode_name(&mut rdata, &answer.value);
+                (TYPE_CNAME, rdata)
+            }
+            RecordType::Txt => {
+                let mut rdata = vec![answer.value.len() as u8];
+                rdata.extend_from_slice(answer.value.as_bytes());
+                (TYPE_TXT, rdata)
+            }
+        };
+        response.extend_from_slice(&record_type.to_be_bytes());
+        response.extend_from_slice(&CLASS_IN.to_be_bytes());
+        response.extend_from_slice(&answer.ttl.to_be_bytes());
+        response.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        response.extend_from_slice(&rdata);
+    }
+
+    // Too large for UDP: send the header with TC set so the client knows it is incomplete
+    if response.len() > MAX_UDP_RESPONSE {
+        response.truncate(question.end);
+        response[2] |= 0x02;
+        response[6..8].copy_from_slice(&0u16.to_be_bytes());
+    }
+    response
+}
+
+fn encode_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+}
@@ -0,0 +1,106 @@
+//! Dry runs: mutating requests that are validated but change nothing
+//!
+//! A state-changing request with the `X-Zero-Dry-Run: true` header has its body checked
+//! against the route's schema, and the resource it would create, change or delete checked
+//! like the route would: that its namespace exists and has the quota, that a new resource
+//! does not exist yet and that an existing one is in the request's namespace. The response
+//! reports the change instead of making it. Policies are checked by the server before any
+//! request reaches the provider, so a dry run is refused exactly when the request would be.
+
+use crate::schema::{self, BodySchema, ValidBody};
+use crate::services::namespace::{self, Usage};
+use zero_control_spi::ZeroRequest;
+use serde::Serialize;
+
+/// Header asking for a dry run
+pub const DRY_RUN_HEADER: &str = "x-zero-dry-run";
+
+/// Kind of change for namespaces themselves, which are not resources of a namespace
+pub const NAMESPACE: &str = "namespace";
+
+/// Whether a request asks for a dry run
+pub fn requested(req: &ZeroRequest) -> bool {
+    req.header(DRY_RUN_HEADER).is_some_and(|value| value.eq_ignore_ascii_case("true") || value == "1")
+}
+
+/// Whether the route streams its body, which a dry run leaves unread
+pub fn streams_body(parts: &[&str]) -> bool {
+    matches!(parts, ["v1", "backup", ..] | ["v1", "store", "buckets", _, "objects", _, ..])
+}
+
+/// Schema of the body of the route matching `method` and the path `parts`
+pub fn route_schema(method: &str, parts: &[&str]) -> Option<&'static BodySchema> {
+    schema::ROUTES.iter().copied().find(|route| {
+        let pattern: Vec<&str> = route.path.split('/').filter(|s| !s.is_empty()).collect();
+        route.method == method
+            && pattern.len() == parts.len()
+            && pattern.iter().zip(parts).all(|(segment, part)| segment.starts_with('{') || segment == part)
+    })
+}
+
+/// What a mutating request would do
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// `create`, `update` or `delete`
+    pub operation: &'static str,
+    /// Kind of resource changed, e.g. `workload`; absent for requests the dry run only validates
+    pub kind: Option<&'static str>,
+    pub id: Option<String>,
+    /// Quota the resource would hold in its namespace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+/// The change a mutating request to the path `parts` would make, given its validated body
+pub fn change(method: &str, parts: &[&str], body: Option<&ValidBody>) -> Change {
+    use namespace::{BUCKET, CLUSTER, FUNCTION, QUEUE, SCALING_GROUP, TABLE, VOLUME, WORKLOAD};
+
+    let operation = match method {
+        "POST" => "create",
+        "DELETE" => "delete",
+        _ => "update",
+    };
+    let field = |name: &str| body.map(|body| body.str(name).to_string());
+    let of = |operation, kind, id: Option<String>| Change { operation, kind: Some(kind), id, usage: None };
+
+    match (method, parts) {
+        ("POST", ["v1", "workloads"]) => Change {
+            // CPU is rounded to the precision workloads are started with, as the route does
+            usage: body.map(|body| Usage {
+                cpu: body.get("cpu").as_f64().unwrap_or_default() as f32 as f64,
+                memory_mb: body.int("memory_mb"),
+                volume_gb: 0,
+            }),
+            ..of("create", WORKLOAD, field("id"))
+        },
+        ("DELETE", ["v1", "workloads"]) => of("delete", WORKLOAD, field("id")),
+        ("POST", ["v1", "volumes"]) => Change {
+            usage: body.map(|body| Usage { volume_gb: body.int("size_gb"), ..Usage::default() }),
+            ..of("create", VOLUME, field("id"))
+        },
+        ("PUT", ["v1", "volumes", id]) => Change {
+            usage: body.map(|body| Usage { volume_gb: body.int("size_gb"), ..Usage::default() }),
+            ..of("update", VOLUME, Some(id.to_string()))
+        },
+        ("POST", ["v1", "namespaces"]) => of("create", NAMESPACE, field("name")),
+        ("DELETE", ["v1", "namespaces", name]) => of("delete", NAMESPACE, Some(name.to_string())),
+        (_, ["v1", "namespaces", name, ..]) => of("update", NAMESPACE, Some(name.to_string())),
+        ("POST", ["v1", "store", "buckets"]) => of("create", BUCKET, field("name")),
+        ("POST", ["v1", "db", "tables"]) => of("create", TABLE, field("name")),
+        ("POST", ["v1", "func", "functions"]) => of("create", FUNCTION, field("name")),
+        ("POST", ["v1", "queue", "queues"]) => of("create", QUEUE, field("name")),
+        ("POST", ["v1", "autoscaling", "groups"]) => of("create", SCALING_GROUP, field("name")),
+        ("DELETE", ["v1", "autoscaling", "groups", name]) => of("delete", SCALING_GROUP, Some(name.to_string())),
+        ("POST", ["v1", "eks", "clusters"]) => of("create", CLUSTER, field("name")),
+        ("DELETE", ["v1", "eks", "clusters", name]) => of("delete", CLUSTER, Some(name.to_string())),
+        // Anything else within an existing resource changes that resource
+        (_, ["v1", "workloads", id, ..]) => of("update", WORKLOAD, Some(id.to_string())),
+        (_, ["v1", "store", "buckets", name, ..]) => of("update", BUCKET, Some(name.to_string())),
+        (_, ["v1", "db", "tables", name, ..]) => of("update", TABLE, Some(name.to_string())),
+        (_, ["v1", "func", "functions", name, ..]) => of("update", FUNCTION, Some(name.to_string())),
+        (_, ["v1", "queue", "queues", name, ..]) => of("update", QUEUE, Some(name.to_string())),
+        (_, ["v1", "autoscaling", "groups", name, ..]) => of("update", SCALING_GROUP, Some(name.to_string())),
+        (_, ["v1", "eks", "clusters", name, ..]) => of("update", CLUSTER, Some(name.to_string())),
+        _ => Change { operation, kind: None, id: None, usage: None },
+    }
+}
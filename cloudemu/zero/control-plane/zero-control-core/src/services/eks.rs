@@ -1,99 +1,532 @@
-use zero_control_spi::{ZeroResult, ZeroError};
+//! ZeroEKS: Kubernetes clusters and managed node groups.
+//!
+//! A cluster's control plane is a privileged k3s server container on the `ComputeDriver`;
+//! its API endpoint is the container's address on port 6443. Each node of a node group is a
+//! privileged k3s agent container joining the server, and its kubelet is registered as a
+//! ZeroCloud node. Every cluster has one random token: agents join with it, and the API
+//! server accepts it as the bearer token of a cluster admin, so the kubeconfig returned by
+//! `DescribeCluster` lets `kubectl` target the cluster. Drivers that cannot run containers
+//! cannot host clusters.
+//!
+//! A cluster belongs to the namespace it was created in. Its control plane and nodes reserve
+//! their CPU and memory there, and it is only visible from that namespace.
+
+use zero_control_spi::{ContainerSpec, ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::sync::Arc;
 use serde_json::json;
+use super::namespace::{in_namespace, NamespaceService, Usage, CLUSTER, DEFAULT_NAMESPACE, WORKLOAD};
+
+pub const DEFAULT_KUBERNETES_VERSION: &str = "1.29";
+/// k3s release booted for each supported Kubernetes version
+const K3S_IMAGES: &[(&str, &str)] = &[
+    ("1.27", "rancher/k3s:v1.27.16-k3s1"),
+    ("1.28", "rancher/k3s:v1.28.15-k3s1"),
+    ("1.29", "rancher/k3s:v1.29.10-k3s1"),
+];
+const API_SERVER_PORT: u16 = 6443;
+const CONTROL_PLANE_CPU: f32 = 1.0;
+const CONTROL_PLANE_MEMORY_MB: i32 = 1024;
+const NODE_CPU: f32 = 1.0;
+const NODE_MEMORY_MB: i32 = 1024;
+/// Static token file of the API server, holding the cluster token as an admin credential
+const TOKEN_AUTH_FILE: &str = "/etc/rancher/k3s/zero-tokens.csv";
+
+pub const DEFAULT_INSTANCE_TYPE: &str = "t3.medium";
+pub const MAX_NODEGROUP_SIZE: u32 = 10;
 
+#[derive(Clone)]
 pub struct EksService {
     engine: Arc<ZeroEngine>,
+    namespace: NamespaceService,
+}
+
+/// Stored cluster metadata
+struct Cluster {
+    name: String,
+    arn: String,
+    version: String,
+    token: String,
+    created_at: String,
 }
 
 impl EksService {
     pub fn new(engine: Arc<ZeroEngine>) -> Self {
-        Self { engine }
+        Self { namespace: NamespaceService::new(engine.clone()), engine }
     }
 
-    pub async fn handle(&self, action: &str, body: &[u8]) -> ZeroResult<Vec<u8>> {
-        let params: serde_json::Value = serde_json::from_slice(body).unwrap_or(json!({}));
-        
-        match action {
-            "CreateCluster" => self.create_cluster(params).await,
+    /// Run an EKS action in the default namespace. Path parameters (`name`, `nodegroupName`)
+    /// are merged into `params` alongside the request body.
+    pub async fn handle(&self, action: &str, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
+        self.handle_in(DEFAULT_NAMESPACE, action, params).await
+    }
+
+    /// Run an EKS action in `namespace`; clusters of other namespaces are not found
+    pub async fn handle_in(&self, namespace: &str, action: &str, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
+        ensure_tables(&self.engine.db.lock())?;
+        if !matches!(action, "CreateCluster" | "ListClusters") {
+            let name = required_str(&params, "name")?;
+            if !self.namespace.contains(namespace, CLUSTER, name).await? {
+                return Err(ZeroError::NotFound(format!("Cluster not found: {}", name)));
+            }
+        }
+
+        let result = match action {
+            "CreateCluster" => self.create_cluster(namespace, params).await,
             "DescribeCluster" => self.describe_cluster(params).await,
+            "ListClusters" => self.list_clusters(namespace).await,
             "DeleteCluster" => self.delete_cluster(params).await,
             "CreateNodegroup" => self.create_nodegroup(params).await,
             "DescribeNodegroup" => self.describe_nodegroup(params).await,
+            "ListNodegroups" => self.list_nodegroups(params).await,
             "DeleteNodegroup" => self.delete_nodegroup(params).await,
             _ => Err(ZeroError::InvalidRequest(format!("Unknown EKS action: {}", action))),
+        }?;
+        serde_json::to_vec(&result).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    /// Delete the clusters of every namespace with their node groups; returns how many
+    /// clusters there were
+    pub async fn delete_all(&self) -> ZeroResult<usize> {
+        let names = {
+            let conn = self.engine.db.lock();
+            ensure_tables(&conn)?;
+            let mut stmt = conn.prepare("SELECT name FROM eks_clusters ORDER BY name")
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            let names = stmt.query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| ZeroError::Internal(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+            names
+        };
+        for name in &names {
+            for nodegroup in self.nodegroup_names(name)? {
+                self.delete_nodegroup(json!({ "name": name, "nodegroupName": nodegroup })).await?;
+            }
+            self.delete_cluster(json!({ "name": name })).await?;
         }
+        Ok(names.len())
     }
 
-    async fn create_cluster(&self, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        let name = params["name"].as_str().unwrap_or("default");
-        // In a real implementation, we would spin up K3s or similar.
-        // Here we just mock the metadata.
-        
-        let cluster = json!({
-            "cluster": {
-                "name": name,
-                "arn": format!("arn:aws:eks:us-east-1:000000000000:cluster/{}", name),
-                "status": "ACTIVE", // Return ACTIVE immediately for speed
-                "endpoint": "https://localhost:6443",
-                "certificateAuthority": {
-                    "data": "dGVzdC1jZXJ0" // Base64 "test-cert"
-                },
-                "version": "1.27"
+    async fn create_cluster(&self, namespace: &str, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let name = required_str(&params, "name")?;
+        validate_name(name)?;
+        let version = params["version"].as_str().unwrap_or(DEFAULT_KUBERNETES_VERSION);
+        let image = k3s_image(version)?;
+
+        let arn = format!("arn:aws:eks:us-east-1:000000000000:cluster/{}", name);
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let created_at = chrono::Utc::now().to_rfc3339();
+        {
+            let conn = self.engine.db.lock();
+            if find_cluster(&conn, name)?.is_some() {
+                return Err(ZeroError::AlreadyExists(format!("Cluster already exists: {}", name)));
             }
-        });
-        Ok(serde_json::to_vec(&cluster).unwrap())
-    }
-
-    async fn describe_cluster(&self, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        let name = params["name"].as_str().unwrap_or("default");
-        // Mock response
-        let cluster = json!({
-            "cluster": {
-                "name": name,
-                "arn": format!("arn:aws:eks:us-east-1:000000000000:cluster/{}", name),
-                "status": "ACTIVE",
-                "endpoint": "https://localhost:6443",
-                "certificateAuthority": {
-                    "data": "dGVzdC1jZXJ0"
-                },
-                 "version": "1.27"
+        }
+        let control_plane = control_plane_id(name);
+        self.namespace.reserve(namespace, CLUSTER, name, Usage::default()).await?;
+        let usage = Usage { cpu: CONTROL_PLANE_CPU as f64, memory_mb: CONTROL_PLANE_MEMORY_MB as i64, ..Usage::default() };
+        if let Err(e) = self.namespace.reserve(namespace, WORKLOAD, &control_plane, usage).await {
+            self.namespace.release(CLUSTER, name).await?;
+            return Err(e);
+        }
+        let started = async {
+            self.engine.db.lock().execute(
+                "INSERT INTO eks_clusters (name, arn, version, token, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![name, arn, version, token, created_at],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            if let Err(e) = self.engine.compute.create_container(&control_plane, &control_plane_spec(image, &token)).await {
+                self.engine.db.lock().execute("DELETE FROM eks_clusters WHERE name = ?1", [name])
+                    .map_err(|e| ZeroError::Internal(e.to_string()))?;
+                return Err(e);
             }
-        });
-        Ok(serde_json::to_vec(&cluster).unwrap())
+            Ok(())
+        }.await;
+        if let Err(e) = started {
+            self.namespace.release(WORKLOAD, &control_plane).await?;
+            self.namespace.release(CLUSTER, name).await?;
+            return Err(e);
+        }
+
+        let cluster = self.get_cluster(name)?;
+        Ok(json!({ "cluster": self.cluster_json(&cluster).await }))
+    }
+
+    async fn describe_cluster(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        let mut description = self.cluster_json(&cluster).await;
+        description["kubeconfig"] = json!(kubeconfig(&cluster, description["endpoint"].as_str().unwrap_or_default()));
+        Ok(json!({ "cluster": description }))
     }
 
-    async fn delete_cluster(&self, _params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        // Mock success
-        Ok(serde_json::to_vec(&json!({})).unwrap())
+    async fn list_clusters(&self, namespace: &str) -> ZeroResult<serde_json::Value> {
+        let assignments = self.namespace.assignments(CLUSTER).await?;
+        let conn = self.engine.db.lock();
+        let mut stmt = conn.prepare("SELECT name FROM eks_clusters ORDER BY name")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let names: Vec<_> = names.into_iter().filter(|name| in_namespace(&assignments, name, namespace)).collect();
+        Ok(json!({ "clusters": names }))
     }
 
-    async fn create_nodegroup(&self, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        let name = params["nodegroupName"].as_str().unwrap_or("default-ng");
-         let ng = json!({
-            "nodegroup": {
-                "nodegroupName": name,
-                "nodegroupArn": format!("arn:aws:eks:us-east-1:000000000000:nodegroup/cluster/{}", name),
-                "status": "ACTIVE"
+    async fn delete_cluster(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        let nodegroups = self.nodegroup_names(&cluster.name)?;
+        if !nodegroups.is_empty() {
+            return Err(ZeroError::Validation(format!(
+                "Cluster {} has node groups attached: {}", cluster.name, nodegroups.join(", ")
+            )));
+        }
+
+        let mut description = self.cluster_json(&cluster).await;
+        let control_plane = control_plane_id(&cluster.name);
+        if let Err(e) = self.engine.compute.delete_workload(&control_plane).await {
+            tracing::warn!("ZeroEKS: failed to remove control plane of {}: {}", cluster.name, e);
+        }
+        self.engine.db.lock().execute("DELETE FROM eks_clusters WHERE name = ?1", [&cluster.name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        self.namespace.release(WORKLOAD, &control_plane).await?;
+        self.namespace.release(CLUSTER, &cluster.name).await?;
+        description["status"] = json!("DELETING");
+        Ok(json!({ "cluster": description }))
+    }
+
+    async fn create_nodegroup(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        let nodegroup = required_str(&params, "nodegroupName")?;
+        validate_name(nodegroup)?;
+
+        let scaling = &params["scalingConfig"];
+        let size = |key: &str, default: u32| scaling[key].as_u64().map(|v| v as u32).unwrap_or(default);
+        let min_size = size("minSize", 1);
+        let max_size = size("maxSize", 2);
+        let desired_size = size("desiredSize", min_size.max(2).min(max_size));
+        if min_size > desired_size || desired_size > max_size || max_size == 0 || max_size > MAX_NODEGROUP_SIZE {
+            return Err(ZeroError::Validation(format!(
+                "Node group scaling must satisfy minSize <= desiredSize <= maxSize <= {}", MAX_NODEGROUP_SIZE
+            )));
+        }
+        let instance_types = match params["instanceTypes"].as_array() {
+            Some(types) if !types.is_empty() => types.iter()
+                .map(|t| t.as_str().map(str::to_string).ok_or_else(|| ZeroError::Validation("instanceTypes must be strings".into())))
+                .collect::<ZeroResult<Vec<_>>>()?,
+            _ => vec![DEFAULT_INSTANCE_TYPE.to_string()],
+        };
+
+        let arn = format!("arn:aws:eks:us-east-1:000000000000:nodegroup/{}/{}", cluster.name, nodegroup);
+        let created_at = chrono::Utc::now().to_rfc3339();
+        {
+            let conn = self.engine.db.lock();
+            let exists = conn.query_row(
+                "SELECT 1 FROM eks_nodegroups WHERE cluster_name = ?1 AND name = ?2",
+                [&cluster.name, nodegroup],
+                |_| Ok(()),
+            ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+            if exists.is_some() {
+                return Err(ZeroError::AlreadyExists(format!("Node group already exists: {}", nodegroup)));
             }
-        });
-        Ok(serde_json::to_vec(&ng).unwrap())
+            conn.execute(
+                "INSERT INTO eks_nodegroups (cluster_name, name, arn, instance_types, min_size, max_size, desired_size, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![cluster.name, nodegroup, arn, instance_types.join(","), min_size, max_size, desired_size, created_at],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+
+        // A node group is created whole or not at all
+        let mut started = 0;
+        if let Err(e) = self.start_nodes(&cluster, nodegroup, desired_size, &mut started).await {
+            self.remove_nodes(&cluster.name, nodegroup, started).await?;
+            self.engine.db.lock().execute(
+                "DELETE FROM eks_nodegroups WHERE cluster_name = ?1 AND name = ?2",
+                [&cluster.name, nodegroup],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            return Err(e);
+        }
+
+        Ok(json!({ "nodegroup": self.nodegroup_json(&cluster, nodegroup).await? }))
+    }
+
+    /// Boot the nodes of a node group, with their CPU and memory reserved in the cluster's
+    /// namespace, and register their kubelets; `started` counts the nodes to remove on failure
+    async fn start_nodes(&self, cluster: &Cluster, nodegroup: &str, size: u32, started: &mut u32) -> ZeroResult<()> {
+        let control_plane = self.engine.compute.get_workload_status(&control_plane_id(&cluster.name)).await?;
+        let server = control_plane.ip_address
+            .map(|ip| format!("https://{}:{}", ip, API_SERVER_PORT))
+            .ok_or_else(|| ZeroError::Validation(format!("The control plane of {} has no address yet", cluster.name)))?;
+        let spec = node_spec(k3s_image(&cluster.version)?, &server, &cluster.token);
+        let namespace = self.namespace.namespace_of(CLUSTER, &cluster.name).await?;
+        let usage = Usage { cpu: NODE_CPU as f64, memory_mb: NODE_MEMORY_MB as i64, ..Usage::default() };
+        for index in 0..size {
+            let node_id = node_id(&cluster.name, nodegroup, index);
+            self.namespace.reserve(&namespace, WORKLOAD, &node_id, usage).await?;
+            let status = match self.engine.compute.create_container(&node_id, &spec).await {
+                Ok(status) => status,
+                Err(e) => {
+                    self.namespace.release(WORKLOAD, &node_id).await?;
+                    return Err(e);
+                }
+            };
+            *started += 1;
+            let ip = status.ip_address.unwrap_or_default();
+            self.engine.register_node(&node_id, &ip).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+        Ok(())
     }
 
-     async fn describe_nodegroup(&self, params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        let name = params["nodegroupName"].as_str().unwrap_or("default-ng");
-         let ng = json!({
-            "nodegroup": {
-                "nodegroupName": name,
-                "nodegroupArn": format!("arn:aws:eks:us-east-1:000000000000:nodegroup/cluster/{}", name),
-                "status": "ACTIVE"
+    /// Remove the first `count` nodes of a node group, with their registrations and reservations
+    async fn remove_nodes(&self, cluster_name: &str, nodegroup: &str, count: u32) -> ZeroResult<()> {
+        for index in 0..count {
+            let node_id = node_id(cluster_name, nodegroup, index);
+            if let Err(e) = self.engine.compute.delete_workload(&node_id).await {
+                tracing::warn!("ZeroEKS: failed to remove node {}: {}", node_id, e);
             }
-        });
-        Ok(serde_json::to_vec(&ng).unwrap())
+            self.engine.deregister_node(&node_id).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            self.namespace.release(WORKLOAD, &node_id).await?;
+        }
+        Ok(())
     }
-    
-    async fn delete_nodegroup(&self, _params: serde_json::Value) -> ZeroResult<Vec<u8>> {
-        Ok(serde_json::to_vec(&json!({})).unwrap())
+
+    async fn describe_nodegroup(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        let nodegroup = required_str(&params, "nodegroupName")?;
+        Ok(json!({ "nodegroup": self.nodegroup_json(&cluster, nodegroup).await? }))
     }
+
+    async fn list_nodegroups(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        Ok(json!({ "nodegroups": self.nodegroup_names(&cluster.name)? }))
+    }
+
+    async fn delete_nodegroup(&self, params: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        let cluster = self.get_cluster(required_str(&params, "name")?)?;
+        let nodegroup = required_str(&params, "nodegroupName")?;
+        let mut description = self.nodegroup_json(&cluster, nodegroup).await?;
+
+        let desired_size = description["scalingConfig"]["desiredSize"].as_u64().unwrap_or_default() as u32;
+        self.remove_nodes(&cluster.name, nodegroup, desired_size).await?;
+        self.engine.db.lock().execute(
+            "DELETE FROM eks_nodegroups WHERE cluster_name = ?1 AND name = ?2",
+            [&cluster.name, nodegroup],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        description["status"] = json!("DELETING");
+        Ok(json!({ "nodegroup": description }))
+    }
+
+    fn get_cluster(&self, name: &str) -> ZeroResult<Cluster> {
+        find_cluster(&self.engine.db.lock(), name)?
+            .ok_or_else(|| ZeroError::NotFound(format!("Cluster not found: {}", name)))
+    }
+
+    fn nodegroup_names(&self, cluster_name: &str) -> ZeroResult<Vec<String>> {
+        let conn = self.engine.db.lock();
+        let mut stmt = conn.prepare("SELECT name FROM eks_nodegroups WHERE cluster_name = ?1 ORDER BY name")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let names = stmt.query_map([cluster_name], |row| row.get::<_, String>(0))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(names)
+    }
+
+    /// Cluster description; status and endpoint follow the control plane workload
+    async fn cluster_json(&self, cluster: &Cluster) -> serde_json::Value {
+        let (status, endpoint) = match self.engine.compute.get_workload_status(&control_plane_id(&cluster.name)).await {
+            Ok(workload) if workload.state.eq_ignore_ascii_case("running") => {
+                let host = workload.ip_address.unwrap_or_else(|| "localhost".to_string());
+                ("ACTIVE", Some(format!("https://{}:{}", host, API_SERVER_PORT)))
+            }
+            Ok(_) => ("CREATING", None),
+            Err(_) => ("FAILED", None),
+        };
+        json!({
+            "name": cluster.name,
+            "arn": cluster.arn,
+            "version": cluster.version,
+            "status": status,
+            "endpoint": endpoint,
+            "createdAt": cluster.created_at
+        })
+    }
+
+    /// Node group description; it is `ACTIVE` once every node is running
+    async fn nodegroup_json(&self, cluster: &Cluster, nodegroup: &str) -> ZeroResult<serde_json::Value> {
+        let row = self.engine.db.lock().query_row(
+            "SELECT arn, instance_types, min_size, max_size, desired_size, created_at
+             FROM eks_nodegroups WHERE cluster_name = ?1 AND name = ?2",
+            [&cluster.name, nodegroup],
+            |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, String>(5)?,
+            )),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let (arn, instance_types, min_size, max_size, desired_size, created_at) = row
+            .ok_or_else(|| ZeroError::NotFound(format!("Node group not found: {}", nodegroup)))?;
+
+        let mut nodes = Vec::new();
+        let mut running = 0;
+        for index in 0..desired_size {
+            let node_id = node_id(&cluster.name, nodegroup, index);
+            if let Ok(status) = self.engine.compute.get_workload_status(&node_id).await {
+                if status.state.eq_ignore_ascii_case("running") {
+                    running += 1;
+                }
+            }
+            nodes.push(node_id);
+        }
+        let status = if running == desired_size { "ACTIVE" } else { "DEGRADED" };
+
+        Ok(json!({
+            "nodegroupName": nodegroup,
+            "nodegroupArn": arn,
+            "clusterName": cluster.name,
+            "version": cluster.version,
+            "status": status,
+            "instanceTypes": instance_types.split(',').collect::<Vec<_>>(),
+            "scalingConfig": { "minSize": min_size, "maxSize": max_size, "desiredSize": desired_size },
+            "nodes": nodes,
+            "createdAt": created_at
+        }))
+    }
+}
+
+/// Kubeconfig authenticating to the cluster's API server with its bearer token
+fn kubeconfig(cluster: &Cluster, endpoint: &str) -> String {
+    format!(
+        "apiVersion: v1
+kind: Config
+clusters:
+- cluster:
+    server: {endpoint}
+    insecure-skip-tls-verify: true
+  name: {arn}
+contexts:
+- context:
+    cluster: {arn}
+    user: {arn}
+  name: {arn}
+current-context: {arn}
+users:
+- name: {arn}
+  user:
+    token: {token}
+",
+        endpoint = endpoint,
+        arn = cluster.arn,
+        token = cluster.token,
+    )
+}
+
+/// k3s server joined with the cluster token, which the API server also accepts as the bearer
+/// token of a `system:masters` user
+fn control_plane_spec(image: &str, token: &str) -> ContainerSpec {
+    let script = format!(
+        "mkdir -p /etc/rancher/k3s && echo \"$K3S_TOKEN,zero-admin,zero-admin,system:masters\" > {file} \
+         && exec /bin/k3s server --kube-apiserver-arg=token-auth-file={file}",
+        file = TOKEN_AUTH_FILE,
+    );
+    ContainerSpec {
+        image: image.to_string(),
+        entrypoint: vec!["/bin/sh".to_string(), "-c".to_string()],
+        command: vec![script],
+        environment: HashMap::from([("K3S_TOKEN".to_string(), token.to_string())]),
+        privileged: true,
+        cpu: CONTROL_PLANE_CPU,
+        memory_mb: CONTROL_PLANE_MEMORY_MB,
+    }
+}
+
+/// k3s agent joining the server at `server` with the cluster token
+fn node_spec(image: &str, server: &str, token: &str) -> ContainerSpec {
+    ContainerSpec {
+        image: image.to_string(),
+        entrypoint: Vec::new(),
+        command: vec!["agent".to_string()],
+        environment: HashMap::from([
+            ("K3S_URL".to_string(), server.to_string()),
+            ("K3S_TOKEN".to_string(), token.to_string()),
+        ]),
+        privileged: true,
+        cpu: NODE_CPU,
+        memory_mb: NODE_MEMORY_MB,
+    }
+}
+
+fn control_plane_id(cluster_name: &str) -> String {
+    format!("eks-{}-control-plane", cluster_name)
+}
+
+fn node_id(cluster_name: &str, nodegroup: &str, index: u32) -> String {
+    format!("eks-{}-{}-{}", cluster_name, nodegroup, index)
+}
+
+fn k3s_image(version: &str) -> ZeroResult<&'static str> {
+    K3S_IMAGES.iter()
+        .find(|(v, _)| *v == version)
+        .map(|(_, image)| *image)
+        .ok_or_else(|| ZeroError::Validation(format!(
+            "Unsupported Kubernetes version {}; expected one of {}",
+            version,
+            K3S_IMAGES.iter().map(|(v, _)| *v).collect::<Vec<_>>().join(", ")
+        )))
+}
+
+fn required_str<'a>(params: &'a serde_json::Value, key: &str) -> ZeroResult<&'a str> {
+    params[key].as_str().ok_or_else(|| ZeroError::Validation(format!("Missing {}", key)))
+}
+
+/// Names become workload ids, so they follow container naming rules
+fn validate_name(name: &str) -> ZeroResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 100
+        && name.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ZeroError::Validation(format!("Invalid name {}: use letters, digits, '-' and '_'", name)))
+    }
+}
+
+fn find_cluster(conn: &Connection, name: &str) -> ZeroResult<Option<Cluster>> {
+    conn.query_row(
+        "SELECT name, arn, version, token, created_at FROM eks_clusters WHERE name = ?1",
+        [name],
+        |row| Ok(Cluster {
+            name: row.get(0)?,
+            arn: row.get(1)?,
+            version: row.get(2)?,
+            token: row.get(3)?,
+            created_at: row.get(4)?,
+        }),
+    ).optional().map_err(|e| ZeroError::Internal(e.to_string()))
+}
+
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS eks_clusters (
+            name TEXT PRIMARY KEY,
+            arn TEXT NOT NULL,
+            version TEXT NOT NULL,
+            token TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS eks_nodegroups (
+            cluster_name TEXT NOT NULL,
+            name TEXT NOT NULL,
+            arn TEXT NOT NULL,
+            instance_types TEXT NOT NULL,
+            min_size INTEGER NOT NULL,
+            max_size INTEGER NOT NULL,
+            desired_size INTEGER NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (cluster_name, name)
+        );
+    ").map_err(|e| ZeroError::Internal(e.to_string()))
 }
@@ -0,0 +1,354 @@
+//! Event source mappings: background pollers that feed queue messages to functions in batches
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::OptionalExtension;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use tokio::task::AbortHandle;
+use super::func::FuncService;
+use super::queue::{QueueService, ReceiveOptions, DeleteBatchEntry, MAX_BATCH_SIZE};
+
+/// How long each poll waits for messages on an empty queue
+const POLL_WAIT_SECS: u64 = 1;
+
+/// Pause after a failed poll (e.g. the queue was deleted) before trying again
+const ERROR_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Settings for a new mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMappingRequest {
+    pub function_name: String,
+    pub queue_name: String,
+    /// Maximum messages per invocation (1-10, default 10)
+    #[serde(default)]
+    pub batch_size: Option<u32>,
+    /// Failed batches are retried until a message has been received this many extra times;
+    /// without it, retries continue until the queue's own redrive policy applies
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    /// Queue that receives messages which exhausted `max_retries`; they are dropped otherwise
+    #[serde(default)]
+    pub dead_letter_queue: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// A queue-to-function mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSourceMapping {
+    pub id: String,
+    pub function_name: String,
+    pub queue_name: String,
+    pub batch_size: u32,
+    pub max_retries: Option<u32>,
+    pub dead_letter_queue: Option<String>,
+    pub enabled: bool,
+    pub last_processing_result: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Clone)]
+pub struct EventSourceService {
+    engine: Arc<ZeroEngine>,
+    queue: QueueService,
+    func: FuncService,
+    pollers: Arc<Mutex<HashMap<String, AbortHandle>>>,
+}
+
+impl EventSourceService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self {
+            queue: QueueService::new(engine.clone()),
+            func: FuncService::new(engine.clone()),
+            engine,
+            pollers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn ensure_table(conn: &zero_data_core::rusqlite::Connection) -> ZeroResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS event_source_mappings (
+                id TEXT PRIMARY KEY,
+                function_name TEXT NOT NULL,
+                queue_name TEXT NOT NULL,
+                batch_size INTEGER NOT NULL,
+                max_retries INTEGER,
+                dead_letter_queue TEXT,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                last_processing_result TEXT,
+                created_at TEXT NOT NULL
+            )",
+            [],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Create a mapping and, when enabled, start polling its queue
+    pub async fn create_mapping(&self, request: CreateMappingRequest) -> ZeroResult<EventSourceMapping> {
+        let batch_size = request.batch_size.unwrap_or(MAX_BATCH_SIZE as u32);
+        if batch_size == 0 || batch_size as usize > MAX_BATCH_SIZE {
+            return Err(ZeroError::Validation(format!("batch_size must be between 1 and {}", MAX_BATCH_SIZE)));
+        }
+        if !self.func.has_function(&request.function_name) {
+            return Err(ZeroError::NotFound(format!("Function {} not found", request.function_name)));
+        }
+        if !self.queue.has_queue(&request.queue_name) {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", request.queue_name)));
+        }
+        if let Some(dlq) = &request.dead_letter_queue {
+            if dlq == &request.queue_name {
+                return Err(ZeroError::Validation("The dead-letter queue must differ from the source queue".into()));
+            }
+            if !self.queue.has_queue(dlq) {
+                return Err(ZeroError::NotFound(format!("Dead-letter queue {} not found", dlq)));
+            }
+        }
+
+        let mapping = EventSourceMapping {
+            id: uuid::Uuid::new_v4().to_string(),
+            function_name: request.function_name,
+            queue_name: request.queue_name,
+            batch_size,
+            max_retries: request.max_retries,
+            dead_letter_queue: request.dead_letter_queue,
+            enabled: request.enabled,
+            last_processing_result: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_table(&conn)?;
+            conn.execute(
+                "INSERT INTO event_source_mappings (id, function_name, queue_name, batch_size, max_retries, dead_letter_queue, enabled, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                zero_data_core::rusqlite::params![
+                    mapping.id, mapping.function_name, mapping.queue_name, mapping.batch_size,
+                    mapping.max_retries, mapping.dead_letter_queue, mapping.enabled, mapping.created_at
+                ],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+
+        if mapping.enabled {
+            self.start_poller(mapping.clone());
+        }
+        Ok(mapping)
+    }
+
+    pub async fn get_mapping(&self, id: &str) -> ZeroResult<EventSourceMapping> {
+        let conn = self.engine.db.lock();
+        Self::ensure_table(&conn)?;
+        conn.query_row(
+            "SELECT id, function_name, queue_name, batch_size, max_retries, dead_letter_queue, enabled, last_processing_result, created_at
+             FROM event_source_mappings WHERE id = ?1",
+            zero_data_core::rusqlite::params![id],
+            row_to_mapping,
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?
+            .ok_or_else(|| ZeroError::NotFound(format!("Event source mapping {} not found", id)))
+    }
+
+    pub async fn list_mappings(&self) -> ZeroResult<Vec<EventSourceMapping>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_table(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, function_name, queue_name, batch_size, max_retries, dead_letter_queue, enabled, last_processing_result, created_at
+             FROM event_source_mappings ORDER BY created_at"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let mappings = stmt.query_map([], row_to_mapping).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(mappings)
+    }
+
+    /// Pause or resume a mapping
+    pub async fn set_enabled(&self, id: &str, enabled: bool) -> ZeroResult<EventSourceMapping> {
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_table(&conn)?;
+            let rows = conn.execute(
+                "UPDATE event_source_mappings SET enabled = ?1 WHERE id = ?2",
+                zero_data_core::rusqlite::params![enabled, id],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            if rows == 0 {
+                return Err(ZeroError::NotFound(format!("Event source mapping {} not found", id)));
+            }
+        }
+
+        let mapping = self.get_mapping(id).await?;
+        if enabled {
+            self.start_poller(mapping.clone());
+        } else {
+            self.stop_poller(id);
+        }
+        Ok(mapping)
+    }
+
+    pub async fn delete_mapping(&self, id: &str) -> ZeroResult<()> {
+        self.stop_poller(id);
+        let conn = self.engine.db.lock();
+        Self::ensure_table(&conn)?;
+        let rows = conn.execute("DELETE FROM event_source_mappings WHERE id = ?1", zero_data_core::rusqlite::params![id])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if rows == 0 {
+            return Err(ZeroError::NotFound(format!("Event source mapping {} not found", id)));
+        }
+        Ok(())
+    }
+
+    /// Run pollers for exactly the enabled mappings in the database. The engine database
+    /// lives in memory, so mappings only reappear when a backup restore replaces it; call
+    /// this afterwards.
+    pub async fn resume_pollers(&self) -> ZeroResult<usize> {
+        let enabled: Vec<_> = self.list_mappings().await?.into_iter().filter(|m| m.enabled).collect();
+        let running: Vec<String> = self.pollers.lock().unwrap().keys().cloned().collect();
+        for id in running.iter().filter(|id| !enabled.iter().any(|m| &m.id == *id)) {
+            self.stop_poller(id);
+        }
+        let count = enabled.len();
+        for mapping in enabled {
+            self.start_poller(mapping);
+        }
+        Ok(count)
+    }
+
+    fn start_poller(&self, mapping: EventSourceMapping) {
+        let id = mapping.id.clone();
+        let service = self.clone();
+        let handle = tokio::spawn(async move { service.run(mapping).await }).abort_handle();
+        if let Some(previous) = self.pollers.lock().unwrap().insert(id.clone(), handle) {
+            previous.abort();
+        }
+        tracing::debug!("Started event source mapping {}", id);
+    }
+
+    fn stop_poller(&self, id: &str) {
+        if let Some(handle) = self.pollers.lock().unwrap().remove(id) {
+            handle.abort();
+            tracing::debug!("Stopped event source mapping {}", id);
+        }
+    }
+
+    async fn run(&self, mapping: EventSourceMapping) {
+        loop {
+            match self.poll_once(&mapping).await {
+                Ok(_) => {},
+                Err(e) => {
+                    tracing::warn!("Event source mapping {} failed to poll {}: {}", mapping.id, mapping.queue_name, e);
+                    self.record_result(&mapping.id, &format!("PollingFailed: {}", e));
+                    tokio::time::sleep(ERROR_BACKOFF).await;
+                }
+            }
+        }
+    }
+
+    /// Receive one batch and invoke the function with it. Returns the number of messages received.
+    async fn poll_once(&self, mapping: &EventSourceMapping) -> ZeroResult<usize> {
+        let options = ReceiveOptions {
+            max_messages: mapping.batch_size as usize,
+            wait_time_seconds: POLL_WAIT_SECS,
+            visibility_timeout: None,
+        };
+        let messages = self.queue.receive_messages(&mapping.queue_name, options).await?;
+        if messages.is_empty() {
+            return Ok(0);
+        }
+
+        let records: Vec<_> = messages.iter().map(|m| json!({
+            "messageId": m["MessageId"],
+            "receiptHandle": m["ReceiptHandle"],
+            "body": m["Body"],
+            "attributes": m["Attributes"],
+            "eventSource": "zero:queue",
+            "eventSourceQueue": mapping.queue_name,
+        })).collect();
+
+        let failure = match self.func.invoke_function(&mapping.function_name, json!({ "Records": records })).await {
+            Ok(result) => match result["exit_code"].as_i64() {
+                Some(code) if code != 0 => Some(format!("Function exited with code {}", code)),
+                _ => None,
+            },
+            Err(e) => Some(e.to_string()),
+        };
+
+        match failure {
+            None => {
+                self.delete_messages(&mapping.queue_name, &messages).await?;
+                self.record_result(&mapping.id, "OK");
+            }
+            Some(reason) => {
+                tracing::debug!("Event source mapping {} batch failed: {}", mapping.id, reason);
+                self.record_result(&mapping.id, &format!("FunctionError: {}", reason));
+                self.handle_failed_batch(mapping, &messages).await?;
+            }
+        }
+        Ok(messages.len())
+    }
+
+    /// Messages stay on the queue and are retried once their visibility timeout expires.
+    /// Messages past `max_retries` move to the mapping's dead-letter queue (or are dropped).
+    async fn handle_failed_batch(&self, mapping: &EventSourceMapping, messages: &[serde_json::Value]) -> ZeroResult<()> {
+        let Some(max_retries) = mapping.max_retries else {
+            return Ok(());
+        };
+
+        let exhausted: Vec<_> = messages.iter()
+            .filter(|m| {
+                let receive_count = m["Attributes"]["ApproximateReceiveCount"].as_str()
+                    .and_then(|c| c.parse::<u32>().ok())
+                    .unwrap_or(1);
+                receive_count > max_retries
+            })
+            .cloned()
+            .collect();
+        if exhausted.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(dlq) = &mapping.dead_letter_queue {
+            for message in &exhausted {
+                self.queue.send_message(dlq, message["Body"].as_str().unwrap_or_default()).await?;
+            }
+        }
+        self.delete_messages(&mapping.queue_name, &exhausted).await
+    }
+
+    async fn delete_messages(&self, queue_name: &str, messages: &[serde_json::Value]) -> ZeroResult<()> {
+        let entries = messages.iter()
+            .enumerate()
+            .map(|(i, m)| DeleteBatchEntry {
+                id: i.to_string(),
+                receipt_handle: m["ReceiptHandle"].as_str().unwrap_or_default().to_string(),
+            })
+            .collect();
+        self.queue.delete_message_batch(queue_name, entries).await?;
+        Ok(())
+    }
+
+    fn record_result(&self, id: &str, result: &str) {
+        let conn = self.engine.db.lock();
+        let _ = conn.execute(
+            "UPDATE event_source_mappings SET last_processing_result = ?1 WHERE id = ?2",
+            zero_data_core::rusqlite::params![result, id],
+        );
+    }
+}
+
+fn row_to_mapping(row: &zero_data_core::rusqlite::Row) -> zero_data_core::rusqlite::Result<EventSourceMapping> {
+    Ok(EventSourceMapping {
+        id: row.get(0)?,
+        function_name: row.get(1)?,
+        queue_name: row.get(2)?,
+        batch_size: row.get(3)?,
+        max_retries: row.get(4)?,
+        dead_letter_queue: row.get(5)?,
+        enabled: row.get(6)?,
+        last_processing_result: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
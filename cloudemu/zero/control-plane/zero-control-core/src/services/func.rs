@@ -1,36 +1,249 @@
 use zero_control_spi::{ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
-use std::sync::Arc;
-use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use zero_data_core::rusqlite::OptionalExtension;
+use super::func_runtime::{self, Runtime};
+use super::namespace::NamespaceService;
 
+pub const DEFAULT_TIMEOUT_SECS: u32 = 30;
+pub const MAX_TIMEOUT_SECS: u32 = 900;
+pub const DEFAULT_MEMORY_MB: u32 = 128;
+pub const MIN_MEMORY_MB: u32 = 128;
+pub const MAX_MEMORY_MB: u32 = 10240;
+
+/// Request header selecting the invocation type (`Event` or `RequestResponse`)
+pub const INVOCATION_TYPE_HEADER: &str = "X-Zero-Invocation-Type";
+
+/// Asynchronous invocations that may run at the same time
+const MAX_CONCURRENT_INVOCATIONS: usize = 16;
+
+/// How a function is invoked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvocationType {
+    /// Run the function and wait for its result
+    #[default]
+    RequestResponse,
+    /// Queue the invocation and return its record immediately
+    Event,
+}
+
+impl std::str::FromStr for InvocationType {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "RequestResponse" => Ok(InvocationType::RequestResponse),
+            "Event" => Ok(InvocationType::Event),
+            other => Err(ZeroError::Validation(format!("Unknown invocation type {}; expected Event or RequestResponse", other))),
+        }
+    }
+}
+
+/// Progress of an asynchronous invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InvocationStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl InvocationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvocationStatus::Pending => "Pending",
+            InvocationStatus::Running => "Running",
+            InvocationStatus::Succeeded => "Succeeded",
+            InvocationStatus::Failed => "Failed",
+        }
+    }
+}
+
+impl std::str::FromStr for InvocationStatus {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "Pending" => Ok(InvocationStatus::Pending),
+            "Running" => Ok(InvocationStatus::Running),
+            "Succeeded" => Ok(InvocationStatus::Succeeded),
+            "Failed" => Ok(InvocationStatus::Failed),
+            other => Err(ZeroError::Internal(format!("Unknown invocation status {}", other))),
+        }
+    }
+}
+
+/// Record of an asynchronous invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionInvocation {
+    pub id: String,
+    pub function_name: String,
+    pub status: InvocationStatus,
+    pub payload: serde_json::Value,
+    /// Function output once the function ran, including when it exited non-zero
+    pub result: Option<serde_json::Value>,
+    /// Failure reason once the invocation failed
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// Optional settings applied when deploying a function
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FunctionOptions {
+    pub runtime: Runtime,
+    /// Environment variables visible to the function code
+    pub environment: BTreeMap<String, String>,
+    /// Seconds an invocation may run before it is aborted (1-900, default 30)
+    pub timeout_secs: Option<u32>,
+    /// Memory limit in MB (128-10240, default 128)
+    pub memory_mb: Option<u32>,
+}
+
+/// Configuration of a deployed function
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionConfiguration {
+    pub name: String,
+    pub handler: String,
+    pub runtime: Runtime,
+    pub environment: BTreeMap<String, String>,
+    pub timeout_secs: u32,
+    pub memory_mb: u32,
+}
+
+impl FunctionConfiguration {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(u64::from(self.timeout_secs))
+    }
+
+    /// Error returned when an invocation exceeds the timeout
+    pub(crate) fn timed_out(&self) -> ZeroError {
+        ZeroError::Internal(format!("Function {} timed out after {} seconds", self.name, self.timeout_secs))
+    }
+}
+
+#[derive(Clone)]
 pub struct FuncService {
     engine: Arc<ZeroEngine>,
+    /// Queue of the background executor, started by the first asynchronous invocation
+    executor: Arc<OnceLock<tokio::sync::mpsc::UnboundedSender<String>>>,
 }
 
 impl FuncService {
     pub fn new(engine: Arc<ZeroEngine>) -> Self {
-        Self { engine }
+        Self { engine, executor: Arc::new(OnceLock::new()) }
     }
 
     pub async fn create_function(&self, name: &str, handler: &str, code: &str) -> ZeroResult<()> {
+        self.create_function_with_options(name, handler, code, FunctionOptions::default()).await
+    }
+
+    pub async fn create_function_with_runtime(&self, name: &str, handler: &str, code: &str, runtime: Runtime) -> ZeroResult<()> {
+        self.create_function_with_options(name, handler, code, FunctionOptions { runtime, ..Default::default() }).await
+    }
+
+    /// Deploy a function. `code` holds the source for `inline`, the image reference for
+    /// `docker`, and a base64 binary or WebAssembly text module for `wasm`.
+    pub async fn create_function_with_options(&self, name: &str, handler: &str, code: &str, options: FunctionOptions) -> ZeroResult<()> {
+        let timeout_secs = options.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+        if !(1..=MAX_TIMEOUT_SECS).contains(&timeout_secs) {
+            return Err(ZeroError::Validation(format!("Timeout must be between 1 and {} seconds", MAX_TIMEOUT_SECS)));
+        }
+        let memory_mb = options.memory_mb.unwrap_or(DEFAULT_MEMORY_MB);
+        if !(MIN_MEMORY_MB..=MAX_MEMORY_MB).contains(&memory_mb) {
+            return Err(ZeroError::Validation(format!("Memory must be between {} and {} MB", MIN_MEMORY_MB, MAX_MEMORY_MB)));
+        }
+        if let Some(key) = options.environment.keys().find(|k| !valid_env_key(k)) {
+            return Err(ZeroError::Validation(format!("Invalid environment variable name {}", key)));
+        }
+
+        match options.runtime {
+            Runtime::Inline => {}
+            Runtime::Docker if code.trim().is_empty() => {
+                return Err(ZeroError::Validation("Docker functions require an image".into()));
+            }
+            Runtime::Docker => {}
+            #[cfg(feature = "wasm")]
+            Runtime::Wasm => func_runtime::wasm::validate(code)?,
+            #[cfg(not(feature = "wasm"))]
+            Runtime::Wasm => return Err(ZeroError::Validation("This build does not include the wasm runtime".into())),
+        }
+
         // Store function metadata in SQLite
-        // Schema: name (PK), handler, code_zip_path (placeholder for now we just store raw string or path)
         let conn = self.engine.db.lock();
         
         let sql = "CREATE TABLE IF NOT EXISTS functions (
             name TEXT PRIMARY KEY,
             handler TEXT NOT NULL,
-            code TEXT NOT NULL
+            code TEXT NOT NULL,
+            runtime TEXT NOT NULL DEFAULT 'inline',
+            environment TEXT NOT NULL DEFAULT '{}',
+            timeout_secs INTEGER NOT NULL DEFAULT 30,
+            memory_mb INTEGER NOT NULL DEFAULT 128
         )";
         conn.execute(sql, []).map_err(|e| ZeroError::Internal(e.to_string()))?;
 
-        let insert = "INSERT OR REPLACE INTO functions (name, handler, code) VALUES (?1, ?2, ?3)";
-        conn.execute(insert, zero_data_core::rusqlite::params![name, handler, code])
+        let environment = serde_json::to_string(&options.environment).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let insert = "INSERT OR REPLACE INTO functions (name, handler, code, runtime, environment, timeout_secs, memory_mb)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)";
+        conn.execute(insert, zero_data_core::rusqlite::params![name, handler, code, options.runtime.as_str(), environment, timeout_secs, memory_mb])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
             
         Ok(())
     }
 
+    pub async fn get_function(&self, name: &str) -> ZeroResult<FunctionConfiguration> {
+        self.load(name).map(|(config, _)| config)
+    }
+
+    /// Configurations of all deployed functions
+    pub async fn list_function_configurations(&self) -> ZeroResult<Vec<FunctionConfiguration>> {
+        let mut configs = Vec::new();
+        for name in self.list_functions().await? {
+            configs.push(self.get_function(&name).await?);
+        }
+        Ok(configs)
+    }
+
+    /// Configuration and code of a function
+    fn load(&self, name: &str) -> ZeroResult<(FunctionConfiguration, String)> {
+        let conn = self.engine.db.lock();
+        let row = conn.query_row(
+            "SELECT handler, runtime, environment, timeout_secs, memory_mb, code FROM functions WHERE name = ?1",
+            zero_data_core::rusqlite::params![name],
+            |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, String>(5)?,
+            )),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let (handler, runtime, environment, timeout_secs, memory_mb, code) =
+            row.ok_or_else(|| ZeroError::NotFound(format!("Function {} not found", name)))?;
+
+        let config = FunctionConfiguration {
+            name: name.to_string(),
+            handler,
+            runtime: runtime.parse()?,
+            environment: serde_json::from_str(&environment).map_err(|e| ZeroError::Internal(e.to_string()))?,
+            timeout_secs,
+            memory_mb,
+        };
+        Ok((config, code))
+    }
+
+    pub(crate) fn has_function(&self, name: &str) -> bool {
+        let conn = self.engine.db.lock();
+        conn.query_row("SELECT count(*) FROM functions WHERE name = ?1", zero_data_core::rusqlite::params![name], |row| row.get(0))
+            .unwrap_or(false)
+    }
+
     pub async fn list_functions(&self) -> ZeroResult<Vec<String>> {
         let conn = self.engine.db.lock();
         // Check if table exists first? Or just try query
@@ -50,66 +263,231 @@ impl FuncService {
         Ok(funcs)
     }
 
+    /// Delete a function with the records of its invocations
+    pub async fn delete_function(&self, name: &str) -> ZeroResult<()> {
+        if !self.has_function(name) {
+            return Err(ZeroError::NotFound(format!("Function {} not found", name)));
+        }
+        let conn = self.engine.db.lock();
+        Self::ensure_invocations_table(&conn)?;
+        conn.execute("DELETE FROM function_invocations WHERE function_name = ?1", zero_data_core::rusqlite::params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM functions WHERE name = ?1", zero_data_core::rusqlite::params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Invoke a function; runs longer than its timeout are aborted
     pub async fn invoke_function(&self, name: &str, payload: serde_json::Value) -> ZeroResult<serde_json::Value> {
+        // 1. Fetch function code (release the lock before running it)
+        let (config, code) = self.load(name)?;
+
+        // 2. Execute with the function's runtime
+        match config.runtime {
+            Runtime::Inline => func_runtime::invoke_inline(&config, &code, &payload).await,
+            Runtime::Docker => {
+                let namespaces = NamespaceService::new(self.engine.clone());
+                func_runtime::invoke_docker(&self.engine, &namespaces, &config, &code, &payload).await
+            }
+            #[cfg(feature = "wasm")]
+            Runtime::Wasm => {
+                tokio::task::spawn_blocking(move || func_runtime::wasm::invoke(&config, &code, &payload)).await
+                    .map_err(|e| ZeroError::Internal(e.to_string()))?
+            }
+            #[cfg(not(feature = "wasm"))]
+            Runtime::Wasm => Err(ZeroError::Validation("This build does not include the wasm runtime".into())),
+        }
+    }
+}
+
+impl FuncService {
+    fn ensure_invocations_table(conn: &zero_data_core::rusqlite::Connection) -> ZeroResult<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS function_invocations (
+                id TEXT PRIMARY KEY,
+                function_name TEXT NOT NULL,
+                status TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                result TEXT,
+                error TEXT,
+                created_at TEXT NOT NULL,
+                completed_at TEXT
+            )",
+            [],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Queue an invocation for the background executor and return its pending record
+    pub async fn invoke_function_async(&self, name: &str, payload: serde_json::Value) -> ZeroResult<FunctionInvocation> {
+        if !self.has_function(name) {
+            return Err(ZeroError::NotFound(format!("Function {} not found", name)));
+        }
+
+        let invocation = FunctionInvocation {
+            id: uuid::Uuid::new_v4().to_string(),
+            function_name: name.to_string(),
+            status: InvocationStatus::Pending,
+            payload,
+            result: None,
+            error: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            completed_at: None,
+        };
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_invocations_table(&conn)?;
+            conn.execute(
+                "INSERT INTO function_invocations (id, function_name, status, payload, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                zero_data_core::rusqlite::params![
+                    invocation.id, invocation.function_name, invocation.status.as_str(),
+                    invocation.payload.to_string(), invocation.created_at
+                ],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+
+        self.enqueue(&invocation.id)?;
+        Ok(invocation)
+    }
+
+    /// Invoke with the given type; `Event` returns the invocation record instead of the result
+    pub async fn invoke_function_with_type(&self, name: &str, payload: serde_json::Value, invocation_type: InvocationType) -> ZeroResult<serde_json::Value> {
+        match invocation_type {
+            InvocationType::RequestResponse => self.invoke_function(name, payload).await,
+            InvocationType::Event => Ok(serde_json::json!(self.invoke_function_async(name, payload).await?)),
+        }
+    }
+
+    pub async fn get_invocation(&self, name: &str, id: &str) -> ZeroResult<FunctionInvocation> {
+        self.query_invocations("function_name = ?1 AND id = ?2", zero_data_core::rusqlite::params![name, id])?
+            .pop()
+            .ok_or_else(|| ZeroError::NotFound(format!("Invocation {} of function {} not found", id, name)))
+    }
+
+    /// Asynchronous invocations of a function, oldest first
+    pub async fn list_invocations(&self, name: &str) -> ZeroResult<Vec<FunctionInvocation>> {
+        self.query_invocations("function_name = ?1", zero_data_core::rusqlite::params![name])
+    }
+
+    fn query_invocations(&self, filter: &str, params: impl zero_data_core::rusqlite::Params) -> ZeroResult<Vec<FunctionInvocation>> {
         let conn = self.engine.db.lock();
-        
-        // 1. Fetch function code
-        let mut stmt = conn.prepare("SELECT code, handler FROM functions WHERE name = ?1").map_err(|e| ZeroError::Internal(e.to_string()))?;
-        let mut rows = stmt.query(zero_data_core::rusqlite::params![name]).map_err(|e| ZeroError::Internal(e.to_string()))?;
-        
-        let (code, handler) = if let Some(row) = rows.next().map_err(|e| ZeroError::Internal(e.to_string()))? {
-            (
-                row.get::<_, String>(0).map_err(|e| ZeroError::Internal(e.to_string()))?,
-                row.get::<_, String>(1).map_err(|e| ZeroError::Internal(e.to_string()))?
-            )
-        } else {
-             return Err(ZeroError::NotFound(format!("Function {} not found", name)));
+        Self::ensure_invocations_table(&conn)?;
+        let sql = format!(
+            "SELECT id, function_name, status, payload, result, error, created_at, completed_at
+             FROM function_invocations WHERE {} ORDER BY created_at",
+            filter
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let rows = stmt.query_map(params, |row| Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, Option<String>>(5)?,
+            row.get::<_, String>(6)?,
+            row.get::<_, Option<String>>(7)?,
+        ))).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(id, function_name, status, payload, result, error, created_at, completed_at)| Ok(FunctionInvocation {
+                id,
+                function_name,
+                status: status.parse()?,
+                payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null),
+                result: result.and_then(|r| serde_json::from_str(&r).ok()),
+                error,
+                created_at,
+                completed_at,
+            }))
+            .collect()
+    }
+
+    /// Re-queue invocations the database records as pending or running. The engine database
+    /// lives in memory, so these only appear when a backup restore replaces it; call this
+    /// afterwards.
+    pub async fn resume_invocations(&self) -> ZeroResult<usize> {
+        let ids: Vec<String> = {
+            let conn = self.engine.db.lock();
+            Self::ensure_invocations_table(&conn)?;
+            let mut stmt = conn.prepare("SELECT id FROM function_invocations WHERE status IN ('Pending', 'Running') ORDER BY created_at")
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            let ids = stmt.query_map([], |row| row.get(0)).map_err(|e| ZeroError::Internal(e.to_string()))?
+                .collect::<Result<Vec<String>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+            ids
         };
+        for id in &ids {
+            self.enqueue(id)?;
+        }
+        Ok(ids.len())
+    }
 
-        // 2. Write to temp file
-        let _func_id = uuid::Uuid::new_v4().to_string();
-        let tmp_dir = std::env::temp_dir().join("zero_funcs").join(name);
-        std::fs::create_dir_all(&tmp_dir).map_err(|e| ZeroError::Internal(e.to_string()))?;
-        
-        let file_name = if handler.contains("py") { "main.py" } else { "index.js" };
-        let file_path = tmp_dir.join(file_name);
-        std::fs::write(&file_path, &code).map_err(|e| ZeroError::Internal(e.to_string()))?;
-
-        // 3. Execute
-        let output = if handler.contains("py") {
-             std::process::Command::new("python3")
-                .arg(&file_path)
-                .arg(payload.to_string())
-                .output()
-        } else {
-             // Default to Node
-             std::process::Command::new("node")
-                .arg(&file_path)
-                .arg(payload.to_string())
-                .output()
+    fn enqueue(&self, id: &str) -> ZeroResult<()> {
+        self.executor.get_or_init(|| self.spawn_executor())
+            .send(id.to_string())
+            .map_err(|_| ZeroError::Internal("Invocation executor stopped".into()))
+    }
+
+    /// Background task running queued invocations, at most `MAX_CONCURRENT_INVOCATIONS` at a time
+    fn spawn_executor(&self) -> tokio::sync::mpsc::UnboundedSender<String> {
+        let (sender, mut queue) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let service = self.clone();
+        tokio::spawn(async move {
+            let slots = Arc::new(tokio::sync::Semaphore::new(MAX_CONCURRENT_INVOCATIONS));
+            while let Some(id) = queue.recv().await {
+                let Ok(slot) = slots.clone().acquire_owned().await else { break };
+                let service = service.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = service.run_invocation(&id).await {
+                        tracing::error!("Failed to run invocation {}: {}", id, e);
+                    }
+                    drop(slot);
+                });
+            }
+        });
+        sender
+    }
+
+    async fn run_invocation(&self, id: &str) -> ZeroResult<()> {
+        let (name, payload) = {
+            let conn = self.engine.db.lock();
+            let (name, payload): (String, String) = conn.query_row(
+                "SELECT function_name, payload FROM function_invocations WHERE id = ?1",
+                zero_data_core::rusqlite::params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            conn.execute(
+                "UPDATE function_invocations SET status = ?1 WHERE id = ?2",
+                zero_data_core::rusqlite::params![InvocationStatus::Running.as_str(), id],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            (name, serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null))
         };
 
-        match output {
-            Ok(out) => {
-                let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&out.stderr).to_string();
-                Ok(json!({
-                    "status": "Executed",
-                    "function": name,
-                    "stdout": stdout,
-                    "stderr": stderr,
-                    "exit_code": out.status.code()
-                }))
+        let outcome = self.invoke_function(&name, payload).await;
+        let (status, result, error) = match outcome {
+            Ok(result) => match result["exit_code"].as_i64() {
+                Some(code) if code != 0 => (
+                    InvocationStatus::Failed,
+                    Some(result.to_string()),
+                    Some(format!("Function exited with code {}", code)),
+                ),
+                _ => (InvocationStatus::Succeeded, Some(result.to_string()), None),
             },
-            Err(e) => {
-                // If runtime not found, fallback to Mock for stability
-                Ok(json!({
-                    "status": "MockExecuted",
-                    "function": name,
-                    "warning": format!("Runtime execution failed: {}. Falling back to mock.", e),
-                    "result": "Hello from ZeroFunc (Mock)"
-                }))
-            }
-        }
+            Err(e) => (InvocationStatus::Failed, None, Some(e.to_string())),
+        };
+        let conn = self.engine.db.lock();
+        conn.execute(
+            "UPDATE function_invocations SET status = ?1, result = ?2, error = ?3, completed_at = ?4 WHERE id = ?5",
+            zero_data_core::rusqlite::params![status.as_str(), result, error, chrono::Utc::now().to_rfc3339(), id],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
     }
 }
+
+/// Environment variable names: a letter or underscore followed by letters, digits or underscores
+fn valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
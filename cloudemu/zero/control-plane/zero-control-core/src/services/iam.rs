@@ -1,8 +1,123 @@
 use zero_control_spi::{ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
 use serde_json::json;
 
+/// Scheme name at the start of a signed `Authorization` header:
+/// `ZERO-HMAC-SHA256 Credential=<access key id>, SignedHeaders=x-zero-date, Signature=<hex>`
+pub const SIGNING_ALGORITHM: &str = "ZERO-HMAC-SHA256";
+
+/// Request timestamp covered by the signature, formatted `YYYYMMDDTHHMMSSZ`
+pub const DATE_HEADER: &str = "x-zero-date";
+
+/// Signed requests older or newer than this are rejected to limit replays
+pub(crate) const MAX_CLOCK_SKEW_SECS: i64 = 15 * 60;
+
+/// Access keys a user may hold at once
+const MAX_ACCESS_KEYS_PER_USER: i64 = 2;
+
+/// Session token presented alongside temporary credentials from `assume_role`
+pub const SECURITY_TOKEN_HEADER: &str = "x-zero-security-token";
+
+/// Lifetime of role session credentials when the caller does not ask for one
+pub const DEFAULT_SESSION_DURATION_SECS: i64 = 3600;
+
+/// Allowed range for requested session lifetimes
+const MIN_SESSION_DURATION_SECS: i64 = 900;
+const MAX_SESSION_DURATION_SECS: i64 = 12 * 3600;
+
+/// Principals authenticated with role session credentials are assumed-role ARNs with this prefix
+const ASSUMED_ROLE_ARN_PREFIX: &str = "arn:zero:sts::000000:assumed-role/";
+
+/// Set to [`UNSIGNED_PAYLOAD`] by clients that stream a body instead of hashing it up front
+pub const CONTENT_SHA256_HEADER: &str = "x-zero-content-sha256";
+
+/// Payload hash of requests whose body is not covered by the signature
+pub const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// User owning the access key given to the server at startup
+pub const ROOT_USER: &str = "root";
+
+/// Text signed by clients: algorithm, date, method, path and the hex SHA-256 of the body, one per line.
+/// `path` is the request target as sent, including the `?` and query string when there is one.
+pub fn string_to_sign(date: &str, method: &str, path: &str, body: &[u8]) -> String {
+    string_to_sign_with_payload_hash(date, method, path, &hex::encode(Sha256::digest(body)))
+}
+
+/// Text signed by clients with the payload hash given directly, e.g. [`UNSIGNED_PAYLOAD`]
+pub fn string_to_sign_with_payload_hash(date: &str, method: &str, path: &str, payload_hash: &str) -> String {
+    format!("{}\n{}\n{}\n{}\n{}", SIGNING_ALGORITHM, date, method.to_uppercase(), path, payload_hash)
+}
+
+/// Whether a signed request left its body out of the signature so it can be streamed
+pub fn is_unsigned_payload(headers: &HashMap<String, String>) -> bool {
+    headers.iter().any(|(k, v)| k.eq_ignore_ascii_case(CONTENT_SHA256_HEADER) && v == UNSIGNED_PAYLOAD)
+}
+
+/// Access key of a user; the secret is only returned when the key is created
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccessKey {
+    pub user_name: String,
+    pub access_key_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    pub status: String,
+    pub create_date: String,
+}
+
+/// Temporary credentials issued by `assume_role`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SessionCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: String,
+    pub expiration: String,
+}
+
+/// Result of `assume_role`; `assumed_role_arn` is the principal the credentials act as
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AssumedRole {
+    pub credentials: SessionCredentials,
+    pub assumed_role_arn: String,
+}
+
+/// Action and resource a request is authorized against: `<service>:<METHOD>` and the request path,
+/// e.g. `store:PUT` on `/v1/store/buckets/photos`
+pub fn request_action(method: &str, path: &str) -> (String, String) {
+    let service = path.split('/').filter(|s| !s.is_empty()).nth(1).unwrap_or("");
+    (format!("{}:{}", service, method.to_uppercase()), path.to_string())
+}
+
+/// Policy value matcher: exact, `*`, or a trailing-`*` prefix such as `store:*`
+fn pattern_matches(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+/// Whether any `Allow` statement of a policy document covers the action on the resource.
+/// `Action` and `Resource` may be a string or a list of strings.
+fn policy_allows(policy: &serde_json::Value, action: &str, resource: &str) -> bool {
+    let matches = |field: &serde_json::Value, value: &str| match field {
+        serde_json::Value::String(p) => pattern_matches(p, value),
+        serde_json::Value::Array(ps) => ps.iter().filter_map(|p| p.as_str()).any(|p| pattern_matches(p, value)),
+        _ => false,
+    };
+
+    policy["Statement"].as_array().is_some_and(|statements| statements.iter().any(|stmt| {
+        stmt["Effect"].as_str() == Some("Allow")
+            && matches(&stmt["Action"], action)
+            && matches(&stmt["Resource"], resource)
+    }))
+}
+
 pub struct IamService {
     engine: Arc<ZeroEngine>,
 }
@@ -17,17 +132,26 @@ impl IamService {
     }
 
     pub async fn attach_user_policy(&self, username: &str, policy_json: &str) -> ZeroResult<()> {
-        let conn = self.engine.db.lock();
+        self.set_policy("users", "username", "User", username, policy_json)
+    }
+
+    /// Set the permissions policy that scopes sessions of the role
+    pub async fn attach_role_policy(&self, rolename: &str, policy_json: &str) -> ZeroResult<()> {
+        self.set_policy("roles", "rolename", "Role", rolename, policy_json)
+    }
+
+    fn set_policy(&self, table: &str, pk_col: &str, kind: &str, name: &str, policy_json: &str) -> ZeroResult<()> {
         // Validate JSON
         let _parsed: serde_json::Value = serde_json::from_str(policy_json)
              .map_err(|e| ZeroError::Validation(format!("Invalid JSON policy: {}", e)))?;
 
-        let update = "UPDATE users SET policy = ?1 WHERE username = ?2";
-        let affected = conn.execute(update, zero_data_core::rusqlite::params![policy_json, username])
-            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let conn = self.engine.db.lock();
+        let update = format!("UPDATE {} SET policy = ?1 WHERE {} = ?2", table, pk_col);
+        let affected = conn.execute(&update, zero_data_core::rusqlite::params![policy_json, name])
+            .map_err(|_| ZeroError::NotFound(format!("{} {} not found", kind, name)))?;
         
         if affected == 0 {
-            return Err(ZeroError::NotFound(format!("User {} not found", username)));
+            return Err(ZeroError::NotFound(format!("{} {} not found", kind, name)));
         }
         Ok(())
     }
@@ -52,6 +176,253 @@ impl IamService {
         self.list_entities("groups", "groupname", "GroupName")
     }
 
+    fn ensure_access_keys_table(conn: &zero_data_core::rusqlite::Connection) -> ZeroResult<()> {
+        conn.execute("CREATE TABLE IF NOT EXISTS access_keys (
+            access_key_id TEXT PRIMARY KEY,
+            username TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn create_access_key(&self, username: &str) -> ZeroResult<AccessKey> {
+        if !self.list_users().await?.iter().any(|u| u["UserName"] == username) {
+            return Err(ZeroError::NotFound(format!("User {} not found", username)));
+        }
+        let conn = self.engine.db.lock();
+        Self::ensure_access_keys_table(&conn)?;
+
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM access_keys WHERE username = ?1",
+            zero_data_core::rusqlite::params![username],
+            |row| row.get(0),
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if count >= MAX_ACCESS_KEYS_PER_USER {
+            return Err(ZeroError::Validation(format!(
+                "User {} already has {} access keys", username, MAX_ACCESS_KEYS_PER_USER
+            )));
+        }
+
+        let key = AccessKey {
+            user_name: username.to_string(),
+            access_key_id: format!("ZAK{}", &uuid::Uuid::new_v4().simple().to_string().to_uppercase()[..17]),
+            secret_access_key: Some(format!("{}{}", uuid::Uuid::new_v4().simple(), &uuid::Uuid::new_v4().simple().to_string()[..8])),
+            status: "Active".to_string(),
+            create_date: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        };
+        conn.execute(
+            "INSERT INTO access_keys (access_key_id, username, secret, status, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            zero_data_core::rusqlite::params![key.access_key_id, key.user_name, key.secret_access_key, key.status, key.create_date],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(key)
+    }
+
+    /// Register the access key of the root user, e.g. from the environment at startup, so that a
+    /// server requiring signed requests can be reached before any other key exists.
+    /// Replaces the secret if the key is already registered.
+    pub async fn bootstrap_root_key(&self, access_key_id: &str, secret_access_key: &str) -> ZeroResult<()> {
+        if access_key_id.is_empty() || secret_access_key.is_empty() {
+            return Err(ZeroError::Validation("Root access key ID and secret must not be empty".into()));
+        }
+        self.create_user(ROOT_USER).await?;
+        let conn = self.engine.db.lock();
+        Self::ensure_access_keys_table(&conn)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO access_keys (access_key_id, username, secret, status, created_at) VALUES (?1, ?2, ?3, 'Active', ?4)",
+            zero_data_core::rusqlite::params![
+                access_key_id, ROOT_USER, secret_access_key,
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Access keys of a user, without their secrets
+    pub async fn list_access_keys(&self, username: &str) -> ZeroResult<Vec<AccessKey>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_access_keys_table(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT access_key_id, status, created_at FROM access_keys WHERE username = ?1 ORDER BY created_at, access_key_id"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let keys = stmt.query_map(zero_data_core::rusqlite::params![username], |row| {
+            Ok(AccessKey {
+                user_name: username.to_string(),
+                access_key_id: row.get(0)?,
+                secret_access_key: None,
+                status: row.get(1)?,
+                create_date: row.get(2)?,
+            })
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(keys)
+    }
+
+    pub async fn delete_access_key(&self, username: &str, access_key_id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_access_keys_table(&conn)?;
+        let affected = conn.execute(
+            "DELETE FROM access_keys WHERE username = ?1 AND access_key_id = ?2",
+            zero_data_core::rusqlite::params![username, access_key_id],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if affected == 0 {
+            return Err(ZeroError::NotFound(format!("Access key {} not found for user {}", access_key_id, username)));
+        }
+        Ok(())
+    }
+
+    fn ensure_sessions_table(conn: &zero_data_core::rusqlite::Connection) -> ZeroResult<()> {
+        conn.execute("CREATE TABLE IF NOT EXISTS role_sessions (
+            access_key_id TEXT PRIMARY KEY,
+            secret TEXT NOT NULL,
+            session_token TEXT NOT NULL,
+            rolename TEXT NOT NULL,
+            session_name TEXT NOT NULL,
+            expiration TEXT NOT NULL
+        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Issue temporary credentials that act as `rolename`, valid for `duration_secs`
+    /// (default one hour, between 15 minutes and 12 hours).
+    pub async fn assume_role(&self, rolename: &str, session_name: &str, duration_secs: Option<i64>) -> ZeroResult<AssumedRole> {
+        let duration = duration_secs.unwrap_or(DEFAULT_SESSION_DURATION_SECS);
+        if !(MIN_SESSION_DURATION_SECS..=MAX_SESSION_DURATION_SECS).contains(&duration) {
+            return Err(ZeroError::Validation(format!(
+                "DurationSeconds must be between {} and {}", MIN_SESSION_DURATION_SECS, MAX_SESSION_DURATION_SECS
+            )));
+        }
+        let valid_name = (2..=64).contains(&session_name.len())
+            && session_name.chars().all(|c| c.is_ascii_alphanumeric() || "_+=,.@-".contains(c));
+        if !valid_name {
+            return Err(ZeroError::Validation(format!("Invalid RoleSessionName: {}", session_name)));
+        }
+        if !self.list_roles().await?.iter().any(|r| r["RoleName"] == rolename) {
+            return Err(ZeroError::NotFound(format!("Role {} not found", rolename)));
+        }
+
+        let now = chrono::Utc::now();
+        let credentials = SessionCredentials {
+            access_key_id: format!("ZSK{}", &uuid::Uuid::new_v4().simple().to_string().to_uppercase()[..17]),
+            secret_access_key: format!("{}{}", uuid::Uuid::new_v4().simple(), &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            session_token: format!("{}{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+            expiration: (now + chrono::Duration::seconds(duration)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        };
+
+        let conn = self.engine.db.lock();
+        Self::ensure_sessions_table(&conn)?;
+        // Expired sessions can never authenticate again
+        conn.execute(
+            "DELETE FROM role_sessions WHERE expiration < ?1",
+            zero_data_core::rusqlite::params![now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute(
+            "INSERT INTO role_sessions (access_key_id, secret, session_token, rolename, session_name, expiration) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            zero_data_core::rusqlite::params![
+                credentials.access_key_id, credentials.secret_access_key, credentials.session_token,
+                rolename, session_name, credentials.expiration
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        Ok(AssumedRole {
+            credentials,
+            assumed_role_arn: format!("{}{}/{}", ASSUMED_ROLE_ARN_PREFIX, rolename, session_name),
+        })
+    }
+
+    /// Whether an authenticated principal is a role session, whose requests are limited by the role's policy
+    pub fn is_role_session(principal: &str) -> bool {
+        principal.starts_with(ASSUMED_ROLE_ARN_PREFIX)
+    }
+
+    /// Signing secret and principal of an unexpired role session; the session token must match
+    fn session_secret(&self, access_key_id: &str, session_token: Option<&str>) -> ZeroResult<(String, String)> {
+        let denied = |reason: &str| ZeroError::Unauthorized(reason.to_string());
+        let conn = self.engine.db.lock();
+        Self::ensure_sessions_table(&conn)?;
+        let (secret, token, rolename, session_name, expiration) = conn.query_row(
+            "SELECT secret, session_token, rolename, session_name, expiration FROM role_sessions WHERE access_key_id = ?1",
+            zero_data_core::rusqlite::params![access_key_id],
+            |row| Ok((
+                row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?, row.get::<_, String>(4)?,
+            )),
+        ).map_err(|_| denied("The access key ID does not exist"))?;
+
+        if session_token != Some(token.as_str()) {
+            return Err(denied("The security token included in the request is invalid"));
+        }
+        let expires_at = chrono::DateTime::parse_from_rfc3339(&expiration)
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if expires_at <= chrono::Utc::now() {
+            return Err(denied("The security token included in the request is expired"));
+        }
+        Ok((secret, format!("{}{}/{}", ASSUMED_ROLE_ARN_PREFIX, rolename, session_name)))
+    }
+
+    /// Principal and signing secret of an active access key or an unexpired role session
+    pub(crate) fn signing_secret(&self, access_key_id: &str, session_token: Option<&str>) -> ZeroResult<(String, String)> {
+        let user_key = {
+            let conn = self.engine.db.lock();
+            Self::ensure_access_keys_table(&conn)?;
+            conn.query_row(
+                "SELECT username, secret FROM access_keys WHERE access_key_id = ?1 AND status = 'Active'",
+                zero_data_core::rusqlite::params![access_key_id],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+            ).ok()
+        };
+        match user_key {
+            Some(key) => Ok(key),
+            None => {
+                let (secret, principal) = self.session_secret(access_key_id, session_token)?;
+                Ok((principal, secret))
+            }
+        }
+    }
+
+    /// Verify the signed `Authorization` header of a request and return the calling principal:
+    /// the user name for access keys, or the assumed-role ARN for role session credentials.
+    /// Unsigned requests yield `None`; `path` is the raw path and query string the client signed.
+    /// Requests marked with [`UNSIGNED_PAYLOAD`] sign the marker instead of the body hash.
+    pub fn authenticate(&self, method: &str, path: &str, headers: &HashMap<String, String>, body: &[u8]) -> ZeroResult<Option<String>> {
+        let header = |name: &str| headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str());
+        let Some(authorization) = header("authorization") else {
+            return Ok(None);
+        };
+        let denied = |reason: &str| ZeroError::Unauthorized(reason.to_string());
+
+        let fields = authorization.strip_prefix(SIGNING_ALGORITHM)
+            .ok_or_else(|| denied("Unsupported authorization scheme"))?;
+        let field = |name: &str| fields.split(',')
+            .filter_map(|part| part.trim().split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v);
+        let access_key_id = field("Credential").ok_or_else(|| denied("Missing Credential"))?;
+        let signature = field("Signature").ok_or_else(|| denied("Missing Signature"))?;
+        let signature = hex::decode(signature).map_err(|_| denied("Malformed Signature"))?;
+
+        let date = header(DATE_HEADER).ok_or_else(|| denied("Missing X-Zero-Date header"))?;
+        let signed_at = chrono::NaiveDateTime::parse_from_str(date, "%Y%m%dT%H%M%SZ")
+            .map_err(|_| denied("Malformed X-Zero-Date header"))?
+            .and_utc();
+        if (chrono::Utc::now() - signed_at).num_seconds().abs() > MAX_CLOCK_SKEW_SECS {
+            return Err(denied("Request timestamp is too far from the server time"));
+        }
+
+        let (principal, secret) = self.signing_secret(access_key_id, header(SECURITY_TOKEN_HEADER))?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let string_to_sign = if is_unsigned_payload(headers) {
+            string_to_sign_with_payload_hash(date, method, path, UNSIGNED_PAYLOAD)
+        } else {
+            string_to_sign(date, method, path, body)
+        };
+        mac.update(string_to_sign.as_bytes());
+        mac.verify_slice(&signature).map_err(|_| denied("The request signature does not match"))?;
+        Ok(Some(principal))
+    }
+
     // Generic helper to reduce code duplication
     async fn create_entity(&self, table: &str, pk_col: &str, name: &str) -> ZeroResult<()> {
         let conn = self.engine.db.lock();
@@ -97,43 +468,23 @@ impl IamService {
 
     // A simple mock of AWS Policy Eval Logic
     // JSON: { "Statement": [ { "Effect": "Allow", "Action": "*", "Resource": "*" } ] }
-    pub fn verify_permission(&self, username: &str, action: &str, resource: &str) -> bool {
-        let conn = self.engine.db.lock();
-        
-        let mut stmt = match conn.prepare("SELECT policy FROM users WHERE username = ?1") {
-            Ok(s) => s,
-            Err(_) => return false,
-        };
-        
-        // Retrieve policy
-        let policy_str: String = match stmt.query_row(zero_data_core::rusqlite::params![username], |row| row.get(0)) {
-            Ok(p) => p,
-            Err(_) => return false, // User not found or error
+    /// `principal` is a user name or, for role sessions, the assumed-role ARN whose role policy applies.
+    pub fn verify_permission(&self, principal: &str, action: &str, resource: &str) -> bool {
+        let (sql, name) = match principal.strip_prefix(ASSUMED_ROLE_ARN_PREFIX) {
+            Some(role_session) => ("SELECT policy FROM roles WHERE rolename = ?1", role_session.split('/').next().unwrap_or("")),
+            None => ("SELECT policy FROM users WHERE username = ?1", principal),
         };
 
-        // Parse JSON
-        let policy: serde_json::Value = match serde_json::from_str(&policy_str) {
+        let conn = self.engine.db.lock();
+        // Retrieve policy; unknown principals are denied
+        let policy_str: String = match conn.query_row(sql, zero_data_core::rusqlite::params![name], |row| row.get(0)) {
             Ok(p) => p,
             Err(_) => return false,
         };
 
-        // Iterate Statements
-        if let Some(statements) = policy["Statement"].as_array() {
-            for stmt in statements {
-                let effect = stmt["Effect"].as_str().unwrap_or("Deny");
-                let stmt_action = stmt["Action"].as_str().unwrap_or("");
-                let stmt_resource = stmt["Resource"].as_str().unwrap_or("");
-
-                // Super Simple Matcher (supports "*" wildcard only)
-                let action_match = stmt_action == "*" || stmt_action == action;
-                let resource_match = stmt_resource == "*" || stmt_resource == resource;
-
-                if effect == "Allow" && action_match && resource_match {
-                    return true;
-                }
-            }
+        match serde_json::from_str(&policy_str) {
+            Ok(policy) => policy_allows(&policy, action, resource),
+            Err(_) => false,
         }
-
-        false
     }
 }
@@ -0,0 +1,203 @@
+//! Images cached by the compute driver of this server
+//!
+//! `POST /v1/images/pull` pulls an image before the workloads that need it are created, so they
+//! start without waiting for a download. Images pulled this way or used by a workload created on
+//! this server are tracked with the time they were last used, which for an image in use is the
+//! time its last workload was deleted. Garbage collection only removes tracked images, so images
+//! the driver got some other way are never touched:
+//! - `ZeroProvider::spawn_image_gc` removes tracked images that no workload uses and that were
+//!   last used more than [`IMAGE_RETENTION`] ago, every [`IMAGE_GC_TICK`]
+//! - `POST /v1/images/prune` removes them now, by default however recently they were used
+//!
+//! Images a driver refuses to remove, such as those of containers started outside ZeroCloud,
+//! stay cached and tracked.
+
+use zero_control_spi::{image_reference, ImageInfo, ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::events::{IMAGE_PULLED, IMAGE_REMOVED};
+use zero_data_core::rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often `ZeroProvider::spawn_image_gc` looks for unused images
+pub const IMAGE_GC_TICK: Duration = Duration::from_secs(60 * 60);
+/// How long `ZeroProvider::spawn_image_gc` keeps an unused image after its last use
+pub const IMAGE_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// An image in the driver's cache
+#[derive(Debug, Clone, Serialize)]
+pub struct CachedImage {
+    #[serde(flatten)]
+    pub image: ImageInfo,
+    /// Used by a workload created on this server
+    pub in_use: bool,
+    /// When the image was last pulled or used; untracked images, which garbage collection
+    /// leaves alone, have none
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Images removed by a garbage collection
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PruneReport {
+    pub removed: Vec<String>,
+    pub reclaimed_bytes: u64,
+}
+
+/// What the server knows about the images it pulled or ran
+struct Tracked {
+    /// When each tracked image was last used
+    last_used: HashMap<String, DateTime<Utc>>,
+    /// Images workloads use
+    in_use: HashSet<String>,
+}
+
+fn db_error(e: zero_data_core::rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time).ok().map(|time| time.with_timezone(&Utc))
+}
+
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS images (
+            reference TEXT PRIMARY KEY,
+            last_used TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS workload_images (
+            workload_id TEXT PRIMARY KEY,
+            reference TEXT NOT NULL
+        );
+    ").map_err(db_error)
+}
+
+#[derive(Clone)]
+pub struct ImageService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl ImageService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    /// Pull an image into the cache at `now`
+    pub async fn pull(&self, reference: &str, now: DateTime<Utc>) -> ZeroResult<ImageInfo> {
+        if reference.trim().is_empty() {
+            return Err(ZeroError::Validation("Image reference must not be empty".into()));
+        }
+        let image = self.engine.compute.pull_image(reference).await?;
+        self.touch(&image.reference, now)?;
+        self.engine.events.publish(IMAGE_PULLED, &image.reference, json!({ "size_bytes": image.size_bytes }));
+        Ok(image)
+    }
+
+    /// Record that a workload created at `now` uses `image`
+    pub fn use_image(&self, workload_id: &str, image: &str, now: DateTime<Utc>) -> ZeroResult<()> {
+        let reference = image_reference(image);
+        self.touch(&reference, now)?;
+        let conn = self.engine.db.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO workload_images (workload_id, reference) VALUES (?1, ?2)",
+            params![workload_id, reference],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Record that a workload deleted at `now` no longer uses its image
+    pub fn release(&self, workload_id: &str, now: DateTime<Utc>) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let reference: Option<String> = conn.query_row(
+            "SELECT reference FROM workload_images WHERE workload_id = ?1",
+            params![workload_id],
+            |row| row.get(0),
+        ).ok();
+        if let Some(reference) = reference {
+            conn.execute("DELETE FROM workload_images WHERE workload_id = ?1", params![workload_id]).map_err(db_error)?;
+            conn.execute("UPDATE images SET last_used = ?1 WHERE reference = ?2", params![now.to_rfc3339(), reference]).map_err(db_error)?;
+        }
+        Ok(())
+    }
+
+    /// Every image in the driver's cache, tracked or not
+    pub async fn list(&self) -> ZeroResult<Vec<CachedImage>> {
+        let images = self.engine.compute.list_images().await?;
+        let tracked = self.tracked()?;
+        let mut cached: Vec<CachedImage> = images.into_iter().map(|image| CachedImage {
+            in_use: tracked.in_use.contains(&image.reference),
+            last_used: tracked.last_used.get(&image.reference).copied(),
+            image,
+        }).collect();
+        cached.sort_by(|a, b| a.image.reference.cmp(&b.image.reference));
+        Ok(cached)
+    }
+
+    /// Remove the tracked images no workload uses that were last used at least `unused_for`
+    /// before `now`
+    pub async fn prune(&self, unused_for: Duration, now: DateTime<Utc>) -> ZeroResult<PruneReport> {
+        let tracked = self.tracked()?;
+        let cutoff = now - chrono::Duration::from_std(unused_for).map_err(|e| ZeroError::Validation(e.to_string()))?;
+        let candidates: Vec<&String> = tracked.last_used.iter()
+            .filter(|(reference, last_used)| !tracked.in_use.contains(*reference) && **last_used <= cutoff)
+            .map(|(reference, _)| reference)
+            .collect();
+        let mut report = PruneReport::default();
+        if candidates.is_empty() {
+            return Ok(report);
+        }
+
+        let sizes: HashMap<String, u64> = self.engine.compute.list_images().await?
+            .into_iter()
+            .map(|image| (image.reference, image.size_bytes))
+            .collect();
+        for reference in candidates {
+            // Images removed from the driver some other way are only forgotten
+            if let Some(size_bytes) = sizes.get(reference) {
+                if let Err(e) = self.engine.compute.remove_image(reference).await {
+                    tracing::warn!("Cannot remove unused image {}: {}", reference, e);
+                    continue;
+                }
+                report.reclaimed_bytes += size_bytes;
+                report.removed.push(reference.clone());
+                self.engine.events.publish(IMAGE_REMOVED, reference, json!({ "size_bytes": size_bytes }));
+            }
+            self.engine.db.lock().execute("DELETE FROM images WHERE reference = ?1", params![reference]).map_err(db_error)?;
+        }
+        report.removed.sort();
+        Ok(report)
+    }
+
+    fn touch(&self, reference: &str, now: DateTime<Utc>) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.execute(
+            "INSERT INTO images (reference, last_used) VALUES (?1, ?2)
+             ON CONFLICT(reference) DO UPDATE SET last_used = excluded.last_used",
+            params![reference, now.to_rfc3339()],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    fn tracked(&self) -> ZeroResult<Tracked> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let mut stmt = conn.prepare("SELECT reference, last_used FROM images").map_err(db_error)?;
+        let last_used = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(db_error)?
+            .filter_map(|row| row.ok())
+            .filter_map(|(reference, last_used)| Some((reference, parse_time(&last_used)?)))
+            .collect();
+        let mut stmt = conn.prepare("SELECT reference FROM workload_images").map_err(db_error)?;
+        let in_use = stmt.query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_error)?
+            .filter_map(|row| row.ok())
+            .collect();
+        Ok(Tracked { last_used, in_use })
+    }
+}
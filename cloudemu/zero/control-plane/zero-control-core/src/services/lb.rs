@@ -1,16 +1,87 @@
+use super::lb_runtime::{self, ListenerProtocol, TARGET_HEALTHY, TARGET_INITIAL, TARGET_UNHEALTHY};
 use zero_control_spi::{ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::Connection;
 use std::sync::Arc;
 use serde_json::json;
 use std::collections::HashMap;
 use tokio::sync::Mutex;
-use axum::{
-    extract::Request,
-    response::IntoResponse,
-    routing::Router,
-};
 use reqwest::Client;
+use serde::{Serialize, Deserialize};
+use zero_data_core::rusqlite::OptionalExtension;
 
+pub const DEFAULT_HEALTH_CHECK_PATH: &str = "/health";
+pub const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u32 = 10;
+pub const MAX_HEALTH_CHECK_INTERVAL_SECS: u32 = 300;
+pub const DEFAULT_HEALTHY_THRESHOLD: u32 = 3;
+pub const DEFAULT_UNHEALTHY_THRESHOLD: u32 = 2;
+pub const MAX_HEALTH_CHECK_THRESHOLD: u32 = 10;
+
+/// Optional health check settings applied when creating a target group
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthCheckOptions {
+    /// Path probed on HTTP targets (default `/health`)
+    pub path: Option<String>,
+    /// Seconds between checks of each target (1-300, default 10)
+    pub interval_secs: Option<u32>,
+    /// Consecutive passed checks before a target is healthy (1-10, default 3)
+    pub healthy_threshold: Option<u32>,
+    /// Consecutive failed checks before a target is unhealthy (1-10, default 2)
+    pub unhealthy_threshold: Option<u32>,
+}
+
+/// Health check configuration of a target group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthCheckConfig {
+    pub path: String,
+    pub interval_secs: u32,
+    pub healthy_threshold: u32,
+    pub unhealthy_threshold: u32,
+}
+
+impl HealthCheckOptions {
+    fn resolve(self) -> ZeroResult<HealthCheckConfig> {
+        let path = self.path.unwrap_or_else(|| DEFAULT_HEALTH_CHECK_PATH.to_string());
+        if !path.starts_with('/') {
+            return Err(ZeroError::Validation(format!("Health check path must start with '/': {}", path)));
+        }
+        let interval_secs = self.interval_secs.unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_SECS);
+        if !(1..=MAX_HEALTH_CHECK_INTERVAL_SECS).contains(&interval_secs) {
+            return Err(ZeroError::Validation(format!(
+                "Health check interval must be between 1 and {} seconds", MAX_HEALTH_CHECK_INTERVAL_SECS
+            )));
+        }
+        let threshold = |value: Option<u32>, default: u32, name: &str| {
+            let value = value.unwrap_or(default);
+            if (1..=MAX_HEALTH_CHECK_THRESHOLD).contains(&value) {
+                Ok(value)
+            } else {
+                Err(ZeroError::Validation(format!("{} threshold must be between 1 and {}", name, MAX_HEALTH_CHECK_THRESHOLD)))
+            }
+        };
+        Ok(HealthCheckConfig {
+            path,
+            interval_secs,
+            healthy_threshold: threshold(self.healthy_threshold, DEFAULT_HEALTHY_THRESHOLD, "Healthy")?,
+            unhealthy_threshold: threshold(self.unhealthy_threshold, DEFAULT_UNHEALTHY_THRESHOLD, "Unhealthy")?,
+        })
+    }
+}
+
+/// A registered target due for a health check
+struct TargetCheck {
+    group_arn: String,
+    target_id: String,
+    port: u16,
+    status: String,
+    successes: u32,
+    failures: u32,
+    protocol: String,
+    health_check: HealthCheckConfig,
+}
+
+#[derive(Clone)]
 pub struct LbService {
     engine: Arc<ZeroEngine>,
     listeners: Arc<Mutex<HashMap<u16, tokio::task::JoinHandle<()>>>>,
@@ -28,38 +99,7 @@ impl LbService {
 
     pub async fn create_load_balancer(&self, name: &str, lb_type: &str) -> ZeroResult<serde_json::Value> {
         let conn = self.engine.db.lock();
-        
-        // Ensure tables exist
-        conn.execute("CREATE TABLE IF NOT EXISTS load_balancers (
-            name TEXT PRIMARY KEY,
-            type TEXT,
-            dns_name TEXT,
-            status TEXT
-        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
-
-        conn.execute("CREATE TABLE IF NOT EXISTS listeners (
-            id TEXT PRIMARY KEY,
-            lb_name TEXT,
-            port INTEGER,
-            protocol TEXT,
-            target_group_arn TEXT
-        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
-
-        conn.execute("CREATE TABLE IF NOT EXISTS target_groups (
-            arn TEXT PRIMARY KEY,
-            name TEXT,
-            port INTEGER,
-            protocol TEXT,
-            health_check_path TEXT
-        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
-
-        conn.execute("CREATE TABLE IF NOT EXISTS targets (
-            group_arn TEXT,
-            target_id TEXT,
-            port INTEGER,
-            status TEXT,
-            PRIMARY KEY(group_arn, target_id)
-        )", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        ensure_tables(&conn)?;
 
         let dns_name = format!("{}.lb.zero.local", name);
         let status = "active";
@@ -77,66 +117,204 @@ impl LbService {
     }
 
     pub async fn create_target_group(&self, name: &str, port: i32, protocol: &str) -> ZeroResult<String> {
+        self.create_target_group_with_options(name, port, protocol, HealthCheckOptions::default()).await
+    }
+
+    pub async fn create_target_group_with_options(&self, name: &str, port: i32, protocol: &str, health_check: HealthCheckOptions) -> ZeroResult<String> {
+        let protocol: ListenerProtocol = protocol.parse()?;
+        let health_check = health_check.resolve()?;
         let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
         let arn = format!("arn:zero:elasticloadbalancing:000000:targetgroup/{}/{}", name, uuid::Uuid::new_v4());
         
-        let sql = "INSERT INTO target_groups (arn, name, port, protocol, health_check_path) VALUES (?1, ?2, ?3, ?4, ?5)";
-        conn.execute(sql, zero_data_core::rusqlite::params![arn, name, port, protocol, "/health"])
-            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let sql = "INSERT INTO target_groups (arn, name, port, protocol, health_check_path, health_check_interval, healthy_threshold, unhealthy_threshold)
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+        conn.execute(sql, zero_data_core::rusqlite::params![
+            arn, name, port, protocol.as_str(), health_check.path,
+            health_check.interval_secs, health_check.healthy_threshold, health_check.unhealthy_threshold
+        ]).map_err(|e| ZeroError::Internal(e.to_string()))?;
         
         Ok(arn)
     }
 
+    pub async fn get_health_check(&self, group_arn: &str) -> ZeroResult<HealthCheckConfig> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.query_row(
+            "SELECT health_check_path, health_check_interval, healthy_threshold, unhealthy_threshold FROM target_groups WHERE arn = ?1",
+            [group_arn],
+            |row| Ok(HealthCheckConfig {
+                path: row.get(0)?,
+                interval_secs: row.get(1)?,
+                healthy_threshold: row.get(2)?,
+                unhealthy_threshold: row.get(3)?,
+            }),
+        ).optional()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .ok_or_else(|| ZeroError::NotFound(format!("Target group {} not found", group_arn)))
+    }
+
+    /// Register a target (workload id or host) with a group; it is routable until
+    /// health checks mark it unhealthy
     pub async fn register_targets(&self, group_arn: &str, target_id: &str, port: i32) -> ZeroResult<()> {
         let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
         let sql = "INSERT OR REPLACE INTO targets (group_arn, target_id, port, status) VALUES (?1, ?2, ?3, ?4)";
-        conn.execute(sql, zero_data_core::rusqlite::params![group_arn, target_id, port, "healthy"])
+        conn.execute(sql, zero_data_core::rusqlite::params![group_arn, target_id, port, TARGET_INITIAL])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
         Ok(())
     }
 
+    /// Registered targets of a group with their health
+    pub async fn describe_target_health(&self, group_arn: &str) -> ZeroResult<Vec<serde_json::Value>> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT target_id, port, status, description, last_checked FROM targets WHERE group_arn = ?1 ORDER BY target_id, port"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let targets = stmt.query_map([group_arn], |row| {
+            Ok(json!({
+                "Target": { "Id": row.get::<_, String>(0)?, "Port": row.get::<_, i32>(1)? },
+                "TargetHealth": {
+                    "State": row.get::<_, String>(2)?,
+                    "Description": row.get::<_, Option<String>>(3)?,
+                    "LastChecked": row.get::<_, Option<String>>(4)?
+                }
+            }))
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(targets)
+    }
+
+    /// Probe every registered target once and record its health.
+    /// Returns the number of targets whose state changed.
+    pub async fn check_target_health(&self) -> ZeroResult<usize> {
+        self.run_health_checks(false).await
+    }
+
+    /// Probe the targets whose group's health check interval has elapsed since their last check.
+    /// Returns the number of targets whose state changed.
+    pub async fn check_due_target_health(&self) -> ZeroResult<usize> {
+        self.run_health_checks(true).await
+    }
+
+    async fn run_health_checks(&self, due_only: bool) -> ZeroResult<usize> {
+        let now = chrono::Utc::now();
+        let targets = {
+            let conn = self.engine.db.lock();
+            ensure_tables(&conn)?;
+            let mut stmt = conn.prepare(
+                "SELECT t.group_arn, t.target_id, t.port, t.status, t.consecutive_successes, t.consecutive_failures, t.last_checked,
+                        g.protocol, g.health_check_path, g.health_check_interval, g.healthy_threshold, g.unhealthy_threshold
+                 FROM targets t JOIN target_groups g ON g.arn = t.group_arn"
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            let targets = stmt.query_map([], |row| {
+                let last_checked: Option<String> = row.get(6)?;
+                let target = TargetCheck {
+                    group_arn: row.get(0)?,
+                    target_id: row.get(1)?,
+                    port: row.get::<_, i32>(2)? as u16,
+                    status: row.get(3)?,
+                    successes: row.get(4)?,
+                    failures: row.get(5)?,
+                    protocol: row.get(7)?,
+                    health_check: HealthCheckConfig {
+                        path: row.get(8)?,
+                        interval_secs: row.get(9)?,
+                        healthy_threshold: row.get(10)?,
+                        unhealthy_threshold: row.get(11)?,
+                    },
+                };
+                Ok((target, last_checked))
+            }).map_err(|e| ZeroError::Internal(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+            targets
+        };
+
+        let is_due = |last_checked: &Option<String>, interval_secs: u32| {
+            last_checked.as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .is_none_or(|t| now.signed_duration_since(t) >= chrono::Duration::seconds(i64::from(interval_secs)))
+        };
+
+        let mut changed = 0;
+        for (target, last_checked) in targets {
+            if due_only && !is_due(&last_checked, target.health_check.interval_secs) {
+                continue;
+            }
+            if self.check_target(target).await? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Probe one target and update its consecutive check counters and state.
+    /// Returns whether the state changed.
+    async fn check_target(&self, target: TargetCheck) -> ZeroResult<bool> {
+        let host = lb_runtime::resolve_target(&self.engine, &target.target_id).await;
+        let result = lb_runtime::probe_target(&self.http_client, &target.protocol, &host, target.port, &target.health_check.path).await;
+
+        let (successes, failures, description) = match &result {
+            Ok(()) => (target.successes.saturating_add(1), 0, None),
+            Err(reason) => (0, target.failures.saturating_add(1), Some(reason.clone())),
+        };
+        let state = if successes >= target.health_check.healthy_threshold {
+            TARGET_HEALTHY
+        } else if failures >= target.health_check.unhealthy_threshold {
+            TARGET_UNHEALTHY
+        } else {
+            target.status.as_str()
+        };
+
+        if state != target.status {
+            tracing::info!("ZeroLB: target {}:{} in {} is now {}", target.target_id, target.port, target.group_arn, state);
+        }
+        let conn = self.engine.db.lock();
+        conn.execute(
+            "UPDATE targets SET status = ?1, consecutive_successes = ?2, consecutive_failures = ?3, description = ?4, last_checked = ?5
+             WHERE group_arn = ?6 AND target_id = ?7 AND port = ?8",
+            zero_data_core::rusqlite::params![
+                state, successes, failures, description, chrono::Utc::now().to_rfc3339(),
+                target.group_arn, target.target_id, target.port
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(state != target.status)
+    }
+
     pub async fn create_listener(&self, lb_name: &str, port: i32, protocol: &str, target_group_arn: &str) -> ZeroResult<String> {
+        let protocol: ListenerProtocol = protocol.parse()?;
+        let port = u16::try_from(port).map_err(|_| ZeroError::Validation(format!("Invalid listener port {}", port)))?;
+        if self.listeners.lock().await.contains_key(&port) {
+            return Err(ZeroError::Validation(format!("Port {} already has a listener", port)));
+        }
         let id = format!("arn:zero:elasticloadbalancing:000000:listener/{}/{}", lb_name, uuid::Uuid::new_v4());
         
-        {
-            let conn = self.engine.db.lock();
-            let sql = "INSERT INTO listeners (id, lb_name, port, protocol, target_group_arn) VALUES (?1, ?2, ?3, ?4, ?5)";
-            conn.execute(sql, zero_data_core::rusqlite::params![id, lb_name, port, protocol, target_group_arn])
-                .map_err(|e| ZeroError::Internal(e.to_string()))?;
-        }
+        // Bind the port first so a port in use fails the request
+        self.spawn_listener_task(port, protocol, target_group_arn.to_string()).await?;
         
-        // Spawn the data plane for this listener
-        self.spawn_listener_task(port as u16, target_group_arn.to_string()).await?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let sql = "INSERT INTO listeners (id, lb_name, port, protocol, target_group_arn) VALUES (?1, ?2, ?3, ?4, ?5)";
+        conn.execute(sql, zero_data_core::rusqlite::params![id, lb_name, port, protocol.as_str(), target_group_arn])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
         
         Ok(id)
     }
 
-    async fn spawn_listener_task(&self, port: u16, target_group_arn: String) -> ZeroResult<()> {
+    async fn spawn_listener_task(&self, port: u16, protocol: ListenerProtocol, target_group_arn: String) -> ZeroResult<()> {
         let mut listeners = self.listeners.lock().await;
         if listeners.contains_key(&port) {
             return Ok(());
         }
 
-        let engine = self.engine.clone();
-        let http_client = self.http_client.clone();
-        let tg_arn = target_group_arn.clone();
-
-        let handle = tokio::spawn(async move {
-            let app = Router::new()
-                .fallback(move |req: Request| {
-                    let engine = engine.clone();
-                    let http_client = http_client.clone();
-                    let tg_arn = tg_arn.clone();
-                    async move {
-                        proxy_handler(engine, http_client, tg_arn, req).await
-                    }
-                });
-
-            let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
-            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-            println!("📡 ZeroLB listening on port {}", port);
-            axum::serve(listener, app).await.unwrap();
-        });
+        let handle = lb_runtime::start_listener(
+            self.engine.clone(),
+            self.http_client.clone(),
+            port,
+            protocol,
+            target_group_arn,
+        ).await?;
 
         listeners.insert(port, handle);
         Ok(())
@@ -166,6 +344,20 @@ impl LbService {
         Ok(lbs)
     }
 
+    /// Delete every load balancer with its listeners, target groups and targets, and close the
+    /// listeners' ports; returns how many load balancers there were
+    pub async fn delete_all(&self) -> ZeroResult<usize> {
+        for (_, handle) in self.listeners.lock().await.drain() {
+            handle.abort();
+        }
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let deleted = conn.execute("DELETE FROM load_balancers", []).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute_batch("DELETE FROM listeners; DELETE FROM target_groups; DELETE FROM targets;")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(deleted)
+    }
+
     /// Sync active listeners from database
     pub async fn sync_data_plane(&self) -> ZeroResult<()> {
         let items = {
@@ -178,78 +370,56 @@ impl LbService {
 
             if !table_exists { return Ok(()); }
 
-            let mut stmt = conn.prepare("SELECT port, target_group_arn FROM listeners").map_err(|e| ZeroError::Internal(e.to_string()))?;
-            let items: Vec<(u16, String)> = stmt.query_map([], |row| {
-                Ok((row.get::<_, i32>(0)? as u16, row.get::<_, String>(1)?))
+            let mut stmt = conn.prepare("SELECT port, protocol, target_group_arn FROM listeners").map_err(|e| ZeroError::Internal(e.to_string()))?;
+            let items: Vec<(u16, String, String)> = stmt.query_map([], |row| {
+                Ok((row.get::<_, i32>(0)? as u16, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
             }).map_err(|e| ZeroError::Internal(e.to_string()))?
-                .collect::<Result<Vec<(u16, String)>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+                .collect::<Result<Vec<(u16, String, String)>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
             items
         };
 
-        for (port, tg_arn) in items {
-            self.spawn_listener_task(port, tg_arn).await?;
+        for (port, protocol, tg_arn) in items {
+            self.spawn_listener_task(port, protocol.parse()?, tg_arn).await?;
         }
         Ok(())
     }
 }
 
-async fn proxy_handler(engine: Arc<ZeroEngine>, client: Client, tg_arn: String, req: Request) -> impl IntoResponse {
-    // 1. Find healthy targets
-    let targets = {
-        let conn = engine.db.lock();
-        let mut stmt = conn.prepare("SELECT target_id, port FROM targets WHERE group_arn = ?1 AND status = 'healthy'").unwrap();
-        let list = stmt.query_map([tg_arn], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
-        }).unwrap().collect::<Result<Vec<(String, i32)>, _>>().unwrap();
-        list
-    };
-
-    if targets.is_empty() {
-        return (axum::http::StatusCode::SERVICE_UNAVAILABLE, "No healthy targets").into_response();
-    }
-
-    // 2. Round-Robin select (Simple Random for now)
-    let (target_host, target_port) = {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let idx = rng.gen_range(0..targets.len());
-        targets[idx].clone()
-    };
-
-    // 3. Forward request
-    let path = req.uri().path();
-    let query = req.uri().query().map(|q| format!("?{}", q)).unwrap_or_default();
-    let url = format!("http://{}:{}{}{}", target_host, target_port, path, query);
-
-    let method = req.method().clone();
-    let headers = req.headers().clone();
-    
-    // We need to convert axum Request to reqwest Request but for a simple proxy we can just build it
-    let mut proxy_req = client.request(method, &url);
-    for (name, value) in headers {
-        if let Some(n) = name {
-            proxy_req = proxy_req.header(n, value);
-        }
-    }
-
-    // Body streaming is complex, for local emulator we just collect (small payloads)
-    let body_bytes = axum::body::to_bytes(req.into_body(), 10 * 1024 * 1024).await.unwrap_or_default();
-    proxy_req = proxy_req.body(body_bytes);
-
-    match proxy_req.send().await {
-        Ok(res) => {
-            let status = res.status();
-            let headers = res.headers().clone();
-            let body = res.bytes().await.unwrap_or_default();
-            
-            let mut builder = axum::http::Response::builder().status(status.as_u16());
-            for (name, value) in headers.iter() {
-                builder = builder.header(name, value);
-            }
-            builder.body(axum::body::Body::from(body)).unwrap().into_response()
-        }
-        Err(e) => {
-            (axum::http::StatusCode::BAD_GATEWAY, format!("Gateway Error: {}", e)).into_response()
-        }
-    }
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS load_balancers (
+            name TEXT PRIMARY KEY,
+            type TEXT,
+            dns_name TEXT,
+            status TEXT
+        );
+        CREATE TABLE IF NOT EXISTS listeners (
+            id TEXT PRIMARY KEY,
+            lb_name TEXT,
+            port INTEGER,
+            protocol TEXT,
+            target_group_arn TEXT
+        );
+        CREATE TABLE IF NOT EXISTS target_groups (
+            arn TEXT PRIMARY KEY,
+            name TEXT,
+            port INTEGER,
+            protocol TEXT,
+            health_check_path TEXT NOT NULL DEFAULT '/health',
+            health_check_interval INTEGER NOT NULL DEFAULT 10,
+            healthy_threshold INTEGER NOT NULL DEFAULT 3,
+            unhealthy_threshold INTEGER NOT NULL DEFAULT 2
+        );
+        CREATE TABLE IF NOT EXISTS targets (
+            group_arn TEXT,
+            target_id TEXT,
+            port INTEGER,
+            status TEXT,
+            consecutive_successes INTEGER NOT NULL DEFAULT 0,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            description TEXT,
+            last_checked TEXT,
+            PRIMARY KEY(group_arn, target_id)
+        );
+    ").map_err(|e| ZeroError::Internal(e.to_string()))
 }
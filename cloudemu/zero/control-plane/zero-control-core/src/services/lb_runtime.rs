@@ -0,0 +1,230 @@
+//! Load balancer data plane: listener runtimes and target health checks.
+//!
+//! Each listener binds its port and forwards traffic round-robin to the registered
+//! targets of its target group:
+//! - `HTTP` listeners proxy requests
+//! - `TCP` listeners splice connections
+//!
+//! Targets are workload ids, resolved to IP addresses through the `ComputeDriver`; a target
+//! that is not a known workload is used as a host name. Health checks probe each target on
+//! its group's interval (an HTTP GET of the group's health check path, or a TCP connect);
+//! a target changes state after the group's threshold of consecutive passed or failed
+//! checks. Until then a new target is `initial` and still routable.
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use axum::{
+    extract::Request,
+    response::{IntoResponse, Response},
+    routing::Router,
+};
+use reqwest::Client;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+
+/// How often the health checker looks for targets whose check is due
+pub const HEALTH_CHECK_TICK: Duration = Duration::from_secs(1);
+/// A probe that takes longer than this fails
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub const TARGET_INITIAL: &str = "initial";
+pub const TARGET_HEALTHY: &str = "healthy";
+pub const TARGET_UNHEALTHY: &str = "unhealthy";
+
+/// How a listener forwards traffic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListenerProtocol {
+    Http,
+    Tcp,
+}
+
+impl ListenerProtocol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListenerProtocol::Http => "HTTP",
+            ListenerProtocol::Tcp => "TCP",
+        }
+    }
+}
+
+impl std::str::FromStr for ListenerProtocol {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "HTTP" => Ok(ListenerProtocol::Http),
+            "TCP" => Ok(ListenerProtocol::Tcp),
+            other => Err(ZeroError::Validation(format!("Unsupported listener protocol {}; expected HTTP or TCP", other))),
+        }
+    }
+}
+
+/// Address a target receives traffic on: the workload's IP, or the target id itself
+pub async fn resolve_target(engine: &ZeroEngine, target_id: &str) -> String {
+    match engine.compute.get_workload_status(target_id).await {
+        Ok(status) => status.ip_address.unwrap_or_else(|| target_id.to_string()),
+        Err(_) => target_id.to_string(),
+    }
+}
+
+/// Targets of a group that should receive traffic: the healthy ones, or the unchecked
+/// ones while none is healthy
+fn routable_targets(engine: &ZeroEngine, group_arn: &str) -> ZeroResult<Vec<(String, u16)>> {
+    let conn = engine.db.lock();
+    let mut stmt = conn.prepare("SELECT target_id, port, status FROM targets WHERE group_arn = ?1 ORDER BY target_id, port")
+        .map_err(|e| ZeroError::Internal(e.to_string()))?;
+    let targets = stmt.query_map([group_arn], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)? as u16, row.get::<_, String>(2)?))
+    }).map_err(|e| ZeroError::Internal(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+    let with_status = |wanted: &str| targets.iter()
+        .filter(|(_, _, status)| status == wanted)
+        .map(|(id, port, _)| (id.clone(), *port))
+        .collect::<Vec<_>>();
+    let healthy = with_status(TARGET_HEALTHY);
+    Ok(if healthy.is_empty() { with_status(TARGET_INITIAL) } else { healthy })
+}
+
+/// Round-robin target selection for one listener
+struct Balancer {
+    engine: Arc<ZeroEngine>,
+    group_arn: String,
+    next: AtomicUsize,
+}
+
+impl Balancer {
+    /// Host and port of the next target, `None` when no target is routable
+    async fn pick(&self) -> ZeroResult<Option<(String, u16)>> {
+        let targets = routable_targets(&self.engine, &self.group_arn)?;
+        if targets.is_empty() {
+            return Ok(None);
+        }
+        let (id, port) = &targets[self.next.fetch_add(1, Ordering::Relaxed) % targets.len()];
+        Ok(Some((resolve_target(&self.engine, id).await, *port)))
+    }
+}
+
+/// Bind a listener port and serve it until the returned task is aborted
+pub async fn start_listener(
+    engine: Arc<ZeroEngine>,
+    http_client: Client,
+    port: u16,
+    protocol: ListenerProtocol,
+    group_arn: String,
+) -> ZeroResult<tokio::task::JoinHandle<()>> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await
+        .map_err(|e| ZeroError::Internal(format!("Failed to bind listener port {}: {}", port, e)))?;
+    let balancer = Arc::new(Balancer { engine, group_arn, next: AtomicUsize::new(0) });
+    tracing::info!("ZeroLB {} listener on port {}", protocol.as_str(), port);
+
+    let handle = match protocol {
+        ListenerProtocol::Http => tokio::spawn(async move {
+            let app = Router::new().fallback(move |req: Request| {
+                let balancer = balancer.clone();
+                let http_client = http_client.clone();
+                async move { proxy_http(&balancer, &http_client, req).await }
+            });
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("ZeroLB listener on port {} stopped: {}", port, e);
+            }
+        }),
+        ListenerProtocol::Tcp => tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((inbound, _)) => {
+                        let balancer = balancer.clone();
+                        tokio::spawn(async move { proxy_tcp(&balancer, inbound).await });
+                    }
+                    Err(e) => tracing::warn!("ZeroLB listener on port {} failed to accept: {}", port, e),
+                }
+            }
+        }),
+    };
+    Ok(handle)
+}
+
+async fn proxy_http(balancer: &Balancer, client: &Client, req: Request) -> Response {
+    let (target_host, target_port) = match balancer.pick().await {
+        Ok(Some(target)) => target,
+        Ok(None) => return (axum::http::StatusCode::SERVICE_UNAVAILABLE, "No healthy targets").into_response(),
+        Err(e) => return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+
+    let path = req.uri().path();
+    let query = req.uri().query().map(|q| format!("?{}", q)).unwrap_or_default();
+    let url = format!("http://{}:{}{}{}", target_host, target_port, path, query);
+
+    let mut proxy_req = client.request(req.method().clone(), &url);
+    for (name, value) in req.headers() {
+        // The target's address decides the Host header
+        if name != axum::http::header::HOST {
+            proxy_req = proxy_req.header(name, value);
+        }
+    }
+
+    // Body streaming is complex, for local emulator we just collect (small payloads)
+    let body_bytes = axum::body::to_bytes(req.into_body(), 10 * 1024 * 1024).await.unwrap_or_default();
+    proxy_req = proxy_req.body(body_bytes);
+
+    match proxy_req.send().await {
+        Ok(res) => {
+            let mut builder = axum::http::Response::builder().status(res.status().as_u16());
+            for (name, value) in res.headers() {
+                builder = builder.header(name, value);
+            }
+            let body = res.bytes().await.unwrap_or_default();
+            builder.body(axum::body::Body::from(body)).unwrap().into_response()
+        }
+        Err(e) => {
+            (axum::http::StatusCode::BAD_GATEWAY, format!("Gateway Error: {}", e)).into_response()
+        }
+    }
+}
+
+async fn proxy_tcp(balancer: &Balancer, mut inbound: TcpStream) {
+    let (host, port) = match balancer.pick().await {
+        Ok(Some(target)) => target,
+        Ok(None) => {
+            tracing::warn!("ZeroLB: no healthy targets in {}; closing connection", balancer.group_arn);
+            return;
+        }
+        Err(e) => {
+            tracing::error!("ZeroLB: target selection failed: {}", e);
+            return;
+        }
+    };
+
+    match TcpStream::connect((host.as_str(), port)).await {
+        Ok(mut outbound) => {
+            if let Err(e) = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await {
+                tracing::debug!("ZeroLB: connection to {}:{} ended: {}", host, port, e);
+            }
+        }
+        Err(e) => tracing::warn!("ZeroLB: failed to connect to target {}:{}: {}", host, port, e),
+    }
+}
+
+/// Probe a target: TCP groups must accept a connection, HTTP groups must answer the
+/// health check path with a non-error status. Errors describe why the check failed.
+pub async fn probe_target(client: &Client, protocol: &str, host: &str, port: u16, path: &str) -> Result<(), String> {
+    if protocol.eq_ignore_ascii_case("TCP") {
+        return match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, TcpStream::connect((host, port))).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(format!("Connection failed: {}", e)),
+            Err(_) => Err("Health check timed out".to_string()),
+        };
+    }
+
+    let url = format!("http://{}:{}{}", host, port, path);
+    match client.get(&url).timeout(HEALTH_CHECK_TIMEOUT).send().await {
+        Ok(res) if res.status().is_client_error() || res.status().is_server_error() => {
+            Err(format!("Health check returned {}", res.status().as_u16()))
+        }
+        Ok(_) => Ok(()),
+        Err(e) if e.is_timeout() => Err("Health check timed out".to_string()),
+        Err(e) => Err(format!("Health check failed: {}", e)),
+    }
+}
@@ -1,7 +1,26 @@
+pub mod audit;
+pub mod autoscaling;
+pub mod backup;
 pub mod eks;
 pub mod db;
+pub mod dns;
+pub mod dns_server;
+pub mod dry_run;
 pub mod func;
+pub mod func_runtime;
+pub mod image;
+pub mod event_source;
 pub mod queue;
+pub mod rollout;
+pub mod s3_gateway;
 pub mod iam;
 pub mod lb;
+pub mod lb_runtime;
+pub mod namespace;
+pub mod placement;
+pub mod scheduler;
 pub mod store;
+pub mod supervisor;
+pub mod system;
+pub mod topic;
+pub mod workload_metrics;
@@ -0,0 +1,379 @@
+//! Namespaces: groups of resources sharing a CPU, memory, volume, workload and queue quota
+//!
+//! A request works in the namespace named by its `X-Zero-Namespace` header, or in `default`.
+//! Every container the control plane starts, whether asked for directly or launched by a
+//! scaling group, an EKS cluster or a function invocation, reserves its CPU and memory in
+//! the namespace and counts as one of its workloads, and volumes reserve their size; a
+//! reservation that would take the namespace past a quota fails. Queues count against the
+//! queue quota. Buckets, tables, functions, scaling groups and clusters are recorded without
+//! usage, so they are only listed and reachable from their own namespace. Resources created before namespaces existed belong to `default`, which
+//! has no quota until one is set.
+
+use zero_control_spi::{ZeroError, ZeroRequest, ZeroResult};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+
+/// Header naming the namespace a request works in
+pub const NAMESPACE_HEADER: &str = "x-zero-namespace";
+
+pub const DEFAULT_NAMESPACE: &str = "default";
+
+/// Kinds of resources a namespace holds
+pub const WORKLOAD: &str = "workload";
+pub const VOLUME: &str = "volume";
+pub const BUCKET: &str = "bucket";
+pub const TABLE: &str = "table";
+pub const FUNCTION: &str = "function";
+pub const QUEUE: &str = "queue";
+pub const SCALING_GROUP: &str = "scaling_group";
+pub const CLUSTER: &str = "cluster";
+
+/// Namespace a request works in
+pub fn requested(req: &ZeroRequest) -> &str {
+    req.header(NAMESPACE_HEADER).filter(|ns| !ns.is_empty()).unwrap_or(DEFAULT_NAMESPACE)
+}
+
+/// Whether the resource `id` is in `namespace`, given the namespaces from
+/// [`NamespaceService::assignments`]
+pub fn in_namespace(assignments: &HashMap<String, String>, id: &str, namespace: &str) -> bool {
+    assignments.get(id).map_or(DEFAULT_NAMESPACE, String::as_str) == namespace
+}
+
+/// Limits of a namespace; a missing limit is unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Quota {
+    pub cpu: Option<f64>,
+    pub memory_mb: Option<i64>,
+    pub volume_gb: Option<i64>,
+    /// Most containers running at once
+    pub workloads: Option<i64>,
+    pub queues: Option<i64>,
+}
+
+/// Resources held in a namespace
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    pub cpu: f64,
+    pub memory_mb: i64,
+    pub volume_gb: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateNamespaceRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub quota: Quota,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Namespace {
+    pub name: String,
+    pub quota: Quota,
+    pub used: Usage,
+    pub workloads: usize,
+    pub volumes: usize,
+    pub queues: usize,
+    pub created_at: String,
+}
+
+#[derive(Clone)]
+pub struct NamespaceService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl NamespaceService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS namespaces (
+                name TEXT PRIMARY KEY,
+                cpu REAL,
+                memory_mb INTEGER,
+                volume_gb INTEGER,
+                workloads INTEGER,
+                queues INTEGER,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS namespace_resources (
+                kind TEXT NOT NULL,
+                id TEXT NOT NULL,
+                namespace TEXT NOT NULL,
+                cpu REAL NOT NULL DEFAULT 0,
+                memory_mb INTEGER NOT NULL DEFAULT 0,
+                volume_gb INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (kind, id)
+            );"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Self::migrate(conn).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO namespaces (name, created_at) VALUES (?1, ?2)",
+            params![DEFAULT_NAMESPACE, chrono::Utc::now().to_rfc3339()],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Add the count quotas to namespaces tables created before them
+    fn migrate(conn: &Connection) -> zero_data_core::rusqlite::Result<()> {
+        let columns = conn.prepare("SELECT name FROM pragma_table_info('namespaces')")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for column in ["workloads", "queues"] {
+            if !columns.iter().any(|c| c == column) {
+                conn.execute(&format!("ALTER TABLE namespaces ADD COLUMN {} INTEGER", column), [])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn create(&self, req: CreateNamespaceRequest) -> ZeroResult<Namespace> {
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            if Self::describe(&conn, &req.name)?.is_some() {
+                return Err(ZeroError::AlreadyExists(format!("Namespace already exists: {}", req.name)));
+            }
+            conn.execute(
+                "INSERT INTO namespaces (name, cpu, memory_mb, volume_gb, workloads, queues, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![req.name, req.quota.cpu, req.quota.memory_mb, req.quota.volume_gb, req.quota.workloads, req.quota.queues, chrono::Utc::now().to_rfc3339()],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+        self.get(&req.name).await
+    }
+
+    pub async fn list(&self) -> ZeroResult<Vec<Namespace>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let names: Vec<String> = conn.prepare("SELECT name FROM namespaces ORDER BY name")
+            .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        names.iter()
+            .map(|name| Self::describe(&conn, name)?.ok_or_else(|| ZeroError::NotFound(format!("Namespace not found: {}", name))))
+            .collect()
+    }
+
+    pub async fn get(&self, name: &str) -> ZeroResult<Namespace> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::describe(&conn, name)?.ok_or_else(|| ZeroError::NotFound(format!("Namespace not found: {}", name)))
+    }
+
+    /// Replace the quota of a namespace. Resources already past the new quota stay;
+    /// only new ones are refused.
+    pub async fn set_quota(&self, name: &str, quota: Quota) -> ZeroResult<Namespace> {
+        {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            let updated = conn.execute(
+                "UPDATE namespaces SET cpu = ?2, memory_mb = ?3, volume_gb = ?4, workloads = ?5, queues = ?6 WHERE name = ?1",
+                params![name, quota.cpu, quota.memory_mb, quota.volume_gb, quota.workloads, quota.queues],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            if updated == 0 {
+                return Err(ZeroError::NotFound(format!("Namespace not found: {}", name)));
+            }
+        }
+        self.get(name).await
+    }
+
+    /// Delete an empty namespace
+    pub async fn delete(&self, name: &str) -> ZeroResult<()> {
+        if name == DEFAULT_NAMESPACE {
+            return Err(ZeroError::Validation("The default namespace cannot be deleted".into()));
+        }
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let namespace = Self::describe(&conn, name)?
+            .ok_or_else(|| ZeroError::NotFound(format!("Namespace not found: {}", name)))?;
+        if namespace.workloads + namespace.volumes > 0 {
+            return Err(ZeroError::Validation(format!(
+                "Namespace {} still holds {} workloads and {} volumes", name, namespace.workloads, namespace.volumes
+            )));
+        }
+        let others: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM namespace_resources WHERE namespace = ?1", params![name], |row| row.get(0),
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if others > 0 {
+            return Err(ZeroError::Validation(format!("Namespace {} still holds {} resources", name, others)));
+        }
+        conn.execute("DELETE FROM namespaces WHERE name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Record a new resource in a namespace, refusing it if the namespace lacks the quota
+    pub async fn reserve(&self, namespace: &str, kind: &str, id: &str, usage: Usage) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::check_reserve_in(&conn, namespace, kind, id, &usage)?;
+        conn.execute(
+            "INSERT INTO namespace_resources (kind, id, namespace, cpu, memory_mb, volume_gb) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![kind, id, namespace, usage.cpu, usage.memory_mb, usage.volume_gb],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Refuse what [`Self::reserve`] would refuse, recording nothing
+    pub async fn check_reserve(&self, namespace: &str, kind: &str, id: &str, usage: Usage) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::check_reserve_in(&conn, namespace, kind, id, &usage)
+    }
+
+    fn check_reserve_in(conn: &Connection, namespace: &str, kind: &str, id: &str, usage: &Usage) -> ZeroResult<()> {
+        let current = Self::describe(conn, namespace)?
+            .ok_or_else(|| ZeroError::NotFound(format!("Namespace not found: {}", namespace)))?;
+
+        let owner: Option<String> = conn.query_row(
+            "SELECT namespace FROM namespace_resources WHERE kind = ?1 AND id = ?2",
+            params![kind, id],
+            |row| row.get(0),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if let Some(owner) = owner {
+            return Err(ZeroError::AlreadyExists(format!("{} {} already exists in namespace {}", kind, id, owner)));
+        }
+
+        let counted = match kind {
+            WORKLOAD => Some(("workloads", current.workloads, current.quota.workloads)),
+            QUEUE => Some(("queues", current.queues, current.quota.queues)),
+            _ => None,
+        };
+        if let Some((resource, count, Some(limit))) = counted {
+            if count as i64 >= limit {
+                return Err(ZeroError::QuotaExceeded(format!(
+                    "{} {} would exceed the quota of {} {} in namespace {}, which has {}", kind, id, limit, resource, namespace, count
+                )));
+            }
+        }
+
+        Self::check_quota(&current.quota, &current.used, usage, namespace, kind, id)
+    }
+
+    /// Change what a resource holds of its namespace's quota, refusing growth beyond it
+    pub async fn resize(&self, kind: &str, id: &str, usage: Usage) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let namespace = Self::check_resize_in(&conn, kind, id, &usage)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO namespace_resources (kind, id, namespace, cpu, memory_mb, volume_gb) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![kind, id, namespace, usage.cpu, usage.memory_mb, usage.volume_gb],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Refuse what [`Self::resize`] would refuse, changing nothing
+    pub async fn check_resize(&self, kind: &str, id: &str, usage: Usage) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::check_resize_in(&conn, kind, id, &usage).map(|_| ())
+    }
+
+    /// Namespace of the resource, when its quota allows `usage`
+    fn check_resize_in(conn: &Connection, kind: &str, id: &str, usage: &Usage) -> ZeroResult<String> {
+        let held: Option<(String, Usage)> = conn.query_row(
+            "SELECT namespace, cpu, memory_mb, volume_gb FROM namespace_resources WHERE kind = ?1 AND id = ?2",
+            params![kind, id],
+            |row| Ok((row.get(0)?, Usage { cpu: row.get(1)?, memory_mb: row.get(2)?, volume_gb: row.get(3)? })),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let (namespace, held) = held.unwrap_or_else(|| (DEFAULT_NAMESPACE.to_string(), Usage::default()));
+        let current = Self::describe(conn, &namespace)?
+            .ok_or_else(|| ZeroError::NotFound(format!("Namespace not found: {}", namespace)))?;
+
+        let others = Usage {
+            cpu: current.used.cpu - held.cpu,
+            memory_mb: current.used.memory_mb - held.memory_mb,
+            volume_gb: current.used.volume_gb - held.volume_gb,
+        };
+        Self::check_quota(&current.quota, &others, usage, &namespace, kind, id)?;
+        Ok(namespace)
+    }
+
+    /// Refuse `usage` when the namespace already uses `used` and the sum exceeds a limit
+    fn check_quota(quota: &Quota, used: &Usage, usage: &Usage, namespace: &str, kind: &str, id: &str) -> ZeroResult<()> {
+        let Quota { cpu, memory_mb, volume_gb, .. } = *quota;
+        let exceeded = |resource: &str, requested: String, used: String, limit: String| ZeroError::QuotaExceeded(format!(
+            "{} {} needs {} {} but namespace {} uses {} of {}", kind, id, requested, resource, namespace, used, limit
+        ));
+        if let Some(limit) = cpu.filter(|limit| used.cpu + usage.cpu > *limit + f64::EPSILON) {
+            return Err(exceeded("CPU", usage.cpu.to_string(), used.cpu.to_string(), limit.to_string()));
+        }
+        if let Some(limit) = memory_mb.filter(|limit| used.memory_mb + usage.memory_mb > *limit) {
+            return Err(exceeded("MB of memory", usage.memory_mb.to_string(), used.memory_mb.to_string(), limit.to_string()));
+        }
+        if let Some(limit) = volume_gb.filter(|limit| used.volume_gb + usage.volume_gb > *limit) {
+            return Err(exceeded("GB of volumes", usage.volume_gb.to_string(), used.volume_gb.to_string(), limit.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Return a deleted resource's share of the quota
+    pub async fn release(&self, kind: &str, id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        conn.execute("DELETE FROM namespace_resources WHERE kind = ?1 AND id = ?2", params![kind, id])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Namespace of each recorded resource of a kind; unrecorded ones are in `default`
+    pub async fn assignments(&self, kind: &str) -> ZeroResult<HashMap<String, String>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let mut stmt = conn.prepare("SELECT id, namespace FROM namespace_resources WHERE kind = ?1")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let assignments = stmt.query_map(params![kind], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(assignments)
+    }
+
+    /// Whether a resource is in a namespace
+    pub async fn contains(&self, namespace: &str, kind: &str, id: &str) -> ZeroResult<bool> {
+        Ok(self.namespace_of(kind, id).await? == namespace)
+    }
+
+    /// Namespace a resource was created in; unrecorded resources are in `default`
+    pub async fn namespace_of(&self, kind: &str, id: &str) -> ZeroResult<String> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let namespace: Option<String> = conn.query_row(
+            "SELECT namespace FROM namespace_resources WHERE kind = ?1 AND id = ?2",
+            params![kind, id],
+            |row| row.get(0),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(namespace.unwrap_or_else(|| DEFAULT_NAMESPACE.to_string()))
+    }
+
+    fn describe(conn: &Connection, name: &str) -> ZeroResult<Option<Namespace>> {
+        let namespace = conn.query_row(
+            "SELECT n.name, n.cpu, n.memory_mb, n.volume_gb, n.workloads, n.queues, n.created_at,
+                    COALESCE(SUM(r.cpu), 0.0), COALESCE(SUM(r.memory_mb), 0), COALESCE(SUM(r.volume_gb), 0),
+                    COUNT(CASE WHEN r.kind = ?2 THEN 1 END), COUNT(CASE WHEN r.kind = ?3 THEN 1 END),
+                    COUNT(CASE WHEN r.kind = ?4 THEN 1 END)
+             FROM namespaces n LEFT JOIN namespace_resources r ON r.namespace = n.name
+             WHERE n.name = ?1 GROUP BY n.name",
+            params![name, WORKLOAD, VOLUME, QUEUE],
+            |row| Ok(Namespace {
+                name: row.get(0)?,
+                quota: Quota {
+                    cpu: row.get(1)?,
+                    memory_mb: row.get(2)?,
+                    volume_gb: row.get(3)?,
+                    workloads: row.get(4)?,
+                    queues: row.get(5)?,
+                },
+                created_at: row.get(6)?,
+                used: Usage { cpu: row.get(7)?, memory_mb: row.get(8)?, volume_gb: row.get(9)? },
+                workloads: row.get::<_, i64>(10)? as usize,
+                volumes: row.get::<_, i64>(11)? as usize,
+                queues: row.get::<_, i64>(12)? as usize,
+            }),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(namespace)
+    }
+}
@@ -0,0 +1,226 @@
+//! Workload placement: picks the registered node a workload lands on
+//!
+//! Only nodes with a compute driver, usually a Docker endpoint, take workloads; nodes that are
+//! only recorded, such as EKS cluster nodes, never do. A node is eligible when it is `Ready`,
+//! its labels match the workload's node selector and every required affinity rule, and the
+//! workload tolerates each of its taints. The eligible node with the fewest placed workloads
+//! wins, ties going to the hostname that sorts first. Without such nodes, workloads without
+//! constraints run unplaced on the local driver.
+
+use zero_control_spi::{ComputeDriver, WorkloadStatus, ZeroResult, ZeroError};
+use zero_data_core::{LocalNode, ZeroEngine};
+use zero_data_core::rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+
+/// A node added to the registry by hand, such as a machine in the home lab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterNodeRequest {
+    pub hostname: String,
+    pub ip_address: String,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub taints: Vec<String>,
+    /// Docker endpoint to run the node's workloads on, such as `tcp://192.168.1.20:2375`
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// New labels and taints of a node, replacing the old ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeLabels {
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub taints: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AffinityOperator {
+    In,
+    NotIn,
+    Exists,
+    DoesNotExist,
+}
+
+/// A required node affinity rule on one label
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AffinityRule {
+    pub key: String,
+    pub operator: AffinityOperator,
+    /// Values for `In` and `NotIn`
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl AffinityRule {
+    fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let value = labels.get(&self.key);
+        match self.operator {
+            AffinityOperator::In => value.is_some_and(|v| self.values.contains(v)),
+            AffinityOperator::NotIn => value.is_none_or(|v| !self.values.contains(v)),
+            AffinityOperator::Exists => value.is_some(),
+            AffinityOperator::DoesNotExist => value.is_none(),
+        }
+    }
+}
+
+/// Where a workload may run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlacementConstraints {
+    /// Labels the node must carry with exactly these values
+    #[serde(default)]
+    pub node_selector: BTreeMap<String, String>,
+    #[serde(default)]
+    pub affinity: Vec<AffinityRule>,
+    /// `key=value` tolerates that taint, `key` tolerates the key with any value
+    #[serde(default)]
+    pub tolerations: Vec<String>,
+}
+
+impl PlacementConstraints {
+    pub fn is_empty(&self) -> bool {
+        self.node_selector.is_empty() && self.affinity.is_empty() && self.tolerations.is_empty()
+    }
+
+    pub fn allows(&self, node: &LocalNode) -> bool {
+        node.status == "Ready"
+            && self.node_selector.iter().all(|(key, value)| node.labels.get(key) == Some(value))
+            && self.affinity.iter().all(|rule| rule.matches(&node.labels))
+            && node.taints.iter().all(|taint| self.tolerates(taint))
+    }
+
+    fn tolerates(&self, taint: &str) -> bool {
+        let key = taint.split_once('=').map_or(taint, |(key, _)| key);
+        self.tolerations.iter().any(|toleration| toleration == taint || toleration == key)
+    }
+}
+
+#[derive(Clone)]
+pub struct PlacementService {
+    engine: Arc<ZeroEngine>,
+}
+
+impl PlacementService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { engine }
+    }
+
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS workload_placements (
+                workload_id TEXT PRIMARY KEY,
+                node_id TEXT NOT NULL,
+                placed_at TEXT NOT NULL
+            );"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    /// Choose and record the node for a workload, with the driver to run it on; `None` when it
+    /// runs unplaced on the local driver
+    pub async fn place(&self, workload_id: &str, constraints: &PlacementConstraints) -> ZeroResult<Option<(LocalNode, Arc<dyn ComputeDriver>)>> {
+        for rule in &constraints.affinity {
+            let takes_values = matches!(rule.operator, AffinityOperator::In | AffinityOperator::NotIn);
+            if takes_values == rule.values.is_empty() {
+                return Err(ZeroError::Validation(format!(
+                    "Affinity rule on {}: In and NotIn need values, Exists and DoesNotExist take none", rule.key
+                )));
+            }
+        }
+
+        let nodes: Vec<_> = self.engine.list_nodes().map_err(|e| ZeroError::Internal(e.to_string()))?
+            .into_iter()
+            .filter_map(|node| self.engine.node_compute(&node).map(|driver| (node, driver)))
+            .collect();
+        if nodes.is_empty() && constraints.is_empty() {
+            return Ok(None);
+        }
+
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let load = Self::load(&conn)?;
+        let (node, driver) = nodes.into_iter()
+            .filter(|(node, _)| constraints.allows(node))
+            .min_by(|(a, _), (b, _)| {
+                let load_of = |node: &LocalNode| load.get(&node.id).copied().unwrap_or_default();
+                load_of(a).cmp(&load_of(b)).then_with(|| a.hostname.cmp(&b.hostname))
+            })
+            .ok_or_else(|| ZeroError::Validation(format!("No ready node satisfies the placement constraints of {}", workload_id)))?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO workload_placements (workload_id, node_id, placed_at) VALUES (?1, ?2, ?3)",
+            params![workload_id, node.id, chrono::Utc::now().to_rfc3339()],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(Some((node, driver)))
+    }
+
+    /// Driver running a workload: its node's, or the local one when it was not placed
+    pub async fn compute_for(&self, workload_id: &str) -> ZeroResult<Arc<dyn ComputeDriver>> {
+        let node_id: Option<String> = {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            conn.query_row(
+                "SELECT node_id FROM workload_placements WHERE workload_id = ?1",
+                params![workload_id],
+                |row| row.get(0),
+            ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?
+        };
+        let Some(node_id) = node_id else {
+            return Ok(self.engine.compute.clone());
+        };
+        self.engine.list_nodes().map_err(|e| ZeroError::Internal(e.to_string()))?
+            .into_iter()
+            .find(|node| node.id == node_id)
+            .and_then(|node| self.engine.node_compute(&node))
+            .ok_or_else(|| ZeroError::Driver(format!("Node {} running workload {} is no longer reachable", node_id, workload_id)))
+    }
+
+    /// Workloads on each node with a driver, reported by that driver; unreachable nodes are skipped
+    pub async fn node_workloads(&self) -> ZeroResult<Vec<(LocalNode, Vec<WorkloadStatus>)>> {
+        let mut workloads = Vec::new();
+        for node in self.engine.list_nodes().map_err(|e| ZeroError::Internal(e.to_string()))? {
+            let Some(driver) = self.engine.node_compute(&node) else { continue };
+            match driver.list_workloads().await {
+                Ok(list) => workloads.push((node, list)),
+                Err(e) => tracing::warn!("Listing the workloads of node {} failed: {}", node.hostname, e),
+            }
+        }
+        Ok(workloads)
+    }
+
+    /// Hostname of the node each placed workload runs on
+    pub async fn placements(&self) -> ZeroResult<HashMap<String, String>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT p.workload_id, n.hostname FROM workload_placements p JOIN nodes n ON n.id = p.node_id"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let placements = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(placements)
+    }
+
+    /// Forget where a deleted workload ran
+    pub async fn release(&self, workload_id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        conn.execute("DELETE FROM workload_placements WHERE workload_id = ?1", params![workload_id])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Number of workloads placed on each node ID
+    fn load(conn: &Connection) -> ZeroResult<HashMap<String, usize>> {
+        let mut stmt = conn.prepare("SELECT node_id, COUNT(*) FROM workload_placements GROUP BY node_id")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let load = stmt.query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(load)
+    }
+}
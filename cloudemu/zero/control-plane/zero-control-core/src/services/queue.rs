@@ -1,26 +1,235 @@
 use zero_control_spi::{ZeroResult, ZeroError};
 use zero_data_core::ZeroEngine;
+use zero_data_core::events::QUEUE_MESSAGE_SENT;
 use std::sync::Arc;
 use serde_json::json;
 use base64;
 use chrono;
+use serde::{Serialize, Deserialize};
+use zero_data_core::rusqlite::OptionalExtension;
+use sha2::{Digest, Sha256};
 
+/// FIFO deduplication window in seconds (matches SQS)
+const DEDUP_WINDOW_SECS: i64 = 300;
+
+/// Maximum number of entries in a batch request or messages in a single receive (matches SQS)
+pub const MAX_BATCH_SIZE: usize = 10;
+
+/// Maximum long-polling wait in seconds (matches SQS)
+pub const MAX_WAIT_TIME_SECS: u64 = 20;
+
+/// Default visibility timeout in seconds (matches SQS)
+pub const DEFAULT_VISIBILITY_TIMEOUT_SECS: u32 = 30;
+
+/// Maximum visibility timeout in seconds: 12 hours (matches SQS)
+pub const MAX_VISIBILITY_TIMEOUT_SECS: u32 = 43_200;
+
+/// Maximum delivery delay in seconds: 15 minutes (matches SQS)
+pub const MAX_DELAY_SECS: u32 = 900;
+
+/// How often a long poll re-checks an empty queue
+const LONG_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+#[derive(Clone)]
 pub struct QueueService {
     engine: Arc<ZeroEngine>,
 }
 
+/// Dead-letter configuration for a queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedrivePolicy {
+    /// Name of the queue that receives messages after too many receives
+    pub dead_letter_queue: String,
+    /// Number of receives before a message is moved to the DLQ
+    pub max_receive_count: u32,
+}
+
+/// Optional settings applied when creating a queue
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueOptions {
+    pub redrive_policy: Option<RedrivePolicy>,
+    /// FIFO queue: strict per-group ordering and exactly-once delivery within the dedup window
+    pub fifo: bool,
+    /// Derive the deduplication ID from a SHA-256 of the body when none is supplied (FIFO only)
+    pub content_based_deduplication: bool,
+    /// Seconds a received message stays hidden from other consumers (default 30)
+    pub visibility_timeout: Option<u32>,
+    /// Seconds new messages stay hidden after being sent (0-900, default 0)
+    pub delay_seconds: Option<u32>,
+}
+
+/// Message counts of a queue, as SQS's approximate number attributes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStats {
+    pub name: String,
+    /// Messages a receive could return now
+    pub visible: u64,
+    /// Messages that are in flight or delayed
+    pub not_visible: u64,
+}
+
+/// Settings for a single receive call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiveOptions {
+    /// Maximum number of messages to return (1-10)
+    pub max_messages: usize,
+    /// Seconds to wait for messages when the queue is empty (0-20)
+    pub wait_time_seconds: u64,
+    /// Overrides the queue's visibility timeout for the received messages
+    pub visibility_timeout: Option<u32>,
+}
+
+impl Default for ReceiveOptions {
+    fn default() -> Self {
+        Self { max_messages: 1, wait_time_seconds: 0, visibility_timeout: None }
+    }
+}
+
+/// Optional settings applied when sending a message
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SendOptions {
+    /// Message group (required for FIFO queues)
+    pub group_id: Option<String>,
+    /// Deduplication ID (FIFO queues without content-based deduplication)
+    pub deduplication_id: Option<String>,
+    /// Seconds before the message becomes visible, overriding the queue's delay.
+    /// Standard queues only; FIFO queues apply the queue delay to every message (as SQS does).
+    pub delay_seconds: Option<u32>,
+}
+
+/// One message of a SendMessageBatch request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendBatchEntry {
+    /// Caller-chosen ID, unique within the batch, used to report the result
+    pub id: String,
+    pub body: String,
+    #[serde(flatten)]
+    pub options: SendOptions,
+}
+
+/// One receipt handle of a DeleteMessageBatch request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteBatchEntry {
+    pub id: String,
+    pub receipt_handle: String,
+}
+
+/// Successful entry of a batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResultEntry {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+}
+
+/// Failed entry of a batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResultError {
+    pub id: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Per-entry outcome of a batch operation; entries fail independently
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResult {
+    pub successful: Vec<BatchResultEntry>,
+    pub failed: Vec<BatchResultError>,
+}
+
+impl BatchResult {
+    fn record(&mut self, id: String, result: ZeroResult<Option<String>>) {
+        match result {
+            Ok(message_id) => self.successful.push(BatchResultEntry { id, message_id }),
+            Err(e) => {
+                let code = match &e {
+                    ZeroError::NotFound(_) => "NotFound",
+                    ZeroError::Validation(_) | ZeroError::InvalidFields { .. } | ZeroError::InvalidRequest(_) => "InvalidParameterValue",
+                    _ => "InternalError",
+                };
+                self.failed.push(BatchResultError { id, code: code.into(), message: e.to_string() });
+            }
+        }
+    }
+}
+
+fn validate_visibility_timeout(secs: u32) -> ZeroResult<()> {
+    if secs > MAX_VISIBILITY_TIMEOUT_SECS {
+        return Err(ZeroError::Validation(format!("visibility_timeout must be between 0 and {}", MAX_VISIBILITY_TIMEOUT_SECS)));
+    }
+    Ok(())
+}
+
+fn validate_delay(secs: u32) -> ZeroResult<()> {
+    if secs > MAX_DELAY_SECS {
+        return Err(ZeroError::Validation(format!("delay_seconds must be between 0 and {}", MAX_DELAY_SECS)));
+    }
+    Ok(())
+}
+
+/// Receipt handles are URL-safe so they can be used as a path segment
+fn encode_receipt_handle(id: &str, next_visible: i64) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE, format!("{}:{}", id, next_visible))
+}
+
+/// Decode a receipt handle to its message ID (accepts standard base64 from older handles)
+fn decode_receipt_handle(receipt_handle: &str) -> ZeroResult<String> {
+    use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+
+    let bytes = base64::Engine::decode(&URL_SAFE, receipt_handle)
+        .or_else(|_| base64::Engine::decode(&STANDARD, receipt_handle))
+        .map_err(|e| ZeroError::Validation(format!("Invalid receipt handle: {}", e)))?;
+    let decoded = String::from_utf8(bytes)
+        .map_err(|e| ZeroError::Validation(format!("Invalid receipt handle UTF8: {}", e)))?;
+
+    match decoded.split(':').next() {
+        Some(id) if !id.is_empty() => Ok(id.to_string()),
+        _ => Err(ZeroError::Validation("Malformed receipt handle".into())),
+    }
+}
+
+/// Reject empty or oversized batches and duplicate entry IDs
+fn validate_batch_ids<'a>(ids: impl ExactSizeIterator<Item = &'a str>) -> ZeroResult<()> {
+    if ids.len() == 0 || ids.len() > MAX_BATCH_SIZE {
+        return Err(ZeroError::Validation(format!("A batch must contain between 1 and {} entries", MAX_BATCH_SIZE)));
+    }
+    let mut seen = std::collections::HashSet::new();
+    for id in ids {
+        if !seen.insert(id) {
+            return Err(ZeroError::Validation(format!("Batch entry ID {} is not distinct", id)));
+        }
+    }
+    Ok(())
+}
+
 impl QueueService {
     pub fn new(engine: Arc<ZeroEngine>) -> Self {
         Self { engine }
     }
 
     pub async fn create_queue(&self, name: &str) -> ZeroResult<String> {
+        self.create_queue_with_options(name, QueueOptions::default()).await
+    }
+
+    pub async fn create_queue_with_options(&self, name: &str, options: QueueOptions) -> ZeroResult<String> {
         let conn = self.engine.db.lock();
         
         // Ensure table exists
         let sql = "CREATE TABLE IF NOT EXISTS queues (
             name TEXT PRIMARY KEY,
-            url TEXT NOT NULL
+            url TEXT NOT NULL,
+            dlq_name TEXT,
+            max_receive_count INTEGER,
+            fifo INTEGER NOT NULL DEFAULT 0,
+            content_dedup INTEGER NOT NULL DEFAULT 0,
+            visibility_timeout INTEGER NOT NULL DEFAULT 30,
+            delay_seconds INTEGER NOT NULL DEFAULT 0
         )";
         conn.execute(sql, []).map_err(|e| ZeroError::Internal(e.to_string()))?;
 
@@ -29,19 +238,126 @@ impl QueueService {
             id TEXT PRIMARY KEY,
             queue_name TEXT NOT NULL,
             body TEXT NOT NULL,
-            visible_after INTEGER DEFAULT 0
+            visible_after INTEGER DEFAULT 0,
+            receive_count INTEGER DEFAULT 0,
+            source_queue TEXT,
+            group_id TEXT,
+            dedup_id TEXT
         )";
         conn.execute(sql_msg, []).map_err(|e| ZeroError::Internal(e.to_string()))?;
 
+        // Deduplication IDs seen by FIFO queues, kept for the dedup window
+        let sql_dedup = "CREATE TABLE IF NOT EXISTS message_dedup (
+            queue_name TEXT NOT NULL,
+            dedup_id TEXT NOT NULL,
+            message_id TEXT NOT NULL,
+            expires_at INTEGER NOT NULL,
+            PRIMARY KEY (queue_name, dedup_id)
+        )";
+        conn.execute(sql_dedup, []).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        if options.fifo && !name.ends_with(".fifo") {
+            return Err(ZeroError::Validation("FIFO queue names must end with .fifo".into()));
+        }
+        if !options.fifo && options.content_based_deduplication {
+            return Err(ZeroError::Validation("Content-based deduplication is only available for FIFO queues".into()));
+        }
+
+        let visibility_timeout = options.visibility_timeout.unwrap_or(DEFAULT_VISIBILITY_TIMEOUT_SECS);
+        validate_visibility_timeout(visibility_timeout)?;
+        let delay_seconds = options.delay_seconds.unwrap_or(0);
+        validate_delay(delay_seconds)?;
+
+        if let Some(policy) = &options.redrive_policy {
+            Self::validate_redrive_policy(&conn, name, options.fifo, policy)?;
+        }
+
         let url = format!("http://localhost:8080/v1/queue/{}/messages", name); // Mock URL
 
-        let insert = "INSERT OR REPLACE INTO queues (name, url) VALUES (?1, ?2)";
-        conn.execute(insert, zero_data_core::rusqlite::params![name, url])
-            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let insert = "INSERT OR REPLACE INTO queues (name, url, dlq_name, max_receive_count, fifo, content_dedup, visibility_timeout, delay_seconds)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+        conn.execute(insert, zero_data_core::rusqlite::params![
+            name,
+            url,
+            options.redrive_policy.as_ref().map(|p| p.dead_letter_queue.clone()),
+            options.redrive_policy.as_ref().map(|p| p.max_receive_count),
+            options.fifo,
+            options.content_based_deduplication,
+            visibility_timeout,
+            delay_seconds,
+        ]).map_err(|e| ZeroError::Internal(e.to_string()))?;
             
         Ok(url)
     }
 
+    fn validate_redrive_policy(conn: &zero_data_core::rusqlite::Connection, name: &str, fifo: bool, policy: &RedrivePolicy) -> ZeroResult<()> {
+        if policy.dead_letter_queue == name {
+            return Err(ZeroError::Validation("A queue cannot be its own dead-letter queue".into()));
+        }
+        if policy.max_receive_count == 0 {
+            return Err(ZeroError::Validation("max_receive_count must be at least 1".into()));
+        }
+        if !Self::queue_exists(conn, &policy.dead_letter_queue)? {
+            return Err(ZeroError::NotFound(format!("Dead-letter queue {} not found", policy.dead_letter_queue)));
+        }
+        if policy.dead_letter_queue.ends_with(".fifo") != fifo {
+            return Err(ZeroError::Validation("A dead-letter queue must be the same type (FIFO or standard) as its source queue".into()));
+        }
+        Ok(())
+    }
+
+    /// Dead-letter configuration of a queue, if it has one
+    pub async fn redrive_policy(&self, name: &str) -> ZeroResult<Option<RedrivePolicy>> {
+        if self.list_queues().await?.is_empty() {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", name)));
+        }
+        let conn = self.engine.db.lock();
+        let policy: Option<(Option<String>, Option<u32>)> = conn.query_row(
+            "SELECT dlq_name, max_receive_count FROM queues WHERE name = ?1",
+            zero_data_core::rusqlite::params![name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        match policy {
+            None => Err(ZeroError::NotFound(format!("Queue {} not found", name))),
+            Some((Some(dead_letter_queue), Some(max_receive_count))) => Ok(Some(RedrivePolicy { dead_letter_queue, max_receive_count })),
+            Some(_) => Ok(None),
+        }
+    }
+
+    /// Replace the dead-letter configuration of an existing queue, or remove it with `None`.
+    /// Messages already received keep their receive counts.
+    pub async fn set_redrive_policy(&self, name: &str, policy: Option<RedrivePolicy>) -> ZeroResult<()> {
+        if self.list_queues().await?.is_empty() {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", name)));
+        }
+        let conn = self.engine.db.lock();
+        let fifo: Option<bool> = conn.query_row(
+            "SELECT fifo FROM queues WHERE name = ?1",
+            zero_data_core::rusqlite::params![name],
+            |row| row.get(0),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let Some(fifo) = fifo else {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", name)));
+        };
+        if let Some(policy) = &policy {
+            Self::validate_redrive_policy(&conn, name, fifo, policy)?;
+        }
+        conn.execute(
+            "UPDATE queues SET dlq_name = ?1, max_receive_count = ?2 WHERE name = ?3",
+            zero_data_core::rusqlite::params![
+                policy.as_ref().map(|p| p.dead_letter_queue.clone()),
+                policy.as_ref().map(|p| p.max_receive_count),
+                name,
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    fn queue_exists(conn: &zero_data_core::rusqlite::Connection, name: &str) -> ZeroResult<bool> {
+        conn.query_row("SELECT count(*) FROM queues WHERE name = ?1", zero_data_core::rusqlite::params![name], |row| row.get(0))
+            .map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
     pub async fn list_queues(&self) -> ZeroResult<Vec<String>> {
         let conn = self.engine.db.lock();
         
@@ -59,71 +375,303 @@ impl QueueService {
         Ok(urls)
     }
 
+    /// Message counts of every queue, by name
+    pub async fn queue_stats(&self) -> ZeroResult<Vec<QueueStats>> {
+        if self.list_queues().await?.is_empty() {
+            return Ok(vec![]);
+        }
+        let conn = self.engine.db.lock();
+        let mut stmt = conn.prepare(
+            "SELECT q.name, COALESCE(SUM(m.visible_after <= ?1), 0), COALESCE(SUM(m.visible_after > ?1), 0)
+             FROM queues q LEFT JOIN messages m ON m.queue_name = q.name
+             GROUP BY q.name ORDER BY q.name"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let stats = stmt.query_map(zero_data_core::rusqlite::params![chrono::Utc::now().timestamp()], |row| {
+            Ok(QueueStats {
+                name: row.get(0)?,
+                visible: row.get::<_, i64>(1)? as u64,
+                not_visible: row.get::<_, i64>(2)? as u64,
+            })
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(stats)
+    }
+
+    /// Delete a queue and its messages; returns how many messages were purged. Queues
+    /// using it as their dead-letter queue lose their redrive policy.
+    pub async fn delete_queue(&self, name: &str) -> ZeroResult<usize> {
+        let conn = self.engine.db.lock();
+        if !Self::queue_exists(&conn, name)? {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", name)));
+        }
+        let purged = conn.execute("DELETE FROM messages WHERE queue_name = ?1", zero_data_core::rusqlite::params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM message_dedup WHERE queue_name = ?1", zero_data_core::rusqlite::params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute(
+            "UPDATE queues SET dlq_name = NULL, max_receive_count = NULL WHERE dlq_name = ?1",
+            zero_data_core::rusqlite::params![name],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute("DELETE FROM queues WHERE name = ?1", zero_data_core::rusqlite::params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(purged)
+    }
+
     pub async fn send_message(&self, queue_name: &str, body: &str) -> ZeroResult<String> {
+        self.send_message_with_options(queue_name, body, SendOptions::default()).await
+    }
+
+    pub async fn send_message_with_options(&self, queue_name: &str, body: &str, options: SendOptions) -> ZeroResult<String> {
         let conn = self.engine.db.lock();
         let id = uuid::Uuid::new_v4().to_string();
-        
-        // SQS standard: MessageId
-        let insert = "INSERT INTO messages (id, queue_name, body, visible_after) VALUES (?1, ?2, ?3, 0)";
-        conn.execute(insert, zero_data_core::rusqlite::params![id, queue_name, body])
+
+        let (fifo, content_dedup, queue_delay): (bool, bool, u32) = conn.query_row(
+            "SELECT fifo, content_dedup, delay_seconds FROM queues WHERE name = ?1",
+            zero_data_core::rusqlite::params![queue_name],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        ).unwrap_or((false, false, 0));
+
+        if let Some(secs) = options.delay_seconds {
+            validate_delay(secs)?;
+        }
+
+        // Delayed messages are stored up front and only become visible once the delay elapses
+        let now = chrono::Utc::now().timestamp();
+        let visible_after = now + i64::from(options.delay_seconds.unwrap_or(queue_delay));
+
+        if !fifo {
+            if options.group_id.is_some() || options.deduplication_id.is_some() {
+                return Err(ZeroError::Validation("group_id and deduplication_id are only valid for FIFO queues".into()));
+            }
+
+            // SQS standard: MessageId
+            let insert = "INSERT INTO messages (id, queue_name, body, visible_after) VALUES (?1, ?2, ?3, ?4)";
+            conn.execute(insert, zero_data_core::rusqlite::params![id, queue_name, body, visible_after])
+                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            self.engine.events.publish(QUEUE_MESSAGE_SENT, queue_name, json!({ "message_id": id }));
+            return Ok(id);
+        }
+
+        if options.delay_seconds.is_some() {
+            return Err(ZeroError::Validation("Per-message delay_seconds is not supported for FIFO queues; set the queue delay instead".into()));
+        }
+
+        let group_id = options.group_id
+            .ok_or_else(|| ZeroError::Validation("group_id is required for FIFO queues".into()))?;
+        let dedup_id = match options.deduplication_id {
+            Some(d) => d,
+            None if content_dedup => format!("{:x}", Sha256::digest(body.as_bytes())),
+            None => return Err(ZeroError::Validation(
+                "deduplication_id is required when content-based deduplication is disabled".into()
+            )),
+        };
+
+        conn.execute("DELETE FROM message_dedup WHERE expires_at <= ?1", zero_data_core::rusqlite::params![now])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
-            
+
+        // A duplicate within the window is accepted but not enqueued again
+        let existing: Option<String> = conn.query_row(
+            "SELECT message_id FROM message_dedup WHERE queue_name = ?1 AND dedup_id = ?2",
+            zero_data_core::rusqlite::params![queue_name, dedup_id],
+            |row| row.get(0),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if let Some(existing_id) = existing {
+            return Ok(existing_id);
+        }
+
+        conn.execute(
+            "INSERT INTO messages (id, queue_name, body, visible_after, group_id, dedup_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            zero_data_core::rusqlite::params![id, queue_name, body, visible_after, group_id, dedup_id],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        conn.execute(
+            "INSERT INTO message_dedup (queue_name, dedup_id, message_id, expires_at) VALUES (?1, ?2, ?3, ?4)",
+            zero_data_core::rusqlite::params![queue_name, dedup_id, id, now + DEDUP_WINDOW_SECS],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        self.engine.events.publish(QUEUE_MESSAGE_SENT, queue_name, json!({ "message_id": id, "group_id": group_id }));
+
         Ok(id)
     }
 
+    /// Send up to 10 messages in one call; entries fail independently
+    pub async fn send_message_batch(&self, queue_name: &str, entries: Vec<SendBatchEntry>) -> ZeroResult<BatchResult> {
+        validate_batch_ids(entries.iter().map(|e| e.id.as_str()))?;
+        if !self.has_queue(queue_name) {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", queue_name)));
+        }
+
+        let mut result = BatchResult::default();
+        for entry in entries {
+            let sent = self.send_message_with_options(queue_name, &entry.body, entry.options).await;
+            result.record(entry.id, sent.map(Some));
+        }
+        Ok(result)
+    }
+
     pub async fn receive_message(&self, queue_name: &str) -> ZeroResult<Option<serde_json::Value>> {
+        Ok(self.receive_messages(queue_name, ReceiveOptions::default()).await?.pop())
+    }
+
+    /// Receive up to `max_messages` messages. When the queue is empty, wait up to
+    /// `wait_time_seconds` for messages to arrive before returning an empty batch (long polling).
+    pub async fn receive_messages(&self, queue_name: &str, options: ReceiveOptions) -> ZeroResult<Vec<serde_json::Value>> {
+        if options.max_messages == 0 || options.max_messages > MAX_BATCH_SIZE {
+            return Err(ZeroError::Validation(format!("max_messages must be between 1 and {}", MAX_BATCH_SIZE)));
+        }
+        if options.wait_time_seconds > MAX_WAIT_TIME_SECS {
+            return Err(ZeroError::Validation(format!("wait_time_seconds must be between 0 and {}", MAX_WAIT_TIME_SECS)));
+        }
+        if let Some(secs) = options.visibility_timeout {
+            validate_visibility_timeout(secs)?;
+        }
+        if options.wait_time_seconds > 0 && !self.has_queue(queue_name) {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", queue_name)));
+        }
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(options.wait_time_seconds);
+        loop {
+            let messages = self.receive_batch(queue_name, options.max_messages, options.visibility_timeout)?;
+            if !messages.is_empty() || tokio::time::Instant::now() >= deadline {
+                return Ok(messages);
+            }
+            tokio::time::sleep(LONG_POLL_INTERVAL).await;
+        }
+    }
+
+    pub(crate) fn has_queue(&self, name: &str) -> bool {
+        Self::queue_exists(&self.engine.db.lock(), name).unwrap_or(false)
+    }
+
+    /// Messages a receive could return now, as SQS's ApproximateNumberOfMessagesVisible
+    pub(crate) fn visible_messages(&self, name: &str) -> ZeroResult<u64> {
+        if !self.has_queue(name) {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", name)));
+        }
+        self.engine.db.lock().query_row(
+            "SELECT count(*) FROM messages WHERE queue_name = ?1 AND visible_after <= ?2",
+            zero_data_core::rusqlite::params![name, chrono::Utc::now().timestamp()],
+            |row| row.get(0),
+        ).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    fn receive_batch(&self, queue_name: &str, max_messages: usize, visibility_override: Option<u32>) -> ZeroResult<Vec<serde_json::Value>> {
         let conn = self.engine.db.lock();
         let now = chrono::Utc::now().timestamp();
-        
-        // Find first message that is visible
-        let mut stmt = conn.prepare("SELECT id, body FROM messages WHERE queue_name = ?1 AND visible_after <= ?2 LIMIT 1")
-             .map_err(|e| ZeroError::Internal(e.to_string()))?;
-        
-        let mut rows = stmt.query(zero_data_core::rusqlite::params![queue_name, now])
-             .map_err(|e| ZeroError::Internal(e.to_string()))?;
 
-        if let Some(row) = rows.next().map_err(|e| ZeroError::Internal(e.to_string()))? {
-            let id: String = row.get(0).unwrap();
-            let body: String = row.get(1).unwrap();
-            
-            // Standard SQS visibility timeout: 30 seconds
-            let visibility_timeout = 30;
+        let (redrive, queue_visibility) = conn.query_row(
+            "SELECT dlq_name, max_receive_count, visibility_timeout FROM queues WHERE name = ?1",
+            zero_data_core::rusqlite::params![queue_name],
+            |row| Ok((row.get::<_, Option<String>>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, i64>(2)?)),
+        ).ok().map(|(dlq, max, visibility)| (dlq.zip(max), visibility))
+            .unwrap_or((None, DEFAULT_VISIBILITY_TIMEOUT_SECS as i64));
+        let visibility_timeout = visibility_override.map(i64::from).unwrap_or(queue_visibility);
+
+        let mut messages = Vec::new();
+        let mut picked: Vec<String> = Vec::new();
+        while messages.len() < max_messages {
+            // Find first message that is visible. A FIFO message is only eligible while it is the
+            // oldest in its group, so an in-flight message blocks the rest of its group. Messages
+            // picked earlier in this batch do not block, so a batch can carry a group in order.
+            let next = conn.query_row(
+                "SELECT id, body, receive_count, group_id, dedup_id, rowid FROM messages m
+                 WHERE queue_name = ?1 AND visible_after <= ?2
+                 AND NOT EXISTS (
+                     SELECT 1 FROM messages o
+                     WHERE o.queue_name = m.queue_name AND o.group_id = m.group_id AND o.rowid < m.rowid
+                     AND o.id NOT IN (SELECT value FROM json_each(?3))
+                 )
+                 ORDER BY rowid LIMIT 1",
+                zero_data_core::rusqlite::params![queue_name, now, serde_json::to_string(&picked).unwrap_or_default()],
+                |row| Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, i64>(5)?,
+                )),
+            ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+            let Some((id, body, receive_count, group_id, dedup_id, sequence)) = next else {
+                break;
+            };
+
+            // Messages that already hit the receive limit go to the DLQ instead of being delivered
+            if let Some((dlq, max_receive_count)) = &redrive {
+                if receive_count >= *max_receive_count {
+                    conn.execute(
+                        "UPDATE messages SET queue_name = ?1, source_queue = ?2, receive_count = 0, visible_after = 0 WHERE id = ?3",
+                        zero_data_core::rusqlite::params![dlq, queue_name, id],
+                    ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+                    tracing::debug!("Moved message {} from {} to dead-letter queue {}", id, queue_name, dlq);
+                    continue;
+                }
+            }
+
             let next_visible = now + visibility_timeout;
+            let receive_count = receive_count + 1;
             
             // Generate a receipt handle (for now just the id, but in AWS it's a signed blob)
-            let receipt_handle = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, format!("{}:{}", id, next_visible));
+            let receipt_handle = encode_receipt_handle(&id, next_visible);
 
-            conn.execute("UPDATE messages SET visible_after = ?1 WHERE id = ?2", zero_data_core::rusqlite::params![next_visible, id])
-                .map_err(|e| ZeroError::Internal(e.to_string()))?;
+            conn.execute(
+                "UPDATE messages SET visible_after = ?1, receive_count = ?2 WHERE id = ?3",
+                zero_data_core::rusqlite::params![next_visible, receive_count, id],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+            let mut attributes = json!({
+                "ApproximateReceiveCount": receive_count.to_string(),
+                "SentTimestamp": now.to_string()
+            });
+            if let Some(group_id) = group_id {
+                attributes["MessageGroupId"] = json!(group_id);
+                attributes["MessageDeduplicationId"] = json!(dedup_id);
+                attributes["SequenceNumber"] = json!(sequence.to_string());
+            }
 
-            Ok(Some(json!({ 
-                "MessageId": id, 
+            messages.push(json!({ 
+                "MessageId": id.clone(), 
                 "Body": body,
                 "ReceiptHandle": receipt_handle,
-                "Attributes": {
-                    "ApproximateReceiveCount": "1",
-                    "SentTimestamp": now.to_string()
-                }
-            })))
-        } else {
-            Ok(None)
+                "Attributes": attributes
+            }));
+            picked.push(id);
         }
+
+        Ok(messages)
     }
 
-    pub async fn delete_message(&self, _queue_name: &str, receipt_handle: &str) -> ZeroResult<()> {
+    /// Move messages out of a dead-letter queue. Without a `destination` each message
+    /// returns to the queue it was dead-lettered from. Returns the number of messages moved.
+    pub async fn redrive(&self, dlq_name: &str, destination: Option<&str>) -> ZeroResult<usize> {
         let conn = self.engine.db.lock();
-        
-        // Decode receipt handle to get ID
-        let decoded = String::from_utf8(
-            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, receipt_handle)
-                .map_err(|e| ZeroError::Validation(format!("Invalid receipt handle: {}", e)))?
-        ).map_err(|e| ZeroError::Validation(format!("Invalid receipt handle UTF8: {}", e)))?;
-        
-        let parts: Vec<&str> = decoded.split(':').collect();
-        if parts.is_empty() {
-             return Err(ZeroError::Validation("Malformed receipt handle".into()));
+
+        if !Self::queue_exists(&conn, dlq_name)? {
+            return Err(ZeroError::NotFound(format!("Queue {} not found", dlq_name)));
         }
-        let id = parts[0];
+
+        let moved = match destination {
+            Some(dest) => {
+                if !Self::queue_exists(&conn, dest)? {
+                    return Err(ZeroError::NotFound(format!("Queue {} not found", dest)));
+                }
+                conn.execute(
+                    "UPDATE messages SET queue_name = ?1, source_queue = NULL, receive_count = 0, visible_after = 0 WHERE queue_name = ?2",
+                    zero_data_core::rusqlite::params![dest, dlq_name],
+                )
+            }
+            None => conn.execute(
+                "UPDATE messages SET queue_name = source_queue, source_queue = NULL, receive_count = 0, visible_after = 0
+                 WHERE queue_name = ?1 AND source_queue IS NOT NULL",
+                zero_data_core::rusqlite::params![dlq_name],
+            ),
+        }.map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        Ok(moved)
+    }
+
+    pub async fn delete_message(&self, _queue_name: &str, receipt_handle: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        let id = decode_receipt_handle(receipt_handle)?;
 
         let affected = conn.execute("DELETE FROM messages WHERE id = ?1", zero_data_core::rusqlite::params![id])
             .map_err(|e| ZeroError::Internal(e.to_string()))?;
@@ -133,4 +681,46 @@ impl QueueService {
         }
         Ok(())
     }
+
+    /// Change how long an in-flight message stays hidden, counted from now. A timeout of 0
+    /// makes the message visible immediately.
+    pub async fn change_message_visibility(&self, queue_name: &str, receipt_handle: &str, visibility_timeout: u32) -> ZeroResult<()> {
+        validate_visibility_timeout(visibility_timeout)?;
+        let id = decode_receipt_handle(receipt_handle)?;
+        let conn = self.engine.db.lock();
+        let now = chrono::Utc::now().timestamp();
+
+        let message: Option<(i64, i64)> = conn.query_row(
+            "SELECT visible_after, receive_count FROM messages WHERE id = ?1 AND queue_name = ?2",
+            zero_data_core::rusqlite::params![id, queue_name],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        // A message that is still delayed has never been received, so it is not in flight either
+        match message {
+            None => Err(ZeroError::NotFound(format!("Message {} not found in queue {}", id, queue_name))),
+            Some((visible_after, receive_count)) if visible_after <= now || receive_count == 0 => {
+                Err(ZeroError::InvalidRequest(format!("Message {} is not in flight", id)))
+            }
+            Some(_) => {
+                conn.execute(
+                    "UPDATE messages SET visible_after = ?1 WHERE id = ?2",
+                    zero_data_core::rusqlite::params![now + i64::from(visibility_timeout), id],
+                ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Delete up to 10 messages by receipt handle; entries fail independently
+    pub async fn delete_message_batch(&self, queue_name: &str, entries: Vec<DeleteBatchEntry>) -> ZeroResult<BatchResult> {
+        validate_batch_ids(entries.iter().map(|e| e.id.as_str()))?;
+
+        let mut result = BatchResult::default();
+        for entry in entries {
+            let deleted = self.delete_message(queue_name, &entry.receipt_handle).await;
+            result.record(entry.id, deleted.map(|_| None));
+        }
+        Ok(result)
+    }
 }
@@ -0,0 +1,309 @@
+//! Rolling updates and revisions of workloads
+//!
+//! A workload's revisions are numbered from 1, the spec it was created from; every update
+//! adds one. An update to a new image replaces the workload without a manual down and up:
+//! 1. the new revision starts next to the old one, as `<id>-rev<N>` without the workload's
+//!    ports; only the probe's port is published, on a free host port
+//! 2. it must pass a health check within the update's timeout: be running, and pass the
+//!    workload's liveness probe if it has one. A revision the probe cannot reach fails.
+//! 3. the old instance stops and the workload is recreated from the new revision under its own
+//!    ID, with its ports, and checked again; the check instance keeps serving meanwhile
+//! 4. the check instance is removed
+//!
+//! When a check fails the workload stays on, or goes back to, its old revision, and the new
+//! revision is recorded as failed. The supervisor leaves the workload alone during step 3.
+
+use zero_control_spi::{ComputeDriver, LivenessProbe, PortMapping, ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::events::{WORKLOAD_ROLLED_BACK, WORKLOAD_UPDATED};
+use zero_data_core::rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use super::image::ImageService;
+use super::placement::PlacementService;
+use super::supervisor::{self, SupervisorService, WorkloadSpec};
+
+pub const DEFAULT_HEALTH_TIMEOUT_SECS: u32 = 30;
+pub const MAX_HEALTH_TIMEOUT_SECS: u32 = 600;
+/// Time between two looks at a new revision during its health check
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub const REVISION_ACTIVE: &str = "active";
+pub const REVISION_SUPERSEDED: &str = "superseded";
+pub const REVISION_FAILED: &str = "failed";
+
+/// A revision of a workload
+#[derive(Debug, Clone, Serialize)]
+pub struct Revision {
+    pub revision: u32,
+    pub image: String,
+    /// `active` for the one the workload runs, `superseded` or `failed`
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    /// Why a failed revision was rolled back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip)]
+    pub spec: WorkloadSpec,
+}
+
+/// Outcome of an update
+#[derive(Debug, Clone, Serialize)]
+pub struct Rollout {
+    pub id: String,
+    /// `Updated`, or `RolledBack` when the new revision failed its health check
+    pub status: &'static str,
+    /// Revision the workload runs now
+    pub revision: u32,
+    pub image: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_revision: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+fn db_error(e: zero_data_core::rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS workload_revisions (
+            workload_id TEXT NOT NULL,
+            revision INTEGER NOT NULL,
+            spec TEXT NOT NULL,
+            status TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            reason TEXT,
+            PRIMARY KEY (workload_id, revision)
+        );
+    ").map_err(db_error)
+}
+
+#[derive(Clone)]
+pub struct RolloutService {
+    engine: Arc<ZeroEngine>,
+    placement: PlacementService,
+    supervisor: SupervisorService,
+    images: ImageService,
+    http_client: Client,
+    /// Workloads with an update in progress
+    updating: Arc<Mutex<HashSet<String>>>,
+}
+
+impl RolloutService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self {
+            placement: PlacementService::new(engine.clone()),
+            supervisor: SupervisorService::new(engine.clone()),
+            images: ImageService::new(engine.clone()),
+            engine,
+            http_client: Client::new(),
+            updating: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Record the spec a workload was created from at `now` as its first revision
+    pub fn record(&self, id: &str, spec: &WorkloadSpec, now: DateTime<Utc>) -> ZeroResult<()> {
+        self.forget(id)?;
+        self.insert(id, 1, spec, REVISION_ACTIVE, None, now)
+    }
+
+    /// Drop a workload's revisions, as when it is deleted
+    pub fn forget(&self, id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.execute("DELETE FROM workload_revisions WHERE workload_id = ?1", params![id]).map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Revisions of a workload, oldest first
+    pub fn list(&self, id: &str) -> ZeroResult<Vec<Revision>> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT revision, spec, status, created_at, reason FROM workload_revisions WHERE workload_id = ?1 ORDER BY revision"
+        ).map_err(db_error)?;
+        let rows = stmt.query_map(params![id], |row| Ok((
+            row.get::<_, u32>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))).map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>().map_err(db_error)?;
+
+        rows.into_iter().map(|(revision, spec, status, created_at, reason)| {
+            let spec: WorkloadSpec = serde_json::from_str(&spec).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            Ok(Revision {
+                revision,
+                image: spec.image.clone(),
+                status,
+                created_at: DateTime::parse_from_rfc3339(&created_at).map_err(|e| ZeroError::Internal(e.to_string()))?.with_timezone(&Utc),
+                reason,
+                spec,
+            })
+        }).collect()
+    }
+
+    fn insert(&self, id: &str, revision: u32, spec: &WorkloadSpec, status: &str, reason: Option<&str>, now: DateTime<Utc>) -> ZeroResult<()> {
+        let spec = serde_json::to_string(spec).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        if status == REVISION_ACTIVE {
+            conn.execute(
+                "UPDATE workload_revisions SET status = ?1 WHERE workload_id = ?2 AND status = ?3",
+                params![REVISION_SUPERSEDED, id, REVISION_ACTIVE],
+            ).map_err(db_error)?;
+        }
+        conn.execute(
+            "INSERT INTO workload_revisions (workload_id, revision, spec, status, created_at, reason) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, revision, spec, status, now.to_rfc3339(), reason],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    /// The revision a workload runs; workloads created before revisions were recorded get a
+    /// first one from their supervised spec
+    fn active(&self, id: &str, revisions: &[Revision]) -> ZeroResult<Revision> {
+        if let Some(active) = revisions.iter().find(|revision| revision.status == REVISION_ACTIVE) {
+            return Ok(active.clone());
+        }
+        let supervision = self.supervisor.get(id)?.ok_or_else(|| ZeroError::Validation(
+            format!("Workload {} has no recorded spec to update; delete and create it again", id)
+        ))?;
+        let now = Utc::now();
+        self.insert(id, 1, &supervision.spec, REVISION_ACTIVE, None, now)?;
+        Ok(Revision { revision: 1, image: supervision.spec.image.clone(), status: REVISION_ACTIVE.into(), created_at: now, reason: None, spec: supervision.spec })
+    }
+
+    /// Roll a workload over to `image`, waiting up to `health_timeout` for each health check
+    pub async fn update(&self, id: &str, image: &str, health_timeout: Duration) -> ZeroResult<Rollout> {
+        if !self.updating.lock().unwrap_or_else(|e| e.into_inner()).insert(id.to_string()) {
+            return Err(ZeroError::Validation(format!("Workload {} is already being updated", id)));
+        }
+        let result = self.roll(id, image, health_timeout).await;
+        self.updating.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+        result
+    }
+
+    async fn roll(&self, id: &str, image: &str, health_timeout: Duration) -> ZeroResult<Rollout> {
+        let revisions = self.list(id)?;
+        let old = self.active(id, &revisions)?;
+        let next = revisions.iter().map(|revision| revision.revision).max().unwrap_or(old.revision) + 1;
+        let spec = WorkloadSpec { image: image.to_string(), ..old.spec.clone() };
+        let compute = self.placement.compute_for(id).await?;
+        let supervision = self.supervisor.get(id)?;
+        let probe = supervision.as_ref().and_then(|supervision| supervision.liveness_probe.clone());
+
+        // The new revision starts next to the old one; the old one holds the ports, so workloads
+        // without an address of their own are probed through a free one
+        let check_id = format!("{}-rev{}", id, next);
+        let probe_ports: Vec<_> = probe.iter()
+            .map(|probe| PortMapping { host_port: 0, container_port: probe.port, protocol: Default::default() })
+            .collect();
+        let checked = match compute.create_workload_with_ports(&check_id, &spec.image, spec.cpu, spec.memory_mb, &spec.mounts, &probe_ports).await {
+            Ok(_) => self.check_health(&compute, &check_id, probe.as_ref(), health_timeout).await,
+            Err(e) => Err(format!("Starting {} failed: {}", check_id, e)),
+        };
+        if let Err(reason) = checked {
+            if let Err(e) = compute.delete_workload(&check_id).await {
+                tracing::debug!("Rollout: removing {} failed: {}", check_id, e);
+            }
+            return self.rolled_back(id, &old, next, &spec, reason);
+        }
+
+        // Or the supervisor could restart the old revision while it is replaced
+        self.supervisor.forget(id)?;
+        if let Err(e) = compute.delete_workload(id).await {
+            tracing::debug!("Rollout: stopping the old revision of {} failed: {}", id, e);
+        }
+        let replaced = match compute.create_workload_with_ports(id, &spec.image, spec.cpu, spec.memory_mb, &spec.mounts, &spec.ports).await {
+            Ok(_) => self.check_health(&compute, id, probe.as_ref(), health_timeout).await,
+            Err(e) => Err(format!("Starting {} failed: {}", id, e)),
+        };
+        let restored = match &replaced {
+            Ok(()) => Ok(()),
+            Err(_) => {
+                if let Err(e) = compute.delete_workload(id).await {
+                    tracing::debug!("Rollout: removing the failed revision of {} failed: {}", id, e);
+                }
+                let old = &old.spec;
+                compute.create_workload_with_ports(id, &old.image, old.cpu, old.memory_mb, &old.mounts, &old.ports).await.map(|_| ())
+            }
+        };
+        if let Err(e) = compute.delete_workload(&check_id).await {
+            tracing::debug!("Rollout: removing {} failed: {}", check_id, e);
+        }
+        if let Some(supervision) = &supervision {
+            let running = if replaced.is_ok() { &spec } else { &old.spec };
+            self.supervisor.resume(supervision, running, Utc::now())?;
+        }
+
+        match replaced {
+            Ok(()) => {
+                let now = Utc::now();
+                self.insert(id, next, &spec, REVISION_ACTIVE, None, now)?;
+                // Images on nodes are cached by their own drivers
+                if !self.placement.placements().await?.contains_key(id) {
+                    self.images.use_image(id, &spec.image, now)?;
+                }
+                self.engine.events.publish(WORKLOAD_UPDATED, id, json!({ "image": spec.image, "revision": next }));
+                Ok(Rollout { id: id.to_string(), status: "Updated", revision: next, image: spec.image, failed_revision: None, reason: None })
+            }
+            Err(reason) => {
+                if let Err(e) = restored {
+                    self.insert(id, next, &spec, REVISION_FAILED, Some(&reason), Utc::now())?;
+                    return Err(ZeroError::Driver(format!("Rolling workload {} back to revision {} failed: {}", id, old.revision, e)));
+                }
+                self.rolled_back(id, &old, next, &spec, reason)
+            }
+        }
+    }
+
+    fn rolled_back(&self, id: &str, old: &Revision, failed: u32, spec: &WorkloadSpec, reason: String) -> ZeroResult<Rollout> {
+        tracing::warn!("Rollout: workload {} stays on revision {}: {}", id, old.revision, reason);
+        self.insert(id, failed, spec, REVISION_FAILED, Some(&reason), Utc::now())?;
+        self.engine.events.publish(WORKLOAD_ROLLED_BACK, id, json!({
+            "image": spec.image, "revision": old.revision, "failed_revision": failed, "reason": reason
+        }));
+        Ok(Rollout {
+            id: id.to_string(),
+            status: "RolledBack",
+            revision: old.revision,
+            image: old.image.clone(),
+            failed_revision: Some(failed),
+            reason: Some(reason),
+        })
+    }
+
+    /// Wait for a workload to run and pass its liveness probe, failing as soon as it stops or
+    /// fails, when the probe has nothing to reach, or after `timeout`
+    async fn check_health(&self, compute: &Arc<dyn ComputeDriver>, id: &str, probe: Option<&LivenessProbe>, timeout: Duration) -> Result<(), String> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let waiting_for = match compute.get_workload_status(id).await {
+                Ok(status) if status.has_failed() || status.has_stopped() => return Err(format!("{} is {}", id, status.state)),
+                Ok(status) if status.is_running() => match probe {
+                    None => return Ok(()),
+                    Some(probe) => match supervisor::probe_workload(&self.http_client, &status, probe).await {
+                        Some(Ok(())) => return Ok(()),
+                        None => return Err(format!("{} has no address or published port {} to probe", id, probe.port)),
+                        Some(Err(e)) => format!("its liveness probe failed: {}", e),
+                    },
+                },
+                Ok(status) => format!("it is {}", status.state),
+                Err(e) => return Err(format!("{} is gone: {}", id, e)),
+            };
+            if tokio::time::Instant::now() + HEALTH_POLL_INTERVAL > deadline {
+                return Err(format!("{} was not healthy after {} s; {}", id, timeout.as_secs(), waiting_for));
+            }
+            tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,1086 @@
+//! S3-compatible gateway: the S3 REST protocol on its own port, in front of the store
+//! service, so existing S3 clients (aws cli, mc, rclone) can use ZeroCloud buckets.
+//!
+//! Only path-style addressing is served (`http://host:port/<bucket>/<key>`):
+//! - Service: `ListBuckets`
+//! - Buckets: `CreateBucket`, `HeadBucket`, `DeleteBucket`, `GetBucketLocation`,
+//!   `ListObjects` and `ListObjectsV2` (prefix, delimiter and paging), `DeleteObjects`
+//! - Objects: `PutObject`, `CopyObject`, `GetObject` (with a single `Range`), `HeadObject`,
+//!   `DeleteObject` and multipart uploads (create, upload part, complete, abort)
+//!
+//! Requests signed with AWS Signature Version 4, in the `Authorization` header or as a
+//! presigned URL, are verified against IAM access keys and role sessions; the region of the
+//! credential scope is not checked, and for `aws-chunked` uploads only the seed signature is
+//! verified, not the signature of each chunk. Role sessions are authorized as the equivalent
+//! `/v1/store/buckets/...` request.
+//!
+//! Objects are served as `application/octet-stream`. ETags of data written through the gateway
+//! are MD5 digests as in S3; objects written through the ZeroCloud API get an ETag derived from
+//! their size and modification time.
+
+use super::iam::{request_action, IamService, MAX_CLOCK_SKEW_SECS};
+use super::store::{ObjectInfo, StoreService};
+use zero_control_spi::{percent_decode, ByteStream, ZeroBody, ZeroError, ZeroResult};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection};
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, request::Parts, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Router,
+};
+use bytes::Bytes;
+use futures::{StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::net::TcpListener;
+
+/// Scheme of SigV4 `Authorization` headers and presigned URLs
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+/// Longest lifetime S3 allows for a presigned URL
+const MAX_PRESIGNED_EXPIRES_SECS: i64 = 7 * 24 * 3600;
+/// Hash of the payload covered by the signature, or a marker such as `UNSIGNED-PAYLOAD`
+const CONTENT_SHA256_HEADER: &str = "x-amz-content-sha256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+/// Source object of a `CopyObject` request, `<bucket>/<key>`
+const COPY_SOURCE_HEADER: &str = "x-amz-copy-source";
+
+const XMLNS: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+/// Region reported for every bucket
+const REGION: &str = "us-east-1";
+/// Largest page of a listing, and the most keys one `DeleteObjects` request may name
+const MAX_KEYS: usize = 1000;
+/// Largest XML document accepted for `DeleteObjects` and `CompleteMultipartUpload`
+const MAX_XML_BODY_BYTES: usize = 4 * 1024 * 1024;
+/// Part numbers of a multipart upload range from 1 to this
+const MAX_PART_NUMBER: u32 = 10_000;
+/// Longest line of `aws-chunked` framing before the body is rejected as malformed
+const MAX_CHUNK_HEADER_BYTES: usize = 4096;
+
+/// S3 protocol front end of the store service
+pub struct S3Gateway {
+    engine: Arc<ZeroEngine>,
+    store: StoreService,
+    iam: IamService,
+    /// Reject anonymous requests instead of serving them
+    require_auth: bool,
+}
+
+impl S3Gateway {
+    pub fn new(engine: Arc<ZeroEngine>, require_auth: bool) -> Self {
+        let store = StoreService::new(engine.clone());
+        let iam = IamService::new(engine.clone());
+        Self { engine, store, iam, require_auth }
+    }
+
+    /// Bind `port` on all interfaces and serve S3 requests until the returned task is aborted
+    pub async fn start(self, port: u16) -> ZeroResult<tokio::task::JoinHandle<()>> {
+        let listener = TcpListener::bind(("0.0.0.0", port)).await
+            .map_err(|e| ZeroError::Internal(format!("Failed to bind S3 gateway port {}: {}", port, e)))?;
+        let gateway = Arc::new(self);
+        tracing::info!("ZeroStore S3 gateway on port {}", port);
+
+        Ok(tokio::spawn(async move {
+            let app = Router::new().fallback(move |req: Request| {
+                let gateway = gateway.clone();
+                async move { gateway.serve(req).await }
+            });
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::error!("ZeroStore S3 gateway on port {} stopped: {}", port, e);
+            }
+        }))
+    }
+
+    async fn serve(&self, req: Request) -> Response {
+        let request_id = uuid::Uuid::new_v4().to_string();
+        let (parts, body) = req.into_parts();
+        let mut response = match self.dispatch(&parts, body).await {
+            Ok(response) => response,
+            Err(e) => e.into_response(parts.uri.path(), &request_id, parts.method == Method::HEAD),
+        };
+        if let Ok(value) = request_id.parse() {
+            response.headers_mut().insert("x-amz-request-id", value);
+        }
+        response
+    }
+
+    async fn dispatch(&self, parts: &Parts, body: Body) -> Result<Response, S3Error> {
+        let query = Query::parse(parts.uri.query().unwrap_or(""));
+        let principal = self.authenticate(parts, &query)?;
+        match &principal {
+            Some(_) => {},
+            None if !self.require_auth => {},
+            None => return Err(S3Error::access_denied("Anonymous requests are not allowed")),
+        }
+
+        let (bucket, key) = split_path(parts.uri.path());
+        self.authorize(principal.as_deref(), &parts.method, &bucket, &key)?;
+        match (bucket.as_str(), key.as_str()) {
+            ("", _) if parts.method == Method::GET => self.list_buckets().await,
+            ("", _) => Err(S3Error::method_not_allowed()),
+            (bucket, "") => self.bucket_request(parts, &query, bucket, body, principal.as_deref()).await,
+            (bucket, key) => self.object_request(parts, &query, bucket, key, body, principal.as_deref()).await,
+        }
+    }
+
+    async fn bucket_request(
+        &self,
+        parts: &Parts,
+        query: &Query,
+        bucket: &str,
+        body: Body,
+        principal: Option<&str>,
+    ) -> Result<Response, S3Error> {
+        const LIST_PARAMS: &[&str] = &[
+            "prefix", "delimiter", "marker", "max-keys", "encoding-type",
+            "list-type", "continuation-token", "start-after", "fetch-owner",
+        ];
+        if parts.method == Method::PUT && query.only(&[]) {
+            return self.create_bucket(bucket).await;
+        }
+        self.require_bucket(bucket).await?;
+        match parts.method {
+            Method::HEAD => Ok(([("x-amz-bucket-region", REGION)], StatusCode::OK).into_response()),
+            Method::DELETE if query.only(&[]) => self.delete_bucket(bucket).await,
+            Method::GET if query.has("location") && query.only(&["location"]) => {
+                // Buckets in us-east-1 report an empty location constraint
+                Ok(xml_response(StatusCode::OK, format!(r#"<LocationConstraint xmlns="{}"></LocationConstraint>"#, XMLNS)))
+            },
+            Method::GET if query.only(LIST_PARAMS) => {
+                self.list_objects(bucket, query, query.get("list-type") == Some("2")).await
+            },
+            Method::POST if query.has("delete") && query.only(&["delete"]) => {
+                self.delete_objects(parts, bucket, body, principal).await
+            },
+            _ => Err(S3Error::not_implemented()),
+        }
+    }
+
+    async fn object_request(
+        &self,
+        parts: &Parts,
+        query: &Query,
+        bucket: &str,
+        key: &str,
+        body: Body,
+        principal: Option<&str>,
+    ) -> Result<Response, S3Error> {
+        if !query.only(&["uploadId", "uploads", "partNumber"]) {
+            return Err(S3Error::not_implemented());
+        }
+        self.require_bucket(bucket).await?;
+        let copy = parts.headers.contains_key(COPY_SOURCE_HEADER);
+        match (&parts.method, query.get("uploadId")) {
+            (&Method::PUT, Some(_)) if copy => Err(S3Error::not_implemented()),
+            (&Method::PUT, Some(upload_id)) => self.upload_part(parts, query, bucket, upload_id, body).await,
+            (&Method::PUT, None) if copy => self.copy_object(parts, bucket, key, principal).await,
+            (&Method::PUT, None) => self.put_object(parts, bucket, key, body).await,
+            (&Method::GET, None) => self.get_object(parts, bucket, key, true).await,
+            (&Method::HEAD, None) => self.get_object(parts, bucket, key, false).await,
+            (&Method::DELETE, Some(upload_id)) => {
+                self.store.abort_multipart_upload(bucket, upload_id).await.map_err(upload_error)?;
+                Ok(StatusCode::NO_CONTENT.into_response())
+            },
+            (&Method::DELETE, None) => self.delete_object(bucket, key).await,
+            (&Method::POST, None) if query.has("uploads") => self.create_multipart_upload(bucket, key).await,
+            (&Method::POST, Some(upload_id)) => self.complete_multipart_upload(parts, bucket, key, upload_id, body).await,
+            _ => Err(S3Error::not_implemented()),
+        }
+    }
+
+    /// Principal of a request signed with SigV4; `None` for anonymous requests
+    fn authenticate(&self, parts: &Parts, query: &Query) -> Result<Option<String>, S3Error> {
+        let signature = match parts.headers.get(header::AUTHORIZATION) {
+            Some(value) => Signature::from_header(value.to_str().unwrap_or(""), &parts.headers)?,
+            None if query.has("X-Amz-Algorithm") => Signature::from_query(query)?,
+            None if query.has("AWSAccessKeyId") => return Err(S3Error::unsupported_signature()),
+            None => return Ok(None),
+        };
+        signature.check_time()?;
+
+        let (principal, secret) = match self.iam.signing_secret(&signature.access_key_id, signature.session_token.as_deref()) {
+            Ok(found) => found,
+            Err(ZeroError::Unauthorized(message)) => {
+                return Err(S3Error::new(StatusCode::FORBIDDEN, "InvalidAccessKeyId", message));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let string_to_sign = signature.string_to_sign(parts, query);
+        let mut mac = Hmac::<Sha256>::new_from_slice(&signing_key(&secret, &signature.scope))
+            .map_err(|e| S3Error::from(ZeroError::Internal(e.to_string())))?;
+        mac.update(string_to_sign.as_bytes());
+        mac.verify_slice(&signature.signature).map_err(|_| S3Error::new(
+            StatusCode::FORBIDDEN,
+            "SignatureDoesNotMatch",
+            "The request signature we calculated does not match the signature you provided",
+        ))?;
+        Ok(Some(principal))
+    }
+
+    /// Role sessions may only do what the role's policy allows for the equivalent API request
+    fn authorize(&self, principal: Option<&str>, method: &Method, bucket: &str, key: &str) -> Result<(), S3Error> {
+        let Some(principal) = principal.filter(|p| IamService::is_role_session(p)) else {
+            return Ok(());
+        };
+        let mut path = String::from("/v1/store/buckets");
+        if !bucket.is_empty() {
+            path = format!("{}/{}", path, bucket);
+        }
+        if !key.is_empty() {
+            path = format!("{}/objects/{}", path, key);
+        }
+        let method = if method == Method::HEAD { "GET" } else { method.as_str() };
+        let (action, resource) = request_action(method, &path);
+        if !self.iam.verify_permission(principal, &action, &resource) {
+            return Err(S3Error::access_denied(format!(
+                "{} is not authorized to perform {} on {}", principal, action, resource
+            )));
+        }
+        Ok(())
+    }
+
+    async fn require_bucket(&self, bucket: &str) -> Result<(), S3Error> {
+        if self.store.list_buckets().await?.iter().any(|b| b == bucket) {
+            Ok(())
+        } else {
+            Err(S3Error::new(StatusCode::NOT_FOUND, "NoSuchBucket", "The specified bucket does not exist"))
+        }
+    }
+
+    async fn list_buckets(&self) -> Result<Response, S3Error> {
+        let mut volumes = self.engine.storage.list_volumes().await?;
+        volumes.sort_by(|a, b| a.id.cmp(&b.id));
+        let mut buckets = String::new();
+        for volume in volumes {
+            let created = tokio::fs::metadata(&volume.path).await
+                .and_then(|m| m.modified())
+                .map(chrono::DateTime::<chrono::Utc>::from)
+                .unwrap_or_else(|_| chrono::Utc::now());
+            buckets.push_str(&format!(
+                "<Bucket><Name>{}</Name><CreationDate>{}</CreationDate></Bucket>",
+                xml_escape(&volume.id), created.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            ));
+        }
+        Ok(xml_response(StatusCode::OK, format!(
+            r#"<ListAllMyBucketsResult xmlns="{}"><Owner><ID>zero</ID><DisplayName>zero</DisplayName></Owner><Buckets>{}</Buckets></ListAllMyBucketsResult>"#,
+            XMLNS, buckets,
+        )))
+    }
+
+    async fn create_bucket(&self, bucket: &str) -> Result<Response, S3Error> {
+        let valid = (3..=63).contains(&bucket.len())
+            && bucket.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.')
+            && bucket.starts_with(|c: char| c.is_ascii_alphanumeric())
+            && bucket.ends_with(|c: char| c.is_ascii_alphanumeric());
+        if !valid {
+            return Err(S3Error::new(StatusCode::BAD_REQUEST, "InvalidBucketName", "The specified bucket is not valid"));
+        }
+        // As in us-east-1, creating a bucket you already own succeeds
+        self.store.create_bucket(bucket).await?;
+        Ok(([(header::LOCATION, format!("/{}", bucket))], StatusCode::OK).into_response())
+    }
+
+    async fn delete_bucket(&self, bucket: &str) -> Result<Response, S3Error> {
+        match self.store.delete_bucket(bucket).await {
+            Ok(()) => {},
+            Err(ZeroError::InvalidRequest(_)) => return Err(S3Error::new(
+                StatusCode::CONFLICT, "BucketNotEmpty", "The bucket you tried to delete is not empty",
+            )),
+            Err(e) => return Err(e.into()),
+        }
+        let conn = self.engine.db.lock();
+        ensure_etags_table(&conn)?;
+        conn.execute("DELETE FROM s3_etags WHERE bucket = ?1", params![bucket])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
+
+    /// One page of `ListObjects` (`v2` false) or `ListObjectsV2`; keys sharing a prefix up to the
+    /// delimiter are rolled up into one common prefix
+    async fn list_objects(&self, bucket: &str, query: &Query, v2: bool) -> Result<Response, S3Error> {
+        let prefix = query.get("prefix").unwrap_or("");
+        let delimiter = query.get("delimiter").unwrap_or("");
+        let max_keys = match query.get("max-keys") {
+            Some(value) => value.parse::<usize>()
+                .map_err(|_| S3Error::invalid_argument("max-keys must be a non-negative number"))?
+                .min(MAX_KEYS),
+            None => MAX_KEYS,
+        };
+        let url_encoded = query.get("encoding-type") == Some("url");
+        let encode = |value: &str| if url_encoded { uri_encode(value, false) } else { value.to_string() };
+        let marker = match (v2, query.get("continuation-token")) {
+            (true, Some(token)) => base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE, token).ok()
+                .and_then(|marker| String::from_utf8(marker).ok())
+                .ok_or_else(|| S3Error::invalid_argument("The continuation token provided is incorrect"))?,
+            (true, None) => query.get("start-after").unwrap_or("").to_string(),
+            (false, _) => query.get("marker").unwrap_or("").to_string(),
+        };
+
+        let objects = self.store.list_objects(bucket).await?;
+        let mut contents = Vec::new();
+        let mut common_prefixes: Vec<&str> = Vec::new();
+        let mut next_marker = None;
+        let mut truncated = false;
+        for object in &objects {
+            let key = object.key.as_str();
+            if !key.starts_with(prefix) || key <= marker.as_str() {
+                continue;
+            }
+            let common_prefix = if delimiter.is_empty() {
+                None
+            } else {
+                key[prefix.len()..].find(delimiter).map(|i| &key[..prefix.len() + i + delimiter.len()])
+            };
+            if let Some(common_prefix) = common_prefix {
+                if common_prefix <= marker.as_str() || common_prefixes.last() == Some(&common_prefix) {
+                    continue;
+                }
+            }
+            if contents.len() + common_prefixes.len() == max_keys {
+                truncated = true;
+                break;
+            }
+            match common_prefix {
+                Some(common_prefix) => common_prefixes.push(common_prefix),
+                None => contents.push(object),
+            }
+            next_marker = Some(common_prefix.unwrap_or(key));
+        }
+
+        let mut xml = format!(
+            r#"<ListBucketResult xmlns="{}"><Name>{}</Name><Prefix>{}</Prefix><MaxKeys>{}</MaxKeys><IsTruncated>{}</IsTruncated>"#,
+            XMLNS, xml_escape(bucket), xml_escape(&encode(prefix)), max_keys, truncated,
+        );
+        if !delimiter.is_empty() {
+            xml.push_str(&format!("<Delimiter>{}</Delimiter>", xml_escape(&encode(delimiter))));
+        }
+        if url_encoded {
+            xml.push_str("<EncodingType>url</EncodingType>");
+        }
+        if v2 {
+            xml.push_str(&format!("<KeyCount>{}</KeyCount>", contents.len() + common_prefixes.len()));
+            if let Some(token) = query.get("continuation-token") {
+                xml.push_str(&format!("<ContinuationToken>{}</ContinuationToken>", xml_escape(token)));
+            }
+            if let Some(start_after) = query.get("start-after") {
+                xml.push_str(&format!("<StartAfter>{}</StartAfter>", xml_escape(&encode(start_after))));
+            }
+            if let (true, Some(next)) = (truncated, next_marker) {
+                let token = base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE, next);
+                xml.push_str(&format!("<NextContinuationToken>{}</NextContinuationToken>", token));
+            }
+        } else {
+            xml.push_str(&format!("<Marker>{}</Marker>", xml_escape(&encode(&marker))));
+            if let (true, Some(next)) = (truncated, next_marker) {
+                xml.push_str(&format!("<NextMarker>{}</NextMarker>", xml_escape(&encode(next))));
+            }
+        }
+        for object in contents {
+            xml.push_str(&format!(
+                "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag><Size>{}</Size><StorageClass>STANDARD</StorageClass></Contents>",
+                xml_escape(&encode(&object.key)), iso_timestamp(&object.last_modified),
+                xml_escape(&self.etag(bucket, object)?), object.size,
+            ));
+        }
+        for common_prefix in common_prefixes {
+            xml.push_str(&format!("<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>", xml_escape(&encode(common_prefix))));
+        }
+        xml.push_str("</ListBucketResult>");
+        Ok(xml_response(StatusCode::OK, xml))
+    }
+
+    async fn put_object(&self, parts: &Parts, bucket: &str, key: &str, body: Body) -> Result<Response, S3Error> {
+        let (data, digest) = md5_stream(request_body(&parts.headers, body));
+        // A body that does not match its declared hash never replaces the stored object
+        if self.store.put_object_checked(bucket, key, data, declared_sha256(&parts.headers)).await?.is_none() {
+            return Err(S3Error::new(
+                StatusCode::BAD_REQUEST,
+                "XAmzContentSHA256Mismatch",
+                "The provided 'x-amz-content-sha256' header does not match what was computed",
+            ));
+        }
+        let etag = md5_etag(&digest);
+        self.record_etag(bucket, key, &etag).await?;
+        Ok(([(header::ETAG, etag)], StatusCode::OK).into_response())
+    }
+
+    async fn copy_object(&self, parts: &Parts, bucket: &str, key: &str, principal: Option<&str>) -> Result<Response, S3Error> {
+        let source = parts.headers.get(COPY_SOURCE_HEADER).and_then(|v| v.to_str().ok()).unwrap_or("");
+        let source = percent_decode(source.split('?').next().unwrap_or(""));
+        let (source_bucket, source_key) = source.trim_start_matches('/').split_once('/')
+            .filter(|(b, k)| !b.is_empty() && !k.is_empty())
+            .ok_or_else(|| S3Error::invalid_argument("Copy Source must mention the source bucket and key: sourcebucket/sourcekey"))?;
+        self.authorize(principal, &Method::GET, source_bucket, source_key)?;
+        self.require_bucket(source_bucket).await?;
+
+        let (_, data) = self.store.get_object(source_bucket, source_key).await?;
+        let (data, digest) = md5_stream(data);
+        self.store.put_object(bucket, key, data).await?;
+        let etag = md5_etag(&digest);
+        let info = self.record_etag(bucket, key, &etag).await?;
+        Ok(xml_response(StatusCode::OK, format!(
+            r#"<CopyObjectResult xmlns="{}"><LastModified>{}</LastModified><ETag>{}</ETag></CopyObjectResult>"#,
+            XMLNS, iso_timestamp(&info.last_modified), xml_escape(&etag),
+        )))
+    }
+
+    async fn get_object(&self, parts: &Parts, bucket: &str, key: &str, with_body: bool) -> Result<Response, S3Error> {
+        let info = self.store.head_object(bucket, key).await?;
+        let range = match parts.headers.get(header::RANGE) {
+            Some(value) => Some(parse_range(value.to_str().unwrap_or(""), info.size)?),
+            None => None,
+        };
+        let mut response = Response::builder()
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header(header::ETAG, self.etag(bucket, &info)?)
+            .header(header::LAST_MODIFIED, http_date(&info.last_modified))
+            .header(header::ACCEPT_RANGES, "bytes");
+        let (offset, length) = match range {
+            Some((first, last)) => {
+                response = response
+                    .status(StatusCode::PARTIAL_CONTENT)
+                    .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", first, last, info.size));
+                (first, last - first + 1)
+            }
+            None => (0, info.size),
+        };
+        let body = if with_body {
+            let (_, data) = self.store.get_object_range(bucket, key, offset, length).await?;
+            Body::from_stream(data)
+        } else {
+            Body::empty()
+        };
+        response.header(header::CONTENT_LENGTH, length).body(body)
+            .map_err(|e| S3Error::from(ZeroError::Internal(e.to_string())))
+    }
+
+    /// Deleting a missing key succeeds, as in S3
+    async fn delete_object(&self, bucket: &str, key: &str) -> Result<Response, S3Error> {
+        match self.store.delete_object(bucket, key).await {
+            Ok(()) | Err(ZeroError::NotFound(_)) => {},
+            Err(e) => return Err(e.into()),
+        }
+        self.forget_etag(bucket, key)?;
+        Ok(StatusCode::NO_CONTENT.into_response())
+    }
+
+    async fn delete_objects(&self, parts: &Parts, bucket: &str, body: Body, principal: Option<&str>) -> Result<Response, S3Error> {
+        let document = read_xml(&parts.headers, body).await?;
+        let quiet = xml_elements(&document, "Quiet").first().is_some_and(|q| q.trim() == "true");
+        let keys: Vec<String> = xml_elements(&document, "Object").iter()
+            .filter_map(|object| xml_elements(object, "Key").first().map(|key| xml_unescape(key)))
+            .collect();
+        if keys.is_empty() || keys.len() > MAX_KEYS {
+            return Err(S3Error::malformed_xml());
+        }
+
+        let mut results = String::new();
+        for key in keys {
+            let deleted = match self.authorize(principal, &Method::DELETE, bucket, &key) {
+                Ok(()) => self.delete_object(bucket, &key).await.map(|_| ()),
+                Err(e) => Err(e),
+            };
+            match deleted {
+                Ok(()) if quiet => {},
+                Ok(()) => results.push_str(&format!("<Deleted><Key>{}</Key></Deleted>", xml_escape(&key))),
+                Err(e) => results.push_str(&format!(
+                    "<Error><Key>{}</Key><Code>{}</Code><Message>{}</Message></Error>",
+                    xml_escape(&key), e.code, xml_escape(&e.message),
+                )),
+            }
+        }
+        Ok(xml_response(StatusCode::OK, format!(r#"<DeleteResult xmlns="{}">{}</DeleteResult>"#, XMLNS, results)))
+    }
+
+    async fn create_multipart_upload(&self, bucket: &str, key: &str) -> Result<Response, S3Error> {
+        let upload_id = self.store.create_multipart_upload(bucket, key).await?;
+        Ok(xml_response(StatusCode::OK, format!(
+            r#"<InitiateMultipartUploadResult xmlns="{}"><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>"#,
+            XMLNS, xml_escape(bucket), xml_escape(key), upload_id,
+        )))
+    }
+
+    async fn upload_part(&self, parts: &Parts, query: &Query, bucket: &str, upload_id: &str, body: Body) -> Result<Response, S3Error> {
+        let part_number = query.get("partNumber")
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|n| (1..=MAX_PART_NUMBER).contains(n))
+            .ok_or_else(|| S3Error::invalid_argument(format!("Part number must be an integer between 1 and {}", MAX_PART_NUMBER)))?;
+        let (data, digest) = md5_stream(request_body(&parts.headers, body));
+        self.store.upload_part(bucket, upload_id, part_number, data).await.map_err(upload_error)?;
+        Ok(([(header::ETAG, md5_etag(&digest))], StatusCode::OK).into_response())
+    }
+
+    /// The ETag of a multipart object is the MD5 of its parts' MD5 digests, suffixed with the part count
+    async fn complete_multipart_upload(&self, parts: &Parts, bucket: &str, key: &str, upload_id: &str, body: Body) -> Result<Response, S3Error> {
+        let document = read_xml(&parts.headers, body).await?;
+        let mut part_numbers = Vec::new();
+        let mut digests = Md5::new();
+        for part in xml_elements(&document, "Part") {
+            let number = xml_elements(part, "PartNumber").first().and_then(|n| n.trim().parse::<u32>().ok());
+            let digest = xml_elements(part, "ETag").first()
+                .and_then(|etag| hex::decode(xml_unescape(etag).trim().trim_matches('"')).ok());
+            let (Some(number), Some(digest)) = (number, digest) else {
+                return Err(S3Error::malformed_xml());
+            };
+            part_numbers.push(number);
+            digests.update(&digest);
+        }
+
+        match self.store.complete_multipart_upload(bucket, key, upload_id, &part_numbers).await {
+            Ok(_) => {},
+            Err(ZeroError::Validation(message)) => return Err(S3Error::new(StatusCode::BAD_REQUEST, "InvalidPart", message)),
+            Err(e) => return Err(upload_error(e)),
+        }
+        let etag = format!("\"{}-{}\"", hex::encode(digests.finalize()), part_numbers.len());
+        self.record_etag(bucket, key, &etag).await?;
+        Ok(xml_response(StatusCode::OK, format!(
+            r#"<CompleteMultipartUploadResult xmlns="{}"><Location>/{}/{}</Location><Bucket>{}</Bucket><Key>{}</Key><ETag>{}</ETag></CompleteMultipartUploadResult>"#,
+            XMLNS, xml_escape(bucket), xml_escape(&uri_encode(key, false)), xml_escape(bucket), xml_escape(key), xml_escape(&etag),
+        )))
+    }
+
+    /// Remember the ETag of an object just written; it holds while the object's size and
+    /// modification time are unchanged
+    async fn record_etag(&self, bucket: &str, key: &str, etag: &str) -> Result<ObjectInfo, S3Error> {
+        let info = self.store.head_object(bucket, key).await?;
+        let conn = self.engine.db.lock();
+        ensure_etags_table(&conn)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO s3_etags (bucket, key, etag, size, last_modified) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![bucket, key, etag, info.size as i64, info.last_modified],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(info)
+    }
+
+    fn forget_etag(&self, bucket: &str, key: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        ensure_etags_table(&conn)?;
+        conn.execute("DELETE FROM s3_etags WHERE bucket = ?1 AND key = ?2", params![bucket, key])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    fn etag(&self, bucket: &str, info: &ObjectInfo) -> ZeroResult<String> {
+        let recorded = {
+            let conn = self.engine.db.lock();
+            ensure_etags_table(&conn)?;
+            conn.query_row(
+                "SELECT etag FROM s3_etags WHERE bucket = ?1 AND key = ?2 AND size = ?3 AND last_modified = ?4",
+                params![bucket, info.key, info.size as i64, info.last_modified],
+                |row| row.get::<_, String>(0),
+            ).ok()
+        };
+        Ok(recorded.unwrap_or_else(|| {
+            let modified = chrono::DateTime::parse_from_rfc3339(&info.last_modified)
+                .map(|t| t.timestamp())
+                .unwrap_or_default();
+            format!("\"{:x}-{:x}\"", modified, info.size)
+        }))
+    }
+}
+
+fn ensure_etags_table(conn: &Connection) -> ZeroResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS s3_etags (
+            bucket TEXT NOT NULL,
+            key TEXT NOT NULL,
+            etag TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            last_modified TEXT NOT NULL,
+            PRIMARY KEY (bucket, key)
+        )",
+        [],
+    ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+    Ok(())
+}
+
+/// Error returned to the client as an S3 `<Error>` document
+#[derive(Debug)]
+struct S3Error {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl S3Error {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into() }
+    }
+
+    fn access_denied(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "AccessDenied", message)
+    }
+
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "InvalidArgument", message)
+    }
+
+    /// Signature Version 2 and other schemes
+    fn unsupported_signature() -> Self {
+        Self::new(
+            StatusCode::BAD_REQUEST,
+            "InvalidRequest",
+            format!("The authorization mechanism you have provided is not supported. Please use {}.", ALGORITHM),
+        )
+    }
+
+    fn malformed_xml() -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "MalformedXML", "The XML you provided was not well-formed or did not validate against our published schema")
+    }
+
+    fn method_not_allowed() -> Self {
+        Self::new(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed", "The specified method is not allowed against this resource")
+    }
+
+    fn not_implemented() -> Self {
+        Self::new(StatusCode::NOT_IMPLEMENTED, "NotImplemented", "A header or query you provided implies functionality that is not implemented")
+    }
+
+    fn into_response(self, resource: &str, request_id: &str, head: bool) -> Response {
+        if self.status.is_server_error() {
+            tracing::error!("S3 request {} failed: {}", request_id, self.message);
+        }
+        // Responses to HEAD requests carry no body, only the status
+        if head {
+            return self.status.into_response();
+        }
+        xml_response(self.status, format!(
+            "<Error><Code>{}</Code><Message>{}</Message><Resource>{}</Resource><RequestId>{}</RequestId></Error>",
+            self.code, xml_escape(&self.message), xml_escape(resource), request_id,
+        ))
+    }
+}
+
+/// Store errors of object requests; the bucket is known to exist by then
+impl From<ZeroError> for S3Error {
+    fn from(e: ZeroError) -> Self {
+        let message = e.message().to_string();
+        match e {
+            ZeroError::NotFound(_) => Self::new(StatusCode::NOT_FOUND, "NoSuchKey", "The specified key does not exist"),
+            ZeroError::Validation(_) | ZeroError::InvalidRequest(_) | ZeroError::InvalidFields { .. } => Self::invalid_argument(message),
+            ZeroError::Unauthorized(_) => Self::access_denied(message),
+            ZeroError::AlreadyExists(_) => Self::new(StatusCode::CONFLICT, "OperationAborted", message),
+            _ => Self::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalError", message),
+        }
+    }
+}
+
+/// Store errors of multipart requests, where a missing resource is the upload
+fn upload_error(e: ZeroError) -> S3Error {
+    match e {
+        ZeroError::NotFound(_) => S3Error::new(
+            StatusCode::NOT_FOUND,
+            "NoSuchUpload",
+            "The specified multipart upload does not exist",
+        ),
+        e => e.into(),
+    }
+}
+
+/// Fields of a SigV4 signature, from the `Authorization` header or a presigned URL
+struct Signature {
+    access_key_id: String,
+    /// `<date>/<region>/<service>/aws4_request`
+    scope: String,
+    /// Signing time, `YYYYMMDDTHHMMSSZ`
+    timestamp: String,
+    signed_headers: Vec<String>,
+    signature: Vec<u8>,
+    payload_hash: String,
+    session_token: Option<String>,
+    /// Lifetime of a presigned URL; header signatures are valid within the clock skew
+    expires_secs: Option<i64>,
+}
+
+impl Signature {
+    /// `AWS4-HMAC-SHA256 Credential=<key id>/<scope>, SignedHeaders=<a;b>, Signature=<hex>`
+    fn from_header(authorization: &str, headers: &HeaderMap) -> Result<Self, S3Error> {
+        let fields = authorization.strip_prefix(ALGORITHM).ok_or_else(S3Error::unsupported_signature)?;
+        let field = |name: &str| fields.split(',')
+            .filter_map(|part| part.trim().split_once('='))
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v.to_string());
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let missing = |what: &str| S3Error::invalid_argument(format!("Authorization header is missing {}", what));
+
+        let credential = field("Credential").ok_or_else(|| missing("Credential"))?;
+        Self::new(
+            &credential,
+            header("x-amz-date").ok_or_else(|| missing("the x-amz-date header"))?,
+            &field("SignedHeaders").ok_or_else(|| missing("SignedHeaders"))?,
+            &field("Signature").ok_or_else(|| missing("Signature"))?,
+            header(CONTENT_SHA256_HEADER).ok_or_else(|| missing("the x-amz-content-sha256 header"))?,
+            header("x-amz-security-token"),
+            None,
+        )
+    }
+
+    /// Presigned URLs carry the signature in `X-Amz-*` query parameters and leave the payload unsigned
+    fn from_query(query: &Query) -> Result<Self, S3Error> {
+        let param = |name: &str| query.get(name)
+            .ok_or_else(|| S3Error::access_denied(format!("Query-string authentication requires the {} parameter", name)));
+        if param("X-Amz-Algorithm")? != ALGORITHM {
+            return Err(S3Error::invalid_argument("Unsupported X-Amz-Algorithm"));
+        }
+        let expires = param("X-Amz-Expires")?.parse::<i64>().ok()
+            .filter(|secs| (1..=MAX_PRESIGNED_EXPIRES_SECS).contains(secs))
+            .ok_or_else(|| S3Error::access_denied(format!(
+                "X-Amz-Expires must be between 1 and {} seconds", MAX_PRESIGNED_EXPIRES_SECS
+            )))?;
+        Self::new(
+            param("X-Amz-Credential")?,
+            param("X-Amz-Date")?.to_string(),
+            param("X-Amz-SignedHeaders")?,
+            param("X-Amz-Signature")?,
+            UNSIGNED_PAYLOAD.to_string(),
+            query.get("X-Amz-Security-Token").map(str::to_string),
+            Some(expires),
+        )
+    }
+
+    fn new(
+        credential: &str,
+        timestamp: String,
+        signed_headers: &str,
+        signature: &str,
+        payload_hash: String,
+        session_token: Option<String>,
+        expires_secs: Option<i64>,
+    ) -> Result<Self, S3Error> {
+        let malformed = || S3Error::new(StatusCode::BAD_REQUEST, "AuthorizationHeaderMalformed", "The authorization header is malformed");
+        let (access_key_id, scope) = credential.split_once('/').ok_or_else(malformed)?;
+        let scope_parts: Vec<&str> = scope.split('/').collect();
+        if scope_parts.len() != 4 || scope_parts[3] != "aws4_request" || !timestamp.starts_with(scope_parts[0]) {
+            return Err(malformed());
+        }
+        Ok(Self {
+            access_key_id: access_key_id.to_string(),
+            scope: scope.to_string(),
+            timestamp,
+            signed_headers: signed_headers.split(';').map(|h| h.to_ascii_lowercase()).collect(),
+            signature: hex::decode(signature).map_err(|_| malformed())?,
+            payload_hash,
+            session_token,
+            expires_secs,
+        })
+    }
+
+    fn check_time(&self) -> Result<(), S3Error> {
+        let signed_at = chrono::NaiveDateTime::parse_from_str(&self.timestamp, "%Y%m%dT%H%M%SZ")
+            .map_err(|_| S3Error::access_denied("X-Amz-Date must be in the ISO8601 basic format"))?
+            .and_utc();
+        let age = (chrono::Utc::now() - signed_at).num_seconds();
+        match self.expires_secs {
+            Some(expires) if age > expires => Err(S3Error::access_denied("Request has expired")),
+            Some(_) if age < -MAX_CLOCK_SKEW_SECS => Err(S3Error::access_denied("Request is not valid yet")),
+            None if age.abs() > MAX_CLOCK_SKEW_SECS => Err(S3Error::new(
+                StatusCode::FORBIDDEN,
+                "RequestTimeTooSkewed",
+                "The difference between the request time and the current time is too large",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn string_to_sign(&self, parts: &Parts, query: &Query) -> String {
+        let headers: String = self.signed_headers.iter().map(|name| {
+            let values: Vec<String> = parts.headers.get_all(name.as_str()).iter()
+                .map(|v| v.to_str().unwrap_or("").split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect();
+            format!("{}:{}\n", name, values.join(","))
+        }).collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            parts.method.as_str(),
+            canonical_uri(parts.uri.path()),
+            query.canonical(self.expires_secs.is_some()),
+            headers,
+            self.signed_headers.join(";"),
+            self.payload_hash,
+        );
+        format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM, self.timestamp, self.scope, hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        )
+    }
+}
+
+/// Key derived from the secret by signing each element of the credential scope in turn
+fn signing_key(secret: &str, scope: &str) -> Vec<u8> {
+    scope.split('/').fold(format!("AWS4{}", secret).into_bytes(), |key, part| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+        mac.update(part.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    })
+}
+
+/// Decoded query parameters, in request order
+struct Query(Vec<(String, String)>);
+
+impl Query {
+    fn parse(raw: &str) -> Self {
+        Self(raw.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(name), percent_decode(value))
+            })
+            .collect())
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Whether every parameter selecting the operation is one of `allowed`; signing parameters
+    /// and the `x-id` SDKs add for tracing are not part of the operation
+    fn only(&self, allowed: &[&str]) -> bool {
+        self.0.iter()
+            .map(|(name, _)| name.as_str())
+            .filter(|name| !name.to_ascii_lowercase().starts_with("x-amz-") && *name != "x-id")
+            .all(|name| allowed.contains(&name))
+    }
+
+    /// Encoded parameters sorted by name, then value; presigned URLs leave out their signature
+    fn canonical(&self, presigned: bool) -> String {
+        let mut pairs: Vec<(String, String)> = self.0.iter()
+            .filter(|(name, _)| !(presigned && name == "X-Amz-Signature"))
+            .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+            .collect();
+        pairs.sort();
+        pairs.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("&")
+    }
+}
+
+/// Bucket and key of a path-style request path, percent-decoded
+fn split_path(path: &str) -> (String, String) {
+    let path = path.strip_prefix('/').unwrap_or(path);
+    let (bucket, key) = path.split_once('/').unwrap_or((path, ""));
+    (percent_decode(bucket), percent_decode(key))
+}
+
+/// S3 signs the path encoded once, with each segment normalised to SigV4 encoding
+fn canonical_uri(path: &str) -> String {
+    let uri = path.split('/').map(|segment| uri_encode(&percent_decode(segment), true)).collect::<Vec<_>>().join("/");
+    if uri.is_empty() { "/".to_string() } else { uri }
+}
+
+/// Percent-encode everything but unreserved characters (and `/` unless `encode_slash`)
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Range of a `Range: bytes=<first>-<last>` header as inclusive offsets clamped to the object;
+/// suffix ranges (`bytes=-<n>`) select the last `n` bytes
+fn parse_range(value: &str, size: u64) -> Result<(u64, u64), S3Error> {
+    let unsatisfiable = || S3Error::new(StatusCode::RANGE_NOT_SATISFIABLE, "InvalidRange", "The requested range is not satisfiable");
+    let spec = value.trim().strip_prefix("bytes=").filter(|s| !s.contains(',')).ok_or_else(unsatisfiable)?;
+    let (first, last) = spec.split_once('-').ok_or_else(unsatisfiable)?;
+    let number = |n: &str| n.trim().parse::<u64>().map_err(|_| unsatisfiable());
+    let (first, last) = match (first.trim(), last.trim()) {
+        ("", suffix) => {
+            let length = number(suffix)?;
+            if length == 0 {
+                return Err(unsatisfiable());
+            }
+            (size.saturating_sub(length), size.saturating_sub(1))
+        }
+        (first, "") => (number(first)?, size.saturating_sub(1)),
+        (first, last) => (number(first)?, number(last)?.min(size.saturating_sub(1))),
+    };
+    if size == 0 || first >= size || first > last {
+        return Err(unsatisfiable());
+    }
+    Ok((first, last))
+}
+
+/// The request body, with `aws-chunked` framing removed when the client streamed it that way
+fn request_body(headers: &HeaderMap, body: Body) -> ByteStream {
+    let data: ByteStream = Box::pin(body.into_data_stream().map_err(std::io::Error::other));
+    let chunked = headers.get(header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|e| e.trim() == "aws-chunked"))
+        || headers.get(CONTENT_SHA256_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("STREAMING-"));
+    if chunked { decode_aws_chunked(data) } else { data }
+}
+
+/// Strip `aws-chunked` framing: `<hex size>[;chunk-signature=<sig>]\r\n<data>\r\n` repeated
+/// until a zero-size chunk, which may be followed by trailing checksum headers
+fn decode_aws_chunked(input: ByteStream) -> ByteStream {
+    struct Decoder {
+        input: ByteStream,
+        buffer: Vec<u8>,
+        /// Data bytes of the current chunk not yet passed on
+        remaining: usize,
+        /// The CRLF ending the current chunk's data has not been skipped yet
+        closing: bool,
+        done: bool,
+    }
+    let malformed = || std::io::Error::new(std::io::ErrorKind::InvalidData, "Malformed aws-chunked body");
+    let decoder = Decoder { input, buffer: Vec::new(), remaining: 0, closing: false, done: false };
+
+    Box::pin(futures::stream::try_unfold(decoder, move |mut d| async move {
+        loop {
+            if d.done {
+                return Ok(None);
+            }
+            if d.remaining > 0 && !d.buffer.is_empty() {
+                let n = d.remaining.min(d.buffer.len());
+                let data = Bytes::from(d.buffer.drain(..n).collect::<Vec<_>>());
+                d.remaining -= n;
+                d.closing = d.remaining == 0;
+                return Ok(Some((data, d)));
+            }
+            if d.remaining == 0 && d.closing && d.buffer.len() >= 2 {
+                if !d.buffer.starts_with(b"\r\n") {
+                    return Err(malformed());
+                }
+                d.buffer.drain(..2);
+                d.closing = false;
+                continue;
+            }
+            if d.remaining == 0 && !d.closing {
+                if let Some(end) = d.buffer.windows(2).position(|w| w == b"\r\n") {
+                    let line = std::str::from_utf8(&d.buffer[..end]).map_err(|_| malformed())?;
+                    let size = line.split(';').next().unwrap_or("").trim();
+                    let size = usize::from_str_radix(size, 16).map_err(|_| malformed())?;
+                    d.buffer.drain(..end + 2);
+                    // Trailing checksum headers after the last chunk are not checked
+                    d.done = size == 0;
+                    d.remaining = size;
+                    continue;
+                }
+                if d.buffer.len() > MAX_CHUNK_HEADER_BYTES {
+                    return Err(malformed());
+                }
+            }
+            match d.input.next().await {
+                Some(chunk) => d.buffer.extend_from_slice(&chunk?),
+                None => return Err(malformed()),
+            }
+        }
+    }))
+}
+
+/// Pass `data` through while computing its MD5, which is complete once the stream is consumed
+fn md5_stream(data: ByteStream) -> (ByteStream, Arc<Mutex<Md5>>) {
+    let digest = Arc::new(Mutex::new(Md5::new()));
+    let hasher = digest.clone();
+    let data = data.inspect_ok(move |chunk| hasher.lock().unwrap_or_else(PoisonError::into_inner).update(chunk));
+    (Box::pin(data), digest)
+}
+
+fn md5_etag(digest: &Mutex<Md5>) -> String {
+    let digest = digest.lock().unwrap_or_else(PoisonError::into_inner).clone();
+    format!("\"{}\"", hex::encode(digest.finalize()))
+}
+
+/// Hex SHA-256 of the payload declared by a signed request, if it declared one
+fn declared_sha256(headers: &HeaderMap) -> Option<&str> {
+    headers.get(CONTENT_SHA256_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| v.len() == 64 && v.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+async fn read_xml(headers: &HeaderMap, body: Body) -> Result<String, S3Error> {
+    let bytes = ZeroBody::from_stream(request_body(headers, body))
+        .collect(MAX_XML_BODY_BYTES).await
+        .map_err(|_| S3Error::malformed_xml())?;
+    String::from_utf8(bytes.to_vec()).map_err(|_| S3Error::malformed_xml())
+}
+
+/// Text of every `<tag>...</tag>` element of a document, in order; the element must not
+/// contain itself
+fn xml_elements<'a>(document: &'a str, tag: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut elements = Vec::new();
+    let mut rest = document;
+    while let Some(start) = rest.find(&open) {
+        let content = &rest[start + open.len()..];
+        let Some(end) = content.find(&close) else { break };
+        elements.push(&content[..end]);
+        rest = &content[end + close.len()..];
+    }
+    elements
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let entity = &rest[start..];
+        let Some(end) = entity.find(';') else { break };
+        let name = &entity[1..end];
+        let character = match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => name.strip_prefix("#x").map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| name.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match character {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &entity[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &entity[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+fn xml_response(status: StatusCode, document: String) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/xml")],
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", document),
+    ).into_response()
+}
+
+/// RFC 3339 time as S3 lists it, e.g. `2024-01-02T03:04:05.000Z`
+fn iso_timestamp(rfc3339: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.with_timezone(&chrono::Utc).format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
+
+/// RFC 3339 time as an HTTP date, e.g. `Tue, 02 Jan 2024 03:04:05 GMT`
+fn http_date(rfc3339: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.with_timezone(&chrono::Utc).format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
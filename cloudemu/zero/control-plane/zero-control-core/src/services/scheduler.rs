@@ -0,0 +1,554 @@
+//! Scheduled rules: on a cron or rate schedule, invoke a function, send a queue message or
+//! POST to a webhook
+//!
+//! Schedules follow EventBridge:
+//! - `rate(<n> <unit>)` with a unit of `minutes`, `hours` or `days`, and also `seconds` for
+//!   quick local feedback; runs are counted from the rule's creation
+//! - `cron(<minutes> <hours> <day-of-month> <month> <day-of-week> <year>)` in UTC, where exactly
+//!   one of the day fields is `?` and days of the week run from 1 (Sunday) to 7 (Saturday)
+//!
+//! A plain five-field crontab line such as `*/5 * * * 1-5` (days of the week from 0, Sunday)
+//! is accepted too. Fields take `*`, values, ranges, `/` steps, comma lists and the names
+//! `JAN`-`DEC` and `SUN`-`SAT`.
+//!
+//! Each due rule fires once per scheduler tick: runs missed while ZeroCloud was down are
+//! skipped, not replayed.
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection, ToSql};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use super::func::FuncService;
+use super::queue::QueueService;
+
+/// How often `ZeroProvider::spawn_scheduler` looks for due rules
+pub const SCHEDULER_TICK: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Header carrying the ARN of the rule that sent a webhook request
+pub const RULE_HEADER: &str = "x-zero-scheduler-rule";
+
+/// Attempts to deliver a run to a webhook before recording it as failed
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// Pause before the first webhook retry, doubled for each further retry
+const WEBHOOK_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+const MONTHS: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+const DAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Years a cron year field may name, as in EventBridge
+const YEARS: (u32, u32) = (1970, 2199);
+
+/// What a rule runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetType {
+    /// A ZeroCloud function, invoked asynchronously
+    Function,
+    /// A ZeroCloud queue, sent one message per run
+    Queue,
+    /// An HTTP(S) URL that receives each run as a POST
+    Webhook,
+}
+
+impl TargetType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TargetType::Function => "function",
+            TargetType::Queue => "queue",
+            TargetType::Webhook => "webhook",
+        }
+    }
+}
+
+impl std::str::FromStr for TargetType {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "function" => Ok(TargetType::Function),
+            "queue" => Ok(TargetType::Queue),
+            "webhook" => Ok(TargetType::Webhook),
+            other => Err(ZeroError::Validation(format!("Unknown rule target type: {}", other))),
+        }
+    }
+}
+
+/// Settings for a new rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRuleRequest {
+    pub name: String,
+    /// `rate(5 minutes)`, `cron(0 12 ? * MON-FRI *)` or a crontab line such as `*/5 * * * *`
+    pub schedule: String,
+    pub target_type: TargetType,
+    /// Function name, queue name or webhook URL
+    pub target: String,
+    /// Sent on every run instead of the scheduled event; queues receive strings as-is
+    #[serde(default)]
+    pub input: Option<Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub arn: String,
+    pub schedule: String,
+    pub target_type: TargetType,
+    pub target: String,
+    pub input: Option<Value>,
+    /// When the rule fires next; `None` once a cron schedule has no more runs
+    pub next_run: Option<String>,
+    pub last_run: Option<String>,
+    /// `Succeeded`, or why the last run failed
+    pub last_status: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Clone)]
+pub struct SchedulerService {
+    engine: Arc<ZeroEngine>,
+    func: FuncService,
+    queue: QueueService,
+    http: reqwest::Client,
+}
+
+impl SchedulerService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self {
+            func: FuncService::new(engine.clone()),
+            queue: QueueService::new(engine.clone()),
+            engine,
+            http: reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build().unwrap_or_default(),
+        }
+    }
+
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS scheduler_rules (
+                name TEXT PRIMARY KEY,
+                schedule TEXT NOT NULL,
+                target_type TEXT NOT NULL,
+                target TEXT NOT NULL,
+                input TEXT,
+                next_run TEXT,
+                last_run TEXT,
+                last_status TEXT,
+                created_at TEXT NOT NULL
+            );"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    pub async fn create_rule(&self, request: CreateRuleRequest) -> ZeroResult<Rule> {
+        let name = &request.name;
+        if name.is_empty() || name.len() > 64 || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+            return Err(ZeroError::Validation(format!(
+                "Invalid rule name {:?}: use 1-64 letters, digits, dots, hyphens and underscores", name
+            )));
+        }
+        let schedule: Schedule = request.schedule.parse()?;
+        match request.target_type {
+            TargetType::Function => {
+                if !self.func.has_function(&request.target) {
+                    return Err(ZeroError::NotFound(format!("Function not found: {}", request.target)));
+                }
+            }
+            TargetType::Queue => {
+                if !self.queue.has_queue(&request.target) {
+                    return Err(ZeroError::NotFound(format!("Queue not found: {}", request.target)));
+                }
+                // Runs carry no message group, which FIFO queues require
+                if request.target.ends_with(".fifo") {
+                    return Err(ZeroError::Validation("FIFO queues cannot be rule targets".into()));
+                }
+            }
+            TargetType::Webhook => {
+                if !(request.target.starts_with("http://") || request.target.starts_with("https://")) {
+                    return Err(ZeroError::Validation(format!("Webhook target must be an http(s) URL: {}", request.target)));
+                }
+            }
+        }
+
+        let now = Utc::now();
+        let Some(next_run) = schedule.next_after(now, now) else {
+            return Err(ZeroError::Validation(format!("Schedule {} never fires", request.schedule)));
+        };
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        if Self::find_rule(&conn, name)?.is_some() {
+            return Err(ZeroError::AlreadyExists(format!("Rule already exists: {}", name)));
+        }
+        let rule = Rule {
+            arn: rule_arn(name),
+            name: request.name.clone(),
+            schedule: request.schedule.trim().to_string(),
+            target_type: request.target_type,
+            target: request.target,
+            input: request.input,
+            next_run: Some(timestamp(next_run)),
+            last_run: None,
+            last_status: None,
+            created_at: now.to_rfc3339(),
+        };
+        conn.execute(
+            "INSERT INTO scheduler_rules (name, schedule, target_type, target, input, next_run, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                rule.name,
+                rule.schedule,
+                rule.target_type.as_str(),
+                rule.target,
+                rule.input.as_ref().map(|i| i.to_string()),
+                rule.next_run,
+                rule.created_at,
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(rule)
+    }
+
+    pub async fn list_rules(&self) -> ZeroResult<Vec<Rule>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::query_rules(&conn, "ORDER BY name", params![])
+    }
+
+    pub async fn get_rule(&self, name: &str) -> ZeroResult<Rule> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        Self::find_rule(&conn, name)?.ok_or_else(|| ZeroError::NotFound(format!("Rule not found: {}", name)))
+    }
+
+    pub async fn delete_rule(&self, name: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let deleted = conn.execute("DELETE FROM scheduler_rules WHERE name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if deleted == 0 {
+            return Err(ZeroError::NotFound(format!("Rule not found: {}", name)));
+        }
+        Ok(())
+    }
+
+    fn find_rule(conn: &Connection, name: &str) -> ZeroResult<Option<Rule>> {
+        Ok(Self::query_rules(conn, "WHERE name = ?1", params![name])?.pop())
+    }
+
+    fn query_rules(
+        conn: &Connection,
+        clause: &str,
+        params: &[&dyn ToSql],
+    ) -> ZeroResult<Vec<Rule>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT name, schedule, target_type, target, input, next_run, last_run, last_status, created_at
+             FROM scheduler_rules {}", clause
+        )).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let rows = stmt.query_map(params, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, String>(8)?,
+            ))
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        rows.into_iter().map(|(name, schedule, target_type, target, input, next_run, last_run, last_status, created_at)| {
+            Ok(Rule {
+                arn: rule_arn(&name),
+                name,
+                schedule,
+                target_type: target_type.parse()?,
+                target,
+                input: input.map(|i| serde_json::from_str(&i)).transpose()
+                    .map_err(|e| ZeroError::Internal(e.to_string()))?,
+                next_run,
+                last_run,
+                last_status,
+                created_at,
+            })
+        }).collect()
+    }
+
+    /// Fire every rule due at `now` and schedule its next run; returns how many rules fired
+    pub async fn run_due_rules(&self, now: DateTime<Utc>) -> ZeroResult<usize> {
+        // Claim the due rules before running them so an overlapping tick cannot fire them again
+        let due = {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            let due = Self::query_rules(&conn, "WHERE next_run <= ?1 ORDER BY next_run", params![timestamp(now)])?;
+            for rule in &due {
+                let schedule: Schedule = rule.schedule.parse()?;
+                let scheduled = rule.next_run.as_deref()
+                    .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                    .map_or(now, |t| t.with_timezone(&Utc));
+                conn.execute(
+                    "UPDATE scheduler_rules SET next_run = ?2, last_run = ?3 WHERE name = ?1",
+                    params![rule.name, schedule.next_after(scheduled, now).map(timestamp), timestamp(now)],
+                ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+            }
+            due
+        };
+
+        let runs = due.iter().map(|rule| async move { (rule, self.fire(rule, now).await) });
+        for (rule, result) in futures::future::join_all(runs).await {
+            let status = match result {
+                Ok(()) => "Succeeded".to_string(),
+                Err(e) => {
+                    tracing::warn!("Rule {} failed to run its {} target {}: {}", rule.name, rule.target_type.as_str(), rule.target, e);
+                    e.to_string()
+                }
+            };
+            let conn = self.engine.db.lock();
+            conn.execute(
+                "UPDATE scheduler_rules SET last_status = ?2 WHERE name = ?1",
+                params![rule.name, status],
+            ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        }
+        Ok(due.len())
+    }
+
+    async fn fire(&self, rule: &Rule, now: DateTime<Utc>) -> ZeroResult<()> {
+        let event = rule.input.clone().unwrap_or_else(|| json!({
+            "version": "0",
+            "id": uuid::Uuid::new_v4().to_string(),
+            "detail-type": "Scheduled Event",
+            "source": "zero.scheduler",
+            "account": "000000000000",
+            "time": timestamp(now),
+            "region": "us-east-1",
+            "resources": [rule.arn],
+            "detail": {},
+        }));
+        match rule.target_type {
+            TargetType::Function => self.func.invoke_function_async(&rule.target, event).await.map(|_| ()),
+            TargetType::Queue => {
+                let body = match event {
+                    Value::String(text) => text,
+                    other => other.to_string(),
+                };
+                self.queue.send_message(&rule.target, &body).await.map(|_| ())
+            }
+            TargetType::Webhook => self.post_webhook(&rule.target, &rule.arn, event.to_string()).await,
+        }
+    }
+
+    async fn post_webhook(&self, url: &str, rule_arn: &str, payload: String) -> ZeroResult<()> {
+        let mut backoff = WEBHOOK_RETRY_BACKOFF;
+        let mut failure = String::new();
+        for attempt in 1..=WEBHOOK_ATTEMPTS {
+            let result = self.http.post(url)
+                .header(RULE_HEADER, rule_arn)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(payload.clone())
+                .send().await;
+            match result {
+                Ok(resp) if resp.status().is_success() => return Ok(()),
+                Ok(resp) => failure = format!("Webhook answered {}", resp.status()),
+                Err(e) => failure = format!("Webhook request failed: {}", e),
+            }
+            tracing::debug!("{} (attempt {})", failure, attempt);
+            if attempt < WEBHOOK_ATTEMPTS {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+        Err(ZeroError::Driver(failure))
+    }
+}
+
+fn rule_arn(name: &str) -> String {
+    format!("arn:aws:events:us-east-1:000000000000:rule/{}", name)
+}
+
+/// Whole-second UTC timestamps, which compare correctly as text
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// When a rule fires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Rate(Duration),
+    Cron(Cron),
+}
+
+impl Schedule {
+    /// The first run after `now` of a schedule last due at `scheduled`
+    pub fn next_after(&self, scheduled: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Rate(every) => {
+                let every = every.num_milliseconds();
+                let missed = (now - scheduled).num_milliseconds().max(0) / every;
+                Some(scheduled + Duration::milliseconds(every * (missed + 1)))
+            }
+            Schedule::Cron(cron) => cron.next_after(now.max(scheduled)),
+        }
+    }
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = ZeroError;
+
+    fn from_str(expression: &str) -> ZeroResult<Self> {
+        let expression = expression.trim();
+        let parsed = if let Some(rate) = expression.strip_prefix("rate(").and_then(|r| r.strip_suffix(')')) {
+            parse_rate(rate).map(Schedule::Rate)
+        } else if let Some(cron) = expression.strip_prefix("cron(").and_then(|c| c.strip_suffix(')')) {
+            Cron::parse(cron, true).map(Schedule::Cron)
+        } else {
+            Cron::parse(expression, false).map(Schedule::Cron)
+        };
+        parsed.map_err(|reason| ZeroError::Validation(format!("Invalid schedule {:?}: {}", expression, reason)))
+    }
+}
+
+fn parse_rate(rate: &str) -> Result<Duration, String> {
+    let usage = || "use rate(<number> <seconds|minutes|hours|days>)".to_string();
+    let [value, unit] = rate.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(usage());
+    };
+    let value = value.parse::<i64>().ok().filter(|v| *v > 0).ok_or_else(usage)?;
+    match unit {
+        "second" | "seconds" => Ok(Duration::seconds(value)),
+        "minute" | "minutes" => Ok(Duration::minutes(value)),
+        "hour" | "hours" => Ok(Duration::hours(value)),
+        "day" | "days" => Ok(Duration::days(value)),
+        _ => Err(usage()),
+    }
+}
+
+/// A cron expression, as the values each field matches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    /// From 0 (Sunday) to 6
+    days_of_week: Vec<u32>,
+    years: Vec<u32>,
+    /// With both day fields restricted a day matches either of them, as in crontab
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl Cron {
+    /// Parse the six EventBridge fields, or the five crontab fields
+    fn parse(expression: &str, eventbridge: bool) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let expected = if eventbridge { 6 } else { 5 };
+        if fields.len() != expected {
+            return Err(format!("expected {} fields, found {}", expected, fields.len()));
+        }
+        let (day_of_month, day_of_week) = (fields[2], fields[4]);
+        if [0, 1, 3].into_iter().chain(eventbridge.then_some(5)).any(|i| fields[i].contains('?')) {
+            return Err("only the day fields may be ?".into());
+        }
+        if eventbridge && (day_of_month == "?") == (day_of_week == "?") {
+            return Err("use ? in exactly one of the day-of-month and day-of-week fields".into());
+        }
+
+        let days_of_week = if eventbridge {
+            parse_field(day_of_week, 1, 7, &DAYS, 1)?.into_iter().map(|d| d - 1).collect()
+        } else {
+            // Both 0 and 7 are Sunday
+            parse_field(day_of_week, 0, 7, &DAYS, 0)?.into_iter().map(|d| d % 7).collect::<BTreeSet<_>>().into_iter().collect()
+        };
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[], 0)?,
+            hours: parse_field(fields[1], 0, 23, &[], 0)?,
+            days_of_month: parse_field(day_of_month, 1, 31, &[], 0)?,
+            months: parse_field(fields[3], 1, 12, &MONTHS, 1)?,
+            days_of_week,
+            years: if eventbridge { parse_field(fields[5], YEARS.0, YEARS.1, &[], 0)? } else { (YEARS.0..=YEARS.1).collect() },
+            day_of_month_restricted: !matches!(day_of_month, "*" | "?"),
+            day_of_week_restricted: !matches!(day_of_week, "*" | "?"),
+        })
+    }
+
+    /// The first whole minute after `after` that matches every field
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let last_year = *self.years.last()?;
+        while time.year() as u32 <= last_year {
+            time = if !self.years.contains(&(time.year() as u32)) {
+                Utc.with_ymd_and_hms(time.year() + 1, 1, 1, 0, 0, 0).single()?
+            } else if !self.months.contains(&time.month()) {
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?
+            } else if !self.matches_day(time) {
+                Utc.from_utc_datetime(&time.date_naive().succ_opt()?.and_hms_opt(0, 0, 0)?)
+            } else if !self.hours.contains(&time.hour()) {
+                time.with_minute(0)? + Duration::hours(1)
+            } else if !self.minutes.contains(&time.minute()) {
+                time + Duration::minutes(1)
+            } else {
+                return Some(time);
+            };
+        }
+        None
+    }
+
+    fn matches_day(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month.contains(&time.day());
+        let day_of_week = self.days_of_week.contains(&time.weekday().num_days_from_sunday());
+        if self.day_of_month_restricted && self.day_of_week_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/// The sorted values a cron field matches; `names` spell out the values from `first_name`
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], first_name: u32) -> Result<Vec<u32>, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let value = text.parse::<u32>().ok()
+            .or_else(|| names.iter().position(|n| n.eq_ignore_ascii_case(text)).map(|i| i as u32 + first_name))
+            .ok_or_else(|| format!("{:?} is not a value", text))?;
+        if value < min || value > max {
+            return Err(format!("{} is outside {}-{}", value, min, max));
+        }
+        Ok(value)
+    };
+
+    let mut values = BTreeSet::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<usize>().ok().filter(|s| *s > 0)
+                    .ok_or_else(|| format!("{:?} is not a step", step))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" | "?" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `5/15` steps from 5 to the end of the field
+                None => {
+                    let start = value(range)?;
+                    (start, if part.contains('/') { max } else { start })
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("range {} runs backwards", range));
+        }
+        values.extend((start..=end).step_by(step));
+    }
+    Ok(values.into_iter().collect())
+}
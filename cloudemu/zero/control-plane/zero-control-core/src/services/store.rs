@@ -1,7 +1,36 @@
-use zero_control_spi::ZeroResult;
+use zero_control_spi::{ByteStream, ZeroError, ZeroResult};
 use zero_data_core::ZeroEngine;
+use futures::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
+/// Directory inside a bucket's volume that holds object data
+const OBJECTS_DIR: &str = "objects";
+/// Directory inside a bucket's volume for uploads that have not completed
+const UPLOADS_DIR: &str = ".uploads";
+/// Directory inside a bucket's volume holding the parts of multipart uploads
+const MULTIPART_DIR: &str = ".multipart";
+/// File inside an upload's directory recording the key it will complete to
+const UPLOAD_KEY_FILE: &str = "key";
+/// Chunk size used when streaming object data back to clients
+const READ_CHUNK_BYTES: usize = 64 * 1024;
+
+/// An object stored in a bucket
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: String,
+    /// Hex SHA-256 of the data, known when the object was just written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct StoreService {
     engine: Arc<ZeroEngine>,
 }
@@ -22,8 +51,291 @@ impl StoreService {
 
     pub async fn create_bucket(&self, name: &str) -> ZeroResult<()> {
         // Map bucket to a Volume (Directory)
-        // We use a small default size since it's just a folder
-        self.engine.storage.create_volume(name, 1).await?;
+        // Size 0: objects are files in the folder, with no block file behind them
+        self.engine.storage.create_volume(name, 0).await?;
         Ok(())
     }
+
+    /// Remove an empty bucket
+    pub async fn delete_bucket(&self, name: &str) -> ZeroResult<()> {
+        self.volume_path(name).await?;
+        if !self.list_objects(name).await?.is_empty() {
+            return Err(ZeroError::InvalidRequest(format!("Bucket {} is not empty", name)));
+        }
+        self.engine.storage.delete_volume(name).await
+    }
+
+    /// Write an object from a stream of chunks; the previous version stays readable
+    /// until the upload has completed
+    pub async fn put_object(&self, bucket: &str, key: &str, data: ByteStream) -> ZeroResult<ObjectInfo> {
+        let info = self.put_object_checked(bucket, key, data, None).await?;
+        Ok(info.expect("unchecked writes always complete"))
+    }
+
+    /// Write an object only if its content hashes to `expected_sha256` (hex); otherwise
+    /// nothing is stored and `None` is returned
+    pub async fn put_object_checked(&self, bucket: &str, key: &str, data: ByteStream, expected_sha256: Option<&str>) -> ZeroResult<Option<ObjectInfo>> {
+        let volume = self.volume_path(bucket).await?;
+        let path = object_path(&volume, key)?;
+        let Some((size, sha256)) = write_stream_checked(&volume, data, &path, expected_sha256).await? else {
+            return Ok(None);
+        };
+        Ok(Some(ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: chrono::Utc::now().to_rfc3339(),
+            sha256: Some(sha256),
+        }))
+    }
+
+    /// Open an object for reading; the data is read from disk as the stream is consumed
+    pub async fn get_object(&self, bucket: &str, key: &str) -> ZeroResult<(ObjectInfo, ByteStream)> {
+        let (info, file) = self.open_object(bucket, key).await?;
+        let data = tokio_util::io::ReaderStream::with_capacity(file, READ_CHUNK_BYTES);
+        Ok((info, Box::pin(data)))
+    }
+
+    /// Open `length` bytes of an object starting at `offset`; the range is clamped to the object
+    pub async fn get_object_range(&self, bucket: &str, key: &str, offset: u64, length: u64) -> ZeroResult<(ObjectInfo, ByteStream)> {
+        let (info, mut file) = self.open_object(bucket, key).await?;
+        file.seek(std::io::SeekFrom::Start(offset)).await.map_err(io_error)?;
+        let data = tokio_util::io::ReaderStream::with_capacity(file.take(length), READ_CHUNK_BYTES);
+        Ok((info, Box::pin(data)))
+    }
+
+    /// Size and modification time of an object without opening its data
+    pub async fn head_object(&self, bucket: &str, key: &str) -> ZeroResult<ObjectInfo> {
+        let path = object_path(&self.volume_path(bucket).await?, key)?;
+        match fs::metadata(&path).await {
+            Ok(metadata) if metadata.is_file() => Ok(object_info(key, &metadata)),
+            Ok(_) => Err(ZeroError::NotFound(format!("Object {}/{} not found", bucket, key))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ZeroError::NotFound(format!("Object {}/{} not found", bucket, key)))
+            }
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    pub async fn delete_object(&self, bucket: &str, key: &str) -> ZeroResult<()> {
+        let path = object_path(&self.volume_path(bucket).await?, key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(ZeroError::NotFound(format!("Object {}/{} not found", bucket, key)))
+            }
+            Err(e) => Err(io_error(e)),
+        }
+    }
+
+    /// Objects in a bucket, ordered by key
+    pub async fn list_objects(&self, bucket: &str) -> ZeroResult<Vec<ObjectInfo>> {
+        let root = self.volume_path(bucket).await?.join(OBJECTS_DIR);
+        let mut objects = Vec::new();
+        let mut pending = vec![root.clone()];
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(io_error(e)),
+            };
+            while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+                let metadata = entry.metadata().await.map_err(io_error)?;
+                if metadata.is_dir() {
+                    pending.push(entry.path());
+                    continue;
+                }
+                let path = entry.path();
+                let key = path.strip_prefix(&root).unwrap_or(&path)
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                objects.push(object_info(&key, &metadata));
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    /// Start an upload whose parts are written separately and joined into `key` on completion
+    pub async fn create_multipart_upload(&self, bucket: &str, key: &str) -> ZeroResult<String> {
+        let volume = self.volume_path(bucket).await?;
+        object_path(&volume, key)?;
+        let upload_id = uuid::Uuid::new_v4().simple().to_string();
+        let dir = volume.join(MULTIPART_DIR).join(&upload_id);
+        fs::create_dir_all(&dir).await.map_err(io_error)?;
+        fs::write(dir.join(UPLOAD_KEY_FILE), key).await.map_err(io_error)?;
+        Ok(upload_id)
+    }
+
+    /// Write one part of a multipart upload; uploading a part number again replaces it
+    pub async fn upload_part(&self, bucket: &str, upload_id: &str, part_number: u32, data: ByteStream) -> ZeroResult<ObjectInfo> {
+        let volume = self.volume_path(bucket).await?;
+        let dir = upload_dir(&volume, upload_id).await?;
+        let (size, sha256) = write_stream(&volume, data, &dir.join(part_file(part_number))).await?;
+        Ok(ObjectInfo {
+            key: part_number.to_string(),
+            size,
+            last_modified: chrono::Utc::now().to_rfc3339(),
+            sha256: Some(sha256),
+        })
+    }
+
+    /// Join the given parts, in ascending order, into the upload's object and discard the upload
+    pub async fn complete_multipart_upload(&self, bucket: &str, key: &str, upload_id: &str, parts: &[u32]) -> ZeroResult<ObjectInfo> {
+        let volume = self.volume_path(bucket).await?;
+        let dir = upload_dir(&volume, upload_id).await?;
+        let upload_key = fs::read_to_string(dir.join(UPLOAD_KEY_FILE)).await.map_err(io_error)?;
+        if upload_key != key {
+            return Err(ZeroError::NotFound(format!("Upload {} not found for {}/{}", upload_id, bucket, key)));
+        }
+        if parts.is_empty() {
+            return Err(ZeroError::Validation("An upload must complete with at least one part".into()));
+        }
+        if parts.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ZeroError::Validation("Parts must be listed in ascending order".into()));
+        }
+        let mut files = Vec::with_capacity(parts.len());
+        for part in parts {
+            match fs::File::open(dir.join(part_file(*part))).await {
+                Ok(file) => files.push(file),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(ZeroError::Validation(format!("Part {} was not uploaded", part)));
+                }
+                Err(e) => return Err(io_error(e)),
+            }
+        }
+
+        let data = futures::stream::iter(files)
+            .flat_map(|file| tokio_util::io::ReaderStream::with_capacity(file, READ_CHUNK_BYTES));
+        let path = object_path(&volume, key)?;
+        let (size, sha256) = write_stream(&volume, Box::pin(data), &path).await?;
+        let _ = fs::remove_dir_all(&dir).await;
+        Ok(ObjectInfo {
+            key: key.to_string(),
+            size,
+            last_modified: chrono::Utc::now().to_rfc3339(),
+            sha256: Some(sha256),
+        })
+    }
+
+    /// Discard a multipart upload and the parts written so far
+    pub async fn abort_multipart_upload(&self, bucket: &str, upload_id: &str) -> ZeroResult<()> {
+        let volume = self.volume_path(bucket).await?;
+        let dir = upload_dir(&volume, upload_id).await?;
+        fs::remove_dir_all(&dir).await.map_err(io_error)
+    }
+
+    async fn open_object(&self, bucket: &str, key: &str) -> ZeroResult<(ObjectInfo, fs::File)> {
+        let path = object_path(&self.volume_path(bucket).await?, key)?;
+        let file = match fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ZeroError::NotFound(format!("Object {}/{} not found", bucket, key)));
+            }
+            Err(e) => return Err(io_error(e)),
+        };
+        let info = object_info(key, &file.metadata().await.map_err(io_error)?);
+        Ok((info, file))
+    }
+
+    async fn volume_path(&self, bucket: &str) -> ZeroResult<PathBuf> {
+        self.engine.storage.list_volumes().await?
+            .into_iter()
+            .find(|v| v.id == bucket)
+            .map(|v| PathBuf::from(v.path))
+            .ok_or_else(|| ZeroError::NotFound(format!("Bucket {} not found", bucket)))
+    }
+}
+
+/// File holding an object's data; keys may contain `/` but must stay inside the bucket
+fn object_path(volume: &Path, key: &str) -> ZeroResult<PathBuf> {
+    let relative = Path::new(key);
+    let valid = !key.is_empty() && relative.components().all(|c| matches!(c, Component::Normal(_)));
+    if !valid {
+        return Err(ZeroError::Validation(format!("Invalid object key: {}", key)));
+    }
+    Ok(volume.join(OBJECTS_DIR).join(relative))
+}
+
+/// Stream `data` into a temporary file of the volume, then move it to `path`;
+/// returns the size and hex SHA-256 of the data
+async fn write_stream(volume: &Path, data: ByteStream, path: &Path) -> ZeroResult<(u64, String)> {
+    let written = write_stream_checked(volume, data, path, None).await?;
+    Ok(written.expect("unchecked writes always complete"))
+}
+
+/// Stage `data` in a temporary file and move it to `path`, unless its SHA-256 differs from
+/// `expected_sha256`, in which case the staged file is dropped and `None` returned
+async fn write_stream_checked(volume: &Path, mut data: ByteStream, path: &Path, expected_sha256: Option<&str>) -> ZeroResult<Option<(u64, String)>> {
+    let uploads = volume.join(UPLOADS_DIR);
+    fs::create_dir_all(&uploads).await.map_err(io_error)?;
+    let temp_path = uploads.join(uuid::Uuid::new_v4().to_string());
+
+    let written = async {
+        let mut file = fs::File::create(&temp_path).await?;
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        while let Some(chunk) = data.next().await {
+            let chunk = chunk?;
+            hasher.update(&chunk);
+            size += chunk.len() as u64;
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        let sha256 = hex::encode(hasher.finalize());
+        if expected_sha256.is_some_and(|expected| !expected.eq_ignore_ascii_case(&sha256)) {
+            return Ok(None);
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(&temp_path, path).await?;
+        Ok::<_, std::io::Error>(Some((size, sha256)))
+    }.await;
+
+    match written {
+        Ok(Some(written)) => Ok(Some(written)),
+        Ok(None) => {
+            let _ = fs::remove_file(&temp_path).await;
+            Ok(None)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path).await;
+            Err(io_error(e))
+        }
+    }
+}
+
+/// Directory of a multipart upload; ids are generated, so anything else is unknown
+async fn upload_dir(volume: &Path, upload_id: &str) -> ZeroResult<PathBuf> {
+    let not_found = || ZeroError::NotFound(format!("Upload {} not found", upload_id));
+    if upload_id.is_empty() || !upload_id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(not_found());
+    }
+    let dir = volume.join(MULTIPART_DIR).join(upload_id);
+    match fs::metadata(&dir).await {
+        Ok(metadata) if metadata.is_dir() => Ok(dir),
+        _ => Err(not_found()),
+    }
+}
+
+fn part_file(part_number: u32) -> String {
+    format!("part-{:05}", part_number)
+}
+
+fn object_info(key: &str, metadata: &std::fs::Metadata) -> ObjectInfo {
+    let last_modified = metadata.modified()
+        .map(chrono::DateTime::<chrono::Utc>::from)
+        .unwrap_or_else(|_| chrono::Utc::now());
+    ObjectInfo {
+        key: key.to_string(),
+        size: metadata.len(),
+        last_modified: last_modified.to_rfc3339(),
+        sha256: None,
+    }
+}
+
+fn io_error(e: std::io::Error) -> ZeroError {
+    ZeroError::Internal(format!("Object storage error: {}", e))
 }
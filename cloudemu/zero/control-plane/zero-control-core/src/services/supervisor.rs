@@ -0,0 +1,338 @@
+//! Restart policies and liveness probes of workloads
+//!
+//! Workloads created with a restart policy other than `never`, or with a liveness probe, are
+//! supervised: `ZeroProvider::spawn_supervisor` checks them every [`SUPERVISOR_TICK`]. A running
+//! workload is probed on its probe's interval and fails after its threshold of consecutive
+//! failed probes. The supervisor restarts a workload by recreating it from the spec it was
+//! created with:
+//! - `on-failure` restarts workloads that crashed, exited with an error, disappeared from
+//!   their driver or failed their liveness probe
+//! - `always` also restarts workloads that stopped by themselves
+//!
+//! A workload is restarted at most once every [`RESTART_BACKOFF`], so one that fails right away
+//! does not keep its driver busy. Restarts are counted in the workload's `restart_count`.
+
+use zero_control_spi::{LivenessProbe, PortMapping, RestartPolicy, VolumeMount, WorkloadStatus, ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::events::WORKLOAD_RESTARTED;
+use zero_data_core::rusqlite::{params, Connection};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use super::lb_runtime;
+use super::placement::PlacementService;
+
+/// How often `ZeroProvider::spawn_supervisor` looks for workloads to probe or restart
+pub const SUPERVISOR_TICK: Duration = Duration::from_secs(1);
+/// Shortest time between two restarts of a workload
+pub const RESTART_BACKOFF: Duration = Duration::from_secs(10);
+pub const MAX_PROBE_INTERVAL_SECS: u32 = 300;
+pub const MAX_PROBE_FAILURE_THRESHOLD: u32 = 10;
+
+/// What a workload is recreated from when it restarts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadSpec {
+    pub image: String,
+    pub cpu: f32,
+    pub memory_mb: i32,
+    #[serde(default)]
+    pub mounts: Vec<VolumeMount>,
+    #[serde(default)]
+    pub ports: Vec<PortMapping>,
+}
+
+/// A supervised workload
+#[derive(Debug, Clone, Serialize)]
+pub struct Supervision {
+    pub id: String,
+    #[serde(skip)]
+    pub spec: WorkloadSpec,
+    pub restart_policy: RestartPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liveness_probe: Option<LivenessProbe>,
+    pub restart_count: u32,
+    /// Failed liveness probes since the last one that passed
+    pub consecutive_failures: u32,
+    pub last_probed: Option<DateTime<Utc>>,
+    pub last_restarted: Option<DateTime<Utc>>,
+}
+
+/// How a check found a workload
+enum Health {
+    /// Running and passing its probe, or in a state that is neither running nor stopped
+    Alive,
+    Stopped,
+    Failed(String),
+}
+
+/// Reject liveness probes the supervisor cannot run
+pub fn validate_probe(probe: &LivenessProbe) -> ZeroResult<()> {
+    if let Some(path) = &probe.http_path {
+        if !path.starts_with('/') {
+            return Err(ZeroError::Validation(format!("Liveness probe path {} must start with /", path)));
+        }
+    }
+    if probe.port == 0 {
+        return Err(ZeroError::Validation("Liveness probe port must be between 1 and 65535".into()));
+    }
+    if !(1..=MAX_PROBE_INTERVAL_SECS).contains(&probe.interval_secs) {
+        return Err(ZeroError::Validation(format!("Liveness probe interval must be between 1 and {} seconds", MAX_PROBE_INTERVAL_SECS)));
+    }
+    if !(1..=MAX_PROBE_FAILURE_THRESHOLD).contains(&probe.failure_threshold) {
+        return Err(ZeroError::Validation(format!("Liveness probe failure threshold must be between 1 and {}", MAX_PROBE_FAILURE_THRESHOLD)));
+    }
+    Ok(())
+}
+
+/// Run a liveness probe once against a running workload; `None` when it has no address to
+/// probe
+pub async fn probe_workload(client: &Client, status: &WorkloadStatus, probe: &LivenessProbe) -> Option<Result<(), String>> {
+    // Workloads without an address of their own are reached through their published ports
+    let (host, port) = match &status.ip_address {
+        Some(ip) => (ip.clone(), probe.port),
+        None => status.ports.iter()
+            .find(|port| port.container_port == probe.port)
+            .map(|port| ("127.0.0.1".to_string(), port.host_port))?,
+    };
+    let (protocol, path) = match &probe.http_path {
+        Some(path) => ("HTTP", path.as_str()),
+        None => ("TCP", ""),
+    };
+    Some(lb_runtime::probe_target(client, protocol, &host, port, path).await)
+}
+
+fn db_error(e: zero_data_core::rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+fn parse_time(time: Option<String>) -> Option<DateTime<Utc>> {
+    time.and_then(|time| DateTime::parse_from_rfc3339(&time).ok()).map(|time| time.with_timezone(&Utc))
+}
+
+fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+    conn.execute_batch("
+        CREATE TABLE IF NOT EXISTS supervised_workloads (
+            id TEXT PRIMARY KEY,
+            spec TEXT NOT NULL,
+            restart_policy TEXT NOT NULL,
+            liveness_probe TEXT,
+            restart_count INTEGER NOT NULL DEFAULT 0,
+            consecutive_failures INTEGER NOT NULL DEFAULT 0,
+            last_probed TEXT,
+            last_restarted TEXT
+        );
+    ").map_err(db_error)
+}
+
+#[derive(Clone)]
+pub struct SupervisorService {
+    engine: Arc<ZeroEngine>,
+    placement: PlacementService,
+    http_client: Client,
+}
+
+impl SupervisorService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { placement: PlacementService::new(engine.clone()), engine, http_client: Client::new() }
+    }
+
+    /// Supervise a workload created at `now` from `spec`; its first probe is due an interval
+    /// later. Workloads that would never be probed or restarted are not recorded.
+    pub fn supervise(&self, id: &str, spec: &WorkloadSpec, restart_policy: RestartPolicy, liveness_probe: Option<&LivenessProbe>, now: DateTime<Utc>) -> ZeroResult<()> {
+        if restart_policy == RestartPolicy::Never && liveness_probe.is_none() {
+            return Ok(());
+        }
+        if let Some(probe) = liveness_probe {
+            validate_probe(probe)?;
+        }
+        let spec = serde_json::to_string(spec).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let probe = liveness_probe.map(serde_json::to_string).transpose().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO supervised_workloads (id, spec, restart_policy, liveness_probe, last_probed) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, spec, restart_policy.name(), probe, now.to_rfc3339()],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Supervise a workload again after a pause, from `spec` and with its restarts so far,
+    /// as after a rolling update; its next probe is due an interval after `now`
+    pub fn resume(&self, supervision: &Supervision, spec: &WorkloadSpec, now: DateTime<Utc>) -> ZeroResult<()> {
+        let spec = serde_json::to_string(spec).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let probe = supervision.liveness_probe.as_ref().map(serde_json::to_string).transpose().map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.execute(
+            "INSERT OR REPLACE INTO supervised_workloads (id, spec, restart_policy, liveness_probe, restart_count, last_probed, last_restarted)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                supervision.id, spec, supervision.restart_policy.name(), probe, supervision.restart_count,
+                now.to_rfc3339(), supervision.last_restarted.map(|time| time.to_rfc3339())
+            ],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Stop supervising a workload, as when it is deleted
+    pub fn forget(&self, id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        conn.execute("DELETE FROM supervised_workloads WHERE id = ?1", params![id]).map_err(db_error)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> ZeroResult<Option<Supervision>> {
+        Ok(self.list_where("WHERE id = ?1", params![id])?.pop())
+    }
+
+    pub fn list(&self) -> ZeroResult<Vec<Supervision>> {
+        self.list_where("", params![])
+    }
+
+    /// Restarts of every supervised workload, by workload ID
+    pub fn restart_counts(&self) -> ZeroResult<HashMap<String, u32>> {
+        Ok(self.list()?.into_iter().map(|s| (s.id, s.restart_count)).collect())
+    }
+
+    fn list_where(&self, filter: &str, params: &[&dyn zero_data_core::rusqlite::ToSql]) -> ZeroResult<Vec<Supervision>> {
+        let conn = self.engine.db.lock();
+        ensure_tables(&conn)?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, spec, restart_policy, liveness_probe, restart_count, consecutive_failures, last_probed, last_restarted
+             FROM supervised_workloads {} ORDER BY id", filter
+        )).map_err(db_error)?;
+        let rows = stmt.query_map(params, |row| Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, u32>(4)?,
+            row.get::<_, u32>(5)?,
+            row.get::<_, Option<String>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+        ))).map_err(db_error)?
+            .collect::<Result<Vec<_>, _>>().map_err(db_error)?;
+
+        rows.into_iter().map(|(id, spec, restart_policy, probe, restart_count, consecutive_failures, last_probed, last_restarted)| {
+            Ok(Supervision {
+                id,
+                spec: serde_json::from_str(&spec).map_err(|e| ZeroError::Internal(e.to_string()))?,
+                restart_policy: serde_json::from_value(json!(restart_policy)).map_err(|e| ZeroError::Internal(e.to_string()))?,
+                liveness_probe: probe.map(|probe| serde_json::from_str(&probe)).transpose().map_err(|e| ZeroError::Internal(e.to_string()))?,
+                restart_count,
+                consecutive_failures,
+                last_probed: parse_time(last_probed),
+                last_restarted: parse_time(last_restarted),
+            })
+        }).collect()
+    }
+
+    /// Probe the supervised workloads whose probe is due and restart those that stopped or
+    /// failed under their policy. Returns the number of workloads restarted.
+    pub async fn check(&self, now: DateTime<Utc>) -> ZeroResult<usize> {
+        let mut restarted = 0;
+        for supervision in self.list()? {
+            let id = supervision.id.clone();
+            match self.check_workload(supervision, now).await {
+                Ok(true) => restarted += 1,
+                Ok(false) => {},
+                Err(e) => tracing::warn!("Supervisor: checking workload {} failed: {}", id, e),
+            }
+        }
+        Ok(restarted)
+    }
+
+    async fn check_workload(&self, supervision: Supervision, now: DateTime<Utc>) -> ZeroResult<bool> {
+        let health = match self.placement.compute_for(&supervision.id).await?.get_workload_status(&supervision.id).await {
+            Ok(status) if status.is_running() => self.probe(&supervision, &status, now).await?,
+            Ok(status) if status.has_failed() => Health::Failed(format!("Workload is {}", status.state)),
+            Ok(status) if status.has_stopped() => Health::Stopped,
+            Ok(_) => Health::Alive,
+            Err(e) => Health::Failed(format!("Workload is gone: {}", e)),
+        };
+        let reason = match (supervision.restart_policy, health) {
+            (RestartPolicy::Always, Health::Stopped) => "Workload stopped".to_string(),
+            (RestartPolicy::Always | RestartPolicy::OnFailure, Health::Failed(reason)) => reason,
+            _ => return Ok(false),
+        };
+        let backoff = chrono::Duration::from_std(RESTART_BACKOFF).unwrap_or_default();
+        if supervision.last_restarted.is_some_and(|last| now.signed_duration_since(last) < backoff) {
+            return Ok(false);
+        }
+        self.restart(&supervision, &reason, now).await
+    }
+
+    /// Run the liveness probe of a running workload when it is due, recording the result
+    async fn probe(&self, supervision: &Supervision, status: &WorkloadStatus, now: DateTime<Utc>) -> ZeroResult<Health> {
+        let Some(probe) = &supervision.liveness_probe else {
+            return Ok(Health::Alive);
+        };
+        let interval = chrono::Duration::seconds(i64::from(probe.interval_secs));
+        if supervision.last_probed.is_some_and(|last| now.signed_duration_since(last) < interval) {
+            return Ok(Health::Alive);
+        }
+        let Some(result) = probe_workload(&self.http_client, status, probe).await else {
+            tracing::debug!("Supervisor: workload {} has no address to probe", supervision.id);
+            return Ok(Health::Alive);
+        };
+
+        let failures = match &result {
+            Ok(()) => 0,
+            Err(_) => supervision.consecutive_failures.saturating_add(1),
+        };
+        {
+            let conn = self.engine.db.lock();
+            conn.execute(
+                "UPDATE supervised_workloads SET consecutive_failures = ?1, last_probed = ?2 WHERE id = ?3",
+                params![failures, now.to_rfc3339(), supervision.id],
+            ).map_err(db_error)?;
+        }
+        match result {
+            Err(reason) if failures >= probe.failure_threshold => {
+                Ok(Health::Failed(format!("Liveness probe failed {} times: {}", failures, reason)))
+            }
+            _ => Ok(Health::Alive),
+        }
+    }
+
+    /// Recreate a workload from its spec. Returns whether it runs again; a failed attempt
+    /// is retried after the backoff.
+    async fn restart(&self, supervision: &Supervision, reason: &str, now: DateTime<Utc>) -> ZeroResult<bool> {
+        let id = &supervision.id;
+        let spec = &supervision.spec;
+        tracing::info!("Supervisor: restarting workload {}: {}", id, reason);
+        let compute = self.placement.compute_for(id).await?;
+        if let Err(e) = compute.delete_workload(id).await {
+            tracing::debug!("Supervisor: removing workload {} before its restart failed: {}", id, e);
+        }
+        let created = compute.create_workload_with_ports(id, &spec.image, spec.cpu, spec.memory_mb, &spec.mounts, &spec.ports).await;
+
+        let restart_count = match &created {
+            Ok(_) => supervision.restart_count.saturating_add(1),
+            Err(_) => supervision.restart_count,
+        };
+        {
+            let conn = self.engine.db.lock();
+            conn.execute(
+                "UPDATE supervised_workloads SET restart_count = ?1, consecutive_failures = 0, last_probed = ?2, last_restarted = ?2 WHERE id = ?3",
+                params![restart_count, now.to_rfc3339(), id],
+            ).map_err(db_error)?;
+        }
+        match created {
+            Ok(_) => {
+                self.engine.events.publish(WORKLOAD_RESTARTED, id, json!({ "reason": reason, "restart_count": restart_count }));
+                Ok(true)
+            }
+            Err(e) => {
+                tracing::warn!("Supervisor: restarting workload {} failed: {}", id, e);
+                Ok(false)
+            }
+        }
+    }
+}
+
@@ -0,0 +1,251 @@
+//! Teardown of every emulated resource, so a test suite can start from a clean slate
+//!
+//! A reset covers every namespace. `store`, `db` and `queue` remove only the buckets,
+//! tables or queues; `all` also deletes event source mappings, schedules, topics, functions,
+//! load balancers, DNS zones, EKS clusters and scaling groups, stops workloads and removes
+//! volumes.
+
+use zero_control_spi::{ZeroError, ZeroResult};
+use zero_data_core::ZeroEngine;
+use zero_data_core::events::WORKLOAD_STOPPED;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use super::autoscaling::AutoscalingService;
+use super::db::DbService;
+use super::dns::DnsService;
+use super::eks::EksService;
+use super::event_source::EventSourceService;
+use super::func::FuncService;
+use super::lb::LbService;
+use super::namespace::{NamespaceService, BUCKET, FUNCTION, QUEUE, TABLE, VOLUME, WORKLOAD};
+use super::placement::PlacementService;
+use super::queue::QueueService;
+use super::scheduler::SchedulerService;
+use super::store::StoreService;
+use super::topic::TopicService;
+use super::image::ImageService;
+use super::rollout::RolloutService;
+use super::supervisor::SupervisorService;
+
+/// Services a reset clears
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetScope {
+    Store,
+    Db,
+    Queue,
+    All,
+}
+
+impl std::str::FromStr for ResetScope {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "store" => Ok(Self::Store),
+            "db" => Ok(Self::Db),
+            "queue" => Ok(Self::Queue),
+            "all" => Ok(Self::All),
+            other => Err(ZeroError::Validation(format!("Unknown service {}, expected store, db, queue or all", other))),
+        }
+    }
+}
+
+impl ResetScope {
+    fn covers(self, scope: ResetScope) -> bool {
+        self == ResetScope::All || self == scope
+    }
+}
+
+/// What a reset removed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResetSummary {
+    pub event_source_mappings: usize,
+    pub schedules: usize,
+    pub topics: usize,
+    pub subscriptions: usize,
+    pub functions: usize,
+    pub load_balancers: usize,
+    pub dns_zones: usize,
+    pub dns_records: usize,
+    pub clusters: usize,
+    pub scaling_groups: usize,
+    pub workloads: usize,
+    pub volumes: usize,
+    pub buckets: usize,
+    pub objects: usize,
+    pub tables: usize,
+    pub queues: usize,
+    pub messages: usize,
+}
+
+#[derive(Clone)]
+pub struct SystemService {
+    engine: Arc<ZeroEngine>,
+    store: StoreService,
+    db: DbService,
+    queue: QueueService,
+    func: FuncService,
+    topic: TopicService,
+    scheduler: SchedulerService,
+    event_source: EventSourceService,
+    dns: DnsService,
+    lb: LbService,
+    eks: EksService,
+    autoscaling: AutoscalingService,
+    placement: PlacementService,
+    namespace: NamespaceService,
+    supervisor: SupervisorService,
+    images: ImageService,
+    rollout: RolloutService,
+}
+
+impl SystemService {
+    /// `lb` and `event_source` are the provider's, which hold the listeners and pollers a
+    /// reset stops
+    pub fn new(engine: Arc<ZeroEngine>, lb: LbService, event_source: EventSourceService) -> Self {
+        Self {
+            store: StoreService::new(engine.clone()),
+            db: DbService::new(engine.clone()),
+            queue: QueueService::new(engine.clone()),
+            func: FuncService::new(engine.clone()),
+            topic: TopicService::new(engine.clone()),
+            scheduler: SchedulerService::new(engine.clone()),
+            event_source,
+            dns: DnsService::new(engine.clone()),
+            lb,
+            eks: EksService::new(engine.clone()),
+            autoscaling: AutoscalingService::new(engine.clone()),
+            placement: PlacementService::new(engine.clone()),
+            namespace: NamespaceService::new(engine.clone()),
+            supervisor: SupervisorService::new(engine.clone()),
+            images: ImageService::new(engine.clone()),
+            rollout: RolloutService::new(engine.clone()),
+            engine,
+        }
+    }
+
+    /// Delete the resources of `scope` in every namespace
+    pub async fn reset(&self, scope: ResetScope) -> ZeroResult<ResetSummary> {
+        let mut summary = ResetSummary::default();
+        if scope.covers(ResetScope::All) {
+            // Mappings and schedules go before the functions and queues they deliver to
+            self.reset_services(&mut summary).await?;
+            // Groups go first, or the autoscaler would replace the instances stopped below
+            self.reset_compute(&mut summary).await?;
+        }
+        if scope.covers(ResetScope::Store) {
+            self.reset_store(&mut summary).await?;
+        }
+        if scope.covers(ResetScope::Db) {
+            for table in self.db.list_tables().await? {
+                self.db.delete_table(&table).await?;
+                self.namespace.release(TABLE, &table).await?;
+                summary.tables += 1;
+            }
+        }
+        if scope.covers(ResetScope::Queue) {
+            for stats in self.queue.queue_stats().await? {
+                summary.messages += self.queue.delete_queue(&stats.name).await?;
+                self.namespace.release(QUEUE, &stats.name).await?;
+                summary.queues += 1;
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Delete event source mappings, schedules, topics with their subscriptions, functions,
+    /// load balancers, DNS zones with their records and EKS clusters with their nodes
+    async fn reset_services(&self, summary: &mut ResetSummary) -> ZeroResult<()> {
+        for mapping in self.event_source.list_mappings().await? {
+            self.event_source.delete_mapping(&mapping.id).await?;
+            summary.event_source_mappings += 1;
+        }
+        for rule in self.scheduler.list_rules().await? {
+            self.scheduler.delete_rule(&rule.name).await?;
+            summary.schedules += 1;
+        }
+        for topic in self.topic.list_topics().await? {
+            summary.subscriptions += self.topic.list_subscriptions(&topic.name).await?.len();
+            self.topic.delete_topic(&topic.name).await?;
+            summary.topics += 1;
+        }
+        for function in self.func.list_functions().await? {
+            self.func.delete_function(&function).await?;
+            self.namespace.release(FUNCTION, &function).await?;
+            summary.functions += 1;
+        }
+        summary.load_balancers += self.lb.delete_all().await?;
+        for zone in self.dns.list_zones().await? {
+            let name = zone["name"].as_str().unwrap_or_default();
+            self.dns.delete_zone(name).await?;
+            summary.dns_records += zone["record_sets"].as_u64().unwrap_or_default() as usize;
+            summary.dns_zones += 1;
+        }
+        // The clusters' control planes and nodes are workloads; removing them here releases
+        // their reservations and node registrations as well
+        summary.clusters += self.eks.delete_all().await?;
+        Ok(())
+    }
+
+    /// Delete scaling groups, stop every workload on this server and its nodes, and
+    /// remove volumes
+    async fn reset_compute(&self, summary: &mut ResetSummary) -> ZeroResult<()> {
+        for group in self.autoscaling.list_groups().await? {
+            summary.workloads += group.instances.len();
+            self.autoscaling.delete_group(&group.name).await?;
+            summary.scaling_groups += 1;
+        }
+
+        let mut workloads: Vec<String> = self.engine.compute.list_workloads().await?
+            .into_iter().map(|workload| workload.id).collect();
+        for (_, on_node) in self.placement.node_workloads().await? {
+            workloads.extend(on_node.into_iter().map(|workload| workload.id));
+        }
+        workloads.sort();
+        workloads.dedup();
+        for id in workloads {
+            self.supervisor.forget(&id)?;
+            match self.placement.compute_for(&id).await?.delete_workload(&id).await {
+                Ok(()) => {
+                    self.engine.events.publish(WORKLOAD_STOPPED, &id, json!({}));
+                    summary.workloads += 1;
+                }
+                Err(e) => tracing::warn!("Reset: failed to stop workload {}: {}", id, e),
+            }
+            self.placement.release(&id).await?;
+            self.namespace.release(WORKLOAD, &id).await?;
+            self.engine.ipam.release_all(&id)?;
+            self.images.release(&id, chrono::Utc::now())?;
+            self.rollout.forget(&id)?;
+        }
+
+        let volumes = self.namespace.assignments(VOLUME).await?;
+        for volume in self.engine.storage.list_volumes().await? {
+            // Buckets are volumes too; they are counted by the store reset
+            if volumes.contains_key(&volume.id) {
+                self.engine.storage.delete_volume(&volume.id).await?;
+                self.namespace.release(VOLUME, &volume.id).await?;
+                summary.volumes += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove every bucket with its objects
+    async fn reset_store(&self, summary: &mut ResetSummary) -> ZeroResult<()> {
+        let volumes = self.namespace.assignments(VOLUME).await?;
+        for bucket in self.store.list_buckets().await? {
+            if volumes.contains_key(&bucket) {
+                continue;
+            }
+            summary.objects += self.store.list_objects(&bucket).await?.len();
+            // Objects are files in the bucket's volume, so removing it removes them
+            self.engine.storage.delete_volume(&bucket).await?;
+            self.namespace.release(BUCKET, &bucket).await?;
+            summary.buckets += 1;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+//! Pub/Sub topics: messages published to a topic fan out to every subscribed queue and webhook
+//! whose filter policy matches the message attributes
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::rusqlite::{params, Connection, OptionalExtension};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use super::queue::QueueService;
+
+/// Attempts to deliver a message to a webhook before giving up
+const WEBHOOK_ATTEMPTS: u32 = 3;
+
+/// Pause before the first webhook retry, doubled for each further retry
+const WEBHOOK_RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+
+const WEBHOOK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Header telling webhook endpoints what kind of message they received (as SNS does)
+pub const MESSAGE_TYPE_HEADER: &str = "x-amz-sns-message-type";
+
+/// Where a subscription delivers messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// A ZeroCloud queue, named by the endpoint
+    Queue,
+    /// An HTTP(S) URL that receives each message as a POST
+    Webhook,
+}
+
+impl Protocol {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Protocol::Queue => "queue",
+            Protocol::Webhook => "webhook",
+        }
+    }
+}
+
+impl std::str::FromStr for Protocol {
+    type Err = ZeroError;
+
+    fn from_str(s: &str) -> ZeroResult<Self> {
+        match s {
+            "queue" => Ok(Protocol::Queue),
+            "webhook" => Ok(Protocol::Webhook),
+            other => Err(ZeroError::Validation(format!("Unknown subscription protocol: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Topic {
+    pub name: String,
+    pub arn: String,
+    pub created_at: String,
+}
+
+/// Settings for a new subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribeRequest {
+    pub protocol: Protocol,
+    /// Queue name or webhook URL
+    pub endpoint: String,
+    /// Deliver only messages whose attributes match, e.g. `{"event": ["created"], "size": [{"numeric": [">", 10]}]}`
+    #[serde(default)]
+    pub filter_policy: Option<Value>,
+    /// Deliver the message body alone instead of the JSON notification envelope
+    #[serde(default)]
+    pub raw_message_delivery: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Subscription {
+    pub id: String,
+    pub arn: String,
+    pub topic_name: String,
+    pub protocol: Protocol,
+    pub endpoint: String,
+    pub filter_policy: Option<Value>,
+    pub raw_message_delivery: bool,
+    pub created_at: String,
+}
+
+/// A message to publish
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishRequest {
+    pub message: String,
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// String or number attributes matched against filter policies
+    #[serde(default)]
+    pub attributes: BTreeMap<String, Value>,
+}
+
+/// Outcome of a publish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PublishResult {
+    pub message_id: String,
+    /// Subscriptions whose filter policy matched; webhook deliveries complete in the background
+    pub matched_subscriptions: usize,
+    /// Subscriptions that could not be delivered to (webhooks failing are only logged)
+    pub failed_subscriptions: Vec<String>,
+}
+
+#[derive(Clone)]
+pub struct TopicService {
+    engine: Arc<ZeroEngine>,
+    queue: QueueService,
+    http: reqwest::Client,
+}
+
+impl TopicService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self {
+            queue: QueueService::new(engine.clone()),
+            engine,
+            http: reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build().unwrap_or_default(),
+        }
+    }
+
+    fn ensure_tables(conn: &Connection) -> ZeroResult<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS topics (
+                name TEXT PRIMARY KEY,
+                arn TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS topic_subscriptions (
+                id TEXT PRIMARY KEY,
+                topic_name TEXT NOT NULL,
+                protocol TEXT NOT NULL,
+                endpoint TEXT NOT NULL,
+                filter_policy TEXT,
+                raw_message_delivery INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            );"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    pub async fn create_topic(&self, name: &str) -> ZeroResult<Topic> {
+        if name.is_empty() || name.len() > 256 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(ZeroError::Validation(format!(
+                "Invalid topic name {:?}: use 1-256 letters, digits, hyphens and underscores", name
+            )));
+        }
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        // Creating an existing topic returns it, as SNS does
+        if let Some(topic) = Self::find_topic(&conn, name)? {
+            return Ok(topic);
+        }
+        let topic = Topic {
+            name: name.to_string(),
+            arn: format!("arn:aws:sns:us-east-1:000000000000:{}", name),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        conn.execute(
+            "INSERT INTO topics (name, arn, created_at) VALUES (?1, ?2, ?3)",
+            params![topic.name, topic.arn, topic.created_at],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(topic)
+    }
+
+    pub async fn list_topics(&self) -> ZeroResult<Vec<Topic>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let mut stmt = conn.prepare("SELECT name, arn, created_at FROM topics ORDER BY name")
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let topics = stmt.query_map([], |row| Ok(Topic { name: row.get(0)?, arn: row.get(1)?, created_at: row.get(2)? }))
+            .map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(topics)
+    }
+
+    /// Delete a topic and its subscriptions
+    pub async fn delete_topic(&self, name: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let deleted = conn.execute("DELETE FROM topics WHERE name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if deleted == 0 {
+            return Err(ZeroError::NotFound(format!("Topic not found: {}", name)));
+        }
+        conn.execute("DELETE FROM topic_subscriptions WHERE topic_name = ?1", params![name])
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(())
+    }
+
+    fn find_topic(conn: &Connection, name: &str) -> ZeroResult<Option<Topic>> {
+        conn.query_row(
+            "SELECT name, arn, created_at FROM topics WHERE name = ?1",
+            params![name],
+            |row| Ok(Topic { name: row.get(0)?, arn: row.get(1)?, created_at: row.get(2)? }),
+        ).optional().map_err(|e| ZeroError::Internal(e.to_string()))
+    }
+
+    fn get_topic(conn: &Connection, name: &str) -> ZeroResult<Topic> {
+        Self::find_topic(conn, name)?.ok_or_else(|| ZeroError::NotFound(format!("Topic not found: {}", name)))
+    }
+
+    pub async fn subscribe(&self, topic_name: &str, request: SubscribeRequest) -> ZeroResult<Subscription> {
+        match request.protocol {
+            Protocol::Queue => {
+                if !self.queue.has_queue(&request.endpoint) {
+                    return Err(ZeroError::NotFound(format!("Queue not found: {}", request.endpoint)));
+                }
+                // Topics do not assign message groups, which FIFO queues require
+                if request.endpoint.ends_with(".fifo") {
+                    return Err(ZeroError::Validation("FIFO queues cannot subscribe to standard topics".into()));
+                }
+            }
+            Protocol::Webhook => {
+                if !(request.endpoint.starts_with("http://") || request.endpoint.starts_with("https://")) {
+                    return Err(ZeroError::Validation(format!("Webhook endpoint must be an http(s) URL: {}", request.endpoint)));
+                }
+            }
+        }
+        if let Some(policy) = &request.filter_policy {
+            validate_filter_policy(policy)?;
+        }
+
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let topic = Self::get_topic(&conn, topic_name)?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let subscription = Subscription {
+            arn: format!("{}:{}", topic.arn, id),
+            id,
+            topic_name: topic.name,
+            protocol: request.protocol,
+            endpoint: request.endpoint,
+            filter_policy: request.filter_policy,
+            raw_message_delivery: request.raw_message_delivery,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        conn.execute(
+            "INSERT INTO topic_subscriptions (id, topic_name, protocol, endpoint, filter_policy, raw_message_delivery, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                subscription.id,
+                subscription.topic_name,
+                subscription.protocol.as_str(),
+                subscription.endpoint,
+                subscription.filter_policy.as_ref().map(|p| p.to_string()),
+                subscription.raw_message_delivery,
+                subscription.created_at,
+            ],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        Ok(subscription)
+    }
+
+    pub async fn list_subscriptions(&self, topic_name: &str) -> ZeroResult<Vec<Subscription>> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let topic = Self::get_topic(&conn, topic_name)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, protocol, endpoint, filter_policy, raw_message_delivery, created_at
+             FROM topic_subscriptions WHERE topic_name = ?1 ORDER BY created_at"
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        let rows = stmt.query_map(params![topic_name], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        }).map_err(|e| ZeroError::Internal(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ZeroError::Internal(e.to_string()))?;
+
+        rows.into_iter().map(|(id, protocol, endpoint, filter_policy, raw_message_delivery, created_at)| {
+            Ok(Subscription {
+                arn: format!("{}:{}", topic.arn, id),
+                id,
+                topic_name: topic.name.clone(),
+                protocol: protocol.parse()?,
+                endpoint,
+                filter_policy: filter_policy.map(|p| serde_json::from_str(&p)).transpose()
+                    .map_err(|e| ZeroError::Internal(e.to_string()))?,
+                raw_message_delivery,
+                created_at,
+            })
+        }).collect()
+    }
+
+    pub async fn unsubscribe(&self, topic_name: &str, id: &str) -> ZeroResult<()> {
+        let conn = self.engine.db.lock();
+        Self::ensure_tables(&conn)?;
+        let deleted = conn.execute(
+            "DELETE FROM topic_subscriptions WHERE topic_name = ?1 AND id = ?2",
+            params![topic_name, id],
+        ).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        if deleted == 0 {
+            return Err(ZeroError::NotFound(format!("Subscription not found: {}", id)));
+        }
+        Ok(())
+    }
+
+    /// Deliver a message to every matching subscription: queues before returning,
+    /// webhooks in the background with retries
+    pub async fn publish(&self, topic_name: &str, request: PublishRequest) -> ZeroResult<PublishResult> {
+        for (name, value) in &request.attributes {
+            if !(value.is_string() || value.is_number()) {
+                return Err(ZeroError::Validation(format!("Attribute {} must be a string or a number", name)));
+            }
+        }
+        let topic = {
+            let conn = self.engine.db.lock();
+            Self::ensure_tables(&conn)?;
+            Self::get_topic(&conn, topic_name)?
+        };
+        let subscriptions = self.list_subscriptions(topic_name).await?;
+
+        let message_id = uuid::Uuid::new_v4().to_string();
+        let envelope = json!({
+            "Type": "Notification",
+            "MessageId": message_id,
+            "TopicArn": topic.arn,
+            "Subject": request.subject,
+            "Message": request.message,
+            "MessageAttributes": request.attributes,
+            "Timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+
+        let mut matched = 0;
+        let mut failed = Vec::new();
+        for subscription in subscriptions {
+            if !subscription.filter_policy.as_ref().is_none_or(|p| matches_filter_policy(p, &request.attributes)) {
+                continue;
+            }
+            matched += 1;
+            let payload = if subscription.raw_message_delivery {
+                request.message.clone()
+            } else {
+                envelope.to_string()
+            };
+            match subscription.protocol {
+                Protocol::Queue => {
+                    if let Err(e) = self.queue.send_message(&subscription.endpoint, &payload).await {
+                        tracing::warn!("Topic {} failed to deliver to queue {}: {}", topic.name, subscription.endpoint, e);
+                        failed.push(subscription.id);
+                    }
+                }
+                Protocol::Webhook => {
+                    let http = self.http.clone();
+                    tokio::spawn(deliver_webhook(http, subscription.endpoint, topic.arn.clone(), payload));
+                }
+            }
+        }
+        Ok(PublishResult { message_id, matched_subscriptions: matched, failed_subscriptions: failed })
+    }
+}
+
+async fn deliver_webhook(http: reqwest::Client, endpoint: String, topic_arn: String, payload: String) {
+    let mut backoff = WEBHOOK_RETRY_BACKOFF;
+    for attempt in 1..=WEBHOOK_ATTEMPTS {
+        let result = http.post(&endpoint)
+            .header(MESSAGE_TYPE_HEADER, "Notification")
+            .header("x-amz-sns-topic-arn", &topic_arn)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload.clone())
+            .send().await;
+        match result {
+            Ok(resp) if resp.status().is_success() => return,
+            Ok(resp) => tracing::debug!("Webhook {} answered {} (attempt {})", endpoint, resp.status(), attempt),
+            Err(e) => tracing::debug!("Webhook {} failed: {} (attempt {})", endpoint, e, attempt),
+        }
+        if attempt < WEBHOOK_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    tracing::warn!("Gave up delivering a message from {} to {}", topic_arn, endpoint);
+}
+
+/// Check a filter policy is an object mapping attribute names to lists of conditions
+pub fn validate_filter_policy(policy: &Value) -> ZeroResult<()> {
+    let invalid = |msg: String| Err(ZeroError::Validation(format!("Invalid filter policy: {}", msg)));
+    let Some(attributes) = policy.as_object() else {
+        return invalid("must be an object".into());
+    };
+    for (name, conditions) in attributes {
+        let Some(conditions) = conditions.as_array().filter(|c| !c.is_empty()) else {
+            return invalid(format!("{} must be a non-empty list of conditions", name));
+        };
+        for condition in conditions {
+            match condition {
+                Value::String(_) | Value::Number(_) => {}
+                Value::Object(operator) if operator.len() == 1 => {
+                    let (op, operand) = operator.iter().next().expect("one operator");
+                    let valid = match op.as_str() {
+                        "prefix" => operand.is_string(),
+                        "exists" => operand.is_boolean(),
+                        "anything-but" => operand.is_string() || operand.is_number()
+                            || operand.as_array().is_some_and(|a| a.iter().all(|v| v.is_string() || v.is_number())),
+                        "numeric" => parse_numeric(operand).is_some(),
+                        _ => false,
+                    };
+                    if !valid {
+                        return invalid(format!("unsupported condition {} on {}", condition, name));
+                    }
+                }
+                other => return invalid(format!("unsupported condition {} on {}", other, name)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Every attribute named by the policy must match one of its conditions
+pub fn matches_filter_policy(policy: &Value, attributes: &BTreeMap<String, Value>) -> bool {
+    let Some(policy) = policy.as_object() else {
+        return false;
+    };
+    policy.iter().all(|(name, conditions)| {
+        let value = attributes.get(name);
+        conditions.as_array().is_some_and(|conditions| conditions.iter().any(|c| matches_condition(c, value)))
+    })
+}
+
+fn matches_condition(condition: &Value, value: Option<&Value>) -> bool {
+    match condition {
+        Value::Object(operator) => {
+            let Some((op, operand)) = operator.iter().next() else {
+                return false;
+            };
+            match (op.as_str(), value) {
+                ("exists", value) => operand.as_bool() == Some(value.is_some()),
+                ("prefix", Some(Value::String(s))) => operand.as_str().is_some_and(|p| s.starts_with(p)),
+                ("anything-but", Some(value)) => match operand {
+                    Value::Array(excluded) => !excluded.iter().any(|e| values_equal(e, value)),
+                    excluded => !values_equal(excluded, value),
+                },
+                ("numeric", Some(Value::Number(n))) => {
+                    let n = n.as_f64().unwrap_or(f64::NAN);
+                    parse_numeric(operand).is_some_and(|bounds| bounds.iter().all(|(op, bound)| match *op {
+                        "=" => n == *bound,
+                        ">" => n > *bound,
+                        ">=" => n >= *bound,
+                        "<" => n < *bound,
+                        "<=" => n <= *bound,
+                        _ => false,
+                    }))
+                }
+                _ => false,
+            }
+        }
+        exact => value.is_some_and(|v| values_equal(exact, v)),
+    }
+}
+
+/// Numbers compare by value, so `5` matches `5.0`
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// `[">", 5]` or `[">=", 0, "<", 10]` as (operator, bound) pairs
+fn parse_numeric(operand: &Value) -> Option<Vec<(&str, f64)>> {
+    let items = operand.as_array()?;
+    if items.is_empty() || items.len() > 4 || items.len() % 2 != 0 {
+        return None;
+    }
+    items.chunks(2).map(|pair| {
+        let op = pair[0].as_str().filter(|op| ["=", ">", ">=", "<", "<="].contains(op))?;
+        Some((op, pair[1].as_f64()?))
+    }).collect()
+}
@@ -0,0 +1,65 @@
+//! Resource usage of workloads, sampled from the compute driver each workload runs on
+//!
+//! Every sample asks the driver of each workload, local or on a node, for its
+//! `ComputeDriver::workload_usage` and records it in the engine's ring buffer. Workloads whose
+//! driver cannot measure them have no samples, and the samples of workloads that are gone are
+//! dropped.
+
+use zero_control_spi::{ZeroResult, ZeroError};
+use zero_data_core::ZeroEngine;
+use zero_data_core::metrics::Datapoint;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Arc;
+use super::placement::PlacementService;
+
+/// How often `ZeroProvider::spawn_metrics_sampler` samples the workloads
+pub const METRICS_TICK: std::time::Duration = std::time::Duration::from_secs(5);
+
+pub const DEFAULT_PERIOD_SECS: u32 = 60;
+
+#[derive(Clone)]
+pub struct WorkloadMetricsService {
+    engine: Arc<ZeroEngine>,
+    placement: PlacementService,
+}
+
+impl WorkloadMetricsService {
+    pub fn new(engine: Arc<ZeroEngine>) -> Self {
+        Self { placement: PlacementService::new(engine.clone()), engine }
+    }
+
+    /// Record the usage of every workload at `now`, returning how many were measured
+    pub async fn sample(&self, now: DateTime<Utc>) -> ZeroResult<usize> {
+        let mut ids: HashSet<String> = self.engine.compute.list_workloads().await?
+            .into_iter()
+            .map(|workload| workload.id)
+            .collect();
+        ids.extend(self.placement.placements().await?.into_keys());
+
+        let mut sampled = 0;
+        for id in &ids {
+            let usage = match self.placement.compute_for(id).await {
+                Ok(driver) => driver.workload_usage(id).await,
+                Err(e) => Err(e),
+            };
+            match usage {
+                Ok(usage) => {
+                    self.engine.metrics.record(id, now, usage);
+                    sampled += 1;
+                }
+                Err(e) => tracing::debug!("Cannot sample the usage of workload {}: {}", id, e),
+            }
+        }
+        self.engine.metrics.retain(&ids);
+        Ok(sampled)
+    }
+
+    /// Usage of a workload in periods of `period_secs`, oldest first
+    pub fn datapoints(&self, workload_id: &str, period_secs: u32) -> ZeroResult<Vec<Datapoint>> {
+        if period_secs == 0 {
+            return Err(ZeroError::Validation("Metrics period must be at least 1 second".into()));
+        }
+        Ok(self.engine.metrics.datapoints(workload_id, period_secs))
+    }
+}
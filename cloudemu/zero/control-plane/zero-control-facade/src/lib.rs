@@ -1,17 +1,26 @@
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, HeaderMap},
+    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, Path, Query, State},
+    http::{StatusCode, HeaderMap, Uri},
     response::{IntoResponse, Response},
-    routing::any,
+    routing::{any, get},
     Router,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use zero_control_core::ZeroProvider;
-use zero_control_spi::{ZeroRequest, ZeroService};
+use futures::TryStreamExt;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use zero_control_core::{ZeroProvider, MAX_REQUEST_BODY_BYTES};
+use zero_control_core::services::audit::PRINCIPAL_HEADER;
+use zero_control_core::services::iam::{is_unsigned_payload, request_action, IamService};
+use zero_control_spi::{FieldError, ZeroBody, ZeroError, ZeroRequest, ZeroService};
 use zero_data_core::ZeroEngine;
+use zero_data_core::events::ResourceEvent;
 
 pub struct ServerState {
     pub provider: Arc<ZeroProvider>,
+    /// Reject unsigned requests instead of treating them as anonymous
+    pub require_auth: bool,
 }
 
 pub async fn start_server(port: u16, native: bool, mock: bool) -> anyhow::Result<()> {
@@ -37,41 +46,203 @@ pub async fn start_server(port: u16, native: bool, mock: bool) -> anyhow::Result
         }
     });
 
-    let state = Arc::new(ServerState { provider });
+    let sweep_secs = std::env::var("ZERO_DB_TTL_SWEEP_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|s| *s > 0)
+        .unwrap_or(60);
+    provider.spawn_ttl_sweeper(std::time::Duration::from_secs(sweep_secs));
+
+    provider.spawn_lb_health_checker(zero_control_core::services::lb_runtime::HEALTH_CHECK_TICK);
+
+    provider.spawn_scheduler(zero_control_core::services::scheduler::SCHEDULER_TICK);
+    provider.spawn_autoscaler(zero_control_core::services::autoscaling::AUTOSCALING_TICK);
+    provider.spawn_metrics_sampler(zero_control_core::services::workload_metrics::METRICS_TICK);
+    provider.spawn_supervisor(zero_control_core::services::supervisor::SUPERVISOR_TICK);
+    provider.spawn_image_gc(zero_control_core::services::image::IMAGE_GC_TICK, zero_control_core::services::image::IMAGE_RETENTION);
+
+    if let Some(dns_port) = std::env::var("ZERO_DNS_PORT").ok().and_then(|v| v.parse::<u16>().ok()) {
+        if let Err(e) = provider.start_dns_resolver(dns_port).await {
+            tracing::error!("Failed to start DNS resolver: {}", e);
+        }
+    }
+
+    let require_auth = std::env::var("ZERO_REQUIRE_AUTH")
+        .map(|v| matches!(v.as_str(), "1" | "true"))
+        .unwrap_or(false);
+    if require_auth {
+        tracing::info!("Requests must be signed with an access key");
+    }
+
+    // Root key to sign the first requests with, before any user or access key exists
+    match (std::env::var("ZERO_ROOT_ACCESS_KEY_ID"), std::env::var("ZERO_ROOT_SECRET_ACCESS_KEY")) {
+        (Ok(access_key_id), Ok(secret)) => match provider.iam.bootstrap_root_key(&access_key_id, &secret).await {
+            Ok(()) => tracing::info!("Root access key {} registered", access_key_id),
+            Err(e) => tracing::error!("Failed to register the root access key: {}", e),
+        },
+        _ if require_auth => tracing::warn!(
+            "ZERO_REQUIRE_AUTH is set without ZERO_ROOT_ACCESS_KEY_ID and ZERO_ROOT_SECRET_ACCESS_KEY; only existing access keys can sign requests"
+        ),
+        _ => {}
+    }
+
+    if let Some(s3_port) = std::env::var("ZERO_S3_PORT").ok().and_then(|v| v.parse::<u16>().ok()) {
+        if let Err(e) = provider.start_s3_gateway(s3_port, require_auth).await {
+            tracing::error!("Failed to start S3 gateway: {}", e);
+        }
+    }
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_port) = std::env::var("ZERO_GRPC_PORT").ok().and_then(|v| v.parse::<u16>().ok()) {
+        // gRPC calls carry no signature to check
+        if require_auth {
+            tracing::warn!("gRPC API disabled while requests must be signed");
+        } else {
+            let grpc_provider = provider.clone();
+            tokio::spawn(async move {
+                if let Err(e) = zero_control_grpc::serve(grpc_provider, ([0, 0, 0, 0], grpc_port).into()).await {
+                    tracing::error!("gRPC API stopped: {}", e);
+                }
+            });
+        }
+    }
+
+    // The dashboard reads the provider directly, and a browser cannot sign its requests
+    let dashboard = (!require_auth).then(|| zero_dashboard::router(provider.clone()));
+    if dashboard.is_none() {
+        tracing::info!("Dashboard disabled while requests must be signed");
+    }
+
+    let state = Arc::new(ServerState { provider, require_auth });
 
     // 2. Setup CORS
     let cors = tower_http::cors::CorsLayer::permissive();
 
     // 3. Setup Routes
-    let app = Router::new()
+    let mut app = Router::new()
+        .route("/docs", get(swagger_ui))
+        .route(EVENTS_STREAM_PATH, get(events_stream))
         .route("/*path", any(handler))
         .layer(cors)
         .with_state(state);
+    if let Some(dashboard) = dashboard {
+        app = app.merge(dashboard);
+    }
 
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     tracing::info!("ZeroCloud API listening on http://0.0.0.0:{}", port);
+    if !require_auth {
+        tracing::info!("Dashboard at http://localhost:{}{}", port, zero_dashboard::DASHBOARD_PATH);
+    }
     axum::serve(listener, app).await?;
 
     Ok(())
 }
 
+/// Header carrying the id of every API request, also reported in error bodies
+pub const REQUEST_ID_HEADER: &str = "x-zero-request-id";
+
+/// Principal recorded in the audit trail for requests without credentials
+pub const ANONYMOUS_PRINCIPAL: &str = "anonymous";
+
+/// WebSocket pushing resource lifecycle events as JSON text messages
+pub const EVENTS_STREAM_PATH: &str = "/v1/events/stream";
+
+/// Query parameter limiting the event stream to kinds starting with a prefix, e.g. `workload.`
+pub const EVENT_KIND_PARAM: &str = "kind";
+
+/// Kind of the message sent in place of events a slow client missed
+pub const EVENTS_LAGGED: &str = "events.lagged";
+
+/// Paths describing the API, readable without credentials so tools can fetch them
+const PUBLIC_PATHS: &[&str] = &["/v1/openapi.json", "/v1/schemas"];
+
+/// Swagger UI for the OpenAPI document, loaded from a CDN
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>ZeroCloud API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/v1/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+async fn swagger_ui() -> axum::response::Html<&'static str> {
+    axum::response::Html(SWAGGER_UI)
+}
+
+/// JSON body of every failed API request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    /// Stable, machine-readable error code, e.g. `NotFound`
+    pub code: String,
+    pub message: String,
+    pub request_id: String,
+    /// Every invalid field of a `ValidationError` raised by schema validation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldError>,
+}
+
 async fn handler(
     State(state): State<Arc<ServerState>>,
     method: axum::http::Method,
+    uri: axum::http::Uri,
     Path(path): Path<String>,
     headers: HeaderMap,
-    body: axum::body::Bytes,
-) -> impl IntoResponse {
-    let mut zero_headers = std::collections::HashMap::new();
-    for (name, value) in headers.iter() {
-        zero_headers.insert(name.to_string(), value.to_str().unwrap_or("").to_string());
+    body: axum::body::Body,
+) -> Response {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let mut response = handle(&state, method, uri, path, headers, body, &request_id).await;
+    if let Ok(value) = request_id.parse() {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
     }
+    response
+}
+
+async fn handle(
+    state: &ServerState,
+    method: axum::http::Method,
+    uri: axum::http::Uri,
+    path: String,
+    headers: HeaderMap,
+    body: axum::body::Body,
+    request_id: &str,
+) -> Response {
+    let mut zero_headers = header_map(&headers);
+
+    // The body streams through to the provider unless a signature covers its hash
+    let body = ZeroBody::from_stream(body.into_data_stream().map_err(std::io::Error::other));
+    let body = if headers.contains_key(axum::http::header::AUTHORIZATION) && !is_unsigned_payload(&zero_headers) {
+        match body.collect(MAX_REQUEST_BODY_BYTES).await {
+            Ok(bytes) => ZeroBody::from(bytes),
+            Err(e) => return error_response(e, request_id),
+        }
+    } else {
+        body
+    };
+
+    let principal = match authorize(state, method.as_str(), &uri, &zero_headers, body.as_bytes(), request_id) {
+        Ok(principal) => principal,
+        Err(response) => return *response,
+    };
+    zero_headers.insert(PRINCIPAL_HEADER.to_string(), principal);
 
     let req = ZeroRequest {
         method: method.to_string(),
-        path: format!("/{}", path),
+        path: match uri.query() {
+            Some(query) => format!("/{}?{}", path, query),
+            None => format!("/{}", path),
+        },
         headers: zero_headers,
-        body: body.to_vec(),
+        body,
     };
 
     match state.provider.handle_request(req).await {
@@ -83,14 +254,124 @@ async fn handler(
                 axum_resp = axum_resp.header(name, value);
             }
 
-            axum_resp.body(axum::body::Body::from(resp.body)).unwrap()
+            let body = match resp.body {
+                ZeroBody::Full(bytes) => axum::body::Body::from(bytes),
+                stream => axum::body::Body::from_stream(stream.into_stream()),
+            };
+            axum_resp.body(body).unwrap()
         }
-        Err(e) => {
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        Err(e) => error_response(e, request_id),
+    }
+}
+
+/// Request headers as the provider sees them
+fn header_map(headers: &HeaderMap) -> HashMap<String, String> {
+    let mut zero_headers = HashMap::new();
+    for (name, value) in headers.iter() {
+        zero_headers.insert(name.to_string(), value.to_str().unwrap_or("").to_string());
+    }
+    // Only the server says who sent a request
+    zero_headers.remove(PRINCIPAL_HEADER);
+    zero_headers
+}
+
+/// Principal a request is made by, or the response refusing it
+fn authorize(
+    state: &ServerState,
+    method: &str,
+    uri: &Uri,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+    request_id: &str,
+) -> Result<String, Box<Response>> {
+    // Signatures cover the path and query string as sent, before percent-decoding
+    let target = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or(uri.path());
+    match state.provider.iam.authenticate(method, target, headers, body) {
+        // Role sessions can only do what the role's policy allows
+        Ok(Some(principal)) if IamService::is_role_session(&principal) => {
+            let (action, resource) = request_action(method, uri.path());
+            if !state.provider.iam.verify_permission(&principal, &action, &resource) {
+                return Err(Box::new(error_response(ZeroError::Unauthorized(format!(
+                    "{} is not authorized to perform {} on {}", principal, action, resource
+                )), request_id)));
+            }
+            Ok(principal)
+        },
+        Ok(Some(principal)) => Ok(principal),
+        Ok(None) if !state.require_auth || PUBLIC_PATHS.contains(&uri.path()) => Ok(ANONYMOUS_PRINCIPAL.to_string()),
+        Ok(None) => Err(Box::new(error_body(StatusCode::UNAUTHORIZED, "MissingAuthentication", "Missing Authorization header", request_id))),
+        Err(e) => Err(Box::new(error_response(e, request_id))),
+    }
+}
+
+/// Upgrade to a WebSocket that pushes every resource event from now on, signed like any
+/// other request when the server requires it
+async fn events_stream(
+    State(state): State<Arc<ServerState>>,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    if let Err(response) = authorize(&state, "GET", &uri, &header_map(&headers), &[], &request_id) {
+        return *response;
+    }
+    let events = state.provider.subscribe_events();
+    let kind = params.get(EVENT_KIND_PARAM).cloned().unwrap_or_default();
+    ws.on_upgrade(move |socket| forward_events(socket, events, kind))
+}
+
+/// Send events of kinds starting with `kind` until the client goes away
+async fn forward_events(mut socket: WebSocket, mut events: Receiver<ResourceEvent>, kind: String) {
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => continue,
+            },
+        };
+        let event = match event {
+            Ok(event) if event.kind.starts_with(&kind) => event,
+            Ok(_) => continue,
+            // The client missed events and should re-read the state it shows
+            Err(RecvError::Lagged(skipped)) => ResourceEvent::new(EVENTS_LAGGED, "", serde_json::json!({ "skipped": skipped })),
+            Err(RecvError::Closed) => return,
+        };
+        let Ok(text) = serde_json::to_string(&event) else { continue };
+        if socket.send(Message::Text(text)).await.is_err() {
+            return;
         }
     }
 }
 
+fn error_response(e: ZeroError, request_id: &str) -> Response {
+    let status = StatusCode::from_u16(e.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    if status.is_server_error() {
+        tracing::error!("Request {} failed: {}", request_id, e);
+    } else {
+        tracing::debug!("Request {} rejected: {}", request_id, e);
+    }
+    let body = ErrorBody {
+        code: e.code().to_string(),
+        message: e.message().to_string(),
+        request_id: request_id.to_string(),
+        fields: e.fields().to_vec(),
+    };
+    (status, axum::Json(body)).into_response()
+}
+
+fn error_body(status: StatusCode, code: &str, message: &str, request_id: &str) -> Response {
+    let body = ErrorBody {
+        code: code.to_string(),
+        message: message.to_string(),
+        request_id: request_id.to_string(),
+        fields: Vec::new(),
+    };
+    (status, axum::Json(body)).into_response()
+}
+
 fn check_wsl_preflight() {
     #[cfg(target_os = "linux")]
     {
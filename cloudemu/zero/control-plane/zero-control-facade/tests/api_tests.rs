@@ -66,6 +66,21 @@ async fn test_zero_facade_api_integration() {
     let found = bucket_array.iter().any(|b| b.as_str() == Some("test-bucket"));
     assert!(found, "Bucket 'test-bucket' not found in response: {:?}", bucket_array);
 
+    // 6a. Test Object Data streams through (PUT/GET /v1/store/buckets/test-bucket/objects/{key})
+    let data: Vec<u8> = (0..8 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let resp = client.put(format!("{}/v1/store/buckets/test-bucket/objects/large/blob.bin", base_url))
+        .body(data.clone())
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let object: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(object["size"], data.len());
+
+    let resp = client.get(format!("{}/v1/store/buckets/test-bucket/objects/large/blob.bin", base_url))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.content_length(), Some(data.len() as u64));
+    assert!(resp.bytes().await.unwrap() == data);
+
     // 7. Test Create ZeroDB Table (POST /v1/db/tables)
     let resp = client.post(format!("{}/v1/db/tables", base_url))
         .json(&json!({ "name": "TestTable", "pk": "UserId" }))
@@ -197,6 +212,79 @@ async fn test_zero_facade_api_integration() {
     let group_list = groups["Groups"].as_array().unwrap();
     assert!(group_list.iter().any(|g| g["GroupName"] == "ZeroDevelopers"));
 
+    // 22. Test structured errors
+    let resp = client.get(format!("{}/v1/eks/clusters/missing", base_url)).send().await.unwrap();
+    assert_eq!(resp.status(), 404);
+    let request_id = resp.headers()["x-zero-request-id"].to_str().unwrap().to_string();
+    let error: zero_control_facade::ErrorBody = resp.json().await.unwrap();
+    assert_eq!(error.code, "NotFound");
+    assert_eq!(error.message, "Cluster not found: missing");
+    assert_eq!(error.request_id, request_id);
+
+    let resp = client.post(format!("{}/v1/eks/clusters", base_url))
+        .json(&json!({ "name": "dev", "version": "0.1" }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let error: zero_control_facade::ErrorBody = resp.json().await.unwrap();
+    assert_eq!(error.code, "ValidationError");
+    assert!(error.fields.is_empty());
+
+    // 23. Test field-level validation errors
+    let resp = client.post(format!("{}/v1/queue/queues", base_url))
+        .json(&json!({ "fifo": "yes", "delay_seconds": 9000 }))
+        .send().await.unwrap();
+    assert_eq!(resp.status(), 400);
+    let error: zero_control_facade::ErrorBody = resp.json().await.unwrap();
+    assert_eq!(error.code, "ValidationError");
+    let mut fields: Vec<_> = error.fields.iter().map(|f| f.field.as_str()).collect();
+    fields.sort();
+    assert_eq!(fields, ["delay_seconds", "fifo", "name"]);
+
+    let resp = client.get(format!("{}/v1/schemas", base_url)).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let schemas: serde_json::Value = resp.json().await.unwrap();
+    assert!(schemas["routes"].as_array().unwrap().iter().any(|r| r["path"] == "/v1/queue/queues"));
+
+    // 24. Test the OpenAPI document and Swagger UI
+    let resp = client.get(format!("{}/v1/openapi.json", base_url)).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let spec: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(spec["openapi"], "3.0.3");
+    assert_eq!(spec["paths"]["/v1/queue/queues"]["post"]["operationId"], "CreateQueue");
+
+    let resp = client.get(format!("{}/docs", base_url)).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(resp.text().await.unwrap().contains("/v1/openapi.json"));
+
+    // 25. Test the audit trail records the principal the server authenticated
+    let resp = client.delete(format!("{}/v1/store/buckets/test-bucket", base_url))
+        .header("X-Zero-Principal", "root")
+        .send().await.unwrap();
+    let status = resp.status().as_u16();
+    let resp = client.get(format!("{}/v1/audit/events?limit=1000", base_url))
+        .send().await.unwrap();
+    let audit: serde_json::Value = resp.json().await.unwrap();
+    let event = audit["events"].as_array().unwrap().iter()
+        .rfind(|e| e["method"] == "DELETE" && e["path"] == "/v1/store/buckets/test-bucket")
+        .unwrap();
+    assert_eq!(event["principal"], "anonymous");
+    assert_eq!(event["status"], status);
+
+    // 26. Test the event stream pushes workload events, filtered by kind
+    use futures::StreamExt;
+    let (mut events, _) = tokio_tungstenite::connect_async(format!("ws://localhost:{}/v1/events/stream?kind=workload.", port))
+        .await.unwrap();
+    client.post(format!("{}/v1/volumes", base_url))
+        .json(&json!({ "id": "stream-vol", "size_gb": 1 }))
+        .send().await.unwrap();
+    client.post(format!("{}/v1/workloads", base_url))
+        .json(&json!({ "id": "stream-vm", "image": "ubuntu" }))
+        .send().await.unwrap();
+    let message = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap().unwrap();
+    let event: serde_json::Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(event["kind"], "workload.started");
+    assert_eq!(event["resource"], "stream-vm");
+
     // Abort server
     server_handle.abort();
 }
@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .build_client(true)
+        .compile_protos(&["proto/zero/v1/zero.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,383 @@
+//! ZeroCloud control-plane API over gRPC.
+//!
+//! Each RPC is turned into the request its HTTP route takes and handed to
+//! [`ZeroProvider::handle_request`], so both APIs share validation, namespace quotas,
+//! events and the audit trail. Only the framing differs: typed messages instead of JSON
+//! bodies, and server streams instead of the events WebSocket.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::{Stream, StreamExt};
+use serde_json::{json, Value};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tonic::metadata::MetadataMap;
+use tonic::{Code, Request, Response, Status};
+use zero_control_core::services::audit::PRINCIPAL_HEADER;
+use zero_control_core::services::namespace::NAMESPACE_HEADER;
+use zero_control_core::ZeroProvider;
+use zero_control_spi::{ZeroBody, ZeroError, ZeroRequest, ZeroService};
+use zero_data_core::events::ResourceEvent;
+
+pub mod pb {
+    tonic::include_proto!("zero.v1");
+}
+
+use pb::event_service_server::{EventService, EventServiceServer};
+use pb::network_service_server::{NetworkService, NetworkServiceServer};
+use pb::queue_service_server::{QueueService, QueueServiceServer};
+use pb::volume_service_server::{VolumeService, VolumeServiceServer};
+use pb::workload_service_server::{WorkloadService, WorkloadServiceServer};
+
+/// Trailer carrying the error code an HTTP error body would report, e.g. `QuotaExceeded`
+pub const ERROR_CODE_METADATA: &str = "x-zero-error-code";
+
+/// Principal recorded in the audit trail for gRPC calls, which are not signed
+pub const GRPC_PRINCIPAL: &str = "anonymous";
+
+/// Kind of the event sent in place of events a slow client missed, as on the WebSocket
+pub const EVENTS_LAGGED: &str = "events.lagged";
+
+/// Prefix of the kinds of workload lifecycle events
+const WORKLOAD_EVENTS: &str = "workload.";
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::Event, Status>> + Send>>;
+
+/// gRPC services backed by a [`ZeroProvider`]
+#[derive(Clone)]
+pub struct ZeroGrpc {
+    provider: Arc<ZeroProvider>,
+}
+
+impl ZeroGrpc {
+    pub fn new(provider: Arc<ZeroProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Every service on one router
+    pub fn router(self) -> tonic::transport::server::Router {
+        tonic::transport::Server::builder()
+            .add_service(WorkloadServiceServer::new(self.clone()))
+            .add_service(VolumeServiceServer::new(self.clone()))
+            .add_service(NetworkServiceServer::new(self.clone()))
+            .add_service(QueueServiceServer::new(self.clone()))
+            .add_service(EventServiceServer::new(self))
+    }
+
+    /// Run the HTTP route `method path` with a JSON body and return its JSON response
+    async fn call(&self, metadata: &MetadataMap, method: &str, path: &str, body: Option<Value>) -> Result<Value, Status> {
+        let mut headers = HashMap::new();
+        // Namespaces are picked like on HTTP, so one client can mix both APIs
+        if let Some(namespace) = metadata.get(NAMESPACE_HEADER).and_then(|v| v.to_str().ok()) {
+            headers.insert(NAMESPACE_HEADER.to_string(), namespace.to_string());
+        }
+        headers.insert(PRINCIPAL_HEADER.to_string(), GRPC_PRINCIPAL.to_string());
+        let body = match body {
+            Some(body) => {
+                headers.insert("content-type".to_string(), "application/json".to_string());
+                ZeroBody::from(body.to_string())
+            }
+            None => ZeroBody::empty(),
+        };
+        let req = ZeroRequest { method: method.to_string(), path: path.to_string(), headers, body };
+        let resp = self.provider.handle_request(req).await.map_err(status)?;
+        let bytes = resp.body.collect(usize::MAX).await.map_err(status)?;
+        serde_json::from_slice(&bytes).map_err(|e| Status::internal(format!("Invalid response from {} {}: {}", method, path, e)))
+    }
+
+    /// Events whose kind starts with `kind`, and that `keep` accepts, until the client cancels
+    fn watch(&self, kind: String, keep: impl Fn(&ResourceEvent) -> bool + Send + 'static) -> EventStream {
+        let events = BroadcastStream::new(self.provider.subscribe_events()).filter_map(move |event| {
+            let event = match event {
+                Ok(event) if event.kind.starts_with(&kind) && keep(&event) => Some(event),
+                Ok(_) => None,
+                // The client missed events and should re-read the state it shows
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(ResourceEvent::new(EVENTS_LAGGED, "", json!({ "skipped": skipped }))),
+            };
+            futures::future::ready(event.map(|event| Ok(to_event(event))))
+        });
+        Box::pin(events)
+    }
+}
+
+/// Start the gRPC API on `addr` and serve until the server fails
+pub async fn serve(provider: Arc<ZeroProvider>, addr: SocketAddr) -> Result<(), tonic::transport::Error> {
+    tracing::info!("ZeroCloud gRPC API listening on {}", addr);
+    ZeroGrpc::new(provider).router().serve(addr).await
+}
+
+/// The gRPC status of a provider error, with the HTTP error code as a trailer
+pub fn status(e: ZeroError) -> Status {
+    let code = match e {
+        ZeroError::Validation(_) | ZeroError::InvalidFields { .. } | ZeroError::InvalidRequest(_) => Code::InvalidArgument,
+        ZeroError::Unauthorized(_) => Code::PermissionDenied,
+        ZeroError::NotFound(_) => Code::NotFound,
+        ZeroError::AlreadyExists(_) => Code::AlreadyExists,
+        ZeroError::QuotaExceeded(_) => Code::ResourceExhausted,
+        ZeroError::Internal(_) => Code::Internal,
+        ZeroError::Driver(_) => Code::Unavailable,
+    };
+    let mut metadata = MetadataMap::new();
+    if let Ok(value) = e.code().parse() {
+        metadata.insert(ERROR_CODE_METADATA, value);
+    }
+    Status::with_metadata(code, e.message(), metadata)
+}
+
+fn string(value: &Value) -> String {
+    value.as_str().unwrap_or_default().to_string()
+}
+
+fn opt_string(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+fn port(value: &Value) -> pb::PortMapping {
+    pb::PortMapping {
+        host_port: value["host_port"].as_u64().unwrap_or_default() as u32,
+        container_port: value["container_port"].as_u64().unwrap_or_default() as u32,
+        protocol: string(&value["protocol"]),
+    }
+}
+
+fn workload(value: &Value) -> pb::Workload {
+    pb::Workload {
+        id: string(&value["id"]),
+        state: string(&value["state"]),
+        ip_address: opt_string(&value["ip_address"]),
+        ports: value["ports"].as_array().map(|ports| ports.iter().map(port).collect()).unwrap_or_default(),
+        restart_count: value["restart_count"].as_u64().unwrap_or_default() as u32,
+        node: opt_string(&value["node"]),
+    }
+}
+
+fn volume(value: &Value) -> pb::Volume {
+    pb::Volume {
+        id: string(&value["id"]),
+        path: string(&value["path"]),
+        state: string(&value["state"]),
+        size_gb: value["size_gb"].as_i64().map(|size| size as i32),
+        device: opt_string(&value["device"]),
+    }
+}
+
+fn network(value: &Value) -> pb::Network {
+    pb::Network { id: string(&value["id"]), cidr: string(&value["cidr"]), state: string(&value["state"]) }
+}
+
+fn message(value: &Value) -> pb::Message {
+    let attributes = value["Attributes"].as_object()
+        .map(|attributes| attributes.iter().map(|(name, value)| (name.clone(), string(value))).collect())
+        .unwrap_or_default();
+    pb::Message {
+        message_id: string(&value["MessageId"]),
+        body: string(&value["Body"]),
+        receipt_handle: string(&value["ReceiptHandle"]),
+        attributes,
+    }
+}
+
+fn to_event(event: ResourceEvent) -> pb::Event {
+    pb::Event { kind: event.kind, resource: event.resource, time: event.time, detail_json: event.detail.to_string() }
+}
+
+fn list(value: &Value, field: &str) -> Vec<Value> {
+    value[field].as_array().cloned().unwrap_or_default()
+}
+
+/// Path segment of a resource name sent in a message
+fn segment(name: &str) -> Result<&str, Status> {
+    if name.is_empty() || name.contains(['/', '?']) {
+        return Err(Status::invalid_argument(format!("Invalid name: {:?}", name)));
+    }
+    Ok(name)
+}
+
+#[tonic::async_trait]
+impl WorkloadService for ZeroGrpc {
+    type WatchWorkloadStream = EventStream;
+
+    async fn list_workloads(&self, request: Request<pb::ListWorkloadsRequest>) -> Result<Response<pb::ListWorkloadsResponse>, Status> {
+        let listed = self.call(request.metadata(), "GET", "/v1/workloads", None).await?;
+        let workloads = list(&listed, "workloads").iter().map(workload).collect();
+        Ok(Response::new(pb::ListWorkloadsResponse { workloads }))
+    }
+
+    async fn create_workload(&self, request: Request<pb::CreateWorkloadRequest>) -> Result<Response<pb::Workload>, Status> {
+        let (metadata, _, req) = request.into_parts();
+        let mut body = json!({
+            "id": req.id,
+            "image": req.image,
+            "volumes": req.volumes.iter()
+                .map(|mount| json!({ "volume_id": mount.volume_id, "target": mount.target, "read_only": mount.read_only }))
+                .collect::<Vec<_>>(),
+            "ports": req.ports.iter()
+                .map(|port| json!({
+                    "host_port": port.host_port,
+                    "container_port": port.container_port,
+                    "protocol": if port.protocol.is_empty() { "tcp" } else { port.protocol.as_str() },
+                }))
+                .collect::<Vec<_>>(),
+            "node_selector": req.node_selector,
+        });
+        // Unset fields take the schema defaults
+        if let Some(cpu) = req.cpu {
+            body["cpu"] = json!(cpu);
+        }
+        if let Some(memory_mb) = req.memory_mb {
+            body["memory_mb"] = json!(memory_mb);
+        }
+        if let Some(restart_policy) = req.restart_policy {
+            body["restart_policy"] = json!(restart_policy);
+        }
+        let created = self.call(&metadata, "POST", "/v1/workloads", Some(body)).await?;
+        Ok(Response::new(workload(&created)))
+    }
+
+    async fn delete_workload(&self, request: Request<pb::DeleteWorkloadRequest>) -> Result<Response<pb::DeleteWorkloadResponse>, Status> {
+        let body = json!({ "id": request.get_ref().id });
+        let deleted = self.call(request.metadata(), "DELETE", "/v1/workloads", Some(body)).await?;
+        Ok(Response::new(pb::DeleteWorkloadResponse { id: string(&deleted["id"]) }))
+    }
+
+    async fn watch_workload(&self, request: Request<pb::WatchWorkloadRequest>) -> Result<Response<Self::WatchWorkloadStream>, Status> {
+        let id = request.into_inner().id;
+        if id.is_empty() {
+            return Err(Status::invalid_argument("A workload id is required"));
+        }
+        Ok(Response::new(self.watch(WORKLOAD_EVENTS.to_string(), move |event| event.resource == id)))
+    }
+}
+
+#[tonic::async_trait]
+impl VolumeService for ZeroGrpc {
+    async fn list_volumes(&self, request: Request<pb::ListVolumesRequest>) -> Result<Response<pb::ListVolumesResponse>, Status> {
+        let listed = self.call(request.metadata(), "GET", "/v1/volumes", None).await?;
+        let volumes = list(&listed, "volumes").iter().map(volume).collect();
+        Ok(Response::new(pb::ListVolumesResponse { volumes }))
+    }
+
+    async fn create_volume(&self, request: Request<pb::CreateVolumeRequest>) -> Result<Response<pb::Volume>, Status> {
+        let body = json!({ "id": request.get_ref().id, "size_gb": request.get_ref().size_gb });
+        let created = self.call(request.metadata(), "POST", "/v1/volumes", Some(body)).await?;
+        Ok(Response::new(volume(&created)))
+    }
+
+    async fn resize_volume(&self, request: Request<pb::ResizeVolumeRequest>) -> Result<Response<pb::Volume>, Status> {
+        let path = format!("/v1/volumes/{}", segment(&request.get_ref().id)?);
+        let body = json!({ "size_gb": request.get_ref().size_gb });
+        let resized = self.call(request.metadata(), "PUT", &path, Some(body)).await?;
+        Ok(Response::new(volume(&resized)))
+    }
+}
+
+#[tonic::async_trait]
+impl NetworkService for ZeroGrpc {
+    async fn list_networks(&self, request: Request<pb::ListNetworksRequest>) -> Result<Response<pb::ListNetworksResponse>, Status> {
+        let listed = self.call(request.metadata(), "GET", "/v1/networks", None).await?;
+        let networks = list(&listed, "networks").iter().map(network).collect();
+        Ok(Response::new(pb::ListNetworksResponse { networks }))
+    }
+
+    async fn create_network(&self, request: Request<pb::CreateNetworkRequest>) -> Result<Response<pb::Network>, Status> {
+        let body = json!({ "id": request.get_ref().id, "cidr": request.get_ref().cidr });
+        let created = self.call(request.metadata(), "POST", "/v1/networks", Some(body)).await?;
+        Ok(Response::new(network(&created)))
+    }
+
+    async fn delete_network(&self, request: Request<pb::DeleteNetworkRequest>) -> Result<Response<pb::DeleteNetworkResponse>, Status> {
+        let path = format!("/v1/networks/{}", segment(&request.get_ref().id)?);
+        let deleted = self.call(request.metadata(), "DELETE", &path, None).await?;
+        Ok(Response::new(pb::DeleteNetworkResponse { id: string(&deleted["id"]) }))
+    }
+
+    async fn connect_workload(&self, request: Request<pb::ConnectWorkloadRequest>) -> Result<Response<pb::ConnectWorkloadResponse>, Status> {
+        let req = request.get_ref();
+        let path = format!("/v1/networks/{}/workloads", segment(&req.network_id)?);
+        let mut body = json!({ "workload_id": req.workload_id });
+        if let Some(ip) = &req.ip {
+            body["ip"] = json!(ip);
+        }
+        let connected = self.call(request.metadata(), "POST", &path, Some(body)).await?;
+        Ok(Response::new(pb::ConnectWorkloadResponse {
+            network_id: string(&connected["network_id"]),
+            workload_id: string(&connected["workload_id"]),
+            ip: string(&connected["ip"]),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl QueueService for ZeroGrpc {
+    async fn list_queues(&self, request: Request<pb::ListQueuesRequest>) -> Result<Response<pb::ListQueuesResponse>, Status> {
+        let listed = self.call(request.metadata(), "GET", "/v1/queues", None).await?;
+        let queue_urls = list(&listed, "QueueUrls").iter().map(string).collect();
+        Ok(Response::new(pb::ListQueuesResponse { queue_urls }))
+    }
+
+    async fn create_queue(&self, request: Request<pb::CreateQueueRequest>) -> Result<Response<pb::CreateQueueResponse>, Status> {
+        let req = request.get_ref();
+        let mut body = json!({ "name": req.name, "fifo": req.fifo, "content_based_deduplication": req.content_based_deduplication });
+        if let Some(visibility_timeout) = req.visibility_timeout {
+            body["visibility_timeout"] = json!(visibility_timeout);
+        }
+        if let Some(delay_seconds) = req.delay_seconds {
+            body["delay_seconds"] = json!(delay_seconds);
+        }
+        let created = self.call(request.metadata(), "POST", "/v1/queues", Some(body)).await?;
+        Ok(Response::new(pb::CreateQueueResponse { queue_url: string(&created["QueueUrl"]) }))
+    }
+
+    async fn send_message(&self, request: Request<pb::SendMessageRequest>) -> Result<Response<pb::SendMessageResponse>, Status> {
+        let req = request.get_ref();
+        let path = format!("/v1/queues/{}/messages", segment(&req.queue)?);
+        let mut body = json!({ "body": req.body });
+        if let Some(group_id) = &req.group_id {
+            body["group_id"] = json!(group_id);
+        }
+        if let Some(deduplication_id) = &req.deduplication_id {
+            body["deduplication_id"] = json!(deduplication_id);
+        }
+        if let Some(delay_seconds) = req.delay_seconds {
+            body["delay_seconds"] = json!(delay_seconds);
+        }
+        let sent = self.call(request.metadata(), "POST", &path, Some(body)).await?;
+        Ok(Response::new(pb::SendMessageResponse { message_id: string(&sent["MessageId"]) }))
+    }
+
+    async fn receive_messages(&self, request: Request<pb::ReceiveMessagesRequest>) -> Result<Response<pb::ReceiveMessagesResponse>, Status> {
+        let req = request.get_ref();
+        let path = format!("/v1/queues/{}/receive", segment(&req.queue)?);
+        let mut body = json!({});
+        if let Some(max_messages) = req.max_messages {
+            body["max_messages"] = json!(max_messages);
+        }
+        if let Some(wait_time_seconds) = req.wait_time_seconds {
+            body["wait_time_seconds"] = json!(wait_time_seconds);
+        }
+        if let Some(visibility_timeout) = req.visibility_timeout {
+            body["visibility_timeout"] = json!(visibility_timeout);
+        }
+        let received = self.call(request.metadata(), "POST", &path, Some(body)).await?;
+        let messages = list(&received, "Messages").iter().map(message).collect();
+        Ok(Response::new(pb::ReceiveMessagesResponse { messages }))
+    }
+
+    async fn delete_message(&self, request: Request<pb::DeleteMessageRequest>) -> Result<Response<pb::DeleteMessageResponse>, Status> {
+        let req = request.get_ref();
+        let path = format!("/v1/queues/{}/messages/{}", segment(&req.queue)?, segment(&req.receipt_handle)?);
+        self.call(request.metadata(), "DELETE", &path, None).await?;
+        Ok(Response::new(pb::DeleteMessageResponse {}))
+    }
+}
+
+#[tonic::async_trait]
+impl EventService for ZeroGrpc {
+    type WatchEventsStream = EventStream;
+
+    async fn watch_events(&self, request: Request<pb::WatchEventsRequest>) -> Result<Response<Self::WatchEventsStream>, Status> {
+        Ok(Response::new(self.watch(request.into_inner().kind, |_| true)))
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+use tokio::time::{sleep, timeout, Duration};
+use tonic::Code;
+use zero_control_core::ZeroProvider;
+use zero_control_grpc::pb;
+use zero_control_grpc::pb::event_service_client::EventServiceClient;
+use zero_control_grpc::pb::network_service_client::NetworkServiceClient;
+use zero_control_grpc::pb::queue_service_client::QueueServiceClient;
+use zero_control_grpc::pb::volume_service_client::VolumeServiceClient;
+use zero_control_grpc::pb::workload_service_client::WorkloadServiceClient;
+use zero_control_grpc::{serve, ERROR_CODE_METADATA};
+use zero_data_core::ZeroEngine;
+
+/// Serve a provider on mock drivers and return the URL clients connect to
+async fn start(port: u16) -> String {
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = Arc::new(ZeroProvider::new(Arc::new(engine)));
+    tokio::spawn(serve(provider, ([127, 0, 0, 1], port).into()));
+    sleep(Duration::from_millis(300)).await;
+    format!("http://127.0.0.1:{}", port)
+}
+
+#[tokio::test]
+async fn test_grpc_workloads_and_events() {
+    let url = start(50151).await;
+    let mut workloads = WorkloadServiceClient::connect(url.clone()).await.unwrap();
+    let mut events = EventServiceClient::connect(url).await.unwrap();
+
+    let mut stream = events.watch_events(pb::WatchEventsRequest { kind: "workload.".into() }).await.unwrap().into_inner();
+
+    let created = workloads.create_workload(pb::CreateWorkloadRequest {
+        id: "grpc-vm".into(),
+        image: "ubuntu".into(),
+        ..Default::default()
+    }).await.unwrap().into_inner();
+    assert_eq!(created.id, "grpc-vm");
+
+    let event = timeout(Duration::from_secs(5), stream.message()).await.unwrap().unwrap().unwrap();
+    assert_eq!(event.kind, "workload.started");
+    assert_eq!(event.resource, "grpc-vm");
+
+    let listed = workloads.list_workloads(pb::ListWorkloadsRequest {}).await.unwrap().into_inner();
+    assert!(listed.workloads.iter().any(|workload| workload.id == "grpc-vm"));
+
+    // Errors keep the code the HTTP API reports
+    let err = workloads.create_workload(pb::CreateWorkloadRequest {
+        id: "grpc-vm".into(),
+        image: "ubuntu".into(),
+        cpu: Some(0.0),
+        ..Default::default()
+    }).await.unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.metadata().get(ERROR_CODE_METADATA).is_some());
+
+    let deleted = workloads.delete_workload(pb::DeleteWorkloadRequest { id: "grpc-vm".into() }).await.unwrap().into_inner();
+    assert_eq!(deleted.id, "grpc-vm");
+    let err = workloads.delete_workload(pb::DeleteWorkloadRequest { id: "grpc-vm".into() }).await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_grpc_volumes_networks_and_queues() {
+    let url = start(50152).await;
+
+    let mut volumes = VolumeServiceClient::connect(url.clone()).await.unwrap();
+    let volume = volumes.create_volume(pb::CreateVolumeRequest { id: "grpc-vol".into(), size_gb: 1 }).await.unwrap().into_inner();
+    assert_eq!(volume.id, "grpc-vol");
+    let listed = volumes.list_volumes(pb::ListVolumesRequest {}).await.unwrap().into_inner();
+    assert!(listed.volumes.iter().any(|volume| volume.id == "grpc-vol"));
+
+    let mut networks = NetworkServiceClient::connect(url.clone()).await.unwrap();
+    let network = networks.create_network(pb::CreateNetworkRequest { id: "grpc-net".into(), cidr: "10.42.0.0/24".into() }).await.unwrap().into_inner();
+    assert_eq!(network.cidr, "10.42.0.0/24");
+    let deleted = networks.delete_network(pb::DeleteNetworkRequest { id: "grpc-net".into() }).await.unwrap().into_inner();
+    assert_eq!(deleted.id, "grpc-net");
+
+    let mut queues = QueueServiceClient::connect(url).await.unwrap();
+    let created = queues.create_queue(pb::CreateQueueRequest { name: "grpc-queue".into(), ..Default::default() }).await.unwrap().into_inner();
+    assert!(created.queue_url.contains("grpc-queue"));
+    let sent = queues.send_message(pb::SendMessageRequest {
+        queue: "grpc-queue".into(),
+        body: "hello".into(),
+        ..Default::default()
+    }).await.unwrap().into_inner();
+
+    let received = queues.receive_messages(pb::ReceiveMessagesRequest { queue: "grpc-queue".into(), ..Default::default() })
+        .await.unwrap().into_inner();
+    assert_eq!(received.messages.len(), 1);
+    assert_eq!(received.messages[0].message_id, sent.message_id);
+    assert_eq!(received.messages[0].body, "hello");
+
+    queues.delete_message(pb::DeleteMessageRequest {
+        queue: "grpc-queue".into(),
+        receipt_handle: received.messages[0].receipt_handle.clone(),
+    }).await.unwrap();
+
+    let err = queues.send_message(pb::SendMessageRequest { queue: "missing".into(), body: "x".into(), ..Default::default() })
+        .await.unwrap_err();
+    assert_eq!(err.code(), Code::NotFound);
+}
@@ -2,7 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::pin::Pin;
 use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
 
 /// ZeroCloud Result type
 pub type ZeroResult<T> = Result<T, ZeroError>;
@@ -22,20 +25,279 @@ pub enum ZeroError {
     #[error("Resource already exists: {0}")]
     AlreadyExists(String),
 
+    /// A create would take a namespace past its quota
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
     #[error("Driver error: {0}")]
     Driver(String),
 
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// A request body failed schema validation
+    #[error("Validation error: {message}")]
+    InvalidFields { message: String, fields: Vec<FieldError> },
+}
+
+/// A request body field that failed validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Path of the field in the body, e.g. `scalingConfig.desiredSize` or `entries[2].id`;
+    /// empty for the body itself
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
+
+impl ZeroError {
+    /// Validation error listing every invalid field
+    pub fn invalid_fields(fields: Vec<FieldError>) -> Self {
+        let message = fields.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("; ");
+        ZeroError::InvalidFields { message, fields }
+    }
+
+    /// Fields that failed validation; empty for other errors
+    pub fn fields(&self) -> &[FieldError] {
+        match self {
+            ZeroError::InvalidFields { fields, .. } => fields,
+            _ => &[],
+        }
+    }
+
+    /// Stable error code reported to API clients
+    pub fn code(&self) -> &'static str {
+        match self {
+            ZeroError::Internal(_) => "InternalError",
+            ZeroError::NotFound(_) => "NotFound",
+            ZeroError::Validation(_) | ZeroError::InvalidFields { .. } => "ValidationError",
+            ZeroError::AlreadyExists(_) => "AlreadyExists",
+            ZeroError::QuotaExceeded(_) => "QuotaExceeded",
+            ZeroError::Driver(_) => "DriverError",
+            ZeroError::InvalidRequest(_) => "InvalidRequest",
+            ZeroError::Unauthorized(_) => "AccessDenied",
+        }
+    }
+
+    /// HTTP status the error is reported with
+    pub fn status(&self) -> u16 {
+        match self {
+            ZeroError::Validation(_) | ZeroError::InvalidFields { .. } | ZeroError::InvalidRequest(_) => 400,
+            ZeroError::Unauthorized(_) | ZeroError::QuotaExceeded(_) => 403,
+            ZeroError::NotFound(_) => 404,
+            ZeroError::AlreadyExists(_) => 409,
+            ZeroError::Internal(_) => 500,
+            ZeroError::Driver(_) => 502,
+        }
+    }
+
+    /// The error's message without its kind
+    pub fn message(&self) -> &str {
+        match self {
+            ZeroError::Internal(msg)
+            | ZeroError::NotFound(msg)
+            | ZeroError::Validation(msg)
+            | ZeroError::AlreadyExists(msg)
+            | ZeroError::QuotaExceeded(msg)
+            | ZeroError::Driver(msg)
+            | ZeroError::InvalidRequest(msg)
+            | ZeroError::Unauthorized(msg)
+            | ZeroError::InvalidFields { message: msg, .. } => msg,
+        }
+    }
+}
+
+/// Chunks of a streamed body
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Body of a request or response: bytes already in memory, or a stream that is read
+/// chunk by chunk so large payloads are never held in memory as a whole
+pub enum ZeroBody {
+    Full(Bytes),
+    /// Behind a mutex so a request stays `Sync` while routes borrow it
+    Stream(std::sync::Mutex<ByteStream>),
+}
+
+impl ZeroBody {
+    pub fn empty() -> Self {
+        ZeroBody::Full(Bytes::new())
+    }
+
+    pub fn from_stream(stream: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static) -> Self {
+        ZeroBody::Stream(std::sync::Mutex::new(Box::pin(stream)))
+    }
+
+    /// Contents of a buffered body. A stream has to be collected first and reads as empty.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            ZeroBody::Full(bytes) => bytes,
+            ZeroBody::Stream(_) => &[],
+        }
+    }
+
+    pub fn is_stream(&self) -> bool {
+        matches!(self, ZeroBody::Stream(_))
+    }
+
+    /// Read the whole body into memory, failing once it exceeds `limit` bytes
+    pub async fn collect(self, limit: usize) -> ZeroResult<Bytes> {
+        let mut stream = match self {
+            ZeroBody::Full(bytes) if bytes.len() > limit => {
+                return Err(ZeroError::Validation(format!("Body exceeds {} bytes", limit)));
+            }
+            ZeroBody::Full(bytes) => return Ok(bytes),
+            stream => stream.into_stream(),
+        };
+
+        let mut buffer = bytes::BytesMut::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| ZeroError::Internal(format!("Failed to read body: {}", e)))?;
+            if buffer.len() + chunk.len() > limit {
+                return Err(ZeroError::Validation(format!("Body exceeds {} bytes", limit)));
+            }
+            buffer.extend_from_slice(&chunk);
+        }
+        Ok(buffer.freeze())
+    }
+
+    pub fn into_stream(self) -> ByteStream {
+        match self {
+            ZeroBody::Full(bytes) => Box::pin(futures::stream::iter([Ok(bytes)])),
+            ZeroBody::Stream(stream) => stream.into_inner().unwrap_or_else(std::sync::PoisonError::into_inner),
+        }
+    }
+}
+
+impl Default for ZeroBody {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+/// A stream can be read only once, so its clone holds nothing, as `as_bytes` reads it
+impl Clone for ZeroBody {
+    fn clone(&self) -> Self {
+        match self {
+            ZeroBody::Full(bytes) => ZeroBody::Full(bytes.clone()),
+            ZeroBody::Stream(_) => ZeroBody::empty(),
+        }
+    }
+}
+
+/// Bytes, as `Vec<u8>` serializes; a stream has to be collected first
+impl Serialize for ZeroBody {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ZeroBody::Full(bytes) => bytes.as_ref().serialize(serializer),
+            ZeroBody::Stream(_) => Err(serde::ser::Error::custom("a streamed body must be collected before it is serialized")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ZeroBody {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<u8>::deserialize(deserializer).map(ZeroBody::from)
+    }
+}
+
+impl std::fmt::Debug for ZeroBody {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ZeroBody::Full(bytes) => f.debug_tuple("Full").field(bytes).finish(),
+            ZeroBody::Stream(_) => f.write_str("Stream(..)"),
+        }
+    }
+}
+
+impl From<Vec<u8>> for ZeroBody {
+    fn from(bytes: Vec<u8>) -> Self {
+        ZeroBody::Full(bytes.into())
+    }
+}
+
+impl From<Bytes> for ZeroBody {
+    fn from(bytes: Bytes) -> Self {
+        ZeroBody::Full(bytes)
+    }
+}
+
+impl From<String> for ZeroBody {
+    fn from(text: String) -> Self {
+        ZeroBody::Full(text.into())
+    }
+}
+
+impl From<&'static str> for ZeroBody {
+    fn from(text: &'static str) -> Self {
+        ZeroBody::Full(Bytes::from_static(text.as_bytes()))
+    }
 }
 
 /// Generic HTTP-like request for ZeroCloud services
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ZeroRequest {
     pub method: String,
+    /// Percent-decoded path, optionally followed by `?` and the query string as sent
     pub path: String,
     pub headers: HashMap<String, String>,
-    pub body: Vec<u8>,
+    pub body: ZeroBody,
+}
+
+impl ZeroRequest {
+    /// Value of a header, matched case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// The path without its query string
+    pub fn route_path(&self) -> &str {
+        self.path.split_once('?').map_or(self.path.as_str(), |(path, _)| path)
+    }
+
+    /// Percent-decoded value of the first query string parameter called `name`
+    pub fn query(&self, name: &str) -> Option<String> {
+        let (_, query) = self.path.split_once('?')?;
+        query.split('&')
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+            .find(|(key, _)| percent_decode(key) == name)
+            .map(|(_, value)| percent_decode(&value.replace('+', " ")))
+    }
+}
+
+/// Decode `%XX` escapes; malformed escapes are kept as they are
+pub fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
 }
 
 /// Generic HTTP-like response for ZeroCloud services
@@ -43,11 +305,11 @@ pub struct ZeroRequest {
 pub struct ZeroResponse {
     pub status: u16,
     pub headers: HashMap<String, String>,
-    pub body: Vec<u8>,
+    pub body: ZeroBody,
 }
 
 impl ZeroResponse {
-    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+    pub fn ok(body: impl Into<ZeroBody>) -> Self {
         Self {
             status: 200,
             headers: HashMap::new(),
@@ -59,7 +321,7 @@ impl ZeroResponse {
         Self {
             status: 500,
             headers: HashMap::new(),
-            body: msg.as_bytes().to_vec(),
+            body: msg.to_string().into(),
         }
     }
     
@@ -69,7 +331,7 @@ impl ZeroResponse {
         Self {
             status: 200,
             headers,
-            body: val.to_string().into_bytes(),
+            body: val.to_string().into(),
         }
     }
 
@@ -79,7 +341,18 @@ impl ZeroResponse {
         Self {
             status: 200,
             headers,
-            body,
+            body: body.into(),
+        }
+    }
+
+    /// Response whose body is read from `stream` as the client consumes it
+    pub fn stream(content_type: &str, stream: impl Stream<Item = std::io::Result<Bytes>> + Send + 'static) -> Self {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), content_type.to_string());
+        Self {
+            status: 200,
+            headers,
+            body: ZeroBody::from_stream(stream),
         }
     }
 }
@@ -92,6 +365,133 @@ pub trait ComputeDriver: Send + Sync {
     async fn get_workload_status(&self, id: &str) -> ZeroResult<WorkloadStatus>;
     async fn list_workloads(&self) -> ZeroResult<Vec<WorkloadStatus>>;
     async fn get_stats(&self) -> ZeroResult<NodeStats>;
+
+    /// Create a workload with volumes attached. Drivers that cannot attach volumes reject any mounts.
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        if let Some(mount) = mounts.first() {
+            return Err(ZeroError::Driver(format!("This compute driver cannot attach volume {}", mount.volume_id)));
+        }
+        self.create_workload(id, image, cpu, mem_mb).await
+    }
+
+    /// Create a workload with volumes attached and `ports` published on the host, returning
+    /// the bound host ports in its status. Drivers that cannot publish ports create it
+    /// without, and the engine has the network driver forward them instead.
+    async fn create_workload_with_ports(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount], _ports: &[PortMapping]) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_volumes(id, image, cpu, mem_mb, mounts).await
+    }
+
+    /// Run a task to completion and collect its output. Drivers that cannot capture output
+    /// reject tasks.
+    async fn run_task(&self, id: &str, _spec: &TaskSpec) -> ZeroResult<TaskOutput> {
+        Err(ZeroError::Driver(format!("This compute driver cannot run task {}", id)))
+    }
+
+    /// CPU a workload is using, in percent of one CPU (200 is two busy CPUs). Drivers that
+    /// cannot measure single workloads report an error.
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        Err(ZeroError::Driver(format!("This compute driver cannot measure the CPU usage of {}", id)))
+    }
+
+    /// CPU, memory and network a workload is using. Drivers that cannot measure single
+    /// workloads report an error.
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        Err(ZeroError::Driver(format!("This compute driver cannot measure the resource usage of {}", id)))
+    }
+
+    /// Start a long-running container with its own command, environment and privileges.
+    /// Drivers that do not run containers reject it.
+    async fn create_container(&self, id: &str, _spec: &ContainerSpec) -> ZeroResult<WorkloadStatus> {
+        Err(ZeroError::Driver(format!("This compute driver cannot run container {}", id)))
+    }
+
+    /// Pull an image into the driver's cache, see [`image_reference`]. Drivers without an
+    /// image cache reject it.
+    async fn pull_image(&self, reference: &str) -> ZeroResult<ImageInfo> {
+        Err(ZeroError::Driver(format!("This compute driver cannot pull image {}", reference)))
+    }
+
+    /// Images in the driver's cache, one per reference. Drivers without an image cache
+    /// report an error.
+    async fn list_images(&self) -> ZeroResult<Vec<ImageInfo>> {
+        Err(ZeroError::Driver("This compute driver has no image cache".to_string()))
+    }
+
+    /// Remove an image from the driver's cache
+    async fn remove_image(&self, reference: &str) -> ZeroResult<()> {
+        Err(ZeroError::Driver(format!("This compute driver cannot remove image {}", reference)))
+    }
+}
+
+/// An image in a compute driver's cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageInfo {
+    /// Name and tag, such as `nginx:latest`; the ID for untagged images
+    pub reference: String,
+    pub id: String,
+    pub size_bytes: u64,
+}
+
+/// An image reference with its tag, `nginx` becoming `nginx:latest`; references with a tag
+/// or a digest are kept as they are
+pub fn image_reference(image: &str) -> String {
+    let name = image.rsplit('/').next().unwrap_or(image);
+    if name.contains(':') || name.contains('@') {
+        image.to_string()
+    } else {
+        format!("{}:latest", image)
+    }
+}
+
+/// Long-running container started by [`ComputeDriver::create_container`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerSpec {
+    pub image: String,
+    /// Replaces the image's entrypoint; the image default when empty
+    pub entrypoint: Vec<String>,
+    /// Replaces the image's command; the image default when empty
+    pub command: Vec<String>,
+    pub environment: HashMap<String, String>,
+    /// Give the container the host's devices and capabilities, as nested container
+    /// runtimes such as k3s need
+    pub privileged: bool,
+    pub cpu: f32,
+    pub memory_mb: i32,
+}
+
+/// A volume attached to a workload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolumeMount {
+    pub volume_id: String,
+    /// Host directory of the volume, bind-mounted into containers
+    pub source: String,
+    /// Host file holding the volume's blocks, attached to VMs as a virtio disk
+    pub backing_file: Option<String>,
+    /// Mount point inside a container, or the disk name (`vdb`) inside a VM
+    pub target: String,
+    pub read_only: bool,
+}
+
+/// One-shot task run by a compute driver
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskSpec {
+    pub image: String,
+    /// Passed to the task in the `ZERO_EVENT` environment variable
+    pub input: String,
+    /// Additional environment variables
+    pub environment: HashMap<String, String>,
+    /// Memory limit in MB; the driver default when unset
+    pub memory_mb: Option<i32>,
+    /// Seconds after which the task is killed
+    pub timeout_secs: Option<u64>,
+}
+
+/// Result of a one-shot task run by a compute driver
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskOutput {
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +503,20 @@ pub struct NodeStats {
     pub storage_total_gb: u64,
 }
 
+/// Resources one workload is using, as measured by [`ComputeDriver::workload_usage`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadUsage {
+    /// In percent of one CPU, as [`ComputeDriver::workload_cpu_percent`] reports it
+    pub cpu_percent: f32,
+    pub memory_used_mb: u64,
+    /// Memory the workload may use; 0 when the driver reports no limit
+    pub memory_limit_mb: u64,
+    /// Bytes received since the workload started
+    pub network_rx_bytes: u64,
+    /// Bytes sent since the workload started
+    pub network_tx_bytes: u64,
+}
+
 /// Trait for ZeroCloud storage drivers (Local FS, NVMe, etc.)
 #[async_trait]
 pub trait StorageDriver: Send + Sync {
@@ -111,6 +525,16 @@ pub trait StorageDriver: Send + Sync {
     async fn write_block(&self, volume_id: &str, offset: u64, data: Vec<u8>) -> ZeroResult<()>;
     async fn read_block(&self, volume_id: &str, offset: u64, length: u32) -> ZeroResult<Vec<u8>>;
     async fn list_volumes(&self) -> ZeroResult<Vec<VolumeStatus>>;
+
+    /// Host file holding a volume's blocks, for compute drivers that attach volumes as disks
+    async fn backing_file(&self, _volume_id: &str) -> ZeroResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Grow a volume to `size_gb`, keeping its data. Drivers without sized volumes reject it.
+    async fn resize_volume(&self, id: &str, _size_gb: i32) -> ZeroResult<VolumeStatus> {
+        Err(ZeroError::Driver(format!("This storage driver cannot resize volume {}", id)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -118,6 +542,165 @@ pub struct WorkloadStatus {
     pub id: String,
     pub state: String, // Running, Stopped, Failed
     pub ip_address: Option<String>,
+    /// Host ports forwarded to the workload
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<PortMapping>,
+    /// Times the control plane restarted the workload under its restart policy
+    #[serde(default)]
+    pub restart_count: u32,
+}
+
+impl WorkloadStatus {
+    /// Drivers name states differently, e.g. `running` for Docker and `Running` for KVM
+    pub fn is_running(&self) -> bool {
+        self.state.eq_ignore_ascii_case("running")
+    }
+
+    /// Whether the workload stopped by itself without an error
+    pub fn has_stopped(&self) -> bool {
+        ["stopped", "exited"].iter().any(|state| self.state.eq_ignore_ascii_case(state))
+    }
+
+    /// Whether the workload crashed or exited with an error
+    pub fn has_failed(&self) -> bool {
+        ["failed", "dead", "crashed"].iter().any(|state| self.state.eq_ignore_ascii_case(state))
+    }
+}
+
+/// When the control plane restarts a workload
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Leave stopped and failed workloads as they are
+    #[default]
+    Never,
+    /// Restart workloads that fail or whose liveness probe fails
+    OnFailure,
+    /// Also restart workloads that stop by themselves
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::OnFailure => "on-failure",
+            Self::Always => "always",
+        }
+    }
+}
+
+impl std::str::FromStr for RestartPolicy {
+    type Err = ZeroError;
+
+    fn from_str(name: &str) -> ZeroResult<Self> {
+        [Self::Never, Self::OnFailure, Self::Always]
+            .into_iter()
+            .find(|policy| policy.name() == name)
+            .ok_or_else(|| ZeroError::Validation(format!("Invalid restart policy {}, expected never, on-failure or always", name)))
+    }
+}
+
+fn default_probe_interval_secs() -> u32 {
+    10
+}
+
+fn default_probe_failure_threshold() -> u32 {
+    3
+}
+
+/// How the control plane checks that a running workload still works
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LivenessProbe {
+    /// Path of an HTTP GET that must not answer with an error status; without one the
+    /// probe only opens a TCP connection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_path: Option<String>,
+    /// Port of the workload probed
+    pub port: u16,
+    #[serde(default = "default_probe_interval_secs")]
+    pub interval_secs: u32,
+    /// Consecutive failed probes after which the workload counts as failed
+    #[serde(default = "default_probe_failure_threshold")]
+    pub failure_threshold: u32,
+}
+
+impl std::str::FromStr for LivenessProbe {
+    type Err = ZeroError;
+
+    /// `tcp:PORT` or `http:PORT/PATH`, probed at the default interval and threshold
+    fn from_str(spec: &str) -> ZeroResult<Self> {
+        let invalid = || ZeroError::Validation(format!("Invalid liveness probe {}, expected tcp:PORT or http:PORT/PATH", spec));
+        let (port, http_path) = match spec.split_once(':') {
+            Some(("tcp", port)) => (port, None),
+            Some(("http", target)) => match target.find('/') {
+                Some(slash) => (&target[..slash], Some(target[slash..].to_string())),
+                None => (target, Some("/".to_string())),
+            },
+            _ => return Err(invalid()),
+        };
+        let port = port.parse().map_err(|_| invalid())?;
+        if port == 0 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            http_path,
+            port,
+            interval_secs: default_probe_interval_secs(),
+            failure_threshold: default_probe_failure_threshold(),
+        })
+    }
+}
+
+/// A host port forwarded to a port of a workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortMapping {
+    /// Port bound on the host; 0 asks for a free one
+    #[serde(default)]
+    pub host_port: u16,
+    pub container_port: u16,
+    #[serde(default)]
+    pub protocol: PortProtocol,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PortProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl PortProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+        }
+    }
+}
+
+impl std::str::FromStr for PortMapping {
+    type Err = ZeroError;
+
+    /// `HOST:CONTAINER[/PROTOCOL]` as `docker run --publish` takes it; a container port alone
+    /// is published on a free host port
+    fn from_str(spec: &str) -> ZeroResult<Self> {
+        let invalid = || ZeroError::Validation(format!("Invalid port mapping {}, expected HOST:CONTAINER[/tcp|udp]", spec));
+        let (ports, protocol) = match spec.split_once('/') {
+            Some((ports, "tcp")) => (ports, PortProtocol::Tcp),
+            Some((ports, "udp")) => (ports, PortProtocol::Udp),
+            Some(_) => return Err(invalid()),
+            None => (spec, PortProtocol::Tcp),
+        };
+        let (host, container) = ports.split_once(':').unwrap_or(("0", ports));
+        let host_port = host.parse().map_err(|_| invalid())?;
+        let container_port = container.parse().map_err(|_| invalid())?;
+        if container_port == 0 {
+            return Err(invalid());
+        }
+        Ok(Self { host_port, container_port, protocol })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +708,12 @@ pub struct VolumeStatus {
     pub id: String,
     pub path: String,
     pub state: String, // Available, InUse
+    /// Size of the volume's block file, for volumes that have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_gb: Option<i32>,
+    /// Host block device the volume is attached to, such as `/dev/loop3`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
 }
 
 /// Trait for ZeroCloud networking drivers (Linux Bridge, OVS, Hyper-V Switch)
@@ -134,6 +723,90 @@ pub trait NetworkDriver: Send + Sync {
     async fn delete_network(&self, id: &str) -> ZeroResult<()>;
     async fn connect_workload(&self, workload_id: &str, network_id: &str) -> ZeroResult<String>; // Returns assigned IP
     async fn list_networks(&self) -> ZeroResult<Vec<NetworkStatus>>;
+
+    /// Connect a workload with the address IPAM gave it. Drivers whose workloads lease
+    /// addresses from a DHCP server of their own ignore it and return the leased address.
+    async fn connect_workload_with_address(&self, workload_id: &str, network_id: &str, _address: &str) -> ZeroResult<String> {
+        self.connect_workload(workload_id, network_id).await
+    }
+
+    /// Enforce the security groups of a network on its workloads, replacing the groups it had.
+    /// With no groups, all traffic is allowed again. Drivers without a firewall reject it.
+    async fn set_security_groups(&self, network_id: &str, _groups: &[SecurityGroup]) -> ZeroResult<()> {
+        Err(ZeroError::Driver(format!("This network driver cannot enforce security groups on {}", network_id)))
+    }
+
+    /// Forward host ports to a workload at its address in a network, with DNAT or the host's
+    /// equivalent. Drivers without one reject it.
+    async fn publish_ports(&self, workload_id: &str, _network_id: &str, _address: &str, _ports: &[PortMapping]) -> ZeroResult<()> {
+        Err(ZeroError::Driver(format!("This network driver cannot publish ports of {}", workload_id)))
+    }
+
+    /// Stop forwarding the host ports of a workload
+    async fn unpublish_ports(&self, _workload_id: &str, _network_id: &str, _ports: &[PortMapping]) -> ZeroResult<()> {
+        Ok(())
+    }
+}
+
+/// Firewall rules of a network's workloads. Once a network has security groups, traffic
+/// reaches its workloads only when an ingress rule of one of them allows it, and leaves them
+/// only when an egress rule allows it; replies to allowed traffic always pass.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecurityGroup {
+    pub id: String,
+    pub network_id: String,
+    pub name: String,
+    pub rules: Vec<SecurityRule>,
+}
+
+/// Traffic allowed by a security group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecurityRule {
+    pub direction: RuleDirection,
+    pub protocol: RuleProtocol,
+    /// First port of the range, for TCP and UDP; all ports when unset
+    #[serde(default)]
+    pub from_port: Option<u16>,
+    /// Last port of the range; `from_port` alone when unset
+    #[serde(default)]
+    pub to_port: Option<u16>,
+    /// Source of ingress traffic or destination of egress traffic
+    pub cidr: String,
+}
+
+impl SecurityRule {
+    /// Port range of the rule, `None` for all ports
+    pub fn ports(&self) -> Option<(u16, u16)> {
+        self.from_port.map(|from| (from, self.to_port.unwrap_or(from)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleDirection {
+    Ingress,
+    Egress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleProtocol {
+    Tcp,
+    Udp,
+    Icmp,
+    All,
+}
+
+impl RuleProtocol {
+    /// Name iptables and nftables use, `None` for all protocols
+    pub fn name(&self) -> Option<&'static str> {
+        match self {
+            Self::Tcp => Some("tcp"),
+            Self::Udp => Some("udp"),
+            Self::Icmp => Some("icmp"),
+            Self::All => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
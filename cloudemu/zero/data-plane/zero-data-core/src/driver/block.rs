@@ -0,0 +1,193 @@
+use super::storage::{check_resize, FileSystemStorage, BLOCK_FILE, GIB};
+use zero_control_spi::{StorageDriver, ZeroResult, ZeroError, VolumeStatus};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File in a volume's directory that holds its blocks in qcow2 format
+const QCOW2_FILE: &str = "data.qcow2";
+
+/// On-disk format of block volumes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFormat {
+    /// Sparse raw file, attached to a loop device
+    Raw,
+    /// Thin qcow2 image, attached to VMs only
+    Qcow2,
+}
+
+impl std::str::FromStr for BlockFormat {
+    type Err = ZeroError;
+
+    fn from_str(format: &str) -> ZeroResult<Self> {
+        match format {
+            "raw" => Ok(Self::Raw),
+            "qcow2" => Ok(Self::Qcow2),
+            other => Err(ZeroError::Validation(format!("Unknown volume format {}, expected raw or qcow2", other))),
+        }
+    }
+}
+
+/// Block Volume Driver for Linux.
+/// Volumes are directories like those of [`FileSystemStorage`], whose block file is a real
+/// block device. Raw volumes are sparse `data.bin` files attached to a loop device
+/// (`/dev/loopN`), which the host can partition, format and mount; block reads and writes go to
+/// the file. Qcow2 volumes are `data.qcow2` images made with `qemu-img` that only KVM attaches,
+/// as virtio-blk disks, so their blocks are not readable through the API. Both can grow: the
+/// loop device or image is resized with the file, and running VMs see the new size after
+/// `virsh blockresize` or a restart.
+pub struct BlockStorage {
+    files: FileSystemStorage,
+    format: BlockFormat,
+}
+
+impl BlockStorage {
+    pub fn new(base_path: PathBuf, format: BlockFormat) -> Self {
+        Self { files: FileSystemStorage::new(base_path), format }
+    }
+
+    /// Whether the tools of a format are installed: `losetup` for raw volumes, which also
+    /// needs root, and `qemu-img` for qcow2 ones
+    pub fn is_available(format: BlockFormat) -> bool {
+        let installed = |tool: &str, flag: &str| Command::new(tool).arg(flag).output().map(|o| o.status.success()).unwrap_or(false);
+        match format {
+            BlockFormat::Raw => installed("losetup", "--version"),
+            BlockFormat::Qcow2 => installed("qemu-img", "--version"),
+        }
+    }
+
+    fn block_file(&self, id: &str) -> PathBuf {
+        let name = match self.format {
+            BlockFormat::Raw => BLOCK_FILE,
+            BlockFormat::Qcow2 => QCOW2_FILE,
+        };
+        self.files.volume_path(id).join(name)
+    }
+
+    fn status(&self, mut status: VolumeStatus) -> ZeroResult<VolumeStatus> {
+        let file = self.block_file(&status.id);
+        if self.format == BlockFormat::Qcow2 {
+            status.size_gb = file.exists().then(|| qcow2_size(&file)).transpose()?.map(|size| (size / GIB) as i32);
+        }
+        if self.format == BlockFormat::Raw && file.exists() {
+            status.device = loop_device(&file)?;
+        }
+        Ok(status)
+    }
+
+    fn qcow2_blocks(&self, id: &str) -> ZeroResult<()> {
+        match self.format {
+            BlockFormat::Raw => Ok(()),
+            BlockFormat::Qcow2 => Err(ZeroError::Validation(format!("Blocks of qcow2 volume {} are only readable by the VMs attaching it", id))),
+        }
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> ZeroResult<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| ZeroError::Driver(format!("Failed to execute {}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(ZeroError::Driver(format!("Block volume command {} {} failed: {}", program, args.join(" "), err.trim())));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Loop device of a file in `losetup --associated` output, such as
+/// `/dev/loop3: [2049]:1234 (/srv/zero-storage/db/data.bin)`
+pub(crate) fn parse_loop_device(output: &str) -> Option<String> {
+    output.lines().next()?.split_once(':').map(|(device, _)| device.to_string()).filter(|device| device.starts_with("/dev/"))
+}
+
+/// Loop device a block file is attached to
+fn loop_device(file: &Path) -> ZeroResult<Option<String>> {
+    Ok(parse_loop_device(&run("losetup", &["--associated", &file.to_string_lossy()])?))
+}
+
+/// Virtual size of a qcow2 image in bytes, from `qemu-img info --output=json`
+pub(crate) fn parse_virtual_size(info: &str) -> ZeroResult<u64> {
+    serde_json::from_str::<serde_json::Value>(info).ok()
+        .and_then(|info| info["virtual-size"].as_u64())
+        .ok_or_else(|| ZeroError::Driver("qemu-img info reported no virtual size".into()))
+}
+
+fn qcow2_size(file: &Path) -> ZeroResult<u64> {
+    parse_virtual_size(&run("qemu-img", &["info", "--output=json", &file.to_string_lossy()])?)
+}
+
+#[async_trait]
+impl StorageDriver for BlockStorage {
+    async fn create_volume(&self, id: &str, size_gb: i32) -> ZeroResult<VolumeStatus> {
+        let file = self.block_file(id);
+        let status = match self.format {
+            BlockFormat::Raw => self.files.create_volume(id, size_gb).await?,
+            BlockFormat::Qcow2 => {
+                let status = self.files.create_volume(id, 0).await?;
+                // Recreating a volume keeps its data
+                if size_gb > 0 && !file.exists() {
+                    run("qemu-img", &["create", "-q", "-f", "qcow2", &file.to_string_lossy(), &format!("{}G", size_gb)])?;
+                }
+                status
+            }
+        };
+        if self.format == BlockFormat::Raw && file.exists() && loop_device(&file)?.is_none() {
+            run("losetup", &["--find", "--show", &file.to_string_lossy()])?;
+        }
+        self.status(status)
+    }
+
+    async fn delete_volume(&self, id: &str) -> ZeroResult<()> {
+        let file = self.block_file(id);
+        if self.format == BlockFormat::Raw && file.exists() {
+            if let Some(device) = loop_device(&file)? {
+                run("losetup", &["--detach", &device])?;
+            }
+        }
+        self.files.delete_volume(id).await
+    }
+
+    async fn write_block(&self, volume_id: &str, offset: u64, data: Vec<u8>) -> ZeroResult<()> {
+        self.qcow2_blocks(volume_id)?;
+        self.files.write_block(volume_id, offset, data).await
+    }
+
+    async fn read_block(&self, volume_id: &str, offset: u64, length: u32) -> ZeroResult<Vec<u8>> {
+        self.qcow2_blocks(volume_id)?;
+        self.files.read_block(volume_id, offset, length).await
+    }
+
+    async fn list_volumes(&self) -> ZeroResult<Vec<VolumeStatus>> {
+        self.files.list_volumes().await?.into_iter().map(|status| self.status(status)).collect()
+    }
+
+    async fn backing_file(&self, volume_id: &str) -> ZeroResult<Option<String>> {
+        let file = self.block_file(volume_id);
+        Ok(file.exists().then(|| file.to_string_lossy().to_string()))
+    }
+
+    /// Grows the block file, then the loop device or the image's virtual size
+    async fn resize_volume(&self, id: &str, size_gb: i32) -> ZeroResult<VolumeStatus> {
+        let status = match self.format {
+            BlockFormat::Raw => {
+                let status = self.files.resize_volume(id, size_gb).await?;
+                // The loop device keeps its old capacity until told to re-read it
+                if let Some(device) = loop_device(&self.block_file(id))? {
+                    run("losetup", &["--set-capacity", &device])?;
+                }
+                status
+            }
+            BlockFormat::Qcow2 => {
+                let current = self.status(self.files.list_volumes().await?.into_iter().find(|volume| volume.id == id)
+                    .ok_or_else(|| ZeroError::NotFound(format!("Volume not found: {}", id)))?)?;
+                check_resize(id, current.size_gb, size_gb)?;
+                run("qemu-img", &["resize", "-q", &self.block_file(id).to_string_lossy(), &format!("{}G", size_gb)])?;
+                current
+            }
+        };
+        self.status(status)
+    }
+}
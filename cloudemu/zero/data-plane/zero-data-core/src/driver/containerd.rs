@@ -0,0 +1,321 @@
+use zero_control_spi::{image_reference, ComputeDriver, ContainerSpec, ImageInfo, NodeStats, ZeroResult, ZeroError, WorkloadStatus, WorkloadUsage, TaskOutput, TaskSpec, VolumeMount};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::process::Command;
+
+/// containerd namespace workloads are created in, so `nerdctl ps` and Kubernetes
+/// (`k8s.io`) containers on the same host are never listed or deleted
+const DEFAULT_NAMESPACE: &str = "zero";
+
+/// containerd's default socket
+const DEFAULT_ADDRESS: &str = "/run/containerd/containerd.sock";
+
+/// Exit code nerdctl uses when it could not create or start the container itself
+const NERDCTL_ERROR_EXIT: i32 = 125;
+
+/// containerd Driver for hosts without a Docker daemon, such as Kubernetes nodes.
+/// Uses the nerdctl CLI internally, which speaks containerd's API and brings CNI
+/// networking and Docker-compatible output.
+pub struct ContainerdDriver {
+    address: String,
+    namespace: String,
+}
+
+impl Default for ContainerdDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContainerdDriver {
+    pub fn new() -> Self {
+        Self::with_address(DEFAULT_ADDRESS, DEFAULT_NAMESPACE)
+    }
+
+    /// Use the containerd socket at `address` and keep workloads in `namespace`
+    pub fn with_address(address: impl Into<String>, namespace: impl Into<String>) -> Self {
+        Self { address: address.into(), namespace: namespace.into() }
+    }
+
+    /// Whether containerd is running and nerdctl is installed
+    pub fn is_available() -> bool {
+        let nerdctl = Command::new("nerdctl").arg("--version").output().map(|o| o.status.success()).unwrap_or(false);
+        nerdctl && std::path::Path::new(DEFAULT_ADDRESS).exists()
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("nerdctl");
+        command.args(["--address", &self.address, "--namespace", &self.namespace]);
+        command
+    }
+
+    fn run_nerdctl(&self, args: &[&str]) -> ZeroResult<String> {
+        let output = self.command()
+            .args(args)
+            .output()
+            .map_err(|e| ZeroError::Driver(format!("Failed to execute nerdctl: {}", e)))?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            return Err(ZeroError::Driver(format!("containerd command {} failed: {}", args.first().unwrap_or(&""), err.trim())));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// `run` arguments limiting a container's CPU and memory
+    fn limits(cpu: f32, mem_mb: i32) -> Vec<String> {
+        let mut args = Vec::new();
+        if cpu > 0.0 {
+            args.extend(["--cpus".to_string(), cpu.to_string()]);
+        }
+        if mem_mb > 0 {
+            args.extend(["--memory".to_string(), format!("{}m", mem_mb)]);
+        }
+        args
+    }
+
+    fn environment<'a>(variables: impl Iterator<Item = (&'a String, &'a String)>) -> Vec<String> {
+        variables.flat_map(|(key, value)| ["-e".to_string(), format!("{}={}", key, value)]).collect()
+    }
+
+    fn start(&self, id: &str, args: Vec<String>) -> ZeroResult<WorkloadStatus> {
+        let mut run = vec!["run".to_string(), "-d".to_string(), "--name".to_string(), id.to_string()];
+        run.extend(args);
+        self.run_nerdctl(&run.iter().map(String::as_str).collect::<Vec<_>>())?;
+        // The address is only assigned once the container runs
+        self.status(id)
+    }
+
+    fn status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        let output = self.run_nerdctl(&["inspect", "--mode", "dockercompat", id])?;
+        parse_inspect(id, &output)
+    }
+}
+
+#[async_trait]
+impl ComputeDriver for ContainerdDriver {
+    async fn create_workload(&self, id: &str, image: &str, cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_volumes(id, image, cpu, mem_mb, &[]).await
+    }
+
+    /// Bind-mounts each volume's directory into the container
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        let mut args = Self::limits(cpu, mem_mb);
+        for mount in mounts {
+            if !mount.target.starts_with('/') {
+                return Err(ZeroError::Validation(format!("Container mount point must be an absolute path: {}", mount.target)));
+            }
+            args.push("-v".to_string());
+            args.push(format!("{}:{}{}", mount.source, mount.target, if mount.read_only { ":ro" } else { "" }));
+        }
+        args.push(image.to_string());
+        self.start(id, args)
+    }
+
+    async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
+        self.run_nerdctl(&["rm", "-f", id])?;
+        Ok(())
+    }
+
+    async fn get_workload_status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        self.status(id)
+    }
+
+    async fn list_workloads(&self) -> ZeroResult<Vec<WorkloadStatus>> {
+        let output = self.run_nerdctl(&["ps", "-a", "--format", "{{json .}}"])?;
+        Ok(output.lines().filter_map(parse_ps_line).collect())
+    }
+
+    async fn get_stats(&self) -> ZeroResult<NodeStats> {
+        let info: Value = serde_json::from_str(&self.run_nerdctl(&["info", "--format", "{{json .}}"])?)
+            .map_err(|e| ZeroError::Driver(format!("Invalid nerdctl info output: {}", e)))?;
+        Ok(NodeStats {
+            cpu_usage_percent: 0.0,
+            memory_used_mb: 0,
+            memory_total_mb: info["MemTotal"].as_u64().unwrap_or_default() / 1024 / 1024,
+            storage_used_gb: 0,
+            storage_total_gb: 0,
+        })
+    }
+
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        let output = self.run_nerdctl(&["stats", "--no-stream", "--format", "{{json .}}", id])?;
+        output.lines().next().and_then(parse_cpu_percent)
+            .ok_or_else(|| ZeroError::Driver(format!("containerd returned no stats for {}", id)))
+    }
+
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        let output = self.run_nerdctl(&["stats", "--no-stream", "--format", "{{json .}}", id])?;
+        output.lines().next().and_then(parse_usage)
+            .ok_or_else(|| ZeroError::Driver(format!("containerd returned no stats for {}", id)))
+    }
+
+    async fn run_task(&self, id: &str, spec: &TaskSpec) -> ZeroResult<TaskOutput> {
+        let mut args = vec!["run".to_string(), "--name".to_string(), id.to_string()];
+        args.extend(Self::environment(spec.environment.iter()));
+        args.extend(["-e".to_string(), format!("ZERO_EVENT={}", spec.input)]);
+        args.extend(Self::limits(0.0, spec.memory_mb.unwrap_or_default()));
+        args.push(spec.image.clone());
+
+        let mut command = tokio::process::Command::from(self.command());
+        command.args(&args).kill_on_drop(true);
+        let result = match spec.timeout_secs {
+            Some(secs) => tokio::time::timeout(std::time::Duration::from_secs(secs), command.output()).await
+                .unwrap_or_else(|_| Err(std::io::Error::new(std::io::ErrorKind::TimedOut, format!("Task {} timed out after {} seconds", id, secs)))),
+            None => command.output().await,
+        };
+
+        // Always clean up the task container, even when the run failed
+        let _ = self.run_nerdctl(&["rm", "-f", id]);
+        let output = result.map_err(|e| ZeroError::Driver(format!("containerd run error: {}", e)))?;
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if output.status.code() == Some(NERDCTL_ERROR_EXIT) {
+            return Err(ZeroError::Driver(format!("containerd run error: {}", stderr.trim())));
+        }
+        Ok(TaskOutput {
+            exit_code: output.status.code().map(i64::from),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr,
+        })
+    }
+
+    async fn create_container(&self, id: &str, spec: &ContainerSpec) -> ZeroResult<WorkloadStatus> {
+        let mut args = Self::limits(spec.cpu, spec.memory_mb);
+        args.extend(Self::environment(spec.environment.iter()));
+        if spec.privileged {
+            args.push("--privileged".to_string());
+        }
+        // nerdctl takes a single entrypoint program; its other words lead the command
+        let (entrypoint, entrypoint_args) = match spec.entrypoint.split_first() {
+            Some((program, rest)) => (Some(program), rest),
+            None => (None, &[][..]),
+        };
+        if let Some(program) = entrypoint {
+            args.extend(["--entrypoint".to_string(), program.clone()]);
+        }
+        args.push(spec.image.clone());
+        args.extend(entrypoint_args.iter().cloned());
+        args.extend(spec.command.iter().cloned());
+
+        match self.start(id, args) {
+            Ok(status) => Ok(status),
+            Err(e) => {
+                let _ = self.run_nerdctl(&["rm", "-f", id]);
+                Err(e)
+            }
+        }
+    }
+
+    async fn pull_image(&self, reference: &str) -> ZeroResult<ImageInfo> {
+        let reference = image_reference(reference);
+        self.run_nerdctl(&["pull", "--quiet", &reference])?;
+        self.list_images().await?.into_iter().find(|image| image.reference == reference)
+            .ok_or_else(|| ZeroError::Driver(format!("containerd did not list pulled image {}", reference)))
+    }
+
+    async fn list_images(&self) -> ZeroResult<Vec<ImageInfo>> {
+        let output = self.run_nerdctl(&["images", "--format", "{{json .}}"])?;
+        Ok(output.lines().filter_map(parse_image_line).collect())
+    }
+
+    async fn remove_image(&self, reference: &str) -> ZeroResult<()> {
+        self.run_nerdctl(&["rmi", reference])?;
+        Ok(())
+    }
+}
+
+/// Workload of a `nerdctl inspect --mode dockercompat` result
+pub(crate) fn parse_inspect(id: &str, output: &str) -> ZeroResult<WorkloadStatus> {
+    let inspect: Value = serde_json::from_str(output)
+        .map_err(|e| ZeroError::Driver(format!("Invalid nerdctl inspect output: {}", e)))?;
+    let container = inspect.get(0).unwrap_or(&inspect);
+    let network = &container["NetworkSettings"];
+    let ip_address = network["IPAddress"].as_str().filter(|ip| !ip.is_empty())
+        .or_else(|| network["Networks"].as_object()?.values().find_map(|n| n["IPAddress"].as_str().filter(|ip| !ip.is_empty())))
+        .map(str::to_string);
+    Ok(WorkloadStatus {
+        id: id.to_string(),
+        // Like Docker, nerdctl reports containers that exited with an error as exited
+        state: match container["State"]["Status"].as_str().unwrap_or("Unknown") {
+            "exited" if container["State"]["ExitCode"].as_i64().unwrap_or_default() != 0 => "failed".to_string(),
+            state => state.to_string(),
+        },
+        ip_address,
+        ports: Vec::new(),
+        restart_count: 0,
+    })
+}
+
+/// Workload of one `nerdctl ps --format '{{json .}}'` line
+pub(crate) fn parse_ps_line(line: &str) -> Option<WorkloadStatus> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+    let id = entry["Names"].as_str().filter(|n| !n.is_empty()).or(entry["ID"].as_str())?;
+    let status = entry["Status"].as_str().unwrap_or_default();
+    // Same states Docker reports
+    let state = match status.split_whitespace().next().unwrap_or_default() {
+        "Up" if status.contains("(Paused)") => "paused",
+        "Up" => "running",
+        "Created" => "created",
+        "Exited" => "exited",
+        "Restarting" => "restarting",
+        _ => "Unknown",
+    };
+    Some(WorkloadStatus { id: id.to_string(), state: state.to_string(), ip_address: None, ports: Vec::new(), restart_count: 0 })
+}
+
+/// Image of one `nerdctl images --format '{{json .}}'` line; untagged images are named by ID
+pub(crate) fn parse_image_line(line: &str) -> Option<ImageInfo> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+    let id = entry["ID"].as_str()?.to_string();
+    let reference = match (entry["Repository"].as_str(), entry["Tag"].as_str()) {
+        (Some(repository), Some(tag)) if repository != "<none>" && tag != "<none>" => format!("{}:{}", repository, tag),
+        _ => id.clone(),
+    };
+    Some(ImageInfo { reference, id, size_bytes: entry["Size"].as_str().and_then(parse_size).unwrap_or_default() })
+}
+
+/// `CPUPerc` of one `nerdctl stats --format '{{json .}}'` line
+pub(crate) fn parse_cpu_percent(line: &str) -> Option<f32> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+    entry["CPUPerc"].as_str()?.trim_end_matches('%').parse().ok()
+}
+
+/// Usage in one `nerdctl stats --format '{{json .}}'` line, whose `MemUsage` and `NetIO` are
+/// `used / limit` and `received / sent` sizes such as `12.5MiB / 1.944GiB`
+pub(crate) fn parse_usage(line: &str) -> Option<WorkloadUsage> {
+    let entry: Value = serde_json::from_str(line).ok()?;
+    let pair = |field: &str| -> Option<(u64, u64)> {
+        let (first, second) = entry[field].as_str()?.split_once('/')?;
+        Some((parse_size(first)?, parse_size(second)?))
+    };
+    let (memory_used, memory_limit) = pair("MemUsage")?;
+    let (network_rx_bytes, network_tx_bytes) = pair("NetIO").unwrap_or_default();
+    Some(WorkloadUsage {
+        cpu_percent: parse_cpu_percent(line)?,
+        memory_used_mb: memory_used / (1024 * 1024),
+        memory_limit_mb: memory_limit / (1024 * 1024),
+        network_rx_bytes,
+        network_tx_bytes,
+    })
+}
+
+/// Bytes in a size such as `648B`, `1.2kB` or `1.944GiB`
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier: f64 = match unit {
+        "" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number.trim().parse::<f64>().ok()? * multiplier) as u64)
+}
@@ -1,7 +1,13 @@
-use zero_control_spi::{ComputeDriver, ZeroResult, ZeroError, WorkloadStatus};
+use zero_control_spi::{image_reference, ComputeDriver, ContainerSpec, ImageInfo, PortMapping, PortProtocol, ZeroResult, ZeroError, WorkloadStatus, WorkloadUsage, TaskOutput, TaskSpec, VolumeMount};
 use async_trait::async_trait;
 use bollard::Docker;
-use bollard::container::{CreateContainerOptions, Config, StartContainerOptions};
+use bollard::container::{
+    CreateContainerOptions, Config, LogOutput, LogsOptions, RemoveContainerOptions, StartContainerOptions, Stats, StatsOptions, WaitContainerOptions,
+};
+use bollard::image::{CreateImageOptions, ListImagesOptions};
+use bollard::models::{ContainerStateStatusEnum, HostConfig, PortBinding, PortMap, PortTypeEnum};
+use std::collections::HashMap;
+use futures::StreamExt;
 
 pub struct DockerDriver {
     client: Docker,
@@ -13,18 +19,76 @@ impl DockerDriver {
             .map_err(|e| ZeroError::Driver(format!("Failed to connect to Docker: {}", e)))?;
         Ok(Self { client })
     }
+
+    /// Connect to the Docker daemon of another machine, such as `tcp://192.168.1.20:2375`
+    pub fn connect(endpoint: &str) -> ZeroResult<Self> {
+        let client = Docker::connect_with_http(endpoint, 120, bollard::API_DEFAULT_VERSION)
+            .map_err(|e| ZeroError::Driver(format!("Failed to connect to Docker at {}: {}", endpoint, e)))?;
+        Ok(Self { client })
+    }
+
+    /// Connect to a Docker-compatible API on a local Unix socket or named pipe
+    pub fn connect_socket(path: &str) -> ZeroResult<Self> {
+        let client = Docker::connect_with_socket(path, 120, bollard::API_DEFAULT_VERSION)
+            .map_err(|e| ZeroError::Driver(format!("Failed to connect to {}: {}", path, e)))?;
+        Ok(Self { client })
+    }
+
+    /// Whether a Docker daemon is configured: `DOCKER_HOST` is set or the default socket exists
+    pub fn is_available() -> bool {
+        if std::env::var_os("DOCKER_HOST").is_some() {
+            return true;
+        }
+        #[cfg(windows)]
+        let socket = r"\\.\pipe\docker_engine";
+        #[cfg(not(windows))]
+        let socket = "/var/run/docker.sock";
+        std::path::Path::new(socket).exists()
+    }
 }
 
 #[async_trait]
 impl ComputeDriver for DockerDriver {
-    async fn create_workload(&self, id: &str, image: &str, _cpu: f32, _mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+    async fn create_workload(&self, id: &str, image: &str, cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_ports(id, image, cpu, mem_mb, &[], &[]).await
+    }
+
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_ports(id, image, cpu, mem_mb, mounts, &[]).await
+    }
+
+    /// Bind-mounts each volume's directory into the container and publishes the ports as
+    /// `docker run --publish` does, letting Docker pick host ports given as 0
+    async fn create_workload_with_ports(&self, id: &str, image: &str, _cpu: f32, _mem_mb: i32, mounts: &[VolumeMount], ports: &[PortMapping]) -> ZeroResult<WorkloadStatus> {
         let options = Some(CreateContainerOptions {
             name: id,
             ..Default::default()
         });
-        
+
+        let binds = mounts.iter().map(|mount| {
+            if !mount.target.starts_with('/') {
+                return Err(ZeroError::Validation(format!("Container mount point must be an absolute path: {}", mount.target)));
+            }
+            Ok(format!("{}:{}{}", mount.source, mount.target, if mount.read_only { ":ro" } else { "" }))
+        }).collect::<ZeroResult<Vec<_>>>()?;
+
+        // An empty host port has Docker pick a free one
+        let mut bindings: HashMap<String, Vec<PortBinding>> = HashMap::new();
+        for port in ports {
+            let host_port = if port.host_port == 0 { String::new() } else { port.host_port.to_string() };
+            bindings.entry(port_key(port)).or_default().push(PortBinding { host_ip: None, host_port: Some(host_port) });
+        }
+        let exposed_ports: HashMap<&str, HashMap<(), ()>> = bindings.keys().map(|key| (key.as_str(), HashMap::new())).collect();
+        let port_bindings: PortMap = bindings.iter().map(|(key, bindings)| (key.clone(), Some(bindings.clone()))).collect();
+
         let config = Config {
             image: Some(image),
+            exposed_ports: (!exposed_ports.is_empty()).then_some(exposed_ports),
+            host_config: (!binds.is_empty() || !port_bindings.is_empty()).then(|| HostConfig {
+                binds: (!binds.is_empty()).then_some(binds),
+                port_bindings: (!port_bindings.is_empty()).then_some(port_bindings),
+                ..Default::default()
+            }),
             ..Default::default()
         };
 
@@ -34,11 +98,17 @@ impl ComputeDriver for DockerDriver {
         self.client.start_container(id, None::<StartContainerOptions<String>>).await
             .map_err(|e| ZeroError::Driver(format!("Docker start error: {}", e)))?;
 
-        Ok(WorkloadStatus {
-            id: id.to_string(),
-            state: "Running".to_string(),
-            ip_address: None, // Can be fetched via inspect
-        })
+        if ports.is_empty() {
+            return Ok(WorkloadStatus {
+                id: id.to_string(),
+                state: "Running".to_string(),
+                ip_address: None, // Can be fetched via inspect
+                ports: Vec::new(),
+                restart_count: 0,
+            });
+        }
+        // Host ports picked by Docker are only known once the container runs
+        self.get_workload_status(id).await
     }
 
     async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
@@ -51,12 +121,19 @@ impl ComputeDriver for DockerDriver {
         let inspect = self.client.inspect_container(id, None).await
             .map_err(|e| ZeroError::Driver(format!("Docker inspect error: {}", e)))?;
 
-        let state = inspect.state.and_then(|s| s.status).map(|s| s.to_string()).unwrap_or("Unknown".into());
+        // Docker reports containers that exited with an error as exited too
+        let state = match inspect.state {
+            Some(state) if state.status == Some(ContainerStateStatusEnum::EXITED) && state.exit_code.unwrap_or_default() != 0 => "failed".to_string(),
+            state => state.and_then(|s| s.status).map(|s| s.to_string()).unwrap_or("Unknown".into()),
+        };
+        let network_settings = inspect.network_settings.unwrap_or_default();
         
         Ok(WorkloadStatus {
             id: id.to_string(),
             state,
-            ip_address: inspect.network_settings.and_then(|n| n.ip_address),
+            ip_address: network_settings.ip_address,
+            ports: network_settings.ports.as_ref().map(bound_ports).unwrap_or_default(),
+            restart_count: 0,
         })
     }
 
@@ -68,6 +145,14 @@ impl ComputeDriver for DockerDriver {
             id: c.names.unwrap_or_default().first().cloned().unwrap_or_else(|| c.id.unwrap_or_default()),
             state: c.state.unwrap_or_else(|| "Unknown".into()),
             ip_address: None,
+            ports: c.ports.unwrap_or_default().into_iter()
+                .filter_map(|port| Some(PortMapping {
+                    host_port: port.public_port?,
+                    container_port: port.private_port,
+                    protocol: if port.typ == Some(PortTypeEnum::UDP) { PortProtocol::Udp } else { PortProtocol::Tcp },
+                }))
+                .collect(),
+            restart_count: 0,
         }).collect())
     }
 
@@ -82,4 +167,181 @@ impl ComputeDriver for DockerDriver {
             storage_total_gb: 0,
         })
     }
+
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        Ok(cpu_percent(&self.stats(id).await?))
+    }
+
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        let stats = self.stats(id).await?;
+        let networks = stats.networks.iter().flat_map(|networks| networks.values());
+        Ok(WorkloadUsage {
+            cpu_percent: cpu_percent(&stats),
+            memory_used_mb: stats.memory_stats.usage.unwrap_or_default() / (1024 * 1024),
+            memory_limit_mb: stats.memory_stats.limit.unwrap_or_default() / (1024 * 1024),
+            network_rx_bytes: networks.clone().map(|network| network.rx_bytes).sum(),
+            network_tx_bytes: networks.map(|network| network.tx_bytes).sum(),
+        })
+    }
+
+    async fn run_task(&self, id: &str, spec: &TaskSpec) -> ZeroResult<TaskOutput> {
+        let env: Vec<String> = spec.environment.iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .chain(std::iter::once(format!("ZERO_EVENT={}", spec.input)))
+            .collect();
+        let config = Config {
+            image: Some(spec.image.as_str()),
+            env: Some(env.iter().map(String::as_str).collect()),
+            host_config: spec.memory_mb.map(|mb| HostConfig {
+                memory: Some(i64::from(mb) * 1024 * 1024),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.client.create_container(Some(CreateContainerOptions { name: id, ..Default::default() }), config).await
+            .map_err(|e| ZeroError::Driver(format!("Docker create error: {}", e)))?;
+
+        let result = match spec.timeout_secs {
+            Some(secs) => tokio::time::timeout(std::time::Duration::from_secs(secs), self.collect_task_output(id)).await
+                .unwrap_or_else(|_| Err(ZeroError::Driver(format!("Task {} timed out after {} seconds", id, secs)))),
+            None => self.collect_task_output(id).await,
+        };
+
+        // Always clean up the task container, even when the run failed
+        let _ = self.client.remove_container(id, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+        result
+    }
+
+    async fn create_container(&self, id: &str, spec: &ContainerSpec) -> ZeroResult<WorkloadStatus> {
+        let env: Vec<String> = spec.environment.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        let config = Config {
+            image: Some(spec.image.as_str()),
+            entrypoint: (!spec.entrypoint.is_empty()).then(|| spec.entrypoint.iter().map(String::as_str).collect()),
+            cmd: (!spec.command.is_empty()).then(|| spec.command.iter().map(String::as_str).collect()),
+            env: Some(env.iter().map(String::as_str).collect()),
+            host_config: Some(HostConfig {
+                privileged: Some(spec.privileged),
+                memory: (spec.memory_mb > 0).then(|| i64::from(spec.memory_mb) * 1024 * 1024),
+                nano_cpus: (spec.cpu > 0.0).then(|| (f64::from(spec.cpu) * 1e9) as i64),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        self.client.create_container(Some(CreateContainerOptions { name: id, ..Default::default() }), config).await
+            .map_err(|e| ZeroError::Driver(format!("Docker create error: {}", e)))?;
+        if let Err(e) = self.client.start_container(id, None::<StartContainerOptions<String>>).await {
+            let _ = self.client.remove_container(id, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+            return Err(ZeroError::Driver(format!("Docker start error: {}", e)));
+        }
+        // The address is only assigned once the container runs
+        self.get_workload_status(id).await
+    }
+
+    async fn pull_image(&self, reference: &str) -> ZeroResult<ImageInfo> {
+        let reference = image_reference(reference);
+        let mut pull = self.client.create_image(Some(CreateImageOptions { from_image: reference.clone(), ..Default::default() }), None, None);
+        while let Some(progress) = pull.next().await {
+            progress.map_err(|e| ZeroError::Driver(format!("Docker pull error for {}: {}", reference, e)))?;
+        }
+        let image = self.client.inspect_image(&reference).await
+            .map_err(|e| ZeroError::Driver(format!("Docker inspect error for {}: {}", reference, e)))?;
+        Ok(ImageInfo { id: image.id.unwrap_or_default(), size_bytes: image.size.unwrap_or_default().max(0) as u64, reference })
+    }
+
+    async fn list_images(&self) -> ZeroResult<Vec<ImageInfo>> {
+        let images = self.client.list_images(Some(ListImagesOptions::<String>::default())).await
+            .map_err(|e| ZeroError::Driver(format!("Docker list images error: {}", e)))?;
+        Ok(images.into_iter().flat_map(|image| {
+            let size_bytes = image.size.max(0) as u64;
+            let mut references: Vec<String> = image.repo_tags.into_iter().filter(|tag| tag != "<none>:<none>").collect();
+            if references.is_empty() {
+                references.push(image.id.clone());
+            }
+            references.into_iter().map(move |reference| ImageInfo { reference, id: image.id.clone(), size_bytes })
+        }).collect())
+    }
+
+    async fn remove_image(&self, reference: &str) -> ZeroResult<()> {
+        self.client.remove_image(reference, None, None).await
+            .map_err(|e| ZeroError::Driver(format!("Docker remove image error for {}: {}", reference, e)))?;
+        Ok(())
+    }
+}
+
+/// CPU used between the two samples of a stats reading, in percent of one CPU
+fn cpu_percent(stats: &Stats) -> f32 {
+    let cpu_delta = stats.cpu_stats.cpu_usage.total_usage.saturating_sub(stats.precpu_stats.cpu_usage.total_usage);
+    let system_delta = stats.cpu_stats.system_cpu_usage.unwrap_or_default()
+        .saturating_sub(stats.precpu_stats.system_cpu_usage.unwrap_or_default());
+    if system_delta == 0 {
+        return 0.0;
+    }
+    let cpus = stats.cpu_stats.online_cpus.unwrap_or(1);
+    (cpu_delta as f64 / system_delta as f64 * cpus as f64 * 100.0) as f32
+}
+
+/// Docker's name for a container port, such as `80/tcp`
+fn port_key(port: &PortMapping) -> String {
+    format!("{}/{}", port.container_port, port.protocol.name())
+}
+
+/// Host ports bound to the container ports of an inspected container
+fn bound_ports(ports: &PortMap) -> Vec<PortMapping> {
+    let mut bound: Vec<PortMapping> = ports.iter()
+        .filter_map(|(key, bindings)| {
+            let (container_port, protocol) = key.split_once('/')?;
+            let protocol = match protocol {
+                "tcp" => PortProtocol::Tcp,
+                "udp" => PortProtocol::Udp,
+                _ => return None,
+            };
+            let container_port = container_port.parse().ok()?;
+            Some(bindings.iter().flatten().filter_map(move |binding| Some(PortMapping {
+                host_port: binding.host_port.as_deref()?.parse().ok()?,
+                container_port,
+                protocol,
+            })))
+        })
+        .flatten()
+        .collect();
+    // Docker binds IPv4 and IPv6 separately, with the same host port
+    bound.sort_by_key(|port| (port.container_port, port.host_port, port.protocol.name()));
+    bound.dedup();
+    bound
+}
+
+impl DockerDriver {
+    async fn stats(&self, id: &str) -> ZeroResult<Stats> {
+        // Without one-shot, Docker takes a second sample so `precpu_stats` holds the first
+        self.client.stats(id, Some(StatsOptions { stream: false, one_shot: false })).next().await
+            .ok_or_else(|| ZeroError::Driver(format!("Docker returned no stats for {}", id)))?
+            .map_err(|e| ZeroError::Driver(format!("Docker stats error: {}", e)))
+    }
+
+    async fn collect_task_output(&self, id: &str) -> ZeroResult<TaskOutput> {
+        self.client.start_container(id, None::<StartContainerOptions<String>>).await
+            .map_err(|e| ZeroError::Driver(format!("Docker start error: {}", e)))?;
+
+        // A non-zero exit is reported by bollard as a wait error carrying the code
+        let exit_code = match self.client.wait_container(id, None::<WaitContainerOptions<String>>).next().await {
+            Some(Ok(response)) => Some(response.status_code),
+            Some(Err(bollard::errors::Error::DockerContainerWaitError { code, .. })) => Some(code),
+            Some(Err(e)) => return Err(ZeroError::Driver(format!("Docker wait error: {}", e))),
+            None => None,
+        };
+
+        let mut output = TaskOutput { exit_code, stdout: String::new(), stderr: String::new() };
+        let options = LogsOptions::<String> { stdout: true, stderr: true, ..Default::default() };
+        let mut logs = self.client.logs(id, Some(options));
+        while let Some(chunk) = logs.next().await {
+            match chunk.map_err(|e| ZeroError::Driver(format!("Docker logs error: {}", e)))? {
+                LogOutput::StdOut { message } => output.stdout.push_str(&String::from_utf8_lossy(&message)),
+                LogOutput::StdErr { message } => output.stderr.push_str(&String::from_utf8_lossy(&message)),
+                _ => {}
+            }
+        }
+        Ok(output)
+    }
 }
@@ -52,6 +52,8 @@ impl ComputeDriver for HyperVDriver {
             id: id.to_string(),
             state: "Running".to_string(),
             ip_address: None,
+            ports: Vec::new(),
+            restart_count: 0,
         })
     }
 
@@ -80,6 +82,8 @@ impl ComputeDriver for HyperVDriver {
             id: id.to_string(),
             state: normalized_state.to_string(),
             ip_address: ip,
+            ports: Vec::new(),
+            restart_count: 0,
         })
     }
 
@@ -112,6 +116,8 @@ impl ComputeDriver for HyperVDriver {
                 id,
                 state,
                 ip_address: None, // IP requires extra calls per VM
+                ports: Vec::new(),
+                restart_count: 0,
             });
         }
 
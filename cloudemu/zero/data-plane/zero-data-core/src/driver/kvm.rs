@@ -1,41 +1,256 @@
-use zero_control_spi::{ComputeDriver, ZeroResult, ZeroError, WorkloadStatus};
+use zero_control_spi::{ComputeDriver, ZeroResult, ZeroError, WorkloadStatus, WorkloadUsage, VolumeMount};
 use async_trait::async_trait;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Where libvirt keeps disk images unless told otherwise
+const DEFAULT_IMAGE_DIR: &str = "/var/lib/libvirt/images";
+
+/// Libvirt network VMs are attached to; its DHCP leases report their addresses
+const DEFAULT_NETWORK: &str = "default";
+
+/// Domains created by this driver are named `zero-<workload id>`, so other VMs on the
+/// host are never listed or deleted
+const DOMAIN_PREFIX: &str = "zero-";
+
+/// Size of a VM's copy-on-write boot disk
+const BOOT_DISK_SIZE: &str = "10G";
+
 /// KVM Driver for Linux-native virtualization.
 /// Uses libvirt/virsh internally to manage Virtual Machines.
-pub struct KvmDriver;
+///
+/// A workload's image is a cloud image (qcow2 or raw): a path, or the name of a file in the
+/// image directory with or without its `.qcow2`/`.img` extension. Each VM boots from a
+/// copy-on-write overlay of the image and gets a cloud-init NoCloud seed disk that sets its
+/// hostname to the workload ID.
+pub struct KvmDriver {
+    image_dir: PathBuf,
+    network: String,
+}
+
+impl Default for KvmDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl KvmDriver {
     pub fn new() -> Self {
-        Self
+        Self::with_image_dir(DEFAULT_IMAGE_DIR)
+    }
+
+    /// Keep boot disks and cloud-init seeds in `image_dir`, which libvirt must be able to read
+    pub fn with_image_dir(image_dir: impl Into<PathBuf>) -> Self {
+        Self { image_dir: image_dir.into(), network: DEFAULT_NETWORK.to_string() }
+    }
+
+    /// Whether this host can run VMs: `/dev/kvm` is usable and virsh and virt-install are installed
+    pub fn is_available() -> bool {
+        let kvm = std::fs::OpenOptions::new().read(true).write(true).open("/dev/kvm").is_ok();
+        let installed = |tool: &str| Command::new(tool).arg("--version").output().map(|o| o.status.success()).unwrap_or(false);
+        kvm && installed("virsh") && installed("virt-install") && installed("qemu-img")
     }
 
     fn run_virsh(&self, args: Vec<&str>) -> ZeroResult<String> {
-        let output = Command::new("virsh")
-            .args(args)
-            .output()
-            .map_err(|e| ZeroError::Driver(format!("Failed to execute virsh: {}", e)))?;
+        run("virsh", &args)
+    }
 
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            return Err(ZeroError::Driver(format!("KVM command failed: {}", err)));
+    fn domain(id: &str) -> String {
+        format!("{}{}", DOMAIN_PREFIX, id)
+    }
+
+    /// Path of the base image a workload boots from
+    fn resolve_image(&self, image: &str) -> ZeroResult<PathBuf> {
+        let candidates = [
+            PathBuf::from(image),
+            self.image_dir.join(image),
+            self.image_dir.join(format!("{}.qcow2", image)),
+            self.image_dir.join(format!("{}.img", image)),
+        ];
+        candidates.into_iter().find(|path| path.is_file())
+            .ok_or_else(|| ZeroError::Validation(format!("VM image not found: {} (looked in {})", image, self.image_dir.display())))
+    }
+
+    fn boot_disk(&self, id: &str) -> PathBuf {
+        self.image_dir.join(format!("{}.qcow2", Self::domain(id)))
+    }
+
+    fn seed_disk(&self, id: &str) -> PathBuf {
+        self.image_dir.join(format!("{}-seed.iso", Self::domain(id)))
+    }
+
+    /// Write the NoCloud seed disk cloud-init reads on first boot
+    fn create_seed(&self, id: &str) -> ZeroResult<PathBuf> {
+        let dir = self.image_dir.join(format!("{}-cidata", Self::domain(id)));
+        std::fs::create_dir_all(&dir).map_err(|e| ZeroError::Driver(format!("Failed to create {}: {}", dir.display(), e)))?;
+        let (user_data, meta_data) = (dir.join("user-data"), dir.join("meta-data"));
+        let write = |path: &Path, contents: String| std::fs::write(path, contents)
+            .map_err(|e| ZeroError::Driver(format!("Failed to write {}: {}", path.display(), e)));
+        write(&meta_data, format!("instance-id: {}\nlocal-hostname: {}\n", Self::domain(id), id))?;
+        write(&user_data, format!("#cloud-config\nhostname: {}\npreserve_hostname: false\n", id))?;
+
+        let seed = self.seed_disk(id);
+        let (seed_arg, user_arg, meta_arg) = (seed.to_string_lossy(), user_data.to_string_lossy(), meta_data.to_string_lossy());
+        // cloud-localds ships with cloud-image-utils; genisoimage builds the same volume elsewhere
+        let made = run("cloud-localds", &[&seed_arg, &user_arg, &meta_arg])
+            .or_else(|_| run("genisoimage", &["-output", &seed_arg, "-volid", "cidata", "-joliet", "-rock", &user_arg, &meta_arg]));
+        let _ = std::fs::remove_dir_all(&dir);
+        made.map(|_| seed)
+    }
+
+    fn remove_files(&self, id: &str) {
+        for path in [self.boot_disk(id), self.seed_disk(id)] {
+            if path.exists() {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    log::warn!("Failed to remove {}: {}", path.display(), e);
+                }
+            }
         }
+    }
+
+    fn status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        let domain = Self::domain(id);
+        let state = self.run_virsh(vec!["domstate", &domain])
+            .map_err(|_| ZeroError::NotFound(format!("Workload not found: {}", id)))?;
+        let state = normalize_state(&state);
+        let ip_address = match state {
+            // The DHCP lease appears once the guest has booted; the guest agent knows static addresses
+            "Running" => ["lease", "agent"].into_iter()
+                .find_map(|source| self.run_virsh(vec!["domifaddr", &domain, "--source", source]).ok()
+                    .and_then(|out| parse_domifaddr(&out))),
+            _ => None,
+        };
+        Ok(WorkloadStatus { id: id.to_string(), state: state.to_string(), ip_address, ports: Vec::new(), restart_count: 0 })
+    }
+}
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+fn run(program: &str, args: &[&str]) -> ZeroResult<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| ZeroError::Driver(format!("Failed to execute {}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(ZeroError::Driver(format!("KVM command {} failed: {}", program, err.trim())));
     }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Workload state of a `virsh domstate` state
+pub(crate) fn normalize_state(state: &str) -> &'static str {
+    match state.trim() {
+        "running" | "idle" | "in shutdown" => "Running",
+        "shut off" | "paused" | "pmsuspended" => "Stopped",
+        "crashed" => "Failed",
+        _ => "Unknown",
+    }
+}
+
+/// First IPv4 address in `virsh domifaddr` output
+pub(crate) fn parse_domifaddr(output: &str) -> Option<String> {
+    output.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.len() >= 4 && fields[2] == "ipv4")
+        .map(|fields| fields[3].split('/').next().unwrap_or(fields[3]).to_string())
+}
+
+/// Workload IDs of the domains in `virsh list --all --name` output that this driver created
+pub(crate) fn parse_domain_names(output: &str) -> Vec<String> {
+    output.lines()
+        .filter_map(|name| name.trim().strip_prefix(DOMAIN_PREFIX))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Usage of a domain in `virsh domstats --cpu-total --balloon --interface` output, CPU left
+/// at 0 since it takes two readings
+pub(crate) fn parse_domstats(output: &str) -> (u64, WorkloadUsage) {
+    let stats: std::collections::HashMap<&str, u64> = output.lines()
+        .filter_map(|line| line.trim().split_once('='))
+        .filter_map(|(name, value)| Some((name, value.parse().ok()?)))
+        .collect();
+    let sum = |suffix: &str| stats.iter()
+        .filter(|(name, _)| name.starts_with("net.") && name.ends_with(suffix))
+        .map(|(_, value)| value)
+        .sum();
+    // The guest's own view of its memory needs the balloon driver's stats; the host's
+    // resident size is the fallback
+    let used_kb = match (stats.get("balloon.available"), stats.get("balloon.unused")) {
+        (Some(available), Some(unused)) => available.saturating_sub(*unused),
+        _ => stats.get("balloon.rss").copied().unwrap_or_default(),
+    };
+    let usage = WorkloadUsage {
+        cpu_percent: 0.0,
+        memory_used_mb: used_kb / 1024,
+        memory_limit_mb: stats.get("balloon.current").copied().unwrap_or_default() / 1024,
+        network_rx_bytes: sum(".rx.bytes"),
+        network_tx_bytes: sum(".tx.bytes"),
+    };
+    (stats.get("cpu.time").copied().unwrap_or_default(), usage)
+}
+
+/// A `Label: value unit` field of `virsh nodeinfo` or `virsh nodememstats` output
+pub(crate) fn parse_field(output: &str, label: &str) -> Option<u64> {
+    output.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(label))
+        .and_then(|(_, value)| value.split_whitespace().next()?.parse().ok())
 }
 
 #[async_trait]
 impl ComputeDriver for KvmDriver {
-    async fn create_workload(&self, id: &str, image: &str, _cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
-        // virt-install is usually cleaner for creation
-        let mem_str = mem_mb.to_string();
+    async fn create_workload(&self, id: &str, image: &str, cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_volumes(id, image, cpu, mem_mb, &[]).await
+    }
+
+    /// Attaches each volume's block file as a virtio disk, named by the mount target (`vdb`, `vdc`, ...);
+    /// `.qcow2` files are attached as qcow2, others as raw
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        let mut disks = Vec::new();
+        for mount in mounts {
+            // vda is the boot disk
+            let target = mount.target.as_bytes();
+            if !(target.len() == 3 && target.starts_with(b"vd") && (b'b'..=b'z').contains(&target[2])) {
+                return Err(ZeroError::Validation(format!("VM disk name must be one of vdb to vdz: {}", mount.target)));
+            }
+            let file = mount.backing_file.as_ref().ok_or_else(|| {
+                ZeroError::Validation(format!("Volume {} has no block file to attach", mount.volume_id))
+            })?;
+            let format = if file.ends_with(".qcow2") { "qcow2" } else { "raw" };
+            disks.push(format!(
+                "path={},format={},bus=virtio,target.dev={}{}",
+                file, format, mount.target, if mount.read_only { ",readonly=on" } else { "" }
+            ));
+        }
+
+        let base = self.resolve_image(image)?;
+        let format = if base.extension().is_some_and(|ext| ext == "qcow2") { "qcow2" } else { "raw" };
+        let boot_disk = self.boot_disk(id);
+        run("qemu-img", &[
+            "create", "-f", "qcow2", "-F", format,
+            "-b", &base.to_string_lossy(), &boot_disk.to_string_lossy(), BOOT_DISK_SIZE,
+        ])?;
+        let seed = match self.create_seed(id) {
+            Ok(seed) => seed,
+            Err(e) => {
+                self.remove_files(id);
+                return Err(e);
+            }
+        };
+
+        let vcpus = (cpu.ceil() as u32).max(1).to_string();
         let output = Command::new("virt-install")
-            .arg("--name").arg(id)
-            .arg("--memory").arg(mem_str)
-            .arg("--vcpus").arg("1")
-            .arg("--disk").arg(format!("path=/var/lib/libvirt/images/{}.qcow2,size=10", id))
+            .arg("--name").arg(Self::domain(id))
+            .arg("--memory").arg(mem_mb.max(128).to_string())
+            .arg("--vcpus").arg(vcpus)
+            .arg("--disk").arg(format!("path={},format=qcow2,bus=virtio,target.dev=vda", boot_disk.display()))
+            .args(disks.iter().flat_map(|disk| ["--disk", disk.as_str()]))
+            .arg("--disk").arg(format!("path={},device=cdrom", seed.display()))
+            .arg("--network").arg(format!("network={},model=virtio", self.network))
+            .arg("--os-variant").arg("generic")
             .arg("--import")
             .arg("--noautoconsole")
             .arg("--graphics").arg("none")
@@ -43,88 +258,83 @@ impl ComputeDriver for KvmDriver {
             .map_err(|e| ZeroError::Driver(format!("Failed to execute virt-install: {}", e)))?;
 
         if !output.status.success() {
+            self.remove_files(id);
             let err = String::from_utf8_lossy(&output.stderr);
-            return Err(ZeroError::Driver(format!("KVM Create failed: {}", err)));
+            return Err(ZeroError::Driver(format!("KVM Create failed: {}", err.trim())));
         }
 
-        Ok(WorkloadStatus {
-            id: id.to_string(),
-            state: "Running".to_string(),
-            ip_address: None,
-        })
+        // The address is reported once the guest has taken a DHCP lease
+        self.status(id)
     }
 
     async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
+        let domain = Self::domain(id);
         // Force stop and undefine
-        self.run_virsh(vec!["destroy", id]).ok(); // ignore if already stopped
-        self.run_virsh(vec!["undefine", id, "--remove-all-storage"])?;
+        self.run_virsh(vec!["destroy", &domain]).ok(); // ignore if already stopped
+        // Only the boot disk and seed are removed: attached volumes outlive the VM
+        self.run_virsh(vec!["undefine", &domain])?;
+        self.remove_files(id);
         Ok(())
     }
 
     async fn get_workload_status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
-        let state = self.run_virsh(vec!["domstate", id])?;
-        
-        let normalized_state = match state.as_str() {
-            "running" => "Running",
-            "shut off" => "Stopped",
-            _ => "Unknown",
-        };
-
-        // Try to get IP
-        let ip_output = self.run_virsh(vec!["domifaddr", id]).ok();
-        let ip = ip_output.and_then(|out| {
-            // Very basic parsing of virsh domifaddr output
-            out.lines().nth(2).and_then(|line| {
-                line.split_whitespace().nth(3).map(|ip_cidr| {
-                    ip_cidr.split('/').next().unwrap_or(ip_cidr).to_string()
-                })
-            })
-        });
-
-        Ok(WorkloadStatus {
-            id: id.to_string(),
-            state: normalized_state.to_string(),
-            ip_address: ip,
-        })
+        self.status(id)
     }
 
     async fn list_workloads(&self) -> ZeroResult<Vec<WorkloadStatus>> {
         let output = self.run_virsh(vec!["list", "--all", "--name"])?;
         let mut workloads = Vec::new();
-
-        for name in output.lines() {
-            let name = name.trim();
-            if name.is_empty() { continue; }
-            
-            // For listing, we can just return IDs or do a status call per workload for richer data
-            // To keep it efficient, we'll start with just names/running state
-            workloads.push(WorkloadStatus {
-                id: name.to_string(),
-                state: "Unknown".into(), // Requires extra calls per domain
-                ip_address: None,
-            });
+        for id in parse_domain_names(&output) {
+            // A domain undefined since the listing is simply left out
+            if let Ok(status) = self.status(&id) {
+                workloads.push(status);
+            }
         }
-
         Ok(workloads)
     }
 
     async fn get_stats(&self) -> ZeroResult<zero_control_spi::NodeStats> {
-        // virsh nodeinfo gives basic cpu and memory stats
-        let output = self.run_virsh(vec!["nodeinfo"]).unwrap_or("".into());
-        
-        let mut total_kb = 0;
-        for line in output.lines() {
-            if line.contains("Memory size") {
-                total_kb = line.split_whitespace().nth(2).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
-            }
-        }
+        // nodeinfo and nodememstats report memory in KiB, nodecpustats the busy share
+        let info = self.run_virsh(vec!["nodeinfo"]).unwrap_or_default();
+        let mem = self.run_virsh(vec!["nodememstats"]).unwrap_or_default();
+        let cpu = self.run_virsh(vec!["nodecpustats", "--percent"]).unwrap_or_default();
+
+        let total_kb = parse_field(&info, "Memory size").unwrap_or(0);
+        let available_kb = ["free", "buffers", "cached"].iter()
+            .map(|field| parse_field(&mem, field).unwrap_or(0))
+            .sum::<u64>();
+        let cpu_usage_percent = cpu.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim() == "usage")
+            .and_then(|(_, value)| value.trim().trim_end_matches('%').parse().ok())
+            .unwrap_or(0.0);
 
         Ok(zero_control_spi::NodeStats {
-            cpu_usage_percent: 0.0,
-            memory_used_mb: 0,
+            cpu_usage_percent,
+            memory_used_mb: total_kb.saturating_sub(available_kb) / 1024,
             memory_total_mb: total_kb / 1024,
             storage_used_gb: 0,
             storage_total_gb: 0,
         })
     }
+
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        Ok(self.workload_usage(id).await?.cpu_percent)
+    }
+
+    /// Reads the domain's stats twice, a second apart, since libvirt reports CPU as the
+    /// nanoseconds used so far
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        let domain = Self::domain(id);
+        let read = || self.run_virsh(vec!["domstats", "--cpu-total", "--balloon", "--interface", &domain]);
+        let started = std::time::Instant::now();
+        let (first_cpu_ns, _) = parse_domstats(&read()?);
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let (cpu_ns, usage) = parse_domstats(&read()?);
+        let elapsed_ns = started.elapsed().as_nanos().max(1) as f64;
+        Ok(WorkloadUsage {
+            cpu_percent: (cpu_ns.saturating_sub(first_cpu_ns) as f64 / elapsed_ns * 100.0) as f32,
+            ..usage
+        })
+    }
 }
@@ -0,0 +1,205 @@
+use zero_control_spi::{ComputeDriver, NodeStats, ZeroResult, ZeroError, WorkloadStatus, VolumeMount};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::process::Command;
+
+/// Instances created by this driver are named `zero-<workload id>`, so other Lima VMs on
+/// the host are never listed or deleted
+pub(crate) const INSTANCE_PREFIX: &str = "zero-";
+
+/// Template a workload boots when its image names no template, file or URL
+const DEFAULT_TEMPLATE: &str = "template://default";
+
+/// Lima Driver for macOS virtualization.
+/// Uses the limactl CLI internally, which runs VMs on Apple's Virtualization.framework
+/// (`vz`) on macOS 13+ and on QEMU elsewhere.
+///
+/// A workload's image is a Lima template name (`ubuntu`, `debian`, `fedora`, ...), a template
+/// file or URL, or a cloud image file (`.qcow2`, `.img`, `.raw`) booted with the default
+/// template's settings. On macOS each VM gets a `vzNAT` interface, so its address is
+/// reachable from the host.
+pub struct LimaDriver;
+
+impl Default for LimaDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LimaDriver {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Whether limactl is installed
+    pub fn is_available() -> bool {
+        Command::new("limactl").arg("--version").output().map(|o| o.status.success()).unwrap_or(false)
+    }
+
+    fn instance(id: &str) -> String {
+        format!("{}{}", INSTANCE_PREFIX, id)
+    }
+
+    fn status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        let instance = Self::instance(id);
+        let output = run_limactl(&["list", "--json", &instance])
+            .map_err(|_| ZeroError::NotFound(format!("Workload not found: {}", id)))?;
+        let entry = parse_list(&output).into_iter().find(|entry| entry["name"] == instance.as_str())
+            .ok_or_else(|| ZeroError::NotFound(format!("Workload not found: {}", id)))?;
+        let state = normalize_state(entry["status"].as_str().unwrap_or_default());
+        let ip_address = match state {
+            "Running" => guest_addresses(&instance).ok().and_then(|output| parse_ip_addr(&output)),
+            _ => None,
+        };
+        Ok(WorkloadStatus { id: id.to_string(), state: state.to_string(), ip_address, ports: Vec::new(), restart_count: 0 })
+    }
+}
+
+pub(crate) fn run_limactl(args: &[&str]) -> ZeroResult<String> {
+    let output = Command::new("limactl")
+        .args(args)
+        .output()
+        .map_err(|e| ZeroError::Driver(format!("Failed to execute limactl: {}", e)))?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(ZeroError::Driver(format!("Lima command {} failed: {}", args.first().unwrap_or(&""), err.trim())));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// `ip -4 -o addr` output of a running instance's guest
+pub(crate) fn guest_addresses(instance: &str) -> ZeroResult<String> {
+    run_limactl(&["shell", "--workdir", "/", instance, "ip", "-4", "-o", "addr", "show", "scope", "global"])
+}
+
+/// Template locator and `--set` expression that boot `image` with the given mounts
+pub(crate) fn instance_config(image: &str, mounts: &[VolumeMount]) -> ZeroResult<(String, String)> {
+    let mut set = Vec::new();
+    let is_disk = [".qcow2", ".img", ".raw"].iter().any(|ext| image.ends_with(ext));
+    let template = if image.contains("://") || image.ends_with(".yaml") || image.ends_with(".yml") {
+        image.to_string()
+    } else if is_disk {
+        let path = std::fs::canonicalize(image)
+            .map_err(|_| ZeroError::Validation(format!("VM image not found: {}", image)))?;
+        set.push(format!(".images = {}", json!([{ "location": path.to_string_lossy() }])));
+        DEFAULT_TEMPLATE.to_string()
+    } else {
+        format!("template://{}", image)
+    };
+
+    // Replaces the template's mounts, which share the home directory
+    let mut shared = Vec::new();
+    for mount in mounts {
+        if !mount.target.starts_with('/') {
+            return Err(ZeroError::Validation(format!("VM mount point must be an absolute path: {}", mount.target)));
+        }
+        shared.push(json!({ "location": mount.source, "mountPoint": mount.target, "writable": !mount.read_only }));
+    }
+    set.push(format!(".mounts = {}", Value::Array(shared)));
+    Ok((template, set.join(" | ")))
+}
+
+/// Instances of `limactl list --json` output, one JSON object per line
+pub(crate) fn parse_list(output: &str) -> Vec<Value> {
+    output.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()
+}
+
+/// Workload state of a Lima instance status
+pub(crate) fn normalize_state(status: &str) -> &'static str {
+    match status {
+        "Running" => "Running",
+        "Stopped" => "Stopped",
+        "Broken" => "Failed",
+        _ => "Unknown",
+    }
+}
+
+/// Host-reachable IPv4 address in `ip -4 -o addr` output: the `vzNAT` interface `lima0`,
+/// else the first interface that is not Lima's user-mode `eth0`
+pub(crate) fn parse_ip_addr(output: &str) -> Option<String> {
+    let addresses: Vec<(&str, &str)> = output.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let inet = fields.iter().position(|f| *f == "inet")?;
+            Some((*fields.get(1)?, fields.get(inet + 1)?.split('/').next()?))
+        })
+        .collect();
+    addresses.iter().find(|(interface, _)| *interface == "lima0")
+        .or_else(|| addresses.iter().find(|(interface, _)| *interface != "eth0" && *interface != "lo"))
+        .map(|(_, ip)| ip.to_string())
+}
+
+#[async_trait]
+impl ComputeDriver for LimaDriver {
+    async fn create_workload(&self, id: &str, image: &str, cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        self.create_workload_with_volumes(id, image, cpu, mem_mb, &[]).await
+    }
+
+    /// Shares each volume's directory with the VM at the mount target
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        let (template, set) = instance_config(image, mounts)?;
+        let name = format!("--name={}", Self::instance(id));
+        let cpus = format!("--cpus={}", (cpu.ceil() as u32).max(1));
+        // Lima sizes memory in GiB
+        let memory = format!("--memory={:.2}", mem_mb.max(512) as f32 / 1024.0);
+        let mut args = vec!["start", "--tty=false", &name, &cpus, &memory, "--set", &set];
+        if cfg!(target_os = "macos") {
+            args.extend(["--vm-type=vz", "--network=vzNAT"]);
+        }
+        args.push(&template);
+
+        // Returns once the guest has booted
+        if let Err(e) = run_limactl(&args) {
+            let _ = run_limactl(&["delete", "--force", &Self::instance(id)]);
+            return Err(e);
+        }
+        self.status(id)
+    }
+
+    async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
+        // Stops the VM first; shared volume directories outlive it
+        run_limactl(&["delete", "--force", &Self::instance(id)])?;
+        Ok(())
+    }
+
+    async fn get_workload_status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        self.status(id)
+    }
+
+    async fn list_workloads(&self) -> ZeroResult<Vec<WorkloadStatus>> {
+        let output = run_limactl(&["list", "--json"])?;
+        let mut workloads = Vec::new();
+        for entry in parse_list(&output) {
+            let Some(id) = entry["name"].as_str().and_then(|name| name.strip_prefix(INSTANCE_PREFIX)) else {
+                continue;
+            };
+            // An instance deleted since the listing is simply left out
+            if let Ok(status) = self.status(id) {
+                workloads.push(status);
+            }
+        }
+        Ok(workloads)
+    }
+
+    async fn get_stats(&self) -> ZeroResult<NodeStats> {
+        // Memory used is what running workload VMs were given; Lima reports it in bytes
+        let instances = run_limactl(&["list", "--json"]).map(|output| parse_list(&output)).unwrap_or_default();
+        let used_bytes: u64 = instances.iter()
+            .filter(|entry| entry["name"].as_str().is_some_and(|name| name.starts_with(INSTANCE_PREFIX)) && entry["status"] == "Running")
+            .filter_map(|entry| entry["memory"].as_u64())
+            .sum();
+        let total_bytes: u64 = Command::new("sysctl").args(["-n", "hw.memsize"]).output().ok()
+            .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse().ok())
+            .unwrap_or(0);
+
+        Ok(NodeStats {
+            cpu_usage_percent: 0.0,
+            memory_used_mb: used_bytes / 1024 / 1024,
+            memory_total_mb: total_bytes / 1024 / 1024,
+            storage_used_gb: 0,
+            storage_total_gb: 0,
+        })
+    }
+}
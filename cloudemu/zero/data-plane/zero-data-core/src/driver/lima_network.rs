@@ -0,0 +1,183 @@
+use super::lima::{guest_addresses, run_limactl, INSTANCE_PREFIX};
+use zero_control_spi::{NetworkDriver, ZeroResult, ZeroError, NetworkStatus};
+use async_trait::async_trait;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+
+/// Lima Network Driver.
+/// Manages `user-v2` networks in Lima's `_config/networks.yaml`, which connect VMs to each
+/// other without root privileges or socket_vmnet.
+pub struct LimaNetworkDriver {
+    config: PathBuf,
+}
+
+impl Default for LimaNetworkDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LimaNetworkDriver {
+    pub fn new() -> Self {
+        // LIMA_HOME moves Lima's whole state directory
+        let home = std::env::var_os("LIMA_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".lima")))
+            .unwrap_or_else(|| PathBuf::from(".lima"));
+        Self::with_config(home.join("_config").join("networks.yaml"))
+    }
+
+    /// Keep networks in the Lima network config at `config`
+    pub fn with_config(config: impl Into<PathBuf>) -> Self {
+        Self { config: config.into() }
+    }
+
+    fn read(&self) -> ZeroResult<String> {
+        match std::fs::read_to_string(&self.config) {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(ZeroError::Driver(format!("Failed to read {}: {}", self.config.display(), e))),
+        }
+    }
+
+    fn write(&self, contents: &str) -> ZeroResult<()> {
+        if let Some(dir) = self.config.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| ZeroError::Driver(e.to_string()))?;
+        }
+        std::fs::write(&self.config, contents)
+            .map_err(|e| ZeroError::Driver(format!("Failed to write {}: {}", self.config.display(), e)))
+    }
+}
+
+/// Gateway and netmask of an IPv4 CIDR block such as `10.0.1.0/24`
+pub(crate) fn gateway_and_netmask(cidr: &str) -> ZeroResult<(Ipv4Addr, Ipv4Addr)> {
+    let invalid = || ZeroError::Validation(format!("Invalid IPv4 CIDR block: {}", cidr));
+    let (address, prefix) = cidr.split_once('/').ok_or_else(invalid)?;
+    let address: Ipv4Addr = address.parse().map_err(|_| invalid())?;
+    let prefix: u32 = prefix.parse().map_err(|_| invalid())?;
+    if !(8..=30).contains(&prefix) {
+        return Err(invalid());
+    }
+    let mask = u32::MAX << (32 - prefix);
+    Ok((Ipv4Addr::from((u32::from(address) & mask) + 1), Ipv4Addr::from(mask)))
+}
+
+/// Networks of a `networks.yaml` and the CIDR block of each
+pub(crate) fn parse_networks(config: &str) -> Vec<(String, String)> {
+    let mut networks = Vec::new();
+    let mut in_networks = false;
+    let mut current: Option<(String, Option<Ipv4Addr>, Option<Ipv4Addr>)> = None;
+    let mut finish = |entry: Option<(String, Option<Ipv4Addr>, Option<Ipv4Addr>)>| {
+        if let Some((name, gateway, netmask)) = entry {
+            let cidr = match (gateway, netmask) {
+                (Some(gateway), Some(netmask)) => {
+                    let prefix = u32::from(netmask).count_ones();
+                    format!("{}/{}", Ipv4Addr::from(u32::from(gateway) & u32::from(netmask)), prefix)
+                }
+                _ => "Unknown".to_string(),
+            };
+            networks.push((name, cidr));
+        }
+    };
+    for line in config.lines() {
+        let indent = line.len() - line.trim_start().len();
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if indent == 0 {
+            finish(current.take());
+            in_networks = trimmed == "networks:";
+        } else if in_networks && indent == 2 {
+            finish(current.take());
+            current = trimmed.strip_suffix(':').map(|name| (name.to_string(), None, None));
+        } else if let Some((_, gateway, netmask)) = current.as_mut() {
+            match trimmed.split_once(':') {
+                Some(("gateway", value)) => *gateway = value.trim().parse().ok(),
+                Some(("netmask", value)) => *netmask = value.trim().parse().ok(),
+                _ => {}
+            }
+        }
+    }
+    finish(current.take());
+    networks
+}
+
+/// `config` with a `user-v2` network added under `networks:`
+pub(crate) fn add_network(config: &str, id: &str, gateway: Ipv4Addr, netmask: Ipv4Addr) -> String {
+    let entry = format!("  {}:\n    mode: user-v2\n    gateway: {}\n    netmask: {}\n", id, gateway, netmask);
+    let mut lines: Vec<String> = config.lines().map(str::to_string).collect();
+    match lines.iter().position(|line| line.trim_end() == "networks:") {
+        Some(at) => lines.insert(at + 1, entry.trim_end().to_string()),
+        None => lines.extend(["networks:".to_string(), entry.trim_end().to_string()]),
+    }
+    lines.join("\n") + "\n"
+}
+
+/// `config` without the network `id` and its settings
+pub(crate) fn remove_network(config: &str, id: &str) -> String {
+    let mut out = Vec::new();
+    let mut skipping = false;
+    for line in config.lines() {
+        let indent = line.len() - line.trim_start().len();
+        if !line.trim().is_empty() && indent <= 2 {
+            skipping = indent == 2 && line.trim() == format!("{}:", id);
+        }
+        if !skipping {
+            out.push(line);
+        }
+    }
+    out.join("\n") + "\n"
+}
+
+#[async_trait]
+impl NetworkDriver for LimaNetworkDriver {
+    async fn create_network(&self, id: &str, cidr: &str) -> ZeroResult<NetworkStatus> {
+        let (gateway, netmask) = gateway_and_netmask(cidr)?;
+        let config = self.read()?;
+        if parse_networks(&config).iter().any(|(name, _)| name == id) {
+            return Err(ZeroError::Validation(format!("Network already exists: {}", id)));
+        }
+        self.write(&add_network(&config, id, gateway, netmask))?;
+
+        Ok(NetworkStatus {
+            id: id.to_string(),
+            cidr: cidr.to_string(),
+            state: "Available".to_string(),
+        })
+    }
+
+    async fn delete_network(&self, id: &str) -> ZeroResult<()> {
+        let config = self.read()?;
+        if !parse_networks(&config).iter().any(|(name, _)| name == id) {
+            return Err(ZeroError::NotFound(format!("Network not found: {}", id)));
+        }
+        self.write(&remove_network(&config, id))
+    }
+
+    /// Adds an interface on the network to the VM, which restarts it
+    async fn connect_workload(&self, workload_id: &str, network_id: &str) -> ZeroResult<String> {
+        let cidr = parse_networks(&self.read()?).into_iter().find(|(name, _)| name == network_id).map(|(_, cidr)| cidr)
+            .ok_or_else(|| ZeroError::NotFound(format!("Network not found: {}", network_id)))?;
+        let instance = format!("{}{}", INSTANCE_PREFIX, workload_id);
+        let network = format!(".networks += [{{\"lima\": \"{}\"}}]", network_id);
+
+        // Lima only changes the networks of a stopped instance
+        run_limactl(&["stop", &instance])?;
+        run_limactl(&["edit", "--tty=false", &instance, "--set", &network])?;
+        run_limactl(&["start", "--tty=false", &instance])?;
+
+        // The interface's address in the network, once the guest has taken its lease
+        let (gateway, netmask) = gateway_and_netmask(&cidr)?;
+        let subnet = u32::from(gateway) & u32::from(netmask);
+        let ip = guest_addresses(&instance).unwrap_or_default().split_whitespace()
+            .filter_map(|field| field.split('/').next()?.parse::<Ipv4Addr>().ok())
+            .find(|ip| u32::from(*ip) & u32::from(netmask) == subnet && *ip != gateway);
+        Ok(ip.map(|ip| ip.to_string()).unwrap_or_else(|| "CONNECTED".to_string()))
+    }
+
+    async fn list_networks(&self) -> ZeroResult<Vec<NetworkStatus>> {
+        Ok(parse_networks(&self.read()?).into_iter()
+            .map(|(id, cidr)| NetworkStatus { id, cidr, state: "Available".into() })
+            .collect())
+    }
+}
@@ -1,105 +1,454 @@
-use zero_control_spi::{NetworkDriver, ZeroResult, ZeroError, NetworkStatus};
+use crate::ipam::{Cidr, Subnet};
+use zero_control_spi::{NetworkDriver, ZeroResult, ZeroError, NetworkStatus, PortMapping, RuleDirection, SecurityGroup};
 use async_trait::async_trait;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+use std::path::Path;
 use std::process::Command;
-use std::fs;
+
+/// Directory `ip netns` keeps named network namespaces in
+const NETNS_DIR: &str = "/run/netns";
 
 /// Linux Bridge Network Driver.
-/// Manages Linux Bridges and Libvirt Networks.
-pub struct LinuxNetworkDriver;
+/// Each network is a Linux bridge holding the network's gateway address, with NAT for
+/// traffic that leaves the host. Connecting a workload creates a veth pair: one end joins
+/// the bridge, the other moves into the workload's network namespace with an address from
+/// the network's CIDR block and a default route through the gateway.
+///
+/// A workload's namespace is the named namespace `zero-<id>` (or `<id>`), else that of the
+/// Docker, Podman or containerd container with its ID. KVM domains get a virtio NIC on the
+/// bridge instead, to be configured with the returned address. Without any of these, the
+/// driver creates `zero-<id>` for processes started with `ip netns exec`.
+///
+/// Security groups become two iptables chains per network, jumped to from `FORWARD` for
+/// traffic into and out of the bridge. Traffic between workloads of one bridge is filtered
+/// when the `br_netfilter` module is loaded, which the driver tries to do.
+///
+/// Published ports are DNAT rules in a `nat` chain per workload, jumped to from `PREROUTING`
+/// and `OUTPUT` for traffic to the host's own addresses. Forwarded traffic still passes the
+/// network's security groups. Connections to `127.0.0.1` are not forwarded; use another
+/// address of the host.
+pub struct LinuxNetworkDriver {
+    networks: Mutex<HashMap<String, Subnet>>,
+}
+
+impl Default for LinuxNetworkDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a workload's end of a network connection goes
+#[derive(Debug, Clone, PartialEq)]
+enum Endpoint {
+    Namespace(String),
+    Process(u32),
+    Domain(String),
+}
 
 impl LinuxNetworkDriver {
     pub fn new() -> Self {
-        Self
+        Self { networks: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether this process can manage bridges and iptables: it runs as root and `ip` and
+    /// `iptables` are installed
+    pub fn is_available() -> bool {
+        // The effective UID is the second field of the status file's Uid line
+        let root = std::fs::read_to_string("/proc/self/status").ok()
+            .and_then(|status| status.lines().find(|l| l.starts_with("Uid:"))?.split_whitespace().nth(2).map(|uid| uid == "0"))
+            .unwrap_or(false);
+        let installed = |tool: &str, flag: &str| Command::new(tool).arg(flag).output().map(|o| o.status.success()).unwrap_or(false);
+        root && installed("ip", "-V") && installed("iptables", "--version")
+    }
+
+    /// Namespace, container process or VM of a workload
+    fn endpoint(&self, workload_id: &str) -> ZeroResult<Endpoint> {
+        for name in [format!("zero-{}", workload_id), workload_id.to_string()] {
+            if Path::new(NETNS_DIR).join(&name).exists() {
+                return Ok(Endpoint::Namespace(name));
+            }
+        }
+        let domain = format!("zero-{}", workload_id);
+        if run("virsh", &["domstate", &domain]).is_ok() {
+            return Ok(Endpoint::Domain(domain));
+        }
+        let runtimes: [&[&str]; 3] = [&["docker"], &["podman"], &["nerdctl", "--namespace", "zero"]];
+        for runtime in runtimes {
+            let mut args = runtime[1..].to_vec();
+            args.extend(["inspect", "--format", "{{.State.Pid}}", workload_id]);
+            if let Some(pid) = run(runtime[0], &args).ok().and_then(|pid| pid.parse::<u32>().ok()).filter(|pid| *pid > 0) {
+                return Ok(Endpoint::Process(pid));
+            }
+        }
+        let namespace = format!("zero-{}", workload_id);
+        run("ip", &["netns", "add", &namespace])?;
+        Ok(Endpoint::Namespace(namespace))
+    }
+
+    /// Move the workload end of a veth pair into `endpoint` and configure it
+    fn configure(endpoint: &Endpoint, interface: &str, address: &str, gateway: &str) -> ZeroResult<()> {
+        let (program, prefix): (&str, Vec<String>) = match endpoint {
+            Endpoint::Namespace(name) => {
+                run("ip", &["link", "set", interface, "netns", name])?;
+                ("ip", vec!["-n".into(), name.clone()])
+            }
+            Endpoint::Process(pid) => {
+                run("ip", &["link", "set", interface, "netns", &pid.to_string()])?;
+                ("nsenter", vec!["--target".into(), pid.to_string(), "--net".into(), "ip".into()])
+            }
+            Endpoint::Domain(_) => return Ok(()),
+        };
+        let ip = |args: &[&str]| {
+            let mut full: Vec<&str> = prefix.iter().map(String::as_str).collect();
+            full.extend(args);
+            run(program, &full)
+        };
+        ip(&["addr", "add", address, "dev", interface])?;
+        ip(&["link", "set", interface, "up"])?;
+        ip(&["link", "set", "lo", "up"])?;
+        // Containers keep the default route of their own network
+        if ip(&["route", "show", "default"])?.is_empty() {
+            ip(&["route", "add", "default", "via", gateway])?;
+        }
+        Ok(())
+    }
+
+    /// Connect a workload with `requested` as its address, or the next free one
+    fn connect(&self, workload_id: &str, network_id: &str, requested: Option<Ipv4Addr>) -> ZeroResult<String> {
+        let (address, cidr) = {
+            let mut networks = self.networks.lock();
+            let subnet = networks.get_mut(network_id)
+                .ok_or_else(|| ZeroError::NotFound(format!("Network {}", network_id)))?;
+            let address = match requested {
+                Some(ip) => subnet.reserve(workload_id, ip)?,
+                None => subnet.allocate(workload_id)?,
+            };
+            (address, subnet.cidr())
+        };
+        let bridge = bridge_name(network_id);
+        let (host, workload) = veth_names(workload_id, network_id);
+        // Connecting twice keeps the existing connection
+        if run("ip", &["link", "show", &host]).is_ok() {
+            return Ok(address.to_string());
+        }
+
+        let connected = (|| {
+            let endpoint = self.endpoint(workload_id)?;
+            if let Endpoint::Domain(domain) = &endpoint {
+                run("virsh", &["attach-interface", "--domain", domain, "--type", "bridge", "--source", &bridge, "--model", "virtio", "--config", "--live"])?;
+                return Ok(());
+            }
+            run("ip", &["link", "add", &host, "type", "veth", "peer", "name", &workload])?;
+            run("ip", &["link", "set", &host, "master", &bridge])?;
+            run("ip", &["link", "set", &host, "up"])?;
+            Self::configure(&endpoint, &workload, &format!("{}/{}", address, cidr.prefix()), &cidr.gateway().to_string())
+        })();
+        if let Err(e) = connected {
+            let _ = run("ip", &["link", "del", &host]);
+            if let Some(subnet) = self.networks.lock().get_mut(network_id) {
+                subnet.release(workload_id);
+            }
+            return Err(e);
+        }
+        Ok(address.to_string())
+    }
+}
+
+fn run(program: &str, args: &[&str]) -> ZeroResult<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| ZeroError::Driver(format!("Failed to execute {}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let err = String::from_utf8_lossy(&output.stderr);
+        return Err(ZeroError::Driver(format!("Linux Network command {} {} failed: {}", program, args.join(" "), err.trim())));
     }
 
-    fn run_virsh(&self, args: Vec<&str>) -> ZeroResult<String> {
-        let output = Command::new("virsh")
-            .args(args)
-            .output()
-            .map_err(|e| ZeroError::Driver(format!("Failed to execute virsh: {}", e)))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// FNV-1a, stable across builds, for interface names that fit in 15 characters
+fn short_hash(text: &str) -> u32 {
+    text.bytes().fold(0x811c9dc5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+/// Bridge of a network
+pub(crate) fn bridge_name(network_id: &str) -> String {
+    format!("zbr{:08x}", short_hash(network_id))
+}
+
+/// Host and workload ends of the veth pair connecting a workload to a network
+pub(crate) fn veth_names(workload_id: &str, network_id: &str) -> (String, String) {
+    let hash = short_hash(&format!("{}/{}", workload_id, network_id));
+    (format!("zvh{:08x}", hash), format!("zvw{:08x}", hash))
+}
+
+/// iptables rules of a network, as `table chain rule...`: masquerade traffic leaving the
+/// host and forward traffic to and from the bridge
+pub(crate) fn nat_rules(network_id: &str, cidr: &Cidr, bridge: &str) -> Vec<Vec<String>> {
+    let comment = format!("zero:{}", network_id);
+    let cidr = cidr.to_string();
+    [
+        vec!["nat", "POSTROUTING", "-s", &cidr, "!", "-o", bridge, "-j", "MASQUERADE"],
+        vec!["filter", "FORWARD", "-i", bridge, "-j", "ACCEPT"],
+        vec!["filter", "FORWARD", "-o", bridge, "-j", "ACCEPT"],
+    ].into_iter()
+        .map(|rule| rule.into_iter().map(str::to_string).chain(["-m".into(), "comment".into(), "--comment".into(), comment.clone()]).collect())
+        .collect()
+}
+
+/// Chains filtering the traffic into and out of the workloads of a network
+pub(crate) fn security_chains(network_id: &str) -> (String, String) {
+    let hash = short_hash(network_id);
+    (format!("zsgi{:08x}", hash), format!("zsge{:08x}", hash))
+}
 
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            return Err(ZeroError::Driver(format!("Linux Network command failed: {}", err)));
+/// Rules of the ingress and egress chains of a network, as iptables arguments after the
+/// chain: replies pass, traffic a group allows returns to `FORWARD` and the rest is dropped
+pub(crate) fn security_rules(groups: &[SecurityGroup]) -> (Vec<Vec<String>>, Vec<Vec<String>>) {
+    let replies: Vec<String> = ["-m", "conntrack", "--ctstate", "ESTABLISHED,RELATED", "-j", "RETURN"].map(String::from).into();
+    let (mut ingress, mut egress) = (vec![replies.clone()], vec![replies]);
+    for group in groups {
+        for rule in &group.rules {
+            let mut args = Vec::new();
+            if let Some(protocol) = rule.protocol.name() {
+                args.extend(["-p".to_string(), protocol.to_string()]);
+            }
+            let (peer, chain) = match rule.direction {
+                RuleDirection::Ingress => ("-s", &mut ingress),
+                RuleDirection::Egress => ("-d", &mut egress),
+            };
+            args.extend([peer.to_string(), rule.cidr.clone()]);
+            if let Some((from, to)) = rule.ports() {
+                let ports = if from == to { from.to_string() } else { format!("{}:{}", from, to) };
+                args.extend(["--dport".to_string(), ports]);
+            }
+            args.extend(["-m".into(), "comment".into(), "--comment".into(), format!("zero:{}", group.id), "-j".into(), "RETURN".into()]);
+            chain.push(args);
         }
+    }
+    ingress.push(vec!["-j".into(), "DROP".into()]);
+    egress.push(vec!["-j".into(), "DROP".into()]);
+    (ingress, egress)
+}
+
+/// `nat` chain forwarding the published ports of a workload
+pub(crate) fn publish_chain(workload_id: &str) -> String {
+    format!("zpub{:08x}", short_hash(workload_id))
+}
+
+/// Rules of a workload's publish chain, as iptables arguments after the chain: DNAT from
+/// each host port to the port of the workload at `address`
+pub(crate) fn publish_rules(workload_id: &str, address: &str, ports: &[PortMapping]) -> Vec<Vec<String>> {
+    ports.iter()
+        .map(|port| vec![
+            "-p".into(), port.protocol.name().into(),
+            "--dport".into(), port.host_port.to_string(),
+            "-m".into(), "comment".into(), "--comment".into(), format!("zero:{}", workload_id),
+            "-j".into(), "DNAT".into(),
+            "--to-destination".into(), format!("{}:{}", address, port.container_port),
+        ])
+        .collect()
+}
+
+/// Chains jumping to the publish chains of workloads
+const PUBLISH_FROM: [&str; 2] = ["PREROUTING", "OUTPUT"];
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+/// Jump to a publish chain, for traffic to the host's own addresses
+fn publish_jump(chain: &str) -> [&str; 6] {
+    ["-m", "addrtype", "--dst-type", "LOCAL", "-j", chain]
+}
+
+/// Stop forwarding the published ports of a workload
+fn remove_publish_chain(workload_id: &str) {
+    let chain = publish_chain(workload_id);
+    for from in PUBLISH_FROM {
+        let mut args = vec!["-t", "nat", "-D", from];
+        args.extend(publish_jump(&chain));
+        let _ = run("iptables", &args);
     }
+    let _ = run("iptables", &["-t", "nat", "-F", &chain]);
+    let _ = run("iptables", &["-t", "nat", "-X", &chain]);
+}
+
+/// Jumps from `FORWARD` to the security chains of a bridge
+fn security_jumps(bridge: &str, ingress: &str, egress: &str) -> [[String; 4]; 2] {
+    [["-o", bridge, "-j", ingress].map(String::from), ["-i", bridge, "-j", egress].map(String::from)]
+}
+
+/// Stop filtering a network's traffic by its security groups
+fn remove_security_chains(network_id: &str) {
+    let (ingress, egress) = security_chains(network_id);
+    for jump in security_jumps(&bridge_name(network_id), &ingress, &egress) {
+        let mut args = vec!["-D", "FORWARD"];
+        args.extend(jump.iter().map(String::as_str));
+        let _ = run("iptables", &args);
+    }
+    for chain in [&ingress, &egress] {
+        let _ = run("iptables", &["-F", chain]);
+        let _ = run("iptables", &["-X", chain]);
+    }
+}
+
+fn iptables(action: &str, rule: &[String]) -> ZeroResult<String> {
+    let mut args = vec!["-t", &rule[0], action, &rule[1]];
+    args.extend(rule[2..].iter().map(String::as_str));
+    run("iptables", &args)
 }
 
 #[async_trait]
 impl NetworkDriver for LinuxNetworkDriver {
     async fn create_network(&self, id: &str, cidr: &str) -> ZeroResult<NetworkStatus> {
-        // Create a libvirt XML definition for the network
-        // Bridge name will match ID
-        let xml = format!(r#"
-<network>
-  <name>{}</name>
-  <bridge name='{}' stp='on' delay='0'/>
-  <ip address='{}' netmask='255.255.255.0'>
-    <dhcp>
-      <range start='{}.100' end='{}.200'/>
-    </dhcp>
-  </ip>
-</network>
-"#, id, id, cidr.replace("/24", ".1"), cidr.replace(".0/24", ""), cidr.replace(".0/24", ""));
-
-        let temp_xml = format!("/tmp/zero-net-{}.xml", id);
-        fs::write(&temp_xml, xml).map_err(|e| ZeroError::Driver(e.to_string()))?;
-
-        self.run_virsh(vec!["net-define", &temp_xml])?;
-        self.run_virsh(vec!["net-start", id])?;
-        self.run_virsh(vec!["net-autostart", id])?;
+        let block = Cidr::parse(cidr)?;
+        if self.networks.lock().contains_key(id) {
+            return Err(ZeroError::AlreadyExists(format!("Network {}", id)));
+        }
+
+        let bridge = bridge_name(id);
+        let gateway = format!("{}/{}", block.gateway(), block.prefix());
+        run("ip", &["link", "add", &bridge, "type", "bridge"])?;
+        let setup = (|| {
+            run("ip", &["addr", "add", &gateway, "dev", &bridge])?;
+            run("ip", &["link", "set", &bridge, "up"])?;
+            std::fs::write("/proc/sys/net/ipv4/ip_forward", "1")
+                .map_err(|e| ZeroError::Driver(format!("Failed to enable IP forwarding: {}", e)))?;
+            for rule in nat_rules(id, &block, &bridge) {
+                iptables("-A", &rule)?;
+            }
+            Ok(())
+        })();
+        if let Err(e) = setup {
+            for rule in nat_rules(id, &block, &bridge) {
+                let _ = iptables("-D", &rule);
+            }
+            let _ = run("ip", &["link", "del", &bridge]);
+            return Err(e);
+        }
 
+        self.networks.lock().insert(id.to_string(), Subnet::new(block));
         Ok(NetworkStatus {
             id: id.to_string(),
-            cidr: cidr.to_string(),
+            cidr: block.to_string(),
             state: "Available".to_string(),
         })
     }
 
     async fn delete_network(&self, id: &str) -> ZeroResult<()> {
-        self.run_virsh(vec!["net-destroy", id]).ok();
-        self.run_virsh(vec!["net-undefine", id])?;
+        let subnet = self.networks.lock().remove(id)
+            .ok_or_else(|| ZeroError::NotFound(format!("Network {}", id)))?;
+        let bridge = bridge_name(id);
+        // Removing the host end of a veth pair removes the workload end too
+        for (_, workload_id) in subnet.allocations() {
+            let _ = run("ip", &["link", "del", &veth_names(workload_id, id).0]);
+            remove_publish_chain(workload_id);
+        }
+        for rule in nat_rules(id, &subnet.cidr(), &bridge) {
+            let _ = iptables("-D", &rule);
+        }
+        remove_security_chains(id);
+        run("ip", &["link", "del", &bridge])?;
         Ok(())
     }
 
     async fn connect_workload(&self, workload_id: &str, network_id: &str) -> ZeroResult<String> {
-        // Attach interface to the VM
-        let script = format!(
-            "virsh attach-interface --domain {} --type network --source {} --model virtio --config --live",
-            workload_id, network_id
-        );
-        
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(script)
-            .output()
-            .map_err(|e| ZeroError::Driver(format!("Failed to attach interface: {}", e)))?;
-
-        if !output.status.success() {
-            let err = String::from_utf8_lossy(&output.stderr);
-            return Err(ZeroError::Driver(format!("Connect failed: {}", err)));
-        }
+        self.connect(workload_id, network_id, None)
+    }
 
-        Ok("CONNECTED".to_string())
+    async fn connect_workload_with_address(&self, workload_id: &str, network_id: &str, address: &str) -> ZeroResult<String> {
+        let address = address.parse()
+            .map_err(|_| ZeroError::Validation(format!("Invalid IPv4 address: {}", address)))?;
+        self.connect(workload_id, network_id, Some(address))
     }
 
     async fn list_networks(&self) -> ZeroResult<Vec<NetworkStatus>> {
-        let output = self.run_virsh(vec!["net-list", "--all", "--name"])?;
-        let mut networks = Vec::new();
+        let mut networks: Vec<NetworkStatus> = self.networks.lock().iter()
+            .map(|(id, subnet)| NetworkStatus {
+                id: id.clone(),
+                cidr: subnet.cidr().to_string(),
+                // A bridge removed behind the driver's back leaves the network unusable
+                state: if run("ip", &["link", "show", &bridge_name(id)]).is_ok() { "Available" } else { "Failed" }.into(),
+            })
+            .collect();
+        networks.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(networks)
+    }
 
-        for name in output.lines() {
-            let name = name.trim();
-            if name.is_empty() { continue; }
+    async fn set_security_groups(&self, network_id: &str, groups: &[SecurityGroup]) -> ZeroResult<()> {
+        if !self.networks.lock().contains_key(network_id) {
+            return Err(ZeroError::NotFound(format!("Network {}", network_id)));
+        }
+        if groups.is_empty() {
+            remove_security_chains(network_id);
+            return Ok(());
+        }
 
-            networks.push(NetworkStatus {
-                id: name.to_string(),
-                cidr: "Unknown".into(),
-                state: "Available".into(),
-            });
+        // Bridged traffic between workloads only passes iptables with br_netfilter
+        let _ = run("modprobe", &["br_netfilter"]);
+        let _ = std::fs::write("/proc/sys/net/bridge/bridge-nf-call-iptables", "1");
+        let (ingress, egress) = security_chains(network_id);
+        let (ingress_rules, egress_rules) = security_rules(groups);
+        for (chain, rules) in [(&ingress, ingress_rules), (&egress, egress_rules)] {
+            if run("iptables", &["-n", "-L", chain]).is_err() {
+                run("iptables", &["-N", chain])?;
+            }
+            run("iptables", &["-F", chain])?;
+            for rule in rules {
+                let mut args = vec!["-A", chain.as_str()];
+                args.extend(rule.iter().map(String::as_str));
+                run("iptables", &args)?;
+            }
         }
+        // Ahead of the network's ACCEPT rules
+        for jump in security_jumps(&bridge_name(network_id), &ingress, &egress) {
+            let jump: Vec<&str> = jump.iter().map(String::as_str).collect();
+            let mut check = vec!["-C", "FORWARD"];
+            check.extend(&jump);
+            if run("iptables", &check).is_err() {
+                let mut insert = vec!["-I", "FORWARD", "1"];
+                insert.extend(&jump);
+                run("iptables", &insert)?;
+            }
+        }
+        Ok(())
+    }
 
-        Ok(networks)
+    async fn publish_ports(&self, workload_id: &str, network_id: &str, address: &str, ports: &[PortMapping]) -> ZeroResult<()> {
+        if !self.networks.lock().contains_key(network_id) {
+            return Err(ZeroError::NotFound(format!("Network {}", network_id)));
+        }
+        let chain = publish_chain(workload_id);
+        let setup = (|| {
+            if run("iptables", &["-t", "nat", "-n", "-L", &chain]).is_err() {
+                run("iptables", &["-t", "nat", "-N", &chain])?;
+            }
+            run("iptables", &["-t", "nat", "-F", &chain])?;
+            for rule in publish_rules(workload_id, address, ports) {
+                let mut args = vec!["-t", "nat", "-A", chain.as_str()];
+                args.extend(rule.iter().map(String::as_str));
+                run("iptables", &args)?;
+            }
+            for from in PUBLISH_FROM {
+                let mut check = vec!["-t", "nat", "-C", from];
+                check.extend(publish_jump(&chain));
+                if run("iptables", &check).is_err() {
+                    let mut append = vec!["-t", "nat", "-A", from];
+                    append.extend(publish_jump(&chain));
+                    run("iptables", &append)?;
+                }
+            }
+            Ok(())
+        })();
+        if setup.is_err() {
+            remove_publish_chain(workload_id);
+        }
+        setup
+    }
+
+    async fn unpublish_ports(&self, workload_id: &str, _network_id: &str, _ports: &[PortMapping]) -> ZeroResult<()> {
+        remove_publish_chain(workload_id);
+        Ok(())
     }
 }
@@ -1,12 +1,24 @@
-use zero_control_spi::{ComputeDriver, NetworkDriver, ZeroResult, WorkloadStatus, NetworkStatus};
+use zero_control_spi::{image_reference, ComputeDriver, ContainerSpec, ImageInfo, NetworkDriver, PortMapping, ZeroResult, WorkloadStatus, WorkloadUsage, NetworkStatus, SecurityGroup, TaskOutput, TaskSpec, VolumeMount};
 use async_trait::async_trait;
 use parking_lot::Mutex;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Size the mock reports for every pulled image
+const MOCK_IMAGE_SIZE_BYTES: u64 = 5 * 1024 * 1024;
 
 /// A mock compute driver that simulates workloads in-memory.
 /// Useful for testing, CI, or unsupported environments.
 pub struct MockComputeDriver {
     workloads: Mutex<HashMap<String, WorkloadStatus>>,
+    mounts: Mutex<HashMap<String, Vec<VolumeMount>>>,
+    containers: Mutex<HashMap<String, ContainerSpec>>,
+    cpu_usage_percent: Mutex<f32>,
+    workload_cpu_percent: Mutex<HashMap<String, f32>>,
+    workload_usage: Mutex<HashMap<String, WorkloadUsage>>,
+    images: Mutex<BTreeMap<String, ImageInfo>>,
+    workload_images: Mutex<HashMap<String, String>>,
+    failing_images: Mutex<HashSet<String>>,
+    hide_addresses: Mutex<bool>,
 }
 
 impl Default for MockComputeDriver {
@@ -19,20 +31,93 @@ impl MockComputeDriver {
     pub fn new() -> Self {
         Self {
             workloads: Mutex::new(HashMap::new()),
+            mounts: Mutex::new(HashMap::new()),
+            containers: Mutex::new(HashMap::new()),
+            cpu_usage_percent: Mutex::new(15.5),
+            workload_cpu_percent: Mutex::new(HashMap::new()),
+            workload_usage: Mutex::new(HashMap::new()),
+            images: Mutex::new(BTreeMap::new()),
+            workload_images: Mutex::new(HashMap::new()),
+            failing_images: Mutex::new(HashSet::new()),
+            hide_addresses: Mutex::new(false),
+        }
+    }
+
+    /// Set the CPU usage that `get_stats` reports
+    pub fn set_cpu_usage(&self, percent: f32) {
+        *self.cpu_usage_percent.lock() = percent;
+    }
+
+    /// Set the CPU usage that `workload_cpu_percent` reports for a workload; workloads
+    /// without one are idle
+    pub fn set_workload_cpu_usage(&self, id: &str, percent: f32) {
+        self.workload_cpu_percent.lock().insert(id.to_string(), percent);
+    }
+
+    /// Set the memory and network usage that `workload_usage` reports for a workload, along
+    /// with its CPU usage
+    pub fn set_workload_usage(&self, id: &str, usage: WorkloadUsage) {
+        self.set_workload_cpu_usage(id, usage.cpu_percent);
+        self.workload_usage.lock().insert(id.to_string(), usage);
+    }
+
+    /// Change the state a workload reports, as when it stops or crashes
+    pub fn set_workload_state(&self, id: &str, state: &str) {
+        if let Some(workload) = self.workloads.lock().get_mut(id) {
+            workload.state = state.to_string();
         }
     }
+
+    /// Make workloads created from `image` from now on report the `Failed` state, as when
+    /// the image is broken
+    pub fn fail_image(&self, image: &str) {
+        self.failing_images.lock().insert(image.to_string());
+    }
+
+    /// Make workloads created from now on report no IP address, as on drivers that only
+    /// reach workloads through published ports
+    pub fn hide_addresses(&self) {
+        *self.hide_addresses.lock() = true;
+    }
+
+    /// Image a workload was created from
+    pub fn image(&self, id: &str) -> Option<String> {
+        self.workload_images.lock().get(id).cloned()
+    }
+
+    /// Volumes attached to a workload
+    pub fn mounts(&self, id: &str) -> Vec<VolumeMount> {
+        self.mounts.lock().get(id).cloned().unwrap_or_default()
+    }
+
+    /// Spec a workload was started from by `create_container`
+    pub fn container(&self, id: &str) -> Option<ContainerSpec> {
+        self.containers.lock().get(id).cloned()
+    }
 }
 
 /// A mock network driver that simulates networks in-memory.
 #[derive(Default)]
 pub struct MockNetworkDriver {
     networks: Mutex<HashMap<String, NetworkStatus>>,
+    security_groups: Mutex<HashMap<String, Vec<SecurityGroup>>>,
+    published_ports: Mutex<HashMap<String, (String, Vec<PortMapping>)>>,
 }
 
 impl MockNetworkDriver {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Security groups last set on a network
+    pub fn security_groups(&self, network_id: &str) -> Vec<SecurityGroup> {
+        self.security_groups.lock().get(network_id).cloned().unwrap_or_default()
+    }
+
+    /// Address and ports a workload's host ports are forwarded to
+    pub fn published_ports(&self, workload_id: &str) -> Option<(String, Vec<PortMapping>)> {
+        self.published_ports.lock().get(workload_id).cloned()
+    }
 }
 
 #[async_trait]
@@ -56,25 +141,59 @@ impl NetworkDriver for MockNetworkDriver {
         Ok("10.0.0.50".to_string())
     }
 
+    async fn connect_workload_with_address(&self, _workload_id: &str, _network_id: &str, address: &str) -> ZeroResult<String> {
+        Ok(address.to_string())
+    }
+
     async fn list_networks(&self) -> ZeroResult<Vec<NetworkStatus>> {
         Ok(self.networks.lock().values().cloned().collect())
     }
+
+    async fn set_security_groups(&self, network_id: &str, groups: &[SecurityGroup]) -> ZeroResult<()> {
+        self.security_groups.lock().insert(network_id.to_string(), groups.to_vec());
+        Ok(())
+    }
+
+    async fn publish_ports(&self, workload_id: &str, _network_id: &str, address: &str, ports: &[PortMapping]) -> ZeroResult<()> {
+        self.published_ports.lock().insert(workload_id.to_string(), (address.to_string(), ports.to_vec()));
+        Ok(())
+    }
+
+    async fn unpublish_ports(&self, workload_id: &str, _network_id: &str, _ports: &[PortMapping]) -> ZeroResult<()> {
+        self.published_ports.lock().remove(workload_id);
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl ComputeDriver for MockComputeDriver {
-    async fn create_workload(&self, id: &str, _image: &str, _cpu: f32, _mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+    async fn create_workload(&self, id: &str, image: &str, _cpu: f32, _mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        let state = if self.failing_images.lock().contains(image) { "Failed" } else { "Running" };
         let status = WorkloadStatus {
             id: id.to_string(),
-            state: "Running".to_string(),
-            ip_address: Some("127.0.0.1".into()),
+            state: state.to_string(),
+            ip_address: (!*self.hide_addresses.lock()).then(|| "127.0.0.1".into()),
+            ports: Vec::new(),
+            restart_count: 0,
         };
         self.workloads.lock().insert(id.to_string(), status.clone());
+        self.workload_images.lock().insert(id.to_string(), image.to_string());
+        Ok(status)
+    }
+
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        let status = self.create_workload(id, image, cpu, mem_mb).await?;
+        self.mounts.lock().insert(id.to_string(), mounts.to_vec());
         Ok(status)
     }
 
     async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
         self.workloads.lock().remove(id);
+        self.workload_images.lock().remove(id);
+        self.mounts.lock().remove(id);
+        self.containers.lock().remove(id);
+        self.workload_cpu_percent.lock().remove(id);
+        self.workload_usage.lock().remove(id);
         Ok(())
     }
 
@@ -89,11 +208,51 @@ impl ComputeDriver for MockComputeDriver {
 
     async fn get_stats(&self) -> ZeroResult<zero_control_spi::NodeStats> {
         Ok(zero_control_spi::NodeStats {
-            cpu_usage_percent: 15.5,
+            cpu_usage_percent: *self.cpu_usage_percent.lock(),
             memory_used_mb: 2048,
             memory_total_mb: 16384,
             storage_used_gb: 120,
             storage_total_gb: 512,
         })
     }
+
+    /// Echoes the input back as the task's stdout
+    async fn run_task(&self, _id: &str, spec: &TaskSpec) -> ZeroResult<TaskOutput> {
+        Ok(TaskOutput { exit_code: Some(0), stdout: spec.input.clone(), stderr: String::new() })
+    }
+
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        self.get_workload_status(id).await?;
+        Ok(self.workload_cpu_percent.lock().get(id).copied().unwrap_or_default())
+    }
+
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        let cpu_percent = self.workload_cpu_percent(id).await?;
+        let usage = self.workload_usage.lock().get(id).copied().unwrap_or_default();
+        Ok(WorkloadUsage { cpu_percent, ..usage })
+    }
+
+    async fn create_container(&self, id: &str, spec: &ContainerSpec) -> ZeroResult<WorkloadStatus> {
+        let status = self.create_workload(id, &spec.image, spec.cpu, spec.memory_mb).await?;
+        self.containers.lock().insert(id.to_string(), spec.clone());
+        Ok(status)
+    }
+
+    async fn pull_image(&self, reference: &str) -> ZeroResult<ImageInfo> {
+        let reference = image_reference(reference);
+        let mut images = self.images.lock();
+        let id = format!("sha256:{:064x}", images.len() + 1);
+        let image = images.entry(reference.clone())
+            .or_insert(ImageInfo { reference, id, size_bytes: MOCK_IMAGE_SIZE_BYTES });
+        Ok(image.clone())
+    }
+
+    async fn list_images(&self) -> ZeroResult<Vec<ImageInfo>> {
+        Ok(self.images.lock().values().cloned().collect())
+    }
+
+    async fn remove_image(&self, reference: &str) -> ZeroResult<()> {
+        self.images.lock().remove(reference).map(|_| ())
+            .ok_or_else(|| zero_control_spi::ZeroError::NotFound(format!("Image not found: {}", reference)))
+    }
 }
@@ -1,28 +1,48 @@
 pub mod storage;
 pub mod docker;
 pub mod mock;
+pub mod podman;
 
 #[cfg(target_os = "windows")]
 pub mod hyperv;
 #[cfg(target_os = "windows")]
 pub mod network;
 
+#[cfg(target_os = "linux")]
+pub mod block;
+#[cfg(target_os = "linux")]
+pub mod containerd;
 #[cfg(target_os = "linux")]
 pub mod kvm;
 #[cfg(target_os = "linux")]
 pub mod linux_network;
 
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub mod lima;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub mod lima_network;
+
 pub use docker::DockerDriver;
+pub use podman::PodmanDriver;
 #[cfg(target_os = "windows")]
 pub use hyperv::HyperVDriver;
 #[cfg(target_os = "windows")]
 pub use network::HyperVNetworkDriver;
 
+#[cfg(target_os = "linux")]
+pub use block::{BlockFormat, BlockStorage};
+#[cfg(target_os = "linux")]
+pub use containerd::ContainerdDriver;
 #[cfg(target_os = "linux")]
 pub use kvm::KvmDriver;
 #[cfg(target_os = "linux")]
 pub use linux_network::LinuxNetworkDriver;
 
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub use lima::LimaDriver;
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub use lima_network::LimaNetworkDriver;
+
 pub use mock::{MockComputeDriver, MockNetworkDriver};
 pub use storage::FileSystemStorage;
 
@@ -1,9 +1,12 @@
-use zero_control_spi::{NetworkDriver, ZeroResult, ZeroError, NetworkStatus};
+use crate::ipam::Cidr;
+use zero_control_spi::{NetworkDriver, ZeroResult, ZeroError, NetworkStatus, PortMapping, RuleDirection, RuleProtocol, SecurityGroup};
 use async_trait::async_trait;
 use std::process::Command;
 
 /// Hyper-V Network Driver for Windows.
-/// Manages Virtual Switches.
+/// Manages Virtual Switches. Security groups become extended port ACLs on the network
+/// adapters of the VMs connected to a switch. The host adapter of each switch holds the
+/// network's gateway address behind a WinNAT instance, whose static mappings publish ports.
 pub struct HyperVNetworkDriver;
 
 impl Default for HyperVNetworkDriver {
@@ -33,24 +36,106 @@ impl HyperVNetworkDriver {
     }
 }
 
+/// Weight of the ACLs denying what no rule allows; allowing ACLs weigh more and win
+const DENY_WEIGHT: u32 = 1;
+
+/// PowerShell replacing the extended ACLs of every VM adapter on a switch with those of
+/// `groups`; without groups, the adapters are left without ACLs
+pub(crate) fn acl_script(network_id: &str, groups: &[SecurityGroup]) -> String {
+    let mut acls = Vec::new();
+    if !groups.is_empty() {
+        for direction in ["Inbound", "Outbound"] {
+            acls.push(format!("Add-VMNetworkAdapterExtendedAcl -Action Deny -Direction {} -Weight {}", direction, DENY_WEIGHT));
+        }
+    }
+    let (mut inbound, mut outbound) = (DENY_WEIGHT, DENY_WEIGHT);
+    for rule in groups.iter().flat_map(|group| &group.rules) {
+        let (direction, port, weight) = match rule.direction {
+            RuleDirection::Ingress => ("Inbound", "LocalPort", &mut inbound),
+            RuleDirection::Egress => ("Outbound", "RemotePort", &mut outbound),
+        };
+        *weight += 1;
+        let mut acl = format!("Add-VMNetworkAdapterExtendedAcl -Action Allow -Direction {} -RemoteIPAddress '{}' -Weight {}", direction, rule.cidr, weight);
+        match rule.protocol {
+            // Stateful ACLs let replies back through the deny ACL of the other direction
+            RuleProtocol::Tcp | RuleProtocol::Udp => acl.push_str(&format!(" -Protocol {} -Stateful $true", rule.protocol.name().unwrap_or_default().to_uppercase())),
+            RuleProtocol::Icmp => acl.push_str(" -Protocol ICMPv4"),
+            RuleProtocol::All => {}
+        }
+        if let Some((from, to)) = rule.ports() {
+            let ports = if from == to { from.to_string() } else { format!("{}-{}", from, to) };
+            acl.push_str(&format!(" -{} '{}'", port, ports));
+        }
+        acls.push(acl);
+    }
+    let mut script = format!(
+        "Get-VMNetworkAdapter -All | Where-Object {{ $_.SwitchName -eq '{}' -and -not $_.IsManagementOs }} | ForEach-Object {{\n    $_ | Get-VMNetworkAdapterExtendedAcl | Remove-VMNetworkAdapterExtendedAcl\n",
+        network_id
+    );
+    for acl in acls {
+        script.push_str(&format!("    $_ | {}\n", acl));
+    }
+    script.push('}');
+    script
+}
+
+/// WinNAT instance of a network
+pub(crate) fn nat_name(network_id: &str) -> String {
+    format!("zero-{}", network_id)
+}
+
+/// PowerShell forwarding host ports to a workload through its network's WinNAT instance
+pub(crate) fn publish_script(network_id: &str, address: &str, ports: &[PortMapping]) -> String {
+    ports.iter()
+        .map(|port| format!(
+            "Add-NetNatStaticMapping -NatName '{}' -Protocol {} -ExternalIPAddress 0.0.0.0 -ExternalPort {} -InternalIPAddress '{}' -InternalPort {}",
+            nat_name(network_id), port.protocol.name().to_uppercase(), port.host_port, address, port.container_port
+        ))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// PowerShell removing the static mappings of host ports
+pub(crate) fn unpublish_script(network_id: &str, ports: &[PortMapping]) -> String {
+    ports.iter()
+        .map(|port| format!(
+            "Get-NetNatStaticMapping -NatName '{}' | Where-Object {{ $_.ExternalPort -eq {} -and $_.Protocol -eq '{}' }} | Remove-NetNatStaticMapping -Confirm:$false",
+            nat_name(network_id), port.host_port, port.protocol.name().to_uppercase()
+        ))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[async_trait]
 impl NetworkDriver for HyperVNetworkDriver {
     async fn create_network(&self, id: &str, cidr: &str) -> ZeroResult<NetworkStatus> {
+        let block = Cidr::parse(cidr)?;
         // Internal switch for isolation
         let script = format!(
             "New-VMSwitch -Name '{}' -SwitchType Internal",
             id
         );
         self.run_powershell(&script)?;
+
+        // The host side routes the network's traffic out through WinNAT
+        let nat = format!(
+            "New-NetIPAddress -IPAddress '{}' -PrefixLength {} -InterfaceAlias 'vEthernet ({})' | Out-Null\nNew-NetNat -Name '{}' -InternalIPInterfaceAddressPrefix '{}' | Out-Null",
+            block.gateway(), block.prefix(), id, nat_name(id), block
+        );
+        if let Err(e) = self.run_powershell(&nat) {
+            let _ = self.run_powershell(&format!("Remove-VMSwitch -Name '{}' -Force", id));
+            return Err(e);
+        }
         
         Ok(NetworkStatus {
             id: id.to_string(),
-            cidr: cidr.to_string(),
+            cidr: block.to_string(),
             state: "Available".to_string(),
         })
     }
 
     async fn delete_network(&self, id: &str) -> ZeroResult<()> {
+        let _ = self.run_powershell(&format!("Remove-NetNat -Name '{}' -Confirm:$false", nat_name(id)));
         let script = format!("Remove-VMSwitch -Name '{}' -Force", id);
         self.run_powershell(&script)?;
         Ok(())
@@ -95,4 +180,22 @@ impl NetworkDriver for HyperVNetworkDriver {
 
         Ok(networks)
     }
+
+    async fn set_security_groups(&self, network_id: &str, groups: &[SecurityGroup]) -> ZeroResult<()> {
+        self.run_powershell(&acl_script(network_id, groups))?;
+        Ok(())
+    }
+
+    async fn publish_ports(&self, _workload_id: &str, network_id: &str, address: &str, ports: &[PortMapping]) -> ZeroResult<()> {
+        if let Err(e) = self.run_powershell(&publish_script(network_id, address, ports)) {
+            let _ = self.run_powershell(&unpublish_script(network_id, ports));
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    async fn unpublish_ports(&self, _workload_id: &str, network_id: &str, ports: &[PortMapping]) -> ZeroResult<()> {
+        self.run_powershell(&unpublish_script(network_id, ports))?;
+        Ok(())
+    }
 }
@@ -0,0 +1,121 @@
+use zero_control_spi::{ComputeDriver, ContainerSpec, ImageInfo, NodeStats, PortMapping, ZeroResult, ZeroError, WorkloadStatus, WorkloadUsage, TaskOutput, TaskSpec, VolumeMount};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use super::docker::DockerDriver;
+
+/// Podman Driver for daemonless, rootless containers.
+/// Talks to the Docker-compatible REST API Podman serves on its socket, which a user
+/// enables with `systemctl --user enable --now podman.socket` (or `podman machine start`
+/// on macOS and Windows). Containers run as the user who owns the socket.
+pub struct PodmanDriver {
+    docker: DockerDriver,
+    socket: PathBuf,
+}
+
+impl PodmanDriver {
+    /// Connect to the first Podman socket found, see [`PodmanDriver::socket_path`]
+    pub fn new() -> ZeroResult<Self> {
+        let socket = Self::socket_path().ok_or_else(|| ZeroError::Driver(
+            "No Podman socket found; enable one with `systemctl --user enable --now podman.socket`".to_string()
+        ))?;
+        Self::connect(socket)
+    }
+
+    /// Connect to the Podman socket at `socket`
+    pub fn connect(socket: impl Into<PathBuf>) -> ZeroResult<Self> {
+        let socket = socket.into();
+        let docker = DockerDriver::connect_socket(&socket.to_string_lossy())?;
+        Ok(Self { docker, socket })
+    }
+
+    /// Socket of `CONTAINER_HOST` (`unix://...`), else the rootless socket of the current
+    /// user, else the system socket
+    pub fn socket_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var("CONTAINER_HOST").ok().as_deref().and_then(|host| host.strip_prefix("unix://")) {
+            return Some(PathBuf::from(path));
+        }
+        let mut candidates = Vec::new();
+        if let Some(runtime_dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir).join("podman").join("podman.sock"));
+        }
+        #[cfg(target_os = "macos")]
+        if let Some(home) = std::env::var_os("HOME") {
+            candidates.push(PathBuf::from(home).join(".local/share/containers/podman/machine/podman.sock"));
+        }
+        #[cfg(windows)]
+        candidates.push(PathBuf::from(r"\\.\pipe\podman-machine-default"));
+        #[cfg(not(windows))]
+        candidates.push(PathBuf::from("/run/podman/podman.sock"));
+        candidates.into_iter().find(|path| path.exists())
+    }
+
+    /// Whether a Podman socket is listening
+    pub fn is_available() -> bool {
+        Self::socket_path().is_some()
+    }
+
+    /// Socket this driver talks to
+    pub fn socket(&self) -> &std::path::Path {
+        &self.socket
+    }
+}
+
+#[async_trait]
+impl ComputeDriver for PodmanDriver {
+    async fn create_workload(&self, id: &str, image: &str, cpu: f32, mem_mb: i32) -> ZeroResult<WorkloadStatus> {
+        self.docker.create_workload(id, image, cpu, mem_mb).await
+    }
+
+    async fn create_workload_with_volumes(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount]) -> ZeroResult<WorkloadStatus> {
+        self.docker.create_workload_with_volumes(id, image, cpu, mem_mb, mounts).await
+    }
+
+    async fn create_workload_with_ports(&self, id: &str, image: &str, cpu: f32, mem_mb: i32, mounts: &[VolumeMount], ports: &[PortMapping]) -> ZeroResult<WorkloadStatus> {
+        self.docker.create_workload_with_ports(id, image, cpu, mem_mb, mounts, ports).await
+    }
+
+    async fn delete_workload(&self, id: &str) -> ZeroResult<()> {
+        self.docker.delete_workload(id).await
+    }
+
+    async fn get_workload_status(&self, id: &str) -> ZeroResult<WorkloadStatus> {
+        self.docker.get_workload_status(id).await
+    }
+
+    async fn list_workloads(&self) -> ZeroResult<Vec<WorkloadStatus>> {
+        self.docker.list_workloads().await
+    }
+
+    async fn get_stats(&self) -> ZeroResult<NodeStats> {
+        self.docker.get_stats().await
+    }
+
+    /// Rootless Podman on cgroups v1 cannot report per-container CPU and returns an error
+    async fn workload_cpu_percent(&self, id: &str) -> ZeroResult<f32> {
+        self.docker.workload_cpu_percent(id).await
+    }
+
+    async fn workload_usage(&self, id: &str) -> ZeroResult<WorkloadUsage> {
+        self.docker.workload_usage(id).await
+    }
+
+    async fn run_task(&self, id: &str, spec: &TaskSpec) -> ZeroResult<TaskOutput> {
+        self.docker.run_task(id, spec).await
+    }
+
+    async fn create_container(&self, id: &str, spec: &ContainerSpec) -> ZeroResult<WorkloadStatus> {
+        self.docker.create_container(id, spec).await
+    }
+
+    async fn pull_image(&self, reference: &str) -> ZeroResult<ImageInfo> {
+        self.docker.pull_image(reference).await
+    }
+
+    async fn list_images(&self) -> ZeroResult<Vec<ImageInfo>> {
+        self.docker.list_images().await
+    }
+
+    async fn remove_image(&self, reference: &str) -> ZeroResult<()> {
+        self.docker.remove_image(reference).await
+    }
+}
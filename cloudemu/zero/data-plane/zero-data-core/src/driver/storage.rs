@@ -3,6 +3,11 @@ use async_trait::async_trait;
 use std::path::PathBuf;
 use tokio::fs;
 
+/// File in a volume's directory that holds its blocks
+pub(crate) const BLOCK_FILE: &str = "data.bin";
+
+pub(crate) const GIB: u64 = 1024 * 1024 * 1024;
+
 pub struct FileSystemStorage {
     base_path: PathBuf,
 }
@@ -11,20 +16,53 @@ impl FileSystemStorage {
     pub fn new(base_path: PathBuf) -> Self {
         Self { base_path }
     }
+
+    /// Directory of a volume
+    pub(crate) fn volume_path(&self, id: &str) -> PathBuf {
+        self.base_path.join(id)
+    }
+
+    fn status(&self, id: &str) -> VolumeStatus {
+        let path = self.volume_path(id);
+        let size_gb = std::fs::metadata(path.join(BLOCK_FILE)).ok().map(|meta| (meta.len() / GIB) as i32);
+        VolumeStatus {
+            id: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            state: "Available".to_string(),
+            size_gb,
+            device: None,
+        }
+    }
+}
+
+/// Refuse to resize a volume that is missing or would shrink
+pub(crate) fn check_resize(id: &str, current_gb: Option<i32>, size_gb: i32) -> ZeroResult<()> {
+    match current_gb {
+        None => Err(ZeroError::Validation(format!("Volume {} has no block file to resize", id))),
+        Some(current) if size_gb < current => Err(ZeroError::Validation(format!(
+            "Volume {} is {} GB and cannot shrink to {} GB", id, current, size_gb
+        ))),
+        Some(_) => Ok(()),
+    }
 }
 
 #[async_trait]
 impl StorageDriver for FileSystemStorage {
-    async fn create_volume(&self, id: &str, _size_gb: i32) -> ZeroResult<VolumeStatus> {
+    async fn create_volume(&self, id: &str, size_gb: i32) -> ZeroResult<VolumeStatus> {
         let path = self.base_path.join(id);
         fs::create_dir_all(&path).await
             .map_err(|e| ZeroError::Driver(format!("FS create error: {}", e)))?;
-        
-        Ok(VolumeStatus {
-            id: id.to_string(),
-            path: path.to_string_lossy().to_string(),
-            state: "Available".to_string(),
-        })
+
+        // Sparse block file that VMs attach as a disk; recreating a volume keeps its data
+        let block_file = path.join(BLOCK_FILE);
+        if size_gb > 0 && !block_file.exists() {
+            let file = fs::File::create(&block_file).await
+                .map_err(|e| ZeroError::Driver(format!("FS create error: {}", e)))?;
+            file.set_len(size_gb as u64 * GIB).await
+                .map_err(|e| ZeroError::Driver(format!("FS resize error: {}", e)))?;
+        }
+
+        Ok(self.status(id))
     }
 
     async fn delete_volume(&self, id: &str) -> ZeroResult<()> {
@@ -39,7 +77,7 @@ impl StorageDriver for FileSystemStorage {
     #[allow(clippy::suspicious_open_options)]
     async fn write_block(&self, volume_id: &str, offset: u64, data: Vec<u8>) -> ZeroResult<()> {
         use tokio::io::{AsyncWriteExt, AsyncSeekExt};
-        let file_path = self.base_path.join(volume_id).join("data.bin");
+        let file_path = self.base_path.join(volume_id).join(BLOCK_FILE);
         
         let mut file = fs::OpenOptions::new()
             .write(true)
@@ -58,7 +96,7 @@ impl StorageDriver for FileSystemStorage {
 
     async fn read_block(&self, volume_id: &str, offset: u64, length: u32) -> ZeroResult<Vec<u8>> {
         use tokio::io::{AsyncReadExt, AsyncSeekExt};
-        let file_path = self.base_path.join(volume_id).join("data.bin");
+        let file_path = self.base_path.join(volume_id).join(BLOCK_FILE);
         
         let mut file = fs::File::open(&file_path).await
             .map_err(|e| ZeroError::Driver(format!("FS open error: {}", e)))?;
@@ -78,15 +116,28 @@ impl StorageDriver for FileSystemStorage {
         if let Ok(mut entries) = fs::read_dir(&self.base_path).await {
             while let Ok(Some(entry)) = entries.next_entry().await {
                 if entry.file_type().await.map(|t| t.is_dir()).unwrap_or(false) {
-                    let id = entry.file_name().to_string_lossy().to_string();
-                    volumes.push(VolumeStatus {
-                        id,
-                        path: entry.path().to_string_lossy().to_string(),
-                        state: "Available".to_string(),
-                    });
+                    volumes.push(self.status(&entry.file_name().to_string_lossy()));
                 }
             }
         }
         Ok(volumes)
     }
+
+    async fn backing_file(&self, volume_id: &str) -> ZeroResult<Option<String>> {
+        let path = self.base_path.join(volume_id).join(BLOCK_FILE);
+        Ok(path.exists().then(|| path.to_string_lossy().to_string()))
+    }
+
+    /// Grows the sparse block file; the new blocks read as zeros
+    async fn resize_volume(&self, id: &str, size_gb: i32) -> ZeroResult<VolumeStatus> {
+        if !self.volume_path(id).exists() {
+            return Err(ZeroError::NotFound(format!("Volume not found: {}", id)));
+        }
+        check_resize(id, self.status(id).size_gb, size_gb)?;
+        let file = fs::OpenOptions::new().write(true).open(self.volume_path(id).join(BLOCK_FILE)).await
+            .map_err(|e| ZeroError::Driver(format!("FS open error: {}", e)))?;
+        file.set_len(size_gb as u64 * GIB).await
+            .map_err(|e| ZeroError::Driver(format!("FS resize error: {}", e)))?;
+        Ok(self.status(id))
+    }
 }
@@ -14,6 +14,12 @@ async fn test_file_system_storage_driver() {
     assert_eq!(vol.id, "test-vol");
     assert!(PathBuf::from(&vol.path).exists());
 
+    // Sized volumes get a sparse block file that VMs can attach
+    let backing_file = storage.backing_file("test-vol").await.unwrap().unwrap();
+    assert_eq!(std::fs::metadata(&backing_file).unwrap().len(), 10 * 1024 * 1024 * 1024);
+    storage.create_volume("folder", 0).await.unwrap();
+    assert_eq!(storage.backing_file("folder").await.unwrap(), None);
+
     // Test Write/Read Block
     let data = vec![1, 2, 3, 4, 5];
     storage.write_block("test-vol", 0, data.clone()).await.unwrap();
@@ -29,6 +35,13 @@ async fn test_file_system_storage_driver() {
     let full_data = storage.read_block("test-vol", 0, 5).await.unwrap();
     assert_eq!(full_data, vec![1, 2, 9, 9, 9]);
 
+    // Test Resize: volumes grow but never shrink, and folders have nothing to resize
+    assert_eq!(storage.resize_volume("test-vol", 20).await.unwrap().size_gb, Some(20));
+    assert_eq!(storage.read_block("test-vol", 0, 5).await.unwrap(), vec![1, 2, 9, 9, 9]);
+    assert!(storage.resize_volume("test-vol", 5).await.is_err());
+    assert!(storage.resize_volume("folder", 5).await.is_err());
+    assert!(storage.resize_volume("missing", 5).await.is_err());
+
     // Test Delete
     storage.delete_volume("test-vol").await.unwrap();
     assert!(!PathBuf::from(&vol.path).exists());
@@ -70,3 +83,220 @@ async fn test_mock_network_driver() {
     // Test Delete Network
     driver.delete_network("net-1").await.unwrap();
 }
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_kvm_virsh_output_parsing() {
+    use super::kvm::{normalize_state, parse_domain_names, parse_domifaddr, parse_domstats, parse_field};
+
+    let domifaddr = " Name       MAC address          Protocol     Address\n\
+        -------------------------------------------------------------------------------\n \
+        vnet0      52:54:00:4b:73:5f    ipv6         fe80::5054:ff:fe4b:735f/64\n \
+        vnet0      52:54:00:4b:73:5f    ipv4         192.168.122.45/24\n";
+    assert_eq!(parse_domifaddr(domifaddr).as_deref(), Some("192.168.122.45"));
+    assert_eq!(parse_domifaddr(" Name       MAC address          Protocol     Address\n-----\n"), None);
+
+    // Only domains this driver created are workloads
+    assert_eq!(parse_domain_names("zero-web\nubuntu-desktop\n\nzero-db\n"), vec!["web", "db"]);
+
+    assert_eq!((normalize_state("running"), normalize_state("shut off"), normalize_state("crashed")), ("Running", "Stopped", "Failed"));
+
+    let nodeinfo = "CPU model:           x86_64\nCPU(s):              8\nMemory size:         16314628 KiB\n";
+    assert_eq!(parse_field(nodeinfo, "Memory size"), Some(16314628));
+    assert_eq!(parse_field("total  :             16314628 KiB\nfree   :              8000000 KiB\n", "free"), Some(8000000));
+
+    let domstats = "Domain: 'zero-web'\n  cpu.time=2500000000\n  balloon.current=1048576\n  balloon.rss=600000\n  \
+        net.count=2\n  net.0.name=vnet0\n  net.0.rx.bytes=1000\n  net.0.tx.bytes=200\n  net.1.rx.bytes=24\n  net.1.tx.bytes=56\n";
+    let (cpu_ns, usage) = parse_domstats(domstats);
+    assert_eq!(cpu_ns, 2_500_000_000);
+    assert_eq!((usage.memory_used_mb, usage.memory_limit_mb), (585, 1024));
+    assert_eq!((usage.network_rx_bytes, usage.network_tx_bytes), (1024, 256));
+    // The guest's balloon stats win over the host's resident size
+    let (_, usage) = parse_domstats(&format!("{}  balloon.available=1000000\n  balloon.unused=488000\n", domstats));
+    assert_eq!(usage.memory_used_mb, 500);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_containerd_nerdctl_output_parsing() {
+    use super::containerd::{parse_cpu_percent, parse_image_line, parse_inspect, parse_ps_line, parse_usage};
+
+    let inspect = r#"[{"Id":"4f1c","Name":"web","State":{"Status":"running","Running":true},
+        "NetworkSettings":{"IPAddress":"","Networks":{"unknown-eth0":{"IPAddress":"10.4.0.12"}}}}]"#;
+    let status = parse_inspect("web", inspect).unwrap();
+    assert_eq!((status.state.as_str(), status.ip_address.as_deref()), ("running", Some("10.4.0.12")));
+    assert!(parse_inspect("web", "not json").is_err());
+
+    let running = parse_ps_line(r#"{"ID":"4f1c","Names":"web","Status":"Up 2 minutes","Image":"nginx"}"#).unwrap();
+    assert_eq!((running.id.as_str(), running.state.as_str()), ("web", "running"));
+    let exited = parse_ps_line(r#"{"ID":"9a2b","Names":"job","Status":"Exited (0) 5 seconds ago"}"#).unwrap();
+    assert_eq!(exited.state, "exited");
+    assert!(parse_ps_line("").is_none());
+
+    assert_eq!(parse_cpu_percent(r#"{"Name":"web","CPUPerc":"12.50%","MemUsage":"4MiB / 1GiB"}"#), Some(12.5));
+    let usage = parse_usage(r#"{"Name":"web","CPUPerc":"12.50%","MemUsage":"4.5MiB / 1GiB","NetIO":"1.2kB / 648B"}"#).unwrap();
+    assert_eq!((usage.memory_used_mb, usage.memory_limit_mb), (4, 1024));
+    assert_eq!((usage.network_rx_bytes, usage.network_tx_bytes), (1200, 648));
+    assert!(parse_usage(r#"{"Name":"web","CPUPerc":"12.50%","MemUsage":"4 parsecs / 1GiB"}"#).is_none());
+
+    let image = parse_image_line(r#"{"Repository":"nginx","Tag":"latest","ID":"4f1c","Size":"72.8 MiB"}"#).unwrap();
+    assert_eq!((image.reference.as_str(), image.id.as_str(), image.size_bytes), ("nginx:latest", "4f1c", 76336332));
+    let untagged = parse_image_line(r#"{"Repository":"<none>","Tag":"<none>","ID":"9a2b","Size":"1kB"}"#).unwrap();
+    assert_eq!(untagged.reference, "9a2b");
+    assert!(parse_image_line("not json").is_none());
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[test]
+fn test_lima_output_parsing() {
+    use super::lima::{instance_config, normalize_state, parse_ip_addr, parse_list};
+    use super::lima_network::{add_network, gateway_and_netmask, parse_networks, remove_network};
+    use zero_control_spi::VolumeMount;
+
+    let list = "{\"name\":\"zero-web\",\"status\":\"Running\",\"vmType\":\"vz\",\"memory\":1073741824}\nnot json\n";
+    let instances = parse_list(list);
+    assert_eq!((instances.len(), instances[0]["name"].as_str()), (1, Some("zero-web")));
+    assert_eq!((normalize_state("Running"), normalize_state("Broken")), ("Running", "Failed"));
+
+    // The vzNAT interface is reachable from the host; user-mode eth0 is not
+    let addr = "2: eth0    inet 192.168.5.15/24 metric 200 brd 192.168.5.255 scope global dynamic eth0\n\
+        3: lima0    inet 192.168.105.3/24 brd 192.168.105.255 scope global dynamic lima0\n";
+    assert_eq!(parse_ip_addr(addr).as_deref(), Some("192.168.105.3"));
+    assert_eq!(parse_ip_addr("2: eth0    inet 192.168.5.15/24 scope global eth0\n"), None);
+
+    let mount = VolumeMount { volume_id: "data".into(), source: "/tmp/data".into(), backing_file: None, target: "/data".into(), read_only: true };
+    let (template, set) = instance_config("ubuntu", &[mount]).unwrap();
+    assert_eq!(template, "template://ubuntu");
+    assert_eq!(set, r#".mounts = [{"location":"/tmp/data","mountPoint":"/data","writable":false}]"#);
+    assert!(instance_config("missing.qcow2", &[]).is_err());
+
+    let (gateway, netmask) = gateway_and_netmask("10.0.1.0/24").unwrap();
+    assert_eq!((gateway.to_string(), netmask.to_string()), ("10.0.1.1".to_string(), "255.255.255.0".to_string()));
+    assert!(gateway_and_netmask("10.0.1.0").is_err());
+
+    let config = "paths:\n  socketVMNet: /opt/socket_vmnet\nnetworks:\n  user-v2:\n    mode: user-v2\n    gateway: 192.168.104.1\n    netmask: 255.255.255.0\n";
+    let added = add_network(config, "net-1", gateway, netmask);
+    assert_eq!(parse_networks(&added), vec![
+        ("net-1".to_string(), "10.0.1.0/24".to_string()),
+        ("user-v2".to_string(), "192.168.104.0/24".to_string()),
+    ]);
+    let removed = remove_network(&added, "net-1");
+    assert_eq!(parse_networks(&removed), vec![("user-v2".to_string(), "192.168.104.0/24".to_string())]);
+    assert!(removed.contains("socketVMNet"));
+    assert_eq!(parse_networks(&add_network("", "net-1", gateway, netmask)).len(), 1);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_linux_network_naming_and_rules() {
+    use super::linux_network::{bridge_name, nat_rules, veth_names};
+    use crate::ipam::Cidr;
+
+    // Interface names are stable and fit the kernel's 15-character limit
+    let bridge = bridge_name("net-1");
+    assert_eq!(bridge, bridge_name("net-1"));
+    assert_ne!(bridge, bridge_name("net-2"));
+    let (host, workload) = veth_names("a-rather-long-workload-identifier", "net-1");
+    assert!(bridge.len() <= 15 && host.len() <= 15 && workload.len() <= 15);
+    assert_eq!(host[3..], workload[3..]);
+
+    let rules = nat_rules("net-1", &Cidr::parse("10.0.1.0/24").unwrap(), &bridge);
+    assert_eq!(rules.len(), 3);
+    assert_eq!(rules[0][..9].join(" "), format!("nat POSTROUTING -s 10.0.1.0/24 ! -o {} -j MASQUERADE", bridge));
+    assert!(rules.iter().all(|rule| rule.ends_with(&["--comment".to_string(), "zero:net-1".to_string()])));
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn web_group() -> zero_control_spi::SecurityGroup {
+    use zero_control_spi::{RuleDirection, RuleProtocol, SecurityGroup, SecurityRule};
+    SecurityGroup {
+        id: "sg-web".into(),
+        network_id: "net-1".into(),
+        name: "web".into(),
+        rules: vec![
+            SecurityRule { direction: RuleDirection::Ingress, protocol: RuleProtocol::Tcp, from_port: Some(8000), to_port: Some(8080), cidr: "10.0.0.0/8".into() },
+            SecurityRule { direction: RuleDirection::Egress, protocol: RuleProtocol::All, from_port: None, to_port: None, cidr: "0.0.0.0/0".into() },
+        ],
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_linux_security_group_rules() {
+    use super::linux_network::{security_chains, security_rules};
+
+    let (ingress, egress) = security_chains("net-1");
+    assert!(ingress.len() <= 28 && egress.len() <= 28 && ingress != egress);
+
+    let (ingress, egress) = security_rules(&[web_group()]);
+    // Replies first, the default drop last
+    assert_eq!(ingress[0].join(" "), "-m conntrack --ctstate ESTABLISHED,RELATED -j RETURN");
+    assert_eq!(ingress[1][..6].join(" "), "-p tcp -s 10.0.0.0/8 --dport 8000:8080");
+    assert_eq!(ingress.last().unwrap().join(" "), "-j DROP");
+    assert_eq!(egress[1][..2].join(" "), "-d 0.0.0.0/0");
+    assert_eq!(security_rules(&[]).0.len(), 2);
+}
+
+#[cfg(target_os = "windows")]
+#[test]
+fn test_hyperv_security_group_acls() {
+    use super::network::acl_script;
+
+    let script = acl_script("net-1", &[web_group()]);
+    assert!(script.contains("$_.SwitchName -eq 'net-1'"));
+    assert!(script.contains("-Action Deny -Direction Inbound -Weight 1"));
+    assert!(script.contains("-Direction Inbound -RemoteIPAddress '10.0.0.0/8' -Weight 2 -Protocol TCP -Stateful $true -LocalPort '8000-8080'"));
+    assert!(script.contains("-Direction Outbound -RemoteIPAddress '0.0.0.0/0' -Weight 2"));
+    assert!(!acl_script("net-1", &[]).contains("Add-VMNetworkAdapterExtendedAcl"));
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn web_ports() -> Vec<zero_control_spi::PortMapping> {
+    use zero_control_spi::{PortMapping, PortProtocol};
+    vec![
+        PortMapping { host_port: 8080, container_port: 80, protocol: PortProtocol::Tcp },
+        PortMapping { host_port: 5353, container_port: 53, protocol: PortProtocol::Udp },
+    ]
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_linux_publish_rules() {
+    use super::linux_network::{publish_chain, publish_rules};
+
+    let chain = publish_chain("a-rather-long-workload-identifier");
+    assert!(chain.len() <= 28 && chain != publish_chain("web"));
+
+    let rules = publish_rules("web", "10.0.1.2", &web_ports());
+    assert_eq!(rules[0].join(" "), "-p tcp --dport 8080 -m comment --comment zero:web -j DNAT --to-destination 10.0.1.2:80");
+    assert_eq!(rules[1][..4].join(" "), "-p udp --dport 5353");
+    assert!(publish_rules("web", "10.0.1.2", &[]).is_empty());
+}
+
+#[cfg(target_os = "windows")]
+#[test]
+fn test_hyperv_publish_script() {
+    use super::network::{publish_script, unpublish_script};
+
+    let script = publish_script("net-1", "10.0.1.2", &web_ports());
+    assert!(script.contains("-NatName 'zero-net-1' -Protocol TCP -ExternalIPAddress 0.0.0.0 -ExternalPort 8080 -InternalIPAddress '10.0.1.2' -InternalPort 80"));
+    assert!(script.contains("-Protocol UDP"));
+    assert!(unpublish_script("net-1", &web_ports()).contains("$_.ExternalPort -eq 5353 -and $_.Protocol -eq 'UDP'"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_block_volume_tool_output() {
+    use super::block::{parse_loop_device, parse_virtual_size, BlockFormat};
+
+    let associated = "/dev/loop3: [2049]:1234 (/srv/zero-storage/db/data.bin)\n";
+    assert_eq!(parse_loop_device(associated).as_deref(), Some("/dev/loop3"));
+    assert_eq!(parse_loop_device(""), None);
+
+    let info = r#"{ "virtual-size": 21474836480, "filename": "data.qcow2", "format": "qcow2", "actual-size": 200704 }"#;
+    assert_eq!(parse_virtual_size(info).unwrap(), 20 * 1024 * 1024 * 1024);
+    assert!(parse_virtual_size("{}").is_err());
+
+    assert_eq!("qcow2".parse::<BlockFormat>().unwrap(), BlockFormat::Qcow2);
+    assert!("vmdk".parse::<BlockFormat>().is_err());
+}
@@ -0,0 +1,69 @@
+//! Resource lifecycle events, pushed to live subscribers as they happen
+//!
+//! Events are not stored: a subscriber sees what happens after it subscribes, and one that
+//! falls more than [`EVENT_BUFFER`] events behind skips the oldest.
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Events kept for subscribers that have not read them yet
+pub const EVENT_BUFFER: usize = 256;
+
+pub const WORKLOAD_STARTED: &str = "workload.started";
+pub const WORKLOAD_STOPPED: &str = "workload.stopped";
+pub const WORKLOAD_RESTARTED: &str = "workload.restarted";
+pub const WORKLOAD_UPDATED: &str = "workload.updated";
+pub const WORKLOAD_ROLLED_BACK: &str = "workload.rolled_back";
+pub const QUEUE_MESSAGE_SENT: &str = "queue.message_sent";
+pub const VOLUME_CREATED: &str = "volume.created";
+pub const IMAGE_PULLED: &str = "image.pulled";
+pub const IMAGE_REMOVED: &str = "image.removed";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceEvent {
+    /// What happened, e.g. `workload.started`
+    pub kind: String,
+    /// ID or name of the resource it happened to
+    pub resource: String,
+    pub time: String,
+    /// Kind-specific fields, e.g. the message ID of `queue.message_sent`
+    #[serde(default)]
+    pub detail: serde_json::Value,
+}
+
+impl ResourceEvent {
+    /// An event that happens now
+    pub fn new(kind: &str, resource: &str, detail: serde_json::Value) -> Self {
+        Self {
+            kind: kind.to_string(),
+            resource: resource.to_string(),
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            detail,
+        }
+    }
+}
+
+pub struct EventBus {
+    sender: broadcast::Sender<ResourceEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self { sender: broadcast::channel(EVENT_BUFFER).0 }
+    }
+
+    /// Send an event to every current subscriber; without subscribers it is dropped
+    pub fn publish(&self, kind: &str, resource: &str, detail: serde_json::Value) {
+        let _ = self.sender.send(ResourceEvent::new(kind, resource, detail));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ResourceEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
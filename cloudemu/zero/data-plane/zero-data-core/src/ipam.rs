@@ -0,0 +1,416 @@
+//! IP address management for workload networks
+//!
+//! A [`Subnet`] hands out the host addresses of an IPv4 CIDR block to workloads. The first
+//! host address is the network's gateway; the network and broadcast addresses are never
+//! assigned. [`Ipam`] keeps the block of every network and the addresses handed out in the
+//! engine database, so allocations survive backups and restores, and refuses networks
+//! whose blocks overlap.
+
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use zero_control_spi::{ZeroError, ZeroResult};
+
+/// An IPv4 CIDR block such as `10.0.1.0/24`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: Ipv4Addr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Parse `address/prefix`; host bits must be zero and the block must leave room for a
+    /// gateway and at least one workload
+    pub fn parse(cidr: &str) -> ZeroResult<Self> {
+        Self::parse_with_max(cidr, 30)
+    }
+
+    /// Parse a block of any size, from `0.0.0.0/0` to a single `/32` host, as firewall
+    /// rules match
+    pub fn parse_match(cidr: &str) -> ZeroResult<Self> {
+        Self::parse_with_max(cidr, 32)
+    }
+
+    fn parse_with_max(cidr: &str, max_prefix: u8) -> ZeroResult<Self> {
+        let invalid = |reason: &str| ZeroError::Validation(format!("Invalid CIDR block {}: {}", cidr, reason));
+        let (address, prefix) = cidr.trim().split_once('/').ok_or_else(|| invalid("expected ADDRESS/PREFIX"))?;
+        let address: Ipv4Addr = address.parse().map_err(|_| invalid("not an IPv4 address"))?;
+        let prefix: u8 = prefix.parse().map_err(|_| invalid("prefix is not a number"))?;
+        if prefix > max_prefix {
+            return Err(invalid(&format!("prefix must be {} or less", max_prefix)));
+        }
+        let cidr = Self { network: address, prefix };
+        if u32::from(address) & !cidr.mask() != 0 {
+            return Err(invalid(&format!("host bits are set, did you mean {}?", Ipv4Addr::from(u32::from(address) & cidr.mask()))));
+        }
+        Ok(cidr)
+    }
+
+    fn mask(&self) -> u32 {
+        if self.prefix == 0 { 0 } else { u32::MAX << (32 - self.prefix) }
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    pub fn network(&self) -> Ipv4Addr {
+        self.network
+    }
+
+    pub fn netmask(&self) -> Ipv4Addr {
+        Ipv4Addr::from(self.mask())
+    }
+
+    pub fn broadcast(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.network) | !self.mask())
+    }
+
+    /// First host address, kept for the network's gateway
+    pub fn gateway(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.network) + 1)
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        u32::from(ip) & self.mask() == u32::from(self.network)
+    }
+
+    /// Whether the blocks share any address; one then holds the other
+    pub fn overlaps(&self, other: &Cidr) -> bool {
+        self.contains(other.network) || other.contains(self.network)
+    }
+
+    /// Addresses workloads can be given: every host address but the gateway
+    pub fn workload_addresses(&self) -> impl Iterator<Item = Ipv4Addr> {
+        (u32::from(self.gateway()) + 1..u32::from(self.broadcast())).map(Ipv4Addr::from)
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Addresses of one network and the workloads holding them
+#[derive(Debug, Clone)]
+pub struct Subnet {
+    cidr: Cidr,
+    allocated: BTreeMap<Ipv4Addr, String>,
+}
+
+impl Subnet {
+    pub fn new(cidr: Cidr) -> Self {
+        Self { cidr, allocated: BTreeMap::new() }
+    }
+
+    pub fn cidr(&self) -> Cidr {
+        self.cidr
+    }
+
+    /// Address of `owner`, assigning the lowest free one on first use
+    pub fn allocate(&mut self, owner: &str) -> ZeroResult<Ipv4Addr> {
+        if let Some(ip) = self.address_of(owner) {
+            return Ok(ip);
+        }
+        let ip = self.cidr.workload_addresses().find(|ip| !self.allocated.contains_key(ip))
+            .ok_or_else(|| ZeroError::QuotaExceeded(format!("No free addresses left in {}", self.cidr)))?;
+        self.allocated.insert(ip, owner.to_string());
+        Ok(ip)
+    }
+
+    /// Give `owner` the address `ip`, which must be a free workload address of the block
+    pub fn reserve(&mut self, owner: &str, ip: Ipv4Addr) -> ZeroResult<Ipv4Addr> {
+        if !self.cidr.workload_addresses().any(|free| free == ip) {
+            let reason = if ip == self.cidr.gateway() { "it is the gateway" } else { "it is not a workload address of the block" };
+            return Err(ZeroError::Validation(format!("Cannot assign {} in {}: {}", ip, self.cidr, reason)));
+        }
+        match (self.allocated.get(&ip), self.address_of(owner)) {
+            (Some(holder), _) if holder != owner => {
+                Err(ZeroError::AlreadyExists(format!("Address {} is already assigned to {}", ip, holder)))
+            }
+            (_, Some(current)) if current != ip => {
+                Err(ZeroError::Validation(format!("{} already has address {} in {}", owner, current, self.cidr)))
+            }
+            _ => {
+                self.allocated.insert(ip, owner.to_string());
+                Ok(ip)
+            }
+        }
+    }
+
+    /// Return the address of `owner` to the pool
+    pub fn release(&mut self, owner: &str) -> Option<Ipv4Addr> {
+        let ip = self.address_of(owner)?;
+        self.allocated.remove(&ip);
+        Some(ip)
+    }
+
+    pub fn address_of(&self, owner: &str) -> Option<Ipv4Addr> {
+        self.allocated.iter().find(|(_, o)| o.as_str() == owner).map(|(ip, _)| *ip)
+    }
+
+    /// Workloads and their addresses, by address
+    pub fn allocations(&self) -> impl Iterator<Item = (Ipv4Addr, &str)> {
+        self.allocated.iter().map(|(ip, owner)| (*ip, owner.as_str()))
+    }
+}
+
+/// An address handed out by [`Ipam`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Allocation {
+    pub address: String,
+    pub owner: String,
+    /// Requested by the workload rather than picked by IPAM
+    #[serde(rename = "static")]
+    pub is_static: bool,
+}
+
+/// Block of a network and how much of it is in use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkAddresses {
+    pub id: String,
+    pub cidr: String,
+    pub gateway: String,
+    pub allocations: Vec<Allocation>,
+    /// Workload addresses still free
+    pub free: usize,
+}
+
+fn db_error(e: rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+/// Create the IPAM tables, for new engines and databases restored from older backups
+pub(crate) fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ipam_networks (
+            id TEXT PRIMARY KEY,
+            cidr TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS ipam_allocations (
+            network_id TEXT NOT NULL,
+            address TEXT NOT NULL,
+            owner TEXT NOT NULL,
+            static INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (network_id, address)
+        );",
+    )
+}
+
+/// Network blocks and address allocations of an engine
+#[derive(Clone)]
+pub struct Ipam {
+    db: Arc<Mutex<Connection>>,
+}
+
+impl Ipam {
+    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+        Self { db }
+    }
+
+    /// Record the block of a new network; it must not overlap the block of another network
+    pub fn add_network(&self, id: &str, cidr: &str) -> ZeroResult<Cidr> {
+        let block = Cidr::parse(cidr)?;
+        let conn = self.db.lock();
+        for (other, other_block) in Self::blocks(&conn)? {
+            if other == id {
+                return Err(ZeroError::AlreadyExists(format!("Network {} already exists", id)));
+            }
+            if block.overlaps(&other_block) {
+                return Err(ZeroError::Validation(format!("CIDR block {} overlaps {} of network {}", block, other_block, other)));
+            }
+        }
+        conn.execute("INSERT INTO ipam_networks (id, cidr) VALUES (?1, ?2)", params![id, block.to_string()])
+            .map_err(db_error)?;
+        Ok(block)
+    }
+
+    /// Forget a network and every address handed out in it
+    pub fn remove_network(&self, id: &str) -> ZeroResult<()> {
+        let conn = self.db.lock();
+        conn.execute("DELETE FROM ipam_allocations WHERE network_id = ?1", params![id]).map_err(db_error)?;
+        conn.execute("DELETE FROM ipam_networks WHERE id = ?1", params![id]).map_err(db_error)?;
+        Ok(())
+    }
+
+    fn blocks(conn: &Connection) -> ZeroResult<Vec<(String, Cidr)>> {
+        let mut stmt = conn.prepare("SELECT id, cidr FROM ipam_networks ORDER BY id").map_err(db_error)?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(db_error)?
+            .collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)?;
+        rows.into_iter().map(|(id, cidr)| Ok((id, Cidr::parse(&cidr)?))).collect()
+    }
+
+    fn load(conn: &Connection, network_id: &str) -> ZeroResult<Subnet> {
+        let cidr: String = conn.query_row("SELECT cidr FROM ipam_networks WHERE id = ?1", params![network_id], |row| row.get(0))
+            .optional().map_err(db_error)?
+            .ok_or_else(|| ZeroError::NotFound(format!("Network not found: {}", network_id)))?;
+        let mut subnet = Subnet::new(Cidr::parse(&cidr)?);
+        for allocation in Self::allocations(conn, network_id)? {
+            if let Ok(ip) = allocation.address.parse() {
+                subnet.allocated.insert(ip, allocation.owner);
+            }
+        }
+        Ok(subnet)
+    }
+
+    fn allocations(conn: &Connection, network_id: &str) -> ZeroResult<Vec<Allocation>> {
+        let mut stmt = conn.prepare("SELECT address, owner, static FROM ipam_allocations WHERE network_id = ?1")
+            .map_err(db_error)?;
+        let mut allocations = stmt.query_map(params![network_id], |row| Ok(Allocation {
+                address: row.get(0)?,
+                owner: row.get(1)?,
+                is_static: row.get(2)?,
+            }))
+            .map_err(db_error)?
+            .collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)?;
+        allocations.sort_by_key(|a| a.address.parse::<Ipv4Addr>().ok());
+        Ok(allocations)
+    }
+
+    /// Block of a network with the addresses handed out in it
+    pub fn subnet(&self, network_id: &str) -> ZeroResult<Subnet> {
+        Self::load(&self.db.lock(), network_id)
+    }
+
+    /// Address of `owner` in a network: `requested` when given, else the one it already
+    /// holds or the lowest free one
+    pub fn allocate(&self, network_id: &str, owner: &str, requested: Option<Ipv4Addr>) -> ZeroResult<Ipv4Addr> {
+        let conn = self.db.lock();
+        let mut subnet = Self::load(&conn, network_id)?;
+        let held = subnet.address_of(owner);
+        let ip = match requested {
+            Some(ip) => subnet.reserve(owner, ip)?,
+            None => subnet.allocate(owner)?,
+        };
+        if held.is_none() {
+            conn.execute(
+                "INSERT INTO ipam_allocations (network_id, address, owner, static) VALUES (?1, ?2, ?3, ?4)",
+                params![network_id, ip.to_string(), owner, requested.is_some()],
+            ).map_err(db_error)?;
+        }
+        Ok(ip)
+    }
+
+    /// Return the address of `owner` in a network to the pool
+    pub fn release(&self, network_id: &str, owner: &str) -> ZeroResult<Option<Ipv4Addr>> {
+        let conn = self.db.lock();
+        let address = Self::load(&conn, network_id)?.address_of(owner);
+        conn.execute("DELETE FROM ipam_allocations WHERE network_id = ?1 AND owner = ?2", params![network_id, owner])
+            .map_err(db_error)?;
+        Ok(address)
+    }
+
+    /// Return every address of `owner` to the pool, as when the workload is deleted
+    pub fn release_all(&self, owner: &str) -> ZeroResult<usize> {
+        self.db.lock().execute("DELETE FROM ipam_allocations WHERE owner = ?1", params![owner]).map_err(db_error)
+    }
+
+    /// Networks `owner` is connected to and its address in each, by network
+    pub fn addresses_of(&self, owner: &str) -> ZeroResult<Vec<(String, Ipv4Addr)>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare("SELECT network_id, address FROM ipam_allocations WHERE owner = ?1 ORDER BY network_id")
+            .map_err(db_error)?;
+        let rows = stmt.query_map(params![owner], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(db_error)?
+            .collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)?;
+        rows.into_iter()
+            .map(|(network_id, address)| Ok((network_id, address.parse().map_err(|_| ZeroError::Internal(format!("Invalid address {} in IPAM", address)))?)))
+            .collect()
+    }
+
+    /// Block and allocations of a network
+    pub fn addresses(&self, network_id: &str) -> ZeroResult<NetworkAddresses> {
+        let conn = self.db.lock();
+        let subnet = Self::load(&conn, network_id)?;
+        let cidr = subnet.cidr();
+        Ok(NetworkAddresses {
+            id: network_id.to_string(),
+            cidr: cidr.to_string(),
+            gateway: cidr.gateway().to_string(),
+            free: cidr.workload_addresses().filter(|ip| !subnet.allocated.contains_key(ip)).count(),
+            allocations: Self::allocations(&conn, network_id)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cidr_parsing() {
+        let cidr = Cidr::parse("10.0.1.0/24").unwrap();
+        assert_eq!((cidr.gateway(), cidr.broadcast(), cidr.netmask()), (
+            Ipv4Addr::new(10, 0, 1, 1), Ipv4Addr::new(10, 0, 1, 255), Ipv4Addr::new(255, 255, 255, 0),
+        ));
+        assert!(cidr.contains(Ipv4Addr::new(10, 0, 1, 77)));
+        assert!(!cidr.contains(Ipv4Addr::new(10, 0, 2, 1)));
+        assert_eq!(cidr.to_string(), "10.0.1.0/24");
+
+        assert!(Cidr::parse("10.0.1.5/24").is_err());
+        assert!(Cidr::parse("10.0.1.0/31").is_err());
+        assert!(Cidr::parse("10.0.1.0").is_err());
+        assert!(Cidr::parse("fd00::/64").is_err());
+        assert_eq!(Cidr::parse_match("0.0.0.0/0").unwrap().to_string(), "0.0.0.0/0");
+        assert!(Cidr::parse_match("10.0.1.7/32").unwrap().contains(Ipv4Addr::new(10, 0, 1, 7)));
+    }
+
+    #[test]
+    fn test_subnet_allocation() {
+        // A /30 has two host addresses: the gateway and one workload
+        let mut subnet = Subnet::new(Cidr::parse("192.168.50.0/30").unwrap());
+        assert_eq!(subnet.allocate("web").unwrap(), Ipv4Addr::new(192, 168, 50, 2));
+        assert_eq!(subnet.allocate("web").unwrap(), Ipv4Addr::new(192, 168, 50, 2));
+        assert!(subnet.allocate("db").is_err());
+
+        assert_eq!(subnet.release("web"), Some(Ipv4Addr::new(192, 168, 50, 2)));
+        assert_eq!(subnet.allocate("db").unwrap(), Ipv4Addr::new(192, 168, 50, 2));
+        assert_eq!(subnet.allocations().collect::<Vec<_>>(), vec![(Ipv4Addr::new(192, 168, 50, 2), "db")]);
+    }
+
+    #[test]
+    fn test_static_reservation() {
+        let mut subnet = Subnet::new(Cidr::parse("10.0.1.0/24").unwrap());
+        assert_eq!(subnet.reserve("db", Ipv4Addr::new(10, 0, 1, 2)).unwrap(), Ipv4Addr::new(10, 0, 1, 2));
+        assert_eq!(subnet.allocate("web").unwrap(), Ipv4Addr::new(10, 0, 1, 3));
+
+        assert!(matches!(subnet.reserve("cache", Ipv4Addr::new(10, 0, 1, 2)), Err(ZeroError::AlreadyExists(_))));
+        assert!(subnet.reserve("db", Ipv4Addr::new(10, 0, 1, 9)).is_err());
+        for reserved in [Ipv4Addr::new(10, 0, 1, 0), Ipv4Addr::new(10, 0, 1, 1), Ipv4Addr::new(10, 0, 1, 255), Ipv4Addr::new(10, 0, 2, 4)] {
+            assert!(matches!(subnet.reserve("cache", reserved), Err(ZeroError::Validation(_))));
+        }
+    }
+
+    #[test]
+    fn test_ipam_persists_allocations_and_rejects_overlaps() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        let ipam = Ipam::new(Arc::new(Mutex::new(conn)));
+        ipam.add_network("frontend", "10.1.0.0/16").unwrap();
+        assert!(matches!(ipam.add_network("frontend", "10.9.0.0/24"), Err(ZeroError::AlreadyExists(_))));
+        assert!(matches!(ipam.add_network("inner", "10.1.4.0/24"), Err(ZeroError::Validation(_))));
+        assert!(matches!(ipam.add_network("outer", "10.0.0.0/8"), Err(ZeroError::Validation(_))));
+        ipam.add_network("backend", "10.2.0.0/24").unwrap();
+
+        assert_eq!(ipam.allocate("backend", "db", Some(Ipv4Addr::new(10, 2, 0, 10))).unwrap(), Ipv4Addr::new(10, 2, 0, 10));
+        assert_eq!(ipam.allocate("backend", "web", None).unwrap(), Ipv4Addr::new(10, 2, 0, 2));
+        assert_eq!(ipam.allocate("backend", "web", None).unwrap(), Ipv4Addr::new(10, 2, 0, 2));
+        assert!(ipam.allocate("backend", "cache", Some(Ipv4Addr::new(10, 2, 0, 2))).is_err());
+        assert!(matches!(ipam.allocate("missing", "web", None), Err(ZeroError::NotFound(_))));
+
+        let addresses = ipam.addresses("backend").unwrap();
+        assert_eq!((addresses.gateway.as_str(), addresses.free), ("10.2.0.1", 251));
+        assert_eq!(addresses.allocations.iter().map(|a| (a.owner.as_str(), a.is_static)).collect::<Vec<_>>(), vec![("web", false), ("db", true)]);
+
+        assert_eq!(ipam.release("backend", "web").unwrap(), Some(Ipv4Addr::new(10, 2, 0, 2)));
+        assert_eq!(ipam.release_all("db").unwrap(), 1);
+        ipam.remove_network("frontend").unwrap();
+        ipam.add_network("inner", "10.1.4.0/24").unwrap();
+    }
+}
@@ -1,13 +1,19 @@
 //! ZeroCloud Data Plane Engine
 
 pub mod driver;
+pub mod events;
+pub mod ipam;
+pub mod metrics;
+pub mod ports;
+pub mod security_groups;
 pub use rusqlite;
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
-use zero_control_spi::{ComputeDriver, StorageDriver, NetworkDriver};
+use zero_control_spi::{ComputeDriver, StorageDriver, NetworkDriver, NetworkStatus, PortMapping, SecurityGroup, SecurityRule, ZeroError, ZeroResult};
 
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
@@ -17,6 +23,73 @@ pub struct LocalNode {
     pub hostname: String,
     pub ip_address: String,
     pub status: String,
+    /// Matched by the node selectors and affinity rules of workloads
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    /// `key=value` or `key`; only workloads tolerating every taint are placed on the node
+    #[serde(default)]
+    pub taints: Vec<String>,
+    /// Docker endpoint the node runs its workloads on, such as `tcp://192.168.1.20:2375`
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+const NODE_COLUMNS: &str = "id, hostname, ip_address, status, labels, taints, endpoint";
+
+fn node_from_row(row: &rusqlite::Row) -> rusqlite::Result<LocalNode> {
+    let labels: String = row.get(4)?;
+    let taints: String = row.get(5)?;
+    Ok(LocalNode {
+        id: row.get(0)?,
+        hostname: row.get(1)?,
+        ip_address: row.get(2)?,
+        status: row.get(3)?,
+        labels: serde_json::from_str(&labels).unwrap_or_default(),
+        taints: serde_json::from_str(&taints).unwrap_or_default(),
+        endpoint: row.get(6)?,
+    })
+}
+
+/// Add the columns newer versions keep on nodes, for databases restored from older backups
+fn migrate_nodes(conn: &Connection) -> Result<()> {
+    let columns = conn.prepare("SELECT name FROM pragma_table_info('nodes')")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if !columns.iter().any(|c| c == "labels") {
+        conn.execute("ALTER TABLE nodes ADD COLUMN labels TEXT NOT NULL DEFAULT '{}'", [])?;
+    }
+    if !columns.iter().any(|c| c == "taints") {
+        conn.execute("ALTER TABLE nodes ADD COLUMN taints TEXT NOT NULL DEFAULT '[]'", [])?;
+    }
+    if !columns.iter().any(|c| c == "endpoint") {
+        conn.execute("ALTER TABLE nodes ADD COLUMN endpoint TEXT", [])?;
+    }
+    Ok(())
+}
+
+/// Network driver for workloads on this host: Linux bridges when the engine runs as root on
+/// Linux, otherwise the in-memory mock
+fn host_network() -> Arc<dyn NetworkDriver> {
+    #[cfg(target_os = "linux")]
+    if driver::LinuxNetworkDriver::is_available() {
+        return Arc::new(driver::LinuxNetworkDriver::new());
+    }
+    Arc::new(driver::MockNetworkDriver::new())
+}
+
+/// Storage for volumes under `zero-storage`: block volumes on Linux when `ZERO_VOLUME_FORMAT`
+/// is `raw` or `qcow2`, otherwise directories with a sparse block file
+fn host_storage() -> Result<Arc<dyn StorageDriver>> {
+    let base_path = std::env::current_dir()?.join("zero-storage");
+    #[cfg(target_os = "linux")]
+    if let Ok(format) = std::env::var("ZERO_VOLUME_FORMAT") {
+        let format: driver::BlockFormat = format.parse()?;
+        if !driver::BlockStorage::is_available(format) {
+            return Err(format!("{:?} volumes need losetup (raw) or qemu-img (qcow2) to be installed", format).into());
+        }
+        return Ok(Arc::new(driver::BlockStorage::new(base_path, format)));
+    }
+    Ok(Arc::new(driver::FileSystemStorage::new(base_path)))
 }
 
 pub struct ZeroEngine {
@@ -24,6 +97,18 @@ pub struct ZeroEngine {
     pub compute: Arc<dyn ComputeDriver>,
     pub storage: Arc<dyn StorageDriver>,
     pub network: Arc<dyn NetworkDriver>,
+    /// CIDR blocks of networks and the addresses of connected workloads
+    pub ipam: ipam::Ipam,
+    /// Firewall rules of networks
+    pub security_groups: security_groups::SecurityGroups,
+    /// Host ports the network driver forwards to workloads
+    pub published_ports: ports::PublishedPorts,
+    /// Compute driver of each node that has one, by node ID
+    node_drivers: Mutex<HashMap<String, Arc<dyn ComputeDriver>>>,
+    /// Lifecycle events of workloads, queues and volumes
+    pub events: events::EventBus,
+    /// Recent resource usage of each workload
+    pub metrics: metrics::WorkloadMetrics,
 }
 
 impl ZeroEngine {
@@ -35,16 +120,29 @@ impl ZeroEngine {
                 id TEXT PRIMARY KEY,
                 hostname TEXT NOT NULL,
                 ip_address TEXT NOT NULL,
-                status TEXT NOT NULL
+                status TEXT NOT NULL,
+                labels TEXT NOT NULL DEFAULT '{}',
+                taints TEXT NOT NULL DEFAULT '[]',
+                endpoint TEXT
             )",
             [],
         )?;
+        ipam::migrate(&conn)?;
+        security_groups::migrate(&conn)?;
+        ports::migrate(&conn)?;
 
+        let db = Arc::new(Mutex::new(conn));
         Ok(Self {
-            db: Arc::new(Mutex::new(conn)),
+            ipam: ipam::Ipam::new(db.clone()),
+            security_groups: security_groups::SecurityGroups::new(db.clone()),
+            published_ports: ports::PublishedPorts::new(db.clone()),
+            db,
             compute,
             storage,
             network,
+            node_drivers: Mutex::new(HashMap::new()),
+            events: events::EventBus::new(),
+            metrics: metrics::WorkloadMetrics::new(),
         })
     }
 
@@ -61,20 +159,63 @@ impl ZeroEngine {
     #[cfg(target_os = "linux")]
     pub fn linux_local() -> Result<Self> {
         let kvm = Arc::new(driver::KvmDriver::new());
-        let storage = Arc::new(driver::FileSystemStorage::new(std::env::current_dir()?.join("zero-storage")));
+        let storage = host_storage()?;
         let network = Arc::new(driver::LinuxNetworkDriver::new());
         Self::new(kvm, storage, network)
     }
 
+    /// Create a VM engine using Lima, on Virtualization.framework on macOS
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    pub fn lima_local() -> Result<Self> {
+        let lima = Arc::new(driver::LimaDriver::new());
+        let storage = Arc::new(driver::FileSystemStorage::new(std::env::current_dir()?.join("zero-storage")));
+        let network = Arc::new(driver::LimaNetworkDriver::new());
+        Self::new(lima, storage, network)
+    }
+
     /// Create a container-optimized local engine using Docker and local FS
     pub fn docker_local() -> Result<Self> {
         let docker = Arc::new(driver::DockerDriver::new().map_err(|e| e.to_string())?);
-        let storage = Arc::new(driver::FileSystemStorage::new(std::env::current_dir()?.join("zero-storage")));
-        let network = Arc::new(driver::MockNetworkDriver::new());
+        let storage = host_storage()?;
+        let network = host_network();
         Self::new(docker, storage, network)
     }
 
-    /// Explicitly use the OS-native hypervisor (Hyper-V on Windows, KVM on Linux)
+    /// Create a rootless container engine using Podman and local FS
+    pub fn podman_local() -> Result<Self> {
+        let podman = Arc::new(driver::PodmanDriver::new().map_err(|e| e.to_string())?);
+        let storage = host_storage()?;
+        let network = host_network();
+        Self::new(podman, storage, network)
+    }
+
+    /// Create a container engine using containerd (through nerdctl) and local FS
+    #[cfg(target_os = "linux")]
+    pub fn containerd_local() -> Result<Self> {
+        let containerd = Arc::new(driver::ContainerdDriver::new());
+        let storage = host_storage()?;
+        let network = host_network();
+        Self::new(containerd, storage, network)
+    }
+
+    /// Use the runtime named `docker`, `podman`, `containerd` (Linux only), `lima` (macOS and
+    /// Linux), `native` or `mock`
+    pub fn for_runtime(runtime: &str) -> Result<Self> {
+        match runtime {
+            "docker" => Self::docker_local(),
+            "podman" => Self::podman_local(),
+            #[cfg(target_os = "linux")]
+            "containerd" => Self::containerd_local(),
+            #[cfg(any(target_os = "macos", target_os = "linux"))]
+            "lima" => Self::lima_local(),
+            "native" => Self::native(),
+            "mock" => Self::mock_local(),
+            other => Err(format!("Unknown runtime {}, expected docker, podman, containerd, lima, native or mock", other).into()),
+        }
+    }
+
+    /// Explicitly use the OS-native hypervisor (Hyper-V on Windows, KVM on Linux,
+    /// Virtualization.framework through Lima on macOS)
     pub fn native() -> Result<Self> {
         #[cfg(target_os = "windows")]
         {
@@ -84,9 +225,13 @@ impl ZeroEngine {
         {
             Self::linux_local()
         }
-        #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+        #[cfg(target_os = "macos")]
+        {
+            Self::lima_local()
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
         {
-            // For now, fall back to mock if not on windows/linux
+            // For now, fall back to mock on other platforms
             let storage = Arc::new(driver::FileSystemStorage::new(std::env::current_dir()?.join("zero-storage")));
             let compute = Arc::new(driver::MockComputeDriver::new());
             let network = Arc::new(driver::MockNetworkDriver::new());
@@ -104,14 +249,21 @@ impl ZeroEngine {
 
     /// Automatically detect the environment and select the best available drivers.
     pub fn auto() -> Result<Self> {
-        let storage = Arc::new(driver::FileSystemStorage::new(
-            std::env::current_dir()?.join("zero-storage")
-        ));
+        let storage = host_storage()?;
 
-        // 1. Try Docker first as it's the most cross-platform (Windows/Linux/macOS)
-        if let Ok(docker) = driver::DockerDriver::new() {
-            let network = Arc::new(driver::MockNetworkDriver::new());
-            return Self::new(Arc::new(docker), storage, network);
+        // 1. Try container runtimes first as they're the most cross-platform (Windows/Linux/macOS):
+        //    Docker, then rootless Podman, then containerd
+        if driver::DockerDriver::is_available() {
+            if let Ok(docker) = driver::DockerDriver::new() {
+                return Self::new(Arc::new(docker), storage, host_network());
+            }
+        }
+        if let Ok(podman) = driver::PodmanDriver::new() {
+            return Self::new(Arc::new(podman), storage, host_network());
+        }
+        #[cfg(target_os = "linux")]
+        if driver::ContainerdDriver::is_available() {
+            return Self::new(Arc::new(driver::ContainerdDriver::new()), storage, host_network());
         }
 
         // 2. Fallback to OS-specific Native Hypervisors
@@ -122,10 +274,21 @@ impl ZeroEngine {
             }
             #[cfg(target_os = "linux")]
             {
-                // Future: return KvmDriver::new()
-                Arc::new(driver::MockComputeDriver::new())
+                if driver::KvmDriver::is_available() {
+                    Arc::new(driver::KvmDriver::new())
+                } else {
+                    Arc::new(driver::MockComputeDriver::new())
+                }
             }
-            #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+            #[cfg(target_os = "macos")]
+            {
+                if driver::LimaDriver::is_available() {
+                    Arc::new(driver::LimaDriver::new())
+                } else {
+                    Arc::new(driver::MockComputeDriver::new())
+                }
+            }
+            #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
             {
                 Arc::new(driver::MockComputeDriver::new())
             }
@@ -136,43 +299,241 @@ impl ZeroEngine {
             {
                 Arc::new(driver::HyperVNetworkDriver::new())
             }
-            #[cfg(not(target_os = "windows"))]
+            #[cfg(target_os = "macos")]
+            {
+                if driver::LimaDriver::is_available() {
+                    Arc::new(driver::LimaNetworkDriver::new())
+                } else {
+                    Arc::new(driver::MockNetworkDriver::new())
+                }
+            }
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
             {
-                Arc::new(driver::MockNetworkDriver::new())
+                host_network()
             }
         };
 
         Self::new(compute_driver, storage, network_driver)
     }
 
+    /// Copy the engine database to a SQLite file at `path`
+    pub fn backup_db(&self, path: &std::path::Path) -> Result<()> {
+        self.db.lock().backup(rusqlite::DatabaseName::Main, path, None)?;
+        Ok(())
+    }
+
+    /// Replace the engine database with the SQLite file at `path`
+    pub fn restore_db(&self, path: &std::path::Path) -> Result<()> {
+        let mut conn = self.db.lock();
+        conn.restore(rusqlite::DatabaseName::Main, path, None::<fn(rusqlite::backup::Progress)>)?;
+        migrate_nodes(&conn)?;
+        ipam::migrate(&conn)?;
+        security_groups::migrate(&conn)?;
+        ports::migrate(&conn)?;
+        Ok(())
+    }
+
+    /// Record a node without a compute driver of its own; workloads are never placed on it
     pub fn register_node(&self, hostname: &str, ip: &str) -> Result<LocalNode> {
-        let conn = self.db.lock();
+        self.register_labeled_node(hostname, ip, BTreeMap::new(), Vec::new(), None)
+    }
+
+    /// Record a node; with a Docker `endpoint` it takes the workloads placed on it
+    pub fn register_labeled_node(&self, hostname: &str, ip: &str, labels: BTreeMap<String, String>, taints: Vec<String>, endpoint: Option<&str>) -> Result<LocalNode> {
         let id = uuid::Uuid::new_v4().to_string();
-        conn.execute(
-            "INSERT INTO nodes (id, hostname, ip_address, status) VALUES (?1, ?2, ?3, ?4)",
-            params![id, hostname, ip, "Ready"],
+        if let Some(endpoint) = endpoint {
+            let driver = driver::DockerDriver::connect(endpoint).map_err(|e| e.to_string())?;
+            self.set_node_driver(&id, Arc::new(driver));
+        }
+        self.db.lock().execute(
+            "INSERT INTO nodes (id, hostname, ip_address, status, labels, taints, endpoint) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, hostname, ip, "Ready", serde_json::to_string(&labels)?, serde_json::to_string(&taints)?, endpoint],
         )?;
         Ok(LocalNode {
             id,
             hostname: hostname.to_string(),
             ip_address: ip.to_string(),
             status: "Ready".to_string(),
+            labels,
+            taints,
+            endpoint: endpoint.map(str::to_string),
         })
     }
 
+    /// Run the workloads placed on a node with `driver` rather than its endpoint
+    pub fn set_node_driver(&self, node_id: &str, driver: Arc<dyn ComputeDriver>) {
+        self.node_drivers.lock().insert(node_id.to_string(), driver);
+    }
+
+    /// Compute driver of a node, connecting to its endpoint on first use; `None` when the node
+    /// has no driver to run workloads on
+    pub fn node_compute(&self, node: &LocalNode) -> Option<Arc<dyn ComputeDriver>> {
+        let mut drivers = self.node_drivers.lock();
+        if let Some(driver) = drivers.get(&node.id) {
+            return Some(driver.clone());
+        }
+        let driver: Arc<dyn ComputeDriver> = Arc::new(driver::DockerDriver::connect(node.endpoint.as_deref()?).ok()?);
+        drivers.insert(node.id.clone(), driver.clone());
+        Some(driver)
+    }
+
+    /// Replace the labels and taints of a node, found by ID or hostname
+    pub fn set_node_labels(&self, node: &str, labels: BTreeMap<String, String>, taints: Vec<String>) -> Result<Option<LocalNode>> {
+        let conn = self.db.lock();
+        conn.execute(
+            "UPDATE nodes SET labels = ?2, taints = ?3 WHERE id = ?1 OR hostname = ?1",
+            params![node, serde_json::to_string(&labels)?, serde_json::to_string(&taints)?],
+        )?;
+        let node = conn.query_row(
+            &format!("SELECT {} FROM nodes WHERE id = ?1 OR hostname = ?1", NODE_COLUMNS),
+            params![node],
+            node_from_row,
+        ).optional()?;
+        Ok(node)
+    }
+
+    /// Remove the nodes registered under a hostname; returns how many were removed
+    pub fn deregister_node(&self, hostname: &str) -> Result<usize> {
+        let conn = self.db.lock();
+        let ids = conn.prepare("SELECT id FROM nodes WHERE hostname = ?1")?
+            .query_map(params![hostname], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        conn.execute("DELETE FROM nodes WHERE hostname = ?1", params![hostname])?;
+        let mut drivers = self.node_drivers.lock();
+        for id in &ids {
+            drivers.remove(id);
+        }
+        Ok(ids.len())
+    }
+
     pub fn list_nodes(&self) -> Result<Vec<LocalNode>> {
         let conn = self.db.lock();
-        let mut stmt = conn.prepare("SELECT id, hostname, ip_address, status FROM nodes")?;
-        let nodes = stmt.query_map([], |row| {
-            Ok(LocalNode {
-                id: row.get(0)?,
-                hostname: row.get(1)?,
-                ip_address: row.get(2)?,
-                status: row.get(3)?,
-            })
-        })?.collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM nodes", NODE_COLUMNS))?;
+        let nodes = stmt.query_map([], node_from_row)?.collect::<std::result::Result<Vec<_>, _>>()?;
         Ok(nodes)
     }
+
+    /// Create a network after IPAM accepts its CIDR block
+    pub async fn create_network(&self, id: &str, cidr: &str) -> ZeroResult<NetworkStatus> {
+        let block = self.ipam.add_network(id, cidr)?;
+        match self.network.create_network(id, &block.to_string()).await {
+            Ok(status) => Ok(status),
+            Err(e) => {
+                self.ipam.remove_network(id)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Delete a network, returning its block and addresses to IPAM
+    pub async fn delete_network(&self, id: &str) -> ZeroResult<()> {
+        self.ipam.subnet(id)?;
+        if !self.security_groups.list(id)?.is_empty() {
+            self.network.set_security_groups(id, &[]).await?;
+            self.security_groups.remove_network(id)?;
+        }
+        self.network.delete_network(id).await?;
+        self.published_ports.remove_network(id)?;
+        self.ipam.remove_network(id)
+    }
+
+    /// Connect a workload to a network with the static address `requested`, or the one IPAM
+    /// picks. A driver that leases addresses itself has its lease recorded instead.
+    pub async fn connect_workload(&self, workload_id: &str, network_id: &str, requested: Option<&str>) -> ZeroResult<String> {
+        let requested = requested
+            .map(|ip| ip.parse().map_err(|_| ZeroError::Validation(format!("Invalid IPv4 address: {}", ip))))
+            .transpose()?;
+        let held = self.ipam.subnet(network_id)?.address_of(workload_id);
+        let address = self.ipam.allocate(network_id, workload_id, requested)?;
+        let connected = match self.network.connect_workload_with_address(workload_id, network_id, &address.to_string()).await {
+            Ok(connected) => connected,
+            Err(e) => {
+                if held.is_none() {
+                    self.ipam.release(network_id, workload_id)?;
+                }
+                return Err(e);
+            }
+        };
+        if let Ok(leased) = connected.parse() {
+            if leased != address && requested.is_none() {
+                self.ipam.release(network_id, workload_id)?;
+                if self.ipam.allocate(network_id, workload_id, Some(leased)).is_err() {
+                    self.ipam.allocate(network_id, workload_id, Some(address))?;
+                }
+            }
+        }
+        // Drivers that filter per workload rather than per network cover the new workload
+        let groups = self.security_groups.list(network_id)?;
+        if !groups.is_empty() {
+            self.network.set_security_groups(network_id, &groups).await?;
+        }
+        // Ports published before the workload had an address are forwarded to its first one
+        let ports = self.published_ports.list(workload_id)?;
+        if !ports.is_empty() && self.published_ports.network(workload_id)?.is_none() {
+            let address = connected.parse().unwrap_or(address).to_string();
+            self.forward_ports(workload_id, network_id, &address, &ports).await?;
+        }
+        Ok(connected)
+    }
+
+    /// Publish host ports of a workload whose compute driver does not, picking free host
+    /// ports for those given as 0. They are forwarded to the workload's address once it has
+    /// one.
+    pub async fn publish_ports(&self, workload_id: &str, ports: &[PortMapping]) -> ZeroResult<Vec<PortMapping>> {
+        let reserved = self.published_ports.reserve(workload_id, ports)?;
+        if let Some((network_id, address)) = self.ipam.addresses_of(workload_id)?.into_iter().next() {
+            if let Err(e) = self.forward_ports(workload_id, &network_id, &address.to_string(), &reserved).await {
+                self.published_ports.release(workload_id)?;
+                return Err(e);
+            }
+        }
+        Ok(reserved)
+    }
+
+    /// Stop forwarding the host ports of a workload and free them
+    pub async fn unpublish_ports(&self, workload_id: &str) -> ZeroResult<()> {
+        let network_id = self.published_ports.network(workload_id)?;
+        let ports = self.published_ports.release(workload_id)?;
+        if let Some(network_id) = network_id {
+            self.network.unpublish_ports(workload_id, &network_id, &ports).await?;
+        }
+        Ok(())
+    }
+
+    async fn forward_ports(&self, workload_id: &str, network_id: &str, address: &str, ports: &[PortMapping]) -> ZeroResult<()> {
+        self.network.publish_ports(workload_id, network_id, address, ports).await?;
+        self.published_ports.set_network(workload_id, Some(network_id))
+    }
+
+    /// Add a security group to a network once the driver enforces it
+    pub async fn create_security_group(&self, network_id: &str, name: &str, rules: &[SecurityRule]) -> ZeroResult<SecurityGroup> {
+        self.ipam.subnet(network_id)?;
+        let group = self.security_groups.new_group(network_id, name, rules)?;
+        let mut groups = self.security_groups.list(network_id)?;
+        groups.push(group.clone());
+        self.network.set_security_groups(network_id, &groups).await?;
+        self.security_groups.save(&group)?;
+        Ok(group)
+    }
+
+    /// Replace the rules of a security group
+    pub async fn update_security_group(&self, network_id: &str, id: &str, rules: &[SecurityRule]) -> ZeroResult<SecurityGroup> {
+        let group = self.security_groups.with_rules(&self.security_groups.get(network_id, id)?, rules)?;
+        let groups: Vec<SecurityGroup> = self.security_groups.list(network_id)?.into_iter()
+            .map(|g| if g.id == id { group.clone() } else { g })
+            .collect();
+        self.network.set_security_groups(network_id, &groups).await?;
+        self.security_groups.save(&group)?;
+        Ok(group)
+    }
+
+    /// Remove a security group from a network; without groups, all traffic is allowed
+    pub async fn delete_security_group(&self, network_id: &str, id: &str) -> ZeroResult<()> {
+        self.security_groups.get(network_id, id)?;
+        let groups: Vec<SecurityGroup> = self.security_groups.list(network_id)?.into_iter().filter(|g| g.id != id).collect();
+        self.network.set_security_groups(network_id, &groups).await?;
+        self.security_groups.delete(network_id, id)
+    }
 }
 
 #[cfg(test)]
@@ -201,6 +562,13 @@ mod tests {
         let nodes = engine.list_nodes().unwrap();
         assert_eq!(nodes.len(), 1);
         assert_eq!(nodes[0].hostname, "test-host");
+        assert!(nodes[0].labels.is_empty());
+
+        let labels = BTreeMap::from([("disk".to_string(), "ssd".to_string())]);
+        let node = engine.set_node_labels("test-host", labels.clone(), vec!["gpu=true".into()]).unwrap().unwrap();
+        assert_eq!(node.labels, labels);
+        assert_eq!(engine.list_nodes().unwrap()[0].taints, vec!["gpu=true".to_string()]);
+        assert!(engine.set_node_labels("missing", BTreeMap::new(), Vec::new()).unwrap().is_none());
     }
 
     #[tokio::test]
@@ -0,0 +1,172 @@
+//! Resource usage of workloads over time
+//!
+//! The control plane samples each workload's usage periodically. The latest
+//! [`RETAINED_SAMPLES`] samples of a workload are kept in memory, the oldest dropped first, and
+//! are lost when the server restarts. Queries aggregate them into datapoints of a period.
+
+use chrono::{DateTime, TimeZone, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use zero_control_spi::WorkloadUsage;
+
+/// Samples kept per workload: an hour at the control plane's 5 second sampling interval
+pub const RETAINED_SAMPLES: usize = 720;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UsageSample {
+    pub time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub usage: WorkloadUsage,
+}
+
+/// Usage of a workload over one period
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Datapoint {
+    /// Start of the period, a multiple of its length since the Unix epoch
+    pub timestamp: DateTime<Utc>,
+    pub samples: usize,
+    pub cpu_percent_avg: f32,
+    pub cpu_percent_max: f32,
+    pub memory_used_mb_avg: u64,
+    pub memory_used_mb_max: u64,
+    pub memory_limit_mb: u64,
+    /// Bytes received during the period
+    pub network_rx_bytes: u64,
+    /// Bytes sent during the period
+    pub network_tx_bytes: u64,
+}
+
+/// Bytes transferred between two readings of a counter; a counter that went down was reset
+/// by a restart of the workload and counts from 0
+fn transferred(previous: Option<u64>, current: u64) -> u64 {
+    match previous {
+        Some(previous) if previous <= current => current - previous,
+        Some(_) => current,
+        None => 0,
+    }
+}
+
+pub struct WorkloadMetrics {
+    capacity: usize,
+    samples: Mutex<HashMap<String, VecDeque<UsageSample>>>,
+}
+
+impl WorkloadMetrics {
+    pub fn new() -> Self {
+        Self::with_capacity(RETAINED_SAMPLES)
+    }
+
+    /// Keep the latest `capacity` samples of each workload
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity: capacity.max(1), samples: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn record(&self, workload_id: &str, time: DateTime<Utc>, usage: WorkloadUsage) {
+        let mut samples = self.samples.lock();
+        let samples = samples.entry(workload_id.to_string()).or_default();
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(UsageSample { time, usage });
+    }
+
+    /// Drop the samples of a deleted workload
+    pub fn forget(&self, workload_id: &str) {
+        self.samples.lock().remove(workload_id);
+    }
+
+    /// Drop the samples of every workload not in `workload_ids`
+    pub fn retain(&self, workload_ids: &HashSet<String>) {
+        self.samples.lock().retain(|id, _| workload_ids.contains(id));
+    }
+
+    /// Retained samples of a workload, oldest first
+    pub fn samples(&self, workload_id: &str) -> Vec<UsageSample> {
+        self.samples.lock().get(workload_id).map(|samples| samples.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// Retained samples of a workload aggregated into periods of `period_secs`, oldest first;
+    /// periods without samples are left out
+    pub fn datapoints(&self, workload_id: &str, period_secs: u32) -> Vec<Datapoint> {
+        let period = i64::from(period_secs.max(1));
+        let mut datapoints: Vec<Datapoint> = Vec::new();
+        let mut previous: Option<WorkloadUsage> = None;
+        for sample in self.samples(workload_id) {
+            let start = sample.time.timestamp().div_euclid(period) * period;
+            let timestamp = Utc.timestamp_opt(start, 0).single().unwrap_or(sample.time);
+            let usage = sample.usage;
+            let rx = transferred(previous.map(|p| p.network_rx_bytes), usage.network_rx_bytes);
+            let tx = transferred(previous.map(|p| p.network_tx_bytes), usage.network_tx_bytes);
+            previous = Some(usage);
+
+            match datapoints.last_mut().filter(|datapoint| datapoint.timestamp == timestamp) {
+                Some(datapoint) => {
+                    let n = datapoint.samples as f32;
+                    datapoint.cpu_percent_avg = (datapoint.cpu_percent_avg * n + usage.cpu_percent) / (n + 1.0);
+                    datapoint.cpu_percent_max = datapoint.cpu_percent_max.max(usage.cpu_percent);
+                    let n = datapoint.samples as u64;
+                    datapoint.memory_used_mb_avg = (datapoint.memory_used_mb_avg * n + usage.memory_used_mb) / (n + 1);
+                    datapoint.memory_used_mb_max = datapoint.memory_used_mb_max.max(usage.memory_used_mb);
+                    datapoint.memory_limit_mb = usage.memory_limit_mb;
+                    datapoint.network_rx_bytes += rx;
+                    datapoint.network_tx_bytes += tx;
+                    datapoint.samples += 1;
+                }
+                None => datapoints.push(Datapoint {
+                    timestamp,
+                    samples: 1,
+                    cpu_percent_avg: usage.cpu_percent,
+                    cpu_percent_max: usage.cpu_percent,
+                    memory_used_mb_avg: usage.memory_used_mb,
+                    memory_used_mb_max: usage.memory_used_mb,
+                    memory_limit_mb: usage.memory_limit_mb,
+                    network_rx_bytes: rx,
+                    network_tx_bytes: tx,
+                }),
+            }
+        }
+        datapoints
+    }
+}
+
+impl Default for WorkloadMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(cpu_percent: f32, memory_used_mb: u64, network_rx_bytes: u64) -> WorkloadUsage {
+        WorkloadUsage { cpu_percent, memory_used_mb, memory_limit_mb: 512, network_rx_bytes, network_tx_bytes: 0 }
+    }
+
+    #[test]
+    fn test_samples_are_aggregated_by_period() {
+        let metrics = WorkloadMetrics::with_capacity(4);
+        let at = |secs: i64| Utc.timestamp_opt(1_700_000_040 + secs, 0).unwrap();
+        metrics.record("web", at(0), usage(10.0, 100, 1000));
+        metrics.record("web", at(20), usage(30.0, 200, 1500));
+        metrics.record("web", at(40), usage(50.0, 300, 1800));
+        // The workload restarted, so its counters did too
+        metrics.record("web", at(60), usage(20.0, 100, 100));
+        metrics.record("web", at(80), usage(40.0, 100, 400));
+
+        // The oldest sample made room for the newest
+        assert_eq!(metrics.samples("web").len(), 4);
+        let datapoints = metrics.datapoints("web", 60);
+        assert_eq!(datapoints.len(), 2);
+        assert_eq!(datapoints[0].timestamp, Utc.timestamp_opt(1_700_000_040, 0).unwrap());
+        assert_eq!((datapoints[0].samples, datapoints[0].cpu_percent_avg, datapoints[0].cpu_percent_max), (2, 40.0, 50.0));
+        assert_eq!((datapoints[0].memory_used_mb_avg, datapoints[0].memory_used_mb_max), (250, 300));
+        assert_eq!(datapoints[0].network_rx_bytes, 300);
+        assert_eq!((datapoints[1].timestamp, datapoints[1].samples), (Utc.timestamp_opt(1_700_000_100, 0).unwrap(), 2));
+        assert_eq!(datapoints[1].network_rx_bytes, 400);
+
+        metrics.retain(&HashSet::from(["db".to_string()]));
+        assert!(metrics.datapoints("web", 60).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! Host ports published by the engine
+//!
+//! Compute drivers that cannot publish ports themselves leave it to the network driver, which
+//! forwards host ports to a workload's address. [`PublishedPorts`] keeps those ports in the
+//! engine database, so each host port goes to one workload, and the ports of a workload that
+//! is not connected yet are forwarded once it is.
+
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::net::{TcpListener, UdpSocket};
+use std::sync::Arc;
+use zero_control_spi::{PortMapping, PortProtocol, ZeroError, ZeroResult};
+
+fn db_error(e: rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+/// Create the published port table, for new engines and databases restored from older backups
+pub(crate) fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS published_ports (
+            host_port INTEGER NOT NULL,
+            protocol TEXT NOT NULL,
+            workload_id TEXT NOT NULL,
+            container_port INTEGER NOT NULL,
+            network_id TEXT,
+            PRIMARY KEY (host_port, protocol)
+        );",
+    )
+}
+
+/// A host port no process is bound to, as the OS picks for port 0
+pub fn free_host_port(protocol: PortProtocol) -> ZeroResult<u16> {
+    let port = match protocol {
+        PortProtocol::Tcp => TcpListener::bind("0.0.0.0:0").and_then(|listener| listener.local_addr()),
+        PortProtocol::Udp => UdpSocket::bind("0.0.0.0:0").and_then(|socket| socket.local_addr()),
+    };
+    port.map(|addr| addr.port())
+        .map_err(|e| ZeroError::Internal(format!("Failed to find a free {} port: {}", protocol.name(), e)))
+}
+
+fn protocol_of(name: &str) -> PortProtocol {
+    if name == "udp" { PortProtocol::Udp } else { PortProtocol::Tcp }
+}
+
+/// Host ports the engine publishes for workloads
+#[derive(Clone)]
+pub struct PublishedPorts {
+    db: Arc<Mutex<Connection>>,
+}
+
+impl PublishedPorts {
+    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+        Self { db }
+    }
+
+    /// Reserve host ports for a workload, picking free ones for ports given as 0. Fails
+    /// without reserving any when a port is published already.
+    pub fn reserve(&self, workload_id: &str, ports: &[PortMapping]) -> ZeroResult<Vec<PortMapping>> {
+        let mut conn = self.db.lock();
+        let tx = conn.transaction().map_err(db_error)?;
+        let mut reserved = Vec::with_capacity(ports.len());
+        for port in ports {
+            let taken = |host_port: u16| tx
+                .query_row("SELECT workload_id FROM published_ports WHERE host_port = ?1 AND protocol = ?2",
+                    params![host_port, port.protocol.name()], |row| row.get::<_, String>(0))
+                .optional().map_err(db_error);
+            let host_port = if port.host_port == 0 {
+                // The OS only knows the ports bound now, not those reserved for other workloads
+                let mut host_port = free_host_port(port.protocol)?;
+                while taken(host_port)?.is_some() {
+                    host_port = free_host_port(port.protocol)?;
+                }
+                host_port
+            } else {
+                if let Some(owner) = taken(port.host_port)? {
+                    return Err(ZeroError::AlreadyExists(format!(
+                        "Host port {}/{} is already published by workload {}", port.host_port, port.protocol.name(), owner
+                    )));
+                }
+                port.host_port
+            };
+            tx.execute(
+                "INSERT INTO published_ports (host_port, protocol, workload_id, container_port) VALUES (?1, ?2, ?3, ?4)",
+                params![host_port, port.protocol.name(), workload_id, port.container_port],
+            ).map_err(db_error)?;
+            reserved.push(PortMapping { host_port, ..*port });
+        }
+        tx.commit().map_err(db_error)?;
+        Ok(reserved)
+    }
+
+    /// Published ports of a workload, by host port
+    pub fn list(&self, workload_id: &str) -> ZeroResult<Vec<PortMapping>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare("SELECT host_port, container_port, protocol FROM published_ports WHERE workload_id = ?1 ORDER BY host_port, protocol")
+            .map_err(db_error)?;
+        let ports = stmt.query_map(params![workload_id], |row| Ok(PortMapping {
+            host_port: row.get(0)?,
+            container_port: row.get(1)?,
+            protocol: protocol_of(&row.get::<_, String>(2)?),
+        })).map_err(db_error)?;
+        ports.collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)
+    }
+
+    /// Network the ports of a workload are forwarded into, once they are
+    pub fn network(&self, workload_id: &str) -> ZeroResult<Option<String>> {
+        let network: Option<Option<String>> = self.db.lock()
+            .query_row("SELECT network_id FROM published_ports WHERE workload_id = ?1 LIMIT 1", params![workload_id], |row| row.get(0))
+            .optional().map_err(db_error)?;
+        Ok(network.flatten())
+    }
+
+    /// Record the network the ports of a workload are forwarded into
+    pub fn set_network(&self, workload_id: &str, network_id: Option<&str>) -> ZeroResult<()> {
+        self.db.lock()
+            .execute("UPDATE published_ports SET network_id = ?1 WHERE workload_id = ?2", params![network_id, workload_id])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Forget that ports are forwarded into a network, as when the network is deleted; they
+    /// are forwarded again when their workload connects to another one
+    pub fn remove_network(&self, network_id: &str) -> ZeroResult<()> {
+        self.db.lock()
+            .execute("UPDATE published_ports SET network_id = NULL WHERE network_id = ?1", params![network_id])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Free the host ports of a workload, returning them
+    pub fn release(&self, workload_id: &str) -> ZeroResult<Vec<PortMapping>> {
+        let ports = self.list(workload_id)?;
+        self.db.lock().execute("DELETE FROM published_ports WHERE workload_id = ?1", params![workload_id]).map_err(db_error)?;
+        Ok(ports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(host_port: u16, container_port: u16, protocol: PortProtocol) -> PortMapping {
+        PortMapping { host_port, container_port, protocol }
+    }
+
+    #[test]
+    fn test_ports_are_reserved_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        let published = PublishedPorts::new(Arc::new(Mutex::new(conn)));
+
+        let web = published.reserve("web", &[port(18080, 80, PortProtocol::Tcp), port(0, 53, PortProtocol::Udp)]).unwrap();
+        assert_eq!(web[0], port(18080, 80, PortProtocol::Tcp));
+        assert_ne!(web[1].host_port, 0);
+        assert_eq!(published.list("web").unwrap().len(), 2);
+
+        // The same host port is free for the other protocol only
+        assert!(matches!(published.reserve("api", &[port(9000, 9000, PortProtocol::Tcp), port(18080, 80, PortProtocol::Tcp)]), Err(ZeroError::AlreadyExists(_))));
+        assert!(published.list("api").unwrap().is_empty());
+        published.reserve("api", &[port(18080, 8080, PortProtocol::Udp)]).unwrap();
+
+        assert_eq!(published.network("web").unwrap(), None);
+        published.set_network("web", Some("backend")).unwrap();
+        assert_eq!(published.network("web").unwrap().as_deref(), Some("backend"));
+
+        assert_eq!(published.release("web").unwrap().len(), 2);
+        assert!(published.list("web").unwrap().is_empty());
+        published.reserve("api", &[port(18080, 80, PortProtocol::Tcp)]).unwrap();
+    }
+}
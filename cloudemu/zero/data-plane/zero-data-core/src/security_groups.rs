@@ -0,0 +1,195 @@
+//! Security groups of workload networks
+//!
+//! [`SecurityGroups`] checks the rules of groups and keeps the groups of every network in the
+//! engine database. The engine hands the network driver the complete set of a network's
+//! groups whenever one changes, so drivers rebuild their firewall rather than patch it.
+
+use crate::ipam::Cidr;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::Arc;
+use zero_control_spi::{RuleDirection, RuleProtocol, SecurityGroup, SecurityRule, ZeroError, ZeroResult};
+
+fn db_error(e: rusqlite::Error) -> ZeroError {
+    ZeroError::Internal(e.to_string())
+}
+
+/// Create the security group table, for new engines and databases restored from older backups
+pub(crate) fn migrate(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS security_groups (
+            id TEXT PRIMARY KEY,
+            network_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            rules TEXT NOT NULL,
+            UNIQUE (network_id, name)
+        );",
+    )
+}
+
+/// Rule with its block in canonical form; a bare address is a `/32` block
+pub fn validate_rule(rule: &SecurityRule) -> ZeroResult<SecurityRule> {
+    let cidr = if rule.cidr.contains('/') { rule.cidr.clone() } else { format!("{}/32", rule.cidr) };
+    let cidr = Cidr::parse_match(&cidr)?.to_string();
+    match (rule.protocol, rule.from_port, rule.to_port) {
+        (RuleProtocol::Icmp | RuleProtocol::All, Some(_), _) | (RuleProtocol::Icmp | RuleProtocol::All, _, Some(_)) => {
+            return Err(ZeroError::Validation(format!("Ports apply to TCP and UDP rules only, not {:?}", rule.protocol)));
+        }
+        (_, None, Some(_)) => return Err(ZeroError::Validation("to_port needs a from_port".into())),
+        (_, Some(from), Some(to)) if to < from => {
+            return Err(ZeroError::Validation(format!("Port range {}-{} is reversed", from, to)));
+        }
+        _ => {}
+    }
+    Ok(SecurityRule { cidr, ..rule.clone() })
+}
+
+/// Checked rules of a group. A group without egress rules allows all egress, as a new
+/// security group does in AWS.
+fn checked_rules(rules: &[SecurityRule]) -> ZeroResult<Vec<SecurityRule>> {
+    let mut checked = rules.iter().map(validate_rule).collect::<ZeroResult<Vec<_>>>()?;
+    if !checked.iter().any(|rule| rule.direction == RuleDirection::Egress) {
+        checked.push(SecurityRule {
+            direction: RuleDirection::Egress,
+            protocol: RuleProtocol::All,
+            from_port: None,
+            to_port: None,
+            cidr: "0.0.0.0/0".into(),
+        });
+    }
+    Ok(checked)
+}
+
+/// Security groups of an engine's networks
+#[derive(Clone)]
+pub struct SecurityGroups {
+    db: Arc<Mutex<Connection>>,
+}
+
+impl SecurityGroups {
+    pub fn new(db: Arc<Mutex<Connection>>) -> Self {
+        Self { db }
+    }
+
+    /// A new group with checked rules; it is stored by [`SecurityGroups::save`]
+    pub fn new_group(&self, network_id: &str, name: &str, rules: &[SecurityRule]) -> ZeroResult<SecurityGroup> {
+        if name.is_empty() {
+            return Err(ZeroError::Validation("Security group name must not be empty".into()));
+        }
+        if self.list(network_id)?.iter().any(|group| group.name == name) {
+            return Err(ZeroError::AlreadyExists(format!("Security group {} already exists in network {}", name, network_id)));
+        }
+        Ok(SecurityGroup {
+            id: format!("sg-{}", &uuid::Uuid::new_v4().simple().to_string()[..17]),
+            network_id: network_id.to_string(),
+            name: name.to_string(),
+            rules: checked_rules(rules)?,
+        })
+    }
+
+    /// `group` with its rules replaced by checked `rules`
+    pub fn with_rules(&self, group: &SecurityGroup, rules: &[SecurityRule]) -> ZeroResult<SecurityGroup> {
+        Ok(SecurityGroup { rules: checked_rules(rules)?, ..group.clone() })
+    }
+
+    /// Store a group, replacing the one with its ID
+    pub fn save(&self, group: &SecurityGroup) -> ZeroResult<()> {
+        let rules = serde_json::to_string(&group.rules).map_err(|e| ZeroError::Internal(e.to_string()))?;
+        self.db.lock().execute(
+            "INSERT OR REPLACE INTO security_groups (id, network_id, name, rules) VALUES (?1, ?2, ?3, ?4)",
+            params![group.id, group.network_id, group.name, rules],
+        ).map_err(db_error)?;
+        Ok(())
+    }
+
+    pub fn get(&self, network_id: &str, id: &str) -> ZeroResult<SecurityGroup> {
+        let row: Option<(String, String)> = self.db.lock()
+            .query_row("SELECT name, rules FROM security_groups WHERE network_id = ?1 AND id = ?2", params![network_id, id],
+                |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional().map_err(db_error)?;
+        let (name, rules) = row.ok_or_else(|| ZeroError::NotFound(format!("Security group {} not found in network {}", id, network_id)))?;
+        Ok(SecurityGroup {
+            id: id.to_string(),
+            network_id: network_id.to_string(),
+            name,
+            rules: serde_json::from_str(&rules).map_err(|e| ZeroError::Internal(e.to_string()))?,
+        })
+    }
+
+    /// Groups of a network, by name
+    pub fn list(&self, network_id: &str) -> ZeroResult<Vec<SecurityGroup>> {
+        let conn = self.db.lock();
+        let mut stmt = conn.prepare("SELECT id, name, rules FROM security_groups WHERE network_id = ?1 ORDER BY name")
+            .map_err(db_error)?;
+        let rows = stmt.query_map(params![network_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)))
+            .map_err(db_error)?
+            .collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)?;
+        rows.into_iter()
+            .map(|(id, name, rules)| Ok(SecurityGroup {
+                id,
+                network_id: network_id.to_string(),
+                name,
+                rules: serde_json::from_str(&rules).map_err(|e| ZeroError::Internal(e.to_string()))?,
+            }))
+            .collect()
+    }
+
+    pub fn delete(&self, network_id: &str, id: &str) -> ZeroResult<()> {
+        let deleted = self.db.lock()
+            .execute("DELETE FROM security_groups WHERE network_id = ?1 AND id = ?2", params![network_id, id])
+            .map_err(db_error)?;
+        if deleted == 0 {
+            return Err(ZeroError::NotFound(format!("Security group {} not found in network {}", id, network_id)));
+        }
+        Ok(())
+    }
+
+    /// Forget every group of a network, as when the network is deleted
+    pub fn remove_network(&self, network_id: &str) -> ZeroResult<()> {
+        self.db.lock().execute("DELETE FROM security_groups WHERE network_id = ?1", params![network_id]).map_err(db_error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(direction: RuleDirection, protocol: RuleProtocol, ports: Option<(u16, u16)>, cidr: &str) -> SecurityRule {
+        SecurityRule { direction, protocol, from_port: ports.map(|p| p.0), to_port: ports.map(|p| p.1), cidr: cidr.into() }
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let web = validate_rule(&rule(RuleDirection::Ingress, RuleProtocol::Tcp, Some((80, 443)), "10.0.0.0/8")).unwrap();
+        assert_eq!(web.ports(), Some((80, 443)));
+        assert_eq!(validate_rule(&rule(RuleDirection::Ingress, RuleProtocol::Udp, None, "192.168.1.5")).unwrap().cidr, "192.168.1.5/32");
+
+        assert!(validate_rule(&rule(RuleDirection::Ingress, RuleProtocol::Icmp, Some((8, 8)), "0.0.0.0/0")).is_err());
+        assert!(validate_rule(&rule(RuleDirection::Ingress, RuleProtocol::Tcp, Some((443, 80)), "0.0.0.0/0")).is_err());
+        assert!(validate_rule(&rule(RuleDirection::Egress, RuleProtocol::Tcp, None, "10.0.0.1/8")).is_err());
+    }
+
+    #[test]
+    fn test_groups_persist_with_default_egress() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn).unwrap();
+        let groups = SecurityGroups::new(Arc::new(Mutex::new(conn)));
+
+        let web = groups.new_group("backend", "web", &[rule(RuleDirection::Ingress, RuleProtocol::Tcp, Some((80, 80)), "0.0.0.0/0")]).unwrap();
+        assert!(web.id.starts_with("sg-"));
+        assert_eq!(web.rules[1], rule(RuleDirection::Egress, RuleProtocol::All, None, "0.0.0.0/0"));
+        groups.save(&web).unwrap();
+        assert!(matches!(groups.new_group("backend", "web", &[]), Err(ZeroError::AlreadyExists(_))));
+
+        let locked = groups.with_rules(&web, &[rule(RuleDirection::Egress, RuleProtocol::Udp, Some((53, 53)), "10.0.0.2")]).unwrap();
+        assert_eq!(locked.rules.len(), 1);
+        groups.save(&locked).unwrap();
+        assert_eq!(groups.get("backend", &web.id).unwrap(), locked);
+        assert_eq!(groups.list("backend").unwrap(), vec![locked]);
+        assert!(groups.list("frontend").unwrap().is_empty());
+
+        groups.delete("backend", &web.id).unwrap();
+        assert!(matches!(groups.delete("backend", &web.id), Err(ZeroError::NotFound(_))));
+    }
+}
@@ -0,0 +1,115 @@
+//! Access key credentials and request signing
+//!
+//! Signed requests carry an `X-Zero-Date` header and
+//! `Authorization: ZERO-HMAC-SHA256 Credential=<key id>, SignedHeaders=x-zero-date, Signature=<hex>`,
+//! where the signature is an HMAC-SHA256, keyed with the secret access key, of
+//! the algorithm, date, method, path and hex SHA-256 of the body joined by newlines.
+//! Temporary credentials from `assume_role` also send their session token in `X-Zero-Security-Token`.
+//! Streamed bodies cannot be hashed up front: they send `X-Zero-Content-Sha256: UNSIGNED-PAYLOAD`
+//! and sign that marker in place of the body hash.
+
+use crate::ZeroSdkError;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+const SIGNING_ALGORITHM: &str = "ZERO-HMAC-SHA256";
+const DATE_HEADER: &str = "x-zero-date";
+const SECURITY_TOKEN_HEADER: &str = "x-zero-security-token";
+const CONTENT_SHA256_HEADER: &str = "x-zero-content-sha256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// Access key pair of a ZeroCloud user, or temporary role session credentials
+#[derive(Clone)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Set for temporary credentials issued by `assume_role`
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self { access_key_id: access_key_id.into(), secret_access_key: secret_access_key.into(), session_token: None }
+    }
+
+    /// Attach the session token of temporary credentials
+    pub fn with_session_token(mut self, session_token: impl Into<String>) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"** redacted **")
+            .field("session_token", &self.session_token.as_ref().map(|_| "** redacted **"))
+            .finish()
+    }
+}
+
+/// Source of the credentials used to sign each request
+pub trait ProvideCredentials: Send + Sync {
+    /// `None` sends the request unsigned
+    fn provide_credentials(&self) -> Option<Credentials>;
+}
+
+impl ProvideCredentials for Credentials {
+    fn provide_credentials(&self) -> Option<Credentials> {
+        Some(self.clone())
+    }
+}
+
+/// Reads `ZERO_ACCESS_KEY_ID`, `ZERO_SECRET_ACCESS_KEY` and the optional `ZERO_SESSION_TOKEN` on every request
+#[derive(Debug, Clone, Default)]
+pub struct EnvironmentCredentials;
+
+impl ProvideCredentials for EnvironmentCredentials {
+    fn provide_credentials(&self) -> Option<Credentials> {
+        let access_key_id = std::env::var("ZERO_ACCESS_KEY_ID").ok()?;
+        let secret_access_key = std::env::var("ZERO_SECRET_ACCESS_KEY").ok()?;
+        let credentials = Credentials::new(access_key_id, secret_access_key);
+        Some(match std::env::var("ZERO_SESSION_TOKEN") {
+            Ok(token) => credentials.with_session_token(token),
+            Err(_) => credentials,
+        })
+    }
+}
+
+/// Add the date and `Authorization` headers to a built request
+pub(crate) fn sign(request: &mut reqwest::Request, credentials: &Credentials) -> Result<(), ZeroSdkError> {
+    let date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let payload_hash = match request.body() {
+        Some(body) => match body.as_bytes() {
+            Some(bytes) => hex::encode(Sha256::digest(bytes)),
+            None => {
+                request.headers_mut().insert(CONTENT_SHA256_HEADER, reqwest::header::HeaderValue::from_static(UNSIGNED_PAYLOAD));
+                UNSIGNED_PAYLOAD.to_string()
+            }
+        },
+        None => hex::encode(Sha256::digest(b"")),
+    };
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}\n{}",
+        SIGNING_ALGORITHM, date, request.method().as_str(), &request.url()[url::Position::BeforePath..url::Position::AfterQuery], payload_hash
+    );
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(credentials.secret_access_key.as_bytes())
+        .map_err(|e| ZeroSdkError::Internal(e.to_string()))?;
+    mac.update(string_to_sign.as_bytes());
+    let authorization = format!(
+        "{} Credential={}, SignedHeaders={}, Signature={}",
+        SIGNING_ALGORITHM, credentials.access_key_id, DATE_HEADER, hex::encode(mac.finalize().into_bytes())
+    );
+
+    let headers = request.headers_mut();
+    headers.insert(DATE_HEADER, date.parse().map_err(|_| ZeroSdkError::Internal("Invalid date header".into()))?);
+    headers.insert(reqwest::header::AUTHORIZATION, authorization.parse()
+        .map_err(|_| ZeroSdkError::Internal("Invalid access key ID".into()))?);
+    if let Some(token) = &credentials.session_token {
+        headers.insert(SECURITY_TOKEN_HEADER, token.parse()
+            .map_err(|_| ZeroSdkError::Internal("Invalid session token".into()))?);
+    }
+    Ok(())
+}
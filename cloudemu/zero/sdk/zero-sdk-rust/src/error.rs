@@ -1,14 +1,20 @@
 use thiserror::Error;
+use serde::Deserialize;
 
 #[derive(Debug, Error)]
 pub enum ZeroSdkError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
-    #[error("API error (Status: {status}): {body}")]
+    #[error("API error {code} (Status: {status}): {message}")]
     Api {
         status: reqwest::StatusCode,
-        body: String,
+        code: ErrorCode,
+        message: String,
+        /// Id the server logged the request under
+        request_id: Option<String>,
+        /// Every invalid field of a `ValidationError` raised by schema validation
+        fields: Vec<FieldError>,
     },
 
     #[error("Serialization error: {0}")]
@@ -17,3 +23,123 @@ pub enum ZeroSdkError {
     #[error("Internal SDK error: {0}")]
     Internal(String),
 }
+
+impl ZeroSdkError {
+    /// Error code reported by the API, if the error came from the API
+    pub fn code(&self) -> Option<&ErrorCode> {
+        match self {
+            ZeroSdkError::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Invalid request fields reported by the API; empty unless the body failed validation
+    pub fn fields(&self) -> &[FieldError] {
+        match self {
+            ZeroSdkError::Api { fields, .. } => fields,
+            _ => &[],
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code() == Some(&ErrorCode::NotFound)
+    }
+
+    /// Build an API error from a failed response's status and body
+    pub(crate) fn from_response(status: reqwest::StatusCode, body: &str) -> Self {
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            code: String,
+            message: String,
+            request_id: Option<String>,
+            #[serde(default)]
+            fields: Vec<FieldError>,
+        }
+
+        match serde_json::from_str::<ErrorBody>(body) {
+            Ok(error) => ZeroSdkError::Api {
+                status,
+                code: ErrorCode::from(error.code.as_str()),
+                message: error.message,
+                request_id: error.request_id,
+                fields: error.fields,
+            },
+            // Not a ZeroCloud error body, e.g. from a proxy in front of the API
+            Err(_) => ZeroSdkError::Api {
+                status,
+                code: ErrorCode::Unknown(status.as_u16().to_string()),
+                message: body.to_string(),
+                request_id: None,
+                fields: Vec::new(),
+            },
+        }
+    }
+}
+
+/// A request body field the API rejected
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FieldError {
+    /// Path of the field in the body, e.g. `scalingConfig.desiredSize`; empty for the body itself
+    pub field: String,
+    pub message: String,
+}
+
+/// Error codes reported by the ZeroCloud API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// The request is malformed or a parameter is out of range (400)
+    ValidationError,
+    /// The request names an unsupported action (400)
+    InvalidRequest,
+    /// No credentials were sent and the server requires them (401)
+    MissingAuthentication,
+    /// The credentials are invalid or not allowed to perform the action (403)
+    AccessDenied,
+    /// The namespace has no quota left for the resource (403)
+    QuotaExceeded,
+    /// The addressed resource does not exist (404)
+    NotFound,
+    /// A resource with the same name exists (409)
+    AlreadyExists,
+    /// The server failed to handle the request (500)
+    InternalError,
+    /// A compute, storage or network driver failed (502)
+    DriverError,
+    /// A code this SDK does not know, or the status of a response without an error body
+    Unknown(String),
+}
+
+impl From<&str> for ErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "ValidationError" => ErrorCode::ValidationError,
+            "InvalidRequest" => ErrorCode::InvalidRequest,
+            "MissingAuthentication" => ErrorCode::MissingAuthentication,
+            "AccessDenied" => ErrorCode::AccessDenied,
+            "NotFound" => ErrorCode::NotFound,
+            "AlreadyExists" => ErrorCode::AlreadyExists,
+            "QuotaExceeded" => ErrorCode::QuotaExceeded,
+            "InternalError" => ErrorCode::InternalError,
+            "DriverError" => ErrorCode::DriverError,
+            other => ErrorCode::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let code = match self {
+            ErrorCode::ValidationError => "ValidationError",
+            ErrorCode::InvalidRequest => "InvalidRequest",
+            ErrorCode::MissingAuthentication => "MissingAuthentication",
+            ErrorCode::AccessDenied => "AccessDenied",
+            ErrorCode::NotFound => "NotFound",
+            ErrorCode::AlreadyExists => "AlreadyExists",
+            ErrorCode::QuotaExceeded => "QuotaExceeded",
+            ErrorCode::InternalError => "InternalError",
+            ErrorCode::DriverError => "DriverError",
+            ErrorCode::Unknown(code) => code,
+        };
+        f.write_str(code)
+    }
+}
@@ -1,8 +1,13 @@
 pub mod services;
 pub mod error;
+pub mod credentials;
+pub mod retry;
 
-pub use error::ZeroSdkError;
+pub use error::{ErrorCode, FieldError, ZeroSdkError};
+pub use credentials::{Credentials, EnvironmentCredentials, ProvideCredentials};
+pub use retry::RetryPolicy;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone)]
 pub struct ZeroClient {
@@ -12,21 +17,105 @@ pub struct ZeroClient {
 struct ClientInner {
     base_url: String,
     http: reqwest::Client,
+    credentials: Option<Arc<dyn ProvideCredentials>>,
+    retry: RetryPolicy,
+    /// Bounds requests with JSON responses; streamed object transfers can take as long as they need
+    timeout: Option<Duration>,
+}
+
+/// Settings of a [`ZeroClient`]
+pub struct ZeroClientBuilder {
+    base_url: String,
+    credentials: Option<Arc<dyn ProvideCredentials>>,
+    retry: RetryPolicy,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+}
+
+impl ZeroClientBuilder {
+    /// Time after which a request with a JSON response is abandoned, retries aside.
+    /// Unset by default, as long polls and synchronous invocations may take minutes.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Time after which connecting to the server is abandoned, for every request
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Retry failed requests under `policy` instead of [`RetryPolicy::default`]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Sign every request with credentials from `provider`
+    pub fn credentials(mut self, provider: impl ProvideCredentials + 'static) -> Self {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+
+    pub fn build(self) -> Result<ZeroClient, ZeroSdkError> {
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        Ok(ZeroClient {
+            inner: Arc::new(ClientInner {
+                base_url: self.base_url,
+                http: http.build().map_err(ZeroSdkError::Http)?,
+                credentials: self.credentials,
+                retry: self.retry,
+                timeout: self.timeout,
+            }),
+        })
+    }
 }
 
 impl ZeroClient {
+    /// Client with the default retry policy and no timeouts
     pub fn new(base_url: impl Into<String>) -> Self {
         Self {
             inner: Arc::new(ClientInner {
                 base_url: base_url.into().trim_end_matches('/').to_string(),
                 http: reqwest::Client::new(),
+                credentials: None,
+                retry: RetryPolicy::default(),
+                timeout: None,
             }),
         }
     }
 
+    pub fn builder(base_url: impl Into<String>) -> ZeroClientBuilder {
+        ZeroClientBuilder {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            credentials: None,
+            retry: RetryPolicy::default(),
+            timeout: None,
+            connect_timeout: None,
+        }
+    }
+
+    /// Client for `ZERO_URL` that signs requests when `ZERO_ACCESS_KEY_ID` and `ZERO_SECRET_ACCESS_KEY` are set
     pub fn from_env() -> Self {
         let url = std::env::var("ZERO_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
-        Self::new(url)
+        Self::new(url).with_credentials(EnvironmentCredentials)
+    }
+
+    /// Sign every request with credentials from `provider`
+    pub fn with_credentials(self, provider: impl ProvideCredentials + 'static) -> Self {
+        Self {
+            inner: Arc::new(ClientInner {
+                base_url: self.inner.base_url.clone(),
+                http: self.inner.http.clone(),
+                credentials: Some(Arc::new(provider)),
+                retry: self.inner.retry.clone(),
+                timeout: self.inner.timeout,
+            }),
+        }
     }
 
     pub fn store(&self) -> services::store::StoreClient {
@@ -52,6 +141,26 @@ impl ZeroClient {
     pub fn lb(&self) -> services::lb::LbClient {
         services::lb::LbClient::new(self.inner.clone())
     }
+
+    pub fn dns(&self) -> services::dns::DnsClient {
+        services::dns::DnsClient::new(self.inner.clone())
+    }
+
+    pub fn topic(&self) -> services::topic::TopicClient {
+        services::topic::TopicClient::new(self.inner.clone())
+    }
+
+    pub fn scheduler(&self) -> services::scheduler::SchedulerClient {
+        services::scheduler::SchedulerClient::new(self.inner.clone())
+    }
+
+    pub fn autoscaling(&self) -> services::autoscaling::AutoscalingClient {
+        services::autoscaling::AutoscalingClient::new(self.inner.clone())
+    }
+
+    pub fn workload(&self) -> services::workload::WorkloadClient {
+        services::workload::WorkloadClient::new(self.inner.clone())
+    }
 }
 
 pub(crate) mod common {
@@ -63,22 +172,85 @@ pub(crate) mod common {
         method: reqwest::Method,
         path: &str,
         body: Option<serde_json::Value>,
+    ) -> Result<T, ZeroSdkError> {
+        request_with_headers(inner, method, path, &[], body).await
+    }
+
+    pub async fn request_with_headers<T: DeserializeOwned>(
+        inner: &Arc<ClientInner>,
+        method: reqwest::Method,
+        path: &str,
+        headers: &[(&str, &str)],
+        body: Option<serde_json::Value>,
     ) -> Result<T, ZeroSdkError> {
         let url = format!("{}/v1/{}", inner.base_url, path.trim_start_matches('/'));
         let mut req = inner.http.request(method, &url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        if let Some(timeout) = inner.timeout {
+            req = req.timeout(timeout);
+        }
         
         if let Some(b) = body {
             req = req.json(&b);
         }
 
-        let resp = req.send().await.map_err(ZeroSdkError::Http)?;
+        let resp = send(inner, req).await?;
+        resp.json::<T>().await.map_err(ZeroSdkError::Http)
+    }
+
+    /// Send a request with a raw body and return the response without reading it,
+    /// so large payloads can be streamed in both directions
+    pub async fn request_raw(
+        inner: &Arc<ClientInner>,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<reqwest::Body>,
+    ) -> Result<reqwest::Response, ZeroSdkError> {
+        let url = format!("{}/v1/{}", inner.base_url, path.trim_start_matches('/'));
+        let mut req = inner.http.request(method, &url);
+        if let Some(b) = body {
+            req = req.body(b);
+        }
+        send(inner, req).await
+    }
+
+    /// Send a request, again under the retry policy while it fails and can be replayed
+    async fn send(inner: &Arc<ClientInner>, req: reqwest::RequestBuilder) -> Result<reqwest::Response, ZeroSdkError> {
+        let mut req = req.build().map_err(ZeroSdkError::Http)?;
+        let mut retries = 0;
+        loop {
+            // Streamed bodies cannot be cloned, so their requests are sent once
+            let replay = req.try_clone();
+            let error = match send_once(inner, req).await {
+                Ok(resp) => return Ok(resp),
+                Err(e) => e,
+            };
+            match replay {
+                Some(next) if retries + 1 < inner.retry.max_attempts && inner.retry.should_retry(next.method(), &error) => {
+                    tokio::time::sleep(inner.retry.backoff(retries)).await;
+                    retries += 1;
+                    req = next;
+                }
+                _ => return Err(error),
+            }
+        }
+    }
+
+    /// Sign and send a request once
+    async fn send_once(inner: &Arc<ClientInner>, mut req: reqwest::Request) -> Result<reqwest::Response, ZeroSdkError> {
+        if let Some(credentials) = inner.credentials.as_ref().and_then(|p| p.provide_credentials()) {
+            credentials::sign(&mut req, &credentials)?;
+        }
+
+        let resp = inner.http.execute(req).await.map_err(ZeroSdkError::Http)?;
         
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
-            return Err(ZeroSdkError::Api { status, body });
+            return Err(ZeroSdkError::from_response(status, &body));
         }
-
-        resp.json::<T>().await.map_err(ZeroSdkError::Http)
+        Ok(resp)
     }
 }
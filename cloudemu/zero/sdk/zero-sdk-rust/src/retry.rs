@@ -0,0 +1,67 @@
+//! Retries of failed requests
+
+use crate::ZeroSdkError;
+use rand::Rng;
+use std::time::Duration;
+
+/// When and how often a failed request is sent again.
+///
+/// Requests that never reached the server, such as those refused while it restarts, are retried
+/// whatever their method. Responses with a status in `retry_on_status` and timed out requests
+/// may have been handled already, so they are only retried for idempotent methods. Requests
+/// with streamed bodies cannot be replayed and are never retried.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Attempts per request, the first one included; 1 disables retries
+    pub max_attempts: u32,
+    /// Upper bound of the delay before the first retry, doubled for each further one
+    pub initial_backoff: Duration,
+    /// Upper bound of any delay
+    pub max_backoff: Duration,
+    /// Response statuses worth retrying
+    pub retry_on_status: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            retry_on_status: vec![429, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Send every request once
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Delay before retry number `retry` (0 for the first), drawn uniformly up to the
+    /// exponential bound so clients that failed together do not retry together
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let bound = self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        bound.mul_f64(rand::thread_rng().gen_range(0.0..=1.0))
+    }
+
+    /// Whether a request with `method` that failed with `error` is worth sending again
+    pub fn should_retry(&self, method: &reqwest::Method, error: &ZeroSdkError) -> bool {
+        match error {
+            ZeroSdkError::Http(e) if e.is_connect() => true,
+            ZeroSdkError::Http(e) if e.is_timeout() => is_idempotent(method),
+            ZeroSdkError::Api { status, .. } => {
+                is_idempotent(method) && self.retry_on_status.contains(&status.as_u16())
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_idempotent(method: &reqwest::Method) -> bool {
+    use reqwest::Method;
+    [Method::GET, Method::HEAD, Method::PUT, Method::DELETE, Method::OPTIONS].contains(method)
+}
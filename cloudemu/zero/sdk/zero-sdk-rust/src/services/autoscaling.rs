@@ -0,0 +1,67 @@
+use crate::{ClientInner, ZeroSdkError, common::request};
+use std::sync::Arc;
+use serde_json::json;
+
+pub struct AutoscalingClient {
+    inner: Arc<ClientInner>,
+}
+
+/// What an autoscaling group tracks
+#[derive(Debug, Clone)]
+pub enum ScalingMetric {
+    /// CPU usage of the node, in percent, to hold
+    Cpu(f64),
+    /// Visible messages of a queue, with the backlog one instance should handle
+    QueueDepth { queue: String, messages_per_instance: f64 },
+}
+
+impl AutoscalingClient {
+    pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
+        Self { inner }
+    }
+
+    /// Create a group of `image` workloads that starts at `min_size` and scales up to `max_size`
+    pub async fn create_group(
+        &self,
+        name: &str,
+        image: &str,
+        min_size: u32,
+        max_size: u32,
+        metric: ScalingMetric,
+    ) -> Result<serde_json::Value, ZeroSdkError> {
+        let (metric, target_value, queue) = match metric {
+            ScalingMetric::Cpu(percent) => ("cpu", percent, None),
+            ScalingMetric::QueueDepth { queue, messages_per_instance } => ("queue_depth", messages_per_instance, Some(queue)),
+        };
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/autoscaling/groups",
+            Some(json!({
+                "name": name,
+                "image": image,
+                "min_size": min_size,
+                "max_size": max_size,
+                "metric": metric,
+                "target_value": target_value,
+                "queue": queue
+            })),
+        ).await
+    }
+
+    pub async fn list_groups(&self) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, "/autoscaling/groups", None).await?;
+        Ok(resp["Groups"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Describe a group with its instances and the metric seen by the last reconcile
+    pub async fn get_group(&self, name: &str) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::GET, &format!("/autoscaling/groups/{}", name), None).await
+    }
+
+    /// Delete a group and its instances
+    pub async fn delete_group(&self, name: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::DELETE, &format!("/autoscaling/groups/{}", name), None).await?;
+        Ok(())
+    }
+}
@@ -1,11 +1,22 @@
 use crate::{ClientInner, ZeroSdkError, common::request};
 use std::sync::Arc;
+use serde::Deserialize;
 use serde_json::json;
 
 pub struct DbClient {
     inner: Arc<ClientInner>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Table {
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct TableList {
+    tables: Vec<String>,
+}
+
 impl DbClient {
     pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
         Self { inner }
@@ -39,18 +50,13 @@ impl DbClient {
         Ok(())
     }
 
-    pub async fn list_tables(&self) -> Result<Vec<String>, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+    pub async fn list_tables(&self) -> Result<Vec<Table>, ZeroSdkError> {
+        let resp = request::<TableList>(
             &self.inner,
             reqwest::Method::GET,
             "/db/tables",
             None,
         ).await?;
-        
-        let tables = resp["tables"].as_array().ok_or_else(|| {
-            ZeroSdkError::Internal("Invalid response format: missing tables field".to_string())
-        })?;
-        
-        Ok(tables.iter().map(|v| v.as_str().unwrap_or_default().to_string()).collect())
+        Ok(resp.tables.into_iter().map(|name| Table { name }).collect())
     }
 }
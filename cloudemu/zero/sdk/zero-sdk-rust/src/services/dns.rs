@@ -0,0 +1,69 @@
+use crate::{ClientInner, ZeroSdkError, common::request};
+use std::sync::Arc;
+use serde_json::json;
+
+pub struct DnsClient {
+    inner: Arc<ClientInner>,
+}
+
+impl DnsClient {
+    pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
+        Self { inner }
+    }
+
+    pub async fn create_zone(&self, name: &str) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/dns/zones",
+            Some(json!({ "name": name })),
+        ).await
+    }
+
+    pub async fn list_zones(&self) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, "/dns/zones", None).await?;
+        Ok(resp["zones"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Delete a zone and all of its record sets
+    pub async fn delete_zone(&self, zone: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::DELETE, &format!("/dns/zones/{}", zone), None).await?;
+        Ok(())
+    }
+
+    /// Create or replace the `A`, `CNAME` or `TXT` record set of a name; `name` may be
+    /// relative to the zone, and `None` uses the default TTL
+    pub async fn put_record_set(&self, zone: &str, name: &str, record_type: &str, ttl: Option<u32>, values: &[&str]) -> Result<serde_json::Value, ZeroSdkError> {
+        let mut body = json!({ "name": name, "type": record_type, "values": values });
+        if let Some(ttl) = ttl {
+            body["ttl"] = json!(ttl);
+        }
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::PUT,
+            &format!("/dns/zones/{}/records", zone),
+            Some(body),
+        ).await
+    }
+
+    pub async fn list_record_sets(&self, zone: &str) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, &format!("/dns/zones/{}/records", zone), None).await?;
+        Ok(resp["records"].as_array().cloned().unwrap_or_default())
+    }
+
+    pub async fn delete_record_set(&self, zone: &str, name: &str, record_type: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/dns/zones/{}/records/{}/{}", zone, name, record_type),
+            None,
+        ).await?;
+        Ok(())
+    }
+
+    /// Resolve a name as the embedded resolver would, following CNAMEs
+    pub async fn resolve(&self, name: &str, record_type: &str) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, &format!("/dns/resolve/{}/{}", name, record_type), None).await?;
+        Ok(resp["answers"].as_array().cloned().unwrap_or_default())
+    }
+}
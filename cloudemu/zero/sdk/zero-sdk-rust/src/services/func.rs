@@ -1,11 +1,114 @@
-use crate::{ClientInner, ZeroSdkError, common::request};
+use crate::{ClientInner, ZeroSdkError, common::{request, request_with_headers}};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use serde::Deserialize;
 use serde_json::json;
 
 pub struct FuncClient {
     inner: Arc<ClientInner>,
 }
 
+/// Execution environment of a function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Runtime {
+    /// Source code run by a local interpreter
+    Inline,
+    /// Container image; `code` is the image reference
+    Docker,
+    /// WebAssembly module; `code` is WebAssembly text or a base64 binary module
+    Wasm,
+}
+
+impl Runtime {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Runtime::Inline => "inline",
+            Runtime::Docker => "docker",
+            Runtime::Wasm => "wasm",
+        }
+    }
+}
+
+/// Optional settings applied when deploying a function
+#[derive(Debug, Clone)]
+pub struct FunctionOptions {
+    pub runtime: Runtime,
+    /// Environment variables visible to the function code
+    pub environment: BTreeMap<String, String>,
+    /// Seconds an invocation may run before it is aborted (server default 30)
+    pub timeout_secs: Option<u32>,
+    /// Memory limit in MB (server default 128)
+    pub memory_mb: Option<u32>,
+}
+
+impl Default for FunctionOptions {
+    fn default() -> Self {
+        Self { runtime: Runtime::Inline, environment: BTreeMap::new(), timeout_secs: None, memory_mb: None }
+    }
+}
+
+/// Configuration of a deployed function
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Function {
+    pub name: String,
+    pub handler: String,
+    pub runtime: Runtime,
+    pub environment: BTreeMap<String, String>,
+    pub timeout_secs: u32,
+    pub memory_mb: u32,
+}
+
+/// Progress of an asynchronous invocation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum InvocationStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Record of an asynchronous invocation
+#[derive(Debug, Clone, Deserialize)]
+pub struct Invocation {
+    pub id: String,
+    pub function_name: String,
+    pub status: InvocationStatus,
+    pub payload: serde_json::Value,
+    /// Function output once the function ran, including when it exited non-zero
+    pub result: Option<serde_json::Value>,
+    /// Failure reason once the invocation failed
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+/// A queue-to-function mapping
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventSourceMapping {
+    pub id: String,
+    pub function_name: String,
+    pub queue_name: String,
+    pub batch_size: u32,
+    pub max_retries: Option<u32>,
+    pub dead_letter_queue: Option<String>,
+    pub enabled: bool,
+    pub last_processing_result: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Deserialize)]
+struct FunctionList {
+    functions: Vec<String>,
+    configurations: Vec<Function>,
+}
+
+#[derive(Deserialize)]
+struct MappingList {
+    #[serde(rename = "EventSourceMappings")]
+    mappings: Vec<EventSourceMapping>,
+}
+
 impl FuncClient {
     pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
         Self { inner }
@@ -21,6 +124,46 @@ impl FuncClient {
         Ok(())
     }
 
+    /// Deploy a function to a specific runtime
+    pub async fn create_function_with_runtime(&self, name: &str, handler: &str, code: &str, runtime: Runtime) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/func/functions",
+            Some(json!({ "name": name, "handler": handler, "code": code, "runtime": runtime.as_str() })),
+        ).await?;
+        Ok(())
+    }
+
+    /// Deploy a function with environment variables, a timeout and a memory limit
+    pub async fn create_function_with_options(&self, name: &str, handler: &str, code: &str, options: FunctionOptions) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/func/functions",
+            Some(json!({
+                "name": name,
+                "handler": handler,
+                "code": code,
+                "runtime": options.runtime.as_str(),
+                "environment": options.environment,
+                "timeout_secs": options.timeout_secs,
+                "memory_mb": options.memory_mb
+            })),
+        ).await?;
+        Ok(())
+    }
+
+    /// Configuration of a deployed function (runtime, environment, timeout and memory)
+    pub async fn get_function(&self, name: &str) -> Result<Function, ZeroSdkError> {
+        request::<Function>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/func/functions/{}", name),
+            None,
+        ).await
+    }
+
     pub async fn invoke(&self, name: &str, payload: serde_json::Value) -> Result<serde_json::Value, ZeroSdkError> {
         request::<serde_json::Value>(
             &self.inner,
@@ -30,18 +173,72 @@ impl FuncClient {
         ).await
     }
 
+    /// Queue an invocation and return its record without waiting
+    pub async fn invoke_async(&self, name: &str, payload: serde_json::Value) -> Result<Invocation, ZeroSdkError> {
+        request_with_headers::<Invocation>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/func/functions/{}/invocations", name),
+            &[("X-Zero-Invocation-Type", "Event")],
+            Some(payload),
+        ).await
+    }
+
+    /// Status and, once finished, the result or error of an asynchronous invocation
+    pub async fn get_invocation(&self, name: &str, id: &str) -> Result<Invocation, ZeroSdkError> {
+        request::<Invocation>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/func/functions/{}/invocations/{}", name, id),
+            None,
+        ).await
+    }
+
+    /// Invoke `function_name` with batches of up to `batch_size` messages from `queue_name`
+    pub async fn map_queue(&self, function_name: &str, queue_name: &str, batch_size: u32) -> Result<EventSourceMapping, ZeroSdkError> {
+        request::<EventSourceMapping>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/func/event-source-mappings",
+            Some(json!({ "function_name": function_name, "queue_name": queue_name, "batch_size": batch_size })),
+        ).await
+    }
+
+    pub async fn list_event_source_mappings(&self) -> Result<Vec<EventSourceMapping>, ZeroSdkError> {
+        let resp = request::<MappingList>(
+            &self.inner,
+            reqwest::Method::GET,
+            "/func/event-source-mappings",
+            None,
+        ).await?;
+        Ok(resp.mappings)
+    }
+
+    pub async fn delete_event_source_mapping(&self, id: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/func/event-source-mappings/{}", id),
+            None,
+        ).await?;
+        Ok(())
+    }
+
     pub async fn list_functions(&self) -> Result<Vec<String>, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+        Ok(self.list().await?.functions)
+    }
+
+    /// Configuration of every deployed function
+    pub async fn list_function_configurations(&self) -> Result<Vec<Function>, ZeroSdkError> {
+        Ok(self.list().await?.configurations)
+    }
+
+    async fn list(&self) -> Result<FunctionList, ZeroSdkError> {
+        request::<FunctionList>(
             &self.inner,
             reqwest::Method::GET,
             "/func/functions",
             None,
-        ).await?;
-        
-        let functions = resp["functions"].as_array().ok_or_else(|| {
-            ZeroSdkError::Internal("Invalid response format: missing functions field".to_string())
-        })?;
-        
-        Ok(functions.iter().map(|v| v.as_str().unwrap_or_default().to_string()).collect())
+        ).await
     }
 }
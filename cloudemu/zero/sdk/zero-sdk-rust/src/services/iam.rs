@@ -1,4 +1,5 @@
-use crate::{ClientInner, ZeroSdkError, common::request};
+use crate::{ClientInner, Credentials, ZeroSdkError, common::request};
+use serde::Deserialize;
 use std::sync::Arc;
 use serde_json::json;
 
@@ -6,6 +7,51 @@ pub struct IamClient {
     inner: Arc<ClientInner>,
 }
 
+/// Access key of a user; `secret_access_key` is only present in the response to `create_access_key`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccessKey {
+    pub user_name: String,
+    pub access_key_id: String,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    pub status: String,
+    pub create_date: String,
+}
+
+impl AccessKey {
+    /// Signing credentials of a newly created key
+    pub fn credentials(&self) -> Option<Credentials> {
+        self.secret_access_key.as_ref().map(|secret| Credentials::new(self.access_key_id.clone(), secret.clone()))
+    }
+}
+
+/// Temporary credentials of a role session
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SessionCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: String,
+    pub expiration: String,
+}
+
+/// Result of `assume_role`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AssumedRole {
+    pub credentials: SessionCredentials,
+    pub assumed_role_arn: String,
+}
+
+impl AssumedRole {
+    /// Signing credentials of the session
+    pub fn credentials(&self) -> Credentials {
+        Credentials::new(self.credentials.access_key_id.clone(), self.credentials.secret_access_key.clone())
+            .with_session_token(self.credentials.session_token.clone())
+    }
+}
+
 impl IamClient {
     pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
         Self { inner }
@@ -41,6 +87,27 @@ impl IamClient {
         Ok(())
     }
 
+    pub async fn attach_role_policy(&self, rolename: &str, policy: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/iam/roles/{}/policy", rolename),
+            Some(json!({ "PolicyDocument": policy })),
+        ).await?;
+        Ok(())
+    }
+
+    /// Get temporary credentials acting as `rolename`; `duration_secs` defaults to one hour
+    pub async fn assume_role(&self, rolename: &str, session_name: &str, duration_secs: Option<u32>) -> Result<AssumedRole, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/iam/roles/{}/assume", rolename),
+            Some(json!({ "RoleSessionName": session_name, "DurationSeconds": duration_secs })),
+        ).await?;
+        Ok(serde_json::from_value(resp)?)
+    }
+
     pub async fn create_group(&self, groupname: &str) -> Result<(), ZeroSdkError> {
         request::<serde_json::Value>(
             &self.inner,
@@ -80,4 +147,34 @@ impl IamClient {
         ).await?;
         Ok(resp["Groups"].as_array().cloned().unwrap_or_default())
     }
+
+    pub async fn create_access_key(&self, username: &str) -> Result<AccessKey, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/iam/users/{}/access-keys", username),
+            None,
+        ).await?;
+        Ok(serde_json::from_value(resp["AccessKey"].clone())?)
+    }
+
+    pub async fn list_access_keys(&self, username: &str) -> Result<Vec<AccessKey>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/iam/users/{}/access-keys", username),
+            None,
+        ).await?;
+        Ok(serde_json::from_value(resp["AccessKeys"].clone())?)
+    }
+
+    pub async fn delete_access_key(&self, username: &str, access_key_id: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/iam/users/{}/access-keys/{}", username, access_key_id),
+            None,
+        ).await?;
+        Ok(())
+    }
 }
@@ -6,6 +6,19 @@ pub struct LbClient {
     inner: Arc<ClientInner>,
 }
 
+/// Health check settings of a target group; unset fields use the server defaults
+#[derive(Debug, Clone, Default)]
+pub struct HealthCheckOptions {
+    /// Path probed on HTTP targets (server default `/health`)
+    pub path: Option<String>,
+    /// Seconds between checks of each target (server default 10)
+    pub interval_secs: Option<u32>,
+    /// Consecutive passed checks before a target is healthy (server default 3)
+    pub healthy_threshold: Option<u32>,
+    /// Consecutive failed checks before a target is unhealthy (server default 2)
+    pub unhealthy_threshold: Option<u32>,
+}
+
 impl LbClient {
     pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
         Self { inner }
@@ -33,6 +46,30 @@ impl LbClient {
             .ok_or_else(|| ZeroSdkError::Internal("Missing TargetGroupArn".into()))
     }
 
+    /// Create a target group with a protocol (`HTTP` or `TCP`) and health check settings
+    pub async fn create_target_group_with_options(&self, name: &str, port: i32, protocol: &str, health_check: HealthCheckOptions) -> Result<String, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/network/targetgroups",
+            Some(json!({
+                "name": name,
+                "port": port,
+                "protocol": protocol,
+                "health_check": {
+                    "path": health_check.path,
+                    "interval_secs": health_check.interval_secs,
+                    "healthy_threshold": health_check.healthy_threshold,
+                    "unhealthy_threshold": health_check.unhealthy_threshold
+                }
+            })),
+        ).await?;
+
+        resp["TargetGroupArn"].as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| ZeroSdkError::Internal("Missing TargetGroupArn".into()))
+    }
+
     pub async fn register_targets(&self, group_arn: &str, id: &str, port: i32) -> Result<(), ZeroSdkError> {
         request::<serde_json::Value>(
             &self.inner,
@@ -43,6 +80,17 @@ impl LbClient {
         Ok(())
     }
 
+    /// Health of each target registered with a group
+    pub async fn describe_target_health(&self, group_arn: &str) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/network/targetgroups/{}/health", group_arn),
+            None,
+        ).await?;
+        Ok(resp["TargetHealthDescriptions"].as_array().cloned().unwrap_or_default())
+    }
+
     pub async fn create_listener(&self, lb_name: &str, port: i32, target_group_arn: &str) -> Result<String, ZeroSdkError> {
         let resp = request::<serde_json::Value>(
             &self.inner,
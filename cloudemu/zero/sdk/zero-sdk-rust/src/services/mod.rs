@@ -4,3 +4,8 @@ pub mod func;
 pub mod queue;
 pub mod iam;
 pub mod lb;
+pub mod dns;
+pub mod topic;
+pub mod scheduler;
+pub mod autoscaling;
+pub mod workload;
@@ -1,15 +1,112 @@
 use crate::{ClientInner, ZeroSdkError, common::request};
+use std::collections::HashMap;
 use std::sync::Arc;
+use serde::Deserialize;
 use serde_json::json;
 
 pub struct QueueClient {
     inner: Arc<ClientInner>,
 }
 
-pub struct Message {
+/// A received message
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueueMessage {
+    #[serde(rename = "MessageId")]
     pub id: String,
+    #[serde(rename = "Body")]
     pub body: String,
+    /// Deletes the message or changes its visibility until it is received again
+    #[serde(rename = "ReceiptHandle")]
     pub receipt_handle: String,
+    /// `ApproximateReceiveCount` and `SentTimestamp`, plus the group, deduplication ID and
+    /// sequence number of FIFO messages
+    #[serde(rename = "Attributes", default)]
+    pub attributes: HashMap<String, String>,
+}
+
+/// Former name of [`QueueMessage`]
+pub type Message = QueueMessage;
+
+#[derive(Deserialize)]
+struct CreatedQueue {
+    #[serde(rename = "QueueUrl")]
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct SentMessage {
+    #[serde(rename = "MessageId")]
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct ReceivedMessages<T> {
+    #[serde(rename = "Messages")]
+    messages: T,
+}
+
+/// Dead-letter queue of a queue
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RedrivePolicy {
+    /// Name of the queue that receives messages after too many receives
+    pub dead_letter_queue: String,
+    /// Receives after which a message is moved to the dead-letter queue
+    pub max_receive_count: u32,
+}
+
+#[derive(Deserialize)]
+struct RedrivePolicyResponse {
+    #[serde(rename = "RedrivePolicy")]
+    policy: Option<RedrivePolicy>,
+}
+
+#[derive(Deserialize)]
+struct QueueList {
+    #[serde(rename = "QueueUrls")]
+    urls: Vec<String>,
+}
+
+/// Settings for a batch receive
+#[derive(Debug, Clone)]
+pub struct ReceiveOptions {
+    /// Maximum number of messages to return (1-10)
+    pub max_messages: usize,
+    /// Seconds to wait for messages when the queue is empty (0-20)
+    pub wait_time_seconds: u64,
+    /// Overrides the queue's visibility timeout for the received messages
+    pub visibility_timeout: Option<u32>,
+}
+
+impl Default for ReceiveOptions {
+    fn default() -> Self {
+        Self { max_messages: 1, wait_time_seconds: 0, visibility_timeout: None }
+    }
+}
+
+/// Successful entry of a batch operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResultEntry {
+    pub id: String,
+    /// Set for sent messages
+    pub message_id: Option<String>,
+}
+
+/// Failed entry of a batch operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResultError {
+    pub id: String,
+    pub code: String,
+    pub message: String,
+}
+
+/// Per-entry outcome of a batch operation
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchResult {
+    pub successful: Vec<BatchResultEntry>,
+    pub failed: Vec<BatchResultError>,
 }
 
 impl QueueClient {
@@ -18,49 +115,107 @@ impl QueueClient {
     }
 
     pub async fn create_queue(&self, name: &str) -> Result<String, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+        let resp = request::<CreatedQueue>(
             &self.inner,
             reqwest::Method::POST,
             "/queue/queues",
             Some(json!({ "name": name })),
         ).await?;
-        
-        resp["QueueUrl"].as_str()
-            .map(|s| s.to_string())
-            .ok_or_else(|| ZeroSdkError::Internal("Missing QueueUrl".into()))
+        Ok(resp.url)
+    }
+
+    /// Create a queue whose received messages stay hidden for `visibility_timeout` seconds
+    pub async fn create_queue_with_visibility_timeout(&self, name: &str, visibility_timeout: u32) -> Result<String, ZeroSdkError> {
+        let resp = request::<CreatedQueue>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/queue/queues",
+            Some(json!({ "name": name, "visibility_timeout": visibility_timeout })),
+        ).await?;
+        Ok(resp.url)
+    }
+
+    /// Create a queue whose new messages stay hidden for `delay_seconds` (0-900) after being sent
+    pub async fn create_queue_with_delay(&self, name: &str, delay_seconds: u32) -> Result<String, ZeroSdkError> {
+        let resp = request::<CreatedQueue>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/queue/queues",
+            Some(json!({ "name": name, "delay_seconds": delay_seconds })),
+        ).await?;
+        Ok(resp.url)
     }
 
     pub async fn send_message(&self, queue_name: &str, body: &str) -> Result<String, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+        let resp = request::<SentMessage>(
             &self.inner,
             reqwest::Method::POST,
             &format!("/queue/queues/{}/messages", queue_name),
             Some(json!({ "body": body })),
         ).await?;
-        
-        resp["MessageId"].as_str()
-            .map(|s| s.to_string())
-            .ok_or_else(|| ZeroSdkError::Internal("Missing MessageId".into()))
+        Ok(resp.id)
     }
 
-    pub async fn receive_message(&self, queue_name: &str) -> Result<Option<Message>, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+    /// Send a message that becomes visible after `delay_seconds`, overriding the queue's delay
+    pub async fn send_message_with_delay(&self, queue_name: &str, body: &str, delay_seconds: u32) -> Result<String, ZeroSdkError> {
+        let resp = request::<SentMessage>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/queue/queues/{}/messages", queue_name),
+            Some(json!({ "body": body, "delay_seconds": delay_seconds })),
+        ).await?;
+        Ok(resp.id)
+    }
+
+    pub async fn receive_message(&self, queue_name: &str) -> Result<Option<QueueMessage>, ZeroSdkError> {
+        let resp = request::<ReceivedMessages<Option<QueueMessage>>>(
             &self.inner,
             reqwest::Method::GET,
             &format!("/queue/queues/{}/messages", queue_name),
             None,
         ).await?;
-        
-        let messages = resp["Messages"].as_object();
-        if let Some(msg) = messages {
-             Ok(Some(Message {
-                 id: msg["MessageId"].as_str().unwrap_or_default().to_string(),
-                 body: msg["Body"].as_str().unwrap_or_default().to_string(),
-                 receipt_handle: msg["ReceiptHandle"].as_str().unwrap_or_default().to_string(),
-             }))
-        } else {
-             Ok(None)
-        }
+        Ok(resp.messages)
+    }
+
+    /// Receive a batch of messages, long polling when `wait_time_seconds` is set
+    pub async fn receive_messages(&self, queue_name: &str, options: ReceiveOptions) -> Result<Vec<QueueMessage>, ZeroSdkError> {
+        let resp = request::<ReceivedMessages<Vec<QueueMessage>>>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/queue/queues/{}/receive", queue_name),
+            Some(json!({
+                "max_messages": options.max_messages,
+                "wait_time_seconds": options.wait_time_seconds,
+                "visibility_timeout": options.visibility_timeout
+            })),
+        ).await?;
+        Ok(resp.messages)
+    }
+
+    /// Send up to 10 messages given as `(entry id, body)` pairs
+    pub async fn send_message_batch(&self, queue_name: &str, entries: &[(&str, &str)]) -> Result<BatchResult, ZeroSdkError> {
+        let entries: Vec<_> = entries.iter()
+            .map(|(id, body)| json!({ "id": id, "body": body }))
+            .collect();
+        request::<BatchResult>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/queue/queues/{}/batch/send", queue_name),
+            Some(json!({ "entries": entries })),
+        ).await
+    }
+
+    /// Delete up to 10 messages given as `(entry id, receipt handle)` pairs
+    pub async fn delete_message_batch(&self, queue_name: &str, entries: &[(&str, &str)]) -> Result<BatchResult, ZeroSdkError> {
+        let entries: Vec<_> = entries.iter()
+            .map(|(id, handle)| json!({ "id": id, "receipt_handle": handle }))
+            .collect();
+        request::<BatchResult>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/queue/queues/{}/batch/delete", queue_name),
+            Some(json!({ "entries": entries })),
+        ).await
     }
 
     pub async fn delete_message(&self, queue_name: &str, receipt_handle: &str) -> Result<(), ZeroSdkError> {
@@ -73,18 +228,57 @@ impl QueueClient {
         Ok(())
     }
 
+    /// Hide an in-flight message for `visibility_timeout` more seconds (0 releases it immediately)
+    pub async fn change_message_visibility(&self, queue_name: &str, receipt_handle: &str, visibility_timeout: u32) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::PATCH,
+            &format!("/queue/queues/{}/messages/{}/visibility", queue_name, receipt_handle),
+            Some(json!({ "visibility_timeout": visibility_timeout })),
+        ).await?;
+        Ok(())
+    }
+
+    /// Dead-letter queue of a queue, if it has one
+    pub async fn get_redrive_policy(&self, queue_name: &str) -> Result<Option<RedrivePolicy>, ZeroSdkError> {
+        let resp = request::<RedrivePolicyResponse>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/queue/queues/{}/redrive-policy", queue_name),
+            None,
+        ).await?;
+        Ok(resp.policy)
+    }
+
+    /// Move messages received `max_receive_count` times without being deleted to `dead_letter_queue`
+    pub async fn set_redrive_policy(&self, queue_name: &str, dead_letter_queue: &str, max_receive_count: u32) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::PUT,
+            &format!("/queue/queues/{}/redrive-policy", queue_name),
+            Some(json!({ "dead_letter_queue": dead_letter_queue, "max_receive_count": max_receive_count })),
+        ).await?;
+        Ok(())
+    }
+
+    /// Stop dead-lettering the messages of a queue
+    pub async fn delete_redrive_policy(&self, queue_name: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/queue/queues/{}/redrive-policy", queue_name),
+            None,
+        ).await?;
+        Ok(())
+    }
+
     pub async fn list_queues(&self) -> Result<Vec<String>, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+        let resp = request::<QueueList>(
             &self.inner,
             reqwest::Method::GET,
             "/queue/queues",
             None,
         ).await?;
-        
-        let urls = resp["QueueUrls"].as_array().ok_or_else(|| {
-            ZeroSdkError::Internal("Invalid response format: missing QueueUrls field".to_string())
-        })?;
-        
-        Ok(urls.iter().map(|v| v.as_str().unwrap_or_default().to_string()).collect())
+        Ok(resp.urls)
     }
 }
@@ -0,0 +1,46 @@
+use crate::{ClientInner, ZeroSdkError, common::request};
+use std::sync::Arc;
+use serde_json::json;
+
+pub struct SchedulerClient {
+    inner: Arc<ClientInner>,
+}
+
+impl SchedulerClient {
+    pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
+        Self { inner }
+    }
+
+    /// Create a rule; `target_type` is `function`, `queue` or `webhook` and `target` the
+    /// function name, queue name or URL it runs
+    pub async fn create_rule(
+        &self,
+        name: &str,
+        schedule: &str,
+        target_type: &str,
+        target: &str,
+        input: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/scheduler/rules",
+            Some(json!({ "name": name, "schedule": schedule, "target_type": target_type, "target": target, "input": input })),
+        ).await
+    }
+
+    pub async fn list_rules(&self) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, "/scheduler/rules", None).await?;
+        Ok(resp["Rules"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Describe a rule with its next and last runs
+    pub async fn get_rule(&self, name: &str) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::GET, &format!("/scheduler/rules/{}", name), None).await
+    }
+
+    pub async fn delete_rule(&self, name: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::DELETE, &format!("/scheduler/rules/{}", name), None).await?;
+        Ok(())
+    }
+}
@@ -1,11 +1,42 @@
-use crate::{ClientInner, ZeroSdkError, common::request};
+use crate::{ClientInner, ZeroSdkError, common::{request, request_raw}};
+use bytes::Bytes;
+use futures::{Stream, StreamExt, TryStream, TryStreamExt};
+use std::path::Path;
 use std::sync::Arc;
+use serde::Deserialize;
 use serde_json::json;
+use tokio::io::AsyncWriteExt;
 
 pub struct StoreClient {
     inner: Arc<ClientInner>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Bucket {
+    pub name: String,
+}
+
+/// An object stored in a bucket
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: String,
+    /// Hex SHA-256 of the data, returned for uploads
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BucketList {
+    buckets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ObjectList {
+    objects: Vec<ObjectInfo>,
+}
+
 impl StoreClient {
     pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
         Self { inner }
@@ -21,18 +52,98 @@ impl StoreClient {
         Ok(())
     }
 
-    pub async fn list_buckets(&self) -> Result<Vec<String>, ZeroSdkError> {
-        let resp = request::<serde_json::Value>(
+    pub async fn list_buckets(&self) -> Result<Vec<Bucket>, ZeroSdkError> {
+        let resp = request::<BucketList>(
             &self.inner,
             reqwest::Method::GET,
             "/store/buckets",
             None,
         ).await?;
-        
-        let buckets = resp["buckets"].as_array().ok_or_else(|| {
-            ZeroSdkError::Internal("Invalid response format: missing buckets field".to_string())
-        })?;
-        
-        Ok(buckets.iter().map(|v| v.as_str().unwrap_or_default().to_string()).collect())
+        Ok(resp.buckets.into_iter().map(|name| Bucket { name }).collect())
+    }
+
+    /// Upload an object. Bodies built from streams or files are sent as they are read.
+    pub async fn put_object(&self, bucket: &str, key: &str, body: impl Into<reqwest::Body>) -> Result<ObjectInfo, ZeroSdkError> {
+        let resp = request_raw(
+            &self.inner,
+            reqwest::Method::PUT,
+            &format!("/store/buckets/{}/objects/{}", bucket, key),
+            Some(body.into()),
+        ).await?;
+        resp.json().await.map_err(ZeroSdkError::Http)
+    }
+
+    /// Upload an object from a stream of chunks, each sent as soon as it is produced
+    pub async fn put_object_stream<S>(&self, bucket: &str, key: &str, data: S) -> Result<ObjectInfo, ZeroSdkError>
+    where
+        S: TryStream + Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        Bytes: From<S::Ok>,
+    {
+        self.put_object(bucket, key, reqwest::Body::wrap_stream(data)).await
+    }
+
+    /// Upload a file without reading it into memory
+    pub async fn put_object_from_file(&self, bucket: &str, key: &str, path: impl AsRef<Path>) -> Result<ObjectInfo, ZeroSdkError> {
+        let file = tokio::fs::File::open(path).await
+            .map_err(|e| ZeroSdkError::Internal(format!("Failed to open upload: {}", e)))?;
+        self.put_object(bucket, key, file).await
+    }
+
+    /// Object data read into memory, for objects known to be small
+    pub async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, ZeroSdkError> {
+        let resp = request_raw(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/store/buckets/{}/objects/{}", bucket, key),
+            None,
+        ).await?;
+        resp.bytes().await.map_err(ZeroSdkError::Http)
+    }
+
+    /// Object data as a stream of chunks, read from the server as it is consumed
+    pub async fn get_object_stream(&self, bucket: &str, key: &str) -> Result<impl Stream<Item = Result<Bytes, ZeroSdkError>>, ZeroSdkError> {
+        let resp = request_raw(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/store/buckets/{}/objects/{}", bucket, key),
+            None,
+        ).await?;
+        Ok(resp.bytes_stream().map_err(ZeroSdkError::Http))
+    }
+
+    /// Download an object to a file and return its size
+    pub async fn download_object(&self, bucket: &str, key: &str, path: impl AsRef<Path>) -> Result<u64, ZeroSdkError> {
+        let io_error = |e: std::io::Error| ZeroSdkError::Internal(format!("Failed to write download: {}", e));
+        let mut data = std::pin::pin!(self.get_object_stream(bucket, key).await?);
+        let mut file = tokio::fs::File::create(path).await.map_err(io_error)?;
+        let mut size = 0;
+        while let Some(chunk) = data.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await.map_err(io_error)?;
+            size += chunk.len() as u64;
+        }
+        file.flush().await.map_err(io_error)?;
+        Ok(size)
+    }
+
+    pub async fn list_objects(&self, bucket: &str) -> Result<Vec<ObjectInfo>, ZeroSdkError> {
+        let resp = request::<ObjectList>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/store/buckets/{}/objects", bucket),
+            None,
+        ).await?;
+        Ok(resp.objects)
+    }
+
+    pub async fn delete_object(&self, bucket: &str, key: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/store/buckets/{}/objects/{}", bucket, key),
+            None,
+        ).await?;
+        Ok(())
     }
 }
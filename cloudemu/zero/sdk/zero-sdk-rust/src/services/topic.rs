@@ -0,0 +1,77 @@
+use crate::{ClientInner, ZeroSdkError, common::request};
+use std::sync::Arc;
+use serde_json::json;
+
+pub struct TopicClient {
+    inner: Arc<ClientInner>,
+}
+
+impl TopicClient {
+    pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
+        Self { inner }
+    }
+
+    /// Create a topic, or return the existing topic of that name
+    pub async fn create_topic(&self, name: &str) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::POST, "/topics", Some(json!({ "name": name }))).await
+    }
+
+    pub async fn list_topics(&self) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, "/topics", None).await?;
+        Ok(resp["Topics"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Delete a topic and its subscriptions
+    pub async fn delete_topic(&self, name: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(&self.inner, reqwest::Method::DELETE, &format!("/topics/{}", name), None).await?;
+        Ok(())
+    }
+
+    /// Deliver the topic's messages to a queue; returns the subscription id
+    pub async fn subscribe_queue(&self, topic: &str, queue_name: &str, filter_policy: Option<serde_json::Value>) -> Result<String, ZeroSdkError> {
+        self.subscribe(topic, json!({ "protocol": "queue", "endpoint": queue_name, "filter_policy": filter_policy })).await
+    }
+
+    /// POST the topic's messages to an HTTP(S) URL; returns the subscription id
+    pub async fn subscribe_webhook(&self, topic: &str, url: &str, filter_policy: Option<serde_json::Value>) -> Result<String, ZeroSdkError> {
+        self.subscribe(topic, json!({ "protocol": "webhook", "endpoint": url, "filter_policy": filter_policy })).await
+    }
+
+    async fn subscribe(&self, topic: &str, body: serde_json::Value) -> Result<String, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/topics/{}/subscriptions", topic),
+            Some(body),
+        ).await?;
+        resp["id"].as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| ZeroSdkError::Internal("Missing subscription id".into()))
+    }
+
+    pub async fn list_subscriptions(&self, topic: &str) -> Result<Vec<serde_json::Value>, ZeroSdkError> {
+        let resp = request::<serde_json::Value>(&self.inner, reqwest::Method::GET, &format!("/topics/{}/subscriptions", topic), None).await?;
+        Ok(resp["Subscriptions"].as_array().cloned().unwrap_or_default())
+    }
+
+    pub async fn unsubscribe(&self, topic: &str, subscription_id: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            &format!("/topics/{}/subscriptions/{}", topic, subscription_id),
+            None,
+        ).await?;
+        Ok(())
+    }
+
+    /// Publish a message with string or number attributes; returns the message id and
+    /// the number of subscriptions it matched
+    pub async fn publish(&self, topic: &str, message: &str, attributes: serde_json::Value) -> Result<serde_json::Value, ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::POST,
+            &format!("/topics/{}/messages", topic),
+            Some(json!({ "message": message, "attributes": attributes })),
+        ).await
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{ClientInner, ZeroSdkError, common::request};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+pub struct WorkloadClient {
+    inner: Arc<ClientInner>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+/// A host port forwarded to a workload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortMapping {
+    /// Port bound on the host; 0 asks for a free one
+    #[serde(default)]
+    pub host_port: u16,
+    pub container_port: u16,
+    #[serde(default)]
+    pub protocol: Protocol,
+}
+
+/// Settings of a workload to create
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkloadSpec {
+    pub id: String,
+    pub image: String,
+    /// Server default 1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<f32>,
+    /// Server default 512
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<PortMapping>,
+    /// `never` (server default), `on-failure` or `always`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<String>,
+}
+
+impl WorkloadSpec {
+    pub fn new(id: impl Into<String>, image: impl Into<String>) -> Self {
+        Self { id: id.into(), image: image.into(), cpu: None, memory_mb: None, ports: Vec::new(), restart_policy: None }
+    }
+}
+
+/// A running or stopped workload
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workload {
+    pub id: String,
+    /// As the driver names it, e.g. `running` for Docker and `Running` for KVM
+    pub state: String,
+    pub ip_address: Option<String>,
+    #[serde(default)]
+    pub ports: Vec<PortMapping>,
+    /// Times the server restarted the workload under its restart policy
+    #[serde(default)]
+    pub restart_count: u32,
+    /// Node the workload was placed on, unset when it runs on the server itself
+    #[serde(default)]
+    pub node: Option<String>,
+}
+
+impl Workload {
+    pub fn is_running(&self) -> bool {
+        self.state.eq_ignore_ascii_case("running")
+    }
+}
+
+#[derive(Deserialize)]
+struct WorkloadList {
+    workloads: Vec<Workload>,
+}
+
+/// Outcome of a rolling update
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rollout {
+    pub id: String,
+    /// `Updated`, or `RolledBack` when the new revision failed its health check
+    pub status: String,
+    /// Revision the workload runs now
+    pub revision: u32,
+    pub image: String,
+    #[serde(default)]
+    pub failed_revision: Option<u32>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl Rollout {
+    pub fn is_rolled_back(&self) -> bool {
+        self.status == "RolledBack"
+    }
+}
+
+/// A revision of a workload
+#[derive(Debug, Clone, Deserialize)]
+pub struct Revision {
+    pub revision: u32,
+    pub image: String,
+    /// `active` for the one the workload runs, `superseded` or `failed`
+    pub status: String,
+    pub created_at: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RevisionList {
+    revisions: Vec<Revision>,
+}
+
+impl WorkloadClient {
+    pub(crate) fn new(inner: Arc<ClientInner>) -> Self {
+        Self { inner }
+    }
+
+    pub async fn create_workload(&self, spec: &WorkloadSpec) -> Result<Workload, ZeroSdkError> {
+        let body = serde_json::to_value(spec).map_err(|e| ZeroSdkError::Internal(e.to_string()))?;
+        request::<Workload>(
+            &self.inner,
+            reqwest::Method::POST,
+            "/workloads",
+            Some(body),
+        ).await
+    }
+
+    pub async fn list_workloads(&self) -> Result<Vec<Workload>, ZeroSdkError> {
+        let resp = request::<WorkloadList>(
+            &self.inner,
+            reqwest::Method::GET,
+            "/workloads",
+            None,
+        ).await?;
+        Ok(resp.workloads)
+    }
+
+    /// Roll a workload over to `image`; the server keeps the old revision when the new one
+    /// does not run and pass the liveness probe within `health_timeout_secs` (server default 30)
+    pub async fn update_workload(&self, id: &str, image: &str, health_timeout_secs: Option<u32>) -> Result<Rollout, ZeroSdkError> {
+        let mut body = json!({ "image": image });
+        if let Some(secs) = health_timeout_secs {
+            body["health_timeout_secs"] = json!(secs);
+        }
+        request::<Rollout>(
+            &self.inner,
+            reqwest::Method::PUT,
+            &format!("/workloads/{}", id),
+            Some(body),
+        ).await
+    }
+
+    /// Revisions of a workload, oldest first
+    pub async fn list_revisions(&self, id: &str) -> Result<Vec<Revision>, ZeroSdkError> {
+        let resp = request::<RevisionList>(
+            &self.inner,
+            reqwest::Method::GET,
+            &format!("/workloads/{}/revisions", id),
+            None,
+        ).await?;
+        Ok(resp.revisions)
+    }
+
+    pub async fn delete_workload(&self, id: &str) -> Result<(), ZeroSdkError> {
+        request::<serde_json::Value>(
+            &self.inner,
+            reqwest::Method::DELETE,
+            "/workloads",
+            Some(json!({ "id": id })),
+        ).await?;
+        Ok(())
+    }
+}
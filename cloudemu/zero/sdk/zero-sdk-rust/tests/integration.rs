@@ -1,4 +1,13 @@
-use zero_sdk::ZeroClient;
+use zero_sdk::{Credentials, ErrorCode, RetryPolicy, ZeroClient, ZeroSdkError};
+use bytes::Bytes;
+use futures::TryStreamExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use zero_sdk::services::queue::ReceiveOptions;
+use zero_sdk::services::func::{FunctionOptions, InvocationStatus, Runtime};
+use zero_sdk::services::workload::{PortMapping, WorkloadSpec};
 use serde_json::json;
 
 // Note: These tests assume a running ZeroCloud Control Plane at localhost:8080
@@ -14,7 +23,48 @@ async fn test_store_workflow() {
     
     // List
     let buckets = client.store().list_buckets().await.unwrap();
-    assert!(buckets.contains(&bucket_name));
+    assert!(buckets.iter().any(|b| b.name == bucket_name));
+}
+
+#[tokio::test]
+async fn test_store_object_streaming() {
+    let client = ZeroClient::from_env();
+    let bucket = format!("objects-{}", uuid::Uuid::new_v4());
+    client.store().create_bucket(&bucket).await.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("zero-sdk-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let data: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    std::fs::write(dir.join("upload.bin"), &data).unwrap();
+
+    let object = client.store().put_object_from_file(&bucket, "data/upload.bin", dir.join("upload.bin")).await.unwrap();
+    assert_eq!(object.size, data.len() as u64);
+    assert!(object.sha256.is_some());
+    let size = client.store().download_object(&bucket, "data/upload.bin", dir.join("download.bin")).await.unwrap();
+    assert_eq!(size, data.len() as u64);
+    assert!(std::fs::read(dir.join("download.bin")).unwrap() == data);
+    let chunks: Vec<Bytes> = client.store().get_object_stream(&bucket, "data/upload.bin").await.unwrap()
+        .try_collect().await.unwrap();
+    assert!(chunks.len() > 1);
+    assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), data.len());
+
+    let objects = client.store().list_objects(&bucket).await.unwrap();
+    assert_eq!(objects[0].key, "data/upload.bin");
+    client.store().delete_object(&bucket, "data/upload.bin").await.unwrap();
+    assert!(client.store().list_objects(&bucket).await.unwrap().is_empty());
+
+    match client.store().get_object(&bucket, "data/upload.bin").await {
+        Err(e @ ZeroSdkError::Api { .. }) => {
+            assert!(e.is_not_found());
+            let ZeroSdkError::Api { status, message, request_id, .. } = e else { unreachable!() };
+            assert_eq!(status, 404);
+            assert_eq!(message, format!("Object {}/data/upload.bin not found", bucket));
+            assert!(request_id.is_some());
+        }
+        Err(e) => panic!("expected a NotFound API error, got {}", e),
+        Ok(_) => panic!("expected the deleted object to be missing"),
+    }
+    std::fs::remove_dir_all(dir).unwrap();
 }
 
 #[tokio::test]
@@ -31,7 +81,7 @@ async fn test_db_workflow() {
     
     // List
     let tables = client.db().list_tables().await.unwrap();
-    assert!(tables.contains(&table_name.to_string()));
+    assert!(tables.iter().any(|t| t.name == table_name));
 }
 
 #[tokio::test]
@@ -49,11 +99,171 @@ async fn test_queue_workflow() {
     // Receive
     let msg = client.queue().receive_message(q_name).await.unwrap().expect("Should have message");
     assert_eq!(msg.body, body);
+    assert!(msg.attributes.contains_key("ApproximateReceiveCount"));
     
     // Delete
     client.queue().delete_message(q_name, &msg.receipt_handle).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_queue_redrive_policy_workflow() {
+    let client = ZeroClient::from_env();
+    let q_name = format!("sdk-dlq-src-{}", uuid::Uuid::new_v4());
+    let dlq_name = format!("sdk-dlq-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue(&q_name).await.unwrap();
+    client.queue().create_queue(&dlq_name).await.unwrap();
+    assert_eq!(client.queue().get_redrive_policy(&q_name).await.unwrap(), None);
+
+    client.queue().set_redrive_policy(&q_name, &dlq_name, 3).await.unwrap();
+    let policy = client.queue().get_redrive_policy(&q_name).await.unwrap().unwrap();
+    assert_eq!(policy.dead_letter_queue, dlq_name);
+    assert_eq!(policy.max_receive_count, 3);
+    assert!(client.queue().set_redrive_policy(&q_name, &q_name, 3).await.is_err());
+
+    client.queue().delete_redrive_policy(&q_name).await.unwrap();
+    assert_eq!(client.queue().get_redrive_policy(&q_name).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_queue_batch_workflow() {
+    let client = ZeroClient::from_env();
+    let q_name = format!("sdk-batch-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue(&q_name).await.unwrap();
+
+    // Send batch
+    let sent = client.queue().send_message_batch(&q_name, &[("a", "one"), ("b", "two")]).await.unwrap();
+    assert_eq!(sent.successful.len(), 2);
+
+    // Batch receive with long polling
+    let options = ReceiveOptions { max_messages: 10, wait_time_seconds: 1, ..Default::default() };
+    let msgs = client.queue().receive_messages(&q_name, options).await.unwrap();
+    assert_eq!(msgs.len(), 2);
+
+    // Delete batch
+    let handles: Vec<(&str, &str)> = msgs.iter().map(|m| (m.id.as_str(), m.receipt_handle.as_str())).collect();
+    let deleted = client.queue().delete_message_batch(&q_name, &handles).await.unwrap();
+    assert!(deleted.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_queue_visibility_workflow() {
+    let client = ZeroClient::from_env();
+    let q_name = format!("sdk-visibility-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue_with_visibility_timeout(&q_name, 60).await.unwrap();
+    client.queue().send_message(&q_name, "work").await.unwrap();
+
+    let msg = client.queue().receive_message(&q_name).await.unwrap().expect("Should have message");
+    assert!(client.queue().receive_message(&q_name).await.unwrap().is_none());
+
+    // Releasing the message makes it receivable again
+    client.queue().change_message_visibility(&q_name, &msg.receipt_handle, 0).await.unwrap();
+    let again = client.queue().receive_message(&q_name).await.unwrap().expect("Should be visible again");
+    assert_eq!(again.id, msg.id);
+}
+
+#[tokio::test]
+async fn test_queue_delay_workflow() {
+    let client = ZeroClient::from_env();
+    let q_name = format!("sdk-delay-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue_with_delay(&q_name, 1).await.unwrap();
+
+    let err = client.queue().create_queue_with_delay(&q_name, 1000).await.unwrap_err();
+    assert_eq!(err.code(), Some(&ErrorCode::ValidationError));
+    assert_eq!(err.fields().len(), 1);
+    assert_eq!(err.fields()[0].field, "delay_seconds");
+
+    client.queue().send_message(&q_name, "later").await.unwrap();
+    client.queue().send_message_with_delay(&q_name, "now", 0).await.unwrap();
+
+    let msg = client.queue().receive_message(&q_name).await.unwrap().expect("Should have message");
+    assert_eq!(msg.body, "now");
+    assert!(client.queue().receive_message(&q_name).await.unwrap().is_none());
+
+    // The delayed message arrives within a long poll
+    let options = ReceiveOptions { wait_time_seconds: 3, ..Default::default() };
+    let msgs = client.queue().receive_messages(&q_name, options).await.unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].body, "later");
+}
+
+/// WASM module that returns its input unchanged
+const ECHO_WAT: &str = r#"(module
+  (memory (export "memory") 1)
+  (func (export "alloc") (param i32) (result i32) i32.const 1024)
+  (func (export "handle") (param $ptr i32) (param $len i32) (result i64)
+    (i64.or
+      (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+      (i64.extend_i32_u (local.get $len)))))"#;
+
+#[tokio::test]
+async fn test_wasm_function_workflow() {
+    let client = ZeroClient::from_env();
+    let name = format!("sdk-wasm-{}", uuid::Uuid::new_v4());
+
+    client.func().create_function_with_runtime(&name, "", ECHO_WAT, Runtime::Wasm).await.unwrap();
+    let out = client.func().invoke(&name, json!({ "n": 7 })).await.unwrap();
+    assert_eq!(out["result"]["n"], 7);
+}
+
+#[tokio::test]
+async fn test_function_configuration() {
+    let client = ZeroClient::from_env();
+    let name = format!("sdk-config-{}", uuid::Uuid::new_v4());
+    let options = FunctionOptions {
+        environment: [("STAGE".to_string(), "test".to_string())].into(),
+        timeout_secs: Some(5),
+        memory_mb: Some(256),
+        ..Default::default()
+    };
+
+    client.func().create_function_with_options(&name, "index.handler", "console.log('ok')", options).await.unwrap();
+    let config = client.func().get_function(&name).await.unwrap();
+    assert_eq!(config.environment["STAGE"], "test");
+    assert_eq!((config.timeout_secs, config.memory_mb), (5, 256));
+    assert!(client.func().list_function_configurations().await.unwrap().contains(&config));
+
+    let invalid = FunctionOptions { timeout_secs: Some(0), ..Default::default() };
+    assert!(client.func().create_function_with_options(&name, "index.handler", "", invalid).await.is_err());
+}
+
+#[tokio::test]
+async fn test_async_invocation_workflow() {
+    let client = ZeroClient::from_env();
+    let name = format!("sdk-async-{}", uuid::Uuid::new_v4());
+    client.func().create_function_with_runtime(&name, "", ECHO_WAT, Runtime::Wasm).await.unwrap();
+
+    let invocation = client.func().invoke_async(&name, json!({ "n": 3 })).await.unwrap();
+    assert_eq!(invocation.status, InvocationStatus::Pending);
+
+    let mut record = invocation.clone();
+    for _ in 0..50 {
+        record = client.func().get_invocation(&name, &invocation.id).await.unwrap();
+        if record.status == InvocationStatus::Succeeded {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(record.status, InvocationStatus::Succeeded);
+    assert_eq!(record.result.unwrap()["result"]["n"], 3);
+}
+
+#[tokio::test]
+async fn test_event_source_mapping_workflow() {
+    let client = ZeroClient::from_env();
+    let suffix = uuid::Uuid::new_v4();
+    let q_name = format!("sdk-esm-{}", suffix);
+    let func_name = format!("sdk-esm-func-{}", suffix);
+    client.queue().create_queue(&q_name).await.unwrap();
+    client.func().create_function(&func_name, "index.handler", "console.log('ok')").await.unwrap();
+
+    let mapping = client.func().map_queue(&func_name, &q_name, 5).await.unwrap();
+    assert_eq!(mapping.batch_size, 5);
+    assert!(client.func().list_event_source_mappings().await.unwrap().iter().any(|m| m.id == mapping.id));
+
+    client.func().delete_event_source_mapping(&mapping.id).await.unwrap();
+    assert!(!client.func().list_event_source_mappings().await.unwrap().iter().any(|m| m.id == mapping.id));
+}
+
 #[tokio::test]
 async fn test_iam_workflow() {
     let client = ZeroClient::from_env();
@@ -65,3 +275,249 @@ async fn test_iam_workflow() {
     let found = users.iter().any(|u| u["UserName"] == user);
     assert!(found);
 }
+
+#[tokio::test]
+async fn test_signed_requests() {
+    let client = ZeroClient::from_env();
+    let user = format!("signer-{}", uuid::Uuid::new_v4());
+    client.iam().create_user(&user).await.unwrap();
+
+    let key = client.iam().create_access_key(&user).await.unwrap();
+    assert_eq!(key.status, "Active");
+    let credentials = key.credentials().unwrap();
+
+    let url = std::env::var("ZERO_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+    let signed = ZeroClient::new(&url).with_credentials(credentials.clone());
+    let users = signed.iam().list_users().await.unwrap();
+    assert!(users.iter().any(|u| u["UserName"] == user));
+    let bucket = format!("signed-{}", uuid::Uuid::new_v4());
+    signed.store().create_bucket(&bucket).await.unwrap();
+
+    // Streamed bodies are signed as unsigned payloads
+    let chunks = futures::stream::iter(["streamed ", "upload"].map(Ok::<_, std::io::Error>));
+    let object = signed.store().put_object_stream(&bucket, "note.txt", chunks).await.unwrap();
+    assert_eq!(object.size, 15);
+    assert_eq!(signed.store().get_object(&bucket, "note.txt").await.unwrap(), "streamed upload");
+
+    let forged = ZeroClient::new(&url).with_credentials(Credentials::new(credentials.access_key_id.clone(), "wrong-secret"));
+    match forged.iam().list_users().await {
+        Err(ZeroSdkError::Api { status, code, .. }) => {
+            assert_eq!(status, 403);
+            assert_eq!(code, ErrorCode::AccessDenied);
+        }
+        other => panic!("expected a rejected signature, got {:?}", other.map(|_| ())),
+    }
+
+    let keys = client.iam().list_access_keys(&user).await.unwrap();
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].secret_access_key.is_none());
+    client.iam().delete_access_key(&user, &key.access_key_id).await.unwrap();
+    assert!(signed.iam().list_users().await.is_err());
+}
+
+#[tokio::test]
+async fn test_assume_role() {
+    let client = ZeroClient::from_env();
+    let role = format!("reader-{}", uuid::Uuid::new_v4().simple());
+    client.iam().create_role(&role).await.unwrap();
+    let policy = r#"{"Statement":[{"Effect":"Allow","Action":"iam:GET","Resource":"/v1/iam/*"}]}"#;
+    client.iam().attach_role_policy(&role, policy).await.unwrap();
+
+    let assumed = client.iam().assume_role(&role, "integration-test", None).await.unwrap();
+    assert!(assumed.assumed_role_arn.ends_with(&format!("{}/integration-test", role)));
+
+    let url = std::env::var("ZERO_URL").unwrap_or_else(|_| "http://localhost:8080".to_string());
+    let session = ZeroClient::new(&url).with_credentials(assumed.credentials());
+    let roles = session.iam().list_roles().await.unwrap();
+    assert!(roles.iter().any(|r| r["RoleName"] == role));
+
+    // Outside the role's policy
+    match session.store().create_bucket(&format!("denied-{}", uuid::Uuid::new_v4())).await {
+        Err(ZeroSdkError::Api { status, .. }) => assert_eq!(status, 403),
+        other => panic!("expected the role policy to deny the request, got {:?}", other.map(|_| ())),
+    }
+
+    // Session credentials are useless without their token
+    let mut tokenless = assumed.credentials();
+    tokenless.session_token = None;
+    assert!(ZeroClient::new(&url).with_credentials(tokenless).iam().list_roles().await.is_err());
+}
+
+#[tokio::test]
+async fn test_dns_workflow() {
+    let client = ZeroClient::from_env();
+    let zone = format!("z{}.zero", uuid::Uuid::new_v4().simple());
+    client.dns().create_zone(&zone).await.unwrap();
+    assert!(client.dns().list_zones().await.unwrap().iter().any(|z| z["name"] == zone.as_str()));
+
+    client.dns().put_record_set(&zone, "db", "A", Some(30), &["10.1.0.7"]).await.unwrap();
+    client.dns().put_record_set(&zone, "primary", "CNAME", None, &[&format!("db.{}", zone)]).await.unwrap();
+    assert_eq!(client.dns().list_record_sets(&zone).await.unwrap().len(), 2);
+
+    let answers = client.dns().resolve(&format!("primary.{}", zone), "A").await.unwrap();
+    assert_eq!(answers.last().unwrap()["value"], "10.1.0.7");
+
+    client.dns().delete_record_set(&zone, "primary", "CNAME").await.unwrap();
+    client.dns().delete_zone(&zone).await.unwrap();
+    let missing = client.dns().list_record_sets(&zone).await.unwrap_err();
+    assert!(missing.is_not_found());
+}
+
+#[tokio::test]
+async fn test_topic_workflow() {
+    let client = ZeroClient::from_env();
+    let topic = format!("sdk-topic-{}", uuid::Uuid::new_v4());
+    let all = format!("sdk-all-{}", uuid::Uuid::new_v4());
+    let eu = format!("sdk-eu-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue(&all).await.unwrap();
+    client.queue().create_queue(&eu).await.unwrap();
+
+    let created = client.topic().create_topic(&topic).await.unwrap();
+    assert!(created["arn"].as_str().unwrap().ends_with(&topic));
+    client.topic().subscribe_queue(&topic, &all, None).await.unwrap();
+    let eu_subscription = client.topic().subscribe_queue(&topic, &eu, Some(json!({ "region": ["eu"] }))).await.unwrap();
+    assert_eq!(client.topic().list_subscriptions(&topic).await.unwrap().len(), 2);
+
+    let result = client.topic().publish(&topic, "hello", json!({ "region": "us" })).await.unwrap();
+    assert_eq!(result["MatchedSubscriptions"], 1);
+    let result = client.topic().publish(&topic, "bonjour", json!({ "region": "eu" })).await.unwrap();
+    assert_eq!(result["MatchedSubscriptions"], 2);
+
+    let msg = client.queue().receive_message(&eu).await.unwrap().expect("Should have message");
+    let envelope: serde_json::Value = serde_json::from_str(&msg.body).unwrap();
+    assert_eq!(envelope["Message"], "bonjour");
+
+    client.topic().unsubscribe(&topic, &eu_subscription).await.unwrap();
+    client.topic().delete_topic(&topic).await.unwrap();
+    assert!(client.topic().list_subscriptions(&topic).await.unwrap_err().is_not_found());
+}
+
+#[tokio::test]
+async fn test_scheduler_workflow() {
+    let client = ZeroClient::from_env();
+    let queue = format!("sdk-ticks-{}", uuid::Uuid::new_v4());
+    let rule = format!("sdk-rule-{}", uuid::Uuid::new_v4());
+    client.queue().create_queue(&queue).await.unwrap();
+
+    let created = client.scheduler().create_rule(&rule, "rate(1 second)", "queue", &queue, Some(json!("tick"))).await.unwrap();
+    assert!(created["arn"].as_str().unwrap().ends_with(&rule));
+    assert!(client.scheduler().list_rules().await.unwrap().iter().any(|r| r["name"] == rule.as_str()));
+
+    // The server's scheduler loop fires the rule within a few ticks
+    let mut msg = None;
+    for _ in 0..50 {
+        msg = client.queue().receive_message(&queue).await.unwrap();
+        if msg.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(msg.expect("Rule should have fired").body, "tick");
+    assert_eq!(client.scheduler().get_rule(&rule).await.unwrap()["last_status"], "Succeeded");
+
+    client.scheduler().delete_rule(&rule).await.unwrap();
+    assert!(client.scheduler().get_rule(&rule).await.unwrap_err().is_not_found());
+}
+
+#[tokio::test]
+async fn test_autoscaling_workflow() {
+    use zero_sdk::services::autoscaling::ScalingMetric;
+
+    let client = ZeroClient::from_env();
+    let group = format!("sdk-asg-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]);
+
+    let created = client.autoscaling().create_group(&group, "nginx", 1, 3, ScalingMetric::Cpu(200.0)).await.unwrap();
+    assert_eq!(created["desired_capacity"], 1);
+    assert_eq!(created["instances"].as_array().unwrap().len(), 1);
+    assert!(client.autoscaling().list_groups().await.unwrap().iter().any(|g| g["name"] == group.as_str()));
+
+    let missing_queue = ScalingMetric::QueueDepth { queue: format!("{}-missing", group), messages_per_instance: 5.0 };
+    let err = client.autoscaling().create_group(&format!("{}-q", group), "worker", 0, 2, missing_queue).await.unwrap_err();
+    assert!(err.is_not_found());
+
+    client.autoscaling().delete_group(&group).await.unwrap();
+    assert!(client.autoscaling().get_group(&group).await.unwrap_err().is_not_found());
+}
+
+#[tokio::test]
+async fn test_workload_workflow() {
+    let client = ZeroClient::from_env();
+    let id = format!("sdk-workload-{}", uuid::Uuid::new_v4());
+    let mut spec = WorkloadSpec::new(&id, "nginx:latest");
+    spec.memory_mb = Some(128);
+    spec.ports.push(PortMapping { host_port: 0, container_port: 80, protocol: Default::default() });
+
+    let workload = client.workload().create_workload(&spec).await.unwrap();
+    assert_eq!(workload.id, id);
+    assert!(workload.ports.iter().all(|port| port.container_port == 80 && port.host_port != 0));
+    assert!(client.workload().list_workloads().await.unwrap().iter().any(|w| w.id == id));
+
+    client.workload().delete_workload(&id).await.unwrap();
+    assert!(!client.workload().list_workloads().await.unwrap().iter().any(|w| w.id == id));
+}
+
+/// Serve `responses` in turn, the last one for every further request, and count the requests
+async fn flaky_server(responses: Vec<(u16, &'static str)>, delay: Duration) -> (String, Arc<AtomicUsize>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let (status, body) = responses[n.min(responses.len() - 1)];
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                tokio::time::sleep(delay).await;
+                let resp = format!("HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body);
+                let _ = socket.write_all(resp.as_bytes()).await;
+            });
+        }
+    });
+    (url, requests)
+}
+
+#[tokio::test]
+async fn test_retry_policy() {
+    let unavailable = r#"{"code":"ServiceUnavailable","message":"restarting"}"#;
+    let policy = RetryPolicy { initial_backoff: Duration::from_millis(10), ..Default::default() };
+
+    // Idempotent requests are retried until they succeed
+    let (url, requests) = flaky_server(vec![(503, unavailable), (503, unavailable), (200, r#"{"buckets":["a"]}"#)], Duration::ZERO).await;
+    let client = ZeroClient::builder(&url).retry_policy(policy.clone()).build().unwrap();
+    assert_eq!(client.store().list_buckets().await.unwrap()[0].name, "a");
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // Or until the attempts run out
+    let (url, requests) = flaky_server(vec![(503, unavailable)], Duration::ZERO).await;
+    let client = ZeroClient::builder(&url).retry_policy(policy.clone()).build().unwrap();
+    assert_eq!(client.store().list_buckets().await.unwrap_err().code(), Some(&ErrorCode::Unknown("ServiceUnavailable".into())));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // A POST the server may have handled is not sent twice
+    let client = ZeroClient::builder(&url).retry_policy(policy).build().unwrap();
+    assert!(client.store().create_bucket("b").await.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+    // Nor are requests that failed for good
+    let (url, requests) = flaky_server(vec![(404, r#"{"code":"NotFound","message":"gone"}"#)], Duration::ZERO).await;
+    let client = ZeroClient::new(&url);
+    assert!(client.store().list_buckets().await.unwrap_err().is_not_found());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_request_timeout() {
+    let (url, requests) = flaky_server(vec![(200, r#"{"buckets":[]}"#)], Duration::from_secs(2)).await;
+    let client = ZeroClient::builder(&url)
+        .timeout(Duration::from_millis(100))
+        .retry_policy(RetryPolicy::none())
+        .build()
+        .unwrap();
+    match client.store().list_buckets().await {
+        Err(ZeroSdkError::Http(e)) => assert!(e.is_timeout()),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
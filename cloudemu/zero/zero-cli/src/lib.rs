@@ -1,11 +1,16 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use zero_control_core::ZeroProvider;
+use zero_control_core::services::audit::{self, AuditEvent};
+use zero_control_core::services::dry_run::DRY_RUN_HEADER;
+use zero_control_core::services::namespace::{DEFAULT_NAMESPACE, NAMESPACE_HEADER};
 use zero_data_core::ZeroEngine;
-use zero_control_spi::{ZeroRequest, ZeroService};
+use zero_control_spi::{LivenessProbe, PortMapping, RestartPolicy, ZeroBody, ZeroRequest, ZeroResponse, ZeroService};
 use std::sync::Arc;
 use colored::*;
 use serde_json::json;
 
+pub mod top;
+
 #[derive(Parser)]
 #[command(name = "zero")]
 #[command(about = "ZeroCloud Private Cloud CLI", long_about = None)]
@@ -13,9 +18,21 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
 
-    /// Force the use of native OS drivers (Hyper-V / KVM) instead of Docker
+    /// Force the use of native OS drivers (Hyper-V / KVM / Lima) instead of Docker
     #[arg(long, global = true)]
     pub native: bool,
+
+    /// Container runtime or hypervisor to use instead of detecting one; goes before the command
+    #[arg(long, value_parser = ["docker", "podman", "containerd", "lima", "native", "mock"])]
+    pub runtime: Option<String>,
+
+    /// Run destructive commands without asking for confirmation
+    #[arg(short, long, global = true)]
+    pub yes: bool,
+
+    /// Validate the command's changes and show what they would be, changing nothing
+    #[arg(long, global = true)]
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand)]
@@ -30,6 +47,21 @@ pub enum Commands {
         #[command(subcommand)]
         action: VolumeAction,
     },
+    /// Manage the images cached by the compute driver
+    Image {
+        #[command(subcommand)]
+        action: ImageAction,
+    },
+    /// Manage namespaces and their CPU, memory, volume, workload and queue quotas
+    Ns {
+        #[command(subcommand)]
+        action: NsAction,
+    },
+    /// Show what a namespace uses of its quotas
+    Quota {
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
     /// Manage Nodes
     Node {
         #[command(subcommand)]
@@ -75,14 +107,223 @@ pub enum Commands {
         #[command(subcommand)]
         action: EksAction,
     },
+    /// Manage Pub/Sub Topics (SNS)
+    Topic {
+        #[command(subcommand)]
+        action: TopicAction,
+    },
+    /// Manage scheduled rules (EventBridge Scheduler)
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Manage autoscaling groups of workloads
+    Asg {
+        #[command(subcommand)]
+        action: AsgAction,
+    },
+    /// Back up or restore the database and volume data of the control plane
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Read the audit trail of state-changing requests
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// Tear down emulated resources
+    System {
+        #[command(subcommand)]
+        action: SystemAction,
+    },
+    /// Describe the ZeroCloud API
+    Api {
+        #[command(subcommand)]
+        action: ApiAction,
+    },
+    /// Live terminal dashboard of node usage, workloads, queues and recent events
+    Top {
+        /// Seconds between refreshes
+        #[arg(long, default_value_t = top::DEFAULT_REFRESH_SECS, value_parser = clap::value_parser!(u64).range(1..))]
+        refresh: u64,
+    },
+    /// Print a shell completion script, e.g. `zero completions bash > /etc/bash_completion.d/zero`
+    Completions {
+        shell: clap_complete::Shell,
+    },
+}
+
+impl Commands {
+    /// Question to confirm before running a command that deletes or overwrites state
+    pub fn confirmation(&self) -> Option<String> {
+        match self {
+            Commands::Workload { action: WorkloadAction::Down { id, namespace } } =>
+                Some(format!("Delete workload {} in namespace {}?", id, namespace)),
+            Commands::System { action: SystemAction::Reset { service } } =>
+                Some(format!("Delete all {} state of every namespace?", service)),
+            Commands::Backup { action: BackupAction::Restore { input } } =>
+                Some(format!("Replace the database and volumes with {}?", input.display())),
+            Commands::Eks { action: EksAction::Delete { name } } =>
+                Some(format!("Delete cluster {} and its node groups?", name)),
+            Commands::Ns { action: NsAction::Delete { name } } =>
+                Some(format!("Delete namespace {}?", name)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ApiAction {
+    /// Write the OpenAPI document of the API, e.g. to generate Python or TypeScript clients
+    ExportSpec {
+        /// File to write; the document is printed when omitted
+        #[arg(short, long)] output: Option<std::path::PathBuf>,
+        /// Base URL clients generated from the document call
+        #[arg(long, default_value = "http://localhost:8080")] server_url: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SystemAction {
+    /// Delete the resources of every namespace so the next run starts from a clean slate
+    Reset {
+        /// Only clear this service; `all` also stops workloads and removes scaling groups and volumes
+        #[arg(short, long, default_value = "all", value_parser = ["store", "db", "queue", "all"])] service: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditAction {
+    /// Print the latest events, oldest first
+    Tail {
+        /// Number of events to print
+        #[arg(short = 'n', long, default_value_t = 20)] lines: usize,
+        /// Only events at or after this RFC 3339 time
+        #[arg(long)] since: Option<String>,
+        /// Only events at or before this RFC 3339 time
+        #[arg(long, conflicts_with = "follow")] until: Option<String>,
+        /// Keep printing events as they are recorded
+        #[arg(short, long)] follow: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AsgAction {
+    /// Create a group that scales on node CPU usage or on the depth of a queue
+    Create {
+        #[arg(short, long)] name: String,
+        #[arg(long)] image: String,
+        #[arg(long, default_value_t = 1)] min: u32,
+        #[arg(long)] max: u32,
+        /// Starting size; defaults to --min
+        #[arg(long)] desired: Option<u32>,
+        /// CPU usage in percent to hold
+        #[arg(long, conflicts_with = "queue", required_unless_present = "queue")] cpu_target: Option<f64>,
+        /// Queue whose depth drives the group
+        #[arg(long, requires = "messages_per_instance")] queue: Option<String>,
+        /// Visible messages one instance should handle
+        #[arg(long, requires = "queue")] messages_per_instance: Option<f64>,
+        /// Seconds between capacity changes
+        #[arg(long)] cooldown: Option<u32>,
+    },
+    /// List groups with their capacity and instances
+    Ls,
+    /// Delete a group and its instances
+    Delete { #[arg(short, long)] name: String },
+}
+
+#[derive(Subcommand)]
+pub enum BackupAction {
+    /// Write the database and every volume and bucket to a .tar.gz archive
+    Create { #[arg(short, long)] output: std::path::PathBuf },
+    /// Restore an archive written by `backup create`, replacing the database
+    Restore { #[arg(short, long)] input: std::path::PathBuf },
+}
+
+#[derive(Subcommand)]
+pub enum ScheduleAction {
+    /// Create a rule that runs a function, queue or webhook on a schedule
+    Create {
+        #[arg(short, long)] name: String,
+        /// `rate(5 minutes)`, `cron(0 12 ? * MON-FRI *)` or a crontab line such as `*/5 * * * *`
+        #[arg(short, long)] schedule: String,
+        #[arg(long, conflicts_with_all = ["queue", "webhook"], required_unless_present_any = ["queue", "webhook"])] function: Option<String>,
+        #[arg(long, conflicts_with = "webhook")] queue: Option<String>,
+        #[arg(long)] webhook: Option<String>,
+        /// JSON sent on every run instead of the scheduled event
+        #[arg(long)] input: Option<String>,
+    },
+    /// List rules with their next and last runs
+    Ls,
+    /// Delete a rule
+    Delete { #[arg(short, long)] name: String },
+}
+
+#[derive(Subcommand)]
+pub enum TopicAction {
+    /// Create a topic
+    Create { #[arg(short, long)] name: String },
+    /// List topics
+    Ls,
+    /// Delete a topic and its subscriptions
+    Delete { #[arg(short, long)] name: String },
+    /// Subscribe a queue or a webhook URL to a topic
+    Subscribe {
+        #[arg(short, long)] name: String,
+        #[arg(long, conflicts_with = "webhook", required_unless_present = "webhook")] queue: Option<String>,
+        #[arg(long)] webhook: Option<String>,
+        /// Deliver only messages whose attributes match this JSON policy, e.g. '{"event": ["created"]}'
+        #[arg(long)] filter_policy: Option<String>,
+        /// Deliver the message body alone instead of the JSON notification envelope
+        #[arg(long)] raw: bool,
+    },
+    /// List a topic's subscriptions
+    Subscriptions { #[arg(short, long)] name: String },
+    /// Delete a subscription
+    Unsubscribe { #[arg(short, long)] name: String, #[arg(long)] id: String },
+    /// Publish a message to every matching subscription
+    Publish {
+        #[arg(short, long)] name: String,
+        #[arg(short, long)] message: String,
+        #[arg(long)] subject: Option<String>,
+        /// Message attribute as KEY=VALUE; numeric values are sent as numbers (repeatable)
+        #[arg(short, long = "attribute", value_parser = parse_env_var)] attributes: Vec<(String, String)>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum EksAction {
     /// Create a cluster
-    Create { #[arg(short, long)] name: String },
+    Create {
+        #[arg(short, long)] name: String,
+        /// Kubernetes version of the control plane
+        #[arg(long)] version: Option<String>,
+        /// Write the cluster's kubeconfig to this file
+        #[arg(long)] kubeconfig: Option<std::path::PathBuf>,
+    },
     /// Describe a cluster
-    Describe { #[arg(short, long)] name: String },
+    Describe {
+        #[arg(short, long)] name: String,
+        /// Write the cluster's kubeconfig to this file
+        #[arg(long)] kubeconfig: Option<std::path::PathBuf>,
+    },
+    /// Delete a cluster
+    Delete { #[arg(short, long)] name: String },
+    /// List clusters
+    Ls,
+    /// Create a node group and register its nodes with the cluster
+    CreateNodegroup {
+        #[arg(long)] cluster: String,
+        #[arg(short, long)] name: String,
+        /// Number of nodes to boot
+        #[arg(long, default_value_t = 2)] nodes: u32,
+        #[arg(long)] instance_type: Option<String>,
+    },
+    /// List a cluster's node groups
+    Nodegroups { #[arg(long)] cluster: String },
+    /// Delete a node group and its nodes
+    DeleteNodegroup { #[arg(long)] cluster: String, #[arg(short, long)] name: String },
 }
 
 #[derive(Subcommand)]
@@ -91,13 +332,83 @@ pub enum WorkloadAction {
     Up {
         #[arg(short, long)]
         id: String,
+        // `-i` is taken by --id
+        #[arg(long)]
+        image: String,
+        /// Attach a volume as VOLUME:TARGET[:ro], TARGET being a container path or a VM disk such as vdb (repeatable)
+        #[arg(short, long = "volume", value_parser = parse_volume)]
+        volumes: Vec<(String, String, bool)>,
+        /// Publish a port as HOST:CONTAINER[/udp], or CONTAINER on a free host port (repeatable)
+        #[arg(short, long = "publish", value_parser = parse_port)]
+        ports: Vec<PortMapping>,
+        /// Only place on nodes labelled KEY=VALUE (repeatable)
+        #[arg(long = "selector", value_parser = parse_env_var)]
+        selectors: Vec<(String, String)>,
+        /// Required node affinity: KEY=V1,V2 (in), KEY!=V1,V2 (not in), KEY (exists) or !KEY (does not exist) (repeatable)
+        #[arg(long, value_parser = parse_affinity)]
+        affinity: Vec<serde_json::Value>,
+        /// Tolerate a node taint, as KEY=VALUE or KEY for any value (repeatable)
+        #[arg(long = "toleration")]
+        tolerations: Vec<String>,
+        /// CPUs counted against the namespace quota (default 1)
+        #[arg(long)]
+        cpu: Option<f64>,
+        /// Memory in MB counted against the namespace quota (default 512)
+        #[arg(long)]
+        memory_mb: Option<i64>,
+        /// When the server restarts the workload: never, on-failure or always
+        #[arg(long, default_value = "never", value_parser = parse_restart)]
+        restart: RestartPolicy,
+        /// Check the workload is alive with tcp:PORT or http:PORT/PATH; it fails after the failure threshold
+        #[arg(long, value_parser = parse_liveness)]
+        liveness: Option<Box<LivenessProbe>>,
+        /// Seconds between liveness probes
+        #[arg(long, requires = "liveness")]
+        liveness_interval: Option<u32>,
+        /// Consecutive failed liveness probes after which the workload fails
+        #[arg(long, requires = "liveness")]
+        liveness_failures: Option<u32>,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+    /// Roll a workload over to a new image, keeping its old revision if the new one is not healthy
+    Update {
         #[arg(short, long)]
+        id: String,
+        #[arg(long)]
         image: String,
+        /// Seconds the new revision has to run and pass the workload's liveness probe
+        #[arg(long, default_value_t = 30)]
+        timeout: u32,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+    /// List a workload's revisions
+    Revisions {
+        #[arg(short, long)]
+        id: String,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
     },
     /// Delete a workload
     Down {
         #[arg(short, long)]
         id: String,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+    /// Show a workload's CPU, memory and network usage, sampled now
+    Stats {
+        #[arg(short, long)]
+        id: String,
+        /// Seconds each row aggregates
+        #[arg(long, default_value_t = 60)]
+        period: u32,
+        /// Samples to take, 5 seconds apart
+        #[arg(long, default_value_t = 1)]
+        samples: u32,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
     },
 }
 
@@ -109,13 +420,94 @@ pub enum VolumeAction {
         id: String,
         #[arg(short, long)]
         size: i32,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+    /// Grow a volume to a new size in GB, keeping its data
+    Resize {
+        #[arg(short, long)]
+        id: String,
+        #[arg(short, long)]
+        size: i32,
+        #[arg(long, default_value = DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ImageAction {
+    /// Pull an image so workloads using it start without downloading it
+    Pull {
+        /// Image reference; one without a tag gets :latest
+        reference: String,
+    },
+    /// List cached images with their sizes
+    Ls,
+    /// Remove the pulled or used images no workload uses
+    Prune {
+        /// Keep images used in the last SECS seconds
+        #[arg(long, value_name = "SECS", default_value_t = 0)]
+        unused_for: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum NsAction {
+    /// Create a namespace; a quota left out is unlimited
+    Create {
+        name: String,
+        #[arg(long)] cpu: Option<f64>,
+        #[arg(long)] memory_mb: Option<i64>,
+        #[arg(long)] volume_gb: Option<i64>,
+        /// Most containers running at once
+        #[arg(long)] workloads: Option<i64>,
+        #[arg(long)] queues: Option<i64>,
+    },
+    /// List namespaces with their quotas and usage
+    Ls,
+    /// Replace the quota of a namespace; a quota left out becomes unlimited
+    Quota {
+        name: String,
+        #[arg(long)] cpu: Option<f64>,
+        #[arg(long)] memory_mb: Option<i64>,
+        #[arg(long)] volume_gb: Option<i64>,
+        /// Most containers running at once
+        #[arg(long)] workloads: Option<i64>,
+        #[arg(long)] queues: Option<i64>,
     },
+    /// Delete an empty namespace
+    Delete { name: String },
 }
 
 #[derive(Subcommand)]
 pub enum NodeAction {
     /// List all nodes
     List,
+    /// Register a node for workload placement
+    Register {
+        #[arg(long)]
+        hostname: String,
+        #[arg(long)]
+        ip: String,
+        /// KEY=VALUE label (repeatable)
+        #[arg(short, long = "label", value_parser = parse_env_var)]
+        labels: Vec<(String, String)>,
+        /// Taint as KEY=VALUE or KEY; only workloads tolerating it are placed on the node (repeatable)
+        #[arg(short, long = "taint")]
+        taints: Vec<String>,
+        /// Docker endpoint the node runs workloads on, e.g. tcp://192.168.1.20:2375
+        #[arg(long)]
+        endpoint: Option<String>,
+    },
+    /// Replace the labels and taints of a node
+    Label {
+        /// Node ID or hostname
+        node: String,
+        #[arg(short, long = "label", value_parser = parse_env_var)]
+        labels: Vec<(String, String)>,
+        #[arg(short, long = "taint")]
+        taints: Vec<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -127,6 +519,56 @@ pub enum NetworkAction {
         #[arg(short, long, default_value = "10.0.0.0/24")]
         cidr: String,
     },
+    /// Connect a workload to a network
+    Connect {
+        #[arg(short, long)]
+        id: String,
+        #[arg(short, long)]
+        workload: String,
+        /// Static address in the network's CIDR block; the lowest free one when omitted
+        #[arg(long)]
+        ip: Option<String>,
+    },
+    /// Manage the security groups filtering a network's traffic
+    SecurityGroup {
+        #[command(subcommand)]
+        action: SecurityGroupAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SecurityGroupAction {
+    /// Add a security group to a network
+    Create {
+        #[arg(short, long)]
+        network: String,
+        #[arg(long)]
+        name: String,
+        /// DIRECTION:PROTOCOL[:PORTS]:CIDR, e.g. ingress:tcp:80-443:10.0.0.0/8 (repeatable)
+        #[arg(short, long = "rule", value_parser = parse_security_rule)]
+        rules: Vec<serde_json::Value>,
+    },
+    /// Replace the rules of a security group
+    Update {
+        #[arg(short, long)]
+        network: String,
+        #[arg(short, long)]
+        id: String,
+        #[arg(short, long = "rule", value_parser = parse_security_rule)]
+        rules: Vec<serde_json::Value>,
+    },
+    /// List the security groups of a network
+    List {
+        #[arg(short, long)]
+        network: String,
+    },
+    /// Remove a security group from a network
+    Delete {
+        #[arg(short, long)]
+        network: String,
+        #[arg(short, long)]
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -143,28 +585,121 @@ pub enum DbAction {
     Create { #[arg(short, long)] name: String, #[arg(short, long, default_value="id")] pk: String },
     /// List tables
     Ls,
+    /// Manage item expiry (TTL)
+    Ttl {
+        #[command(subcommand)]
+        action: TtlAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TtlAction {
+    /// Enable TTL on a table using the given item attribute (epoch seconds)
+    Enable { #[arg(short, long)] table: String, #[arg(short, long, default_value="ttl")] attribute: String },
+    /// Disable TTL on a table
+    Disable { #[arg(short, long)] table: String },
+    /// Show the TTL configuration of a table
+    Describe { #[arg(short, long)] table: String },
 }
 
 #[derive(Subcommand)]
 pub enum FuncAction {
     /// Deploy a function
-    Deploy { #[arg(short, long)] name: String, #[arg(short, long)] code: String, #[arg(short, long)] handler: String },
+    Deploy {
+        #[arg(short, long)] name: String,
+        /// Source file or code (inline), image reference (docker), or module file (wasm)
+        #[arg(short, long)] code: String,
+        #[arg(short = 'H', long, default_value = "index.handler")] handler: String,
+        /// Execution environment: docker, wasm or inline
+        #[arg(short, long, default_value = "inline", value_parser = ["docker", "wasm", "inline"])] runtime: String,
+        /// Environment variable as KEY=VALUE (repeatable)
+        #[arg(short, long = "env", value_parser = parse_env_var)] env: Vec<(String, String)>,
+        /// Seconds an invocation may run before it is aborted
+        #[arg(short, long)] timeout: Option<u32>,
+        /// Memory limit in MB
+        #[arg(short, long)] memory: Option<u32>,
+    },
+    /// Show a function's configuration
+    Describe { #[arg(short, long)] name: String },
     /// Invoke a function
     Invoke { #[arg(short, long)] name: String, #[arg(short, long)] payload: String },
     /// List functions
     Ls,
+    /// Invoke a function with batches of messages from a queue
+    MapQueue {
+        #[arg(short, long)] function: String,
+        #[arg(short, long)] queue: String,
+        /// Maximum messages per invocation (1-10)
+        #[arg(short, long, default_value_t = 10)] batch_size: u32,
+        /// Retries before a failing message is sent to the DLQ (or dropped)
+        #[arg(long)] max_retries: Option<u32>,
+        /// Queue that receives messages which exhausted their retries
+        #[arg(long, requires = "max_retries")] dlq: Option<String>,
+    },
+    /// List event source mappings
+    Mappings,
+    /// Delete an event source mapping
+    Unmap { #[arg(long)] id: String },
 }
 
 #[derive(Subcommand)]
 pub enum QueueAction {
-    /// Create a queue
-    Create { #[arg(short, long)] name: String },
+    /// Create a queue, optionally with a dead-letter queue
+    Create {
+        #[arg(short, long)] name: String,
+        /// Dead-letter queue that receives messages after too many receives
+        #[arg(long)] dlq: Option<String>,
+        #[arg(long, default_value_t = 5)] max_receive_count: u32,
+        /// Create a FIFO queue (name must end with .fifo)
+        #[arg(long)] fifo: bool,
+        /// Deduplicate FIFO messages by a hash of their body
+        #[arg(long, requires = "fifo")] content_based_dedup: bool,
+        /// Seconds a received message stays hidden (0-43200, default 30)
+        #[arg(long)] visibility_timeout: Option<u32>,
+        /// Seconds new messages stay hidden after being sent (0-900, default 0)
+        #[arg(long)] delay_seconds: Option<u32>,
+    },
     /// Send a message
-    Send { #[arg(short, long)] name: String, #[arg(short, long)] body: String },
+    Send {
+        #[arg(short, long)] name: String,
+        #[arg(short, long)] body: String,
+        /// Message group (FIFO queues)
+        #[arg(short, long)] group_id: Option<String>,
+        /// Deduplication ID (FIFO queues)
+        #[arg(long)] dedup_id: Option<String>,
+        /// Delay delivery by this many seconds, overriding the queue delay (standard queues)
+        #[arg(long)] delay_seconds: Option<u32>,
+    },
     /// Receive messages (with Visibility Timeout)
-    Receive { #[arg(short, long)] name: String },
+    Receive {
+        #[arg(short, long)] name: String,
+        /// Maximum number of messages to return (1-10)
+        #[arg(short, long, default_value_t = 1)] max_messages: usize,
+        /// Seconds to wait for messages when the queue is empty (0-20)
+        #[arg(short, long, default_value_t = 0)] wait: u64,
+        /// Override the queue's visibility timeout for these messages
+        #[arg(long)] visibility_timeout: Option<u32>,
+    },
+    /// Send up to 10 messages in one request
+    SendBatch {
+        #[arg(short, long)] name: String,
+        #[arg(short, long = "body", required = true)] bodies: Vec<String>,
+    },
     /// Delete a message (using ReceiptHandle)
     Delete { #[arg(short, long)] name: String, #[arg(long)] handle: String },
+    /// Extend (or end, with 0) the visibility timeout of an in-flight message
+    ChangeVisibility {
+        #[arg(short, long)] name: String,
+        #[arg(long)] handle: String,
+        #[arg(short, long)] timeout: u32,
+    },
+    /// Delete up to 10 messages in one request
+    DeleteBatch {
+        #[arg(short, long)] name: String,
+        #[arg(long = "handle", required = true)] handles: Vec<String>,
+    },
+    /// Move messages from a dead-letter queue back to their source queue
+    Redrive { #[arg(short, long)] name: String, #[arg(short, long)] destination: Option<String> },
     /// List queues
     Ls,
 }
@@ -181,6 +716,15 @@ pub enum IamAction {
     CreateRole { #[arg(long)] rolename: String },
     /// List roles
     ListRoles,
+    /// Attach a permissions policy to a role
+    AttachRolePolicy { #[arg(long)] rolename: String, #[arg(short, long)] policy: String },
+    /// Get temporary credentials for a role
+    AssumeRole {
+        #[arg(long)] rolename: String,
+        #[arg(long)] session_name: String,
+        /// Session lifetime in seconds (900-43200)
+        #[arg(long)] duration: Option<i64>,
+    },
     /// Create a group
     CreateGroup { #[arg(long)] groupname: String },
     /// List groups
@@ -192,18 +736,118 @@ pub enum LbAction {
     /// Create a Load Balancer
     Create { #[arg(short, long)] name: String, #[arg(short, long, default_value="application")] lb_type: String },
     /// Create a Target Group
-    CreateTargetGroup { #[arg(short, long)] name: String, #[arg(short, long, default_value_t=80)] port: i32 },
+    CreateTargetGroup {
+        #[arg(short, long)] name: String,
+        #[arg(short, long, default_value_t=80)] port: i32,
+        #[arg(long, default_value = "HTTP", value_parser = ["HTTP", "TCP"])] protocol: String,
+        /// Path probed on HTTP targets
+        #[arg(long)] health_check_path: Option<String>,
+        /// Seconds between health checks of each target
+        #[arg(long)] health_check_interval: Option<u32>,
+        /// Consecutive passed checks before a target is healthy
+        #[arg(long)] healthy_threshold: Option<u32>,
+        /// Consecutive failed checks before a target is unhealthy
+        #[arg(long)] unhealthy_threshold: Option<u32>,
+    },
     /// Register target to group
     Register { #[arg(long)] group: String, #[arg(long)] id: String, #[arg(short, long, default_value_t=80)] port: i32 },
     /// Create a Listener
     CreateListener { #[arg(long)] lb: String, #[arg(short, long)] port: i32, #[arg(long)] target_group: String },
+    /// Show the health of a target group's targets
+    Health { #[arg(long)] group: String },
     /// List Load Balancers
     Ls,
 }
 
+/// Parse a `KEY=VALUE` environment variable argument
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid KEY=VALUE: no `=` found in `{}`", s))
+}
+
+/// Parse a node affinity rule into its API form
+fn parse_affinity(s: &str) -> Result<serde_json::Value, String> {
+    let values = |values: &str| values.split(',').map(str::to_string).collect::<Vec<_>>();
+    let (key, operator, values) = if let Some((key, list)) = s.split_once("!=") {
+        (key, "NotIn", values(list))
+    } else if let Some((key, list)) = s.split_once('=') {
+        (key, "In", values(list))
+    } else if let Some(key) = s.strip_prefix('!') {
+        (key, "DoesNotExist", Vec::new())
+    } else {
+        (s, "Exists", Vec::new())
+    };
+    if key.is_empty() || values.iter().any(String::is_empty) {
+        return Err(format!("invalid affinity rule `{}`: use KEY=V1,V2, KEY!=V1,V2, KEY or !KEY", s));
+    }
+    Ok(json!({ "key": key, "operator": operator, "values": values }))
+}
+
+/// Parse a security group rule into its API form
+fn parse_security_rule(s: &str) -> Result<serde_json::Value, String> {
+    let invalid = || format!("invalid rule `{}`: use DIRECTION:PROTOCOL[:PORTS]:CIDR, e.g. ingress:tcp:443:0.0.0.0/0", s);
+    let parts: Vec<&str> = s.split(':').collect();
+    let (direction, protocol, ports, cidr) = match parts[..] {
+        [direction, protocol, cidr] => (direction, protocol, None, cidr),
+        [direction, protocol, ports, cidr] => (direction, protocol, Some(ports), cidr),
+        _ => return Err(invalid()),
+    };
+    if !matches!(direction, "ingress" | "egress") || !matches!(protocol, "tcp" | "udp" | "icmp" | "all") || cidr.is_empty() {
+        return Err(invalid());
+    }
+    let mut rule = json!({ "direction": direction, "protocol": protocol, "cidr": cidr });
+    if let Some(ports) = ports {
+        let (from, to) = ports.split_once('-').unwrap_or((ports, ports));
+        let (from, to) = (from.parse::<u16>().map_err(|_| invalid())?, to.parse::<u16>().map_err(|_| invalid())?);
+        rule["from_port"] = json!(from);
+        rule["to_port"] = json!(to);
+    }
+    Ok(rule)
+}
+
+fn parse_port(s: &str) -> Result<PortMapping, String> {
+    s.parse().map_err(|e: zero_control_spi::ZeroError| e.to_string())
+}
+
+fn parse_restart(s: &str) -> Result<RestartPolicy, String> {
+    s.parse().map_err(|e: zero_control_spi::ZeroError| e.to_string())
+}
+
+fn parse_liveness(s: &str) -> Result<Box<LivenessProbe>, String> {
+    s.parse().map(Box::new).map_err(|e: zero_control_spi::ZeroError| e.to_string())
+}
+
+fn parse_volume(s: &str) -> Result<(String, String, bool), String> {
+    let (spec, read_only) = match s.strip_suffix(":ro") {
+        Some(spec) => (spec, true),
+        None => (s, false),
+    };
+    spec.split_once(':')
+        .filter(|(volume, target)| !volume.is_empty() && !target.is_empty())
+        .map(|(volume, target)| (volume.to_string(), target.to_string(), read_only))
+        .ok_or_else(|| format!("invalid VOLUME:TARGET[:ro]: `{}`", s))
+}
+
 pub async fn run_cli(cli: Cli) -> anyhow::Result<()> {
+    // Completion scripts need no engine
+    if let Commands::Completions { shell } = cli.command {
+        write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+    if let Some(question) = cli.command.confirmation().filter(|_| !cli.dry_run) {
+        if !cli.yes && !confirm(&question)? {
+            println!("{}", "Cancelled".yellow());
+            return Ok(());
+        }
+    }
+
     check_wsl_preflight();
-    let engine = if cli.native {
+    let engine = if let Some(runtime) = &cli.runtime {
+        println!("{} using the {} runtime...", "🔧".blue(), runtime);
+        ZeroEngine::for_runtime(runtime)
+    } else if cli.native {
         println!("{} forcing native OS drivers...", "🔧".blue());
         ZeroEngine::native()
     } else {
@@ -211,7 +855,58 @@ pub async fn run_cli(cli: Cli) -> anyhow::Result<()> {
     }.map_err(|e| anyhow::anyhow!("Failed to init ZeroEngine: {}", e))?;
     
     let provider = ZeroProvider::new(Arc::new(engine));
-    execute_command(cli.command, &provider).await
+    if cli.dry_run {
+        execute_dry_run(cli.command, &provider).await
+    } else {
+        execute_command(cli.command, &provider).await
+    }
+}
+
+/// Write the completion script of `zero` for `shell`
+pub fn write_completions(shell: clap_complete::Shell, out: &mut dyn std::io::Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "zero", out);
+}
+
+/// Ask `question` on the terminal, defaulting to no; without one, only `--yes` confirms
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    use std::io::IsTerminal;
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("{} Pass --yes to confirm when not running in a terminal", question);
+    }
+    Ok(dialoguer::Confirm::new().with_prompt(question).default(false).interact()?)
+}
+
+/// What a namespace from `GET /v1/namespaces/{name}` uses of each quota, keyed as in its `quota`
+pub fn quota_usage(namespace: &serde_json::Value) -> [(&'static str, &serde_json::Value); 5] {
+    [
+        ("cpu", &namespace["used"]["cpu"]),
+        ("memory_mb", &namespace["used"]["memory_mb"]),
+        ("volume_gb", &namespace["used"]["volume_gb"]),
+        ("workloads", &namespace["workloads"]),
+        ("queues", &namespace["queues"]),
+    ]
+}
+
+/// Headers selecting the namespace a request works in
+fn namespace_headers(namespace: &str) -> std::collections::HashMap<String, String> {
+    std::collections::HashMap::from([(NAMESPACE_HEADER.to_string(), namespace.to_string())])
+}
+
+/// Save a cluster's kubeconfig for `kubectl --kubeconfig`
+async fn write_kubeconfig(provider: &ZeroProvider, cluster: &str, path: &std::path::Path) -> anyhow::Result<()> {
+    let req = ZeroRequest {
+        method: "GET".into(),
+        path: format!("/v1/eks/clusters/{}", cluster),
+        headers: std::collections::HashMap::new(),
+        body: ZeroBody::empty()
+    };
+    let resp = provider.handle_request(req).await?;
+    let description: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+    let kubeconfig = description["cluster"]["kubeconfig"].as_str()
+        .ok_or_else(|| anyhow::anyhow!("Cluster {} has no kubeconfig", cluster))?;
+    std::fs::write(path, kubeconfig)?;
+    println!("{} kubeconfig to {}", "📝 Wrote".green(), path.display());
+    Ok(())
 }
 
 fn check_wsl_preflight() {
@@ -234,43 +929,301 @@ fn check_wsl_preflight() {
     }
 }
 
+/// Sends the requests of a command, marking them as dry runs when asked
+struct Client<'a> {
+    provider: &'a ZeroProvider,
+    dry_run: bool,
+}
+
+impl std::ops::Deref for Client<'_> {
+    type Target = ZeroProvider;
+
+    fn deref(&self) -> &ZeroProvider {
+        self.provider
+    }
+}
+
+impl Client<'_> {
+    /// Serve a request; in a dry run, the first change is printed instead of made and ends the command
+    async fn handle_request(&self, mut req: ZeroRequest) -> anyhow::Result<ZeroResponse> {
+        if !self.dry_run {
+            return Ok(self.provider.handle_request(req).await?);
+        }
+        req.headers.insert(DRY_RUN_HEADER.to_string(), "true".to_string());
+        let mutation = audit::is_mutation(&req.method);
+        let resp = self.provider.handle_request(req).await?;
+        if !mutation {
+            return Ok(resp);
+        }
+        let report: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+        println!("{} nothing was changed", "🔍 Dry run:".cyan());
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        // Later steps of the command depend on this change
+        Err(DryRunComplete.into())
+    }
+}
+
+/// Ends a command at the change a dry run reported
+#[derive(Debug)]
+struct DryRunComplete;
+
+impl std::fmt::Display for DryRunComplete {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("dry run complete")
+    }
+}
+
+impl std::error::Error for DryRunComplete {}
+
 pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyhow::Result<()> {
+    execute(command, &Client { provider, dry_run: false }).await
+}
+
+/// Run a command with every request it sends marked as a dry run, so it validates its first
+/// change and reports it without making it
+pub async fn execute_dry_run(command: Commands, provider: &ZeroProvider) -> anyhow::Result<()> {
+    match execute(command, &Client { provider, dry_run: true }).await {
+        Err(e) if e.is::<DryRunComplete>() => Ok(()),
+        result => result,
+    }
+}
+
+async fn execute(command: Commands, provider: &Client<'_>) -> anyhow::Result<()> {
     match command {
         Commands::Workload { action } => match action {
-            WorkloadAction::Up { id, image } => {
+            WorkloadAction::Up { id, image, volumes, ports, selectors, affinity, tolerations, cpu, memory_mb, restart, liveness, liveness_interval, liveness_failures, namespace } => {
                 println!("{} Workload {} with image {}...", "🚀 Starting".green(), id.bold(), image.cyan());
+                let volumes: Vec<_> = volumes.into_iter()
+                    .map(|(volume_id, target, read_only)| json!({ "volume_id": volume_id, "target": target, "read_only": read_only }))
+                    .collect();
+                let node_selector: std::collections::BTreeMap<_, _> = selectors.into_iter().collect();
+                let liveness = liveness.map(|probe| LivenessProbe {
+                    interval_secs: liveness_interval.unwrap_or(probe.interval_secs),
+                    failure_threshold: liveness_failures.unwrap_or(probe.failure_threshold),
+                    ..*probe
+                });
                 let req = ZeroRequest {
                     method: "POST".into(),
                     path: "/v1/workloads".into(),
-                    headers: std::collections::HashMap::new(),
-                    body: json!({ "id": id, "image": image }).to_string().into_bytes(),
+                    headers: namespace_headers(&namespace),
+                    body: json!({
+                        "id": id, "image": image, "volumes": volumes, "ports": ports, "cpu": cpu, "memory_mb": memory_mb,
+                        "node_selector": node_selector, "affinity": affinity, "tolerations": tolerations,
+                        "restart_policy": restart, "liveness_probe": liveness
+                    }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            WorkloadAction::Update { id, image, timeout, namespace } => {
+                println!("{} Workload {} to image {}...", "🔄 Updating".blue(), id.bold(), image.cyan());
+                let req = ZeroRequest {
+                    method: "PUT".into(),
+                    path: format!("/v1/workloads/{}", id),
+                    headers: namespace_headers(&namespace),
+                    body: json!({ "image": image, "health_timeout_secs": timeout }).to_string().into_bytes().into(),
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(&resp.body));
+                let rollout: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                if rollout["status"] == "RolledBack" {
+                    anyhow::bail!("Revision {} of {} was rolled back, the workload stays on revision {} ({}): {}",
+                        rollout["failed_revision"], id, rollout["revision"], rollout["image"].as_str().unwrap_or_default(),
+                        rollout["reason"].as_str().unwrap_or_default());
+                }
+                println!("{} Workload {} runs revision {} ({})", "✅ Updated".green(), id.bold(), rollout["revision"], image.cyan());
             }
-            WorkloadAction::Down { id } => {
+            WorkloadAction::Revisions { id, namespace } => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: format!("/v1/workloads/{}/revisions", id),
+                    headers: namespace_headers(&namespace),
+                    body: ZeroBody::empty(),
+                };
+                let resp = provider.handle_request(req).await?;
+                let revisions: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                println!("{:<9} {:<30} {:<11} {:<26} REASON", "REVISION", "IMAGE", "STATUS", "CREATED");
+                for revision in revisions["revisions"].as_array().cloned().unwrap_or_default() {
+                    println!("{:<9} {:<30} {:<11} {:<26} {}",
+                        revision["revision"].to_string(), revision["image"].as_str().unwrap_or_default(),
+                        revision["status"].as_str().unwrap_or_default(), revision["created_at"].as_str().unwrap_or_default(),
+                        revision["reason"].as_str().unwrap_or_default());
+                }
+            }
+            WorkloadAction::Down { id, namespace } => {
                 println!("{} Workload {}...", "🛑 Stopping".red(), id.bold());
                 let req = ZeroRequest {
                     method: "DELETE".into(),
                     path: "/v1/workloads".into(),
-                    headers: std::collections::HashMap::new(),
-                    body: json!({ "id": id }).to_string().into_bytes(),
+                    headers: namespace_headers(&namespace),
+                    body: json!({ "id": id }).to_string().into_bytes().into(),
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(&resp.body));
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            WorkloadAction::Stats { id, period, samples, namespace } => {
+                // Usage is only kept by a running server, so take the samples here
+                for n in 0..samples {
+                    if n > 0 {
+                        tokio::time::sleep(zero_control_core::services::workload_metrics::METRICS_TICK).await;
+                    }
+                    provider.workload_metrics.sample(chrono::Utc::now()).await?;
+                }
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: format!("/v1/workloads/{}/metrics?period={}", id, period),
+                    headers: namespace_headers(&namespace),
+                    body: ZeroBody::empty(),
+                };
+                let resp = provider.handle_request(req).await?;
+                let metrics: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                let datapoints = metrics["datapoints"].as_array().cloned().unwrap_or_default();
+                if datapoints.is_empty() {
+                    println!("{} No usage reported for {}; its driver cannot measure single workloads", "📈".yellow(), id.bold());
+                }
+                for point in datapoints {
+                    println!("{}  CPU {:.1}% (max {:.1}%)  memory {} MB (max {}) of {} MB  network {} B in, {} B out",
+                        point["timestamp"].as_str().unwrap_or_default().cyan(),
+                        point["cpu_percent_avg"].as_f64().unwrap_or_default(), point["cpu_percent_max"].as_f64().unwrap_or_default(),
+                        point["memory_used_mb_avg"], point["memory_used_mb_max"], point["memory_limit_mb"],
+                        point["network_rx_bytes"], point["network_tx_bytes"]);
+                }
             }
         },
         Commands::Volume { action } => match action {
-            VolumeAction::Create { id, size } => {
+            VolumeAction::Create { id, size, namespace } => {
                 println!("{} Volume {} ({} GB)...", "📂 Provisioning".blue(), id.bold(), size);
                 let req = ZeroRequest {
                     method: "POST".into(),
                     path: "/v1/volumes".into(),
+                    headers: namespace_headers(&namespace),
+                    body: json!({ "id": id, "size_gb": size }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            VolumeAction::Resize { id, size, namespace } => {
+                println!("{} Volume {} to {} GB...", "📂 Resizing".blue(), id.bold(), size);
+                let req = ZeroRequest {
+                    method: "PUT".into(),
+                    path: format!("/v1/volumes/{}", id),
+                    headers: namespace_headers(&namespace),
+                    body: json!({ "size_gb": size }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+        },
+        Commands::Image { action } => match action {
+            ImageAction::Pull { reference } => {
+                println!("{} image {}...", "📥 Pulling".blue(), reference.bold());
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/images/pull".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({ "reference": reference }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                let image: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                println!("{} {} ({} MB)", "✅ Pulled".green(), image["reference"].as_str().unwrap_or_default(), image["size_bytes"].as_u64().unwrap_or_default() / (1024 * 1024));
+            }
+            ImageAction::Ls => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/images".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty(),
+                };
+                let resp = provider.handle_request(req).await?;
+                let listed: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                for image in listed["images"].as_array().into_iter().flatten() {
+                    let usage = match (image["in_use"].as_bool().unwrap_or_default(), image["last_used"].as_str()) {
+                        (true, _) => "in use".to_string(),
+                        (false, Some(last_used)) => format!("last used {}", last_used),
+                        (false, None) => "not managed".to_string(),
+                    };
+                    println!("{}  {} MB  {}", image["reference"].as_str().unwrap_or_default().cyan(), image["size_bytes"].as_u64().unwrap_or_default() / (1024 * 1024), usage);
+                }
+            }
+            ImageAction::Prune { unused_for } => {
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/images/prune".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({ "unused_for_secs": unused_for }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                let report: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                for reference in report["removed"].as_array().into_iter().flatten() {
+                    println!("{} {}", "🗑️ Removed".red(), reference.as_str().unwrap_or_default());
+                }
+                println!("{} {} MB", "♻️ Reclaimed".green(), report["reclaimed_bytes"].as_u64().unwrap_or_default() / (1024 * 1024));
+            }
+        },
+        Commands::Ns { action } => match action {
+            NsAction::Create { name, cpu, memory_mb, volume_gb, workloads, queues } => {
+                println!("{} Namespace {}...", "📁 Creating".green(), name.bold());
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/namespaces".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({
+                        "name": name, "cpu": cpu, "memory_mb": memory_mb, "volume_gb": volume_gb, "workloads": workloads, "queues": queues
+                    }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NsAction::Ls => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/namespaces".into(),
                     headers: std::collections::HashMap::new(),
-                    body: json!({ "id": id, "size_gb": size }).to_string().into_bytes(),
+                    body: ZeroBody::empty(),
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(&resp.body));
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NsAction::Quota { name, cpu, memory_mb, volume_gb, workloads, queues } => {
+                println!("{} quota of {}...", "📏 Setting".cyan(), name.bold());
+                let req = ZeroRequest {
+                    method: "PUT".into(),
+                    path: format!("/v1/namespaces/{}/quota", name),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({
+                        "cpu": cpu, "memory_mb": memory_mb, "volume_gb": volume_gb, "workloads": workloads, "queues": queues
+                    }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NsAction::Delete { name } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/namespaces/{}", name),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty(),
+                };
+                provider.handle_request(req).await?;
+                println!("{} Namespace {}", "🗑️ Deleted".red(), name);
+            }
+        },
+        Commands::Quota { namespace } => {
+            let req = ZeroRequest {
+                method: "GET".into(),
+                path: format!("/v1/namespaces/{}", namespace),
+                headers: std::collections::HashMap::new(),
+                body: ZeroBody::empty(),
+            };
+            let resp = provider.handle_request(req).await?;
+            let namespace: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+            println!("{} {}", "📏 Quota of".cyan(), namespace["name"].as_str().unwrap_or_default().bold());
+            println!("{:<12} {:>10} {:>10}", "RESOURCE", "USED", "LIMIT");
+            for (resource, used) in quota_usage(&namespace) {
+                let limit = match &namespace["quota"][resource] {
+                    serde_json::Value::Null => "unlimited".to_string(),
+                    limit => limit.to_string(),
+                };
+                println!("{:<12} {:>10} {:>10}", resource, used.to_string(), limit);
             }
         },
         Commands::Node { action } => match action {
@@ -279,11 +1232,39 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                     method: "GET".into(),
                     path: "/v1/nodes".into(),
                     headers: std::collections::HashMap::new(),
-                    body: vec![],
+                    body: ZeroBody::empty(),
                 };
                 let resp = provider.handle_request(req).await?;
                 println!("{}", "📋 Local Compute Nodes:".bold().underline());
-                println!("{}", String::from_utf8_lossy(&resp.body));
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NodeAction::Register { hostname, ip, labels, taints, endpoint } => {
+                println!("{} Node {} ({})...", "🖥️ Registering".green(), hostname.bold(), ip);
+                let labels: std::collections::BTreeMap<_, _> = labels.into_iter().collect();
+                let mut body = json!({ "hostname": hostname, "ip_address": ip, "labels": labels, "taints": taints });
+                if let Some(endpoint) = endpoint {
+                    body["endpoint"] = json!(endpoint);
+                }
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/nodes".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: body.to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NodeAction::Label { node, labels, taints } => {
+                println!("{} Node {}...", "🏷️ Labelling".cyan(), node.bold());
+                let labels: std::collections::BTreeMap<_, _> = labels.into_iter().collect();
+                let req = ZeroRequest {
+                    method: "PUT".into(),
+                    path: format!("/v1/nodes/{}", node),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({ "labels": labels, "taints": taints }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
             }
         },
         Commands::Network { action } => match action {
@@ -293,10 +1274,50 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                     method: "POST".into(),
                     path: "/v1/networks".into(),
                     headers: std::collections::HashMap::new(),
-                    body: json!({ "id": id, "cidr": cidr }).to_string().into_bytes(),
+                    body: json!({ "id": id, "cidr": cidr }).to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NetworkAction::Connect { id, workload, ip } => {
+                println!("{} {} to Network {}...", "🔌 Connecting".cyan(), workload.bold(), id.bold());
+                let mut body = json!({ "workload_id": workload });
+                if let Some(ip) = ip {
+                    body["ip"] = json!(ip);
+                }
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: format!("/v1/networks/{}/workloads", id),
+                    headers: std::collections::HashMap::new(),
+                    body: body.to_string().into_bytes().into(),
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(&resp.body));
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            NetworkAction::SecurityGroup { action } => {
+                let (method, path, body) = match action {
+                    SecurityGroupAction::Create { network, name, rules } => {
+                        println!("{} Security group {} in Network {}...", "🛡️ Creating".cyan(), name.bold(), network.bold());
+                        ("POST", format!("/v1/networks/{}/security-groups", network), json!({ "name": name, "rules": rules }))
+                    }
+                    SecurityGroupAction::Update { network, id, rules } => {
+                        println!("{} Security group {}...", "🛡️ Updating".cyan(), id.bold());
+                        ("PUT", format!("/v1/networks/{}/security-groups/{}", network, id), json!({ "rules": rules }))
+                    }
+                    SecurityGroupAction::List { network } => ("GET", format!("/v1/networks/{}/security-groups", network), json!({})),
+                    SecurityGroupAction::Delete { network, id } => {
+                        println!("{} Security group {}...", "🗑️ Deleting".cyan(), id.bold());
+                        ("DELETE", format!("/v1/networks/{}/security-groups/{}", network, id), json!({}))
+                    }
+                };
+                let req = ZeroRequest {
+                    method: method.into(),
+                    path,
+                    headers: std::collections::HashMap::new(),
+                    body: body.to_string().into_bytes().into(),
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{} Response: {}", "✅".green(), String::from_utf8_lossy(resp.body.as_bytes()));
             }
         },
         Commands::Store { action } => match action {
@@ -306,20 +1327,20 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/store/buckets".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name }).to_string().into_bytes()
+                     body: json!({ "name": name }).to_string().into_bytes().into()
                  };
                 let resp = provider.handle_request(req).await?;
-                println!("{}", String::from_utf8_lossy(&resp.body));
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
             StoreAction::Ls => {
                 let req = ZeroRequest {
                     method: "GET".into(),
                     path: "/v1/store/buckets".into(),
                     headers: std::collections::HashMap::new(),
-                    body: vec![],
+                    body: ZeroBody::empty(),
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{}", String::from_utf8_lossy(&resp.body));
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
         },
         Commands::Db { action } => match action {
@@ -329,38 +1350,93 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(), 
                      path: "/v1/db/tables".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name, "pk": pk }).to_string().into_bytes()
+                     body: json!({ "name": name, "pk": pk }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              DbAction::Ls => {
                  let req = ZeroRequest {
                      method: "GET".into(), 
                      path: "/v1/db/tables".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             DbAction::Ttl { action } => {
+                 let req = match action {
+                     TtlAction::Enable { table, attribute } => {
+                         println!("{} TTL on {} (attribute: {})...", "⏳ Enabling".blue(), table, attribute);
+                         ZeroRequest {
+                             method: "PUT".into(),
+                             path: format!("/v1/db/tables/{}/ttl", table),
+                             headers: std::collections::HashMap::new(),
+                             body: json!({ "attribute": attribute, "enabled": true }).to_string().into_bytes().into()
+                         }
+                     }
+                     TtlAction::Disable { table } => {
+                         println!("{} TTL on {}...", "⏳ Disabling".blue(), table);
+                         ZeroRequest {
+                             method: "PUT".into(),
+                             path: format!("/v1/db/tables/{}/ttl", table),
+                             headers: std::collections::HashMap::new(),
+                             body: json!({ "enabled": false }).to_string().into_bytes().into()
+                         }
+                     }
+                     TtlAction::Describe { table } => ZeroRequest {
+                         method: "GET".into(),
+                         path: format!("/v1/db/tables/{}/ttl", table),
+                         headers: std::collections::HashMap::new(),
+                         body: ZeroBody::empty()
+                     },
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
         },
         Commands::Func { action } => match action {
-            FuncAction::Deploy { name, code, handler } => {
-                 let code_content = if std::path::Path::new(&code).exists() {
-                     std::fs::read_to_string(&code).unwrap_or(code)
-                 } else {
-                     code
+            FuncAction::Deploy { name, code, handler, runtime, env, timeout, memory } => {
+                 let code_content = match runtime.as_str() {
+                     "docker" => code,
+                     // Binary modules are sent base64-encoded; text modules are sent as-is
+                     "wasm" => {
+                         let bytes = std::fs::read(&code)?;
+                         String::from_utf8(bytes.clone()).unwrap_or_else(|_| {
+                             base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+                         })
+                     }
+                     _ if std::path::Path::new(&code).exists() => std::fs::read_to_string(&code).unwrap_or(code),
+                     _ => code,
                  };
                  println!("{} Function {}...", "⚡ Deploying".yellow(), name);
                  let req = ZeroRequest {
                      method: "POST".into(),
                      path: "/v1/func/functions".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name, "code": code_content, "handler": handler }).to_string().into_bytes()
+                     body: json!({
+                         "name": name,
+                         "code": code_content,
+                         "handler": handler,
+                         "runtime": runtime,
+                         "environment": env.into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+                         "timeout_secs": timeout,
+                         "memory_mb": memory
+                     }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            FuncAction::Describe { name } => {
+                 let req = ZeroRequest {
+                     method: "GET".into(),
+                     path: format!("/v1/func/functions/{}", name),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
             FuncAction::Invoke { name, payload } => {
                  println!("{} Function {}...", "▶️ Invoking".green(), name);
@@ -368,74 +1444,184 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: format!("/v1/func/functions/{}/invocations", name),
                      headers: std::collections::HashMap::new(),
-                     body: payload.into_bytes()
+                     body: payload.into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
             FuncAction::Ls => {
                  let req = ZeroRequest {
                      method: "GET".into(), 
                      path: "/v1/func/functions".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            FuncAction::MapQueue { function, queue, batch_size, max_retries, dlq } => {
+                 println!("{} Queue {} to function {}...", "🔗 Mapping".yellow(), queue, function);
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: "/v1/func/event-source-mappings".into(),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({
+                         "function_name": function,
+                         "queue_name": queue,
+                         "batch_size": batch_size,
+                         "max_retries": max_retries,
+                         "dead_letter_queue": dlq
+                     }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            FuncAction::Mappings => {
+                 let req = ZeroRequest {
+                     method: "GET".into(),
+                     path: "/v1/func/event-source-mappings".into(),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            FuncAction::Unmap { id } => {
+                 let req = ZeroRequest {
+                     method: "DELETE".into(),
+                     path: format!("/v1/func/event-source-mappings/{}", id),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
         },
         Commands::Queue { action } => match action {
-            QueueAction::Create { name } => {
+            QueueAction::Create { name, dlq, max_receive_count, fifo, content_based_dedup, visibility_timeout, delay_seconds } => {
                 println!("{} Queue {}...", "📥 Creating".magenta(), name);
+                let mut body = json!({
+                    "name": name,
+                    "fifo": fifo,
+                    "content_based_deduplication": content_based_dedup,
+                    "visibility_timeout": visibility_timeout,
+                    "delay_seconds": delay_seconds
+                });
+                if let Some(dlq) = dlq {
+                    body["redrive_policy"] = json!({ "dead_letter_queue": dlq, "max_receive_count": max_receive_count });
+                }
                 let req = ZeroRequest {
                     method: "POST".into(),
                     path: "/v1/queue/queues".into(),
                     headers: std::collections::HashMap::new(),
-                    body: json!({ "name": name }).to_string().into_bytes()
+                    body: body.to_string().into_bytes().into()
                 };
                 let resp = provider.handle_request(req).await?;
-                println!("{}", String::from_utf8_lossy(&resp.body));
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
-            QueueAction::Send { name, body } => {
+            QueueAction::Send { name, body, group_id, dedup_id, delay_seconds } => {
                  println!("{} Message to {}...", "📨 Sending".magenta(), name);
+                 let mut payload = json!({ "body": body });
+                 if let Some(group_id) = group_id {
+                     payload["group_id"] = json!(group_id);
+                 }
+                 if let Some(dedup_id) = dedup_id {
+                     payload["deduplication_id"] = json!(dedup_id);
+                 }
+                 if let Some(delay_seconds) = delay_seconds {
+                     payload["delay_seconds"] = json!(delay_seconds);
+                 }
                  let req = ZeroRequest {
                      method: "POST".into(),
                      path: format!("/v1/queue/queues/{}/messages", name),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "body": body }).to_string().into_bytes()
+                     body: payload.to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
-            QueueAction::Receive { name } => {
+            QueueAction::Receive { name, max_messages, wait, visibility_timeout } => {
+                 let body = json!({
+                     "max_messages": max_messages,
+                     "wait_time_seconds": wait,
+                     "visibility_timeout": visibility_timeout
+                 });
                  let req = ZeroRequest {
-                     method: "GET".into(),
-                     path: format!("/v1/queue/queues/{}/messages", name),
+                     method: "POST".into(),
+                     path: format!("/v1/queue/queues/{}/receive", name),
+                     headers: std::collections::HashMap::new(),
+                     body: body.to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            QueueAction::SendBatch { name, bodies } => {
+                 println!("{} {} messages to {}...", "📨 Sending".magenta(), bodies.len(), name);
+                 let entries: Vec<_> = bodies.iter().enumerate()
+                     .map(|(i, body)| json!({ "id": i.to_string(), "body": body }))
+                     .collect();
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/queue/queues/{}/batch/send", name),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: json!({ "entries": entries }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
             QueueAction::Delete { name, handle } => {
                  let req = ZeroRequest {
                      method: "DELETE".into(),
                      path: format!("/v1/queue/queues/{}/messages/{}", name, handle),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            QueueAction::ChangeVisibility { name, handle, timeout } => {
+                 let req = ZeroRequest {
+                     method: "PATCH".into(),
+                     path: format!("/v1/queue/queues/{}/messages/{}/visibility", name, handle),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({ "visibility_timeout": timeout }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            QueueAction::DeleteBatch { name, handles } => {
+                 let entries: Vec<_> = handles.iter().enumerate()
+                     .map(|(i, handle)| json!({ "id": i.to_string(), "receipt_handle": handle }))
+                     .collect();
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/queue/queues/{}/batch/delete", name),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({ "entries": entries }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            QueueAction::Redrive { name, destination } => {
+                 println!("{} messages from {}...", "♻️ Redriving".magenta(), name);
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/queue/queues/{}/redrive", name),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({ "destination": destination }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
             QueueAction::Ls => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: "/v1/queue/queues".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
             }
         },
         Commands::Iam { action } => match action {
@@ -445,10 +1631,10 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/iam/users".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "username": username }).to_string().into_bytes()
+                     body: json!({ "username": username }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::CreateRole { rolename } => {
                  println!("{} Role {}...", "🎭 Creating".cyan(), rolename);
@@ -456,10 +1642,10 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/iam/roles".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "Rolename": rolename }).to_string().into_bytes()
+                     body: json!({ "Rolename": rolename }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::CreateGroup { groupname } => {
                  println!("{} Group {}...", "👥 Creating".cyan(), groupname);
@@ -467,10 +1653,10 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/iam/groups".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "Groupname": groupname }).to_string().into_bytes()
+                     body: json!({ "Groupname": groupname }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::AttachPolicy { username, policy } => {
                  println!("{} Policy to {}...", "🔐 Attaching".cyan(), username);
@@ -478,40 +1664,62 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: format!("/v1/iam/users/{}/policy", username),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "PolicyDocument": policy }).to_string().into_bytes()
+                     body: json!({ "PolicyDocument": policy }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::ListUsers => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: "/v1/iam/users".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::ListRoles => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: "/v1/iam/roles".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             IamAction::AttachRolePolicy { rolename, policy } => {
+                 println!("{} Policy to role {}...", "🔐 Attaching".cyan(), rolename);
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/iam/roles/{}/policy", rolename),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({ "PolicyDocument": policy }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             IamAction::AssumeRole { rolename, session_name, duration } => {
+                 println!("{} Role {} as {}...", "🎭 Assuming".cyan(), rolename, session_name);
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/iam/roles/{}/assume", rolename),
+                     headers: std::collections::HashMap::new(),
+                     body: json!({ "RoleSessionName": session_name, "DurationSeconds": duration }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              IamAction::ListGroups => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: "/v1/iam/groups".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
         },
         Commands::Lb { action } => match action {
@@ -521,21 +1729,31 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/network/loadbalancers".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name, "type": lb_type }).to_string().into_bytes()
+                     body: json!({ "name": name, "type": lb_type }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
-             LbAction::CreateTargetGroup { name, port } => {
+             LbAction::CreateTargetGroup { name, port, protocol, health_check_path, health_check_interval, healthy_threshold, unhealthy_threshold } => {
                  println!("{} Target Group {} on port {}...", "🎯 Creating".white(), name, port);
                  let req = ZeroRequest {
                      method: "POST".into(),
                      path: "/v1/network/targetgroups".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name, "port": port }).to_string().into_bytes()
+                     body: json!({
+                         "name": name,
+                         "port": port,
+                         "protocol": protocol,
+                         "health_check": {
+                             "path": health_check_path,
+                             "interval_secs": health_check_interval,
+                             "healthy_threshold": healthy_threshold,
+                             "unhealthy_threshold": unhealthy_threshold
+                         }
+                     }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              LbAction::Register { group, id, port } => {
                  println!("{} Target {} to group {}...", "🔗 Registering".white(), id, group);
@@ -543,10 +1761,10 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: format!("/v1/network/targetgroups/{}/targets", group),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "id": id, "port": port }).to_string().into_bytes()
+                     body: json!({ "id": id, "port": port }).to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
              LbAction::CreateListener { lb, port, target_group } => {
                  println!("{} Listener for {} on port {}...", "👂 Creating".white(), lb, port);
@@ -554,46 +1772,470 @@ pub async fn execute_command(command: Commands, provider: &ZeroProvider) -> anyh
                      method: "POST".into(),
                      path: "/v1/network/listeners".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "load_balancer_name": lb, "port": port, "target_group_arn": target_group }).to_string().into_bytes()
+                     body: json!({ "load_balancer_name": lb, "port": port, "target_group_arn": target_group }).to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             LbAction::Health { group } => {
+                 let req = ZeroRequest {
+                     method: "GET".into(),
+                     path: format!("/v1/network/targetgroups/{}/health", group),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 let health: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                 let check = &health["HealthCheck"];
+                 println!("{} {} every {}s (healthy after {}, unhealthy after {})",
+                     "🩺 Health check".white(), check["path"].as_str().unwrap_or_default(), check["interval_secs"],
+                     check["healthy_threshold"], check["unhealthy_threshold"]);
+                 for target in health["TargetHealthDescriptions"].as_array().into_iter().flatten() {
+                     let state = target["TargetHealth"]["State"].as_str().unwrap_or_default();
+                     let state = match state {
+                         "healthy" => state.green(),
+                         "unhealthy" => state.red(),
+                         _ => state.yellow(),
+                     };
+                     let description = target["TargetHealth"]["Description"].as_str().unwrap_or_default();
+                     println!("  {}:{}  {}  {}", target["Target"]["Id"].as_str().unwrap_or_default(), target["Target"]["Port"], state, description);
+                 }
              }
              LbAction::Ls => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: "/v1/network/loadbalancers".into(),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
              }
         },
         Commands::Eks { action } => match action {
-             EksAction::Create { name } => {
+             EksAction::Create { name, version, kubeconfig } => {
                  println!("{} Cluster {}...", "☸️ Creating".cyan(), name);
+                 let mut body = json!({ "name": name });
+                 if let Some(version) = version {
+                     body["version"] = json!(version);
+                 }
                  let req = ZeroRequest {
                      method: "POST".into(),
                      path: "/v1/eks/clusters".into(),
                      headers: std::collections::HashMap::new(),
-                     body: json!({ "name": name }).to_string().into_bytes()
+                     body: body.to_string().into_bytes().into()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+                 if let Some(path) = kubeconfig {
+                     write_kubeconfig(provider, &name, &path).await?;
+                 }
              },
-             EksAction::Describe { name } => {
+             EksAction::Describe { name, kubeconfig } => {
                  let req = ZeroRequest {
                      method: "GET".into(),
                      path: format!("/v1/eks/clusters/{}", name),
                      headers: std::collections::HashMap::new(),
-                     body: vec![]
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+                 if let Some(path) = kubeconfig {
+                     write_kubeconfig(provider, &name, &path).await?;
+                 }
+             }
+             EksAction::Delete { name } => {
+                 let req = ZeroRequest {
+                     method: "DELETE".into(),
+                     path: format!("/v1/eks/clusters/{}", name),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
+                 };
+                 provider.handle_request(req).await?;
+                 println!("{} Cluster {}", "🗑️ Deleted".red(), name);
+             }
+             EksAction::Ls => {
+                 let req = ZeroRequest {
+                     method: "GET".into(),
+                     path: "/v1/eks/clusters".into(),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             EksAction::CreateNodegroup { cluster, name, nodes, instance_type } => {
+                 println!("{} Node group {} ({} nodes) in {}...", "☸️ Creating".cyan(), name, nodes, cluster);
+                 let mut body = json!({
+                     "nodegroupName": name,
+                     "scalingConfig": { "minSize": nodes.min(1), "maxSize": nodes.max(1), "desiredSize": nodes }
+                 });
+                 if let Some(instance_type) = instance_type {
+                     body["instanceTypes"] = json!([instance_type]);
+                 }
+                 let req = ZeroRequest {
+                     method: "POST".into(),
+                     path: format!("/v1/eks/clusters/{}/node-groups", cluster),
+                     headers: std::collections::HashMap::new(),
+                     body: body.to_string().into_bytes().into()
+                 };
+                 let resp = provider.handle_request(req).await?;
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             EksAction::Nodegroups { cluster } => {
+                 let req = ZeroRequest {
+                     method: "GET".into(),
+                     path: format!("/v1/eks/clusters/{}/node-groups", cluster),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
                  };
                  let resp = provider.handle_request(req).await?;
-                 println!("{}", String::from_utf8_lossy(&resp.body));
+                 println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+             }
+             EksAction::DeleteNodegroup { cluster, name } => {
+                 let req = ZeroRequest {
+                     method: "DELETE".into(),
+                     path: format!("/v1/eks/clusters/{}/node-groups/{}", cluster, name),
+                     headers: std::collections::HashMap::new(),
+                     body: ZeroBody::empty()
+                 };
+                 provider.handle_request(req).await?;
+                 println!("{} Node group {}", "🗑️ Deleted".red(), name);
              }
         },
+        Commands::Topic { action } => match action {
+            TopicAction::Create { name } => {
+                println!("{} Topic {}...", "📣 Creating".magenta(), name);
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/topics".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({ "name": name }).to_string().into_bytes().into()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            TopicAction::Ls => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/topics".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            TopicAction::Delete { name } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/topics/{}", name),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                provider.handle_request(req).await?;
+                println!("{} Topic {}", "🗑️ Deleted".red(), name);
+            }
+            TopicAction::Subscribe { name, queue, webhook, filter_policy, raw } => {
+                let (protocol, endpoint) = match (queue, webhook) {
+                    (Some(queue), _) => ("queue", queue),
+                    (None, Some(webhook)) => ("webhook", webhook),
+                    (None, None) => anyhow::bail!("Either --queue or --webhook is required"),
+                };
+                let filter_policy = filter_policy
+                    .map(|p| serde_json::from_str::<serde_json::Value>(&p))
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("Invalid filter policy: {}", e))?;
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: format!("/v1/topics/{}/subscriptions", name),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({
+                        "protocol": protocol,
+                        "endpoint": endpoint,
+                        "filter_policy": filter_policy,
+                        "raw_message_delivery": raw
+                    }).to_string().into_bytes().into()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            TopicAction::Subscriptions { name } => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: format!("/v1/topics/{}/subscriptions", name),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            TopicAction::Unsubscribe { name, id } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/topics/{}/subscriptions/{}", name, id),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                provider.handle_request(req).await?;
+                println!("{} Subscription {}", "🗑️ Deleted".red(), id);
+            }
+            TopicAction::Publish { name, message, subject, attributes } => {
+                let attributes: serde_json::Map<String, serde_json::Value> = attributes.into_iter()
+                    .map(|(key, value)| {
+                        let value = value.parse::<f64>().ok()
+                            .and_then(serde_json::Number::from_f64)
+                            .map(serde_json::Value::Number)
+                            .unwrap_or(serde_json::Value::String(value));
+                        (key, value)
+                    })
+                    .collect();
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: format!("/v1/topics/{}/messages", name),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({ "message": message, "subject": subject, "attributes": attributes }).to_string().into_bytes().into()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+        },
+        Commands::Schedule { action } => match action {
+            ScheduleAction::Create { name, schedule, function, queue, webhook, input } => {
+                let (target_type, target) = match (function, queue, webhook) {
+                    (Some(function), _, _) => ("function", function),
+                    (None, Some(queue), _) => ("queue", queue),
+                    (None, None, Some(webhook)) => ("webhook", webhook),
+                    (None, None, None) => anyhow::bail!("One of --function, --queue or --webhook is required"),
+                };
+                let input = input
+                    .map(|i| serde_json::from_str::<serde_json::Value>(&i))
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("Invalid input: {}", e))?;
+                println!("{} Rule {}...", "⏰ Scheduling".magenta(), name);
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/scheduler/rules".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({
+                        "name": name,
+                        "schedule": schedule,
+                        "target_type": target_type,
+                        "target": target,
+                        "input": input
+                    }).to_string().into_bytes().into()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            ScheduleAction::Ls => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/scheduler/rules".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            ScheduleAction::Delete { name } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/scheduler/rules/{}", name),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                provider.handle_request(req).await?;
+                println!("{} Rule {}", "🗑️ Deleted".red(), name);
+            }
+        },
+        Commands::Asg { action } => match action {
+            AsgAction::Create { name, image, min, max, desired, cpu_target, queue, messages_per_instance, cooldown } => {
+                let (metric, target_value) = match (cpu_target, messages_per_instance) {
+                    (Some(cpu_target), _) => ("cpu", cpu_target),
+                    (None, Some(messages)) => ("queue_depth", messages),
+                    (None, None) => anyhow::bail!("One of --cpu-target or --queue is required"),
+                };
+                println!("{} Autoscaling group {} ({}-{} x {})...", "📈 Creating".green(), name.bold(), min, max, image.cyan());
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/autoscaling/groups".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: json!({
+                        "name": name,
+                        "image": image,
+                        "min_size": min,
+                        "max_size": max,
+                        "desired_capacity": desired,
+                        "metric": metric,
+                        "target_value": target_value,
+                        "queue": queue,
+                        "cooldown_seconds": cooldown
+                    }).to_string().into_bytes().into()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            AsgAction::Ls => {
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/autoscaling/groups".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+            AsgAction::Delete { name } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/autoscaling/groups/{}", name),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                provider.handle_request(req).await?;
+                println!("{} Autoscaling group {}", "🗑️ Deleted".red(), name);
+            }
+        },
+        Commands::Backup { action } => match action {
+            BackupAction::Create { output } => {
+                use futures::StreamExt;
+                use tokio::io::AsyncWriteExt;
+                println!("{} control plane to {}...", "💾 Backing up".blue(), output.display());
+                let req = ZeroRequest {
+                    method: "GET".into(),
+                    path: "/v1/backup".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                let mut archive = resp.body.into_stream();
+                let mut file = tokio::fs::File::create(&output).await?;
+                while let Some(chunk) = archive.next().await {
+                    file.write_all(&chunk?).await?;
+                }
+                file.flush().await?;
+                println!("{} {}", "✅ Wrote".green(), output.display());
+            }
+            BackupAction::Restore { input } => {
+                println!("{} control plane from {}...", "♻️ Restoring".yellow(), input.display());
+                let file = tokio::fs::File::open(&input).await?;
+                let req = ZeroRequest {
+                    method: "POST".into(),
+                    path: "/v1/backup/restore".into(),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::from_stream(tokio_util::io::ReaderStream::new(file))
+                };
+                let resp = provider.handle_request(req).await?;
+                println!("{}", String::from_utf8_lossy(resp.body.as_bytes()));
+            }
+        },
+        Commands::Audit { action } => match action {
+            AuditAction::Tail { lines, since, until, follow } => {
+                // Page through the log so only the last `lines` events are kept
+                let mut after = 0;
+                let mut latest = std::collections::VecDeque::with_capacity(lines);
+                loop {
+                    let page = audit_events(provider, after, since.as_deref(), until.as_deref()).await?;
+                    let Some(last) = page.last() else { break };
+                    after = last.seq;
+                    for event in page {
+                        if latest.len() == lines {
+                            latest.pop_front();
+                        }
+                        if lines > 0 {
+                            latest.push_back(event);
+                        }
+                    }
+                }
+                latest.iter().for_each(print_audit_event);
+
+                if follow {
+                    loop {
+                        tokio::time::sleep(AUDIT_POLL_INTERVAL).await;
+                        for event in audit_events(provider, after, since.as_deref(), None).await? {
+                            after = event.seq;
+                            print_audit_event(&event);
+                        }
+                    }
+                }
+            }
+        },
+        Commands::System { action } => match action {
+            SystemAction::Reset { service } => {
+                let req = ZeroRequest {
+                    method: "DELETE".into(),
+                    path: format!("/v1/system/state?service={}", service),
+                    headers: std::collections::HashMap::new(),
+                    body: ZeroBody::empty()
+                };
+                let resp = provider.handle_request(req).await?;
+                let json: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+                println!("{} {} state", "🧹 Reset".red(), service);
+                if let Some(removed) = json["removed"].as_object() {
+                    for (kind, count) in removed.iter().filter(|(_, count)| count.as_u64().unwrap_or_default() > 0) {
+                        println!("  {}: {}", kind.replace('_', " "), count);
+                    }
+                }
+            }
+        },
+        Commands::Api { action } => match action {
+            ApiAction::ExportSpec { output, server_url } => {
+                let mut spec = zero_control_core::openapi::document();
+                spec["servers"] = json!([{ "url": server_url }]);
+                let spec = serde_json::to_string_pretty(&spec)?;
+                match output {
+                    Some(output) => {
+                        tokio::fs::write(&output, spec).await?;
+                        println!("{} {}", "✅ Wrote".green(), output.display());
+                    }
+                    None => println!("{}", spec),
+                }
+            }
+        },
+        #[cfg(feature = "tui")]
+        Commands::Top { refresh } => top::run(provider, std::time::Duration::from_secs(refresh)).await?,
+        #[cfg(not(feature = "tui"))]
+        Commands::Top { .. } => anyhow::bail!("zero was built without the tui feature"),
+        Commands::Completions { shell } => write_completions(shell, &mut std::io::stdout()),
     }
 
     Ok(())
 }
+
+/// How often `audit tail --follow` looks for new events
+const AUDIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// One page of audit events after the sequence number `after`
+async fn audit_events(
+    provider: &ZeroProvider,
+    after: i64,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> anyhow::Result<Vec<AuditEvent>> {
+    let filter = audit::AuditFilter {
+        since: since.map(str::to_string),
+        until: until.map(str::to_string),
+        after: Some(after),
+        limit: Some(audit::MAX_LIMIT),
+    };
+    let req = ZeroRequest {
+        method: "GET".into(),
+        path: format!("/v1/audit/events?{}", filter.to_query()),
+        headers: std::collections::HashMap::new(),
+        body: ZeroBody::empty()
+    };
+    let resp = provider.handle_request(req).await?;
+    let mut body: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+    Ok(serde_json::from_value(body["events"].take())?)
+}
+
+fn print_audit_event(event: &AuditEvent) {
+    let outcome = match &event.error {
+        Some(code) => format!("{} {}", event.status, code).red(),
+        None => event.status.to_string().green(),
+    };
+    println!(
+        "{} {} {} {} {} {}",
+        event.seq.to_string().dimmed(), event.time, event.principal.bold(), event.method.cyan(), event.path, outcome
+    );
+}
@@ -0,0 +1,225 @@
+//! `zero top`: a terminal dashboard of the node, its workloads, queues and recent events
+//!
+//! Each refresh samples workload usage, since only a running server keeps it, then reads
+//! the same [`Overview`] as the web dashboard, the latest `/v1/workloads/{id}/metrics`
+//! datapoint of every workload and the tail of the audit trail.
+
+use std::collections::HashMap;
+use zero_control_core::services::audit::{AuditEvent, AuditFilter, MAX_LIMIT};
+use zero_control_core::services::namespace::NAMESPACE_HEADER;
+use zero_control_core::ZeroProvider;
+use zero_control_spi::{ZeroBody, ZeroRequest, ZeroService};
+use zero_dashboard::Overview;
+use zero_data_core::metrics::Datapoint;
+
+/// Default seconds between refreshes
+pub const DEFAULT_REFRESH_SECS: u64 = 2;
+
+/// Events shown at the bottom of the screen
+pub const RECENT_EVENTS: usize = 8;
+
+/// Seconds each workload usage datapoint aggregates
+const USAGE_PERIOD_SECS: u32 = 60;
+
+/// Everything one frame of `zero top` shows
+pub struct TopSnapshot {
+    pub overview: Overview,
+    /// Latest usage datapoint of each workload that has one
+    pub usage: HashMap<String, Datapoint>,
+    /// Most recent audit events, oldest first
+    pub events: Vec<AuditEvent>,
+}
+
+impl TopSnapshot {
+    /// Share of CPU, memory and storage in use on this node, from 0 to 1
+    pub fn node_usage(&self) -> [(&'static str, f64); 3] {
+        let stats = &self.overview.stats;
+        [
+            ("CPU", stats.cpu_usage_percent as f64 / 100.0),
+            ("Memory", ratio(stats.memory_used_mb, stats.memory_total_mb)),
+            ("Storage", ratio(stats.storage_used_gb, stats.storage_total_gb)),
+        ]
+    }
+
+    /// ID, namespace, state, node, CPU and memory of each workload
+    pub fn workload_rows(&self) -> Vec<[String; 6]> {
+        self.overview.workloads.iter().map(|workload| {
+            let usage = self.usage.get(&workload.status.id);
+            [
+                workload.status.id.clone(),
+                workload.namespace.clone(),
+                workload.status.state.clone(),
+                workload.node.clone().unwrap_or_else(|| "local".to_string()),
+                usage.map(|u| format!("{:.1}%", u.cpu_percent_avg)).unwrap_or_else(|| "-".to_string()),
+                usage.map(|u| format!("{} MB", u.memory_used_mb_avg)).unwrap_or_else(|| "-".to_string()),
+            ]
+        }).collect()
+    }
+
+    /// Name, visible and in-flight message counts of each queue
+    pub fn queue_rows(&self) -> Vec<[String; 3]> {
+        self.overview.queues.iter()
+            .map(|queue| [queue.name.clone(), queue.visible.to_string(), queue.not_visible.to_string()])
+            .collect()
+    }
+
+    /// Hostname, address and status of each registered node
+    pub fn node_rows(&self) -> Vec<[String; 3]> {
+        self.overview.nodes.iter()
+            .map(|node| [text(&node["hostname"]), text(&node["ip_address"]), text(&node["status"])])
+            .collect()
+    }
+
+    /// One line per recent event
+    pub fn event_lines(&self) -> Vec<String> {
+        self.events.iter().map(|event| {
+            let outcome = match &event.error {
+                Some(code) => format!("{} {}", event.status, code),
+                None => event.status.to_string(),
+            };
+            format!("{} {} {} {} {}", event.time, event.principal, event.method, event.path, outcome)
+        }).collect()
+    }
+}
+
+fn ratio(used: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { (used as f64 / total as f64).min(1.0) }
+}
+
+fn text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => "-".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Read what `zero top` shows now, with the last `events` audit events
+pub async fn snapshot(provider: &ZeroProvider, events: usize) -> anyhow::Result<TopSnapshot> {
+    provider.workload_metrics.sample(chrono::Utc::now()).await?;
+    let overview = zero_dashboard::overview(provider).await?;
+
+    let mut usage = HashMap::new();
+    for workload in &overview.workloads {
+        let req = ZeroRequest {
+            method: "GET".into(),
+            path: format!("/v1/workloads/{}/metrics?period={}", workload.status.id, USAGE_PERIOD_SECS),
+            headers: HashMap::from([(NAMESPACE_HEADER.to_string(), workload.namespace.clone())]),
+            body: ZeroBody::empty(),
+        };
+        // A workload stopped since it was listed has no metrics left
+        let Ok(resp) = provider.handle_request(req).await else { continue };
+        let mut body: serde_json::Value = serde_json::from_slice(resp.body.as_bytes())?;
+        let datapoints: Vec<Datapoint> = serde_json::from_value(body["datapoints"].take())?;
+        if let Some(latest) = datapoints.into_iter().last() {
+            usage.insert(workload.status.id.clone(), latest);
+        }
+    }
+
+    Ok(TopSnapshot { overview, usage, events: recent_events(provider, events).await? })
+}
+
+/// The last `count` audit events, oldest first
+async fn recent_events(provider: &ZeroProvider, count: usize) -> anyhow::Result<Vec<AuditEvent>> {
+    let mut after = 0;
+    let mut latest = std::collections::VecDeque::with_capacity(count);
+    loop {
+        let filter = AuditFilter { after: Some(after), limit: Some(MAX_LIMIT), ..AuditFilter::default() };
+        let page = provider.audit.events(&filter).await?;
+        let Some(last) = page.last() else { break };
+        after = last.seq;
+        for event in page {
+            if latest.len() == count {
+                latest.pop_front();
+            }
+            if count > 0 {
+                latest.push_back(event);
+            }
+        }
+    }
+    Ok(latest.into())
+}
+
+/// Redraw the dashboard every `refresh` until `q` or Esc is pressed
+#[cfg(feature = "tui")]
+pub async fn run(provider: &ZeroProvider, refresh: std::time::Duration) -> anyhow::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, provider, refresh).await;
+    ratatui::restore();
+    result
+}
+
+#[cfg(feature = "tui")]
+async fn run_loop(terminal: &mut ratatui::DefaultTerminal, provider: &ZeroProvider, refresh: std::time::Duration) -> anyhow::Result<()> {
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+
+    loop {
+        let snapshot = snapshot(provider, RECENT_EVENTS).await?;
+        terminal.draw(|frame| draw(frame, &snapshot))?;
+
+        let next = std::time::Instant::now() + refresh;
+        while let Some(wait) = next.checked_duration_since(std::time::Instant::now()) {
+            if !tokio::task::block_in_place(|| event::poll(wait))? {
+                break;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn draw(frame: &mut ratatui::Frame, snapshot: &TopSnapshot) {
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Style, Stylize};
+    use ratatui::widgets::{Block, Gauge, List, Row, Table};
+
+    let [gauges, tables, events] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(6),
+        Constraint::Length(RECENT_EVENTS as u16 + 2),
+    ]).areas(frame.area());
+
+    let gauge_areas = Layout::horizontal([Constraint::Ratio(1, 3); 3]).split(gauges);
+    for ((name, used), area) in snapshot.node_usage().into_iter().zip(gauge_areas.iter()) {
+        let gauge = Gauge::default()
+            .block(Block::bordered().title(name))
+            .gauge_style(Style::new().cyan())
+            .ratio(used);
+        frame.render_widget(gauge, *area);
+    }
+
+    let [workloads, side] = Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(tables);
+    let [nodes, queues] = Layout::vertical([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(side);
+
+    let table = Table::new(
+        snapshot.workload_rows().into_iter().map(Row::new),
+        [Constraint::Fill(2), Constraint::Fill(1), Constraint::Length(9), Constraint::Fill(1), Constraint::Length(7), Constraint::Length(9)],
+    )
+        .header(Row::new(["ID", "NAMESPACE", "STATE", "NODE", "CPU", "MEMORY"]).bold())
+        .block(Block::bordered().title(format!("Workloads ({})", snapshot.overview.workloads.len())));
+    frame.render_widget(table, workloads);
+
+    let table = Table::new(
+        snapshot.node_rows().into_iter().map(Row::new),
+        [Constraint::Fill(2), Constraint::Fill(2), Constraint::Fill(1)],
+    )
+        .header(Row::new(["HOSTNAME", "ADDRESS", "STATUS"]).bold())
+        .block(Block::bordered().title("Nodes"));
+    frame.render_widget(table, nodes);
+
+    let table = Table::new(
+        snapshot.queue_rows().into_iter().map(Row::new),
+        [Constraint::Fill(2), Constraint::Length(8), Constraint::Length(10)],
+    )
+        .header(Row::new(["QUEUE", "VISIBLE", "IN FLIGHT"]).bold())
+        .block(Block::bordered().title("Queues"));
+    frame.render_widget(table, queues);
+
+    let list = List::new(snapshot.event_lines())
+        .block(Block::bordered().title("Recent events (q to quit)"));
+    frame.render_widget(list, events);
+}
@@ -48,3 +48,761 @@ async fn test_cli_native_flag_parsing() {
         panic!("Wrong command");
     }
 }
+
+#[test]
+fn test_cli_runtime_flag_parsing() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["zero", "--runtime", "podman", "node", "list"]).unwrap();
+    assert_eq!(cli.runtime.as_deref(), Some("podman"));
+    assert!(Cli::try_parse_from(["zero", "--runtime", "lxc", "node", "list"]).is_err());
+
+    // `func deploy --runtime` selects the function's execution environment, not the engine's
+    let cli = Cli::try_parse_from(["zero", "func", "deploy", "--name", "f", "--code", "alpine", "--runtime", "docker"]).unwrap();
+    assert_eq!(cli.runtime, None);
+}
+
+#[test]
+fn test_cli_confirmation_and_yes_flag() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["zero", "workload", "down", "--id", "web", "--yes"]).unwrap();
+    assert!(cli.yes);
+    assert_eq!(cli.command.confirmation().as_deref(), Some("Delete workload web in namespace default?"));
+
+    let cli = Cli::try_parse_from(["zero", "-y", "system", "reset", "--service", "queue"]).unwrap();
+    assert!(cli.yes);
+    assert!(cli.command.confirmation().is_some());
+
+    let cli = Cli::try_parse_from(["zero", "node", "list"]).unwrap();
+    assert!(!cli.yes);
+    assert_eq!(cli.command.confirmation(), None);
+}
+
+#[tokio::test]
+async fn test_cli_dry_run() {
+    use clap::Parser;
+    use zero_cli::execute_dry_run;
+    use zero_control_spi::{ZeroBody, ZeroRequest, ZeroService};
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+
+    let cli = Cli::try_parse_from(["zero", "queue", "create", "--name", "jobs", "--dry-run"]).unwrap();
+    assert!(cli.dry_run);
+    execute_dry_run(cli.command, &provider).await.unwrap();
+    let list = ZeroRequest { method: "GET".into(), path: "/v1/queue/queues".into(), headers: Default::default(), body: ZeroBody::empty() };
+    let resp = provider.handle_request(list).await.unwrap();
+    let queues: serde_json::Value = serde_json::from_slice(resp.body.as_bytes()).unwrap();
+    assert_eq!(queues["QueueUrls"], serde_json::json!([]));
+
+    // A dry run fails the way the command would
+    let cli = Cli::try_parse_from(["zero", "--dry-run", "workload", "down", "--id", "missing"]).unwrap();
+    assert!(execute_dry_run(cli.command, &provider).await.is_err());
+}
+
+#[test]
+fn test_cli_completions() {
+    use clap::Parser;
+    use zero_cli::write_completions;
+
+    assert!(Cli::try_parse_from(["zero", "completions", "tcsh"]).is_err());
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let Commands::Completions { shell } = Cli::try_parse_from(["zero", "completions", shell]).unwrap().command else {
+            panic!("Wrong command");
+        };
+        let mut script = Vec::new();
+        write_completions(shell, &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("workload"), "{} script has no subcommands", shell);
+    }
+}
+
+#[tokio::test]
+async fn test_cli_db_ttl_enable_parsing() {
+    use clap::Parser;
+    use zero_cli::{DbAction, TtlAction};
+
+    let args = vec!["zero", "db", "ttl", "enable", "--table", "sessions", "--attribute", "expires_at"];
+    let cli = Cli::try_parse_from(args).unwrap();
+
+    match cli.command {
+        Commands::Db { action: DbAction::Ttl { action: TtlAction::Enable { table, attribute } } } => {
+            assert_eq!(table, "sessions");
+            assert_eq!(attribute, "expires_at");
+        }
+        _ => panic!("Wrong command"),
+    }
+}
+
+#[tokio::test]
+async fn test_cli_queue_send_batch_parsing() {
+    use clap::Parser;
+    use zero_cli::QueueAction;
+
+    let args = vec!["zero", "queue", "send-batch", "--name", "jobs", "--body", "one", "--body", "two"];
+    let cli = Cli::try_parse_from(args).unwrap();
+
+    match cli.command {
+        Commands::Queue { action: QueueAction::SendBatch { name, bodies } } => {
+            assert_eq!(name, "jobs");
+            assert_eq!(bodies, vec!["one", "two"]);
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "queue", "receive", "--name", "jobs", "--max-messages", "10", "--wait", "20"];
+    assert!(Cli::try_parse_from(args).is_ok());
+
+    let args = vec!["zero", "queue", "change-visibility", "--name", "jobs", "--handle", "abc", "--timeout", "120"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Queue { action: QueueAction::ChangeVisibility { timeout, .. } } => assert_eq!(timeout, 120),
+        _ => panic!("Wrong command"),
+    }
+}
+
+#[tokio::test]
+async fn test_cli_queue_delay_parsing() {
+    use clap::Parser;
+    use zero_cli::QueueAction;
+
+    let args = vec!["zero", "queue", "send", "--name", "jobs", "--body", "later", "--delay-seconds", "30"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Queue { action: QueueAction::Send { delay_seconds, .. } } => assert_eq!(delay_seconds, Some(30)),
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "queue", "create", "--name", "jobs", "--delay-seconds", "5"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Queue { action: QueueAction::Create { delay_seconds, .. } } => assert_eq!(delay_seconds, Some(5)),
+        _ => panic!("Wrong command"),
+    }
+}
+
+#[tokio::test]
+async fn test_cli_func_deploy_runtime_parsing() {
+    use clap::Parser;
+    use zero_cli::FuncAction;
+
+    let args = vec!["zero", "func", "deploy", "--name", "resize", "--code", "resizer:latest", "--runtime", "docker"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Func { action: FuncAction::Deploy { runtime, handler, .. } } => {
+            assert_eq!(runtime, "docker");
+            assert_eq!(handler, "index.handler");
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "func", "deploy", "--name", "f", "--code", "x", "--runtime", "jvm"];
+    assert!(Cli::try_parse_from(args).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_func_deploy_config_parsing() {
+    use clap::Parser;
+    use zero_cli::FuncAction;
+
+    let args = vec!["zero", "func", "deploy", "--name", "f", "--code", "x", "--env", "STAGE=dev", "-e", "URL=a=b", "--timeout", "5", "--memory", "256"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Func { action: FuncAction::Deploy { env, timeout, memory, .. } } => {
+            assert_eq!(env, vec![("STAGE".to_string(), "dev".to_string()), ("URL".to_string(), "a=b".to_string())]);
+            assert_eq!((timeout, memory), (Some(5), Some(256)));
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "func", "deploy", "--name", "f", "--code", "x", "--env", "STAGE"];
+    assert!(Cli::try_parse_from(args).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_func_map_queue_parsing() {
+    use clap::Parser;
+    use zero_cli::FuncAction;
+
+    let args = vec!["zero", "func", "map-queue", "--function", "process", "--queue", "orders", "--batch-size", "5", "--max-retries", "3", "--dlq", "orders-dlq"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Func { action: FuncAction::MapQueue { function, queue, batch_size, max_retries, dlq } } => {
+            assert_eq!((function.as_str(), queue.as_str(), batch_size), ("process", "orders", 5));
+            assert_eq!(max_retries, Some(3));
+            assert_eq!(dlq.as_deref(), Some("orders-dlq"));
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    // A DLQ only applies once retries are bounded
+    let args = vec!["zero", "func", "map-queue", "--function", "f", "--queue", "q", "--dlq", "d"];
+    assert!(Cli::try_parse_from(args).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_lb_health_check_parsing() {
+    use clap::Parser;
+    use zero_cli::LbAction;
+
+    let args = vec!["zero", "lb", "create-target-group", "--name", "api", "--protocol", "TCP", "--health-check-interval", "5", "--unhealthy-threshold", "3"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Lb { action: LbAction::CreateTargetGroup { protocol, health_check_path, health_check_interval, healthy_threshold, unhealthy_threshold, .. } } => {
+            assert_eq!(protocol, "TCP");
+            assert_eq!(health_check_path, None);
+            assert_eq!((health_check_interval, healthy_threshold, unhealthy_threshold), (Some(5), None, Some(3)));
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "lb", "health", "--group", "arn:zero:elasticloadbalancing:000000:targetgroup/api/1"];
+    assert!(matches!(Cli::try_parse_from(args).unwrap().command, Commands::Lb { action: LbAction::Health { .. } }));
+}
+
+#[tokio::test]
+async fn test_cli_eks_parsing() {
+    use clap::Parser;
+    use zero_cli::EksAction;
+
+    let args = vec!["zero", "eks", "create", "--name", "dev", "--version", "1.28", "--kubeconfig", "dev.kubeconfig"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Eks { action: EksAction::Create { name, version, kubeconfig } } => {
+            assert_eq!(name, "dev");
+            assert_eq!(version.as_deref(), Some("1.28"));
+            assert_eq!(kubeconfig, Some(std::path::PathBuf::from("dev.kubeconfig")));
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "eks", "create-nodegroup", "--cluster", "dev", "--name", "workers", "--nodes", "3"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Eks { action: EksAction::CreateNodegroup { cluster, name, nodes, instance_type } } => {
+            assert_eq!((cluster.as_str(), name.as_str(), nodes), ("dev", "workers", 3));
+            assert_eq!(instance_type, None);
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    let args = vec!["zero", "eks", "delete-nodegroup", "--cluster", "dev", "--name", "workers"];
+    assert!(matches!(Cli::try_parse_from(args).unwrap().command, Commands::Eks { action: EksAction::DeleteNodegroup { .. } }));
+}
+
+#[tokio::test]
+async fn test_cli_topic_parsing() {
+    use clap::Parser;
+    use zero_cli::TopicAction;
+
+    let args = vec!["zero", "topic", "subscribe", "--name", "orders", "--queue", "billing", "--filter-policy", r#"{"event": ["created"]}"#];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Topic { action: TopicAction::Subscribe { name, queue, webhook, filter_policy, raw } } => {
+            assert_eq!(name, "orders");
+            assert_eq!(queue.as_deref(), Some("billing"));
+            assert_eq!(webhook, None);
+            assert_eq!(filter_policy.as_deref(), Some(r#"{"event": ["created"]}"#));
+            assert!(!raw);
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    // A subscription needs exactly one endpoint
+    assert!(Cli::try_parse_from(vec!["zero", "topic", "subscribe", "--name", "orders"]).is_err());
+    assert!(Cli::try_parse_from(vec!["zero", "topic", "subscribe", "--name", "orders", "--queue", "q", "--webhook", "http://x"]).is_err());
+
+    let args = vec!["zero", "topic", "publish", "--name", "orders", "--message", "hi", "-a", "event=created", "-a", "total=12"];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Topic { action: TopicAction::Publish { message, attributes, .. } } => {
+            assert_eq!(message, "hi");
+            assert_eq!(attributes, vec![("event".to_string(), "created".to_string()), ("total".to_string(), "12".to_string())]);
+        }
+        _ => panic!("Wrong command"),
+    }
+}
+
+#[tokio::test]
+async fn test_cli_schedule_parsing() {
+    use clap::Parser;
+    use zero_cli::ScheduleAction;
+
+    let args = vec!["zero", "schedule", "create", "--name", "nightly", "--schedule", "cron(0 2 * * ? *)", "--queue", "jobs", "--input", r#"{"job": "report"}"#];
+    match Cli::try_parse_from(args).unwrap().command {
+        Commands::Schedule { action: ScheduleAction::Create { name, schedule, function, queue, webhook, input } } => {
+            assert_eq!(name, "nightly");
+            assert_eq!(schedule, "cron(0 2 * * ? *)");
+            assert_eq!(function, None);
+            assert_eq!(queue.as_deref(), Some("jobs"));
+            assert_eq!(webhook, None);
+            assert_eq!(input.as_deref(), Some(r#"{"job": "report"}"#));
+        }
+        _ => panic!("Wrong command"),
+    }
+
+    // A rule needs exactly one target
+    assert!(Cli::try_parse_from(vec!["zero", "schedule", "create", "--name", "r", "--schedule", "rate(1 minute)"]).is_err());
+    assert!(Cli::try_parse_from(vec!["zero", "schedule", "create", "--name", "r", "--schedule", "rate(1 minute)", "--function", "f", "--webhook", "http://x"]).is_err());
+
+    let args = vec!["zero", "schedule", "delete", "--name", "nightly"];
+    assert!(matches!(Cli::try_parse_from(args).unwrap().command, Commands::Schedule { action: ScheduleAction::Delete { .. } }));
+}
+
+#[tokio::test]
+async fn test_cli_workload_volumes() {
+    use clap::Parser;
+    use zero_cli::WorkloadAction;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute.clone(), storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+    provider.store.create_bucket("pgdata").await.unwrap();
+    provider.store.create_bucket("assets").await.unwrap();
+
+    let args = vec!["zero", "workload", "up", "--id", "db", "--image", "postgres", "-v", "pgdata:/var/lib/postgresql/data", "-v", "assets:/srv:ro"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    match &command {
+        Commands::Workload { action: WorkloadAction::Up { volumes, .. } } => {
+            assert_eq!(volumes[1], ("assets".to_string(), "/srv".to_string(), true));
+        }
+        _ => panic!("Wrong command"),
+    }
+    execute_command(command, &provider).await.unwrap();
+    let mounts = compute.mounts("db");
+    assert_eq!(mounts.iter().map(|m| m.target.as_str()).collect::<Vec<_>>(), ["/var/lib/postgresql/data", "/srv"]);
+
+    assert!(Cli::try_parse_from(vec!["zero", "workload", "up", "--id", "db", "--image", "postgres", "-v", "pgdata"]).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_workload_stats() {
+    use clap::Parser;
+    use zero_cli::WorkloadAction;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = Arc::new(ZeroEngine::new(compute.clone(), storage, network).unwrap());
+    let provider = ZeroProvider::new(engine.clone());
+    execute_command(Cli::try_parse_from(["zero", "workload", "up", "--id", "web", "--image", "nginx"]).unwrap().command, &provider).await.unwrap();
+    compute.set_workload_cpu_usage("web", 42.0);
+
+    let command = Cli::try_parse_from(["zero", "workload", "stats", "--id", "web", "--period", "300"]).unwrap().command;
+    match &command {
+        Commands::Workload { action: WorkloadAction::Stats { id, period, samples, .. } } => assert_eq!((id.as_str(), *period, *samples), ("web", 300, 1)),
+        _ => panic!("Wrong command"),
+    }
+    execute_command(command, &provider).await.unwrap();
+    assert_eq!(engine.metrics.samples("web")[0].usage.cpu_percent, 42.0);
+
+    let missing = Cli::try_parse_from(["zero", "workload", "stats", "--id", "api"]).unwrap().command;
+    assert!(execute_command(missing, &provider).await.is_err());
+}
+
+#[tokio::test]
+async fn test_cli_workload_update() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let provider = ZeroProvider::new(Arc::new(ZeroEngine::new(compute.clone(), storage, network).unwrap()));
+    execute_command(Cli::try_parse_from(["zero", "workload", "up", "--id", "web", "--image", "nginx:1.25"]).unwrap().command, &provider).await.unwrap();
+
+    execute_command(Cli::try_parse_from(["zero", "workload", "update", "--id", "web", "--image", "nginx:1.27"]).unwrap().command, &provider).await.unwrap();
+    assert_eq!(compute.image("web").as_deref(), Some("nginx:1.27"));
+
+    compute.fail_image("nginx:broken");
+    let args = ["zero", "workload", "update", "--id", "web", "--image", "nginx:broken", "--timeout", "1"];
+    let err = execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap_err();
+    assert!(err.to_string().contains("rolled back"), "{}", err);
+    assert_eq!(compute.image("web").as_deref(), Some("nginx:1.27"));
+
+    execute_command(Cli::try_parse_from(["zero", "workload", "revisions", "--id", "web"]).unwrap().command, &provider).await.unwrap();
+    let statuses: Vec<_> = provider.rollout.list("web").unwrap().into_iter().map(|revision| revision.status).collect();
+    assert_eq!(statuses, ["superseded", "active", "failed"]);
+}
+
+#[tokio::test]
+async fn test_cli_image_commands() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let provider = ZeroProvider::new(Arc::new(ZeroEngine::new(compute, storage, network).unwrap()));
+
+    for args in [&["zero", "image", "pull", "nginx"][..], &["zero", "image", "ls"], &["zero", "image", "prune", "--unused-for", "3600"]] {
+        execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap();
+    }
+    assert_eq!(provider.images.list().await.unwrap().len(), 1);
+    execute_command(Cli::try_parse_from(["zero", "image", "prune"]).unwrap().command, &provider).await.unwrap();
+    assert!(provider.images.list().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cli_workload_restart_policy() {
+    use clap::Parser;
+    use zero_control_spi::RestartPolicy;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let provider = ZeroProvider::new(Arc::new(ZeroEngine::new(compute, storage, network).unwrap()));
+
+    let args = ["zero", "workload", "up", "--id", "api", "--image", "api", "--restart", "on-failure", "--liveness", "http:8080/healthz", "--liveness-failures", "2"];
+    execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap();
+    let supervision = provider.supervisor.get("api").unwrap().unwrap();
+    assert_eq!(supervision.restart_policy, RestartPolicy::OnFailure);
+    let probe = supervision.liveness_probe.unwrap();
+    assert_eq!((probe.port, probe.http_path.as_deref(), probe.interval_secs, probe.failure_threshold), (8080, Some("/healthz"), 10, 2));
+
+    assert!(Cli::try_parse_from(["zero", "workload", "up", "--id", "api", "--image", "api", "--restart", "sometimes"]).is_err());
+    assert!(Cli::try_parse_from(["zero", "workload", "up", "--id", "api", "--image", "api", "--liveness", "udp:53"]).is_err());
+    assert!(Cli::try_parse_from(["zero", "workload", "up", "--id", "api", "--image", "api", "--liveness-interval", "5"]).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_workload_ports() {
+    use clap::Parser;
+    use zero_cli::WorkloadAction;
+    use zero_control_spi::{PortMapping, PortProtocol};
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = Arc::new(ZeroEngine::new(compute, storage, network).unwrap());
+    let provider = ZeroProvider::new(engine.clone());
+
+    let args = vec!["zero", "workload", "up", "--id", "web", "--image", "nginx", "--publish", "18082:80", "-p", "53/udp"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    match &command {
+        Commands::Workload { action: WorkloadAction::Up { ports, .. } } => {
+            assert_eq!(ports[0], PortMapping { host_port: 18082, container_port: 80, protocol: PortProtocol::Tcp });
+            assert_eq!((ports[1].host_port, ports[1].protocol), (0, PortProtocol::Udp));
+        }
+        _ => panic!("Wrong command"),
+    }
+    execute_command(command, &provider).await.unwrap();
+    assert_eq!(engine.published_ports.list("web").unwrap().len(), 2);
+
+    for bad in ["80:", "http:80", "8080:80/sctp", "0"] {
+        assert!(Cli::try_parse_from(vec!["zero", "workload", "up", "--id", "web", "--image", "nginx", "-p", bad]).is_err(), "{}", bad);
+    }
+}
+
+#[tokio::test]
+async fn test_cli_backup_restore() {
+    use clap::Parser;
+
+    let provider = || {
+        let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+        let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+            tempfile::tempdir().unwrap().path().to_path_buf()
+        ));
+        let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+        ZeroProvider::new(Arc::new(ZeroEngine::new(compute, storage, network).unwrap()))
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("backup.tar.gz");
+    let archive_arg = archive.to_str().unwrap();
+
+    let source = provider();
+    source.queue.create_queue("jobs").await.unwrap();
+    let command = Cli::try_parse_from(vec!["zero", "backup", "create", "--output", archive_arg]).unwrap().command;
+    execute_command(command, &source).await.unwrap();
+    assert!(std::fs::metadata(&archive).unwrap().len() > 0);
+
+    let target = provider();
+    let command = Cli::try_parse_from(vec!["zero", "backup", "restore", "-i", archive_arg]).unwrap().command;
+    execute_command(command, &target).await.unwrap();
+    assert!(target.queue.list_queues().await.unwrap()[0].ends_with("/jobs/messages"));
+
+    assert!(Cli::try_parse_from(vec!["zero", "backup", "restore"]).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_asg() {
+    use clap::Parser;
+    use zero_cli::AsgAction;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let provider = ZeroProvider::new(Arc::new(ZeroEngine::new(compute, storage, network).unwrap()));
+    provider.queue.create_queue("jobs").await.unwrap();
+
+    let args = vec!["zero", "asg", "create", "--name", "workers", "--image", "worker", "--max", "5", "--queue", "jobs", "--messages-per-instance", "10"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    match &command {
+        Commands::Asg { action: AsgAction::Create { min, max, cpu_target, queue, messages_per_instance, .. } } => {
+            assert_eq!((*min, *max), (1, 5));
+            assert_eq!(*cpu_target, None);
+            assert_eq!(queue.as_deref(), Some("jobs"));
+            assert_eq!(*messages_per_instance, Some(10.0));
+        }
+        _ => panic!("Wrong command"),
+    }
+    execute_command(command, &provider).await.unwrap();
+    assert_eq!(provider.autoscaling.get_group("workers").await.unwrap().instances.len(), 1);
+
+    // A group scales on exactly one metric
+    assert!(Cli::try_parse_from(vec!["zero", "asg", "create", "--name", "web", "--image", "nginx", "--max", "3"]).is_err());
+    assert!(Cli::try_parse_from(vec!["zero", "asg", "create", "--name", "web", "--image", "nginx", "--max", "3", "--cpu-target", "60", "--queue", "jobs"]).is_err());
+    assert!(Cli::try_parse_from(vec!["zero", "asg", "create", "--name", "web", "--image", "nginx", "--max", "3", "--queue", "jobs"]).is_err());
+
+    let command = Cli::try_parse_from(vec!["zero", "asg", "delete", "--name", "workers"]).unwrap().command;
+    execute_command(command, &provider).await.unwrap();
+    assert!(provider.autoscaling.list_groups().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cli_node_labels_and_selectors() {
+    use clap::Parser;
+    use zero_cli::{NodeAction, WorkloadAction};
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = Arc::new(ZeroEngine::new(compute, storage, network).unwrap());
+    let provider = ZeroProvider::new(engine.clone());
+
+    let args = vec!["zero", "node", "register", "--hostname", "lab", "--ip", "10.0.0.7", "--endpoint", "tcp://10.0.0.7:2375"];
+    assert!(matches!(Cli::try_parse_from(args).unwrap().command, Commands::Node { action: NodeAction::Register { endpoint: Some(endpoint), .. } }
+        if endpoint == "tcp://10.0.0.7:2375"));
+    let args = vec!["zero", "node", "register", "--hostname", "nas", "--ip", "10.0.0.5", "-l", "disk=hdd", "--taint", "storage-only"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    assert!(matches!(&command, Commands::Node { action: NodeAction::Register { labels, taints, .. } }
+        if labels == &[("disk".to_string(), "hdd".to_string())] && taints == &["storage-only".to_string()]));
+    execute_command(command, &provider).await.unwrap();
+    let args = vec!["zero", "node", "register", "--hostname", "desk", "--ip", "10.0.0.6", "-l", "disk=hdd"];
+    execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap();
+    let args = vec!["zero", "node", "label", "desk", "-l", "disk=ssd", "-l", "zone=home"];
+    execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap();
+    // Stand in for the Docker endpoints of the nodes
+    for node in engine.list_nodes().unwrap() {
+        engine.set_node_driver(&node.id, Arc::new(zero_data_core::driver::MockComputeDriver::new()));
+    }
+
+    let args = vec!["zero", "workload", "up", "--id", "web", "--image", "nginx", "--selector", "disk=ssd", "--affinity", "zone!=office", "--affinity", "!gpu"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    match &command {
+        Commands::Workload { action: WorkloadAction::Up { affinity, .. } } => {
+            assert_eq!(affinity[0], serde_json::json!({ "key": "zone", "operator": "NotIn", "values": ["office"] }));
+            assert_eq!(affinity[1], serde_json::json!({ "key": "gpu", "operator": "DoesNotExist", "values": [] }));
+        }
+        _ => panic!("Wrong command"),
+    }
+    execute_command(command, &provider).await.unwrap();
+    let args = vec!["zero", "workload", "up", "--id", "backup", "--image", "restic", "--selector", "disk=hdd", "--toleration", "storage-only"];
+    execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.unwrap();
+    let placements = provider.placement.placements().await.unwrap();
+    assert_eq!(placements["web"], "desk");
+    assert_eq!(placements["backup"], "nas");
+
+    let args = vec!["zero", "workload", "up", "--id", "db", "--image", "postgres", "--selector", "disk=nvme"];
+    assert!(execute_command(Cli::try_parse_from(args).unwrap().command, &provider).await.is_err());
+    assert!(Cli::try_parse_from(vec!["zero", "workload", "up", "--id", "db", "--image", "postgres", "--affinity", "zone="]).is_err());
+}
+
+#[tokio::test]
+async fn test_cli_namespaces() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+
+    let run = |args: Vec<&str>| Cli::try_parse_from(args).unwrap().command;
+    execute_command(run(vec!["zero", "ns", "create", "lab", "--cpu", "2", "--volume-gb", "20"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "workload", "up", "--id", "web", "--image", "nginx", "--cpu", "1.5", "--namespace", "lab"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "volume", "create", "--id", "data", "--size", "20", "--namespace", "lab"]), &provider).await.unwrap();
+
+    // Over quota: 1.5 + 1 CPUs
+    let err = execute_command(run(vec!["zero", "workload", "up", "--id", "api", "--image", "nginx", "--namespace", "lab"]), &provider).await.unwrap_err();
+    assert!(err.to_string().contains("Quota exceeded"));
+    execute_command(run(vec!["zero", "ns", "quota", "lab", "--cpu", "4"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "workload", "up", "--id", "api", "--image", "nginx", "--namespace", "lab"]), &provider).await.unwrap();
+
+    let lab = provider.namespace.get("lab").await.unwrap();
+    assert_eq!((lab.used.cpu, lab.used.volume_gb, lab.quota.volume_gb), (2.5, 20, None));
+    execute_command(run(vec!["zero", "volume", "resize", "--id", "data", "--size", "25", "--namespace", "lab"]), &provider).await.unwrap();
+    assert_eq!(provider.namespace.get("lab").await.unwrap().used.volume_gb, 25);
+
+    // Only empty namespaces can be deleted
+    assert!(execute_command(run(vec!["zero", "ns", "delete", "lab"]), &provider).await.is_err());
+    execute_command(run(vec!["zero", "workload", "down", "--id", "web", "--namespace", "lab"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "workload", "down", "--id", "api", "--namespace", "lab"]), &provider).await.unwrap();
+    provider.namespace.release(zero_control_core::services::namespace::VOLUME, "data").await.unwrap();
+    execute_command(run(vec!["zero", "ns", "delete", "lab"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "ns", "ls"]), &provider).await.unwrap();
+
+    // Count quotas, shown with their usage by `zero quota`
+    execute_command(run(vec!["zero", "ns", "create", "small", "--workloads", "1", "--queues", "0"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "workload", "up", "--id", "web", "--image", "nginx", "--namespace", "small"]), &provider).await.unwrap();
+    let err = execute_command(run(vec!["zero", "workload", "up", "--id", "api", "--image", "nginx", "--namespace", "small"]), &provider).await.unwrap_err();
+    assert!(err.to_string().contains("Quota exceeded"));
+    execute_command(run(vec!["zero", "quota", "--namespace", "small"]), &provider).await.unwrap();
+    assert!(execute_command(run(vec!["zero", "quota", "--namespace", "missing"]), &provider).await.is_err());
+
+    let small = serde_json::to_value(provider.namespace.get("small").await.unwrap()).unwrap();
+    let usage: Vec<_> = zero_cli::quota_usage(&small).iter().map(|(resource, used)| (*resource, (*used).clone())).collect();
+    assert_eq!(usage, [
+        ("cpu", serde_json::json!(1.0)),
+        ("memory_mb", serde_json::json!(512)),
+        ("volume_gb", serde_json::json!(0)),
+        ("workloads", serde_json::json!(1)),
+        ("queues", serde_json::json!(0)),
+    ]);
+}
+
+#[tokio::test]
+async fn test_cli_audit_tail() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+
+    let run = |args: Vec<&str>| Cli::try_parse_from(args).unwrap().command;
+    execute_command(run(vec!["zero", "ns", "create", "lab"]), &provider).await.unwrap();
+    assert!(execute_command(run(vec!["zero", "ns", "create", "lab"]), &provider).await.is_err());
+    execute_command(run(vec!["zero", "audit", "tail", "-n", "1"]), &provider).await.unwrap();
+    execute_command(run(vec!["zero", "audit", "tail", "--since", "2020-01-01T00:00:00Z"]), &provider).await.unwrap();
+    assert!(execute_command(run(vec!["zero", "audit", "tail", "--since", "last week"]), &provider).await.is_err());
+    assert!(Cli::try_parse_from(["zero", "audit", "tail", "--follow", "--until", "2020-01-01T00:00:00Z"]).is_err());
+
+    let events = provider.audit.events(&Default::default()).await.unwrap();
+    let outcomes: Vec<_> = events.iter().map(|e| (e.principal.as_str(), e.status)).collect();
+    assert_eq!(outcomes, [("local", 200), ("local", 409)]);
+}
+
+#[tokio::test]
+async fn test_cli_system_reset() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+
+    let run = |args: Vec<&str>| Cli::try_parse_from(args).unwrap().command;
+    provider.db.create_table("users", "id").await.unwrap();
+    provider.queue.create_queue("jobs").await.unwrap();
+    assert!(Cli::try_parse_from(["zero", "system", "reset", "--service", "cache"]).is_err());
+
+    execute_command(run(vec!["zero", "system", "reset", "--service", "db"]), &provider).await.unwrap();
+    assert!(provider.db.list_tables().await.unwrap().is_empty());
+    assert_eq!(provider.queue.list_queues().await.unwrap().len(), 1);
+
+    execute_command(run(vec!["zero", "system", "reset"]), &provider).await.unwrap();
+    assert!(provider.queue.list_queues().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cli_security_groups() {
+    use clap::Parser;
+    use zero_cli::SecurityGroupAction;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = Arc::new(ZeroEngine::new(compute, storage, network.clone()).unwrap());
+    let provider = ZeroProvider::new(engine.clone());
+
+    execute_command(Cli::try_parse_from(["zero", "network", "create", "--id", "lab", "--cidr", "10.50.0.0/24"]).unwrap().command, &provider).await.unwrap();
+    let args = ["zero", "network", "security-group", "create", "-n", "lab", "--name", "web", "-r", "ingress:tcp:80-443:10.0.0.0/8", "-r", "egress:udp:53:10.50.0.1"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    assert!(matches!(&command, Commands::Network { action: NetworkAction::SecurityGroup { action: SecurityGroupAction::Create { rules, .. } } }
+        if rules[0] == serde_json::json!({ "direction": "ingress", "protocol": "tcp", "from_port": 80, "to_port": 443, "cidr": "10.0.0.0/8" })));
+    execute_command(command, &provider).await.unwrap();
+    assert_eq!(network.security_groups("lab")[0].rules.len(), 2);
+
+    assert!(Cli::try_parse_from(["zero", "network", "security-group", "create", "-n", "lab", "--name", "x", "-r", "inbound:tcp:0.0.0.0/0"]).is_err());
+    assert!(Cli::try_parse_from(["zero", "network", "security-group", "create", "-n", "lab", "--name", "x", "-r", "ingress:tcp:http:0.0.0.0/0"]).is_err());
+
+    let id = engine.security_groups.list("lab").unwrap()[0].id.clone();
+    execute_command(Cli::try_parse_from(["zero", "network", "security-group", "delete", "-n", "lab", "-i", &id]).unwrap().command, &provider).await.unwrap();
+    assert!(network.security_groups("lab").is_empty());
+}
+
+#[tokio::test]
+async fn test_cli_api_export_spec() {
+    use clap::Parser;
+    use zero_cli::ApiAction;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(dir.path().to_path_buf()));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+
+    let path = dir.path().join("zerocloud.json");
+    let args = ["zero", "api", "export-spec", "-o", path.to_str().unwrap(), "--server-url", "http://zero.internal:8080"];
+    let command = Cli::try_parse_from(args).unwrap().command;
+    assert!(matches!(&command, Commands::Api { action: ApiAction::ExportSpec { server_url, .. } } if server_url == "http://zero.internal:8080"));
+    execute_command(command, &provider).await.unwrap();
+
+    let spec: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(spec["openapi"], zero_control_core::openapi::OPENAPI_VERSION);
+    assert_eq!(spec["servers"][0]["url"], "http://zero.internal:8080");
+    assert_eq!(spec["paths"]["/v1/openapi.json"]["get"]["operationId"], "GetOpenApi");
+}
+
+#[tokio::test]
+async fn test_cli_top_snapshot() {
+    use clap::Parser;
+
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(
+        tempfile::tempdir().unwrap().path().to_path_buf()
+    ));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute.clone(), storage, network).unwrap();
+    let provider = ZeroProvider::new(Arc::new(engine));
+    execute_command(Cli::try_parse_from(["zero", "workload", "up", "--id", "web", "--image", "nginx"]).unwrap().command, &provider).await.unwrap();
+    execute_command(Cli::try_parse_from(["zero", "queue", "create", "--name", "jobs"]).unwrap().command, &provider).await.unwrap();
+    compute.set_workload_cpu_usage("web", 42.0);
+
+    let snapshot = zero_cli::top::snapshot(&provider, 1).await.unwrap();
+    let rows = snapshot.workload_rows();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0][0], "web");
+    assert_eq!(rows[0][4], "42.0%");
+    assert_eq!(snapshot.queue_rows(), [["jobs".to_string(), "0".to_string(), "0".to_string()]]);
+    // Only the latest event is kept: the queue creation
+    let events = snapshot.event_lines();
+    assert_eq!(events.len(), 1);
+    assert!(events[0].contains("POST /v1/queue/queues"), "{}", events[0]);
+
+    assert!(matches!(Cli::try_parse_from(["zero", "top", "--refresh", "5"]).unwrap().command, Commands::Top { refresh: 5 }));
+    assert!(Cli::try_parse_from(["zero", "top", "--refresh", "0"]).is_err());
+}
@@ -0,0 +1,181 @@
+// ZeroCloud dashboard: polls the overview document and drives workloads through the v1 API
+"use strict";
+
+const REFRESH_MS = 5000;
+
+async function api(method, path, body, namespace) {
+  const headers = body ? { "Content-Type": "application/json" } : {};
+  if (namespace) {
+    headers["X-Zero-Namespace"] = namespace;
+  }
+  const response = await fetch(path, {
+    method,
+    headers,
+    body: body ? JSON.stringify(body) : undefined,
+  });
+  const data = await response.json().catch(() => ({}));
+  if (!response.ok) {
+    throw new Error(data.message || `${method} ${path} failed with status ${response.status}`);
+  }
+  return data;
+}
+
+function showError(message) {
+  const banner = document.getElementById("error");
+  banner.textContent = message || "";
+  banner.hidden = !message;
+}
+
+function cell(value) {
+  const td = document.createElement("td");
+  td.textContent = value ?? "—";
+  return td;
+}
+
+function button(label, title, onClick) {
+  const element = document.createElement("button");
+  element.textContent = label;
+  element.title = title;
+  element.addEventListener("click", onClick);
+  return element;
+}
+
+// Rows are built with textContent, so names from the API are never parsed as HTML
+function fillTable(id, rows, columns, actions) {
+  const body = document.querySelector(`#${id} tbody`);
+  if (!rows.length) {
+    const empty = cell("None");
+    empty.colSpan = columns.length + (actions ? 1 : 0);
+    empty.className = "empty";
+    const tr = document.createElement("tr");
+    tr.append(empty);
+    body.replaceChildren(tr);
+    return;
+  }
+  body.replaceChildren(...rows.map((row) => {
+    const tr = document.createElement("tr");
+    tr.append(...columns.map((column) => cell(column(row))));
+    if (actions) {
+      const td = document.createElement("td");
+      td.append(...actions(row));
+      tr.append(td);
+    }
+    return tr;
+  }));
+}
+
+function setMeter(id, used, total, unit) {
+  const percent = total ? Math.min(100, (used / total) * 100) : 0;
+  document.querySelector(`#${id} .bar`).style.width = `${percent}%`;
+  document.querySelector(`#${id} .value`).textContent = total ? `${used} / ${total} ${unit}` : "Not reported";
+}
+
+function render({ stats, nodes, workloads, queues, groups }) {
+  setMeter("cpu", Number(stats.cpu_usage_percent.toFixed(1)), 100, "%");
+  setMeter("memory", stats.memory_used_mb, stats.memory_total_mb, "MB");
+  setMeter("storage", stats.storage_used_gb, stats.storage_total_gb, "GB");
+
+  fillTable("workloads", workloads, [(w) => w.id, (w) => w.namespace, (w) => w.state, (w) => w.restart_count, (w) => w.ip_address, (w) => w.node],
+    (w) => [
+      button("Usage", "Show this workload's resource usage", () => showUsage(w.id, w.namespace)),
+      button("Stop", "Stop and remove this workload", () => stopWorkload(w.id, w.namespace)),
+    ]);
+  fillTable("nodes", nodes, [
+    (n) => n.hostname,
+    (n) => n.ip_address,
+    (n) => n.status,
+    (n) => Object.entries(n.labels || {}).map(([key, value]) => `${key}=${value}`).join(", ") || null,
+    (n) => (n.taints || []).join(", ") || null,
+  ]);
+  fillTable("queues", queues, [(q) => q.name, (q) => q.visible, (q) => q.not_visible]);
+  fillTable("groups", groups, [
+    (g) => g.name,
+    (g) => (g.metric === "cpu" ? `CPU ${g.target_value}%` : `${g.target_value} messages per instance of ${g.queue}`),
+    (g) => g.last_metric,
+    (g) => `${g.instances.length} of ${g.desired_capacity}`,
+    (g) => `${g.min_size}–${g.max_size}`,
+  ]);
+}
+
+// Workload whose usage is shown, followed on every refresh
+let usageOf = null;
+
+function bytes(value) {
+  const units = ["B", "kB", "MB", "GB"];
+  let unit = 0;
+  while (value >= 1000 && unit < units.length - 1) {
+    value /= 1000;
+    unit += 1;
+  }
+  return `${Number(value.toFixed(1))} ${units[unit]}`;
+}
+
+async function renderUsage() {
+  if (!usageOf) {
+    return;
+  }
+  const { id, namespace } = usageOf;
+  const metrics = await api("GET", `/v1/workloads/${encodeURIComponent(id)}/metrics?period=60`, null, namespace);
+  document.getElementById("usage-workload").textContent = id;
+  fillTable("usage", metrics.datapoints.slice().reverse(), [
+    (d) => new Date(d.timestamp).toLocaleTimeString(),
+    (d) => `${d.cpu_percent_avg.toFixed(1)}%`,
+    (d) => `${d.cpu_percent_max.toFixed(1)}%`,
+    (d) => `${d.memory_used_mb_avg} MB`,
+    (d) => (d.memory_limit_mb ? `${d.memory_used_mb_max} / ${d.memory_limit_mb} MB` : `${d.memory_used_mb_max} MB`),
+    (d) => bytes(d.network_rx_bytes),
+    (d) => bytes(d.network_tx_bytes),
+  ]);
+  document.getElementById("usage-section").hidden = false;
+}
+
+function showUsage(id, namespace) {
+  usageOf = { id, namespace };
+  refresh();
+}
+
+async function refresh() {
+  const status = document.getElementById("status");
+  try {
+    render(await api("GET", "/dashboard/api/overview"));
+    await renderUsage().catch(() => {
+      // The workload is gone
+      usageOf = null;
+      document.getElementById("usage-section").hidden = true;
+    });
+    status.textContent = `Updated ${new Date().toLocaleTimeString()}`;
+    status.className = "";
+  } catch (e) {
+    status.textContent = `Cannot reach ZeroCloud: ${e.message}`;
+    status.className = "offline";
+  }
+}
+
+async function act(action) {
+  showError(null);
+  try {
+    await action();
+  } catch (e) {
+    showError(e.message);
+  }
+  await refresh();
+}
+
+function stopWorkload(id, namespace) {
+  if (confirm(`Stop and remove workload ${id}?`)) {
+    act(() => api("DELETE", "/v1/workloads", { id }, namespace));
+  }
+}
+
+document.getElementById("start-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  const form = event.target;
+  const workload = { id: form.elements.workload.value, image: form.elements.image.value };
+  act(async () => {
+    await api("POST", "/v1/workloads", workload, form.elements.namespace.value);
+    form.reset();
+  });
+});
+
+refresh();
+setInterval(refresh, REFRESH_MS);
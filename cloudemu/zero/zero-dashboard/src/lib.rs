@@ -0,0 +1,102 @@
+//! ZeroCloud web dashboard
+//!
+//! A single page at [`DASHBOARD_PATH`] showing the node's metrics, nodes, workloads of every
+//! namespace, queues and autoscaling groups, refreshed every few seconds from one [`Overview`]
+//! document. Workloads are started and stopped through the `/v1/workloads` API, like
+//! `zero workload up/down`, and their usage is read from `/v1/workloads/{id}/metrics`.
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use zero_control_core::ZeroProvider;
+use zero_control_core::services::autoscaling::ScalingGroup;
+use zero_control_core::services::namespace::{DEFAULT_NAMESPACE, NAMESPACE_HEADER};
+use zero_control_core::services::queue::QueueStats;
+use zero_control_spi::{NodeStats, WorkloadStatus, ZeroBody, ZeroError, ZeroRequest, ZeroResult, ZeroService};
+
+pub const DASHBOARD_PATH: &str = "/dashboard";
+
+const INDEX_HTML: &str = include_str!("../assets/index.html");
+
+const APP_JS: &str = include_str!("../assets/app.js");
+
+const STYLE_CSS: &str = include_str!("../assets/style.css");
+
+/// Everything the dashboard shows
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Overview {
+    pub stats: NodeStats,
+    pub nodes: Vec<serde_json::Value>,
+    pub workloads: Vec<Workload>,
+    pub queues: Vec<QueueStats>,
+    pub groups: Vec<ScalingGroup>,
+}
+
+/// A workload with its namespace and the node it was placed on
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Workload {
+    #[serde(flatten)]
+    pub status: WorkloadStatus,
+    pub node: Option<String>,
+    #[serde(default)]
+    pub namespace: String,
+}
+
+/// Routes of the dashboard page, its assets and its overview document
+pub fn router(provider: Arc<ZeroProvider>) -> Router {
+    Router::new()
+        .route(DASHBOARD_PATH, get(|| async { Html(INDEX_HTML) }))
+        .route("/dashboard/app.js", get(|| async { ([(header::CONTENT_TYPE, "text/javascript")], APP_JS) }))
+        .route("/dashboard/style.css", get(|| async { ([(header::CONTENT_TYPE, "text/css")], STYLE_CSS) }))
+        .route("/dashboard/api/overview", get(overview_handler))
+        .with_state(provider)
+}
+
+/// Collect the dashboard's data from the provider
+pub async fn overview(provider: &ZeroProvider) -> ZeroResult<Overview> {
+    let mut nodes = get_json(provider, "/v1/nodes", DEFAULT_NAMESPACE).await?;
+    let mut workloads = Vec::new();
+    for namespace in provider.namespace.list().await? {
+        let mut listed = get_json(provider, "/v1/workloads", &namespace.name).await?;
+        for mut workload in from_json::<Vec<Workload>>(listed["workloads"].take())? {
+            workload.namespace = namespace.name.clone();
+            workloads.push(workload);
+        }
+    }
+    Ok(Overview {
+        stats: from_json(get_json(provider, "/v1/stats", DEFAULT_NAMESPACE).await?)?,
+        nodes: from_json(nodes["nodes"].take())?,
+        workloads,
+        queues: provider.queue.queue_stats().await?,
+        groups: provider.autoscaling.list_groups().await?,
+    })
+}
+
+async fn overview_handler(State(provider): State<Arc<ZeroProvider>>) -> Response {
+    match overview(&provider).await {
+        Ok(overview) => Json(overview).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "message": e.to_string() }))).into_response(),
+    }
+}
+
+/// Read an API route the way a client would, so the dashboard shows what the API returns
+async fn get_json(provider: &ZeroProvider, path: &str, namespace: &str) -> ZeroResult<serde_json::Value> {
+    let resp = provider.handle_request(ZeroRequest {
+        method: "GET".into(),
+        path: path.into(),
+        headers: std::collections::HashMap::from([(NAMESPACE_HEADER.to_string(), namespace.to_string())]),
+        body: ZeroBody::empty(),
+    }).await?;
+    serde_json::from_slice(resp.body.as_bytes()).map_err(|e| ZeroError::Internal(e.to_string()))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> ZeroResult<T> {
+    serde_json::from_value(value).map_err(|e| ZeroError::Internal(e.to_string()))
+}
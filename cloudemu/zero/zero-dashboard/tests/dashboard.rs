@@ -0,0 +1,75 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+use std::sync::Arc;
+use tower::ServiceExt;
+use zero_control_core::ZeroProvider;
+use zero_control_spi::{ZeroRequest, ZeroService};
+use zero_dashboard::Overview;
+use zero_data_core::ZeroEngine;
+
+fn mock_provider(dir: &tempfile::TempDir) -> Arc<ZeroProvider> {
+    let compute = Arc::new(zero_data_core::driver::MockComputeDriver::new());
+    let storage = Arc::new(zero_data_core::driver::FileSystemStorage::new(dir.path().to_path_buf()));
+    let network = Arc::new(zero_data_core::driver::MockNetworkDriver::new());
+    let engine = ZeroEngine::new(compute, storage, network).unwrap();
+    Arc::new(ZeroProvider::new(Arc::new(engine)))
+}
+
+async fn get(provider: &Arc<ZeroProvider>, path: &str) -> (StatusCode, Option<String>, Vec<u8>) {
+    let resp = zero_dashboard::router(provider.clone())
+        .oneshot(Request::get(path).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let content_type = resp.headers().get("content-type").map(|v| v.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (status, content_type, body.to_vec())
+}
+
+#[tokio::test]
+async fn test_dashboard_assets() {
+    let dir = tempfile::tempdir().unwrap();
+    let provider = mock_provider(&dir);
+
+    let (status, content_type, body) = get(&provider, "/dashboard").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/html"));
+    assert!(String::from_utf8(body).unwrap().contains("/dashboard/app.js"));
+
+    let (status, content_type, _) = get(&provider, "/dashboard/app.js").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/javascript"));
+
+    let (status, _, _) = get(&provider, "/dashboard/missing.js").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_dashboard_overview() {
+    let dir = tempfile::tempdir().unwrap();
+    let provider = mock_provider(&dir);
+
+    let resp = provider.handle_request(ZeroRequest {
+        method: "POST".into(),
+        path: "/v1/workloads".into(),
+        headers: std::collections::HashMap::new(),
+        body: json!({ "id": "web-1", "image": "nginx:alpine" }).to_string().into_bytes().into(),
+    }).await.unwrap();
+    assert_eq!(resp.status, 200);
+    provider.queue.create_queue("jobs").await.unwrap();
+    provider.queue.send_message("jobs", "one").await.unwrap();
+    provider.queue.send_message("jobs", "two").await.unwrap();
+    provider.queue.receive_message("jobs").await.unwrap().unwrap();
+
+    let (status, _, body) = get(&provider, "/dashboard/api/overview").await;
+    assert_eq!(status, StatusCode::OK);
+    let overview: Overview = serde_json::from_slice(&body).unwrap();
+    assert!(overview.workloads.iter().any(|w| w.status.id == "web-1" && w.node.is_none() && w.namespace == "default"));
+    assert_eq!(overview.queues.len(), 1);
+    assert_eq!(overview.queues[0].name, "jobs");
+    assert_eq!(overview.queues[0].visible, 1);
+    assert_eq!(overview.queues[0].not_visible, 1);
+    assert!(overview.groups.is_empty());
+    assert!(overview.stats.memory_total_mb > 0);
+}
@@ -7,11 +7,21 @@
 //! - **AWS**: KMS
 //! - **Azure**: Key Vault Keys
 //! - **GCP**: Cloud KMS
+//!
+//! ## Envelope encryption
+//!
+//! `encrypt_envelope` has the provider KMS generate a data key, encrypts locally with
+//! AES-256-GCM and returns an [`Envelope`] holding the encrypted data key next to the
+//! ciphertext; `decrypt_envelope` has the KMS decrypt the data key and decrypts locally.
+//! Only the data key travels to the KMS, so payloads of any size cost one KMS call.
 
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
 use async_trait::async_trait;
-use cloudkit_spi::{CloudResult, Metadata};
+use cloudkit_spi::{CloudError, CloudResult, Metadata};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Encryption key metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,6 +178,143 @@ pub struct DataKey {
     pub key_id: String,
 }
 
+/// Data encrypted under a KMS-wrapped data key.
+///
+/// The byte format is the same for every provider:
+///
+/// | Field | Size |
+/// | :--- | :--- |
+/// | Magic `CKE` and version `1` | 4 |
+/// | Key ID length, big endian | 2 |
+/// | Key ID, UTF-8 | variable |
+/// | Encrypted data key length, big endian | 2 |
+/// | Encrypted data key | variable |
+/// | AES-GCM nonce | 12 |
+/// | Ciphertext and 16-byte tag | rest |
+///
+/// Everything before the nonce, and the encryption context, is authenticated with the
+/// ciphertext, so neither can be swapped without decryption failing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Envelope {
+    /// Key ID of the CMK that encrypted the data key.
+    pub key_id: String,
+    /// Data key, encrypted by the CMK.
+    pub encrypted_key: Vec<u8>,
+    /// AES-GCM nonce.
+    pub nonce: [u8; Envelope::NONCE_LEN],
+    /// AES-256-GCM ciphertext followed by its tag.
+    pub ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    /// Magic and format version leading every envelope.
+    pub const MAGIC: [u8; 4] = *b"CKE\x01";
+    /// Length of the AES-GCM nonce.
+    pub const NONCE_LEN: usize = 12;
+    /// Length of the AES-256 data key.
+    pub const DATA_KEY_LEN: usize = 32;
+
+    /// Encrypt `plaintext` with a freshly generated data key.
+    pub fn seal(
+        data_key: &DataKey,
+        plaintext: &[u8],
+        context: Option<&EncryptionContext>,
+    ) -> CloudResult<Self> {
+        let cipher = Self::cipher(&data_key.plaintext)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let mut envelope = Self {
+            key_id: data_key.key_id.clone(),
+            encrypted_key: data_key.ciphertext.clone(),
+            nonce: nonce.into(),
+            ciphertext: Vec::new(),
+        };
+        let aad = envelope.aad(context)?;
+        envelope.ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad: &aad })
+            .map_err(|_| CloudError::Internal("AES-GCM encryption failed".to_string()))?;
+        Ok(envelope)
+    }
+
+    /// Decrypt the ciphertext with the decrypted data key.
+    pub fn open(&self, data_key: &[u8], context: Option<&EncryptionContext>) -> CloudResult<Vec<u8>> {
+        let cipher = Self::cipher(data_key)?;
+        let aad = self.aad(context)?;
+        cipher
+            .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ciphertext, aad: &aad })
+            .map_err(|_| CloudError::Validation(
+                "Envelope authentication failed: the ciphertext, its header or the encryption context do not match".to_string(),
+            ))
+    }
+
+    /// Serialize to the portable envelope format.
+    pub fn to_bytes(&self) -> CloudResult<Vec<u8>> {
+        let mut bytes = self.header()?;
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+        Ok(bytes)
+    }
+
+    /// Parse the portable envelope format.
+    pub fn from_bytes(bytes: &[u8]) -> CloudResult<Self> {
+        let malformed = |what: &str| CloudError::Validation(format!("Malformed envelope: {}", what));
+        let rest = bytes.strip_prefix(&Self::MAGIC[..]).ok_or_else(|| malformed("unknown format or version"))?;
+
+        let (key_id, rest) = Self::field(rest).ok_or_else(|| malformed("truncated key ID"))?;
+        let key_id = String::from_utf8(key_id.to_vec()).map_err(|_| malformed("key ID is not UTF-8"))?;
+        let (encrypted_key, rest) = Self::field(rest).ok_or_else(|| malformed("truncated data key"))?;
+        if rest.len() < Self::NONCE_LEN + 16 {
+            return Err(malformed("truncated ciphertext"));
+        }
+        let (nonce, ciphertext) = rest.split_at(Self::NONCE_LEN);
+
+        Ok(Self {
+            key_id,
+            encrypted_key: encrypted_key.to_vec(),
+            nonce: nonce.try_into().expect("nonce length checked"),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    fn cipher(data_key: &[u8]) -> CloudResult<Aes256Gcm> {
+        if data_key.len() != Self::DATA_KEY_LEN {
+            return Err(CloudError::Validation(format!(
+                "Envelope encryption needs a {}-byte AES-256 data key, got {} bytes",
+                Self::DATA_KEY_LEN,
+                data_key.len()
+            )));
+        }
+        Aes256Gcm::new_from_slice(data_key).map_err(|e| CloudError::Internal(e.to_string()))
+    }
+
+    /// Length-prefixed field and the bytes after it
+    fn field(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+        let (len, rest) = bytes.split_first_chunk::<2>()?;
+        let len = u16::from_be_bytes(*len) as usize;
+        (rest.len() >= len).then(|| rest.split_at(len))
+    }
+
+    fn header(&self) -> CloudResult<Vec<u8>> {
+        let mut header = Self::MAGIC.to_vec();
+        for field in [self.key_id.as_bytes(), &self.encrypted_key] {
+            let len = u16::try_from(field.len())
+                .map_err(|_| CloudError::Validation("Envelope fields are limited to 65535 bytes".to_string()))?;
+            header.extend_from_slice(&len.to_be_bytes());
+            header.extend_from_slice(field);
+        }
+        Ok(header)
+    }
+
+    /// Additional authenticated data: the header, then the context as JSON with sorted keys
+    fn aad(&self, context: Option<&EncryptionContext>) -> CloudResult<Vec<u8>> {
+        let mut aad = self.header()?;
+        if let Some(context) = context.filter(|context| !context.is_empty()) {
+            let sorted: BTreeMap<_, _> = context.iter().collect();
+            aad.extend(serde_json::to_vec(&sorted).map_err(|e| CloudError::Serialization(e.to_string()))?);
+        }
+        Ok(aad)
+    }
+}
+
 /// Signing algorithm.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SigningAlgorithm {
@@ -267,6 +414,32 @@ pub trait KeyManagement: Send + Sync {
         context: Option<EncryptionContext>,
     ) -> CloudResult<Vec<u8>>;
 
+    /// Encrypt data of any size under a new data key of `key_id`, returning the
+    /// serialized [`Envelope`].
+    ///
+    /// The same `context` must be given to decrypt.
+    async fn encrypt_envelope(
+        &self,
+        key_id: &str,
+        plaintext: &[u8],
+        context: Option<EncryptionContext>,
+    ) -> CloudResult<Vec<u8>> {
+        let data_key = self.generate_data_key(key_id, context.clone()).await?;
+        Envelope::seal(&data_key, plaintext, context.as_ref())?.to_bytes()
+    }
+
+    /// Decrypt a serialized [`Envelope`] made by `encrypt_envelope`, with any provider
+    /// that can decrypt its data key.
+    async fn decrypt_envelope(
+        &self,
+        envelope: &[u8],
+        context: Option<EncryptionContext>,
+    ) -> CloudResult<Vec<u8>> {
+        let envelope = Envelope::from_bytes(envelope)?;
+        let data_key = self.decrypt(&envelope.encrypted_key, context.clone()).await?;
+        envelope.open(&data_key.plaintext, context.as_ref())
+    }
+
     // --- Digital Signatures ---
 
     /// Sign a message digest.
@@ -298,3 +471,66 @@ pub trait KeyManagement: Send + Sync {
     async fn list_key_tags(&self, key_id: &str) -> CloudResult<Metadata>;
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_key() -> DataKey {
+        DataKey { plaintext: vec![7u8; 32], ciphertext: b"wrapped-key".to_vec(), key_id: "alias/app".to_string() }
+    }
+
+    #[test]
+    fn test_envelope_round_trip() {
+        let context: EncryptionContext = [("tenant".to_string(), "acme".to_string())].into();
+        let envelope = Envelope::seal(&data_key(), b"secret payload", Some(&context)).unwrap();
+        assert_eq!(envelope.key_id, "alias/app");
+        assert_eq!(envelope.encrypted_key, b"wrapped-key");
+        assert_eq!(envelope.ciphertext.len(), b"secret payload".len() + 16);
+
+        let bytes = envelope.to_bytes().unwrap();
+        assert!(bytes.starts_with(&Envelope::MAGIC));
+        let parsed = Envelope::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(parsed.open(&[7u8; 32], Some(&context)).unwrap(), b"secret payload");
+
+        // A fresh nonce each time
+        let again = Envelope::seal(&data_key(), b"secret payload", Some(&context)).unwrap();
+        assert_ne!(again.nonce, envelope.nonce);
+    }
+
+    #[test]
+    fn test_envelope_authentication() {
+        let context: EncryptionContext = [("tenant".to_string(), "acme".to_string())].into();
+        let envelope = Envelope::seal(&data_key(), b"secret payload", Some(&context)).unwrap();
+
+        assert!(envelope.open(&[8u8; 32], Some(&context)).is_err());
+        assert!(envelope.open(&[7u8; 32], None).is_err());
+        let other: EncryptionContext = [("tenant".to_string(), "other".to_string())].into();
+        assert!(envelope.open(&[7u8; 32], Some(&other)).is_err());
+
+        let mut swapped = envelope.clone();
+        swapped.key_id = "alias/other".to_string();
+        assert!(swapped.open(&[7u8; 32], Some(&context)).is_err());
+
+        let mut tampered = envelope.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(tampered.open(&[7u8; 32], Some(&context)).is_err());
+
+        // An empty context is no context
+        let plain = Envelope::seal(&data_key(), b"x", Some(&EncryptionContext::new())).unwrap();
+        assert_eq!(plain.open(&[7u8; 32], None).unwrap(), b"x");
+    }
+
+    #[test]
+    fn test_envelope_validation() {
+        let short = DataKey { plaintext: vec![7u8; 16], ..data_key() };
+        assert!(matches!(Envelope::seal(&short, b"x", None), Err(CloudError::Validation(_))));
+
+        let bytes = Envelope::seal(&data_key(), b"x", None).unwrap().to_bytes().unwrap();
+        assert!(Envelope::from_bytes(&bytes[..bytes.len() - 2]).is_err());
+        assert!(Envelope::from_bytes(&bytes[..20]).is_err());
+        assert!(Envelope::from_bytes(b"CKE\x02").is_err());
+        assert!(Envelope::from_bytes(b"").is_err());
+    }
+}
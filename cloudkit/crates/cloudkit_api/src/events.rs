@@ -9,7 +9,7 @@
 //! - **GCP**: Eventarc
 
 use async_trait::async_trait;
-use cloudkit_spi::CloudResult;
+use cloudkit_spi::{CloudError, CloudResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -124,6 +124,36 @@ impl EventRule {
             arn: None,
         }
     }
+
+    /// Exact-match filters of the pattern, as `(field, accepted values)` pairs.
+    ///
+    /// Event Grid and Eventarc only filter on event attributes, so a pattern they
+    /// accept must be an object whose fields each list the string values to match,
+    /// e.g. `{"source": ["myapp.orders"], "detail-type": ["OrderCreated"]}`.
+    pub fn match_filters(&self) -> CloudResult<Vec<(String, Vec<String>)>> {
+        let Some(pattern) = &self.event_pattern else {
+            return Err(CloudError::Validation(format!("rule {} has no event pattern", self.name)));
+        };
+        let Some(fields) = pattern.as_object() else {
+            return Err(CloudError::Validation("event pattern must be a JSON object".to_string()));
+        };
+        let mut filters = Vec::new();
+        for (field, values) in fields {
+            let values: Option<Vec<String>> = values.as_array()
+                .map(|values| values.iter().map(|v| v.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+            match values {
+                Some(values) if !values.is_empty() => filters.push((field.clone(), values)),
+                _ => return Err(CloudError::Validation(format!(
+                    "pattern field {} must list the string values to match", field
+                ))),
+            }
+        }
+        if filters.is_empty() {
+            return Err(CloudError::Validation("event pattern matches no field".to_string()));
+        }
+        Ok(filters)
+    }
 }
 
 /// Rule state.
@@ -136,13 +166,26 @@ pub enum RuleState {
     Disabled,
 }
 
+/// Kind of resource a rule delivers matching events to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetKind {
+    /// A message queue (SQS queue, Storage queue).
+    Queue,
+    /// A serverless function (Lambda, Azure Functions, Cloud Functions).
+    Function,
+    /// An HTTPS endpoint.
+    Webhook,
+}
+
 /// Event target for a rule.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventTarget {
     /// Target ID.
     pub id: String,
-    /// Target ARN (e.g., Lambda function ARN, SQS queue ARN).
+    /// Target ARN or resource ID (e.g., Lambda function ARN, SQS queue ARN), or the URL of a webhook.
     pub arn: String,
+    /// Kind of target, when known.
+    pub kind: Option<TargetKind>,
     /// Input transformer template.
     pub input_template: Option<String>,
     /// Input path mappings.
@@ -155,10 +198,26 @@ impl EventTarget {
         Self {
             id: id.into(),
             arn: arn.into(),
+            kind: None,
             input_template: None,
             input_paths: HashMap::new(),
         }
     }
+
+    /// Create a target delivering to a queue.
+    pub fn queue(id: impl Into<String>, arn: impl Into<String>) -> Self {
+        Self { kind: Some(TargetKind::Queue), ..Self::new(id, arn) }
+    }
+
+    /// Create a target invoking a function.
+    pub fn function(id: impl Into<String>, arn: impl Into<String>) -> Self {
+        Self { kind: Some(TargetKind::Function), ..Self::new(id, arn) }
+    }
+
+    /// Create a target posting to a webhook URL.
+    pub fn webhook(id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self { kind: Some(TargetKind::Webhook), ..Self::new(id, url) }
+    }
 }
 
 /// Event bus operations.
@@ -183,6 +242,17 @@ pub trait EventBus: Send + Sync {
     /// Create or update an event rule.
     async fn put_rule(&self, bus_name: &str, rule: EventRule) -> CloudResult<String>;
 
+    /// Create or update a pattern rule together with the targets it delivers to.
+    ///
+    /// Event Grid subscriptions and Eventarc triggers need their destination when
+    /// they are created, so this is the portable way to subscribe to events.
+    async fn create_rule(
+        &self,
+        bus_name: &str,
+        rule: EventRule,
+        targets: Vec<EventTarget>,
+    ) -> CloudResult<String>;
+
     /// Delete a rule.
     async fn delete_rule(&self, bus_name: &str, rule_name: &str) -> CloudResult<()>;
 
@@ -219,3 +289,36 @@ pub trait EventBus: Send + Sync {
     ) -> CloudResult<Vec<EventTarget>>;
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_match_filters() {
+        let rule = EventRule::pattern("orders", json!({
+            "source": ["myapp.orders"],
+            "detail-type": ["OrderCreated", "OrderShipped"],
+        }));
+        let filters = rule.match_filters().unwrap();
+        assert!(filters.contains(&("source".to_string(), vec!["myapp.orders".to_string()])));
+        assert!(filters.contains(&(
+            "detail-type".to_string(),
+            vec!["OrderCreated".to_string(), "OrderShipped".to_string()],
+        )));
+
+        let nested = EventRule::pattern("big", json!({"detail": {"amount": [{"numeric": [">", 100]}]}}));
+        assert!(matches!(nested.match_filters(), Err(CloudError::Validation(_))));
+        assert!(EventRule::pattern("empty", json!({})).match_filters().is_err());
+        assert!(EventRule::schedule("nightly", "rate(1 day)").match_filters().is_err());
+    }
+
+    #[test]
+    fn test_target_kinds() {
+        assert_eq!(EventTarget::queue("q", "arn:aws:sqs:us-east-1:1:jobs").kind, Some(TargetKind::Queue));
+        assert_eq!(EventTarget::function("f", "arn:aws:lambda:us-east-1:1:function:f").kind, Some(TargetKind::Function));
+        assert_eq!(EventTarget::webhook("w", "https://example.com/hook").kind, Some(TargetKind::Webhook));
+        assert_eq!(EventTarget::new("t", "arn").kind, None);
+    }
+}
@@ -193,6 +193,35 @@ pub trait IdentityProvider: Send + Sync {
     /// Search users by attribute.
     async fn search_users(&self, filter: &str) -> CloudResult<Vec<User>>;
 
+    /// Set a user's password as an administrator.
+    ///
+    /// A `permanent` password confirms the user; otherwise it must be changed at the next
+    /// sign in, which then answers with a `NewPasswordRequired` challenge.
+    async fn set_password(&self, username: &str, password: &str, permanent: bool) -> CloudResult<()>;
+
+    /// Create a user with a permanent password, member of `groups`.
+    ///
+    /// Provisions test and seed users in one call, without an invitation or password change.
+    async fn provision_user(
+        &self,
+        username: &str,
+        email: Option<&str>,
+        password: &str,
+        groups: &[&str],
+    ) -> CloudResult<User> {
+        let options = CreateUserOptions {
+            temporary_password: Some(password.to_string()),
+            email_verified: email.is_some(),
+            ..Default::default()
+        };
+        let user = self.create_user(username, email, options).await?;
+        self.set_password(username, password, true).await?;
+        for group in groups {
+            self.add_user_to_group(username, group).await?;
+        }
+        Ok(user)
+    }
+
     // --- Authentication ---
 
     /// Initiate authentication with username/password.
@@ -202,6 +231,16 @@ pub trait IdentityProvider: Send + Sync {
         password: &str,
     ) -> CloudResult<InitiateAuthResult>;
 
+    /// Authenticate a user with username/password as an administrator.
+    ///
+    /// Runs server side with the provider credentials, so no client secret, SRP exchange or
+    /// browser flow is involved.
+    async fn admin_initiate_auth(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> CloudResult<InitiateAuthResult>;
+
     /// Respond to an auth challenge.
     async fn respond_to_challenge(
         &self,
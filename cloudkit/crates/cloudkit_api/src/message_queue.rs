@@ -26,10 +26,25 @@ pub struct Message {
 }
 
 /// Options for sending messages.
+///
+/// A message can be held back for a `delay` or until a `scheduled_at` time,
+/// which takes precedence:
+///
+/// | Provider | Mapping | Longest hold |
+/// |----------|---------|--------------|
+/// | AWS SQS | `DelaySeconds` | 15 minutes |
+/// | Azure Service Bus | `ScheduledEnqueueTimeUtc` | None |
+/// | GCP Pub/Sub | Not supported | - |
+/// | ZeroCloud | `delay_seconds` | 15 minutes |
+///
+/// Providers reject holds they cannot honor with a `NotSupported` provider
+/// error rather than delivering the message early.
 #[derive(Debug, Clone, Default)]
 pub struct SendOptions {
     /// Delay before message becomes visible
     pub delay: Option<Duration>,
+    /// Time at which the message becomes visible
+    pub scheduled_at: Option<DateTime<Utc>>,
     /// Message group ID (for FIFO queues)
     pub message_group_id: Option<String>,
     /// Deduplication ID (for FIFO queues)
@@ -50,6 +65,30 @@ impl SendOptions {
         self
     }
 
+    /// Make the message visible at `time`.
+    pub fn schedule_at(mut self, time: DateTime<Utc>) -> Self {
+        self.scheduled_at = Some(time);
+        self
+    }
+
+    /// How long after `now` the message stays hidden, if at all. Scheduled
+    /// times in the past make it visible at once.
+    pub fn enqueue_delay(&self, now: DateTime<Utc>) -> Option<Duration> {
+        match self.scheduled_at {
+            Some(time) => Some((time - now).to_std().unwrap_or(Duration::ZERO)),
+            None => self.delay,
+        }
+    }
+
+    /// When the message becomes visible if sent at `now`, if it is held back.
+    pub fn enqueue_time(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match (self.scheduled_at, self.delay) {
+            (Some(time), _) => Some(time),
+            (None, Some(delay)) => chrono::Duration::from_std(delay).ok().map(|delay| now + delay),
+            (None, None) => None,
+        }
+    }
+
     /// Set message group ID.
     pub fn message_group_id(mut self, id: impl Into<String>) -> Self {
         self.message_group_id = Some(id.into());
@@ -99,6 +138,32 @@ impl ReceiveOptions {
     }
 }
 
+/// Dead-letter queue of a queue.
+///
+/// | Provider | Mapping |
+/// |----------|---------|
+/// | AWS SQS | `RedrivePolicy` with the DLQ's ARN and `maxReceiveCount` |
+/// | Azure Service Bus | `MaxDeliveryCount` and `ForwardDeadLetteredMessagesTo` |
+/// | GCP Pub/Sub | Subscription dead-letter topic and maximum delivery attempts |
+/// | ZeroCloud | Queue redrive policy |
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetterConfig {
+    /// URL of the queue that receives the failed messages
+    pub dead_letter_queue: String,
+    /// Receives after which a message that was not deleted is dead-lettered
+    pub max_receive_count: u32,
+}
+
+impl DeadLetterConfig {
+    /// Dead-letter messages to `dead_letter_queue` after `max_receive_count` receives.
+    pub fn new(dead_letter_queue: impl Into<String>, max_receive_count: u32) -> Self {
+        Self {
+            dead_letter_queue: dead_letter_queue.into(),
+            max_receive_count,
+        }
+    }
+}
+
 /// Message queue service trait.
 ///
 /// This trait abstracts queue operations across cloud providers:
@@ -193,6 +258,29 @@ pub trait MessageQueue: Send + Sync {
 
     /// Purge all messages from queue.
     async fn purge(&self, queue_url: &str) -> CloudResult<()>;
+
+    // =========================================================================
+    // Dead-Letter Queues
+    // =========================================================================
+
+    /// Set the dead-letter queue of a queue, or stop dead-lettering its
+    /// messages with `None`. The dead-letter queue must already exist.
+    async fn configure_dlq(
+        &self,
+        queue_url: &str,
+        config: Option<DeadLetterConfig>,
+    ) -> CloudResult<()>;
+
+    /// Drain messages from the dead-letter queue of a queue.
+    ///
+    /// The messages are received and deleted from the dead-letter queue, so
+    /// each one is returned once. Fails with `NotFound` when the queue has no
+    /// dead-letter queue.
+    async fn read_dlq(
+        &self,
+        queue_url: &str,
+        options: ReceiveOptions,
+    ) -> CloudResult<Vec<Message>>;
 }
 
 #[cfg(test)]
@@ -211,6 +299,30 @@ mod tests {
         assert_eq!(options.attributes.get("key"), Some(&"value".to_string()));
     }
 
+    #[test]
+    fn test_send_options_schedule() {
+        let now = Utc::now();
+        let delayed = SendOptions::new().delay(Duration::from_secs(60));
+        assert_eq!(delayed.enqueue_delay(now), Some(Duration::from_secs(60)));
+        assert_eq!(delayed.enqueue_time(now), Some(now + chrono::Duration::seconds(60)));
+
+        let scheduled = delayed.schedule_at(now + chrono::Duration::minutes(5));
+        assert_eq!(scheduled.enqueue_delay(now), Some(Duration::from_secs(300)));
+        assert_eq!(scheduled.enqueue_time(now), Some(now + chrono::Duration::minutes(5)));
+
+        let past = SendOptions::new().schedule_at(now - chrono::Duration::minutes(1));
+        assert_eq!(past.enqueue_delay(now), Some(Duration::ZERO));
+        assert_eq!(SendOptions::new().enqueue_delay(now), None);
+        assert_eq!(SendOptions::new().enqueue_time(now), None);
+    }
+
+    #[test]
+    fn test_dead_letter_config() {
+        let config = DeadLetterConfig::new("orders-dlq", 5);
+        assert_eq!(config.dead_letter_queue, "orders-dlq");
+        assert_eq!(config.max_receive_count, 5);
+    }
+
     #[test]
     fn test_receive_options_builder() {
         let options = ReceiveOptions::new()
@@ -9,10 +9,12 @@
 //! - **GCP**: Cloud Monitoring + Cloud Logging
 
 use async_trait::async_trait;
-use cloudkit_spi::CloudResult;
+use cloudkit_spi::{CloudError, CloudResult};
 use chrono::{DateTime, Utc};
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::pin::Pin;
 
 /// A single metric data point.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,6 +174,123 @@ pub enum MetricStatistic {
     SampleCount,
 }
 
+impl MetricStatistic {
+    /// Parse a statistic as any provider names it, e.g. `Average`, `avg`, `mean` or
+    /// `ALIGN_MEAN`; Azure's `Total` and `Count` are `Sum` and `SampleCount`.
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = name.strip_prefix("align_").unwrap_or(&name);
+        match name {
+            "average" | "avg" | "mean" => Some(Self::Average),
+            "sum" | "total" => Some(Self::Sum),
+            "minimum" | "min" => Some(Self::Minimum),
+            "maximum" | "max" => Some(Self::Maximum),
+            "samplecount" | "sample_count" | "count" => Some(Self::SampleCount),
+            _ => None,
+        }
+    }
+
+    /// Name of the statistic in CloudKit, as CloudWatch spells it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Average => "Average",
+            Self::Sum => "Sum",
+            Self::Minimum => "Minimum",
+            Self::Maximum => "Maximum",
+            Self::SampleCount => "SampleCount",
+        }
+    }
+}
+
+/// Time range of a metric query, start inclusive and end exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    /// Start time.
+    pub start: DateTime<Utc>,
+    /// End time.
+    pub end: DateTime<Utc>,
+}
+
+impl TimeRange {
+    /// Create a time range.
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self { start, end }
+    }
+
+    /// The range ending now that spans `duration`.
+    pub fn last(duration: chrono::Duration) -> Self {
+        let end = Utc::now();
+        Self { start: end - duration, end }
+    }
+}
+
+/// Query for the data points of one metric over a time range, aggregated per period.
+///
+/// Dimensions are exact-match filters. Data points from every series that matches
+/// them are combined with the statistic, so the result is one series whatever
+/// other dimensions the metric was published with.
+#[derive(Debug, Clone)]
+pub struct MetricRangeQuery {
+    /// Metric name.
+    pub name: String,
+    /// Time range.
+    pub range: TimeRange,
+    /// Length of each aggregation period in seconds.
+    pub period_seconds: u32,
+    /// Statistic combining the samples of a period.
+    pub statistic: MetricStatistic,
+    /// Dimension filters.
+    pub dimensions: HashMap<String, String>,
+}
+
+impl MetricRangeQuery {
+    /// Default aggregation period, in seconds.
+    pub const DEFAULT_PERIOD_SECONDS: u32 = 60;
+
+    /// Query the average of a metric per minute over a time range.
+    pub fn new(name: impl Into<String>, range: TimeRange) -> Self {
+        Self {
+            name: name.into(),
+            range,
+            period_seconds: Self::DEFAULT_PERIOD_SECONDS,
+            statistic: MetricStatistic::Average,
+            dimensions: HashMap::new(),
+        }
+    }
+
+    /// Set the aggregation period in seconds.
+    pub fn period(mut self, seconds: u32) -> Self {
+        self.period_seconds = seconds;
+        self
+    }
+
+    /// Set the statistic.
+    pub fn statistic(mut self, statistic: MetricStatistic) -> Self {
+        self.statistic = statistic;
+        self
+    }
+
+    /// Add a dimension filter.
+    pub fn dimension(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.dimensions.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check the query before it is sent to a provider.
+    pub fn validate(&self) -> CloudResult<()> {
+        if self.name.is_empty() {
+            return Err(CloudError::Validation("metric name is required".to_string()));
+        }
+        if self.range.start >= self.range.end {
+            return Err(CloudError::Validation("time range must start before it ends".to_string()));
+        }
+        if self.period_seconds == 0 {
+            return Err(CloudError::Validation("period must be at least one second".to_string()));
+        }
+        Ok(())
+    }
+}
+
 /// Result of a metric query.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricResult {
@@ -192,6 +311,153 @@ pub struct MetricDataPoint {
     pub unit: MetricUnit,
 }
 
+/// Query for the historical events of a log group.
+#[derive(Debug, Clone)]
+pub struct LogQuery {
+    /// Log group.
+    pub group: String,
+    /// Streams to read; all streams of the group when empty.
+    pub streams: Vec<String>,
+    /// Text the message must contain.
+    pub contains: Option<String>,
+    /// Time range.
+    pub range: TimeRange,
+    /// Maximum number of events to return.
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    /// Query every event of a log group in a time range.
+    pub fn new(group: impl Into<String>, range: TimeRange) -> Self {
+        Self { group: group.into(), streams: Vec::new(), contains: None, range, limit: None }
+    }
+
+    /// Only read a stream; call again to read several.
+    pub fn stream(mut self, stream: impl Into<String>) -> Self {
+        self.streams.push(stream.into());
+        self
+    }
+
+    /// Only return events whose message contains `text`.
+    pub fn contains(mut self, text: impl Into<String>) -> Self {
+        self.contains = Some(text.into());
+        self
+    }
+
+    /// Return at most `limit` events, the oldest ones.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Check the query before it is sent to a provider.
+    pub fn validate(&self) -> CloudResult<()> {
+        if self.group.is_empty() {
+            return Err(CloudError::Validation("log group is required".to_string()));
+        }
+        if self.range.start >= self.range.end {
+            return Err(CloudError::Validation("time range must start before it ends".to_string()));
+        }
+        if self.limit == Some(0) {
+            return Err(CloudError::Validation("limit must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Live events of a log group, in the order they were written. The stream ends after
+/// the first error.
+pub type LogStream = Pin<Box<dyn Stream<Item = CloudResult<LogEvent>> + Send>>;
+
+/// What a log tail follows.
+#[derive(Debug, Clone)]
+pub struct LogTail {
+    /// Log group.
+    pub group: String,
+    /// Streams to follow; all streams of the group when empty.
+    pub streams: Vec<String>,
+    /// Text the message must contain.
+    pub contains: Option<String>,
+    /// Events written at or after this time are returned.
+    pub since: DateTime<Utc>,
+    /// How long to wait between reads of providers without a push API.
+    pub poll_interval: std::time::Duration,
+}
+
+impl LogTail {
+    /// Default wait between reads.
+    pub const DEFAULT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+    /// Follow a log group from now on.
+    pub fn new(group: impl Into<String>) -> Self {
+        Self {
+            group: group.into(),
+            streams: Vec::new(),
+            contains: None,
+            since: Utc::now(),
+            poll_interval: Self::DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Only follow a stream; call again to follow several.
+    pub fn stream(mut self, stream: impl Into<String>) -> Self {
+        self.streams.push(stream.into());
+        self
+    }
+
+    /// Only return events whose message contains `text`.
+    pub fn contains(mut self, text: impl Into<String>) -> Self {
+        self.contains = Some(text.into());
+        self
+    }
+
+    /// Start with the events written since `time`, e.g. to catch up after a restart.
+    pub fn since(mut self, time: DateTime<Utc>) -> Self {
+        self.since = time;
+        self
+    }
+
+    /// Set the wait between reads.
+    pub fn poll_every(mut self, interval: std::time::Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+}
+
+/// How far a polling log tail has read. Each read starts at the newest timestamp seen so
+/// far, so events written in the same millisecond are not missed, and the cursor drops
+/// the ones already returned.
+#[derive(Debug, Clone)]
+pub struct LogCursor {
+    since: DateTime<Utc>,
+    seen: HashSet<String>,
+}
+
+impl LogCursor {
+    /// Start at `since`.
+    pub fn new(since: DateTime<Utc>) -> Self {
+        Self { since, seen: HashSet::new() }
+    }
+
+    /// Time the next read starts at.
+    pub fn since(&self) -> DateTime<Utc> {
+        self.since
+    }
+
+    /// Whether an event was not returned yet, recording it if so. Offer events in time order.
+    pub fn advance(&mut self, id: &str, timestamp: DateTime<Utc>) -> bool {
+        if timestamp < self.since || self.seen.contains(id) {
+            return false;
+        }
+        if timestamp > self.since {
+            self.since = timestamp;
+            self.seen.clear();
+        }
+        self.seen.insert(id.to_string());
+        true
+    }
+}
+
 /// Alarm state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AlarmState {
@@ -252,6 +518,14 @@ pub trait MetricsService: Send + Sync {
         query: MetricQuery,
     ) -> CloudResult<MetricResult>;
 
+    /// Query the data points of a metric over a time range, one per period and in
+    /// time order. Providers round the period up to the nearest one they support.
+    async fn query_metrics(
+        &self,
+        namespace: &str,
+        query: MetricRangeQuery,
+    ) -> CloudResult<MetricResult>;
+
     /// List available metrics.
     async fn list_metrics(&self, namespace: Option<&str>) -> CloudResult<Vec<String>>;
 
@@ -294,6 +568,13 @@ pub trait LoggingService: Send + Sync {
         end_time: DateTime<Utc>,
     ) -> CloudResult<Vec<LogEvent>>;
 
+    /// Search the events of a log group in a time range, oldest first. Each event has
+    /// the stream it was written to in its `stream` field.
+    async fn search_logs(&self, query: LogQuery) -> CloudResult<Vec<LogEvent>>;
+
+    /// Follow a log group, yielding events as they are written until the stream is dropped.
+    async fn tail_logs(&self, tail: LogTail) -> CloudResult<LogStream>;
+
     /// List log groups.
     async fn list_log_groups(&self) -> CloudResult<Vec<String>>;
 
@@ -301,3 +582,59 @@ pub trait LoggingService: Send + Sync {
     async fn list_log_streams(&self, group: &str) -> CloudResult<Vec<String>>;
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statistic_names() {
+        assert_eq!(MetricStatistic::parse("Average"), Some(MetricStatistic::Average));
+        assert_eq!(MetricStatistic::parse("ALIGN_MEAN"), Some(MetricStatistic::Average));
+        assert_eq!(MetricStatistic::parse("Total"), Some(MetricStatistic::Sum));
+        assert_eq!(MetricStatistic::parse("count"), Some(MetricStatistic::SampleCount));
+        assert_eq!(MetricStatistic::parse("p99"), None);
+        assert_eq!(MetricStatistic::parse(MetricStatistic::Maximum.name()), Some(MetricStatistic::Maximum));
+    }
+
+    #[test]
+    fn test_metric_range_query_validation() {
+        let range = TimeRange::last(chrono::Duration::hours(1));
+        let query = MetricRangeQuery::new("CPUUtilization", range)
+            .period(300)
+            .statistic(MetricStatistic::Maximum)
+            .dimension("InstanceId", "i-123");
+        assert!(query.validate().is_ok());
+        assert_eq!(query.dimensions["InstanceId"], "i-123");
+
+        assert!(query.clone().period(0).validate().is_err());
+        let backwards = TimeRange::new(range.end, range.start);
+        assert!(MetricRangeQuery::new("CPUUtilization", backwards).validate().is_err());
+        assert!(MetricRangeQuery::new("", range).validate().is_err());
+    }
+
+    #[test]
+    fn test_log_query_validation() {
+        let range = TimeRange::last(chrono::Duration::minutes(15));
+        let query = LogQuery::new("app", range).stream("web-1").stream("web-2").contains("timeout").limit(100);
+        assert!(query.validate().is_ok());
+        assert_eq!(query.streams, ["web-1", "web-2"]);
+        assert!(query.clone().limit(0).validate().is_err());
+        assert!(LogQuery::new("", range).validate().is_err());
+    }
+
+    #[test]
+    fn test_log_cursor_skips_returned_events() {
+        let start = Utc::now();
+        let later = start + chrono::Duration::milliseconds(5);
+        let mut cursor = LogCursor::new(start);
+        assert!(!cursor.advance("old", start - chrono::Duration::seconds(1)));
+        assert!(cursor.advance("a", start));
+        assert!(cursor.advance("b", later));
+        assert_eq!(cursor.since(), later);
+
+        // The next read starts at `later` and sees `b` again, plus `c` from the same millisecond
+        assert!(!cursor.advance("b", later));
+        assert!(cursor.advance("c", later));
+        assert_eq!(cursor.since(), later);
+    }
+}
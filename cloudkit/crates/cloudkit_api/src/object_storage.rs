@@ -1,9 +1,82 @@
 //! Object storage trait for blob/object storage operations.
 
-use cloudkit_spi::{BucketMetadata, CloudResult, ListResult, ObjectMetadata};
+use cloudkit_spi::{BucketMetadata, CloudError, CloudResult, ListResult, ObjectMetadata};
 use async_trait::async_trait;
 use bytes::Bytes;
 
+/// Server-side encryption of an object.
+///
+/// | Option | AWS S3 | Azure Blob | GCS |
+/// |--------|--------|------------|-----|
+/// | `ProviderManaged` | SSE-S3 | Microsoft-managed keys | Google-managed keys |
+/// | `CustomerManaged` | SSE-KMS | Encryption scope | Cloud KMS key |
+/// | `CustomerProvided` | SSE-C | Customer-provided key | Customer-supplied key |
+///
+/// `Debug` never prints a customer-provided key.
+#[derive(Clone, PartialEq, Eq)]
+pub enum EncryptionOptions {
+    /// Encrypt with a key the provider manages.
+    ProviderManaged,
+    /// Encrypt with a key held in the provider's key management service.
+    CustomerManaged {
+        /// Key ID or ARN (AWS), encryption scope (Azure) or key resource name (GCP)
+        key_id: String,
+    },
+    /// Encrypt with an AES-256 key sent with the request. The provider does
+    /// not keep the key, so reading or copying the object needs it again.
+    CustomerProvided {
+        /// The 32 bytes of the key
+        key: Vec<u8>,
+    },
+}
+
+impl EncryptionOptions {
+    /// Length of a customer-provided key in bytes.
+    pub const CUSTOMER_KEY_LEN: usize = 32;
+
+    /// Encrypt with a key held in the provider's key management service.
+    pub fn customer_managed(key_id: impl Into<String>) -> Self {
+        Self::CustomerManaged { key_id: key_id.into() }
+    }
+
+    /// Encrypt with an AES-256 key sent with the request.
+    pub fn customer_provided(key: impl Into<Vec<u8>>) -> CloudResult<Self> {
+        let key = key.into();
+        if key.len() != Self::CUSTOMER_KEY_LEN {
+            return Err(CloudError::Validation(format!(
+                "Customer-provided key must be {} bytes, got {}",
+                Self::CUSTOMER_KEY_LEN,
+                key.len()
+            )));
+        }
+        Ok(Self::CustomerProvided { key })
+    }
+
+    /// The key to send with the request, for a customer-provided key.
+    pub fn customer_key(&self) -> Option<&[u8]> {
+        match self {
+            Self::CustomerProvided { key } => Some(key),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Debug for EncryptionOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ProviderManaged => write!(f, "ProviderManaged"),
+            Self::CustomerManaged { key_id } => f
+                .debug_struct("CustomerManaged")
+                .field("key_id", key_id)
+                .finish(),
+            Self::CustomerProvided { .. } => f
+                .debug_struct("CustomerProvided")
+                .field("key", &"<redacted>")
+                .finish(),
+        }
+    }
+}
+
 /// Options for put operations.
 #[derive(Debug, Clone, Default)]
 pub struct PutOptions {
@@ -17,6 +90,10 @@ pub struct PutOptions {
     pub metadata: std::collections::HashMap<String, String>,
     /// Storage class (provider-specific)
     pub storage_class: Option<String>,
+    /// Object tags
+    pub tags: std::collections::HashMap<String, String>,
+    /// Server-side encryption; the bucket default when unset
+    pub encryption: Option<EncryptionOptions>,
 }
 
 impl PutOptions {
@@ -42,6 +119,18 @@ impl PutOptions {
         self.metadata.insert(key.into(), value.into());
         self
     }
+
+    /// Add an object tag.
+    pub fn tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set server-side encryption.
+    pub fn encryption(mut self, encryption: EncryptionOptions) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
 }
 
 /// Options for get operations.
@@ -55,6 +144,9 @@ pub struct GetOptions {
     pub if_match: Option<String>,
     /// If-none-match ETag
     pub if_none_match: Option<String>,
+    /// Encryption the object was written with; only a customer-provided key
+    /// has to be sent to read it
+    pub encryption: Option<EncryptionOptions>,
 }
 
 impl GetOptions {
@@ -69,6 +161,42 @@ impl GetOptions {
         self.range_end = Some(end);
         self
     }
+
+    /// Set the encryption the object was written with.
+    pub fn encryption(mut self, encryption: EncryptionOptions) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+}
+
+/// Options for copy operations.
+#[derive(Debug, Clone, Default)]
+pub struct CopyOptions {
+    /// Encryption the source object was written with; only a
+    /// customer-provided key has to be sent to read it
+    pub source_encryption: Option<EncryptionOptions>,
+    /// Server-side encryption of the copy; the destination bucket default
+    /// when unset
+    pub encryption: Option<EncryptionOptions>,
+}
+
+impl CopyOptions {
+    /// Create new copy options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the encryption the source object was written with.
+    pub fn source_encryption(mut self, encryption: EncryptionOptions) -> Self {
+        self.source_encryption = Some(encryption);
+        self
+    }
+
+    /// Set server-side encryption of the copy.
+    pub fn encryption(mut self, encryption: EncryptionOptions) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
 }
 
 /// Options for list operations.
@@ -115,6 +243,120 @@ impl ListOptions {
     }
 }
 
+/// Cheaper storage tier objects move to as they age.
+///
+/// | Tier | AWS S3 | Azure Blob | GCS |
+/// |------|--------|------------|-----|
+/// | `InfrequentAccess` | `STANDARD_IA` | Cool | `NEARLINE` |
+/// | `Archive` | `GLACIER` | Cold | `COLDLINE` |
+/// | `DeepArchive` | `DEEP_ARCHIVE` | Archive | `ARCHIVE` |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageTier {
+    /// Read rarely but still at once.
+    InfrequentAccess,
+    /// Read a few times a year.
+    Archive,
+    /// Kept for retention only; reads may take hours.
+    DeepArchive,
+}
+
+/// Move of objects to another tier some days after they were created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LifecycleTransition {
+    /// Age of the objects in days
+    pub after_days: u32,
+    /// Tier they move to
+    pub tier: StorageTier,
+}
+
+/// Rule of a bucket lifecycle configuration.
+///
+/// A rule applies to the objects whose key starts with its prefix, or to
+/// every object of the bucket without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleRule {
+    /// Rule ID, unique within the bucket
+    pub id: String,
+    /// Key prefix of the objects the rule applies to
+    pub prefix: Option<String>,
+    /// Disabled rules are kept but not applied
+    pub enabled: bool,
+    /// Age in days after which objects are deleted
+    pub expiration_days: Option<u32>,
+    /// Tier transitions, by ascending age
+    pub transitions: Vec<LifecycleTransition>,
+}
+
+impl LifecycleRule {
+    /// Create an enabled rule applying to every object, with no action yet.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            prefix: None,
+            enabled: true,
+            expiration_days: None,
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Apply the rule only to keys starting with `prefix`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Enable or disable the rule.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Delete objects `days` days after they were created.
+    pub fn expire_after_days(mut self, days: u32) -> Self {
+        self.expiration_days = Some(days);
+        self
+    }
+
+    /// Move objects to `tier` `days` days after they were created.
+    pub fn transition(mut self, days: u32, tier: StorageTier) -> Self {
+        self.transitions.push(LifecycleTransition { after_days: days, tier });
+        self
+    }
+
+    /// Check that the rule has an action, that its transitions are by
+    /// ascending age and that objects expire after their last transition.
+    pub fn validate(&self) -> CloudResult<()> {
+        let invalid = |reason: &str| {
+            Err(CloudError::Validation(format!(
+                "Lifecycle rule '{}' {}",
+                self.id, reason
+            )))
+        };
+        if self.id.is_empty() {
+            return Err(CloudError::Validation("Lifecycle rule ID must not be empty".into()));
+        }
+        if self.expiration_days.is_none() && self.transitions.is_empty() {
+            return invalid("has neither an expiration nor a transition");
+        }
+        if self.expiration_days == Some(0) {
+            return invalid("must expire objects after at least one day");
+        }
+        if self
+            .transitions
+            .windows(2)
+            .any(|pair| pair[0].after_days >= pair[1].after_days)
+        {
+            return invalid("must list its transitions by ascending age");
+        }
+        if let (Some(expiration), Some(last)) = (self.expiration_days, self.transitions.last()) {
+            if expiration <= last.after_days {
+                return invalid("must expire objects after their last transition");
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Object storage service trait.
 ///
 /// This trait abstracts blob/object storage operations across cloud providers:
@@ -187,7 +429,9 @@ pub trait ObjectStorage: Send + Sync {
     /// Delete multiple objects.
     async fn delete_objects(&self, bucket: &str, keys: &[&str]) -> CloudResult<()>;
 
-    /// Copy an object.
+    /// Copy an object within a bucket or to another bucket.
+    ///
+    /// The provider copies the data itself; nothing is downloaded.
     async fn copy_object(
         &self,
         source_bucket: &str,
@@ -196,6 +440,28 @@ pub trait ObjectStorage: Send + Sync {
         dest_key: &str,
     ) -> CloudResult<()>;
 
+    /// Copy an object with options.
+    async fn copy_object_with_options(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        options: CopyOptions,
+    ) -> CloudResult<()>;
+
+    /// Move an object within a bucket or to another bucket.
+    ///
+    /// The object is copied server-side, then the source is deleted. Moving an
+    /// object onto itself does nothing.
+    async fn move_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> CloudResult<()>;
+
     /// Check if an object exists.
     async fn object_exists(&self, bucket: &str, key: &str) -> CloudResult<bool>;
 
@@ -206,6 +472,54 @@ pub trait ObjectStorage: Send + Sync {
         options: ListOptions,
     ) -> CloudResult<ListResult<ObjectMetadata>>;
 
+    // =========================================================================
+    // Metadata and Tags
+    // =========================================================================
+
+    /// Replace the content type, cache control, content encoding and custom
+    /// metadata of an object without uploading it again.
+    ///
+    /// Read them back with [`head_object`](Self::head_object). The tags and
+    /// storage class of the options are ignored.
+    async fn set_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        options: PutOptions,
+    ) -> CloudResult<()>;
+
+    /// Get the tags of an object.
+    async fn get_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> CloudResult<std::collections::HashMap<String, String>>;
+
+    /// Replace the tags of an object.
+    async fn set_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+        tags: std::collections::HashMap<String, String>,
+    ) -> CloudResult<()>;
+
+    // =========================================================================
+    // Lifecycle
+    // =========================================================================
+
+    /// Get the lifecycle rules of a bucket; empty when it has none.
+    async fn get_bucket_lifecycle(&self, bucket: &str) -> CloudResult<Vec<LifecycleRule>>;
+
+    /// Replace the lifecycle rules of a bucket; an empty list removes them.
+    ///
+    /// Every rule is [validated](LifecycleRule::validate) before the bucket is
+    /// changed.
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket: &str,
+        rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()>;
+
     // =========================================================================
     // Presigned URLs
     // =========================================================================
@@ -236,11 +550,58 @@ mod tests {
         let options = PutOptions::new()
             .content_type("application/json")
             .cache_control("max-age=3600")
-            .metadata("custom", "value");
+            .metadata("custom", "value")
+            .tag("team", "storage");
 
         assert_eq!(options.content_type, Some("application/json".to_string()));
         assert_eq!(options.cache_control, Some("max-age=3600".to_string()));
         assert_eq!(options.metadata.get("custom"), Some(&"value".to_string()));
+        assert_eq!(options.tags.get("team"), Some(&"storage".to_string()));
+    }
+
+    #[test]
+    fn test_encryption_options() {
+        let kms = EncryptionOptions::customer_managed("alias/app");
+        let options = PutOptions::new().encryption(kms.clone());
+        assert_eq!(options.encryption, Some(kms.clone()));
+        assert_eq!(kms.customer_key(), None);
+
+        assert!(EncryptionOptions::customer_provided(vec![7u8; 16]).is_err());
+        let customer = EncryptionOptions::customer_provided([7u8; 32]).unwrap();
+        assert_eq!(customer.customer_key(), Some(&[7u8; 32][..]));
+        assert!(!format!("{:?}", customer).contains('7'));
+
+        let copy = CopyOptions::new()
+            .source_encryption(customer.clone())
+            .encryption(EncryptionOptions::ProviderManaged);
+        assert_eq!(copy.source_encryption, Some(customer));
+        assert_eq!(copy.encryption, Some(EncryptionOptions::ProviderManaged));
+    }
+
+    #[test]
+    fn test_lifecycle_rule_validation() {
+        let rule = LifecycleRule::new("logs")
+            .prefix("logs/")
+            .transition(30, StorageTier::InfrequentAccess)
+            .transition(90, StorageTier::Archive)
+            .expire_after_days(365);
+        assert!(rule.validate().is_ok());
+        assert_eq!(rule.prefix, Some("logs/".to_string()));
+        assert_eq!(rule.transitions[1].tier, StorageTier::Archive);
+
+        assert!(LifecycleRule::new("empty").validate().is_err());
+        assert!(LifecycleRule::new("").expire_after_days(1).validate().is_err());
+        assert!(LifecycleRule::new("now").expire_after_days(0).validate().is_err());
+        assert!(LifecycleRule::new("order")
+            .transition(90, StorageTier::Archive)
+            .transition(30, StorageTier::InfrequentAccess)
+            .validate()
+            .is_err());
+        assert!(LifecycleRule::new("early")
+            .transition(90, StorageTier::Archive)
+            .expire_after_days(60)
+            .validate()
+            .is_err());
     }
 
     #[test]
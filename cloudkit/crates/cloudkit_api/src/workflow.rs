@@ -78,6 +78,13 @@ pub enum ExecutionStatus {
     PendingRedrive,
 }
 
+impl ExecutionStatus {
+    /// Whether the execution has stopped and will not change status again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed | Self::TimedOut | Self::Aborted)
+    }
+}
+
 /// Workflow execution details.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Execution {
@@ -119,19 +126,121 @@ pub struct StartExecutionOptions {
     pub trace_header: Option<String>,
 }
 
+/// Normalized kind of an execution history event.
+///
+/// Step Functions reports an event per state transition, while Logic Apps and GCP
+/// Workflows report each action or step as a record with a start and an end; both
+/// are mapped onto the same start/finish events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionEventKind {
+    /// The execution started.
+    ExecutionStarted,
+    /// The execution completed successfully.
+    ExecutionSucceeded,
+    /// The execution failed.
+    ExecutionFailed,
+    /// The execution timed out.
+    ExecutionTimedOut,
+    /// The execution was stopped.
+    ExecutionAborted,
+    /// A step (state, action) started.
+    StepStarted,
+    /// A step completed successfully.
+    StepSucceeded,
+    /// A step failed.
+    StepFailed,
+    /// Any other provider event, see [`HistoryEvent::event_type`].
+    Other,
+}
+
+impl ExecutionEventKind {
+    /// Status of the execution after this event, for events that end it.
+    pub fn terminal_status(&self) -> Option<ExecutionStatus> {
+        match self {
+            Self::ExecutionSucceeded => Some(ExecutionStatus::Succeeded),
+            Self::ExecutionFailed => Some(ExecutionStatus::Failed),
+            Self::ExecutionTimedOut => Some(ExecutionStatus::TimedOut),
+            Self::ExecutionAborted => Some(ExecutionStatus::Aborted),
+            _ => None,
+        }
+    }
+
+    /// The event ending an execution with the given status.
+    pub fn ending(status: ExecutionStatus) -> Option<Self> {
+        match status {
+            ExecutionStatus::Succeeded => Some(Self::ExecutionSucceeded),
+            ExecutionStatus::Failed => Some(Self::ExecutionFailed),
+            ExecutionStatus::TimedOut => Some(Self::ExecutionTimedOut),
+            ExecutionStatus::Aborted => Some(Self::ExecutionAborted),
+            ExecutionStatus::Running | ExecutionStatus::PendingRedrive => None,
+        }
+    }
+}
+
 /// Execution history event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEvent {
-    /// Event ID.
+    /// Event ID, increasing in the order events happened.
     pub id: u64,
-    /// Event type.
+    /// Normalized event kind.
+    pub kind: ExecutionEventKind,
+    /// Provider event type, e.g. `TaskStateEntered`.
     pub event_type: String,
     /// Timestamp.
     pub timestamp: DateTime<Utc>,
+    /// Step (state, action) the event belongs to.
+    pub step: Option<String>,
+    /// Error info for failed, timed out and aborted events.
+    pub error: Option<ExecutionError>,
     /// Event details.
     pub details: Value,
 }
 
+impl HistoryEvent {
+    /// Create a new event; the ID is set by [`sequence_events`] or the provider.
+    pub fn new(kind: ExecutionEventKind, event_type: impl Into<String>, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            id: 0,
+            kind,
+            event_type: event_type.into(),
+            timestamp,
+            step: None,
+            error: None,
+            details: Value::Null,
+        }
+    }
+
+    /// Set the step.
+    pub fn step(mut self, step: impl Into<String>) -> Self {
+        self.step = Some(step.into());
+        self
+    }
+
+    /// Set the error.
+    pub fn error(mut self, error: ExecutionError) -> Self {
+        self.error = Some(error);
+        self
+    }
+
+    /// Set the details.
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Order events by time and number them from 1.
+///
+/// For providers that report steps as records rather than as an event log. The sort is
+/// stable, so events with the same timestamp keep the order they were built in.
+pub fn sequence_events(mut events: Vec<HistoryEvent>) -> Vec<HistoryEvent> {
+    events.sort_by_key(|event| event.timestamp);
+    for (i, event) in events.iter_mut().enumerate() {
+        event.id = i as u64 + 1;
+    }
+    events
+}
+
 /// Filter for listing executions.
 #[derive(Debug, Clone, Default)]
 pub struct ExecutionFilter {
@@ -171,7 +280,7 @@ pub trait WorkflowService: Send + Sync {
         options: StartExecutionOptions,
     ) -> CloudResult<Execution>;
 
-    /// Stop a running execution.
+    /// Stop a running execution, recording the error and cause where the provider supports them.
     async fn stop_execution(
         &self,
         execution_id: &str,
@@ -182,14 +291,14 @@ pub trait WorkflowService: Send + Sync {
     /// Get execution details.
     async fn describe_execution(&self, execution_id: &str) -> CloudResult<Execution>;
 
-    /// List executions for a workflow.
+    /// List executions for a workflow, most recent first.
     async fn list_executions(
         &self,
         workflow_arn: &str,
         filter: ExecutionFilter,
     ) -> CloudResult<Vec<Execution>>;
 
-    /// Get execution history.
+    /// Get execution history as normalized events, oldest first.
     async fn get_execution_history(
         &self,
         execution_id: &str,
@@ -214,3 +323,43 @@ pub trait WorkflowService: Send + Sync {
     async fn send_task_heartbeat(&self, task_token: &str) -> CloudResult<()>;
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_event_kind_status() {
+        for status in [
+            ExecutionStatus::Succeeded,
+            ExecutionStatus::Failed,
+            ExecutionStatus::TimedOut,
+            ExecutionStatus::Aborted,
+        ] {
+            assert!(status.is_terminal());
+            let kind = ExecutionEventKind::ending(status).unwrap();
+            assert_eq!(kind.terminal_status(), Some(status));
+        }
+        assert!(!ExecutionStatus::Running.is_terminal());
+        assert_eq!(ExecutionEventKind::ending(ExecutionStatus::Running), None);
+        assert_eq!(ExecutionEventKind::StepFailed.terminal_status(), None);
+    }
+
+    #[test]
+    fn test_sequence_events() {
+        let start = Utc::now();
+        let events = sequence_events(vec![
+            HistoryEvent::new(ExecutionEventKind::StepSucceeded, "SUCCEEDED", start + Duration::seconds(2)).step("init"),
+            HistoryEvent::new(ExecutionEventKind::ExecutionStarted, "ACTIVE", start),
+            HistoryEvent::new(ExecutionEventKind::StepStarted, "IN_PROGRESS", start).step("init"),
+        ]);
+        let kinds: Vec<_> = events.iter().map(|e| (e.id, e.kind)).collect();
+        assert_eq!(kinds, vec![
+            (1, ExecutionEventKind::ExecutionStarted),
+            (2, ExecutionEventKind::StepStarted),
+            (3, ExecutionEventKind::StepSucceeded),
+        ]);
+        assert_eq!(events[2].step.as_deref(), Some("init"));
+    }
+}
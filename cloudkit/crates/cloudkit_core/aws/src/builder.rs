@@ -1,6 +1,9 @@
 //! AWS client builder.
 
+use async_trait::async_trait;
 use cloudkit_spi::{CloudConfig, CloudResult, Region};
+use cloudkit_spi::{HealthCheck, HealthCheckOptions, HealthProbes, HealthReport};
+use cloudkit_spi::{Capabilities, CapabilityDiscovery};
 use cloudkit_spi::{CloudContext, ProviderType};
 // use cloudkit_spi::spi::{AuthProvider, MetricsCollector, RetryPolicy};
 use std::sync::Arc;
@@ -184,6 +187,97 @@ impl AwsClient {
     }
 }
 
+/// Probes each enabled service with a single request that lists at most one resource.
+#[async_trait]
+impl HealthCheck for AwsClient {
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport {
+        #[allow(unused_mut)]
+        let mut probes = HealthProbes::new(ProviderType::Aws, options);
+
+        // Probe a service with a request built from a fresh SDK client
+        #[allow(unused_macros)]
+        macro_rules! probe {
+            ($service:literal, $sdk:ident, |$client:ident| $request:expr) => {{
+                let $client = $sdk::Client::new(&self.sdk_config);
+                probes.probe($service, async move {
+                    $request
+                        .send()
+                        .await
+                        .map_err(|e| cloudkit_spi::CloudError::ServiceError(e.to_string()))
+                });
+            }};
+        }
+
+        #[cfg(feature = "s3")]
+        probe!("s3", aws_sdk_s3, |client| client.list_buckets());
+        #[cfg(feature = "dynamodb")]
+        probe!("dynamodb", aws_sdk_dynamodb, |client| client.list_tables().limit(1));
+        #[cfg(feature = "sqs")]
+        probe!("sqs", aws_sdk_sqs, |client| client.list_queues().max_results(1));
+        #[cfg(feature = "sns")]
+        probe!("sns", aws_sdk_sns, |client| client.list_topics());
+        #[cfg(feature = "lambda")]
+        probe!("lambda", aws_sdk_lambda, |client| client.list_functions().max_items(1));
+        #[cfg(feature = "secrets")]
+        probe!("secretsmanager", aws_sdk_secretsmanager, |client| client.list_secrets().max_results(1));
+        #[cfg(feature = "cloudwatch")]
+        probe!("cloudwatch", aws_sdk_cloudwatch, |client| client.describe_alarms().max_records(1));
+        #[cfg(feature = "eventbridge")]
+        probe!("eventbridge", aws_sdk_eventbridge, |client| client.list_event_buses().limit(1));
+        #[cfg(feature = "stepfunctions")]
+        probe!("stepfunctions", aws_sdk_sfn, |client| client.list_state_machines().max_results(1));
+        #[cfg(feature = "cognito")]
+        probe!("cognito", aws_sdk_cognitoidentityprovider, |client| client.list_user_pools().max_results(1));
+        #[cfg(feature = "kms")]
+        probe!("kms", aws_sdk_kms, |client| client.list_keys().limit(1));
+        #[cfg(any(feature = "ec2", feature = "vpc"))]
+        probe!("ec2", aws_sdk_ec2, |client| client.describe_availability_zones());
+
+        probes.run().await
+    }
+}
+
+/// Every enabled service; DynamoDB batch writes are not transactional.
+impl CapabilityDiscovery for AwsClient {
+    fn capabilities(&self) -> Capabilities {
+        #[allow(unused_imports)]
+        use cloudkit_spi::{Feature, Service};
+        #[allow(unused_mut)]
+        let mut capabilities = Capabilities::new(ProviderType::Aws);
+
+        #[cfg(feature = "s3")]
+        capabilities.service(Service::ObjectStorage, &[Feature::Presign, Feature::Lifecycle]);
+        #[cfg(feature = "dynamodb")]
+        capabilities.service(Service::KeyValueStore, &[Feature::ConditionalWrites]);
+        #[cfg(feature = "sqs")]
+        capabilities.service(Service::MessageQueue, &[Feature::Fifo, Feature::DelayedMessages, Feature::DeadLetterQueues]);
+        #[cfg(feature = "sns")]
+        capabilities.service(Service::PubSub, &[]);
+        #[cfg(feature = "lambda")]
+        capabilities.service(Service::Functions, &[]);
+        #[cfg(feature = "secrets")]
+        capabilities.service(Service::SecretsManager, &[]);
+        #[cfg(feature = "kms")]
+        capabilities.service(Service::KeyManagement, &[]);
+        #[cfg(feature = "cloudwatch")]
+        capabilities
+            .service(Service::MetricsService, &[])
+            .service(Service::LoggingService, &[Feature::Streaming]);
+        #[cfg(feature = "eventbridge")]
+        capabilities.service(Service::EventBus, &[]);
+        #[cfg(feature = "stepfunctions")]
+        capabilities.service(Service::WorkflowService, &[]);
+        #[cfg(feature = "cognito")]
+        capabilities.service(Service::IdentityProvider, &[]);
+        #[cfg(feature = "ec2")]
+        capabilities.service(Service::Compute, &[]);
+        #[cfg(feature = "vpc")]
+        capabilities.service(Service::Networking, &[]);
+
+        capabilities
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,5 +294,18 @@ mod tests {
         assert_eq!(client.context().provider(), ProviderType::Aws);
         assert_eq!(client.profile(), Some("default"));
     }
+
+    #[cfg(all(feature = "s3", feature = "sqs"))]
+    #[tokio::test]
+    async fn test_aws_capabilities() {
+        use cloudkit_spi::{Feature, Service};
+        let client = AwsBuilder::new().build().await.unwrap();
+        let capabilities = client.capabilities();
+
+        assert!(capabilities.supports(Service::ObjectStorage));
+        assert!(capabilities.has(Feature::Presign));
+        assert!(capabilities.has(Feature::Fifo));
+        assert!(capabilities.require(Feature::Transactions).is_err());
+    }
 }
 
@@ -3,8 +3,9 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use cloudkit_api::{
-    AlarmConfig, AlarmState, LogEvent, LogLevel, LoggingService, MetricDatum, MetricQuery,
-    MetricResult, MetricsService, MetricStatistic,
+    AlarmConfig, AlarmState, LogCursor, LogEvent, LogLevel, LogQuery, LogStream, LogTail,
+    LoggingService, MetricDataPoint, MetricDatum, MetricQuery, MetricRangeQuery, MetricResult,
+    MetricsService, MetricStatistic, MetricUnit,
 };
 use cloudkit_spi::{CloudResult, CloudError};
 use cloudkit_spi::CloudContext;
@@ -24,6 +25,18 @@ impl CloudWatchMetrics {
     }
 }
 
+/// Period CloudWatch accepts for `seconds`: 1, 5, 10 or 30 seconds for high-resolution
+/// metrics, otherwise a multiple of a minute.
+fn cloudwatch_period(seconds: u32) -> i32 {
+    match seconds {
+        0..=1 => 1,
+        2..=5 => 5,
+        6..=10 => 10,
+        11..=30 => 30,
+        _ => seconds.div_ceil(60) as i32 * 60,
+    }
+}
+
 #[async_trait]
 impl MetricsService for CloudWatchMetrics {
     async fn put_metric_data(&self, namespace: &str, data: Vec<MetricDatum>) -> CloudResult<()> {
@@ -109,6 +122,68 @@ impl MetricsService for CloudWatchMetrics {
         })
     }
 
+    async fn query_metrics(
+        &self,
+        namespace: &str,
+        query: MetricRangeQuery,
+    ) -> CloudResult<MetricResult> {
+        query.validate()?;
+
+        let dimensions = query.dimensions.iter()
+            .map(|(k, v)| aws_sdk_cloudwatch::types::Dimension::builder().name(k).value(v).build())
+            .collect();
+        let metric = aws_sdk_cloudwatch::types::Metric::builder()
+            .namespace(namespace)
+            .metric_name(&query.name)
+            .set_dimensions(Some(dimensions))
+            .build();
+        let data_query = aws_sdk_cloudwatch::types::MetricDataQuery::builder()
+            .id("q1")
+            .metric_stat(
+                aws_sdk_cloudwatch::types::MetricStat::builder()
+                    .metric(metric)
+                    .period(cloudwatch_period(query.period_seconds))
+                    .stat(query.statistic.name())
+                    .build()
+            )
+            .return_data(true)
+            .build();
+
+        let mut data_points = Vec::new();
+        let mut next_token = None;
+        loop {
+            let resp = self.client.get_metric_data()
+                .metric_data_queries(data_query.clone())
+                .start_time(aws_sdk_cloudwatch::primitives::DateTime::from_secs(query.range.start.timestamp()))
+                .end_time(aws_sdk_cloudwatch::primitives::DateTime::from_secs(query.range.end.timestamp()))
+                .scan_by(aws_sdk_cloudwatch::types::ScanBy::TimestampAscending)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+
+            for result in resp.metric_data_results() {
+                for (v, t) in result.values().iter().zip(result.timestamps().iter()) {
+                    data_points.push(MetricDataPoint {
+                        timestamp: DateTime::<Utc>::from_timestamp(t.secs(), 0).unwrap_or_default(),
+                        value: *v,
+                        unit: MetricUnit::None,
+                    });
+                }
+            }
+
+            next_token = resp.next_token().map(str::to_string);
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(MetricResult {
+            name: query.name,
+            data_points,
+        })
+    }
+
     async fn list_metrics(&self, namespace: Option<&str>) -> CloudResult<Vec<String>> {
         let mut req = self.client.list_metrics();
         if let Some(ns) = namespace {
@@ -197,6 +272,58 @@ impl CloudWatchLogs {
     }
 }
 
+/// Events of a log group from `start` on, before `end` if given, with their event IDs and
+/// in time order. FilterLogEvents matches a quoted filter pattern as a phrase.
+async fn filter_log_events(
+    client: &aws_sdk_cloudwatchlogs::Client,
+    group: &str,
+    streams: &[String],
+    contains: Option<&str>,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+) -> CloudResult<Vec<(String, LogEvent)>> {
+    let mut events = Vec::new();
+    let mut next_token = None;
+    loop {
+        let mut req = client.filter_log_events()
+            .log_group_name(group)
+            .start_time(start.timestamp_millis())
+            .set_end_time(end.map(|end| end.timestamp_millis()))
+            .set_next_token(next_token);
+        if !streams.is_empty() {
+            req = req.set_log_stream_names(Some(streams.to_vec()));
+        }
+        if let Some(text) = contains {
+            req = req.filter_pattern(format!("\"{}\"", text));
+        }
+        if let Some(limit) = limit {
+            req = req.limit(limit.saturating_sub(events.len()).min(10_000) as i32);
+        }
+        let resp = req.send().await.map_err(|e| CloudError::ServiceError(e.to_string()))?;
+
+        for event in resp.events() {
+            let millis = event.timestamp().unwrap_or_default();
+            let mut log_event = LogEvent::new(event.message().unwrap_or_default());
+            log_event.timestamp = DateTime::<Utc>::from_timestamp_millis(millis).unwrap_or_default();
+            if let Some(stream) = event.log_stream_name() {
+                log_event.fields.insert("stream".to_string(), stream.to_string());
+            }
+            events.push((event.event_id().unwrap_or_default().to_string(), log_event));
+        }
+
+        next_token = resp.next_token().map(str::to_string);
+        if next_token.is_none() || limit.is_some_and(|limit| events.len() >= limit) {
+            break;
+        }
+    }
+    events.sort_by_key(|(_, event)| event.timestamp);
+    if let Some(limit) = limit {
+        events.truncate(limit);
+    }
+    Ok(events)
+}
+
 #[async_trait]
 impl LoggingService for CloudWatchLogs {
     async fn create_log_group(&self, name: &str) -> CloudResult<()> {
@@ -348,6 +475,47 @@ impl LoggingService for CloudWatchLogs {
         })
     }
 
+    async fn search_logs(&self, query: LogQuery) -> CloudResult<Vec<LogEvent>> {
+        query.validate()?;
+        let events = filter_log_events(
+            &self.client,
+            &query.group,
+            &query.streams,
+            query.contains.as_deref(),
+            query.range.start,
+            Some(query.range.end),
+            query.limit,
+        ).await?;
+        Ok(events.into_iter().map(|(_, event)| event).collect())
+    }
+
+    async fn tail_logs(&self, tail: LogTail) -> CloudResult<LogStream> {
+        let state = (self.client.clone(), LogCursor::new(tail.since), std::collections::VecDeque::new(), false);
+        let stream = futures::stream::try_unfold(state, move |(client, mut cursor, mut pending, mut polled)| {
+            let tail = tail.clone();
+            async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Ok::<_, CloudError>(Some((event, (client, cursor, pending, polled))));
+                    }
+                    if polled {
+                        tokio::time::sleep(tail.poll_interval).await;
+                    }
+                    polled = true;
+                    let events = filter_log_events(
+                        &client, &tail.group, &tail.streams, tail.contains.as_deref(), cursor.since(), None, None,
+                    ).await?;
+                    for (id, event) in events {
+                        if cursor.advance(&id, event.timestamp) {
+                            pending.push_back(event);
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
     async fn list_log_groups(&self) -> CloudResult<Vec<String>> {
         let resp = self.client.describe_log_groups()
             .send()
@@ -418,6 +586,16 @@ mod tests {
 
     // CloudWatch Metrics Tests
 
+    #[test]
+    fn test_cloudwatch_period() {
+        assert_eq!(cloudwatch_period(1), 1);
+        assert_eq!(cloudwatch_period(7), 10);
+        assert_eq!(cloudwatch_period(30), 30);
+        assert_eq!(cloudwatch_period(45), 60);
+        assert_eq!(cloudwatch_period(300), 300);
+        assert_eq!(cloudwatch_period(301), 360);
+    }
+
     #[tokio::test]
     async fn test_cloudwatch_metrics_new() {
         let (context, sdk_config) = create_test_context().await;
@@ -7,6 +7,8 @@ use cloudkit_api::{
 };
 use cloudkit_spi::{CloudError, CloudResult, Metadata};
 use cloudkit_spi::CloudContext;
+use aws_sdk_cognitoidentityprovider::types::{AuthenticationResultType, ChallengeNameType};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// AWS Cognito User Pools implementation.
@@ -81,6 +83,56 @@ impl AwsIdentity {
     }
 }
 
+/// Map a Cognito challenge to the portable challenge type.
+fn challenge_type(name: &ChallengeNameType) -> ChallengeType {
+    match name {
+        ChallengeNameType::SmsMfa => ChallengeType::SmsMfa,
+        ChallengeNameType::SoftwareTokenMfa => ChallengeType::SoftwareTokenMfa,
+        ChallengeNameType::NewPasswordRequired => ChallengeType::NewPasswordRequired,
+        ChallengeNameType::MfaSetup => ChallengeType::MfaSetup,
+        ChallengeNameType::SelectMfaType => ChallengeType::SelectMfaType,
+        other => ChallengeType::Custom(other.as_str().to_string()),
+    }
+}
+
+/// Map a portable challenge type back to the Cognito challenge it answers.
+fn challenge_name(challenge: &ChallengeType) -> ChallengeNameType {
+    match challenge {
+        ChallengeType::SmsMfa => ChallengeNameType::SmsMfa,
+        ChallengeType::SoftwareTokenMfa => ChallengeNameType::SoftwareTokenMfa,
+        ChallengeType::NewPasswordRequired => ChallengeNameType::NewPasswordRequired,
+        ChallengeType::MfaSetup => ChallengeNameType::MfaSetup,
+        ChallengeType::SelectMfaType => ChallengeNameType::SelectMfaType,
+        ChallengeType::Custom(name) => ChallengeNameType::from(name.as_str()),
+    }
+}
+
+/// Outcome of an auth call: the next challenge, or the tokens once none is left.
+fn auth_outcome(
+    challenge: Option<&ChallengeNameType>,
+    session: Option<&str>,
+    parameters: Option<&HashMap<String, String>>,
+    result: Option<&AuthenticationResultType>,
+) -> CloudResult<InitiateAuthResult> {
+    if let Some(challenge) = challenge {
+        return Ok(InitiateAuthResult::Challenge(AuthChallenge {
+            challenge_name: challenge_type(challenge),
+            session: session.unwrap_or_default().to_string(),
+            parameters: parameters.cloned().unwrap_or_default(),
+        }));
+    }
+    let result = result.ok_or_else(|| CloudError::ServiceError(
+        "Cognito returned neither a challenge nor an authentication result".to_string(),
+    ))?;
+    Ok(InitiateAuthResult::Success(AuthResult {
+        access_token: result.access_token().unwrap_or_default().to_string(),
+        id_token: result.id_token().map(|s| s.to_string()),
+        refresh_token: result.refresh_token().map(|s| s.to_string()),
+        token_type: "Bearer".to_string(),
+        expires_in: result.expires_in() as u64,
+    }))
+}
+
 #[async_trait]
 impl IdentityProvider for AwsIdentity {
     async fn create_user(
@@ -198,71 +250,70 @@ impl IdentityProvider for AwsIdentity {
         Ok(resp.users().iter().map(|u| self.map_user(u)).collect())
     }
 
+    async fn set_password(&self, username: &str, password: &str, permanent: bool) -> CloudResult<()> {
+        self.client.admin_set_user_password()
+            .user_pool_id(self.get_user_pool_id()?)
+            .username(username)
+            .password(password)
+            .permanent(permanent)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(())
+    }
+
     async fn initiate_auth(
         &self,
         username: &str,
         password: &str,
+    ) -> CloudResult<InitiateAuthResult> {
+        let resp = self.client.initiate_auth()
+            .client_id(self.get_client_id()?)
+            .auth_flow(aws_sdk_cognitoidentityprovider::types::AuthFlowType::UserPasswordAuth)
+            .auth_parameters("USERNAME", username)
+            .auth_parameters("PASSWORD", password)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+
+        auth_outcome(resp.challenge_name(), resp.session(), resp.challenge_parameters(), resp.authentication_result())
+    }
+
+    async fn admin_initiate_auth(
+        &self,
+        username: &str,
+        password: &str,
     ) -> CloudResult<InitiateAuthResult> {
         let resp = self.client.admin_initiate_auth()
             .user_pool_id(self.get_user_pool_id()?)
             .client_id(self.get_client_id()?)
-            .auth_flow(aws_sdk_cognitoidentityprovider::types::AuthFlowType::AdminNoSrpAuth)
+            .auth_flow(aws_sdk_cognitoidentityprovider::types::AuthFlowType::AdminUserPasswordAuth)
             .auth_parameters("USERNAME", username)
             .auth_parameters("PASSWORD", password)
             .send()
             .await
             .map_err(|e| CloudError::ServiceError(e.to_string()))?;
-            
-        if let Some(challenge_name) = resp.challenge_name() {
-             Ok(InitiateAuthResult::Challenge(AuthChallenge {
-                challenge_name: ChallengeType::Custom(format!("{:?}", challenge_name)),
-                session: resp.session().unwrap_or_default().to_string(),
-                parameters: resp.challenge_parameters().cloned().unwrap_or_default(),
-            }))
-        } else {
-            let auth_result = resp.authentication_result().unwrap();
-            Ok(InitiateAuthResult::Success(AuthResult {
-                access_token: auth_result.access_token().unwrap_or_default().to_string(),
-                id_token: auth_result.id_token().map(|s| s.to_string()),
-                refresh_token: auth_result.refresh_token().map(|s| s.to_string()),
-                token_type: "Bearer".to_string(),
-                expires_in: auth_result.expires_in() as u64,
-            }))
-        }
+
+        auth_outcome(resp.challenge_name(), resp.session(), resp.challenge_parameters(), resp.authentication_result())
     }
 
     async fn respond_to_challenge(
         &self,
-        _challenge_name: ChallengeType,
+        challenge_name: ChallengeType,
         session: &str,
         responses: Metadata,
     ) -> CloudResult<InitiateAuthResult> {
         let resp = self.client.admin_respond_to_auth_challenge()
             .user_pool_id(self.get_user_pool_id()?)
             .client_id(self.get_client_id()?)
-            .challenge_name(aws_sdk_cognitoidentityprovider::types::ChallengeNameType::CustomChallenge) // Mapping needed
+            .challenge_name(self::challenge_name(&challenge_name))
             .session(session)
             .set_challenge_responses(Some(responses))
             .send()
             .await
             .map_err(|e| CloudError::ServiceError(e.to_string()))?;
-            
-        if let Some(challenge_name) = resp.challenge_name() {
-             Ok(InitiateAuthResult::Challenge(AuthChallenge {
-                challenge_name: ChallengeType::Custom(format!("{:?}", challenge_name)),
-                session: resp.session().unwrap_or_default().to_string(),
-                parameters: resp.challenge_parameters().cloned().unwrap_or_default(),
-            }))
-        } else {
-            let auth_result = resp.authentication_result().unwrap();
-            Ok(InitiateAuthResult::Success(AuthResult {
-                access_token: auth_result.access_token().unwrap_or_default().to_string(),
-                id_token: auth_result.id_token().map(|s| s.to_string()),
-                refresh_token: auth_result.refresh_token().map(|s| s.to_string()),
-                token_type: "Bearer".to_string(),
-                expires_in: auth_result.expires_in() as u64,
-            }))
-        }
+
+        auth_outcome(resp.challenge_name(), resp.session(), resp.challenge_parameters(), resp.authentication_result())
     }
 
     async fn refresh_tokens(&self, refresh_token: &str) -> CloudResult<AuthResult> {
@@ -454,5 +505,26 @@ mod tests {
         let context = create_test_context().await;
         let _cognito = AwsIdentity::new(context, sdk_config);
     }
+
+    #[test]
+    fn test_challenge_mapping() {
+        assert_eq!(challenge_type(&ChallengeNameType::NewPasswordRequired), ChallengeType::NewPasswordRequired);
+        assert_eq!(challenge_type(&ChallengeNameType::CustomChallenge), ChallengeType::Custom("CUSTOM_CHALLENGE".to_string()));
+        for challenge in [ChallengeNameType::SmsMfa, ChallengeNameType::NewPasswordRequired, ChallengeNameType::DeviceSrpAuth] {
+            assert_eq!(challenge_name(&challenge_type(&challenge)), challenge);
+        }
+    }
+
+    #[test]
+    fn test_auth_outcome() {
+        let challenge = auth_outcome(Some(&ChallengeNameType::NewPasswordRequired), Some("session"), None, None).unwrap();
+        assert!(matches!(challenge, InitiateAuthResult::Challenge(c) if c.session == "session"));
+
+        let tokens = AuthenticationResultType::builder().access_token("access").expires_in(3600).build();
+        let success = auth_outcome(None, None, None, Some(&tokens)).unwrap();
+        assert!(matches!(success, InitiateAuthResult::Success(r) if r.access_token == "access" && r.expires_in == 3600));
+
+        assert!(auth_outcome(None, None, None, None).is_err());
+    }
 }
 
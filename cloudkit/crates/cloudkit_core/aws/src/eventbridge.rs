@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use cloudkit_api::{
-    Event, EventBus, EventRule, EventTarget, FailedEntry, PutEventsResult, RuleState,
+    Event, EventBus, EventRule, EventTarget, FailedEntry, PutEventsResult, RuleState, TargetKind,
 };
 use cloudkit_spi::{CloudResult, CloudError};
 use cloudkit_spi::CloudContext;
@@ -22,6 +22,37 @@ impl AwsEvents {
     }
 }
 
+/// ARN EventBridge delivers to for a target.
+///
+/// EventBridge only calls HTTPS endpoints through API destinations, which carry
+/// the endpoint's connection and credentials, so webhooks take their ARN.
+fn target_arn(target: &EventTarget) -> CloudResult<&str> {
+    if target.kind == Some(TargetKind::Webhook) && !target.arn.starts_with("arn:") {
+        return Err(CloudError::Provider {
+            provider: "aws".to_string(),
+            code: "NotSupported".to_string(),
+            message: format!(
+                "EventBridge cannot post to {} directly; create an API destination and target its ARN",
+                target.arn
+            ),
+        });
+    }
+    Ok(&target.arn)
+}
+
+/// Kind of target an ARN names.
+fn target_kind(arn: &str) -> Option<TargetKind> {
+    if arn.starts_with("arn:aws:sqs:") {
+        Some(TargetKind::Queue)
+    } else if arn.starts_with("arn:aws:lambda:") {
+        Some(TargetKind::Function)
+    } else if arn.contains(":api-destination/") {
+        Some(TargetKind::Webhook)
+    } else {
+        None
+    }
+}
+
 #[async_trait]
 impl EventBus for AwsEvents {
     async fn put_events(
@@ -114,6 +145,28 @@ impl EventBus for AwsEvents {
         Ok(resp.rule_arn().unwrap_or_default().to_string())
     }
 
+    async fn create_rule(
+        &self,
+        bus_name: &str,
+        rule: EventRule,
+        targets: Vec<EventTarget>,
+    ) -> CloudResult<String> {
+        if rule.event_pattern.is_none() {
+            return Err(CloudError::Validation(format!("rule {} has no event pattern", rule.name)));
+        }
+        // Check the targets before the rule exists, so a bad target leaves nothing behind
+        for target in &targets {
+            target_arn(target)?;
+        }
+
+        let rule_name = rule.name.clone();
+        let arn = self.put_rule(bus_name, rule).await?;
+        if !targets.is_empty() {
+            self.put_targets(bus_name, &rule_name, targets).await?;
+        }
+        Ok(arn)
+    }
+
     async fn delete_rule(&self, bus_name: &str, rule_name: &str) -> CloudResult<()> {
         self.client.delete_rule()
             .event_bus_name(bus_name)
@@ -176,8 +229,8 @@ impl EventBus for AwsEvents {
         let mut aws_targets = Vec::new();
         for t in targets {
             let mut builder = aws_sdk_eventbridge::types::Target::builder()
-                .id(t.id)
-                .arn(t.arn);
+                .arn(target_arn(&t)?)
+                .id(t.id);
                 
             if let Some(template) = t.input_template {
                 builder = builder.input_transformer(
@@ -243,6 +296,7 @@ impl EventBus for AwsEvents {
             EventTarget {
                 id: t.id().to_string(),
                 arn: t.arn().to_string(),
+                kind: target_kind(t.arn()),
                 input_template: template,
                 input_paths: paths,
             }
@@ -270,5 +324,20 @@ mod tests {
         let context = create_test_context().await;
         let _events = AwsEvents::new(context, sdk_config);
     }
+
+    #[test]
+    fn test_target_arns() {
+        let queue = EventTarget::queue("q", "arn:aws:sqs:us-east-1:123456789012:jobs");
+        assert_eq!(target_arn(&queue).unwrap(), queue.arn);
+        assert_eq!(target_kind(&queue.arn), Some(TargetKind::Queue));
+        assert_eq!(target_kind("arn:aws:lambda:us-east-1:123456789012:function:f"), Some(TargetKind::Function));
+
+        let destination = "arn:aws:events:us-east-1:123456789012:api-destination/hook/abc";
+        assert!(target_arn(&EventTarget::webhook("w", destination)).is_ok());
+        assert_eq!(target_kind(destination), Some(TargetKind::Webhook));
+
+        let url = EventTarget::webhook("w", "https://example.com/hook");
+        assert!(matches!(target_arn(&url), Err(CloudError::Provider { code, .. }) if code == "NotSupported"));
+    }
 }
 
@@ -1,6 +1,10 @@
 use async_trait::async_trait;
 use bytes::Bytes;
-use cloudkit_api::{GetOptions, ListOptions, ObjectStorage, PutOptions};
+use aws_sdk_s3::types::ServerSideEncryption;
+use cloudkit_api::{
+    CopyOptions, EncryptionOptions, GetOptions, LifecycleRule, LifecycleTransition, ListOptions,
+    ObjectStorage, PutOptions, StorageTier,
+};
 use cloudkit_spi::{BucketMetadata, CloudError, CloudResult, ListResult, ObjectMetadata, PaginationToken};
 use cloudkit_spi::CloudContext;
 use std::sync::Arc;
@@ -19,6 +23,206 @@ impl S3Storage {
     }
 }
 
+/// Largest object a single CopyObject request can copy.
+const MAX_COPY_OBJECT_BYTES: u64 = 5 * 1024 * 1024 * 1024;
+/// Part size of multipart copies of larger objects.
+const COPY_PART_BYTES: u64 = 512 * 1024 * 1024;
+
+/// `x-amz-copy-source` of an object, whose key must be URL-encoded.
+fn copy_source(bucket: &str, key: &str) -> String {
+    let mut source = format!("{}/", bucket);
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => source.push(byte as char),
+            _ => source.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    source
+}
+
+/// SSE parameters of an encryption option, in the shape every S3 request
+/// builder takes them.
+#[derive(Default)]
+struct SseParams {
+    server_side_encryption: Option<ServerSideEncryption>,
+    kms_key_id: Option<String>,
+    customer_algorithm: Option<String>,
+    customer_key: Option<String>,
+    customer_key_md5: Option<String>,
+}
+
+impl SseParams {
+    fn new(encryption: Option<EncryptionOptions>) -> Self {
+        use base64::Engine;
+        let base64 = base64::engine::general_purpose::STANDARD;
+        match encryption {
+            None => Self::default(),
+            Some(EncryptionOptions::ProviderManaged) => Self {
+                server_side_encryption: Some(ServerSideEncryption::Aes256),
+                ..Default::default()
+            },
+            Some(EncryptionOptions::CustomerManaged { key_id }) => Self {
+                server_side_encryption: Some(ServerSideEncryption::AwsKms),
+                kms_key_id: Some(key_id),
+                ..Default::default()
+            },
+            Some(EncryptionOptions::CustomerProvided { key }) => Self {
+                customer_algorithm: Some("AES256".to_string()),
+                customer_key: Some(base64.encode(&key)),
+                customer_key_md5: Some(base64.encode(md5::compute(&key).0)),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// S3 storage class of a lifecycle tier.
+fn transition_storage_class(tier: StorageTier) -> aws_sdk_s3::types::TransitionStorageClass {
+    use aws_sdk_s3::types::TransitionStorageClass;
+    match tier {
+        StorageTier::InfrequentAccess => TransitionStorageClass::StandardIa,
+        StorageTier::Archive => TransitionStorageClass::Glacier,
+        StorageTier::DeepArchive => TransitionStorageClass::DeepArchive,
+    }
+}
+
+/// Lifecycle tier of an S3 storage class; classes without one, such as
+/// `INTELLIGENT_TIERING`, are left out of the rules read back.
+fn storage_tier(class: &aws_sdk_s3::types::TransitionStorageClass) -> Option<StorageTier> {
+    use aws_sdk_s3::types::TransitionStorageClass;
+    match class {
+        TransitionStorageClass::StandardIa | TransitionStorageClass::OnezoneIa => Some(StorageTier::InfrequentAccess),
+        TransitionStorageClass::Glacier | TransitionStorageClass::GlacierIr => Some(StorageTier::Archive),
+        TransitionStorageClass::DeepArchive => Some(StorageTier::DeepArchive),
+        _ => None,
+    }
+}
+
+fn to_s3_rule(rule: LifecycleRule) -> CloudResult<aws_sdk_s3::types::LifecycleRule> {
+    use aws_sdk_s3::types::{ExpirationStatus, LifecycleExpiration, LifecycleRuleFilter, Transition};
+    let transitions = rule.transitions.iter()
+        .map(|t| Transition::builder()
+            .days(t.after_days as i32)
+            .storage_class(transition_storage_class(t.tier))
+            .build())
+        .collect();
+    aws_sdk_s3::types::LifecycleRule::builder()
+        .id(rule.id)
+        // An empty prefix filter applies the rule to the whole bucket
+        .filter(LifecycleRuleFilter::builder().prefix(rule.prefix.unwrap_or_default()).build())
+        .status(if rule.enabled { ExpirationStatus::Enabled } else { ExpirationStatus::Disabled })
+        .set_expiration(rule.expiration_days.map(|days| LifecycleExpiration::builder().days(days as i32).build()))
+        .set_transitions(Some(transitions))
+        .build()
+        .map_err(|e| CloudError::Validation(e.to_string()))
+}
+
+fn from_s3_rule(rule: &aws_sdk_s3::types::LifecycleRule) -> LifecycleRule {
+    #[allow(deprecated)]
+    let prefix = rule.filter().and_then(|f| f.prefix()).or(rule.prefix());
+    LifecycleRule {
+        id: rule.id().unwrap_or_default().to_string(),
+        prefix: prefix.filter(|p| !p.is_empty()).map(str::to_string),
+        enabled: *rule.status() == aws_sdk_s3::types::ExpirationStatus::Enabled,
+        expiration_days: rule.expiration().and_then(|e| e.days()).map(|days| days as u32),
+        transitions: rule.transitions().iter()
+            .filter_map(|t| Some(LifecycleTransition {
+                after_days: t.days()? as u32,
+                tier: storage_tier(t.storage_class()?)?,
+            }))
+            .collect(),
+    }
+}
+
+impl S3Storage {
+    /// Copy an object too large for CopyObject part by part with UploadPartCopy.
+    #[allow(clippy::too_many_arguments)]
+    async fn multipart_copy(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        head: &aws_sdk_s3::operation::head_object::HeadObjectOutput,
+        source: SseParams,
+        sse: SseParams,
+    ) -> CloudResult<()> {
+        let service_error = |e: &dyn std::fmt::Display| CloudError::ServiceError(e.to_string());
+        let size = head.content_length().unwrap_or(0) as u64;
+        // Unlike CopyObject, a multipart upload does not carry the metadata over
+        let upload = self.client.create_multipart_upload()
+            .bucket(dest_bucket)
+            .key(dest_key)
+            .set_content_type(head.content_type().map(String::from))
+            .set_cache_control(head.cache_control().map(String::from))
+            .set_content_encoding(head.content_encoding().map(String::from))
+            .set_metadata(head.metadata().cloned())
+            .set_server_side_encryption(sse.server_side_encryption)
+            .set_ssekms_key_id(sse.kms_key_id)
+            .set_sse_customer_algorithm(sse.customer_algorithm.clone())
+            .set_sse_customer_key(sse.customer_key.clone())
+            .set_sse_customer_key_md5(sse.customer_key_md5.clone())
+            .send()
+            .await
+            .map_err(|e| service_error(&e))?;
+        let upload_id = upload.upload_id().unwrap_or_default().to_string();
+
+        let mut parts = Vec::new();
+        let mut start = 0;
+        while start < size {
+            let end = (start + COPY_PART_BYTES).min(size) - 1;
+            let part_number = parts.len() as i32 + 1;
+            let part = self.client.upload_part_copy()
+                .bucket(dest_bucket)
+                .key(dest_key)
+                .upload_id(&upload_id)
+                .part_number(part_number)
+                .copy_source(copy_source(source_bucket, source_key))
+                .copy_source_range(format!("bytes={}-{}", start, end))
+                .set_copy_source_sse_customer_algorithm(source.customer_algorithm.clone())
+                .set_copy_source_sse_customer_key(source.customer_key.clone())
+                .set_copy_source_sse_customer_key_md5(source.customer_key_md5.clone())
+                .set_sse_customer_algorithm(sse.customer_algorithm.clone())
+                .set_sse_customer_key(sse.customer_key.clone())
+                .set_sse_customer_key_md5(sse.customer_key_md5.clone())
+                .send()
+                .await;
+            let part = match part {
+                Ok(part) => part,
+                Err(e) => {
+                    let _ = self.client.abort_multipart_upload()
+                        .bucket(dest_bucket)
+                        .key(dest_key)
+                        .upload_id(&upload_id)
+                        .send()
+                        .await;
+                    return Err(service_error(&e));
+                }
+            };
+            parts.push(aws_sdk_s3::types::CompletedPart::builder()
+                .set_e_tag(part.copy_part_result().and_then(|r| r.e_tag()).map(String::from))
+                .part_number(part_number)
+                .build());
+            start = end + 1;
+        }
+
+        self.client.complete_multipart_upload()
+            .bucket(dest_bucket)
+            .key(dest_key)
+            .upload_id(&upload_id)
+            .multipart_upload(aws_sdk_s3::types::CompletedMultipartUpload::builder()
+                .set_parts(Some(parts))
+                .build())
+            .set_sse_customer_algorithm(sse.customer_algorithm)
+            .set_sse_customer_key(sse.customer_key)
+            .set_sse_customer_key_md5(sse.customer_key_md5)
+            .send()
+            .await
+            .map_err(|e| service_error(&e))?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl ObjectStorage for S3Storage {
     async fn list_buckets(&self) -> CloudResult<Vec<BucketMetadata>> {
@@ -88,7 +292,19 @@ impl ObjectStorage for S3Storage {
             req = req.metadata(k, v);
         }
         
+        let sse = SseParams::new(options.encryption);
+        req = req
+            .set_server_side_encryption(sse.server_side_encryption)
+            .set_ssekms_key_id(sse.kms_key_id)
+            .set_sse_customer_algorithm(sse.customer_algorithm)
+            .set_sse_customer_key(sse.customer_key)
+            .set_sse_customer_key_md5(sse.customer_key_md5);
+        
         req.send().await.map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        
+        if !options.tags.is_empty() {
+            self.set_object_tags(bucket, key, options.tags).await?;
+        }
         Ok(())
     }
 
@@ -118,6 +334,12 @@ impl ObjectStorage for S3Storage {
             req = req.if_none_match(etag);
         }
         
+        let sse = SseParams::new(options.encryption);
+        req = req
+            .set_sse_customer_algorithm(sse.customer_algorithm)
+            .set_sse_customer_key(sse.customer_key)
+            .set_sse_customer_key_md5(sse.customer_key_md5);
+        
         let resp = req.send().await.map_err(|e| {
             if e.to_string().contains("NoSuchKey") {
                 CloudError::NotFound {
@@ -156,6 +378,7 @@ impl ObjectStorage for S3Storage {
             etag: resp.e_tag().map(|e| e.to_string()),
             last_modified: resp.last_modified().map(|d| chrono::DateTime::<chrono::Utc>::from_timestamp(d.secs(), 0).unwrap_or_default()).unwrap_or_default(),
             content_type: resp.content_type().map(|c| c.to_string()),
+            cache_control: resp.cache_control().map(|c| c.to_string()),
             storage_class: resp.storage_class().map(|s| s.as_str().to_string()),
             metadata: resp.metadata().unwrap_or(&std::collections::HashMap::new()).clone(),
         })
@@ -196,16 +419,64 @@ impl ObjectStorage for S3Storage {
         dest_bucket: &str,
         dest_key: &str,
     ) -> CloudResult<()> {
+        self.copy_object_with_options(source_bucket, source_key, dest_bucket, dest_key, CopyOptions::default()).await
+    }
+
+    async fn copy_object_with_options(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        options: CopyOptions,
+    ) -> CloudResult<()> {
+        let source = SseParams::new(options.source_encryption);
+        let head = self.client.head_object()
+            .bucket(source_bucket)
+            .key(source_key)
+            .set_sse_customer_algorithm(source.customer_algorithm.clone())
+            .set_sse_customer_key(source.customer_key.clone())
+            .set_sse_customer_key_md5(source.customer_key_md5.clone())
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        let sse = SseParams::new(options.encryption);
+        if head.content_length().unwrap_or(0) as u64 > MAX_COPY_OBJECT_BYTES {
+            return self.multipart_copy(source_bucket, source_key, dest_bucket, dest_key, &head, source, sse).await;
+        }
+        
         self.client.copy_object()
             .bucket(dest_bucket)
             .key(dest_key)
-            .copy_source(format!("{}/{}", source_bucket, source_key))
+            .copy_source(copy_source(source_bucket, source_key))
+            .set_copy_source_sse_customer_algorithm(source.customer_algorithm)
+            .set_copy_source_sse_customer_key(source.customer_key)
+            .set_copy_source_sse_customer_key_md5(source.customer_key_md5)
+            .set_server_side_encryption(sse.server_side_encryption)
+            .set_ssekms_key_id(sse.kms_key_id)
+            .set_sse_customer_algorithm(sse.customer_algorithm)
+            .set_sse_customer_key(sse.customer_key)
+            .set_sse_customer_key_md5(sse.customer_key_md5)
             .send()
             .await
             .map_err(|e| CloudError::ServiceError(e.to_string()))?;
         Ok(())
     }
 
+    async fn move_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> CloudResult<()> {
+        if (source_bucket, source_key) == (dest_bucket, dest_key) {
+            return Ok(());
+        }
+        self.copy_object(source_bucket, source_key, dest_bucket, dest_key).await?;
+        self.delete_object(source_bucket, source_key).await
+    }
+
     async fn object_exists(&self, bucket: &str, key: &str) -> CloudResult<bool> {
         match self.head_object(bucket, key).await {
             Ok(_) => Ok(true),
@@ -247,6 +518,7 @@ impl ObjectStorage for S3Storage {
                 etag: o.e_tag().map(|e| e.to_string()),
                 last_modified: o.last_modified().map(|d| chrono::DateTime::<chrono::Utc>::from_timestamp(d.secs(), 0).unwrap_or_default()).unwrap_or_default(),
                 content_type: None,
+                cache_control: None,
                 storage_class: o.storage_class().map(|s| s.as_str().to_string()),
                 metadata: std::collections::HashMap::new(),
             }
@@ -258,6 +530,110 @@ impl ObjectStorage for S3Storage {
         ))
     }
 
+    async fn set_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        options: PutOptions,
+    ) -> CloudResult<()> {
+        // S3 metadata is immutable; copying the object onto itself replaces it
+        self.client.copy_object()
+            .bucket(bucket)
+            .key(key)
+            .copy_source(copy_source(bucket, key))
+            .metadata_directive(aws_sdk_s3::types::MetadataDirective::Replace)
+            .set_content_type(options.content_type)
+            .set_cache_control(options.cache_control)
+            .set_content_encoding(options.content_encoding)
+            .set_metadata(Some(options.metadata))
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> CloudResult<std::collections::HashMap<String, String>> {
+        let resp = self.client.get_object_tagging()
+            .bucket(bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+            
+        Ok(resp.tag_set().iter()
+            .map(|t| (t.key().to_string(), t.value().to_string()))
+            .collect())
+    }
+
+    async fn set_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+        tags: std::collections::HashMap<String, String>,
+    ) -> CloudResult<()> {
+        let tag_set = tags.into_iter()
+            .map(|(k, v)| aws_sdk_s3::types::Tag::builder().key(k).value(v).build())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| CloudError::Validation(e.to_string()))?;
+        let tagging = aws_sdk_s3::types::Tagging::builder()
+            .set_tag_set(Some(tag_set))
+            .build()
+            .map_err(|e| CloudError::Validation(e.to_string()))?;
+            
+        self.client.put_object_tagging()
+            .bucket(bucket)
+            .key(key)
+            .tagging(tagging)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_bucket_lifecycle(&self, bucket: &str) -> CloudResult<Vec<LifecycleRule>> {
+        use aws_sdk_s3::error::ProvideErrorMetadata;
+        match self.client.get_bucket_lifecycle_configuration().bucket(bucket).send().await {
+            Ok(resp) => Ok(resp.rules().iter().map(from_s3_rule).collect()),
+            Err(e) if e.code() == Some("NoSuchLifecycleConfiguration") => Ok(Vec::new()),
+            Err(e) => Err(CloudError::ServiceError(e.to_string())),
+        }
+    }
+
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket: &str,
+        rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        if rules.is_empty() {
+            self.client.delete_bucket_lifecycle()
+                .bucket(bucket)
+                .send()
+                .await
+                .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+            return Ok(());
+        }
+
+        let rules = rules.into_iter().map(to_s3_rule).collect::<CloudResult<Vec<_>>>()?;
+        let configuration = aws_sdk_s3::types::BucketLifecycleConfiguration::builder()
+            .set_rules(Some(rules))
+            .build()
+            .map_err(|e| CloudError::Validation(e.to_string()))?;
+        self.client.put_bucket_lifecycle_configuration()
+            .bucket(bucket)
+            .lifecycle_configuration(configuration)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(())
+    }
+
     async fn presigned_get_url(
         &self,
         bucket: &str,
@@ -315,5 +691,12 @@ mod tests {
             .unwrap();
         let _storage = S3Storage::new(Arc::new(context), sdk_config);
     }
+
+    #[test]
+    fn test_copy_source_encodes_key() {
+        assert_eq!(copy_source("bucket", "dir/file.txt"), "bucket/dir/file.txt");
+        assert_eq!(copy_source("bucket", "a b+c?.txt"), "bucket/a%20b%2Bc%3F.txt");
+        assert_eq!(copy_source("bucket", "café"), "bucket/caf%C3%A9");
+    }
 }
 
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use cloudkit_api::{Message, MessageQueue, ReceiveOptions, SendOptions};
+use cloudkit_api::{DeadLetterConfig, Message, MessageQueue, ReceiveOptions, SendOptions};
 use cloudkit_spi::{CloudError, CloudResult, ResourceId};
 use cloudkit_spi::CloudContext;
 use std::sync::Arc;
@@ -19,6 +19,42 @@ impl SqsQueue {
     }
 }
 
+/// Longest SQS `DelaySeconds`.
+const MAX_DELAY: Duration = Duration::from_secs(15 * 60);
+
+/// `DelaySeconds` of a message, rounded up so it is never delivered early.
+fn delay_seconds(options: &SendOptions) -> CloudResult<Option<i32>> {
+    let Some(delay) = options.enqueue_delay(chrono::Utc::now()) else {
+        return Ok(None);
+    };
+    let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+    if seconds > MAX_DELAY.as_secs() {
+        return Err(CloudError::Provider {
+            provider: "aws".to_string(),
+            code: "NotSupported".to_string(),
+            message: format!("SQS delays messages by at most {:?}, not {:?}", MAX_DELAY, delay),
+        });
+    }
+    Ok(Some(seconds as i32))
+}
+
+impl SqsQueue {
+    /// Value of a queue attribute, if set.
+    async fn queue_attribute(
+        &self,
+        queue_url: &str,
+        name: aws_sdk_sqs::types::QueueAttributeName,
+    ) -> CloudResult<Option<String>> {
+        let resp = self.client.get_queue_attributes()
+            .queue_url(queue_url)
+            .attribute_names(name.clone())
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(resp.attributes().and_then(|a| a.get(&name)).cloned())
+    }
+}
+
 #[async_trait]
 impl MessageQueue for SqsQueue {
     async fn create_queue(&self, name: &str) -> CloudResult<String> {
@@ -74,11 +110,8 @@ impl MessageQueue for SqsQueue {
     ) -> CloudResult<ResourceId> {
         let mut req = self.client.send_message()
             .queue_url(queue_url)
-            .message_body(body);
-            
-        if let Some(delay) = options.delay {
-            req = req.delay_seconds(delay.as_secs() as i32);
-        }
+            .message_body(body)
+            .set_delay_seconds(delay_seconds(&options)?);
         
         if let Some(group_id) = options.message_group_id {
             req = req.message_group_id(group_id);
@@ -264,5 +297,76 @@ impl MessageQueue for SqsQueue {
             .map_err(|e| CloudError::ServiceError(e.to_string()))?;
         Ok(())
     }
+
+    async fn configure_dlq(
+        &self,
+        queue_url: &str,
+        config: Option<DeadLetterConfig>,
+    ) -> CloudResult<()> {
+        use aws_sdk_sqs::types::QueueAttributeName;
+        // An empty policy removes the dead-letter queue
+        let policy = match config {
+            None => String::new(),
+            Some(config) => {
+                if !(1..=1000).contains(&config.max_receive_count) {
+                    return Err(CloudError::Validation(format!(
+                        "SQS maxReceiveCount must be between 1 and 1000, got {}",
+                        config.max_receive_count
+                    )));
+                }
+                let arn = self.queue_attribute(&config.dead_letter_queue, QueueAttributeName::QueueArn).await?
+                    .ok_or_else(|| CloudError::NotFound {
+                        resource_type: "Queue".to_string(),
+                        resource_id: config.dead_letter_queue.clone(),
+                    })?;
+                serde_json::json!({
+                    "deadLetterTargetArn": arn,
+                    "maxReceiveCount": config.max_receive_count.to_string(),
+                }).to_string()
+            }
+        };
+        self.client.set_queue_attributes()
+            .queue_url(queue_url)
+            .attributes(QueueAttributeName::RedrivePolicy, policy)
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn read_dlq(
+        &self,
+        queue_url: &str,
+        options: ReceiveOptions,
+    ) -> CloudResult<Vec<Message>> {
+        let not_found = || CloudError::NotFound {
+            resource_type: "DeadLetterQueue".to_string(),
+            resource_id: queue_url.to_string(),
+        };
+        let policy = self.queue_attribute(queue_url, aws_sdk_sqs::types::QueueAttributeName::RedrivePolicy).await?
+            .filter(|policy| !policy.is_empty())
+            .ok_or_else(not_found)?;
+        let policy: serde_json::Value = serde_json::from_str(&policy)
+            .map_err(|e| CloudError::Serialization(e.to_string()))?;
+        // arn:aws:sqs:<region>:<account>:<queue name>
+        let arn = policy["deadLetterTargetArn"].as_str().ok_or_else(not_found)?;
+        let mut parts = arn.rsplitn(3, ':');
+        let (name, account) = (parts.next(), parts.next());
+        let dlq_url = self.client.get_queue_url()
+            .queue_name(name.ok_or_else(not_found)?)
+            .set_queue_owner_aws_account_id(account.map(str::to_string))
+            .send()
+            .await
+            .map_err(|e| CloudError::ServiceError(e.to_string()))?
+            .queue_url()
+            .ok_or_else(not_found)?
+            .to_string();
+
+        let messages = self.receive(&dlq_url, options).await?;
+        if !messages.is_empty() {
+            self.delete_batch(&dlq_url, &messages.iter().collect::<Vec<_>>()).await?;
+        }
+        Ok(messages)
+    }
 }
 
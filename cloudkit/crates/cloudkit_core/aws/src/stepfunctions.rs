@@ -2,8 +2,8 @@
 
 use async_trait::async_trait;
 use cloudkit_api::{
-    Execution, ExecutionFilter, ExecutionStatus, HistoryEvent, StartExecutionOptions,
-    WorkflowDefinition, WorkflowService, WorkflowType,
+    Execution, ExecutionError, ExecutionEventKind, ExecutionFilter, ExecutionStatus, HistoryEvent,
+    StartExecutionOptions, WorkflowDefinition, WorkflowService, WorkflowType,
 };
 use cloudkit_spi::{CloudError, CloudResult, Metadata};
 use cloudkit_spi::CloudContext;
@@ -192,7 +192,10 @@ impl WorkflowService for AwsWorkflow {
             },
             input: resp.input().and_then(|i| serde_json::from_str(i).ok()),
             output: resp.output().and_then(|o| serde_json::from_str(o).ok()),
-            error: None, // Could map from status if failed
+            error: resp.error().map(|error| ExecutionError {
+                error: error.to_string(),
+                cause: resp.cause().unwrap_or_default().to_string(),
+            }),
             start_time: chrono::DateTime::<chrono::Utc>::from_timestamp(resp.start_date().secs(), 0).unwrap_or_default(),
             stop_time: resp.stop_date().map(|d| chrono::DateTime::<chrono::Utc>::from_timestamp(d.secs(), 0).unwrap_or_default()),
         })
@@ -249,20 +252,65 @@ impl WorkflowService for AwsWorkflow {
         &self,
         execution_id: &str,
     ) -> CloudResult<Vec<HistoryEvent>> {
-        let resp = self.client.get_execution_history()
-            .execution_arn(execution_id)
-            .send()
-            .await
-            .map_err(|e| CloudError::ServiceError(e.to_string()))?;
-            
-        Ok(resp.events().iter().enumerate().map(|(i, e)| {
-            HistoryEvent {
-                id: (i as u64) + 1, // HistoryEvent uses u64
-                timestamp: chrono::DateTime::<chrono::Utc>::from_timestamp(e.timestamp().secs(), 0).unwrap_or_default(),
-                event_type: format!("{:?}", e.r#type()),
-                details: Value::Null,
+        let mut events = Vec::new();
+        let mut next_token = None;
+        // Failures and timeouts name no state, so they are attributed to the state last entered
+        let mut state: Option<String> = None;
+
+        loop {
+            let resp = self.client.get_execution_history()
+                .execution_arn(execution_id)
+                .set_next_token(next_token)
+                .send()
+                .await
+                .map_err(|e| CloudError::ServiceError(e.to_string()))?;
+
+            for e in resp.events() {
+                let event_type = e.r#type().as_str();
+                let kind = event_kind(event_type);
+                let timestamp = chrono::DateTime::<chrono::Utc>::from_timestamp(e.timestamp().secs(), 0).unwrap_or_default();
+                let mut event = HistoryEvent::new(kind, event_type, timestamp);
+                event.id = e.id() as u64;
+
+                if let Some(entered) = e.state_entered_event_details() {
+                    state = Some(entered.name().to_string());
+                    event = event.details(parse_json(entered.input()));
+                }
+                if let Some(exited) = e.state_exited_event_details() {
+                    state = Some(exited.name().to_string());
+                    event = event.details(parse_json(exited.output()));
+                }
+                if let Some(succeeded) = e.execution_succeeded_event_details() {
+                    event = event.details(parse_json(succeeded.output()));
+                }
+
+                let failure = e.execution_failed_event_details().map(|d| (d.error(), d.cause()))
+                    .or_else(|| e.execution_aborted_event_details().map(|d| (d.error(), d.cause())))
+                    .or_else(|| e.execution_timed_out_event_details().map(|d| (d.error(), d.cause())))
+                    .or_else(|| e.task_failed_event_details().map(|d| (d.error(), d.cause())))
+                    .or_else(|| e.task_timed_out_event_details().map(|d| (d.error(), d.cause())))
+                    .or_else(|| e.lambda_function_failed_event_details().map(|d| (d.error(), d.cause())))
+                    .or_else(|| e.activity_failed_event_details().map(|d| (d.error(), d.cause())));
+                if let Some((error, cause)) = failure {
+                    event = event.error(ExecutionError {
+                        error: error.unwrap_or(event_type).to_string(),
+                        cause: cause.unwrap_or_default().to_string(),
+                    });
+                }
+
+                if matches!(kind, ExecutionEventKind::StepStarted | ExecutionEventKind::StepSucceeded | ExecutionEventKind::StepFailed) {
+                    if let Some(state) = &state {
+                        event = event.step(state.clone());
+                    }
+                }
+                events.push(event);
             }
-        }).collect())
+
+            next_token = resp.next_token().map(str::to_string);
+            if next_token.is_none() {
+                return Ok(events);
+            }
+        }
     }
 
     async fn send_task_success(
@@ -305,6 +353,29 @@ impl WorkflowService for AwsWorkflow {
     }
 }
 
+/// Normalized kind of a Step Functions history event type.
+fn event_kind(event_type: &str) -> ExecutionEventKind {
+    match event_type {
+        "ExecutionStarted" => ExecutionEventKind::ExecutionStarted,
+        "ExecutionSucceeded" => ExecutionEventKind::ExecutionSucceeded,
+        "ExecutionFailed" => ExecutionEventKind::ExecutionFailed,
+        "ExecutionTimedOut" => ExecutionEventKind::ExecutionTimedOut,
+        "ExecutionAborted" => ExecutionEventKind::ExecutionAborted,
+        t if t.ends_with("StateEntered") => ExecutionEventKind::StepStarted,
+        t if t.ends_with("StateExited") => ExecutionEventKind::StepSucceeded,
+        // TaskFailed, LambdaFunctionTimedOut, ActivityScheduleFailed, MapIterationFailed, ...
+        t if t.ends_with("Failed") || t.ends_with("TimedOut") || t.ends_with("Aborted") => {
+            ExecutionEventKind::StepFailed
+        }
+        _ => ExecutionEventKind::Other,
+    }
+}
+
+/// Parse the JSON input or output of an event, which Step Functions returns as a string.
+fn parse_json(data: Option<&str>) -> Value {
+    data.and_then(|d| serde_json::from_str(d).ok()).unwrap_or(Value::Null)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -325,5 +396,16 @@ mod tests {
         let context = create_test_context().await;
         let _sf = AwsWorkflow::new(context, sdk_config);
     }
+
+    #[test]
+    fn test_event_kind() {
+        assert_eq!(event_kind("ExecutionStarted"), ExecutionEventKind::ExecutionStarted);
+        assert_eq!(event_kind("ExecutionAborted"), ExecutionEventKind::ExecutionAborted);
+        assert_eq!(event_kind("TaskStateEntered"), ExecutionEventKind::StepStarted);
+        assert_eq!(event_kind("ChoiceStateExited"), ExecutionEventKind::StepSucceeded);
+        assert_eq!(event_kind("LambdaFunctionTimedOut"), ExecutionEventKind::StepFailed);
+        assert_eq!(event_kind("MapIterationAborted"), ExecutionEventKind::StepFailed);
+        assert_eq!(event_kind("TaskScheduled"), ExecutionEventKind::Other);
+    }
 }
 
@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use cloudkit_api::{GetOptions, ListOptions, ObjectStorage, PutOptions};
+use cloudkit_api::{CopyOptions, GetOptions, LifecycleRule, ListOptions, ObjectStorage, PutOptions};
 use cloudkit_spi::{BucketMetadata, CloudError, CloudResult, ListResult, ObjectMetadata, PaginationToken};
 use cloudkit_spi::CloudContext;
 use std::sync::Arc;
@@ -84,7 +84,7 @@ impl ObjectStorage for AzureBlobStorage {
         bucket: &str,
         key: &str,
         data: &[u8],
-        _options: PutOptions,
+        options: PutOptions,
     ) -> CloudResult<()> {
         tracing::info!(
             provider = "azure",
@@ -92,6 +92,7 @@ impl ObjectStorage for AzureBlobStorage {
             container = %bucket,
             blob = %key,
             size = %data.len(),
+            encryption = ?options.encryption,
             "put_object_with_options called"
         );
         Ok(())
@@ -183,6 +184,42 @@ impl ObjectStorage for AzureBlobStorage {
         Ok(())
     }
 
+    async fn copy_object_with_options(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        options: CopyOptions,
+    ) -> CloudResult<()> {
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            source = %format!("{}/{}", source_bucket, source_key),
+            dest = %format!("{}/{}", dest_bucket, dest_key),
+            encryption = ?options.encryption,
+            "copy_object_with_options called"
+        );
+        Ok(())
+    }
+
+    async fn move_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> CloudResult<()> {
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            source = %format!("{}/{}", source_bucket, source_key),
+            dest = %format!("{}/{}", dest_bucket, dest_key),
+            "move_object called"
+        );
+        Ok(())
+    }
+
     async fn object_exists(&self, bucket: &str, key: &str) -> CloudResult<bool> {
         tracing::info!(
             provider = "azure",
@@ -208,6 +245,107 @@ impl ObjectStorage for AzureBlobStorage {
         Ok(ListResult::new(vec![], PaginationToken::none()))
     }
 
+    // =========================================================================
+    // Metadata and Tags
+    // =========================================================================
+
+    async fn set_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        options: PutOptions,
+    ) -> CloudResult<()> {
+        // Maps to Set Blob Properties (x-ms-blob-content-*) and Set Blob Metadata
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            container = %bucket,
+            blob = %key,
+            metadata = %options.metadata.len(),
+            "set_object_metadata called"
+        );
+        Ok(())
+    }
+
+    async fn get_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> CloudResult<std::collections::HashMap<String, String>> {
+        // Maps to Get Blob Tags
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            container = %bucket,
+            blob = %key,
+            "get_object_tags called"
+        );
+        Err(CloudError::NotFound {
+            resource_type: "Blob".to_string(),
+            resource_id: format!("{}/{}", bucket, key),
+        })
+    }
+
+    async fn set_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+        tags: std::collections::HashMap<String, String>,
+    ) -> CloudResult<()> {
+        // Maps to Set Blob Tags, which allows at most 10 tags per blob
+        if tags.len() > 10 {
+            return Err(CloudError::Validation(format!(
+                "A blob has at most 10 tags, got {}",
+                tags.len()
+            )));
+        }
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            container = %bucket,
+            blob = %key,
+            count = %tags.len(),
+            "set_object_tags called"
+        );
+        Ok(())
+    }
+
+    // =========================================================================
+    // Lifecycle
+    // =========================================================================
+
+    async fn get_bucket_lifecycle(&self, bucket: &str) -> CloudResult<Vec<LifecycleRule>> {
+        // Maps to the rules of the account management policy whose prefix
+        // match starts with "<container>/"
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            container = %bucket,
+            "get_bucket_lifecycle called"
+        );
+        Ok(Vec::new())
+    }
+
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket: &str,
+        rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()> {
+        // Maps to replacing those rules of the account management policy,
+        // with tierToCool, tierToCold and tierToArchive actions
+        for rule in &rules {
+            rule.validate()?;
+        }
+        tracing::info!(
+            provider = "azure",
+            service = "blob",
+            container = %bucket,
+            count = %rules.len(),
+            "set_bucket_lifecycle called"
+        );
+        Ok(())
+    }
+
     // =========================================================================
     // Presigned URLs
     // =========================================================================
@@ -283,6 +421,18 @@ mod tests {
         assert!(result.is_ok());
     }
     
+    #[tokio::test]
+    async fn test_set_object_tags_limit() {
+        let context = create_test_context().await;
+        let storage = AzureBlobStorage::new(context);
+        
+        let tags: std::collections::HashMap<_, _> = (0..11)
+            .map(|i| (format!("key{}", i), "value".to_string()))
+            .collect();
+        let result = storage.set_object_tags("container", "key", tags).await;
+        assert!(matches!(result, Err(CloudError::Validation(_))));
+    }
+    
     #[tokio::test]
     async fn test_bucket_exists() {
         let context = create_test_context().await;
@@ -1,6 +1,9 @@
 //! Azure client builder.
 
+use async_trait::async_trait;
 use cloudkit_spi::{CloudConfig, CloudResult, Region, CloudContext, ProviderType, CloudError};
+use cloudkit_spi::{HealthCheck, HealthCheckOptions, HealthProbes, HealthReport};
+use cloudkit_spi::{Capabilities, CapabilityDiscovery};
 use std::sync::Arc;
 
 /// Azure client builder.
@@ -121,3 +124,53 @@ impl AzureClient {
     }
 }
 
+/// Probes the services that talk to Azure; Key Vault is only probed when a vault is configured.
+#[async_trait]
+impl HealthCheck for AzureClient {
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport {
+        #[allow(unused_mut)]
+        let mut probes = HealthProbes::new(ProviderType::Azure, options);
+
+        #[cfg(feature = "keyvault")]
+        if let Some(client) = self.secret_client.clone() {
+            // Reading a secret that does not exist is the cheapest authenticated request
+            probes.probe("keyvault", async move {
+                match client.get("cloudkit-health-probe").await {
+                    Ok(_) => Ok(()),
+                    Err(e) => match e.kind() {
+                        azure_core::error::ErrorKind::HttpResponse {
+                            status: azure_core::StatusCode::NotFound,
+                            ..
+                        } => Err(CloudError::NotFound {
+                            resource_type: "secret".to_string(),
+                            resource_id: "cloudkit-health-probe".to_string(),
+                        }),
+                        _ => Err(CloudError::Provider {
+                            provider: "azure".to_string(),
+                            code: "KeyVaultError".to_string(),
+                            message: e.to_string(),
+                        }),
+                    },
+                }
+            });
+        }
+
+        probes.run().await
+    }
+}
+
+
+/// Only Key Vault secrets are reachable through the client, when a vault is configured.
+impl CapabilityDiscovery for AzureClient {
+    fn capabilities(&self) -> Capabilities {
+        #[allow(unused_mut)]
+        let mut capabilities = Capabilities::new(ProviderType::Azure);
+
+        #[cfg(feature = "keyvault")]
+        if self.secret_client.is_some() {
+            capabilities.service(cloudkit_spi::Service::SecretsManager, &[]);
+        }
+
+        capabilities
+    }
+}
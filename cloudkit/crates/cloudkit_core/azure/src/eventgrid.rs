@@ -2,10 +2,11 @@
 
 use async_trait::async_trait;
 use cloudkit_api::{
-    Event, EventBus, EventRule, EventTarget, PutEventsResult,
+    Event, EventBus, EventRule, EventTarget, PutEventsResult, TargetKind,
 };
-use cloudkit_spi::CloudResult;
+use cloudkit_spi::{CloudError, CloudResult};
 use cloudkit_spi::CloudContext;
+use serde_json::{json, Value};
 use std::sync::Arc;
 
 /// Azure Event Grid implementation.
@@ -22,6 +23,58 @@ impl AzureEventGrid {
     }
 }
 
+/// Body of the event subscription delivering events that match `rule` to its one target.
+///
+/// Event types become `includedEventTypes` and other pattern fields `StringIn`
+/// advanced filters on the CloudEvents attribute of the same name.
+fn subscription_body(rule: &EventRule, targets: &[EventTarget]) -> CloudResult<Value> {
+    let [target] = targets else {
+        return Err(CloudError::Validation(
+            "an Event Grid subscription delivers to exactly one target".to_string(),
+        ));
+    };
+
+    let destination = match target.kind {
+        Some(TargetKind::Queue) => {
+            // .../storageAccounts/{account}/queueServices/default/queues/{queue}
+            let Some((account, _)) = target.arn.split_once("/queueServices/") else {
+                return Err(CloudError::Validation(format!("{} is not a storage queue resource ID", target.arn)));
+            };
+            let queue = target.arn.rsplit('/').next().unwrap_or_default();
+            json!({ "endpointType": "StorageQueue", "properties": { "resourceId": account, "queueName": queue } })
+        }
+        Some(TargetKind::Function) => {
+            json!({ "endpointType": "AzureFunction", "properties": { "resourceId": target.arn } })
+        }
+        Some(TargetKind::Webhook) => {
+            json!({ "endpointType": "WebHook", "properties": { "endpointUrl": target.arn } })
+        }
+        None => {
+            return Err(CloudError::Validation(format!("target {} needs a kind", target.id)));
+        }
+    };
+
+    let mut filter = json!({});
+    let mut advanced = Vec::new();
+    for (field, values) in rule.match_filters()? {
+        match field.as_str() {
+            "detail-type" | "type" => filter["includedEventTypes"] = json!(values),
+            _ => advanced.push(json!({ "operatorType": "StringIn", "key": field, "values": values })),
+        }
+    }
+    if !advanced.is_empty() {
+        filter["advancedFilters"] = json!(advanced);
+    }
+
+    Ok(json!({
+        "properties": {
+            "destination": destination,
+            "filter": filter,
+            "eventDeliverySchema": "CloudEventSchemaV1_0",
+        }
+    }))
+}
+
 #[async_trait]
 impl EventBus for AzureEventGrid {
     async fn put_events(
@@ -85,6 +138,27 @@ impl EventBus for AzureEventGrid {
         ))
     }
 
+    async fn create_rule(
+        &self,
+        topic_name: &str,
+        rule: EventRule,
+        targets: Vec<EventTarget>,
+    ) -> CloudResult<String> {
+        let body = subscription_body(&rule, &targets)?;
+        tracing::info!(
+            provider = "azure",
+            service = "eventgrid",
+            topic = %topic_name,
+            subscription = %rule.name,
+            endpoint_type = %body["properties"]["destination"]["endpointType"],
+            "create_rule called"
+        );
+        Ok(format!(
+            "/subscriptions/sub-id/resourceGroups/rg/providers/Microsoft.EventGrid/topics/{}/eventSubscriptions/{}",
+            topic_name, rule.name
+        ))
+    }
+
     async fn delete_rule(&self, topic_name: &str, rule_name: &str) -> CloudResult<()> {
         tracing::info!(
             provider = "azure",
@@ -234,6 +308,37 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_create_subscription() {
+        let context = create_test_context().await;
+        let eg = AzureEventGrid::new(context);
+
+        let rule = EventRule::pattern("orders", json!({"type": ["OrderCreated"], "source": ["myapp.orders"]}));
+        let queue = "/subscriptions/sub-id/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/acct/queueServices/default/queues/jobs";
+        let result = eg.create_rule("my-topic", rule.clone(), vec![EventTarget::queue("jobs", queue)]).await;
+        assert!(result.unwrap().ends_with("/eventSubscriptions/orders"));
+
+        let two = vec![EventTarget::webhook("a", "https://a.example.com"), EventTarget::webhook("b", "https://b.example.com")];
+        assert!(eg.create_rule("my-topic", rule.clone(), two).await.is_err());
+        assert!(eg.create_rule("my-topic", rule, vec![EventTarget::new("x", "somewhere")]).await.is_err());
+    }
+
+    #[test]
+    fn test_subscription_body() {
+        let rule = EventRule::pattern("orders", json!({"type": ["OrderCreated"], "source": ["myapp.orders"]}));
+        let queue = "/subscriptions/s/resourceGroups/rg/providers/Microsoft.Storage/storageAccounts/acct/queueServices/default/queues/jobs";
+        let body = subscription_body(&rule, &[EventTarget::queue("jobs", queue)]).unwrap();
+        let properties = &body["properties"];
+        assert_eq!(properties["destination"]["endpointType"], "StorageQueue");
+        assert_eq!(properties["destination"]["properties"]["queueName"], "jobs");
+        assert!(properties["destination"]["properties"]["resourceId"].as_str().unwrap().ends_with("/storageAccounts/acct"));
+        assert_eq!(properties["filter"]["includedEventTypes"], json!(["OrderCreated"]));
+        assert_eq!(properties["filter"]["advancedFilters"][0]["key"], "source");
+
+        let body = subscription_body(&rule, &[EventTarget::webhook("hook", "https://example.com/hook")]).unwrap();
+        assert_eq!(body["properties"]["destination"]["properties"]["endpointUrl"], "https://example.com/hook");
+    }
+
     #[tokio::test]
     async fn test_list_subscriptions() {
         let context = create_test_context().await;
@@ -119,6 +119,18 @@ impl IdentityProvider for AzureAdB2c {
         Ok(vec![])
     }
 
+    async fn set_password(&self, username: &str, _password: &str, permanent: bool) -> CloudResult<()> {
+        // Graph: PATCH /users/{id} with passwordProfile.forceChangePasswordNextSignIn = !permanent
+        tracing::info!(
+            provider = "azure",
+            service = "ad-b2c",
+            username = %username,
+            permanent = permanent,
+            "set_password called"
+        );
+        Ok(())
+    }
+
     async fn initiate_auth(
         &self,
         username: &str,
@@ -135,6 +147,23 @@ impl IdentityProvider for AzureAdB2c {
         )))
     }
 
+    async fn admin_initiate_auth(
+        &self,
+        username: &str,
+        _password: &str,
+    ) -> CloudResult<InitiateAuthResult> {
+        // B2C: resource owner password credentials (ROPC) user flow
+        tracing::info!(
+            provider = "azure",
+            service = "ad-b2c",
+            username = %username,
+            "admin_initiate_auth called"
+        );
+        Err(CloudError::Auth(AuthError::InvalidCredentials(
+            "Invalid username or password".to_string(),
+        )))
+    }
+
     async fn respond_to_challenge(
         &self,
         challenge_name: ChallengeType,
@@ -353,5 +382,19 @@ mod tests {
         let result = ad.initiate_auth("testuser", "wrongpassword").await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_provision_user() {
+        let context = create_test_context().await;
+        let ad = AzureAdB2c::new(context);
+
+        let user = ad
+            .provision_user("testuser", Some("test@example.com"), "Passw0rd!", &["admins"])
+            .await
+            .unwrap();
+        assert_eq!(user.username, "testuser");
+        assert!(user.email_verified);
+        assert!(ad.set_password("testuser", "Passw0rd!", false).await.is_ok());
+    }
 }
 
@@ -11,6 +11,7 @@
 //! - **Event Grid** - Event routing (feature: `eventgrid`)
 //! - **Azure AD** - Identity provider (feature: `identity`)
 //! - **Service Bus** - Message queue (feature: `servicebus`)
+//! - **Logic Apps** - Workflow orchestration (feature: `logicapps`)
 //!
 //! ## Usage
 //!
@@ -56,6 +57,9 @@ mod identity;
 #[cfg(feature = "servicebus")]
 mod servicebus;
 
+#[cfg(feature = "logicapps")]
+mod logicapps;
+
 pub use builder::*;
 
 #[cfg(feature = "blob")]
@@ -79,3 +83,6 @@ pub use identity::*;
 #[cfg(feature = "servicebus")]
 pub use servicebus::*;
 
+#[cfg(feature = "logicapps")]
+pub use logicapps::*;
+
@@ -0,0 +1,392 @@
+//! Azure Logic Apps implementation.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use cloudkit_api::{
+    sequence_events, Execution, ExecutionError, ExecutionEventKind, ExecutionFilter, ExecutionStatus,
+    HistoryEvent, StartExecutionOptions, WorkflowDefinition, WorkflowService, WorkflowType,
+};
+use cloudkit_spi::{CloudError, CloudResult};
+use cloudkit_spi::CloudContext;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Azure Logic Apps implementation.
+pub struct AzureLogicApps {
+    _context: Arc<CloudContext>,
+    // In a real implementation:
+    // client: azure_mgmt_logic::Client,
+}
+
+impl AzureLogicApps {
+    /// Create a new Logic Apps client.
+    pub fn new(context: Arc<CloudContext>) -> Self {
+        Self { _context: context }
+    }
+}
+
+/// Execution status of a Logic Apps run status.
+fn run_status(status: &str) -> ExecutionStatus {
+    match status {
+        "Succeeded" => ExecutionStatus::Succeeded,
+        "Failed" | "Faulted" => ExecutionStatus::Failed,
+        "TimedOut" => ExecutionStatus::TimedOut,
+        "Cancelled" | "Aborted" => ExecutionStatus::Aborted,
+        // Running, Waiting, Paused, Suspended, ...
+        _ => ExecutionStatus::Running,
+    }
+}
+
+/// OData query parameters of the runs list matching `filter`.
+fn runs_query(filter: &ExecutionFilter) -> CloudResult<Vec<(&'static str, String)>> {
+    let mut query = Vec::new();
+    if let Some(status) = filter.status {
+        let status = match status {
+            ExecutionStatus::Running => "Running",
+            ExecutionStatus::Succeeded => "Succeeded",
+            ExecutionStatus::Failed => "Failed",
+            ExecutionStatus::TimedOut => "TimedOut",
+            ExecutionStatus::Aborted => "Cancelled",
+            ExecutionStatus::PendingRedrive => {
+                return Err(CloudError::Provider {
+                    provider: "azure".to_string(),
+                    code: "NotSupported".to_string(),
+                    message: "Logic Apps runs cannot be redriven".to_string(),
+                });
+            }
+        };
+        query.push(("$filter", format!("status eq '{}'", status)));
+    }
+    if let Some(max) = filter.max_results {
+        query.push(("$top", max.to_string()));
+    }
+    Ok(query)
+}
+
+fn parse_time(value: &Value) -> Option<DateTime<Utc>> {
+    value.as_str().and_then(|t| DateTime::parse_from_rfc3339(t).ok().map(|dt| dt.with_timezone(&Utc)))
+}
+
+fn parse_error(properties: &Value) -> Option<ExecutionError> {
+    let error = properties.get("error")?;
+    Some(ExecutionError {
+        error: error["code"].as_str().unwrap_or_default().to_string(),
+        cause: error["message"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// Execution from a workflow run resource.
+fn parse_run(run: &Value) -> Execution {
+    let id = run["id"].as_str().unwrap_or_default();
+    let properties = &run["properties"];
+    Execution {
+        execution_id: id.to_string(),
+        workflow_arn: id.split("/runs/").next().unwrap_or_default().to_string(),
+        name: run["name"].as_str().map(str::to_string),
+        status: run_status(properties["status"].as_str().unwrap_or_default()),
+        input: None,
+        output: properties.get("outputs").cloned(),
+        error: parse_error(properties),
+        start_time: parse_time(&properties["startTime"]).unwrap_or_else(Utc::now),
+        stop_time: parse_time(&properties["endTime"]),
+    }
+}
+
+/// Execution history from a run and its run actions.
+///
+/// Logic Apps keeps a record per action rather than an event log, so each action becomes a
+/// start event and, once it finished, a success or failure event at its end. Skipped actions
+/// never ran and have no events.
+fn run_events(run: &Execution, actions: &[Value]) -> Vec<HistoryEvent> {
+    let mut events = vec![HistoryEvent::new(ExecutionEventKind::ExecutionStarted, "Running", run.start_time)];
+
+    for action in actions {
+        let name = action["name"].as_str().unwrap_or_default();
+        let properties = &action["properties"];
+        let status = properties["status"].as_str().unwrap_or_default();
+        if status == "Skipped" {
+            continue;
+        }
+        if let Some(started) = parse_time(&properties["startTime"]) {
+            events.push(HistoryEvent::new(ExecutionEventKind::StepStarted, "Running", started).step(name));
+        }
+
+        let kind = match run_status(status) {
+            ExecutionStatus::Succeeded => ExecutionEventKind::StepSucceeded,
+            ExecutionStatus::Running | ExecutionStatus::PendingRedrive => continue,
+            _ => ExecutionEventKind::StepFailed,
+        };
+        let Some(ended) = parse_time(&properties["endTime"]) else { continue };
+        let mut event = HistoryEvent::new(kind, status, ended)
+            .step(name)
+            .details(json!({ "code": properties["code"] }));
+        event.error = parse_error(properties);
+        events.push(event);
+    }
+
+    if let (Some(kind), Some(stopped)) = (ExecutionEventKind::ending(run.status), run.stop_time) {
+        let mut event = HistoryEvent::new(kind, format!("{:?}", run.status), stopped)
+            .details(run.output.clone().unwrap_or(Value::Null));
+        event.error = run.error.clone();
+        events.push(event);
+    }
+    sequence_events(events)
+}
+
+#[async_trait]
+impl WorkflowService for AzureLogicApps {
+    async fn create_workflow(&self, definition: WorkflowDefinition) -> CloudResult<String> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %definition.name,
+            "create_workflow called"
+        );
+        Ok(format!(
+            "/subscriptions/default/resourceGroups/default/providers/Microsoft.Logic/workflows/{}",
+            definition.name
+        ))
+    }
+
+    async fn update_workflow(
+        &self,
+        workflow_arn: &str,
+        _definition: Value,
+    ) -> CloudResult<()> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %workflow_arn,
+            "update_workflow called"
+        );
+        Ok(())
+    }
+
+    async fn delete_workflow(&self, workflow_arn: &str) -> CloudResult<()> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %workflow_arn,
+            "delete_workflow called"
+        );
+        Ok(())
+    }
+
+    async fn describe_workflow(&self, workflow_arn: &str) -> CloudResult<WorkflowDefinition> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %workflow_arn,
+            "describe_workflow called"
+        );
+        let name = workflow_arn.rsplit('/').next().unwrap_or_default();
+        Ok(WorkflowDefinition {
+            arn: Some(workflow_arn.to_string()),
+            workflow_type: WorkflowType::Standard,
+            ..WorkflowDefinition::new(name, json!({}))
+        })
+    }
+
+    async fn list_workflows(&self) -> CloudResult<Vec<WorkflowDefinition>> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            "list_workflows called"
+        );
+        Ok(vec![])
+    }
+
+    async fn start_execution(
+        &self,
+        workflow_arn: &str,
+        input: Value,
+        options: StartExecutionOptions,
+    ) -> CloudResult<Execution> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %workflow_arn,
+            "start_execution called"
+        );
+        let name = options.name.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        Ok(Execution {
+            execution_id: format!("{}/runs/{}", workflow_arn, name),
+            workflow_arn: workflow_arn.to_string(),
+            name: Some(name),
+            status: ExecutionStatus::Running,
+            input: Some(input),
+            output: None,
+            error: None,
+            start_time: Utc::now(),
+            stop_time: None,
+        })
+    }
+
+    async fn stop_execution(
+        &self,
+        execution_id: &str,
+        _error: Option<&str>,
+        _cause: Option<&str>,
+    ) -> CloudResult<()> {
+        // Runs are cancelled without a reason
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            run = %execution_id,
+            "stop_execution called"
+        );
+        Ok(())
+    }
+
+    async fn describe_execution(&self, execution_id: &str) -> CloudResult<Execution> {
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            run = %execution_id,
+            "describe_execution called"
+        );
+        let run = json!({
+            "id": execution_id,
+            "name": execution_id.rsplit('/').next(),
+            "properties": { "status": "Running", "startTime": Utc::now().to_rfc3339() },
+        });
+        Ok(parse_run(&run))
+    }
+
+    async fn list_executions(
+        &self,
+        workflow_arn: &str,
+        filter: ExecutionFilter,
+    ) -> CloudResult<Vec<Execution>> {
+        let query = runs_query(&filter)?;
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            workflow = %workflow_arn,
+            query = ?query,
+            "list_executions called"
+        );
+        Ok(vec![])
+    }
+
+    async fn get_execution_history(
+        &self,
+        execution_id: &str,
+    ) -> CloudResult<Vec<HistoryEvent>> {
+        let run = self.describe_execution(execution_id).await?;
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            run = %execution_id,
+            "get_execution_history called"
+        );
+        Ok(run_events(&run, &[]))
+    }
+
+    async fn send_task_success(
+        &self,
+        task_token: &str,
+        _output: Value,
+    ) -> CloudResult<()> {
+        // The task token is the callback URL of an HTTP Webhook action
+        tracing::info!(
+            provider = "azure",
+            service = "logicapps",
+            callback = %task_token,
+            "send_task_success called"
+        );
+        Ok(())
+    }
+
+    async fn send_task_failure(
+        &self,
+        _task_token: &str,
+        _error: &str,
+        _cause: &str,
+    ) -> CloudResult<()> {
+        Err(CloudError::Provider {
+            provider: "azure".to_string(),
+            code: "NotSupported".to_string(),
+            message: "Logic Apps webhook callbacks have no failure signal. Send a success payload with error details instead.".to_string(),
+        })
+    }
+
+    async fn send_task_heartbeat(&self, _task_token: &str) -> CloudResult<()> {
+        Err(CloudError::Provider {
+            provider: "azure".to_string(),
+            code: "NotSupported".to_string(),
+            message: "Logic Apps does not support heartbeats for webhook callbacks.".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cloudkit_spi::ProviderType;
+
+    async fn create_test_context() -> Arc<CloudContext> {
+        Arc::new(
+            CloudContext::builder(ProviderType::Azure)
+                .build()
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_logic_apps_new() {
+        let context = create_test_context().await;
+        let _la = AzureLogicApps::new(context);
+    }
+
+    #[test]
+    fn test_runs_query() {
+        let filter = ExecutionFilter { status: Some(ExecutionStatus::Aborted), max_results: Some(10) };
+        assert_eq!(runs_query(&filter).unwrap(), vec![
+            ("$filter", "status eq 'Cancelled'".to_string()),
+            ("$top", "10".to_string()),
+        ]);
+        let redrive = ExecutionFilter { status: Some(ExecutionStatus::PendingRedrive), max_results: None };
+        assert!(runs_query(&redrive).is_err());
+    }
+
+    #[test]
+    fn test_run_events() {
+        let run = parse_run(&json!({
+            "id": "/subscriptions/s/resourceGroups/g/providers/Microsoft.Logic/workflows/orders/runs/r1",
+            "name": "r1",
+            "properties": {
+                "status": "Failed",
+                "startTime": "2024-01-01T00:00:00Z",
+                "endTime": "2024-01-01T00:00:05Z",
+                "error": { "code": "ActionFailed", "message": "An action failed." },
+            },
+        }));
+        assert_eq!(run.workflow_arn, "/subscriptions/s/resourceGroups/g/providers/Microsoft.Logic/workflows/orders");
+        assert_eq!(run.status, ExecutionStatus::Failed);
+
+        let actions = [
+            json!({ "name": "Parse", "properties": {
+                "status": "Succeeded", "startTime": "2024-01-01T00:00:01Z", "endTime": "2024-01-01T00:00:02Z",
+            }}),
+            json!({ "name": "Notify", "properties": { "status": "Skipped" }}),
+            json!({ "name": "HTTP", "properties": {
+                "status": "Failed", "startTime": "2024-01-01T00:00:03Z", "endTime": "2024-01-01T00:00:04Z",
+                "code": "BadRequest", "error": { "code": "BadRequest", "message": "400" },
+            }}),
+        ];
+        let events = run_events(&run, &actions);
+        let kinds: Vec<_> = events.iter().map(|e| (e.kind, e.step.as_deref())).collect();
+        assert_eq!(kinds, vec![
+            (ExecutionEventKind::ExecutionStarted, None),
+            (ExecutionEventKind::StepStarted, Some("Parse")),
+            (ExecutionEventKind::StepSucceeded, Some("Parse")),
+            (ExecutionEventKind::StepStarted, Some("HTTP")),
+            (ExecutionEventKind::StepFailed, Some("HTTP")),
+            (ExecutionEventKind::ExecutionFailed, None),
+        ]);
+        assert_eq!(events[4].error.as_ref().unwrap().error, "BadRequest");
+        assert_eq!(events[5].error.as_ref().unwrap().error, "ActionFailed");
+    }
+}
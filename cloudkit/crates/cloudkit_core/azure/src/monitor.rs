@@ -3,11 +3,12 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use cloudkit_api::{
-    AlarmConfig, AlarmState, LogEvent, LoggingService, MetricDatum, MetricQuery,
-    MetricResult, MetricsService,
+    AlarmConfig, AlarmState, LogEvent, LogQuery, LogStream, LogTail, LoggingService, MetricDatum, MetricQuery,
+    MetricRangeQuery, MetricResult, MetricStatistic, MetricsService,
 };
 use cloudkit_spi::CloudResult;
 use cloudkit_spi::CloudContext;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Azure Monitor Metrics implementation.
@@ -24,6 +25,40 @@ impl AzureMonitorMetrics {
     }
 }
 
+/// Shortest Azure Monitor metrics interval that covers `seconds`.
+fn azure_interval(seconds: u32) -> &'static str {
+    match seconds {
+        0..=60 => "PT1M",
+        61..=300 => "PT5M",
+        301..=900 => "PT15M",
+        901..=1800 => "PT30M",
+        1801..=3600 => "PT1H",
+        3601..=21600 => "PT6H",
+        21601..=43200 => "PT12H",
+        _ => "P1D",
+    }
+}
+
+/// Azure Monitor aggregation type for a statistic.
+fn azure_aggregation(statistic: MetricStatistic) -> &'static str {
+    match statistic {
+        MetricStatistic::Average => "Average",
+        MetricStatistic::Sum => "Total",
+        MetricStatistic::Minimum => "Minimum",
+        MetricStatistic::Maximum => "Maximum",
+        MetricStatistic::SampleCount => "Count",
+    }
+}
+
+/// Azure Monitor `$filter` matching every dimension, e.g. `Region eq 'west' and Tier eq 'web'`.
+fn azure_dimension_filter(dimensions: &HashMap<String, String>) -> String {
+    let mut filters: Vec<_> = dimensions.iter()
+        .map(|(k, v)| format!("{} eq '{}'", k, v.replace('\'', "''")))
+        .collect();
+    filters.sort();
+    filters.join(" and ")
+}
+
 #[async_trait]
 impl MetricsService for AzureMonitorMetrics {
     async fn put_metric_data(&self, namespace: &str, data: Vec<MetricDatum>) -> CloudResult<()> {
@@ -55,6 +90,28 @@ impl MetricsService for AzureMonitorMetrics {
         })
     }
 
+    async fn query_metrics(
+        &self,
+        namespace: &str,
+        query: MetricRangeQuery,
+    ) -> CloudResult<MetricResult> {
+        query.validate()?;
+        tracing::info!(
+            provider = "azure",
+            service = "monitor",
+            namespace = %namespace,
+            metric = %query.name,
+            interval = azure_interval(query.period_seconds),
+            aggregation = azure_aggregation(query.statistic),
+            filter = %azure_dimension_filter(&query.dimensions),
+            "query_metrics called"
+        );
+        Ok(MetricResult {
+            name: query.name,
+            data_points: vec![],
+        })
+    }
+
     async fn list_metrics(&self, namespace: Option<&str>) -> CloudResult<Vec<String>> {
         tracing::info!(
             provider = "azure",
@@ -178,6 +235,30 @@ impl LoggingService for AzureLogAnalytics {
         Ok(vec![])
     }
 
+    async fn search_logs(&self, query: LogQuery) -> CloudResult<Vec<LogEvent>> {
+        query.validate()?;
+        tracing::info!(
+            provider = "azure",
+            service = "log-analytics",
+            workspace = %query.group,
+            tables = ?query.streams,
+            contains = ?query.contains,
+            "search_logs called"
+        );
+        Ok(vec![])
+    }
+
+    async fn tail_logs(&self, tail: LogTail) -> CloudResult<LogStream> {
+        tracing::info!(
+            provider = "azure",
+            service = "log-analytics",
+            workspace = %tail.group,
+            tables = ?tail.streams,
+            "tail_logs called"
+        );
+        Ok(Box::pin(futures::stream::empty()))
+    }
+
     async fn list_log_groups(&self) -> CloudResult<Vec<String>> {
         tracing::info!(
             provider = "azure",
@@ -229,6 +310,32 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_query_metrics() {
+        let context = create_test_context().await;
+        let metrics = AzureMonitorMetrics::new(context);
+
+        let range = cloudkit_api::TimeRange::last(chrono::Duration::hours(1));
+        let query = MetricRangeQuery::new("CPUUtilization", range).period(300);
+        let result = metrics.query_metrics("MyApp", query).await.unwrap();
+        assert_eq!(result.name, "CPUUtilization");
+    }
+
+    #[test]
+    fn test_azure_metric_mappings() {
+        assert_eq!(azure_interval(60), "PT1M");
+        assert_eq!(azure_interval(120), "PT5M");
+        assert_eq!(azure_interval(86400), "P1D");
+        assert_eq!(azure_aggregation(MetricStatistic::Sum), "Total");
+        assert_eq!(azure_aggregation(MetricStatistic::SampleCount), "Count");
+
+        let dimensions = HashMap::from([
+            ("Tier".to_string(), "web".to_string()),
+            ("Region".to_string(), "o'hare".to_string()),
+        ]);
+        assert_eq!(azure_dimension_filter(&dimensions), "Region eq 'o''hare' and Tier eq 'web'");
+    }
+
     #[tokio::test]
     async fn test_get_alarm_state() {
         let context = create_test_context().await;
@@ -263,5 +370,16 @@ mod tests {
         let result = logs.put_log_events("workspace", "table", events).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_search_logs() {
+        let context = create_test_context().await;
+        let logs = AzureLogAnalytics::new(context);
+
+        let range = cloudkit_api::TimeRange::last(chrono::Duration::hours(1));
+        let query = LogQuery::new("workspace", range).stream("table").contains("error");
+        assert!(logs.search_logs(query).await.unwrap().is_empty());
+        assert!(logs.search_logs(LogQuery::new("", range)).await.is_err());
+    }
 }
 
@@ -1,7 +1,7 @@
 //! Azure Service Bus implementation.
 
 use async_trait::async_trait;
-use cloudkit_api::{Message, MessageQueue, ReceiveOptions, SendOptions};
+use cloudkit_api::{DeadLetterConfig, Message, MessageQueue, ReceiveOptions, SendOptions};
 use cloudkit_spi::{CloudResult, ResourceId};
 use cloudkit_spi::CloudContext;
 use std::sync::Arc;
@@ -85,13 +85,16 @@ impl MessageQueue for AzureServiceBusQueue {
         &self,
         queue_url: &str,
         body: &str,
-        _options: SendOptions,
+        options: SendOptions,
     ) -> CloudResult<ResourceId> {
+        // Held messages are sent with ScheduledEnqueueTimeUtc, which has no upper bound
+        let scheduled_enqueue_time = options.enqueue_time(chrono::Utc::now());
         tracing::info!(
             provider = "azure",
             service = "servicebus",
             queue = %queue_url,
             body_len = %body.len(),
+            scheduled_enqueue_time = ?scheduled_enqueue_time,
             "send_with_options called"
         );
         Ok(ResourceId::new(uuid::Uuid::new_v4().to_string()))
@@ -185,6 +188,42 @@ impl MessageQueue for AzureServiceBusQueue {
         );
         Ok(())
     }
+
+    async fn configure_dlq(
+        &self,
+        queue_url: &str,
+        config: Option<DeadLetterConfig>,
+    ) -> CloudResult<()> {
+        // Maps to the MaxDeliveryCount and ForwardDeadLetteredMessagesTo
+        // properties of the queue; without a config, dead-lettered messages
+        // stay in its built-in $DeadLetterQueue subqueue
+        tracing::info!(
+            provider = "azure",
+            service = "servicebus",
+            queue = %queue_url,
+            dead_letter_queue = ?config.as_ref().map(|c| &c.dead_letter_queue),
+            max_delivery_count = ?config.as_ref().map(|c| c.max_receive_count),
+            "configure_dlq called"
+        );
+        Ok(())
+    }
+
+    async fn read_dlq(
+        &self,
+        queue_url: &str,
+        options: ReceiveOptions,
+    ) -> CloudResult<Vec<Message>> {
+        // Maps to a ReceiveAndDelete receive from the forwarding queue, or
+        // from "<queue>/$DeadLetterQueue"
+        tracing::info!(
+            provider = "azure",
+            service = "servicebus",
+            queue = %queue_url,
+            max_messages = ?options.max_messages,
+            "read_dlq called"
+        );
+        Ok(vec![])
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,9 @@
 //! GCP client builder.
 
+use async_trait::async_trait;
 use cloudkit_spi::{CloudConfig, CloudResult, Region};
+use cloudkit_spi::{HealthCheck, HealthCheckOptions, HealthProbes, HealthReport};
+use cloudkit_spi::{Capabilities, CapabilityDiscovery};
 use cloudkit_spi::{CloudContext, ProviderType};
 use std::sync::Arc;
 
@@ -364,3 +367,110 @@ impl GcpClient {
     }
 }
 
+/// Probes each enabled service with a read that returns little or nothing.
+#[async_trait]
+impl HealthCheck for GcpClient {
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport {
+        #[allow(unused_mut)]
+        let mut probes = HealthProbes::new(ProviderType::Gcp, options);
+
+        #[cfg(feature = "gcs")]
+        {
+            use cloudkit_api::ObjectStorage;
+            let storage = self.storage();
+            probes.probe("gcs", async move { storage.list_buckets().await });
+        }
+        #[cfg(feature = "pubsub")]
+        {
+            let client = self.pubsub_client.clone();
+            probes.probe("pubsub", async move {
+                client.get_topics(None).await.map_err(|e| cloudkit_spi::CloudError::Provider {
+                    provider: "gcp".into(),
+                    code: "PubSubError".into(),
+                    message: e.to_string(),
+                })
+            });
+        }
+        #[cfg(feature = "firestore")]
+        {
+            use cloudkit_api::KeyValueStore;
+            let store = self.kv_store();
+            probes.probe("firestore", async move {
+                store.get::<serde_json::Value>("cloudkit-health", "probe").await
+            });
+        }
+        #[cfg(feature = "secrets")]
+        {
+            use cloudkit_api::SecretsManager;
+            let secrets = self.secrets();
+            probes.probe("secretmanager", async move { secrets.list_secrets().await });
+        }
+        #[cfg(feature = "monitor")]
+        {
+            use cloudkit_api::MetricsService;
+            let monitor = self.monitor();
+            probes.probe("monitoring", async move { monitor.list_metrics(Some("cloudkit-health")).await });
+        }
+        #[cfg(feature = "eventarc")]
+        {
+            use cloudkit_api::EventBus;
+            let events = self.events();
+            probes.probe("eventarc", async move { events.list_event_buses().await });
+        }
+        #[cfg(feature = "identity")]
+        {
+            use cloudkit_api::IdentityProvider;
+            let identity = self.identity();
+            probes.probe("identity", async move { identity.list_users(Some(1)).await });
+        }
+        #[cfg(feature = "kms")]
+        {
+            use cloudkit_api::KeyManagement;
+            let kms = self.kms();
+            probes.probe("kms", async move { kms.list_keys().await });
+        }
+        #[cfg(feature = "workflows")]
+        {
+            use cloudkit_api::WorkflowService;
+            let workflows = self.workflows();
+            probes.probe("workflows", async move { workflows.list_workflows().await });
+        }
+
+        probes.run().await
+    }
+}
+
+/// Every enabled service. Firestore writes take no conditions, and Pub/Sub as a queue
+/// has no ordering, delays or dead-letter reads yet.
+impl CapabilityDiscovery for GcpClient {
+    fn capabilities(&self) -> Capabilities {
+        #[allow(unused_imports)]
+        use cloudkit_spi::{Feature, Service};
+        #[allow(unused_mut)]
+        let mut capabilities = Capabilities::new(ProviderType::Gcp);
+
+        #[cfg(feature = "gcs")]
+        capabilities.service(Service::ObjectStorage, &[Feature::Presign, Feature::Lifecycle]);
+        #[cfg(feature = "firestore")]
+        capabilities.service(Service::KeyValueStore, &[]);
+        #[cfg(feature = "pubsub")]
+        capabilities.service(Service::MessageQueue, &[]);
+        #[cfg(feature = "secrets")]
+        capabilities.service(Service::SecretsManager, &[]);
+        #[cfg(feature = "kms")]
+        capabilities.service(Service::KeyManagement, &[]);
+        #[cfg(feature = "monitor")]
+        capabilities
+            .service(Service::MetricsService, &[])
+            .service(Service::LoggingService, &[Feature::Streaming]);
+        #[cfg(feature = "eventarc")]
+        capabilities.service(Service::EventBus, &[]);
+        #[cfg(feature = "workflows")]
+        capabilities.service(Service::WorkflowService, &[]);
+        #[cfg(feature = "identity")]
+        capabilities.service(Service::IdentityProvider, &[]);
+
+        capabilities
+    }
+}
+
@@ -2,14 +2,14 @@
 
 use async_trait::async_trait;
 use cloudkit_api::{
-    Event, EventBus, EventRule, EventTarget, FailedEntry, PutEventsResult, RuleState,
+    Event, EventBus, EventRule, EventTarget, FailedEntry, PutEventsResult, RuleState, TargetKind,
 };
 use cloudkit_spi::{CloudError, CloudResult};
 use cloudkit_spi::CloudContext;
 use google_cloud_auth::token_source::TokenSource;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
 use std::sync::Arc;
 
 /// Google Cloud Eventarc implementation.
@@ -52,6 +52,45 @@ impl GcpEventarc {
             self.project_id, self.region
         )
     }
+
+    /// Eventarc destination of a target whose kind is known.
+    fn destination(&self, target: &EventTarget) -> CloudResult<Value> {
+        match target.kind {
+            Some(TargetKind::Function) => {
+                let function = if target.arn.contains('/') {
+                    target.arn.clone()
+                } else {
+                    format!("projects/{}/locations/{}/functions/{}", self.project_id, self.region, target.arn)
+                };
+                Ok(json!({ "cloudFunction": function }))
+            }
+            Some(TargetKind::Webhook) => Ok(json!({ "httpEndpoint": { "uri": target.arn } })),
+            Some(TargetKind::Queue) => Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: "NotSupported".to_string(),
+                message: "Eventarc triggers cannot deliver to a Pub/Sub queue; target a function or webhook".to_string(),
+            }),
+            None => Err(CloudError::Validation(format!("target {} needs a kind", target.id))),
+        }
+    }
+}
+
+/// Eventarc filters of a pattern rule; `detail-type` is the CloudEvents `type` attribute.
+fn event_filters(rule: &EventRule) -> CloudResult<Vec<EventFilter>> {
+    let mut filters = Vec::new();
+    for (field, mut values) in rule.match_filters()? {
+        if values.len() > 1 {
+            return Err(CloudError::Validation(format!(
+                "Eventarc matches one value per attribute, but {} lists {}", field, values.len()
+            )));
+        }
+        let attribute = if field == "detail-type" { "type".to_string() } else { field };
+        filters.push(EventFilter { attribute, value: values.remove(0) });
+    }
+    if !filters.iter().any(|f| f.attribute == "type") {
+        return Err(CloudError::Validation("Eventarc triggers must filter on the event type".to_string()));
+    }
+    Ok(filters)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -84,9 +123,18 @@ struct EventFilter {
 struct TriggerDestination {
     #[serde(rename = "cloudRun")]
     cloud_run: Option<CloudRunDestination>,
+    #[serde(rename = "cloudFunction")]
+    cloud_function: Option<String>,
+    #[serde(rename = "httpEndpoint")]
+    http_endpoint: Option<HttpEndpoint>,
     workflow: Option<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct HttpEndpoint {
+    uri: String,
+}
+
 #[derive(Serialize, Deserialize)]
 struct CloudRunDestination {
     service: String,
@@ -241,10 +289,55 @@ impl EventBus for GcpEventarc {
         Err(CloudError::Provider {
             provider: "gcp".to_string(),
             code: "NotSupported".to_string(),
-            message: "GCP Triggers require a destination at creation. Use a higher level abstraction.".to_string(),
+            message: "GCP Triggers require a destination at creation. Use create_rule.".to_string(),
         })
     }
 
+    async fn create_rule(
+        &self,
+        _bus_name: &str,
+        rule: EventRule,
+        targets: Vec<EventTarget>,
+    ) -> CloudResult<String> {
+        let [target] = targets.as_slice() else {
+            return Err(CloudError::Validation("an Eventarc trigger delivers to exactly one target".to_string()));
+        };
+        let body = json!({
+            "eventFilters": event_filters(&rule)?,
+            "destination": self.destination(target)?,
+        });
+        let token = self.token().await?;
+        let name = format!("projects/{}/locations/{}/triggers/{}", self.project_id, self.region, rule.name);
+
+        let url = format!("{}/triggers?triggerId={}", self.base_url(), rule.name);
+        let mut resp = self.client.post(&url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+        if resp.status() == StatusCode::CONFLICT {
+            let url = format!("https://eventarc.googleapis.com/v1/{}?updateMask=eventFilters,destination", name);
+            resp = self.client.patch(&url)
+                .bearer_auth(&token)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+        }
+
+        if !resp.status().is_success() {
+            return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: resp.status().as_u16().to_string(),
+                message: resp.text().await.unwrap_or_default(),
+            });
+        }
+        // The response is a long-running operation; the trigger is ready once it completes
+        Ok(name)
+    }
+
     async fn delete_rule(&self, _bus_name: &str, rule_name: &str) -> CloudResult<()> {
         let token = self.token().await?;
         let resource_name = if rule_name.contains('/') {
@@ -324,8 +417,10 @@ impl EventBus for GcpEventarc {
         let resource_name = format!("projects/{}/locations/{}/triggers/{}", self.project_id, self.region, rule_name);
         let url = format!("https://eventarc.googleapis.com/v1/{}?updateMask=destination", resource_name);
         
-        let body = json!({
-            "destination": {
+        let destination = if target.kind.is_some() {
+            self.destination(target)?
+        } else {
+            json!({
                 // Infer type from ARN?
                 // If ARN starts with 'arn:aws:lambda' -> not supported.
                 // Expecting 'projects/.../services/...' for Cloud Run or '.../workflows/...'
@@ -334,8 +429,9 @@ impl EventBus for GcpEventarc {
                     "service": target.arn.split('/').next_back().unwrap_or("unknown"),
                     "region": self.region
                 }
-            }
-        });
+            })
+        };
+        let body = json!({ "destination": destination });
 
         let _resp = self.client.patch(&url)
             .bearer_auth(&token)
@@ -377,8 +473,32 @@ impl EventBus for GcpEventarc {
             if let Some(cr) = d.cloud_run {
                 targets.push(EventTarget::new("default", cr.service));
             }
+            if let Some(function) = d.cloud_function {
+                targets.push(EventTarget::function("default", function));
+            }
+            if let Some(endpoint) = d.http_endpoint {
+                targets.push(EventTarget::webhook("default", endpoint.uri));
+            }
         }
         Ok(targets)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filters() {
+        let rule = EventRule::pattern("orders", json!({"detail-type": ["OrderCreated"], "source": ["myapp.orders"]}));
+        let filters = event_filters(&rule).unwrap();
+        assert!(filters.iter().any(|f| f.attribute == "type" && f.value == "OrderCreated"));
+        assert!(filters.iter().any(|f| f.attribute == "source" && f.value == "myapp.orders"));
+
+        let several = EventRule::pattern("orders", json!({"type": ["OrderCreated", "OrderShipped"]}));
+        assert!(matches!(event_filters(&several), Err(CloudError::Validation(_))));
+        let untyped = EventRule::pattern("orders", json!({"source": ["myapp.orders"]}));
+        assert!(matches!(event_filters(&untyped), Err(CloudError::Validation(_))));
+    }
+}
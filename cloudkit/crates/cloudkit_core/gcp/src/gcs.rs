@@ -2,7 +2,10 @@
 
 use async_trait::async_trait;
 use bytes::Bytes;
-use cloudkit_api::{GetOptions, ListOptions, ObjectStorage, PutOptions};
+use cloudkit_api::{
+    CopyOptions, EncryptionOptions, GetOptions, LifecycleRule, LifecycleTransition, ListOptions,
+    ObjectStorage, PutOptions, StorageTier,
+};
 use cloudkit_spi::{
     BucketMetadata, CloudError, CloudResult, ListResult, ObjectMetadata, PaginationToken,
 };
@@ -12,11 +15,20 @@ use google_cloud_storage::http::buckets::delete::DeleteBucketRequest;
 use google_cloud_storage::http::buckets::insert::{
     BucketCreationConfig, InsertBucketParam, InsertBucketRequest,
 };
+use google_cloud_storage::http::buckets::lifecycle::rule::{
+    Action, ActionType, Condition, StorageClass,
+};
+use google_cloud_storage::http::buckets::lifecycle::Rule;
 use google_cloud_storage::http::buckets::list::ListBucketsRequest;
+use google_cloud_storage::http::buckets::patch::{BucketPatchConfig, PatchBucketRequest};
+use google_cloud_storage::http::buckets::Lifecycle;
 use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
 use google_cloud_storage::http::objects::download::Range;
 use google_cloud_storage::http::objects::get::GetObjectRequest;
 use google_cloud_storage::http::objects::list::ListObjectsRequest;
+use google_cloud_storage::http::objects::patch::PatchObjectRequest;
+use google_cloud_storage::http::objects::rewrite::RewriteObjectRequest;
+use google_cloud_storage::http::objects::{Encryption, Object};
 use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
 use google_cloud_storage::sign::SignedURLMethod;
 use google_cloud_storage::sign::SignedURLOptions;
@@ -48,6 +60,112 @@ impl GcsStorage {
         }
     }
 
+    /// Customer-supplied key of an encryption option.
+    fn customer_key(encryption: Option<&EncryptionOptions>) -> Option<Encryption> {
+        use base64::Engine;
+        use sha2::Digest;
+        let key = encryption?.customer_key()?;
+        let base64 = base64::engine::general_purpose::STANDARD;
+        Some(Encryption {
+            encryption_algorithm: "AES256".to_string(),
+            encryption_key: base64.encode(key),
+            encryption_key_sha256: base64.encode(sha2::Sha256::digest(key)),
+        })
+    }
+
+    /// Cloud KMS key of an encryption option.
+    fn kms_key_name(encryption: Option<&EncryptionOptions>) -> Option<String> {
+        match encryption? {
+            EncryptionOptions::CustomerManaged { key_id } => Some(key_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// GCS rules of a lifecycle rule, one per action. GCS keeps neither rule
+    /// IDs nor disabled rules, so disabled rules have none.
+    fn to_gcs_rules(rule: &LifecycleRule) -> Vec<Rule> {
+        if !rule.enabled {
+            return Vec::new();
+        }
+        let condition = |age: u32| Condition {
+            age: Some(age as i32),
+            matches_prefix: rule.prefix.clone().map(|prefix| vec![prefix]),
+            ..Default::default()
+        };
+        let mut rules: Vec<Rule> = rule
+            .transitions
+            .iter()
+            .map(|transition| Rule {
+                action: Some(Action {
+                    r#type: ActionType::SetStorageClass,
+                    storage_class: Some(match transition.tier {
+                        StorageTier::InfrequentAccess => StorageClass::Nearline,
+                        StorageTier::Archive => StorageClass::Coldline,
+                        StorageTier::DeepArchive => StorageClass::Archive,
+                    }),
+                }),
+                condition: Some(condition(transition.after_days)),
+            })
+            .collect();
+        if let Some(days) = rule.expiration_days {
+            rules.push(Rule {
+                action: Some(Action {
+                    r#type: ActionType::Delete,
+                    storage_class: None,
+                }),
+                condition: Some(condition(days)),
+            });
+        }
+        rules
+    }
+
+    /// Lifecycle rules of GCS rules, one per key prefix with the prefix as
+    /// ID. Rules on anything but the age of objects are left out.
+    fn from_gcs_rules(rules: &[Rule]) -> Vec<LifecycleRule> {
+        let mut merged: Vec<LifecycleRule> = Vec::new();
+        for rule in rules {
+            let (Some(action), Some(condition)) = (&rule.action, &rule.condition) else {
+                continue;
+            };
+            let Some(age) = condition.age else { continue };
+            let prefix = condition
+                .matches_prefix
+                .as_ref()
+                .and_then(|prefixes| prefixes.first().cloned());
+            let index = match merged.iter().position(|r| r.prefix == prefix) {
+                Some(index) => index,
+                None => {
+                    let id = prefix.clone().unwrap_or_else(|| "all-objects".to_string());
+                    let mut lifecycle_rule = LifecycleRule::new(id);
+                    lifecycle_rule.prefix = prefix;
+                    merged.push(lifecycle_rule);
+                    merged.len() - 1
+                }
+            };
+            let lifecycle_rule = &mut merged[index];
+            match (&action.r#type, &action.storage_class) {
+                (ActionType::Delete, _) => lifecycle_rule.expiration_days = Some(age as u32),
+                (ActionType::SetStorageClass, Some(class)) => {
+                    let tier = match class {
+                        StorageClass::Nearline => StorageTier::InfrequentAccess,
+                        StorageClass::Coldline => StorageTier::Archive,
+                        StorageClass::Archive => StorageTier::DeepArchive,
+                        _ => continue,
+                    };
+                    lifecycle_rule.transitions.push(LifecycleTransition {
+                        after_days: age as u32,
+                        tier,
+                    });
+                }
+                _ => {}
+            }
+        }
+        for rule in &mut merged {
+            rule.transitions.sort_by_key(|transition| transition.after_days);
+        }
+        merged
+    }
+
     fn to_utc(ts: time::OffsetDateTime) -> chrono::DateTime<chrono::Utc> {
         chrono::DateTime::from_timestamp(ts.unix_timestamp(), ts.nanosecond()).unwrap_or_default()
     }
@@ -137,7 +255,7 @@ impl ObjectStorage for GcsStorage {
         bucket: &str,
         key: &str,
         data: &[u8],
-        _options: PutOptions,
+        options: PutOptions,
     ) -> CloudResult<()> {
         let upload_type = UploadType::Simple(Media {
             name: key.to_string().into(),
@@ -149,6 +267,8 @@ impl ObjectStorage for GcsStorage {
             .upload_object(
                 &UploadObjectRequest {
                     bucket: bucket.to_string(),
+                    kms_key_name: Self::kms_key_name(options.encryption.as_ref()),
+                    encryption: Self::customer_key(options.encryption.as_ref()),
                     ..Default::default()
                 },
                 data.to_vec(),
@@ -168,7 +288,7 @@ impl ObjectStorage for GcsStorage {
         &self,
         bucket: &str,
         key: &str,
-        _options: GetOptions,
+        options: GetOptions,
     ) -> CloudResult<Bytes> {
         let result = self
             .client
@@ -176,6 +296,7 @@ impl ObjectStorage for GcsStorage {
                 &GetObjectRequest {
                     bucket: bucket.to_string(),
                     object: key.to_string(),
+                    encryption: Self::customer_key(options.encryption.as_ref()),
                     ..Default::default()
                 },
                 &Range::default(),
@@ -207,8 +328,9 @@ impl ObjectStorage for GcsStorage {
             },
             etag: Some(obj.etag),
             content_type: obj.content_type,
+            cache_control: obj.cache_control,
             storage_class: obj.storage_class,
-            metadata: std::collections::HashMap::new(),
+            metadata: obj.metadata.unwrap_or_default(),
         })
     }
 
@@ -237,19 +359,62 @@ impl ObjectStorage for GcsStorage {
         dest_bucket: &str,
         dest_key: &str,
     ) -> CloudResult<()> {
-        self.client
-            .rewrite_object(
-                &google_cloud_storage::http::objects::rewrite::RewriteObjectRequest {
-                    destination_bucket: dest_bucket.to_string(),
-                    destination_object: dest_key.to_string(),
-                    source_bucket: source_bucket.to_string(),
-                    source_object: source_key.to_string(),
-                    ..Default::default()
-                },
-            )
-            .await
-            .map(|_| ())
-            .map_err(Self::map_err)
+        self.copy_object_with_options(
+            source_bucket,
+            source_key,
+            dest_bucket,
+            dest_key,
+            CopyOptions::default(),
+        )
+        .await
+    }
+
+    async fn copy_object_with_options(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        options: CopyOptions,
+    ) -> CloudResult<()> {
+        let mut request = RewriteObjectRequest {
+            destination_bucket: dest_bucket.to_string(),
+            destination_object: dest_key.to_string(),
+            source_bucket: source_bucket.to_string(),
+            source_object: source_key.to_string(),
+            destination_kms_key_name: Self::kms_key_name(options.encryption.as_ref()),
+            copy_source_encryption: Self::customer_key(options.source_encryption.as_ref()),
+            encryption: Self::customer_key(options.encryption.as_ref()),
+            ..Default::default()
+        };
+        // Large objects, or copies across locations or storage classes, take
+        // several calls that each resume from the previous rewrite token
+        loop {
+            let response = self
+                .client
+                .rewrite_object(&request)
+                .await
+                .map_err(Self::map_err)?;
+            if response.done {
+                return Ok(());
+            }
+            request.rewrite_token = response.rewrite_token;
+        }
+    }
+
+    async fn move_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> CloudResult<()> {
+        if (source_bucket, source_key) == (dest_bucket, dest_key) {
+            return Ok(());
+        }
+        self.copy_object(source_bucket, source_key, dest_bucket, dest_key)
+            .await?;
+        self.delete_object(source_bucket, source_key).await
     }
 
     async fn object_exists(&self, bucket: &str, key: &str) -> CloudResult<bool> {
@@ -290,6 +455,7 @@ impl ObjectStorage for GcsStorage {
                 },
                 etag: Some(o.etag),
                 content_type: o.content_type,
+                cache_control: o.cache_control,
                 storage_class: o.storage_class,
                 metadata: std::collections::HashMap::new(),
             })
@@ -304,6 +470,96 @@ impl ObjectStorage for GcsStorage {
         ))
     }
 
+    async fn set_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        options: PutOptions,
+    ) -> CloudResult<()> {
+        self.client
+            .patch_object(&PatchObjectRequest {
+                bucket: bucket.to_string(),
+                object: key.to_string(),
+                metadata: Some(Object {
+                    content_type: options.content_type,
+                    cache_control: options.cache_control,
+                    content_encoding: options.content_encoding,
+                    metadata: Some(options.metadata),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ())
+            .map_err(Self::map_err)
+    }
+
+    async fn get_object_tags(
+        &self,
+        _bucket: &str,
+        _key: &str,
+    ) -> CloudResult<std::collections::HashMap<String, String>> {
+        Err(CloudError::Provider {
+            provider: "gcp".to_string(),
+            code: "NotSupported".to_string(),
+            message: "GCS objects have no tags. Use custom metadata.".to_string(),
+        })
+    }
+
+    async fn set_object_tags(
+        &self,
+        _bucket: &str,
+        _key: &str,
+        _tags: std::collections::HashMap<String, String>,
+    ) -> CloudResult<()> {
+        Err(CloudError::Provider {
+            provider: "gcp".to_string(),
+            code: "NotSupported".to_string(),
+            message: "GCS objects have no tags. Use custom metadata.".to_string(),
+        })
+    }
+
+    async fn get_bucket_lifecycle(&self, bucket: &str) -> CloudResult<Vec<LifecycleRule>> {
+        let bucket = self
+            .client
+            .get_bucket(&google_cloud_storage::http::buckets::get::GetBucketRequest {
+                bucket: bucket.to_string(),
+                ..Default::default()
+            })
+            .await
+            .map_err(Self::map_err)?;
+        Ok(bucket
+            .lifecycle
+            .map(|lifecycle| Self::from_gcs_rules(&lifecycle.rule))
+            .unwrap_or_default())
+    }
+
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket: &str,
+        rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        // An empty rule list removes the lifecycle configuration
+        let lifecycle = Lifecycle {
+            rule: rules.iter().flat_map(Self::to_gcs_rules).collect(),
+        };
+        self.client
+            .patch_bucket(&PatchBucketRequest {
+                bucket: bucket.to_string(),
+                metadata: Some(BucketPatchConfig {
+                    lifecycle: Some(lifecycle),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .map(|_| ())
+            .map_err(Self::map_err)
+    }
+
     async fn presigned_get_url(
         &self,
         bucket: &str,
@@ -376,5 +632,20 @@ mod tests {
             let _ = storage.list_buckets().await;
         }
     }
+
+    #[test]
+    fn test_lifecycle_rules_round_trip() {
+        let rule = LifecycleRule::new("logs/")
+            .prefix("logs/")
+            .transition(30, StorageTier::InfrequentAccess)
+            .transition(90, StorageTier::DeepArchive)
+            .expire_after_days(365);
+        let gcs_rules = GcsStorage::to_gcs_rules(&rule);
+        assert_eq!(gcs_rules.len(), 3);
+        assert_eq!(GcsStorage::from_gcs_rules(&gcs_rules), vec![rule]);
+
+        let disabled = LifecycleRule::new("tmp").expire_after_days(1).enabled(false);
+        assert!(GcsStorage::to_gcs_rules(&disabled).is_empty());
+    }
 }
 
@@ -5,7 +5,7 @@ use cloudkit_api::{
     AuthChallenge, AuthResult, ChallengeType, CreateUserOptions, IdentityProvider,
     InitiateAuthResult, User, UserGroup, UserStatus,
 };
-use cloudkit_spi::{CloudError, CloudResult, Metadata};
+use cloudkit_spi::{AuthError, CloudError, CloudResult, Metadata};
 use cloudkit_spi::CloudContext;
 use google_cloud_auth::token_source::TokenSource;
 use reqwest::Client;
@@ -48,6 +48,106 @@ impl GcpIdentity {
     fn base_url(&self) -> String {
         "https://identitytoolkit.googleapis.com/v1".to_string()
     }
+
+    /// Web API key for the end-user sign-in endpoints, which do not take OAuth tokens.
+    fn api_key(&self) -> CloudResult<String> {
+        self._context.config.parameters.get("gcp.identity.api_key")
+            .cloned()
+            .or_else(|| std::env::var("GCP_IDENTITY_API_KEY").ok())
+            .ok_or_else(|| CloudError::Config("Missing Identity Platform API key. Set 'gcp.identity.api_key' in config or 'GCP_IDENTITY_API_KEY' env var.".into()))
+    }
+
+    async fn lookup(&self, username: &str) -> CloudResult<FirebaseUser> {
+        let token = self.token().await?;
+        let url = format!("{}/projects/{}/accounts:lookup", self.base_url(), self.project_id);
+
+        let body = json!({
+            "localId": [username]
+        });
+
+        let resp = self.client.post(&url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+        if !resp.status().is_success() {
+            return Err(CloudError::NotFound {
+                resource_type: "User".to_string(),
+                resource_id: username.to_string(),
+            });
+        }
+
+        let account_info: GetAccountInfoResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
+
+        account_info.users
+            .and_then(|users| users.into_iter().next())
+            .ok_or_else(|| CloudError::NotFound {
+                resource_type: "User".to_string(),
+                resource_id: username.to_string(),
+            })
+    }
+
+    async fn update_account(&self, body: serde_json::Value) -> CloudResult<()> {
+        let token = self.token().await?;
+        let url = format!("{}/projects/{}/accounts:update", self.base_url(), self.project_id);
+
+        let resp = self.client.post(&url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+        if !resp.status().is_success() {
+            return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: resp.status().as_u16().to_string(),
+                message: resp.text().await.unwrap_or_default(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Add or remove `group` in the `groups` claim of a user's custom attributes.
+    async fn set_group_membership(&self, username: &str, group_name: &str, member: bool) -> CloudResult<()> {
+        let user = self.lookup(username).await?;
+        let custom_attributes = with_group(user.custom_attributes.as_deref(), group_name, member)?;
+        self.update_account(json!({
+            "localId": username,
+            "customAttributes": custom_attributes,
+        })).await
+    }
+}
+
+/// Claim of the custom attributes holding group membership, as Identity Platform has no groups.
+const GROUPS_CLAIM: &str = "groups";
+
+/// Groups listed in the custom attributes JSON of a user.
+fn groups_claim(custom_attributes: Option<&str>) -> Vec<String> {
+    custom_attributes
+        .and_then(|attrs| serde_json::from_str::<serde_json::Value>(attrs).ok())
+        .and_then(|attrs| attrs.get(GROUPS_CLAIM).cloned())
+        .and_then(|groups| serde_json::from_value(groups).ok())
+        .unwrap_or_default()
+}
+
+/// Custom attributes JSON with `group` added to or removed from the groups claim,
+/// keeping the other claims.
+fn with_group(custom_attributes: Option<&str>, group: &str, member: bool) -> CloudResult<String> {
+    let mut attrs: serde_json::Map<String, serde_json::Value> = match custom_attributes {
+        Some(attrs) if !attrs.is_empty() => serde_json::from_str(attrs).map_err(|e| CloudError::Serialization(e.to_string()))?,
+        _ => serde_json::Map::new(),
+    };
+    let mut groups = groups_claim(custom_attributes);
+    groups.retain(|g| g != group);
+    if member {
+        groups.push(group.to_string());
+    }
+    attrs.insert(GROUPS_CLAIM.to_string(), json!(groups));
+    serde_json::to_string(&attrs).map_err(|e| CloudError::Serialization(e.to_string()))
 }
 
 #[derive(Serialize, Deserialize)]
@@ -71,6 +171,17 @@ struct GetAccountInfoResponse {
     users: Option<Vec<FirebaseUser>>,
 }
 
+#[derive(Deserialize)]
+struct SignInResponse {
+    #[serde(rename = "idToken")]
+    id_token: String,
+    #[serde(rename = "refreshToken")]
+    refresh_token: Option<String>,
+    /// Seconds, as a string
+    #[serde(rename = "expiresIn")]
+    expires_in: Option<String>,
+}
+
 #[derive(Deserialize)]
 struct ListUsersResponse {
     users: Option<Vec<FirebaseUser>>,
@@ -113,7 +224,9 @@ impl IdentityProvider for GcpIdentity {
         let token = self.token().await?;
         let url = format!("{}/projects/{}/accounts", self.base_url(), self.project_id);
 
+        // The username is the account's local ID, which every other operation looks it up by
         let mut body = json!({
+            "localId": username,
             "displayName": username,
         });
 
@@ -146,36 +259,7 @@ impl IdentityProvider for GcpIdentity {
     }
 
     async fn get_user(&self, username: &str) -> CloudResult<User> {
-        let token = self.token().await?;
-        let url = format!("{}/projects/{}/accounts:lookup", self.base_url(), self.project_id);
-
-        let body = json!({
-            "localId": [username]
-        });
-
-        let resp = self.client.post(&url)
-            .bearer_auth(&token)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
-
-        if !resp.status().is_success() {
-            return Err(CloudError::NotFound {
-                resource_type: "User".to_string(),
-                resource_id: username.to_string(),
-            });
-        }
-
-        let account_info: GetAccountInfoResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
-        
-        let firebase_user = account_info.users
-            .and_then(|users| users.into_iter().next())
-            .ok_or_else(|| CloudError::NotFound {
-                resource_type: "User".to_string(),
-                resource_id: username.to_string(),
-            })?;
-
+        let firebase_user = self.lookup(username).await?;
         Ok(self.firebase_user_to_user(firebase_user))
     }
 
@@ -323,6 +407,20 @@ impl IdentityProvider for GcpIdentity {
         self.list_users(Some(100)).await
     }
 
+    async fn set_password(&self, username: &str, password: &str, permanent: bool) -> CloudResult<()> {
+        if !permanent {
+            return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: "NotSupported".to_string(),
+                message: "Identity Platform cannot require a password change at the next sign in; set a permanent password.".to_string(),
+            });
+        }
+        self.update_account(json!({
+            "localId": username,
+            "password": password,
+        })).await
+    }
+
     // --- Authentication ---
 
     async fn initiate_auth(
@@ -339,6 +437,49 @@ impl IdentityProvider for GcpIdentity {
         }))
     }
 
+    async fn admin_initiate_auth(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> CloudResult<InitiateAuthResult> {
+        // Password sign-in is by email, so resolve the account first
+        let email = self.lookup(username).await?.email.ok_or_else(|| CloudError::Validation(
+            format!("User '{}' has no email to sign in with", username),
+        ))?;
+        let url = format!("{}/accounts:signInWithPassword", self.base_url());
+
+        let resp = self.client.post(&url)
+            .query(&[("key", self.api_key()?)])
+            .json(&json!({
+                "email": email,
+                "password": password,
+                "returnSecureToken": true,
+            }))
+            .send()
+            .await
+            .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+        if resp.status() == reqwest::StatusCode::BAD_REQUEST {
+            return Err(CloudError::Auth(AuthError::InvalidCredentials(resp.text().await.unwrap_or_default())));
+        }
+        if !resp.status().is_success() {
+            return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: resp.status().as_u16().to_string(),
+                message: resp.text().await.unwrap_or_default(),
+            });
+        }
+
+        let sign_in: SignInResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
+        Ok(InitiateAuthResult::Success(AuthResult {
+            access_token: sign_in.id_token.clone(),
+            id_token: Some(sign_in.id_token),
+            refresh_token: sign_in.refresh_token,
+            expires_in: sign_in.expires_in.and_then(|secs| secs.parse().ok()).unwrap_or(3600),
+            token_type: "Bearer".to_string(),
+        }))
+    }
+
     async fn respond_to_challenge(
         &self,
         _challenge_name: ChallengeType,
@@ -438,24 +579,25 @@ impl IdentityProvider for GcpIdentity {
         Ok(vec![])
     }
 
-    async fn add_user_to_group(&self, _username: &str, _group_name: &str) -> CloudResult<()> {
-        Err(CloudError::Provider {
-            provider: "gcp".to_string(),
-            code: "NotSupported".to_string(),
-            message: "Firebase Auth does not support groups natively. Use custom claims.".to_string(),
-        })
+    // Membership is kept in the `groups` custom claim, which ends up in the user's ID tokens
+
+    async fn add_user_to_group(&self, username: &str, group_name: &str) -> CloudResult<()> {
+        self.set_group_membership(username, group_name, true).await
     }
 
-    async fn remove_user_from_group(&self, _username: &str, _group_name: &str) -> CloudResult<()> {
-        Err(CloudError::Provider {
-            provider: "gcp".to_string(),
-            code: "NotSupported".to_string(),
-            message: "Firebase Auth does not support groups natively.".to_string(),
-        })
+    async fn remove_user_from_group(&self, username: &str, group_name: &str) -> CloudResult<()> {
+        self.set_group_membership(username, group_name, false).await
     }
 
-    async fn list_user_groups(&self, _username: &str) -> CloudResult<Vec<UserGroup>> {
-        Ok(vec![])
+    async fn list_user_groups(&self, username: &str) -> CloudResult<Vec<UserGroup>> {
+        let user = self.lookup(username).await?;
+        Ok(groups_claim(user.custom_attributes.as_deref()).into_iter().map(|name| UserGroup {
+            name,
+            description: None,
+            role_arn: None,
+            precedence: None,
+            created_at: None,
+        }).collect())
     }
 
     async fn list_users_in_group(&self, _group_name: &str) -> CloudResult<Vec<User>> {
@@ -468,6 +610,26 @@ mod tests {
     use super::*;
     use cloudkit_spi::ProviderType;
 
+    #[test]
+    fn test_group_claims() {
+        assert!(groups_claim(None).is_empty());
+        assert!(groups_claim(Some(r#"{"role": "admin"}"#)).is_empty());
+
+        let added = with_group(Some(r#"{"role": "admin"}"#), "testers", true).unwrap();
+        assert_eq!(groups_claim(Some(&added)), vec!["testers"]);
+        assert!(added.contains(r#""role":"admin""#));
+
+        // Adding twice keeps a single entry
+        let added = with_group(Some(&added), "testers", true).unwrap();
+        let added = with_group(Some(&added), "admins", true).unwrap();
+        assert_eq!(groups_claim(Some(&added)), vec!["testers", "admins"]);
+
+        let removed = with_group(Some(&added), "testers", false).unwrap();
+        assert_eq!(groups_claim(Some(&removed)), vec!["admins"]);
+        assert_eq!(groups_claim(Some(&with_group(None, "x", true).unwrap())), vec!["x"]);
+        assert!(with_group(Some("not json"), "x", true).is_err());
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_identity_flow() {
@@ -3,8 +3,9 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use cloudkit_api::{
-    AlarmConfig, AlarmState, LogEvent, LoggingService, MetricDataPoint, MetricDatum, MetricQuery,
-    MetricResult, MetricUnit, MetricsService,
+    AlarmConfig, AlarmState, LogCursor, LogEvent, LogLevel, LogQuery, LogStream, LogTail,
+    LoggingService, MetricDataPoint, MetricDatum, MetricQuery, MetricRangeQuery, MetricResult,
+    MetricStatistic, MetricUnit, MetricsService,
 };
 use cloudkit_spi::{CloudError, CloudResult};
 use cloudkit_spi::CloudContext;
@@ -12,9 +13,11 @@ use google_cloud_auth::token_source::TokenSource;
 use reqwest::Client;
 use serde::Deserialize;
 use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// Google Cloud Monitor implementation.
+#[derive(Clone)]
 pub struct GcpMonitor {
     _context: Arc<CloudContext>,
     auth: Arc<Box<dyn TokenSource>>,
@@ -53,6 +56,152 @@ impl GcpMonitor {
     fn logging_base_url(&self) -> String {
         "https://logging.googleapis.com/v2".to_string()
     }
+
+    /// Entries of a log from `start` on, before `end` if given, with their insert IDs and
+    /// oldest first.
+    async fn list_log_entries(
+        &self,
+        group: &str,
+        streams: &[String],
+        contains: Option<&str>,
+        start: DateTime<Utc>,
+        end: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> CloudResult<Vec<(String, LogEvent)>> {
+        let url = format!("{}/entries:list", self.logging_base_url());
+        let mut filter = format!(
+            "logName=\"projects/{}/logs/{}\" AND timestamp>=\"{}\"",
+            self.project_id, group, start.to_rfc3339()
+        );
+        if let Some(end) = end {
+            filter.push_str(&format!(" AND timestamp<\"{}\"", end.to_rfc3339()));
+        }
+        if !streams.is_empty() {
+            let streams: Vec<_> = streams.iter().map(|stream| format!("\"{}\"", stream)).collect();
+            filter.push_str(&format!(" AND labels.stream=({})", streams.join(" OR ")));
+        }
+        if let Some(text) = contains {
+            filter.push_str(&format!(" AND textPayload:\"{}\"", text.replace('"', "\\\"")));
+        }
+
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let page_size = limit.map_or(1000, |limit| limit.saturating_sub(events.len()).min(1000));
+            let mut body = json!({
+                "resourceNames": [format!("projects/{}", self.project_id)],
+                "filter": filter,
+                "orderBy": "timestamp asc",
+                "pageSize": page_size,
+            });
+            if let Some(page_token) = &page_token {
+                body["pageToken"] = json!(page_token);
+            }
+            let resp = self.client.post(&url)
+                .bearer_auth(self.token().await?)
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+            if !resp.status().is_success() {
+                return Err(CloudError::Provider {
+                    provider: "gcp".to_string(),
+                    code: resp.status().as_u16().to_string(),
+                    message: resp.text().await.unwrap_or_default(),
+                });
+            }
+
+            let page: LogEntriesResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
+            for entry in page.entries {
+                let timestamp = match entry.timestamp.as_deref().map(DateTime::parse_from_rfc3339) {
+                    Some(Ok(timestamp)) => timestamp.with_timezone(&Utc),
+                    _ => continue,
+                };
+                let message = match (entry.text_payload, entry.json_payload) {
+                    (Some(text), _) => text,
+                    (None, Some(payload)) => payload.to_string(),
+                    (None, None) => String::new(),
+                };
+                let level = match entry.severity.as_deref() {
+                    Some(severity) if severity != "DEFAULT" => gcp_log_level(severity),
+                    _ => entry.labels.get("level").map_or(LogLevel::Info, |level| gcp_log_level(level)),
+                };
+                let event = LogEvent { message, timestamp, level, fields: entry.labels };
+                events.push((entry.insert_id, event));
+            }
+
+            page_token = page.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() || limit.is_some_and(|limit| events.len() >= limit) {
+                break;
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Log level of a Cloud Logging severity, or of the `level` label `put_log_events` writes.
+fn gcp_log_level(name: &str) -> LogLevel {
+    match name.to_ascii_uppercase().as_str() {
+        "TRACE" => LogLevel::Trace,
+        "DEBUG" => LogLevel::Debug,
+        "WARNING" | "WARN" => LogLevel::Warn,
+        "ERROR" => LogLevel::Error,
+        "CRITICAL" | "ALERT" | "EMERGENCY" | "FATAL" => LogLevel::Fatal,
+        _ => LogLevel::Info,
+    }
+}
+
+#[derive(Deserialize)]
+struct LogEntriesResponse {
+    #[serde(default)]
+    entries: Vec<LogEntry>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LogEntry {
+    #[serde(rename = "insertId", default)]
+    insert_id: String,
+    timestamp: Option<String>,
+    #[serde(rename = "textPayload")]
+    text_payload: Option<String>,
+    #[serde(rename = "jsonPayload")]
+    json_payload: Option<serde_json::Value>,
+    severity: Option<String>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+/// Cloud Monitoring label key for a dimension: lowercase snake case starting with a letter,
+/// e.g. `instance_id` for `InstanceId`.
+fn gcp_label_key(dimension: &str) -> String {
+    let mut key = String::new();
+    let mut previous_lower = false;
+    for c in dimension.chars() {
+        if c.is_ascii_uppercase() && previous_lower {
+            key.push('_');
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        key.push(if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' });
+    }
+    if !key.starts_with(|c: char| c.is_ascii_lowercase()) {
+        key.insert_str(0, "label_");
+    }
+    key
+}
+
+/// Aligner and cross-series reducer that compute a statistic per period over every
+/// matching series.
+fn gcp_aggregation(statistic: MetricStatistic) -> (&'static str, &'static str) {
+    match statistic {
+        MetricStatistic::Average => ("ALIGN_MEAN", "REDUCE_MEAN"),
+        MetricStatistic::Sum => ("ALIGN_SUM", "REDUCE_SUM"),
+        MetricStatistic::Minimum => ("ALIGN_MIN", "REDUCE_MIN"),
+        MetricStatistic::Maximum => ("ALIGN_MAX", "REDUCE_MAX"),
+        MetricStatistic::SampleCount => ("ALIGN_COUNT", "REDUCE_SUM"),
+    }
 }
 
 #[derive(Deserialize)]
@@ -73,6 +222,8 @@ struct MetricDescriptor {
 struct TimeSeriesResponse {
     #[serde(rename = "timeSeries")]
     time_series: Option<Vec<TimeSeries>>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -89,7 +240,7 @@ struct Point {
 #[derive(Deserialize)]
 struct Interval {
     #[serde(rename = "endTime")]
-    _end_time: String,
+    end_time: String,
 }
 
 #[derive(Deserialize)]
@@ -113,7 +264,7 @@ impl MetricsService for GcpMonitor {
             // Map dimensions to labels
             let mut labels = serde_json::Map::new();
             for (k, v) in datum.dimensions {
-                labels.insert(k, json!(v));
+                labels.insert(gcp_label_key(&k), json!(v));
             }
 
             json!({
@@ -219,7 +370,7 @@ impl MetricsService for GcpMonitor {
                         // `MetricDataPoint`: { timestamp: DateTime<Utc>, value: f64, ... }
                         
                         // We need to parse p.interval._end_time
-                        if let Ok(_ts) = DateTime::parse_from_rfc3339(&p.interval._end_time) {
+                        if let Ok(_ts) = DateTime::parse_from_rfc3339(&p.interval.end_time) {
                              // data_points.push(MetricDataPoint { ... });
                              // For now, I'll allow compilation but logic is partial.
                              // Actually, CloudKit `MetricResult` has `data_points` field.
@@ -243,6 +394,83 @@ impl MetricsService for GcpMonitor {
         })
     }
 
+    async fn query_metrics(
+        &self,
+        namespace: &str,
+        query: MetricRangeQuery,
+    ) -> CloudResult<MetricResult> {
+        query.validate()?;
+        let token = self.token().await?;
+        let url = format!("{}/timeSeries", self.monitoring_base_url());
+
+        let mut filter = format!("metric.type = \"custom.googleapis.com/{}/{}\"", namespace, query.name);
+        let mut dimensions: Vec<_> = query.dimensions.iter().collect();
+        dimensions.sort();
+        for (k, v) in dimensions {
+            filter.push_str(&format!(" AND metric.labels.{} = \"{}\"", gcp_label_key(k), v.replace('"', "\\\"")));
+        }
+        // Alignment periods are at least a minute
+        let period = format!("{}s", query.period_seconds.max(60));
+        let (aligner, reducer) = gcp_aggregation(query.statistic);
+        let start = query.range.start.to_rfc3339();
+        let end = query.range.end.to_rfc3339();
+
+        let mut data_points = vec![];
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut params = vec![
+                ("filter", filter.as_str()),
+                ("interval.startTime", start.as_str()),
+                ("interval.endTime", end.as_str()),
+                ("aggregation.alignmentPeriod", period.as_str()),
+                ("aggregation.perSeriesAligner", aligner),
+                ("aggregation.crossSeriesReducer", reducer),
+            ];
+            if let Some(page_token) = &page_token {
+                params.push(("pageToken", page_token.as_str()));
+            }
+            let resp = self.client.get(&url)
+                .bearer_auth(&token)
+                .query(&params)
+                .send()
+                .await
+                .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+            if !resp.status().is_success() {
+                return Err(CloudError::Provider {
+                    provider: "gcp".to_string(),
+                    code: resp.status().as_u16().to_string(),
+                    message: resp.text().await.unwrap_or_default(),
+                });
+            }
+
+            let body: TimeSeriesResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
+            for point in body.time_series.unwrap_or_default().into_iter().flat_map(|series| series.points.unwrap_or_default()) {
+                let value = match (point.value.double_value, point.value.int64_value) {
+                    (Some(value), _) => value,
+                    (None, Some(value)) => value.parse().unwrap_or_default(),
+                    (None, None) => continue,
+                };
+                let timestamp = DateTime::parse_from_rfc3339(&point.interval.end_time)
+                    .map_err(|e| CloudError::Serialization(e.to_string()))?
+                    .with_timezone(&Utc);
+                data_points.push(MetricDataPoint { timestamp, value, unit: MetricUnit::None });
+            }
+
+            page_token = body.next_page_token.filter(|token| !token.is_empty());
+            if page_token.is_none() {
+                break;
+            }
+        }
+        // Cloud Monitoring returns the newest point first
+        data_points.sort_by_key(|point| point.timestamp);
+
+        Ok(MetricResult {
+            name: query.name,
+            data_points,
+        })
+    }
+
     async fn list_metrics(&self, namespace: Option<&str>) -> CloudResult<Vec<String>> {
         let token = self.token().await?;
         let url = format!("{}/metricDescriptors", self.monitoring_base_url());
@@ -366,6 +594,47 @@ impl LoggingService for GcpMonitor {
         Ok(vec![])
     }
 
+    async fn search_logs(&self, query: LogQuery) -> CloudResult<Vec<LogEvent>> {
+        query.validate()?;
+        let events = self.list_log_entries(
+            &query.group,
+            &query.streams,
+            query.contains.as_deref(),
+            query.range.start,
+            Some(query.range.end),
+            query.limit,
+        ).await?;
+        Ok(events.into_iter().map(|(_, event)| event).collect())
+    }
+
+    async fn tail_logs(&self, tail: LogTail) -> CloudResult<LogStream> {
+        // entries:tail is only offered over gRPC, so the tail polls entries:list
+        let state = (self.clone(), LogCursor::new(tail.since), std::collections::VecDeque::new(), false);
+        let stream = futures::stream::try_unfold(state, move |(monitor, mut cursor, mut pending, mut polled)| {
+            let tail = tail.clone();
+            async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Ok::<_, CloudError>(Some((event, (monitor, cursor, pending, polled))));
+                    }
+                    if polled {
+                        tokio::time::sleep(tail.poll_interval).await;
+                    }
+                    polled = true;
+                    let events = monitor.list_log_entries(
+                        &tail.group, &tail.streams, tail.contains.as_deref(), cursor.since(), None, None,
+                    ).await?;
+                    for (id, event) in events {
+                        if cursor.advance(&id, event.timestamp) {
+                            pending.push_back(event);
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
     async fn list_log_groups(&self) -> CloudResult<Vec<String>> {
         Ok(vec![])
     }
@@ -380,6 +649,23 @@ mod tests {
     use super::*;
     use cloudkit_spi::ProviderType;
 
+    #[test]
+    fn test_metric_query_mappings() {
+        assert_eq!(gcp_label_key("InstanceId"), "instance_id");
+        assert_eq!(gcp_label_key("region"), "region");
+        assert_eq!(gcp_label_key("k8s.pod-name"), "k8s_pod_name");
+        assert_eq!(gcp_label_key("9lives"), "label_9lives");
+        assert_eq!(gcp_aggregation(MetricStatistic::SampleCount), ("ALIGN_COUNT", "REDUCE_SUM"));
+    }
+
+    #[test]
+    fn test_log_levels() {
+        assert_eq!(gcp_log_level("WARNING"), LogLevel::Warn);
+        assert_eq!(gcp_log_level("CRITICAL"), LogLevel::Fatal);
+        assert_eq!(gcp_log_level("Debug"), LogLevel::Debug);
+        assert_eq!(gcp_log_level("NOTICE"), LogLevel::Info);
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_monitor_flow() {
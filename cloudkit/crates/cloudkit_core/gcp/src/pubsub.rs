@@ -1,7 +1,7 @@
 //! Google Cloud Pub/Sub implementation.
 
 use async_trait::async_trait;
-use cloudkit_api::{Message, MessageQueue, ReceiveOptions, SendOptions};
+use cloudkit_api::{DeadLetterConfig, Message, MessageQueue, ReceiveOptions, SendOptions};
 use cloudkit_spi::{CloudError, CloudResult, ResourceId};
 use cloudkit_spi::CloudContext;
 use google_cloud_pubsub::client::Client;
@@ -126,8 +126,15 @@ impl MessageQueue for GcpPubSub {
         &self,
         _queue_url: &str,
         _body: &str,
-        _options: SendOptions,
+        options: SendOptions,
     ) -> CloudResult<ResourceId> {
+        if options.delay.is_some() || options.scheduled_at.is_some() {
+            return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: "NotSupported".to_string(),
+                message: "Pub/Sub delivers messages at once; schedule them with Cloud Tasks".to_string(),
+            });
+        }
         tracing::info!("send_with_options called stub");
         Ok(ResourceId::new("stub".to_string()))
     }
@@ -180,6 +187,33 @@ impl MessageQueue for GcpPubSub {
     async fn purge(&self, _queue_url: &str) -> CloudResult<()> {
         Ok(())
     }
+
+    async fn configure_dlq(
+        &self,
+        _queue_url: &str,
+        config: Option<DeadLetterConfig>,
+    ) -> CloudResult<()> {
+        // Pub/Sub allows 5 to 100 delivery attempts before dead-lettering
+        if let Some(config) = &config {
+            if !(5..=100).contains(&config.max_receive_count) {
+                return Err(CloudError::Validation(format!(
+                    "Pub/Sub max delivery attempts must be between 5 and 100, got {}",
+                    config.max_receive_count
+                )));
+            }
+        }
+        tracing::info!("configure_dlq called stub");
+        Ok(())
+    }
+
+    async fn read_dlq(
+        &self,
+        _queue_url: &str,
+        _options: ReceiveOptions,
+    ) -> CloudResult<Vec<Message>> {
+        tracing::info!("read_dlq called stub");
+        Ok(vec![])
+    }
 }
 
 #[cfg(test)]
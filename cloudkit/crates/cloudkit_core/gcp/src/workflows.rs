@@ -3,8 +3,8 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use cloudkit_api::{
-    Execution, ExecutionError, ExecutionFilter, ExecutionStatus, HistoryEvent,
-    StartExecutionOptions, WorkflowDefinition, WorkflowService, WorkflowType,
+    sequence_events, Execution, ExecutionError, ExecutionEventKind, ExecutionFilter, ExecutionStatus,
+    HistoryEvent, StartExecutionOptions, WorkflowDefinition, WorkflowService, WorkflowType,
 };
 use cloudkit_spi::{CloudError, CloudResult, Metadata};
 use cloudkit_spi::CloudContext;
@@ -116,6 +116,103 @@ struct ListExecutionsResponse {
     executions: Option<Vec<GcpExecution>>,
 }
 
+#[derive(Serialize, Deserialize)]
+struct GcpStepEntry {
+    step: Option<String>,
+    #[serde(rename = "stepType")]
+    step_type: Option<String>,
+    state: Option<String>,
+    #[serde(rename = "createTime")]
+    create_time: Option<String>,
+    #[serde(rename = "updateTime")]
+    update_time: Option<String>,
+    exception: Option<GcpStepException>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GcpStepException {
+    payload: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ListStepEntriesResponse {
+    #[serde(rename = "stepEntries")]
+    step_entries: Option<Vec<GcpStepEntry>>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
+/// Execution status of a Workflows execution state.
+fn execution_status(state: &str) -> ExecutionStatus {
+    match state {
+        "SUCCEEDED" => ExecutionStatus::Succeeded,
+        "FAILED" => ExecutionStatus::Failed,
+        "CANCELLED" => ExecutionStatus::Aborted,
+        // ACTIVE, QUEUED and UNAVAILABLE
+        _ => ExecutionStatus::Running,
+    }
+}
+
+/// Workflows execution state matching a status, for the `filter` of executions.list.
+fn state_filter(status: ExecutionStatus) -> CloudResult<&'static str> {
+    match status {
+        ExecutionStatus::Running => Ok("ACTIVE"),
+        ExecutionStatus::Succeeded => Ok("SUCCEEDED"),
+        ExecutionStatus::Failed => Ok("FAILED"),
+        ExecutionStatus::Aborted => Ok("CANCELLED"),
+        ExecutionStatus::TimedOut | ExecutionStatus::PendingRedrive => Err(CloudError::Provider {
+            provider: "gcp".to_string(),
+            code: "NotSupported".to_string(),
+            message: format!("GCP Workflows executions have no {:?} state; timeouts fail the execution", status),
+        }),
+    }
+}
+
+fn parse_time(time: Option<&str>) -> Option<DateTime<Utc>> {
+    time.and_then(|t| DateTime::parse_from_rfc3339(t).ok().map(|dt| dt.with_timezone(&Utc)))
+}
+
+/// Execution history from an execution and its step entries.
+///
+/// Workflows keeps a record per step entry rather than an event log, so each entry becomes a
+/// start event at its creation and, once it finished, a success or failure event at its last update.
+fn execution_events(execution: &Execution, entries: Vec<GcpStepEntry>) -> Vec<HistoryEvent> {
+    let mut events = vec![HistoryEvent::new(ExecutionEventKind::ExecutionStarted, "ACTIVE", execution.start_time)];
+
+    for entry in entries {
+        let step = entry.step.unwrap_or_default();
+        let details = json!({ "stepType": entry.step_type });
+        if let Some(created) = parse_time(entry.create_time.as_deref()) {
+            events.push(
+                HistoryEvent::new(ExecutionEventKind::StepStarted, "STATE_IN_PROGRESS", created)
+                    .step(step.clone())
+                    .details(details.clone()),
+            );
+        }
+
+        let state = entry.state.unwrap_or_default();
+        let kind = match state.as_str() {
+            "STATE_SUCCEEDED" => ExecutionEventKind::StepSucceeded,
+            "STATE_FAILED" | "STATE_CANCELLED" => ExecutionEventKind::StepFailed,
+            _ => continue,
+        };
+        let Some(updated) = parse_time(entry.update_time.as_deref()) else { continue };
+        let mut event = HistoryEvent::new(kind, state.as_str(), updated).step(step).details(details);
+        if let Some(payload) = entry.exception.and_then(|e| e.payload) {
+            event = event.error(ExecutionError { error: state.clone(), cause: payload });
+        }
+        events.push(event);
+    }
+
+    if let (Some(kind), Some(stopped)) = (ExecutionEventKind::ending(execution.status), execution.stop_time) {
+        let mut event = HistoryEvent::new(kind, format!("{:?}", execution.status).to_uppercase(), stopped)
+            .details(execution.output.clone().unwrap_or(Value::Null));
+        event.error = execution.error.clone();
+        events.push(event);
+    }
+    sequence_events(events)
+}
+
 #[async_trait]
 impl WorkflowService for GcpWorkflows {
     async fn create_workflow(&self, definition: WorkflowDefinition) -> CloudResult<String> {
@@ -298,13 +395,7 @@ impl WorkflowService for GcpWorkflows {
             execution_id: exec.name.clone(),
             workflow_arn: resource_name,
             name: Some(exec.name.split('/').next_back().unwrap_or_default().to_string()),
-            status: match exec.state.as_str() {
-                "ACTIVE" => ExecutionStatus::Running,
-                "SUCCEEDED" => ExecutionStatus::Succeeded,
-                "FAILED" => ExecutionStatus::Failed,
-                "CANCELLED" => ExecutionStatus::Aborted,
-                _ => ExecutionStatus::Running,
-            },
+            status: execution_status(&exec.state),
             input: Some(input),
             output: None, // Not available immediately on start usually
             error: None,
@@ -378,13 +469,7 @@ impl WorkflowService for GcpWorkflows {
             execution_id: exec.name.clone(),
             workflow_arn,
             name: Some(exec.name.split('/').next_back().unwrap_or_default().to_string()),
-            status: match exec.state.as_str() {
-                "ACTIVE" => ExecutionStatus::Running,
-                "SUCCEEDED" => ExecutionStatus::Succeeded,
-                "FAILED" => ExecutionStatus::Failed,
-                "CANCELLED" => ExecutionStatus::Aborted,
-                _ => ExecutionStatus::Running,
-            },
+            status: execution_status(&exec.state),
             input,
             output, 
             error: exec.error.map(|e| ExecutionError {
@@ -399,7 +484,7 @@ impl WorkflowService for GcpWorkflows {
     async fn list_executions(
         &self,
         workflow_arn: &str,
-        _filter: ExecutionFilter,
+        filter: ExecutionFilter,
     ) -> CloudResult<Vec<Execution>> {
          let token = self.token().await?;
          let resource_name = if workflow_arn.contains('/') {
@@ -410,14 +495,28 @@ impl WorkflowService for GcpWorkflows {
         
         let url = format!("https://workflowexecutions.googleapis.com/v1/{}/executions", resource_name);
 
+        // Executions are listed most recently created first
+        let mut query = Vec::new();
+        if let Some(status) = filter.status {
+            query.push(("filter", format!("state=\"{}\"", state_filter(status)?)));
+        }
+        if let Some(max) = filter.max_results {
+            query.push(("pageSize", max.to_string()));
+        }
+
         let resp = self.client.get(&url)
             .bearer_auth(&token)
+            .query(&query)
             .send()
             .await
             .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
 
         if !resp.status().is_success() {
-             return Ok(vec![]);
+             return Err(CloudError::Provider {
+                provider: "gcp".to_string(),
+                code: resp.status().as_u16().to_string(),
+                message: resp.text().await.unwrap_or_default(),
+            });
         }
 
         let body: ListExecutionsResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
@@ -445,10 +544,39 @@ impl WorkflowService for GcpWorkflows {
 
     async fn get_execution_history(
         &self,
-        _execution_id: &str,
+        execution_id: &str,
     ) -> CloudResult<Vec<HistoryEvent>> {
-        // GCP doesn't expose granular history steps in the same way as Step Functions via HTTP easily
-        Ok(vec![])
+        let execution = self.describe_execution(execution_id).await?;
+        let token = self.token().await?;
+        let url = format!("https://workflowexecutions.googleapis.com/v1/{}/stepEntries", execution_id);
+
+        let mut entries = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut req = self.client.get(&url).bearer_auth(&token);
+            if let Some(page_token) = &page_token {
+                req = req.query(&[("pageToken", page_token)]);
+            }
+            let resp = req
+                .send()
+                .await
+                .map_err(|e| CloudError::Provider { provider: "gcp".into(), code: "ReqwestError".into(), message: e.to_string() })?;
+
+            if !resp.status().is_success() {
+                return Err(CloudError::Provider {
+                    provider: "gcp".to_string(),
+                    code: resp.status().as_u16().to_string(),
+                    message: resp.text().await.unwrap_or_default(),
+                });
+            }
+
+            let body: ListStepEntriesResponse = resp.json().await.map_err(|e| CloudError::Serialization(e.to_string()))?;
+            entries.extend(body.step_entries.unwrap_or_default());
+            page_token = body.next_page_token.filter(|t| !t.is_empty());
+            if page_token.is_none() {
+                return Ok(execution_events(&execution, entries));
+            }
+        }
     }
 
     async fn send_task_success(
@@ -554,5 +682,52 @@ mod tests {
         // This Create uses YAML/JSON based syntax. The generic definition is JSON. GCP Workflows uses YAML usually.
         // We pass it as string sourceContents.
     }
+
+    #[test]
+    fn test_state_filter() {
+        assert_eq!(state_filter(ExecutionStatus::Aborted).unwrap(), "CANCELLED");
+        assert_eq!(execution_status(state_filter(ExecutionStatus::Failed).unwrap()), ExecutionStatus::Failed);
+        assert!(state_filter(ExecutionStatus::TimedOut).is_err());
+        assert_eq!(execution_status("QUEUED"), ExecutionStatus::Running);
+    }
+
+    #[test]
+    fn test_execution_events() {
+        let entry = |step: &str, state: &str, created: &str, updated: &str| GcpStepEntry {
+            step: Some(step.to_string()),
+            step_type: Some("STEP_ASSIGN".to_string()),
+            state: Some(state.to_string()),
+            create_time: Some(created.to_string()),
+            update_time: Some(updated.to_string()),
+            exception: (state == "STATE_FAILED").then(|| GcpStepException { payload: Some("boom".to_string()) }),
+        };
+        let execution = Execution {
+            execution_id: "projects/p/locations/l/workflows/w/executions/e".to_string(),
+            workflow_arn: "projects/p/locations/l/workflows/w".to_string(),
+            name: Some("e".to_string()),
+            status: ExecutionStatus::Failed,
+            input: None,
+            output: None,
+            error: Some(ExecutionError { error: "ExecutionFailed".to_string(), cause: "boom".to_string() }),
+            start_time: parse_time(Some("2024-01-01T00:00:00Z")).unwrap(),
+            stop_time: parse_time(Some("2024-01-01T00:00:05Z")),
+        };
+
+        let events = execution_events(&execution, vec![
+            entry("init", "STATE_SUCCEEDED", "2024-01-01T00:00:01Z", "2024-01-01T00:00:02Z"),
+            entry("call", "STATE_FAILED", "2024-01-01T00:00:03Z", "2024-01-01T00:00:04Z"),
+        ]);
+        let kinds: Vec<_> = events.iter().map(|e| (e.id, e.kind, e.step.as_deref())).collect();
+        assert_eq!(kinds, vec![
+            (1, ExecutionEventKind::ExecutionStarted, None),
+            (2, ExecutionEventKind::StepStarted, Some("init")),
+            (3, ExecutionEventKind::StepSucceeded, Some("init")),
+            (4, ExecutionEventKind::StepStarted, Some("call")),
+            (5, ExecutionEventKind::StepFailed, Some("call")),
+            (6, ExecutionEventKind::ExecutionFailed, None),
+        ]);
+        assert_eq!(events[4].error.as_ref().unwrap().cause, "boom");
+        assert_eq!(events[5].error.as_ref().unwrap().error, "ExecutionFailed");
+    }
 }
 
@@ -1,6 +1,9 @@
 //! Oracle Cloud client builder.
 
+use async_trait::async_trait;
 use cloudkit_spi::{CloudConfig, CloudResult, Region, CloudContext, ProviderType};
+use cloudkit_spi::{HealthCheck, HealthCheckOptions, HealthProbes, HealthReport};
+use cloudkit_spi::{Capabilities, CapabilityDiscovery};
 use std::sync::Arc;
 
 /// Oracle Cloud client builder.
@@ -104,3 +107,18 @@ impl OracleClient {
     }
 }
 
+/// No Oracle Cloud service is implemented yet, so there is nothing to probe and the report is empty.
+#[async_trait]
+impl HealthCheck for OracleClient {
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport {
+        HealthProbes::new(ProviderType::Oracle, options).run().await
+    }
+}
+
+
+/// No Oracle Cloud service is implemented yet, so the client supports nothing.
+impl CapabilityDiscovery for OracleClient {
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::new(ProviderType::Oracle)
+    }
+}
@@ -0,0 +1,331 @@
+//! Bulk deletion helpers.
+//!
+//! Emptying a bucket or a queue means walking a paginated listing and deleting what it
+//! returns, with enough calls in flight to finish in reasonable time but not so many that the
+//! provider throttles them. [`delete_prefix`], [`empty_bucket`] and [`purge_queue`] do that
+//! loop once for every provider behind the CloudKit traits, with a cap on concurrency, an
+//! optional cap on how much is deleted, and a dry run that only counts.
+
+use cloudkit_api::{ListOptions, Message, MessageQueue, ObjectStorage, ReceiveOptions};
+use cloudkit_spi::{CloudError, CloudResult};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Limits of a bulk deletion.
+#[derive(Debug, Clone)]
+pub struct BulkDeleteOptions {
+    /// Delete calls in flight at once
+    pub concurrency: usize,
+    /// Objects per `delete_objects` call; S3 accepts at most 1000
+    pub batch_size: usize,
+    /// Stop after deleting this many objects or messages
+    pub max_items: Option<u64>,
+    /// Count what would be deleted without deleting it
+    pub dry_run: bool,
+}
+
+impl Default for BulkDeleteOptions {
+    fn default() -> Self {
+        Self { concurrency: 8, batch_size: 1000, max_items: None, dry_run: false }
+    }
+}
+
+impl BulkDeleteOptions {
+    /// Create options with 8 calls in flight and batches of 1000.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of delete calls in flight.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Set the number of objects per delete call.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Stop after deleting `max` items.
+    pub fn max_items(mut self, max: u64) -> Self {
+        self.max_items = Some(max);
+        self
+    }
+
+    /// Only count what would be deleted.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    fn validate(&self) -> CloudResult<()> {
+        if self.concurrency == 0 || self.batch_size == 0 {
+            return Err(CloudError::Validation("Bulk delete concurrency and batch size must be at least 1".into()));
+        }
+        Ok(())
+    }
+
+    fn remaining(&self, deleted: u64) -> u64 {
+        self.max_items.map_or(u64::MAX, |max| max.saturating_sub(deleted))
+    }
+}
+
+/// What a bulk deletion did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BulkDeleteReport {
+    /// Objects or messages deleted, or that would be in a dry run
+    pub deleted: u64,
+    /// Listing pages or receive calls made
+    pub pages: u64,
+    /// Whether `max_items` stopped the deletion before everything was deleted
+    pub truncated: bool,
+}
+
+/// Delete every object under `prefix` in a bucket.
+///
+/// An empty prefix is refused, so a missing value cannot empty a bucket by accident; use
+/// [`empty_bucket`] for that. Objects written while the deletion runs may be left behind.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let report = delete_prefix(&storage, "logs", "2024/", BulkDeleteOptions::new().concurrency(4)).await?;
+/// println!("deleted {} objects", report.deleted);
+/// ```
+pub async fn delete_prefix(
+    storage: &dyn ObjectStorage,
+    bucket: &str,
+    prefix: &str,
+    options: BulkDeleteOptions,
+) -> CloudResult<BulkDeleteReport> {
+    if prefix.is_empty() {
+        return Err(CloudError::Validation("Refusing to delete an empty prefix; use empty_bucket to delete every object".into()));
+    }
+    delete_objects(storage, bucket, Some(prefix), &options).await
+}
+
+/// Delete every object in a bucket, leaving the bucket itself.
+pub async fn empty_bucket(storage: &dyn ObjectStorage, bucket: &str, options: BulkDeleteOptions) -> CloudResult<BulkDeleteReport> {
+    delete_objects(storage, bucket, None, &options).await
+}
+
+async fn delete_objects(
+    storage: &dyn ObjectStorage,
+    bucket: &str,
+    prefix: Option<&str>,
+    options: &BulkDeleteOptions,
+) -> CloudResult<BulkDeleteReport> {
+    options.validate()?;
+    let mut report = BulkDeleteReport::default();
+    let mut batches: Vec<Vec<String>> = Vec::new();
+    let mut listed = 0;
+    let mut token: Option<String> = None;
+
+    loop {
+        let mut list = ListOptions::new().max_results(options.batch_size.min(1000) as u32);
+        if let Some(prefix) = prefix {
+            list = list.prefix(prefix);
+        }
+        if let Some(token) = &token {
+            list = list.continuation_token(token.clone());
+        }
+        let page = storage.list_objects(bucket, list).await?;
+        report.pages += 1;
+
+        let remaining = options.remaining(listed);
+        let more = page.has_more();
+        let keys: Vec<String> = page.items.into_iter().map(|object| object.key).collect();
+        report.truncated = keys.len() as u64 > remaining || (more && keys.len() as u64 == remaining);
+        let keys: Vec<String> = keys.into_iter().take(remaining.min(usize::MAX as u64) as usize).collect();
+        listed += keys.len() as u64;
+        batches.extend(keys.chunks(options.batch_size).map(<[String]>::to_vec));
+
+        // A provider repeating its token would otherwise be listed forever
+        let next = page.next_token.0;
+        let done = !more || report.truncated || next.is_none() || next == token;
+        // Keys are listed a few pages ahead so that `concurrency` delete calls can run at once
+        if done || batches.len() >= options.concurrency {
+            report.deleted += flush(storage, bucket, std::mem::take(&mut batches), options).await?;
+        }
+        if done {
+            break;
+        }
+        token = next;
+    }
+
+    tracing::info!(bucket = %bucket, prefix = prefix.unwrap_or(""), deleted = report.deleted, dry_run = options.dry_run, "Bulk deleted objects");
+    Ok(report)
+}
+
+async fn flush(storage: &dyn ObjectStorage, bucket: &str, batches: Vec<Vec<String>>, options: &BulkDeleteOptions) -> CloudResult<u64> {
+    if options.dry_run {
+        return Ok(batches.iter().map(|keys| keys.len() as u64).sum());
+    }
+    stream::iter(batches)
+        .map(|keys| async move {
+            let refs: Vec<&str> = keys.iter().map(String::as_str).collect();
+            storage.delete_objects(bucket, &refs).await.map(|_| keys.len() as u64)
+        })
+        .buffer_unordered(options.concurrency)
+        .try_fold(0, |deleted, batch| async move { Ok(deleted + batch) })
+        .await
+}
+
+/// Delete every message of a queue by receiving and deleting them.
+///
+/// Unlike [`MessageQueue::purge`], which some providers run asynchronously, rate-limit or do
+/// not support, this returns once the queue is drained: `concurrency` workers receive batches
+/// of up to 10 messages until a receive comes back empty. Messages in flight with other
+/// consumers are not received, so they stay. A dry run reports the queue depth.
+pub async fn purge_queue(queue: &dyn MessageQueue, queue_url: &str, options: BulkDeleteOptions) -> CloudResult<BulkDeleteReport> {
+    options.validate()?;
+    if options.dry_run {
+        let depth = queue.get_queue_depth(queue_url).await?;
+        let remaining = options.remaining(0);
+        return Ok(BulkDeleteReport { deleted: depth.min(remaining), pages: 1, truncated: depth > remaining });
+    }
+
+    // Workers claim their share of `max_items` before receiving, so together they never exceed it
+    let (claimed, pages, truncated) = (AtomicU64::new(0), AtomicU64::new(0), AtomicBool::new(false));
+    let (counts, options) = ((&claimed, &pages, &truncated), &options);
+    let worker = || async move {
+        let (claimed, pages, truncated) = counts;
+        loop {
+            let claim = claimed.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                let batch = options.remaining(n).min(10);
+                (batch > 0).then_some(n + batch)
+            });
+            let Ok(before) = claim else {
+                truncated.store(true, Ordering::SeqCst);
+                return Ok::<_, CloudError>(());
+            };
+            let batch = options.remaining(before).min(10);
+            let receive = ReceiveOptions::new()
+                .max_messages(batch as u32)
+                .visibility_timeout(Duration::from_secs(60))
+                .wait_time(Duration::ZERO);
+            let messages = match queue.receive(queue_url, receive).await {
+                Ok(messages) => messages,
+                Err(e) => {
+                    claimed.fetch_sub(batch, Ordering::SeqCst);
+                    return Err(e);
+                }
+            };
+            pages.fetch_add(1, Ordering::SeqCst);
+            claimed.fetch_sub(batch.saturating_sub(messages.len() as u64), Ordering::SeqCst);
+            if messages.is_empty() {
+                return Ok(());
+            }
+            let refs: Vec<&Message> = messages.iter().collect();
+            if let Err(e) = queue.delete_batch(queue_url, &refs).await {
+                claimed.fetch_sub(messages.len() as u64, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+    };
+    futures::future::try_join_all((0..options.concurrency).map(|_| worker())).await?;
+
+    let report = BulkDeleteReport {
+        deleted: claimed.into_inner(),
+        pages: pages.into_inner(),
+        truncated: truncated.into_inner(),
+    };
+    tracing::info!(queue = %queue_url, deleted = report.deleted, "Purged queue");
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use cloudkit_api::{CopyOptions, GetOptions, LifecycleRule, PutOptions};
+    use cloudkit_spi::{BucketMetadata, ListResult, ObjectMetadata, PaginationToken};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Bucket listing two keys per page, after the key given as token
+    struct PagedBucket {
+        keys: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ObjectStorage for PagedBucket {
+        async fn list_buckets(&self) -> CloudResult<Vec<BucketMetadata>> { unimplemented!() }
+        async fn create_bucket(&self, _: &str) -> CloudResult<()> { unimplemented!() }
+        async fn delete_bucket(&self, _: &str) -> CloudResult<()> { unimplemented!() }
+        async fn bucket_exists(&self, _: &str) -> CloudResult<bool> { unimplemented!() }
+        async fn put_object(&self, _: &str, _: &str, _: &[u8]) -> CloudResult<()> { unimplemented!() }
+        async fn put_object_with_options(&self, _: &str, _: &str, _: &[u8], _: PutOptions) -> CloudResult<()> { unimplemented!() }
+        async fn get_object(&self, _: &str, _: &str) -> CloudResult<Bytes> { unimplemented!() }
+        async fn get_object_with_options(&self, _: &str, _: &str, _: GetOptions) -> CloudResult<Bytes> { unimplemented!() }
+        async fn head_object(&self, _: &str, _: &str) -> CloudResult<ObjectMetadata> { unimplemented!() }
+        async fn delete_object(&self, _: &str, _: &str) -> CloudResult<()> { unimplemented!() }
+        async fn copy_object(&self, _: &str, _: &str, _: &str, _: &str) -> CloudResult<()> { unimplemented!() }
+        async fn copy_object_with_options(&self, _: &str, _: &str, _: &str, _: &str, _: CopyOptions) -> CloudResult<()> { unimplemented!() }
+        async fn move_object(&self, _: &str, _: &str, _: &str, _: &str) -> CloudResult<()> { unimplemented!() }
+        async fn object_exists(&self, _: &str, _: &str) -> CloudResult<bool> { unimplemented!() }
+        async fn set_object_metadata(&self, _: &str, _: &str, _: PutOptions) -> CloudResult<()> { unimplemented!() }
+        async fn get_object_tags(&self, _: &str, _: &str) -> CloudResult<HashMap<String, String>> { unimplemented!() }
+        async fn set_object_tags(&self, _: &str, _: &str, _: HashMap<String, String>) -> CloudResult<()> { unimplemented!() }
+        async fn get_bucket_lifecycle(&self, _: &str) -> CloudResult<Vec<LifecycleRule>> { unimplemented!() }
+        async fn set_bucket_lifecycle(&self, _: &str, _: Vec<LifecycleRule>) -> CloudResult<()> { unimplemented!() }
+        async fn presigned_get_url(&self, _: &str, _: &str, _: Duration) -> CloudResult<String> { unimplemented!() }
+        async fn presigned_put_url(&self, _: &str, _: &str, _: Duration) -> CloudResult<String> { unimplemented!() }
+
+        async fn delete_objects(&self, _: &str, keys: &[&str]) -> CloudResult<()> {
+            self.keys.lock().unwrap().retain(|key| !keys.contains(&key.as_str()));
+            Ok(())
+        }
+
+        async fn list_objects(&self, _: &str, options: ListOptions) -> CloudResult<ListResult<ObjectMetadata>> {
+            let keys = self.keys.lock().unwrap();
+            let after = options.continuation_token.unwrap_or_default();
+            let matching: Vec<&String> = keys.iter()
+                .filter(|key| key.starts_with(options.prefix.as_deref().unwrap_or("")) && **key > after)
+                .collect();
+            let page: Vec<ObjectMetadata> = matching.iter().take(2).map(|key| ObjectMetadata {
+                key: key.to_string(),
+                size: 1,
+                content_type: None,
+                cache_control: None,
+                etag: None,
+                last_modified: chrono::Utc::now(),
+                storage_class: None,
+                metadata: Default::default(),
+            }).collect();
+            let next = if matching.len() > 2 { PaginationToken::some(matching[1].clone()) } else { PaginationToken::none() };
+            Ok(ListResult::new(page, next))
+        }
+    }
+
+    fn bucket() -> PagedBucket {
+        let keys = ["logs/1", "logs/2", "logs/3", "logs/4", "logs/5", "keep"];
+        PagedBucket { keys: Mutex::new(keys.iter().map(|key| key.to_string()).collect()) }
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix_walks_every_page() {
+        let storage = bucket();
+        let options = BulkDeleteOptions::new().batch_size(2).concurrency(2);
+
+        let dry = delete_prefix(&storage, "b", "logs/", options.clone().dry_run(true)).await.unwrap();
+        assert_eq!((dry.deleted, dry.pages), (5, 3));
+        assert_eq!(storage.keys.lock().unwrap().len(), 6);
+
+        let limited = delete_prefix(&storage, "b", "logs/", options.clone().max_items(3)).await.unwrap();
+        assert_eq!(limited.deleted, 3);
+        assert!(limited.truncated);
+
+        let report = delete_prefix(&storage, "b", "logs/", options.clone()).await.unwrap();
+        assert_eq!(report.deleted, 2);
+        assert!(!report.truncated);
+        assert_eq!(*storage.keys.lock().unwrap(), vec!["keep".to_string()]);
+
+        assert!(delete_prefix(&storage, "b", "", options.clone()).await.is_err());
+        assert!(empty_bucket(&storage, "b", options.concurrency(0)).await.is_err());
+    }
+}
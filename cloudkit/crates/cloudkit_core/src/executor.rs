@@ -1,7 +1,7 @@
 //! Operation executor with retry and metrics.
 
 use cloudkit_spi::{CloudError, CloudResult};
-use cloudkit_spi::{MetricsCollector, OperationMetrics, RetryDecision, RetryPolicy};
+use cloudkit_spi::{LogEntry, LogLevel, Logger, MetricsCollector, NoopLogger, OperationMetrics, RetryDecision, RetryPolicy};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -12,6 +12,7 @@ pub struct OperationExecutor {
     service: String,
     retry_policy: Arc<dyn RetryPolicy>,
     metrics: Arc<dyn MetricsCollector>,
+    logger: Arc<dyn Logger>,
 }
 
 impl OperationExecutor {
@@ -27,9 +28,16 @@ impl OperationExecutor {
             service: service.into(),
             retry_policy,
             metrics,
+            logger: Arc::new(NoopLogger),
         }
     }
 
+    /// Log the outcome of each operation: successes at debug level, failures at warn level.
+    pub fn with_logger(mut self, logger: Arc<dyn Logger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
     /// Execute an operation with retry and metrics.
     pub async fn execute<F, Fut, T>(
         &self,
@@ -114,6 +122,7 @@ impl OperationExecutor {
         ).with_retry_count(retry_count);
 
         self.metrics.record(metrics).await;
+        self.log(LogLevel::Debug, "Operation succeeded", operation, duration, retry_count, None).await;
     }
 
     async fn record_failure(&self, operation: &str, duration: Duration, retry_count: u32, error: &CloudError) {
@@ -136,6 +145,24 @@ impl OperationExecutor {
         ).with_retry_count(retry_count);
 
         self.metrics.record(metrics).await;
+        self.log(LogLevel::Warn, "Operation failed", operation, duration, retry_count, Some(error)).await;
+    }
+
+    async fn log(&self, level: LogLevel, message: &str, operation: &str, duration: Duration, retry_count: u32, error: Option<&CloudError>) {
+        let mut entry = LogEntry::new(level, message)
+            .with_target("cloudkit::executor")
+            .with_operation(self.service.as_str(), operation);
+        if !self.logger.is_enabled_for(&entry) {
+            return;
+        }
+        entry = entry
+            .with_field("provider", self.provider.as_str())
+            .with_field("duration_ms", duration.as_millis() as u64)
+            .with_field("retry_count", retry_count);
+        if let Some(error) = error {
+            entry = entry.with_field("error", error.to_string());
+        }
+        self.logger.log(entry).await;
     }
 }
 
@@ -189,5 +216,41 @@ mod tests {
         assert_eq!(result.unwrap(), 42);
         assert_eq!(call_count.load(Ordering::SeqCst), 3);
     }
-}
 
+    #[tokio::test]
+    async fn test_failures_are_logged() {
+        #[derive(Default)]
+        struct Captured(std::sync::Mutex<Vec<LogEntry>>);
+
+        #[async_trait::async_trait]
+        impl Logger for Captured {
+            async fn log(&self, entry: LogEntry) {
+                self.0.lock().unwrap().push(entry);
+            }
+
+            fn is_enabled(&self, level: LogLevel) -> bool {
+                level >= LogLevel::Warn
+            }
+        }
+
+        let logger = Arc::new(Captured::default());
+        let executor = OperationExecutor::new(
+            "aws",
+            "s3",
+            Arc::new(ExponentialBackoff::new(0)),
+            Arc::new(NoopMetrics),
+        )
+        .with_logger(logger.clone());
+
+        executor.execute_once("head_bucket", || async { Ok::<_, CloudError>(()) }).await.unwrap();
+        let result = executor
+            .execute_once("get_object", || async { Err::<(), _>(CloudError::Validation("bad key".into())) })
+            .await;
+        assert!(result.is_err());
+
+        let entries = logger.0.lock().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation.as_deref(), Some("get_object"));
+        assert_eq!(entries[0].fields["error"], "Validation error: bad key");
+    }
+}
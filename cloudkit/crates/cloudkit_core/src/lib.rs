@@ -11,6 +11,8 @@
 //! - **CloudContext**: Central configuration and service aggregation
 //! - **ProviderType**: Enum for cloud providers (AWS, Azure, GCP, Oracle)
 //! - **OperationExecutor**: Retry and metrics handling
+//! - **ResourceExporter / ResourceImporter**: Cross-provider migration rehearsals
+//! - **delete_prefix / empty_bucket / purge_queue**: Paginated bulk deletion with bounded concurrency
 //!
 //! ## Usage
 //!
@@ -52,7 +54,11 @@ pub use cloudkit_spi;
 pub use cloudkit_api;
 
 // Core modules
+mod bulk;
 mod executor;
+mod migration;
 
 // Re-export core types
+pub use bulk::*;
 pub use executor::*;
+pub use migration::*;
@@ -0,0 +1,429 @@
+//! Cross-provider resource export and import.
+//!
+//! A [`ResourceExporter`] reads buckets with their objects, queues and secrets from one
+//! provider through the CloudKit service traits into a [`ResourceSnapshot`]. A
+//! [`ResourceImporter`] re-creates them in another provider, renaming resources whose names
+//! the target does not accept. Snapshots serialize to JSON, so an export can be kept as a
+//! fixture and replayed against several targets to rehearse a migration.
+//!
+//! Key-value stores cannot be listed through [`KeyValueStore`], so tables are exported by
+//! the keys you name with [`ResourceExporter::export_table`]. Target tables must already
+//! exist. Queue messages are not exported, since reading them would consume them.
+
+use cloudkit_api::{CreateSecretOptions, KeyValueStore, ListOptions, MessageQueue, ObjectStorage, PutOptions, SecretsManager};
+use cloudkit_spi::{CloudError, CloudResult, ProviderType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Kind of an exported resource, which decides the naming rules it follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ResourceKind {
+    /// S3 bucket, Blob container or GCS bucket
+    Bucket,
+    /// DynamoDB table, Cosmos container or Firestore collection
+    Table,
+    /// SQS queue, Service Bus queue or Pub/Sub topic
+    Queue,
+    /// Secrets Manager, Key Vault or Secret Manager secret
+    Secret,
+}
+
+/// Resources read from one provider.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSnapshot {
+    /// Provider the resources were exported from, such as `aws`
+    pub source: Option<String>,
+    /// Buckets with their objects
+    pub buckets: Vec<BucketSnapshot>,
+    /// Tables with the exported items
+    pub tables: Vec<TableSnapshot>,
+    /// Queue names
+    pub queues: Vec<String>,
+    /// Secrets with their current values
+    pub secrets: Vec<SecretSnapshot>,
+}
+
+/// An exported bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BucketSnapshot {
+    /// Bucket name
+    pub name: String,
+    /// Objects in the bucket
+    pub objects: Vec<ObjectSnapshot>,
+}
+
+/// An exported object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectSnapshot {
+    /// Object key
+    pub key: String,
+    /// Content type, if the source recorded one
+    pub content_type: Option<String>,
+    /// Object content
+    pub data: Vec<u8>,
+}
+
+/// An exported table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSnapshot {
+    /// Table name
+    pub name: String,
+    /// Items by key
+    pub items: BTreeMap<String, serde_json::Value>,
+}
+
+/// An exported secret.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecretSnapshot {
+    /// Secret name
+    pub name: String,
+    /// Current value
+    pub value: String,
+    /// Description, if any
+    pub description: Option<String>,
+}
+
+/// Reads resources from a provider.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let snapshot = ResourceExporter::new()
+///     .storage(&aws_s3)
+///     .queues(&aws_sqs)
+///     .secrets(&aws_secrets)
+///     .export(ProviderType::Aws)
+///     .await?;
+/// ```
+#[derive(Default)]
+pub struct ResourceExporter<'a> {
+    storage: Option<&'a dyn ObjectStorage>,
+    queues: Option<&'a dyn MessageQueue>,
+    secrets: Option<&'a dyn SecretsManager>,
+}
+
+impl<'a> ResourceExporter<'a> {
+    /// Create an exporter that reads nothing until services are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Export every bucket and its objects.
+    pub fn storage(mut self, storage: &'a dyn ObjectStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Export every queue.
+    pub fn queues(mut self, queues: &'a dyn MessageQueue) -> Self {
+        self.queues = Some(queues);
+        self
+    }
+
+    /// Export every secret and its current value.
+    pub fn secrets(mut self, secrets: &'a dyn SecretsManager) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Read the resources of the configured services.
+    pub async fn export(&self, source: ProviderType) -> CloudResult<ResourceSnapshot> {
+        let mut snapshot = ResourceSnapshot { source: Some(source.to_string()), ..Default::default() };
+
+        if let Some(storage) = self.storage {
+            for bucket in storage.list_buckets().await? {
+                let objects = export_objects(storage, &bucket.name).await?;
+                snapshot.buckets.push(BucketSnapshot { name: bucket.name, objects });
+            }
+        }
+        if let Some(queues) = self.queues {
+            for url in queues.list_queues(None).await? {
+                snapshot.queues.push(queue_name(&url).to_string());
+            }
+        }
+        if let Some(secrets) = self.secrets {
+            for metadata in secrets.list_secrets().await? {
+                let value = secrets.get_secret(&metadata.name).await?;
+                snapshot.secrets.push(SecretSnapshot { name: metadata.name, value, description: metadata.description });
+            }
+        }
+
+        tracing::info!(
+            provider = %source,
+            buckets = snapshot.buckets.len(),
+            queues = snapshot.queues.len(),
+            secrets = snapshot.secrets.len(),
+            "Exported resources"
+        );
+        Ok(snapshot)
+    }
+
+    /// Read the items stored under `keys` in `table`; keys without an item are skipped.
+    pub async fn export_table<K: KeyValueStore>(&self, store: &K, table: &str, keys: &[&str]) -> CloudResult<TableSnapshot> {
+        let mut items = BTreeMap::new();
+        for key in keys {
+            if let Some(item) = store.get::<serde_json::Value>(table, key).await? {
+                items.insert(key.to_string(), item);
+            }
+        }
+        Ok(TableSnapshot { name: table.to_string(), items })
+    }
+}
+
+async fn export_objects(storage: &dyn ObjectStorage, bucket: &str) -> CloudResult<Vec<ObjectSnapshot>> {
+    let mut objects = Vec::new();
+    let mut token = None;
+    loop {
+        let mut options = ListOptions::new();
+        if let Some(token) = token.take() {
+            options = options.continuation_token(token);
+        }
+        let page = storage.list_objects(bucket, options).await?;
+        for metadata in &page.items {
+            let data = storage.get_object(bucket, &metadata.key).await?;
+            objects.push(ObjectSnapshot {
+                key: metadata.key.clone(),
+                content_type: metadata.content_type.clone(),
+                data: data.to_vec(),
+            });
+        }
+        if !page.has_more() {
+            return Ok(objects);
+        }
+        token = page.next_token.0;
+    }
+}
+
+/// Queue name of a queue URL (`.../000000000000/orders`), or the name itself.
+fn queue_name(url: &str) -> &str {
+    url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
+}
+
+/// What an import created.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Buckets created or reused
+    pub buckets: usize,
+    /// Objects written
+    pub objects: usize,
+    /// Items written
+    pub items: usize,
+    /// Queues created or reused
+    pub queues: usize,
+    /// Secrets created or updated
+    pub secrets: usize,
+    /// Resources stored under another name: kind, source name and target name
+    pub renamed: Vec<(ResourceKind, String, String)>,
+}
+
+/// Maps resource names onto the naming rules of a target provider.
+#[derive(Debug, Clone)]
+pub struct NameMapper {
+    target: ProviderType,
+    renames: HashMap<(ResourceKind, String), String>,
+}
+
+impl NameMapper {
+    /// Mapper for resources created in `target`.
+    pub fn new(target: ProviderType) -> Self {
+        Self { target, renames: HashMap::new() }
+    }
+
+    /// Store the `kind` resource `from` as `to`, whatever the naming rules.
+    pub fn rename(mut self, kind: ResourceKind, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.renames.insert((kind, from.into()), to.into());
+        self
+    }
+
+    /// Name of a resource in the target provider.
+    pub fn map(&self, kind: ResourceKind, name: &str) -> String {
+        if let Some(renamed) = self.renames.get(&(kind, name.to_string())) {
+            return renamed.clone();
+        }
+        let (allowed, max_len, lowercase): (fn(char) -> bool, usize, bool) = match (kind, self.target) {
+            // Blob containers: lowercase letters, digits and single hyphens
+            (ResourceKind::Bucket, ProviderType::Azure) => (|c| c.is_ascii_alphanumeric() || c == '-', 63, true),
+            (ResourceKind::Bucket, ProviderType::Gcp) => (|c| c.is_ascii_alphanumeric() || "-_.".contains(c), 63, true),
+            (ResourceKind::Bucket, _) => (|c| c.is_ascii_alphanumeric() || "-.".contains(c), 63, true),
+            (ResourceKind::Table, ProviderType::Aws | ProviderType::Zero) => (|c| c.is_ascii_alphanumeric() || "-_.".contains(c), 255, false),
+            (ResourceKind::Table, _) => (|c| !"/\\#?".contains(c), 255, false),
+            (ResourceKind::Queue, ProviderType::Azure) => (|c| c.is_ascii_alphanumeric() || "-_./".contains(c), 260, false),
+            (ResourceKind::Queue, ProviderType::Gcp) => (|c| c.is_ascii_alphanumeric() || "-_.~+%".contains(c), 255, false),
+            (ResourceKind::Queue, _) => (|c| c.is_ascii_alphanumeric() || "-_".contains(c), 80, false),
+            // Key Vault secrets: letters, digits and hyphens
+            (ResourceKind::Secret, ProviderType::Azure) => (|c| c.is_ascii_alphanumeric() || c == '-', 127, false),
+            (ResourceKind::Secret, ProviderType::Gcp) => (|c| c.is_ascii_alphanumeric() || "-_".contains(c), 255, false),
+            (ResourceKind::Secret, _) => (|c| c.is_ascii_alphanumeric() || "/_+=.@-".contains(c), 512, false),
+        };
+
+        let mut mapped = String::with_capacity(name.len());
+        for c in name.chars() {
+            let c = if lowercase { c.to_ascii_lowercase() } else { c };
+            let c = if allowed(c) { c } else { '-' };
+            // Hyphens never repeat, which Blob containers require
+            if !(c == '-' && mapped.ends_with('-')) {
+                mapped.push(c);
+            }
+        }
+        let mut mapped: String = mapped.trim_matches('-').chars().take(max_len).collect();
+        if kind == ResourceKind::Bucket {
+            mapped = mapped.trim_end_matches('-').to_string();
+            while mapped.len() < 3 {
+                mapped.push('0');
+            }
+        }
+        // Pub/Sub topics start with a letter
+        if kind == ResourceKind::Queue && self.target == ProviderType::Gcp && !mapped.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            mapped.insert(0, 'q');
+        }
+        mapped
+    }
+}
+
+/// Re-creates exported resources in a provider.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let report = ResourceImporter::new(NameMapper::new(ProviderType::Azure))
+///     .storage(&azure_blob)
+///     .queues(&azure_service_bus)
+///     .secrets(&azure_key_vault)
+///     .import(&snapshot)
+///     .await?;
+/// ```
+pub struct ResourceImporter<'a> {
+    mapper: NameMapper,
+    storage: Option<&'a dyn ObjectStorage>,
+    queues: Option<&'a dyn MessageQueue>,
+    secrets: Option<&'a dyn SecretsManager>,
+}
+
+impl<'a> ResourceImporter<'a> {
+    /// Create an importer that writes nothing until services are added.
+    pub fn new(mapper: NameMapper) -> Self {
+        Self { mapper, storage: None, queues: None, secrets: None }
+    }
+
+    /// Create buckets and write their objects.
+    pub fn storage(mut self, storage: &'a dyn ObjectStorage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Create queues.
+    pub fn queues(mut self, queues: &'a dyn MessageQueue) -> Self {
+        self.queues = Some(queues);
+        self
+    }
+
+    /// Create secrets, or update the ones that exist.
+    pub fn secrets(mut self, secrets: &'a dyn SecretsManager) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Create the snapshot's buckets, queues and secrets for which a service was added.
+    /// Resources that exist already are reused, so an import can be repeated.
+    pub async fn import(&self, snapshot: &ResourceSnapshot) -> CloudResult<ImportReport> {
+        let mut report = ImportReport::default();
+
+        if let Some(storage) = self.storage {
+            for bucket in &snapshot.buckets {
+                let name = self.target_name(ResourceKind::Bucket, &bucket.name, &mut report);
+                if !storage.bucket_exists(&name).await? {
+                    storage.create_bucket(&name).await?;
+                }
+                report.buckets += 1;
+                for object in &bucket.objects {
+                    let mut options = PutOptions::new();
+                    if let Some(content_type) = &object.content_type {
+                        options = options.content_type(content_type);
+                    }
+                    storage.put_object_with_options(&name, &object.key, &object.data, options).await?;
+                    report.objects += 1;
+                }
+            }
+        }
+        if let Some(queues) = self.queues {
+            for queue in &snapshot.queues {
+                let name = self.target_name(ResourceKind::Queue, queue, &mut report);
+                match queues.create_queue(&name).await {
+                    Ok(_) | Err(CloudError::AlreadyExists { .. }) => report.queues += 1,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        if let Some(secrets) = self.secrets {
+            for secret in &snapshot.secrets {
+                let name = self.target_name(ResourceKind::Secret, &secret.name, &mut report);
+                let options = CreateSecretOptions { description: secret.description.clone(), ..Default::default() };
+                match secrets.create_secret(&name, &secret.value, options).await {
+                    Ok(_) => {}
+                    Err(CloudError::AlreadyExists { .. }) => {
+                        secrets.update_secret(&name, &secret.value).await?;
+                    }
+                    Err(e) => return Err(e),
+                }
+                report.secrets += 1;
+            }
+        }
+
+        tracing::info!(
+            buckets = report.buckets,
+            objects = report.objects,
+            queues = report.queues,
+            secrets = report.secrets,
+            "Imported resources"
+        );
+        Ok(report)
+    }
+
+    /// Write the items of exported tables into tables of the same (mapped) name in `store`.
+    pub async fn import_tables<K: KeyValueStore>(&self, store: &K, tables: &[TableSnapshot]) -> CloudResult<ImportReport> {
+        let mut report = ImportReport::default();
+        for table in tables {
+            let name = self.target_name(ResourceKind::Table, &table.name, &mut report);
+            for (key, item) in &table.items {
+                store.put(&name, key, item).await?;
+                report.items += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    fn target_name(&self, kind: ResourceKind, name: &str, report: &mut ImportReport) -> String {
+        let mapped = self.mapper.map(kind, name);
+        if mapped != name {
+            report.renamed.push((kind, name.to_string(), mapped.clone()));
+        }
+        mapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_follow_target_rules() {
+        let azure = NameMapper::new(ProviderType::Azure);
+        assert_eq!(azure.map(ResourceKind::Bucket, "My_Assets.2024"), "my-assets-2024");
+        assert_eq!(azure.map(ResourceKind::Secret, "prod/db_password"), "prod-db-password");
+        assert_eq!(azure.map(ResourceKind::Queue, "orders.fifo"), "orders.fifo");
+
+        let aws = NameMapper::new(ProviderType::Aws);
+        assert_eq!(aws.map(ResourceKind::Queue, "orders.fifo"), "orders-fifo");
+        assert_eq!(aws.map(ResourceKind::Bucket, "a"), "a00");
+
+        let gcp = NameMapper::new(ProviderType::Gcp).rename(ResourceKind::Table, "Users", "users_v2");
+        assert_eq!(gcp.map(ResourceKind::Queue, "1-jobs"), "q1-jobs");
+        assert_eq!(gcp.map(ResourceKind::Table, "Users"), "users_v2");
+    }
+
+    #[test]
+    fn test_queue_name_from_url() {
+        assert_eq!(queue_name("http://localhost:4566/000000000000/orders"), "orders");
+        assert_eq!(queue_name("orders"), "orders");
+    }
+}
@@ -67,6 +67,10 @@ impl IdentityProvider for ZeroId {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
 
+    async fn set_password(&self, _username: &str, _password: &str, _permanent: bool) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
     async fn initiate_auth(
         &self,
         _username: &str,
@@ -75,6 +79,14 @@ impl IdentityProvider for ZeroId {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
 
+    async fn admin_initiate_auth(
+        &self,
+        _username: &str,
+        _password: &str,
+    ) -> CloudResult<InitiateAuthResult> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
     async fn respond_to_challenge(
         &self,
         _challenge_name: ChallengeType,
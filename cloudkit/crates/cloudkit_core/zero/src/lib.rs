@@ -6,7 +6,10 @@ pub mod lambda;
 pub mod sqs;
 pub mod iam;
 
+use async_trait::async_trait;
 use cloudkit_spi::{CloudConfig, CloudResult, CloudContext, ProviderType, Region};
+use cloudkit_spi::{CloudError, HealthCheck, HealthCheckOptions, HealthProbes, HealthReport};
+use cloudkit_spi::{Capabilities, CapabilityDiscovery, Feature, Service};
 use std::sync::Arc;
 use zero_sdk::ZeroClient as RawZeroClient;
 
@@ -101,3 +104,40 @@ impl ZeroClient {
         iam::ZeroId::new(self.sdk.clone())
     }
 }
+
+/// Probes each service of the ZeroCloud server with a list request.
+#[async_trait]
+impl HealthCheck for ZeroClient {
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport {
+        let mut probes = HealthProbes::new(ProviderType::Zero, options);
+        let map_err = |e: zero_sdk::ZeroSdkError| CloudError::Internal(e.to_string());
+
+        let store = self.sdk.store();
+        probes.probe("storage", async move { store.list_buckets().await.map_err(map_err) });
+        let db = self.sdk.db();
+        probes.probe("kv_store", async move { db.list_tables().await.map_err(map_err) });
+        let queue = self.sdk.queue();
+        probes.probe("queue", async move { queue.list_queues().await.map_err(map_err) });
+        let func = self.sdk.func();
+        probes.probe("functions", async move { func.list_functions().await.map_err(map_err) });
+        let iam = self.sdk.iam();
+        probes.probe("identity", async move { iam.list_users().await.map_err(map_err) });
+
+        probes.run().await
+    }
+}
+
+/// The services of the ZeroCloud server. ZeroStore has no presigned URLs or lifecycle rules,
+/// ZeroDb no conditional writes, and ZeroQueue no FIFO queues.
+impl CapabilityDiscovery for ZeroClient {
+    fn capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::new(ProviderType::Zero);
+        capabilities
+            .service(Service::ObjectStorage, &[])
+            .service(Service::KeyValueStore, &[])
+            .service(Service::MessageQueue, &[Feature::DelayedMessages, Feature::DeadLetterQueues])
+            .service(Service::Functions, &[])
+            .service(Service::IdentityProvider, &[]);
+        capabilities
+    }
+}
@@ -1,4 +1,4 @@
-use cloudkit_api::{ObjectStorage, PutOptions, GetOptions, ListOptions};
+use cloudkit_api::{ObjectStorage, PutOptions, GetOptions, ListOptions, CopyOptions, LifecycleRule};
 use cloudkit_spi::{BucketMetadata, CloudResult, ListResult, ObjectMetadata, CloudError};
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -21,7 +21,7 @@ impl ObjectStorage for ZeroStore {
             .map_err(|e| CloudError::Internal(e.to_string()))?;
         
         Ok(buckets.into_iter()
-            .map(|name| BucketMetadata::new(name, "zero-local"))
+            .map(|bucket| BucketMetadata::new(bucket.name, "zero-local"))
             .collect())
     }
 
@@ -88,6 +88,27 @@ impl ObjectStorage for ZeroStore {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
 
+    async fn copy_object_with_options(
+        &self,
+        _source_bucket: &str,
+        _source_key: &str,
+        _dest_bucket: &str,
+        _dest_key: &str,
+        _options: CopyOptions,
+    ) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
+    async fn move_object(
+        &self,
+        _source_bucket: &str,
+        _source_key: &str,
+        _dest_bucket: &str,
+        _dest_key: &str,
+    ) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
     async fn object_exists(&self, _bucket: &str, _key: &str) -> CloudResult<bool> {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
@@ -100,6 +121,44 @@ impl ObjectStorage for ZeroStore {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
 
+    async fn set_object_metadata(
+        &self,
+        _bucket: &str,
+        _key: &str,
+        _options: PutOptions,
+    ) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
+    async fn get_object_tags(
+        &self,
+        _bucket: &str,
+        _key: &str,
+    ) -> CloudResult<std::collections::HashMap<String, String>> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
+    async fn set_object_tags(
+        &self,
+        _bucket: &str,
+        _key: &str,
+        _tags: std::collections::HashMap<String, String>,
+    ) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
+    async fn get_bucket_lifecycle(&self, _bucket: &str) -> CloudResult<Vec<LifecycleRule>> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
+    async fn set_bucket_lifecycle(
+        &self,
+        _bucket: &str,
+        _rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()> {
+        Err(CloudError::ServiceError("Not implemented".to_string()))
+    }
+
     async fn presigned_get_url(
         &self,
         _bucket: &str,
@@ -1,4 +1,4 @@
-use cloudkit_api::{MessageQueue, Message, SendOptions, ReceiveOptions};
+use cloudkit_api::{MessageQueue, Message, SendOptions, ReceiveOptions, DeadLetterConfig};
 use cloudkit_spi::{CloudResult, CloudError, ResourceId};
 use async_trait::async_trait;
 use std::time::Duration;
@@ -14,6 +14,9 @@ impl ZeroQueue {
     }
 }
 
+/// Longest delay of a ZeroQueue message.
+const MAX_DELAY: Duration = Duration::from_secs(15 * 60);
+
 #[async_trait]
 impl MessageQueue for ZeroQueue {
     async fn create_queue(&self, name: &str) -> CloudResult<String> {
@@ -45,9 +48,22 @@ impl MessageQueue for ZeroQueue {
         &self,
         queue_url: &str,
         body: &str,
-        _options: SendOptions,
+        options: SendOptions,
     ) -> CloudResult<ResourceId> {
-        self.send(queue_url, body).await
+        let Some(delay) = options.enqueue_delay(chrono::Utc::now()) else {
+            return self.send(queue_url, body).await;
+        };
+        let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+        if seconds > MAX_DELAY.as_secs() {
+            return Err(CloudError::Provider {
+                provider: "zero".to_string(),
+                code: "NotSupported".to_string(),
+                message: format!("ZeroQueue delays messages by at most {:?}, not {:?}", MAX_DELAY, delay),
+            });
+        }
+        let msg_id = self.client.queue().send_message_with_delay(queue_url, body, seconds as u32).await
+            .map_err(|e| CloudError::Internal(e.to_string()))?;
+        Ok(ResourceId::new(msg_id))
     }
 
     async fn send_batch(
@@ -111,4 +127,54 @@ impl MessageQueue for ZeroQueue {
     async fn purge(&self, _queue_url: &str) -> CloudResult<()> {
         Err(CloudError::ServiceError("Not implemented".to_string()))
     }
+
+    async fn configure_dlq(
+        &self,
+        queue_url: &str,
+        config: Option<DeadLetterConfig>,
+    ) -> CloudResult<()> {
+        let queue = self.client.queue();
+        match config {
+            Some(config) => queue.set_redrive_policy(queue_url, &config.dead_letter_queue, config.max_receive_count).await,
+            None => queue.delete_redrive_policy(queue_url).await,
+        }.map_err(|e| CloudError::Internal(e.to_string()))
+    }
+
+    async fn read_dlq(
+        &self,
+        queue_url: &str,
+        options: ReceiveOptions,
+    ) -> CloudResult<Vec<Message>> {
+        let queue = self.client.queue();
+        let policy = queue.get_redrive_policy(queue_url).await
+            .map_err(|e| CloudError::Internal(e.to_string()))?
+            .ok_or_else(|| CloudError::NotFound {
+                resource_type: "DeadLetterQueue".to_string(),
+                resource_id: queue_url.to_string(),
+            })?;
+        let received = queue.receive_messages(&policy.dead_letter_queue, zero_sdk::services::queue::ReceiveOptions {
+            max_messages: options.max_messages.unwrap_or(1).clamp(1, 10) as usize,
+            wait_time_seconds: options.wait_time.map(|wait| wait.as_secs()).unwrap_or(0),
+            visibility_timeout: options.visibility_timeout.map(|timeout| timeout.as_secs() as u32),
+        }).await.map_err(|e| CloudError::Internal(e.to_string()))?;
+
+        let mut messages = Vec::with_capacity(received.len());
+        for m in received {
+            queue.delete_message(&policy.dead_letter_queue, &m.receipt_handle).await
+                .map_err(|e| CloudError::Internal(e.to_string()))?;
+            let receive_count = m.attributes.get("ApproximateReceiveCount")
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(1);
+            messages.push(Message {
+                id: ResourceId::new(m.id),
+                body: m.body,
+                receipt_handle: None,
+                attributes: m.attributes,
+                receive_count,
+                sent_at: chrono::Utc::now(),
+                first_received_at: None,
+            });
+        }
+        Ok(messages)
+    }
 }
@@ -18,6 +18,7 @@ use std::sync::{Arc, Mutex};
 #[derive(Default)]
 pub struct MockStorage {
     buckets: Arc<Mutex<HashMap<String, HashMap<String, Vec<u8>>>>>,
+    lifecycles: Arc<Mutex<HashMap<String, Vec<LifecycleRule>>>>,
 }
 
 impl MockStorage {
@@ -119,6 +120,7 @@ impl ObjectStorage for MockStorage {
             key: key.to_string(),
             size: data.len() as u64,
             content_type: Some("application/octet-stream".to_string()),
+            cache_control: None,
             etag: Some("mock-etag".to_string()),
             last_modified: chrono::Utc::now(),
             storage_class: None,
@@ -152,6 +154,31 @@ impl ObjectStorage for MockStorage {
         self.put_object(dest_bucket, dest_key, &data).await
     }
 
+    async fn copy_object_with_options(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+        _options: CopyOptions,
+    ) -> CloudResult<()> {
+        self.copy_object(source_bucket, source_key, dest_bucket, dest_key).await
+    }
+
+    async fn move_object(
+        &self,
+        source_bucket: &str,
+        source_key: &str,
+        dest_bucket: &str,
+        dest_key: &str,
+    ) -> CloudResult<()> {
+        if (source_bucket, source_key) == (dest_bucket, dest_key) {
+            return Ok(());
+        }
+        self.copy_object(source_bucket, source_key, dest_bucket, dest_key).await?;
+        self.delete_object(source_bucket, source_key).await
+    }
+
     async fn object_exists(&self, bucket: &str, key: &str) -> CloudResult<bool> {
         match self.get_object(bucket, key).await {
             Ok(_) => Ok(true),
@@ -182,6 +209,7 @@ impl ObjectStorage for MockStorage {
                 key: key.clone(),
                 size: data.len() as u64,
                 content_type: Some("application/octet-stream".to_string()),
+                cache_control: None,
                 etag: Some("mock-etag".to_string()),
                 last_modified: chrono::Utc::now(),
                 storage_class: None,
@@ -192,6 +220,53 @@ impl ObjectStorage for MockStorage {
         Ok(ListResult::new(items, PaginationToken::none()))
     }
 
+    async fn set_object_metadata(
+        &self,
+        bucket: &str,
+        key: &str,
+        _options: PutOptions,
+    ) -> CloudResult<()> {
+        self.head_object(bucket, key).await.map(|_| ())
+    }
+
+    async fn get_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+    ) -> CloudResult<HashMap<String, String>> {
+        self.head_object(bucket, key).await.map(|_| HashMap::new())
+    }
+
+    async fn set_object_tags(
+        &self,
+        bucket: &str,
+        key: &str,
+        _tags: HashMap<String, String>,
+    ) -> CloudResult<()> {
+        self.head_object(bucket, key).await.map(|_| ())
+    }
+
+    async fn get_bucket_lifecycle(&self, bucket: &str) -> CloudResult<Vec<LifecycleRule>> {
+        Ok(self.lifecycles.lock().unwrap().get(bucket).cloned().unwrap_or_default())
+    }
+
+    async fn set_bucket_lifecycle(
+        &self,
+        bucket: &str,
+        rules: Vec<LifecycleRule>,
+    ) -> CloudResult<()> {
+        for rule in &rules {
+            rule.validate()?;
+        }
+        let mut lifecycles = self.lifecycles.lock().unwrap();
+        if rules.is_empty() {
+            lifecycles.remove(bucket);
+        } else {
+            lifecycles.insert(bucket.to_string(), rules);
+        }
+        Ok(())
+    }
+
     async fn presigned_get_url(
         &self,
         bucket: &str,
@@ -1,7 +1,7 @@
 //! Main CloudKit entry point.
 
 use cloudkit_spi::CloudConfig;
-use cloudkit_spi::{CloudContextBuilder, ProviderType};
+use cloudkit_spi::{CloudContextBuilder, HealthCheck, HealthReport, ProviderType};
 
 /// Main entry point for CloudKit.
 ///
@@ -105,6 +105,20 @@ impl CloudKit {
     pub fn from_config(provider: ProviderType, config: CloudConfig) -> CloudContextBuilder {
         CloudContextBuilder::new(provider).config(config)
     }
+
+    /// Check the health of several provider clients, one report per client.
+    ///
+    /// ```rust,ignore
+    /// let reports = CloudKit::health(&[&aws, &zero]).await;
+    /// let ready = reports.iter().all(|report| report.is_ready());
+    /// ```
+    pub async fn health(clients: &[&dyn HealthCheck]) -> Vec<HealthReport> {
+        let mut reports = Vec::with_capacity(clients.len());
+        for client in clients {
+            reports.push(client.health().await);
+        }
+        reports
+    }
 }
 
 #[cfg(test)]
@@ -140,4 +154,29 @@ mod tests {
         let context = CloudKit::zero().build().await.unwrap();
         assert_eq!(context.provider(), ProviderType::Zero);
     }
+
+    struct Probed(ProviderType, Result<(), &'static str>);
+
+    #[async_trait::async_trait]
+    impl HealthCheck for Probed {
+        async fn health_with(&self, options: cloudkit_spi::HealthCheckOptions) -> HealthReport {
+            let mut probes = cloudkit_spi::HealthProbes::new(self.0, options);
+            let result = self.1.map_err(|e| cloudkit_spi::CloudError::Internal(e.to_string()));
+            probes.probe("storage", async move { result });
+            probes.run().await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cloudkit_health() {
+        let up = Probed(ProviderType::Zero, Ok(()));
+        let down = Probed(ProviderType::Aws, Err("connection refused"));
+        let reports = CloudKit::health(&[&up, &down]).await;
+
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].is_ready());
+        assert_eq!(reports[1].provider, ProviderType::Aws);
+        assert!(!reports[1].is_ready());
+        assert_eq!(reports[1].service("storage").unwrap().message.as_deref(), Some("Internal error: connection refused"));
+    }
 }
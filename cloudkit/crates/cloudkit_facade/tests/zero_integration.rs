@@ -1,5 +1,5 @@
 use cloudkit::CloudKit;
-use cloudkit_spi::{ProviderType, Region};
+use cloudkit_spi::{CapabilityDiscovery, Feature, ProviderType, Region, Service};
 use cloudkit_zero::ZeroBuilder;
 
 #[tokio::test]
@@ -46,3 +46,16 @@ async fn test_zero_cloudemu_integration() {
 
     assert_eq!(context.provider(), ProviderType::Zero);
 }
+
+#[tokio::test]
+async fn test_zero_capabilities() {
+    let client = ZeroBuilder::new().build().await.expect("Failed to build Zero client");
+    let capabilities = client.capabilities();
+
+    assert_eq!(capabilities.provider, ProviderType::Zero);
+    assert!(capabilities.supports(Service::MessageQueue));
+    assert!(!capabilities.supports(Service::PubSub));
+    assert!(capabilities.has(Feature::DeadLetterQueues));
+    assert!(!capabilities.has(Feature::Presign));
+    assert!(capabilities.require(Feature::Fifo).is_err());
+}
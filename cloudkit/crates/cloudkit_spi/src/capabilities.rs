@@ -0,0 +1,223 @@
+//! Capability discovery for provider clients.
+//!
+//! Providers implement the CloudKit service traits to different extents: a
+//! client is built with some services, and some optional features of those
+//! services return `NotSupported` on one provider and work on another. A
+//! client describes what it supports so that generic code can branch before
+//! it starts an operation instead of failing partway through it.
+
+use crate::{CloudError, CloudResult, ProviderType};
+use std::collections::BTreeSet;
+
+/// A CloudKit service trait.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Service {
+    /// `ObjectStorage`
+    ObjectStorage,
+    /// `KeyValueStore`
+    KeyValueStore,
+    /// `MessageQueue`
+    MessageQueue,
+    /// `PubSub`
+    PubSub,
+    /// `Functions`
+    Functions,
+    /// `SecretsManager`
+    SecretsManager,
+    /// `KeyManagement`
+    KeyManagement,
+    /// `MetricsService`
+    MetricsService,
+    /// `LoggingService`
+    LoggingService,
+    /// `EventBus`
+    EventBus,
+    /// `WorkflowService`
+    WorkflowService,
+    /// `IdentityProvider`
+    IdentityProvider,
+    /// `Compute`
+    Compute,
+    /// `Networking`
+    Networking,
+}
+
+impl std::fmt::Display for Service {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Service::ObjectStorage => "object_storage",
+            Service::KeyValueStore => "kv_store",
+            Service::MessageQueue => "message_queue",
+            Service::PubSub => "pubsub",
+            Service::Functions => "functions",
+            Service::SecretsManager => "secrets",
+            Service::KeyManagement => "key_management",
+            Service::MetricsService => "metrics",
+            Service::LoggingService => "logging",
+            Service::EventBus => "events",
+            Service::WorkflowService => "workflow",
+            Service::IdentityProvider => "identity",
+            Service::Compute => "compute",
+            Service::Networking => "networking",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An optional feature of a service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Feature {
+    /// Presigned object URLs
+    Presign,
+    /// Bucket lifecycle rules
+    Lifecycle,
+    /// Conditional key-value writes and deletes
+    ConditionalWrites,
+    /// Key-value batch writes applied atomically
+    Transactions,
+    /// FIFO queues, with message groups and deduplication IDs
+    Fifo,
+    /// Delayed and scheduled messages
+    DelayedMessages,
+    /// Dead-letter queues
+    DeadLetterQueues,
+    /// Streaming of live log events
+    Streaming,
+}
+
+impl Feature {
+    /// The service the feature belongs to.
+    pub fn service(&self) -> Service {
+        match self {
+            Feature::Presign | Feature::Lifecycle => Service::ObjectStorage,
+            Feature::ConditionalWrites | Feature::Transactions => Service::KeyValueStore,
+            Feature::Fifo | Feature::DelayedMessages | Feature::DeadLetterQueues => Service::MessageQueue,
+            Feature::Streaming => Service::LoggingService,
+        }
+    }
+}
+
+impl std::fmt::Display for Feature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Feature::Presign => "presign",
+            Feature::Lifecycle => "lifecycle",
+            Feature::ConditionalWrites => "conditional_writes",
+            Feature::Transactions => "transactions",
+            Feature::Fifo => "fifo",
+            Feature::DelayedMessages => "delayed_messages",
+            Feature::DeadLetterQueues => "dead_letter_queues",
+            Feature::Streaming => "streaming",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Services and features a provider client supports.
+///
+/// ```rust,ignore
+/// let capabilities = client.capabilities();
+/// if capabilities.has(Feature::Presign) {
+///     let url = storage.presigned_get_url("bucket", "key", expiry).await?;
+/// }
+/// capabilities.require(Feature::Fifo)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Provider of the client
+    pub provider: ProviderType,
+    services: BTreeSet<Service>,
+    features: BTreeSet<Feature>,
+}
+
+impl Capabilities {
+    /// Capabilities of a client that supports nothing yet.
+    pub fn new(provider: ProviderType) -> Self {
+        Self {
+            provider,
+            services: BTreeSet::new(),
+            features: BTreeSet::new(),
+        }
+    }
+
+    /// Add a service and the features of it the client supports.
+    pub fn service(&mut self, service: Service, features: &[Feature]) -> &mut Self {
+        debug_assert!(features.iter().all(|feature| feature.service() == service));
+        self.services.insert(service);
+        self.features.extend(features);
+        self
+    }
+
+    /// Whether the client has a service.
+    pub fn supports(&self, service: Service) -> bool {
+        self.services.contains(&service)
+    }
+
+    /// Whether the client supports a feature.
+    pub fn has(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+
+    /// Fail with a `NotSupported` error unless the client supports a feature.
+    pub fn require(&self, feature: Feature) -> CloudResult<()> {
+        if self.has(feature) {
+            return Ok(());
+        }
+        let message = if self.supports(feature.service()) {
+            format!("The {} {} client does not support {}", self.provider, feature.service(), feature)
+        } else {
+            format!("The {} client has no {} service", self.provider, feature.service())
+        };
+        Err(CloudError::Provider {
+            provider: self.provider.to_string(),
+            code: "NotSupported".to_string(),
+            message,
+        })
+    }
+
+    /// Supported services, in a stable order.
+    pub fn services(&self) -> impl Iterator<Item = Service> + '_ {
+        self.services.iter().copied()
+    }
+
+    /// Supported features, in a stable order.
+    pub fn features(&self) -> impl Iterator<Item = Feature> + '_ {
+        self.features.iter().copied()
+    }
+}
+
+/// A provider client that can describe what it supports.
+pub trait CapabilityDiscovery: Send + Sync {
+    /// Services the client was built with and the optional features they support.
+    fn capabilities(&self) -> Capabilities;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let mut capabilities = Capabilities::new(ProviderType::Zero);
+        capabilities
+            .service(Service::MessageQueue, &[Feature::DelayedMessages])
+            .service(Service::ObjectStorage, &[]);
+
+        assert!(capabilities.supports(Service::ObjectStorage));
+        assert!(!capabilities.supports(Service::KeyValueStore));
+        assert!(capabilities.has(Feature::DelayedMessages));
+        assert!(!capabilities.has(Feature::Fifo));
+        assert_eq!(capabilities.services().collect::<Vec<_>>(), [Service::ObjectStorage, Service::MessageQueue]);
+        assert!(capabilities.require(Feature::DelayedMessages).is_ok());
+
+        let message = |feature| match capabilities.require(feature) {
+            Err(CloudError::Provider { provider, code, message }) => {
+                assert_eq!((provider.as_str(), code.as_str()), ("zero", "NotSupported"));
+                message
+            }
+            other => panic!("expected NotSupported, got {:?}", other),
+        };
+        assert_eq!(message(Feature::Fifo), "The zero message_queue client does not support fifo");
+        assert_eq!(message(Feature::Transactions), "The zero client has no kv_store service");
+    }
+}
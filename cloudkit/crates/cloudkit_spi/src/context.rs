@@ -1,7 +1,7 @@
 //! Base cloud client implementation.
 
 use crate::{CloudConfig, CloudResult, Region};
-use crate::{AuthProvider, BoxedAuthProvider, EnvAuthProvider, ExponentialBackoff, MetricsCollector, NoopMetrics, RetryPolicy};
+use crate::{AuthProvider, BoxedAuthProvider, EnvAuthProvider, ExponentialBackoff, Logger, MetricsCollector, NoopMetrics, RetryPolicy, TracingLogger};
 use std::sync::Arc;
 
 /// Cloud provider type.
@@ -46,6 +46,8 @@ pub struct CloudContext {
     pub retry_policy: Arc<dyn RetryPolicy>,
     /// Metrics collector
     pub metrics: Arc<dyn MetricsCollector>,
+    /// Logger, which redacts credentials from what it logs
+    pub logger: Arc<dyn Logger>,
 }
 
 impl std::fmt::Debug for CloudContext {
@@ -81,6 +83,7 @@ pub struct CloudContextBuilder {
     auth_provider: Option<BoxedAuthProvider>,
     retry_policy: Option<Arc<dyn RetryPolicy>>,
     metrics: Option<Arc<dyn MetricsCollector>>,
+    logger: Option<Arc<dyn Logger>>,
 }
 
 impl CloudContextBuilder {
@@ -92,6 +95,7 @@ impl CloudContextBuilder {
             auth_provider: None,
             retry_policy: None,
             metrics: None,
+            logger: None,
         }
     }
 
@@ -133,6 +137,12 @@ impl CloudContextBuilder {
         self
     }
 
+    /// Set the logger.
+    pub fn logger<L: Logger + 'static>(mut self, logger: L) -> Self {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
     /// Build the context.
     pub async fn build(self) -> CloudResult<CloudContext> {
         Ok(CloudContext {
@@ -141,6 +151,7 @@ impl CloudContextBuilder {
             auth_provider: self.auth_provider.unwrap_or_else(|| Arc::new(EnvAuthProvider)),
             retry_policy: self.retry_policy.unwrap_or_else(|| Arc::new(ExponentialBackoff::default())),
             metrics: self.metrics.unwrap_or_else(|| Arc::new(NoopMetrics)),
+            logger: self.logger.unwrap_or_else(|| Arc::new(TracingLogger::default())),
         })
     }
 }
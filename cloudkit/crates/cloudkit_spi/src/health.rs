@@ -0,0 +1,257 @@
+//! Health checks for provider clients.
+//!
+//! A provider client reports its health by running one cheap probe per
+//! service it was built with, such as listing a single bucket or queue. The
+//! probes run concurrently, each bounded by a timeout, and the report they
+//! produce is meant for application readiness endpoints.
+
+use crate::{CloudError, CloudResult, ProviderType};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+/// Health of a service or of a whole provider, from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    /// The service answered promptly
+    Healthy,
+    /// The service answered slowly, or is throttling or shedding requests
+    Degraded,
+    /// The service could not be reached or rejected the probe
+    Unhealthy,
+}
+
+impl std::fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HealthStatus::Healthy => write!(f, "healthy"),
+            HealthStatus::Degraded => write!(f, "degraded"),
+            HealthStatus::Unhealthy => write!(f, "unhealthy"),
+        }
+    }
+}
+
+/// Outcome of the probe of one service.
+#[derive(Debug, Clone)]
+pub struct ServiceHealth {
+    /// Service name (e.g., "s3", "storage")
+    pub service: String,
+    /// Health of the service
+    pub status: HealthStatus,
+    /// How long the probe took
+    pub latency: Duration,
+    /// Why the service is not healthy
+    pub message: Option<String>,
+}
+
+/// Health of a provider client, one entry per probed service.
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    /// Provider that was checked
+    pub provider: ProviderType,
+    /// Worst status of the services, healthy when there are none
+    pub status: HealthStatus,
+    /// Probed services, sorted by name
+    pub services: Vec<ServiceHealth>,
+    /// When the check started
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    /// Create a report from the services that were probed.
+    pub fn new(provider: ProviderType, mut services: Vec<ServiceHealth>, checked_at: DateTime<Utc>) -> Self {
+        services.sort_by(|a, b| a.service.cmp(&b.service));
+        let status = services
+            .iter()
+            .map(|service| service.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+        Self {
+            provider,
+            status,
+            services,
+            checked_at,
+        }
+    }
+
+    /// Whether the provider can serve requests; degraded services still count as ready.
+    pub fn is_ready(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+
+    /// Health of a probed service.
+    pub fn service(&self, name: &str) -> Option<&ServiceHealth> {
+        self.services.iter().find(|service| service.service == name)
+    }
+}
+
+/// Options for a health check.
+#[derive(Debug, Clone)]
+pub struct HealthCheckOptions {
+    /// Time after which a probe is abandoned and its service is unhealthy
+    pub timeout: Duration,
+    /// Time after which a successful probe marks its service as degraded
+    pub slow_after: Duration,
+}
+
+impl Default for HealthCheckOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            slow_after: Duration::from_secs(1),
+        }
+    }
+}
+
+type Probe = Pin<Box<dyn Future<Output = CloudResult<()>> + Send>>;
+
+/// Probes run concurrently to build a [`HealthReport`].
+///
+/// ```rust,ignore
+/// let mut probes = HealthProbes::new(ProviderType::Aws, options);
+/// let s3 = self.s3_client.clone();
+/// probes.probe("s3", async move { s3.list_buckets().send().await.map_err(...) });
+/// let report = probes.run().await;
+/// ```
+pub struct HealthProbes {
+    provider: ProviderType,
+    options: HealthCheckOptions,
+    probes: Vec<(String, Probe)>,
+}
+
+impl HealthProbes {
+    /// Start a health check of a provider.
+    pub fn new(provider: ProviderType, options: HealthCheckOptions) -> Self {
+        Self {
+            provider,
+            options,
+            probes: Vec::new(),
+        }
+    }
+
+    /// Probe a service; the result of the probe is discarded.
+    pub fn probe<T, F>(&mut self, service: impl Into<String>, probe: F) -> &mut Self
+    where
+        F: Future<Output = CloudResult<T>> + Send + 'static,
+    {
+        self.probes
+            .push((service.into(), Box::pin(async move { probe.await.map(|_| ()) })));
+        self
+    }
+
+    /// Run every probe concurrently and report the health of their services.
+    pub async fn run(self) -> HealthReport {
+        let checked_at = Utc::now();
+        let options = self.options;
+        let handles: Vec<_> = self
+            .probes
+            .into_iter()
+            .map(|(service, probe)| {
+                let options = options.clone();
+                let handle = tokio::spawn(async move {
+                    let started = Instant::now();
+                    let result = tokio::time::timeout(options.timeout, probe).await;
+                    (classify(result, started.elapsed(), &options), started.elapsed())
+                });
+                (service, handle)
+            })
+            .collect();
+
+        let mut services = Vec::with_capacity(handles.len());
+        for (service, handle) in handles {
+            let ((status, message), latency) = match handle.await {
+                Ok(outcome) => outcome,
+                Err(e) => (
+                    (HealthStatus::Unhealthy, Some(format!("Probe failed: {}", e))),
+                    Duration::ZERO,
+                ),
+            };
+            services.push(ServiceHealth {
+                service,
+                status,
+                latency,
+                message,
+            });
+        }
+        HealthReport::new(self.provider, services, checked_at)
+    }
+}
+
+fn classify(
+    result: Result<CloudResult<()>, tokio::time::error::Elapsed>,
+    latency: Duration,
+    options: &HealthCheckOptions,
+) -> (HealthStatus, Option<String>) {
+    match result {
+        Err(_) => (
+            HealthStatus::Unhealthy,
+            Some(format!("No answer within {:?}", options.timeout)),
+        ),
+        // A missing resource still proves the service answered
+        Ok(Ok(())) | Ok(Err(CloudError::NotFound { .. })) if latency > options.slow_after => (
+            HealthStatus::Degraded,
+            Some(format!("Answered in {:?}", latency)),
+        ),
+        Ok(Ok(())) | Ok(Err(CloudError::NotFound { .. })) => (HealthStatus::Healthy, None),
+        Ok(Err(e @ (CloudError::RateLimited { .. } | CloudError::ServiceUnavailable { .. }))) => {
+            (HealthStatus::Degraded, Some(e.to_string()))
+        }
+        Ok(Err(e)) => (HealthStatus::Unhealthy, Some(e.to_string())),
+    }
+}
+
+/// A provider client that can check the health of its services.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Check the health of every service of the client with the default options.
+    async fn health(&self) -> HealthReport {
+        self.health_with(HealthCheckOptions::default()).await
+    }
+
+    /// Check the health of every service of the client.
+    async fn health_with(&self, options: HealthCheckOptions) -> HealthReport;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_probes_are_classified() {
+        let options = HealthCheckOptions {
+            timeout: Duration::from_millis(200),
+            slow_after: Duration::from_millis(100),
+        };
+        let mut probes = HealthProbes::new(ProviderType::Zero, options);
+        probes
+            .probe("storage", async { Ok(vec!["bucket"]) })
+            .probe("kv", async {
+                Err::<(), _>(CloudError::NotFound {
+                    resource_type: "table".into(),
+                    resource_id: "probe".into(),
+                })
+            })
+            .probe("queue", async {
+                Err::<(), _>(CloudError::RateLimited { retry_after: None })
+            })
+            .probe("functions", async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(())
+            });
+        let report = probes.run().await;
+
+        let names: Vec<_> = report.services.iter().map(|s| s.service.as_str()).collect();
+        assert_eq!(names, ["functions", "kv", "queue", "storage"]);
+        assert_eq!(report.service("storage").unwrap().status, HealthStatus::Healthy);
+        assert_eq!(report.service("kv").unwrap().status, HealthStatus::Healthy);
+        assert_eq!(report.service("queue").unwrap().status, HealthStatus::Degraded);
+        assert_eq!(report.service("functions").unwrap().status, HealthStatus::Unhealthy);
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert!(!report.is_ready());
+
+        let empty = HealthProbes::new(ProviderType::Oracle, HealthCheckOptions::default()).run().await;
+        assert!(empty.is_ready());
+    }
+}
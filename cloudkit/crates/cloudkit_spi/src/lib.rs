@@ -7,6 +7,8 @@
 //! - **Common types**: Shared data structures (Region, Metadata, etc.)
 //! - **Configuration**: Cloud provider configuration
 //! - **Extension points**: Traits for retry policies, metrics, auth, and logging
+//! - **Health checks**: Per-service probes and readiness reports for provider clients
+//! - **Capabilities**: Services and optional features a provider client supports
 //!
 //! ## Architecture
 //!
@@ -28,6 +30,8 @@ mod auth;
 mod retry;
 mod metrics;
 mod logger;
+mod health;
+mod capabilities;
 
 // Re-export everything
 pub use error::*;
@@ -40,3 +44,5 @@ pub use auth::*;
 pub use retry::*;
 pub use metrics::*;
 pub use logger::*;
+pub use health::*;
+pub use capabilities::*;
@@ -88,6 +88,8 @@ pub struct ObjectMetadata {
     pub size: u64,
     /// Content type (MIME)
     pub content_type: Option<String>,
+    /// Cache control header
+    pub cache_control: Option<String>,
     /// ETag (entity tag)
     pub etag: Option<String>,
     /// Last modified timestamp